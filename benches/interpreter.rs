@@ -0,0 +1,245 @@
+//! Interpreter benchmarks: each workload runs with the instructions as
+//! decoded and again through the optimizer, on one executor per mode so the
+//! decoded bodies stay cached between runs
+//!
+//! ```text
+//! cargo bench --bench interpreter                  # built-in workloads
+//! cargo bench --bench interpreter -- loop          # only names containing "loop"
+//! cargo bench --bench interpreter -- app.wasm:run  # also time an exported function
+//! ```
+
+use std::time::{Duration, Instant};
+use wasmrun_core::{Executor, Module};
+
+const I32: u8 = 0x7f;
+const RUNS: usize = 5;
+
+struct Workload {
+    name: String,
+    wasm: Vec<u8>,
+    export: String,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    let (modules, filters): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.contains(".wasm:"));
+
+    let mut workloads = builtin_workloads();
+    workloads.retain(|w| filters.is_empty() || filters.iter().any(|f| w.name.contains(*f)));
+    for module in modules {
+        let (path, export) = module.rsplit_once(':').unwrap();
+        let wasm = std::fs::read(path).unwrap_or_else(|e| panic!("Failed to read {path}: {e}"));
+        workloads.push(Workload {
+            name: format!("{path} {export}"),
+            wasm,
+            export: export.to_string(),
+        });
+    }
+
+    println!(
+        "{:<28} {:>12} {:>12} {:>8}",
+        "workload", "decoded", "optimized", "speedup"
+    );
+    for workload in &workloads {
+        let decoded = time(workload, false);
+        let optimized = time(workload, true);
+        println!(
+            "{:<28} {:>12} {:>12} {:>7.2}x",
+            workload.name,
+            format!("{decoded:.2?}"),
+            format!("{optimized:.2?}"),
+            decoded.as_secs_f64() / optimized.as_secs_f64()
+        );
+    }
+}
+
+/// Fastest of [`RUNS`] calls, after a first call that fills the code cache
+fn time(workload: &Workload, optimize: bool) -> Duration {
+    let module = Module::parse(&workload.wasm).expect("workload module parses");
+    let mut executor = Executor::new(module).expect("workload module instantiates");
+    executor.set_optimize(optimize);
+    let func = executor
+        .find_function(&workload.export)
+        .unwrap_or_else(|| panic!("No export '{}'", workload.export));
+
+    let expected = executor.execute(func).expect("workload runs");
+    (0..RUNS)
+        .map(|_| {
+            let started = Instant::now();
+            let results = executor.execute(func).expect("workload runs");
+            let elapsed = started.elapsed();
+            assert_eq!(results, expected, "{} changed its result", workload.name);
+            elapsed
+        })
+        .min()
+        .unwrap()
+}
+
+fn builtin_workloads() -> Vec<Workload> {
+    let workload = |name: &str, wasm| Workload {
+        name: name.to_string(),
+        wasm,
+        export: "run".to_string(),
+    };
+    vec![
+        workload("calls: fib(25)", fib_module(25)),
+        workload("loop: branchy sum 2M", loop_module(2_000_000)),
+        workload("memory: store+load 256K", memory_module(256 * 1024)),
+    ]
+}
+
+/// Recursive `fib(n)`: mostly calls, compares and small adds
+fn fib_module(n: i32) -> Vec<u8> {
+    #[rustfmt::skip]
+    let fib = [
+        0x20, 0x00, 0x41, 0x02, 0x48,             // local.get 0; i32.const 2; i32.lt_s
+        0x04, I32,                                // if (result i32)
+        0x20, 0x00,                               //   local.get 0
+        0x05,                                     // else
+        0x20, 0x00, 0x41, 0x01, 0x6b, 0x10, 0x00, //   fib(n - 1)
+        0x20, 0x00, 0x41, 0x02, 0x6b, 0x10, 0x00, //   fib(n - 2)
+        0x6a,                                     //   i32.add
+        0x0b, 0x0b,                               // end; end
+    ];
+    let mut run = vec![0x41];
+    run.extend(sleb(n as i64));
+    run.extend([0x10, 0x00, 0x0b]); // call fib; end
+    module(&[(&[I32], 0, fib.to_vec()), (&[], 0, run)], false)
+}
+
+/// A counted loop with an `if`/`else` on every iteration, all in locals
+fn loop_module(n: i32) -> Vec<u8> {
+    let mut run = vec![
+        0x02, 0x40, 0x03, 0x40, // block; loop
+        0x20, 0x00, 0x41, // local.get i; i32.const n
+    ];
+    run.extend(sleb(n as i64));
+    #[rustfmt::skip]
+    run.extend([
+        0x4e, 0x0d, 0x01,                         // i32.ge_s; br_if 1
+        0x20, 0x00, 0x41, 0x03, 0x70, 0x45,       // i % 3 == 0
+        0x04, 0x40,                               // if
+        0x20, 0x01, 0x20, 0x00, 0x6a, 0x21, 0x01, //   s = s + i
+        0x05,                                     // else
+        0x20, 0x01, 0x20, 0x00, 0x73, 0x21, 0x01, //   s = s ^ i
+        0x0b,                                     // end
+        0x20, 0x00, 0x41, 0x01, 0x6a, 0x21, 0x00, // i = i + 1
+        0x0c, 0x00, 0x0b, 0x0b,                   // br 0; end; end
+        0x20, 0x01, 0x0b,                         // local.get s; end
+    ]);
+    module(&[(&[], 2, run)], false)
+}
+
+/// Stores `n` words into linear memory, then loads and sums them
+fn memory_module(n: i32) -> Vec<u8> {
+    let limit = sleb(n as i64);
+    let mut run = vec![0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x41];
+    run.extend(&limit);
+    #[rustfmt::skip]
+    run.extend([
+        0x4f, 0x0d, 0x01,                         // i32.ge_u; br_if 1
+        0x20, 0x00, 0x41, 0x02, 0x74,             // i << 2
+        0x20, 0x00, 0x36, 0x02, 0x00,             // i32.store i
+        0x20, 0x00, 0x41, 0x01, 0x6a, 0x21, 0x00, // i = i + 1
+        0x0c, 0x00, 0x0b, 0x0b,                   // br 0; end; end
+        0x41, 0x00, 0x21, 0x00,                   // i = 0
+        0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x41, // block; loop; local.get i; i32.const n
+    ]);
+    run.extend(&limit);
+    #[rustfmt::skip]
+    run.extend([
+        0x4f, 0x0d, 0x01,                         // i32.ge_u; br_if 1
+        0x20, 0x01,                               // local.get s
+        0x20, 0x00, 0x41, 0x02, 0x74,             // i << 2
+        0x28, 0x02, 0x00, 0x6a, 0x21, 0x01,       // s = s + i32.load
+        0x20, 0x00, 0x41, 0x01, 0x6a, 0x21, 0x00, // i = i + 1
+        0x0c, 0x00, 0x0b, 0x0b,                   // br 0; end; end
+        0x20, 0x01, 0x0b,                         // local.get s; end
+    ]);
+    module(&[(&[], 2, run)], true)
+}
+
+/// A module of functions `(params, i32 locals, body)` that all return one
+/// i32, exporting the last as `run`. With `memory`, enough pages for
+/// [`memory_module`].
+fn module(functions: &[(&[u8], u32, Vec<u8>)], memory: bool) -> Vec<u8> {
+    let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+
+    let types = functions.iter().map(|(params, _, _)| {
+        let mut ty = vec![0x60];
+        ty.extend(vector(params.iter().map(|&p| vec![p])));
+        ty.extend([0x01, I32]);
+        ty
+    });
+    section(&mut wasm, 1, vector(types));
+    let indices = (0..functions.len()).map(|i| uleb(i as u32));
+    section(&mut wasm, 3, vector(indices));
+    if memory {
+        section(&mut wasm, 5, vec![0x01, 0x00, 0x10]);
+    }
+    let mut export = vec![0x03];
+    export.extend(b"run\x00");
+    export.extend(uleb(functions.len() as u32 - 1));
+    section(&mut wasm, 7, vector([export].into_iter()));
+
+    let bodies = functions.iter().map(|(_, locals, code)| {
+        let mut body = if *locals == 0 {
+            vec![0x00]
+        } else {
+            let mut body = vec![0x01];
+            body.extend(uleb(*locals));
+            body.push(I32);
+            body
+        };
+        body.extend(code);
+        let mut sized = uleb(body.len() as u32);
+        sized.extend(body);
+        sized
+    });
+    section(&mut wasm, 10, vector(bodies));
+    wasm
+}
+
+fn section(wasm: &mut Vec<u8>, id: u8, contents: Vec<u8>) {
+    wasm.push(id);
+    wasm.extend(uleb(contents.len() as u32));
+    wasm.extend(contents);
+}
+
+/// Items prefixed with their count
+fn vector(items: impl ExactSizeIterator<Item = Vec<u8>>) -> Vec<u8> {
+    let mut out = uleb(items.len() as u32);
+    items.for_each(|item| out.extend(item));
+    out
+}
+
+fn uleb(mut value: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn sleb(mut value: i64) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
@@ -83,26 +83,33 @@ fn main() {
         );
     }
 
-    eprintln!("Building OS mode UI...");
-    let os_output = Command::new("pnpm")
-        .args(["vite", "build"])
-        .env("VITE_TEMPLATE", "os")
-        .current_dir(ui_dir)
-        .output()
-        .expect("Failed to build OS mode UI. Make sure pnpm is installed.");
+    if os_mode_enabled() {
+        eprintln!("Building OS mode UI...");
+        let os_output = Command::new("pnpm")
+            .args(["vite", "build"])
+            .env("VITE_TEMPLATE", "os")
+            .current_dir(ui_dir)
+            .output()
+            .expect("Failed to build OS mode UI. Make sure pnpm is installed.");
 
-    if !os_output.status.success() {
-        panic!(
-            "OS mode UI build failed: {}\nStdout: {}",
-            String::from_utf8_lossy(&os_output.stderr),
-            String::from_utf8_lossy(&os_output.stdout)
-        );
+        if !os_output.status.success() {
+            panic!(
+                "OS mode UI build failed: {}\nStdout: {}",
+                String::from_utf8_lossy(&os_output.stderr),
+                String::from_utf8_lossy(&os_output.stdout)
+            );
+        }
     }
 
     reorganize_build_output();
     eprintln!("UI build completed successfully!");
 }
 
+/// The OS mode UI is only embedded with the `os-mode` feature
+fn os_mode_enabled() -> bool {
+    env::var_os("CARGO_FEATURE_OS_MODE").is_some()
+}
+
 fn reorganize_build_output() {
     use std::fs;
 
@@ -121,7 +128,9 @@ fn reorganize_build_output() {
 
     process_template_v2(&temp_dir.join("app"), target_dir, "app");
     process_template_v2(&temp_dir.join("console"), target_dir, "console");
-    process_template_v2(&temp_dir.join("os"), target_dir, "os");
+    if os_mode_enabled() {
+        process_template_v2(&temp_dir.join("os"), target_dir, "os");
+    }
 
     let assets_source = Path::new("assets");
     let assets_dest = target_dir.join("assets");
@@ -21,12 +21,20 @@ const sidebars: SidebarsConfig = {
           collapsed: false,
           link: { type: 'doc', id: 'server/usage/index' },
           items: [
+            'server/usage/init',
             'server/usage/run',
             'server/usage/compile',
+            'server/usage/repro',
+            'server/usage/matrix',
+            'server/usage/artifacts',
             'server/usage/verify',
             'server/usage/inspect',
+            'server/usage/trigger',
             'server/usage/stop',
             'server/usage/clean',
+            'server/usage/registry',
+            'server/usage/snapshot',
+            'server/usage/e2e',
           ],
         },
         {
@@ -9,6 +9,7 @@ use crate::agent::limits::{dir_size, ResourceLimits};
 use crate::runtime::core::native_executor::{execute_wasm_bytes_with_env, ExecLimits};
 use crate::runtime::runtime_cache::RuntimeCache;
 use crate::runtime::wasi::WasiEnv;
+use crate::utils::strip_jsonc;
 use std::collections::HashMap;
 use std::path::{Component, Path};
 use std::sync::atomic::AtomicBool;
@@ -364,69 +365,6 @@ fn write_alias_shim(
     write_checked(&work_dir.join(shim_path), body.as_bytes(), limits, work_dir)
 }
 
-/// Strip `//` and `/* */` comments and trailing commas from JSONC. Real
-/// tsconfigs have comments, so refusing them would make this useless.
-fn strip_jsonc(src: &str) -> String {
-    let mut out = String::with_capacity(src.len());
-    let mut chars = src.chars().peekable();
-    let mut in_string = false;
-    let mut escaped = false;
-    while let Some(c) = chars.next() {
-        if in_string {
-            out.push(c);
-            if escaped {
-                escaped = false;
-            } else if c == '\\' {
-                escaped = true;
-            } else if c == '"' {
-                in_string = false;
-            }
-            continue;
-        }
-        match c {
-            '"' => {
-                in_string = true;
-                out.push(c);
-            }
-            '/' if chars.peek() == Some(&'/') => {
-                for c in chars.by_ref() {
-                    if c == '\n' {
-                        out.push('\n');
-                        break;
-                    }
-                }
-            }
-            '/' if chars.peek() == Some(&'*') => {
-                chars.next();
-                let mut prev = '\0';
-                for c in chars.by_ref() {
-                    if prev == '*' && c == '/' {
-                        break;
-                    }
-                    prev = c;
-                }
-            }
-            _ => out.push(c),
-        }
-    }
-    // Trailing commas: drop any comma followed only by whitespace and a close.
-    let mut cleaned = String::with_capacity(out.len());
-    let bytes: Vec<char> = out.chars().collect();
-    let mut i = 0;
-    while i < bytes.len() {
-        if bytes[i] == ',' {
-            let next = bytes[i + 1..].iter().find(|c| !c.is_whitespace());
-            if matches!(next, Some('}') | Some(']')) {
-                i += 1;
-                continue;
-            }
-        }
-        cleaned.push(bytes[i]);
-        i += 1;
-    }
-    cleaned
-}
-
 /// True for paths the transpile stage should convert to JavaScript.
 fn is_ts_path(path: &str) -> bool {
     path.ends_with(".ts") || path.ends_with(".tsx")
@@ -7,6 +7,8 @@
 //!
 //! A field of `None` means "unlimited" for that dimension.
 
+pub use crate::utils::dir_size;
+
 /// WASM linear memory page size (64 KiB), used to convert MB → pages.
 const WASM_PAGE_SIZE_BYTES: u64 = 65536;
 const BYTES_PER_MB: u64 = 1024 * 1024;
@@ -156,28 +158,6 @@ pub struct LimitsOverride {
     pub max_disk_mb: Option<u32>,
 }
 
-/// Recursively sum the size of all regular files under `dir`.
-///
-/// Returns 0 if the directory does not exist or cannot be read; symlinks are
-/// not followed. Used to measure a session's current disk footprint.
-pub fn dir_size(dir: &std::path::Path) -> u64 {
-    let mut total = 0u64;
-    let Ok(entries) = std::fs::read_dir(dir) else {
-        return 0;
-    };
-    for entry in entries.flatten() {
-        let Ok(meta) = entry.metadata() else {
-            continue;
-        };
-        if meta.is_dir() {
-            total += dir_size(&entry.path());
-        } else if meta.is_file() {
-            total += meta.len();
-        }
-    }
-    total
-}
-
 /// Clamp an optional ceiling onto an optional value, treating `None` as +∞
 /// (unlimited): no ceiling leaves the value as-is; an unlimited value is pulled
 /// down to a finite ceiling; two finite values take the smaller.
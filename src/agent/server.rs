@@ -10,7 +10,10 @@ use crate::agent::shell;
 use crate::agent::tools;
 use crate::agent::vendor;
 use crate::error::{Result, WasmrunError};
-use crate::runtime::core::native_executor::{execute_wasm_bytes_with_env, ExecLimits};
+use crate::runtime::core::executor::DEFAULT_MAX_CALL_DEPTH;
+use crate::runtime::core::native_executor::{
+    execute_wasm_bytes_with_env, interpreter_stack_bytes, ExecLimits,
+};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::io::Read;
@@ -133,7 +136,8 @@ const API_PREFIX: &str = "/api/v1";
 const DEFAULT_EXEC_TIMEOUT_SECS: u64 = 30;
 // Language runtimes (e.g. QuickJS compiled to WASM) generate deep call chains that
 // overflow the default 8 MB thread stack when run through the WASM interpreter.
-const EXEC_THREAD_STACK_BYTES: usize = 64 * 1024 * 1024;
+// Sized so the interpreter's call-depth limit traps before the stack runs out.
+const EXEC_THREAD_STACK_BYTES: usize = interpreter_stack_bytes(DEFAULT_MAX_CALL_DEPTH);
 /// Default request body cap (32 MB) when none is configured.
 const DEFAULT_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
 /// Default ceiling on concurrent exec workers when none is configured.
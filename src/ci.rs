@@ -0,0 +1,174 @@
+//! `--ci`: one switch that makes wasmrun predictable in pipelines.
+//!
+//! Progress is printed as plain lines without colour, nothing prompts or
+//! opens a browser, every build runs under a timeout, and warnings from
+//! validation steps (module checks, `wasmrun.toml`, license scans) fail the
+//! command. When the command ends, a one-line JSON summary is printed to
+//! stderr, and written to the file `WASMRUN_CI_SUMMARY` names, if set.
+
+use crate::error::{Result, WasmrunError};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// File the JSON summary is also written to
+pub const SUMMARY_ENV: &str = "WASMRUN_CI_SUMMARY";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+/// Turn on CI mode; call before anything prints
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A warning from a validation or lint step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    /// The step that raised it, e.g. `verify` or `licenses`
+    pub step: String,
+    pub message: String,
+}
+
+/// Record a warning from a validation step. The caller still prints it;
+/// under `--ci` it fails the command once the command is done.
+pub fn warn(step: &str, message: impl Into<String>) {
+    if let Ok(mut warnings) = WARNINGS.lock() {
+        warnings.push(Warning {
+            step: step.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+pub fn warnings() -> Vec<Warning> {
+    WARNINGS.lock().map(|w| w.clone()).unwrap_or_default()
+}
+
+/// The command's own result, or an error when it succeeded but raised
+/// warnings
+pub fn fail_on_warnings(result: Result<()>, warnings: &[Warning]) -> Result<()> {
+    if result.is_err() || warnings.is_empty() {
+        return result;
+    }
+    let steps: Vec<&str> =
+        warnings
+            .iter()
+            .map(|w| w.step.as_str())
+            .fold(Vec::new(), |mut steps, step| {
+                if !steps.contains(&step) {
+                    steps.push(step);
+                }
+                steps
+            });
+    Err(WasmrunError::from(format!(
+        "{} warning(s) from {}; --ci treats warnings as errors",
+        warnings.len(),
+        steps.join(", ")
+    )))
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub version: &'static str,
+    /// The command line, without the program name
+    pub args: Vec<String>,
+    pub success: bool,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Summary {
+    pub fn new(
+        args: Vec<String>,
+        result: &Result<()>,
+        warnings: Vec<Warning>,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            args,
+            success: result.is_ok(),
+            exit_code: if result.is_ok() { 0 } else { 1 },
+            duration_ms: elapsed.as_millis() as u64,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            warnings,
+        }
+    }
+
+    /// Print the summary to stderr, and write it to `WASMRUN_CI_SUMMARY`
+    pub fn emit(&self) {
+        let Ok(line) = serde_json::to_string(self) else {
+            return;
+        };
+        eprintln!("{line}");
+        if let Some(path) = std::env::var_os(SUMMARY_ENV).filter(|p| !p.is_empty()) {
+            if let Err(e) = std::fs::write(Path::new(&path), format!("{line}\n")) {
+                eprintln!("⚠️  Failed to write {}: {e}", Path::new(&path).display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(step: &str) -> Warning {
+        Warning {
+            step: step.to_string(),
+            message: "something looks off".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_fail_on_warnings() {
+        assert!(fail_on_warnings(Ok(()), &[]).is_ok());
+
+        let err = fail_on_warnings(
+            Ok(()),
+            &[warning("verify"), warning("licenses"), warning("verify")],
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("3 warning(s) from verify, licenses; --ci treats warnings as errors"));
+
+        // The command's own error wins
+        let err = fail_on_warnings(
+            Err(WasmrunError::from("build failed")),
+            &[warning("verify")],
+        );
+        assert!(err.unwrap_err().to_string().contains("build failed"));
+    }
+
+    #[test]
+    fn test_summary_json() {
+        let summary = Summary::new(
+            vec!["compile".to_string(), "--ci".to_string()],
+            &Err(WasmrunError::from("build failed")),
+            vec![warning("verify")],
+            Duration::from_millis(1500),
+        );
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["exit_code"], 1);
+        assert_eq!(json["duration_ms"], 1500);
+        assert!(json["error"].as_str().unwrap().contains("build failed"));
+        assert_eq!(json["warnings"][0]["step"], "verify");
+
+        let ok = Summary::new(Vec::new(), &Ok(()), Vec::new(), Duration::ZERO);
+        let json = serde_json::to_value(&ok).unwrap();
+        assert_eq!(json["exit_code"], 0);
+        assert!(json.get("error").is_none());
+    }
+}
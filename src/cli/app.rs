@@ -0,0 +1,744 @@
+use crate::cli::{self, get_args, Commands, ExamplesSubcommands, ResolvedArgs};
+use crate::compiler::builder::{OptimizationLevel, OutputNaming};
+use crate::compiler::sbom::SbomFormat;
+use crate::debug::enable_debug;
+use crate::error::WasmrunError;
+use crate::utils::PathResolver;
+use crate::{ci, commands, i18n, server, ui};
+use crate::{debug_enter, debug_exit, debug_println, error_println, t};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// The `wasmrun` command: parse the arguments and run the command they name
+pub fn run() {
+    std::panic::set_hook(Box::new(|panic_info| {
+        eprintln!("\n{}", t!("panic.title"));
+        eprintln!("{panic_info}");
+        eprintln!("\n{}", t!("panic.bug"));
+        eprintln!("   https://github.com/anistark/wasmrun/issues");
+        eprintln!("\n{}", t!("panic.include"));
+    }));
+
+    let mut args = get_args();
+
+    if let Err(e) = i18n::init(args.lang.as_deref()) {
+        eprintln!("❌ {e}");
+        std::process::exit(2);
+    }
+
+    if args.ci {
+        ci::enable();
+    }
+    let started = Instant::now();
+    ui::progress::init(args.quiet, args.no_color, args.ci);
+
+    if args.command.is_none() && cli::is_bare_invocation() {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| ".".into());
+        match commands::handle_first_run(&cwd) {
+            Ok(commands::FirstRun::Args(argv)) => args = cli::parse_from(argv),
+            Ok(commands::FirstRun::Default) => {}
+            Ok(commands::FirstRun::Done) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("❌ {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if args.debug {
+        enable_debug();
+    }
+
+    debug_enter!("run_cli", "args = {:?}", args);
+
+    let mut result = match &args.command {
+        Some(Commands::Stop) => commands::handle_stop_command(),
+
+        Some(Commands::Trigger { name, port }) => {
+            commands::handle_trigger_command(name.as_deref(), *port)
+        }
+
+        Some(Commands::Compile {
+            path,
+            positional_path,
+            output,
+            verbose,
+            optimization,
+            language,
+            dry_run,
+            build_timeout,
+            out_name,
+            name_pattern,
+            target_dirs,
+            sbom,
+            embed_meta,
+            require_clean,
+            pipeline,
+            no_store,
+            in_container,
+            local_build,
+        }) => {
+            debug_println!("Processing compile command");
+            let project_path =
+                PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+            let output_dir = output.clone().unwrap_or_else(|| ".".to_string());
+            debug_println!(
+                "Resolved paths: project={}, output={}",
+                project_path,
+                output_dir
+            );
+
+            let opt_level = match optimization.as_str() {
+                "debug" => OptimizationLevel::Debug,
+                "size" => OptimizationLevel::Size,
+                _ => OptimizationLevel::Release,
+            };
+            debug_println!("Optimization level: {:?}", opt_level);
+
+            commands::apply_build_timeout(&project_path, *build_timeout)
+                .and_then(|()| {
+                    commands::apply_container(
+                        &project_path,
+                        in_container.as_ref().map(Option::as_deref),
+                        language.as_deref(),
+                        &[PathBuf::from(&output_dir)],
+                    )
+                })
+                .and_then(|()| commands::apply_remote_build(&project_path, *local_build))
+                .and_then(|()| {
+                    commands::handle_compile_command(
+                        project_path,
+                        output_dir,
+                        opt_level,
+                        language.as_deref(),
+                        OutputNaming {
+                            name: out_name.clone(),
+                            pattern: name_pattern.clone(),
+                            target_dirs: *target_dirs,
+                        },
+                        commands::Provenance {
+                            sbom: sbom.as_deref().and_then(SbomFormat::from_name),
+                            embed_meta: *embed_meta,
+                            require_clean: *require_clean,
+                        },
+                        pipeline.as_deref(),
+                        !*no_store,
+                        *verbose,
+                        *dry_run,
+                    )
+                })
+        }
+        .map_err(|e| match e {
+            WasmrunError::Command(_) | WasmrunError::Compilation(_) | WasmrunError::Path { .. } => {
+                e
+            }
+            _ => e,
+        }),
+
+        Some(Commands::Verify {
+            path,
+            positional_path,
+            detailed,
+            fast,
+        }) => {
+            debug_println!("Processing verify command with detailed={}", detailed);
+            commands::handle_verify_command(path, positional_path, *detailed, *fast).map_err(|e| {
+                match e {
+                    WasmrunError::Command(_)
+                    | WasmrunError::Wasm(_)
+                    | WasmrunError::Path { .. } => e,
+                    _ => e,
+                }
+            })
+        }
+
+        Some(Commands::Inspect {
+            path,
+            positional_path,
+            unused,
+            entry,
+            remove_unused_exports,
+            output,
+            callgraph,
+            fast,
+        }) => commands::handle_inspect_command(
+            path,
+            positional_path,
+            *unused,
+            entry,
+            remove_unused_exports,
+            output,
+            callgraph,
+            *fast,
+        )
+        .map_err(|e| match e {
+            WasmrunError::Command(_) | WasmrunError::Wasm(_) | WasmrunError::Path { .. } => e,
+            _ => e,
+        }),
+
+        Some(Commands::Run {
+            path,
+            positional_path,
+            port,
+            language,
+            watch,
+            verbose: _verbose,
+            serve,
+            sha256,
+            manifest,
+            worker,
+            access_log,
+            api_port,
+            listen,
+            http2,
+            name,
+            runner,
+            dirs,
+            allow_net,
+            build_timeout,
+            in_container,
+            local_build,
+        }) => {
+            debug_println!(
+                "Processing run command: port={}, language={:?}, watch={}, serve={}",
+                port,
+                language,
+                watch,
+                serve
+            );
+            let project_path =
+                PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+            commands::apply_build_timeout(&project_path, *build_timeout)
+                .and_then(|()| {
+                    commands::apply_container(
+                        &project_path,
+                        in_container.as_ref().map(Option::as_deref),
+                        language.as_deref(),
+                        &commands::run_build_dirs(),
+                    )
+                })
+                .and_then(|()| commands::apply_remote_build(&project_path, *local_build))
+                .and_then(|()| {
+                    if api_port.is_some_and(|api| api == *port) {
+                        return Err(WasmrunError::from(format!(
+                            "--api-port must differ from the app port ({port})"
+                        )));
+                    }
+                    server::set_api_port(*api_port);
+                    let socket = listen
+                        .as_deref()
+                        .map(server::parse_listen)
+                        .transpose()
+                        .map_err(WasmrunError::from)?;
+                    server::set_unix_socket(socket);
+                    server::set_http2(http2.options());
+                    server::set_instance_name(name.clone());
+                    commands::handle_run_command(
+                        path,
+                        positional_path,
+                        *port,
+                        language,
+                        *watch,
+                        false,
+                        *serve,
+                        sha256,
+                        manifest,
+                        *worker,
+                        *access_log,
+                        runner,
+                        dirs,
+                        allow_net,
+                    )
+                })
+                .map_err(|e| match e {
+                    WasmrunError::Command(_)
+                    | WasmrunError::Server(_)
+                    | WasmrunError::Path { .. } => e,
+                    _ => e,
+                })
+        }
+
+        Some(Commands::Snapshot {
+            path,
+            positional_path,
+            url,
+            url_path,
+            out,
+            wait_for,
+            ready_event,
+            timeout,
+            delay,
+            width,
+            height,
+            full_page,
+            duration,
+            port,
+        }) => {
+            debug_println!(
+                "Processing snapshot command: url_path={}, out={}",
+                url_path,
+                out
+            );
+            commands::handle_snapshot_command(&commands::SnapshotOptions {
+                path: path.clone(),
+                positional_path: positional_path.clone(),
+                url: url.clone(),
+                url_path: url_path.clone(),
+                out: out.clone(),
+                wait_for: wait_for.clone(),
+                ready_event: ready_event.clone(),
+                timeout: *timeout,
+                delay: *delay,
+                width: *width,
+                height: *height,
+                full_page: *full_page,
+                duration: *duration,
+                port: *port,
+            })
+        }
+
+        Some(Commands::E2e {
+            path,
+            positional_path,
+            url,
+            url_path,
+            expect_console,
+            ignore_error,
+            timeout,
+            junit,
+            port,
+        }) => {
+            debug_println!(
+                "Processing e2e command: expect_console={:?}",
+                expect_console
+            );
+            commands::handle_e2e_command(&commands::E2eOptions {
+                path: path.clone(),
+                positional_path: positional_path.clone(),
+                url: url.clone(),
+                url_path: url_path.clone(),
+                expect_console: expect_console.clone(),
+                ignore_errors: ignore_error.clone(),
+                timeout: *timeout,
+                junit: junit.clone(),
+                port: *port,
+            })
+        }
+
+        #[cfg(feature = "interpreter")]
+        Some(Commands::Exec {
+            wasm_file,
+            sha256,
+            call,
+            interpreter,
+            cross_check,
+            bindgen,
+            glue,
+            json,
+            args,
+        }) => {
+            debug_println!(
+                "Processing exec command with {} args, call: {:?}",
+                args.len(),
+                call
+            );
+            if *bindgen {
+                commands::handle_bindgen_command(wasm_file, glue.as_deref(), call.as_deref(), args)
+            } else {
+                commands::exec_options(interpreter)
+                    .and_then(|options| {
+                        commands::handle_exec_command(
+                            wasm_file,
+                            sha256,
+                            call,
+                            args.clone(),
+                            &options,
+                            cross_check.as_deref(),
+                            *json,
+                        )
+                    })
+                    .map_err(|e| match e {
+                        WasmrunError::Command(_) | WasmrunError::Path { .. } => e,
+                        _ => e,
+                    })
+            }
+        }
+
+        #[cfg(feature = "os-mode")]
+        Some(Commands::Os {
+            hash_password: true,
+            ..
+        }) => commands::handle_hash_password_command(),
+
+        #[cfg(feature = "os-mode")]
+        Some(Commands::Os {
+            path,
+            positional_path,
+            port,
+            language,
+            watch,
+            verbose,
+            allow_cors,
+            max_body,
+            rate_limit,
+            read_only,
+            mounts,
+            hash_password: _,
+            http2,
+        }) => {
+            debug_println!(
+                "Processing os command: port={}, language={:?}, watch={}, verbose={}, allow_cors={}",
+                port,
+                language,
+                watch,
+                verbose,
+                allow_cors
+            );
+            server::set_http2(http2.options());
+            commands::handle_os_command(
+                path,
+                positional_path,
+                *port,
+                language,
+                *watch,
+                *verbose,
+                *allow_cors,
+                *max_body,
+                *rate_limit,
+                *read_only,
+                mounts,
+            )
+            .map_err(|e| match e {
+                WasmrunError::Command(_) | WasmrunError::Server(_) | WasmrunError::Path { .. } => e,
+                _ => e,
+            })
+        }
+
+        #[cfg(feature = "interpreter")]
+        Some(Commands::Agent {
+            port,
+            timeout,
+            max_sessions,
+            max_memory,
+            max_fuel,
+            max_output,
+            max_file_size,
+            max_disk,
+            max_body,
+            max_concurrent_exec,
+            npm_registry,
+            allow_cors,
+            verbose,
+            auth_config,
+            hash_key,
+        }) => {
+            debug_println!(
+                "Processing agent command: port={}, timeout={}, max_sessions={}, max_memory={}MB, max_fuel={}, max_output={}MB, max_file_size={}MB, max_disk={}MB, max_body={}MB, max_concurrent_exec={}",
+                port,
+                timeout,
+                max_sessions,
+                max_memory,
+                max_fuel,
+                max_output,
+                max_file_size,
+                max_disk,
+                max_body,
+                max_concurrent_exec
+            );
+            commands::handle_agent_command(
+                *port,
+                *timeout,
+                *max_sessions,
+                *max_memory,
+                *max_fuel,
+                *max_output,
+                *max_file_size,
+                *max_disk,
+                *max_body,
+                *max_concurrent_exec,
+                npm_registry,
+                *allow_cors,
+                *verbose,
+                auth_config.as_deref(),
+                hash_key.as_deref(),
+            )
+        }
+
+        Some(Commands::Plugin(plugin_cmd)) => {
+            commands::run_plugin_command(plugin_cmd).map_err(|e| match e {
+                WasmrunError::Command(_) | WasmrunError::Path { .. } => e,
+                _ => e,
+            })
+        }
+
+        Some(Commands::Clean {
+            path,
+            positional_path,
+            all,
+            deep,
+            include_deps,
+            dry_run,
+        }) => commands::handle_clean_command(
+            &path.clone(),
+            &positional_path.clone(),
+            &commands::CleanOptions {
+                all: *all,
+                deep: *deep,
+                include_deps: *include_deps,
+                dry_run: *dry_run,
+            },
+        ),
+
+        Some(Commands::Repro {
+            path,
+            positional_path,
+            optimization,
+            language,
+            isolated,
+            keep,
+            json,
+        }) => {
+            let project_path =
+                PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+            let optimization = match optimization.as_str() {
+                "debug" => OptimizationLevel::Debug,
+                "size" => OptimizationLevel::Size,
+                _ => OptimizationLevel::Release,
+            };
+            commands::apply_build_timeout(&project_path, None).and_then(|()| {
+                commands::handle_repro_command(
+                    &path.clone(),
+                    &positional_path.clone(),
+                    &commands::ReproOptions {
+                        optimization,
+                        language: language.clone(),
+                        isolated: *isolated,
+                        keep: *keep,
+                        json: *json,
+                    },
+                )
+            })
+        }
+
+        Some(Commands::Matrix {
+            path,
+            positional_path,
+            opt,
+            language,
+            bench,
+            args,
+            iterations,
+            keep,
+            json,
+        }) => {
+            let project_path =
+                PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+            let levels = opt
+                .iter()
+                .map(|level| match level.as_str() {
+                    "debug" => OptimizationLevel::Debug,
+                    "size" => OptimizationLevel::Size,
+                    _ => OptimizationLevel::Release,
+                })
+                .collect();
+            commands::apply_build_timeout(&project_path, None).and_then(|()| {
+                commands::handle_matrix_command(
+                    &path.clone(),
+                    &positional_path.clone(),
+                    &commands::MatrixOptions {
+                        levels,
+                        language: language.clone(),
+                        bench: bench.clone(),
+                        args: args.clone(),
+                        iterations: *iterations,
+                        keep: *keep,
+                        json: *json,
+                    },
+                )
+            })
+        }
+
+        #[cfg(feature = "deploy")]
+        Some(Commands::Push {
+            reference,
+            path,
+            positional_path,
+            require_clean,
+        }) => {
+            debug_println!("Processing push command: reference={}", reference);
+            commands::handle_push_command(reference, path, positional_path, *require_clean)
+        }
+
+        #[cfg(feature = "interpreter")]
+        Some(Commands::Coverage(coverage_cmd)) => commands::run_coverage_command(coverage_cmd),
+
+        Some(Commands::Init {
+            name,
+            template,
+            directory,
+            list,
+            refresh,
+            registry,
+        }) => commands::handle_init_command(&commands::InitOptions {
+            name: name.clone(),
+            template: template.clone(),
+            directory: directory.clone(),
+            list: *list,
+            refresh: *refresh,
+            registry: registry.clone(),
+        }),
+
+        Some(Commands::Examples {
+            action:
+                Some(ExamplesSubcommands::Run {
+                    name,
+                    port,
+                    watch,
+                    refresh,
+                }),
+            ..
+        }) => commands::handle_examples_run_command(name, *port, *watch, *refresh),
+
+        Some(Commands::Examples { language, .. }) => {
+            commands::handle_examples_command(language.as_deref())
+        }
+
+        Some(Commands::Ide(ide_cmd)) => commands::run_ide_command(ide_cmd),
+
+        Some(Commands::Artifacts(artifacts_cmd)) => commands::run_artifacts_command(artifacts_cmd),
+
+        #[cfg(feature = "deploy")]
+        Some(Commands::BuildAgent { listen, workdir }) => {
+            commands::handle_build_agent_command(listen, workdir.as_deref())
+        }
+
+        Some(Commands::ServeApi { listen }) => {
+            commands::handle_serve_api_command(listen.as_deref())
+        }
+
+        #[cfg(feature = "deploy")]
+        Some(Commands::Pull { reference, output }) => {
+            debug_println!("Processing pull command: reference={}", reference);
+            commands::handle_pull_command(reference, output)
+        }
+
+        #[cfg(not(feature = "interpreter"))]
+        Some(Commands::Exec { .. }) => Err(WasmrunError::feature_disabled(
+            "`wasmrun exec`",
+            "interpreter",
+        )),
+        #[cfg(not(feature = "interpreter"))]
+        Some(Commands::Coverage(_)) => Err(WasmrunError::feature_disabled(
+            "`wasmrun coverage`",
+            "interpreter",
+        )),
+        #[cfg(not(feature = "interpreter"))]
+        Some(Commands::Agent { .. }) => Err(WasmrunError::feature_disabled(
+            "`wasmrun agent`",
+            "interpreter",
+        )),
+        #[cfg(not(feature = "os-mode"))]
+        Some(Commands::Os { .. }) => Err(WasmrunError::feature_disabled("`wasmrun os`", "os-mode")),
+        #[cfg(not(feature = "deploy"))]
+        Some(Commands::Push { .. }) => {
+            Err(WasmrunError::feature_disabled("`wasmrun push`", "deploy"))
+        }
+        #[cfg(not(feature = "deploy"))]
+        Some(Commands::Pull { .. }) => {
+            Err(WasmrunError::feature_disabled("`wasmrun pull`", "deploy"))
+        }
+        #[cfg(not(feature = "deploy"))]
+        Some(Commands::BuildAgent { .. }) => Err(WasmrunError::feature_disabled(
+            "`wasmrun build-agent`",
+            "deploy",
+        )),
+
+        None => {
+            debug_println!(
+                "No subcommand provided, running default mode (equivalent to 'run' command)"
+            );
+            let resolved_args = match ResolvedArgs::from_args(args) {
+                Ok(args) => {
+                    debug_println!("Resolved args: {:?}", args);
+                    args
+                }
+                Err(e) => {
+                    error_println!("{e}");
+                    debug_println!("Failed to resolve args: {:?}", e);
+                    std::process::exit(1);
+                }
+            };
+            debug_println!(
+                "Running project/WASM: {}, language: {:?}, watch: {}",
+                resolved_args.path,
+                resolved_args.language,
+                resolved_args.watch
+            );
+            commands::apply_build_timeout(&resolved_args.path, None)
+                .and_then(|()| commands::apply_remote_build(&resolved_args.path, false))
+                .and_then(|()| {
+                    commands::handle_run_command(
+                        &None,
+                        &Some(resolved_args.path),
+                        resolved_args.port,
+                        &resolved_args.language,
+                        resolved_args.watch,
+                        false, // verbose mode for default command
+                        resolved_args.serve,
+                        &None,
+                        &None,
+                        false,
+                        false,
+                        &None,
+                        &[],
+                        &None,
+                    )
+                })
+                .map_err(|e| match e {
+                    WasmrunError::Command(_)
+                    | WasmrunError::Server(_)
+                    | WasmrunError::Path { .. } => e,
+                    _ => e,
+                })
+        }
+    };
+
+    // Under --ci, warnings from validation steps fail the command, and a
+    // JSON summary is the last thing printed
+    let summary = if ci::is_enabled() {
+        let warnings = ci::warnings();
+        result = ci::fail_on_warnings(result, &warnings);
+        Some(ci::Summary::new(
+            std::env::args().skip(1).collect(),
+            &result,
+            warnings,
+            started.elapsed(),
+        ))
+    } else {
+        None
+    };
+
+    if let Err(e) = result {
+        debug_println!("Command execution failed: {:?}", e);
+        let mut error_source: &dyn Error = &e;
+        eprintln!("❌ {error_source}");
+
+        while let Some(source) = error_source.source() {
+            eprintln!("   {}", t!("error.caused_by", source = source));
+            debug_println!("Error chain: {}", source);
+            error_source = source;
+        }
+
+        if let Some(summary) = summary {
+            summary.emit();
+        }
+        debug_exit!("run_cli", "exit code: 1");
+        std::process::exit(1);
+    }
+
+    if let Some(summary) = summary {
+        summary.emit();
+    }
+    debug_exit!("run_cli", "exit code: 0");
+}
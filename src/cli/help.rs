@@ -0,0 +1,272 @@
+//! Top-level `--help` layout: colours, subcommands grouped by purpose, and
+//! the examples shown under each subcommand's own help.
+
+use clap::builder::styling::{AnsiColor, Style, Styles};
+use clap::{ColorChoice, Command, CommandFactory};
+
+use super::Args;
+
+/// Subcommands by group, in the order they are listed
+const GROUPS: &[(&str, &[&str])] = &[
+    (
+        "Build",
+        &[
+            "init",
+            "compile",
+            "repro",
+            "matrix",
+            "artifacts",
+            "verify",
+            "inspect",
+            "clean",
+            "build-agent",
+        ],
+    ),
+    ("Serve", &["run", "trigger", "snapshot", "e2e", "stop"]),
+    ("Runtime", &["exec", "coverage", "agent", "push", "pull"]),
+    ("Plugin", &["plugin"]),
+    ("OS mode", &["os"]),
+    ("Integration", &["ide", "serve-api"]),
+    ("Help", &["examples"]),
+];
+
+pub fn styles() -> Styles {
+    Styles::styled()
+        .header(AnsiColor::Green.on_default().bold())
+        .usage(AnsiColor::Green.on_default().bold())
+        .literal(AnsiColor::Cyan.on_default().bold())
+        .placeholder(AnsiColor::Cyan.on_default())
+        .valid(AnsiColor::Cyan.on_default().bold())
+        .invalid(AnsiColor::Yellow.on_default().bold())
+        .error(AnsiColor::Red.on_default().bold())
+}
+
+/// The root command with grouped subcommands in its help. Colour follows
+/// the terminal, `NO_COLOR` and `--no-color`, which has to be checked
+/// before parsing because `--help` exits as soon as it is seen.
+pub fn command() -> Command {
+    let command = Args::command();
+    let color = if std::env::args().any(|arg| arg == "--no-color") {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    };
+    let template = help_template(&command);
+    command.color(color).help_template(template)
+}
+
+fn help_template(command: &Command) -> String {
+    let styles = command.get_styles();
+    let header = *styles.get_header();
+    let literal = *styles.get_literal();
+
+    let mut template =
+        String::from("{before-help}{about-with-newline}\n{usage-heading} {usage}\n\n");
+    template.push_str(&format!(
+        "{header}Arguments:{header:#}\n{{positionals}}\n\n"
+    ));
+    template.push_str(&grouped_subcommands(command, header, literal));
+    template.push_str(&format!(
+        "{header}Options:{header:#}\n{{options}}{{after-help}}"
+    ));
+    template
+}
+
+fn grouped_subcommands(command: &Command, header: Style, literal: Style) -> String {
+    let entries: Vec<(&str, Vec<(String, String)>)> = GROUPS
+        .iter()
+        .map(|(group, names)| {
+            let rows: Vec<(String, String)> = names
+                .iter()
+                .filter_map(|name| command.find_subcommand(name))
+                .filter(|sub| !sub.is_hide_set())
+                .map(|sub| {
+                    let aliases: Vec<&str> = sub.get_visible_aliases().collect();
+                    let name = if aliases.is_empty() {
+                        sub.get_name().to_string()
+                    } else {
+                        format!("{}, {}", sub.get_name(), aliases.join(", "))
+                    };
+                    let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+                    (name, about)
+                })
+                .collect();
+            (*group, rows)
+        })
+        .filter(|(_, rows)| !rows.is_empty())
+        .collect();
+    let width = entries
+        .iter()
+        .flat_map(|(_, rows)| rows.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for (group, rows) in entries {
+        out.push_str(&format!("{header}{group} commands:{header:#}\n"));
+        for (name, about) in rows {
+            let padding = " ".repeat(width - name.len());
+            out.push_str(&format!("  {literal}{name}{literal:#}{padding}  {about}\n"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub const COMPILE_EXAMPLES: &str = "\
+Examples:
+  wasmrun compile                            Build the current directory
+  wasmrun b ./app --optimization release     Release build with the short alias
+  wasmrun compile ./app -o dist --dry-run    Show the build plan without running it
+  wasmrun compile ./app --in-container       Build with the toolchain from a container image
+  wasmrun compile ./app --pipeline release   Run the release pipeline from wasmrun.toml after building";
+
+pub const BUILD_AGENT_EXAMPLES: &str = "\
+Examples:
+  wasmrun build-agent                              Accept builds from this machine only
+  WASMRUN_BUILD_TOKEN=s3cret wasmrun build-agent --listen 0.0.0.0:8420
+                                                   Accept builds from the network, with a token";
+
+pub const CLEAN_EXAMPLES: &str = "\
+Examples:
+  wasmrun clean                       Remove wasmrun's temporary directories
+  wasmrun clean ./app --all           Also the project's build output
+  wasmrun clean --deep --dry-run      List build output, caches and stale state to delete
+  wasmrun clean --deep --include-deps Also node_modules and other installed dependencies";
+
+pub const REPRO_EXAMPLES: &str = "\
+Examples:
+  wasmrun repro                       Build twice in place and compare
+  wasmrun repro ./app --isolated      Second build from a clean copy in a temp directory
+  wasmrun repro --json > repro.json   Machine-readable report; exits non-zero on a mismatch";
+
+pub const MATRIX_EXAMPLES: &str = "\
+Examples:
+  wasmrun matrix                              Build at debug, release and size and compare
+  wasmrun matrix --opt release,size           Only the levels you care about
+  wasmrun matrix --bench fib --arg 25         Also time fib(25) at each level
+  wasmrun matrix --json > matrix.json         Machine-readable report";
+
+pub const ARTIFACTS_EXAMPLES: &str = "\
+Examples:
+  wasmrun artifacts list                   Stored builds, most recently used first
+  wasmrun artifacts gc --dry-run           What pruning builds unused for 30 days would free
+  wasmrun artifacts gc --older-than 7 --keep 20
+                                           Keep at most 20 builds, all used this week
+  wasmrun artifacts gc --all               Empty the store";
+
+pub const VERIFY_EXAMPLES: &str = "\
+Examples:
+  wasmrun verify ./dist/app.wasm
+  wasmrun verify ./dist/app.wasm --detailed
+  wasmrun verify ./big.wasm --detailed --fast  Skip function bodies";
+
+pub const INSPECT_EXAMPLES: &str = "\
+Examples:
+  wasmrun inspect ./dist/app.wasm
+  wasmrun inspect ./big.wasm --fast   Skip code-section analysis";
+
+pub const RUN_EXAMPLES: &str = "\
+Examples:
+  wasmrun r ./app --watch             Serve with live reload
+  wasmrun run ./app.wasm --port 3000  Serve a built module on another port
+  wasmrun run ghcr.io/me/app:v1       Pull a module from a registry and serve it";
+
+pub const TRIGGER_EXAMPLES: &str = "\
+Examples:
+  wasmrun trigger                     Rebuild the only running server
+  wasmrun trigger --name docs         Rebuild the server started with `run --name docs`
+  curl -X POST localhost:8420/api/build/trigger";
+
+pub const EXEC_EXAMPLES: &str = "\
+Examples:
+  wasmrun exec ./app.wasm
+  wasmrun exec ./app.wasm --input data.txt   Arguments after the file go to the module
+  wasmrun exec ./lib.wasm --call add 2 3     Call an exported function";
+
+pub const OS_EXAMPLES: &str = "\
+Examples:
+  wasmrun os ./node-app
+  wasmrun os ./py-app --language python --watch";
+
+pub const PLUGIN_EXAMPLES: &str = "\
+Examples:
+  wasmrun plugin list
+  wasmrun plugin install wasmrust
+  wasmrun plugin info wasmgo";
+
+pub const IDE_EXAMPLES: &str = "\
+Examples:
+  wasmrun ide vscode                         Tasks and launch config for the current project
+  wasmrun ide vscode ./game --workspace .    A project in a subfolder of the opened workspace
+  wasmrun ide vscode --port 3000 --dry-run   Print the files instead of writing them";
+
+pub const SERVE_API_EXAMPLES: &str = "\
+Examples:
+  wasmrun serve-api                          JSON-RPC over stdin/stdout, as editors spawn it
+  wasmrun serve-api --listen 127.0.0.1:8421  Accept clients on a TCP port
+  wasmrun serve-api --listen unix:/tmp/wasmrun-api.sock";
+
+pub const INIT_EXAMPLES: &str = "\
+Examples:
+  wasmrun init my-app                     Rust project from the built-in template
+  wasmrun init my-app -t go               Go project
+  wasmrun init --list                     Built-in and registry templates
+  wasmrun init my-app -t rust-canvas      Template from the registry
+  wasmrun init my-app -t mine --registry ../my-templates";
+
+pub const EXAMPLES_EXAMPLES: &str = "\
+Examples:
+  wasmrun examples                       Every language, and the demos
+  wasmrun examples go                    Only Go
+  wasmrun examples run leptos-todo       Fetch, build and serve a demo";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_subcommand_is_grouped_once() {
+        let command = Args::command();
+        let grouped: Vec<&str> = GROUPS
+            .iter()
+            .flat_map(|(_, names)| *names)
+            .copied()
+            .collect();
+        for sub in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+            let count = grouped
+                .iter()
+                .filter(|name| **name == sub.get_name())
+                .count();
+            assert_eq!(
+                count,
+                1,
+                "`{}` must be in exactly one help group",
+                sub.get_name()
+            );
+        }
+        for name in grouped {
+            assert!(
+                command.find_subcommand(name).is_some(),
+                "unknown command `{name}`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_help_lists_groups_and_aliases() {
+        let help = command()
+            .color(ColorChoice::Never)
+            .render_help()
+            .to_string();
+        assert!(help.contains("Build commands:"));
+        // Groups whose commands are all compiled out are left off
+        assert_eq!(
+            help.contains("OS mode commands:"),
+            cfg!(feature = "os-mode")
+        );
+        assert!(help.contains("compile, b"));
+        assert!(help.contains("run, r"));
+        assert!(help.contains("Options:"));
+    }
+}
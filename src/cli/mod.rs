@@ -0,0 +1,1936 @@
+mod app;
+mod help;
+
+pub use app::run;
+
+use crate::error::{Result, WasmrunError};
+use crate::server::{Http2Options, TlsFiles};
+use crate::utils::PathResolver;
+use clap::{FromArgMatches, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Wasmrun - WebAssembly project compiler and runtime 🌟
+#[derive(Parser, Debug)]
+#[command(
+    name = "wasmrun",
+    author,
+    version = get_version_string(),
+    about = "A lightweight WebAssembly runner",
+    long_about = "Wasmrun is a CLI tool for compiling, running, and debugging WebAssembly modules with full WASI support.",
+    styles = help::styles(),
+    after_help = "Run `wasmrun examples` for copy-pasteable commands for each language.\n\nIf you find Wasmrun useful, please consider starring the repository on GitHub! ✨\nhttps://github.com/anistark/wasmrun"
+)]
+pub struct Args {
+    /// Subcommands to control Wasmrun server
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Path to project directory or WASM file (default: current directory)
+    #[arg(
+        short = 'p',
+        long,
+        default_value = "./",
+        value_hint = clap::ValueHint::AnyPath,
+        help = "Project directory or WASM file path"
+    )]
+    pub path: String,
+
+    /// Project directory or WASM file path (positional argument)
+    #[arg(index = 1, value_hint = clap::ValueHint::AnyPath)]
+    pub positional_path: Option<String>,
+
+    /// Port to serve (default: 8420)
+    // TODO: Apply to web server as well if provided.
+    #[arg(
+        short = 'P',
+        long,
+        default_value_t = 8420,
+        value_parser = clap::value_parser!(u16).range(1..=65535),
+        help = "Server port number"
+    )]
+    pub port: u16,
+
+    /// Interpret path as a WebAssembly file (instead of a project directory)
+    #[arg(short = 'w', long, help = "Run WASM file directly")]
+    pub wasm: bool,
+
+    /// Enable watch mode for live-reloading on file changes
+    #[arg(short = 'W', long, help = "Watch for file changes and reload")]
+    pub watch: bool,
+
+    /// Enable debug output with detailed information
+    #[arg(long, global = true, help = "Show detailed debug information")]
+    pub debug: bool,
+
+    /// Language for messages and the served UI (default: from WASMRUN_LANG or the locale)
+    #[arg(
+        long,
+        global = true,
+        value_name = "LANG",
+        help = "Language for messages and the served UI (en, es)"
+    )]
+    pub lang: Option<String>,
+
+    /// Hide progress bars and status messages; errors are still shown
+    #[arg(
+        short = 'q',
+        long,
+        global = true,
+        help = "Hide progress and status output"
+    )]
+    pub quiet: bool,
+
+    /// Print without ANSI colours (also set by NO_COLOR)
+    #[arg(long, global = true, help = "Disable coloured output")]
+    pub no_color: bool,
+
+    /// Strict, non-interactive mode for pipelines
+    #[arg(
+        long,
+        global = true,
+        help = "Run for CI: plain output, no prompts or browser, build timeouts, fail on warnings, JSON summary"
+    )]
+    pub ci: bool,
+
+    /// Serve the UI in browser (default: false)
+    #[arg(short = 's', long, help = "Open UI in browser when server starts")]
+    pub serve: bool,
+
+    /// Language to use for compilation (auto-detect if not specified)
+    #[arg(
+        short = 'l',
+        long,
+        value_parser = ["rust", "go", "c", "asc", "python"],
+        help = "Force specific language for compilation"
+    )]
+    pub language: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Stop any running Wasmrun server instance
+    #[command(alias = "kill")]
+    Stop,
+
+    /// Compile a project to WebAssembly with optimization options
+    #[command(visible_alias = "b", aliases = ["build", "c"], after_help = help::COMPILE_EXAMPLES)]
+    Compile {
+        /// Path to the project directory
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to compile"
+        )]
+        path: Option<String>,
+
+        /// Project directory path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Output directory for the WASM file (default: current directory)
+        #[arg(
+            short = 'o',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Output directory for compiled files"
+        )]
+        output: Option<String>,
+
+        /// Enable verbose output
+        #[arg(short = 'v', long, help = "Show detailed compilation output")]
+        verbose: bool,
+
+        /// Optimization level: debug, release, size
+        #[arg(
+            long,
+            default_value = "release",
+            value_parser = ["debug", "release", "size"],
+            help = "Compilation optimization level"
+        )]
+        optimization: String,
+
+        /// Language to compile as (auto-detect if not specified)
+        #[arg(
+            short = 'l',
+            long,
+            value_parser = ["rust", "go", "c", "asc", "python"],
+            help = "Force specific language for compilation"
+        )]
+        language: Option<String>,
+
+        /// Print the build plan without running it
+        #[arg(
+            long,
+            help = "Show the plugin, commands and outputs a build would use, without building"
+        )]
+        dry_run: bool,
+
+        /// Seconds each toolchain command may run (0 for no limit)
+        #[arg(
+            long,
+            value_name = "SECS",
+            help = "Stop a build command that runs longer than this (default 1800, 0 for no limit)"
+        )]
+        build_timeout: Option<u64>,
+
+        /// Build in the language's pinned toolchain image
+        #[arg(
+            long,
+            value_name = "RUNTIME",
+            num_args = 0..=1,
+            value_parser = ["docker", "podman"],
+            help = "Run the build's toolchain commands in a container (docker or podman, detected if omitted)"
+        )]
+        in_container: Option<Option<String>>,
+
+        /// Ignore `[remote_build]` in wasmrun.toml
+        #[arg(
+            long,
+            help = "Build on this machine even when wasmrun.toml sets up a remote build agent"
+        )]
+        local_build: bool,
+
+        /// File stem for the built artifacts
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Name the artifacts NAME.wasm, NAME.js, ... instead of the toolchain's default"
+        )]
+        out_name: Option<String>,
+
+        /// File stem pattern for the built artifacts
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            help = "Name the artifacts from a pattern with {name}, {profile} and {target}, e.g. {name}-{profile}"
+        )]
+        name_pattern: Option<String>,
+
+        /// Put artifacts in a subdirectory per build target
+        #[arg(
+            long,
+            help = "Write artifacts to <output>/<target>/, e.g. dist/wasm32-unknown-unknown/"
+        )]
+        target_dirs: bool,
+
+        /// Write a software bill of materials beside the artifacts
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            num_args = 0..=1,
+            default_missing_value = "cyclonedx",
+            value_parser = ["cyclonedx", "spdx"],
+            help = "Write an SBOM of dependencies, toolchain versions and artifact hashes (cyclonedx or spdx)"
+        )]
+        sbom: Option<String>,
+
+        /// Embed build metadata in the module
+        #[arg(
+            long,
+            help = "Embed a wasmrun.meta section with the project, git commit, build time and toolchain"
+        )]
+        embed_meta: bool,
+
+        /// Refuse to build a git tree with uncommitted changes
+        #[arg(long, help = "Fail if the project's git tree has uncommitted changes")]
+        require_clean: bool,
+
+        /// Post-build steps to run, from `[pipelines]` in wasmrun.toml
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Run the named pipeline from wasmrun.toml after building (default: the one named 'default', if any)"
+        )]
+        pipeline: Option<String>,
+
+        /// Skip the artifact store
+        #[arg(
+            long,
+            help = "Always run the build, and don't keep its outputs in the artifact store"
+        )]
+        no_store: bool,
+    },
+
+    /// Verify WebAssembly file format and structure
+    #[command(after_help = help::VERIFY_EXAMPLES)]
+    Verify {
+        /// Path to the WASM file
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::FilePath,
+            help = "WASM file to verify"
+        )]
+        path: Option<String>,
+
+        /// WASM file path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::FilePath)]
+        positional_path: Option<String>,
+
+        /// Show detailed information about the WASM module
+        #[arg(short = 'd', long, help = "Show detailed verification results")]
+        detailed: bool,
+
+        /// Skip parsing function bodies, for quicker checks of large modules
+        #[arg(long, help = "Skip code-section analysis")]
+        fast: bool,
+    },
+
+    /// Perform detailed inspection on a WebAssembly file
+    #[command(after_help = help::INSPECT_EXAMPLES)]
+    Inspect {
+        /// Path to the WASM file
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::FilePath,
+            help = "WASM file to inspect"
+        )]
+        path: Option<String>,
+
+        /// WASM file path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::FilePath)]
+        positional_path: Option<String>,
+
+        /// Report exports the entry points never reach and the code they hold
+        #[arg(long, help = "Show a tree-shaking report of unused exports")]
+        unused: bool,
+
+        /// Entry points used by --unused (defaults to _start, main, ...)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        entry: Vec<String>,
+
+        /// Write a copy of the module without these exports, emptying code only they used
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        remove_unused_exports: Vec<String>,
+
+        /// Output path for --remove-unused-exports (defaults to <name>.stripped.wasm)
+        #[arg(short = 'o', long, value_name = "FILE")]
+        output: Option<String>,
+
+        /// Write the function call graph to FILE (Graphviz DOT, or JSON for .json)
+        #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+        callgraph: Option<String>,
+
+        /// Skip parsing function bodies: no size profile, code checks or call graph
+        #[arg(
+            long,
+            help = "Skip code-section analysis",
+            conflicts_with_all = ["unused", "remove_unused_exports", "callgraph"]
+        )]
+        fast: bool,
+    },
+
+    /// Compile and run a project with live development server
+    #[command(visible_alias = "r", aliases = ["dev", "serve"], after_help = help::RUN_EXAMPLES)]
+    Run {
+        /// Path to the project
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to run"
+        )]
+        path: Option<String>,
+
+        /// Project path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Port to serve (default: 8420)
+        #[arg(
+            short = 'P',
+            long,
+            default_value_t = 8420,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "Development server port"
+        )]
+        port: u16,
+
+        /// Language to use for compilation (auto-detect if not specified)
+        #[arg(
+            short = 'l',
+            long,
+            value_parser = ["rust", "go", "c", "asc", "python"],
+            help = "Force specific language for compilation"
+        )]
+        language: Option<String>,
+
+        /// Enable watch mode for live-reloading on file changes
+        #[arg(long, help = "Watch for changes and auto-reload")]
+        watch: bool,
+
+        /// Enable verbose output
+        #[arg(short = 'v', long, help = "Show detailed build output")]
+        verbose: bool,
+
+        /// Serve the UI in browser (default: false)
+        #[arg(short = 's', long, help = "Open UI in browser when server starts")]
+        serve: bool,
+
+        /// Expected SHA-256 of the module (required to cache downloads)
+        #[arg(
+            long,
+            value_name = "DIGEST",
+            help = "Verify the module against this SHA-256 digest"
+        )]
+        sha256: Option<String>,
+
+        /// Serve several modules on one page from an orchestration manifest
+        #[arg(
+            long,
+            value_name = "FILE",
+            value_hint = clap::ValueHint::FilePath,
+            conflicts_with_all = ["path", "positional_path", "language", "sha256"],
+            help = "Load the modules listed in a manifest (e.g. modules.toml) on one page"
+        )]
+        manifest: Option<String>,
+
+        /// Instantiate the module in a Web Worker so long-running exports don't block the UI
+        #[arg(
+            long,
+            conflicts_with = "manifest",
+            help = "Run the module in a Web Worker; exports are called over RPC"
+        )]
+        worker: bool,
+
+        /// Log every request with status, size and timing
+        #[arg(
+            long,
+            help = "Print an access log line per request (also kept at /api/logs)"
+        )]
+        access_log: bool,
+
+        /// Serve the control endpoints on a second port
+        #[arg(
+            long,
+            value_name = "PORT",
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            conflicts_with = "runner",
+            help = "Serve /api/*, /metrics and /reload on PORT so the main port serves only the app"
+        )]
+        api_port: Option<u16>,
+
+        /// Listen on a Unix socket instead of a TCP port
+        #[arg(
+            long,
+            value_name = "ADDR",
+            conflicts_with_all = ["serve", "runner", "http2"],
+            help = "Listen on unix:<path> instead of a TCP port, e.g. behind nginx or caddy"
+        )]
+        listen: Option<String>,
+
+        #[command(flatten)]
+        http2: Http2Args,
+
+        /// Name for this server, for `wasmrun trigger --name`
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Name this server so `wasmrun trigger --name NAME` can reach it (default: \"default\")"
+        )]
+        name: Option<String>,
+
+        /// Run the built module under a JS runtime instead of serving it
+        #[arg(
+            long,
+            value_name = "RUNNER",
+            value_parser = ["node", "deno"],
+            conflicts_with_all = ["manifest", "serve"],
+            help = "Run the build under Node.js or Deno (WASI modules, or wasm-bindgen glue) instead of serving it"
+        )]
+        runner: Option<String>,
+
+        /// Host directory the module may use under --runner (repeatable)
+        #[arg(
+            long = "dir",
+            value_name = "HOST[::GUEST][:ro]",
+            requires = "runner",
+            help = "Give the module a host directory: a WASI preopen under node, read/write permission under deno (repeatable)"
+        )]
+        dirs: Vec<String>,
+
+        /// Hosts the module may reach under --runner deno
+        #[arg(
+            long,
+            value_name = "HOSTS",
+            num_args = 0..=1,
+            requires = "runner",
+            help = "Allow network access, to any host or a comma-separated list (deno denies it otherwise)"
+        )]
+        allow_net: Option<Option<String>>,
+
+        /// Seconds each toolchain command may run (0 for no limit)
+        #[arg(
+            long,
+            value_name = "SECS",
+            help = "Stop a build command that runs longer than this (default 1800, 0 for no limit)"
+        )]
+        build_timeout: Option<u64>,
+
+        /// Build in the language's pinned toolchain image
+        #[arg(
+            long,
+            value_name = "RUNTIME",
+            num_args = 0..=1,
+            value_parser = ["docker", "podman"],
+            help = "Run the build's toolchain commands in a container (docker or podman, detected if omitted)"
+        )]
+        in_container: Option<Option<String>>,
+
+        /// Ignore `[remote_build]` in wasmrun.toml
+        #[arg(
+            long,
+            help = "Build on this machine even when wasmrun.toml sets up a remote build agent"
+        )]
+        local_build: bool,
+    },
+
+    /// Ask a running dev server to rebuild now
+    #[command(after_help = help::TRIGGER_EXAMPLES)]
+    Trigger {
+        /// Server to rebuild, as named with `run --name`
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Server to rebuild (default: the only one running)"
+        )]
+        name: Option<String>,
+
+        /// Control port of the server, instead of looking it up by name
+        #[arg(
+            short = 'P',
+            long,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            conflicts_with = "name",
+            help = "Port of the server's control endpoints (its --api-port, or its port)"
+        )]
+        port: Option<u16>,
+    },
+
+    /// Capture a screenshot or short recording of the served app
+    #[command(alias = "screenshot")]
+    Snapshot {
+        /// Path to the project or WASM file
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::AnyPath,
+            help = "Project directory or WASM file to serve"
+        )]
+        path: Option<String>,
+
+        /// Project path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::AnyPath)]
+        positional_path: Option<String>,
+
+        /// Capture a server that is already running instead of starting one
+        #[arg(
+            long,
+            value_name = "URL",
+            conflicts_with_all = ["path", "positional_path", "port"],
+            help = "Base URL of a running server, e.g. http://localhost:8420"
+        )]
+        url: Option<String>,
+
+        /// Page to load, relative to the server root
+        #[arg(long, default_value = "/", help = "Path of the page to capture")]
+        url_path: String,
+
+        /// Output file; the extension picks the format (.png, .jpg or .webm)
+        #[arg(
+            short = 'o',
+            long,
+            default_value = "snapshot.png",
+            value_hint = clap::ValueHint::FilePath,
+            help = "Where to save the capture (.png, .jpg or .webm)"
+        )]
+        out: String,
+
+        /// CSS selector that must exist before capturing
+        #[arg(
+            long,
+            value_name = "SELECTOR",
+            help = "Wait for an element matching this selector"
+        )]
+        wait_for: Option<String>,
+
+        /// Event the page dispatches on window or document once it is ready
+        #[arg(
+            long,
+            value_name = "EVENT",
+            conflicts_with = "wait_for",
+            help = "Wait for the page to dispatch this event"
+        )]
+        ready_event: Option<String>,
+
+        /// Seconds to wait for the page to become ready
+        #[arg(long, default_value_t = 30, help = "Readiness timeout in seconds")]
+        timeout: u64,
+
+        /// Extra settle time after the page is ready
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 0,
+            help = "Milliseconds to wait after the page is ready"
+        )]
+        delay: u64,
+
+        /// Viewport width
+        #[arg(long, default_value_t = 1280, help = "Viewport width in pixels")]
+        width: u32,
+
+        /// Viewport height
+        #[arg(long, default_value_t = 720, help = "Viewport height in pixels")]
+        height: u32,
+
+        /// Capture the whole scrollable page, not just the viewport
+        #[arg(long, help = "Capture the full page height")]
+        full_page: bool,
+
+        /// Recording length for .webm output
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = 3,
+            help = "Recording length for .webm output"
+        )]
+        duration: u64,
+
+        /// Port for the temporary server (default: any free port)
+        #[arg(
+            short = 'P',
+            long,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "Port for the temporary server"
+        )]
+        port: Option<u16>,
+    },
+
+    /// Run the served app headlessly and check what it logs to the console
+    E2e {
+        /// Path to the project or WASM file
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::AnyPath,
+            help = "Project directory or WASM file to serve"
+        )]
+        path: Option<String>,
+
+        /// Project path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::AnyPath)]
+        positional_path: Option<String>,
+
+        /// Test a server that is already running instead of starting one
+        #[arg(
+            long,
+            value_name = "URL",
+            conflicts_with_all = ["path", "positional_path", "port"],
+            help = "Base URL of a running server, e.g. http://localhost:8420"
+        )]
+        url: Option<String>,
+
+        /// Page to load, relative to the server root
+        #[arg(long, default_value = "/", help = "Path of the page to test")]
+        url_path: String,
+
+        /// Text the page must log; repeat for several markers
+        #[arg(
+            long,
+            value_name = "TEXT",
+            required = true,
+            help = "Fail unless the console shows this text (repeatable)"
+        )]
+        expect_console: Vec<String>,
+
+        /// Console errors containing this text don't fail the run
+        #[arg(
+            long,
+            value_name = "TEXT",
+            help = "Ignore console errors containing this text (repeatable)"
+        )]
+        ignore_error: Vec<String>,
+
+        /// Seconds to wait for every marker
+        #[arg(long, default_value_t = 60, help = "Timeout in seconds")]
+        timeout: u64,
+
+        /// Write a JUnit XML report
+        #[arg(
+            long,
+            value_name = "FILE",
+            value_hint = clap::ValueHint::FilePath,
+            help = "Write results as JUnit XML"
+        )]
+        junit: Option<String>,
+
+        /// Port for the temporary server (default: any free port)
+        #[arg(
+            short = 'P',
+            long,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "Port for the temporary server"
+        )]
+        port: Option<u16>,
+    },
+
+    /// Execute a WASM file directly with arguments
+    #[cfg_attr(not(feature = "interpreter"), command(hide = true))]
+    #[command(after_help = help::EXEC_EXAMPLES)]
+    Exec {
+        /// Path or URL of the WASM file
+        #[arg(
+            value_hint = clap::ValueHint::FilePath,
+            help = "Path or http(s) URL of the WASM file to execute"
+        )]
+        wasm_file: Option<String>,
+
+        /// Expected SHA-256 of the module (required to cache downloads)
+        #[arg(
+            long,
+            value_name = "DIGEST",
+            help = "Verify the module against this SHA-256 digest"
+        )]
+        sha256: Option<String>,
+
+        /// Exported function name to call (if not specified, uses entry point)
+        #[arg(
+            short = 'c',
+            long,
+            value_hint = clap::ValueHint::Other,
+            help = "Exported function to call (defaults to entry point: main, _start, or start)"
+        )]
+        call: Option<String>,
+
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+
+        /// External engine to compare the interpreter against
+        #[arg(
+            long,
+            value_name = "ENGINE",
+            help = "Also run on an external engine (wasmtime, wasmer, or a path to one) and report where results, traps or output differ"
+        )]
+        cross_check: Option<String>,
+
+        /// Run a wasm-bindgen module through its JS glue in an embedded engine
+        #[arg(
+            long,
+            help = "Experimental: run a wasm-bindgen module's --target web or nodejs glue in an embedded JS engine (needs the js-engine feature)"
+        )]
+        bindgen: bool,
+
+        /// JS glue for --bindgen (defaults to the module name without _bg, plus .js)
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "bindgen",
+            value_hint = clap::ValueHint::FilePath,
+            help = "JS glue to load with --bindgen (default: app.js beside app_bg.wasm)"
+        )]
+        glue: Option<String>,
+
+        /// Print a JSON report instead of the program's output
+        #[arg(
+            long,
+            help = "Print one JSON object with the exit code, captured output, and any trap with its backtrace"
+        )]
+        json: bool,
+
+        /// Arguments to pass to the WASM program (after the WASM file)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Summarize coverage recorded by `wasmrun exec --coverage`
+    #[cfg_attr(not(feature = "interpreter"), command(hide = true))]
+    #[command(subcommand)]
+    Coverage(CoverageSubcommands),
+
+    /// Run projects in browser-based multi-language OS mode
+    #[cfg_attr(not(feature = "os-mode"), command(hide = true))]
+    #[command(after_help = help::OS_EXAMPLES)]
+    Os {
+        /// Path to the project
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to run in OS mode"
+        )]
+        path: Option<String>,
+
+        /// Project path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Port to serve (default: 8420)
+        #[arg(
+            short = 'P',
+            long,
+            default_value_t = 8420,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "OS mode server port"
+        )]
+        port: u16,
+
+        /// Language to use for OS mode execution (auto-detect if not specified)
+        #[arg(
+            short = 'l',
+            long,
+            value_parser = ["nodejs", "python"],
+            help = "Force specific language for OS mode execution"
+        )]
+        language: Option<String>,
+
+        /// Enable watch mode for live-reloading on file changes
+        #[arg(long, help = "Watch for changes and auto-reload")]
+        watch: bool,
+
+        /// Enable verbose output
+        #[arg(short = 'v', long, help = "Show detailed build output")]
+        verbose: bool,
+
+        /// Allow wildcard CORS (Access-Control-Allow-Origin: *)
+        #[arg(
+            long,
+            help = "Allow cross-origin requests from any domain (default: localhost only)"
+        )]
+        allow_cors: bool,
+
+        /// Maximum accepted request body size in MB (default: 32, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 32,
+            help = "Maximum accepted request body size in MB (0 = unlimited)"
+        )]
+        max_body: u32,
+
+        /// State-changing API requests allowed per client IP per minute (default: 120, 0 = unlimited)
+        #[arg(
+            long,
+            value_name = "PER_MINUTE",
+            default_value_t = 120,
+            help = "POST/DELETE API requests allowed per client IP per minute (0 = unlimited)"
+        )]
+        rate_limit: u32,
+
+        /// Disable every endpoint that changes state (file writes, project control, port forwards)
+        #[arg(
+            long,
+            help = "Serve the UI and read-only APIs only; reject writes and process control"
+        )]
+        read_only: bool,
+
+        /// Mount a named volume or host directory into the project process (repeatable)
+        #[arg(
+            long = "mount",
+            value_name = "SOURCE:GUEST[:ro|rw]",
+            help = "Mount a named volume or host directory for the project, e.g. shared:/data:ro (repeatable)"
+        )]
+        mounts: Vec<String>,
+
+        /// Print a password hash for `[auth.users]` and exit
+        #[arg(
+            long,
+            help = "Read a password from stdin, print its Argon2 hash for [auth.users], then exit"
+        )]
+        hash_password: bool,
+
+        #[command(flatten)]
+        http2: Http2Args,
+    },
+
+    /// Start the agent sandbox API server for AI agents
+    #[cfg_attr(not(feature = "interpreter"), command(hide = true))]
+    Agent {
+        /// Server port (default: 8430)
+        #[arg(
+            short = 'P',
+            long,
+            default_value_t = 8430,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "Agent API server port"
+        )]
+        port: u16,
+
+        /// Default session timeout in seconds (default: 300)
+        #[arg(
+            short = 't',
+            long,
+            default_value_t = 300,
+            help = "Default idle timeout per session (seconds)"
+        )]
+        timeout: u64,
+
+        /// Maximum concurrent sessions (default: 100)
+        #[arg(
+            short = 'm',
+            long,
+            default_value_t = 100,
+            help = "Maximum number of concurrent sandbox sessions"
+        )]
+        max_sessions: usize,
+
+        /// Memory limit per session in MB (default: 256, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 256,
+            help = "Maximum linear memory per session in MB (0 = unlimited)"
+        )]
+        max_memory: u32,
+
+        /// Instruction budget ("fuel") per execution (default: 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Maximum WASM instructions per execution (0 = unlimited)"
+        )]
+        max_fuel: u64,
+
+        /// Captured output cap per execution in MB (default: 10, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 10,
+            help = "Maximum captured stdout+stderr per execution in MB (0 = unlimited)"
+        )]
+        max_output: u32,
+
+        /// Per-file write size cap in MB (default: 50, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 50,
+            help = "Maximum size of any single file in MB (0 = unlimited)"
+        )]
+        max_file_size: u32,
+
+        /// Total disk usage cap per session in MB (default: 100, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 100,
+            help = "Maximum total disk usage per session in MB (0 = unlimited)"
+        )]
+        max_disk: u32,
+
+        /// Maximum accepted request body size in MB (default: 32, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 32,
+            help = "Maximum accepted request body size in MB (0 = unlimited)"
+        )]
+        max_body: u32,
+
+        /// Maximum concurrent executions across all sessions (default: 100, 0 = unlimited)
+        #[arg(
+            long,
+            default_value_t = 100,
+            help = "Maximum concurrent executions across all sessions (0 = unlimited)"
+        )]
+        max_concurrent_exec: usize,
+
+        /// npm registry base URL used to vendor `dependencies` requests
+        #[arg(
+            long,
+            value_name = "URL",
+            default_value = "https://registry.npmjs.org",
+            help = "npm registry base URL for dependency vendoring"
+        )]
+        npm_registry: String,
+
+        /// Allow wildcard CORS (Access-Control-Allow-Origin: *)
+        #[arg(long, help = "Allow cross-origin requests from any domain")]
+        allow_cors: bool,
+
+        /// Enable verbose request logging
+        #[arg(short = 'v', long, help = "Log all incoming requests")]
+        verbose: bool,
+
+        /// Path to a TOML auth config enabling API-key auth and tenant isolation
+        #[arg(
+            long = "auth",
+            value_name = "PATH",
+            help = "Enable API-key auth from a TOML config (omit = open mode)"
+        )]
+        auth_config: Option<String>,
+
+        /// Print the SHA-256 hash of an API key (for the auth config) and exit
+        #[arg(
+            long,
+            value_name = "KEY",
+            help = "Print sha256(KEY) for use in the auth config, then exit"
+        )]
+        hash_key: Option<String>,
+    },
+
+    /// Plugin management commands
+    #[command(subcommand, after_help = help::PLUGIN_EXAMPLES)]
+    Plugin(PluginSubcommands),
+
+    /// Print example commands for each supported language, or run a demo
+    #[command(after_help = help::EXAMPLES_EXAMPLES, args_conflicts_with_subcommands = true)]
+    Examples {
+        #[command(subcommand)]
+        action: Option<ExamplesSubcommands>,
+
+        /// Only show this language
+        #[arg(index = 1, value_name = "LANGUAGE")]
+        language: Option<String>,
+    },
+
+    /// Create a new project from a built-in or community template
+    #[command(alias = "new", after_help = help::INIT_EXAMPLES)]
+    Init {
+        /// Project name
+        #[arg(index = 1, help = "Name of the new project")]
+        name: Option<String>,
+
+        /// Built-in template (rust, go, c, asc, python) or one from the registry
+        #[arg(
+            short = 't',
+            long,
+            default_value = "rust",
+            help = "Project template to use"
+        )]
+        template: String,
+
+        /// Target directory (default: project name)
+        #[arg(
+            short = 'd',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Directory to create project in"
+        )]
+        directory: Option<String>,
+
+        /// List the built-in and registry templates
+        #[arg(long)]
+        list: bool,
+
+        /// Fetch the registry and template again instead of using the cache
+        #[arg(long)]
+        refresh: bool,
+
+        /// Git URL or directory of a template registry
+        #[arg(long, value_name = "URL|PATH")]
+        registry: Option<String>,
+    },
+    /// Clean build artifacts and temporary files
+    #[command(aliases = ["clear", "reset"], after_help = help::CLEAN_EXAMPLES)]
+    Clean {
+        /// Path to the project directory
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to clean"
+        )]
+        path: Option<String>,
+
+        /// Project directory path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Clean everything (project artifacts and temp directories)
+        #[arg(
+            short = 'a',
+            long,
+            help = "Clean both project artifacts and temp directories"
+        )]
+        all: bool,
+
+        /// Also clean tool caches, wasmrun's caches and stale server state
+        #[arg(
+            long,
+            help = "Also clean plugin caches (e.g. TinyGo), ~/.wasmrun caches and stale PID/state files"
+        )]
+        deep: bool,
+
+        /// Also delete installed dependencies
+        #[arg(long, help = "Also delete installed dependencies such as node_modules")]
+        include_deps: bool,
+
+        /// List what would be deleted without deleting it
+        #[arg(long, help = "List what would be deleted without deleting anything")]
+        dry_run: bool,
+    },
+
+    /// Build twice and check the outputs are identical
+    #[command(after_help = help::REPRO_EXAMPLES)]
+    Repro {
+        /// Path to the project directory
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to build"
+        )]
+        path: Option<String>,
+
+        /// Project directory path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Optimization level: debug, release, size
+        #[arg(
+            long,
+            default_value = "release",
+            value_parser = ["debug", "release", "size"],
+            help = "Compilation optimization level"
+        )]
+        optimization: String,
+
+        /// Language to compile as (auto-detect if not specified)
+        #[arg(
+            short = 'l',
+            long,
+            value_parser = ["rust", "go", "c", "asc", "python"],
+            help = "Force specific language for compilation"
+        )]
+        language: Option<String>,
+
+        /// Build the second time from a copy of the sources
+        #[arg(
+            long,
+            help = "Build the second time from a clean copy of the sources in a temp directory"
+        )]
+        isolated: bool,
+
+        /// Keep both builds for inspection
+        #[arg(long, help = "Keep both builds instead of deleting them")]
+        keep: bool,
+
+        /// Print the report as JSON
+        #[arg(long, help = "Print the comparison as JSON")]
+        json: bool,
+    },
+
+    /// Build at several optimization levels and compare the results
+    #[command(after_help = help::MATRIX_EXAMPLES)]
+    Matrix {
+        /// Path to the project directory
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to build"
+        )]
+        path: Option<String>,
+
+        /// Project directory path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Optimization levels to build, in order
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "debug,release,size",
+            value_parser = ["debug", "release", "size"],
+            help = "Comma-separated optimization levels to build"
+        )]
+        opt: Vec<String>,
+
+        /// Language to compile as (auto-detect if not specified)
+        #[arg(
+            short = 'l',
+            long,
+            value_parser = ["rust", "go", "c", "asc", "python"],
+            help = "Force specific language for compilation"
+        )]
+        language: Option<String>,
+
+        /// Export to time at each level
+        #[arg(
+            long,
+            value_name = "EXPORT",
+            help = "Time calls to this export in the interpreter at each level"
+        )]
+        bench: Option<String>,
+
+        /// Arguments for the benchmarked export
+        #[arg(
+            long = "arg",
+            value_name = "VALUE",
+            help = "Argument for the benchmarked export (repeatable)"
+        )]
+        args: Vec<String>,
+
+        /// Timed calls per level
+        #[arg(
+            long,
+            default_value_t = 20,
+            help = "Timed calls per level with --bench"
+        )]
+        iterations: usize,
+
+        /// Keep the builds for inspection
+        #[arg(long, help = "Keep the builds instead of deleting them")]
+        keep: bool,
+
+        /// Print the report as JSON
+        #[arg(long, help = "Print the comparison as JSON")]
+        json: bool,
+    },
+
+    /// List and prune the builds `compile` keeps in the artifact store
+    #[command(subcommand, after_help = help::ARTIFACTS_EXAMPLES)]
+    Artifacts(ArtifactsSubcommands),
+
+    /// Push a WASM module to an OCI registry
+    #[cfg_attr(not(feature = "deploy"), command(hide = true))]
+    Push {
+        /// Registry reference, e.g. ghcr.io/me/app:v1
+        #[arg(
+            index = 1,
+            value_name = "REFERENCE",
+            help = "Registry reference to push to"
+        )]
+        reference: String,
+
+        /// Path to the WASM file
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::FilePath,
+            help = "WASM file to push"
+        )]
+        path: Option<String>,
+
+        /// WASM file path (positional argument)
+        #[arg(index = 2, value_hint = clap::ValueHint::FilePath)]
+        positional_path: Option<String>,
+
+        /// Refuse to push from a git tree with uncommitted changes
+        #[arg(
+            long,
+            help = "Fail if the git tree the module is in has uncommitted changes"
+        )]
+        require_clean: bool,
+    },
+
+    /// Pull a WASM module from an OCI registry
+    #[cfg_attr(not(feature = "deploy"), command(hide = true))]
+    Pull {
+        /// Registry reference, e.g. ghcr.io/me/app:v1 or ghcr.io/me/app@sha256:...
+        #[arg(
+            index = 1,
+            value_name = "REFERENCE",
+            help = "Registry reference to pull"
+        )]
+        reference: String,
+
+        /// Where to write the module (file or directory, default: ./<name>.wasm)
+        #[arg(
+            short = 'o',
+            long,
+            value_hint = clap::ValueHint::AnyPath,
+            help = "Output file or directory"
+        )]
+        output: Option<String>,
+    },
+
+    /// Build projects for machines whose wasmrun.toml sets up `[remote_build]`
+    #[cfg_attr(not(feature = "deploy"), command(hide = true))]
+    #[command(after_help = help::BUILD_AGENT_EXAMPLES)]
+    BuildAgent {
+        /// Address to accept builds on
+        #[arg(
+            long,
+            value_name = "HOST:PORT",
+            default_value = "127.0.0.1:8420",
+            help = "Listen on HOST:PORT (use 0.0.0.0:8420 to accept other machines)"
+        )]
+        listen: String,
+
+        /// Where projects are synced and built
+        #[arg(
+            long,
+            value_name = "DIR",
+            value_hint = clap::ValueHint::DirPath,
+            help = "Directory for synced projects and their build caches (default ~/.wasmrun/build-agent)"
+        )]
+        workdir: Option<String>,
+    },
+
+    /// Generate editor configuration for a project
+    #[command(subcommand, after_help = help::IDE_EXAMPLES)]
+    Ide(IdeSubcommands),
+
+    /// Serve a JSON-RPC control API for editors and other tools
+    #[command(after_help = help::SERVE_API_EXAMPLES)]
+    ServeApi {
+        /// Listen on a socket instead of stdio
+        #[arg(
+            long,
+            value_name = "ADDR",
+            help = "Listen on HOST:PORT or unix:<path> instead of speaking over stdin/stdout"
+        )]
+        listen: Option<String>,
+    },
+}
+
+/// HTTP/2 flags shared by `wasmrun run` and `wasmrun os`
+#[derive(clap::Args, Debug, Clone)]
+pub struct Http2Args {
+    /// Serve over HTTP/2
+    #[arg(
+        long,
+        help = "Serve HTTP/2 as well as HTTP/1.1 (h2c without TLS; browsers need --tls-cert and --tls-key)"
+    )]
+    pub http2: bool,
+
+    /// PEM certificate chain for HTTP/2 over TLS
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        requires_all = ["http2", "tls_key"],
+        help = "PEM certificate chain to serve HTTP/2 over TLS, e.g. from mkcert"
+    )]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key for `--tls-cert`
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        requires = "tls_cert",
+        help = "PEM private key for --tls-cert"
+    )]
+    pub tls_key: Option<PathBuf>,
+}
+
+impl Http2Args {
+    pub fn options(&self) -> Option<Http2Options> {
+        self.http2.then(|| Http2Options {
+            tls: self
+                .tls_cert
+                .clone()
+                .zip(self.tls_key.clone())
+                .map(|(cert, key)| TlsFiles { cert, key }),
+        })
+    }
+}
+
+/// Interpreter debugging flags for `wasmrun exec`
+#[derive(clap::Args, Debug, Clone)]
+pub struct InterpreterArgs {
+    /// Linear-memory ranges to watch for writes
+    #[arg(
+        long = "watch-mem",
+        value_name = "RANGE",
+        help = "Report writes to a memory range: 0x1000..0x1010, 0x1000+16 or 0x1000 (repeatable)"
+    )]
+    pub watch_mem: Vec<String>,
+
+    /// What a write to a watched range does
+    #[arg(
+        long,
+        value_name = "ACTION",
+        default_value = "log",
+        value_parser = ["log", "pause", "stop"],
+        help = "On a watched write: log a backtrace, pause for Enter, or stop execution"
+    )]
+    pub watch_action: String,
+
+    /// Nested call limit
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Trap after N nested calls instead of overflowing the stack (default: 4096)"
+    )]
+    pub max_call_depth: Option<u32>,
+
+    /// Operand stack limit
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Trap once the operand stack holds more than N values (default: 1048576)"
+    )]
+    pub max_stack_values: Option<u32>,
+
+    /// TOML file defining host functions for the module's imports
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        help = "Register host functions from a TOML spec that returns constants, echoes arguments or reads a file into memory"
+    )]
+    pub host_functions: Option<String>,
+
+    /// Stub for imports wasmrun doesn't provide
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_parser = ["trap", "zero", "log"],
+        help = "Stub unresolved function imports: trap naming the import, return zeroes, or log each call and return zeroes"
+    )]
+    pub stub_imports: Option<String>,
+
+    /// File to log executed instructions to
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        help = "Log each executed instruction with the top of the operand stack to FILE"
+    )]
+    pub trace_instructions: Option<String>,
+
+    /// Functions to trace
+    #[arg(
+        long,
+        value_name = "FUNC",
+        requires = "trace_instructions",
+        help = "Only trace this function, by name or index (repeatable)"
+    )]
+    pub trace_filter: Vec<String>,
+
+    /// Trace entries to keep
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "trace_instructions",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Keep only the last N traced instructions (default: 100000)"
+    )]
+    pub trace_limit: Option<u32>,
+
+    /// File to write coverage counts to
+    #[arg(
+        long,
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        help = "Write per-function execution counts to FILE as JSON (see `wasmrun coverage report`)"
+    )]
+    pub coverage: Option<String>,
+
+    /// Count block entries in the coverage file
+    #[arg(
+        long,
+        requires = "coverage",
+        help = "Also count entries into each block, loop and if"
+    )]
+    pub coverage_blocks: bool,
+
+    /// Skip the interpreter's optimizer
+    #[arg(
+        long,
+        help = "Run every instruction as decoded instead of fusing common sequences (for comparing timings or ruling out the optimizer)"
+    )]
+    pub no_optimize: bool,
+}
+
+/// Coverage subcommands
+#[derive(Subcommand, Debug)]
+pub enum CoverageSubcommands {
+    /// Print a summary of a coverage file
+    Report {
+        /// Coverage file written by `wasmrun exec --coverage`
+        #[arg(value_hint = clap::ValueHint::FilePath)]
+        file: String,
+
+        /// Module to read DWARF line info from
+        #[arg(
+            long,
+            value_name = "WASM",
+            value_hint = clap::ValueHint::FilePath,
+            help = "Module to map functions and blocks to source lines from (default: the one recorded)"
+        )]
+        wasm: Option<String>,
+
+        /// List every uncovered function and block
+        #[arg(
+            long,
+            help = "List every uncovered function and block, not just the first 20"
+        )]
+        all: bool,
+    },
+}
+
+/// `wasmrun examples` subcommands
+#[derive(Subcommand, Debug)]
+pub enum ExamplesSubcommands {
+    /// Fetch a curated demo from wasmrun's repository, build it and serve it
+    Run {
+        /// Demo to run; `wasmrun examples` lists them
+        #[arg(index = 1, value_name = "NAME")]
+        name: String,
+
+        #[arg(
+            short = 'P',
+            long,
+            default_value_t = 8420,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "Development server port"
+        )]
+        port: u16,
+
+        /// Rebuild and reload when the demo's sources change
+        #[arg(long)]
+        watch: bool,
+
+        /// Fetch the demo again instead of using the cached copy
+        #[arg(long)]
+        refresh: bool,
+    },
+}
+
+/// Artifact store subcommands
+#[derive(Subcommand, Debug)]
+pub enum ArtifactsSubcommands {
+    /// List stored builds, most recently used first
+    List {
+        /// Print the builds as JSON
+        #[arg(long, help = "Print the stored builds as JSON")]
+        json: bool,
+    },
+
+    /// Remove stored builds that haven't been used lately, and the artifacts only they refer to
+    Gc {
+        /// Age in days past which an unused build is removed
+        #[arg(
+            long,
+            value_name = "DAYS",
+            default_value_t = 30,
+            help = "Remove builds not used in this many days"
+        )]
+        older_than: u64,
+
+        /// Most builds to keep
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Also keep no more than N builds, most recently used first"
+        )]
+        keep: Option<usize>,
+
+        /// Empty the store
+        #[arg(long, conflicts_with_all = ["older_than", "keep"], help = "Remove every stored build")]
+        all: bool,
+
+        /// Only report what would be removed
+        #[arg(long, help = "Show what would be removed without deleting anything")]
+        dry_run: bool,
+    },
+}
+
+/// Editor setup subcommands
+#[derive(Subcommand, Debug)]
+pub enum IdeSubcommands {
+    /// Write build and run tasks to .vscode/tasks.json and a launch configuration to launch.json
+    Vscode {
+        /// Path to the project
+        #[arg(
+            short = 'p',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Project directory to set up"
+        )]
+        path: Option<String>,
+
+        /// Project path (positional argument)
+        #[arg(index = 1, value_hint = clap::ValueHint::DirPath)]
+        positional_path: Option<String>,
+
+        /// Workspace folder opened in VS Code (default: the project)
+        #[arg(
+            long,
+            value_name = "DIR",
+            value_hint = clap::ValueHint::DirPath,
+            help = "Folder opened in VS Code, when the project is a subdirectory of it"
+        )]
+        workspace: Option<String>,
+
+        /// Dev server port (default: [defaults] in wasmrun.toml, or 8420)
+        #[arg(
+            short = 'P',
+            long,
+            value_parser = clap::value_parser!(u16).range(1..=65535),
+            help = "Port the run task serves on"
+        )]
+        port: Option<u16>,
+
+        /// Build output directory (default: [defaults] in wasmrun.toml, or dist)
+        #[arg(
+            short = 'o',
+            long,
+            value_hint = clap::ValueHint::DirPath,
+            help = "Output directory for the build task, relative to the project"
+        )]
+        output: Option<String>,
+
+        /// Language to pass to wasmrun (auto-detect if not specified)
+        #[arg(
+            short = 'l',
+            long,
+            value_parser = ["rust", "go", "c", "asc", "python"],
+            help = "Force specific language for the tasks"
+        )]
+        language: Option<String>,
+
+        /// Print the files instead of writing them
+        #[arg(long, help = "Print the merged files instead of writing them")]
+        dry_run: bool,
+    },
+}
+
+/// Plugin management subcommands
+#[derive(Subcommand, Debug)]
+pub enum PluginSubcommands {
+    /// List all available plugins
+    List {
+        /// Show detailed information
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Install a plugin
+    #[cfg_attr(not(feature = "external-plugins"), command(hide = true))]
+    Install {
+        /// Plugin name, URL, or path
+        plugin: String,
+
+        /// Specific version to install (for crates.io plugins)
+        #[arg(short, long)]
+        version: Option<String>,
+    },
+
+    /// Uninstall a plugin
+    #[cfg_attr(not(feature = "external-plugins"), command(hide = true))]
+    Uninstall {
+        /// Plugin name to uninstall
+        plugin: String,
+    },
+
+    /// Update a plugin
+    #[cfg_attr(not(feature = "external-plugins"), command(hide = true))]
+    Update {
+        /// Plugin name to update, or 'all' for all plugins
+        plugin: String,
+    },
+
+    /// Enable or disable a plugin
+    Enable {
+        /// Plugin name
+        plugin: String,
+
+        /// Disable instead of enable
+        #[arg(long)]
+        disable: bool,
+    },
+
+    /// Show detailed information about a plugin
+    Info {
+        /// Plugin name
+        plugin: String,
+    },
+    // TODO: Implement plugin search with proper plugin registry system
+    // /// Search for available plugins
+    // Search {
+    //     /// Search query
+    //     query: String,
+    // },
+}
+
+/// Argument resolution with validation
+#[derive(Debug)]
+pub struct ResolvedArgs {
+    pub path: String,
+    pub port: u16,
+    pub wasm: bool,
+    pub watch: bool,
+    #[allow(dead_code)] // TODO: Used for debug output control
+    pub debug: bool,
+    #[allow(dead_code)] // TODO: Used for command validation and processing
+    pub command: Option<Commands>,
+    pub serve: bool,
+    pub language: Option<String>,
+}
+
+impl ResolvedArgs {
+    /// Create from CLI args with path resolution and validation
+    pub fn from_args(args: Args) -> Result<Self> {
+        let resolved_path = PathResolver::resolve_input_path(args.positional_path, Some(args.path));
+
+        Ok(Self {
+            path: resolved_path,
+            port: args.port,
+            wasm: args.wasm,
+            watch: args.watch,
+            debug: args.debug,
+            command: args.command,
+            serve: args.serve,
+            language: args.language,
+        })
+    }
+
+    /// Validate the resolved arguments
+    #[allow(dead_code)] // TODO: Future argument validation system
+    pub fn validate(&self) -> Result<()> {
+        // Validate port range
+        if self.port == 0 {
+            return Err(WasmrunError::from(format!(
+                "Invalid port number: {}. Must be between 1-65535",
+                self.port
+            )));
+        }
+
+        // Validate path based on context
+        match &self.command {
+            Some(Commands::Verify { .. }) | Some(Commands::Inspect { .. }) => {
+                // These commands expect WASM files
+                PathResolver::validate_wasm_file(&self.path)?;
+            }
+            Some(Commands::Compile { .. })
+            | Some(Commands::Run { .. })
+            | Some(Commands::Os { .. })
+            | Some(Commands::Clean { .. })
+            | Some(Commands::Repro { .. })
+            | Some(Commands::Matrix { .. }) => {
+                // These commands expect project directories
+                PathResolver::validate_directory_exists(&self.path)?;
+            }
+            _ => {
+                // For default run command, validate based on wasm flag
+                if self.wasm {
+                    PathResolver::validate_wasm_file(&self.path)?;
+                } else {
+                    // Could be either file or directory
+                    if !std::path::Path::new(&self.path).exists() {
+                        return Err(WasmrunError::path(format!("Path not found: {}", self.path)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Command-specific argument resolution
+pub trait CommandArgs {
+    #[allow(dead_code)] // TODO: Future path resolution trait implementation
+    fn resolve_path(&self) -> String;
+}
+
+impl CommandArgs for Commands {
+    fn resolve_path(&self) -> String {
+        match self {
+            Commands::Compile {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Verify {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Inspect {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Run {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Snapshot {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::E2e {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Exec { wasm_file, .. } => {
+                PathResolver::resolve_input_path(wasm_file.clone(), None)
+            }
+            Commands::Os {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Clean {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Repro {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Matrix {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            // TODO: Implement Init command
+            Commands::Init {
+                name, directory, ..
+            } => directory.clone().unwrap_or_else(|| {
+                name.clone()
+                    .unwrap_or_else(|| "my-wasmrun-project".to_string())
+            }),
+            Commands::Push {
+                path,
+                positional_path,
+                ..
+            } => PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            Commands::Pull { .. } => "./".to_string(),
+            Commands::Agent { .. } => "./".to_string(),
+            Commands::Plugin(_) => "./".to_string(),
+            Commands::Examples { .. } => "./".to_string(),
+            Commands::Ide(_) => "./".to_string(),
+            Commands::Artifacts(_) => "./".to_string(),
+            Commands::ServeApi { .. } => "./".to_string(),
+            Commands::BuildAgent { .. } => "./".to_string(),
+            Commands::Coverage(_) => "./".to_string(),
+            Commands::Stop => "./".to_string(),
+            Commands::Trigger { .. } => "./".to_string(),
+        }
+    }
+}
+
+/// Validation helper for specific command arguments
+pub struct CommandValidator;
+
+impl CommandValidator {
+    #[allow(dead_code)] // TODO: Future compile command validation
+    pub fn validate_compile_args(
+        path: &Option<String>,
+        positional_path: &Option<String>,
+        output: &Option<String>,
+    ) -> Result<(String, String)> {
+        let project_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+        let output_dir = output.clone().unwrap_or_else(|| ".".to_string());
+
+        PathResolver::validate_directory_exists(&project_path)?;
+        PathResolver::ensure_output_directory(&output_dir)?;
+
+        Ok((project_path, output_dir))
+    }
+
+    pub fn validate_verify_args(
+        path: &Option<String>,
+        positional_path: &Option<String>,
+    ) -> Result<String> {
+        let wasm_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+        PathResolver::validate_wasm_file(&wasm_path)?;
+        Ok(wasm_path)
+    }
+
+    #[allow(dead_code)] // TODO: Future run command validation
+    pub fn validate_run_args(
+        path: &Option<String>,
+        positional_path: &Option<String>,
+        port: u16,
+    ) -> Result<(String, u16)> {
+        let project_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+
+        if !std::path::Path::new(&project_path).exists() {
+            return Err(WasmrunError::path(format!(
+                "Path not found: {project_path}"
+            )));
+        }
+
+        Ok((project_path, port))
+    }
+
+    #[allow(dead_code)] // TODO: Future init command validation
+    pub fn validate_init_args(
+        name: &Option<String>,
+        template: &str,
+        directory: &Option<String>,
+    ) -> Result<(String, String, String)> {
+        let project_name = name
+            .clone()
+            .unwrap_or_else(|| "my-wasmrun-project".to_string());
+        let target_dir = directory.clone().unwrap_or_else(|| project_name.clone());
+
+        if template.is_empty() {
+            return Err(WasmrunError::from("Template name can't be empty"));
+        }
+
+        if std::path::Path::new(&target_dir).exists() {
+            return Err(WasmrunError::path(format!(
+                "Directory '{target_dir}' already exists"
+            )));
+        }
+
+        Ok((project_name, template.to_string(), target_dir))
+    }
+}
+
+/// Get version string
+fn get_version_string() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+pub fn get_args() -> Args {
+    if std::env::args().any(|arg| arg == "-V" || arg == "--version") {
+        print_styled_version();
+        std::process::exit(0);
+    }
+
+    resolve(help::command().get_matches())
+}
+
+/// Parse `argv` as though it had been typed, for commands built by the
+/// first-run prompt
+pub fn parse_from(argv: Vec<String>) -> Args {
+    resolve(help::command().get_matches_from(argv))
+}
+
+/// True when `wasmrun` was started with no arguments at all
+pub fn is_bare_invocation() -> bool {
+    std::env::args_os().len() == 1
+}
+
+fn resolve(matches: clap::ArgMatches) -> Args {
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if let Some(pos_path) = args.positional_path.take() {
+        args.path = pos_path;
+    }
+
+    args
+}
+
+/// Print styled version output
+fn print_styled_version() {
+    let version = env!("CARGO_PKG_VERSION");
+    let name = env!("CARGO_PKG_NAME");
+
+    println!(
+        "\n\x1b[1;34m╭\x1b[0m\n\
+         \x1b[1;34m│\x1b[0m  🅦 \x1b[1;36m{name} v{version}\x1b[0m\n\
+         \x1b[1;34m│\x1b[0m  \x1b[0;90mA lightweight WebAssembly runner\x1b[0m\n\
+         \x1b[1;34m╰\x1b[0m\n"
+    );
+}
+
+/// Argument parsing with validation
+#[allow(dead_code)] // TODO: Future validated argument parsing system
+pub fn get_validated_args() -> Result<ResolvedArgs> {
+    let args = get_args();
+    let resolved = ResolvedArgs::from_args(args)?;
+    resolved.validate()?;
+    Ok(resolved)
+}
+
+// Helper function for error conversion
+impl From<String> for WasmrunError {
+    fn from(message: String) -> Self {
+        Self::Command(crate::error::CommandError::invalid_arguments(message))
+    }
+}
@@ -0,0 +1,109 @@
+//! `wasmrun artifacts`: list and prune the builds `wasmrun compile` keeps
+//! in the artifact store
+
+use crate::cli::ArtifactsSubcommands;
+use crate::compiler::store::{ArtifactStore, GcPolicy, StoredBuild};
+use crate::error::{Result, WasmrunError};
+use crate::utils::CommandExecutor;
+use chrono::{DateTime, Utc};
+
+pub fn run_artifacts_command(subcommand: &ArtifactsSubcommands) -> Result<()> {
+    let store = ArtifactStore::open()?;
+    match subcommand {
+        ArtifactsSubcommands::List { json } => handle_list(&store, *json),
+        ArtifactsSubcommands::Gc {
+            older_than,
+            keep,
+            all,
+            dry_run,
+        } => {
+            let policy = if *all {
+                GcPolicy {
+                    unused_since: None,
+                    keep: Some(0),
+                }
+            } else {
+                GcPolicy {
+                    unused_since: Some(Utc::now() - chrono::Duration::days(*older_than as i64)),
+                    keep: *keep,
+                }
+            };
+            handle_gc(&store, policy, *dry_run)
+        }
+    }
+}
+
+fn handle_list(store: &ArtifactStore, json: bool) -> Result<()> {
+    let builds = store.list();
+    if json {
+        let json = serde_json::to_string_pretty(&builds)
+            .map_err(|e| WasmrunError::from(format!("Failed to write the list: {e}")))?;
+        println!("{json}");
+        return Ok(());
+    }
+    if builds.is_empty() {
+        println!("📦 The artifact store is empty; `wasmrun compile` fills it");
+        return Ok(());
+    }
+
+    println!("📦 Stored builds in {}", store.root().display());
+    println!(
+        "  {:<12} {:<10} {:<8} {:>9} {:>10}  {:<12} project",
+        "key", "language", "profile", "artifacts", "size", "last used"
+    );
+    for build in &builds {
+        println!(
+            "  {:<12} {:<10} {:<8} {:>9} {:>10}  {:<12} {}",
+            &build.key[..12.min(build.key.len())],
+            build.language,
+            build.profile,
+            build.artifacts.len(),
+            CommandExecutor::format_file_size(build.size()),
+            last_used(build),
+            build.project
+        );
+    }
+    let total: u64 = builds.iter().map(StoredBuild::size).sum();
+    println!(
+        "\n  {} build(s), {} of artifacts before sharing identical files",
+        builds.len(),
+        CommandExecutor::format_file_size(total)
+    );
+    Ok(())
+}
+
+fn handle_gc(store: &ArtifactStore, policy: GcPolicy, dry_run: bool) -> Result<()> {
+    let summary = store
+        .gc(policy, dry_run)
+        .map_err(|e| WasmrunError::add_context("Pruning the artifact store", e))?;
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    println!(
+        "🗑️  {verb} {} build(s) and {} artifact(s), {}",
+        summary.builds_removed,
+        summary.objects_removed,
+        CommandExecutor::format_file_size(summary.bytes_freed)
+    );
+    println!(
+        "📦 {} build(s) kept, {}",
+        summary.builds_kept,
+        CommandExecutor::format_file_size(summary.bytes_kept)
+    );
+    Ok(())
+}
+
+/// How long ago the build was last stored or restored
+fn last_used(build: &StoredBuild) -> String {
+    let Ok(at) = DateTime::parse_from_rfc3339(&build.last_used) else {
+        return "unknown".to_string();
+    };
+    let age = Utc::now().signed_duration_since(at);
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m ago", age.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
@@ -0,0 +1,468 @@
+//! `wasmrun build-agent`: build projects for other machines, whose
+//! wasmrun.toml points `[remote_build]` here. The protocol is described in
+//! [`crate::remote::build`].
+
+use crate::compiler::builder::BuildConfig;
+use crate::error::{Result, WasmrunError};
+use crate::plugin::manager::PluginManager;
+use crate::remote::build::{
+    file_digest, list_files, pack, safe_relative, unpack, BuildRequest, BuildResponse,
+    ErrorResponse, GitSource, SyncRequest, SyncResponse, BUILD_TOKEN_ENV, MAX_ARCHIVE_BYTES,
+    MAX_JSON_BYTES,
+};
+use crate::utils::CommandExecutor;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+
+/// Requests handled at once; more wait for a free worker. Builds already
+/// use every core, so a few are plenty.
+const WORKERS: usize = 4;
+
+/// The reply body, or an HTTP status and message for the client
+type Reply = std::result::Result<Response<Box<dyn Read + Send>>, (u16, String)>;
+
+/// Handle `wasmrun build-agent`
+pub fn handle_build_agent_command(listen: &str, workdir: Option<&str>) -> Result<()> {
+    let workdir = match workdir {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir()
+            .ok_or_else(|| WasmrunError::from("Could not determine home directory".to_string()))?
+            .join(".wasmrun")
+            .join("build-agent"),
+    };
+    fs::create_dir_all(&workdir)
+        .map_err(|e| WasmrunError::add_context(format!("Creating {}", workdir.display()), e))?;
+    let token = std::env::var(BUILD_TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .map(|token| Sha256::digest(token.as_bytes()).to_vec());
+
+    let server = Server::http(listen)
+        .map_err(|e| WasmrunError::from(format!("Failed to listen on {listen}: {e}")))?;
+    eprintln!(
+        "🏗️  wasmrun build agent listening on http://{listen}, building in {}",
+        workdir.display()
+    );
+    if token.is_none() {
+        eprintln!(
+            "⚠️  {BUILD_TOKEN_ENV} isn't set, so anyone who can reach {listen} can build here"
+        );
+    }
+
+    let agent = BuildAgent {
+        workdir,
+        token,
+        projects: Mutex::new(HashMap::new()),
+    };
+    thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| {
+                while let Ok(request) = server.recv() {
+                    agent.handle(request);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+struct BuildAgent {
+    workdir: PathBuf,
+    /// SHA-256 of the token clients must present. Comparing hashes keeps the
+    /// comparison's timing from leaking the token.
+    token: Option<Vec<u8>>,
+    /// One lock per project, so a project's sync, upload and build don't
+    /// interleave with another client's
+    projects: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl BuildAgent {
+    fn handle(&self, mut request: Request) {
+        let response = match self.route(&mut request) {
+            Ok(response) => response,
+            Err((status, error)) => json(&ErrorResponse { error })
+                .unwrap_or_else(|_| reply(io::empty(), Some(0), "application/json"))
+                .with_status_code(status),
+        };
+        let _ = request.respond(response);
+    }
+
+    /// The lock a project's requests hold while they touch its directory
+    fn project_lock(&self, key: &str) -> Arc<Mutex<()>> {
+        Arc::clone(
+            self.projects
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(key.to_string())
+                .or_default(),
+        )
+    }
+
+    fn route(&self, request: &mut Request) -> Reply {
+        if let Some(expected) = &self.token {
+            let presented = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+                .map(|token| Sha256::digest(token.trim().as_bytes()).to_vec());
+            if presented.as_ref() != Some(expected) {
+                return Err((
+                    401,
+                    format!("Missing or wrong token; set {BUILD_TOKEN_ENV}"),
+                ));
+            }
+        }
+
+        let url = request.url().to_string();
+        let Some((key, action)) = url
+            .strip_prefix("/v1/projects/")
+            .and_then(|rest| rest.split_once('/'))
+        else {
+            return Err((404, format!("No such endpoint: {url}")));
+        };
+        if *request.method() != Method::Post {
+            return Err((405, format!("{url} takes POST")));
+        }
+        let valid_key = !key.is_empty()
+            && !key.starts_with('.')
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        if !valid_key {
+            return Err((400, format!("Invalid project key {key:?}")));
+        }
+
+        // Bodies are read before taking the project's lock, so a slow
+        // client doesn't hold up others building the same project
+        let dir = self.workdir.join(key);
+        let lock = self.project_lock(key);
+        match action {
+            "sync" => {
+                let request: SyncRequest = parse(request)?;
+                let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                sync(&dir, request)
+            }
+            "files" => {
+                let upload = spool(request, &self.workdir)?;
+                let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                let files = unpack(BufReader::new(upload), &dir.join("src"))
+                    .map_err(|e| (400, format!("Bad upload: {e}")))?;
+                json(&serde_json::json!({ "files": files }))
+            }
+            "build" => {
+                let request: BuildRequest = parse(request)?;
+                let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                let started = Instant::now();
+                eprintln!("🔨 {key}: building {}", request.language);
+                let reply = build(&dir, request, started);
+                match &reply {
+                    Ok(_) => {
+                        eprintln!("✅ {key}: built in {:.1}s", started.elapsed().as_secs_f64())
+                    }
+                    Err((_, error)) => eprintln!("❌ {key}: {error}"),
+                }
+                reply
+            }
+            _ => Err((404, format!("No such endpoint: {url}"))),
+        }
+    }
+}
+
+/// The request's JSON body
+fn parse<T: for<'de> serde::Deserialize<'de>>(
+    request: &mut Request,
+) -> std::result::Result<T, (u16, String)> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_JSON_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| (400, format!("Failed to read request body: {e}")))?;
+    if body.len() as u64 > MAX_JSON_BYTES {
+        return Err((413, "Request body is too large".to_string()));
+    }
+    serde_json::from_slice(&body).map_err(|e| (400, format!("Bad request: {e}")))
+}
+
+/// The request's body written to an unnamed file in `dir`, rewound to its
+/// start, so a large upload never sits in memory
+fn spool(request: &mut Request, dir: &Path) -> std::result::Result<File, (u16, String)> {
+    let mut file = tempfile::tempfile_in(dir).map_err(internal)?;
+    let written = io::copy(
+        &mut request.as_reader().take(MAX_ARCHIVE_BYTES + 1),
+        &mut file,
+    )
+    .map_err(|e| (400, format!("Failed to read request body: {e}")))?;
+    if written > MAX_ARCHIVE_BYTES {
+        return Err((413, "Request body is too large".to_string()));
+    }
+    file.rewind().map_err(internal)?;
+    Ok(file)
+}
+
+fn reply(
+    body: impl Read + Send + 'static,
+    length: Option<usize>,
+    content_type: &str,
+) -> Response<Box<dyn Read + Send>> {
+    let content_type = Header::from_bytes("Content-Type", content_type).expect("static header");
+    Response::new(
+        StatusCode(200),
+        vec![content_type],
+        Box::new(body),
+        length,
+        None,
+    )
+}
+
+fn json(value: &impl Serialize) -> Reply {
+    let body = serde_json::to_vec(value).map_err(internal)?;
+    let length = body.len();
+    Ok(reply(Cursor::new(body), Some(length), "application/json"))
+}
+
+fn internal(e: impl std::fmt::Display) -> (u16, String) {
+    (500, e.to_string())
+}
+
+/// Drop files the client no longer has and list the ones to upload
+fn sync(dir: &Path, request: SyncRequest) -> Reply {
+    if let Some(unsafe_path) = request.files.keys().find(|p| safe_relative(p).is_none()) {
+        return Err((400, format!("Unsafe path {unsafe_path:?}")));
+    }
+    let src = dir.join("src");
+    fs::create_dir_all(&src).map_err(internal)?;
+    let existing = list_files(&src, &[]).map_err(internal)?;
+    for (name, path) in &existing {
+        if !request.files.contains_key(name) {
+            fs::remove_file(path).map_err(internal)?;
+        }
+    }
+    let missing = request
+        .files
+        .iter()
+        .filter(|(name, digest)| {
+            existing
+                .get(*name)
+                .and_then(|path| file_digest(path).ok())
+                .as_ref()
+                != Some(*digest)
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    json(&SyncResponse { missing })
+}
+
+fn build(dir: &Path, request: BuildRequest, started: Instant) -> Reply {
+    // A name or pattern with a path in it would put the artifact outside
+    // the output directory, and it would be copied back to the client
+    request.naming.validate().map_err(|e| (400, e))?;
+    let project = match &request.git {
+        Some(git) => {
+            git.validate().map_err(|e| (400, e))?;
+            let repo = checkout(&dir.join("git"), git).map_err(|e| (422, e))?;
+            match safe_relative(&git.subdir) {
+                Some(subdir) => repo.join(subdir),
+                None => repo,
+            }
+        }
+        None => dir.join("src"),
+    };
+    let out = dir.join("out");
+    let _ = fs::remove_dir_all(&out);
+    fs::create_dir_all(&out).map_err(internal)?;
+
+    let project_path = project.to_string_lossy().to_string();
+    let manager = PluginManager::new().map_err(internal)?;
+    let plugin = manager
+        .select_plugin(&project_path, Some(&request.language))
+        .ok_or_else(|| {
+            (
+                422,
+                format!("This agent can't build {} projects", request.language),
+            )
+        })?;
+    let builder = plugin.get_builder();
+    let missing = builder.check_dependencies();
+    if !missing.is_empty() {
+        return Err((
+            422,
+            format!("The build agent is missing {}", missing.join(", ")),
+        ));
+    }
+    builder
+        .validate_project(&project_path)
+        .map_err(|e| (422, e.to_string()))?;
+    let config = BuildConfig {
+        project_path,
+        output_dir: out.to_string_lossy().to_string(),
+        optimization_level: request.optimization,
+        verbose: false,
+        watch: false,
+        target_type: request.target_type,
+        naming: request.naming,
+    };
+    let mut result = builder.build(&config).map_err(|e| (422, e.to_string()))?;
+
+    // The client gets paths inside the output directory; anything a builder
+    // left elsewhere is copied in
+    let relative = |path: &str| -> std::io::Result<String> {
+        let path = Path::new(path);
+        let inside = match path.strip_prefix(&out) {
+            Ok(inside) => inside.to_path_buf(),
+            Err(_) => {
+                let name = PathBuf::from(path.file_name().unwrap_or_default());
+                fs::copy(path, out.join(&name))?;
+                name
+            }
+        };
+        Ok(inside.to_string_lossy().replace('\\', "/"))
+    };
+    result.wasm_path = relative(&result.wasm_path).map_err(internal)?;
+    result.js_path = result
+        .js_path
+        .as_deref()
+        .map(relative)
+        .transpose()
+        .map_err(internal)?;
+    result.additional_files = result
+        .additional_files
+        .iter()
+        .map(|path| relative(path))
+        .collect::<std::io::Result<_>>()
+        .map_err(internal)?;
+
+    let files: Vec<String> = list_files(&out, &[])
+        .map_err(internal)?
+        .into_keys()
+        .collect();
+    // The result line, then the tarball, streamed from an unnamed file
+    // that outlives the next build replacing `out`
+    let mut line = serde_json::to_vec(&BuildResponse {
+        result,
+        seconds: started.elapsed().as_secs_f64(),
+    })
+    .map_err(internal)?;
+    line.push(b'\n');
+    let mut archive = tempfile::tempfile_in(dir)
+        .and_then(|file| pack(&out, &files, file))
+        .map_err(internal)?;
+    let length = archive.stream_position().map_err(internal)? as usize + line.len();
+    archive.rewind().map_err(internal)?;
+    Ok(reply(
+        Cursor::new(line).chain(archive),
+        Some(length),
+        "application/octet-stream",
+    ))
+}
+
+/// Check out `source.commit` in the clone at `repo`, cloning it first if
+/// needed. Ignored files, such as `target`, survive from the last build.
+/// `source` comes from the client, so it's validated and passed after `--`.
+fn checkout(repo: &Path, source: &GitSource) -> std::result::Result<PathBuf, String> {
+    source.validate()?;
+    let git = |args: &[&str], cwd: &Path| {
+        let output = CommandExecutor::execute_command("git", args, &cwd.to_string_lossy(), false)
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+
+    if repo.join(".git").is_dir() {
+        git(&["remote", "set-url", "origin", "--", &source.url], repo)?;
+    } else {
+        let parent = repo.parent().unwrap_or(repo);
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        git(
+            &[
+                "clone",
+                "--no-checkout",
+                "--",
+                &source.url,
+                &repo.to_string_lossy(),
+            ],
+            parent,
+        )?;
+    }
+    // Fetching a commit by hash needs the server's permission; fetching
+    // everything finds it otherwise, once it's pushed
+    if git(&["fetch", "origin", "--", &source.commit], repo).is_err() {
+        git(&["fetch", "origin"], repo)?;
+    }
+    git(
+        &["checkout", "--force", "--detach", &source.commit, "--"],
+        repo,
+    )
+    .map_err(|e| format!("{e} (has {} been pushed?)", source.commit))?;
+    git(&["clean", "-fd"], repo)?;
+    Ok(repo.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::builder::{OptimizationLevel, OutputNaming, TargetType};
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    #[test]
+    fn test_output_name_with_a_path_is_refused() {
+        let workdir = tempfile::tempdir().unwrap();
+        let agent = BuildAgent {
+            workdir: workdir.path().join("agent"),
+            token: None,
+            projects: Mutex::new(HashMap::new()),
+        };
+        fs::create_dir_all(&agent.workdir).unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+
+        let body = serde_json::to_string(&BuildRequest {
+            language: "Rust".to_string(),
+            optimization: OptimizationLevel::Release,
+            target_type: TargetType::Standard,
+            naming: OutputNaming {
+                name: Some("../x".to_string()),
+                ..OutputNaming::default()
+            },
+            git: None,
+        })
+        .unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(
+                stream,
+                "POST /v1/projects/app/build HTTP/1.0\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        agent.handle(server.recv().unwrap());
+        let response = client.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.0 400"), "{response}");
+        assert!(response.contains("Output name"), "{response}");
+        // Nothing was checked out, built or written next to the project
+        assert_eq!(fs::read_dir(&agent.workdir).unwrap().count(), 0);
+        assert!(!workdir.path().join("x.wasm").exists());
+    }
+}
@@ -1,119 +1,311 @@
-use crate::compiler;
+//! `wasmrun clean`: wasmrun's temporary directories by default, the
+//! project's build output with `--all`, and with `--deep` also tool caches,
+//! wasmrun's own caches and whatever exited servers left behind. Every
+//! plugin that claims the project lists what it builds, so one code path
+//! cleans Rust, Go, C, AssemblyScript and external languages alike.
+
+use crate::compiler::builder::{BuilderFactory, CleanKind, CleanTarget};
+use crate::compiler::detect_project_language;
+use crate::config::project::ProjectConfig;
+use crate::config::PID_FILE;
 use crate::error::Result;
+use crate::plugin::manager::PluginManager;
+use crate::server::{is_server_running, process_alive, recorded_socket};
 use crate::ui::print_clean_info;
 use crate::utils::PathResolver;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CleanOptions {
+    /// Also the project's build output
+    pub all: bool,
+    /// Also tool caches, wasmrun's caches and stale server state
+    pub deep: bool,
+    /// Also installed dependencies such as `node_modules`
+    pub include_deps: bool,
+    /// List what would go without deleting anything
+    pub dry_run: bool,
+}
+
+impl CleanOptions {
+    fn includes(&self, kind: CleanKind) -> bool {
+        match kind {
+            CleanKind::Artifact => true,
+            CleanKind::Cache => self.deep,
+            CleanKind::Dependency => self.include_deps,
+        }
+    }
+}
 
 /// Handle clean command
 pub fn handle_clean_command(
     path: &Option<String>,
     positional_path: &Option<String>,
-    all: bool,
+    options: &CleanOptions,
 ) -> Result<()> {
-    println!("🧹 Cleaning wasmrun temporary directories...");
-    PathResolver::cleanup_all_temp_directories()?;
+    let mut targets = temp_targets(options.deep);
 
-    // If all flag is set, also clean project artifacts
-    if all {
+    if options.all || options.deep || options.include_deps {
         let project_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
         PathResolver::validate_directory_exists(&project_path)?;
-
         print_clean_info(&project_path);
+        targets.extend(project_targets(&project_path, options)?);
+    }
+    if options.deep {
+        targets.extend(cache_targets());
+    }
+    let targets = prune(targets);
 
-        let language = compiler::detect_project_language(&project_path);
-
-        match language {
-            // compiler::ProjectLanguage::Rust => clean_rust_project(&project_path),
-            // compiler::ProjectLanguage::Go => clean_go_project(&project_path),
-            compiler::ProjectLanguage::C => clean_c_project(&project_path),
-            compiler::ProjectLanguage::Asc => clean_asc_project(&project_path),
-            _ => {
-                println!("⚠️ Clean operation not specifically implemented for {language:?}");
-                println!("💡 You can manually delete build artifacts in your project directory.");
-                Ok(())
+    if targets.is_empty() {
+        println!("✨ Nothing to clean");
+        return Ok(());
+    }
+    if options.dry_run {
+        println!("🔍 Would delete {} item(s):", targets.len());
+        for target in &targets {
+            println!("   {}  ({})", target.path.display(), target.what);
+        }
+        return Ok(());
+    }
+
+    let mut removed = 0;
+    for target in &targets {
+        let path = target.path.to_string_lossy();
+        let result = if target.path.is_dir() {
+            PathResolver::remove_dir_all(&path)
+        } else {
+            PathResolver::remove_file(&path)
+        };
+        match result {
+            Ok(()) => {
+                println!("🗑️  Removed {}: {}", target.what, target.path.display());
+                removed += 1;
             }
+            Err(e) => println!("⚠️  Warning: {e}"),
         }
-    } else {
-        Ok(())
     }
+    println!("✅ Cleaned {removed} item(s)");
+    Ok(())
 }
 
-// fn clean_rust_project(project_path: &str) -> Result<()> {
-//     let target_dir = PathResolver::join_paths(project_path, "target");
-//     let pkg_dir = PathResolver::join_paths(project_path, "pkg");
-
-//     let mut cleaned = Vec::new();
-
-//     if std::path::Path::new(&target_dir).exists() {
-//         PathResolver::remove_dir_all(&target_dir)?;
-//         cleaned.push("target/");
-//     }
-
-//     if std::path::Path::new(&pkg_dir).exists() {
-//         PathResolver::remove_dir_all(&pkg_dir)?;
-//         cleaned.push("pkg/");
-//     }
-
-//     if cleaned.is_empty() {
-//         println!("✨ Project is already clean!");
-//     } else {
-//         println!("🧹 Cleaned: {}", cleaned.join(", "));
-//     }
-
-//     Ok(())
-// }
-
-// fn clean_go_project(project_path: &str) -> Result<()> {
-//     let output = std::process::Command::new("go")
-//         .args(["clean", "-cache", "-modcache"])
-//         .current_dir(project_path)
-//         .output()
-//         .map_err(|e| WasmrunError::from(format!("Failed to run go clean: {}", e)))?;
-
-//     if output.status.success() {
-//         println!("🧹 Go project cleaned successfully");
-//     } else {
-//         println!("⚠️ Go clean completed with warnings");
-//     }
-
-//     Ok(())
-// }
-
-fn clean_c_project(project_path: &str) -> Result<()> {
-    let mut cleaned = Vec::new();
-
-    let wasm_files = PathResolver::find_files_with_extension(project_path, "wasm")?;
-    for file in wasm_files {
-        PathResolver::remove_file(&file)?;
-        cleaned.push(PathResolver::get_filename(&file)?);
+/// wasmrun's scratch directories, plus with `deep` the build output and OS
+/// workspaces of servers that are no longer running
+fn temp_targets(deep: bool) -> Vec<CleanTarget> {
+    let temp = std::env::temp_dir();
+    let mut targets = Vec::new();
+    let server_running = is_server_running();
+
+    if let Ok(entries) = fs::read_dir(&temp) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("wasmrun_") {
+                targets.push(CleanTarget {
+                    path,
+                    kind: CleanKind::Cache,
+                    what: "temporary directory".to_string(),
+                });
+            } else if deep && name == "wasmrun" && !server_running {
+                targets.push(CleanTarget {
+                    path,
+                    kind: CleanKind::Cache,
+                    what: "dev server build output".to_string(),
+                });
+            } else if let Some(pid) = deep
+                .then(|| name.strip_prefix("wasmrun-"))
+                .flatten()
+                .and_then(|pid| pid.parse::<u32>().ok())
+            {
+                if pid != std::process::id() && !process_alive(pid) {
+                    targets.push(CleanTarget {
+                        path,
+                        kind: CleanKind::Cache,
+                        what: format!("OS mode workspace of exited process {pid}"),
+                    });
+                }
+            }
+        }
     }
 
-    if cleaned.is_empty() {
-        println!("✨ No C build artifacts found to clean");
+    if !server_running {
+        if let Some(socket) = recorded_socket().filter(|socket| socket.exists()) {
+            targets.push(CleanTarget {
+                path: socket,
+                kind: CleanKind::Cache,
+                what: "stale server socket".to_string(),
+            });
+        }
+        targets.extend(CleanTarget::existing(
+            PID_FILE,
+            CleanKind::Cache,
+            "stale server PID file",
+        ));
+    }
+    targets
+}
+
+/// What the project's plugins build, and with `--deep` the compile output
+/// directory saved in `wasmrun.toml`
+fn project_targets(project_path: &str, options: &CleanOptions) -> Result<Vec<CleanTarget>> {
+    let plugin_targets: Vec<CleanTarget> = match PluginManager::new() {
+        Ok(manager) => manager
+            .plugins_for_project(project_path)
+            .iter()
+            .flat_map(|plugin| plugin.get_builder().clean_targets(project_path))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let plugin_targets = if plugin_targets.is_empty() {
+        BuilderFactory::create_builder(&detect_project_language(project_path))
+            .clean_targets(project_path)
     } else {
-        println!("🧹 Cleaned: {}", cleaned.join(", "));
+        plugin_targets
+    };
+
+    let mut targets: Vec<CleanTarget> = plugin_targets
+        .into_iter()
+        .filter(|target| options.includes(target.kind))
+        .collect();
+
+    if options.deep {
+        let output =
+            ProjectConfig::load(Path::new(project_path))?.and_then(|config| config.defaults.output);
+        if let Some(output) = output {
+            let project = Path::new(project_path);
+            let dir = project.join(&output);
+            // Never the project itself or anything outside it
+            let inside = match (dir.canonicalize(), project.canonicalize()) {
+                (Ok(dir), Ok(project)) => dir != project && dir.starts_with(&project),
+                _ => false,
+            };
+            if inside {
+                targets.extend(CleanTarget::existing(
+                    dir,
+                    CleanKind::Artifact,
+                    "compile output from wasmrun.toml",
+                ));
+            }
+        }
     }
+    Ok(targets)
+}
 
-    Ok(())
+/// Downloads wasmrun keeps under `~/.wasmrun`
+fn cache_targets() -> Vec<CleanTarget> {
+    let Some(root) = dirs::home_dir().map(|home| home.join(".wasmrun")) else {
+        return Vec::new();
+    };
+    [
+        (
+            "cache",
+            "downloaded modules, plugin metadata and stored builds",
+        ),
+        ("runtimes", "downloaded runtimes"),
+        ("npm", "npm packages fetched for the agent"),
+    ]
+    .into_iter()
+    .filter_map(|(dir, what)| CleanTarget::existing(root.join(dir), CleanKind::Cache, what))
+    .collect()
 }
 
-fn clean_asc_project(project_path: &str) -> Result<()> {
-    let build_dir = PathResolver::join_paths(project_path, "build");
-    let dist_dir = PathResolver::join_paths(project_path, "dist");
+/// Sort targets and drop duplicates and anything inside another target
+fn prune(mut targets: Vec<CleanTarget>) -> Vec<CleanTarget> {
+    targets.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut kept: Vec<CleanTarget> = Vec::with_capacity(targets.len());
+    for target in targets {
+        let covered = kept
+            .iter()
+            .any(|parent: &CleanTarget| target.path.starts_with(&parent.path));
+        if !covered {
+            kept.push(target);
+        }
+    }
+    kept
+}
 
-    let mut cleaned = Vec::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
 
-    for dir in [&build_dir, &dist_dir] {
-        if std::path::Path::new(dir).exists() {
-            PathResolver::remove_dir_all(dir)?;
-            cleaned.push(PathResolver::get_filename(dir)?);
+    fn target(path: &str, kind: CleanKind) -> CleanTarget {
+        CleanTarget {
+            path: PathBuf::from(path),
+            kind,
+            what: String::new(),
         }
     }
 
-    if cleaned.is_empty() {
-        println!("✨ Project is already clean!");
-    } else {
-        println!("🧹 Cleaned: {}", cleaned.join(", "));
+    #[test]
+    fn test_prune_drops_nested_and_duplicate_targets() {
+        let pruned = prune(vec![
+            target("/p/target/debug", CleanKind::Artifact),
+            target("/p/target", CleanKind::Artifact),
+            target("/p/pkg", CleanKind::Artifact),
+            target("/p/target", CleanKind::Artifact),
+            target("/p/targets", CleanKind::Artifact),
+        ]);
+        let paths: Vec<&Path> = pruned.iter().map(|t| t.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("/p/pkg"),
+                Path::new("/p/target"),
+                Path::new("/p/targets")
+            ]
+        );
     }
 
-    Ok(())
+    #[test]
+    fn test_project_targets_follow_options() {
+        let dir = tempdir().unwrap();
+        let project = dir.path();
+        fs::write(
+            project.join("package.json"),
+            r#"{"devDependencies":{"assemblyscript":"0.27"}}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(project.join("assembly")).unwrap();
+        fs::write(
+            project.join("assembly/index.ts"),
+            "export function f(): void {}",
+        )
+        .unwrap();
+        fs::write(project.join("asconfig.json"), "{}").unwrap();
+        fs::create_dir_all(project.join("build")).unwrap();
+        fs::create_dir_all(project.join("node_modules")).unwrap();
+        fs::create_dir_all(project.join("out")).unwrap();
+        fs::write(
+            project.join("wasmrun.toml"),
+            "[defaults]\ncommand = \"compile\"\noutput = \"out\"\n",
+        )
+        .unwrap();
+        let project_path = project.to_string_lossy();
+        let names = |options: CleanOptions| -> Vec<String> {
+            let mut names: Vec<String> = project_targets(&project_path, &options)
+                .unwrap()
+                .iter()
+                .map(|t| t.path.file_name().unwrap().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let all = CleanOptions {
+            all: true,
+            ..CleanOptions::default()
+        };
+        assert_eq!(names(all), ["build"]);
+        let deep = CleanOptions {
+            deep: true,
+            include_deps: true,
+            ..CleanOptions::default()
+        };
+        assert_eq!(names(deep), ["build", "node_modules", "out"]);
+    }
 }
@@ -1,37 +1,104 @@
 //! Compilation command implementation
 
-use crate::compiler::builder::{BuildConfig, BuilderFactory, OptimizationLevel, TargetType};
-use crate::compiler::{detect_operating_system, detect_project_language, get_missing_tools};
+use crate::commands::policy::{enforce_license_policy, enforce_project_policy};
+use crate::compiler::artifacts::{
+    record_store_key, write_build_manifest_with_licenses, BUILD_MANIFEST_FILE,
+};
+use crate::compiler::builder::{
+    BuildConfig, BuildResult, BuildStep, BuilderFactory, OptimizationLevel, OutputNaming,
+    TargetType, WasmBuilder,
+};
+use crate::compiler::metadata::{self, BuildMetadata};
+use crate::compiler::pipeline::{Pipeline, StepCache};
+use crate::compiler::sbom::{build_tools, write_sbom, SbomFormat, Tool};
+use crate::compiler::store::{builder_env, ArtifactStore, BuildInputs};
+use crate::compiler::{
+    choose_project_language, configured_language, detect_language_candidates,
+    detect_operating_system, detect_project_language, get_missing_tools, ProjectLanguage,
+};
+use crate::config::project::{ProjectConfig, PROJECT_CONFIG_FILE};
 use crate::error::{Result, WasmrunError};
 use crate::plugin::manager::PluginManager;
-use crate::utils::PathResolver;
-use std::path::Path;
+use crate::plugin::PluginInfo;
+use crate::ui::progress;
+use crate::utils::{
+    require_clean, CommandExecutor, ContainerBuild, ContainerRuntime, PathResolver,
+    DEFAULT_BUILD_TIMEOUT,
+};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+/// What a build records about itself besides the build manifest
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Provenance {
+    pub sbom: Option<SbomFormat>,
+    /// Embed a `wasmrun.meta` section in the module; also set by
+    /// `embed_meta` under `[build]` in wasmrun.toml
+    pub embed_meta: bool,
+    /// Refuse to build from a git tree with uncommitted changes, so the
+    /// recorded commit is what was actually built
+    pub require_clean: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_compile_command(
     project_path: String,
     output_dir: String,
     optimization_level: OptimizationLevel,
+    language: Option<&str>,
+    naming: OutputNaming,
+    provenance: Provenance,
+    pipeline: Option<&str>,
+    use_store: bool,
     verbose: bool,
+    dry_run: bool,
 ) -> Result<()> {
-    run_compile(project_path, output_dir, optimization_level, verbose)
+    run_compile(
+        project_path,
+        output_dir,
+        optimization_level,
+        language,
+        naming,
+        provenance,
+        pipeline,
+        use_store,
+        verbose,
+        dry_run,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_compile(
     project_path: String,
     output_dir: String,
     optimization_level: OptimizationLevel,
+    language: Option<&str>,
+    naming: OutputNaming,
+    provenance: Provenance,
+    pipeline: Option<&str>,
+    use_store: bool,
     verbose: bool,
+    dry_run: bool,
 ) -> Result<()> {
     PathResolver::validate_directory_exists(&project_path)?;
-    PathResolver::ensure_output_directory(&output_dir)?;
+    if provenance.require_clean {
+        require_clean(Path::new(&project_path), "build")?;
+    }
+    let naming = output_naming(&project_path, naming)?;
+    let pipeline = Pipeline::select(Path::new(&project_path), pipeline)?;
+    if !dry_run {
+        PathResolver::ensure_output_directory(&output_dir)?;
+    }
 
     if verbose {
         println!("🔍 Detecting project type...");
     }
+    let language_flag = language;
+    let language = choose_project_language(&project_path, language);
 
     // Try plugin-based compilation first
     if let Ok(plugin_manager) = PluginManager::new() {
-        if let Some(plugin) = plugin_manager.find_plugin_for_project(&project_path) {
+        if let Some(plugin) = plugin_manager.select_plugin(&project_path, language.as_deref()) {
             if verbose {
                 println!(
                     "🔌 Using plugin: {} v{}",
@@ -41,36 +108,75 @@ pub fn run_compile(
                 println!("📝 Description: {}", plugin.info().description);
             }
 
-            // Check plugin dependencies
             let builder = plugin.get_builder();
-            let missing_deps = builder.check_dependencies();
-            if !missing_deps.is_empty() {
-                return Err(WasmrunError::from(format!(
-                    "Missing dependencies for {}: {}",
-                    plugin.info().name,
-                    missing_deps.join(", ")
-                )));
-            }
-
             let config = BuildConfig {
-                project_path,
+                project_path: project_path.clone(),
                 output_dir,
                 verbose,
                 optimization_level,
                 watch: false,
                 target_type: TargetType::Standard,
+                naming,
             };
+            if dry_run {
+                let source = language_source(&project_path, language_flag, builder.as_ref());
+                return print_build_plan(
+                    &source,
+                    Some(plugin.info()),
+                    builder.as_ref(),
+                    &config,
+                    pipeline.as_ref(),
+                );
+            }
 
-            let result = if verbose {
-                builder
-                    .build_verbose(&config)
-                    .map_err(WasmrunError::Compilation)?
-            } else {
-                builder.build(&config).map_err(WasmrunError::Compilation)?
+            let language = builder.language_name();
+            let plugin_tool = Tool {
+                name: plugin.info().name.clone(),
+                version: plugin.info().version.clone(),
             };
+            let stored = StoreEntry::new(
+                use_store,
+                &config,
+                language,
+                Some(&plugin_tool),
+                pipeline.as_ref(),
+            );
+            if let Some(result) = stored.as_ref().and_then(|entry| entry.restore(&config)) {
+                return finish_build(
+                    &config,
+                    &result,
+                    language,
+                    Some(plugin_tool),
+                    &provenance,
+                    stored.as_ref(),
+                );
+            }
+
+            // Check plugin dependencies
+            let missing_deps = builder.check_dependencies();
+            if !missing_deps.is_empty() {
+                return Err(WasmrunError::from(format!(
+                    "Missing dependencies for {}: {}",
+                    plugin.info().name,
+                    missing_deps.join(", ")
+                )));
+            }
 
-            print_compilation_success(&result.wasm_path, &result.js_path, &result.additional_files);
-            return Ok(());
+            let mut result = run_build(builder.as_ref(), &config)?;
+            if let Some(pipeline) = &pipeline {
+                pipeline.run(&mut result, StepCache::open().as_ref())?;
+            }
+            if let Some(entry) = &stored {
+                entry.keep(&config, &result, language);
+            }
+            return finish_build(
+                &config,
+                &result,
+                language,
+                Some(plugin_tool),
+                &provenance,
+                stored.as_ref(),
+            );
         }
     }
 
@@ -79,8 +185,45 @@ pub fn run_compile(
         println!("🔄 No plugin found, using legacy detection...");
     }
 
-    let language = detect_project_language(&project_path);
+    let language = match language.as_deref() {
+        Some(name) => ProjectLanguage::from_name(name)
+            .ok_or_else(|| WasmrunError::from(format!("Unsupported language: {name}")))?,
+        None => detect_project_language(&project_path),
+    };
     let os = detect_operating_system();
+    let builder = BuilderFactory::create_builder(&language);
+
+    let config = BuildConfig {
+        project_path: project_path.clone(),
+        output_dir,
+        verbose,
+        optimization_level,
+        watch: false,
+        target_type: TargetType::Standard,
+        naming,
+    };
+    if dry_run {
+        let source = language_source(&project_path, language_flag, builder.as_ref());
+        return print_build_plan(&source, None, builder.as_ref(), &config, pipeline.as_ref());
+    }
+
+    let stored = StoreEntry::new(
+        use_store,
+        &config,
+        builder.language_name(),
+        None,
+        pipeline.as_ref(),
+    );
+    if let Some(result) = stored.as_ref().and_then(|entry| entry.restore(&config)) {
+        return finish_build(
+            &config,
+            &result,
+            builder.language_name(),
+            None,
+            &provenance,
+            stored.as_ref(),
+        );
+    }
 
     let missing_tools = get_missing_tools(&language, &os);
     if !missing_tools.is_empty() {
@@ -92,29 +235,467 @@ pub fn run_compile(
         println!("💻 OS: {os:?}");
     }
 
-    let builder = BuilderFactory::create_builder(&language);
+    let mut result = run_build(builder.as_ref(), &config)?;
+    if let Some(pipeline) = &pipeline {
+        pipeline.run(&mut result, StepCache::open().as_ref())?;
+    }
+    if let Some(entry) = &stored {
+        entry.keep(&config, &result, builder.language_name());
+    }
+    finish_build(
+        &config,
+        &result,
+        builder.language_name(),
+        None,
+        &provenance,
+        stored.as_ref(),
+    )
+}
 
-    let config = BuildConfig {
-        project_path,
-        output_dir,
-        verbose,
-        optimization_level,
-        watch: false,
-        target_type: TargetType::Standard,
+/// A build's entry in the artifact store, which `--no-store` turns off
+struct StoreEntry {
+    store: ArtifactStore,
+    key: String,
+}
+
+impl StoreEntry {
+    fn new(
+        enabled: bool,
+        config: &BuildConfig,
+        language: &str,
+        plugin: Option<&Tool>,
+        pipeline: Option<&Pipeline>,
+    ) -> Option<Self> {
+        if !enabled {
+            return None;
+        }
+        let store = ArtifactStore::open().ok()?;
+        let inputs = BuildInputs {
+            language,
+            profile: config.optimization_level.to_string(),
+            naming: &config.naming,
+            tools: build_tools(Path::new(&config.project_path), language, plugin.cloned()),
+            pipeline: pipeline.map(|pipeline| pipeline.steps.as_slice()),
+            env: builder_env(),
+        };
+        match ArtifactStore::input_key(config, &inputs) {
+            Ok(key) => Some(Self { store, key }),
+            Err(e) => {
+                eprintln!("⚠️  Not using the artifact store: {e}");
+                None
+            }
+        }
+    }
+
+    /// The stored outputs, copied into the output directory
+    fn restore(&self, config: &BuildConfig) -> Option<BuildResult> {
+        let result = self
+            .store
+            .restore(&self.key, Path::new(&config.output_dir))?;
+        println!(
+            "⚡ Inputs unchanged, restored build {} from the artifact store",
+            &self.key[..12]
+        );
+        Some(result)
+    }
+
+    /// Keep a fresh build's outputs. A build that can't be stored is
+    /// reported but doesn't fail.
+    fn keep(&self, config: &BuildConfig, result: &BuildResult, language: &str) {
+        if let Err(e) = self.store.put(&self.key, config, result, language) {
+            eprintln!("⚠️  Failed to keep the build in the artifact store: {e}");
+        }
+    }
+}
+
+/// The builder `run_compile` would use for the project: its plugin, else
+/// the legacy builder once its tools are known to be installed
+pub(super) fn select_builder(
+    project_path: &str,
+    language: Option<&str>,
+) -> Result<Box<dyn WasmBuilder>> {
+    let language = choose_project_language(project_path, language);
+    if let Ok(plugin_manager) = PluginManager::new() {
+        if let Some(plugin) = plugin_manager.select_plugin(project_path, language.as_deref()) {
+            let builder = plugin.get_builder();
+            let missing_deps = builder.check_dependencies();
+            if !missing_deps.is_empty() {
+                return Err(WasmrunError::from(format!(
+                    "Missing dependencies for {}: {}",
+                    plugin.info().name,
+                    missing_deps.join(", ")
+                )));
+            }
+            return Ok(builder);
+        }
+    }
+
+    let language = match language.as_deref() {
+        Some(name) => ProjectLanguage::from_name(name)
+            .ok_or_else(|| WasmrunError::from(format!("Unsupported language: {name}")))?,
+        None => detect_project_language(project_path),
     };
+    let missing_tools = get_missing_tools(&language, &detect_operating_system());
+    if !missing_tools.is_empty() {
+        return Err(WasmrunError::missing_tools(missing_tools));
+    }
+    Ok(BuilderFactory::create_builder(&language))
+}
 
-    let result = if verbose {
-        builder
-            .build_verbose(&config)
-            .map_err(WasmrunError::Compilation)?
+/// Run the build behind a spinner; verbose builds stream their tool output
+/// instead
+pub(super) fn run_build(builder: &dyn WasmBuilder, config: &BuildConfig) -> Result<BuildResult> {
+    if config.verbose {
+        return builder
+            .build_verbose(config)
+            .map_err(WasmrunError::Compilation);
+    }
+    let task = progress::spinner(format!("Compiling {} project", builder.language_name()));
+    match builder.build(config) {
+        Ok(result) => {
+            task.finish(format!("Compiled {}", result.wasm_path));
+            Ok(result)
+        }
+        Err(e) => {
+            task.fail();
+            Err(WasmrunError::Compilation(e))
+        }
+    }
+}
+
+/// Environment variable that sets the build timeout in seconds
+const BUILD_TIMEOUT_ENV: &str = "WASMRUN_BUILD_TIMEOUT";
+
+/// Set how long each toolchain command may run: `--build-timeout`, else
+/// `$WASMRUN_BUILD_TIMEOUT`, else `[build] timeout` in the project's
+/// wasmrun.toml, else the default. 0 means no limit.
+pub fn apply_build_timeout(project_path: &str, flag: Option<u64>) -> Result<()> {
+    let secs = match flag {
+        Some(secs) => Some(secs),
+        None => match std::env::var(BUILD_TIMEOUT_ENV) {
+            Ok(value) => Some(value.trim().parse().map_err(|_| {
+                WasmrunError::from(format!(
+                    "{BUILD_TIMEOUT_ENV} must be a number of seconds, not {value:?}"
+                ))
+            })?),
+            // A broken wasmrun.toml is reported when the build reads it
+            Err(_) => ProjectConfig::load(Path::new(project_path))
+                .ok()
+                .flatten()
+                .and_then(|config| config.build.timeout),
+        },
+    };
+    CommandExecutor::set_build_timeout(match secs {
+        // Under --ci every build has a deadline
+        Some(0) if crate::ci::is_enabled() => Some(DEFAULT_BUILD_TIMEOUT),
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(DEFAULT_BUILD_TIMEOUT),
+    });
+    Ok(())
+}
+
+/// With `--in-container`, run the build's toolchain commands in the
+/// language's image, mounting the project and `output_dirs`. The runtime is
+/// the one the flag names, else `runtime` under `[container]` in
+/// wasmrun.toml, else docker or podman, whichever is installed.
+pub fn apply_container(
+    project_path: &str,
+    flag: Option<Option<&str>>,
+    language: Option<&str>,
+    output_dirs: &[PathBuf],
+) -> Result<()> {
+    let Some(runtime) = flag else {
+        CommandExecutor::set_container(None);
+        return Ok(());
+    };
+    if !cfg!(unix) {
+        return Err(WasmrunError::from(
+            "--in-container is only supported on Linux and macOS".to_string(),
+        ));
+    }
+    let project = Path::new(project_path);
+    if !project.is_dir() {
+        // A .wasm or .js file has nothing to build
+        return Ok(());
+    }
+
+    let config = ProjectConfig::load(project)?
+        .map(|config| config.container)
+        .unwrap_or_default();
+    let runtime = match runtime.or(config.runtime.as_deref()) {
+        Some(name) => ContainerRuntime::from_name(name).ok_or_else(|| {
+            WasmrunError::from(format!(
+                "Unknown container runtime {name:?}; use docker or podman"
+            ))
+        })?,
+        None => ContainerRuntime::detect().ok_or_else(|| {
+            WasmrunError::from("--in-container needs docker or podman on the PATH".to_string())
+        })?,
+    };
+    let language = match language {
+        Some(name) => ProjectLanguage::from_name(name)
+            .ok_or_else(|| WasmrunError::from(format!("Unsupported language: {name}")))?,
+        None => detect_project_language(project_path),
+    };
+
+    let mut mounts: Vec<PathBuf> = Vec::new();
+    for dir in std::iter::once(project).chain(output_dirs.iter().map(PathBuf::as_path)) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| WasmrunError::add_context(format!("Creating {}", dir.display()), e))?;
+        let dir = std::path::absolute(dir)
+            .map_err(|e| WasmrunError::add_context(format!("Resolving {}", dir.display()), e))?;
+        // Mounting a directory twice fails, and once is enough for its
+        // subdirectories
+        if !mounts.iter().any(|mount| dir.starts_with(mount)) {
+            mounts.push(dir);
+        }
+    }
+
+    let build = ContainerBuild::for_language(language, runtime, &config, project, mounts)
+        .map_err(WasmrunError::from)?;
+    println!("🐳 Building in {} with {}", build.image, runtime.program());
+    CommandExecutor::set_container(Some(build));
+    Ok(())
+}
+
+/// Hand builds to the agent `[remote_build]` in the project's wasmrun.toml
+/// names, unless `local` (`--local-build`) keeps them on this machine
+pub fn apply_remote_build(project_path: &str, local: bool) -> Result<()> {
+    let project = Path::new(project_path);
+    let config = match local || !project.is_dir() {
+        true => None,
+        false => ProjectConfig::load(project)?
+            .map(|config| config.remote_build)
+            .filter(|remote| remote.url.is_some()),
+    };
+
+    #[cfg(feature = "deploy")]
+    {
+        use crate::remote::build::RemoteBuild;
+        let remote = match config {
+            Some(config) => RemoteBuild::from_config(&config)?,
+            None => None,
+        };
+        if let Some(remote) = &remote {
+            println!("📡 Building on {}", remote.url);
+        }
+        RemoteBuild::set_active(remote);
+        Ok(())
+    }
+    #[cfg(not(feature = "deploy"))]
+    match config {
+        Some(_) => Err(WasmrunError::feature_disabled("[remote_build]", "deploy")),
+        None => Ok(()),
+    }
+}
+
+/// `--out-name`, `--name-pattern` and `--target-dirs` over the `[output]`
+/// section of the project's wasmrun.toml. A name or pattern from the
+/// command line replaces both of the file's.
+fn output_naming(project_path: &str, flags: OutputNaming) -> Result<OutputNaming> {
+    let configured = ProjectConfig::load(Path::new(project_path))?
+        .map(|config| config.output)
+        .unwrap_or_default();
+    let (name, pattern) = if flags.name.is_some() || flags.pattern.is_some() {
+        (flags.name, flags.pattern)
     } else {
-        builder.build(&config).map_err(WasmrunError::Compilation)?
+        (configured.name, configured.pattern)
+    };
+    let naming = OutputNaming {
+        name,
+        pattern,
+        target_dirs: flags.target_dirs || configured.target_dirs,
     };
+    naming.validate().map_err(WasmrunError::from)?;
+    Ok(naming)
+}
 
+/// Where the language being built came from, for the build plan
+fn language_source(project_path: &str, flag: Option<&str>, builder: &dyn WasmBuilder) -> String {
+    if let Some(language) = flag {
+        return format!("{language} (--language)");
+    }
+    if let Some(language) = configured_language(project_path) {
+        return format!("{language} ({PROJECT_CONFIG_FILE})");
+    }
+    match detect_language_candidates(project_path).first() {
+        Some(top) => format!("{} (detected: {})", top.language, top.reasons.join(", ")),
+        None => format!("{} (claimed by plugin)", builder.language_name()),
+    }
+}
+
+/// Print what building with `builder` would do, for `--dry-run`
+fn print_build_plan(
+    language: &str,
+    plugin: Option<&PluginInfo>,
+    builder: &dyn WasmBuilder,
+    config: &BuildConfig,
+    pipeline: Option<&Pipeline>,
+) -> Result<()> {
+    let plan = builder.plan(config).map_err(WasmrunError::Compilation)?;
+
+    println!(
+        "📋 Build plan for {} (dry run, nothing is executed)",
+        config.project_path
+    );
+    println!("   Language:     {language}");
+    match plugin {
+        Some(info) => println!(
+            "   Plugin:       {} v{} ({:?})",
+            info.name, info.version, info.plugin_type
+        ),
+        None => println!(
+            "   Plugin:       none; legacy {} builder",
+            builder.language_name()
+        ),
+    }
+    println!("   Output dir:   {}", config.output_dir);
+    println!("   Optimization: {}", config.optimization_level);
+    if !config.naming.is_default() {
+        let naming = &config.naming;
+        let mut parts = Vec::new();
+        if let Some(name) = &naming.name {
+            parts.push(format!("name {name}"));
+        } else if let Some(pattern) = &naming.pattern {
+            parts.push(format!("pattern {pattern}"));
+        }
+        if naming.target_dirs {
+            parts.push("a directory per target".to_string());
+        }
+        println!("   Naming:       {}", parts.join(", "));
+    }
+    let missing = builder.check_dependencies();
+    if !missing.is_empty() {
+        println!("   ⚠️  Missing:   {}", missing.join(", "));
+        crate::ci::warn("compile", format!("missing {}", missing.join(", ")));
+    }
+
+    println!();
+    println!("   Steps:");
+    for (i, step) in plan.steps.iter().enumerate() {
+        let n = i + 1;
+        match step {
+            BuildStep::Run { command, when } => {
+                match when {
+                    Some(when) => println!("   {n}. ({when}) $ {command}"),
+                    None => println!("   {n}. $ {command}"),
+                }
+                println!("        in {}", command.working_dir);
+                for (name, value) in &command.env {
+                    println!("        {name}={value}");
+                }
+            }
+            BuildStep::Copy { from, to } => println!("   {n}. copy {from} -> {to}"),
+            BuildStep::Note(note) => println!("   {n}. {note}"),
+        }
+    }
+    let mut steps = plan.steps.len();
+    if let Some(pipeline) = pipeline {
+        for step in &pipeline.steps {
+            steps += 1;
+            println!("   {steps}. ({} pipeline) {step}", pipeline.name);
+        }
+    }
+    if ProjectConfig::load(Path::new(&config.project_path))
+        .ok()
+        .flatten()
+        .is_some_and(|project| !project.policy.is_empty())
+    {
+        println!(
+            "   {}. check the module against [policy] in {PROJECT_CONFIG_FILE}",
+            steps + 1
+        );
+    }
+
+    println!();
+    if plan.outputs.is_empty() {
+        println!("   Outputs: decided by the build, listed in");
+    } else {
+        println!("   Outputs:");
+        for output in &plan.outputs {
+            println!("      {output}");
+        }
+    }
+    println!(
+        "      {}",
+        Path::new(&config.output_dir)
+            .join(BUILD_MANIFEST_FILE)
+            .display()
+    );
+    Ok(())
+}
+
+/// Report a successful build, embed its metadata when asked to, check it
+/// against the project's import/export and license policies, and record its
+/// outputs in the build manifest and, when asked for, an SBOM
+fn finish_build(
+    config: &BuildConfig,
+    result: &BuildResult,
+    language: &str,
+    plugin: Option<Tool>,
+    provenance: &Provenance,
+    stored: Option<&StoreEntry>,
+) -> Result<()> {
     print_compilation_success(&result.wasm_path, &result.js_path, &result.additional_files);
+    let embed_meta = provenance.embed_meta
+        || ProjectConfig::load(Path::new(&config.project_path))?
+            .is_some_and(|project| project.build.embed_meta);
+    if embed_meta {
+        embed_build_metadata(config, result, language, plugin.clone())?;
+    }
+    check_build_policy(&config.project_path, &result.wasm_path)?;
+    let licenses = enforce_license_policy(Path::new(&config.project_path))?;
+    if let Some(manifest) = write_build_manifest_with_licenses(config, result, language, licenses) {
+        if let Some(entry) = stored {
+            if let Err(e) = record_store_key(Path::new(&config.output_dir), &entry.key) {
+                eprintln!("⚠️  Failed to record the store key: {e}");
+            }
+        }
+        println!("🧾 Manifest: {}", manifest.display());
+    }
+    if let Some(format) = provenance.sbom {
+        if let Some(sbom) = write_sbom(config, result, language, format, plugin) {
+            println!("📦 SBOM: {}", sbom.display());
+        }
+    }
+    Ok(())
+}
+
+/// Write a `wasmrun.meta` section into the built module. Web-app builds
+/// produce a directory and are skipped.
+fn embed_build_metadata(
+    config: &BuildConfig,
+    result: &BuildResult,
+    language: &str,
+    plugin: Option<Tool>,
+) -> Result<()> {
+    let wasm_path = Path::new(&result.wasm_path);
+    if !wasm_path.is_file() {
+        return Ok(());
+    }
+    let module = std::fs::read(wasm_path)
+        .map_err(|e| WasmrunError::add_context(format!("Reading {}", wasm_path.display()), e))?;
+    let meta = BuildMetadata::collect(config, language, plugin);
+    std::fs::write(wasm_path, metadata::embed(&module, &meta))
+        .map_err(|e| WasmrunError::add_context(format!("Writing {}", wasm_path.display()), e))?;
+    println!(
+        "🏷️  Embedded {} ({})",
+        metadata::META_SECTION,
+        meta.revision().unwrap_or_else(|| meta.built_at.clone())
+    );
     Ok(())
 }
 
+/// Fail the build if the produced module breaks the project's `[policy]`.
+/// Web-app builds produce a directory and are skipped.
+fn check_build_policy(project_path: &str, wasm_path: &str) -> Result<()> {
+    if !Path::new(wasm_path).is_file() {
+        return Ok(());
+    }
+    enforce_project_policy(Path::new(project_path), wasm_path)
+}
+
 fn print_compilation_success(
     wasm_path: &str,
     js_path: &Option<String>,
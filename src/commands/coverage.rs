@@ -0,0 +1,209 @@
+//! `wasmrun coverage report`: summarize a file from `wasmrun exec --coverage`
+
+use crate::cli::CoverageSubcommands;
+use crate::error::{Result, WasmrunError};
+use crate::runtime::core::coverage::CoverageData;
+use crate::runtime::core::trap::function_label;
+use crate::utils::dwarf::{code_body_offsets, LineTable};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Uncovered items listed without `--all`
+const LISTED_UNCOVERED: usize = 20;
+
+pub fn run_coverage_command(subcommand: &CoverageSubcommands) -> Result<()> {
+    match subcommand {
+        CoverageSubcommands::Report { file, wasm, all } => {
+            handle_coverage_report(file, wasm.as_deref(), *all)
+        }
+    }
+}
+
+/// Maps function body offsets to source lines
+struct SourceMap {
+    lines: LineTable,
+    /// DWARF address of each defined function's first instruction
+    bodies: Vec<u64>,
+    import_func_count: u32,
+}
+
+impl SourceMap {
+    fn load(wasm_path: &Path, coverage: &CoverageData) -> Option<Self> {
+        let bytes = fs::read(wasm_path).ok()?;
+        let lines = LineTable::from_module(&bytes)?;
+        let bodies = code_body_offsets(&bytes);
+        // Function indices count imports first
+        let first_defined = coverage.functions.first().map_or(0, |f| f.index);
+        Some(Self {
+            lines,
+            bodies,
+            import_func_count: first_defined,
+        })
+    }
+
+    fn locate(&self, func_index: u32, offset: usize) -> Option<String> {
+        let body = *self
+            .bodies
+            .get(func_index.checked_sub(self.import_func_count)? as usize)?;
+        let (file, line) = self.lines.lookup(body + offset as u64)?;
+        Some(format!("{file}:{line}"))
+    }
+
+    fn file_of(&self, func_index: u32, offset: usize) -> Option<String> {
+        self.locate(func_index, offset)
+            .and_then(|loc| loc.rsplit_once(':').map(|(file, _)| file.to_string()))
+    }
+}
+
+fn percent(covered: usize, total: usize) -> String {
+    if total == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}%", covered as f64 * 100.0 / total as f64)
+    }
+}
+
+fn handle_coverage_report(file: &str, wasm: Option<&str>, all: bool) -> Result<()> {
+    let json = fs::read_to_string(file)
+        .map_err(|e| WasmrunError::from(format!("Failed to read coverage file '{file}': {e}")))?;
+    let coverage: CoverageData = serde_json::from_str(&json)
+        .map_err(|e| WasmrunError::from(format!("'{file}' is not a coverage file: {e}")))?;
+
+    let wasm_path = wasm.unwrap_or(&coverage.module);
+    let sources = SourceMap::load(Path::new(wasm_path), &coverage);
+
+    let functions = &coverage.functions;
+    let covered_functions = functions.iter().filter(|f| f.calls > 0).count();
+    let blocks: Vec<_> = functions
+        .iter()
+        .flat_map(|f| f.blocks.iter().flatten().map(move |block| (f, block)))
+        .collect();
+    let has_blocks = functions.iter().any(|f| f.blocks.is_some());
+    let covered_blocks = blocks.iter().filter(|(_, b)| b.count > 0).count();
+
+    println!("📊 Coverage of {}", coverage.module);
+    println!(
+        "   Functions  {covered_functions}/{}  {}",
+        functions.len(),
+        percent(covered_functions, functions.len())
+    );
+    if has_blocks {
+        println!(
+            "   Blocks     {covered_blocks}/{}  {}",
+            blocks.len(),
+            percent(covered_blocks, blocks.len())
+        );
+    }
+
+    if let Some(sources) = &sources {
+        // (functions covered, functions, blocks covered, blocks) per file
+        let mut files: BTreeMap<String, (usize, usize, usize, usize)> = BTreeMap::new();
+        for function in functions {
+            if let Some(path) = sources.file_of(function.index, 0) {
+                let entry = files.entry(path).or_default();
+                entry.1 += 1;
+                if function.calls > 0 {
+                    entry.0 += 1;
+                }
+            }
+        }
+        for (function, block) in &blocks {
+            if let Some(path) = sources.file_of(function.index, block.offset) {
+                let entry = files.entry(path).or_default();
+                entry.3 += 1;
+                if block.count > 0 {
+                    entry.2 += 1;
+                }
+            }
+        }
+        if !files.is_empty() {
+            println!("\n📁 By source file:");
+            for (path, (fc, ft, bc, bt)) in &files {
+                if has_blocks {
+                    println!(
+                        "   {path}  functions {fc}/{ft} {}  blocks {bc}/{bt} {}",
+                        percent(*fc, *ft),
+                        percent(*bc, *bt)
+                    );
+                } else {
+                    println!("   {path}  functions {fc}/{ft} {}", percent(*fc, *ft));
+                }
+            }
+        }
+    } else if wasm.is_some() {
+        println!("\n   No DWARF line info in {wasm_path}");
+    }
+
+    let locate = |func_index: u32, offset: usize| {
+        sources
+            .as_ref()
+            .and_then(|s| s.locate(func_index, offset))
+            .map(|loc| format!("  {loc}"))
+            .unwrap_or_default()
+    };
+    let limit = if all { usize::MAX } else { LISTED_UNCOVERED };
+
+    let uncovered: Vec<_> = functions.iter().filter(|f| f.calls == 0).collect();
+    if !uncovered.is_empty() {
+        println!("\n❌ Uncovered functions:");
+        for function in uncovered.iter().take(limit) {
+            println!(
+                "   {}{}",
+                function_label(function.index, function.name.as_deref()),
+                locate(function.index, 0)
+            );
+        }
+        if uncovered.len() > limit {
+            println!("   ... {} more (use --all)", uncovered.len() - limit);
+        }
+    }
+
+    // Blocks in functions that never ran are implied by the list above
+    let uncovered_blocks: Vec<_> = blocks
+        .iter()
+        .filter(|(f, b)| b.count == 0 && f.calls > 0)
+        .collect();
+    if !uncovered_blocks.is_empty() {
+        println!("\n❌ Uncovered blocks in executed functions:");
+        for (function, block) in uncovered_blocks.iter().take(limit) {
+            println!(
+                "   {} +{:#x} {}{}",
+                function_label(function.index, function.name.as_deref()),
+                block.offset,
+                block.kind,
+                locate(function.index, block.offset)
+            );
+        }
+        if uncovered_blocks.len() > limit {
+            println!("   ... {} more (use --all)", uncovered_blocks.len() - limit);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage_report_reads_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cov.json");
+        fs::write(
+            &path,
+            r#"{"module":"missing.wasm","functions":[
+                {"index":1,"name":"main","calls":1,"blocks":[{"offset":2,"kind":"loop","count":0}]},
+                {"index":2,"calls":0}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(handle_coverage_report(path.to_str().unwrap(), None, false).is_ok());
+
+        fs::write(&path, "{}").unwrap();
+        assert!(handle_coverage_report(path.to_str().unwrap(), None, false).is_err());
+        assert_eq!(percent(1, 3), "33.3%");
+        assert_eq!(percent(0, 0), "-");
+    }
+}
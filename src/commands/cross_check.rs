@@ -0,0 +1,444 @@
+//! `wasmrun exec --cross-check <ENGINE>`: run a module on the interpreter and
+//! on an external engine, and report where the two disagree
+
+use crate::error::{Result, WasmrunError};
+use crate::runtime::core::native_executor::{self, ExecOptions, ExecReport};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+/// External engines that can be compared against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Wasmtime,
+    Wasmer,
+}
+
+impl Engine {
+    /// The engine named by `spec`, which is an engine name or a path to its
+    /// binary
+    fn parse(spec: &str) -> Result<Self> {
+        let name = Path::new(spec)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(spec);
+        match name {
+            "wasmtime" => Ok(Engine::Wasmtime),
+            "wasmer" => Ok(Engine::Wasmer),
+            _ => Err(WasmrunError::from(format!(
+                "Unknown engine '{spec}' for --cross-check (expected wasmtime or wasmer)"
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Engine::Wasmtime => "wasmtime",
+            Engine::Wasmer => "wasmer",
+        }
+    }
+
+    fn command(
+        self,
+        program: &str,
+        wasm_path: &str,
+        call: Option<&str>,
+        args: &[String],
+    ) -> Command {
+        let mut command = Command::new(program);
+        command.arg("run");
+        match self {
+            Engine::Wasmtime => {
+                if let Some(function) = call {
+                    command.args(["--invoke", function]);
+                }
+                command.arg(wasm_path).args(args);
+            }
+            Engine::Wasmer => {
+                command.arg(wasm_path);
+                if let Some(function) = call {
+                    command.args(["--invoke", function]);
+                }
+                command.arg("--").args(args);
+            }
+        }
+        command
+    }
+}
+
+/// What the external engine did
+#[derive(Debug, Clone, Default, Serialize)]
+struct EngineRun {
+    exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    results: Vec<String>,
+    stdout: String,
+    stderr: String,
+    /// The trap or error the engine reported
+    error: Option<String>,
+}
+
+impl EngineRun {
+    /// Split the engine's output into program output, returned values and
+    /// error. `wasmtime --invoke` prints the results after the program's own
+    /// output, one per line.
+    fn from_output(
+        exit_code: Option<i32>,
+        stdout: String,
+        stderr: String,
+        result_count: usize,
+    ) -> Self {
+        let error = engine_error(exit_code, &stderr);
+        let (stdout, results) = if error.is_none() && result_count > 0 {
+            split_results(&stdout, result_count)
+        } else {
+            (stdout, Vec::new())
+        };
+        Self {
+            exit_code,
+            results,
+            stdout,
+            stderr,
+            error,
+        }
+    }
+}
+
+/// One way the interpreter and the engine disagree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct Divergence {
+    what: String,
+    interpreter: String,
+    engine: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: interpreter {}, engine {}",
+            self.what, self.interpreter, self.engine
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct CrossCheckReport<'a> {
+    engine: &'static str,
+    interpreter: &'a ExecReport,
+    external: &'a EngineRun,
+    divergences: &'a [Divergence],
+}
+
+pub fn run_cross_check(
+    engine_spec: &str,
+    wasm_path: &str,
+    call: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+    json: bool,
+) -> Result<()> {
+    let engine = Engine::parse(engine_spec)?;
+    if !json {
+        println!("🔀 Cross-checking {wasm_path} against {}", engine.name());
+    }
+
+    let report =
+        native_executor::execute_wasm_file_report(wasm_path, call.clone(), args.clone(), options)?;
+
+    let output = engine
+        .command(engine_spec, wasm_path, call.as_deref(), &args)
+        .output()
+        .map_err(|e| {
+            WasmrunError::from(format!(
+                "Failed to run {engine_spec}: {e}. Install {} or pass the path to its binary",
+                engine.name()
+            ))
+        })?;
+    let external = EngineRun::from_output(
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+        report.results.len(),
+    );
+
+    let divergences = compare(&report, &external);
+
+    if json {
+        let json = serde_json::to_string_pretty(&CrossCheckReport {
+            engine: engine.name(),
+            interpreter: &report,
+            external: &external,
+            divergences: &divergences,
+        })
+        .map_err(|e| WasmrunError::from(format!("Failed to serialize cross-check report: {e}")))?;
+        println!("{json}");
+    } else if divergences.is_empty() {
+        println!(
+            "✅ Interpreter and {} agree: {}",
+            engine.name(),
+            describe_outcome(&report)
+        );
+    } else {
+        println!("❌ Interpreter and {} diverge:", engine.name());
+        for divergence in &divergences {
+            println!("   {divergence}");
+        }
+    }
+
+    if divergences.is_empty() {
+        Ok(())
+    } else {
+        Err(WasmrunError::from(format!(
+            "Interpreter and {} diverged in {} way(s)",
+            engine.name(),
+            divergences.len()
+        )))
+    }
+}
+
+fn describe_outcome(report: &ExecReport) -> String {
+    match (&report.error, report.exit_code) {
+        (Some(error), _) => match trap_kind(error) {
+            Some(kind) => format!("both trap with {kind}"),
+            None => "both fail".to_string(),
+        },
+        (None, code) => format!(
+            "exit code {}, {} byte(s) of stdout",
+            code.unwrap_or(0),
+            report.stdout.len()
+        ),
+    }
+}
+
+/// Every difference in outcome, returned values and output
+fn compare(interpreter: &ExecReport, engine: &EngineRun) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    let mut diverge = |what: &str, interpreter: String, engine: String| {
+        divergences.push(Divergence {
+            what: what.to_string(),
+            interpreter,
+            engine,
+        })
+    };
+
+    match (&interpreter.error, &engine.error) {
+        (Some(ours), Some(theirs)) => {
+            let (our_kind, their_kind) = (trap_kind(ours), trap_kind(theirs));
+            if our_kind.is_some() && their_kind.is_some() && our_kind != their_kind {
+                diverge("trap", quote(ours), quote(theirs));
+            }
+        }
+        (Some(ours), None) => diverge(
+            "trap",
+            quote(ours),
+            format!("none (exit code {})", exit_code(engine.exit_code)),
+        ),
+        (None, Some(theirs)) => diverge(
+            "trap",
+            format!("none (exit code {})", exit_code(interpreter.exit_code)),
+            quote(theirs),
+        ),
+        (None, None) => {
+            if interpreter.exit_code.unwrap_or(0) != engine.exit_code.unwrap_or(0) {
+                diverge(
+                    "exit code",
+                    exit_code(interpreter.exit_code),
+                    exit_code(engine.exit_code),
+                );
+            }
+            if interpreter.results != engine.results {
+                diverge(
+                    "results",
+                    format!("[{}]", interpreter.results.join(", ")),
+                    format!("[{}]", engine.results.join(", ")),
+                );
+            }
+            // The engine's own error messages go to stderr, so it's only
+            // comparable when neither side failed
+            if let Some(divergence) =
+                first_difference("stderr", &interpreter.stderr, &engine.stderr)
+            {
+                divergences.push(divergence);
+            }
+        }
+    }
+    if let Some(divergence) = first_difference("stdout", &interpreter.stdout, &engine.stdout) {
+        divergences.push(divergence);
+    }
+    divergences
+}
+
+/// The first line where two outputs differ
+fn first_difference(stream: &str, ours: &str, theirs: &str) -> Option<Divergence> {
+    if ours == theirs {
+        return None;
+    }
+    let mut our_lines = ours.split('\n');
+    let mut their_lines = theirs.split('\n');
+    let mut line = 1;
+    loop {
+        match (our_lines.next(), their_lines.next()) {
+            (Some(a), Some(b)) if a == b => line += 1,
+            (a, b) => {
+                let show = |l: Option<&str>| l.map_or_else(|| "<end>".to_string(), quote);
+                return Some(Divergence {
+                    what: format!("{stream} line {line}"),
+                    interpreter: show(a),
+                    engine: show(b),
+                });
+            }
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("{text:?}")
+}
+
+fn exit_code(code: Option<i32>) -> String {
+    code.map_or_else(|| "none".to_string(), |c| c.to_string())
+}
+
+/// The trap or error in an engine's stderr. An engine that exits non-zero
+/// without one has called `proc_exit`.
+fn engine_error(exit_code: Option<i32>, stderr: &str) -> Option<String> {
+    for marker in ["wasm trap: ", "RuntimeError: "] {
+        if let Some(line) = stderr.lines().find(|line| line.contains(marker)) {
+            let (_, message) = line.split_once(marker)?;
+            return Some(message.trim().to_string());
+        }
+    }
+    let failed = stderr
+        .lines()
+        .any(|line| line.starts_with("Error: ") || line.starts_with("error: "));
+    if exit_code != Some(0) && failed {
+        // The innermost cause is the last line of the error chain
+        return stderr
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .map(|line| {
+                line.trim_start_matches(|c: char| c.is_ascii_digit() || c == ':')
+                    .trim()
+                    .to_string()
+            });
+    }
+    None
+}
+
+/// Split the last `count` lines of `stdout` off as returned values
+fn split_results(stdout: &str, count: usize) -> (String, Vec<String>) {
+    let lines: Vec<&str> = stdout.lines().collect();
+    let split = lines.len().saturating_sub(count);
+    let results = lines[split..]
+        .iter()
+        .map(|l| l.trim().to_string())
+        .collect();
+    let mut rest = lines[..split].join("\n");
+    if split > 0 {
+        rest.push('\n');
+    }
+    (rest, results)
+}
+
+/// The spec name of a trap, from the interpreter's or an engine's message
+fn trap_kind(message: &str) -> Option<&'static str> {
+    let message = message.to_lowercase();
+    const KINDS: &[(&str, &str)] = &[
+        ("division by zero", "integer divide by zero"),
+        ("divide by zero", "integer divide by zero"),
+        (
+            "invalid conversion to integer",
+            "invalid conversion to integer",
+        ),
+        ("integer overflow", "integer overflow"),
+        ("unreachable", "unreachable"),
+        ("memory access out of bounds", "out of bounds memory access"),
+        ("out of bounds memory access", "out of bounds memory access"),
+        ("signature mismatch", "indirect call type mismatch"),
+        ("indirect call type mismatch", "indirect call type mismatch"),
+        ("null function reference", "uninitialized element"),
+        ("uninitialized element", "uninitialized element"),
+        ("table access out of bounds", "undefined element"),
+        ("undefined element", "undefined element"),
+        ("call stack exhausted", "call stack exhausted"),
+        ("stack overflow", "call stack exhausted"),
+    ];
+    KINDS
+        .iter()
+        .find(|(needle, _)| message.contains(needle))
+        .map(|(_, kind)| *kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_parse() {
+        assert_eq!(Engine::parse("wasmtime").unwrap(), Engine::Wasmtime);
+        assert_eq!(
+            Engine::parse("/opt/wasmer/bin/wasmer").unwrap(),
+            Engine::Wasmer
+        );
+        assert!(Engine::parse("v8").is_err());
+    }
+
+    #[test]
+    fn test_engine_error_and_results() {
+        let stderr = "Error: failed to run main module `t.wasm`\n\nCaused by:\n    0: failed to invoke command default\n    1: error while executing at wasm backtrace:\n           0:   0x2a - <unknown>!divide\n    2: wasm trap: integer divide by zero\n";
+        let run = EngineRun::from_output(Some(134), String::new(), stderr.to_string(), 0);
+        assert_eq!(run.error.as_deref(), Some("integer divide by zero"));
+
+        // proc_exit(3) is not an error
+        assert_eq!(engine_error(Some(3), ""), None);
+
+        let run = EngineRun::from_output(Some(0), "hello\n42\n".to_string(), String::new(), 1);
+        assert_eq!(run.stdout, "hello\n");
+        assert_eq!(run.results, vec!["42"]);
+    }
+
+    #[test]
+    fn test_compare_reports_divergences() {
+        let ours = ExecReport {
+            exit_code: Some(0),
+            results: vec!["7".to_string()],
+            stdout: "a\nb\n".to_string(),
+            ..ExecReport::default()
+        };
+        let mut theirs = EngineRun {
+            exit_code: Some(0),
+            results: vec!["7".to_string()],
+            stdout: "a\nb\n".to_string(),
+            ..EngineRun::default()
+        };
+        assert!(compare(&ours, &theirs).is_empty());
+
+        theirs.stdout = "a\nc\n".to_string();
+        theirs.results = vec!["8".to_string()];
+        let divergences = compare(&ours, &theirs);
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(divergences[0].what, "results");
+        assert_eq!(
+            divergences[1].to_string(),
+            "stdout line 2: interpreter \"b\", engine \"c\""
+        );
+
+        // The same trap worded differently agrees
+        let trapped = ExecReport {
+            error: Some("Error executing WASM function (index 1): Integer division by zero".into()),
+            ..ExecReport::default()
+        };
+        theirs = EngineRun {
+            exit_code: Some(134),
+            error: Some("integer divide by zero".to_string()),
+            ..EngineRun::default()
+        };
+        assert!(compare(&trapped, &theirs).is_empty());
+
+        theirs.error = Some("wasm `unreachable` instruction executed".to_string());
+        assert_eq!(compare(&trapped, &theirs)[0].what, "trap");
+    }
+}
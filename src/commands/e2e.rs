@@ -0,0 +1,319 @@
+//! `e2e` command: load the served app headlessly and pass or fail on what it
+//! logs to the console, so an example's "All tests passed" becomes a CI check.
+
+use super::snapshot::{browser_error, find_browser, page_url, DevServer};
+use crate::error::{Result, ServerError, WasmrunError};
+use crate::ui::print_status;
+use crate::utils::{ConsoleMessage, HeadlessBrowser};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const CONSOLE_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone)]
+pub struct E2eOptions {
+    pub path: Option<String>,
+    pub positional_path: Option<String>,
+    /// Test an already running server instead of starting one
+    pub url: Option<String>,
+    pub url_path: String,
+    /// Text that must show up in the console; every marker has to appear
+    pub expect_console: Vec<String>,
+    /// `console.error` text containing any of these does not fail the run
+    pub ignore_errors: Vec<String>,
+    pub timeout: u64,
+    pub junit: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// Outcome of watching the page's console
+#[derive(Debug, Default)]
+struct E2eReport {
+    url: String,
+    /// Each expected marker and when it was first seen
+    expectations: Vec<(String, Option<Duration>)>,
+    errors: Vec<ConsoleMessage>,
+    console: Vec<ConsoleMessage>,
+    elapsed: Duration,
+}
+
+impl E2eReport {
+    fn failures(&self) -> usize {
+        let missing = self
+            .expectations
+            .iter()
+            .filter(|(_, seen)| seen.is_none())
+            .count();
+        missing + usize::from(!self.errors.is_empty())
+    }
+
+    fn total(&self) -> usize {
+        self.expectations.len() + 1
+    }
+}
+
+/// Handle e2e command
+pub fn handle_e2e_command(options: &E2eOptions) -> Result<()> {
+    let browser_path = find_browser()?;
+    let (base_url, _server) = DevServer::start_or_attach(
+        &options.url,
+        &options.path,
+        &options.positional_path,
+        options.port,
+    )?;
+    let url = page_url(&base_url, &options.url_path);
+
+    print_status(&format!("Running {url} in a headless browser..."));
+    let mut browser = HeadlessBrowser::launch(&browser_path, 1280, 720).map_err(browser_error)?;
+    browser.capture_console().map_err(browser_error)?;
+    browser.navigate(&url).map_err(browser_error)?;
+
+    let report = watch_console(&mut browser, &url, options)?;
+    drop(browser);
+
+    if let Some(junit) = &options.junit {
+        if let Some(parent) = Path::new(junit)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(junit, junit_xml(&report))
+            .map_err(|e| WasmrunError::add_context(format!("Writing {junit}"), e))?;
+    }
+
+    print_summary(&report, options);
+
+    match report.failures() {
+        0 => Ok(()),
+        failed => Err(WasmrunError::Server(ServerError::E2eFailed {
+            url,
+            failed,
+            total: report.total(),
+        })),
+    }
+}
+
+/// Echo console output until every marker has been seen, an error is
+/// logged, or the timeout runs out
+fn watch_console(
+    browser: &mut HeadlessBrowser,
+    url: &str,
+    options: &E2eOptions,
+) -> Result<E2eReport> {
+    let mut report = E2eReport {
+        url: url.to_string(),
+        expectations: options
+            .expect_console
+            .iter()
+            .map(|marker| (marker.clone(), None))
+            .collect(),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(options.timeout);
+    while Instant::now() < deadline {
+        for message in browser
+            .console_messages(CONSOLE_POLL)
+            .map_err(browser_error)?
+        {
+            println!("  \x1b[0;37m[{}]\x1b[0m {}", message.level, message.text);
+            for (marker, seen) in &mut report.expectations {
+                if seen.is_none() && message.text.contains(marker.as_str()) {
+                    *seen = Some(start.elapsed());
+                }
+            }
+            if message.is_error()
+                && !options
+                    .ignore_errors
+                    .iter()
+                    .any(|pattern| message.text.contains(pattern.as_str()))
+            {
+                report.errors.push(message.clone());
+            }
+            report.console.push(message);
+        }
+
+        let all_seen = report.expectations.iter().all(|(_, seen)| seen.is_some());
+        if all_seen || !report.errors.is_empty() {
+            break;
+        }
+    }
+    report.elapsed = start.elapsed();
+    Ok(report)
+}
+
+fn print_summary(report: &E2eReport, options: &E2eOptions) {
+    let passed = report.failures() == 0;
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    if passed {
+        println!("  ✅ \x1b[1;36mEnd-to-end checks passed\x1b[0m\n");
+    } else {
+        println!("  ❌ \x1b[1;31mEnd-to-end checks failed\x1b[0m\n");
+    }
+    println!(
+        "  🌐 \x1b[1;34mPage:\x1b[0m \x1b[0;37m{}\x1b[0m",
+        report.url
+    );
+    for (marker, seen) in &report.expectations {
+        match seen {
+            Some(at) => println!(
+                "  ✅ Console showed \x1b[1;33m{marker:?}\x1b[0m after {:.1}s",
+                at.as_secs_f64()
+            ),
+            None if !report.errors.is_empty() => println!(
+                "  ❌ Console hadn't shown \x1b[1;33m{marker:?}\x1b[0m before the first error"
+            ),
+            None => println!(
+                "  ❌ Console never showed \x1b[1;33m{marker:?}\x1b[0m within {}s",
+                options.timeout
+            ),
+        }
+    }
+    if report.errors.is_empty() {
+        println!("  ✅ No console errors");
+    } else {
+        println!("  ❌ {} console error(s):", report.errors.len());
+        for error in &report.errors {
+            println!("     \x1b[1;31m{}\x1b[0m", error.text);
+        }
+    }
+    if let Some(junit) = &options.junit {
+        println!("\n  📄 \x1b[1;34mJUnit report:\x1b[0m {junit}");
+    }
+    println!("\x1b[1;34m╰\x1b[0m\n");
+}
+
+/// JUnit XML with one test case per expected marker plus one for console
+/// errors; the full console log goes in `<system-out>`.
+fn junit_xml(report: &E2eReport) -> String {
+    let mut cases = String::new();
+    for (marker, seen) in &report.expectations {
+        let name = xml_escape(&format!("console shows {marker:?}"));
+        match seen {
+            Some(at) => cases.push_str(&format!(
+                "    <testcase classname=\"wasmrun.e2e\" name=\"{name}\" time=\"{:.3}\"/>\n",
+                at.as_secs_f64()
+            )),
+            None => cases.push_str(&format!(
+                "    <testcase classname=\"wasmrun.e2e\" name=\"{name}\" time=\"{:.3}\">\n      <failure message=\"not seen within {:.1}s\"/>\n    </testcase>\n",
+                report.elapsed.as_secs_f64(),
+                report.elapsed.as_secs_f64()
+            )),
+        }
+    }
+    if report.errors.is_empty() {
+        cases.push_str(&format!(
+            "    <testcase classname=\"wasmrun.e2e\" name=\"no console errors\" time=\"{:.3}\"/>\n",
+            report.elapsed.as_secs_f64()
+        ));
+    } else {
+        let details: Vec<String> = report.errors.iter().map(|e| xml_escape(&e.text)).collect();
+        cases.push_str(&format!(
+            "    <testcase classname=\"wasmrun.e2e\" name=\"no console errors\" time=\"{:.3}\">\n      <failure message=\"{} console error(s)\">{}</failure>\n    </testcase>\n",
+            report.elapsed.as_secs_f64(),
+            report.errors.len(),
+            details.join("\n")
+        ));
+    }
+
+    let console: Vec<String> = report
+        .console
+        .iter()
+        .map(|m| xml_escape(&format!("[{}] {}", m.level, m.text)))
+        .collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"wasmrun e2e\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">\n  \
+         <testsuite name=\"{url}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" time=\"{time:.3}\">\n\
+         {cases}    <system-out>{console}</system-out>\n  \
+         </testsuite>\n\
+         </testsuites>\n",
+        tests = report.total(),
+        failures = report.failures(),
+        time = report.elapsed.as_secs_f64(),
+        url = xml_escape(&report.url),
+        console = console.join("\n"),
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline are not allowed in XML 1.0
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(level: &str, text: &str) -> ConsoleMessage {
+        ConsoleMessage {
+            level: level.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_junit_reports_missing_markers_and_errors() {
+        let report = E2eReport {
+            url: "http://127.0.0.1:8420/".to_string(),
+            expectations: vec![
+                ("booted".to_string(), Some(Duration::from_millis(1500))),
+                ("All tests passed".to_string(), None),
+            ],
+            errors: vec![message(
+                "error",
+                "panicked at src/lib.rs: <oops> & \"more\"",
+            )],
+            console: vec![
+                message("log", "booted"),
+                message("error", "panicked at src/lib.rs: <oops> & \"more\""),
+            ],
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(report.failures(), 2);
+
+        let xml = junit_xml(&report);
+        assert!(
+            xml.contains(r#"<testsuites name="wasmrun e2e" tests="3" failures="2" time="2.000">"#)
+        );
+        assert!(xml.contains(r#"name="console shows &quot;booted&quot;" time="1.500"/>"#));
+        assert!(xml.contains(r#"<failure message="not seen within 2.0s"/>"#));
+        assert!(xml.contains(
+            r#"<failure message="1 console error(s)">panicked at src/lib.rs: &lt;oops&gt; &amp; &quot;more&quot;</failure>"#
+        ));
+        assert!(xml.contains("<system-out>[log] booted\n[error] panicked"));
+    }
+
+    #[test]
+    fn test_passing_report_has_no_failures() {
+        let report = E2eReport {
+            url: "http://localhost/".to_string(),
+            expectations: vec![("ok".to_string(), Some(Duration::from_millis(10)))],
+            console: vec![message("log", "ok")],
+            ..Default::default()
+        };
+        assert_eq!(report.failures(), 0);
+        assert!(!junit_xml(&report).contains("<failure"));
+    }
+
+    #[test]
+    fn test_xml_escape_drops_control_characters() {
+        assert_eq!(xml_escape("a\u{1b}[31mb\tc\n"), "a[31mb\tc\n");
+    }
+}
@@ -0,0 +1,345 @@
+//! `wasmrun examples`: copy-pasteable commands for each supported language,
+//! and `wasmrun examples run` to fetch and serve a curated demo
+
+use super::templates::checkout;
+use crate::error::{Result, WasmrunError};
+use crate::plugin::manager::PluginManager;
+use std::path::PathBuf;
+
+struct LanguageExamples {
+    name: &'static str,
+    /// Names accepted on the command line
+    keys: &'static [&'static str],
+    commands: &'static [(&'static str, &'static str)],
+}
+
+const LANGUAGES: &[LanguageExamples] = &[
+    LanguageExamples {
+        name: "Rust",
+        keys: &["rust", "rs"],
+        commands: &[
+            ("wasmrun plugin install wasmrust", "install the Rust plugin"),
+            (
+                "wasmrun ./my-rust-app --watch",
+                "build and serve with live reload",
+            ),
+            (
+                "wasmrun compile ./my-rust-app --optimization release -o dist",
+                "release build into dist/",
+            ),
+            (
+                "wasmrun exec dist/my_rust_app.wasm",
+                "run a WASI build in the terminal",
+            ),
+        ],
+    },
+    LanguageExamples {
+        name: "Go",
+        keys: &["go", "tinygo"],
+        commands: &[
+            ("wasmrun plugin install wasmgo", "install the Go plugin"),
+            ("wasmrun ./my-go-app --watch", "build with TinyGo and serve"),
+            ("wasmrun compile ./my-go-app -o dist", "build into dist/"),
+        ],
+    },
+    LanguageExamples {
+        name: "C/C++",
+        keys: &["c", "cpp", "c++"],
+        commands: &[
+            ("wasmrun ./my-c-app", "build with Emscripten and serve"),
+            (
+                "wasmrun compile ./my-c-app --optimization size -o dist",
+                "size-optimised build",
+            ),
+        ],
+    },
+    LanguageExamples {
+        name: "AssemblyScript",
+        keys: &["asc", "assemblyscript"],
+        commands: &[
+            (
+                "wasmrun plugin install wasmasc",
+                "install the AssemblyScript plugin",
+            ),
+            (
+                "wasmrun ./my-asc-app --watch",
+                "build and serve with live reload",
+            ),
+        ],
+    },
+    LanguageExamples {
+        name: "Python",
+        keys: &["python", "py"],
+        commands: &[
+            ("wasmrun plugin install waspy", "install the Python plugin"),
+            ("wasmrun ./my-python-app", "compile with waspy and serve"),
+            (
+                "wasmrun os ./my-python-app --language python",
+                "run in the browser OS mode",
+            ),
+        ],
+    },
+    LanguageExamples {
+        name: "Node.js",
+        keys: &["nodejs", "node", "js"],
+        commands: &[(
+            "wasmrun os ./my-node-app --language nodejs --watch",
+            "run in the browser OS mode",
+        )],
+    },
+    LanguageExamples {
+        name: "Prebuilt .wasm",
+        keys: &["wasm"],
+        commands: &[
+            ("wasmrun ./module.wasm", "serve a module in the browser"),
+            ("wasmrun exec ./module.wasm", "run it in the terminal"),
+            ("wasmrun exec ./math.wasm --call add 2 3", "call an export"),
+            (
+                "wasmrun inspect ./module.wasm",
+                "list exports, imports and sections",
+            ),
+        ],
+    },
+];
+
+/// A demo from the `examples/` directory of wasmrun's repository
+#[derive(Debug)]
+struct Demo {
+    name: &'static str,
+    /// Directory under `examples/`
+    dir: &'static str,
+    language: &'static str,
+    description: &'static str,
+    /// Plugin to install when nothing installed can build the demo
+    plugin: Option<&'static str>,
+}
+
+const DEMOS: &[Demo] = &[
+    Demo {
+        name: "leptos-todo",
+        dir: "web-leptos",
+        language: "Rust",
+        description: "Leptos app with a counter, todo list and routing",
+        plugin: Some("wasmrust"),
+    },
+    Demo {
+        name: "rust-hello",
+        dir: "rust-hello",
+        language: "Rust",
+        description: "wasm-bindgen exports called from the console",
+        plugin: Some("wasmrust"),
+    },
+    Demo {
+        name: "go-hello",
+        dir: "go-hello",
+        language: "Go",
+        description: "syscall/js functions built with TinyGo",
+        plugin: Some("wasmgo"),
+    },
+    Demo {
+        name: "c-hello",
+        dir: "c-hello",
+        language: "C",
+        description: "Math functions built with Emscripten",
+        plugin: None,
+    },
+    Demo {
+        name: "asc-hello",
+        dir: "asc-hello",
+        language: "AssemblyScript",
+        description: "Typed exports built with asc",
+        plugin: Some("wasmasc"),
+    },
+    Demo {
+        name: "asc-web",
+        dir: "web-asc",
+        language: "AssemblyScript",
+        description: "Interactive page calling AssemblyScript",
+        plugin: Some("wasmasc"),
+    },
+    Demo {
+        name: "python-hello",
+        dir: "python-hello",
+        language: "Python",
+        description: "Python compiled to WebAssembly with waspy",
+        plugin: Some("waspy"),
+    },
+];
+
+/// Demos are fetched at the tag of the running release, so they match
+/// what this wasmrun can build
+fn demo_revision() -> String {
+    format!("v{}", env!("CARGO_PKG_VERSION"))
+}
+
+fn find_demo(name: &str) -> Result<&'static Demo> {
+    DEMOS.iter().find(|demo| demo.name == name).ok_or_else(|| {
+        let known: Vec<&str> = DEMOS.iter().map(|demo| demo.name).collect();
+        WasmrunError::from(format!(
+            "No demo named '{name}'. Try one of: {}",
+            known.join(", ")
+        ))
+    })
+}
+
+/// Fetch a demo and serve it
+pub fn handle_examples_run_command(
+    name: &str,
+    port: u16,
+    watch: bool,
+    refresh: bool,
+) -> Result<()> {
+    let demo = find_demo(name)?;
+    let path = fetch_demo(demo, refresh)?;
+    let project_path = path.to_string_lossy().to_string();
+    println!("📂 {} ({}): {project_path}", demo.name, demo.description);
+
+    ensure_plugin(demo, &project_path)?;
+    super::apply_build_timeout(&project_path, None)?;
+    super::handle_run_command(
+        &None,
+        &Some(project_path),
+        port,
+        &None,
+        watch,
+        false,
+        false,
+        &None,
+        &None,
+        false,
+        false,
+        &None,
+        &[],
+        &None,
+    )
+}
+
+fn fetch_demo(demo: &Demo, refresh: bool) -> Result<PathBuf> {
+    let repo = checkout(
+        env!("CARGO_PKG_REPOSITORY"),
+        Some(&demo_revision()),
+        refresh,
+    )?;
+    let path = repo.join("examples").join(demo.dir);
+    if !path.is_dir() {
+        return Err(WasmrunError::path(format!(
+            "{} has no examples/{} at {}",
+            env!("CARGO_PKG_REPOSITORY"),
+            demo.dir,
+            demo_revision()
+        )));
+    }
+    Ok(path)
+}
+
+/// Install the demo's plugin if no installed plugin can build it
+fn ensure_plugin(demo: &Demo, project_path: &str) -> Result<()> {
+    let Some(plugin) = demo.plugin else {
+        return Ok(());
+    };
+    let manager = PluginManager::new()?;
+    if manager.find_plugin_for_project(project_path).is_some() {
+        return Ok(());
+    }
+
+    #[cfg(feature = "external-plugins")]
+    {
+        let mut manager = manager;
+        println!("🔌 {} needs the {plugin} plugin; installing it", demo.name);
+        manager.install_plugin(plugin)
+    }
+    #[cfg(not(feature = "external-plugins"))]
+    Err(WasmrunError::from(format!(
+        "{} needs the {plugin} plugin, and this wasmrun was built without external plugins",
+        demo.name
+    )))
+}
+
+/// Handle examples command
+pub fn handle_examples_command(language: Option<&str>) -> Result<()> {
+    print!("{}", render_examples(language)?);
+    Ok(())
+}
+
+fn render_examples(language: Option<&str>) -> Result<String> {
+    let selected: Vec<&LanguageExamples> = match language {
+        Some(language) => {
+            let key = language.to_lowercase();
+            let found = LANGUAGES
+                .iter()
+                .find(|l| l.keys.contains(&key.as_str()))
+                .ok_or_else(|| {
+                    let known: Vec<&str> = LANGUAGES.iter().map(|l| l.keys[0]).collect();
+                    WasmrunError::from(format!(
+                        "No examples for '{language}'. Try one of: {}",
+                        known.join(", ")
+                    ))
+                })?;
+            vec![found]
+        }
+        None => LANGUAGES.iter().collect(),
+    };
+
+    let width = selected
+        .iter()
+        .flat_map(|l| l.commands.iter().map(|(command, _)| command.len()))
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for language in selected {
+        out.push_str(&format!("\n\x1b[1;36m{}\x1b[0m\n", language.name));
+        for (command, what) in language.commands {
+            out.push_str(&format!("  {command:<width$}  \x1b[0;90m# {what}\x1b[0m\n"));
+        }
+    }
+    if language.is_none() {
+        let width = DEMOS.iter().map(|demo| demo.name.len()).max().unwrap_or(0);
+        out.push_str("\n\x1b[1;36mDemos\x1b[0m \x1b[0;90m(wasmrun examples run <NAME>)\x1b[0m\n");
+        for demo in DEMOS {
+            out.push_str(&format!(
+                "  {:<width$}  \x1b[0;90m# {}: {}\x1b[0m\n",
+                demo.name, demo.language, demo.description
+            ));
+        }
+    }
+    out.push('\n');
+    Ok(crate::ui::progress::paint(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples_filter_by_language() {
+        let all = render_examples(None).unwrap();
+        assert!(all.contains("Rust") && all.contains("Python"));
+
+        let go = render_examples(Some("TinyGo")).unwrap();
+        assert!(go.contains("wasmrun plugin install wasmgo"));
+        assert!(!go.contains("wasmrust"));
+
+        let err = render_examples(Some("cobol")).unwrap_err().to_string();
+        assert!(err.contains("rust, go"));
+
+        assert!(all.contains("leptos-todo"));
+        assert!(!go.contains("Demos"));
+    }
+
+    #[test]
+    fn test_demos_are_in_the_repository() {
+        let examples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+        for demo in DEMOS {
+            assert!(
+                examples.join(demo.dir).is_dir(),
+                "examples/{} is missing",
+                demo.dir
+            );
+        }
+        assert_eq!(find_demo("leptos-todo").unwrap().dir, "web-leptos");
+        assert!(find_demo("cobol-hello")
+            .unwrap_err()
+            .to_string()
+            .contains("go-hello"));
+    }
+}
@@ -1,22 +1,136 @@
 //! Exec command implementation for running WASM files with arguments
 
+use super::cross_check::run_cross_check;
+use super::registry::{fetch_remote_module, verify_module_sha256};
+use crate::cli::InterpreterArgs;
 use crate::error::{Result, WasmrunError};
-use crate::runtime::core::native_executor;
-use std::path::Path;
+use crate::remote::is_remote_url;
+use crate::runtime::core::native_executor::{self, ExecOptions};
+use crate::runtime::core::stubs::StubMode;
+use crate::runtime::core::watchpoint::{WatchAction, Watchpoint};
+use std::path::{Path, PathBuf};
+
+/// Build interpreter options from the `exec` flags
+pub fn exec_options(flags: &InterpreterArgs) -> Result<ExecOptions> {
+    let watchpoints = flags
+        .watch_mem
+        .iter()
+        .map(|spec| Watchpoint::parse(spec))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(WasmrunError::from)?;
+    Ok(ExecOptions {
+        watchpoints,
+        watch_action: WatchAction::parse(&flags.watch_action).map_err(WasmrunError::from)?,
+        max_call_depth: flags.max_call_depth.map(|n| n as usize),
+        max_operand_stack: flags.max_stack_values.map(|n| n as usize),
+        host_functions: flags.host_functions.as_ref().map(PathBuf::from),
+        stub_imports: flags
+            .stub_imports
+            .as_deref()
+            .map(StubMode::parse)
+            .transpose()
+            .map_err(WasmrunError::from)?,
+        trace_path: flags.trace_instructions.as_ref().map(PathBuf::from),
+        trace_filter: flags.trace_filter.clone(),
+        trace_limit: flags.trace_limit.map(|n| n as usize),
+        coverage_path: flags.coverage.as_ref().map(PathBuf::from),
+        coverage_blocks: flags.coverage_blocks,
+        no_optimize: flags.no_optimize,
+    })
+}
 
 pub fn handle_exec_command(
     wasm_file: &Option<String>,
+    sha256: &Option<String>,
     call: &Option<String>,
     args: Vec<String>,
+    options: &ExecOptions,
+    cross_check: Option<&str>,
+    json: bool,
 ) -> Result<()> {
     let wasm_path = wasm_file
         .as_ref()
         .ok_or_else(|| WasmrunError::from("WASM file path is required".to_string()))?;
 
-    execute_wasm_with_args(wasm_path, call.clone(), args)
+    // Registry references aren't taken here, only URLs
+    let fetched = if is_remote_url(wasm_path) {
+        fetch_remote_module(wasm_path, sha256.as_deref())?
+    } else {
+        None
+    };
+    if let Some(cached_path) = fetched {
+        return execute_wasm_with_args(
+            &cached_path.to_string_lossy(),
+            call.clone(),
+            args,
+            options,
+            cross_check,
+            json,
+        );
+    }
+
+    if let Some(expected) = sha256 {
+        if Path::new(wasm_path).exists() {
+            verify_module_sha256(Path::new(wasm_path), expected)?;
+        }
+    }
+
+    execute_wasm_with_args(wasm_path, call.clone(), args, options, cross_check, json)
+}
+
+/// Run a wasm-bindgen module through its JS glue (`exec --bindgen`)
+pub fn handle_bindgen_command(
+    wasm_file: &Option<String>,
+    glue: Option<&str>,
+    call: Option<&str>,
+    args: &[String],
+) -> Result<()> {
+    let wasm_path = wasm_file
+        .as_deref()
+        .map(Path::new)
+        .ok_or_else(|| WasmrunError::from("WASM file path is required".to_string()))?;
+    if is_remote_url(&wasm_path.to_string_lossy()) {
+        return Err(WasmrunError::from(
+            "--bindgen needs a local module with its JS glue beside it".to_string(),
+        ));
+    }
+    run_bindgen(wasm_path, glue.map(Path::new), call, args)
+}
+
+#[cfg(feature = "js-engine")]
+fn run_bindgen(
+    wasm_path: &Path,
+    glue: Option<&Path>,
+    call: Option<&str>,
+    args: &[String],
+) -> Result<()> {
+    use crate::runtime::bindgen;
+
+    let glue = glue.map_or_else(|| bindgen::default_glue_path(wasm_path), Path::to_path_buf);
+    if !glue.exists() {
+        return Err(WasmrunError::from(format!(
+            "JS glue not found at {}; pass it with --glue",
+            glue.display()
+        )));
+    }
+    bindgen::run_bindgen(wasm_path, &glue, call, args)
+}
+
+#[cfg(not(feature = "js-engine"))]
+fn run_bindgen(_: &Path, _: Option<&Path>, _: Option<&str>, _: &[String]) -> Result<()> {
+    Err(WasmrunError::from(
+        "--bindgen needs the embedded JS engine; rebuild with --features js-engine".to_string(),
+    ))
 }
 
-fn execute_wasm_with_args(wasm_path: &str, call: Option<String>, args: Vec<String>) -> Result<()> {
+fn execute_wasm_with_args(
+    wasm_path: &str,
+    call: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+    cross_check: Option<&str>,
+    json: bool,
+) -> Result<()> {
     if !Path::new(wasm_path).exists() {
         return Err(WasmrunError::from(format!(
             "WASM file not found: {wasm_path}"
@@ -29,6 +143,14 @@ fn execute_wasm_with_args(wasm_path: &str, call: Option<String>, args: Vec<Strin
         )));
     }
 
+    if let Some(engine) = cross_check {
+        return run_cross_check(engine, wasm_path, call, args, options, json);
+    }
+
+    if json {
+        return print_json_report(wasm_path, call, args, options);
+    }
+
     println!("🎯 Running WASM file: {wasm_path}");
     if let Some(ref func) = call {
         println!("📍 Calling: {func}");
@@ -37,8 +159,15 @@ fn execute_wasm_with_args(wasm_path: &str, call: Option<String>, args: Vec<Strin
         println!("📝 Arguments: {}", args.join(" "));
     }
     println!("🏃 Executing natively (interpreter mode)");
+    for watchpoint in &options.watchpoints {
+        println!("👁️  Watching writes to {watchpoint}");
+    }
+    if let Some(path) = &options.trace_path {
+        println!("🧵 Tracing instructions to {}", path.display());
+    }
 
-    let exit_code = native_executor::execute_wasm_file_with_args(wasm_path, call, args)?;
+    let exit_code =
+        native_executor::execute_wasm_file_with_options(wasm_path, call, args, options)?;
     if exit_code != 0 {
         println!("✅ Execution completed (exit code: {exit_code})");
     } else {
@@ -48,6 +177,24 @@ fn execute_wasm_with_args(wasm_path: &str, call: Option<String>, args: Vec<Strin
     Ok(())
 }
 
+/// Run the module and print one JSON object with its exit code, captured
+/// output, and any trap with its backtrace
+fn print_json_report(
+    wasm_path: &str,
+    call: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+) -> Result<()> {
+    let report = native_executor::execute_wasm_file_report(wasm_path, call, args, options)?;
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| WasmrunError::from(format!("Failed to serialize exec report: {e}")))?;
+    println!("{json}");
+    match report.error {
+        Some(error) => Err(WasmrunError::from(error)),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,7 +202,15 @@ mod tests {
     /// Test: Missing WASM file path parameter
     #[test]
     fn test_handle_exec_missing_wasm_path() {
-        let result = handle_exec_command(&None, &None, Vec::new());
+        let result = handle_exec_command(
+            &None,
+            &None,
+            &None,
+            Vec::new(),
+            &ExecOptions::default(),
+            None,
+            false,
+        );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("required"));
     }
@@ -63,7 +218,15 @@ mod tests {
     /// Test: Non-existent WASM file
     #[test]
     fn test_handle_exec_nonexistent_file() {
-        let result = handle_exec_command(&Some("nonexistent.wasm".to_string()), &None, Vec::new());
+        let result = handle_exec_command(
+            &Some("nonexistent.wasm".to_string()),
+            &None,
+            &None,
+            Vec::new(),
+            &ExecOptions::default(),
+            None,
+            false,
+        );
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("not found"));
@@ -72,7 +235,15 @@ mod tests {
     /// Test: Invalid file extension (not .wasm)
     #[test]
     fn test_handle_exec_invalid_extension() {
-        let result = handle_exec_command(&Some("test_file.txt".to_string()), &None, Vec::new());
+        let result = handle_exec_command(
+            &Some("test_file.txt".to_string()),
+            &None,
+            &None,
+            Vec::new(),
+            &ExecOptions::default(),
+            None,
+            false,
+        );
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         // Error could be either about extension or file not found
@@ -80,6 +251,29 @@ mod tests {
         assert!(err.contains(".wasm") || err.contains("not found"));
     }
 
+    /// Test: --sha256 mismatch on a local file is rejected before execution
+    #[test]
+    fn test_handle_exec_sha256_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm_path = dir.path().join("empty.wasm");
+        std::fs::write(&wasm_path, b"\0asm\x01\0\0\0").unwrap();
+
+        let wrong = "0".repeat(64);
+        let result = handle_exec_command(
+            &Some(wasm_path.to_string_lossy().to_string()),
+            &Some(wrong),
+            &None,
+            Vec::new(),
+            &ExecOptions::default(),
+            None,
+            false,
+        );
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Checksum mismatch"));
+    }
+
     /// Test: Valid WASM file path with Go example (if available)
     #[test]
     fn test_handle_exec_go_example() {
@@ -89,7 +283,15 @@ mod tests {
             return;
         }
 
-        let result = handle_exec_command(&Some(wasm_path.to_string()), &None, Vec::new());
+        let result = handle_exec_command(
+            &Some(wasm_path.to_string()),
+            &None,
+            &None,
+            Vec::new(),
+            &ExecOptions::default(),
+            None,
+            false,
+        );
 
         match result {
             Ok(_) => println!("✓ Successfully executed Go example WASM"),
@@ -109,8 +311,12 @@ mod tests {
         // Try calling a function that likely doesn't exist (for error testing)
         let result = handle_exec_command(
             &Some(wasm_path.to_string()),
+            &None,
             &Some("nonexistent_func".to_string()),
             Vec::new(),
+            &ExecOptions::default(),
+            None,
+            false,
         );
 
         assert!(result.is_err());
@@ -128,7 +334,15 @@ mod tests {
         }
 
         let args = vec!["arg1".to_string(), "arg2".to_string()];
-        let result = handle_exec_command(&Some(wasm_path.to_string()), &None, args);
+        let result = handle_exec_command(
+            &Some(wasm_path.to_string()),
+            &None,
+            &None,
+            args,
+            &ExecOptions::default(),
+            None,
+            false,
+        );
 
         match result {
             Ok(_) => println!("✓ Successfully executed with arguments"),
@@ -146,8 +360,15 @@ mod tests {
         }
 
         let args = vec!["test_arg".to_string()];
-        let result =
-            handle_exec_command(&Some(wasm_path.to_string()), &Some("run".to_string()), args);
+        let result = handle_exec_command(
+            &Some(wasm_path.to_string()),
+            &None,
+            &Some("run".to_string()),
+            args,
+            &ExecOptions::default(),
+            None,
+            false,
+        );
 
         match result {
             Ok(_) => println!("✓ Successfully executed with function and arguments"),
@@ -0,0 +1,334 @@
+//! A bare `wasmrun` with no arguments. On a terminal, the first launch in a
+//! project asks what to do with it (mode, port, watch) instead of guessing,
+//! and offers to save the answers as `[defaults]` in `wasmrun.toml`. Once
+//! saved, later bare runs use them without asking.
+
+use crate::compiler::detect_language_candidates;
+use crate::config::project::{DefaultsConfig, ProjectConfig, PROJECT_CONFIG_FILE};
+use crate::error::{Result, WasmrunError};
+use crate::t;
+use crate::ui::progress::{self, Display};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+const DEFAULT_PORT: u16 = 8420;
+const DEFAULT_OUTPUT: &str = "dist";
+
+#[derive(Debug, PartialEq)]
+pub enum FirstRun {
+    /// Parse these arguments as if they had been typed
+    Args(Vec<String>),
+    /// Carry on as `wasmrun run .`, the behaviour without a prompt
+    Default,
+    /// Nothing to run here; guidance has been printed
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Run,
+    Compile,
+    Os,
+}
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Run => "run",
+            Mode::Compile => "compile",
+            Mode::Os => "os",
+        }
+    }
+}
+
+/// Decide what a bare `wasmrun` in `dir` should do
+pub fn handle_first_run(dir: &Path) -> Result<FirstRun> {
+    let config = ProjectConfig::load(dir)?;
+    if let Some(args) = config
+        .as_ref()
+        .map(|config| saved_args(&config.defaults))
+        .transpose()?
+        .flatten()
+    {
+        return Ok(FirstRun::Args(args));
+    }
+    if !io::stdin().is_terminal() || progress::display() != Display::Animated {
+        return Ok(FirstRun::Default);
+    }
+    prompt(dir, &mut io::stdin().lock(), &mut io::stdout())
+}
+
+/// The arguments for the command saved under `[defaults]`, if any
+fn saved_args(defaults: &DefaultsConfig) -> Result<Option<Vec<String>>> {
+    let mode = match defaults.command.as_deref() {
+        None => return Ok(None),
+        Some("run") => Mode::Run,
+        Some("compile") => Mode::Compile,
+        Some("os") => Mode::Os,
+        Some(other) => {
+            return Err(WasmrunError::from(format!(
+                "Unknown defaults.command '{other}' in {PROJECT_CONFIG_FILE}; expected run, compile or os"
+            )))
+        }
+    };
+    let mut args: Vec<String> = vec!["wasmrun".into(), mode.name().into(), ".".into()];
+    if mode == Mode::Compile {
+        if let Some(output) = &defaults.output {
+            args.extend(["-o".into(), output.clone()]);
+        }
+    } else {
+        if let Some(port) = defaults.port {
+            args.extend(["--port".into(), port.to_string()]);
+        }
+        if defaults.watch {
+            args.push("--watch".into());
+        }
+    }
+    Ok(Some(args))
+}
+
+fn prompt(dir: &Path, input: &mut impl BufRead, out: &mut impl Write) -> Result<FirstRun> {
+    let dir_path = dir.to_string_lossy();
+    let found: Vec<String> = detect_language_candidates(&dir_path)
+        .into_iter()
+        .filter(|candidate| candidate.score > 0)
+        .map(|candidate| format!("{} ({})", candidate.language, candidate.reasons.join(", ")))
+        .collect();
+    let node = dir.join("package.json").is_file();
+
+    writeln!(out, "\n{}", t!("first_run.welcome"))?;
+    if found.is_empty() && !node {
+        writeln!(
+            out,
+            "{}",
+            t!("first_run.nothing_found", path = dir.display())
+        )?;
+        writeln!(out, "{}", t!("first_run.try_examples"))?;
+        return Ok(FirstRun::Done);
+    }
+    writeln!(out, "{}", t!("first_run.found", path = dir.display()))?;
+    for language in &found {
+        writeln!(out, "   • {language}")?;
+    }
+    if node {
+        writeln!(out, "   • Node.js (package.json)")?;
+    }
+
+    let default_mode = if found.is_empty() && cfg!(feature = "os-mode") {
+        Mode::Os
+    } else {
+        Mode::Run
+    };
+    writeln!(out, "\n{}", t!("first_run.choose_mode"))?;
+    writeln!(out, "  1) run      {}", t!("first_run.mode_run"))?;
+    writeln!(out, "  2) compile  {}", t!("first_run.mode_compile"))?;
+    if cfg!(feature = "os-mode") {
+        writeln!(out, "  3) os       {}", t!("first_run.mode_os"))?;
+    }
+    let default_choice = match default_mode {
+        Mode::Os => "3",
+        _ => "1",
+    };
+    let mode = loop {
+        match ask(input, out, &t!("first_run.choice"), default_choice)?.as_str() {
+            "1" | "run" => break Mode::Run,
+            "2" | "compile" => break Mode::Compile,
+            "3" | "os" if cfg!(feature = "os-mode") => break Mode::Os,
+            _ => writeln!(out, "{}", t!("first_run.invalid_choice"))?,
+        }
+    };
+
+    let mut defaults = DefaultsConfig {
+        command: Some(mode.name().to_string()),
+        ..DefaultsConfig::default()
+    };
+    if mode == Mode::Compile {
+        let output = ask(input, out, &t!("first_run.output"), DEFAULT_OUTPUT)?;
+        defaults.output = Some(output);
+    } else {
+        let port = loop {
+            let answer = ask(input, out, &t!("first_run.port"), &DEFAULT_PORT.to_string())?;
+            match answer.parse::<u16>() {
+                Ok(port) if port > 0 => break port,
+                _ => writeln!(out, "{}", t!("first_run.invalid_port", port = answer))?,
+            }
+        };
+        defaults.port = Some(port);
+        defaults.watch = yes(&ask(input, out, &t!("first_run.watch"), "y")?);
+    }
+
+    let args = saved_args(&defaults)?.unwrap_or_default();
+    let command = args.join(" ");
+    if yes(&ask(
+        input,
+        out,
+        &t!("first_run.save", file = PROJECT_CONFIG_FILE),
+        "y",
+    )?) {
+        if save_defaults(dir, &defaults)? {
+            writeln!(out, "{}", t!("first_run.saved", file = PROJECT_CONFIG_FILE))?;
+        } else {
+            writeln!(
+                out,
+                "{}",
+                t!("first_run.already_has_defaults", file = PROJECT_CONFIG_FILE)
+            )?;
+        }
+    }
+    writeln!(out, "{}\n", t!("first_run.equivalent", command = command))?;
+    Ok(FirstRun::Args(args))
+}
+
+/// Print `question [default]: ` and read an answer; an empty line or the
+/// end of input takes the default
+fn ask(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    question: &str,
+    default: &str,
+) -> io::Result<String> {
+    write!(out, "{question} [{default}]: ")?;
+    out.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        writeln!(out)?;
+    }
+    let answer = line.trim();
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer.to_string()
+    })
+}
+
+fn yes(answer: &str) -> bool {
+    matches!(answer.to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Append `[defaults]` to `wasmrun.toml`, keeping whatever is already there.
+/// Returns false without writing if the file already has that table.
+fn save_defaults(dir: &Path, defaults: &DefaultsConfig) -> Result<bool> {
+    let path = dir.join(PROJECT_CONFIG_FILE);
+    let existing = if path.is_file() {
+        fs::read_to_string(&path)
+            .map_err(|e| WasmrunError::add_context(format!("Reading {}", path.display()), e))?
+    } else {
+        String::new()
+    };
+    if existing
+        .parse::<toml::Table>()
+        .is_ok_and(|table| table.contains_key("defaults"))
+    {
+        return Ok(false);
+    }
+
+    let table = toml::to_string(defaults)
+        .map_err(|e| WasmrunError::from(format!("Failed to write [defaults]: {e}")))?;
+    let mut section = String::new();
+    if !existing.is_empty() {
+        section.push_str(if existing.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        });
+    }
+    section.push_str("[defaults]\n");
+    section.push_str(&table);
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(section.as_bytes()))
+        .map_err(|e| WasmrunError::add_context(format!("Writing {}", path.display()), e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn answer(dir: &Path, lines: &str) -> (FirstRun, String) {
+        let mut out = Vec::new();
+        let result = prompt(dir, &mut lines.as_bytes(), &mut out).unwrap();
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_prompt_saves_defaults() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "# mine\nlanguage = \"rust\"",
+        )
+        .unwrap();
+
+        // Default mode, a bad port then 3000, no watch, save
+        let (result, out) = answer(dir.path(), "\nnope\n3000\nn\n\n");
+        let expected: Vec<String> = ["wasmrun", "run", ".", "--port", "3000"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(result, FirstRun::Args(expected.clone()));
+        assert!(out.contains("Rust (Cargo.toml)"));
+        assert!(out.contains("'nope'"));
+
+        let saved = fs::read_to_string(dir.path().join(PROJECT_CONFIG_FILE)).unwrap();
+        assert!(saved.starts_with("# mine\nlanguage = \"rust\"\n\n[defaults]\n"));
+        let config = ProjectConfig::load(dir.path()).unwrap().unwrap();
+        assert_eq!(config.language.as_deref(), Some("rust"));
+        assert_eq!(saved_args(&config.defaults).unwrap(), Some(expected));
+        assert_eq!(
+            handle_first_run(dir.path()).unwrap(),
+            FirstRun::Args(saved_args(&config.defaults).unwrap().unwrap())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "os-mode")]
+    fn test_prompt_node_project_and_empty_dir() {
+        let dir = tempdir().unwrap();
+        let (result, out) = answer(dir.path(), "");
+        assert_eq!(result, FirstRun::Done);
+        assert!(out.contains("wasmrun examples"));
+
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        // Every answer left at its default, with input ending early
+        let (result, _) = answer(dir.path(), "\n");
+        assert_eq!(
+            result,
+            FirstRun::Args(
+                ["wasmrun", "os", ".", "--port", "8420", "--watch"]
+                    .map(String::from)
+                    .to_vec()
+            )
+        );
+        assert!(dir.path().join(PROJECT_CONFIG_FILE).is_file());
+    }
+
+    #[test]
+    fn test_saved_args() {
+        let compile = DefaultsConfig {
+            command: Some("compile".into()),
+            output: Some("out".into()),
+            watch: true,
+            ..DefaultsConfig::default()
+        };
+        assert_eq!(
+            saved_args(&compile).unwrap().unwrap(),
+            ["wasmrun", "compile", ".", "-o", "out"]
+        );
+        assert_eq!(saved_args(&DefaultsConfig::default()).unwrap(), None);
+
+        let unknown = DefaultsConfig {
+            command: Some("deploy".into()),
+            ..DefaultsConfig::default()
+        };
+        assert!(saved_args(&unknown)
+            .unwrap_err()
+            .to_string()
+            .contains("deploy"));
+    }
+}
@@ -0,0 +1,453 @@
+//! `wasmrun ide vscode`: VS Code tasks and launch configurations for a
+//! project. Entries are labelled `wasmrun: ...` and regenerating replaces
+//! only those, so tasks and configurations the user wrote are kept.
+
+use crate::cli::IdeSubcommands;
+use crate::compiler::{detect_project_language, ProjectLanguage};
+use crate::config::project::ProjectConfig;
+use crate::error::{Result, WasmrunError};
+use crate::utils::{strip_jsonc, PathResolver};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of every generated task label and launch configuration name
+const LABEL_PREFIX: &str = "wasmrun: ";
+
+const DEFAULT_PORT: u16 = 8420;
+const DEFAULT_OUTPUT: &str = "dist";
+
+/// Lines the dev server prints when a build starts and when it is serving
+/// again, which tell VS Code when the background run task is ready
+const BUILD_STARTED: &str = "Building project|Files changed, recompiling";
+const BUILD_SETTLED: &str = "Listening on|Watching for changes|Recompilation (completed|failed)";
+
+pub fn run_ide_command(subcommand: &IdeSubcommands) -> Result<()> {
+    match subcommand {
+        IdeSubcommands::Vscode {
+            path,
+            positional_path,
+            workspace,
+            port,
+            output,
+            language,
+            dry_run,
+        } => handle_vscode(
+            &PathResolver::resolve_input_path(positional_path.clone(), path.clone()),
+            workspace.as_deref(),
+            *port,
+            output.as_deref(),
+            language.as_deref(),
+            *dry_run,
+        ),
+    }
+}
+
+fn handle_vscode(
+    project: &str,
+    workspace: Option<&str>,
+    port: Option<u16>,
+    output: Option<&str>,
+    language: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    let project = canonical(project)?;
+    let workspace = match workspace {
+        Some(workspace) => canonical(workspace)?,
+        None => project.clone(),
+    };
+    let relative = project.strip_prefix(&workspace).map_err(|_| {
+        WasmrunError::from(format!(
+            "{} is not inside the workspace {}",
+            project.display(),
+            workspace.display()
+        ))
+    })?;
+
+    let config = ProjectConfig::load(&project)?.unwrap_or_default();
+    let detected = match language {
+        Some(name) => ProjectLanguage::from_name(name)
+            .ok_or_else(|| WasmrunError::from(format!("Unsupported language: {name}")))?,
+        None => detect_project_language(&project.to_string_lossy()),
+    };
+    let setup = VscodeSetup {
+        project_dir: workspace_path(relative),
+        language: detected,
+        language_flag: language.map(str::to_string),
+        port: port.or(config.defaults.port).unwrap_or(DEFAULT_PORT),
+        output: output
+            .map(str::to_string)
+            .or(config.defaults.output)
+            .unwrap_or_else(|| DEFAULT_OUTPUT.to_string()),
+    };
+
+    let dir = workspace.join(".vscode");
+    let files = [
+        ("tasks.json", "2.0.0", "tasks", "label", setup.tasks()),
+        (
+            "launch.json",
+            "0.2.0",
+            "configurations",
+            "name",
+            setup.launch_configurations(),
+        ),
+    ];
+    if !dry_run {
+        fs::create_dir_all(&dir)
+            .map_err(|e| WasmrunError::add_context(format!("Creating {}", dir.display()), e))?;
+    }
+
+    println!(
+        "🧩 VS Code setup for a {} project in {}",
+        setup.language,
+        workspace.display()
+    );
+    for (name, version, key, name_field, entries) in files {
+        let path = dir.join(name);
+        let existing = fs::read_to_string(&path).ok();
+        let merged = merge_entries(existing.as_deref(), version, key, name_field, entries)
+            .map_err(|e| WasmrunError::from(format!("{}: {e}", path.display())))?;
+        let text = serde_json::to_string_pretty(&merged.document)
+            .map_err(|e| WasmrunError::from(e.to_string()))?
+            + "\n";
+
+        if dry_run {
+            println!("\n// {}\n{text}", path.display());
+            continue;
+        }
+        fs::write(&path, text)
+            .map_err(|e| WasmrunError::add_context(format!("Writing {}", path.display()), e))?;
+        println!(
+            "  📝 {}: {} from wasmrun, {} of your own kept",
+            path.display(),
+            merged.added,
+            merged.kept
+        );
+        if merged.dropped_comments {
+            println!("  ⚠️  Comments and trailing commas in {name} were not kept");
+        }
+    }
+    if !dry_run {
+        println!(
+            "  Build with Ctrl+Shift+B; \"{LABEL_PREFIX}open in Chrome\" in Run and Debug serves the project with --watch"
+        );
+    }
+    Ok(())
+}
+
+fn canonical(path: &str) -> Result<PathBuf> {
+    fs::canonicalize(path)
+        .ok()
+        .filter(|p| p.is_dir())
+        .ok_or_else(|| WasmrunError::directory_not_found(path))
+}
+
+/// `${workspaceFolder}`, joined with the project's path inside it
+fn workspace_path(relative: &Path) -> String {
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    if relative.is_empty() {
+        "${workspaceFolder}".to_string()
+    } else {
+        format!("${{workspaceFolder}}/{relative}")
+    }
+}
+
+/// What the generated entries are customised to
+struct VscodeSetup {
+    /// The project directory in VS Code variables
+    project_dir: String,
+    language: ProjectLanguage,
+    /// `--language`, passed on only when given, so detection and
+    /// wasmrun.toml still apply
+    language_flag: Option<String>,
+    port: u16,
+    output: String,
+}
+
+impl VscodeSetup {
+    fn tasks(&self) -> Vec<Value> {
+        let output = if Path::new(&self.output).is_absolute() {
+            self.output.clone()
+        } else {
+            format!("{}/{}", self.project_dir, self.output)
+        };
+        let mut build_args = vec![
+            "compile".to_string(),
+            self.project_dir.clone(),
+            "--output".to_string(),
+            output,
+        ];
+        let mut run_args = vec![
+            "run".to_string(),
+            self.project_dir.clone(),
+            "--port".to_string(),
+            self.port.to_string(),
+            "--watch".to_string(),
+        ];
+        if let Some(language) = &self.language_flag {
+            for args in [&mut build_args, &mut run_args] {
+                args.extend(["--language".to_string(), language.clone()]);
+            }
+        }
+
+        let mut run_matcher = self.problem_matcher();
+        run_matcher["background"] = json!({
+            "activeBegins": true,
+            "beginsPattern": BUILD_STARTED,
+            "endsPattern": BUILD_SETTLED,
+        });
+
+        vec![
+            json!({
+                "label": format!("{LABEL_PREFIX}build"),
+                "type": "shell",
+                "command": "wasmrun",
+                "args": build_args,
+                "group": { "kind": "build", "isDefault": true },
+                "problemMatcher": self.problem_matcher(),
+            }),
+            json!({
+                "label": format!("{LABEL_PREFIX}run"),
+                "type": "shell",
+                "command": "wasmrun",
+                "args": run_args,
+                "isBackground": true,
+                "problemMatcher": run_matcher,
+            }),
+            json!({
+                "label": format!("{LABEL_PREFIX}stop"),
+                "type": "shell",
+                "command": "wasmrun",
+                "args": ["stop"],
+                "problemMatcher": [],
+                "presentation": { "reveal": "silent" },
+            }),
+        ]
+    }
+
+    fn launch_configurations(&self) -> Vec<Value> {
+        vec![json!({
+            "name": format!("{LABEL_PREFIX}open in Chrome"),
+            "type": "chrome",
+            "request": "launch",
+            "url": format!("http://localhost:{}", self.port),
+            "webRoot": self.project_dir,
+            "preLaunchTask": format!("{LABEL_PREFIX}run"),
+            "postDebugTask": format!("{LABEL_PREFIX}stop"),
+        })]
+    }
+
+    /// The toolchain's own matcher where VS Code ships one, with paths
+    /// resolved from the project directory
+    fn problem_matcher(&self) -> Value {
+        let file_location = json!(["relative", self.project_dir]);
+        let base = match self.language {
+            ProjectLanguage::Rust => Some("$rustc"),
+            ProjectLanguage::Go => Some("$go"),
+            ProjectLanguage::C => Some("$gcc"),
+            _ => None,
+        };
+        match base {
+            Some(base) => json!({ "base": base, "fileLocation": file_location }),
+            None => json!({
+                "owner": "wasmrun",
+                "fileLocation": file_location,
+                "pattern": {
+                    "regexp": "^(.+?):(\\d+):(\\d+):\\s+(?:error|ERROR):?\\s+(.*)$",
+                    "file": 1,
+                    "line": 2,
+                    "column": 3,
+                    "message": 4,
+                },
+            }),
+        }
+    }
+}
+
+struct Merged {
+    document: Value,
+    added: usize,
+    kept: usize,
+    dropped_comments: bool,
+}
+
+/// `existing` with the `wasmrun: ` entries of its `key` array replaced by
+/// `entries`. Anything else in the file is left as it was.
+fn merge_entries(
+    existing: Option<&str>,
+    version: &str,
+    key: &str,
+    name_field: &str,
+    entries: Vec<Value>,
+) -> std::result::Result<Merged, String> {
+    let (mut document, dropped_comments) = match existing.filter(|t| !t.trim().is_empty()) {
+        Some(text) => {
+            let stripped = strip_jsonc(text);
+            let document: Value = serde_json::from_str(&stripped)
+                .map_err(|e| format!("can't be read as JSON ({e}); fix or remove it first"))?;
+            (document, stripped != text)
+        }
+        None => (json!({ "version": version }), false),
+    };
+    let Some(object) = document.as_object_mut() else {
+        return Err("expected a JSON object".to_string());
+    };
+
+    let mut list = match object.remove(key) {
+        None => Vec::new(),
+        Some(Value::Array(list)) => list,
+        Some(_) => return Err(format!("`{key}` is not an array")),
+    };
+    list.retain(|entry| {
+        !entry[name_field]
+            .as_str()
+            .is_some_and(|name| name.starts_with(LABEL_PREFIX))
+    });
+    let kept = list.len();
+    let added = entries.len();
+    list.extend(entries);
+    object.insert(key.to_string(), Value::Array(list));
+
+    Ok(Merged {
+        document,
+        added,
+        kept,
+        dropped_comments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(language: ProjectLanguage, project_dir: &str) -> VscodeSetup {
+        VscodeSetup {
+            project_dir: project_dir.to_string(),
+            language,
+            language_flag: None,
+            port: 3000,
+            output: DEFAULT_OUTPUT.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tasks_follow_language_and_layout() {
+        let rust = setup(ProjectLanguage::Rust, "${workspaceFolder}/app").tasks();
+        assert_eq!(rust[0]["label"], "wasmrun: build");
+        assert_eq!(
+            rust[0]["args"],
+            json!([
+                "compile",
+                "${workspaceFolder}/app",
+                "--output",
+                "${workspaceFolder}/app/dist"
+            ])
+        );
+        assert_eq!(rust[0]["problemMatcher"]["base"], "$rustc");
+        assert_eq!(
+            rust[0]["problemMatcher"]["fileLocation"],
+            json!(["relative", "${workspaceFolder}/app"])
+        );
+        assert_eq!(rust[1]["isBackground"], true);
+        assert!(rust[1]["args"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("--watch")));
+        assert_eq!(
+            rust[1]["problemMatcher"]["background"]["activeBegins"],
+            true
+        );
+
+        let mut python = setup(ProjectLanguage::Python, "${workspaceFolder}");
+        python.language_flag = Some("python".to_string());
+        let tasks = python.tasks();
+        assert_eq!(tasks[0]["problemMatcher"]["owner"], "wasmrun");
+        assert_eq!(tasks[1]["args"][6], "python");
+
+        let launch = python.launch_configurations();
+        assert_eq!(launch[0]["url"], "http://localhost:3000");
+        assert_eq!(launch[0]["preLaunchTask"], tasks[1]["label"]);
+        assert_eq!(launch[0]["postDebugTask"], tasks[2]["label"]);
+    }
+
+    #[test]
+    fn test_merge_keeps_user_entries() {
+        let existing = r#"{
+            // Generated by VS Code
+            "version": "2.0.0",
+            "tasks": [
+                { "label": "lint", "command": "cargo clippy" },
+                { "label": "wasmrun: old", "command": "wasmrun" },
+            ],
+            "inputs": [],
+        }"#;
+        let merged = merge_entries(
+            Some(existing),
+            "2.0.0",
+            "tasks",
+            "label",
+            vec![json!({ "label": "wasmrun: build" })],
+        )
+        .unwrap();
+        assert!(merged.dropped_comments);
+        assert_eq!((merged.added, merged.kept), (1, 1));
+        let labels: Vec<&str> = merged.document["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels, ["lint", "wasmrun: build"]);
+        assert_eq!(merged.document["inputs"], json!([]));
+
+        let fresh = merge_entries(None, "0.2.0", "configurations", "name", vec![]).unwrap();
+        assert_eq!(
+            fresh.document,
+            json!({ "version": "0.2.0", "configurations": [] })
+        );
+        assert!(!fresh.dropped_comments);
+
+        assert!(
+            merge_entries(Some("{ \"tasks\": {} }"), "2.0.0", "tasks", "label", vec![]).is_err()
+        );
+        assert!(merge_entries(Some("not json"), "2.0.0", "tasks", "label", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_writes_into_workspace() {
+        let workspace = tempfile::tempdir().unwrap();
+        let project = workspace.path().join("game");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("go.mod"), "module game\n").unwrap();
+
+        handle_vscode(
+            project.to_str().unwrap(),
+            workspace.path().to_str(),
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        let tasks: Value = serde_json::from_str(
+            &fs::read_to_string(workspace.path().join(".vscode/tasks.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(tasks["version"], "2.0.0");
+        assert_eq!(tasks["tasks"][0]["args"][1], "${workspaceFolder}/game");
+        assert_eq!(tasks["tasks"][0]["problemMatcher"]["base"], "$go");
+        assert!(workspace.path().join(".vscode/launch.json").is_file());
+
+        // A project outside the workspace can't use ${workspaceFolder}
+        let elsewhere = tempfile::tempdir().unwrap();
+        assert!(handle_vscode(
+            elsewhere.path().to_str().unwrap(),
+            workspace.path().to_str(),
+            None,
+            None,
+            None,
+            true,
+        )
+        .is_err());
+    }
+}
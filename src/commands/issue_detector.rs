@@ -38,10 +38,23 @@ impl IssueSeverity {
 
 /// Analyze a WASM module and detect potential issues
 pub fn detect_issues(module: &Module) -> Vec<WasmIssue> {
+    detect(module, true)
+}
+
+/// [`detect_issues`] without the checks that look at function bodies, for
+/// a module from [`Module::parse_headers`]
+pub fn detect_header_issues(module: &Module) -> Vec<WasmIssue> {
+    detect(module, false)
+}
+
+fn detect(module: &Module, with_code: bool) -> Vec<WasmIssue> {
     let mut issues = Vec::new();
 
     // Check for missing or unusual sections
     check_section_completeness(module, &mut issues);
+    if with_code {
+        check_function_bodies(module, &mut issues);
+    }
 
     // Check memory configuration
     check_memory_configuration(module, &mut issues);
@@ -50,7 +63,9 @@ pub fn detect_issues(module: &Module) -> Vec<WasmIssue> {
     check_export_patterns(module, &mut issues);
 
     // Check for suspicious code characteristics
-    check_code_characteristics(module, &mut issues);
+    if with_code {
+        check_code_characteristics(module, &mut issues);
+    }
 
     // Check for import/export mismatch
     check_import_export_consistency(module, &mut issues);
@@ -71,7 +86,10 @@ fn check_section_completeness(module: &Module, issues: &mut Vec<WasmIssue>) {
             description: "Module has imports but no type section. This is unusual.".to_string(),
         });
     }
+}
 
+/// Check that declared functions have bodies, and that they aren't stubs
+fn check_function_bodies(module: &Module, issues: &mut Vec<WasmIssue>) {
     // If there are functions, there should be code
     let function_count = module.functions.len();
     let code_count = module
@@ -0,0 +1,361 @@
+//! `wasmrun matrix`: build the project at several optimization levels and
+//! compare what each one costs. Every level is built into its own directory
+//! with the project's builder; the report gives the module's size, gzipped
+//! size and code and data sections, how long the build took, and, with
+//! `--bench`, how long an export takes to run in the interpreter.
+
+use super::compile::{run_build, select_builder};
+use crate::compiler::builder::{
+    BuildConfig, OptimizationLevel, OutputNaming, TargetType, WasmBuilder,
+};
+use crate::error::{Result, WasmrunError};
+use crate::utils::{gzip_size, CommandExecutor, ModuleDetails, PathResolver};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct MatrixOptions {
+    pub levels: Vec<OptimizationLevel>,
+    pub language: Option<String>,
+    /// Export to time at each level
+    pub bench: Option<String>,
+    /// Arguments for the benchmarked export
+    pub args: Vec<String>,
+    /// Timed calls per level
+    pub iterations: usize,
+    /// Leave the builds on disk
+    pub keep: bool,
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct MatrixReport {
+    project: String,
+    language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bench: Option<String>,
+    levels: Vec<LevelResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct LevelResult {
+    optimization: String,
+    /// The module's file name
+    wasm: String,
+    bytes: u64,
+    gzip_bytes: u64,
+    code_bytes: u64,
+    data_bytes: u64,
+    functions: usize,
+    build_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bench: Option<BenchResult>,
+    /// Why the export couldn't be timed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bench_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    iterations: usize,
+    median_us: f64,
+    min_us: f64,
+    /// What the export returned, to check every level agrees
+    results: Vec<String>,
+}
+
+/// Handle matrix command
+pub fn handle_matrix_command(
+    path: &Option<String>,
+    positional_path: &Option<String>,
+    options: &MatrixOptions,
+) -> Result<()> {
+    if options.levels.is_empty() {
+        return Err(WasmrunError::from("No optimization levels to build"));
+    }
+    if options.bench.is_some() && !cfg!(feature = "interpreter") {
+        return Err(WasmrunError::from(
+            "--bench runs exports in the interpreter; rebuild wasmrun with the `interpreter` feature",
+        ));
+    }
+
+    let project_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+    PathResolver::validate_directory_exists(&project_path)?;
+    let builder = select_builder(&project_path, options.language.as_deref())?;
+
+    let work = std::env::temp_dir().join(format!("wasmrun_matrix_{}", std::process::id()));
+    let report = build_levels(builder.as_ref(), &project_path, &work, options);
+    if options.keep {
+        println!("📁 Builds kept in {}", work.display());
+    } else {
+        let _ = fs::remove_dir_all(&work);
+    }
+    let report = report?;
+
+    if options.json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| WasmrunError::from(format!("Failed to write report: {e}")))?;
+        println!("{json}");
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn build_levels(
+    builder: &dyn WasmBuilder,
+    project_path: &str,
+    work: &Path,
+    options: &MatrixOptions,
+) -> Result<MatrixReport> {
+    let _ = fs::remove_dir_all(work);
+    let mut levels = Vec::new();
+    for level in &options.levels {
+        if !options.json {
+            println!("🔨 Building at {level}...");
+        }
+        let output_dir = work.join(level.to_string());
+        fs::create_dir_all(&output_dir).map_err(|e| {
+            WasmrunError::add_context(format!("Creating {}", output_dir.display()), e)
+        })?;
+        let config = BuildConfig {
+            project_path: project_path.to_string(),
+            output_dir: output_dir.to_string_lossy().to_string(),
+            verbose: false,
+            optimization_level: level.clone(),
+            watch: false,
+            target_type: TargetType::Standard,
+            naming: OutputNaming::default(),
+        };
+        let started = Instant::now();
+        let result = run_build(builder, &config)
+            .map_err(|e| WasmrunError::add_context(format!("Building at {level}"), e))?;
+        let build_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let bytes = fs::read(&result.wasm_path)
+            .map_err(|e| WasmrunError::add_context(format!("Reading {}", result.wasm_path), e))?;
+        let mut row = measure(level, &result.wasm_path, &bytes, build_ms)?;
+        if let Some(export) = &options.bench {
+            match bench(&bytes, export, &options.args, options.iterations) {
+                Ok(bench) => row.bench = Some(bench),
+                Err(e) => row.bench_error = Some(e.to_string()),
+            }
+        }
+        levels.push(row);
+    }
+
+    Ok(MatrixReport {
+        project: project_path.to_string(),
+        language: builder.language_name().to_string(),
+        bench: options.bench.clone(),
+        levels,
+    })
+}
+
+/// Sizes of one level's module
+fn measure(
+    level: &OptimizationLevel,
+    wasm_path: &str,
+    bytes: &[u8],
+    build_ms: f64,
+) -> Result<LevelResult> {
+    let details = ModuleDetails::from_bytes(bytes)
+        .map_err(|e| WasmrunError::from(format!("Failed to parse {wasm_path}: {e}")))?;
+    let section = |name: &str| {
+        details
+            .sections
+            .iter()
+            .filter(|section| !section.custom && section.name == name)
+            .map(|section| section.size as u64)
+            .sum()
+    };
+    Ok(LevelResult {
+        optimization: level.to_string(),
+        wasm: Path::new(wasm_path).file_name().map_or_else(
+            || wasm_path.to_string(),
+            |name| name.to_string_lossy().to_string(),
+        ),
+        bytes: bytes.len() as u64,
+        gzip_bytes: gzip_size(bytes),
+        code_bytes: section("Code"),
+        data_bytes: section("Data"),
+        functions: details.functions.defined,
+        build_ms,
+        bench: None,
+        bench_error: None,
+    })
+}
+
+/// Call `export` `iterations` times, each in a fresh instance, timing only
+/// the call
+#[cfg(feature = "interpreter")]
+fn bench(bytes: &[u8], export: &str, args: &[String], iterations: usize) -> Result<BenchResult> {
+    use crate::runtime::core::native_executor::{execute_wasm_bytes_report, ExecOptions};
+
+    let options = ExecOptions::default();
+    let mut times = Vec::with_capacity(iterations);
+    let mut results = Vec::new();
+    for _ in 0..iterations.max(1) {
+        let report =
+            execute_wasm_bytes_report(bytes, Some(export.to_string()), args.to_vec(), &options)?;
+        if let Some(error) = report.error {
+            return Err(WasmrunError::from(error));
+        }
+        times.push(report.elapsed.as_secs_f64() * 1_000_000.0);
+        results = report.results;
+    }
+    times.sort_by(f64::total_cmp);
+    Ok(BenchResult {
+        iterations: times.len(),
+        median_us: times[times.len() / 2],
+        min_us: times[0],
+        results,
+    })
+}
+
+#[cfg(not(feature = "interpreter"))]
+fn bench(_: &[u8], _: &str, _: &[String], _: usize) -> Result<BenchResult> {
+    Err(WasmrunError::from("Built without the interpreter"))
+}
+
+/// A change from the first level, e.g. `-38.2%`
+fn relative(value: f64, base: f64) -> String {
+    if base == 0.0 {
+        return "—".to_string();
+    }
+    format!("{:+.1}%", (value - base) / base * 100.0)
+}
+
+fn format_micros(us: f64) -> String {
+    if us >= 1000.0 {
+        format!("{:.2} ms", us / 1000.0)
+    } else {
+        format!("{us:.1} µs")
+    }
+}
+
+fn print_report(report: &MatrixReport) {
+    println!(
+        "\n📊 Build matrix for {} ({})",
+        report.project, report.language
+    );
+    let mut header = format!(
+        "  {:<8} {:>11} {:>9} {:>11} {:>11} {:>11} {:>9}",
+        "level", "size", "vs first", "gzip", "code", "data", "build"
+    );
+    if let Some(export) = &report.bench {
+        header.push_str(&format!(" {:>11} {:>9}", format!("{export}()"), "vs first"));
+    }
+    println!("{header}");
+
+    let first = report.levels.first();
+    for row in &report.levels {
+        let base = first.map_or(row.bytes, |first| first.bytes);
+        let mut line = format!(
+            "  {:<8} {:>11} {:>9} {:>11} {:>11} {:>11} {:>9}",
+            row.optimization,
+            CommandExecutor::format_file_size(row.bytes),
+            relative(row.bytes as f64, base as f64),
+            CommandExecutor::format_file_size(row.gzip_bytes),
+            CommandExecutor::format_file_size(row.code_bytes),
+            CommandExecutor::format_file_size(row.data_bytes),
+            format!("{:.1}s", row.build_ms / 1000.0),
+        );
+        if report.bench.is_some() {
+            let base = first.and_then(|first| first.bench.as_ref());
+            match (&row.bench, base) {
+                (Some(bench), Some(base)) => line.push_str(&format!(
+                    " {:>11} {:>9}",
+                    format_micros(bench.median_us),
+                    relative(bench.median_us, base.median_us)
+                )),
+                (Some(bench), None) => line.push_str(&format!(
+                    " {:>11} {:>9}",
+                    format_micros(bench.median_us),
+                    "—"
+                )),
+                (None, _) => line.push_str(&format!(" {:>11} {:>9}", "failed", "—")),
+            }
+        }
+        println!("{line}");
+    }
+
+    for row in &report.levels {
+        if let Some(error) = &row.bench_error {
+            println!(
+                "\n⚠️  {} at {}: {error}",
+                report.bench.as_deref().unwrap_or(""),
+                row.optimization
+            );
+        }
+    }
+    let mut results = report
+        .levels
+        .iter()
+        .filter_map(|row| row.bench.as_ref().map(|bench| &bench.results));
+    if let Some(first) = results.next() {
+        if results.any(|other| other != first) {
+            println!("\n⚠️  The export returned different results at different levels");
+        }
+    }
+    if let Some(smallest) = report.levels.iter().min_by_key(|row| row.gzip_bytes) {
+        println!(
+            "\n📦 Smallest download: {} ({} gzip)",
+            smallest.optimization,
+            CommandExecutor::format_file_size(smallest.gzip_bytes)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (module (func (export "add") (param i32 i32) (result i32)
+    //   local.get 0 local.get 1 i32.add)
+    //   (memory 1) (data (i32.const 0) "hi"))
+    const ADD_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f, // type
+        0x03, 0x02, 0x01, 0x00, // function
+        0x05, 0x03, 0x01, 0x00, 0x01, // memory
+        0x07, 0x07, 0x01, 0x03, b'a', b'd', b'd', 0x00, 0x00, // export
+        0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x0b, // code
+        0x0b, 0x08, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x02, b'h', b'i', // data
+    ];
+
+    #[test]
+    fn test_measure_sizes() {
+        let row = measure(&OptimizationLevel::Size, "out/add.wasm", ADD_WASM, 1500.0).unwrap();
+        assert_eq!(row.optimization, "size");
+        assert_eq!(row.wasm, "add.wasm");
+        assert_eq!(row.bytes, ADD_WASM.len() as u64);
+        assert_eq!(row.code_bytes, 9);
+        assert_eq!(row.data_bytes, 8);
+        assert_eq!(row.functions, 1);
+        assert!(row.gzip_bytes > 0);
+
+        assert!(measure(&OptimizationLevel::Debug, "bad.wasm", b"nope", 0.0).is_err());
+    }
+
+    #[cfg(feature = "interpreter")]
+    #[test]
+    fn test_bench_times_an_export() {
+        let args = vec!["2".to_string(), "3".to_string()];
+        let bench = bench(ADD_WASM, "add", &args, 5).unwrap();
+        assert_eq!(bench.iterations, 5);
+        assert_eq!(bench.results, ["5"]);
+        assert!(bench.min_us <= bench.median_us);
+
+        assert!(super::bench(ADD_WASM, "missing", &[], 1).is_err());
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(relative(50.0, 100.0), "-50.0%");
+        assert_eq!(relative(110.0, 100.0), "+10.0%");
+        assert_eq!(relative(1.0, 0.0), "—");
+    }
+}
@@ -1,22 +1,71 @@
+#[cfg(feature = "interpreter")]
 mod agent;
+mod artifacts;
+#[cfg(feature = "deploy")]
+mod build_agent;
 mod clean;
 mod compile;
+#[cfg(feature = "interpreter")]
+mod coverage;
+#[cfg(feature = "interpreter")]
+mod cross_check;
+mod e2e;
+mod examples;
+#[cfg(feature = "interpreter")]
 mod exec;
+mod first_run;
+mod ide;
 mod init;
 mod issue_detector;
-mod module_display;
+mod matrix;
+pub mod module_display;
+#[cfg(feature = "os-mode")]
 mod os;
 mod plugin;
+mod policy;
+mod registry;
+mod repro;
 mod run;
+mod runner;
+mod serve_api;
+mod snapshot;
 mod stop;
+mod templates;
+mod tree_shake;
+mod trigger;
 mod verify;
 
+#[cfg(feature = "interpreter")]
 pub use agent::handle_agent_command;
-pub use clean::handle_clean_command;
-pub use compile::handle_compile_command;
-pub use exec::handle_exec_command;
-pub use os::handle_os_command;
+pub use artifacts::run_artifacts_command;
+#[cfg(feature = "deploy")]
+pub use build_agent::handle_build_agent_command;
+pub use clean::{handle_clean_command, CleanOptions};
+pub use compile::{
+    apply_build_timeout, apply_container, apply_remote_build, handle_compile_command, Provenance,
+};
+#[cfg(feature = "interpreter")]
+pub use coverage::run_coverage_command;
+pub use e2e::{handle_e2e_command, E2eOptions};
+pub use examples::{handle_examples_command, handle_examples_run_command};
+#[cfg(feature = "interpreter")]
+pub use exec::{exec_options, handle_bindgen_command, handle_exec_command};
+pub use first_run::{handle_first_run, FirstRun};
+pub use ide::run_ide_command;
+pub use init::{handle_init_command, InitOptions};
+pub use matrix::{handle_matrix_command, MatrixOptions};
+#[cfg(feature = "os-mode")]
+pub use os::{handle_hash_password_command, handle_os_command};
 pub use plugin::run_plugin_command;
-pub use run::handle_run_command;
+#[cfg(feature = "deploy")]
+pub use registry::{handle_pull_command, handle_push_command};
+pub use repro::{handle_repro_command, ReproOptions};
+pub use run::{handle_run_command, run_build_dirs};
+pub use serve_api::handle_serve_api_command;
+pub use snapshot::{handle_snapshot_command, SnapshotOptions};
 pub use stop::handle_stop_command;
-pub use verify::{handle_inspect_command, handle_verify_command, verify_wasm, VerificationResult};
+pub use trigger::handle_trigger_command;
+pub use verify::{
+    handle_inspect_command, handle_verify_command, verify_wasm, verify_wasm_bytes,
+    VerificationResult,
+};
@@ -1,6 +1,7 @@
 //! Display helpers for formatting WASM module information
 
 use crate::runtime::core::module::{ExportKind, ImportKind, Module, ValueType};
+use std::collections::HashMap;
 
 /// Format a value type for display
 pub fn format_value_type(vt: ValueType) -> &'static str {
@@ -143,6 +144,37 @@ pub fn display_functions(module: &Module) {
     println!("     └─ Smallest function: {min_size} bytes");
 }
 
+/// Display the largest function bodies, labelled from the name section
+pub fn display_largest_functions(module: &Module, names: &HashMap<u32, String>, limit: usize) {
+    if module.functions.is_empty() {
+        return;
+    }
+
+    let import_count = module
+        .imports
+        .iter()
+        .filter(|i| matches!(i.kind, ImportKind::Function(_)))
+        .count();
+    let mut sizes: Vec<(usize, usize)> = module
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (import_count + i, f.code.len()))
+        .collect();
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let shown = limit.min(sizes.len());
+    println!("\n  📏 Largest functions (top {shown} of {}):", sizes.len());
+    for (i, (index, size)) in sizes.iter().take(shown).enumerate() {
+        let branch = if i + 1 == shown { "└─" } else { "├─" };
+        let name = names
+            .get(&(*index as u32))
+            .cloned()
+            .unwrap_or_else(|| format!("func[{index}]"));
+        println!("     {branch} {size:>8} bytes  {name}");
+    }
+}
+
 /// Display module exports
 pub fn display_exports(module: &Module) {
     if module.exports.is_empty() {
@@ -281,13 +313,30 @@ pub fn display_element_segments(module: &Module) {
 
 /// Display complete module summary
 pub fn display_module_summary(module: &Module) {
+    display_summary(module, true);
+}
+
+/// [`display_module_summary`] for a module from [`Module::parse_headers`],
+/// whose functions are counted but have no code to measure
+pub fn display_module_headers(module: &Module) {
+    display_summary(module, false);
+}
+
+fn display_summary(module: &Module, with_code: bool) {
     println!("\n  ╭ Module Analysis");
     println!("  │");
     println!("  ├─ Version: {}", module.version);
 
     display_types(module);
     display_imports(module);
-    display_functions(module);
+    if with_code {
+        display_functions(module);
+    } else {
+        println!(
+            "  🔧 Functions: {} (code not analysed with --fast)",
+            module.functions.len()
+        );
+    }
     display_exports(module);
     display_globals(module);
     display_memory(module);
@@ -1,8 +1,11 @@
 //! OS mode command implementation
 
+use crate::config::project::ProjectConfig;
 use crate::error::{Result, WasmrunError};
 use crate::runtime::multilang_kernel::{MultiLanguageKernel, OsRunConfig};
+use crate::runtime::os_auth;
 use crate::runtime::os_server::OsServer;
+use crate::runtime::wasi_fs::MountSpec;
 use crate::utils::PathResolver;
 use std::fmt;
 use std::path::Path;
@@ -33,7 +36,27 @@ impl OsLanguage {
     }
 }
 
+/// `wasmrun os --hash-password`: read a password from stdin and print the
+/// hash to list under `[auth.users]`, without starting the server
+pub fn handle_hash_password_command() -> Result<()> {
+    if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        eprintln!("Password (input is shown; pipe it in to keep it off screen):");
+    }
+    let mut password = String::new();
+    std::io::stdin()
+        .read_line(&mut password)
+        .map_err(|e| WasmrunError::from(format!("Failed to read the password: {e}")))?;
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        return Err(WasmrunError::from("The password is empty"));
+    }
+    let hash = os_auth::hash_password(password).map_err(|e| WasmrunError::from(e.to_string()))?;
+    println!("{hash}");
+    Ok(())
+}
+
 /// Handle the OS mode command
+#[allow(clippy::too_many_arguments)]
 pub fn handle_os_command(
     path: &Option<String>,
     positional_path: &Option<String>,
@@ -42,15 +65,24 @@ pub fn handle_os_command(
     watch: bool,
     verbose: bool,
     allow_cors: bool,
+    max_body_mb: u32,
+    rate_limit: u32,
+    read_only: bool,
+    mounts: &[String],
 ) -> Result<()> {
     let resolved_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
 
     let validated_language = if let Some(lang) = language {
         Some(os_validate_language(lang)?)
     } else {
-        None
+        configured_os_language(&resolved_path)
     };
 
+    let mounts = mounts
+        .iter()
+        .map(|spec| MountSpec::parse(spec).map_err(|e| WasmrunError::from(e.to_string())))
+        .collect::<Result<Vec<_>>>()?;
+
     os_run_project(
         resolved_path,
         port,
@@ -58,15 +90,35 @@ pub fn handle_os_command(
         watch,
         verbose,
         allow_cors,
+        max_body_mb,
+        rate_limit,
+        read_only,
+        mounts,
     )
 }
 
+/// `language` from the project's wasmrun.toml, when OS mode can run it
+fn configured_os_language(project_path: &str) -> Option<OsLanguage> {
+    let language = crate::compiler::configured_language(project_path)?;
+    match OsLanguage::from_str(&language) {
+        Ok(os_language) => {
+            println!("🎯 Using language = \"{language}\" from wasmrun.toml");
+            Some(os_language)
+        }
+        Err(_) => {
+            println!("ℹ️  wasmrun.toml sets language = \"{language}\", which OS mode doesn't run; detecting instead");
+            None
+        }
+    }
+}
+
 /// Validate OS mode language
 fn os_validate_language(language: &str) -> Result<OsLanguage> {
     OsLanguage::from_str(language)
 }
 
 /// Run a project in OS mode with browser-based multi-language kernel
+#[allow(clippy::too_many_arguments)]
 pub fn os_run_project(
     path: String,
     port: u16,
@@ -74,6 +126,10 @@ pub fn os_run_project(
     watch: bool,
     verbose: bool,
     allow_cors: bool,
+    max_body_mb: u32,
+    rate_limit: u32,
+    read_only: bool,
+    mounts: Vec<MountSpec>,
 ) -> Result<()> {
     if verbose {
         println!("🔍 OS Mode: Analyzing project path: {path}");
@@ -91,10 +147,22 @@ pub fn os_run_project(
         )));
     }
 
-    os_start_kernel_and_server(path, port, language, watch, verbose, allow_cors)
+    os_start_kernel_and_server(
+        path,
+        port,
+        language,
+        watch,
+        verbose,
+        allow_cors,
+        max_body_mb,
+        rate_limit,
+        read_only,
+        mounts,
+    )
 }
 
 /// Start the OS mode kernel and server
+#[allow(clippy::too_many_arguments)]
 fn os_start_kernel_and_server(
     path: String,
     port: u16,
@@ -102,6 +170,10 @@ fn os_start_kernel_and_server(
     watch: bool,
     verbose: bool,
     allow_cors: bool,
+    max_body_mb: u32,
+    rate_limit: u32,
+    read_only: bool,
+    mounts: Vec<MountSpec>,
 ) -> Result<()> {
     println!("🚀 Starting wasmrun in OS mode for project: {path}");
 
@@ -117,7 +189,22 @@ fn os_start_kernel_and_server(
         println!("🔍 Verbose output enabled");
     }
 
-    let config = os_create_config(path, language, watch, verbose, allow_cors)?;
+    let mut config = os_create_config(
+        path,
+        language,
+        watch,
+        verbose,
+        allow_cors,
+        max_body_mb,
+        rate_limit,
+    )?;
+    config.read_only = read_only;
+    config.mounts = mounts;
+    if let Some(project) = ProjectConfig::load(Path::new(&config.project_path))? {
+        config.network = project.network;
+        config.fs = project.fs;
+        config.auth = project.auth;
+    }
     let kernel = os_initialize_kernel(config.clone())?;
     let server = os_create_server(kernel, config)?;
     os_start_server(server, port)
@@ -130,6 +217,8 @@ fn os_create_config(
     watch: bool,
     _verbose: bool,
     allow_cors: bool,
+    max_body_mb: u32,
+    rate_limit: u32,
 ) -> Result<OsRunConfig> {
     Ok(OsRunConfig {
         project_path,
@@ -142,12 +231,47 @@ fn os_create_config(
         tunnel_server: None,
         tunnel_secret: None,
         allow_cors,
+        max_body_mb,
+        rate_limit,
+        read_only: false,
+        mounts: Vec::new(),
+        network: Default::default(),
+        fs: Default::default(),
+        auth: Default::default(),
     })
 }
 
-fn os_initialize_kernel(_config: OsRunConfig) -> Result<MultiLanguageKernel> {
+fn os_initialize_kernel(config: OsRunConfig) -> Result<MultiLanguageKernel> {
     let kernel = MultiLanguageKernel::new();
-    // TODO: Apply config to kernel
+    // TODO: Apply the rest of the config to the kernel
+    kernel.configure_network(&config.network);
+    if !config.network.hosts.is_empty() {
+        println!(
+            "🧭 Host overrides: {}",
+            config
+                .network
+                .hosts
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    kernel
+        .configure_fs(&config.fs)
+        .map_err(|e| WasmrunError::from(e.to_string()))?;
+    if !config.fs.quotas.is_empty() {
+        println!(
+            "💾 Disk quotas: {}",
+            config
+                .fs
+                .quotas
+                .iter()
+                .map(|(path, mb)| format!("{path} {mb}MB"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
     println!("✅ Multi-language kernel started");
     Ok(kernel)
 }
@@ -160,7 +284,10 @@ fn os_create_server(kernel: MultiLanguageKernel, config: OsRunConfig) -> Result<
 
 /// Start the OS mode server
 fn os_start_server(server: OsServer, port: u16) -> Result<()> {
-    println!("🌐 OS Mode interface starting on http://localhost:{port}");
+    println!(
+        "🌐 OS Mode interface starting on {}://localhost:{port}",
+        crate::server::scheme()
+    );
     println!("📱 Open your browser to access the development environment");
 
     server.start(port)
@@ -170,14 +297,7 @@ fn os_start_server(server: OsServer, port: u16) -> Result<()> {
 #[allow(dead_code)]
 pub fn os_detect_project_language(project_path: &str) -> Result<String> {
     let language = crate::compiler::detect_project_language(project_path);
-    match language {
-        crate::compiler::ProjectLanguage::Rust => Ok("rust".to_string()),
-        crate::compiler::ProjectLanguage::Go => Ok("go".to_string()),
-        crate::compiler::ProjectLanguage::C => Ok("c".to_string()),
-        crate::compiler::ProjectLanguage::Asc => Ok("asc".to_string()),
-        crate::compiler::ProjectLanguage::Python => Ok("python".to_string()),
-        crate::compiler::ProjectLanguage::Unknown => Ok("unknown".to_string()),
-    }
+    Ok(language.name().to_string())
 }
 
 // TODO: OS mode project validation
@@ -60,9 +60,19 @@ use crate::plugin::manager::PluginManager;
 pub fn run_plugin_command(subcommand: &PluginSubcommands) -> Result<()> {
     match subcommand {
         PluginSubcommands::List { all: _ } => run_plugin_list(),
+        #[cfg(feature = "external-plugins")]
         PluginSubcommands::Install { plugin, version: _ } => run_plugin_install(plugin),
+        #[cfg(feature = "external-plugins")]
         PluginSubcommands::Uninstall { plugin } => run_plugin_uninstall(plugin),
+        #[cfg(feature = "external-plugins")]
         PluginSubcommands::Update { plugin } => run_plugin_update(plugin),
+        #[cfg(not(feature = "external-plugins"))]
+        PluginSubcommands::Install { .. }
+        | PluginSubcommands::Uninstall { .. }
+        | PluginSubcommands::Update { .. } => Err(crate::error::WasmrunError::feature_disabled(
+            "Managing external plugins",
+            "external-plugins",
+        )),
         PluginSubcommands::Enable { plugin, disable } => {
             if *disable {
                 run_plugin_disable(plugin)
@@ -155,6 +165,7 @@ pub fn run_plugin_list() -> Result<()> {
 //     Ok(())
 // }
 
+#[cfg(feature = "external-plugins")]
 pub fn run_plugin_install(plugin: &str) -> Result<()> {
     let mut manager = PluginManager::new()?;
     println!("🔄 Installing plugin: {plugin}");
@@ -165,6 +176,7 @@ pub fn run_plugin_install(plugin: &str) -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "external-plugins")]
 pub fn run_plugin_uninstall(plugin: &str) -> Result<()> {
     let mut manager = PluginManager::new()?;
     println!("🗑️  Uninstalling plugin: {plugin}");
@@ -175,6 +187,7 @@ pub fn run_plugin_uninstall(plugin: &str) -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "external-plugins")]
 pub fn run_plugin_update(plugin: &str) -> Result<()> {
     let mut manager = PluginManager::new()?;
     println!("🔄 Updating plugin: {plugin}");
@@ -273,6 +286,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "external-plugins")]
     fn test_run_plugin_install_invalid() {
         use std::time::{SystemTime, UNIX_EPOCH};
         let timestamp = SystemTime::now()
@@ -286,6 +300,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "external-plugins")]
     fn test_run_plugin_uninstall_nonexistent() {
         let result = run_plugin_uninstall("nonexistent_plugin_12345");
         // Should succeed (no-op for non-existent plugin)
@@ -293,6 +308,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "external-plugins")]
     fn test_run_plugin_update_nonexistent() {
         let result = run_plugin_update("nonexistent_plugin_12345");
         assert!(result.is_err());
@@ -357,6 +373,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "external-plugins")]
     fn test_plugin_command_error_handling() {
         // Test that plugin commands handle various error conditions gracefully
 
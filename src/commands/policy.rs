@@ -0,0 +1,334 @@
+//! Enforce the import/export and license policies declared in a project's
+//! `wasmrun.toml`
+
+use crate::compiler::licenses::{self, DependencyLicense};
+use crate::config::project::{PolicyConfig, ProjectConfig};
+use crate::error::{Result, WasmError, WasmrunError};
+use crate::runtime::core::module::{ImportKind, Module};
+use crate::ui::progress;
+use std::fs;
+use std::path::Path;
+
+/// A single rule the module breaks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    ForbiddenImport {
+        module: String,
+        name: String,
+        kind: &'static str,
+    },
+    MissingExport(String),
+    ForbiddenExport(String),
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::ForbiddenImport { module, name, kind } => {
+                write!(f, "forbidden import {module}::{name} ({kind})")
+            }
+            PolicyViolation::MissingExport(name) => write!(f, "missing required export {name}"),
+            PolicyViolation::ForbiddenExport(name) => write!(f, "forbidden export {name}"),
+        }
+    }
+}
+
+/// Check a parsed module against `policy`
+pub fn check_policy(module: &Module, policy: &PolicyConfig) -> Vec<PolicyViolation> {
+    let mut violations = Vec::new();
+
+    if let Some(allowed) = &policy.allowed_imports {
+        for import in &module.imports {
+            if !allowed
+                .iter()
+                .any(|p| import_matches(p, &import.module, &import.name))
+            {
+                violations.push(PolicyViolation::ForbiddenImport {
+                    module: import.module.clone(),
+                    name: import.name.clone(),
+                    kind: match import.kind {
+                        ImportKind::Function(_) => "function",
+                        ImportKind::Table(_) => "table",
+                        ImportKind::Memory(_) => "memory",
+                        ImportKind::Global(_) => "global",
+                    },
+                });
+            }
+        }
+    }
+
+    for required in &policy.required_exports {
+        if !module.exports.keys().any(|name| glob_match(required, name)) {
+            violations.push(PolicyViolation::MissingExport(required.clone()));
+        }
+    }
+
+    let mut exports: Vec<&String> = module.exports.keys().collect();
+    exports.sort();
+    for name in exports {
+        if policy.forbidden_exports.iter().any(|p| glob_match(p, name)) {
+            violations.push(PolicyViolation::ForbiddenExport(name.clone()));
+        }
+    }
+
+    violations
+}
+
+/// Check `wasm_path` against the policy in the nearest `wasmrun.toml` at or
+/// above `search_from`. Does nothing when there is no config or it declares
+/// no policy.
+pub fn enforce_project_policy(search_from: &Path, wasm_path: &str) -> Result<()> {
+    let Some((config_path, config)) = ProjectConfig::find(search_from)? else {
+        return Ok(());
+    };
+    if config.policy.is_empty() {
+        return Ok(());
+    }
+
+    let bytes = fs::read(wasm_path)?;
+    let module =
+        Module::parse(&bytes).map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+    let violations = check_policy(&module, &config.policy);
+    let config_display = config_path.display().to_string();
+
+    if violations.is_empty() {
+        println!("🛡️  Module satisfies the import/export policy in {config_display}");
+        return Ok(());
+    }
+
+    print_policy_report(&config_display, &config.policy, &violations);
+    Err(WasmrunError::Wasm(WasmError::PolicyViolation {
+        path: wasm_path.to_string(),
+        config: config_display,
+        count: violations.len(),
+    }))
+}
+
+fn print_policy_report(config_path: &str, policy: &PolicyConfig, violations: &[PolicyViolation]) {
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  🛡️  \x1b[1;31mPolicy violations\x1b[0m \x1b[0;90m({config_path})\x1b[0m\n");
+    for violation in violations {
+        println!("     \x1b[0;31m✗ {violation}\x1b[0m");
+    }
+    if let Some(allowed) = &policy.allowed_imports {
+        let allowed = if allowed.is_empty() {
+            "none".to_string()
+        } else {
+            allowed.join(", ")
+        };
+        println!("\n  💡 \x1b[1;34mAllowed imports:\x1b[0m {allowed}");
+    }
+    println!("\x1b[1;34m╰\x1b[0m\n");
+}
+
+/// Check the licenses of the dependencies of the project whose
+/// `wasmrun.toml` is nearest `search_from` against its `[licenses]` section.
+/// Returns what was scanned, which is nothing when no check is configured.
+pub fn enforce_license_policy(search_from: &Path) -> Result<Vec<DependencyLicense>> {
+    let Some((config_path, config)) = ProjectConfig::find(search_from)? else {
+        return Ok(Vec::new());
+    };
+    if config.licenses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let project = config_path.parent().unwrap_or(Path::new("."));
+
+    let task = progress::spinner("Checking dependency licenses");
+    let deps = licenses::scan(project);
+    let violations = licenses::violations(&deps, &config.licenses);
+    if violations.is_empty() {
+        let counts: Vec<String> = licenses::summary(&deps)
+            .iter()
+            .take(5)
+            .map(|(license, count)| format!("{license} {count}"))
+            .collect();
+        task.finish(format!(
+            "Licenses of {} dependencies allowed ({})",
+            deps.len(),
+            counts.join(", ")
+        ));
+        return Ok(deps);
+    }
+    task.fail();
+
+    let config_display = config_path.display().to_string();
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  📜 \x1b[1;31mDenied licenses\x1b[0m \x1b[0;90m({config_display})\x1b[0m\n");
+    for dep in &violations {
+        println!(
+            "     \x1b[0;31m✗ {} {} ({}): {}\x1b[0m",
+            dep.name,
+            dep.version,
+            dep.ecosystem,
+            dep.license.as_deref().unwrap_or("no license declared")
+        );
+    }
+    println!(
+        "\n  💡 \x1b[1;34mDenied:\x1b[0m {}{}",
+        config.licenses.deny.join(", "),
+        if config.licenses.deny_unknown {
+            " and undeclared licenses"
+        } else {
+            ""
+        }
+    );
+    println!("     Exempt a package with allow_packages under [licenses]");
+    println!("\x1b[1;34m╰\x1b[0m\n");
+    Err(WasmrunError::from(format!(
+        "{} of {} dependencies use licenses denied in {config_display}",
+        violations.len(),
+        deps.len()
+    )))
+}
+
+/// `namespace` matches every import from it; `namespace::name` matches one.
+fn import_matches(pattern: &str, module: &str, name: &str) -> bool {
+    match pattern.split_once("::") {
+        Some((ns, item)) => glob_match(ns, module) && glob_match(item, name),
+        None => glob_match(pattern, module),
+    }
+}
+
+/// Exact match, or prefix match when the pattern ends in `*`
+fn glob_match(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::core::module::{ExportDesc, ExportKind, ImportDesc};
+
+    fn module_with(imports: &[(&str, &str)], exports: &[&str]) -> Module {
+        let mut module = Module::new();
+        for (ns, name) in imports {
+            module.imports.push(ImportDesc {
+                module: ns.to_string(),
+                name: name.to_string(),
+                kind: ImportKind::Function(0),
+            });
+        }
+        for name in exports {
+            module.exports.insert(
+                name.to_string(),
+                ExportDesc {
+                    name: name.to_string(),
+                    kind: ExportKind::Function,
+                    index: 0,
+                },
+            );
+        }
+        module
+    }
+
+    fn wasi_only() -> PolicyConfig {
+        PolicyConfig {
+            allowed_imports: Some(vec!["wasi_snapshot_preview1".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pure_wasi_module_passes() {
+        let module = module_with(
+            &[
+                ("wasi_snapshot_preview1", "fd_write"),
+                ("wasi_snapshot_preview1", "proc_exit"),
+            ],
+            &["_start"],
+        );
+        assert!(check_policy(&module, &wasi_only()).is_empty());
+    }
+
+    #[test]
+    fn test_bindgen_import_is_reported() {
+        let module = module_with(
+            &[
+                ("wasi_snapshot_preview1", "fd_write"),
+                ("__wbindgen_placeholder__", "__wbindgen_describe"),
+            ],
+            &[],
+        );
+        let violations = check_policy(&module, &wasi_only());
+        assert_eq!(
+            violations,
+            vec![PolicyViolation::ForbiddenImport {
+                module: "__wbindgen_placeholder__".to_string(),
+                name: "__wbindgen_describe".to_string(),
+                kind: "function",
+            }]
+        );
+    }
+
+    #[test]
+    fn test_item_and_wildcard_patterns() {
+        let module = module_with(&[("env", "memory"), ("env", "abort"), ("env", "seed")], &[]);
+        let policy = PolicyConfig {
+            allowed_imports: Some(vec!["env::memory".to_string(), "env::a*".to_string()]),
+            ..Default::default()
+        };
+        let violations = check_policy(&module, &policy);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "forbidden import env::seed (function)"
+        );
+    }
+
+    #[test]
+    fn test_empty_allow_list_forbids_everything() {
+        let module = module_with(&[("wasi_snapshot_preview1", "fd_write")], &[]);
+        let policy = PolicyConfig {
+            allowed_imports: Some(vec![]),
+            ..Default::default()
+        };
+        assert_eq!(check_policy(&module, &policy).len(), 1);
+    }
+
+    #[test]
+    fn test_export_rules() {
+        let module = module_with(&[], &["memory", "__internal_debug"]);
+        let policy = PolicyConfig {
+            allowed_imports: None,
+            required_exports: vec!["_start".to_string()],
+            forbidden_exports: vec!["__internal*".to_string()],
+        };
+        let violations = check_policy(&module, &policy);
+        assert_eq!(
+            violations,
+            vec![
+                PolicyViolation::MissingExport("_start".to_string()),
+                PolicyViolation::ForbiddenExport("__internal_debug".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enforce_without_config_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = dir.path().join("app.wasm");
+        std::fs::write(&wasm, b"\0asm\x01\0\0\0").unwrap();
+        assert!(enforce_project_policy(dir.path(), wasm.to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_enforce_reports_violation_error() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("wasmrun.toml"),
+            "[policy]\nrequired_exports = [\"_start\"]\n",
+        )
+        .unwrap();
+        let wasm = dir.path().join("app.wasm");
+        std::fs::write(&wasm, b"\0asm\x01\0\0\0").unwrap();
+
+        let err = enforce_project_policy(dir.path(), wasm.to_str().unwrap()).unwrap_err();
+        assert!(matches!(
+            err,
+            WasmrunError::Wasm(WasmError::PolicyViolation { count: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! `push` / `pull` commands: distribute modules through OCI registries.
+//!
+//! Everything that reaches the network is behind the `deploy` feature;
+//! without it, URLs and `oci://` references are refused up front.
+
+#[cfg(feature = "deploy")]
+use crate::commands::policy::enforce_license_policy;
+use crate::error::{Result, WasmrunError};
+use crate::remote::cache::{normalize_digest, sha256_digest};
+use crate::remote::is_remote_url;
+#[cfg(feature = "deploy")]
+use crate::remote::{fetch_module, BlobCache, OciClient, OciReference};
+#[cfg(feature = "deploy")]
+use crate::t;
+#[cfg(feature = "deploy")]
+use crate::ui::{print_status, progress};
+#[cfg(feature = "deploy")]
+use crate::utils::{
+    require_clean as require_clean_tree, CommandExecutor, PathResolver, WasmAnalysis,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Handle push command
+#[cfg(feature = "deploy")]
+pub fn handle_push_command(
+    reference: &str,
+    path: &Option<String>,
+    positional_path: &Option<String>,
+    require_clean: bool,
+) -> Result<()> {
+    let reference = OciReference::parse(reference)?;
+    let wasm_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+
+    if Path::new(&wasm_path).is_dir() {
+        return Err(WasmrunError::from(format!(
+            "{wasm_path} is a directory. Build it with `wasmrun compile` and push the resulting .wasm file"
+        )));
+    }
+
+    let analysis = WasmAnalysis::analyze(&wasm_path)?;
+    if !analysis.is_valid {
+        return Err(WasmrunError::invalid_file_format(
+            &wasm_path,
+            "Not a valid WebAssembly module",
+        ));
+    }
+    if require_clean {
+        let dir = Path::new(&wasm_path).parent().unwrap_or(Path::new("."));
+        require_clean_tree(dir, "push")?;
+    }
+    enforce_license_policy(Path::new(&wasm_path))?;
+    let wasm = fs::read(&wasm_path)?;
+    let os = if analysis.is_wasi {
+        "wasip1"
+    } else {
+        "unknown"
+    };
+
+    let task = progress::spinner(format!("Pushing {} to {reference}", analysis.filename));
+    let pushed = match OciClient::new(reference.clone()).push(&wasm, &analysis.filename, os) {
+        Ok(pushed) => {
+            task.finish(format!("Pushed {}", analysis.filename));
+            pushed
+        }
+        Err(e) => {
+            task.fail();
+            return Err(e);
+        }
+    };
+
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  📦 \x1b[1;36mPushed {reference}\x1b[0m\n");
+    println!(
+        "  📄 \x1b[1;34mModule:\x1b[0m \x1b[1;33m{}\x1b[0m ({})",
+        analysis.filename,
+        CommandExecutor::format_file_size(pushed.size)
+    );
+    println!(
+        "  🔒 \x1b[1;34mDigest:\x1b[0m \x1b[0;37m{}\x1b[0m",
+        pushed.manifest_digest
+    );
+    println!(
+        "  🧱 \x1b[1;34mLayer:\x1b[0m \x1b[0;37m{}\x1b[0m",
+        pushed.layer_digest
+    );
+    println!(
+        "\n  💡 Run it anywhere with: \x1b[1;32mwasmrun run {}/{}@{}\x1b[0m",
+        reference.registry, reference.repository, pushed.manifest_digest
+    );
+    println!("\x1b[1;34m╰\x1b[0m\n");
+    Ok(())
+}
+
+/// Handle pull command
+#[cfg(feature = "deploy")]
+pub fn handle_pull_command(reference: &str, output: &Option<String>) -> Result<()> {
+    let reference = OciReference::parse(reference)?;
+    print_status(&format!("Pulling {reference}..."));
+    let (pulled_path, digest) = pull_to_cache(&reference)?;
+
+    let destination = match output {
+        Some(out) if Path::new(out).is_dir() => Path::new(out).join(reference.module_filename()),
+        Some(out) => PathBuf::from(out),
+        None => PathBuf::from(reference.module_filename()),
+    };
+    fs::copy(&pulled_path, &destination).map_err(|e| {
+        WasmrunError::add_context(format!("Writing module to {}", destination.display()), e)
+    })?;
+
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  📥 \x1b[1;36mPulled {reference}\x1b[0m\n");
+    println!(
+        "  📄 \x1b[1;34mSaved to:\x1b[0m \x1b[1;33m{}\x1b[0m",
+        destination.display()
+    );
+    println!("  🔒 \x1b[1;34mDigest:\x1b[0m \x1b[0;37m{digest}\x1b[0m");
+    println!("\x1b[1;34m╰\x1b[0m\n");
+    Ok(())
+}
+
+/// Resolve `reference` into the local blob cache, returning the cached
+/// module path and the manifest digest it was resolved to.
+#[cfg(feature = "deploy")]
+pub fn pull_to_cache(reference: &OciReference) -> Result<(PathBuf, String)> {
+    let cache = BlobCache::new()?;
+    let pulled = OciClient::new(reference.clone()).pull(&cache)?;
+
+    if pulled.from_cache {
+        println!("   ✓ Using cached module ({})", pulled.layer_digest);
+    } else {
+        println!(
+            "   ✓ Downloaded {} ({})",
+            pulled.layer_digest,
+            CommandExecutor::format_file_size(pulled.size)
+        );
+    }
+    Ok((pulled.path, pulled.manifest_digest))
+}
+
+/// Download a module URL into the local blob cache, returning the cached
+/// module path. With `sha256` the download is verified and later runs are
+/// served from the cache.
+#[cfg(feature = "deploy")]
+pub fn fetch_url_to_cache(url: &str, sha256: Option<&str>) -> Result<PathBuf> {
+    let cache = BlobCache::new()?;
+    let fetched = fetch_module(url, sha256, &cache)?;
+
+    if fetched.from_cache {
+        println!("   ✓ Using cached module ({})", fetched.digest);
+    } else {
+        println!(
+            "   ✓ Downloaded {} ({})",
+            fetched.digest,
+            CommandExecutor::format_file_size(fetched.size)
+        );
+        if sha256.is_none() {
+            let hex = fetched.digest.trim_start_matches("sha256:");
+            println!("   ⚠️  No --sha256 given, so the download was not verified");
+            println!("   💡 Pin it with: --sha256 {hex}");
+        }
+    }
+    Ok(fetched.path)
+}
+
+/// Fetch `input` into the blob cache when it's a module URL or a registry
+/// reference, returning the cached path; `None` means it's a local path.
+/// `sha256` is checked against whatever was fetched.
+#[cfg(feature = "deploy")]
+pub fn fetch_remote_module(input: &str, sha256: Option<&str>) -> Result<Option<PathBuf>> {
+    if is_remote_url(input) {
+        println!("{}", t!("run.fetching", source = input));
+        return fetch_url_to_cache(input, sha256).map(Some);
+    }
+    if OciReference::is_reference(input) {
+        let reference = OciReference::parse(input)?;
+        println!("{}", t!("run.fetching", source = reference));
+        let (cached_path, _) = pull_to_cache(&reference)?;
+        if let Some(expected) = sha256 {
+            verify_module_sha256(&cached_path, expected)?;
+        }
+        return Ok(Some(cached_path));
+    }
+    Ok(None)
+}
+
+/// Without `deploy` only local paths run, so a URL or an explicit `oci://`
+/// reference is an error rather than a confusing missing file.
+#[cfg(not(feature = "deploy"))]
+pub fn fetch_remote_module(input: &str, _sha256: Option<&str>) -> Result<Option<PathBuf>> {
+    if is_remote_url(input) || input.starts_with("oci://") {
+        return Err(WasmrunError::feature_disabled(
+            format!("Running {input}"),
+            "deploy",
+        ));
+    }
+    Ok(None)
+}
+
+/// Check a module on disk against an expected sha256 digest.
+pub fn verify_module_sha256(path: &Path, expected: &str) -> Result<()> {
+    let expected = normalize_digest(expected)?;
+    let actual = sha256_digest(&fs::read(path)?);
+    if actual != expected {
+        return Err(WasmrunError::from(format!(
+            "Checksum mismatch for {}: expected {expected}, got {actual}",
+            path.display()
+        )));
+    }
+    println!("   ✓ Checksum verified ({actual})");
+    Ok(())
+}
@@ -0,0 +1,476 @@
+//! `wasmrun repro`: build the project twice and compare what came out byte
+//! for byte. When the builds differ, each differing artifact is checked for
+//! the usual culprits (build paths, timestamps, debug sections) and the
+//! report says how to remove them for the project's toolchain, so a team
+//! can trust the hash of a module before publishing it.
+
+use super::compile::{run_build, select_builder};
+use crate::compiler::artifacts::BuildManifest;
+use crate::compiler::builder::{
+    BuildConfig, CleanKind, OptimizationLevel, OutputNaming, TargetType, WasmBuilder,
+};
+use crate::error::{Result, WasmrunError};
+use crate::utils::{section_spans, PathResolver};
+use regex::bytes::Regex;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Timestamps are reported at most this many per artifact
+const MAX_TIMESTAMPS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct ReproOptions {
+    pub optimization: OptimizationLevel,
+    pub language: Option<String>,
+    /// Build the second time from a copy of the sources in a temp directory
+    pub isolated: bool,
+    /// Leave both builds on disk
+    pub keep: bool,
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ReproReport {
+    project: String,
+    language: String,
+    isolated: bool,
+    reproducible: bool,
+    artifacts: Vec<Comparison>,
+}
+
+#[derive(Debug, Serialize)]
+struct Comparison {
+    path: String,
+    first: Option<String>,
+    second: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    difference: Option<Difference>,
+}
+
+#[derive(Debug, Serialize)]
+struct Difference {
+    /// Offset of the first byte that differs
+    offset: usize,
+    /// Sections whose contents differ, for modules
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sections: Vec<String>,
+    causes: Vec<Cause>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CauseKind {
+    BuildPath,
+    Timestamp,
+    DebugInfo,
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+struct Cause {
+    kind: CauseKind,
+    detail: String,
+    fix: String,
+}
+
+/// Handle repro command
+pub fn handle_repro_command(
+    path: &Option<String>,
+    positional_path: &Option<String>,
+    options: &ReproOptions,
+) -> Result<()> {
+    let project_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+    PathResolver::validate_directory_exists(&project_path)?;
+    let builder = select_builder(&project_path, options.language.as_deref())?;
+
+    let work = std::env::temp_dir().join(format!("wasmrun_repro_{}", std::process::id()));
+    let report = compare_builds(builder.as_ref(), &project_path, &work, options);
+    if options.keep {
+        println!("📁 Builds kept in {}", work.display());
+    } else {
+        let _ = fs::remove_dir_all(&work);
+    }
+    let report = report?;
+
+    if options.json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| WasmrunError::from(format!("Failed to write report: {e}")))?;
+        println!("{json}");
+    } else {
+        print_report(&report);
+    }
+
+    if report.reproducible {
+        Ok(())
+    } else {
+        let differing = report
+            .artifacts
+            .iter()
+            .filter(|a| a.first != a.second)
+            .count();
+        Err(WasmrunError::from(format!(
+            "Build is not reproducible: {differing} of {} artifact(s) differ",
+            report.artifacts.len()
+        )))
+    }
+}
+
+/// Build into `work/a` and `work/b` and compare the two
+fn compare_builds(
+    builder: &dyn WasmBuilder,
+    project_path: &str,
+    work: &Path,
+    options: &ReproOptions,
+) -> Result<ReproReport> {
+    let _ = fs::remove_dir_all(work);
+    let second_source = if options.isolated {
+        let source = work.join("src");
+        let skip: Vec<PathBuf> = builder
+            .clean_targets(project_path)
+            .into_iter()
+            .filter(|target| target.kind == CleanKind::Artifact)
+            .map(|target| target.path)
+            .collect();
+        copy_sources(Path::new(project_path), &source, &skip).map_err(|e| {
+            WasmrunError::add_context(format!("Copying sources to {}", source.display()), e)
+        })?;
+        source.to_string_lossy().to_string()
+    } else {
+        project_path.to_string()
+    };
+
+    let first = build_into(builder, project_path, &work.join("a"), options)?;
+    let second = build_into(builder, &second_source, &work.join("b"), options)?;
+
+    // Paths that differ between the two builds, so embedding one shows up
+    // as a difference
+    let mut paths = vec![first.1.clone(), second.1.clone()];
+    if options.isolated {
+        paths.extend([
+            canonical(Path::new(project_path)),
+            canonical(Path::new(&second_source)),
+        ]);
+    }
+
+    let mut names: BTreeSet<&str> = BTreeSet::new();
+    names.extend(first.0.artifacts.iter().map(|a| a.path.as_str()));
+    names.extend(second.0.artifacts.iter().map(|a| a.path.as_str()));
+
+    let mut artifacts = Vec::new();
+    for name in names {
+        let a = first.0.artifacts.iter().find(|a| a.path == name);
+        let b = second.0.artifacts.iter().find(|a| a.path == name);
+        let mut comparison = Comparison {
+            path: name.to_string(),
+            first: a.map(|a| a.sha256.clone()),
+            second: b.map(|b| b.sha256.clone()),
+            difference: None,
+        };
+        if a.is_some() && b.is_some() && comparison.first != comparison.second {
+            let read = |dir: &str| {
+                let file = Path::new(dir).join(name);
+                fs::read(&file).map_err(|e| {
+                    WasmrunError::add_context(format!("Reading {}", file.display()), e)
+                })
+            };
+            comparison.difference = Some(explain(
+                name,
+                &read(&first.1)?,
+                &read(&second.1)?,
+                &paths,
+                builder.language_name(),
+            ));
+        }
+        artifacts.push(comparison);
+    }
+
+    Ok(ReproReport {
+        project: project_path.to_string(),
+        language: builder.language_name().to_string(),
+        isolated: options.isolated,
+        reproducible: !artifacts.is_empty() && artifacts.iter().all(|a| a.first == a.second),
+        artifacts,
+    })
+}
+
+/// One build, with the manifest of what it produced and its output directory
+fn build_into(
+    builder: &dyn WasmBuilder,
+    project_path: &str,
+    output_dir: &Path,
+    options: &ReproOptions,
+) -> Result<(BuildManifest, String)> {
+    fs::create_dir_all(output_dir)
+        .map_err(|e| WasmrunError::add_context(format!("Creating {}", output_dir.display()), e))?;
+    let config = BuildConfig {
+        project_path: project_path.to_string(),
+        output_dir: canonical(output_dir),
+        verbose: false,
+        optimization_level: options.optimization.clone(),
+        watch: false,
+        target_type: TargetType::Standard,
+        naming: OutputNaming::default(),
+    };
+    let result = run_build(builder, &config)?;
+    let manifest = BuildManifest::from_build(&config, &result, builder.language_name())
+        .map_err(|e| WasmrunError::add_context("Hashing build output", e))?;
+    Ok((manifest, config.output_dir))
+}
+
+fn canonical(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Copy a project, leaving out version control and what its builds produce
+fn copy_sources(from: &Path, to: &Path, skip: &[PathBuf]) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name() == ".git" || skip.iter().any(|s| s == &path) {
+            continue;
+        }
+        let dest = to.join(entry.file_name());
+        if path.is_dir() {
+            // Symlinked directories can loop; builds rarely need them
+            if !entry.file_type()?.is_symlink() {
+                copy_sources(&path, &dest, skip)?;
+            }
+        } else {
+            fs::copy(&path, &dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Where two builds of the same artifact differ, and the likely reasons
+fn explain(name: &str, a: &[u8], b: &[u8], paths: &[String], language: &str) -> Difference {
+    let offset = a
+        .iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .unwrap_or(a.len().min(b.len()));
+    let sections = if name.ends_with(".wasm") {
+        differing_sections(a, b)
+    } else {
+        Vec::new()
+    };
+
+    let mut causes = Vec::new();
+    let embedded: Vec<&str> = paths
+        .iter()
+        .filter(|path| !path.is_empty())
+        .filter(|path| contains(a, path.as_bytes()) || contains(b, path.as_bytes()))
+        .map(String::as_str)
+        .collect();
+    if !embedded.is_empty() {
+        causes.push(Cause {
+            kind: CauseKind::BuildPath,
+            detail: format!("Embeds the build directory: {}", embedded.join(", ")),
+            fix: path_fix(language).to_string(),
+        });
+    }
+
+    let (ta, tb) = (timestamps(a), timestamps(b));
+    let changed: Vec<String> = ta
+        .symmetric_difference(&tb)
+        .take(MAX_TIMESTAMPS)
+        .cloned()
+        .collect();
+    if !changed.is_empty() {
+        causes.push(Cause {
+            kind: CauseKind::Timestamp,
+            detail: format!("Contains the build time: {}", changed.join(", ")),
+            fix: "Read the time from SOURCE_DATE_EPOCH instead of the clock (build scripts, \
+                  __DATE__/__TIME__ macros), or leave it out of the output"
+                .to_string(),
+        });
+    }
+
+    if causes.is_empty() && !sections.is_empty() && sections.iter().all(|s| is_custom(a, s)) {
+        causes.push(Cause {
+            kind: CauseKind::DebugInfo,
+            detail: format!("Only custom sections differ: {}", sections.join(", ")),
+            fix: "Strip debug info and the producers section from release builds, \
+                  e.g. with `wasm-opt --strip-debug --strip-producers`"
+                .to_string(),
+        });
+    }
+
+    if causes.is_empty() {
+        causes.push(Cause {
+            kind: CauseKind::Unknown,
+            detail: "No paths or timestamps found in the differing bytes".to_string(),
+            fix: unknown_fix(language).to_string(),
+        });
+    }
+
+    Difference {
+        offset,
+        sections,
+        causes,
+    }
+}
+
+/// Names of the sections whose contents differ, or every section when the
+/// two modules don't have the same layout
+fn differing_sections(a: &[u8], b: &[u8]) -> Vec<String> {
+    let (sa, sb) = (section_spans(a), section_spans(b));
+    let same_layout = sa.len() == sb.len()
+        && sa
+            .iter()
+            .zip(&sb)
+            .all(|(x, y)| x.id == y.id && x.name == y.name);
+    if !same_layout {
+        return vec!["section layout".to_string()];
+    }
+    sa.iter()
+        .zip(&sb)
+        .filter(|(x, y)| a[x.range.clone()] != b[y.range.clone()])
+        .map(|(x, _)| x.name.clone())
+        .collect()
+}
+
+fn is_custom(bytes: &[u8], name: &str) -> bool {
+    section_spans(bytes)
+        .iter()
+        .any(|span| span.id == 0 && span.name == name)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Dates and times written as text: ISO 8601, `__DATE__` and `__TIME__`
+fn timestamps(bytes: &[u8]) -> BTreeSet<String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(
+            r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}|(?:Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec) [ 0-3]\d \d{4}|\b\d{2}:\d{2}:\d{2}\b",
+        )
+        .expect("valid timestamp pattern")
+    });
+    pattern
+        .find_iter(bytes)
+        .map(|m| String::from_utf8_lossy(m.as_bytes()).to_string())
+        .collect()
+}
+
+fn path_fix(language: &str) -> &'static str {
+    match language {
+        "Rust" => "Remap the build directory: RUSTFLAGS=\"--remap-path-prefix=$(pwd)=.\"",
+        "C" => "Add -ffile-prefix-map=$(pwd)=. to the compiler flags",
+        "Go" => "Build without debug info (tinygo -no-debug) so source paths aren't embedded",
+        _ => "Remap or strip the build directory from debug info and panic messages",
+    }
+}
+
+fn unknown_fix(language: &str) -> &'static str {
+    match language {
+        "Rust" => {
+            "Try codegen-units = 1 in the release profile, and look for HashMap iteration \
+             order in build scripts and proc macros"
+        }
+        _ => "Look for parallel code generation, hash ordering or random names in the toolchain",
+    }
+}
+
+fn print_report(report: &ReproReport) {
+    println!(
+        "\n🔁 Reproducibility of {} ({})",
+        report.project, report.language
+    );
+    if report.isolated {
+        println!("   Second build from a copy of the sources in a temp directory");
+    }
+    for artifact in &report.artifacts {
+        match (&artifact.first, &artifact.second) {
+            (Some(a), Some(b)) if a == b => {
+                println!("  ✅ {}  {}", artifact.path, &a[..16]);
+            }
+            (Some(_), Some(_)) => {
+                println!("  ❌ {}", artifact.path);
+                if let Some(difference) = &artifact.difference {
+                    println!("     First difference at byte {}", difference.offset);
+                    if !difference.sections.is_empty() {
+                        println!("     Sections: {}", difference.sections.join(", "));
+                    }
+                    for cause in &difference.causes {
+                        println!("     {}", cause.detail);
+                        println!("     💡 {}", cause.fix);
+                    }
+                }
+            }
+            (Some(_), None) => println!("  ❌ {}  only in the first build", artifact.path),
+            (None, _) => println!("  ❌ {}  only in the second build", artifact.path),
+        }
+    }
+    if report.reproducible {
+        println!("\n✅ Both builds are identical");
+    } else if !report.isolated {
+        println!("\n💡 Run with --isolated to also catch differences in the build directory");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module with one code section and a custom section
+    fn module(code: &[u8], custom: &[u8]) -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        bytes.extend([10, code.len() as u8]);
+        bytes.extend(code);
+        bytes.extend([0, (custom.len() + 5) as u8, 4]);
+        bytes.extend(b"note");
+        bytes.extend(custom);
+        bytes
+    }
+
+    #[test]
+    fn test_explain_finds_paths_and_timestamps() {
+        let paths = ["/tmp/w/a".to_string(), "/tmp/w/b".to_string()];
+        let a = module(b"\x01", b"/tmp/w/a/lib.rs built 2024-05-01 10:00:00");
+        let b = module(b"\x01", b"/tmp/w/b/lib.rs built 2024-05-01 10:00:07");
+        let difference = explain("app.wasm", &a, &b, &paths, "Rust");
+        assert_eq!(difference.sections, ["note"]);
+        let kinds: Vec<CauseKind> = difference.causes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [CauseKind::BuildPath, CauseKind::Timestamp]);
+        assert!(difference.causes[0].fix.contains("--remap-path-prefix"));
+        assert!(difference.causes[1].detail.contains("10:00:07"));
+    }
+
+    #[test]
+    fn test_explain_without_known_cause() {
+        let a = module(b"\x01", b"x");
+        let debug = explain("app.wasm", &a, &module(b"\x01", b"y"), &[], "C");
+        assert_eq!(debug.causes[0].kind, CauseKind::DebugInfo);
+
+        let code = explain("app.wasm", &a, &module(b"\x02", b"x"), &[], "C");
+        assert_eq!(code.offset, 10);
+        assert_eq!(code.sections, ["Code"]);
+        assert_eq!(code.causes[0].kind, CauseKind::Unknown);
+    }
+
+    #[test]
+    fn test_copy_sources_skips_build_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("app");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::create_dir_all(project.join("target/release")).unwrap();
+        fs::create_dir_all(project.join(".git")).unwrap();
+        fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(project.join("target/release/app.wasm"), "").unwrap();
+
+        let copy = dir.path().join("copy");
+        copy_sources(&project, &copy, &[project.join("target")]).unwrap();
+        assert!(copy.join("src/main.rs").is_file());
+        assert!(!copy.join("target").exists() && !copy.join(".git").exists());
+    }
+}
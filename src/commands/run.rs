@@ -1,12 +1,29 @@
 //! Run command implementation
 
-use crate::compiler::builder::{BuildConfig, OptimizationLevel, TargetType};
-use crate::compiler::{compile_for_execution, detect_project_language};
+use crate::compiler::artifacts::{record_rebuild_cause, write_build_manifest};
+use crate::compiler::builder::{BuildConfig, OptimizationLevel, OutputNaming, TargetType};
+use crate::compiler::retention::BuildHistory;
+use crate::compiler::{
+    choose_project_language, compile_for_execution, detect_project_language, ProjectLanguage,
+};
+use crate::config::manifest::ModuleManifest;
+use crate::config::project::ProjectConfig;
 use crate::error::{Result, WasmrunError};
+use crate::logging::FileChange;
 use crate::plugin::manager::PluginManager;
+use crate::plugin::Plugin;
+use crate::server::{hot, record_build, trigger};
+use crate::t;
+use crate::ui::keys;
 use crate::utils::PathResolver;
-use std::path::Path;
+use crate::watcher::{ChangeKind, PauseGate, PAUSE_POLL};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+use super::registry::{fetch_remote_module, verify_module_sha256};
+use super::runner::{run_with_runner, Runner, RunnerOptions};
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_run_command(
     path: &Option<String>,
     positional_path: &Option<String>,
@@ -15,7 +32,36 @@ pub fn handle_run_command(
     watch: bool,
     verbose: bool,
     serve: bool,
+    sha256: &Option<String>,
+    manifest: &Option<String>,
+    worker: bool,
+    access_log: bool,
+    runner: &Option<String>,
+    dirs: &[String],
+    allow_net: &Option<Option<String>>,
 ) -> Result<()> {
+    if let Some(runner) = runner {
+        if watch {
+            println!("⚠️  --watch is not supported with --runner; running the current build");
+        }
+        let resolved_path = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+        return run_with_runner(
+            Runner::parse(runner)?,
+            &resolved_path,
+            language.as_deref(),
+            sha256.as_deref(),
+            &RunnerOptions::new(dirs, allow_net)?,
+            verbose,
+        );
+    }
+
+    if let Some(manifest) = manifest {
+        if watch {
+            println!("⚠️  --watch is not supported with --manifest; serving the modules as built");
+        }
+        return run_manifest(manifest, port, serve, access_log);
+    }
+
     let resolved_path =
         crate::utils::PathResolver::resolve_input_path(positional_path.clone(), path.clone());
 
@@ -26,9 +72,46 @@ pub fn handle_run_command(
         language.clone(),
         verbose,
         serve,
+        sha256.clone(),
+        worker,
+        access_log,
     )
 }
 
+/// Serve every module listed in a `--manifest` file on one page
+fn run_manifest(manifest_path: &str, port: u16, serve: bool, access_log: bool) -> Result<()> {
+    let manifest = ModuleManifest::load(Path::new(manifest_path))?;
+    let order = manifest.load_order()?;
+
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!(
+        "  🧩 \x1b[1;36mRunning {} modules from {manifest_path}\x1b[0m\n",
+        order.len()
+    );
+    for (i, module) in order.iter().enumerate() {
+        let mount = module
+            .mount
+            .as_deref()
+            .map(|m| format!(" → #{m}"))
+            .unwrap_or_default();
+        println!(
+            "  {}. \x1b[1;33m{}\x1b[0m \x1b[0;37m{}{mount}\x1b[0m",
+            i + 1,
+            module.name,
+            manifest.resolve(module).display()
+        );
+    }
+    println!(
+        "\n  🌐 \x1b[1;34m{}\x1b[0m",
+        crate::server::server_url(port)
+    );
+    println!("\x1b[1;34m╰\x1b[0m\n");
+
+    crate::server::manifest::serve_manifest(&manifest, port, serve, access_log)
+        .map_err(|e| WasmrunError::Server(crate::error::ServerError::startup_failed(port, e)))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn run_project(
     path: String,
     port: Option<u16>,
@@ -36,19 +119,51 @@ pub fn run_project(
     language: Option<String>,
     verbose: bool,
     serve: bool,
+    sha256: Option<String>,
+    worker: bool,
+    access_log: bool,
 ) -> Result<()> {
     let resolved_path = PathResolver::resolve_input_path(Some(path.clone()), None);
 
     if verbose {
-        println!("🔍 Analyzing path: {resolved_path}");
+        println!("{}", t!("run.analyzing", path = resolved_path));
+    }
+
+    if let Some(cached_path) = fetch_remote_module(&resolved_path, sha256.as_deref())? {
+        return run_wasm_file(
+            &cached_path.to_string_lossy(),
+            port,
+            serve,
+            worker,
+            access_log,
+        );
     }
 
     if is_wasm_file(&resolved_path) {
-        return run_wasm_file(&resolved_path, port, serve);
+        if let Some(expected) = &sha256 {
+            verify_module_sha256(Path::new(&resolved_path), expected)?;
+        }
+        return run_wasm_file(&resolved_path, port, serve, worker, access_log);
+    }
+
+    if sha256.is_some() {
+        return Err(WasmrunError::from(
+            "--sha256 applies to .wasm files, URLs and registry references, not project directories"
+                .to_string(),
+        ));
     }
 
     if Path::new(&resolved_path).is_dir() {
-        return run_project_directory(&resolved_path, port, watch, language, verbose, serve);
+        return run_project_directory(
+            &resolved_path,
+            port,
+            watch,
+            language,
+            verbose,
+            serve,
+            worker,
+            access_log,
+        );
     }
 
     Err(WasmrunError::from(format!(
@@ -63,11 +178,17 @@ fn is_wasm_file(path: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn run_wasm_file(wasm_path: &str, port: Option<u16>, serve: bool) -> Result<()> {
-    println!("🎯 Running WASM file: {wasm_path}");
+fn run_wasm_file(
+    wasm_path: &str,
+    port: Option<u16>,
+    serve: bool,
+    worker: bool,
+    access_log: bool,
+) -> Result<()> {
+    println!("{}", t!("run.running_wasm", path = wasm_path));
 
     let server_port = port.unwrap_or(8420);
-    println!("🚀 Starting server on port {server_port}");
+    println!("{}", t!("run.starting_on_port", port = server_port));
 
     let server_config = crate::config::ServerConfig {
         wasm_path: wasm_path.to_string(),
@@ -77,11 +198,14 @@ fn run_wasm_file(wasm_path: &str, port: Option<u16>, serve: bool) -> Result<()>
         project_path: None,
         output_dir: None,
         serve,
+        worker,
+        access_log,
     };
 
     crate::config::run_server(server_config)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_project_directory(
     project_path: &str,
     port: Option<u16>,
@@ -89,22 +213,32 @@ fn run_project_directory(
     language: Option<String>,
     verbose: bool,
     serve: bool,
+    worker: bool,
+    access_log: bool,
 ) -> Result<()> {
     if verbose {
-        println!("🔍 Detecting project type in: {project_path}");
+        println!("{}", t!("run.detecting", path = project_path));
+    }
+
+    let language = choose_project_language(project_path, language.as_deref());
+    if verbose {
+        if let Some(lang) = &language {
+            println!("{}", t!("run.using_language", language = lang));
+        }
     }
 
     // Try plugin-based compilation first
     if let Ok(plugin_manager) = PluginManager::new() {
-        if let Some(plugin) = plugin_manager.find_plugin_for_project(project_path) {
+        if let Some(plugin) = plugin_manager.select_plugin(project_path, language.as_deref()) {
             return run_with_plugin(
-                &plugin_manager,
-                plugin.info().name.clone(),
+                plugin,
                 project_path,
                 port,
                 watch,
                 verbose,
                 serve,
+                worker,
+                access_log,
             );
         }
     }
@@ -112,39 +246,43 @@ fn run_project_directory(
     // Fall back to legacy language detection
     if verbose {
         println!("🔄 No plugin found, using legacy detection...");
-    }
-
-    let detected_language = detect_project_language(project_path);
-
-    if let Some(lang) = language {
-        if verbose {
-            println!("🎯 Using specified language: {lang}");
-        }
-        run_with_language_override(project_path, &lang, port, watch, verbose, serve)
-    } else {
-        if verbose {
-            println!("🎯 Detected language: {detected_language:?}");
+        if language.is_none() {
+            println!(
+                "🎯 Detected language: {:?}",
+                detect_project_language(project_path)
+            );
         }
-        run_with_detected_language(project_path, port, watch, verbose, serve)
     }
+
+    run_legacy(
+        project_path,
+        language.as_deref(),
+        port,
+        watch,
+        verbose,
+        serve,
+        worker,
+        access_log,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_with_plugin(
-    plugin_manager: &PluginManager,
-    plugin_name: String,
+    plugin: &dyn Plugin,
     project_path: &str,
     port: Option<u16>,
     watch: bool,
     verbose: bool,
     serve: bool,
+    worker: bool,
+    access_log: bool,
 ) -> Result<()> {
+    let plugin_name = &plugin.info().name;
     if verbose {
-        println!("🔌 Using plugin: {plugin_name}");
+        println!("{}", t!("run.using_plugin", plugin = plugin_name));
     }
 
-    let builder = plugin_manager
-        .get_builder_for_project(project_path)
-        .ok_or_else(|| WasmrunError::from("Failed to get builder for project"))?;
+    let builder = plugin.get_builder();
 
     // Check dependencies
     let missing_deps = builder.check_dependencies();
@@ -163,57 +301,59 @@ fn run_with_plugin(
     if watch {
         run_with_watch(project_path, &output_dir, port, builder, verbose, serve)
     } else {
-        run_once(project_path, &output_dir, port, builder, verbose, serve)
+        run_once(
+            project_path,
+            &output_dir,
+            port,
+            builder,
+            verbose,
+            serve,
+            worker,
+            access_log,
+        )
     }
 }
 
-fn run_with_language_override(
-    project_path: &str,
-    language: &str,
-    port: Option<u16>,
-    watch: bool,
-    verbose: bool,
-    serve: bool,
-) -> Result<()> {
-    if let Ok(plugin_manager) = PluginManager::new() {
-        if let Some(plugin) = plugin_manager.get_plugin_by_language(language) {
-            return run_with_plugin(
-                &plugin_manager,
-                plugin.info().name.clone(),
-                project_path,
-                port,
-                watch,
-                verbose,
-                serve,
-            );
-        }
-    }
-
-    if verbose {
-        println!("🔄 Plugin not found for language '{language}', using legacy detection");
-    }
-
-    run_with_detected_language(project_path, port, watch, verbose, serve)
+/// Where `run` puts what it builds: its own directory under the system temp
+/// directory, and the dev server's
+pub fn run_build_dirs() -> Vec<PathBuf> {
+    ["wasmrun", "wasmrun_temp"]
+        .map(|name| std::env::temp_dir().join(name))
+        .to_vec()
 }
 
-fn run_with_detected_language(
+#[allow(clippy::too_many_arguments)]
+fn run_legacy(
     project_path: &str,
+    language: Option<&str>,
     port: Option<u16>,
     watch: bool,
     verbose: bool,
     serve: bool,
+    worker: bool,
+    access_log: bool,
 ) -> Result<()> {
     let temp_dir = std::env::temp_dir().join("wasmrun");
     std::fs::create_dir_all(&temp_dir)?;
     let output_dir = temp_dir.to_string_lossy().to_string();
 
     if watch {
-        run_with_watch_legacy(project_path, &output_dir, port, verbose, serve)
+        run_with_watch_legacy(project_path, language, &output_dir, port, verbose, serve)
     } else {
-        run_once_legacy(project_path, &output_dir, port, verbose, serve)
+        run_once_legacy(
+            project_path,
+            language,
+            &output_dir,
+            port,
+            verbose,
+            serve,
+            worker,
+            access_log,
+        )
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_once(
     project_path: &str,
     output_dir: &str,
@@ -221,9 +361,11 @@ fn run_once(
     builder: Box<dyn crate::compiler::builder::WasmBuilder>,
     verbose: bool,
     serve: bool,
+    worker: bool,
+    access_log: bool,
 ) -> Result<()> {
     if verbose {
-        println!("🔧 Building project...");
+        println!("{}", t!("run.building"));
     }
 
     let config = BuildConfig {
@@ -233,13 +375,23 @@ fn run_once(
         verbose,
         watch: false,
         target_type: TargetType::Standard,
+        naming: OutputNaming::default(),
     };
 
-    let result = builder.build(&config).map_err(WasmrunError::Compilation)?;
+    let build_started = Instant::now();
+    let result = builder.build(&config);
+    record_build(
+        builder.language_name(),
+        build_started.elapsed(),
+        result.is_ok(),
+    );
+    let result = result.map_err(WasmrunError::Compilation)?;
+    write_build_manifest(&config, &result, builder.language_name());
+    rebuild_on_trigger(builder, config);
 
     if verbose {
-        println!("✅ Build completed");
-        println!("🚀 Starting server...");
+        println!("{}", t!("run.build_completed"));
+        println!("{}", t!("run.starting"));
     }
 
     let server_port = port.unwrap_or(8420);
@@ -252,11 +404,29 @@ fn run_once(
         project_path: Some(project_path.to_string()),
         output_dir: Some(output_dir.to_string()),
         serve,
+        worker,
+        access_log,
     };
 
     crate::config::run_server(server_config)
 }
 
+/// Let `POST /api/build/trigger` rebuild into the directory being served
+fn rebuild_on_trigger(
+    builder: Box<dyn crate::compiler::builder::WasmBuilder>,
+    config: BuildConfig,
+) {
+    trigger::set_rebuild(move || {
+        let started = Instant::now();
+        let result = builder.build(&config);
+        record_build(builder.language_name(), started.elapsed(), result.is_ok());
+        let result = result.map_err(|e| e.to_string())?;
+        write_build_manifest(&config, &result, builder.language_name());
+        println!("{}", t!("watch.recompiled", file = result.wasm_path));
+        Ok(())
+    });
+}
+
 fn run_with_watch(
     project_path: &str,
     output_dir: &str,
@@ -265,79 +435,204 @@ fn run_with_watch(
     verbose: bool,
     _serve: bool,
 ) -> Result<()> {
-    println!("👀 Watch mode enabled - monitoring for changes...");
+    println!("{}", t!("watch.enabled"));
 
     let server_port = port.unwrap_or(8420);
-
-    // Initial build
-    let config = BuildConfig {
-        project_path: project_path.to_string(),
-        output_dir: output_dir.to_string(),
-        optimization_level: OptimizationLevel::Release,
-        verbose,
-        watch: true,
-        target_type: TargetType::Standard,
+    let mut history = watch_history(project_path, output_dir);
+
+    let build = |history: &mut BuildHistory| -> Result<(PathBuf, String)> {
+        let dir = history.next_build_dir()?;
+        let config = BuildConfig {
+            project_path: project_path.to_string(),
+            output_dir: dir.to_string_lossy().to_string(),
+            optimization_level: OptimizationLevel::Release,
+            verbose,
+            watch: true,
+            target_type: TargetType::Standard,
+            naming: OutputNaming::default(),
+        };
+        match builder.build(&config) {
+            Ok(result) => {
+                write_build_manifest(&config, &result, builder.language_name());
+                Ok((dir, result.js_path.unwrap_or(result.wasm_path)))
+            }
+            Err(e) => {
+                history.discard(&dir);
+                Err(WasmrunError::Compilation(e))
+            }
+        }
     };
 
-    let initial_result = builder.build(&config).map_err(WasmrunError::Compilation)?;
-    let primary_file = initial_result
-        .js_path
-        .as_ref()
-        .unwrap_or(&initial_result.wasm_path);
+    // Initial build
+    let (mut latest, primary_file) = build(&mut history)?;
 
-    println!("✅ Initial build completed");
-    println!("🚀 Server would start on port {server_port} for file: {primary_file}");
-    println!("👀 Watching for changes... (press Ctrl+C to stop)");
+    println!("{}", t!("watch.initial_build"));
+    println!("{}", history.prune().line());
+    println!(
+        "{}",
+        t!("watch.would_start", port = server_port, file = primary_file)
+    );
+    println!("{}", t!("watch.watching"));
 
     // Set up file watcher
     let watcher = crate::watcher::ProjectWatcher::new(project_path)
         .map_err(|e| WasmrunError::from(format!("Failed to create file watcher: {e}")))?;
 
+    let (mut pause, _keys) = pause_gate(&history)?;
+
     loop {
-        if let Some(events_result) = watcher.wait_for_change() {
-            match events_result {
-                Ok(events) => {
-                    if watcher.should_recompile(&events) {
-                        println!("📂 Files changed, recompiling...");
-
-                        // Recompile the project
-                        match builder.build(&config) {
-                            Ok(result) => {
-                                let new_primary_file =
-                                    result.js_path.as_ref().unwrap_or(&result.wasm_path);
-                                println!("✅ Recompilation completed: {new_primary_file}");
-                            }
-                            Err(e) => {
-                                eprintln!("❌ Recompilation failed: {e:?}");
-                                println!("👀 Continuing to watch for changes...");
-                            }
-                        }
+        let events = match watcher.wait_for_change_timeout(PAUSE_POLL) {
+            Some(Ok(events)) => events,
+            Some(Err(errors)) => {
+                eprintln!(
+                    "{}",
+                    t!("watch.watcher_errors", errors = format!("{errors:?}"))
+                );
+                continue;
+            }
+            None => Vec::new(),
+        };
+        let events = pause.pass(events);
+        if events.is_empty() {
+            continue;
+        }
+
+        let swaps = watcher.hot_swaps(&events);
+        let rebuild = watcher.should_recompile(&events)
+            || (!swaps.is_empty() && !hot_swap(project_path, &latest, &swaps));
+        if rebuild {
+            let changes = watcher.changed_files(&events);
+            println!("{}", t!("watch.changed"));
+
+            // Recompile the project
+            match build(&mut history) {
+                Ok((dir, new_primary_file)) => {
+                    println!("{}", t!("watch.recompiled", file = new_primary_file));
+                    annotate_rebuild(&dir, changes);
+                    if let Err(e) = hot::record_reload(&dir) {
+                        eprintln!("⚠️  Failed to announce the rebuild: {e}");
                     }
+                    latest = dir;
+                    println!("{}", history.prune().line());
                 }
-                Err(errors) => {
-                    eprintln!("⚠️ File watcher errors: {errors:?}");
+                Err(e) => {
+                    eprintln!("{}", t!("watch.failed", error = format!("{e:?}")));
+                    println!("{}", t!("watch.continuing"));
                 }
             }
         }
     }
 }
 
+/// Pausing for a watch session, kept beside its builds, with `p` in the
+/// terminal toggling it and `q` quitting while the listener is held
+fn pause_gate(history: &BuildHistory) -> Result<(PauseGate, Option<keys::KeyListener>)> {
+    let gate = PauseGate::new(history.root())
+        .map_err(|e| WasmrunError::add_context("Failed to set up pausing", e))?;
+    let dir = gate.dir().to_path_buf();
+    let listener = keys::listen(move |key| match key {
+        'p' | 'w' => {
+            if let Err(e) = PauseGate::toggle(&dir) {
+                eprintln!("⚠️  Failed to pause or resume watching: {e}");
+            }
+        }
+        'q' => keys::quit(0),
+        _ => {}
+    });
+    if listener.is_some() {
+        println!("{}", t!("watch.pause_hint"));
+    }
+    Ok((gate, listener))
+}
+
+/// Print which edits a rebuild picked up and record them in its manifest,
+/// where the dev server and the browser overlay read them from
+fn annotate_rebuild(build_dir: &Path, changes: Vec<FileChange>) {
+    if changes.is_empty() {
+        return;
+    }
+    let files: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+    println!("{}", t!("watch.rebuilt_due_to", files = files.join(", ")));
+    for change in &changes {
+        let hash = change.sha256.as_deref().map_or("deleted", |h| &h[..12]);
+        println!("   {} {hash}", change.path);
+    }
+    if let Err(e) = record_rebuild_cause(build_dir, &changes) {
+        eprintln!("⚠️  Failed to record the rebuild cause: {e}");
+    }
+}
+
+/// Copy edited stylesheets and assets into the latest build for open pages
+/// to swap in. `false` when they need a rebuild instead.
+fn hot_swap(project_path: &str, build_dir: &Path, swaps: &[(ChangeKind, String)]) -> bool {
+    match hot::swap(Path::new(project_path), build_dir, swaps) {
+        Ok(Some(updates)) => {
+            for update in updates {
+                println!("{}", t!("watch.hot_swapped", file = update.path));
+            }
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            eprintln!("⚠️  Hot swap failed, rebuilding instead: {e}");
+            false
+        }
+    }
+}
+
+/// Numbered build directories under `output_dir`, kept to the project's
+/// `[watch]` limits
+fn watch_history(project_path: &str, output_dir: &str) -> BuildHistory {
+    let policy = ProjectConfig::load(Path::new(project_path))
+        .ok()
+        .flatten()
+        .map(|config| config.watch)
+        .unwrap_or_default();
+    BuildHistory::new(Path::new(output_dir).join("builds"), policy)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_once_legacy(
     project_path: &str,
+    language: Option<&str>,
     output_dir: &str,
     port: Option<u16>,
     verbose: bool,
     serve: bool,
+    worker: bool,
+    access_log: bool,
 ) -> Result<()> {
     if verbose {
         println!("🔧 Compiling project (legacy mode)...");
     }
 
-    let primary_file = compile_for_execution(project_path, output_dir)?;
+    let build_started = Instant::now();
+    let primary_file = compile_for_execution(project_path, output_dir, language);
+    let built_as = language
+        .and_then(ProjectLanguage::from_name)
+        .unwrap_or_else(|| detect_project_language(project_path));
+    record_build(
+        &format!("{built_as:?}"),
+        build_started.elapsed(),
+        primary_file.is_ok(),
+    );
+    let primary_file = primary_file?;
+    {
+        let (project_path, output_dir) = (project_path.to_string(), output_dir.to_string());
+        let language = language.map(str::to_string);
+        trigger::set_rebuild(move || {
+            let started = Instant::now();
+            let file = compile_for_execution(&project_path, &output_dir, language.as_deref());
+            record_build(&format!("{built_as:?}"), started.elapsed(), file.is_ok());
+            let file = file.map_err(|e| e.to_string())?;
+            println!("{}", t!("watch.recompiled", file = file));
+            Ok(())
+        });
+    }
 
     if verbose {
-        println!("✅ Compilation completed");
-        println!("🚀 Starting server...");
+        println!("{}", t!("run.build_completed"));
+        println!("{}", t!("run.starting"));
     }
 
     let server_port = port.unwrap_or(8420);
@@ -350,6 +645,8 @@ fn run_once_legacy(
         project_path: Some(project_path.to_string()),
         output_dir: Some(output_dir.to_string()),
         serve,
+        worker,
+        access_log,
     };
 
     crate::config::run_server(server_config)
@@ -357,6 +654,7 @@ fn run_once_legacy(
 
 fn run_with_watch_legacy(
     project_path: &str,
+    language: Option<&str>,
     output_dir: &str,
     port: Option<u16>,
     _verbose: bool,
@@ -365,39 +663,74 @@ fn run_with_watch_legacy(
     println!("👀 Watch mode enabled (legacy) - monitoring for changes...");
 
     let server_port = port.unwrap_or(8420);
+    let mut history = watch_history(project_path, output_dir);
+
+    let build = |history: &mut BuildHistory| -> Result<(PathBuf, String)> {
+        let dir = history.next_build_dir()?;
+        match compile_for_execution(project_path, &dir.to_string_lossy(), language) {
+            Ok(file) => Ok((dir, file)),
+            Err(e) => {
+                history.discard(&dir);
+                Err(e)
+            }
+        }
+    };
 
     // Initial compilation
-    let initial_file = compile_for_execution(project_path, output_dir)?;
+    let (mut latest, initial_file) = build(&mut history)?;
 
-    println!("✅ Initial compilation completed");
-    println!("🚀 Server would start on port {server_port} for file: {initial_file}");
-    println!("👀 Watching for changes... (press Ctrl+C to stop)");
+    println!("{}", t!("watch.initial_build"));
+    println!("{}", history.prune().line());
+    println!(
+        "{}",
+        t!("watch.would_start", port = server_port, file = initial_file)
+    );
+    println!("{}", t!("watch.watching"));
 
     // Set up file watcher
     let watcher = crate::watcher::ProjectWatcher::new(project_path)
         .map_err(|e| WasmrunError::from(format!("Failed to create file watcher: {e}")))?;
 
+    let (mut pause, _keys) = pause_gate(&history)?;
+
     loop {
-        if let Some(events_result) = watcher.wait_for_change() {
-            match events_result {
-                Ok(events) => {
-                    if watcher.should_recompile(&events) {
-                        println!("📂 Files changed, recompiling...");
-
-                        // Recompile the project
-                        match crate::compiler::compile_for_execution(project_path, output_dir) {
-                            Ok(result_file) => {
-                                println!("✅ Recompilation completed: {result_file}");
-                            }
-                            Err(e) => {
-                                eprintln!("❌ Recompilation failed: {e}");
-                                println!("👀 Continuing to watch for changes...");
-                            }
-                        }
+        let events = match watcher.wait_for_change_timeout(PAUSE_POLL) {
+            Some(Ok(events)) => events,
+            Some(Err(errors)) => {
+                eprintln!(
+                    "{}",
+                    t!("watch.watcher_errors", errors = format!("{errors:?}"))
+                );
+                continue;
+            }
+            None => Vec::new(),
+        };
+        let events = pause.pass(events);
+        if events.is_empty() {
+            continue;
+        }
+
+        let swaps = watcher.hot_swaps(&events);
+        let rebuild = watcher.should_recompile(&events)
+            || (!swaps.is_empty() && !hot_swap(project_path, &latest, &swaps));
+        if rebuild {
+            let changes = watcher.changed_files(&events);
+            println!("{}", t!("watch.changed"));
+
+            // Recompile the project
+            match build(&mut history) {
+                Ok((dir, result_file)) => {
+                    println!("{}", t!("watch.recompiled", file = result_file));
+                    annotate_rebuild(&dir, changes);
+                    if let Err(e) = hot::record_reload(&dir) {
+                        eprintln!("⚠️  Failed to announce the rebuild: {e}");
                     }
+                    latest = dir;
+                    println!("{}", history.prune().line());
                 }
-                Err(errors) => {
-                    eprintln!("⚠️ File watcher errors: {errors:?}");
+                Err(e) => {
+                    eprintln!("{}", t!("watch.failed", error = e));
+                    println!("{}", t!("watch.continuing"));
                 }
             }
         }
@@ -0,0 +1,559 @@
+//! `wasmrun run --runner`: run the built module under a JS runtime instead of
+//! serving it to a browser
+
+use super::registry::{fetch_remote_module, verify_module_sha256};
+use crate::compiler::artifacts::write_build_manifest;
+use crate::compiler::builder::BuildConfig;
+use crate::compiler::{
+    choose_project_language, compile_for_execution, detect_project_language, ProjectLanguage,
+};
+use crate::error::{CompilationError, Result, WasmrunError};
+use crate::plugin::manager::PluginManager;
+use crate::runtime::bindgen::{default_glue_path, glue_kind, GlueKind};
+use crate::runtime::core::module::Module;
+use crate::server::record_build;
+use crate::utils::{MappedFile, SystemUtils};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runner {
+    Node,
+    Deno,
+}
+
+impl Runner {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "node" => Ok(Runner::Node),
+            "deno" => Ok(Runner::Deno),
+            _ => Err(WasmrunError::from(format!(
+                "Unknown runner '{name}' (expected node or deno)"
+            ))),
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            Runner::Node => "node",
+            Runner::Deno => "deno",
+        }
+    }
+
+    /// Arguments that start `script`, with `options` as the runtime's own
+    /// permissions where it has them
+    fn args(self, artifact: &Artifact, options: &RunnerOptions, script: &Path) -> Vec<String> {
+        let mut args = Vec::new();
+        match self {
+            Runner::Node => {
+                if artifact.glue.is_none() {
+                    args.push("--experimental-wasi-unstable-preview1".to_string());
+                }
+            }
+            Runner::Deno => {
+                args.extend(["run".to_string(), "--no-prompt".to_string()]);
+                let mut read = vec![artifact.wasm.clone()];
+                if let Some(dir) = artifact.glue.as_deref().and_then(Path::parent) {
+                    read.push(dir.to_path_buf());
+                }
+                read.extend(options.dirs.iter().map(|dir| dir.host.clone()));
+                args.push(format!("--allow-read={}", join_paths(&read)));
+                let write: Vec<_> = options
+                    .dirs
+                    .iter()
+                    .filter(|dir| !dir.read_only)
+                    .map(|dir| dir.host.clone())
+                    .collect();
+                if !write.is_empty() {
+                    args.push(format!("--allow-write={}", join_paths(&write)));
+                }
+                match &options.allow_net {
+                    Some(hosts) if hosts.is_empty() => args.push("--allow-net".to_string()),
+                    Some(hosts) => args.push(format!("--allow-net={}", hosts.join(","))),
+                    None => {}
+                }
+            }
+        }
+        args.push(script.to_string_lossy().to_string());
+        args
+    }
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Access granted to the module, from `run --dir` and `--allow-net`
+#[derive(Debug, Clone, Default)]
+pub struct RunnerOptions {
+    pub dirs: Vec<DirMapping>,
+    /// Hosts the module may reach; empty allows any. `None` denies the
+    /// network where the runtime can (Deno).
+    pub allow_net: Option<Vec<String>>,
+}
+
+impl RunnerOptions {
+    pub fn new(dirs: &[String], allow_net: &Option<Option<String>>) -> Result<Self> {
+        Ok(Self {
+            dirs: dirs
+                .iter()
+                .map(|spec| DirMapping::parse(spec))
+                .collect::<Result<_>>()?,
+            allow_net: allow_net.as_ref().map(|hosts| {
+                hosts
+                    .iter()
+                    .flat_map(|hosts| hosts.split(','))
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }),
+        })
+    }
+}
+
+/// A host directory the module may use, from `HOST[::GUEST][:ro]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirMapping {
+    pub host: PathBuf,
+    /// Where WASI modules see it; defaults to the host path as given
+    pub guest: String,
+    pub read_only: bool,
+}
+
+impl DirMapping {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (spec, read_only) = match spec.strip_suffix(":ro") {
+            Some(spec) => (spec, true),
+            None => (spec, false),
+        };
+        let (host, guest) = spec.split_once("::").unwrap_or((spec, spec));
+        if host.is_empty() || guest.is_empty() {
+            return Err(WasmrunError::from(format!(
+                "Invalid --dir '{spec}' (expected HOST[::GUEST][:ro])"
+            )));
+        }
+        let host = absolute(Path::new(host))?;
+        if !host.is_dir() {
+            return Err(WasmrunError::directory_not_found(
+                host.display().to_string(),
+            ));
+        }
+        Ok(Self {
+            host,
+            guest: guest.to_string(),
+            read_only,
+        })
+    }
+}
+
+/// A module to run and, when it was built with wasm-bindgen, its JS glue
+#[derive(Debug, Clone, PartialEq)]
+struct Artifact {
+    wasm: PathBuf,
+    glue: Option<PathBuf>,
+}
+
+impl Artifact {
+    /// The artifact for a `.wasm` or glue `.js` file, picking up the glue
+    /// wasm-bindgen writes beside its `_bg.wasm`
+    fn from_file(path: &Path) -> Self {
+        if path.extension().is_some_and(|ext| ext == "js") {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            return Artifact {
+                wasm: path.with_file_name(format!("{stem}_bg.wasm")),
+                glue: Some(path.to_path_buf()),
+            };
+        }
+        let glue = default_glue_path(path);
+        let is_bindgen = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .is_some_and(|stem| stem.ends_with("_bg"));
+        Artifact {
+            wasm: path.to_path_buf(),
+            glue: (is_bindgen && glue.exists()).then_some(glue),
+        }
+    }
+}
+
+/// Build `path` if it's a project, then run the result under `runner`,
+/// streaming its output
+pub fn run_with_runner(
+    runner: Runner,
+    path: &str,
+    language: Option<&str>,
+    sha256: Option<&str>,
+    options: &RunnerOptions,
+    verbose: bool,
+) -> Result<()> {
+    let program = runner.program();
+    if !SystemUtils::is_tool_available(program) {
+        return Err(WasmrunError::missing_tools(vec![program.to_string()]));
+    }
+
+    let artifact = resolve_artifact(path, language, sha256, verbose)?;
+    let script_dir = tempfile::tempdir()?;
+    let script = write_bootstrap(runner, &artifact, options, script_dir.path())?;
+
+    println!(
+        "🎯 Running {} under {program}",
+        artifact.glue.as_deref().unwrap_or(&artifact.wasm).display()
+    );
+    let mut command = Command::new(program);
+    command.args(runner.args(&artifact, options, &script));
+    stream_output(program, command)
+}
+
+fn resolve_artifact(
+    path: &str,
+    language: Option<&str>,
+    sha256: Option<&str>,
+    verbose: bool,
+) -> Result<Artifact> {
+    if let Some(cached_path) = fetch_remote_module(path, sha256)? {
+        return Ok(Artifact::from_file(&cached_path));
+    }
+
+    let path = Path::new(path);
+    if path.is_dir() {
+        if sha256.is_some() {
+            return Err(WasmrunError::from(
+                "--sha256 applies to .wasm files, URLs and registry references, not project directories"
+                    .to_string(),
+            ));
+        }
+        return build_project(path, language, verbose);
+    }
+    if !path.exists() {
+        return Err(WasmrunError::file_not_found(path.display().to_string()));
+    }
+    if let Some(expected) = sha256 {
+        verify_module_sha256(path, expected)?;
+    }
+    Ok(Artifact::from_file(path))
+}
+
+fn build_project(project_path: &Path, language: Option<&str>, verbose: bool) -> Result<Artifact> {
+    let project = project_path.to_string_lossy().to_string();
+    let output_dir = std::env::temp_dir().join("wasmrun");
+    fs::create_dir_all(&output_dir)?;
+    let output = output_dir.to_string_lossy().to_string();
+
+    println!("🔧 Building {project}...");
+    let language = choose_project_language(&project, language);
+    let plugin_manager = PluginManager::new().ok();
+    let plugin = plugin_manager
+        .as_ref()
+        .and_then(|manager| manager.select_plugin(&project, language.as_deref()));
+
+    let build_started = Instant::now();
+    let Some(plugin) = plugin else {
+        let primary_file = compile_for_execution(&project, &output, language.as_deref());
+        let built_as = language
+            .as_deref()
+            .and_then(ProjectLanguage::from_name)
+            .unwrap_or_else(|| detect_project_language(&project));
+        record_build(
+            &format!("{built_as:?}"),
+            build_started.elapsed(),
+            primary_file.is_ok(),
+        );
+        return Ok(Artifact::from_file(Path::new(&primary_file?)));
+    };
+
+    let builder = plugin.get_builder();
+    let missing_deps = builder.check_dependencies();
+    if !missing_deps.is_empty() {
+        return Err(WasmrunError::missing_tools(missing_deps));
+    }
+    let mut config = BuildConfig::with_defaults(project, output);
+    config.verbose = verbose;
+    let result = builder.build(&config);
+    record_build(
+        builder.language_name(),
+        build_started.elapsed(),
+        result.is_ok(),
+    );
+    let result = result.map_err(WasmrunError::Compilation)?;
+    write_build_manifest(&config, &result, builder.language_name());
+    Ok(Artifact {
+        wasm: PathBuf::from(result.wasm_path),
+        glue: result.js_path.map(PathBuf::from),
+    })
+}
+
+/// Write the script the runner starts with into `dir`. Both runtimes
+/// understand the same `node:` imports, so only plain modules differ.
+fn write_bootstrap(
+    runner: Runner,
+    artifact: &Artifact,
+    options: &RunnerOptions,
+    dir: &Path,
+) -> Result<PathBuf> {
+    let wasm = js_string(&absolute(&artifact.wasm)?);
+    let Some(glue_path) = &artifact.glue else {
+        let (name, bootstrap) = match runner {
+            Runner::Node => (
+                "main.cjs",
+                wasi_bootstrap(&wasm, &artifact.wasm, &options.dirs),
+            ),
+            Runner::Deno => {
+                if imports_wasi(&artifact.wasm)? {
+                    return Err(WasmrunError::from(
+                        "Deno has no WASI support; run WASI modules with --runner node".to_string(),
+                    ));
+                }
+                ("main.mjs", plain_bootstrap(&wasm))
+            }
+        };
+        let script = dir.join(name);
+        fs::write(&script, bootstrap)?;
+        return Ok(script);
+    };
+
+    let source = fs::read_to_string(glue_path)?;
+    match glue_kind(&source).map_err(WasmrunError::from)? {
+        GlueKind::CommonJs => {
+            let script = dir.join("main.mjs");
+            let glue = js_string(&absolute(glue_path)?);
+            fs::write(
+                &script,
+                format!(
+                    "import {{ createRequire }} from \"node:module\";\n\ncreateRequire(import.meta.url)({glue});\n"
+                ),
+            )?;
+            Ok(script)
+        }
+        GlueKind::EsModule { init_takes_object } => {
+            // The glue is a `.js` ES module with no `package.json` saying so,
+            // so Node only loads it from a copy named `.mjs`
+            fs::write(dir.join("glue.mjs"), source)?;
+            let module = format!("readFileSync({wasm})");
+            let init = if init_takes_object {
+                format!("{{ module: {module} }}")
+            } else {
+                module
+            };
+            let script = dir.join("main.mjs");
+            fs::write(
+                &script,
+                format!(
+                    "import {{ readFileSync }} from \"node:fs\";\nimport {{ initSync }} from \"./glue.mjs\";\n\ninitSync({init});\n"
+                ),
+            )?;
+            Ok(script)
+        }
+    }
+}
+
+fn imports_wasi(wasm_path: &Path) -> Result<bool> {
+    // Only the imports are needed, so function bodies are never decoded
+    let module = Module::parse_headers(&MappedFile::open(wasm_path)?)
+        .map_err(|e| WasmrunError::invalid_file_format(wasm_path.display().to_string(), e))?;
+    Ok(module
+        .imports
+        .iter()
+        .any(|import| import.module.starts_with("wasi_")))
+}
+
+/// Runs `_start` with WASI, or calls `main` and prints what it returns.
+/// `dirs` are preopened, or the current directory as `.` if there are none.
+fn wasi_bootstrap(wasm: &str, wasm_path: &Path, dirs: &[DirMapping]) -> String {
+    let program_name = js_string(&wasm_path.file_name().map(PathBuf::from).unwrap_or_default());
+    let preopens = if dirs.is_empty() {
+        "{ \".\": process.cwd() }".to_string()
+    } else {
+        let entries: Vec<_> = dirs
+            .iter()
+            .map(|dir| {
+                format!(
+                    "{}: {}",
+                    serde_json::to_string(&dir.guest).unwrap_or_default(),
+                    js_string(&dir.host)
+                )
+            })
+            .collect();
+        format!("{{ {} }}", entries.join(", "))
+    };
+    format!(
+        r#"const {{ readFileSync }} = require("node:fs");
+const {{ WASI }} = require("node:wasi");
+
+const wasmModule = new WebAssembly.Module(readFileSync({wasm}));
+const usesWasi = WebAssembly.Module.imports(wasmModule).some((i) => i.module.startsWith("wasi_"));
+const wasi = new WASI({{
+  version: "preview1",
+  args: [{program_name}],
+  env: process.env,
+  preopens: {preopens},
+  returnOnExit: true,
+}});
+const instance = new WebAssembly.Instance(wasmModule, usesWasi ? wasi.getImportObject() : {{}});
+const {{ _start, _initialize, main }} = instance.exports;
+if (_start) {{
+  process.exitCode = usesWasi ? wasi.start(instance) : (_start(), 0);
+}} else if (main) {{
+  if (usesWasi && _initialize) wasi.initialize(instance);
+  const result = main();
+  if (result !== undefined) console.log(result);
+}} else {{
+  throw new Error("the module exports no _start or main function");
+}}
+"#
+    )
+}
+
+/// Runs `_start`, or calls `main` and prints what it returns, with no imports
+fn plain_bootstrap(wasm: &str) -> String {
+    format!(
+        r#"import {{ readFileSync }} from "node:fs";
+
+const wasmModule = new WebAssembly.Module(readFileSync({wasm}));
+const {{ _start, main }} = new WebAssembly.Instance(wasmModule, {{}}).exports;
+if (_start) {{
+  _start();
+}} else if (main) {{
+  const result = main();
+  if (result !== undefined) console.log(result);
+}} else {{
+  throw new Error("the module exports no _start or main function");
+}}
+"#
+    )
+}
+
+fn absolute(path: &Path) -> Result<PathBuf> {
+    path.canonicalize()
+        .map_err(|_| WasmrunError::file_not_found(path.display().to_string()))
+}
+
+/// `path` as a JS string literal
+fn js_string(path: &Path) -> String {
+    serde_json::to_string(&path.to_string_lossy()).unwrap_or_default()
+}
+
+/// Run `command`, relaying each line it prints tagged with `program`
+fn stream_output(program: &str, mut command: Command) -> Result<()> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| tool_failed(program, format!("failed to start: {e}")))?;
+
+    let tag = format!("\x1b[0;37m[{program}]\x1b[0m");
+    let relay = |stream: Box<dyn Read + Send>, to_stderr: bool| {
+        let tag = tag.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(|line| line.ok()) {
+                if to_stderr {
+                    eprintln!("{tag} {line}");
+                } else {
+                    println!("{tag} {line}");
+                }
+            }
+        })
+    };
+    let relays = [
+        child.stdout.take().map(|s| relay(Box::new(s), false)),
+        child.stderr.take().map(|s| relay(Box::new(s), true)),
+    ];
+    let status = child.wait()?;
+    for relay in relays.into_iter().flatten() {
+        let _ = relay.join();
+    }
+
+    if status.success() {
+        Ok(())
+    } else {
+        let reason = match status.code() {
+            Some(code) => format!("exited with code {code}"),
+            None => "was killed by a signal".to_string(),
+        };
+        Err(tool_failed(program, reason))
+    }
+}
+
+fn tool_failed(program: &str, reason: String) -> WasmrunError {
+    WasmrunError::Compilation(CompilationError::ToolExecutionFailed {
+        tool: program.to_string(),
+        reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_and_bootstrap() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = dir.path().join("app_bg.wasm");
+        let glue = dir.path().join("app.js");
+        fs::write(&wasm, b"\0asm\x01\0\0\0").unwrap();
+        assert_eq!(
+            Artifact::from_file(&wasm),
+            Artifact {
+                wasm: wasm.clone(),
+                glue: None
+            }
+        );
+
+        fs::write(
+            &glue,
+            "export function greet() {}\nfunction initSync(module) {\n  console.warn('using deprecated parameters for `initSync()`; pass a single object instead')\n}\nexport { initSync };\n",
+        )
+        .unwrap();
+        let artifact = Artifact::from_file(&glue);
+        assert_eq!(artifact, Artifact::from_file(&wasm));
+        assert_eq!(artifact.glue.as_deref(), Some(glue.as_path()));
+
+        let scripts = tempfile::tempdir().unwrap();
+        let script = write_bootstrap(
+            Runner::Node,
+            &artifact,
+            &RunnerOptions::default(),
+            scripts.path(),
+        )
+        .unwrap();
+        assert_eq!(script.file_name().unwrap(), "main.mjs");
+        let main = fs::read_to_string(&script).unwrap();
+        assert!(main.contains("initSync({ module: readFileSync("));
+        assert!(scripts.path().join("glue.mjs").exists());
+
+        // Plain modules run with WASI
+        let plain = Artifact { wasm, glue: None };
+        let options = RunnerOptions::new(
+            &[format!("{}::/data:ro", dir.path().display())],
+            &Some(Some("example.com, localhost:8080".to_string())),
+        )
+        .unwrap();
+        let script = write_bootstrap(Runner::Node, &plain, &options, scripts.path()).unwrap();
+        let main = fs::read_to_string(&script).unwrap();
+        assert!(main.contains("require(\"node:wasi\")"));
+        assert!(main.contains("preopens: { \"/data\": "));
+
+        // Deno gets the same access as permissions
+        let host = dir.path().canonicalize().unwrap();
+        let args = Runner::Deno.args(&plain, &options, &script);
+        assert!(args.contains(&format!(
+            "--allow-read={},{}",
+            host.join("app_bg.wasm").display(),
+            host.display()
+        )));
+        assert!(args.contains(&"--allow-net=example.com,localhost:8080".to_string()));
+        assert!(!args.iter().any(|arg| arg.starts_with("--allow-write")));
+        assert!(DirMapping::parse("missing-dir::/x").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::error::{Result, WasmrunError};
+use crate::rpc::{Api, Peer};
+use std::io::{self, BufReader};
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+
+/// Handle `wasmrun serve-api`: answer JSON-RPC on stdio, or on `listen`
+/// (`HOST:PORT` or `unix:<path>`) for any number of clients
+pub fn handle_serve_api_command(listen: Option<&str>) -> Result<()> {
+    let api = Api::new()?;
+    let Some(listen) = listen else {
+        let result = api.serve(io::stdin().lock(), Peer::new(Box::new(io::stdout())));
+        // A client that goes away takes its dev server with it
+        api.stop_app();
+        return result.map_err(|e| WasmrunError::add_context("Reading from stdin", e));
+    };
+
+    match listen.strip_prefix("unix:") {
+        Some(path) => serve_unix(&api, path),
+        None => serve_tcp(&api, listen),
+    }
+}
+
+/// The API can build and run anything on this machine and has no login, so
+/// TCP clients are only accepted on a loopback address
+fn serve_tcp(api: &Arc<Api>, addr: &str) -> Result<()> {
+    let resolved: Vec<SocketAddr> = addr
+        .to_socket_addrs()
+        .map_err(|e| WasmrunError::add_context(format!("Resolving {addr}"), e))?
+        .collect();
+    if resolved.is_empty() || resolved.iter().any(|a| !a.ip().is_loopback()) {
+        return Err(WasmrunError::from(format!(
+            "Refusing to listen on {addr}: the API has no authentication, so only \
+             loopback addresses (127.0.0.1, [::1], localhost) are allowed. \
+             Use unix:<path> or an SSH tunnel to reach it from elsewhere"
+        )));
+    }
+    let listener = TcpListener::bind(&resolved[..])
+        .map_err(|e| WasmrunError::add_context(format!("Listening on {addr}"), e))?;
+    eprintln!(
+        "🔌 wasmrun API listening on {}",
+        listener
+            .local_addr()
+            .map_or(addr.to_string(), |a| a.to_string())
+    );
+    for stream in listener.incoming().map_while(io::Result::ok) {
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        let api = Arc::clone(api);
+        thread::spawn(move || api.serve(BufReader::new(stream), Peer::new(Box::new(writer))));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve_unix(api: &Arc<Api>, path: &str) -> Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    // A socket nobody answers on is left over from an earlier run
+    crate::utils::remove_stale_socket(std::path::Path::new(path)).map_err(WasmrunError::from)?;
+    let listener = UnixListener::bind(path)
+        .map_err(|e| WasmrunError::add_context(format!("Listening on {path}"), e))?;
+    eprintln!("🔌 wasmrun API listening on unix:{path}");
+    for stream in listener.incoming().map_while(io::Result::ok) {
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        let api = Arc::clone(api);
+        thread::spawn(move || api.serve(BufReader::new(stream), Peer::new(Box::new(writer))));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_unix(_api: &Arc<Api>, path: &str) -> Result<()> {
+    Err(WasmrunError::from(format!(
+        "Unix sockets aren't supported on this platform ({path})"
+    )))
+}
@@ -0,0 +1,404 @@
+//! `snapshot` command: load the served app in a headless browser and save a
+//! screenshot or a short recording, for visual regression checks.
+
+use crate::error::{CompilationError, Result, ServerError, WasmrunError};
+use crate::server::is_server_running;
+use crate::server::utils::is_port_available;
+use crate::ui::print_status;
+use crate::utils::{
+    CommandExecutor, HeadlessBrowser, PathResolver, ReadyCondition, ScreencastFrame,
+};
+use std::fs;
+use std::io::{Read, Seek};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `wasmrun run` gets to compile the project and bind its port
+const SERVER_START_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+pub struct SnapshotOptions {
+    pub path: Option<String>,
+    pub positional_path: Option<String>,
+    /// Capture an already running server instead of starting one
+    pub url: Option<String>,
+    pub url_path: String,
+    pub out: String,
+    pub wait_for: Option<String>,
+    pub ready_event: Option<String>,
+    pub timeout: u64,
+    pub delay: u64,
+    pub width: u32,
+    pub height: u32,
+    pub full_page: bool,
+    pub duration: u64,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SnapshotFormat {
+    Png,
+    Jpeg,
+    Webm,
+}
+
+impl SnapshotFormat {
+    fn from_path(path: &str) -> Result<Self> {
+        let ext = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "png" => Ok(Self::Png),
+            "jpg" | "jpeg" => Ok(Self::Jpeg),
+            "webm" => Ok(Self::Webm),
+            _ => Err(WasmrunError::from(format!(
+                "Unsupported snapshot format for {path}: use .png, .jpg or .webm"
+            ))),
+        }
+    }
+}
+
+/// Handle snapshot command
+pub fn handle_snapshot_command(options: &SnapshotOptions) -> Result<()> {
+    let format = SnapshotFormat::from_path(&options.out)?;
+    let ready = match (&options.wait_for, &options.ready_event) {
+        (Some(selector), _) => ReadyCondition::Selector(selector.clone()),
+        (None, Some(event)) => ReadyCondition::Event(event.clone()),
+        (None, None) => ReadyCondition::Load,
+    };
+
+    let browser_path = find_browser()?;
+    if format == SnapshotFormat::Webm && !CommandExecutor::is_tool_installed("ffmpeg") {
+        return Err(WasmrunError::missing_tools(vec!["ffmpeg".to_string()]));
+    }
+
+    // Keep the server alive until the capture is written
+    let (base_url, _server) = DevServer::start_or_attach(
+        &options.url,
+        &options.path,
+        &options.positional_path,
+        options.port,
+    )?;
+    let url = page_url(&base_url, &options.url_path);
+
+    print_status(&format!(
+        "Loading {url} (waiting for {})...",
+        ready.describe()
+    ));
+    let mut browser = HeadlessBrowser::launch(&browser_path, options.width, options.height)
+        .map_err(browser_error)?;
+    browser
+        .load(&url, &ready, Duration::from_secs(options.timeout))
+        .map_err(browser_error)?;
+    if options.delay > 0 {
+        thread::sleep(Duration::from_millis(options.delay));
+    }
+
+    let detail = match format {
+        SnapshotFormat::Png | SnapshotFormat::Jpeg => {
+            let image_format = if format == SnapshotFormat::Png {
+                "png"
+            } else {
+                "jpeg"
+            };
+            let bytes = browser
+                .screenshot(image_format, options.full_page)
+                .map_err(browser_error)?;
+            write_output(&options.out, &bytes)?;
+            let size = if options.full_page {
+                "full page".to_string()
+            } else {
+                format!("{}x{}", options.width, options.height)
+            };
+            format!(
+                "{size}, {}",
+                CommandExecutor::format_file_size(bytes.len() as u64)
+            )
+        }
+        SnapshotFormat::Webm => {
+            print_status(&format!("Recording for {}s...", options.duration));
+            let duration = Duration::from_secs(options.duration.max(1));
+            let frames = browser.record(duration).map_err(browser_error)?;
+            encode_webm(&frames, duration, &options.out)?;
+            format!("{} frame(s) over {}s", frames.len(), duration.as_secs())
+        }
+    };
+    drop(browser);
+
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  📸 \x1b[1;36mSnapshot saved\x1b[0m\n");
+    println!("  🌐 \x1b[1;34mPage:\x1b[0m \x1b[0;37m{url}\x1b[0m");
+    println!(
+        "  📄 \x1b[1;34mOutput:\x1b[0m \x1b[1;33m{}\x1b[0m ({detail})",
+        options.out
+    );
+    println!("\x1b[1;34m╰\x1b[0m\n");
+    Ok(())
+}
+
+pub(super) fn find_browser() -> Result<PathBuf> {
+    HeadlessBrowser::find_executable().ok_or_else(|| {
+        WasmrunError::from(
+            "No Chrome or Chromium found. Install one or set WASMRUN_CHROME to the browser executable"
+                .to_string(),
+        )
+    })
+}
+
+pub(super) fn browser_error(reason: String) -> WasmrunError {
+    WasmrunError::Compilation(CompilationError::ToolExecutionFailed {
+        tool: "headless browser".to_string(),
+        reason,
+    })
+}
+
+pub(super) fn page_url(base: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+fn write_output(out: &str, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = Path::new(out)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(out, bytes).map_err(|e| WasmrunError::add_context(format!("Writing {out}"), e))
+}
+
+/// A `wasmrun run` child process serving the project on a local port
+pub(super) struct DevServer {
+    process: Child,
+    port: u16,
+}
+
+impl DevServer {
+    /// Base URL to load pages from: `url` when given, otherwise a freshly
+    /// started server for the project, which must be kept alive meanwhile.
+    pub(super) fn start_or_attach(
+        url: &Option<String>,
+        path: &Option<String>,
+        positional_path: &Option<String>,
+        port: Option<u16>,
+    ) -> Result<(String, Option<Self>)> {
+        if let Some(url) = url {
+            return Ok((url.clone(), None));
+        }
+        let project = PathResolver::resolve_input_path(positional_path.clone(), path.clone());
+        let server = Self::start(&project, port)?;
+        Ok((format!("http://127.0.0.1:{}", server.port), Some(server)))
+    }
+
+    fn start(project: &str, port: Option<u16>) -> Result<Self> {
+        // `wasmrun run` replaces whatever server the PID file points at
+        if is_server_running() {
+            return Err(WasmrunError::from(
+                "A wasmrun server is already running. Pass --url to capture it, or stop it with `wasmrun stop`"
+                    .to_string(),
+            ));
+        }
+        let port = match port {
+            Some(port) if !is_port_available(port) => {
+                return Err(WasmrunError::from(format!(
+                    "Port {port} is already in use. Pass --url to capture the server that is running there"
+                )))
+            }
+            Some(port) => port,
+            None => TcpListener::bind("127.0.0.1:0")?.local_addr()?.port(),
+        };
+
+        let log = tempfile::tempfile()?;
+        let process = Command::new(std::env::current_exe()?)
+            .args(["run", project, "--port", &port.to_string()])
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log.try_clone()?)
+            .spawn()?;
+        let mut server = Self { process, port };
+
+        print_status(&format!("Starting server for {project} on port {port}..."));
+        let deadline = Instant::now() + SERVER_START_TIMEOUT;
+        loop {
+            if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return Ok(server);
+            }
+            if let Some(status) = server.process.try_wait()? {
+                return Err(WasmrunError::Server(ServerError::startup_failed(
+                    port,
+                    format!("`wasmrun run` exited with {status}\n{}", log_tail(log)),
+                )));
+            }
+            if Instant::now() >= deadline {
+                return Err(WasmrunError::Server(ServerError::startup_failed(
+                    port,
+                    format!(
+                        "not ready after {}s\n{}",
+                        SERVER_START_TIMEOUT.as_secs(),
+                        log_tail(log)
+                    ),
+                )));
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
+
+impl Drop for DevServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Last lines of the child's output, for startup errors
+fn log_tail(mut log: fs::File) -> String {
+    let mut output = String::new();
+    let _ = log.rewind();
+    let _ = log.read_to_string(&mut output);
+    let lines: Vec<&str> = output.lines().collect();
+    lines[lines.len().saturating_sub(20)..].join("\n")
+}
+
+/// Encode screencast frames with ffmpeg, holding each frame until the next
+/// one was painted so the video keeps the page's real timing.
+fn encode_webm(frames: &[ScreencastFrame], duration: Duration, out: &str) -> Result<()> {
+    if frames.is_empty() {
+        return Err(browser_error(
+            "the page painted no frames while recording".to_string(),
+        ));
+    }
+
+    let dir = tempfile::tempdir()?;
+    for (i, frame) in frames.iter().enumerate() {
+        fs::write(dir.path().join(frame_name(i)), &frame.png)?;
+    }
+    let list = dir.path().join("frames.txt");
+    fs::write(&list, concat_list(frames, duration.as_secs_f64()))?;
+
+    if let Some(parent) = Path::new(out)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let list_arg = list.to_string_lossy();
+    CommandExecutor::execute_command(
+        "ffmpeg",
+        &[
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "concat",
+            "-safe",
+            "0",
+            "-i",
+            &list_arg,
+            "-r",
+            "30",
+            "-c:v",
+            "libvpx-vp9",
+            "-pix_fmt",
+            "yuv420p",
+            out,
+        ],
+        ".",
+        false,
+    )
+    .map_err(WasmrunError::from)
+    .and_then(|output| {
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(WasmrunError::Compilation(
+                CompilationError::ToolExecutionFailed {
+                    tool: "ffmpeg".to_string(),
+                    reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                },
+            ))
+        }
+    })
+}
+
+fn frame_name(index: usize) -> String {
+    format!("frame-{index:05}.png")
+}
+
+/// ffmpeg concat-demuxer script; the last frame fills the rest of the
+/// recording and is listed twice because the demuxer ignores the final
+/// entry's duration.
+fn concat_list(frames: &[ScreencastFrame], total: f64) -> String {
+    let start = frames.first().map(|f| f.timestamp).unwrap_or(0.0);
+    let mut list = String::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let shown = match frames.get(i + 1) {
+            Some(next) => next.timestamp - frame.timestamp,
+            None => total - (frame.timestamp - start),
+        };
+        list.push_str(&format!(
+            "file '{}'\nduration {:.3}\n",
+            frame_name(i),
+            shown.max(1.0 / 30.0)
+        ));
+    }
+    list.push_str(&format!("file '{}'\n", frame_name(frames.len() - 1)));
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_output_extension() {
+        assert_eq!(
+            SnapshotFormat::from_path("shots/home.PNG").unwrap(),
+            SnapshotFormat::Png
+        );
+        assert_eq!(
+            SnapshotFormat::from_path("a.jpeg").unwrap(),
+            SnapshotFormat::Jpeg
+        );
+        assert_eq!(
+            SnapshotFormat::from_path("clip.webm").unwrap(),
+            SnapshotFormat::Webm
+        );
+        assert!(SnapshotFormat::from_path("shot.gif").is_err());
+    }
+
+    #[test]
+    fn test_page_url_joins_paths() {
+        assert_eq!(
+            page_url("http://127.0.0.1:8420", "/"),
+            "http://127.0.0.1:8420/"
+        );
+        assert_eq!(
+            page_url("http://localhost:3000/", "/app?x=1"),
+            "http://localhost:3000/app?x=1"
+        );
+    }
+
+    #[test]
+    fn test_concat_list_keeps_frame_timing() {
+        let frame = |timestamp| ScreencastFrame {
+            timestamp,
+            png: Vec::new(),
+        };
+        let list = concat_list(&[frame(10.0), frame(10.5), frame(11.0)], 3.0);
+        assert_eq!(
+            list,
+            "file 'frame-00000.png'\nduration 0.500\n\
+             file 'frame-00001.png'\nduration 0.500\n\
+             file 'frame-00002.png'\nduration 2.000\n\
+             file 'frame-00002.png'\n"
+        );
+    }
+}
@@ -1,19 +1,20 @@
 use crate::error::Result;
 use crate::server;
+use crate::t;
 use crate::ui::{print_info, print_status, print_success};
 
 /// Handle stop command
 pub fn handle_stop_command() -> Result<()> {
     if !server::is_server_running() {
-        print_info("No Wasmrun server is currently running");
+        print_info(&t!("stop.not_running"));
         return Ok(());
     }
 
-    print_status("Stopping Wasmrun server...");
+    print_status(&t!("stop.stopping"));
 
     match server::stop_existing_server() {
         Ok(()) => {
-            print_success("Wasmrun Server Stopped", "Server terminated successfully");
+            print_success(&t!("stop.stopped_title"), &t!("stop.stopped"));
             Ok(())
         }
         Err(e) => Err(e),
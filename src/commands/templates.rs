@@ -0,0 +1,442 @@
+//! Project templates for `wasmrun init`: the built-in scaffolds, plus
+//! community templates from a git registry.
+//!
+//! A registry is a git repository (or a local directory) with a
+//! `templates.toml` at its root listing templates by name. A template is a
+//! directory in the registry itself or in another repository; both are
+//! cloned into `~/.wasmrun/templates` and reused until `--refresh`.
+
+use crate::config::WasmrunConfig;
+use crate::error::{Result, WasmrunError};
+use crate::utils::{safe_relative, CommandExecutor};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The registry `init` reads when neither `--registry` nor
+/// `WASMRUN_TEMPLATE_REGISTRY` name one
+pub const DEFAULT_TEMPLATE_REGISTRY: &str = "https://github.com/anistark/wasmrun-templates";
+/// The index at the root of a registry
+pub const REGISTRY_INDEX_FILE: &str = "templates.toml";
+
+/// Built-in templates, by name, with their language and a description
+pub const BUILTIN_TEMPLATES: &[(&str, &str, &str)] = &[
+    ("rust", "rust", "wasm-bindgen library with greet and add"),
+    ("go", "go", "TinyGo module with exported functions"),
+    ("c", "c", "C functions built with clang"),
+    ("asc", "asc", "AssemblyScript module"),
+    ("python", "python", "Python script for waspy"),
+];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegistryIndex {
+    #[serde(default, rename = "template")]
+    pub templates: Vec<RegistryTemplate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RegistryTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Language the generated project builds as; written to wasmrun.toml
+    /// when detection would pick another
+    pub language: Option<String>,
+    /// Repository holding the template; the registry's own when unset
+    pub repo: Option<String>,
+    /// Directory of the template within its repository
+    pub subdir: Option<String>,
+    /// Branch or tag to clone
+    pub rev: Option<String>,
+}
+
+impl RegistryIndex {
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content)
+            .map_err(|e| WasmrunError::from(format!("Invalid {REGISTRY_INDEX_FILE}: {e}")))
+    }
+
+    pub fn find(&self, name: &str) -> Option<&RegistryTemplate> {
+        self.templates.iter().find(|template| template.name == name)
+    }
+}
+
+/// A registry checked out locally
+pub struct TemplateRegistry {
+    /// Where the registry came from, as given
+    pub source: String,
+    /// The registry's files on disk
+    pub root: PathBuf,
+    pub index: RegistryIndex,
+}
+
+impl TemplateRegistry {
+    /// The registry named by `--registry`, else `WASMRUN_TEMPLATE_REGISTRY`,
+    /// else the default one
+    pub fn source(registry: Option<&str>) -> String {
+        registry
+            .map(str::to_string)
+            .or_else(|| std::env::var("WASMRUN_TEMPLATE_REGISTRY").ok())
+            .unwrap_or_else(|| DEFAULT_TEMPLATE_REGISTRY.to_string())
+    }
+
+    /// Open `source`, cloning it on first use or when `refresh` is set
+    pub fn open(source: &str, refresh: bool) -> Result<Self> {
+        let root = checkout(source, None, refresh)?;
+        let index_path = root.join(REGISTRY_INDEX_FILE);
+        let content = fs::read_to_string(&index_path).map_err(|e| {
+            WasmrunError::from(format!(
+                "Template registry {source} has no {REGISTRY_INDEX_FILE}: {e}"
+            ))
+        })?;
+        Ok(Self {
+            source: source.to_string(),
+            root,
+            index: RegistryIndex::parse(&content)?,
+        })
+    }
+
+    /// The directory holding `template`'s files, fetching its repository
+    /// if it lives outside the registry. A fetched index can only point at
+    /// remote repositories and at directories inside them; local paths
+    /// are for registries that are local themselves.
+    pub fn template_dir(&self, template: &RegistryTemplate, refresh: bool) -> Result<PathBuf> {
+        let repo_root = match &template.repo {
+            Some(repo) => {
+                if !Path::new(&self.source).is_dir() && !is_remote_source(repo) {
+                    return Err(WasmrunError::from(format!(
+                        "Template '{}' points at {repo}, which isn't a remote repository",
+                        template.name
+                    )));
+                }
+                checkout(repo, template.rev.as_deref(), refresh)?
+            }
+            None => self.root.clone(),
+        };
+        let dir = match &template.subdir {
+            Some(subdir) => repo_root.join(safe_relative(subdir).ok_or_else(|| {
+                WasmrunError::path(format!(
+                    "Template '{}' has subdir {subdir:?}, which isn't inside its repository",
+                    template.name
+                ))
+            })?),
+            None => repo_root.clone(),
+        };
+        if !dir.is_dir() {
+            return Err(WasmrunError::path(format!(
+                "Template '{}' points at {}, which doesn't exist",
+                template.name,
+                dir.display()
+            )));
+        }
+        // A symlink in the repository could still lead out of it
+        let inside = match (dir.canonicalize(), repo_root.canonicalize()) {
+            (Ok(dir), Ok(root)) => dir.starts_with(root),
+            _ => false,
+        };
+        if !inside {
+            return Err(WasmrunError::path(format!(
+                "Template '{}' points outside its repository",
+                template.name
+            )));
+        }
+        Ok(dir)
+    }
+}
+
+/// Where git sources are cloned
+fn cache_dir() -> Result<PathBuf> {
+    Ok(WasmrunConfig::config_dir()?.join("templates"))
+}
+
+/// Whether `source` is a git URL on another machine, rather than a path
+/// or a `file://` URL on this one
+fn is_remote_source(source: &str) -> bool {
+    if ["https://", "http://", "ssh://", "git://"]
+        .iter()
+        .any(|scheme| source.starts_with(scheme))
+    {
+        return true;
+    }
+    // scp-style `user@host:path`
+    match source.split_once(':') {
+        Some((host, _)) => host.contains('@') && !host.contains('/'),
+        None => false,
+    }
+}
+
+/// A local directory as is, or a shallow clone of a git URL under the
+/// template cache, keyed by URL and revision
+pub(super) fn checkout(source: &str, rev: Option<&str>, refresh: bool) -> Result<PathBuf> {
+    let local = Path::new(source);
+    if local.is_dir() {
+        return Ok(local.to_path_buf());
+    }
+    // Both reach git's command line, where a leading `-` is an option
+    if source.starts_with('-') || rev.is_some_and(|rev| rev.starts_with('-')) {
+        return Err(WasmrunError::from(format!(
+            "Refusing to fetch {source}{}: it looks like a git option",
+            rev.map(|rev| format!(" at {rev}")).unwrap_or_default()
+        )));
+    }
+
+    let key = format!("{source}#{}", rev.unwrap_or(""));
+    let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+    let dest = cache_dir()?.join(&hash[..16]);
+    if dest.join(".git").is_dir() && !refresh {
+        return Ok(dest);
+    }
+    if dest.exists() {
+        fs::remove_dir_all(&dest)
+            .map_err(|e| WasmrunError::from(format!("Failed to clear {}: {e}", dest.display())))?;
+    }
+    let parent = dest.parent().unwrap_or(&dest);
+    fs::create_dir_all(parent)
+        .map_err(|e| WasmrunError::from(format!("Failed to create {}: {e}", parent.display())))?;
+
+    println!("📥 Fetching {source}...");
+    let dest_arg = dest.to_string_lossy();
+    let mut args = vec!["clone", "--depth", "1", "--quiet"];
+    if let Some(rev) = rev {
+        args.extend(["--branch", rev]);
+    }
+    args.extend(["--", source, &dest_arg]);
+    let output = CommandExecutor::execute_command("git", &args, &parent.to_string_lossy(), false)?;
+    if !output.status.success() {
+        let _ = fs::remove_dir_all(&dest);
+        return Err(WasmrunError::from(format!(
+            "Failed to clone {source}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(dest)
+}
+
+/// Values substituted for `{{project-name}}` and `{{crate_name}}`
+pub struct TemplateVars {
+    pub project_name: String,
+    pub crate_name: String,
+}
+
+impl TemplateVars {
+    pub fn new(project_name: &str) -> Self {
+        let crate_name = project_name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Self {
+            project_name: project_name.to_string(),
+            crate_name,
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        text.replace("{{project-name}}", &self.project_name)
+            .replace("{{crate_name}}", &self.crate_name)
+    }
+}
+
+/// Copy `template_dir` to `target_dir`, substituting `vars` in file names
+/// and in every file that is UTF-8 text. Returns how many files were
+/// written. The template's `.git`, the registry index and symlinks, which
+/// could point anywhere on this machine, are left out.
+pub fn render(template_dir: &Path, target_dir: &Path, vars: &TemplateVars) -> Result<usize> {
+    let mut written = 0;
+    let entries = fs::read_dir(template_dir).map_err(|e| {
+        WasmrunError::from(format!("Failed to read {}: {e}", template_dir.display()))
+    })?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" || name == REGISTRY_INDEX_FILE {
+            continue;
+        }
+        let source = entry.path();
+        let dest = target_dir.join(vars.apply(&name));
+        let file_type = fs::symlink_metadata(&source)
+            .map_err(|e| WasmrunError::from(format!("Failed to read {}: {e}", source.display())))?
+            .file_type();
+        if file_type.is_symlink() {
+            eprintln!("⚠️  Skipping symlink {name} in the template");
+            continue;
+        }
+        if file_type.is_dir() {
+            fs::create_dir_all(&dest).map_err(|e| {
+                WasmrunError::from(format!("Failed to create {}: {e}", dest.display()))
+            })?;
+            written += render(&source, &dest, vars)?;
+            continue;
+        }
+        let bytes = fs::read(&source)
+            .map_err(|e| WasmrunError::from(format!("Failed to read {}: {e}", source.display())))?;
+        let contents = match String::from_utf8(bytes) {
+            Ok(text) => vars.apply(&text).into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+        fs::write(&dest, contents)
+            .map_err(|e| WasmrunError::from(format!("Failed to write {}: {e}", dest.display())))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const INDEX: &str = r#"
+[[template]]
+name = "rust-canvas"
+description = "Canvas drawing with web-sys"
+language = "rust"
+subdir = "rust/canvas"
+
+[[template]]
+name = "zig-hello"
+repo = "https://example.com/zig-templates.git"
+rev = "v1"
+"#;
+
+    #[test]
+    fn test_parse_registry_index() {
+        let index = RegistryIndex::parse(INDEX).unwrap();
+        assert_eq!(index.templates.len(), 2);
+
+        let canvas = index.find("rust-canvas").unwrap();
+        assert_eq!(canvas.language.as_deref(), Some("rust"));
+        assert_eq!(canvas.subdir.as_deref(), Some("rust/canvas"));
+        assert!(canvas.repo.is_none());
+
+        let zig = index.find("zig-hello").unwrap();
+        assert_eq!(zig.rev.as_deref(), Some("v1"));
+        assert_eq!(zig.description, "");
+        assert!(index.find("missing").is_none());
+        assert!(RegistryIndex::parse("").unwrap().templates.is_empty());
+    }
+
+    #[test]
+    fn test_template_vars() {
+        let vars = TemplateVars::new("My-App");
+        assert_eq!(vars.crate_name, "my_app");
+        assert_eq!(
+            vars.apply("name = \"{{project-name}}\" # {{crate_name}}"),
+            "name = \"My-App\" # my_app"
+        );
+    }
+
+    #[test]
+    fn test_local_registry_renders_template() {
+        let registry = tempdir().unwrap();
+        fs::write(registry.path().join(REGISTRY_INDEX_FILE), INDEX).unwrap();
+        let template = registry.path().join("rust/canvas");
+        fs::create_dir_all(template.join("src")).unwrap();
+        fs::write(
+            template.join("Cargo.toml"),
+            "[package]\nname = \"{{project-name}}\"\n",
+        )
+        .unwrap();
+        fs::write(
+            template.join("src/{{crate_name}}.rs"),
+            "// {{project-name}}\n",
+        )
+        .unwrap();
+        fs::write(template.join("logo.bin"), [0xff, 0xfe, b'{', b'{']).unwrap();
+
+        let source = registry.path().to_string_lossy().to_string();
+        let opened = TemplateRegistry::open(&source, false).unwrap();
+        let entry = opened.index.find("rust-canvas").unwrap();
+        let dir = opened.template_dir(entry, false).unwrap();
+
+        let target = tempdir().unwrap();
+        let written = render(&dir, target.path(), &TemplateVars::new("paint-app")).unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(
+            fs::read_to_string(target.path().join("Cargo.toml")).unwrap(),
+            "[package]\nname = \"paint-app\"\n"
+        );
+        assert_eq!(
+            fs::read_to_string(target.path().join("src/paint_app.rs")).unwrap(),
+            "// paint-app\n"
+        );
+        // Binary files are copied untouched
+        assert_eq!(
+            fs::read(target.path().join("logo.bin")).unwrap(),
+            [0xff, 0xfe, b'{', b'{']
+        );
+    }
+
+    #[test]
+    fn test_fetched_index_stays_remote_and_inside_its_repository() {
+        let root = tempdir().unwrap();
+        fs::create_dir_all(root.path().join("rust/canvas")).unwrap();
+        let fetched = TemplateRegistry {
+            source: "https://example.com/templates.git".to_string(),
+            root: root.path().to_path_buf(),
+            index: RegistryIndex::default(),
+        };
+        let template = RegistryTemplate {
+            name: "t".to_string(),
+            description: String::new(),
+            language: None,
+            repo: None,
+            subdir: Some("rust/canvas".to_string()),
+            rev: None,
+        };
+        assert!(fetched.template_dir(&template, false).is_ok());
+        for subdir in ["../..", "/etc"] {
+            let outside = RegistryTemplate {
+                subdir: Some(subdir.to_string()),
+                ..template.clone()
+            };
+            assert!(fetched.template_dir(&outside, false).is_err(), "{subdir}");
+        }
+        let home = std::env::temp_dir().to_string_lossy().to_string();
+        for repo in [home.as_str(), "file:///etc", "--upload-pack=touch x"] {
+            let local = RegistryTemplate {
+                repo: Some(repo.to_string()),
+                subdir: None,
+                ..template.clone()
+            };
+            assert!(fetched.template_dir(&local, false).is_err(), "{repo}");
+        }
+
+        assert!(is_remote_source("https://github.com/a/b"));
+        assert!(is_remote_source("git@github.com:a/b.git"));
+        assert!(!is_remote_source("./templates"));
+        assert!(!is_remote_source("file:///srv/templates"));
+        assert!(checkout("--upload-pack=touch x", None, false).is_err());
+        assert!(checkout("https://example.com/t.git", Some("-x"), false).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_skips_symlinks() {
+        let template = tempdir().unwrap();
+        fs::write(template.path().join("main.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink("/etc/passwd", template.path().join("passwd")).unwrap();
+        std::os::unix::fs::symlink("/etc", template.path().join("etc")).unwrap();
+
+        let target = tempdir().unwrap();
+        let written = render(template.path(), target.path(), &TemplateVars::new("app")).unwrap();
+        assert_eq!(written, 1);
+        assert!(!target.path().join("passwd").exists());
+        assert!(!target.path().join("etc").exists());
+    }
+
+    #[test]
+    fn test_missing_subdir_is_an_error() {
+        let registry = tempdir().unwrap();
+        fs::write(registry.path().join(REGISTRY_INDEX_FILE), INDEX).unwrap();
+        let source = registry.path().to_string_lossy().to_string();
+        let opened = TemplateRegistry::open(&source, false).unwrap();
+        let entry = opened.index.find("rust-canvas").unwrap();
+        assert!(opened.template_dir(entry, false).is_err());
+    }
+}
@@ -0,0 +1,554 @@
+//! Tree-shaking report for `inspect --unused` and export stripping for
+//! `inspect --remove-unused-exports`
+
+use super::verify::is_entry_point;
+use crate::error::{Result, WasmError, WasmrunError};
+use crate::runtime::core::module::{ImportKind, Module};
+use crate::utils::call_graph::{leb128_len, CallGraph};
+use crate::utils::{read_demangled_function_names, CommandExecutor};
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Body that replaces removed code: no locals, `unreachable`, `end`
+const SNIPPED_BODY: [u8; 3] = [0x00, 0x00, 0x0B];
+
+#[derive(Debug)]
+pub struct UnusedExport {
+    pub name: String,
+    pub function: String,
+    /// Code freed by removing only this export
+    pub reclaimable: usize,
+}
+
+#[derive(Debug)]
+pub struct TreeShakeReport {
+    pub entries: Vec<String>,
+    pub unused_exports: Vec<UnusedExport>,
+    /// Functions no export, start function or table can reach at all
+    pub unreachable: Vec<String>,
+    pub unreachable_bytes: usize,
+    /// Table entries kept alive because some function calls indirectly
+    pub indirect_targets: usize,
+    /// Code freed by removing every unused export
+    pub reclaimable: usize,
+    pub code_size: usize,
+}
+
+#[derive(Debug)]
+pub struct StrippedModule {
+    pub bytes: Vec<u8>,
+    pub removed_exports: Vec<String>,
+    pub snipped_functions: usize,
+}
+
+/// Handle `inspect --unused` / `--remove-unused-exports`
+pub fn handle_tree_shake(
+    wasm_path: &str,
+    unused: bool,
+    entries: &[String],
+    remove: &[String],
+    output: &Option<String>,
+) -> Result<()> {
+    let bytes = fs::read(wasm_path)?;
+
+    if unused {
+        let report = analyze_unused(&bytes, entries)?;
+        print_report(wasm_path, &report);
+    }
+
+    if !remove.is_empty() {
+        let stripped = strip_exports(&bytes, remove)?;
+        let destination = match output {
+            Some(out) => out.clone(),
+            None => default_output_path(wasm_path),
+        };
+        fs::write(&destination, &stripped.bytes).map_err(|e| {
+            WasmrunError::add_context(format!("Writing stripped module to {destination}"), e)
+        })?;
+
+        let saved = bytes.len().saturating_sub(stripped.bytes.len()) as u64;
+        println!("\n\x1b[1;34m╭\x1b[0m");
+        println!(
+            "  ✂️  \x1b[1;36mRemoved exports:\x1b[0m {}",
+            stripped.removed_exports.join(", ")
+        );
+        println!(
+            "  🧹 \x1b[1;34mFunctions emptied:\x1b[0m \x1b[1;33m{}\x1b[0m",
+            stripped.snipped_functions
+        );
+        println!(
+            "  💾 \x1b[1;34mSize:\x1b[0m {} → \x1b[1;32m{}\x1b[0m (saved {})",
+            CommandExecutor::format_file_size(bytes.len() as u64),
+            CommandExecutor::format_file_size(stripped.bytes.len() as u64),
+            CommandExecutor::format_file_size(saved)
+        );
+        println!("  📄 \x1b[1;34mWritten to:\x1b[0m \x1b[1;33m{destination}\x1b[0m");
+        println!("\x1b[1;34m╰\x1b[0m\n");
+    }
+
+    Ok(())
+}
+
+/// Find exports that `entries` never reach. With no explicit entries, the
+/// module's conventional entry points (`_start`, `main`, ...) are used.
+pub fn analyze_unused(bytes: &[u8], entries: &[String]) -> Result<TreeShakeReport> {
+    let graph = build_graph(bytes)?;
+
+    let entries: Vec<String> = if entries.is_empty() {
+        graph
+            .exports
+            .keys()
+            .filter(|name| is_entry_point(name))
+            .cloned()
+            .collect()
+    } else {
+        entries.to_vec()
+    };
+    if entries.is_empty() && graph.start.is_none() {
+        return Err(WasmrunError::from(
+            "No entry points found. Pass the exports your host calls with --entry name1,name2",
+        ));
+    }
+
+    let mut roots = graph.implicit_roots();
+    for entry in &entries {
+        let index = graph.exports.get(entry).ok_or_else(|| {
+            WasmrunError::from(format!("Entry point '{entry}' is not an exported function"))
+        })?;
+        roots.push(*index);
+    }
+    let live = graph.reachable_from(roots.iter().copied());
+
+    let unused: Vec<(&String, u32)> = graph
+        .exports
+        .iter()
+        .filter(|(name, index)| !entries.contains(name) && !live.contains(index))
+        .map(|(name, index)| (name, *index))
+        .collect();
+
+    let unused_exports = unused
+        .iter()
+        .map(|(name, index)| {
+            let others = unused.iter().filter(|(n, _)| n != name).map(|(_, i)| *i);
+            let kept = graph.reachable_from(roots.iter().copied().chain(others));
+            let freed = graph.reachable_from([*index]);
+            UnusedExport {
+                name: (*name).clone(),
+                function: graph.display_name(*index),
+                reclaimable: reclaimable_bytes(&graph, freed.difference(&kept)),
+            }
+        })
+        .collect();
+
+    let everything = graph.reachable_from(
+        graph
+            .implicit_roots()
+            .into_iter()
+            .chain(graph.exports.values().copied()),
+    );
+    let dead: Vec<u32> = graph
+        .functions
+        .iter()
+        .map(|f| f.index)
+        .filter(|i| !everything.contains(i))
+        .collect();
+
+    let not_live: Vec<u32> = graph
+        .functions
+        .iter()
+        .map(|f| f.index)
+        .filter(|i| !live.contains(i))
+        .collect();
+
+    Ok(TreeShakeReport {
+        entries,
+        unused_exports,
+        unreachable: dead.iter().map(|i| graph.display_name(*i)).collect(),
+        unreachable_bytes: reclaimable_bytes(&graph, dead.iter()),
+        indirect_targets: if graph.functions.iter().any(|f| f.calls_indirect) {
+            graph.table_functions.len()
+        } else {
+            0
+        },
+        reclaimable: reclaimable_bytes(&graph, not_live.iter()),
+        code_size: graph.functions.iter().map(|f| f.body_size).sum(),
+    })
+}
+
+/// Remove the named exports and empty every function that is no longer
+/// reachable. Bodies are replaced by `unreachable` rather than deleted, so
+/// function indices, tables and the name section stay valid.
+pub fn strip_exports(bytes: &[u8], remove: &[String]) -> Result<StrippedModule> {
+    let module = parse(bytes)?;
+    for name in remove {
+        if !module.exports.contains_key(name) {
+            return Err(WasmrunError::from(format!(
+                "Module has no export named '{name}'"
+            )));
+        }
+    }
+    let remove: HashSet<&str> = remove.iter().map(String::as_str).collect();
+
+    let graph = CallGraph::build(&module, &Default::default())
+        .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+    let kept_roots = graph
+        .exports
+        .iter()
+        .filter(|(name, _)| !remove.contains(name.as_str()))
+        .map(|(_, index)| *index);
+    let live = graph.reachable_from(graph.implicit_roots().into_iter().chain(kept_roots));
+
+    let snip: BTreeSet<u32> = graph
+        .functions
+        .iter()
+        .filter(|f| !live.contains(&f.index) && f.body_size > SNIPPED_BODY.len())
+        .map(|f| f.index)
+        .collect();
+
+    let import_count = module
+        .imports
+        .iter()
+        .filter(|i| matches!(i.kind, ImportKind::Function(_)))
+        .count() as u32;
+    let rewritten = rewrite_sections(bytes, &remove, &snip, import_count)
+        .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+
+    let mut removed_exports: Vec<String> = remove.iter().map(|s| s.to_string()).collect();
+    removed_exports.sort();
+    Ok(StrippedModule {
+        bytes: rewritten,
+        removed_exports,
+        snipped_functions: snip.len(),
+    })
+}
+
+fn rewrite_sections(
+    bytes: &[u8],
+    remove: &HashSet<&str>,
+    snip: &BTreeSet<u32>,
+    import_count: u32,
+) -> std::result::Result<Vec<u8>, String> {
+    let mut out = bytes[..8].to_vec();
+    let mut pos = 8;
+
+    while pos < bytes.len() {
+        let id = bytes[pos];
+        pos += 1;
+        let size = read_u32(bytes, &mut pos)? as usize;
+        let payload = bytes
+            .get(pos..pos + size)
+            .ok_or("Section extends beyond end of module")?;
+        pos += size;
+
+        let payload = match id {
+            7 => rewrite_exports(payload, remove)?,
+            10 => rewrite_code(payload, snip, import_count)?,
+            _ => payload.to_vec(),
+        };
+        out.push(id);
+        write_u32(&mut out, payload.len() as u32);
+        out.extend_from_slice(&payload);
+    }
+
+    Ok(out)
+}
+
+fn rewrite_exports(payload: &[u8], remove: &HashSet<&str>) -> std::result::Result<Vec<u8>, String> {
+    let mut pos = 0;
+    let count = read_u32(payload, &mut pos)?;
+    let mut kept = Vec::new();
+    let mut kept_count = 0;
+
+    for _ in 0..count {
+        let start = pos;
+        let len = read_u32(payload, &mut pos)? as usize;
+        let name = payload
+            .get(pos..pos + len)
+            .ok_or("Export name extends beyond section")?;
+        pos += len + 1; // name, kind
+        read_u32(payload, &mut pos)?; // index
+
+        if !remove.contains(String::from_utf8_lossy(name).as_ref()) {
+            kept.extend_from_slice(&payload[start..pos]);
+            kept_count += 1;
+        }
+    }
+
+    let mut out = Vec::with_capacity(kept.len() + 5);
+    write_u32(&mut out, kept_count);
+    out.extend_from_slice(&kept);
+    Ok(out)
+}
+
+fn rewrite_code(
+    payload: &[u8],
+    snip: &BTreeSet<u32>,
+    import_count: u32,
+) -> std::result::Result<Vec<u8>, String> {
+    let mut pos = 0;
+    let count = read_u32(payload, &mut pos)?;
+    let mut out = Vec::with_capacity(payload.len());
+    write_u32(&mut out, count);
+
+    for i in 0..count {
+        let size = read_u32(payload, &mut pos)? as usize;
+        let body = payload
+            .get(pos..pos + size)
+            .ok_or("Function body extends beyond code section")?;
+        pos += size;
+
+        let body = if snip.contains(&(import_count + i)) {
+            &SNIPPED_BODY[..]
+        } else {
+            body
+        };
+        write_u32(&mut out, body.len() as u32);
+        out.extend_from_slice(body);
+    }
+
+    Ok(out)
+}
+
+fn print_report(wasm_path: &str, report: &TreeShakeReport) {
+    let fmt = |n: usize| CommandExecutor::format_file_size(n as u64);
+
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  🌳 \x1b[1;36mTree-shaking report\x1b[0m\n");
+    println!(
+        "  🎯 \x1b[1;34mEntry points:\x1b[0m {}",
+        if report.entries.is_empty() {
+            "start function only".to_string()
+        } else {
+            report.entries.join(", ")
+        }
+    );
+
+    if report.unused_exports.is_empty() {
+        println!(
+            "  ✅ \x1b[1;32mEvery exported function is reachable from the entry points\x1b[0m"
+        );
+    } else {
+        println!(
+            "\n  🔍 \x1b[1;34mUnused exports ({}):\x1b[0m",
+            report.unused_exports.len()
+        );
+        for export in &report.unused_exports {
+            let target = if export.function == export.name {
+                String::new()
+            } else {
+                format!(" → {}", export.function)
+            };
+            println!(
+                "     \x1b[1;33m{}\x1b[0m\x1b[0;90m{target}\x1b[0m  {}",
+                export.name,
+                fmt(export.reclaimable)
+            );
+        }
+    }
+
+    if !report.unreachable.is_empty() {
+        println!(
+            "\n  💤 \x1b[1;34mUnreachable functions ({}, {}):\x1b[0m",
+            report.unreachable.len(),
+            fmt(report.unreachable_bytes)
+        );
+        for name in report.unreachable.iter().take(10) {
+            println!("     \x1b[0;37m{name}\x1b[0m");
+        }
+        if report.unreachable.len() > 10 {
+            println!(
+                "     \x1b[0;90m... and {} more\x1b[0m",
+                report.unreachable.len() - 10
+            );
+        }
+    }
+
+    if report.indirect_targets > 0 {
+        println!(
+            "\n  ℹ️  {} table entries are treated as reachable, since call_indirect may reach them",
+            report.indirect_targets
+        );
+    }
+
+    let percent = if report.code_size > 0 {
+        report.reclaimable as f64 * 100.0 / report.code_size as f64
+    } else {
+        0.0
+    };
+    println!(
+        "\n  💾 \x1b[1;34mEstimated reclaimable:\x1b[0m \x1b[1;32m{}\x1b[0m of {} code ({percent:.1}%)",
+        fmt(report.reclaimable),
+        fmt(report.code_size)
+    );
+    if !report.unused_exports.is_empty() {
+        let names: Vec<&str> = report
+            .unused_exports
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        println!(
+            "  💡 Strip them with: \x1b[1;32mwasmrun inspect {wasm_path} --remove-unused-exports {}\x1b[0m",
+            names.join(",")
+        );
+    }
+    println!("\x1b[1;34m╰\x1b[0m\n");
+}
+
+fn build_graph(bytes: &[u8]) -> Result<CallGraph> {
+    let module = parse(bytes)?;
+    CallGraph::build(&module, &read_demangled_function_names(bytes))
+        .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))
+}
+
+fn parse(bytes: &[u8]) -> Result<Module> {
+    Module::parse(bytes).map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))
+}
+
+/// Bytes saved by snipping each of `functions`, counting the size prefix
+fn reclaimable_bytes<'a>(graph: &CallGraph, functions: impl Iterator<Item = &'a u32>) -> usize {
+    functions
+        .filter_map(|i| graph.function(*i))
+        .map(|f| {
+            (f.body_size + leb128_len(f.body_size as u32)).saturating_sub(SNIPPED_BODY.len() + 1)
+        })
+        .sum()
+}
+
+fn default_output_path(wasm_path: &str) -> String {
+    let path = Path::new(wasm_path);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "module".to_string());
+    path.with_file_name(format!("{stem}.stripped.wasm"))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn read_u32(bytes: &[u8], pos: &mut usize) -> std::result::Result<u32, String> {
+    let mut result = 0u32;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or("Unexpected end of section")?;
+        *pos += 1;
+        result |= ((byte & 0x7F) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+        shift += 7;
+        if shift >= 35 {
+            return Err("Invalid LEB128 encoding".to_string());
+        }
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Module with three `() -> ()` functions: `_start` (0) calls `helper` (1);
+    /// `debug_dump` (2) is exported but only reachable by itself.
+    fn sample_module() -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        // type section: one () -> () type
+        bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        // function section: three functions of type 0
+        bytes.extend_from_slice(&[0x03, 0x04, 0x03, 0x00, 0x00, 0x00]);
+        // export section: _start -> 0, debug_dump -> 2
+        let mut exports = vec![0x02];
+        exports.push(6);
+        exports.extend_from_slice(b"_start");
+        exports.extend_from_slice(&[0x00, 0x00]);
+        exports.push(10);
+        exports.extend_from_slice(b"debug_dump");
+        exports.extend_from_slice(&[0x00, 0x02]);
+        bytes.push(0x07);
+        bytes.push(exports.len() as u8);
+        bytes.extend_from_slice(&exports);
+        // code section
+        let bodies: [&[u8]; 3] = [
+            &[0x00, 0x10, 0x01, 0x0B],                   // call 1
+            &[0x00, 0x01, 0x0B],                         // nop
+            &[0x00, 0x41, 0x01, 0x1A, 0x01, 0x01, 0x0B], // i32.const 1, drop, nop, nop
+        ];
+        let mut code = vec![0x03];
+        for body in bodies {
+            code.push(body.len() as u8);
+            code.extend_from_slice(body);
+        }
+        bytes.push(0x0A);
+        bytes.push(code.len() as u8);
+        bytes.extend_from_slice(&code);
+        bytes
+    }
+
+    #[test]
+    fn test_report_flags_unused_export() {
+        let report = analyze_unused(&sample_module(), &[]).unwrap();
+        assert_eq!(report.entries, vec!["_start"]);
+        assert_eq!(report.unused_exports.len(), 1);
+        assert_eq!(report.unused_exports[0].name, "debug_dump");
+        // 8 bytes (7 + size prefix) shrink to 4
+        assert_eq!(report.unused_exports[0].reclaimable, 4);
+        assert_eq!(report.reclaimable, 4);
+        assert!(report.unreachable.is_empty());
+    }
+
+    #[test]
+    fn test_report_with_explicit_entries() {
+        let entries = vec!["debug_dump".to_string()];
+        let report = analyze_unused(&sample_module(), &entries).unwrap();
+        assert_eq!(report.unused_exports[0].name, "_start");
+        // _start and helper are both freed, but helper is already minimal
+        assert_eq!(report.unused_exports[0].reclaimable, 1);
+    }
+
+    #[test]
+    fn test_report_rejects_unknown_entry() {
+        let entries = vec!["nope".to_string()];
+        assert!(analyze_unused(&sample_module(), &entries).is_err());
+    }
+
+    #[test]
+    fn test_strip_removes_export_and_snips_body() {
+        let original = sample_module();
+        let stripped = strip_exports(&original, &["debug_dump".to_string()]).unwrap();
+        assert_eq!(stripped.snipped_functions, 1);
+        assert!(stripped.bytes.len() < original.len());
+
+        let module = Module::parse(&stripped.bytes).unwrap();
+        assert!(module.exports.contains_key("_start"));
+        assert!(!module.exports.contains_key("debug_dump"));
+        assert_eq!(module.functions.len(), 3);
+        assert_eq!(module.functions[1].code, vec![0x01, 0x0B]);
+        assert_eq!(module.functions[2].code, vec![0x00, 0x0B]);
+    }
+
+    #[test]
+    fn test_strip_unknown_export_fails() {
+        assert!(strip_exports(&sample_module(), &["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_default_output_path() {
+        assert_eq!(
+            default_output_path("build/app.wasm"),
+            Path::new("build")
+                .join("app.stripped.wasm")
+                .to_string_lossy()
+        );
+    }
+}
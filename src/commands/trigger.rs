@@ -0,0 +1,116 @@
+//! `wasmrun trigger`: ask a running dev server to rebuild now
+
+use crate::error::{Result, WasmrunError};
+use crate::server::instances::{self, Instance};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+const TRIGGER_ROUTE: &str = "/api/build/trigger";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handle trigger command
+pub fn handle_trigger_command(name: Option<&str>, port: Option<u16>) -> Result<()> {
+    let instance = match port {
+        Some(port) => Instance {
+            name: format!("port {port}"),
+            pid: 0,
+            control_port: Some(port),
+            socket: None,
+            tls: false,
+        },
+        None => instances::find(name)?,
+    };
+
+    let (status, body) = post(&instance, TRIGGER_ROUTE)?;
+    let reply: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    match (status, reply["status"].as_str()) {
+        (202, Some("started")) => println!("🔁 Rebuilding '{}'", instance.name),
+        (202, Some("queued")) => println!(
+            "⏳ '{}' is already building; another build is queued after it",
+            instance.name
+        ),
+        _ => {
+            let error = reply["error"].as_str().unwrap_or(body.trim());
+            return Err(WasmrunError::from(format!(
+                "'{}' refused the rebuild ({status}): {error}",
+                instance.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Send an empty POST to `route` on the instance's control endpoint and
+/// return the status and body
+fn post(instance: &Instance, route: &str) -> Result<(u16, String)> {
+    let request = format!(
+        "POST {route} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    let unreachable =
+        |e: std::io::Error| WasmrunError::from(format!("Can't reach '{}': {e}", instance.name));
+    let mut response = Vec::new();
+
+    if let Some(port) = instance.control_port {
+        if instance.tls {
+            return Err(WasmrunError::from(format!(
+                "'{}' serves HTTPS; start it with --api-port to trigger rebuilds",
+                instance.name
+            )));
+        }
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).map_err(unreachable)?;
+        stream
+            .set_read_timeout(Some(TIMEOUT))
+            .map_err(unreachable)?;
+        stream.write_all(request.as_bytes()).map_err(unreachable)?;
+        stream.read_to_end(&mut response).map_err(unreachable)?;
+    } else if let Some(socket) = &instance.socket {
+        #[cfg(unix)]
+        {
+            let mut stream =
+                std::os::unix::net::UnixStream::connect(socket).map_err(unreachable)?;
+            stream
+                .set_read_timeout(Some(TIMEOUT))
+                .map_err(unreachable)?;
+            stream.write_all(request.as_bytes()).map_err(unreachable)?;
+            stream.read_to_end(&mut response).map_err(unreachable)?;
+        }
+        #[cfg(not(unix))]
+        return Err(WasmrunError::from(format!(
+            "Unix sockets aren't supported on this platform ({})",
+            socket.display()
+        )));
+    } else {
+        return Err(WasmrunError::from(format!(
+            "'{}' has no control endpoint",
+            instance.name
+        )));
+    }
+
+    parse_response(&String::from_utf8_lossy(&response))
+}
+
+fn parse_response(response: &str) -> Result<(u16, String)> {
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((response, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| WasmrunError::from("The dev server sent an invalid response"))?;
+    Ok((status, body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let (status, body) = parse_response(
+            "HTTP/1.1 202 Accepted\r\nContent-Type: application/json\r\n\r\n{\"status\":\"queued\"}",
+        )
+        .unwrap();
+        assert_eq!(status, 202);
+        assert_eq!(body, "{\"status\":\"queued\"}");
+        assert!(parse_response("garbage").is_err());
+    }
+}
@@ -1,12 +1,17 @@
 use crate::cli::CommandValidator;
-use crate::commands::{issue_detector, module_display};
+use crate::commands::policy::enforce_project_policy;
+use crate::commands::{issue_detector, module_display, tree_shake};
+use crate::compiler::metadata;
 use crate::config::WASM_MAGIC_BYTES;
 use crate::error::{Result, WasmError, WasmrunError};
 use crate::runtime::core::module::Module;
-use crate::utils::PathResolver;
+use crate::utils::call_graph::CallGraph;
+use crate::utils::{read_demangled_function_names, MappedFile, PathResolver};
+use std::collections::HashMap;
 use std::fs;
 use std::io::{Cursor, Read};
 use std::path::Path;
+use std::thread;
 
 /// WASM section
 #[derive(Debug)]
@@ -38,74 +43,282 @@ pub fn handle_verify_command(
     path: &Option<String>,
     positional_path: &Option<String>,
     detailed: bool,
+    fast: bool,
 ) -> Result<()> {
     let wasm_path = resolve_and_validate_wasm_path(path, positional_path)?;
 
     println!("🔍 Verifying WebAssembly file: {wasm_path}");
 
-    let result =
-        verify_wasm(&wasm_path).map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+    let wasm_bytes = MappedFile::open(&wasm_path).map_err(|e| {
+        WasmrunError::Wasm(WasmError::validation_failed(format!(
+            "Error reading file: {e}"
+        )))
+    })?;
+    let ci = crate::ci::is_enabled();
 
-    print_verification_results(&wasm_path, &result, detailed);
+    thread::scope(|scope| {
+        // The full parse is only needed for --detailed and the issue checks
+        // under --ci; it runs while the section scan is reported
+        let parsed = (detailed || ci).then(|| scope.spawn(|| parse_module(&wasm_bytes, fast)));
 
-    if !result.valid_magic {
-        return Err(WasmrunError::Wasm(WasmError::InvalidMagicBytes {
-            path: wasm_path,
-        }));
-    }
+        let result = verify_wasm_bytes(&wasm_bytes)
+            .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
 
-    if result.section_count == 0 {
-        return Err(WasmrunError::Wasm(WasmError::validation_failed(
-            "No sections found in WASM file",
-        )));
-    }
+        print_verification_results(&wasm_path, &result, detailed);
 
-    // Show detailed module analysis if requested
-    if detailed {
-        if let Ok(wasm_bytes) = fs::read(&wasm_path) {
-            if let Ok(module) = Module::parse(&wasm_bytes) {
-                module_display::display_module_summary(&module);
+        if !result.valid_magic {
+            return Err(WasmrunError::Wasm(WasmError::InvalidMagicBytes {
+                path: wasm_path.clone(),
+            }));
+        }
+
+        if result.section_count == 0 {
+            return Err(WasmrunError::Wasm(WasmError::validation_failed(
+                "No sections found in WASM file",
+            )));
+        }
+
+        let module = parsed
+            .and_then(|handle| handle.join().ok())
+            .and_then(|module| module.ok());
+
+        // Show detailed module analysis if requested
+        if let Some(module) = module.as_ref().filter(|_| detailed) {
+            if fast {
+                module_display::display_module_headers(module);
+            } else {
+                module_display::display_module_summary(module);
             }
         }
-    }
 
-    Ok(())
+        // Under --ci the issue checks are part of verification, and their
+        // warnings fail it
+        if let Some(module) = module.as_ref().filter(|_| ci) {
+            let issues = if fast {
+                issue_detector::detect_header_issues(module)
+            } else {
+                issue_detector::detect_issues(module)
+            };
+            println!();
+            issue_detector::display_issues(&issues);
+            for issue in issues
+                .iter()
+                .filter(|issue| issue.severity >= issue_detector::IssueSeverity::Warning)
+            {
+                crate::ci::warn("verify", &issue.title);
+            }
+        }
+
+        Ok(())
+    })?;
+
+    enforce_project_policy(Path::new(&wasm_path), &wasm_path)
 }
 
 /// Handle inspect command
+#[allow(clippy::too_many_arguments)]
 pub fn handle_inspect_command(
     path: &Option<String>,
     positional_path: &Option<String>,
+    unused: bool,
+    entry: &[String],
+    remove_unused_exports: &[String],
+    output: &Option<String>,
+    callgraph: &Option<String>,
+    fast: bool,
 ) -> Result<()> {
     let wasm_path = CommandValidator::validate_verify_args(path, positional_path)?;
 
     PathResolver::validate_wasm_file(&wasm_path)?;
 
+    if let Some(callgraph_path) = callgraph {
+        write_call_graph(&wasm_path, callgraph_path)?;
+        if !unused && remove_unused_exports.is_empty() {
+            return Ok(());
+        }
+    }
+
+    if unused || !remove_unused_exports.is_empty() {
+        return tree_shake::handle_tree_shake(
+            &wasm_path,
+            unused,
+            entry,
+            remove_unused_exports,
+            output,
+        );
+    }
+
     println!("🔍 Inspecting WebAssembly file: {wasm_path}\n");
 
-    // Show binary information
-    print_detailed_binary_info(&wasm_path)
-        .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+    let wasm_bytes = MappedFile::open(&wasm_path).map_err(|e| {
+        WasmrunError::Wasm(WasmError::validation_failed(format!(
+            "Error reading file: {e}"
+        )))
+    })?;
+
+    thread::scope(|scope| {
+        // Parse and analyse the module in the background, so the section
+        // listing, which only needs a scan, prints straight away
+        let analysis = scope.spawn(|| analyze_module(&wasm_bytes, fast));
 
-    // Also show parsed module analysis
-    if let Ok(wasm_bytes) = fs::read(&wasm_path) {
-        if let Ok(module) = Module::parse(&wasm_bytes) {
+        print_detailed_binary_info(&wasm_path, &wasm_bytes)
+            .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+
+        let analysis = analysis.join().ok().flatten();
+        if let Some(analysis) = &analysis {
+            println!();
+            issue_detector::display_issues(&analysis.issues);
+        }
+        println!("\x1b[1;34m╰\x1b[0m");
+
+        print_build_metadata(&wasm_bytes);
+        if let Some(analysis) = &analysis {
             println!("\n📊 Parsed Module Analysis:");
-            module_display::display_module_summary(&module);
+            if fast {
+                module_display::display_module_headers(&analysis.module);
+            } else {
+                module_display::display_module_summary(&analysis.module);
+                module_display::display_largest_functions(&analysis.module, &analysis.names, 10);
+            }
         }
+
+        Ok(())
+    })
+}
+
+/// What `inspect` learns from parsing the module
+struct ModuleAnalysis {
+    module: Module,
+    issues: Vec<issue_detector::WasmIssue>,
+    /// Demangled function names; empty under `--fast`
+    names: HashMap<u32, String>,
+}
+
+/// Parse the whole module, or with `fast` everything but the code section
+fn parse_module(bytes: &[u8], fast: bool) -> std::result::Result<Module, String> {
+    if fast {
+        Module::parse_headers(bytes)
+    } else {
+        Module::parse(bytes)
     }
+}
 
+/// Parse the module and check it for issues, reading the name section
+/// alongside the parse; `None` when the module doesn't parse
+fn analyze_module(bytes: &[u8], fast: bool) -> Option<ModuleAnalysis> {
+    if fast {
+        let module = Module::parse_headers(bytes).ok()?;
+        let issues = issue_detector::detect_header_issues(&module);
+        return Some(ModuleAnalysis {
+            module,
+            issues,
+            names: HashMap::new(),
+        });
+    }
+
+    let (module, names) = rayon::join(
+        || Module::parse(bytes),
+        || read_demangled_function_names(bytes),
+    );
+    let module = module.ok()?;
+    let issues = issue_detector::detect_issues(&module);
+    Some(ModuleAnalysis {
+        module,
+        issues,
+        names,
+    })
+}
+
+fn print_build_metadata(wasm_bytes: &[u8]) {
+    let meta = match metadata::read(wasm_bytes) {
+        None => return,
+        Some(Ok(meta)) => meta,
+        Some(Err(e)) => {
+            println!("\n  ⚠️  \x1b[1;33m{e}\x1b[0m");
+            return;
+        }
+    };
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!(
+        "  🏷️  \x1b[1;36mBuild Metadata\x1b[0m \x1b[0;90m({})\x1b[0m\n",
+        metadata::META_SECTION
+    );
+    let project = match &meta.version {
+        Some(version) => format!("{} {version}", meta.project),
+        None => meta.project.clone(),
+    };
+    println!("  📦 \x1b[1;34mProject:\x1b[0m \x1b[1;33m{project}\x1b[0m");
+    if let Some(revision) = meta.revision() {
+        println!("  🔖 \x1b[1;34mCommit:\x1b[0m \x1b[1;33m{revision}\x1b[0m");
+    }
+    println!(
+        "  🕒 \x1b[1;34mBuilt:\x1b[0m \x1b[1;33m{}\x1b[0m",
+        meta.built_at
+    );
+    println!(
+        "  🛠️  \x1b[1;34mLanguage:\x1b[0m \x1b[1;33m{} ({})\x1b[0m",
+        meta.language, meta.profile
+    );
+    for tool in &meta.toolchain {
+        println!("     \x1b[0;90m{} {}\x1b[0m", tool.name, tool.version);
+    }
+    println!("\x1b[1;34m╰\x1b[0m");
+}
+
+/// Write the module's call graph as DOT, or JSON when `output` ends in `.json`
+fn write_call_graph(wasm_path: &str, output: &str) -> Result<()> {
+    let bytes = MappedFile::open(wasm_path)?;
+    let module =
+        Module::parse(&bytes).map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+    let graph = CallGraph::build(&module, &read_demangled_function_names(&bytes))
+        .map_err(|e| WasmrunError::Wasm(WasmError::validation_failed(e)))?;
+
+    let is_json = Path::new(output)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let content = if is_json {
+        serde_json::to_string_pretty(&graph.to_json())
+            .map_err(|e| WasmrunError::from(format!("Failed to serialize call graph: {e}")))?
+    } else {
+        graph.to_dot()
+    };
+    fs::write(output, content)
+        .map_err(|e| WasmrunError::add_context(format!("Writing call graph to {output}"), e))?;
+
+    let calls: usize = graph.functions.iter().map(|f| f.callees.len()).sum();
+    println!("\n\x1b[1;34m╭\x1b[0m");
+    println!("  🕸️  \x1b[1;36mCall graph written to {output}\x1b[0m\n");
+    println!(
+        "  🔧 \x1b[1;34mFunctions:\x1b[0m \x1b[1;33m{}\x1b[0m defined, {} imported",
+        graph.functions.len(),
+        graph.import_count
+    );
+    println!("  🔗 \x1b[1;34mCall edges:\x1b[0m \x1b[1;33m{calls}\x1b[0m");
+    if !is_json {
+        let svg = Path::new(output).with_extension("svg");
+        println!(
+            "\n  💡 Render it with: \x1b[1;32mdot -Tsvg {output} -o {}\x1b[0m",
+            svg.display()
+        );
+    }
+    println!("\x1b[1;34m╰\x1b[0m\n");
     Ok(())
 }
 
-/// Verify a WebAssembly file
+/// Verify a WebAssembly file. Large files are mapped, so this is for
+/// one-shot checks; a server whose module can be rebuilt under it reads the
+/// file and calls [`verify_wasm_bytes`].
 pub fn verify_wasm(path: &str) -> std::result::Result<VerificationResult, String> {
     if !Path::new(path).exists() {
         return Err(format!("File not found: {path}"));
     }
 
-    let wasm_bytes = fs::read(path).map_err(|e| format!("Error reading file: {e}"))?;
+    let wasm_bytes = MappedFile::open(path).map_err(|e| format!("Error reading file: {e}"))?;
+    verify_wasm_bytes(&wasm_bytes)
+}
 
+/// [`verify_wasm`] for a module already in memory
+pub fn verify_wasm_bytes(wasm_bytes: &[u8]) -> std::result::Result<VerificationResult, String> {
     if wasm_bytes.len() < 8 {
         return Err("File is too small to be a valid WASM module".to_string());
     }
@@ -129,7 +342,7 @@ pub fn verify_wasm(path: &str) -> std::result::Result<VerificationResult, String
         });
     }
 
-    let mut reader = Cursor::new(wasm_bytes.clone());
+    let mut reader = Cursor::new(wasm_bytes);
 
     reader.set_position(8);
 
@@ -483,9 +696,13 @@ pub fn print_verification_results(path: &str, results: &VerificationResult, deta
     println!("\x1b[1;34m╰\x1b[0m");
 }
 
-pub fn print_detailed_binary_info(path: &str) -> std::result::Result<(), String> {
-    let wasm_bytes = fs::read(path).map_err(|e| format!("Error reading file: {e}"))?;
-
+/// Open the binary analysis box and list the module's sections; the
+/// caller adds the issues found and closes the box. On error the box is
+/// closed here.
+pub fn print_detailed_binary_info(
+    path: &str,
+    wasm_bytes: &[u8],
+) -> std::result::Result<(), String> {
     println!("\n\x1b[1;34m╭\x1b[0m");
     println!("  🔬 \x1b[1;36mDetailed WASM Binary Analysis\x1b[0m\n");
     println!(
@@ -551,7 +768,7 @@ pub fn print_detailed_binary_info(path: &str) -> std::result::Result<(), String>
         "DataCount",
     ];
 
-    let mut reader = Cursor::new(wasm_bytes.clone());
+    let mut reader = Cursor::new(wasm_bytes);
     reader.set_position(8);
 
     while (reader.position() as usize) < wasm_bytes.len() {
@@ -604,17 +821,6 @@ pub fn print_detailed_binary_info(path: &str) -> std::result::Result<(), String>
         println!("  ❌ \x1b[1;31mNo sections found in WASM file\x1b[0m");
     }
 
-    // Analyze the module for issues
-    println!();
-    if let Ok(wasm_bytes) = fs::read(path) {
-        if let Ok(module) = Module::parse(&wasm_bytes) {
-            let issues = issue_detector::detect_issues(&module);
-            issue_detector::display_issues(&issues);
-        }
-    }
-
-    println!("\x1b[1;34m╰\x1b[0m");
-
     Ok(())
 }
 
@@ -627,7 +833,7 @@ pub fn is_entry_point(name: &str) -> bool {
 }
 
 /// Read unsigned LEB128 encoded 32-bit value
-fn read_leb128_u32(reader: &mut Cursor<Vec<u8>>) -> std::result::Result<u32, String> {
+fn read_leb128_u32<T: AsRef<[u8]>>(reader: &mut Cursor<T>) -> std::result::Result<u32, String> {
     let mut result = 0u32;
     let mut shift = 0;
 
@@ -657,7 +863,7 @@ trait CursorExt {
     fn read_u8(&mut self) -> std::result::Result<u8, String>;
 }
 
-impl CursorExt for Cursor<Vec<u8>> {
+impl<T: AsRef<[u8]>> CursorExt for Cursor<T> {
     fn read_u8(&mut self) -> std::result::Result<u8, String> {
         let mut byte = [0u8; 1];
         if self.read_exact(&mut byte).is_err() {
@@ -744,6 +950,17 @@ mod tests {
         assert_eq!(verification.file_size, 8);
     }
 
+    #[test]
+    fn test_verify_wasm_bytes_matches_file() {
+        let temp_file = create_wasm_file(&VALID_WASM_BYTES);
+        let from_file = verify_wasm(temp_file.path().to_str().unwrap()).unwrap();
+        let from_bytes = verify_wasm_bytes(&VALID_WASM_BYTES).unwrap();
+
+        assert!(from_bytes.valid_magic);
+        assert_eq!(from_bytes.file_size, from_file.file_size);
+        assert_eq!(from_bytes.section_count, from_file.section_count);
+    }
+
     #[test]
     fn test_verify_wasm_invalid_magic() {
         let temp_file = create_wasm_file(&INVALID_WASM_BYTES);
@@ -896,6 +1113,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_analyze_module_fast_skips_code_checks() {
+        let mut bytes = VALID_WASM_BYTES.to_vec();
+        bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x03, 0x02, 0x01, 0x00]);
+        bytes.extend_from_slice(&[0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b]);
+
+        let full = analyze_module(&bytes, false).unwrap();
+        assert_eq!(full.module.functions[0].code, vec![0x0b]);
+
+        let fast = analyze_module(&bytes, true).unwrap();
+        assert!(fast.module.functions[0].code.is_empty());
+        // Bodies that were never read aren't reported as missing
+        let missing_code = |issues: &[issue_detector::WasmIssue]| {
+            issues.iter().any(|i| i.title == "Functions without code")
+        };
+        assert!(!missing_code(&fast.issues));
+        assert!(missing_code(&issue_detector::detect_issues(&fast.module)));
+    }
+
     #[test]
     fn test_resolve_and_validate_wasm_path() {
         let temp_file = create_wasm_file_with_extension(&VALID_WASM_BYTES);
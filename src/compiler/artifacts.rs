@@ -0,0 +1,342 @@
+//! `wasmrun-manifest.json`: what a build produced, written to the output
+//! directory after every successful build so CI and deploy steps can find
+//! the artifacts without guessing their names
+
+use super::builder::{BuildConfig, BuildResult};
+use super::licenses::DependencyLicense;
+use crate::logging::FileChange;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const BUILD_MANIFEST_FILE: &str = "wasmrun-manifest.json";
+
+/// Glue wasm-bindgen writes beside `<stem>.js`, other than the module
+const BINDGEN_GLUE_SUFFIXES: [&str; 3] = [".d.ts", "_bg.js", "_bg.wasm.d.ts"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub wasmrun_version: String,
+    pub built_at: String,
+    pub project: String,
+    pub language: String,
+    pub profile: String,
+    /// The target the builder compiled for, when it reports one
+    pub target: Option<String>,
+    pub artifacts: Vec<Artifact>,
+    /// Dependency licenses, when `[licenses]` in wasmrun.toml had them checked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<DependencyLicense>,
+    /// The edits that set off this build, for watch rebuilds
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rebuilt_for: Vec<FileChange>,
+    /// The artifact store entry the build was kept as or restored from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_key: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Relative to the output directory when inside it
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Module,
+    /// JS that loads the module: wasm-bindgen or Emscripten output
+    Glue,
+    /// The page a web app build opens
+    Entry,
+    Asset,
+}
+
+impl BuildManifest {
+    /// Describe the files `result` lists, hashing each one
+    pub fn from_build(
+        config: &BuildConfig,
+        result: &BuildResult,
+        language: &str,
+    ) -> io::Result<Self> {
+        let output_dir = Path::new(&config.output_dir);
+        let mut files: Vec<(PathBuf, ArtifactKind)> = Vec::new();
+
+        let wasm_path = Path::new(&result.wasm_path);
+        if wasm_path.is_dir() {
+            // A web app: everything in its directory
+            let entry = result.js_path.as_deref().map(Path::new);
+            for file in files_under(wasm_path)? {
+                let kind = if Some(file.as_path()) == entry {
+                    ArtifactKind::Entry
+                } else if file.extension().is_some_and(|e| e == "wasm") {
+                    ArtifactKind::Module
+                } else {
+                    ArtifactKind::Asset
+                };
+                files.push((file, kind));
+            }
+        } else {
+            files.push((wasm_path.to_path_buf(), ArtifactKind::Module));
+            if let Some(js_path) = &result.js_path {
+                let js_path = Path::new(js_path);
+                files.push((js_path.to_path_buf(), ArtifactKind::Glue));
+                if result.is_wasm_bindgen {
+                    files.extend(
+                        bindgen_glue(js_path)?
+                            .into_iter()
+                            .map(|f| (f, ArtifactKind::Glue)),
+                    );
+                }
+            }
+        }
+        files.extend(
+            result
+                .additional_files
+                .iter()
+                .map(|f| (PathBuf::from(f), ArtifactKind::Asset)),
+        );
+
+        let mut artifacts = Vec::new();
+        for (file, kind) in files {
+            if file.file_name().is_some_and(|n| n == BUILD_MANIFEST_FILE)
+                || artifacts
+                    .iter()
+                    .any(|a: &Artifact| a.path == relative(&file, output_dir))
+            {
+                continue;
+            }
+            let data = fs::read(&file).map_err(|e| {
+                io::Error::new(e.kind(), format!("Reading {}: {e}", file.display()))
+            })?;
+            artifacts.push(Artifact {
+                path: relative(&file, output_dir),
+                kind,
+                size: data.len() as u64,
+                sha256: Sha256::digest(&data)
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect(),
+            });
+        }
+
+        Ok(Self {
+            wasmrun_version: env!("CARGO_PKG_VERSION").to_string(),
+            built_at: chrono::Utc::now().to_rfc3339(),
+            project: config.project_path.clone(),
+            language: language.to_string(),
+            profile: config.optimization_level.to_string(),
+            target: result.target.clone(),
+            artifacts,
+            licenses: Vec::new(),
+            rebuilt_for: Vec::new(),
+            store_key: None,
+        })
+    }
+
+    /// The manifest for the build that produced `wasm_path`, looked for
+    /// beside it and, for per-target layouts, one directory up
+    pub fn find_for(wasm_path: &Path) -> Option<PathBuf> {
+        let dir = if wasm_path.is_dir() {
+            wasm_path
+        } else {
+            wasm_path.parent()?
+        };
+        dir.ancestors()
+            .take(2)
+            .map(|d| d.join(BUILD_MANIFEST_FILE))
+            .find(|p| p.is_file())
+    }
+}
+
+/// Write `wasmrun-manifest.json` for a successful build. A manifest that
+/// can't be written is reported but doesn't fail the build.
+pub fn write_build_manifest(
+    config: &BuildConfig,
+    result: &BuildResult,
+    language: &str,
+) -> Option<PathBuf> {
+    write_build_manifest_with_licenses(config, result, language, Vec::new())
+}
+
+/// Write the build manifest along with the dependency licenses checked
+/// before the build shipped
+pub fn write_build_manifest_with_licenses(
+    config: &BuildConfig,
+    result: &BuildResult,
+    language: &str,
+    licenses: Vec<DependencyLicense>,
+) -> Option<PathBuf> {
+    let path = Path::new(&config.output_dir).join(BUILD_MANIFEST_FILE);
+    let written = BuildManifest::from_build(config, result, language).and_then(|mut manifest| {
+        manifest.licenses = licenses;
+        let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(&path, json + "\n")
+    });
+    match written {
+        Ok(()) => Some(path),
+        Err(e) => {
+            eprintln!("⚠️  Failed to write {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Name the changed files behind a watch rebuild in the manifest already
+/// written to `output_dir`
+pub fn record_rebuild_cause(output_dir: &Path, changes: &[FileChange]) -> io::Result<()> {
+    let path = output_dir.join(BUILD_MANIFEST_FILE);
+    let mut manifest: BuildManifest =
+        serde_json::from_str(&fs::read_to_string(&path)?).map_err(io::Error::other)?;
+    manifest.rebuilt_for = changes.to_vec();
+    let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+    fs::write(&path, json + "\n")
+}
+
+/// Name the artifact store entry behind the build in the manifest already
+/// written to `output_dir`
+pub fn record_store_key(output_dir: &Path, key: &str) -> io::Result<()> {
+    let path = output_dir.join(BUILD_MANIFEST_FILE);
+    let mut manifest: BuildManifest =
+        serde_json::from_str(&fs::read_to_string(&path)?).map_err(io::Error::other)?;
+    manifest.store_key = Some(key.to_string());
+    let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+    fs::write(&path, json + "\n")
+}
+
+/// Type declarations, bundler glue and snippets next to wasm-bindgen's JS
+fn bindgen_glue(js_path: &Path) -> io::Result<Vec<PathBuf>> {
+    let Some(dir) = js_path.parent() else {
+        return Ok(Vec::new());
+    };
+    let stem = js_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut glue: Vec<PathBuf> = BINDGEN_GLUE_SUFFIXES
+        .iter()
+        .map(|suffix| dir.join(format!("{stem}{suffix}")))
+        .filter(|p| p.is_file())
+        .collect();
+    let snippets = dir.join("snippets");
+    if snippets.is_dir() {
+        glue.extend(files_under(&snippets)?);
+    }
+    Ok(glue)
+}
+
+/// Every file under `dir`, sorted
+fn files_under(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+pub(super) fn relative(path: &Path, base: &Path) -> String {
+    let path = path.strip_prefix(base).unwrap_or(path);
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_lists_bindgen_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("dist");
+        fs::create_dir_all(out.join("snippets/app-1234")).unwrap();
+        fs::write(out.join("app_bg.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        fs::write(out.join("app.js"), "export function initSync() {}").unwrap();
+        fs::write(out.join("app.d.ts"), "export {};").unwrap();
+        fs::write(out.join("snippets/app-1234/inline0.js"), "").unwrap();
+        fs::write(out.join("unrelated.txt"), "").unwrap();
+
+        let mut config =
+            BuildConfig::with_defaults("app".to_string(), out.to_string_lossy().to_string());
+        config.optimization_level = crate::compiler::builder::OptimizationLevel::Size;
+        let result = BuildResult {
+            wasm_path: out.join("app_bg.wasm").to_string_lossy().to_string(),
+            js_path: Some(out.join("app.js").to_string_lossy().to_string()),
+            additional_files: vec![],
+            is_wasm_bindgen: true,
+            target: Some("wasm32-unknown-unknown".to_string()),
+        };
+
+        let path = write_build_manifest(&config, &result, "Rust").unwrap();
+        let manifest: BuildManifest =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(manifest.profile, "size");
+        assert_eq!(manifest.target.as_deref(), Some("wasm32-unknown-unknown"));
+        let listed: Vec<(&str, ArtifactKind)> = manifest
+            .artifacts
+            .iter()
+            .map(|a| (a.path.as_str(), a.kind))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("app_bg.wasm", ArtifactKind::Module),
+                ("app.js", ArtifactKind::Glue),
+                ("app.d.ts", ArtifactKind::Glue),
+                ("snippets/app-1234/inline0.js", ArtifactKind::Glue),
+            ]
+        );
+        assert_eq!(manifest.artifacts[0].size, 8);
+        assert_eq!(
+            manifest.artifacts[0].sha256,
+            "93a44bbb96c751218e4c00d479e4c14358122a389acca16205b1e4d0dc5f9476"
+        );
+
+        assert_eq!(
+            BuildManifest::find_for(&out.join("app_bg.wasm")),
+            Some(path.clone())
+        );
+    }
+
+    #[test]
+    fn test_record_rebuild_cause() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        let config =
+            BuildConfig::with_defaults("app".to_string(), dir.path().to_string_lossy().to_string());
+        let result = BuildResult {
+            wasm_path: dir.path().join("app.wasm").to_string_lossy().to_string(),
+            js_path: None,
+            additional_files: vec![],
+            is_wasm_bindgen: false,
+            target: None,
+        };
+        let path = write_build_manifest(&config, &result, "Rust").unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("rebuilt_for"));
+
+        let changes = vec![FileChange {
+            path: "src/lib.rs".to_string(),
+            sha256: Some("ab".repeat(32)),
+        }];
+        record_rebuild_cause(dir.path(), &changes).unwrap();
+        let manifest: BuildManifest =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(manifest.rebuilt_for, changes);
+        assert_eq!(manifest.artifacts.len(), 1);
+    }
+}
@@ -1,9 +1,11 @@
 //! Build system abstraction for different languages and compilation targets
 
+use super::artifacts::write_build_manifest;
 use crate::error::{CompilationResult, Result};
 use crate::plugin::manager::PluginManager;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildConfig {
@@ -13,6 +15,23 @@ pub struct BuildConfig {
     pub verbose: bool,
     pub watch: bool,
     pub target_type: TargetType,
+    #[serde(default)]
+    pub naming: OutputNaming,
+}
+
+/// How artifacts are named and laid out in the output directory. Set from
+/// `--out-name`, `--name-pattern` and `--target-dirs`, or the `[output]`
+/// section of `wasmrun.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputNaming {
+    /// File stem for every artifact, e.g. `app` for `app.wasm` and `app.js`
+    pub name: Option<String>,
+    /// File stem pattern with `{name}`, `{profile}` and `{target}`
+    /// placeholders, e.g. `{name}-{profile}`. `name` wins over it.
+    pub pattern: Option<String>,
+    /// Put artifacts in a subdirectory named after the build target
+    pub target_dirs: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +63,165 @@ pub struct BuildResult {
     pub js_path: Option<String>,
     pub additional_files: Vec<String>,
     pub is_wasm_bindgen: bool,
+    /// The target the module was compiled for, e.g. `wasm32-unknown-unknown`
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// What a build would do, for `wasmrun compile --dry-run`
+#[derive(Debug, Clone, Default)]
+pub struct BuildPlan {
+    pub steps: Vec<BuildStep>,
+    /// Files the build is expected to produce
+    pub outputs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildStep {
+    /// Run a tool; `when` says when a conditional or fallback step runs
+    Run {
+        command: PlannedCommand,
+        when: Option<String>,
+    },
+    Copy {
+        from: String,
+        to: String,
+    },
+    Note(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: String,
+    /// Variables from wasmrun's environment the tool reads, if set
+    pub env: Vec<(String, String)>,
+}
+
+impl BuildPlan {
+    pub fn run(mut self, command: PlannedCommand) -> Self {
+        self.steps.push(BuildStep::Run {
+            command,
+            when: None,
+        });
+        self
+    }
+
+    pub fn run_when(mut self, command: PlannedCommand, when: &str) -> Self {
+        self.steps.push(BuildStep::Run {
+            command,
+            when: Some(when.to_string()),
+        });
+        self
+    }
+
+    pub fn copy(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.steps.push(BuildStep::Copy {
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.steps.push(BuildStep::Note(note.into()));
+        self
+    }
+
+    pub fn output(mut self, path: impl Into<String>) -> Self {
+        self.outputs.push(path.into());
+        self
+    }
+}
+
+impl PlannedCommand {
+    /// `env_vars` are the variables the tool reads; those set in the
+    /// current environment are recorded
+    pub fn new<S: AsRef<str>>(
+        program: &str,
+        args: &[S],
+        working_dir: &str,
+        env_vars: &[&str],
+    ) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.as_ref().to_string()).collect(),
+            working_dir: working_dir.to_string(),
+            env: env_vars
+                .iter()
+                .filter_map(|name| std::env::var(name).ok().map(|v| (name.to_string(), v)))
+                .collect(),
+        }
+    }
+}
+
+/// Something `wasmrun clean` can delete
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanTarget {
+    pub path: PathBuf,
+    pub kind: CleanKind,
+    /// What it is, e.g. "cargo target directory"
+    pub what: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanKind {
+    /// Build output inside the project, removed by `--all`
+    Artifact,
+    /// Caches and stale state, removed by `--deep`
+    Cache,
+    /// Installed dependencies such as `node_modules`, only with `--include-deps`
+    Dependency,
+}
+
+impl CleanTarget {
+    /// A target for `path`, if it exists
+    pub fn existing(path: impl Into<PathBuf>, kind: CleanKind, what: &str) -> Option<Self> {
+        let path = path.into();
+        path.exists().then(|| Self {
+            path,
+            kind,
+            what: what.to_string(),
+        })
+    }
+
+    /// Files directly in `dir` with the given extension
+    pub fn files_with_extension(dir: &Path, extension: &str, what: &str) -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<Self> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == extension))
+            .map(|path| Self {
+                path,
+                kind: CleanKind::Artifact,
+                what: what.to_string(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+}
+
+impl fmt::Display for PlannedCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+            if plain {
+                write!(f, " {arg}")?;
+            } else {
+                write!(f, " '{}'", arg.replace('\'', "'\\''"))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 pub trait WasmBuilder: Send + Sync {
@@ -66,6 +244,22 @@ pub trait WasmBuilder: Send + Sync {
         println!("Building {} project...", self.language_name());
         self.build(config)
     }
+
+    /// What `wasmrun clean` may delete for this project: build output, tool
+    /// caches and installed dependencies. Only paths that exist are listed.
+    fn clean_targets(&self, _project_path: &str) -> Vec<CleanTarget> {
+        Vec::new()
+    }
+
+    /// The steps `build` would take with `config`, without running them
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        Ok(BuildPlan::default().note(format!(
+            "The {} builder doesn't describe its steps; it builds {} into {}",
+            self.language_name(),
+            config.project_path,
+            config.output_dir
+        )))
+    }
 }
 
 pub trait CloneableWasmBuilder: WasmBuilder {
@@ -82,7 +276,83 @@ where
     }
 }
 
+impl OutputNaming {
+    const PLACEHOLDERS: [&'static str; 3] = ["{name}", "{profile}", "{target}"];
+
+    /// Check the name and pattern make a usable file stem
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if let Some(name) = &self.name {
+            check_stem(name, "Output name")?;
+        }
+        if let Some(pattern) = &self.pattern {
+            let mut rest = pattern.clone();
+            for placeholder in Self::PLACEHOLDERS {
+                rest = rest.replace(placeholder, "x");
+            }
+            if let Some(start) = rest.find('{') {
+                let end = rest[start..]
+                    .find('}')
+                    .map_or(rest.len(), |i| start + i + 1);
+                return Err(format!(
+                    "Unknown placeholder {} in name pattern {pattern:?} (expected {})",
+                    &rest[start..end],
+                    Self::PLACEHOLDERS.join(", ")
+                ));
+            }
+            check_stem(&rest, "Name pattern")?;
+        }
+        Ok(())
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn check_stem(stem: &str, what: &str) -> std::result::Result<(), String> {
+    if stem.is_empty() || stem == "." || stem == ".." {
+        return Err(format!("{what} {stem:?} is not a file name"));
+    }
+    if stem.contains(['/', '\\', '{', '}']) {
+        return Err(format!(
+            "{what} {stem:?} can't contain path separators or braces"
+        ));
+    }
+    Ok(())
+}
+
 impl BuildConfig {
+    /// The file stem for an artifact the toolchain would call `name`, built
+    /// for `target`
+    pub fn artifact_stem(&self, name: &str, target: &str) -> String {
+        if let Some(stem) = &self.naming.name {
+            return stem.clone();
+        }
+        match &self.naming.pattern {
+            Some(pattern) => pattern
+                .replace("{name}", name)
+                .replace("{profile}", &self.optimization_level.to_string())
+                .replace("{target}", target),
+            None => name.to_string(),
+        }
+    }
+
+    /// The directory artifacts built for `target` go in
+    pub fn artifact_dir(&self, target: &str) -> PathBuf {
+        let dir = Path::new(&self.output_dir);
+        if self.naming.target_dirs {
+            dir.join(target)
+        } else {
+            dir.to_path_buf()
+        }
+    }
+
+    /// `artifact_dir(target)/artifact_stem(name, target).extension`
+    pub fn artifact_path(&self, name: &str, target: &str, extension: &str) -> PathBuf {
+        self.artifact_dir(target)
+            .join(format!("{}.{extension}", self.artifact_stem(name, target)))
+    }
+
     #[allow(dead_code)] // TODO: Future builder pattern implementation
     pub fn new(
         project_path: String,
@@ -98,6 +368,7 @@ impl BuildConfig {
             verbose,
             watch,
             target_type: TargetType::Standard,
+            naming: OutputNaming::default(),
         }
     }
 
@@ -109,6 +380,7 @@ impl BuildConfig {
             verbose: false,
             watch: false,
             target_type: TargetType::Standard,
+            naming: OutputNaming::default(),
         }
     }
 }
@@ -127,6 +399,7 @@ impl BuildResult {
             js_path: None,
             additional_files: Vec::new(),
             is_wasm_bindgen: false,
+            target: None,
         }
     }
 
@@ -137,6 +410,7 @@ impl BuildResult {
             js_path: Some(js_path),
             additional_files: Vec::new(),
             is_wasm_bindgen: true,
+            target: None,
         }
     }
 
@@ -147,6 +421,7 @@ impl BuildResult {
             js_path: Some(index_path),
             additional_files: Vec::new(),
             is_wasm_bindgen: false,
+            target: None,
         }
     }
 
@@ -250,6 +525,12 @@ impl WasmBuilder for UnknownBuilder {
         println!("❌ Unknown language project");
         self.build(config)
     }
+
+    fn plan(&self, _config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        Err(crate::error::CompilationError::UnsupportedLanguage {
+            language: "Unknown".to_string(),
+        })
+    }
 }
 
 /// Build WASM project using plugin system
@@ -266,6 +547,7 @@ pub fn build_wasm_project(
         optimization_level: OptimizationLevel::Release,
         watch: false,
         target_type: TargetType::Standard,
+        naming: OutputNaming::default(),
     };
 
     // Try plugin-based building first
@@ -274,20 +556,73 @@ pub fn build_wasm_project(
             println!("🔌 Using plugin: {}", builder.language_name());
         }
         builder.validate_project(project_path)?;
-        return if verbose {
+        let result = if verbose {
             builder.build_verbose(&config)
         } else {
             builder.build(&config)
-        };
+        }?;
+        write_build_manifest(&config, &result, builder.language_name());
+        return Ok(result);
     }
 
     // Fall back to legacy language detection
     let builder = BuilderFactory::create_builder(language);
     builder.validate_project(project_path)?;
 
-    if verbose {
+    let result = if verbose {
         builder.build_verbose(&config)
     } else {
         builder.build(&config)
+    }?;
+    write_build_manifest(&config, &result, builder.language_name());
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_clean_targets_only_take_glue_beside_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["app.wasm", "app.js", "main.o", "site.js", "main.c"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        let builder = crate::plugin::languages::c_plugin::CPlugin::new();
+        let mut names: Vec<String> = builder
+            .clean_targets(&dir.path().to_string_lossy())
+            .iter()
+            .map(|t| t.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["app.js", "app.wasm", "main.o"]);
+    }
+
+    #[test]
+    fn test_output_naming() {
+        let mut config = BuildConfig::with_defaults("app".to_string(), "dist".to_string());
+        assert_eq!(
+            config.artifact_path("app", "wasm32-unknown-unknown", "wasm"),
+            Path::new("dist/app.wasm")
+        );
+
+        config.optimization_level = OptimizationLevel::Size;
+        config.naming.pattern = Some("{name}-{profile}-{target}".to_string());
+        assert_eq!(config.artifact_stem("app", "wasi"), "app-size-wasi");
+
+        config.naming.name = Some("bundle".to_string());
+        config.naming.target_dirs = true;
+        assert_eq!(
+            config.artifact_path("app", "emscripten", "js"),
+            Path::new("dist/emscripten/bundle.js")
+        );
+
+        assert!(config.naming.validate().is_ok());
+        config.naming.pattern = Some("{name}-{arch}".to_string());
+        let err = config.naming.validate().unwrap_err();
+        assert!(err.contains("{arch}"), "{err}");
+        config.naming.pattern = None;
+        config.naming.name = Some("../app".to_string());
+        assert!(config.naming.validate().is_err());
     }
 }
@@ -1,10 +1,11 @@
+use crate::config::project::{ProjectConfig, PROJECT_CONFIG_FILE};
 use crate::{debug_enter, debug_exit, debug_println};
 use std::fmt;
 use std::fs;
 use std::path::Path;
 
 /// Supported project languages
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ProjectLanguage {
     Rust,
     Go,
@@ -14,6 +15,32 @@ pub enum ProjectLanguage {
     Unknown,
 }
 
+impl ProjectLanguage {
+    /// Parse a `--language` value or `language` in wasmrun.toml
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" => Some(ProjectLanguage::Rust),
+            "go" | "tinygo" => Some(ProjectLanguage::Go),
+            "c" | "cpp" | "c++" => Some(ProjectLanguage::C),
+            "asc" | "assemblyscript" => Some(ProjectLanguage::Asc),
+            "python" | "py" => Some(ProjectLanguage::Python),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` accepts and plugins register
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectLanguage::Rust => "rust",
+            ProjectLanguage::Go => "go",
+            ProjectLanguage::C => "c",
+            ProjectLanguage::Asc => "asc",
+            ProjectLanguage::Python => "python",
+            ProjectLanguage::Unknown => "unknown",
+        }
+    }
+}
+
 /// Supported OS
 #[derive(Debug, PartialEq)]
 #[allow(dead_code)] // TODO: Future OS-specific compilation features
@@ -24,107 +51,255 @@ pub enum OperatingSystem {
     Other,
 }
 
-/// Detect project language
-pub fn detect_project_language(project_path: &str) -> ProjectLanguage {
-    debug_enter!("detect_project_language", "project_path={}", project_path);
+/// A language a project may be written in, and the evidence for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageCandidate {
+    pub language: ProjectLanguage,
+    pub score: u32,
+    pub reasons: Vec<String>,
+}
+
+/// Weight of a build manifest such as `Cargo.toml` or `go.mod`
+const MANIFEST_SCORE: u32 = 100;
+/// Weight of a weaker marker such as `requirements.txt` or a `Makefile`
+const MARKER_SCORE: u32 = 60;
+/// Weight of each source file, up to `SOURCE_SCORE_CAP` per language
+const SOURCE_SCORE: u32 = 2;
+const SOURCE_SCORE_CAP: u32 = 40;
+
+/// Directories that hold dependencies or build output, not the project's code
+const SKIPPED_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "vendor",
+    "build",
+    "dist",
+    "pkg",
+    "__pycache__",
+];
+
+/// Rank the languages `project_path` may be written in, most likely first.
+/// Manifests count most and each source file a little, so a repo with
+/// manifests for two languages goes to the one with more code. Ties keep
+/// the order Rust, Go, AssemblyScript, C, Python.
+pub fn detect_language_candidates(project_path: &str) -> Vec<LanguageCandidate> {
     let path = Path::new(project_path);
+    let mut candidates: Vec<LanguageCandidate> = [
+        ProjectLanguage::Rust,
+        ProjectLanguage::Go,
+        ProjectLanguage::Asc,
+        ProjectLanguage::C,
+        ProjectLanguage::Python,
+    ]
+    .into_iter()
+    .map(|language| LanguageCandidate {
+        language,
+        score: 0,
+        reasons: Vec::new(),
+    })
+    .collect();
+    let mut add = |language: ProjectLanguage, score: u32, reason: String| {
+        if let Some(candidate) = candidates.iter_mut().find(|c| c.language == language) {
+            candidate.score += score;
+            candidate.reasons.push(reason);
+        }
+    };
 
-    if !path.exists() || !path.is_dir() {
-        debug_println!(
-            "Project path validation failed: exists={}, is_dir={}",
-            path.exists(),
-            path.is_dir()
-        );
-        eprintln!("❌ Project path does not exist or is not a directory: {project_path}");
-        debug_exit!("detect_project_language", ProjectLanguage::Unknown);
-        return ProjectLanguage::Unknown;
+    let markers = [
+        ("Cargo.toml", ProjectLanguage::Rust, MANIFEST_SCORE),
+        ("go.mod", ProjectLanguage::Go, MANIFEST_SCORE),
+        // asconfig.json is the definitive indicator of an AssemblyScript project
+        ("asconfig.json", ProjectLanguage::Asc, MANIFEST_SCORE),
+        ("CMakeLists.txt", ProjectLanguage::C, MARKER_SCORE),
+        ("requirements.txt", ProjectLanguage::Python, MARKER_SCORE),
+        ("pyproject.toml", ProjectLanguage::Python, MARKER_SCORE),
+    ];
+    for (file, language, score) in markers {
+        if path.join(file).exists() {
+            debug_println!("Found {} - {} candidate", file, language);
+            add(language, score, file.to_string());
+        }
+    }
+    if let Ok(package_json) = fs::read_to_string(path.join("package.json")) {
+        if package_json.contains("assemblyscript") || package_json.contains("\"asc\"") {
+            add(
+                ProjectLanguage::Asc,
+                MARKER_SCORE,
+                "assemblyscript in package.json".to_string(),
+            );
+        }
+    }
+    if let Ok(makefile) = fs::read_to_string(path.join("Makefile")) {
+        if makefile.contains("emcc") {
+            add(
+                ProjectLanguage::C,
+                MARKER_SCORE,
+                "Makefile using emcc".to_string(),
+            );
+        }
     }
 
-    debug_println!("Checking for language-specific configuration files");
-    if path.join("Cargo.toml").exists() {
-        debug_println!("Found Cargo.toml - detected Rust project");
-        debug_exit!("detect_project_language", ProjectLanguage::Rust);
-        return ProjectLanguage::Rust;
+    for (language, extension, count) in count_source_files(path) {
+        let noun = if count == 1 { "file" } else { "files" };
+        add(
+            language,
+            (count * SOURCE_SCORE).min(SOURCE_SCORE_CAP),
+            format!("{count} .{extension} {noun}"),
+        );
     }
 
-    if path.join("go.mod").exists() {
-        debug_println!("Found go.mod - detected Go project");
-        debug_exit!("detect_project_language", ProjectLanguage::Go);
-        return ProjectLanguage::Go;
-    } else if let Ok(entries) = fs::read_dir(path) {
-        debug_println!("Scanning directory for Go source files");
+    candidates.retain(|c| c.score > 0);
+    // Stable, so equal scores keep the order above
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.score));
+    candidates
+}
+
+/// Count source files in the project root and the directories directly
+/// below it, per language
+fn count_source_files(path: &Path) -> Vec<(ProjectLanguage, &'static str, u32)> {
+    let extensions = [
+        ("rs", ProjectLanguage::Rust),
+        ("go", ProjectLanguage::Go),
+        ("c", ProjectLanguage::C),
+        ("cpp", ProjectLanguage::C),
+        ("py", ProjectLanguage::Python),
+    ];
+    let mut counts = vec![0u32; extensions.len()];
+    let mut assembly_ts = 0;
+
+    let mut dirs = vec![(path.to_path_buf(), true)];
+    while let Some((dir, is_root)) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
         for entry in entries.flatten() {
-            if let Some(extension) = entry.path().extension() {
-                let ext = extension.to_string_lossy().to_lowercase();
-                if ext == "go" {
-                    debug_println!("Found .go file - detected Go project");
-                    debug_exit!("detect_project_language", ProjectLanguage::Go);
-                    return ProjectLanguage::Go;
+            let entry_path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry_path.is_dir() {
+                if is_root && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                    dirs.push((entry_path, false));
                 }
+                continue;
+            }
+            let Some(ext) = entry_path.extension() else {
+                continue;
+            };
+            let ext = ext.to_string_lossy().to_lowercase();
+            if let Some(i) = extensions.iter().position(|(e, _)| *e == ext) {
+                counts[i] += 1;
+            } else if ext == "ts" && dir.ends_with("assembly") {
+                // AssemblyScript sources live in assembly/ by convention
+                assembly_ts += 1;
             }
         }
     }
 
-    // asconfig.json is the definitive indicator of an AssemblyScript project
-    if path.join("asconfig.json").exists() {
-        debug_println!("Found asconfig.json - detected AssemblyScript project");
-        return ProjectLanguage::Asc;
+    let mut found: Vec<_> = extensions
+        .iter()
+        .zip(counts)
+        .filter(|(_, count)| *count > 0)
+        .map(|((ext, language), count)| (*language, *ext, count))
+        .collect();
+    if assembly_ts > 0 {
+        found.push((ProjectLanguage::Asc, "ts", assembly_ts));
     }
+    found
+}
 
-    if let Ok(package_json) = fs::read_to_string(path.join("package.json")) {
-        if package_json.contains("assemblyscript") || package_json.contains("\"asc\"") {
-            debug_println!(
-                "Found assemblyscript in package.json - detected AssemblyScript project"
-            );
-            return ProjectLanguage::Asc;
-        }
+/// Whether the top two candidates are close enough that the choice may be
+/// wrong
+pub fn is_ambiguous(candidates: &[LanguageCandidate]) -> bool {
+    match candidates {
+        [first, second, ..] => second.score * 2 >= first.score,
+        _ => false,
     }
+}
 
-    let mut has_c_files = false;
+/// The language a user chose for `project_path`: `flag` (`--language`),
+/// else `language` in the project's wasmrun.toml. With neither and an
+/// ambiguous detection, the ranked candidates are printed with how to
+/// choose one.
+pub fn choose_project_language(project_path: &str, flag: Option<&str>) -> Option<String> {
+    if let Some(language) = flag {
+        return Some(language.to_string());
+    }
+    if let Some(language) = configured_language(project_path) {
+        println!("🎯 Using language = \"{language}\" from {PROJECT_CONFIG_FILE}");
+        return Some(language);
+    }
 
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            if let Some(extension) = entry.path().extension() {
-                let ext = extension.to_string_lossy().to_lowercase();
+    let candidates = detect_language_candidates(project_path);
+    if is_ambiguous(&candidates) {
+        print_language_candidates(project_path, &candidates);
+    }
+    None
+}
 
-                if ext == "c" {
-                    has_c_files = true;
-                }
-            }
+/// `language` from the project's wasmrun.toml, if set
+pub fn configured_language(project_path: &str) -> Option<String> {
+    match ProjectConfig::load(Path::new(project_path)) {
+        Ok(config) => config.and_then(|config| config.language),
+        Err(e) => {
+            eprintln!("⚠️  Ignoring {PROJECT_CONFIG_FILE}: {e}");
+            crate::ci::warn("config", format!("ignoring {PROJECT_CONFIG_FILE}: {e}"));
+            None
         }
     }
+}
 
-    if has_c_files {
-        return ProjectLanguage::C;
+fn print_language_candidates(project_path: &str, candidates: &[LanguageCandidate]) {
+    println!("🔎 Several languages found in {project_path}:");
+    for (i, candidate) in candidates.iter().enumerate() {
+        println!(
+            "   {}. {} (score {}): {}",
+            i + 1,
+            candidate.language,
+            candidate.score,
+            candidate.reasons.join(", ")
+        );
     }
-
-    if path.join("requirements.txt").exists() {
-        debug_println!("Found requirements.txt - detected Python project");
-        debug_exit!("detect_project_language", ProjectLanguage::Python);
-        return ProjectLanguage::Python;
+    if let [chosen, other, ..] = candidates {
+        println!(
+            "   Building as {}. Set language = \"{}\" in {PROJECT_CONFIG_FILE} or pass --language to choose another.",
+            chosen.language,
+            other.language.name()
+        );
     }
+}
 
-    if path.join("pyproject.toml").exists() {
-        debug_println!("Found pyproject.toml - detected Python project");
-        debug_exit!("detect_project_language", ProjectLanguage::Python);
-        return ProjectLanguage::Python;
+/// Detect project language: `language` in wasmrun.toml if set, else the
+/// best-ranked candidate
+pub fn detect_project_language(project_path: &str) -> ProjectLanguage {
+    debug_enter!("detect_project_language", "project_path={}", project_path);
+    let path = Path::new(project_path);
+
+    if !path.exists() || !path.is_dir() {
+        debug_println!(
+            "Project path validation failed: exists={}, is_dir={}",
+            path.exists(),
+            path.is_dir()
+        );
+        eprintln!("❌ Project path does not exist or is not a directory: {project_path}");
+        debug_exit!("detect_project_language", ProjectLanguage::Unknown);
+        return ProjectLanguage::Unknown;
     }
 
-    if let Ok(entries) = fs::read_dir(path) {
-        debug_println!("Scanning directory for Python source files");
-        for entry in entries.flatten() {
-            if let Some(extension) = entry.path().extension() {
-                let ext = extension.to_string_lossy().to_lowercase();
-                if ext == "py" {
-                    debug_println!("Found .py file - detected Python project");
-                    debug_exit!("detect_project_language", ProjectLanguage::Python);
-                    return ProjectLanguage::Python;
-                }
-            }
+    if let Some(language) = configured_language(project_path).and_then(|name| {
+        let language = ProjectLanguage::from_name(&name);
+        if language.is_none() {
+            debug_println!("{} names unknown language {}", PROJECT_CONFIG_FILE, name);
         }
+        language
+    }) {
+        debug_exit!("detect_project_language", language);
+        return language;
     }
 
-    ProjectLanguage::Unknown
+    let language = detect_language_candidates(project_path)
+        .first()
+        .map_or(ProjectLanguage::Unknown, |candidate| candidate.language);
+    debug_exit!("detect_project_language", language);
+    language
 }
 
 /// Detect the OS Wasmrun is running on
@@ -312,6 +487,72 @@ mod tests {
         assert_eq!(result, ProjectLanguage::Unknown);
     }
 
+    #[test]
+    fn test_detect_mixed_project_ranks_candidates() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path();
+        create_test_file(path, "Cargo.toml", "[package]\nname = \"test\"");
+        create_test_file(path, "requirements.txt", "");
+        fs::create_dir_all(path.join("src")).unwrap();
+        create_test_file(&path.join("src"), "lib.rs", "");
+        fs::create_dir_all(path.join("scripts")).unwrap();
+        for i in 0..3 {
+            create_test_file(&path.join("scripts"), &format!("s{i}.py"), "");
+        }
+        // Dependencies don't count
+        fs::create_dir_all(path.join("node_modules")).unwrap();
+        create_test_file(&path.join("node_modules"), "x.py", "");
+
+        let candidates = detect_language_candidates(path.to_str().unwrap());
+        let ranked: Vec<_> = candidates.iter().map(|c| (c.language, c.score)).collect();
+        assert_eq!(
+            ranked,
+            vec![(ProjectLanguage::Rust, 102), (ProjectLanguage::Python, 66)]
+        );
+        assert_eq!(
+            candidates[1].reasons,
+            vec!["requirements.txt", "3 .py files"]
+        );
+        assert!(is_ambiguous(&candidates));
+        assert_eq!(
+            detect_project_language(path.to_str().unwrap()),
+            ProjectLanguage::Rust
+        );
+
+        // wasmrun.toml overrides detection, and --language overrides that
+        create_test_file(path, PROJECT_CONFIG_FILE, "language = \"py\"\n");
+        let path = path.to_str().unwrap();
+        assert_eq!(detect_project_language(path), ProjectLanguage::Python);
+        assert_eq!(choose_project_language(path, None).as_deref(), Some("py"));
+        assert_eq!(
+            choose_project_language(path, Some("go")).as_deref(),
+            Some("go")
+        );
+    }
+
+    #[test]
+    fn test_project_language_from_name() {
+        assert_eq!(
+            ProjectLanguage::from_name("Rust"),
+            Some(ProjectLanguage::Rust)
+        );
+        assert_eq!(
+            ProjectLanguage::from_name("assemblyscript"),
+            Some(ProjectLanguage::Asc)
+        );
+        assert_eq!(ProjectLanguage::from_name("c++"), Some(ProjectLanguage::C));
+        assert_eq!(ProjectLanguage::from_name("cobol"), None);
+        for language in [
+            ProjectLanguage::Rust,
+            ProjectLanguage::Go,
+            ProjectLanguage::C,
+            ProjectLanguage::Asc,
+            ProjectLanguage::Python,
+        ] {
+            assert_eq!(ProjectLanguage::from_name(language.name()), Some(language));
+        }
+    }
+
     #[test]
     fn test_detect_operating_system() {
         let os = detect_operating_system();
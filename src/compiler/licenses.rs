@@ -0,0 +1,353 @@
+//! Licenses of a project's dependencies, gathered from the package
+//! managers (`cargo metadata`, `go list -m`, `npm ls`) and checked against
+//! the `[licenses]` denylist in `wasmrun.toml` before a build is shipped.
+
+use crate::config::project::LicenseConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// License files looked for in a Go module's directory
+const LICENSE_FILES: [&str; 5] = ["LICENSE", "LICENSE.md", "LICENSE.txt", "COPYING", "LICENCE"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyLicense {
+    pub name: String,
+    pub version: String,
+    /// Package URL type: `cargo`, `golang` or `npm`
+    pub ecosystem: String,
+    /// SPDX expression, or `None` when the package doesn't declare one
+    pub license: Option<String>,
+}
+
+impl DependencyLicense {
+    fn new(name: &str, version: &str, ecosystem: &str, license: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            ecosystem: ecosystem.to_string(),
+            license: license.filter(|l| !l.trim().is_empty()),
+        }
+    }
+}
+
+/// Every dependency of the project whose package manager is installed,
+/// sorted by ecosystem and name
+pub fn scan(project: &Path) -> Vec<DependencyLicense> {
+    let mut deps = Vec::new();
+    if project.join("Cargo.toml").is_file() {
+        if let Some(json) = run_json(project, "cargo", &["metadata", "--format-version", "1"]) {
+            deps.extend(cargo_licenses(&json));
+        }
+    }
+    if project.join("go.mod").is_file() {
+        if let Some(output) = run(project, "go", &["list", "-m", "-json", "all"]) {
+            deps.extend(go_licenses(&output));
+        }
+    }
+    if project.join("package.json").is_file() {
+        if let Some(json) = run_json(project, "npm", &["ls", "--all", "--json", "--long"]) {
+            deps.extend(npm_licenses(&json));
+        }
+    }
+    deps.sort_by(|a, b| {
+        (&a.ecosystem, &a.name, &a.version).cmp(&(&b.ecosystem, &b.name, &b.version))
+    });
+    deps.dedup();
+    deps
+}
+
+/// The dependencies `config` rejects, in the order given
+pub fn violations<'a>(
+    deps: &'a [DependencyLicense],
+    config: &LicenseConfig,
+) -> Vec<&'a DependencyLicense> {
+    deps.iter()
+        .filter(|dep| !config.allow_packages.iter().any(|name| name == &dep.name))
+        .filter(|dep| match &dep.license {
+            Some(license) => denied(license, &config.deny),
+            None => config.deny_unknown,
+        })
+        .collect()
+}
+
+/// How many dependencies use each license, most common first
+pub fn summary(deps: &[DependencyLicense]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for dep in deps {
+        let license = dep.license.clone().unwrap_or_else(|| "unknown".to_string());
+        *counts.entry(license).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+/// Whether an SPDX expression leaves no choice but a denied license. `OR`
+/// (and Cargo's old `/`) offers alternatives; `AND` needs every part.
+fn denied(expression: &str, deny: &[String]) -> bool {
+    let expression = expression.replace(['(', ')'], " ").replace('/', " OR ");
+    expression.split(" OR ").all(|alternative| {
+        alternative.split(" AND ").any(|term| {
+            // `GPL-2.0 WITH Classpath-exception-2.0` is still GPL-2.0
+            let id = term.split(" WITH ").next().unwrap_or(term).trim();
+            deny.iter().any(|pattern| license_matches(pattern, id))
+        })
+    })
+}
+
+/// Case-insensitive match, or a prefix match when the pattern ends in `*`
+fn license_matches(pattern: &str, id: &str) -> bool {
+    let (pattern, id) = (pattern.to_lowercase(), id.to_lowercase());
+    match pattern.strip_suffix('*') {
+        Some(prefix) => id.starts_with(prefix),
+        None => pattern == id,
+    }
+}
+
+/// Every package `cargo metadata` resolved, except the workspace's own
+fn cargo_licenses(metadata: &Value) -> Vec<DependencyLicense> {
+    let members: Vec<&str> = metadata["workspace_members"]
+        .as_array()
+        .map(|ids| ids.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|package| {
+            !package["id"]
+                .as_str()
+                .is_some_and(|id| members.contains(&id))
+        })
+        .filter_map(|package| {
+            Some(DependencyLicense::new(
+                package["name"].as_str()?,
+                package["version"].as_str()?,
+                "cargo",
+                package["license"].as_str().map(String::from),
+            ))
+        })
+        .collect()
+}
+
+/// Modules from `go list -m -json all`, a stream of JSON objects. Go has no
+/// license metadata, so the license comes from each module's license file.
+fn go_licenses(output: &str) -> Vec<DependencyLicense> {
+    serde_json::Deserializer::from_str(output)
+        .into_iter::<Value>()
+        .flatten()
+        .filter(|module| module["Main"].as_bool() != Some(true))
+        .filter_map(|module| {
+            let license = module["Dir"].as_str().and_then(|dir| {
+                LICENSE_FILES
+                    .iter()
+                    .find_map(|file| fs::read_to_string(Path::new(dir).join(file)).ok())
+                    .and_then(|text| identify_license(&text))
+            });
+            Some(DependencyLicense::new(
+                module["Path"].as_str()?,
+                module["Version"].as_str()?,
+                "golang",
+                license.map(String::from),
+            ))
+        })
+        .collect()
+}
+
+/// Installed packages from `npm ls --all --json --long`, at every depth
+fn npm_licenses(tree: &Value) -> Vec<DependencyLicense> {
+    let mut deps = Vec::new();
+    let mut pending = vec![tree];
+    while let Some(node) = pending.pop() {
+        let Some(children) = node["dependencies"].as_object() else {
+            continue;
+        };
+        for (name, child) in children {
+            if child["missing"].as_bool() == Some(true) {
+                continue;
+            }
+            let license = match &child["license"] {
+                Value::String(license) => Some(license.clone()),
+                // The old `{ "type": "MIT", "url": ... }` form
+                Value::Object(license) => license
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                _ => None,
+            };
+            if let Some(version) = child["version"].as_str() {
+                deps.push(DependencyLicense::new(name, version, "npm", license));
+            }
+            pending.push(child);
+        }
+    }
+    deps
+}
+
+/// The SPDX id of a common license from its text
+fn identify_license(text: &str) -> Option<&'static str> {
+    let has = |needle: &str| text.contains(needle);
+    let id = if has("GNU AFFERO GENERAL PUBLIC LICENSE") {
+        "AGPL-3.0"
+    } else if has("GNU LESSER GENERAL PUBLIC LICENSE") {
+        if has("Version 3") {
+            "LGPL-3.0"
+        } else {
+            "LGPL-2.1"
+        }
+    } else if has("GNU GENERAL PUBLIC LICENSE") {
+        if has("Version 3") {
+            "GPL-3.0"
+        } else {
+            "GPL-2.0"
+        }
+    } else if has("Mozilla Public License") {
+        "MPL-2.0"
+    } else if has("Apache License") {
+        "Apache-2.0"
+    } else if has("Permission is hereby granted, free of charge") {
+        "MIT"
+    } else if has("Permission to use, copy, modify, and/or distribute") {
+        "ISC"
+    } else if has("Redistribution and use in source and binary forms") {
+        if has("Neither the name") {
+            "BSD-3-Clause"
+        } else {
+            "BSD-2-Clause"
+        }
+    } else if has("This is free and unencumbered software") {
+        "Unlicense"
+    } else {
+        return None;
+    };
+    Some(id)
+}
+
+fn run(project: &Path, program: &str, args: &[&str]) -> Option<String> {
+    // `npm ls` exits non-zero for problems such as extraneous packages but
+    // still prints the tree, so only the output matters
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(project)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if stdout.trim().is_empty() {
+        let message = format!(
+            "`{program} {}` printed nothing; its dependencies are not checked",
+            args.join(" ")
+        );
+        eprintln!("⚠️  {message}");
+        crate::ci::warn("licenses", message);
+        return None;
+    }
+    Some(stdout)
+}
+
+fn run_json(project: &Path, program: &str, args: &[&str]) -> Option<Value> {
+    serde_json::from_str(&run(project, program, args)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deny(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_denied_follows_spdx_operators() {
+        let gpl = deny(&["GPL-3.0*", "AGPL-3.0"]);
+        assert!(denied("GPL-3.0-only", &gpl));
+        assert!(denied("agpl-3.0", &gpl));
+        assert!(!denied("MIT OR GPL-3.0-or-later", &gpl));
+        assert!(!denied("MIT/Apache-2.0", &gpl));
+        assert!(denied("MIT AND GPL-3.0-only", &gpl));
+        assert!(denied("(GPL-3.0-only WITH Classpath-exception-2.0)", &gpl));
+        assert!(!denied("LGPL-3.0", &gpl));
+    }
+
+    #[test]
+    fn test_violations_respect_unknown_and_allowed_packages() {
+        let deps = vec![
+            DependencyLicense::new("a", "1.0.0", "cargo", Some("GPL-3.0".into())),
+            DependencyLicense::new("b", "1.0.0", "npm", None),
+            DependencyLicense::new("c", "1.0.0", "npm", Some("MIT".into())),
+        ];
+        let mut config = LicenseConfig {
+            deny: deny(&["GPL-3.0"]),
+            ..LicenseConfig::default()
+        };
+        let names = |config: &LicenseConfig| -> Vec<String> {
+            violations(&deps, config)
+                .iter()
+                .map(|d| d.name.clone())
+                .collect()
+        };
+        assert_eq!(names(&config), ["a"]);
+        config.deny_unknown = true;
+        assert_eq!(names(&config), ["a", "b"]);
+        config.allow_packages = vec!["a".into()];
+        assert_eq!(names(&config), ["b"]);
+        assert_eq!(summary(&deps)[0].1, 1);
+    }
+
+    #[test]
+    fn test_package_manager_output() {
+        let metadata = json!({
+            "workspace_members": ["app 0.1.0 (path+file:///app)"],
+            "packages": [
+                {"id": "app 0.1.0 (path+file:///app)", "name": "app", "version": "0.1.0", "license": "GPL-3.0"},
+                {"id": "serde", "name": "serde", "version": "1.0.210", "license": "MIT OR Apache-2.0"},
+                {"id": "odd", "name": "odd", "version": "0.1.0", "license": null},
+            ]
+        });
+        let cargo = cargo_licenses(&metadata);
+        assert_eq!(cargo.len(), 2);
+        assert_eq!(cargo[0].license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(cargo[1].license, None);
+
+        let tree = json!({
+            "name": "app",
+            "dependencies": {
+                "left-pad": {"version": "1.3.0", "license": "WTFPL", "dependencies": {
+                    "nested": {"version": "2.0.0", "license": {"type": "MIT"}}
+                }},
+                "gone": {"required": "1.0.0", "missing": true}
+            }
+        });
+        let mut npm: Vec<(String, Option<String>)> = npm_licenses(&tree)
+            .into_iter()
+            .map(|d| (d.name, d.license))
+            .collect();
+        npm.sort();
+        assert_eq!(
+            npm,
+            [
+                ("left-pad".to_string(), Some("WTFPL".to_string())),
+                ("nested".to_string(), Some("MIT".to_string()))
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("LICENSE"),
+            "Apache License\nVersion 2.0, January 2004",
+        )
+        .unwrap();
+        let modules = format!(
+            "{{\"Path\": \"example.com/app\", \"Main\": true}}\n{{\"Path\": \"github.com/a/b\", \"Version\": \"v1.2.3\", \"Dir\": {:?}}}\n{{\"Path\": \"github.com/c/d\", \"Version\": \"v0.1.0\"}}",
+            dir.path().to_string_lossy()
+        );
+        let go = go_licenses(&modules);
+        assert_eq!(go.len(), 2);
+        assert_eq!(go[0].license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(go[1].license, None);
+    }
+}
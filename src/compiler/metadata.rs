@@ -0,0 +1,178 @@
+//! The `wasmrun.meta` custom section: the project, git commit, build time
+//! and toolchain a module was built from, embedded by `compile
+//! --embed-meta` so a deployed module says where it came from. `inspect`
+//! decodes it. Runtimes ignore custom sections, so the module behaves the
+//! same with or without it.
+
+use super::builder::BuildConfig;
+use super::sbom::{build_tools, project_identity, Tool};
+use crate::utils::{section_spans, GitState};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const META_SECTION: &str = "wasmrun.meta";
+
+/// The section's payload, stored as JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildMetadata {
+    pub project: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub git_commit: Option<String>,
+    /// The working tree had uncommitted changes
+    #[serde(default)]
+    pub git_dirty: bool,
+    /// RFC 3339; taken from `SOURCE_DATE_EPOCH` when it is set
+    pub built_at: String,
+    pub language: String,
+    pub profile: String,
+    #[serde(default)]
+    pub toolchain: Vec<Tool>,
+}
+
+impl BuildMetadata {
+    pub fn collect(config: &BuildConfig, language: &str, plugin: Option<Tool>) -> Self {
+        let project = Path::new(&config.project_path);
+        let (name, version) = project_identity(project);
+        let git = GitState::detect(project);
+        Self {
+            project: name,
+            version,
+            git_dirty: git.as_ref().is_some_and(|git| git.dirty),
+            git_commit: git.map(|git| git.commit),
+            built_at: build_time(),
+            language: language.to_string(),
+            profile: config.optimization_level.to_string(),
+            toolchain: build_tools(project, language, plugin),
+        }
+    }
+
+    /// The commit, shortened, with `-dirty` for uncommitted changes
+    pub fn revision(&self) -> Option<String> {
+        self.git_commit.as_ref().map(|commit| {
+            let short = &commit[..commit.len().min(12)];
+            if self.git_dirty {
+                format!("{short}-dirty")
+            } else {
+                short.to_string()
+            }
+        })
+    }
+}
+
+/// `module` with its `wasmrun.meta` section replaced by `meta`, appended
+/// after every other section
+pub fn embed(module: &[u8], meta: &BuildMetadata) -> Vec<u8> {
+    let mut out = module[..8.min(module.len())].to_vec();
+    let mut start = 8;
+    for span in section_spans(module) {
+        if !(span.id == 0 && span.name == META_SECTION) {
+            out.extend_from_slice(&module[start..span.range.end]);
+        }
+        start = span.range.end;
+    }
+
+    let payload = serde_json::to_vec(meta).unwrap_or_default();
+    let mut contents = Vec::with_capacity(payload.len() + META_SECTION.len() + 1);
+    write_leb(&mut contents, META_SECTION.len());
+    contents.extend_from_slice(META_SECTION.as_bytes());
+    contents.extend_from_slice(&payload);
+    out.push(0);
+    write_leb(&mut out, contents.len());
+    out.extend_from_slice(&contents);
+    out
+}
+
+/// The module's `wasmrun.meta` section: `None` when it has none, an error
+/// when the section can't be decoded
+pub fn read(module: &[u8]) -> Option<Result<BuildMetadata, String>> {
+    let span = section_spans(module)
+        .into_iter()
+        .find(|span| span.id == 0 && span.name == META_SECTION)?;
+    // The section name is a one-byte length and the name itself
+    let payload = &module[span.range.start + 1 + META_SECTION.len()..span.range.end];
+    Some(serde_json::from_slice(payload).map_err(|e| format!("Malformed {META_SECTION}: {e}")))
+}
+
+/// `SOURCE_DATE_EPOCH` if set, so reproducible builds stay reproducible;
+/// otherwise now
+fn build_time() -> String {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.trim().parse::<i64>().ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(chrono::Utc::now)
+        .to_rfc3339()
+}
+
+fn write_leb(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(commit: &str) -> BuildMetadata {
+        BuildMetadata {
+            project: "app".to_string(),
+            version: Some("0.1.0".to_string()),
+            git_commit: Some(commit.to_string()),
+            git_dirty: true,
+            built_at: "2025-01-01T00:00:00+00:00".to_string(),
+            language: "Rust".to_string(),
+            profile: "release".to_string(),
+            toolchain: vec![Tool {
+                name: "rustc".to_string(),
+                version: "1.85.0".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_embed_and_read_round_trip() {
+        // A type section and a `producers` custom section
+        let mut module = b"\0asm\x01\0\0\0\x01\x01\x00".to_vec();
+        module.extend(b"\x00\x0a\x09producers");
+        assert!(read(&module).is_none());
+
+        let first = embed(&module, &meta("0123456789abcdef0123"));
+        assert!(first.starts_with(&module));
+        assert_eq!(read(&first).unwrap().unwrap(), meta("0123456789abcdef0123"));
+
+        // Embedding again replaces the section instead of adding another
+        let second = embed(&first, &meta("fedcba"));
+        let sections = section_spans(&second);
+        assert_eq!(
+            sections.iter().filter(|s| s.name == META_SECTION).count(),
+            1
+        );
+        assert_eq!(sections.len(), 3);
+        let decoded = read(&second).unwrap().unwrap();
+        assert_eq!(decoded.revision().as_deref(), Some("fedcba-dirty"));
+    }
+
+    #[test]
+    fn test_build_time_follows_source_date_epoch() {
+        // Other tests don't read this variable, so setting it is safe
+        std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+        assert_eq!(build_time(), "2023-11-14T22:13:20+00:00");
+        std::env::remove_var("SOURCE_DATE_EPOCH");
+    }
+
+    #[test]
+    fn test_write_leb() {
+        let mut out = Vec::new();
+        write_leb(&mut out, 624485);
+        assert_eq!(out, [0xe5, 0x8e, 0x26]);
+    }
+}
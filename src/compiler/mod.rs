@@ -1,8 +1,16 @@
+pub mod artifacts;
 pub mod builder;
 mod detect;
+pub mod licenses;
+pub mod metadata;
+pub mod pipeline;
+pub mod retention;
+pub mod sbom;
+pub mod store;
 
 pub use builder::build_wasm_project;
 pub use detect::{
+    choose_project_language, configured_language, detect_language_candidates,
     detect_operating_system, detect_project_language, get_missing_tools, print_system_info,
     ProjectLanguage,
 };
@@ -11,8 +19,8 @@ use crate::error::{Result, WasmrunError};
 use crate::plugin::manager::PluginManager;
 use crate::utils::PathResolver;
 
-/// Compile a WASM file from a project directory using plugin system
-#[allow(dead_code)] // TODO: Future project compilation interface
+/// Compile a WASM file from a project directory using plugin system,
+/// returning the path of the built module
 pub fn create_wasm_from_project(project_path: &str, output_dir: &str) -> Result<String> {
     PathResolver::ensure_output_directory(output_dir)?;
 
@@ -25,6 +33,7 @@ pub fn create_wasm_from_project(project_path: &str, output_dir: &str) -> Result<
             );
 
             let result = builder.build(&config).map_err(WasmrunError::Compilation)?;
+            artifacts::write_build_manifest(&config, &result, builder.language_name());
             return Ok(result.wasm_path);
         }
     }
@@ -36,13 +45,18 @@ pub fn create_wasm_from_project(project_path: &str, output_dir: &str) -> Result<
     Ok(result.wasm_path)
 }
 
-/// AOT compile a project for execution using plugin system
-pub fn compile_for_execution(project_path: &str, output_dir: &str) -> Result<String> {
+/// AOT compile a project for execution using plugin system. `language`
+/// overrides detection, as from `--language` or wasmrun.toml.
+pub fn compile_for_execution(
+    project_path: &str,
+    output_dir: &str,
+    language: Option<&str>,
+) -> Result<String> {
     PathResolver::ensure_output_directory(output_dir)?;
 
     // Try plugin-based compilation first
     if let Ok(plugin_manager) = PluginManager::new() {
-        if let Some(plugin) = plugin_manager.find_plugin_for_project(project_path) {
+        if let Some(plugin) = plugin_manager.select_plugin(project_path, language) {
             let builder = plugin.get_builder();
 
             // Check dependencies
@@ -57,12 +71,17 @@ pub fn compile_for_execution(project_path: &str, output_dir: &str) -> Result<Str
             );
 
             let result = builder.build(&config).map_err(WasmrunError::Compilation)?;
+            artifacts::write_build_manifest(&config, &result, builder.language_name());
             return Ok(result.js_path.unwrap_or(result.wasm_path));
         }
     }
 
     // Fall back to legacy detection
-    let language_type = detect_project_language(project_path);
+    let language_type = match language {
+        Some(name) => ProjectLanguage::from_name(name)
+            .ok_or_else(|| WasmrunError::from(format!("Unsupported language: {name}")))?,
+        None => detect_project_language(project_path),
+    };
     let os = detect_operating_system();
 
     let missing_tools = get_missing_tools(&language_type, &os);
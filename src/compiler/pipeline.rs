@@ -0,0 +1,610 @@
+//! Named pipelines: post-build steps declared in `wasmrun.toml` and run in
+//! order once `wasmrun compile` has built the module.
+//!
+//! ```toml
+//! [pipelines.release]
+//! steps = [
+//!     { step = "wasm-opt", args = ["-Oz"] },
+//!     { step = "strip" },
+//!     { step = "hash" },
+//! ]
+//! ```
+//!
+//! `compile --pipeline release` runs that one; without the flag, a pipeline
+//! named `default` runs if the project has one. Steps that rewrite the
+//! module are cached in `pipeline/` under wasmrun's cache directory
+//! (`~/.wasmrun/cache` unless `cache_dir` is set) by their settings, tool
+//! version and input, so rebuilding an unchanged module skips them.
+
+use super::builder::BuildResult;
+use super::metadata::META_SECTION;
+use crate::config::project::{ProjectConfig, PROJECT_CONFIG_FILE};
+use crate::config::WasmrunConfig;
+use crate::error::{Result, WasmrunError};
+use crate::utils::{section_spans, CommandExecutor};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// The pipeline that runs when `--pipeline` isn't given
+pub const DEFAULT_PIPELINE: &str = "default";
+/// Name of a cached module in its cache entry
+const CACHED_MODULE: &str = "module.wasm";
+
+/// `[pipelines.<name>]` in `wasmrun.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub steps: Vec<Step>,
+}
+
+/// One post-build step, named by its `step` key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "kebab-case")]
+pub enum Step {
+    /// Optimize the module with binaryen's `wasm-opt`
+    WasmOpt {
+        #[serde(default = "default_wasm_opt_args")]
+        args: Vec<String>,
+    },
+    /// Drop custom sections such as debug info, names and producers.
+    /// `wasmrun.meta` and the sections in `keep` stay.
+    Strip {
+        #[serde(default)]
+        keep: Vec<String>,
+    },
+    /// Generate JS glue with `wasm-bindgen`
+    Bindgen {
+        #[serde(default = "default_bindgen_target")]
+        target: String,
+    },
+    /// Rename the module to `<name>.<hash>.wasm`, updating its glue, so it
+    /// can be cached forever
+    Hash,
+    /// Write `<name>.bundle.js`, an ES module with the module inlined, for
+    /// hosts that serve a single file
+    Bundle,
+}
+
+fn default_wasm_opt_args() -> Vec<String> {
+    vec!["-O".to_string()]
+}
+
+fn default_bindgen_target() -> String {
+    "web".to_string()
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::WasmOpt { args } => write!(f, "wasm-opt {}", args.join(" ")),
+            Step::Strip { keep } if keep.is_empty() => write!(f, "strip"),
+            Step::Strip { keep } => write!(f, "strip (keeping {})", keep.join(", ")),
+            Step::Bindgen { target } => write!(f, "wasm-bindgen --target {target}"),
+            Step::Hash => write!(f, "hash"),
+            Step::Bundle => write!(f, "bundle"),
+        }
+    }
+}
+
+/// A pipeline picked for a build
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// The pipeline `name` from the project's `wasmrun.toml`, or without a
+    /// name the `default` one, if declared. Asking for a pipeline the
+    /// project doesn't declare is an error.
+    pub fn select(project_path: &Path, name: Option<&str>) -> Result<Option<Self>> {
+        let pipelines = ProjectConfig::load(project_path)?
+            .map(|config| config.pipelines)
+            .unwrap_or_default();
+        let pipeline = |name: &str, config: &PipelineConfig| Self {
+            name: name.to_string(),
+            steps: config.steps.clone(),
+        };
+        let Some(name) = name else {
+            return Ok(pipelines
+                .get(DEFAULT_PIPELINE)
+                .map(|config| pipeline(DEFAULT_PIPELINE, config)));
+        };
+        match pipelines.get(name) {
+            Some(config) => Ok(Some(pipeline(name, config))),
+            None if pipelines.is_empty() => Err(WasmrunError::from(format!(
+                "No pipeline named '{name}': {PROJECT_CONFIG_FILE} declares none"
+            ))),
+            None => Err(WasmrunError::from(format!(
+                "No pipeline named '{name}' (declared: {})",
+                pipelines.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+
+    /// Run the steps in order over the build's outputs, updating `result`
+    /// to the files the last step left. Web-app builds, whose output is a
+    /// directory, are left alone.
+    pub fn run(&self, result: &mut BuildResult, cache: Option<&StepCache>) -> Result<()> {
+        if !Path::new(&result.wasm_path).is_file() {
+            println!(
+                "⏭️  Pipeline '{}' skipped: {} isn't a module",
+                self.name, result.wasm_path
+            );
+            return Ok(());
+        }
+        println!("🧪 Pipeline '{}': {} step(s)", self.name, self.steps.len());
+        for (i, step) in self.steps.iter().enumerate() {
+            let started = Instant::now();
+            let cached = step.apply(result, cache).map_err(|e| {
+                WasmrunError::from(format!("Pipeline '{}' step {step} failed: {e}", self.name))
+            })?;
+            let how = if cached { "cached" } else { "done" };
+            println!("   {}. {step}: {how} in {:.2?}", i + 1, started.elapsed());
+        }
+        Ok(())
+    }
+}
+
+impl Step {
+    /// Run the step; `true` when its output came from the cache
+    fn apply(&self, result: &mut BuildResult, cache: Option<&StepCache>) -> Result<bool> {
+        let wasm = PathBuf::from(&result.wasm_path);
+        match self {
+            Step::WasmOpt { args } => {
+                require_tool("wasm-opt", "install binaryen")?;
+                let key = self.cache_key(&wasm, &tool_version("wasm-opt"))?;
+                transform(&wasm, cache, &key, |input, output| {
+                    let (input, output) = (input.to_string_lossy(), output.to_string_lossy());
+                    let mut command: Vec<&str> = vec![&input];
+                    command.extend(args.iter().map(String::as_str));
+                    command.extend(["-o", &output]);
+                    run_tool("wasm-opt", &command)
+                })
+            }
+            Step::Strip { keep } => {
+                let key = self.cache_key(&wasm, "")?;
+                transform(&wasm, cache, &key, |input, output| {
+                    let module = fs::read(input)?;
+                    fs::write(output, strip_custom_sections(&module, keep))?;
+                    Ok(())
+                })
+            }
+            Step::Bindgen { target } => bindgen(self, result, target, cache),
+            Step::Hash => {
+                hash(result)?;
+                Ok(false)
+            }
+            Step::Bundle => {
+                bundle(result)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// The step's settings, the tool's version and the input module
+    fn cache_key(&self, input: &Path, tool_version: &str) -> Result<String> {
+        let module = fs::read(input)
+            .map_err(|e| WasmrunError::add_context(format!("Reading {}", input.display()), e))?;
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_vec(self).unwrap_or_default());
+        hasher.update(tool_version.as_bytes());
+        hasher.update(&module);
+        Ok(hex(&hasher.finalize()))
+    }
+}
+
+/// Replace the module at `wasm` with what `run` writes from it, or with
+/// the cached result for `key`
+fn transform(
+    wasm: &Path,
+    cache: Option<&StepCache>,
+    key: &str,
+    run: impl FnOnce(&Path, &Path) -> Result<()>,
+) -> Result<bool> {
+    if let Some(hit) = cache.and_then(|cache| cache.get(key)) {
+        fs::copy(hit.join(CACHED_MODULE), wasm)?;
+        return Ok(true);
+    }
+    let output = wasm.with_extension("pipeline.wasm");
+    run(wasm, &output)?;
+    if let Some(cache) = cache {
+        if let Err(e) = cache.put(key, &[(CACHED_MODULE, &output)]) {
+            eprintln!("⚠️  Failed to cache the step's output: {e}");
+        }
+    }
+    fs::rename(&output, wasm)?;
+    Ok(false)
+}
+
+/// Run wasm-bindgen over a plain module, replacing it with the glue and
+/// the module wasm-bindgen writes beside it
+fn bindgen(
+    step: &Step,
+    result: &mut BuildResult,
+    target: &str,
+    cache: Option<&StepCache>,
+) -> Result<bool> {
+    if result.js_path.is_some() {
+        return Err(WasmrunError::from(
+            "the build already generated JS glue".to_string(),
+        ));
+    }
+    require_tool("wasm-bindgen", "cargo install wasm-bindgen-cli")?;
+    let wasm = PathBuf::from(&result.wasm_path);
+    let out_dir = wasm.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let key = step.cache_key(&wasm, &tool_version("wasm-bindgen"))?;
+
+    let cached = cache.and_then(|cache| cache.get(&key));
+    let staging = tempfile::tempdir()?;
+    let generated = match &cached {
+        Some(entry) => entry.clone(),
+        None => {
+            run_tool(
+                "wasm-bindgen",
+                &[
+                    &wasm.to_string_lossy(),
+                    "--out-dir",
+                    &staging.path().to_string_lossy(),
+                    "--target",
+                    target,
+                ],
+            )?;
+            staging.path().to_path_buf()
+        }
+    };
+    let files = list_files(&generated)?;
+    if let (None, Some(cache)) = (&cached, cache) {
+        let named: Vec<(&str, &Path)> = files
+            .iter()
+            .map(|(name, path)| (name.as_str(), path.as_path()))
+            .collect();
+        if let Err(e) = cache.put(&key, &named) {
+            eprintln!("⚠️  Failed to cache the step's output: {e}");
+        }
+    }
+    copy_files(&files, &out_dir)?;
+
+    // wasm-bindgen names its outputs after the input module
+    let stem = file_stem(&wasm);
+    let glue = format!("{stem}.js");
+    let module = format!("{stem}_bg.wasm");
+    if !files.iter().any(|(name, _)| *name == glue) {
+        return Err(WasmrunError::from(format!(
+            "wasm-bindgen didn't write {glue}"
+        )));
+    }
+    let path = |name: &str| out_dir.join(name).to_string_lossy().to_string();
+    if out_dir.join(&module) != wasm {
+        let _ = fs::remove_file(&wasm);
+    }
+    result.wasm_path = path(&module);
+    result.js_path = Some(path(&glue));
+    result.is_wasm_bindgen = true;
+    for (name, _) in &files {
+        if *name != glue && *name != module && !result.additional_files.contains(&path(name)) {
+            result.additional_files.push(path(name));
+        }
+    }
+    Ok(cached.is_some())
+}
+
+/// Rename the module to carry a hash of its contents, and point its glue at
+/// the new name
+fn hash(result: &mut BuildResult) -> Result<()> {
+    let wasm = PathBuf::from(&result.wasm_path);
+    let module = fs::read(&wasm)?;
+    let digest = hex(&Sha256::digest(&module));
+    let old_name = file_name(&wasm);
+    let new_name = format!("{}.{}.wasm", file_stem(&wasm), &digest[..12]);
+    let hashed = wasm.with_file_name(&new_name);
+    fs::rename(&wasm, &hashed)?;
+
+    if let Some(js) = &result.js_path {
+        let glue = fs::read_to_string(js)?;
+        fs::write(js, glue.replace(&old_name, &new_name))?;
+    }
+    result.wasm_path = hashed.to_string_lossy().to_string();
+    Ok(())
+}
+
+/// Write `<name>.bundle.js` with the module inlined as base64
+fn bundle(result: &mut BuildResult) -> Result<()> {
+    if result.js_path.is_some() {
+        return Err(WasmrunError::from(
+            "bundling needs a plain module; wasm-bindgen output already loads through its glue"
+                .to_string(),
+        ));
+    }
+    let wasm = PathBuf::from(&result.wasm_path);
+    let module = fs::read(&wasm)?;
+    let stem = file_stem(&wasm);
+    let bundle = wasm.with_file_name(format!(
+        "{}.bundle.js",
+        stem.split('.').next().unwrap_or(&stem)
+    ));
+    fs::write(&bundle, bundle_script(&file_name(&wasm), &module))?;
+    let bundle = bundle.to_string_lossy().to_string();
+    if !result.additional_files.contains(&bundle) {
+        result.additional_files.push(bundle);
+    }
+    Ok(())
+}
+
+fn bundle_script(name: &str, module: &[u8]) -> String {
+    let base64 = base64::engine::general_purpose::STANDARD.encode(module);
+    format!(
+        r#"// {name}, inlined by wasmrun's bundle step
+const base64 = "{base64}";
+
+export function bytes() {{
+  return Uint8Array.from(atob(base64), (c) => c.charCodeAt(0));
+}}
+
+export async function instantiate(imports = {{}}) {{
+  const {{ instance }} = await WebAssembly.instantiate(bytes(), imports);
+  return instance;
+}}
+
+export default instantiate;
+"#
+    )
+}
+
+/// `module` without its custom sections, except `wasmrun.meta` and those
+/// named in `keep`
+pub fn strip_custom_sections(module: &[u8], keep: &[String]) -> Vec<u8> {
+    let mut out = module[..8.min(module.len())].to_vec();
+    let mut start = 8;
+    for span in section_spans(module) {
+        let kept = span.id != 0 || span.name == META_SECTION || keep.contains(&span.name);
+        if kept {
+            out.extend_from_slice(&module[start..span.range.end]);
+        }
+        start = span.range.end;
+    }
+    out
+}
+
+/// Step outputs kept by a hash of the step's settings, tool version and
+/// input, one directory per entry
+pub struct StepCache {
+    dir: PathBuf,
+}
+
+impl StepCache {
+    /// The cache in wasmrun's cache directory; `None` when there's no home
+    /// directory
+    pub fn open() -> Option<Self> {
+        WasmrunConfig::cache_dir()
+            .ok()
+            .map(|dir| Self::at(dir.join("pipeline")))
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn get(&self, key: &str) -> Option<PathBuf> {
+        let entry = self.dir.join(key);
+        entry.is_dir().then_some(entry)
+    }
+
+    /// Store `files` under `key`, by name; the entry's directory
+    fn put(&self, key: &str, files: &[(&str, &Path)]) -> std::io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        // Written aside and renamed, so a reader never sees half an entry
+        let staging = tempfile::tempdir_in(&self.dir)?;
+        for (name, path) in files {
+            fs::copy(path, staging.path().join(name))?;
+        }
+        let entry = self.dir.join(key);
+        let staged = staging.keep();
+        if fs::rename(&staged, &entry).is_err() {
+            // Another build stored it first
+            let _ = fs::remove_dir_all(&staged);
+        }
+        Ok(entry)
+    }
+}
+
+fn require_tool(tool: &str, install: &str) -> Result<()> {
+    if CommandExecutor::is_tool_installed(tool) {
+        Ok(())
+    } else {
+        Err(WasmrunError::from(format!(
+            "{tool} isn't installed ({install})"
+        )))
+    }
+}
+
+/// What `tool --version` prints, to key the cache on
+fn tool_version(tool: &str) -> String {
+    CommandExecutor::execute_command(tool, &["--version"], ".", false)
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+fn run_tool(tool: &str, args: &[&str]) -> Result<()> {
+    let output = CommandExecutor::execute_command(tool, args, ".", false)
+        .map_err(WasmrunError::Compilation)?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(WasmrunError::from(format!(
+        "{tool} exited with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+fn list_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push((file_name(&path), path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn copy_files(files: &[(String, PathBuf)], dir: &Path) -> Result<()> {
+    for (name, path) in files {
+        fs::copy(path, dir.join(name))?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// A module with a type section and the given custom sections
+    fn module(custom: &[&str]) -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        bytes.extend([1, 1, 0]);
+        for name in custom {
+            bytes.extend([0, name.len() as u8 + 2, name.len() as u8]);
+            bytes.extend(name.as_bytes());
+            bytes.push(7);
+        }
+        bytes
+    }
+
+    fn build(dir: &Path, bytes: &[u8]) -> BuildResult {
+        let wasm = dir.join("app.wasm");
+        fs::write(&wasm, bytes).unwrap();
+        BuildResult {
+            wasm_path: wasm.to_string_lossy().to_string(),
+            js_path: None,
+            additional_files: Vec::new(),
+            is_wasm_bindgen: false,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn test_select_reads_pipelines_from_project_config() {
+        let dir = tempdir().unwrap();
+        assert_eq!(Pipeline::select(dir.path(), None).unwrap(), None);
+        assert!(Pipeline::select(dir.path(), Some("release")).is_err());
+
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            r#"
+[pipelines.default]
+steps = [{ step = "strip" }]
+
+[pipelines.release]
+steps = [
+    { step = "wasm-opt", args = ["-Oz"] },
+    { step = "bindgen" },
+    { step = "strip", keep = ["name"] },
+    { step = "hash" },
+]
+"#,
+        )
+        .unwrap();
+        let default = Pipeline::select(dir.path(), None).unwrap().unwrap();
+        assert_eq!(default.steps, vec![Step::Strip { keep: Vec::new() }]);
+
+        let release = Pipeline::select(dir.path(), Some("release"))
+            .unwrap()
+            .unwrap();
+        let steps: Vec<String> = release.steps.iter().map(Step::to_string).collect();
+        assert_eq!(
+            steps,
+            [
+                "wasm-opt -Oz",
+                "wasm-bindgen --target web",
+                "strip (keeping name)",
+                "hash"
+            ]
+        );
+        let unknown = Pipeline::select(dir.path(), Some("docs")).unwrap_err();
+        assert!(unknown.to_string().contains("declared: default, release"));
+    }
+
+    #[test]
+    fn test_strip_keeps_meta_and_listed_sections() {
+        let stripped = strip_custom_sections(
+            &module(&["name", META_SECTION, "producers", "keepme"]),
+            &["keepme".to_string()],
+        );
+        assert_eq!(stripped, module(&[META_SECTION, "keepme"]));
+    }
+
+    #[test]
+    fn test_steps_are_cached_by_input() {
+        let dir = tempdir().unwrap();
+        let cache = StepCache::at(dir.path().join("cache"));
+        let pipeline = Pipeline {
+            name: "small".to_string(),
+            steps: vec![Step::Strip { keep: Vec::new() }],
+        };
+
+        let mut result = build(dir.path(), &module(&["name"]));
+        let step = &pipeline.steps[0];
+        assert!(!step.apply(&mut result, Some(&cache)).unwrap());
+        assert_eq!(fs::read(&result.wasm_path).unwrap(), module(&[]));
+
+        let mut result = build(dir.path(), &module(&["name"]));
+        assert!(step.apply(&mut result, Some(&cache)).unwrap());
+        assert_eq!(fs::read(&result.wasm_path).unwrap(), module(&[]));
+
+        let mut result = build(dir.path(), &module(&["producers"]));
+        assert!(!step.apply(&mut result, Some(&cache)).unwrap());
+    }
+
+    #[test]
+    fn test_hash_renames_module_and_updates_glue() {
+        let dir = tempdir().unwrap();
+        let mut result = build(dir.path(), &module(&[]));
+        let js = dir.path().join("app.js");
+        fs::write(&js, "fetch(new URL('app.wasm', import.meta.url))").unwrap();
+        result.js_path = Some(js.to_string_lossy().to_string());
+
+        hash(&mut result).unwrap();
+        let hashed = file_name(Path::new(&result.wasm_path));
+        assert!(hashed.starts_with("app.") && hashed.len() == "app..wasm".len() + 12);
+        assert!(!dir.path().join("app.wasm").exists());
+        assert!(fs::read_to_string(&js).unwrap().contains(&hashed));
+    }
+
+    #[test]
+    fn test_bundle_inlines_plain_modules_only() {
+        let dir = tempdir().unwrap();
+        let mut result = build(dir.path(), &module(&[]));
+        bundle(&mut result).unwrap();
+        let script = fs::read_to_string(dir.path().join("app.bundle.js")).unwrap();
+        assert!(script.contains(&base64::engine::general_purpose::STANDARD.encode(module(&[]))));
+        assert_eq!(result.additional_files.len(), 1);
+
+        result.js_path = Some("app.js".to_string());
+        assert!(bundle(&mut result).is_err());
+    }
+}
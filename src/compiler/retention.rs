@@ -0,0 +1,210 @@
+//! Watch-mode build history. Each rebuild goes to its own numbered
+//! directory, so a page still loading the previous build isn't handed a
+//! half-written one, and old builds are pruned to the `[watch]` limits in
+//! `wasmrun.toml` after every successful rebuild.
+
+use crate::utils::dir_size;
+use crate::utils::CommandExecutor;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const BUILD_PREFIX: &str = "build-";
+
+/// `[watch]` in wasmrun.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Builds to keep, newest first; the latest build is always kept
+    pub keep_builds: usize,
+    /// Total size the kept builds may take, in MB; 0 or unset means no limit
+    pub max_total_mb: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_builds: 5,
+            max_total_mb: None,
+        }
+    }
+}
+
+/// What a prune kept and removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    pub kept: usize,
+    pub kept_bytes: u64,
+    pub pruned: usize,
+    pub pruned_bytes: u64,
+}
+
+impl PruneSummary {
+    /// The line printed after a rebuild
+    pub fn line(&self) -> String {
+        let size = CommandExecutor::format_file_size(self.kept_bytes);
+        if self.pruned == 0 {
+            crate::t!("watch.retained", kept = self.kept, size = size)
+        } else {
+            crate::t!(
+                "watch.pruned",
+                pruned = self.pruned,
+                freed = CommandExecutor::format_file_size(self.pruned_bytes),
+                kept = self.kept,
+                size = size
+            )
+        }
+    }
+}
+
+pub struct BuildHistory {
+    root: PathBuf,
+    next: u64,
+    policy: RetentionPolicy,
+}
+
+impl BuildHistory {
+    /// Builds under `root`, numbered on from any a previous session left
+    pub fn new(root: impl Into<PathBuf>, policy: RetentionPolicy) -> Self {
+        let root = root.into();
+        let next = builds(&root).last().map_or(1, |(number, _)| number + 1);
+        Self { root, next, policy }
+    }
+
+    /// The directory the numbered builds go in
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Create the directory for the next build
+    pub fn next_build_dir(&mut self) -> io::Result<PathBuf> {
+        let dir = self.root.join(format!("{BUILD_PREFIX}{:04}", self.next));
+        self.next += 1;
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Drop a build that failed, so it never counts as the latest
+    pub fn discard(&self, dir: &Path) {
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    /// Delete the oldest builds until the rest fit the policy
+    pub fn prune(&self) -> PruneSummary {
+        let mut builds: Vec<(PathBuf, u64)> = builds(&self.root)
+            .into_iter()
+            .map(|(_, dir)| {
+                let size = dir_size(&dir);
+                (dir, size)
+            })
+            .collect();
+        let keep = self.policy.keep_builds.max(1);
+        let max_bytes = self
+            .policy
+            .max_total_mb
+            .filter(|mb| *mb > 0)
+            .map(|mb| mb * 1024 * 1024);
+
+        let mut summary = PruneSummary::default();
+        let mut total: u64 = builds.iter().map(|(_, size)| size).sum();
+        while builds.len() > 1 && (builds.len() > keep || max_bytes.is_some_and(|max| total > max))
+        {
+            let (dir, size) = builds.remove(0);
+            if fs::remove_dir_all(&dir).is_ok() {
+                summary.pruned += 1;
+                summary.pruned_bytes += size;
+            }
+            total -= size;
+        }
+        summary.kept = builds.len();
+        summary.kept_bytes = total;
+        summary
+    }
+}
+
+/// Numbered build directories under `root`, oldest first
+fn builds(root: &Path) -> Vec<(u64, PathBuf)> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut builds: Vec<(u64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let number = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(BUILD_PREFIX)?
+                .parse()
+                .ok()?;
+            Some((number, entry.path()))
+        })
+        .collect();
+    builds.sort();
+    builds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn build(history: &mut BuildHistory, bytes: usize) -> PathBuf {
+        let dir = history.next_build_dir().unwrap();
+        fs::write(dir.join("app.wasm"), vec![0u8; bytes]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_builds() {
+        let root = tempdir().unwrap();
+        let mut history = BuildHistory::new(
+            root.path(),
+            RetentionPolicy {
+                keep_builds: 2,
+                max_total_mb: None,
+            },
+        );
+        let first = build(&mut history, 10);
+        build(&mut history, 10);
+        let third = build(&mut history, 10);
+        let summary = history.prune();
+        assert_eq!(
+            summary,
+            PruneSummary {
+                kept: 2,
+                kept_bytes: 20,
+                pruned: 1,
+                pruned_bytes: 10
+            }
+        );
+        assert!(!first.exists() && third.exists());
+
+        // A new session numbers on from the builds already there
+        let mut resumed = BuildHistory::new(root.path(), RetentionPolicy::default());
+        assert!(resumed
+            .next_build_dir()
+            .unwrap()
+            .ends_with(format!("{BUILD_PREFIX}0004")));
+    }
+
+    #[test]
+    fn test_prune_by_size_keeps_latest() {
+        let root = tempdir().unwrap();
+        let mut history = BuildHistory::new(
+            root.path(),
+            RetentionPolicy {
+                keep_builds: 10,
+                max_total_mb: Some(1),
+            },
+        );
+        build(&mut history, 600 * 1024);
+        build(&mut history, 600 * 1024);
+        let latest = build(&mut history, 2 * 1024 * 1024);
+        let summary = history.prune();
+        assert_eq!((summary.kept, summary.pruned), (1, 2));
+        assert!(latest.exists());
+        assert!(summary.line().contains("Pruned 2"));
+    }
+}
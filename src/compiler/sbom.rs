@@ -0,0 +1,600 @@
+//! Software bill of materials for a build, written beside the artifacts
+//! with `compile --sbom`. It records the project's direct dependencies
+//! from its lockfiles, the toolchain, plugin and wasmrun versions that
+//! built it, and the SHA-256 of every artifact, as CycloneDX or SPDX JSON.
+
+use super::artifacts::BuildManifest;
+use super::builder::{BuildConfig, BuildResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const CYCLONEDX_FILE: &str = "wasmrun-sbom.cdx.json";
+pub const SPDX_FILE: &str = "wasmrun-sbom.spdx.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+impl SbomFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "cyclonedx" | "cdx" => Some(Self::CycloneDx),
+            "spdx" => Some(Self::Spdx),
+            _ => None,
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::CycloneDx => CYCLONEDX_FILE,
+            Self::Spdx => SPDX_FILE,
+        }
+    }
+}
+
+/// A direct dependency as its lockfile pins it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub version: String,
+    /// Package URL type: `cargo`, `golang` or `npm`
+    pub ecosystem: &'static str,
+}
+
+impl Dependency {
+    fn purl(&self) -> String {
+        format!("pkg:{}/{}@{}", self.ecosystem, self.name, self.version)
+    }
+}
+
+/// Something that took part in the build, with the version it reported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tool {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sbom {
+    pub name: String,
+    pub version: Option<String>,
+    pub tools: Vec<Tool>,
+    pub dependencies: Vec<Dependency>,
+    pub manifest: BuildManifest,
+}
+
+impl Sbom {
+    /// Describe a finished build. `plugin` is the name and version of the
+    /// plugin that built it, if one did.
+    pub fn collect(project_path: &str, manifest: BuildManifest, plugin: Option<Tool>) -> Self {
+        let project = Path::new(project_path);
+        let (name, version) = project_identity(project);
+        Self {
+            name,
+            version,
+            tools: build_tools(project, &manifest.language, plugin),
+            dependencies: direct_dependencies(project),
+            manifest,
+        }
+    }
+
+    pub fn to_cyclonedx(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|tool| json!({"type": "application", "name": tool.name, "version": tool.version}))
+            .collect();
+        let mut project = json!({"type": "application", "bom-ref": "project", "name": self.name});
+        if let Some(version) = &self.version {
+            project["version"] = json!(version);
+        }
+
+        let mut components: Vec<Value> = self
+            .manifest
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                json!({
+                    "type": "file",
+                    "bom-ref": format!("file:{}", artifact.path),
+                    "name": artifact.path,
+                    "hashes": [{"alg": "SHA-256", "content": artifact.sha256}],
+                })
+            })
+            .collect();
+        components.extend(self.dependencies.iter().map(|dep| {
+            json!({
+                "type": "library",
+                "bom-ref": dep.purl(),
+                "name": dep.name,
+                "version": dep.version,
+                "purl": dep.purl(),
+            })
+        }));
+        let depends_on: Vec<String> = self.dependencies.iter().map(Dependency::purl).collect();
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "timestamp": self.manifest.built_at,
+                "tools": {"components": tools},
+                "component": project,
+                "properties": [
+                    {"name": "wasmrun:language", "value": self.manifest.language},
+                    {"name": "wasmrun:profile", "value": self.manifest.profile},
+                ],
+            },
+            "components": components,
+            "dependencies": [{"ref": "project", "dependsOn": depends_on}],
+        })
+    }
+
+    pub fn to_spdx(&self) -> Value {
+        let mut creators = vec![format!("Tool: wasmrun-{}", env!("CARGO_PKG_VERSION"))];
+        creators.extend(
+            self.tools
+                .iter()
+                .skip(1)
+                .map(|tool| format!("Tool: {}-{}", tool.name, tool.version)),
+        );
+        let mut project = json!({
+            "SPDXID": "SPDXRef-Project",
+            "name": self.name,
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": false,
+        });
+        if let Some(version) = &self.version {
+            project["versionInfo"] = json!(version);
+        }
+
+        let mut packages = vec![project];
+        let mut relationships = vec![json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": "SPDXRef-Project",
+        })];
+        for (i, dep) in self.dependencies.iter().enumerate() {
+            let id = format!("SPDXRef-Dependency-{i}");
+            packages.push(json!({
+                "SPDXID": id,
+                "name": dep.name,
+                "versionInfo": dep.version,
+                "downloadLocation": "NOASSERTION",
+                "filesAnalyzed": false,
+                "externalRefs": [{
+                    "referenceCategory": "PACKAGE-MANAGER",
+                    "referenceType": "purl",
+                    "referenceLocator": dep.purl(),
+                }],
+            }));
+            relationships.push(json!({
+                "spdxElementId": "SPDXRef-Project",
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": id,
+            }));
+        }
+        let files: Vec<Value> = self
+            .manifest
+            .artifacts
+            .iter()
+            .enumerate()
+            .map(|(i, artifact)| {
+                let id = format!("SPDXRef-File-{i}");
+                relationships.push(json!({
+                    "spdxElementId": "SPDXRef-Project",
+                    "relationshipType": "GENERATES",
+                    "relatedSpdxElement": id,
+                }));
+                json!({
+                    "SPDXID": id,
+                    "fileName": format!("./{}", artifact.path),
+                    "checksums": [{"algorithm": "SHA256", "checksumValue": artifact.sha256}],
+                })
+            })
+            .collect();
+
+        // Unique per build: the first artifact's hash identifies its contents
+        let digest = self
+            .manifest
+            .artifacts
+            .first()
+            .map_or("empty", |a| &a.sha256[..16]);
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": self.name,
+            "documentNamespace": format!(
+                "{}/spdx/{}-{}",
+                env!("CARGO_PKG_REPOSITORY"),
+                self.name,
+                digest
+            ),
+            "creationInfo": {"created": self.manifest.built_at, "creators": creators},
+            "packages": packages,
+            "files": files,
+            "relationships": relationships,
+        })
+    }
+}
+
+/// Write the SBOM for a successful build to the output directory. Like the
+/// build manifest, failing to write one is reported but doesn't fail the
+/// build.
+pub fn write_sbom(
+    config: &BuildConfig,
+    result: &BuildResult,
+    language: &str,
+    format: SbomFormat,
+    plugin: Option<Tool>,
+) -> Option<PathBuf> {
+    let path = Path::new(&config.output_dir).join(format.file_name());
+    let written = BuildManifest::from_build(config, result, language).and_then(|manifest| {
+        let sbom = Sbom::collect(&config.project_path, manifest, plugin);
+        let document = match format {
+            SbomFormat::CycloneDx => sbom.to_cyclonedx(),
+            SbomFormat::Spdx => sbom.to_spdx(),
+        };
+        let json = serde_json::to_string_pretty(&document).map_err(io::Error::other)?;
+        fs::write(&path, json + "\n")
+    });
+    match written {
+        Ok(()) => Some(path),
+        Err(e) => {
+            eprintln!("⚠️  Failed to write {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// The project's name and version from its manifest, else the directory name
+pub fn project_identity(project: &Path) -> (String, Option<String>) {
+    if let Some(package) = read_toml(&project.join("Cargo.toml"))
+        .and_then(|toml| toml.get("package").and_then(|p| p.as_table()).cloned())
+    {
+        if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
+            let version = package.get("version").and_then(|v| v.as_str());
+            return (name.to_string(), version.map(String::from));
+        }
+    }
+    if let Some(package) = read_json(&project.join("package.json")) {
+        if let Some(name) = package["name"].as_str() {
+            return (
+                name.to_string(),
+                package["version"].as_str().map(String::from),
+            );
+        }
+    }
+    if let Ok(go_mod) = fs::read_to_string(project.join("go.mod")) {
+        if let Some(module) = go_mod
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("module "))
+        {
+            return (module.trim().to_string(), None);
+        }
+    }
+    let name = project
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "project".to_string());
+    (name, None)
+}
+
+/// Direct dependencies from `Cargo.toml`/`Cargo.lock`, `go.mod` and
+/// `package.json`/`package-lock.json`, sorted by name
+fn direct_dependencies(project: &Path) -> Vec<Dependency> {
+    let mut deps = cargo_dependencies(project);
+    deps.extend(go_dependencies(project));
+    deps.extend(npm_dependencies(project));
+    deps.sort_by(|a, b| (a.ecosystem, &a.name).cmp(&(b.ecosystem, &b.name)));
+    deps
+}
+
+fn cargo_dependencies(project: &Path) -> Vec<Dependency> {
+    let Some(manifest) = read_toml(&project.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let lock = read_toml(&project.join("Cargo.lock"));
+    let locked: Vec<(&str, &str)> = lock
+        .as_ref()
+        .and_then(|lock| lock.get("package"))
+        .and_then(|p| p.as_array())
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|p| Some((p.get("name")?.as_str()?, p.get("version")?.as_str()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let Some(table) = manifest.get("dependencies").and_then(|d| d.as_table()) else {
+        return Vec::new();
+    };
+    table
+        .iter()
+        .filter_map(|(key, spec)| {
+            // `foo = { package = "bar" }` depends on bar
+            let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+            let requirement = spec
+                .as_str()
+                .or_else(|| spec.get("version").and_then(|v| v.as_str()));
+            let version = locked
+                .iter()
+                .find(|(locked_name, _)| *locked_name == name)
+                .map(|(_, version)| *version)
+                .or(requirement)?;
+            Some(Dependency {
+                name: name.to_string(),
+                version: version.to_string(),
+                ecosystem: "cargo",
+            })
+        })
+        .collect()
+}
+
+fn go_dependencies(project: &Path) -> Vec<Dependency> {
+    let Ok(go_mod) = fs::read_to_string(project.join("go.mod")) else {
+        return Vec::new();
+    };
+    let mut deps = Vec::new();
+    let mut in_block = false;
+    for line in go_mod.lines().map(str::trim) {
+        let spec = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(spec) = line.strip_prefix("require ") {
+            spec
+        } else {
+            continue;
+        };
+        if spec.ends_with("// indirect") {
+            continue;
+        }
+        let mut parts = spec.split_whitespace();
+        if let (Some(name), Some(version)) = (parts.next(), parts.next()) {
+            deps.push(Dependency {
+                name: name.to_string(),
+                version: version.to_string(),
+                ecosystem: "golang",
+            });
+        }
+    }
+    deps
+}
+
+fn npm_dependencies(project: &Path) -> Vec<Dependency> {
+    let Some(package) = read_json(&project.join("package.json")) else {
+        return Vec::new();
+    };
+    let lock = read_json(&project.join("package-lock.json"));
+    let Some(dependencies) = package["dependencies"].as_object() else {
+        return Vec::new();
+    };
+    dependencies
+        .iter()
+        .filter_map(|(name, range)| {
+            let locked = lock.as_ref().and_then(|lock| {
+                lock["packages"][format!("node_modules/{name}")]["version"].as_str()
+            });
+            let version = locked.or(range.as_str())?;
+            Some(Dependency {
+                name: name.clone(),
+                version: version.to_string(),
+                ecosystem: "npm",
+            })
+        })
+        .collect()
+}
+
+/// wasmrun, the plugin that ran the build, if any, and the toolchain
+pub fn build_tools(project: &Path, language: &str, plugin: Option<Tool>) -> Vec<Tool> {
+    let mut tools = vec![Tool {
+        name: "wasmrun".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    }];
+    tools.extend(plugin);
+    tools.extend(toolchain(project, language));
+    tools
+}
+
+/// The compiler behind each language, as reported by the installed tool
+fn toolchain(project: &Path, language: &str) -> Vec<Tool> {
+    let commands: &[(&str, &[&str])] = match language {
+        "Rust" => &[("rustc", &["--version"]), ("cargo", &["--version"])],
+        "Go" => &[("tinygo", &["version"])],
+        "C" => &[("emcc", &["--version"])],
+        _ => &[],
+    };
+    let mut tools: Vec<Tool> = commands
+        .iter()
+        .filter_map(|(name, args)| {
+            let output = Command::new(name).args(*args).output().ok()?;
+            let text = String::from_utf8_lossy(&output.stdout);
+            Some(Tool {
+                name: name.to_string(),
+                version: version_in(text.lines().next()?)?.to_string(),
+            })
+        })
+        .collect();
+    if language == "AssemblyScript" {
+        // The compiler is a project dependency; its lockfile has the version
+        let locked = read_json(&project.join("package-lock.json")).and_then(|lock| {
+            lock["packages"]["node_modules/assemblyscript"]["version"]
+                .as_str()
+                .map(String::from)
+        });
+        tools.extend(locked.map(|version| Tool {
+            name: "assemblyscript".to_string(),
+            version,
+        }));
+    }
+    tools
+}
+
+/// The first word of a `--version` line that looks like a version number
+fn version_in(line: &str) -> Option<&str> {
+    line.split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.'))
+}
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    fs::read_to_string(path).ok()?.parse().ok()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::artifacts::{Artifact, ArtifactKind};
+
+    fn manifest() -> BuildManifest {
+        BuildManifest {
+            wasmrun_version: "0.0.0".to_string(),
+            built_at: "2025-01-01T00:00:00+00:00".to_string(),
+            project: ".".to_string(),
+            language: "Rust".to_string(),
+            profile: "release".to_string(),
+            target: None,
+            artifacts: vec![Artifact {
+                path: "app.wasm".to_string(),
+                kind: ArtifactKind::Module,
+                size: 8,
+                sha256: "93a44bbb96c751218e4c00d479e4c14358122a389acca16205b1e4d0dc5f9476"
+                    .to_string(),
+            }],
+            licenses: Vec::new(),
+            rebuilt_for: Vec::new(),
+            store_key: None,
+        }
+    }
+
+    #[test]
+    fn test_direct_dependencies_from_lockfiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path();
+        fs::write(
+            project.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.2.0\"\n\n[dependencies]\nserde = \"1\"\nrenamed = { package = \"wasm-bindgen\", version = \"0.2\" }\n\n[dev-dependencies]\ntempfile = \"3\"\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join("Cargo.lock"),
+            "[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\n\n[[package]]\nname = \"tempfile\"\nversion = \"3.10.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join("go.mod"),
+            "module example.com/app\n\nrequire github.com/a/b v1.2.3\n\nrequire (\n\tgithub.com/c/d v0.1.0\n\tgithub.com/e/f v2.0.0 // indirect\n)\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join("package.json"),
+            r#"{"dependencies":{"left-pad":"^1.3.0"},"devDependencies":{"assemblyscript":"0.27"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            project.join("package-lock.json"),
+            r#"{"packages":{"node_modules/left-pad":{"version":"1.3.0"}}}"#,
+        )
+        .unwrap();
+
+        let purls: Vec<String> = direct_dependencies(project)
+            .iter()
+            .map(Dependency::purl)
+            .collect();
+        assert_eq!(
+            purls,
+            [
+                "pkg:cargo/serde@1.0.210",
+                "pkg:cargo/wasm-bindgen@0.2",
+                "pkg:golang/github.com/a/b@v1.2.3",
+                "pkg:golang/github.com/c/d@v0.1.0",
+                "pkg:npm/left-pad@1.3.0",
+            ]
+        );
+        assert_eq!(
+            project_identity(project),
+            ("app".to_string(), Some("0.2.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sbom_documents() {
+        let sbom = Sbom {
+            name: "app".to_string(),
+            version: Some("0.2.0".to_string()),
+            tools: vec![
+                Tool {
+                    name: "wasmrun".to_string(),
+                    version: "0.0.0".to_string(),
+                },
+                Tool {
+                    name: "rustc".to_string(),
+                    version: "1.85.0".to_string(),
+                },
+            ],
+            dependencies: vec![Dependency {
+                name: "serde".to_string(),
+                version: "1.0.210".to_string(),
+                ecosystem: "cargo",
+            }],
+            manifest: manifest(),
+        };
+
+        let cdx = sbom.to_cyclonedx();
+        assert_eq!(cdx["bomFormat"], "CycloneDX");
+        assert_eq!(cdx["metadata"]["component"]["version"], "0.2.0");
+        assert_eq!(cdx["metadata"]["tools"]["components"][1]["name"], "rustc");
+        assert_eq!(cdx["components"][0]["hashes"][0]["alg"], "SHA-256");
+        assert_eq!(cdx["components"][1]["purl"], "pkg:cargo/serde@1.0.210");
+        assert_eq!(
+            cdx["dependencies"][0]["dependsOn"][0],
+            "pkg:cargo/serde@1.0.210"
+        );
+
+        let spdx = sbom.to_spdx();
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(spdx["creationInfo"]["creators"][1], "Tool: rustc-1.85.0");
+        assert_eq!(spdx["files"][0]["fileName"], "./app.wasm");
+        assert_eq!(spdx["relationships"].as_array().unwrap().len(), 3);
+        assert!(spdx["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .ends_with("/spdx/app-93a44bbb96c75121"));
+    }
+
+    #[test]
+    fn test_version_in() {
+        assert_eq!(
+            version_in("rustc 1.85.0 (4d91de4e4 2025-02-17)"),
+            Some("1.85.0")
+        );
+        assert_eq!(
+            version_in("tinygo version 0.31.2 linux/amd64"),
+            Some("0.31.2")
+        );
+        assert_eq!(
+            version_in("emcc (Emscripten gcc/clang-like replacement) 3.1.50 (abc)"),
+            Some("3.1.50")
+        );
+        assert_eq!(version_in("no version here"), None);
+    }
+}
@@ -0,0 +1,713 @@
+//! Content-addressed artifact store: `wasmrun compile` outputs kept by a
+//! hash of everything that went into them, so building an unchanged
+//! project again restores its artifacts instead of running the toolchain.
+//!
+//! The store is `artifacts/` under wasmrun's cache directory:
+//!
+//! - `objects/<sha256>` holds artifact contents by digest, stored once
+//!   however many builds produced them and never rewritten
+//! - `builds/<key>.json` is a [`StoredBuild`] per input key, naming the
+//!   objects that make up the build's outputs
+//!
+//! The input key covers the project's source files, leaving out build
+//! directories and the build's own outputs, along with the language,
+//! profile, artifact naming, pipeline, the versions of wasmrun, the plugin
+//! and the toolchain, and the environment variables toolchains read. Sources
+//! outside the project count too where they can be found: Cargo path
+//! dependencies and workspace files, `.cargo/config.toml` in the
+//! directories above, and local `replace` targets in `go.mod`.
+
+use super::artifacts::{relative, Artifact, BuildManifest, BUILD_MANIFEST_FILE};
+use super::builder::{BuildConfig, BuildResult, OutputNaming};
+use super::pipeline::Step;
+use super::sbom::{SbomFormat, Tool};
+use crate::config::WasmrunConfig;
+use crate::error::Result;
+use crate::remote::cache::sha256_digest;
+use crate::utils::{file_digest, list_files};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// The store's directory under wasmrun's cache directory
+pub const STORE_DIR: &str = "artifacts";
+
+/// Environment variables toolchains read that change what they build
+const BUILD_ENV_VARS: &[&str] = &[
+    "RUSTFLAGS",
+    "RUSTC",
+    "RUSTC_WRAPPER",
+    "CC",
+    "CXX",
+    "AR",
+    "CFLAGS",
+    "CXXFLAGS",
+    "LDFLAGS",
+    "WASI_SDK_PATH",
+    "EMCC_CFLAGS",
+    "GOFLAGS",
+    "GOOS",
+    "GOARCH",
+    "TINYGOROOT",
+];
+/// Prefixes of more of them: Cargo's settings and per-target compilers and
+/// flags such as `CC_wasm32_wasip1`
+const BUILD_ENV_PREFIXES: &[&str] = &["CARGO_", "RUSTC_", "CC_", "CXX_", "AR_", "CFLAGS_"];
+/// Cargo settings that only change how it reports
+const COSMETIC_ENV_PREFIXES: &[&str] = &["CARGO_TERM_"];
+
+/// What a build's outputs depend on besides the project's source files
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInputs<'a> {
+    pub language: &'a str,
+    pub profile: String,
+    pub naming: &'a OutputNaming,
+    /// wasmrun, the plugin and the toolchain, with their versions
+    pub tools: Vec<Tool>,
+    pub pipeline: Option<&'a [Step]>,
+    /// The variables toolchains read, from [`builder_env`]
+    pub env: BTreeMap<String, String>,
+}
+
+/// The toolchain variables set in wasmrun's environment, which builds
+/// inherit
+pub fn builder_env() -> BTreeMap<String, String> {
+    builder_vars(std::env::vars_os().map(|(name, value)| {
+        (
+            name.to_string_lossy().into(),
+            value.to_string_lossy().into(),
+        )
+    }))
+}
+
+fn builder_vars(vars: impl Iterator<Item = (String, String)>) -> BTreeMap<String, String> {
+    vars.filter(|(name, _)| {
+        (BUILD_ENV_VARS.contains(&name.as_str())
+            || BUILD_ENV_PREFIXES.iter().any(|p| name.starts_with(p)))
+            && !COSMETIC_ENV_PREFIXES.iter().any(|p| name.starts_with(p))
+    })
+    .collect()
+}
+
+/// `builds/<key>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredBuild {
+    pub key: String,
+    pub project: String,
+    pub language: String,
+    pub profile: String,
+    pub target: Option<String>,
+    pub stored_at: String,
+    pub last_used: String,
+    /// The build's outputs, relative to the output directory
+    pub result: BuildResult,
+    /// Every file the build wrote, relative to the output directory, with
+    /// the digest of the object holding it
+    pub artifacts: Vec<Artifact>,
+}
+
+impl StoredBuild {
+    pub fn size(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.size).sum()
+    }
+
+    fn last_used_at(&self) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&self.last_used)
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_default()
+    }
+}
+
+/// What a garbage collection removed and kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcSummary {
+    pub builds_removed: usize,
+    pub objects_removed: usize,
+    pub bytes_freed: u64,
+    pub builds_kept: usize,
+    pub bytes_kept: u64,
+}
+
+/// Which stored builds a garbage collection keeps
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GcPolicy {
+    /// Remove builds not used since
+    pub unused_since: Option<DateTime<Utc>>,
+    /// Keep at most this many builds, most recently used first
+    pub keep: Option<usize>,
+}
+
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    /// The store in wasmrun's cache directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(WasmrunConfig::cache_dir()?.join(STORE_DIR)))
+    }
+
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn object_path(&self, digest: &str) -> PathBuf {
+        self.root.join("objects").join(digest)
+    }
+
+    fn build_path(&self, key: &str) -> PathBuf {
+        self.root.join("builds").join(format!("{key}.json"))
+    }
+
+    /// The key for building `config` from the sources on disk now. An
+    /// error, such as Cargo failing to resolve the dependencies, leaves the
+    /// build out of the store.
+    pub fn input_key(config: &BuildConfig, inputs: &BuildInputs) -> io::Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_vec(inputs).map_err(io::Error::other)?);
+        let project = Path::new(&config.project_path);
+        // First, as resolving a Cargo project can write its Cargo.lock
+        let outside = outside_sources(project)?;
+        let mut hash_file = |name: &str, path: &Path| -> io::Result<()> {
+            hasher.update(name.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(file_digest(path)?.as_bytes());
+            hasher.update(b"\n");
+            Ok(())
+        };
+        for (name, path) in list_files(project, &outputs_in_project(config)?)? {
+            hash_file(&name, &path)?;
+        }
+        for source in outside {
+            let label = source.to_string_lossy();
+            if source.is_dir() {
+                for (name, path) in list_files(&source, &[])? {
+                    hash_file(&format!("{label}/{name}"), &path)?;
+                }
+            } else if source.is_file() {
+                hash_file(&label, &source)?;
+            }
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect())
+    }
+
+    /// Keep the outputs `result` lists under `key`
+    pub fn put(
+        &self,
+        key: &str,
+        config: &BuildConfig,
+        result: &BuildResult,
+        language: &str,
+    ) -> io::Result<StoredBuild> {
+        let manifest = BuildManifest::from_build(config, result, language)?;
+        if let Some(outside) = manifest
+            .artifacts
+            .iter()
+            .find(|artifact| !is_relative(&artifact.path))
+        {
+            return Err(io::Error::other(format!(
+                "{} is outside the output directory",
+                outside.path
+            )));
+        }
+
+        let output_dir = Path::new(&config.output_dir);
+        fs::create_dir_all(self.root.join("objects"))?;
+        for artifact in &manifest.artifacts {
+            let object = self.object_path(&artifact.sha256);
+            if !object.is_file() {
+                write_atomically(&object, &fs::read(output_dir.join(&artifact.path))?)?;
+            }
+        }
+
+        let in_output = |path: &str| relative(Path::new(path), output_dir);
+        let now = Utc::now().to_rfc3339();
+        let build = StoredBuild {
+            key: key.to_string(),
+            project: manifest.project,
+            language: manifest.language,
+            profile: manifest.profile,
+            target: manifest.target,
+            stored_at: now.clone(),
+            last_used: now,
+            result: BuildResult {
+                wasm_path: in_output(&result.wasm_path),
+                js_path: result.js_path.as_deref().map(in_output),
+                additional_files: result
+                    .additional_files
+                    .iter()
+                    .map(|f| in_output(f))
+                    .collect(),
+                is_wasm_bindgen: result.is_wasm_bindgen,
+                target: result.target.clone(),
+            },
+            artifacts: manifest.artifacts,
+        };
+        self.save(&build)?;
+        Ok(build)
+    }
+
+    /// Copy the build stored under `key` into `output_dir` and return its
+    /// outputs there. `None` when nothing is stored or an object is missing
+    /// or damaged, which leaves the build to run.
+    pub fn restore(&self, key: &str, output_dir: &Path) -> Option<BuildResult> {
+        let mut build = self.get(key)?;
+        let mut objects = Vec::with_capacity(build.artifacts.len());
+        for artifact in &build.artifacts {
+            let data = fs::read(self.object_path(&artifact.sha256)).ok()?;
+            if sha256_digest(&data) != format!("sha256:{}", artifact.sha256) {
+                let _ = fs::remove_file(self.object_path(&artifact.sha256));
+                return None;
+            }
+            objects.push(data);
+        }
+        for (artifact, data) in build.artifacts.iter().zip(objects) {
+            let path = output_dir.join(&artifact.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).ok()?;
+            }
+            fs::write(path, data).ok()?;
+        }
+
+        build.last_used = Utc::now().to_rfc3339();
+        let _ = self.save(&build);
+        let in_output = |path: &str| output_dir.join(path).to_string_lossy().to_string();
+        let result = build.result;
+        Some(BuildResult {
+            wasm_path: in_output(&result.wasm_path),
+            js_path: result.js_path.as_deref().map(in_output),
+            additional_files: result
+                .additional_files
+                .iter()
+                .map(|f| in_output(f))
+                .collect(),
+            is_wasm_bindgen: result.is_wasm_bindgen,
+            target: result.target,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<StoredBuild> {
+        let json = fs::read_to_string(self.build_path(key)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    fn save(&self, build: &StoredBuild) -> io::Result<()> {
+        fs::create_dir_all(self.root.join("builds"))?;
+        let json = serde_json::to_string_pretty(build).map_err(io::Error::other)?;
+        write_atomically(&self.build_path(&build.key), (json + "\n").as_bytes())
+    }
+
+    /// Every stored build, most recently used first
+    pub fn list(&self) -> Vec<StoredBuild> {
+        let mut builds: Vec<StoredBuild> = fs::read_dir(self.root.join("builds"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let json = fs::read_to_string(entry.path()).ok()?;
+                serde_json::from_str(&json).ok()
+            })
+            .collect();
+        builds.sort_by_key(|build| std::cmp::Reverse(build.last_used_at()));
+        builds
+    }
+
+    /// Remove the builds `policy` doesn't keep, then every object no kept
+    /// build refers to. With `dry_run`, only count them.
+    pub fn gc(&self, policy: GcPolicy, dry_run: bool) -> io::Result<GcSummary> {
+        let mut summary = GcSummary::default();
+        let mut referenced = HashSet::new();
+        for (i, build) in self.list().into_iter().enumerate() {
+            let stale = policy
+                .unused_since
+                .is_some_and(|since| build.last_used_at() < since);
+            let over = policy.keep.is_some_and(|keep| i >= keep);
+            let complete = build
+                .artifacts
+                .iter()
+                .all(|artifact| self.object_path(&artifact.sha256).is_file());
+            if stale || over || !complete {
+                summary.builds_removed += 1;
+                if !dry_run {
+                    fs::remove_file(self.build_path(&build.key))?;
+                }
+            } else {
+                summary.builds_kept += 1;
+                referenced.extend(build.artifacts.into_iter().map(|artifact| artifact.sha256));
+            }
+        }
+
+        let Ok(objects) = fs::read_dir(self.root.join("objects")) else {
+            return Ok(summary);
+        };
+        for entry in objects.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if referenced.contains(&name) {
+                summary.bytes_kept += size;
+                continue;
+            }
+            summary.objects_removed += 1;
+            summary.bytes_freed += size;
+            if !dry_run {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// What a previous build wrote into the project, relative to it, so the
+/// outputs of one build aren't inputs to the next
+fn outputs_in_project(config: &BuildConfig) -> io::Result<Vec<PathBuf>> {
+    let project = std::path::absolute(&config.project_path)?;
+    let output_dir = std::path::absolute(&config.output_dir)?;
+    let Ok(inside) = output_dir.strip_prefix(&project) else {
+        return Ok(Vec::new());
+    };
+    if !inside.as_os_str().is_empty() {
+        return Ok(vec![inside.to_path_buf()]);
+    }
+
+    // Built into the project root: leave out what the last manifest lists
+    let mut outputs = vec![PathBuf::from(BUILD_MANIFEST_FILE)];
+    outputs.extend(
+        [SbomFormat::CycloneDx, SbomFormat::Spdx]
+            .iter()
+            .map(|format| PathBuf::from(format.file_name())),
+    );
+    let manifest = fs::read_to_string(output_dir.join(BUILD_MANIFEST_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str::<BuildManifest>(&json).ok());
+    outputs.extend(
+        manifest
+            .into_iter()
+            .flat_map(|manifest| manifest.artifacts)
+            .filter(|artifact| is_relative(&artifact.path))
+            .map(|artifact| PathBuf::from(artifact.path)),
+    );
+    Ok(outputs)
+}
+
+/// Files and directories outside `project` its build reads, as absolute
+/// paths: Cargo's config in the directories above it, and for a Cargo
+/// project the workspace manifest and lockfile and every path dependency,
+/// or for a Go module its local `replace` targets
+fn outside_sources(project: &Path) -> io::Result<BTreeSet<PathBuf>> {
+    let project = project.canonicalize()?;
+    let mut sources = BTreeSet::new();
+    for dir in project.ancestors().skip(1) {
+        sources.insert(dir.join(".cargo/config.toml"));
+        sources.insert(dir.join(".cargo/config"));
+    }
+
+    if project.join("Cargo.toml").is_file() {
+        let output = Command::new("cargo")
+            .args(["metadata", "--format-version", "1", "--offline"])
+            .current_dir(&project)
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&output.stdout).map_err(io::Error::other)?;
+        if let Some(root) = metadata["workspace_root"].as_str() {
+            sources.insert(Path::new(root).join("Cargo.toml"));
+            sources.insert(Path::new(root).join("Cargo.lock"));
+        }
+        let packages = metadata["packages"].as_array().into_iter().flatten();
+        // Packages without a source are local: workspace members and path
+        // dependencies
+        let local = packages.filter(|package| package["source"].is_null());
+        sources.extend(local.filter_map(|package| {
+            Some(
+                Path::new(package["manifest_path"].as_str()?)
+                    .parent()?
+                    .to_path_buf(),
+            )
+        }));
+    }
+
+    if let Ok(go_mod) = fs::read_to_string(project.join("go.mod")) {
+        sources.extend(
+            go_local_replacements(&go_mod)
+                .into_iter()
+                .map(|path| project.join(path)),
+        );
+    }
+
+    Ok(sources
+        .into_iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .filter(|path| !path.starts_with(&project))
+        .collect())
+}
+
+/// The local directories `go.mod` replaces modules with
+fn go_local_replacements(go_mod: &str) -> Vec<String> {
+    let mut in_block = false;
+    let mut paths = Vec::new();
+    for line in go_mod.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let directive = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("replace") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let Some((_, target)) = directive.split_once("=>") else {
+            continue;
+        };
+        let target = target.split_whitespace().next().unwrap_or_default();
+        if target.starts_with("./") || target.starts_with("../") || target.starts_with('/') {
+            paths.push(target.to_string());
+        }
+    }
+    paths
+}
+
+/// Whether `path` stays below the directory it's relative to
+fn is_relative(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Write `data` beside `path` and rename it into place, so a reader never
+/// sees a partial file
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    let staging = path.with_extension("partial");
+    fs::write(&staging, data)?;
+    fs::rename(&staging, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn project(root: &Path) -> BuildConfig {
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}").unwrap();
+        let root = root.to_string_lossy().to_string();
+        BuildConfig::with_defaults(root.clone(), root)
+    }
+
+    fn inputs(naming: &OutputNaming) -> BuildInputs<'_> {
+        BuildInputs {
+            language: "Rust",
+            profile: "release".to_string(),
+            naming,
+            tools: Vec::new(),
+            pipeline: None,
+            env: BTreeMap::new(),
+        }
+    }
+
+    fn build(config: &BuildConfig, module: &[u8]) -> BuildResult {
+        let wasm = Path::new(&config.output_dir).join("app.wasm");
+        fs::write(&wasm, module).unwrap();
+        BuildResult {
+            wasm_path: wasm.to_string_lossy().to_string(),
+            js_path: None,
+            additional_files: Vec::new(),
+            is_wasm_bindgen: false,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn test_input_key_follows_sources_not_outputs() {
+        let dir = tempdir().unwrap();
+        let config = project(dir.path());
+        let naming = OutputNaming::default();
+        let key = ArtifactStore::input_key(&config, &inputs(&naming)).unwrap();
+
+        // Outputs of a build into the project root aren't inputs
+        let result = build(&config, b"\0asm\x01\0\0\0");
+        crate::compiler::artifacts::write_build_manifest(&config, &result, "Rust");
+        assert_eq!(
+            ArtifactStore::input_key(&config, &inputs(&naming)).unwrap(),
+            key
+        );
+
+        let mut release = inputs(&naming);
+        release.profile = "debug".to_string();
+        assert_ne!(ArtifactStore::input_key(&config, &release).unwrap(), key);
+
+        fs::write(dir.path().join("src/lib.rs"), "pub fn b() {}").unwrap();
+        assert_ne!(
+            ArtifactStore::input_key(&config, &inputs(&naming)).unwrap(),
+            key
+        );
+    }
+
+    #[test]
+    fn test_input_key_follows_builder_env_and_path_dependencies() {
+        let dir = tempdir().unwrap();
+        let app = dir.path().join("app");
+        let config = project(&app);
+        let naming = OutputNaming::default();
+        let key = ArtifactStore::input_key(&config, &inputs(&naming)).unwrap();
+
+        let mut flags = inputs(&naming);
+        flags.env = builder_vars(
+            [
+                ("RUSTFLAGS", "-C opt-level=1"),
+                ("CARGO_TERM_COLOR", "always"),
+                ("HOME", "/root"),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        assert_eq!(flags.env.keys().collect::<Vec<_>>(), ["RUSTFLAGS"]);
+        assert_ne!(ArtifactStore::input_key(&config, &flags).unwrap(), key);
+
+        // A path dependency beside the project is an input
+        let shared = dir.path().join("shared");
+        fs::create_dir_all(shared.join("src")).unwrap();
+        fs::write(
+            shared.join("Cargo.toml"),
+            "[package]\nname = \"shared\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        fs::write(shared.join("src/lib.rs"), "pub fn one() {}").unwrap();
+        let mut manifest = fs::read_to_string(app.join("Cargo.toml")).unwrap();
+        manifest.push_str("\n[dependencies]\nshared = { path = \"../shared\" }\n");
+        fs::write(app.join("Cargo.toml"), manifest).unwrap();
+        let with_dependency = ArtifactStore::input_key(&config, &inputs(&naming)).unwrap();
+        assert_eq!(
+            ArtifactStore::input_key(&config, &inputs(&naming)).unwrap(),
+            with_dependency
+        );
+        fs::write(shared.join("src/lib.rs"), "pub fn two() {}").unwrap();
+        assert_ne!(
+            ArtifactStore::input_key(&config, &inputs(&naming)).unwrap(),
+            with_dependency
+        );
+    }
+
+    #[test]
+    fn test_go_local_replacements() {
+        let go_mod = "module app\n\nreplace example.com/a => ../a\nreplace example.com/b v1.0.0 => example.com/c v1.1.0\nreplace (\n\texample.com/d => ./vendor/d // patched\n\texample.com/e => /src/e\n)\n";
+        assert_eq!(
+            go_local_replacements(go_mod),
+            ["../a", "./vendor/d", "/src/e"]
+        );
+    }
+
+    #[test]
+    fn test_put_and_restore() {
+        let dir = tempdir().unwrap();
+        let out = dir.path().join("dist");
+        fs::create_dir_all(&out).unwrap();
+        let config = BuildConfig::with_defaults(
+            dir.path().to_string_lossy().to_string(),
+            out.to_string_lossy().to_string(),
+        );
+        let store = ArtifactStore::at(dir.path().join("store"));
+        let module = b"\0asm\x01\0\0\0";
+        let result = build(&config, module);
+        let stored = store.put("k1", &config, &result, "Rust").unwrap();
+        assert_eq!(stored.result.wasm_path, "app.wasm");
+        assert_eq!(stored.size(), module.len() as u64);
+        assert!(store.restore("k2", &out).is_none());
+
+        fs::remove_dir_all(&out).unwrap();
+        let restored = store.restore("k1", &out).unwrap();
+        assert_eq!(restored.wasm_path, result.wasm_path);
+        assert_eq!(fs::read(&restored.wasm_path).unwrap(), module);
+
+        // A damaged object is a miss, not a broken build
+        let object = store.object_path(&stored.artifacts[0].sha256);
+        fs::write(&object, b"garbage").unwrap();
+        assert!(store.restore("k1", &out).is_none());
+        assert!(!object.exists());
+    }
+
+    #[test]
+    fn test_gc_removes_unused_builds_and_their_objects() {
+        let dir = tempdir().unwrap();
+        let out = dir.path().join("dist");
+        fs::create_dir_all(&out).unwrap();
+        let config = BuildConfig::with_defaults(
+            dir.path().to_string_lossy().to_string(),
+            out.to_string_lossy().to_string(),
+        );
+        let store = ArtifactStore::at(dir.path().join("store"));
+        let mut old = store
+            .put("old", &config, &build(&config, b"old module"), "Rust")
+            .unwrap();
+        old.last_used = "2020-01-01T00:00:00+00:00".to_string();
+        store.save(&old).unwrap();
+        store
+            .put("new", &config, &build(&config, b"new module"), "Rust")
+            .unwrap();
+        assert_eq!(
+            store
+                .list()
+                .iter()
+                .map(|b| b.key.as_str())
+                .collect::<Vec<_>>(),
+            ["new", "old"]
+        );
+
+        let policy = GcPolicy {
+            unused_since: Some(Utc::now() - chrono::Duration::days(30)),
+            keep: None,
+        };
+        let preview = store.gc(policy, true).unwrap();
+        assert_eq!((preview.builds_removed, preview.objects_removed), (1, 1));
+        assert_eq!(store.list().len(), 2);
+
+        let summary = store.gc(policy, false).unwrap();
+        assert_eq!(summary, preview);
+        assert_eq!(summary.bytes_freed, b"old module".len() as u64);
+        assert_eq!(store.list().len(), 1);
+        assert!(store.restore("new", &out).is_some());
+
+        let emptied = store
+            .gc(
+                GcPolicy {
+                    unused_since: None,
+                    keep: Some(0),
+                },
+                false,
+            )
+            .unwrap();
+        assert_eq!((emptied.builds_removed, emptied.builds_kept), (1, 0));
+        assert!(store.list().is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! Orchestration manifest for `wasmrun run --manifest`: several modules served
+//! on one page, each with its own mount point.
+//!
+//! ```toml
+//! [[module]]
+//! name = "store"
+//! wasm = "store/store.wasm"
+//!
+//! [[module]]
+//! name = "ui"
+//! js = "ui/pkg/ui.js"        # wasm-bindgen glue (web target)
+//! mount = "app"
+//! after = ["store"]
+//! ```
+//!
+//! Paths are relative to the manifest file. Modules load in the order they
+//! are listed, except that a module always loads after everything in its
+//! `after` list.
+
+use crate::error::{ConfigError, Result, WasmrunError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModuleManifest {
+    #[serde(rename = "module", default)]
+    pub modules: Vec<ManifestModule>,
+    /// Directory the module paths are resolved against
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ManifestModule {
+    pub name: String,
+    /// Plain `.wasm` module, instantiated directly
+    pub wasm: Option<String>,
+    /// wasm-bindgen JS entry; its default export is called to initialize it
+    pub js: Option<String>,
+    /// Id of the element the module renders into; created if the page lacks it
+    pub mount: Option<String>,
+    /// Modules that must finish loading before this one starts
+    pub after: Vec<String>,
+}
+
+impl ManifestModule {
+    /// The file the page loads for this module
+    pub fn entry(&self) -> &str {
+        self.js.as_deref().or(self.wasm.as_deref()).unwrap_or("")
+    }
+
+    pub fn is_bindgen(&self) -> bool {
+        self.js.is_some()
+    }
+}
+
+impl ModuleManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| WasmrunError::add_context(format!("Reading {}", path.display()), e))?;
+        let mut manifest: Self = toml::from_str(&content).map_err(|e| {
+            WasmrunError::Config(ConfigError::ParseError {
+                message: format!("{}: {e}", path.display()),
+            })
+        })?;
+        manifest.base_dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Absolute-or-relative path of a module's entry file on disk
+    pub fn resolve(&self, module: &ManifestModule) -> PathBuf {
+        self.base_dir.join(module.entry())
+    }
+
+    pub fn module(&self, name: &str) -> Option<&ManifestModule> {
+        self.modules.iter().find(|m| m.name == name)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.modules.is_empty() {
+            return Err(invalid("manifest declares no [[module]] entries"));
+        }
+
+        let mut names = HashSet::new();
+        for module in &self.modules {
+            if module.name.is_empty()
+                || !module
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(invalid(format!(
+                    "module name '{}' must be non-empty and use only letters, digits, '-' or '_'",
+                    module.name
+                )));
+            }
+            if !names.insert(module.name.as_str()) {
+                return Err(invalid(format!("duplicate module name '{}'", module.name)));
+            }
+            match (&module.wasm, &module.js) {
+                (None, None) => {
+                    return Err(invalid(format!(
+                        "module '{}' needs a `wasm` or `js` path",
+                        module.name
+                    )))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(invalid(format!(
+                    "module '{}' sets both `wasm` and `js`; a wasm-bindgen module only needs `js`",
+                    module.name
+                )))
+                }
+                _ => {}
+            }
+            let path = self.resolve(module);
+            if !path.is_file() {
+                return Err(WasmrunError::file_not_found(path.display().to_string()));
+            }
+        }
+
+        for module in &self.modules {
+            for dep in &module.after {
+                if !names.contains(dep.as_str()) {
+                    return Err(invalid(format!(
+                        "module '{}' loads after unknown module '{dep}'",
+                        module.name
+                    )));
+                }
+            }
+        }
+
+        self.load_order().map(|_| ())
+    }
+
+    /// Modules in the order the page loads them: listing order, with each
+    /// module moved behind the modules named in its `after` list.
+    pub fn load_order(&self) -> Result<Vec<&ManifestModule>> {
+        let mut ordered: Vec<&ManifestModule> = Vec::with_capacity(self.modules.len());
+        let mut placed: HashSet<&str> = HashSet::new();
+
+        while ordered.len() < self.modules.len() {
+            let next = self.modules.iter().find(|m| {
+                !placed.contains(m.name.as_str())
+                    && m.after.iter().all(|dep| placed.contains(dep.as_str()))
+            });
+            match next {
+                Some(module) => {
+                    placed.insert(&module.name);
+                    ordered.push(module);
+                }
+                None => {
+                    let stuck: Vec<&str> = self
+                        .modules
+                        .iter()
+                        .filter(|m| !placed.contains(m.name.as_str()))
+                        .map(|m| m.name.as_str())
+                        .collect();
+                    return Err(invalid(format!(
+                        "load order has a cycle between: {}",
+                        stuck.join(", ")
+                    )));
+                }
+            }
+        }
+
+        Ok(ordered)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> WasmrunError {
+    WasmrunError::Config(ConfigError::InvalidValue {
+        message: message.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_manifest(dir: &Path, body: &str) -> PathBuf {
+        fs::write(dir.join("a.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        fs::write(dir.join("b.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        fs::write(dir.join("c.js"), "export default async function init() {}").unwrap();
+        let path = dir.join("modules.toml");
+        fs::write(&path, body).unwrap();
+        path
+    }
+
+    fn names(manifest: &ModuleManifest) -> Vec<&str> {
+        manifest
+            .load_order()
+            .unwrap()
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_load_order_respects_after() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            r#"
+[[module]]
+name = "ui"
+js = "c.js"
+mount = "app"
+after = ["store"]
+
+[[module]]
+name = "store"
+wasm = "a.wasm"
+
+[[module]]
+name = "logger"
+wasm = "b.wasm"
+"#,
+        );
+        let manifest = ModuleManifest::load(&path).unwrap();
+        assert_eq!(names(&manifest), vec!["store", "ui", "logger"]);
+        assert!(manifest.module("ui").unwrap().is_bindgen());
+        assert_eq!(
+            manifest.resolve(manifest.module("store").unwrap()),
+            dir.path().join("a.wasm")
+        );
+    }
+
+    #[test]
+    fn test_cycle_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            r#"
+[[module]]
+name = "a"
+wasm = "a.wasm"
+after = ["b"]
+
+[[module]]
+name = "b"
+wasm = "b.wasm"
+after = ["a"]
+"#,
+        );
+        let err = ModuleManifest::load(&path).unwrap_err();
+        assert!(err.to_string().contains("cycle between: a, b"));
+    }
+
+    #[test]
+    fn test_invalid_entries_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        for (body, expected) in [
+            ("", "no [[module]] entries"),
+            ("[[module]]\nname = \"a\"\n", "needs a `wasm` or `js` path"),
+            (
+                "[[module]]\nname = \"a\"\nwasm = \"a.wasm\"\n[[module]]\nname = \"a\"\nwasm = \"b.wasm\"\n",
+                "duplicate module name",
+            ),
+            (
+                "[[module]]\nname = \"a\"\nwasm = \"a.wasm\"\nafter = [\"z\"]\n",
+                "unknown module 'z'",
+            ),
+            ("[[module]]\nname = \"a b\"\nwasm = \"a.wasm\"\n", "must be non-empty"),
+            ("[[module]]\nname = \"a\"\nwasm = \"missing.wasm\"\n", "missing.wasm"),
+        ] {
+            let path = write_manifest(dir.path(), body);
+            let err = ModuleManifest::load(&path).unwrap_err().to_string();
+            assert!(err.contains(expected), "{err} should mention {expected}");
+        }
+    }
+}
@@ -1,7 +1,9 @@
 //! Configuration module for Wasmrun
 
 pub mod constants;
+pub mod manifest;
 pub mod plugin;
+pub mod project;
 pub mod server;
 
 pub use constants::*;
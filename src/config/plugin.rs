@@ -94,7 +94,6 @@ impl WasmrunConfig {
         }
     }
 
-    #[allow(dead_code)] // TODO: Future plugin cache directory management
     pub fn cache_dir() -> Result<PathBuf> {
         let config = Self::load_or_default()?;
 
@@ -0,0 +1,389 @@
+//! Per-project configuration read from `wasmrun.toml` at the project root.
+//!
+//! Every section is optional and unknown keys are ignored, so a project can
+//! carry settings for plugins or newer wasmrun versions without breaking
+//! older ones.
+
+use crate::compiler::builder::OutputNaming;
+use crate::compiler::pipeline::PipelineConfig;
+use crate::compiler::retention::RetentionPolicy;
+use crate::error::{ConfigError, Result, WasmrunError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+pub const PROJECT_CONFIG_FILE: &str = "wasmrun.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    /// Language to build the project as, overriding detection; accepts the
+    /// same names as `--language`.
+    pub language: Option<String>,
+    pub build: BuildSettings,
+    /// How `wasmrun compile` names and lays out artifacts
+    pub output: OutputNaming,
+    pub policy: PolicyConfig,
+    /// Host overrides and DNS caching for OS mode guests
+    pub network: NetworkConfig,
+    /// Disk quotas for OS mode guests
+    pub fs: FsConfig,
+    /// Login for the OS mode server
+    pub auth: AuthConfig,
+    /// What a bare `wasmrun` runs, as saved by the first-run prompt
+    pub defaults: DefaultsConfig,
+    /// How many `run --watch` builds to keep
+    pub watch: RetentionPolicy,
+    /// Dependency licenses a build may not ship with
+    pub licenses: LicenseConfig,
+    /// Image and runtime for `--in-container` builds
+    pub container: ContainerConfig,
+    /// A `wasmrun build-agent` to hand builds to
+    pub remote_build: RemoteBuildConfig,
+    /// Post-build steps by name, for `wasmrun compile --pipeline`
+    pub pipelines: BTreeMap<String, PipelineConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefaultsConfig {
+    /// `run`, `compile` or `os`
+    pub command: Option<String>,
+    pub port: Option<u16>,
+    pub watch: bool,
+    /// Output directory for `compile`
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildSettings {
+    /// Seconds each toolchain command may run before it's stopped; 0 means
+    /// no limit
+    pub timeout: Option<u64>,
+    /// Embed a `wasmrun.meta` section in every compiled module, as
+    /// `compile --embed-meta` does
+    pub embed_meta: bool,
+}
+
+/// `[container]` in `wasmrun.toml`, overriding the language's defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerConfig {
+    /// `docker` or `podman`
+    pub runtime: Option<String>,
+    pub image: Option<String>,
+    /// Shell command run in the container before each build command
+    pub setup: Option<String>,
+    /// Commands the image provides
+    pub tools: Option<Vec<String>>,
+}
+
+/// `[remote_build]` in `wasmrun.toml`: build on a machine running
+/// `wasmrun build-agent` instead of this one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteBuildConfig {
+    /// The agent's address, e.g. `http://buildbox:8420`; builds stay local
+    /// without one
+    pub url: Option<String>,
+    pub source: RemoteSource,
+    /// Environment variable holding the agent's token, by default
+    /// `WASMRUN_BUILD_TOKEN`
+    pub token_env: Option<String>,
+    /// Paths under the project that aren't uploaded
+    pub exclude: Vec<String>,
+}
+
+/// How a remote build gets the project's sources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteSource {
+    /// Upload the files that changed since the agent's last build
+    #[default]
+    Upload,
+    /// Have the agent check out the pushed `HEAD` commit
+    Git,
+}
+
+/// Import/export rules a built module must satisfy.
+///
+/// Patterns are either a namespace (`wasi_snapshot_preview1`, matching every
+/// import from it) or `namespace::name`; a trailing `*` in either part
+/// matches any suffix.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// Imports the module may declare. `None` disables the import check;
+    /// an empty list forbids all imports.
+    pub allowed_imports: Option<Vec<String>>,
+    /// Export names the module must provide.
+    pub required_exports: Vec<String>,
+    /// Export names the module must not provide.
+    pub forbidden_exports: Vec<String>,
+}
+
+/// `[network]` in `wasmrun.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Hostnames guests resolve to a fixed address, like `/etc/hosts`
+    pub hosts: BTreeMap<String, IpAddr>,
+    /// Seconds a host lookup is reused; 0 disables caching
+    pub dns_cache_ttl: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            hosts: BTreeMap::new(),
+            dns_cache_ttl: 30,
+        }
+    }
+}
+
+/// `[fs]` in `wasmrun.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FsConfig {
+    /// Megabytes allowed under each guest path, e.g. `"/tmp" = 100`
+    pub quotas: BTreeMap<String, u64>,
+}
+
+/// `[auth]` in `wasmrun.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub enabled: bool,
+    /// Environment variable holding the `admin` password
+    pub password_env: String,
+    /// More users, as name = Argon2 hash of their password from
+    /// `wasmrun os --hash-password`
+    pub users: BTreeMap<String, String>,
+    /// Hours a login lasts
+    pub session_hours: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            password_env: "WASMRUN_OS_PASSWORD".to_string(),
+            users: BTreeMap::new(),
+            session_hours: 12,
+        }
+    }
+}
+
+impl PolicyConfig {
+    pub fn is_empty(&self) -> bool {
+        self.allowed_imports.is_none()
+            && self.required_exports.is_empty()
+            && self.forbidden_exports.is_empty()
+    }
+}
+
+/// Licenses checked before `compile` and `push` ship a build. Patterns are
+/// SPDX ids matched case-insensitively; a trailing `*` matches any suffix,
+/// so `GPL-3.0*` covers `GPL-3.0-only` and `GPL-3.0-or-later`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LicenseConfig {
+    /// Licenses that fail the check unless an `OR` offers another one
+    pub deny: Vec<String>,
+    /// Also fail on dependencies that declare no license
+    pub deny_unknown: bool,
+    /// Packages exempt from the check, by name
+    pub allow_packages: Vec<String>,
+}
+
+impl LicenseConfig {
+    pub fn is_empty(&self) -> bool {
+        self.deny.is_empty() && !self.deny_unknown
+    }
+}
+
+impl ProjectConfig {
+    /// Load `wasmrun.toml` from `project_dir`, if present.
+    pub fn load(project_dir: &Path) -> Result<Option<Self>> {
+        let path = project_dir.join(PROJECT_CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::load_file(&path).map(Some)
+    }
+
+    pub fn load_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| WasmrunError::add_context(format!("Reading {}", path.display()), e))?;
+        toml::from_str(&content).map_err(|e| {
+            WasmrunError::Config(ConfigError::ParseError {
+                message: format!("{}: {e}", path.display()),
+            })
+        })
+    }
+
+    /// Find the nearest `wasmrun.toml` at or above `start` (a file or a
+    /// directory), returning its path and contents. Used when all we have is
+    /// a built module, e.g. `target/wasm32-wasip1/release/app.wasm`.
+    pub fn find(start: &Path) -> Result<Option<(PathBuf, Self)>> {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        let mut dir = if start.is_dir() {
+            Some(start.as_path())
+        } else {
+            start.parent()
+        };
+
+        while let Some(current) = dir {
+            let candidate = current.join(PROJECT_CONFIG_FILE);
+            if !is_plugin_manifest(&candidate) {
+                if let Some(config) = Self::load(current)? {
+                    return Ok(Some((candidate, config)));
+                }
+            }
+            dir = current.parent();
+        }
+        Ok(None)
+    }
+}
+
+/// Plugins ship their own `wasmrun.toml` with a `[plugin]` table; don't
+/// mistake one for project configuration while walking up the tree.
+fn is_plugin_manifest(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| c.parse::<toml::Table>().ok())
+        .is_some_and(|t| t.contains_key("plugin"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_missing_config_is_none() {
+        let dir = tempdir().unwrap();
+        assert!(ProjectConfig::load(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            r#"
+language = "rust"
+
+[build]
+timeout = 600
+embed_meta = true
+
+[output]
+pattern = "{name}-{profile}"
+target_dirs = true
+
+[policy]
+allowed_imports = ["wasi_snapshot_preview1"]
+required_exports = ["_start"]
+
+[network.hosts]
+"my-api.internal" = "127.0.0.1"
+
+[fs.quotas]
+"/tmp" = 100
+
+[auth]
+enabled = true
+
+[defaults]
+command = "os"
+port = 9000
+watch = true
+
+[watch]
+keep_builds = 3
+max_total_mb = 50
+
+[licenses]
+deny = ["GPL-3.0*"]
+
+[some_plugin]
+ignored = true
+"#,
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(dir.path()).unwrap().unwrap();
+        assert_eq!(config.language.as_deref(), Some("rust"));
+        assert_eq!(config.build.timeout, Some(600));
+        assert!(config.build.embed_meta);
+        assert_eq!(config.output.pattern.as_deref(), Some("{name}-{profile}"));
+        assert!(config.output.target_dirs);
+        assert_eq!(config.output.name, None);
+        assert_eq!(
+            config.policy.allowed_imports,
+            Some(vec!["wasi_snapshot_preview1".to_string()])
+        );
+        assert_eq!(config.policy.required_exports, vec!["_start"]);
+        assert!(config.policy.forbidden_exports.is_empty());
+        assert!(!config.policy.is_empty());
+        assert_eq!(
+            config.network.hosts.get("my-api.internal"),
+            Some(&std::net::IpAddr::from([127, 0, 0, 1]))
+        );
+        assert_eq!(config.network.dns_cache_ttl, 30);
+        assert_eq!(config.fs.quotas.get("/tmp"), Some(&100));
+        assert!(config.auth.enabled);
+        assert_eq!(config.auth.password_env, "WASMRUN_OS_PASSWORD");
+        assert_eq!(config.defaults.command.as_deref(), Some("os"));
+        assert_eq!(config.defaults.port, Some(9000));
+        assert!(config.defaults.watch);
+        assert_eq!(config.watch.keep_builds, 3);
+        assert_eq!(config.watch.max_total_mb, Some(50));
+        assert_eq!(config.licenses.deny, vec!["GPL-3.0*"]);
+        assert!(!config.licenses.deny_unknown && !config.licenses.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_toml_is_a_parse_error() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(PROJECT_CONFIG_FILE), "[policy\n").unwrap();
+        let err = ProjectConfig::load(dir.path()).unwrap_err();
+        assert!(matches!(
+            err,
+            WasmrunError::Config(ConfigError::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_walks_up_from_artifact() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[policy]\nallowed_imports = []\n",
+        )
+        .unwrap();
+        let nested = dir.path().join("target").join("release");
+        fs::create_dir_all(&nested).unwrap();
+        let wasm = nested.join("app.wasm");
+        fs::write(&wasm, b"\0asm\x01\0\0\0").unwrap();
+
+        let (path, config) = ProjectConfig::find(&wasm).unwrap().unwrap();
+        assert_eq!(path.file_name().unwrap(), PROJECT_CONFIG_FILE);
+        assert_eq!(config.policy.allowed_imports, Some(vec![]));
+    }
+
+    #[test]
+    fn test_find_skips_plugin_manifest() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[plugin]\nname = \"x\"\n",
+        )
+        .unwrap();
+        assert!(ProjectConfig::find(dir.path()).unwrap().is_none());
+    }
+}
@@ -1,6 +1,10 @@
 use std::path::Path;
+use std::time::Instant;
 
-use crate::compiler::builder::{BuildConfig, BuilderFactory, OptimizationLevel, TargetType};
+use crate::compiler::artifacts::write_build_manifest;
+use crate::compiler::builder::{
+    BuildConfig, BuilderFactory, OptimizationLevel, OutputNaming, TargetType,
+};
 use crate::error::{Result, ServerError, WasmrunError};
 use crate::plugin::manager::PluginManager;
 use crate::utils::PluginUtils;
@@ -8,7 +12,9 @@ use crate::utils::{ProjectAnalysis, WasmAnalysis};
 
 use crate::server::utils::{find_wasm_files, is_port_available};
 use crate::server::wasm;
-use crate::server::{is_server_running, stop_existing_server, ServerUtils};
+use crate::server::{
+    is_server_running, record_build, server_url, stop_existing_server, unix_socket, ServerUtils,
+};
 
 #[derive(Debug)]
 #[allow(dead_code)] // TODO: Future server configuration system
@@ -20,6 +26,10 @@ pub struct ServerConfig {
     pub project_path: Option<String>,
     pub output_dir: Option<String>,
     pub serve: bool,
+    /// Instantiate the module in a Web Worker instead of on the page
+    pub worker: bool,
+    /// Print one line per request and keep it in the `/api/logs` trail
+    pub access_log: bool,
 }
 
 pub struct ServerInfo {
@@ -42,7 +52,7 @@ impl ServerInfo {
         let analysis = WasmAnalysis::analyze(wasm_path)?;
 
         Ok(Self {
-            url: format!("http://localhost:{port}"),
+            url: server_url(port),
             port,
             server_pid: std::process::id(),
             watch_mode,
@@ -56,7 +66,7 @@ impl ServerInfo {
         let content_type = ContentType::Project(analysis);
 
         Ok(Self {
-            url: format!("http://localhost:{port}"),
+            url: server_url(port),
             port,
             server_pid: std::process::id(),
             watch_mode,
@@ -262,6 +272,7 @@ pub fn compile_project(
         verbose: false,
         watch: false,
         target_type: TargetType::Standard,
+        naming: OutputNaming::default(),
     };
 
     // First try plugin-based compilation
@@ -280,8 +291,16 @@ pub fn compile_project(
                 println!("🔄 Falling back to built-in compilation...");
             } else {
                 println!("🔌 Compiling with plugin: {}", plugin.info().name);
-                match builder.build(&config) {
+                let build_started = Instant::now();
+                let build = builder.build(&config);
+                record_build(
+                    builder.language_name(),
+                    build_started.elapsed(),
+                    build.is_ok(),
+                );
+                match build {
                     Ok(result) => {
+                        write_build_manifest(&config, &result, builder.language_name());
                         println!("✅ Plugin compilation successful!");
                         println!("📦 WASM file: {}", result.wasm_path);
                         if let Some(ref js_path) = result.js_path {
@@ -301,8 +320,16 @@ pub fn compile_project(
     // Fallback to built-in compilation system
     println!("🔧 Using built-in compiler for {lang:?}");
     let builder = BuilderFactory::create_builder(&lang);
-    match builder.build(&config) {
+    let build_started = Instant::now();
+    let build = builder.build(&config);
+    record_build(
+        builder.language_name(),
+        build_started.elapsed(),
+        build.is_ok(),
+    );
+    match build {
         Ok(result) => {
+            write_build_manifest(&config, &result, builder.language_name());
             println!("✅ Built-in compilation successful!");
             println!("📦 WASM file: {}", result.wasm_path);
             if let Some(ref js_path) = result.js_path {
@@ -325,7 +352,7 @@ pub fn run_server(config: ServerConfig) -> Result<()> {
         }
     }
 
-    if !is_port_available(config.port) {
+    if unix_socket().is_none() && !is_port_available(config.port) {
         return Err(WasmrunError::Server(ServerError::RequestHandlingFailed {
             reason: format!("Port {} is already in use", config.port),
         }));
@@ -394,7 +421,10 @@ pub fn run_server(config: ServerConfig) -> Result<()> {
         config.port,
         &wasm_filename,
         config.project_path.as_deref(),
+        config.watch_mode,
         config.serve,
+        config.worker,
+        config.access_log,
     )
     .map_err(|e| {
         WasmrunError::Server(ServerError::RequestHandlingFailed {
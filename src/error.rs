@@ -75,6 +75,14 @@ pub enum WasmError {
     /// wasm-bindgen detection
     #[error("wasm-bindgen module detected but JavaScript file not found")]
     WasmBindgenJsNotFound,
+
+    /// Module breaks the import/export policy in wasmrun.toml
+    #[error("{path} violates the policy in {config}: {count} violation(s)")]
+    PolicyViolation {
+        path: String,
+        config: String,
+        count: usize,
+    },
 }
 
 /// Compilation-related errors
@@ -96,6 +104,15 @@ pub enum CompilationError {
     #[error("Failed to execute {tool}: {reason}")]
     ToolExecutionFailed { tool: String, reason: String },
 
+    /// Tool ran past the build timeout and was stopped
+    #[error("{tool} timed out after {timeout_secs}s and was stopped{}", partial_output(.output))]
+    TimedOut {
+        tool: String,
+        timeout_secs: u64,
+        /// The tail of what the tool printed before it was stopped
+        output: String,
+    },
+
     /// Project structure invalid
     #[error("Invalid {language} project structure: {reason}")]
     InvalidProjectStructure { language: String, reason: String },
@@ -138,6 +155,14 @@ pub enum ServerError {
     /// Failed to stop server
     #[error("Failed to stop server with PID {pid}: {reason}")]
     StopFailed { pid: u32, reason: String },
+
+    /// `wasmrun e2e` checks against the served page failed
+    #[error("End-to-end checks failed for {url}: {failed} of {total} check(s) failed")]
+    E2eFailed {
+        url: String,
+        failed: usize,
+        total: usize,
+    },
 }
 
 /// Command execution errors
@@ -208,6 +233,14 @@ impl WasmrunError {
         }
     }
 
+    /// A command or input that needs a cargo feature this build was made without
+    #[allow(dead_code)] // Unused when every feature is enabled
+    pub fn feature_disabled(what: impl std::fmt::Display, feature: &str) -> Self {
+        Self::from(format!(
+            "{what} needs the `{feature}` feature, which this build of wasmrun leaves out. Reinstall with `cargo install wasmrun --features {feature}`"
+        ))
+    }
+
     /// missing tools error
     pub fn missing_tools(tools: Vec<String>) -> Self {
         Self::MissingTools { tools }
@@ -300,6 +333,14 @@ impl CompilationError {
     }
 }
 
+fn partial_output(output: &str) -> String {
+    if output.trim().is_empty() {
+        String::new()
+    } else {
+        format!("; last output:\n{output}")
+    }
+}
+
 impl ServerError {
     /// new startup failed error
     pub fn startup_failed(port: u16, reason: impl Into<String>) -> Self {
@@ -0,0 +1,252 @@
+//! Translated CLI messages and language selection
+//!
+//! Each language is a TOML catalog beside this module whose tables and keys
+//! form dotted message ids (`[run] building = …` is `run.building`), with
+//! `{name}` marking an argument. English is the complete catalog; the others
+//! fall back to it key by key, so a partial translation is still usable.
+//! Adding a language means adding its file and listing it in `CATALOGS`.
+//!
+//! The CLI language comes from `--lang`, then `WASMRUN_LANG`, then the usual
+//! `LC_ALL`/`LC_MESSAGES`/`LANG` locale variables. Served pages use an
+//! explicit `--lang`/`WASMRUN_LANG` if given and otherwise the browser's
+//! `Accept-Language`.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("en.toml")),
+    ("es", include_str!("es.toml")),
+];
+
+/// Set by `--lang` or `WASMRUN_LANG`
+static EXPLICIT: RwLock<Option<&'static str>> = RwLock::new(None);
+
+type Catalog = HashMap<String, String>;
+
+fn catalogs() -> &'static HashMap<&'static str, Catalog> {
+    static PARSED: OnceLock<HashMap<&'static str, Catalog>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .map(|(language, source)| {
+                let table: toml::Table = toml::from_str(source)
+                    .unwrap_or_else(|e| panic!("invalid {language} message catalog: {e}"));
+                let mut catalog = Catalog::new();
+                flatten("", &table, &mut catalog);
+                (*language, catalog)
+            })
+            .collect()
+    })
+}
+
+fn flatten(prefix: &str, table: &toml::Table, catalog: &mut Catalog) {
+    for (key, value) in table {
+        let id = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => flatten(&id, table, catalog),
+            toml::Value::String(text) => {
+                catalog.insert(id, text.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Languages with a catalog, by code
+pub fn supported_languages() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(language, _)| *language)
+}
+
+/// The supported language for a tag like `es`, `es-MX` or `es_ES.UTF-8`
+pub fn match_language(tag: &str) -> Option<&'static str> {
+    let primary = tag
+        .split(['-', '_', '.', '@'])
+        .next()?
+        .trim()
+        .to_ascii_lowercase();
+    supported_languages().find(|language| *language == primary)
+}
+
+/// Pick the language for this run from `--lang` and the environment. An
+/// unsupported `--lang` is an error; unsupported locale variables are skipped.
+pub fn init(requested: Option<&str>) -> Result<(), String> {
+    let explicit = match requested {
+        Some(tag) => Some(match_language(tag).ok_or_else(|| {
+            let known: Vec<_> = supported_languages().collect();
+            format!(
+                "Unsupported language '{tag}' (available: {})",
+                known.join(", ")
+            )
+        })?),
+        None => std::env::var("WASMRUN_LANG")
+            .ok()
+            .and_then(|tag| match_language(&tag)),
+    };
+    *EXPLICIT.write().unwrap_or_else(|e| e.into_inner()) = explicit;
+    Ok(())
+}
+
+/// The language CLI messages are printed in
+pub fn language() -> &'static str {
+    explicit_language()
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|var| std::env::var(var).ok())
+                .find(|value| !value.is_empty())
+                .and_then(|value| match_language(&value))
+        })
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+fn explicit_language() -> Option<&'static str> {
+    *EXPLICIT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The language for a served page, given the request's `Accept-Language`
+pub fn page_language(accept_language: Option<&str>) -> &'static str {
+    explicit_language()
+        .or_else(|| accept_language.and_then(negotiate))
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// The best supported language in an `Accept-Language` header
+pub fn negotiate(accept_language: &str) -> Option<&'static str> {
+    let mut ranges: Vec<(f32, &str)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse().ok())?;
+            (quality > 0.0).then_some((quality, tag))
+        })
+        .collect();
+    // Stable, so equal weights keep the browser's order
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges.into_iter().find_map(|(_, tag)| match_language(tag))
+}
+
+/// The message `key` in the current language with `{name}` arguments filled
+/// in. Missing translations fall back to English, then to the key itself.
+pub fn translate(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate_in(language(), key, args)
+}
+
+pub fn translate_in(language: &str, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let catalogs = catalogs();
+    let template = catalogs
+        .get(language)
+        .and_then(|catalog| catalog.get(key))
+        .or_else(|| catalogs.get(DEFAULT_LANGUAGE)?.get(key));
+    let Some(template) = template else {
+        return key.to_string();
+    };
+    // One pass, so an argument containing `{name}` is left alone
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let arg = after.split_once('}').and_then(|(name, tail)| {
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((value, tail))
+        });
+        match arg {
+            Some((value, tail)) => {
+                message.push_str(&value.to_string());
+                rest = tail;
+            }
+            None => {
+                message.push('{');
+                rest = after;
+            }
+        }
+    }
+    message.push_str(rest);
+    message
+}
+
+/// Translate a message id, with optional `name = value` arguments:
+/// `t!("run.analyzing", path = resolved_path)`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $key,
+            &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_match_english() {
+        let catalogs = catalogs();
+        let english = &catalogs[DEFAULT_LANGUAGE];
+        for (language, catalog) in catalogs {
+            for (key, text) in catalog {
+                let Some(original) = english.get(key) else {
+                    panic!("{language} has a message {key} that English doesn't");
+                };
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(original),
+                    "{language} {key} has different arguments"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_with_fallbacks() {
+        assert_eq!(
+            translate_in("es", "run.running_wasm", &[("path", &"app.wasm")]),
+            "🎯 Ejecutando el archivo WASM: app.wasm"
+        );
+        assert_eq!(
+            translate_in("xx", "run.running_wasm", &[("path", &"app.wasm")]),
+            "🎯 Running WASM file: app.wasm"
+        );
+        assert_eq!(
+            translate_in("en", "watch.recompiled", &[("file", &"{file}.wasm")]),
+            "✅ Recompilation completed: {file}.wasm"
+        );
+        assert_eq!(translate_in("es", "no.such.key", &[]), "no.such.key");
+    }
+
+    #[test]
+    fn test_language_matching_and_negotiation() {
+        assert_eq!(match_language("es_ES.UTF-8"), Some("es"));
+        assert_eq!(match_language("EN-gb"), Some("en"));
+        assert_eq!(match_language("C"), None);
+
+        assert_eq!(negotiate("fr-CH, fr;q=0.9, es;q=0.8, en;q=0.7"), Some("es"));
+        assert_eq!(negotiate("en;q=0.5, es-MX"), Some("es"));
+        assert_eq!(negotiate("es;q=0, de"), None);
+        assert_eq!(negotiate(""), None);
+    }
+}
@@ -0,0 +1,107 @@
+//! # wasmrun-core
+//!
+//! The library behind the `wasmrun` CLI: project detection, build
+//! orchestration through the language plugins, WebAssembly inspection and
+//! the native runtime. Editors, CI plugins and other tools can embed these
+//! directly instead of shelling out to `wasmrun`.
+//!
+//! ```toml
+//! [dependencies]
+//! wasmrun = { version = "0.21", default-features = false, features = ["interpreter"] }
+//! ```
+//!
+//! The package is `wasmrun`; the library is imported as `wasmrun_core`.
+//!
+//! ## Detecting and building a project
+//!
+//! ```no_run
+//! use wasmrun_core::{create_wasm_from_project, detect_project_language, ProjectLanguage};
+//!
+//! let language = detect_project_language("./my-project");
+//! if language != ProjectLanguage::Unknown {
+//!     let wasm = create_wasm_from_project("./my-project", "./dist")?;
+//!     println!("built {} module at {wasm}", language.name());
+//! }
+//! # Ok::<(), wasmrun_core::WasmrunError>(())
+//! ```
+//!
+//! ## Inspecting a module
+//!
+//! ```no_run
+//! use wasmrun_core::{verify_wasm, WasmAnalysis};
+//!
+//! let analysis = WasmAnalysis::analyze("app.wasm")?;
+//! println!("{} exports, entry points {:?}", analysis.exports_count, analysis.entry_points);
+//!
+//! if let Ok(verification) = verify_wasm("app.wasm") {
+//!     println!("{} sections", verification.section_count);
+//! }
+//! # Ok::<(), wasmrun_core::WasmrunError>(())
+//! ```
+//!
+//! ## What's public
+//!
+//! The re-exports below and the [`error`] module are the whole API:
+//!
+//! - **Projects**: [`detect_project_language`], [`detect_language_candidates`]
+//!   and [`ProjectLanguage`]
+//! - **Builds**: [`create_wasm_from_project`], [`compile_for_execution`], and
+//!   [`BuildConfig`] with its [`OutputNaming`], [`OptimizationLevel`] and
+//!   [`TargetType`], giving a [`BuildResult`]
+//! - **Inspection**: [`WasmAnalysis`], [`ModuleType`], [`verify_wasm`],
+//!   [`verify_wasm_bytes`] and the parsed [`Module`]
+//! - **Running**, with the `interpreter` feature: [`execute_wasm_file`],
+//!   [`execute_wasm_bytes`], and an [`Executor`] over a [`Module`] for
+//!   calling functions with [`Value`]s, on a thread with
+//!   [`interpreter_stack_bytes`] of stack
+//!
+//! Everything else, the CLI, its commands, the compiler and plugin
+//! internals, the OS mode runtime and the dev server among them, is private
+//! to the crate. The `wasmrun` binary only calls [`run_cli`].
+
+#[cfg(feature = "interpreter")]
+mod agent;
+mod ci;
+mod cli;
+mod commands;
+mod compiler;
+mod config;
+mod debug;
+pub mod error;
+mod i18n;
+mod logging;
+mod plugin;
+mod remote;
+mod rpc;
+mod runtime;
+mod server;
+mod template;
+mod ui;
+mod utils;
+mod watcher;
+
+pub use commands::{verify_wasm, verify_wasm_bytes, VerificationResult};
+pub use compiler::builder::{
+    BuildConfig, BuildResult, OptimizationLevel, OutputNaming, TargetType,
+};
+pub use compiler::{
+    compile_for_execution, create_wasm_from_project, detect_language_candidates,
+    detect_project_language, ProjectLanguage,
+};
+pub use error::{Result, WasmrunError};
+pub use runtime::core::module::Module;
+pub use utils::{ModuleType, WasmAnalysis};
+
+#[cfg(feature = "interpreter")]
+pub use runtime::core::executor::Executor;
+#[cfg(feature = "interpreter")]
+pub use runtime::core::native_executor::{
+    execute_wasm_bytes, execute_wasm_file, interpreter_stack_bytes,
+};
+#[cfg(feature = "interpreter")]
+pub use runtime::core::values::Value;
+
+/// Run the `wasmrun` command line with the process's arguments, as the
+/// `wasmrun` binary does
+#[doc(hidden)]
+pub use cli::run as run_cli;
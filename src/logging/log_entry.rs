@@ -45,6 +45,15 @@ impl fmt::Display for LogSource {
     }
 }
 
+/// A source file whose change set off a watch rebuild
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Relative to the project root
+    pub path: String,
+    /// SHA-256 of the contents the rebuild saw; `None` once deleted
+    pub sha256: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub timestamp: String,
@@ -52,6 +61,9 @@ pub struct LogEntry {
     pub source: LogSource,
     pub message: String,
     pub pid: Option<u32>,
+    /// The edits behind a rebuild entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<FileChange>,
 }
 
 impl LogEntry {
@@ -66,9 +78,21 @@ impl LogEntry {
             source,
             message: message.into(),
             pid: None,
+            changes: Vec::new(),
+        }
+    }
+
+    /// "Rebuilt due to src/lib.rs, style.css", carrying the changes
+    pub fn rebuild(changes: Vec<FileChange>) -> Self {
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        let message = format!("Rebuilt due to {}", paths.join(", "));
+        Self {
+            changes,
+            ..Self::info(LogSource::DevServer, message)
         }
     }
 
+    #[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
     pub fn with_pid(mut self, pid: u32) -> Self {
         self.pid = Some(pid);
         self
@@ -1,5 +1,5 @@
 pub mod log_entry;
 pub mod system;
 
-pub use log_entry::{LogEntry, LogSource};
+pub use log_entry::{FileChange, LogEntry, LogSource};
 pub use system::LogTrailSystem;
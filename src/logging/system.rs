@@ -4,19 +4,28 @@ use std::sync::{Arc, Mutex};
 
 pub const MAX_LOG_ENTRIES: usize = 1000;
 
+/// Called with each new entry; returns `false` to unsubscribe
+type Subscriber = Box<dyn FnMut(&LogEntry) -> bool + Send>;
+
 pub struct LogTrailSystem {
     entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
 }
 
 impl LogTrailSystem {
     pub fn new() -> Self {
         Self {
             entries: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_LOG_ENTRIES))),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
     pub fn log(&self, entry: LogEntry) {
         let mut entries = self.entries.lock().unwrap();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain_mut(|subscriber| subscriber(&entry));
         entries.push_back(entry);
 
         if entries.len() > MAX_LOG_ENTRIES {
@@ -24,11 +33,28 @@ impl LogTrailSystem {
         }
     }
 
+    /// Call `subscriber` with the last `backlog` entries and then with every
+    /// new one, until it returns `false`
+    #[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
+    pub fn subscribe(
+        &self,
+        backlog: usize,
+        mut subscriber: impl FnMut(&LogEntry) -> bool + Send + 'static,
+    ) {
+        // Holding the entries lock means nothing is logged in between
+        let entries = self.entries.lock().unwrap();
+        let skip = entries.len().saturating_sub(backlog);
+        if entries.iter().skip(skip).all(&mut subscriber) {
+            self.subscribers.lock().unwrap().push(Box::new(subscriber));
+        }
+    }
+
     pub fn get_all(&self) -> Vec<LogEntry> {
         let entries = self.entries.lock().unwrap();
         entries.iter().cloned().collect()
     }
 
+    #[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
     pub fn get_recent(&self, count: usize) -> Vec<LogEntry> {
         let entries = self.entries.lock().unwrap();
         entries
@@ -63,6 +89,35 @@ impl Clone for LogTrailSystem {
     fn clone(&self) -> Self {
         Self {
             entries: Arc::clone(&self.entries),
+            subscribers: Arc::clone(&self.subscribers),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::LogSource;
+
+    #[test]
+    fn test_subscribe_sends_backlog_then_new_entries() {
+        let logs = LogTrailSystem::new();
+        for message in ["one", "two", "three"] {
+            logs.log(LogEntry::info(LogSource::Kernel, message));
         }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        logs.subscribe(2, move |entry| {
+            let mut seen = sink.lock().unwrap();
+            seen.push(entry.message.clone());
+            seen.len() < 3
+        });
+        logs.log(LogEntry::info(LogSource::Kernel, "four"));
+        // Unsubscribed after its third entry
+        logs.log(LogEntry::info(LogSource::Kernel, "five"));
+
+        assert_eq!(*seen.lock().unwrap(), ["two", "three", "four"]);
+        assert!(logs.subscribers.lock().unwrap().is_empty());
     }
 }
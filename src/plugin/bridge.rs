@@ -216,6 +216,11 @@ impl StringArrayC {
     }
 
     /// Convert from C array to Rust Vec<String>
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to `len` pointers, each null or a valid
+    /// NUL-terminated string, as built by [`Self::from_vec`].
     #[allow(dead_code)]
     pub unsafe fn to_vec(&self) -> Vec<String> {
         if self.data.is_null() || self.len == 0 {
@@ -240,7 +245,13 @@ impl StringArrayC {
 }
 
 impl BuildResultC {
-    pub fn to_build_result(ptr: *mut BuildResultC) -> BuildResult {
+    /// Copy a plugin's build result into a [`BuildResult`]
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to a `BuildResultC` returned by a
+    /// plugin's exported build function, whose strings are valid.
+    pub unsafe fn to_build_result(ptr: *mut BuildResultC) -> BuildResult {
         unsafe {
             if ptr.is_null() {
                 return BuildResult {
@@ -248,6 +259,7 @@ impl BuildResultC {
                     js_path: None,
                     additional_files: vec![],
                     is_wasm_bindgen: false,
+                    target: None,
                 };
             }
 
@@ -275,6 +287,7 @@ impl BuildResultC {
                 js_path,
                 additional_files: vec![],
                 is_wasm_bindgen: result.is_wasm_bindgen,
+                target: None,
             }
         }
     }
@@ -293,11 +306,14 @@ impl BuildConfigExt for BuildConfig {
 
 #[allow(dead_code)]
 pub trait BuildResultExt {
-    fn from_c_ptr(ptr: *mut BuildResultC) -> Self;
+    /// # Safety
+    ///
+    /// The same as [`BuildResultC::to_build_result`].
+    unsafe fn from_c_ptr(ptr: *mut BuildResultC) -> Self;
 }
 
 impl BuildResultExt for BuildResult {
-    fn from_c_ptr(ptr: *mut BuildResultC) -> Self {
+    unsafe fn from_c_ptr(ptr: *mut BuildResultC) -> Self {
         BuildResultC::to_build_result(ptr)
     }
 }
@@ -69,7 +69,7 @@ impl Plugin for BuiltinPlugin {
     }
 
     fn get_builder(&self) -> Box<dyn WasmBuilder> {
-        self.inner_plugin.get_builder()
+        crate::remote::remote_or_local(self.inner_plugin.get_builder())
     }
 }
 
@@ -145,6 +145,13 @@ impl WasmBuilder for BuiltinBuilderWrapper {
         self.builder.validate_project(project_path)
     }
 
+    fn plan(
+        &self,
+        config: &crate::compiler::builder::BuildConfig,
+    ) -> crate::error::CompilationResult<crate::compiler::builder::BuildPlan> {
+        self.builder.plan(config)
+    }
+
     fn can_handle_project(&self, project_path: &str) -> bool {
         self.builder.can_handle_project(project_path)
     }
@@ -153,6 +160,10 @@ impl WasmBuilder for BuiltinBuilderWrapper {
         self.builder.clean(project_path)
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<crate::compiler::builder::CleanTarget> {
+        self.builder.clean_targets(project_path)
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         self.builder.clone_box()
     }
@@ -253,6 +264,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_builtin_plugin_build_plans() {
+        use crate::compiler::builder::{BuildConfig, BuildStep};
+
+        let mut plugins = Vec::new();
+        load_all_builtin_plugins(&mut plugins).unwrap();
+        let plan_for = |name: &str, project: &std::path::Path| {
+            let plugin = plugins.iter().find(|p| p.info().name == name).unwrap();
+            let config = BuildConfig::with_defaults(
+                project.to_string_lossy().to_string(),
+                "out".to_string(),
+            );
+            plugin.get_builder().plan(&config).unwrap()
+        };
+
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("go.mod"), "module example.com/hello\n").unwrap();
+        let plan = plan_for("go", temp_dir.path());
+        let commands: Vec<String> = plan
+            .steps
+            .iter()
+            .map(|step| match step {
+                BuildStep::Run { command, .. } => command.to_string(),
+                other => panic!("unexpected step {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                "tinygo build -o out/hello.wasm -target wasi .",
+                "tinygo build -o out/hello.wasm -target wasm .",
+            ]
+        );
+        assert_eq!(plan.outputs, vec!["out/hello.wasm"]);
+
+        // Naming applies per target
+        let go = plugins.iter().find(|p| p.info().name == "go").unwrap();
+        let mut config = BuildConfig::with_defaults(
+            temp_dir.path().to_string_lossy().to_string(),
+            "out".to_string(),
+        );
+        config.naming.pattern = Some("{name}-{profile}".to_string());
+        config.naming.target_dirs = true;
+        let plan = go.get_builder().plan(&config).unwrap();
+        let BuildStep::Run { command, .. } = &plan.steps[1] else {
+            panic!("expected the fallback build");
+        };
+        assert_eq!(
+            command.to_string(),
+            "tinygo build -o out/wasm/hello-release.wasm -target wasm ."
+        );
+        assert_eq!(plan.outputs, vec!["out/wasi/hello-release.wasm"]);
+
+        // Arguments with spaces or quotes are shell-quoted
+        let temp_dir = tempdir().unwrap();
+        File::create(temp_dir.path().join("my app.c")).unwrap();
+        let plan = plan_for("c", temp_dir.path());
+        let BuildStep::Run { command, .. } = &plan.steps[0] else {
+            panic!("expected emcc to run");
+        };
+        let line = command.to_string();
+        assert!(line.starts_with("emcc -o 'out/my app.js'"), "{line}");
+        assert!(line.contains("'EXPORTED_RUNTIME_METHODS=['\\''cwrap'\\'']'"));
+    }
+
     #[test]
     fn test_builtin_plugin_can_handle_project() {
         let mut plugins = Vec::new();
@@ -1,23 +1,33 @@
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::Arc;
 
-use crate::compiler::builder::{BuildConfig, BuildResult, WasmBuilder};
+use crate::compiler::builder::{
+    BuildConfig, BuildPlan, BuildResult, CleanKind, CleanTarget, PlannedCommand, WasmBuilder,
+};
 use crate::config::ExternalPluginEntry;
 use crate::error::{CompilationError, CompilationResult, Result, WasmrunError};
 use crate::plugin::metadata::PluginMetadata;
+use crate::plugin::panel::{PanelRequest, PanelResponse, PluginPanel};
 use crate::plugin::{Plugin, PluginInfo};
-use crate::utils::{PluginUtils, SystemUtils};
+use crate::utils::{CommandExecutor, PluginUtils, SystemUtils};
 
 #[cfg(not(target_os = "windows"))]
 use crate::plugin::bridge::symbols;
 #[cfg(not(target_os = "windows"))]
 use libloading::Library;
 
+/// Where external plugins usually write their builds
+const EXTERNAL_BUILD_DIRS: [&str; 4] = ["target", "build", "dist", "out"];
+
 /// Generic wrapper for all external plugins (no hardcoding)
 pub struct ExternalPluginWrapper {
     info: PluginInfo,
     plugin_name: String,
     metadata: PluginMetadata,
+    /// Where the plugin is installed; panel fragments are relative to it
+    plugin_dir: PathBuf,
     #[cfg(not(target_os = "windows"))]
     library: Option<Arc<Library>>,
 }
@@ -45,6 +55,7 @@ impl ExternalPluginWrapper {
             info: entry.info,
             plugin_name,
             metadata,
+            plugin_dir: plugin_path,
             #[cfg(not(target_os = "windows"))]
             library,
         })
@@ -151,12 +162,96 @@ impl Plugin for ExternalPluginWrapper {
     }
 
     fn get_builder(&self) -> Box<dyn WasmBuilder> {
-        Box::new(ExternalWasmBuilder::new(
+        crate::remote::remote_or_local(Box::new(ExternalWasmBuilder::new(
             self.plugin_name.clone(),
             self.metadata.clone(),
             #[cfg(not(target_os = "windows"))]
             self.library.clone(),
-        ))
+        )))
+    }
+
+    fn panels(&self, _project_path: &str) -> Vec<PluginPanel> {
+        self.metadata
+            .panels
+            .iter()
+            .filter_map(|panel| {
+                let path = self.plugin_dir.join(&panel.html);
+                match std::fs::read_to_string(&path) {
+                    Ok(html) => Some(PluginPanel {
+                        id: panel.id.clone(),
+                        title: panel.title.clone(),
+                        html,
+                    }),
+                    Err(e) => {
+                        eprintln!(
+                            "⚠️  Skipping panel '{}' of plugin '{}': {}: {e}",
+                            panel.id,
+                            self.plugin_name,
+                            path.display()
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn handle_panel_request(&self, request: &PanelRequest) -> Option<PanelResponse> {
+        if self.metadata.panels.is_empty() {
+            return None;
+        }
+        let binary = plugin_binary(&self.plugin_name);
+        let mut route = request.path.to_string();
+        if !request.query.is_empty() {
+            route = format!("{route}?{}", request.query);
+        }
+        let mut command = Command::new(&binary);
+        command
+            .args(["panel-api", request.method, &route])
+            .args(
+                request
+                    .project_path
+                    .map(|path| ["-p", path])
+                    .iter()
+                    .flatten(),
+            )
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let output = command.spawn().and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                // A plugin that doesn't read the body closes stdin early
+                let _ = stdin.write_all(request.body);
+            }
+            child.wait_with_output()
+        });
+        Some(match output {
+            Ok(output) if output.status.success() => PanelResponse {
+                status: 200,
+                content_type: "application/json".to_string(),
+                body: output.stdout,
+            },
+            Ok(output) => PanelResponse::error(
+                500,
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ),
+            Err(e) => PanelResponse::error(500, format!("Failed to run {binary}: {e}")),
+        })
+    }
+}
+
+/// The plugin's executable: the copy in `~/.wasmrun/bin` if installed there,
+/// otherwise whatever `plugin_name` resolves to on `PATH`
+fn plugin_binary(plugin_name: &str) -> String {
+    let wasmrun_bin_path = dirs::home_dir()
+        .map(|home| home.join(".wasmrun").join("bin").join(plugin_name))
+        .unwrap_or_else(|| PathBuf::from(plugin_name));
+
+    if wasmrun_bin_path.exists() {
+        wasmrun_bin_path.to_string_lossy().to_string()
+    } else {
+        plugin_name.to_string()
     }
 }
 
@@ -182,22 +277,28 @@ impl ExternalWasmBuilder {
         }
     }
 
-    fn build_via_command(&self, config: &BuildConfig) -> CompilationResult<BuildResult> {
-        // Try to find the plugin binary in ~/.wasmrun/bin first, then fallback to system PATH
-        let wasmrun_bin_path = dirs::home_dir()
-            .map(|home| home.join(".wasmrun").join("bin").join(&self.plugin_name))
-            .unwrap_or_else(|| PathBuf::from(&self.plugin_name));
+    /// The plugin binary in ~/.wasmrun/bin, else the one on PATH
+    fn plugin_binary(&self) -> String {
+        plugin_binary(&self.plugin_name)
+    }
 
-        let plugin_binary = if wasmrun_bin_path.exists() {
-            wasmrun_bin_path.to_string_lossy().to_string()
-        } else {
-            self.plugin_name.clone()
-        };
+    fn command_args(config: &BuildConfig) -> [&str; 5] {
+        [
+            "compile",
+            "-p",
+            &config.project_path,
+            "-o",
+            &config.output_dir,
+        ]
+    }
 
-        let output = std::process::Command::new(&plugin_binary)
-            .args(["compile", "-p", &config.project_path])
-            .args(["-o", &config.output_dir])
-            .output();
+    fn build_via_command(&self, config: &BuildConfig) -> CompilationResult<BuildResult> {
+        let output = CommandExecutor::execute_command(
+            &self.plugin_binary(),
+            &Self::command_args(config),
+            ".",
+            config.verbose,
+        );
 
         match output {
             Ok(result) if result.status.success() => {
@@ -212,6 +313,7 @@ impl ExternalWasmBuilder {
                                 js_path: None,
                                 additional_files: vec![],
                                 is_wasm_bindgen: false,
+                                target: None,
                             });
                         }
                     }
@@ -230,10 +332,13 @@ impl ExternalWasmBuilder {
                     reason: format!("Build failed: {stderr}"),
                 })
             }
-            Err(e) => Err(CompilationError::BuildFailed {
-                language: self.plugin_name.clone(),
-                reason: format!("Failed to execute plugin: {e}"),
-            }),
+            Err(CompilationError::ToolExecutionFailed { reason, .. }) => {
+                Err(CompilationError::BuildFailed {
+                    language: self.plugin_name.clone(),
+                    reason: format!("Failed to execute plugin: {reason}"),
+                })
+            }
+            Err(e) => Err(e),
         }
     }
 }
@@ -297,6 +402,23 @@ impl WasmBuilder for ExternalWasmBuilder {
         self.build_via_command(config)
     }
 
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        let mut plan = BuildPlan::default();
+        #[cfg(not(target_os = "windows"))]
+        if self.library.is_some() {
+            plan = plan.note(format!(
+                "Calls the {} library in-process; if it can't build, falls back to the command below",
+                self.plugin_name
+            ));
+        }
+        let command =
+            PlannedCommand::new(&self.plugin_binary(), &Self::command_args(config), ".", &[]);
+        Ok(plan.run(command).note(format!(
+            "The first .wasm the plugin writes to {} is the result",
+            config.output_dir
+        )))
+    }
+
     fn check_dependencies(&self) -> Vec<String> {
         let mut missing = Vec::new();
 
@@ -383,9 +505,7 @@ impl WasmBuilder for ExternalWasmBuilder {
         }
 
         let project_path = Path::new(project_path);
-        let build_dirs = ["target", "build", "dist", "out"];
-
-        for dir in &build_dirs {
+        for dir in EXTERNAL_BUILD_DIRS {
             let build_path = project_path.join(dir);
             if build_path.exists() {
                 let _ = std::fs::remove_dir_all(&build_path);
@@ -395,6 +515,21 @@ impl WasmBuilder for ExternalWasmBuilder {
         Ok(())
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<CleanTarget> {
+        // The plugin's own clean export can't say what it would remove, so
+        // list the build directories it falls back to
+        EXTERNAL_BUILD_DIRS
+            .iter()
+            .filter_map(|dir| {
+                CleanTarget::existing(
+                    Path::new(project_path).join(dir),
+                    CleanKind::Artifact,
+                    "build output",
+                )
+            })
+            .collect()
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         Box::new(ExternalWasmBuilder::new(
             self.plugin_name.clone(),
@@ -510,6 +645,7 @@ mod tests {
             },
             exports: None,
             frameworks: None,
+            panels: vec![],
         }
     }
 
@@ -695,6 +831,7 @@ mod tests {
             verbose: false,
             watch: false,
             target_type: crate::compiler::builder::TargetType::Standard,
+            naming: Default::default(),
         };
 
         let result = builder.build(&config);
@@ -807,6 +944,7 @@ mod tests {
                 verbose: false,
                 watch: false,
                 target_type: crate::compiler::builder::TargetType::Standard,
+                naming: Default::default(),
             },
             BuildConfig {
                 project_path: temp_dir.path().to_str().unwrap().to_string(),
@@ -815,6 +953,7 @@ mod tests {
                 verbose: true,
                 watch: true,
                 target_type: crate::compiler::builder::TargetType::Standard,
+                naming: Default::default(),
             },
         ];
 
@@ -824,6 +963,47 @@ mod tests {
             assert!(result.is_err()); // Expected to fail
         }
     }
+
+    #[test]
+    fn test_metadata_panels_from_cargo_toml() {
+        let content = r#"
+[package]
+name = "wasmrun-test"
+version = "0.1.0"
+
+[package.metadata.wasm_plugin]
+name = "test"
+version = "0.1.0"
+description = "Test plugin"
+author = "Test Author"
+extensions = ["test"]
+entry_files = ["main.test"]
+
+[package.metadata.wasm_plugin.capabilities]
+compile_wasm = true
+compile_webapp = false
+live_reload = false
+optimization = false
+custom_targets = []
+
+[package.metadata.wasm_plugin.dependencies]
+tools = []
+
+[[package.metadata.wasm_plugin.panels]]
+id = "stats"
+title = "Test Stats"
+html = "panels/stats.html"
+"#;
+        let metadata = PluginMetadata::from_cargo_toml_content(content).unwrap();
+        assert_eq!(metadata.panels.len(), 1);
+        assert_eq!(metadata.panels[0].id, "stats");
+        assert_eq!(metadata.panels[0].html, "panels/stats.html");
+
+        // Plugins without panels still parse
+        let without = content.split("[[package").next().unwrap();
+        let metadata = PluginMetadata::from_cargo_toml_content(without).unwrap();
+        assert!(metadata.panels.is_empty());
+    }
 }
 
 /// New API - WasmBuilder that directly interfaces with plugin library
@@ -958,6 +1138,7 @@ impl WasmBuilder for NewApiWasmBuilder {
                             js_path: None,
                             additional_files: vec![],
                             is_wasm_bindgen: false,
+                            target: None,
                         });
                     } else {
                         let error_msg = if !result.error_message.is_null() {
@@ -1048,6 +1229,7 @@ impl WasmBuilder for NewApiWasmBuilder {
                             js_path: None,
                             additional_files: vec![],
                             is_wasm_bindgen: false,
+                            target: None,
                         });
                     } else {
                         let error_msg = if !result.error_message.is_null() {
@@ -1132,6 +1314,16 @@ impl WasmBuilder for NewApiWasmBuilder {
         Ok(())
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<CleanTarget> {
+        CleanTarget::existing(
+            Path::new(project_path).join("dist"),
+            CleanKind::Artifact,
+            "waspy output",
+        )
+        .into_iter()
+        .collect()
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         Box::new(Self {
             plugin_name: self.plugin_name.clone(),
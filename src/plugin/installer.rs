@@ -1,7 +1,33 @@
 use crate::error::{Result, WasmrunError};
 use crate::plugin::registry::PluginRegistry;
+use crate::ui::progress;
 use crate::utils::{PluginUtils, SystemUtils};
 use std::path::{Path, PathBuf};
+use std::process::Output;
+
+/// Run cargo behind a spinner, capturing its output for error reports
+fn run_cargo(doing: String, done: String, dir: Option<&Path>, args: &[&str]) -> Result<Output> {
+    let task = progress::spinner(doing);
+    let mut command = std::process::Command::new("cargo");
+    command.args(args);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    match command.output() {
+        Ok(output) if output.status.success() => {
+            task.finish(done);
+            Ok(output)
+        }
+        Ok(output) => {
+            task.fail();
+            Ok(output)
+        }
+        Err(e) => {
+            task.fail();
+            Err(WasmrunError::from(format!("Failed to run cargo: {e}")))
+        }
+    }
+}
 
 /// Candidate dynamic-library file names for a plugin, in platform-preferred order.
 ///
@@ -265,8 +291,9 @@ impl PluginInstaller {
 
         let mut result = InstallationResult::new(plugin_name);
 
-        // Download the crate source
-        println!("📥 Downloading {plugin_name} from crates.io...");
+        // Download the crate source. This usually fails for library-only
+        // crates, so the spinner is cleared rather than reported as failed.
+        let download = progress::spinner(format!("Downloading {plugin_name} from crates.io"));
         let _output = std::process::Command::new("cargo")
             .args([
                 "install",
@@ -276,6 +303,7 @@ impl PluginInstaller {
                 "--no-track",
             ])
             .output();
+        drop(download);
 
         // Even if cargo install fails for library, we can try building manually
         let temp_download = std::env::temp_dir().join(format!("{plugin_name}_download"));
@@ -329,12 +357,12 @@ impl PluginInstaller {
             .map_err(|e| WasmrunError::from(format!("Failed to write Cargo.toml: {e}")))?;
 
         // Download dependencies
-        println!("⬇️  Downloading dependencies...");
-        let fetch_output = std::process::Command::new("cargo")
-            .current_dir(&temp_download)
-            .args(["fetch"])
-            .output()
-            .map_err(|e| WasmrunError::from(format!("Failed to fetch dependencies: {e}")))?;
+        let fetch_output = run_cargo(
+            "Downloading dependencies".to_string(),
+            "Downloaded dependencies".to_string(),
+            Some(&temp_download),
+            &["fetch"],
+        )?;
 
         if !fetch_output.status.success() {
             let stderr = String::from_utf8_lossy(&fetch_output.stderr);
@@ -342,8 +370,6 @@ impl PluginInstaller {
         }
 
         // Now copy the downloaded source to plugin directory
-        println!("🔨 Building {plugin_name} as dynamic library...");
-
         // Build the plugin as a cdylib in the plugin directory
         std::fs::create_dir_all(plugin_dir)
             .map_err(|e| WasmrunError::from(format!("Failed to create plugin directory: {e}")))?;
@@ -377,11 +403,12 @@ crate-type = ["cdylib", "rlib"]
             .map_err(|e| WasmrunError::from(format!("Failed to write lib.rs: {e}")))?;
 
         // Build the dynamic library
-        let build_output = std::process::Command::new("cargo")
-            .current_dir(plugin_dir)
-            .args(["build", "--release", "--lib"])
-            .output()
-            .map_err(|e| WasmrunError::from(format!("Failed to build plugin: {e}")))?;
+        let build_output = run_cargo(
+            format!("Building {plugin_name} as a dynamic library"),
+            format!("Built {plugin_name}"),
+            Some(plugin_dir),
+            &["build", "--release", "--lib"],
+        )?;
 
         if !build_output.status.success() {
             let stderr = String::from_utf8_lossy(&build_output.stderr);
@@ -428,8 +455,6 @@ crate-type = ["cdylib", "rlib"]
     }
 
     fn install_generic_plugin(plugin_name: &str, plugin_dir: &Path) -> Result<InstallationResult> {
-        println!("Installing {plugin_name} plugin via cargo...");
-
         let mut result = InstallationResult::new(plugin_name);
 
         std::fs::create_dir_all(plugin_dir)
@@ -442,21 +467,21 @@ crate-type = ["cdylib", "rlib"]
         std::fs::create_dir_all(&wasmrun_root)
             .map_err(|e| WasmrunError::from(format!("Failed to create .wasmrun directory: {e}")))?;
 
-        let output = std::process::Command::new("cargo")
-            .args([
+        let output = run_cargo(
+            format!("Installing {plugin_name} with cargo"),
+            format!("Installed {plugin_name} to ~/.wasmrun/"),
+            None,
+            &[
                 "install",
                 plugin_name,
                 "--root",
                 &wasmrun_root.to_string_lossy(),
                 "--features",
                 "cli",
-            ])
-            .output()
-            .map_err(|e| WasmrunError::from(format!("Failed to execute cargo install: {e}")))?;
+            ],
+        )?;
 
         if output.status.success() {
-            println!("✅ Plugin installed successfully via cargo to ~/.wasmrun/");
-
             let bin_path = wasmrun_root.join("bin").join(plugin_name);
             if bin_path.exists() {
                 println!("📦 Binary found at: {}", bin_path.display());
@@ -1,10 +1,18 @@
-use crate::compiler::builder::{BuildConfig, BuildResult, WasmBuilder};
+use crate::compiler::builder::{
+    BuildConfig, BuildPlan, BuildResult, CleanKind, CleanTarget, PlannedCommand, WasmBuilder,
+};
 use crate::error::{CompilationError, CompilationResult, Result};
 use crate::plugin::{Plugin, PluginCapabilities, PluginInfo, PluginType};
 use crate::utils::{CommandExecutor, PathResolver};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Target name for `{target}` and per-target output directories
+const ASC_TARGET: &str = "wasm32";
+const NPM_ARGS: [&str; 2] = ["run", "build"];
+/// Environment npm reads that changes the build
+const NPM_ENV: &[&str] = &["NODE_OPTIONS", "NODE_ENV", "npm_config_script_shell"];
+
 /// AssemblyScript WebAssembly plugin
 #[derive(Clone)]
 pub struct AscPlugin {
@@ -52,6 +60,15 @@ impl AscPlugin {
         false
     }
 
+    /// The asc invocation used when `npm run build` fails
+    fn asc_command() -> (&'static str, &'static [&'static str]) {
+        if CommandExecutor::is_tool_installed("asc") {
+            ("asc", &["assembly/index.ts", "--target", "release"])
+        } else {
+            ("npx", &["asc", "assembly/index.ts", "--target", "release"])
+        }
+    }
+
     fn find_output_wasm(project_path: &str) -> Option<PathBuf> {
         let build_dir = Path::new(project_path).join("build");
         if !build_dir.exists() {
@@ -163,7 +180,7 @@ impl WasmBuilder for AscPlugin {
         // Prefer npm run build; fall back to asc directly
         let npm_result = CommandExecutor::execute_command(
             "npm",
-            &["run", "build"],
+            &NPM_ARGS,
             &config.project_path,
             config.verbose,
         );
@@ -175,19 +192,10 @@ impl WasmBuilder for AscPlugin {
                 if config.verbose {
                     println!("⚠️  npm run build failed, trying asc directly...");
                 }
-                let asc_cmd = if CommandExecutor::is_tool_installed("asc") {
-                    "asc"
-                } else {
-                    "npx"
-                };
-                let asc_args: Vec<&str> = if asc_cmd == "npx" {
-                    vec!["asc", "assembly/index.ts", "--target", "release"]
-                } else {
-                    vec!["assembly/index.ts", "--target", "release"]
-                };
+                let (asc_cmd, asc_args) = Self::asc_command();
                 match CommandExecutor::execute_command(
                     asc_cmd,
-                    &asc_args,
+                    asc_args,
                     &config.project_path,
                     config.verbose,
                 ) {
@@ -211,9 +219,13 @@ impl WasmBuilder for AscPlugin {
             }
         })?;
 
-        let output_wasm = CommandExecutor::copy_to_output(
+        let name = wasm_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("output");
+        let output_wasm = CommandExecutor::copy_as(
             wasm_path.to_str().unwrap_or_default(),
-            &config.output_dir,
+            &config.artifact_path(name, ASC_TARGET, "wasm"),
             "AssemblyScript",
         )?;
 
@@ -222,9 +234,35 @@ impl WasmBuilder for AscPlugin {
             js_path: None,
             additional_files: vec![],
             is_wasm_bindgen: false,
+            target: Some(ASC_TARGET.to_string()),
         })
     }
 
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        let (asc_cmd, asc_args) = Self::asc_command();
+        let build_dir = Path::new(&config.project_path).join("build");
+        Ok(BuildPlan::default()
+            .run(PlannedCommand::new(
+                "npm",
+                &NPM_ARGS,
+                &config.project_path,
+                NPM_ENV,
+            ))
+            .run_when(
+                PlannedCommand::new(asc_cmd, asc_args, &config.project_path, NPM_ENV),
+                "if npm run build fails",
+            )
+            .copy(
+                format!(
+                    "{} (optimized, release, output or main.wasm, else the first .wasm)",
+                    build_dir.display()
+                ),
+                config
+                    .artifact_path("{name}", ASC_TARGET, "wasm")
+                    .to_string_lossy(),
+            ))
+    }
+
     fn clean(&self, project_path: &str) -> Result<()> {
         let build_dir = Path::new(project_path).join("build");
         if build_dir.exists() {
@@ -233,6 +271,21 @@ impl WasmBuilder for AscPlugin {
         Ok(())
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<CleanTarget> {
+        let project = Path::new(project_path);
+        [
+            CleanTarget::existing(project.join("build"), CleanKind::Artifact, "asc output"),
+            CleanTarget::existing(
+                project.join("node_modules"),
+                CleanKind::Dependency,
+                "npm packages",
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         Box::new(self.clone())
     }
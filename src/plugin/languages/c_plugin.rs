@@ -1,10 +1,20 @@
-use crate::compiler::builder::{BuildConfig, BuildResult, OptimizationLevel, WasmBuilder};
+use crate::compiler::builder::{
+    BuildConfig, BuildPlan, BuildResult, CleanKind, CleanTarget, OptimizationLevel, PlannedCommand,
+    WasmBuilder,
+};
 use crate::error::{CompilationError, CompilationResult};
 use crate::plugin::{Plugin, PluginCapabilities, PluginInfo, PluginType};
 use crate::utils::{CommandExecutor, PathResolver};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Target name for `{target}` and per-target output directories
+const C_TARGET: &str = "emscripten";
+/// Tried before a bare `make`
+const MAKE_TARGET: [&str; 1] = ["wasm"];
+/// Environment make and emcc read that changes the build
+const EMCC_ENV: &[&str] = &["EMSDK", "EM_CONFIG", "EMCC_CFLAGS", "CFLAGS", "MAKEFLAGS"];
+
 /// C WebAssembly plugin
 #[derive(Clone)]
 pub struct CPlugin {
@@ -96,7 +106,7 @@ impl CPlugin {
         // Execute make
         let build_output = CommandExecutor::execute_command(
             "make",
-            &["wasm"],
+            &MAKE_TARGET,
             &config.project_path,
             config.verbose,
         )?;
@@ -133,20 +143,21 @@ impl CPlugin {
             });
         }
 
-        let output_path = CommandExecutor::copy_to_output(&wasm_files[0], &config.output_dir, "C")?;
+        let wasm_source = Path::new(&wasm_files[0]);
+        let wasm_output = config.artifact_path(&file_stem(wasm_source), C_TARGET, "wasm");
+        let output_path = CommandExecutor::copy_as(&wasm_files[0], &wasm_output, "C")?;
 
         // Look for JS files (for Emscripten)
         let js_files =
             PathResolver::find_files_with_extension(&config.project_path, "js").unwrap_or_default();
 
-        let js_output_path = if !js_files.is_empty() {
-            Some(CommandExecutor::copy_to_output(
-                &js_files[0],
-                &config.output_dir,
-                "C",
-            )?)
-        } else {
-            None
+        let js_output_path = match js_files.first() {
+            Some(js_file) => {
+                let js_output =
+                    config.artifact_path(&file_stem(Path::new(js_file)), C_TARGET, "js");
+                Some(self.copy_glue(js_file, &js_output, wasm_source, &wasm_output)?)
+            }
+            None => None,
         };
 
         let has_js_bindings = js_output_path.is_some();
@@ -156,6 +167,7 @@ impl CPlugin {
             js_path: js_output_path,
             additional_files: vec![],
             is_wasm_bindgen: has_js_bindings,
+            target: Some(C_TARGET.to_string()),
         })
     }
 
@@ -163,52 +175,20 @@ impl CPlugin {
     fn build_with_emscripten(&self, config: &BuildConfig) -> CompilationResult<BuildResult> {
         let entry_path = self.find_entry_file(&config.project_path)?;
 
-        PathResolver::ensure_output_directory(&config.output_dir).map_err(|_| {
+        let output_dir = config.artifact_dir(C_TARGET);
+        let output_dir = output_dir.to_string_lossy();
+        PathResolver::ensure_output_directory(&output_dir).map_err(|_| {
             CompilationError::OutputDirectoryCreationFailed {
-                path: config.output_dir.clone(),
+                path: output_dir.to_string(),
             }
         })?;
 
-        let output_name = entry_path
-            .file_stem()
-            .unwrap()
-            .to_string_lossy()
-            .to_string();
-        let wasm_output_file = Path::new(&config.output_dir).join(format!("{output_name}.wasm"));
-        let js_output_file = Path::new(&config.output_dir).join(format!("{output_name}.js"));
+        let (wasm_output_file, js_output_file) = Self::emscripten_outputs(config, &entry_path);
 
         println!("🔨 Building with Emscripten...");
 
-        // Collect all .c files in the project
-        let c_files = self.collect_c_files(&config.project_path)?;
-
-        // Build args for emcc
-        let mut args = vec![
-            "-o",
-            js_output_file.to_str().unwrap(),
-            "-s",
-            "WASM=1",
-            "-s",
-            "EXPORTED_RUNTIME_METHODS=['cwrap']",
-        ];
-
-        // Add optimization flags based on build config
-        match config.optimization_level {
-            OptimizationLevel::Debug => {
-                args.extend(&["-g", "-O0"]);
-            }
-            OptimizationLevel::Release => {
-                args.extend(&["-O3"]);
-            }
-            OptimizationLevel::Size => {
-                args.extend(&["-Os", "-s", "ELIMINATE_DUPLICATE_FUNCTIONS=1"]);
-            }
-        }
-
-        // Add all C files
-        for c_file in &c_files {
-            args.push(c_file);
-        }
+        let args = self.emcc_args(config, &js_output_file)?;
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
 
         // Run emcc
         let build_output =
@@ -236,9 +216,85 @@ impl CPlugin {
             js_path: Some(js_output_file.to_string_lossy().to_string()),
             additional_files: vec![],
             is_wasm_bindgen: true,
+            target: Some(C_TARGET.to_string()),
         })
     }
 
+    fn emscripten_outputs(config: &BuildConfig, entry_path: &Path) -> (PathBuf, PathBuf) {
+        let output_name = file_stem(entry_path);
+        (
+            config.artifact_path(&output_name, C_TARGET, "wasm"),
+            config.artifact_path(&output_name, C_TARGET, "js"),
+        )
+    }
+
+    /// Copy Emscripten's JS glue, pointing it at the module's new name when
+    /// the module was renamed
+    fn copy_glue(
+        &self,
+        js_file: &str,
+        destination: &Path,
+        wasm_source: &Path,
+        wasm_output: &Path,
+    ) -> CompilationResult<String> {
+        let (Some(old_name), Some(new_name)) = (wasm_source.file_name(), wasm_output.file_name())
+        else {
+            return CommandExecutor::copy_as(js_file, destination, "C");
+        };
+        if old_name == new_name {
+            return CommandExecutor::copy_as(js_file, destination, "C");
+        }
+
+        let (old_name, new_name) = (old_name.to_string_lossy(), new_name.to_string_lossy());
+        let glue = fs::read_to_string(js_file).map_err(|e| CompilationError::BuildFailed {
+            language: self.language_name().to_string(),
+            reason: format!("Failed to read {js_file}: {e}"),
+        })?;
+        let glue = ['"', '\''].iter().fold(glue, |glue, quote| {
+            glue.replace(
+                &format!("{quote}{old_name}{quote}"),
+                &format!("{quote}{new_name}{quote}"),
+            )
+        });
+        if let Some(dir) = destination.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        fs::write(destination, glue).map_err(|e| CompilationError::BuildFailed {
+            language: self.language_name().to_string(),
+            reason: format!("Failed to write {}: {e}", destination.display()),
+        })?;
+        Ok(destination.to_string_lossy().to_string())
+    }
+
+    fn emcc_args(
+        &self,
+        config: &BuildConfig,
+        js_output_file: &Path,
+    ) -> CompilationResult<Vec<String>> {
+        let mut args: Vec<String> = [
+            "-o",
+            js_output_file.to_str().unwrap(),
+            "-s",
+            "WASM=1",
+            "-s",
+            "EXPORTED_RUNTIME_METHODS=['cwrap']",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        // Add optimization flags based on build config
+        let optimization: &[&str] = match config.optimization_level {
+            OptimizationLevel::Debug => &["-g", "-O0"],
+            OptimizationLevel::Release => &["-O3"],
+            OptimizationLevel::Size => &["-Os", "-s", "ELIMINATE_DUPLICATE_FUNCTIONS=1"],
+        };
+        args.extend(optimization.iter().map(|a| a.to_string()));
+
+        // Add all C files
+        args.extend(self.collect_c_files(&config.project_path)?);
+        Ok(args)
+    }
+
     /// Collect all .c files in the project directory
     fn collect_c_files(&self, project_path: &str) -> CompilationResult<Vec<String>> {
         let mut c_files = Vec::new();
@@ -371,6 +427,33 @@ impl WasmBuilder for CPlugin {
         }
     }
 
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        let project = &config.project_path;
+        if self.has_makefile(project) {
+            let no_args: [&str; 0] = [];
+            return Ok(BuildPlan::default()
+                .run(PlannedCommand::new("make", &MAKE_TARGET, project, EMCC_ENV))
+                .run_when(
+                    PlannedCommand::new("make", &no_args, project, EMCC_ENV),
+                    "if make wasm fails",
+                )
+                .copy(
+                    format!("the first .wasm and .js found under {project}"),
+                    config
+                        .artifact_path("{name}", C_TARGET, "{wasm,js}")
+                        .to_string_lossy(),
+                ));
+        }
+
+        let entry_path = self.find_entry_file(project)?;
+        let (wasm_output_file, js_output_file) = Self::emscripten_outputs(config, &entry_path);
+        let args = self.emcc_args(config, &js_output_file)?;
+        Ok(BuildPlan::default()
+            .run(PlannedCommand::new("emcc", &args, project, EMCC_ENV))
+            .output(wasm_output_file.to_string_lossy())
+            .output(js_output_file.to_string_lossy()))
+    }
+
     fn can_handle_project(&self, project_path: &str) -> bool {
         if let Ok(entries) = std::fs::read_dir(project_path) {
             for entry in entries.flatten() {
@@ -408,6 +491,33 @@ impl WasmBuilder for CPlugin {
         Ok(())
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<CleanTarget> {
+        let project = Path::new(project_path);
+        let mut targets: Vec<CleanTarget> = CleanTarget::existing(
+            project.join("build"),
+            CleanKind::Artifact,
+            "build directory",
+        )
+        .into_iter()
+        .collect();
+        targets.extend(CleanTarget::files_with_extension(
+            project,
+            "o",
+            "object file",
+        ));
+        let wasm = CleanTarget::files_with_extension(project, "wasm", "Emscripten output");
+        // Only JavaScript that sits beside a module of the same name is glue
+        for module in &wasm {
+            targets.extend(CleanTarget::existing(
+                module.path.with_extension("js"),
+                CleanKind::Artifact,
+                "Emscripten JS glue",
+            ));
+        }
+        targets.extend(wasm);
+        targets
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         Box::new(self.clone())
     }
@@ -418,3 +528,9 @@ impl Default for CPlugin {
         Self::new()
     }
 }
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string())
+}
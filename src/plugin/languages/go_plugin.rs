@@ -1,9 +1,45 @@
-use crate::compiler::builder::{BuildConfig, BuildResult, WasmBuilder};
+use crate::compiler::builder::{
+    BuildConfig, BuildPlan, BuildResult, CleanKind, CleanTarget, PlannedCommand, WasmBuilder,
+};
 use crate::error::{CompilationError, CompilationResult, Result};
+use crate::plugin::panel::{PanelRequest, PanelResponse, PluginPanel};
 use crate::plugin::{Plugin, PluginCapabilities, PluginInfo, PluginType};
 use crate::utils::{CommandExecutor, PathResolver};
+use serde_json::json;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// TinyGo targets to try, in order
+const TINYGO_TARGETS: [&str; 2] = ["wasi", "wasm"];
+/// Environment TinyGo reads that changes the build
+const TINYGO_ENV: &[&str] = &["GOFLAGS", "GOROOT", "TINYGOROOT"];
+
+/// The console panel showing the Go toolchain; its script asks `env` below
+const TOOLCHAIN_PANEL: &str = r#"<div class="p-6 space-y-4 font-mono text-sm" data-go-toolchain>
+  <p data-status>Loading toolchain…</p>
+</div>
+<script>
+  const render = env => {
+    const row = (label, value) =>
+      `<tr><td class="pr-4 opacity-70">${label}</td><td>${value ?? '—'}</td></tr>`
+    const targets = env.targets
+      .map(t => row(`-target ${t.target}`, `GOOS=${t.goos} GOARCH=${t.goarch}`))
+      .join('')
+    const shims = env.wasm_exec.length
+      ? env.wasm_exec.map(p => `<li>${p}</li>`).join('')
+      : '<li>not found</li>'
+    panel.root.innerHTML = `
+      <table>${row('tinygo', env.tinygo)}${row('go', env.go)}${row('GOROOT', env.goroot)}${row('TINYGOROOT', env.tinygoroot)}${targets}</table>
+      <h3 class="font-bold">wasm_exec.js</h3><ul>${shims}</ul>`
+  }
+  panel
+    .api('env')
+    .then(res => res.json())
+    .then(render)
+    .catch(e => (panel.root.textContent = `Failed to read the Go toolchain: ${e}`))
+</script>
+"#;
 
 /// Go WebAssembly plugin (uses TinyGo)
 #[derive(Clone)]
@@ -56,6 +92,19 @@ impl GoPlugin {
         false
     }
 
+    fn tinygo_args<'a>(wasm_output: &'a str, target: &'a str) -> [&'a str; 6] {
+        ["build", "-o", wasm_output, "-target", target, "."]
+    }
+
+    /// Where the build for a TinyGo `target` writes the module
+    fn wasm_output(config: &BuildConfig, target: &str) -> String {
+        let pkg_name = Self::find_package_name(&config.project_path);
+        config
+            .artifact_path(&pkg_name, target, "wasm")
+            .to_string_lossy()
+            .to_string()
+    }
+
     fn find_package_name(project_path: &str) -> String {
         let go_mod = Path::new(project_path).join("go.mod");
         if let Ok(content) = fs::read_to_string(go_mod) {
@@ -70,6 +119,50 @@ impl GoPlugin {
         }
         "main".to_string()
     }
+
+    /// First line of stdout from running `tool`, if it ran
+    fn tool_output(tool: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(tool).args(args).output().ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().next()?.trim();
+        (output.status.success() && !line.is_empty()).then(|| line.to_string())
+    }
+
+    /// The wasm_exec.js shims that ship with the installed toolchains. The
+    /// `wasm` target needs the one matching the compiler that built the module.
+    fn wasm_exec_paths(goroot: Option<&str>, tinygoroot: Option<&str>) -> Vec<PathBuf> {
+        let tinygo = tinygoroot.map(|root| Path::new(root).join("targets").join("wasm_exec.js"));
+        // Go 1.24 moved it from misc/wasm to lib/wasm
+        let go = goroot.into_iter().flat_map(|root| {
+            ["lib", "misc"].map(|dir| Path::new(root).join(dir).join("wasm").join("wasm_exec.js"))
+        });
+        tinygo
+            .into_iter()
+            .chain(go)
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    fn toolchain_env() -> serde_json::Value {
+        let goroot = Self::tool_output("go", &["env", "GOROOT"]);
+        let tinygoroot = Self::tool_output("tinygo", &["env", "TINYGOROOT"]);
+        let targets: Vec<_> = TINYGO_TARGETS
+            .iter()
+            .map(|&target| {
+                let goos = if target == "wasi" { "wasip1" } else { "js" };
+                json!({ "target": target, "goos": goos, "goarch": "wasm" })
+            })
+            .collect();
+
+        json!({
+            "tinygo": Self::tool_output("tinygo", &["version"]),
+            "go": Self::tool_output("go", &["version"]),
+            "goroot": goroot,
+            "tinygoroot": tinygoroot,
+            "targets": targets,
+            "wasm_exec": Self::wasm_exec_paths(goroot.as_deref(), tinygoroot.as_deref()),
+        })
+    }
 }
 
 impl Plugin for GoPlugin {
@@ -84,6 +177,21 @@ impl Plugin for GoPlugin {
     fn get_builder(&self) -> Box<dyn WasmBuilder> {
         Box::new(GoPlugin::new())
     }
+
+    fn panels(&self, _project_path: &str) -> Vec<PluginPanel> {
+        vec![PluginPanel {
+            id: "toolchain".to_string(),
+            title: "Go/WASM".to_string(),
+            html: TOOLCHAIN_PANEL.to_string(),
+        }]
+    }
+
+    fn handle_panel_request(&self, request: &PanelRequest) -> Option<PanelResponse> {
+        match (request.method, request.path) {
+            ("GET", "env") => Some(PanelResponse::json(200, &Self::toolchain_env())),
+            _ => None,
+        }
+    }
 }
 
 impl WasmBuilder for GoPlugin {
@@ -139,30 +247,25 @@ impl WasmBuilder for GoPlugin {
             });
         }
 
-        PathResolver::ensure_output_directory(&config.output_dir).map_err(|_| {
-            CompilationError::OutputDirectoryCreationFailed {
-                path: config.output_dir.clone(),
-            }
-        })?;
-
-        let pkg_name = Self::find_package_name(&config.project_path);
-        let wasm_output = Path::new(&config.output_dir)
-            .join(format!("{pkg_name}.wasm"))
-            .to_string_lossy()
-            .to_string();
-
         if config.verbose {
             println!("🔨 Building Go project with TinyGo...");
         }
 
         // Try wasi target first, fall back to wasm
-        let targets = ["wasi", "wasm"];
         let mut last_error = String::new();
 
-        for target in &targets {
+        for target in TINYGO_TARGETS {
+            let output_dir = config.artifact_dir(target);
+            let output_dir = output_dir.to_string_lossy();
+            PathResolver::ensure_output_directory(&output_dir).map_err(|_| {
+                CompilationError::OutputDirectoryCreationFailed {
+                    path: output_dir.to_string(),
+                }
+            })?;
+            let wasm_output = Self::wasm_output(config, target);
             let output = CommandExecutor::execute_command(
                 "tinygo",
-                &["build", "-o", &wasm_output, "-target", target, "."],
+                &Self::tinygo_args(&wasm_output, target),
                 &config.project_path,
                 config.verbose,
             );
@@ -175,6 +278,7 @@ impl WasmBuilder for GoPlugin {
                             js_path: None,
                             additional_files: vec![],
                             is_wasm_bindgen: false,
+                            target: Some(target.to_string()),
                         });
                     }
                 }
@@ -193,6 +297,22 @@ impl WasmBuilder for GoPlugin {
         })
     }
 
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        let [first, fallback] = TINYGO_TARGETS;
+        let command = |target| {
+            PlannedCommand::new(
+                "tinygo",
+                &Self::tinygo_args(&Self::wasm_output(config, target), target),
+                &config.project_path,
+                TINYGO_ENV,
+            )
+        };
+        Ok(BuildPlan::default()
+            .run(command(first))
+            .run_when(command(fallback), &format!("if the {first} build fails"))
+            .output(Self::wasm_output(config, first)))
+    }
+
     fn clean(&self, project_path: &str) -> Result<()> {
         let artifacts = ["main.wasm", "*.wasm"];
         for artifact in &artifacts {
@@ -204,6 +324,20 @@ impl WasmBuilder for GoPlugin {
         Ok(())
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<CleanTarget> {
+        let mut targets =
+            CleanTarget::files_with_extension(Path::new(project_path), "wasm", "TinyGo output");
+        // TinyGo keeps compiled packages under the user cache directory
+        targets.extend(dirs::cache_dir().and_then(|cache| {
+            CleanTarget::existing(
+                cache.join("tinygo"),
+                CleanKind::Cache,
+                "TinyGo build cache (shared by all projects)",
+            )
+        }));
+        targets
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         Box::new(self.clone())
     }
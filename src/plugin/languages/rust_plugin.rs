@@ -1,9 +1,20 @@
-use crate::compiler::builder::{BuildConfig, BuildResult, WasmBuilder};
+use crate::compiler::builder::{
+    BuildConfig, BuildPlan, BuildResult, CleanKind, CleanTarget, PlannedCommand, WasmBuilder,
+};
 use crate::error::{CompilationError, CompilationResult, Result};
 use crate::plugin::{Plugin, PluginCapabilities, PluginInfo, PluginType};
 use crate::utils::{CommandExecutor, PathResolver};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+const RUST_TARGET: &str = "wasm32-unknown-unknown";
+const CARGO_ARGS: [&str; 4] = ["build", "--release", "--target", RUST_TARGET];
+/// Environment cargo and rustc read that changes the build
+const CARGO_ENV: &[&str] = &[
+    "RUSTFLAGS",
+    "CARGO_TARGET_DIR",
+    "CARGO_PROFILE_RELEASE_OPT_LEVEL",
+];
 
 /// Rust WebAssembly plugin (uses cargo + wasm-bindgen)
 #[derive(Clone)]
@@ -57,6 +68,35 @@ impl RustPlugin {
         None
     }
 
+    fn package_name(project_path: &str) -> String {
+        Self::read_package_name(project_path).unwrap_or_else(|| "output".to_string())
+    }
+
+    fn cargo_output(project_path: &str, pkg_name: &str) -> PathBuf {
+        Path::new(project_path)
+            .join("target")
+            .join(RUST_TARGET)
+            .join("release")
+            .join(format!("{pkg_name}.wasm"))
+    }
+
+    fn bindgen_args<'a>(
+        output_dir: &'a str,
+        out_name: &'a str,
+        wasm_file: &'a Path,
+    ) -> [&'a str; 8] {
+        [
+            "--out-dir",
+            output_dir,
+            "--out-name",
+            out_name,
+            "--target",
+            "web",
+            "--no-typescript",
+            wasm_file.to_str().unwrap_or_default(),
+        ]
+    }
+
     fn has_cdylib(project_path: &str) -> bool {
         let cargo_toml = Path::new(project_path).join("Cargo.toml");
         if let Ok(content) = fs::read_to_string(cargo_toml) {
@@ -134,9 +174,11 @@ impl WasmBuilder for RustPlugin {
             });
         }
 
-        PathResolver::ensure_output_directory(&config.output_dir).map_err(|_| {
+        let output_dir = config.artifact_dir(RUST_TARGET);
+        let output_dir = output_dir.to_string_lossy();
+        PathResolver::ensure_output_directory(&output_dir).map_err(|_| {
             CompilationError::OutputDirectoryCreationFailed {
-                path: config.output_dir.clone(),
+                path: output_dir.to_string(),
             }
         })?;
 
@@ -144,11 +186,9 @@ impl WasmBuilder for RustPlugin {
             println!("🔨 Building Rust project for wasm32-unknown-unknown...");
         }
 
-        let cargo_args = ["build", "--release", "--target", "wasm32-unknown-unknown"];
-
         let build_output = CommandExecutor::execute_command(
             "cargo",
-            &cargo_args,
+            &CARGO_ARGS,
             &config.project_path,
             config.verbose,
         )?;
@@ -163,13 +203,9 @@ impl WasmBuilder for RustPlugin {
             });
         }
 
-        let pkg_name =
-            Self::read_package_name(&config.project_path).unwrap_or_else(|| "output".to_string());
-        let wasm_file = Path::new(&config.project_path)
-            .join("target")
-            .join("wasm32-unknown-unknown")
-            .join("release")
-            .join(format!("{pkg_name}.wasm"));
+        let pkg_name = Self::package_name(&config.project_path);
+        let wasm_file = Self::cargo_output(&config.project_path, &pkg_name);
+        let stem = config.artifact_stem(&pkg_name, RUST_TARGET);
 
         if !wasm_file.exists() {
             return Err(CompilationError::BuildFailed {
@@ -187,21 +223,14 @@ impl WasmBuilder for RustPlugin {
             }
 
             // Run wasm-bindgen from wasmrun's own working directory, not the
-            // project directory: both `wasm_file` and `config.output_dir` are
+            // project directory: both `wasm_file` and the output directory are
             // relative to wasmrun's cwd (as are the .exists() checks above and
             // below). Setting the cwd to `config.project_path` would re-prefix
             // those paths with the project path, so wasm-bindgen would look for
             // `<project>/<project>/target/...` and fail with "No such file".
             let bindgen_output = CommandExecutor::execute_command(
                 "wasm-bindgen",
-                &[
-                    "--out-dir",
-                    &config.output_dir,
-                    "--target",
-                    "web",
-                    "--no-typescript",
-                    wasm_file.to_str().unwrap_or_default(),
-                ],
+                &Self::bindgen_args(&output_dir, &stem, &wasm_file),
                 ".",
                 config.verbose,
             )?;
@@ -217,8 +246,8 @@ impl WasmBuilder for RustPlugin {
             }
 
             // Find the generated _bg.wasm file
-            let bg_wasm = Path::new(&config.output_dir).join(format!("{pkg_name}_bg.wasm"));
-            let js_file = Path::new(&config.output_dir).join(format!("{pkg_name}.js"));
+            let bg_wasm = Path::new(output_dir.as_ref()).join(format!("{stem}_bg.wasm"));
+            let js_file = Path::new(output_dir.as_ref()).join(format!("{stem}.js"));
 
             if bg_wasm.exists() {
                 return Ok(BuildResult {
@@ -230,12 +259,13 @@ impl WasmBuilder for RustPlugin {
                     },
                     additional_files: vec![],
                     is_wasm_bindgen: true,
+                    target: Some(RUST_TARGET.to_string()),
                 });
             }
         }
 
         // Copy plain wasm to output dir
-        let output_wasm = Path::new(&config.output_dir).join(format!("{pkg_name}.wasm"));
+        let output_wasm = config.artifact_path(&pkg_name, RUST_TARGET, "wasm");
         fs::copy(&wasm_file, &output_wasm).map_err(|e| CompilationError::BuildFailed {
             language: self.language_name().to_string(),
             reason: format!("Failed to copy wasm file: {e}"),
@@ -246,9 +276,44 @@ impl WasmBuilder for RustPlugin {
             js_path: None,
             additional_files: vec![],
             is_wasm_bindgen: false,
+            target: Some(RUST_TARGET.to_string()),
         })
     }
 
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        let pkg_name = Self::package_name(&config.project_path);
+        let wasm_file = Self::cargo_output(&config.project_path, &pkg_name);
+        let mut plan = BuildPlan::default().run(PlannedCommand::new(
+            "cargo",
+            &CARGO_ARGS,
+            &config.project_path,
+            CARGO_ENV,
+        ));
+
+        if Self::has_cdylib(&config.project_path) {
+            if CommandExecutor::is_tool_installed("wasm-bindgen") {
+                let output = config.artifact_dir(RUST_TARGET);
+                let stem = config.artifact_stem(&pkg_name, RUST_TARGET);
+                return Ok(plan
+                    .run(PlannedCommand::new(
+                        "wasm-bindgen",
+                        &Self::bindgen_args(&output.to_string_lossy(), &stem, &wasm_file),
+                        ".",
+                        &[],
+                    ))
+                    .output(output.join(format!("{stem}_bg.wasm")).to_string_lossy())
+                    .output(output.join(format!("{stem}.js")).to_string_lossy()));
+            }
+            plan = plan
+                .note("cdylib crate, but wasm-bindgen isn't installed; copying the plain module");
+        }
+
+        let output_wasm = config.artifact_path(&pkg_name, RUST_TARGET, "wasm");
+        Ok(plan
+            .copy(wasm_file.to_string_lossy(), output_wasm.to_string_lossy())
+            .output(output_wasm.to_string_lossy()))
+    }
+
     fn clean(&self, project_path: &str) -> Result<()> {
         let target_dir = Path::new(project_path).join("target");
         if target_dir.exists() {
@@ -257,6 +322,21 @@ impl WasmBuilder for RustPlugin {
         Ok(())
     }
 
+    fn clean_targets(&self, project_path: &str) -> Vec<CleanTarget> {
+        let project = Path::new(project_path);
+        [
+            CleanTarget::existing(
+                project.join("target"),
+                CleanKind::Artifact,
+                "cargo target directory",
+            ),
+            CleanTarget::existing(project.join("pkg"), CleanKind::Artifact, "wasm-pack output"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
     fn clone_box(&self) -> Box<dyn WasmBuilder> {
         Box::new(self.clone())
     }
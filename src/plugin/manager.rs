@@ -1,10 +1,13 @@
 //! Plugin management and registry
 
 use crate::compiler::builder::WasmBuilder;
+use crate::compiler::{detect_project_language, ProjectLanguage};
 use crate::config::{ExternalPluginEntry, WasmrunConfig};
 use crate::error::{Result, WasmrunError};
 use crate::plugin::builtin::load_all_builtin_plugins;
+#[cfg(feature = "external-plugins")]
 use crate::plugin::external::ExternalPluginLoader;
+#[cfg(feature = "external-plugins")]
 use crate::plugin::installer::PluginInstaller;
 use crate::plugin::registry::PluginRegistry;
 use crate::plugin::{Plugin, PluginCapabilities, PluginInfo, PluginSource};
@@ -71,7 +74,7 @@ impl PluginManager {
 
         for (name, entry) in &self.config.external_plugins {
             if entry.enabled {
-                match ExternalPluginLoader::load(entry) {
+                match load_external_plugin(entry) {
                     Ok(plugin) => {
                         debug_println!("Loaded external plugin: {name}");
                         self.external_plugins.insert(name.clone(), plugin);
@@ -123,6 +126,39 @@ impl PluginManager {
         None
     }
 
+    /// Every plugin that claims `project_path`, external ones first
+    pub fn plugins_for_project(&self, project_path: &str) -> Vec<&dyn Plugin> {
+        self.external_plugins
+            .values()
+            .map(|plugin| plugin.as_ref())
+            .chain(self.builtin_plugins.iter().map(|plugin| plugin.as_ref()))
+            .filter(|plugin| plugin.can_handle_project(project_path))
+            .collect()
+    }
+
+    /// The plugin to build `project_path` with: the one for `language` when
+    /// the user chose one, else the one for the best-ranked detected
+    /// language, else the first that claims the project
+    pub fn select_plugin(&self, project_path: &str, language: Option<&str>) -> Option<&dyn Plugin> {
+        if let Some(language) = language {
+            return self.find_plugin_for_language(language).or_else(|| {
+                ProjectLanguage::from_name(language)
+                    .and_then(|known| self.find_plugin_for_language(known.name()))
+            });
+        }
+
+        let detected = detect_project_language(project_path);
+        if detected != ProjectLanguage::Unknown {
+            if let Some(plugin) = self
+                .find_plugin_for_language(detected.name())
+                .filter(|plugin| plugin.can_handle_project(project_path))
+            {
+                return Some(plugin);
+            }
+        }
+        self.find_plugin_for_project(project_path)
+    }
+
     pub fn find_plugin_for_language(&self, language: &str) -> Option<&dyn Plugin> {
         // Check external plugins first using the common utility
         for plugin in self.external_plugins.values() {
@@ -193,11 +229,6 @@ impl PluginManager {
         None
     }
 
-    pub fn get_plugin_by_language(&self, language: &str) -> Option<&dyn Plugin> {
-        // Use the common utility function to find plugins that support the language
-        self.find_plugin_for_language(language)
-    }
-
     #[allow(dead_code)]
     pub fn get_available_languages(&self) -> Vec<String> {
         let mut languages = Vec::new();
@@ -248,201 +279,6 @@ impl PluginManager {
             .unwrap_or(false)
     }
 
-    pub fn update_plugin(&mut self, plugin_name: &str) -> Result<()> {
-        if plugin_name == "all" {
-            self.update_all_external_plugins()
-        } else {
-            self.update_single_plugin(plugin_name)
-        }
-    }
-
-    fn update_single_plugin(&mut self, plugin_name: &str) -> Result<()> {
-        println!("🔄 Updating plugin: {plugin_name}");
-
-        // Check if plugin exists
-        if !self.config.external_plugins.contains_key(plugin_name) {
-            return Err(WasmrunError::from(format!(
-                "Plugin '{plugin_name}' is not installed"
-            )));
-        }
-
-        // Get current version
-        let current_version = self.get_current_plugin_version(plugin_name);
-        println!("📦 Current version: {current_version}");
-
-        // Check for latest version
-        let latest_version = self.get_latest_plugin_version(plugin_name)?;
-        println!("🆕 Latest version: {latest_version}");
-
-        // Compare versions
-        if current_version == latest_version {
-            println!("✅ Plugin '{plugin_name}' is already up to date (v{current_version})");
-            return Ok(());
-        }
-
-        // Perform the update
-        println!("⬆️  Updating from v{current_version} to v{latest_version}");
-
-        // For external plugins, we need to reinstall
-        self.reinstall_external_plugin(plugin_name, &latest_version)?;
-
-        println!("✅ Plugin '{plugin_name}' updated successfully to v{latest_version}");
-        Ok(())
-    }
-
-    fn update_all_external_plugins(&mut self) -> Result<()> {
-        println!("🔄 Updating all external plugins...");
-
-        let plugin_names: Vec<String> = self.config.external_plugins.keys().cloned().collect();
-        let mut updated_count = 0;
-        let mut failed_count = 0;
-
-        for plugin_name in plugin_names {
-            match self.update_single_plugin(&plugin_name) {
-                Ok(()) => {
-                    updated_count += 1;
-                }
-                Err(e) => {
-                    eprintln!("❌ Failed to update {plugin_name}: {e}");
-                    failed_count += 1;
-                }
-            }
-        }
-
-        println!("📊 Update summary: {updated_count} updated, {failed_count} failed");
-        Ok(())
-    }
-
-    fn get_current_plugin_version(&self, plugin_name: &str) -> String {
-        // First try to get version from config
-        if let Some(entry) = self.config.external_plugins.get(plugin_name) {
-            if let PluginSource::CratesIo { version, .. } = &entry.source {
-                if version != "unknown" && !version.is_empty() {
-                    return version.clone();
-                }
-            }
-
-            // Also check the info version
-            if !entry.info.version.is_empty() && entry.info.version != "unknown" {
-                return entry.info.version.clone();
-            }
-        }
-
-        // Try to detect from plugin directory files
-        self.detect_plugin_version_from_directory(plugin_name)
-            .unwrap_or_else(|| "unknown".to_string())
-    }
-
-    fn get_latest_plugin_version(&self, plugin_name: &str) -> Result<String> {
-        // Try to get version from crates.io for any plugin
-        self.get_latest_crates_io_version(plugin_name)
-    }
-
-    fn get_latest_crates_io_version(&self, crate_name: &str) -> Result<String> {
-        // Use cargo search to find the latest version
-        let output = std::process::Command::new("cargo")
-            .args(["search", crate_name, "--limit", "1"])
-            .output()
-            .map_err(|e| WasmrunError::from(format!("Failed to run cargo search: {e}")))?;
-
-        if !output.status.success() {
-            return Err(WasmrunError::from(format!(
-                "cargo search failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            )));
-        }
-
-        let search_output = String::from_utf8_lossy(&output.stdout);
-        if let Some(line) = search_output.lines().next() {
-            // Parse output like: wasmrust = "0.3.0"    # Rust to WebAssembly compiler
-            if let Some(start) = line.find('"') {
-                if let Some(end) = line[start + 1..].find('"') {
-                    let version = &line[start + 1..start + 1 + end];
-                    return Ok(version.to_string());
-                }
-            }
-        }
-
-        Err(WasmrunError::from(format!(
-            "Could not parse version from cargo search output for {crate_name}"
-        )))
-    }
-
-    fn detect_plugin_version_from_directory(&self, plugin_name: &str) -> Option<String> {
-        // Try to get plugin directory
-        let plugin_dir = match PluginUtils::get_plugin_directory(plugin_name) {
-            Ok(dir) => dir,
-            Err(_) => return None,
-        };
-
-        // Try to read version from Cargo.toml
-        let cargo_toml_path = plugin_dir.join("Cargo.toml");
-        if cargo_toml_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&cargo_toml_path) {
-                // Simple TOML parsing for version
-                for line in content.lines() {
-                    if line.trim().starts_with("version") && line.contains('=') {
-                        if let Some(start) = line.find('"') {
-                            if let Some(end) = line[start + 1..].find('"') {
-                                let version = &line[start + 1..start + 1 + end];
-                                return Some(version.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // Try to read from metadata file
-        let metadata_path = plugin_dir.join(".wasmrun_metadata");
-        if metadata_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&metadata_path) {
-                for line in content.lines() {
-                    if line.trim().starts_with("version") && line.contains('=') {
-                        if let Some(start) = line.find('"') {
-                            if let Some(end) = line[start + 1..].find('"') {
-                                let version = &line[start + 1..start + 1 + end];
-                                return Some(version.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        None
-    }
-
-    fn reinstall_external_plugin(&mut self, plugin_name: &str, new_version: &str) -> Result<()> {
-        // Remove current plugin from memory
-        self.external_plugins.remove(plugin_name);
-
-        // Remove plugin directory
-        PluginInstaller::remove_plugin_directory(plugin_name)?;
-
-        // Install the plugin again (this will get the latest version)
-        let _result = PluginInstaller::install_external_plugin(plugin_name)?;
-
-        // 🔧 FIX: Update the actual plugin metadata files with the new version
-        PluginInstaller::update_plugin_metadata(plugin_name, new_version)?;
-
-        // Update the config with the new version
-        if let Some(entry) = self.config.external_plugins.get_mut(plugin_name) {
-            entry.info.version = new_version.to_string();
-            if let PluginSource::CratesIo { version, .. } = &mut entry.source {
-                *version = new_version.to_string();
-            }
-        }
-
-        // Save config
-        self.config.save()?;
-
-        // Reload the plugin
-        self.reload_single_plugin(plugin_name)?;
-
-        Ok(())
-    }
-
     pub fn enable_plugin(&mut self, plugin_name: &str) -> Result<()> {
         if let Some(entry) = self.config.external_plugins.get_mut(plugin_name) {
             entry.enabled = true;
@@ -470,20 +306,6 @@ impl PluginManager {
         Ok(())
     }
 
-    pub fn uninstall_plugin(&mut self, plugin_name: &str) -> Result<()> {
-        if self.external_plugins.contains_key(plugin_name) {
-            self.external_plugins.remove(plugin_name);
-        }
-
-        if self.config.external_plugins.contains_key(plugin_name) {
-            self.config.external_plugins.remove(plugin_name);
-            self.config.save()?;
-        }
-
-        self.update_stats();
-        Ok(())
-    }
-
     #[allow(dead_code)]
     pub fn get_plugin_capabilities(&self, plugin_name: &str) -> Option<&PluginCapabilities> {
         if let Some(info) = self.get_plugin_info(plugin_name) {
@@ -584,7 +406,7 @@ impl PluginManager {
 
         if let Some(entry) = self.config.external_plugins.get(plugin_name) {
             if entry.enabled {
-                match crate::plugin::external::ExternalPluginLoader::load(entry) {
+                match load_external_plugin(entry) {
                     Ok(plugin) => {
                         println!(
                             "✅ Successfully loaded plugin: {} v{}",
@@ -612,7 +434,7 @@ impl PluginManager {
 
         for (name, entry) in &self.config.external_plugins {
             if entry.enabled {
-                match ExternalPluginLoader::load(entry) {
+                match load_external_plugin(entry) {
                     Ok(plugin) => {
                         self.external_plugins.insert(name.clone(), plugin);
                     }
@@ -688,41 +510,292 @@ impl PluginManager {
         }
     }
 
-    // Detect the actual version of an installed external plugin
-    fn detect_plugin_version(&self, plugin_name: &str) -> String {
-        if let Ok(output) = std::process::Command::new(plugin_name)
-            .arg("--version")
-            .output()
+    pub fn is_plugin_installed(&self, plugin_name: &str) -> bool {
+        // Check if it's a builtin plugin
+        if self
+            .builtin_plugins
+            .iter()
+            .any(|p| p.info().name == plugin_name)
         {
-            if output.status.success() {
-                let version_output = String::from_utf8_lossy(&output.stdout);
-                if let Some(version_line) = version_output.lines().next() {
-                    let parts: Vec<&str> = version_line.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        return parts[1].to_string();
-                    }
-                    if let Ok(re) = regex::Regex::new(r"(\d+\.\d+\.\d+)") {
-                        if let Some(cap) = re.captures(&version_output) {
-                            if let Some(version) = cap.get(1) {
-                                return version.as_str().to_string();
-                            }
-                        }
-                    }
-                }
-            }
+            return true;
         }
 
-        if let Ok(output) = std::process::Command::new("cargo")
-            .args(["search", plugin_name, "--limit", "1"])
-            .output()
-        {
-            if output.status.success() {
-                let search_output = String::from_utf8_lossy(&output.stdout);
-                if let Some(line) = search_output.lines().next() {
-                    if let Ok(re) = regex::Regex::new(r#"=\s*"([^"]+)""#) {
-                        if let Some(cap) = re.captures(line) {
-                            if let Some(version) = cap.get(1) {
-                                return version.as_str().to_string();
+        // Check if it's an external plugin that's actually loaded and functional
+        if self.external_plugins.contains_key(plugin_name) {
+            return true;
+        }
+
+        false
+    }
+
+    #[allow(dead_code)] // TODO: Report plugin health in `wasmrun plugin info`
+    pub fn check_plugin_health(&self, plugin_name: &str) -> Result<PluginHealthStatus> {
+        if !self.is_plugin_installed(plugin_name) {
+            return Ok(PluginHealthStatus::NotFound);
+        }
+
+        // Use registry for dependency checking
+        let missing_deps = PluginRegistry::check_plugin_dependencies(plugin_name);
+
+        if !missing_deps.is_empty() {
+            return Ok(PluginHealthStatus::MissingDependencies(missing_deps));
+        }
+
+        if let Some(entry) = self.config.external_plugins.get(plugin_name) {
+            match load_external_plugin(entry) {
+                Ok(_) => Ok(PluginHealthStatus::Healthy),
+                Err(e) => Ok(PluginHealthStatus::LoadError(e.to_string())),
+            }
+        } else {
+            Ok(PluginHealthStatus::Healthy)
+        }
+    }
+
+    #[allow(dead_code)] // TODO: Check plugin tool dependencies on PATH before loading
+    fn is_tool_available_in_path(&self, tool: &str) -> bool {
+        let which_cmd = if cfg!(target_os = "windows") {
+            "where"
+        } else {
+            "which"
+        };
+
+        std::process::Command::new(which_cmd)
+            .arg(tool)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
+
+/// Installing, updating and removing plugins from crates.io
+#[cfg(feature = "external-plugins")]
+impl PluginManager {
+    pub fn update_plugin(&mut self, plugin_name: &str) -> Result<()> {
+        if plugin_name == "all" {
+            self.update_all_external_plugins()
+        } else {
+            self.update_single_plugin(plugin_name)
+        }
+    }
+
+    fn update_single_plugin(&mut self, plugin_name: &str) -> Result<()> {
+        println!("🔄 Updating plugin: {plugin_name}");
+
+        // Check if plugin exists
+        if !self.config.external_plugins.contains_key(plugin_name) {
+            return Err(WasmrunError::from(format!(
+                "Plugin '{plugin_name}' is not installed"
+            )));
+        }
+
+        // Get current version
+        let current_version = self.get_current_plugin_version(plugin_name);
+        println!("📦 Current version: {current_version}");
+
+        // Check for latest version
+        let latest_version = self.get_latest_plugin_version(plugin_name)?;
+        println!("🆕 Latest version: {latest_version}");
+
+        // Compare versions
+        if current_version == latest_version {
+            println!("✅ Plugin '{plugin_name}' is already up to date (v{current_version})");
+            return Ok(());
+        }
+
+        // Perform the update
+        println!("⬆️  Updating from v{current_version} to v{latest_version}");
+
+        // For external plugins, we need to reinstall
+        self.reinstall_external_plugin(plugin_name, &latest_version)?;
+
+        println!("✅ Plugin '{plugin_name}' updated successfully to v{latest_version}");
+        Ok(())
+    }
+
+    fn update_all_external_plugins(&mut self) -> Result<()> {
+        println!("🔄 Updating all external plugins...");
+
+        let plugin_names: Vec<String> = self.config.external_plugins.keys().cloned().collect();
+        let mut updated_count = 0;
+        let mut failed_count = 0;
+
+        for plugin_name in plugin_names {
+            match self.update_single_plugin(&plugin_name) {
+                Ok(()) => {
+                    updated_count += 1;
+                }
+                Err(e) => {
+                    eprintln!("❌ Failed to update {plugin_name}: {e}");
+                    failed_count += 1;
+                }
+            }
+        }
+
+        println!("📊 Update summary: {updated_count} updated, {failed_count} failed");
+        Ok(())
+    }
+
+    fn get_current_plugin_version(&self, plugin_name: &str) -> String {
+        // First try to get version from config
+        if let Some(entry) = self.config.external_plugins.get(plugin_name) {
+            if let PluginSource::CratesIo { version, .. } = &entry.source {
+                if version != "unknown" && !version.is_empty() {
+                    return version.clone();
+                }
+            }
+
+            // Also check the info version
+            if !entry.info.version.is_empty() && entry.info.version != "unknown" {
+                return entry.info.version.clone();
+            }
+        }
+
+        // Try to detect from plugin directory files
+        self.detect_plugin_version_from_directory(plugin_name)
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    fn get_latest_plugin_version(&self, plugin_name: &str) -> Result<String> {
+        // Try to get version from crates.io for any plugin
+        self.get_latest_crates_io_version(plugin_name)
+    }
+
+    fn get_latest_crates_io_version(&self, crate_name: &str) -> Result<String> {
+        // Use cargo search to find the latest version
+        let output = std::process::Command::new("cargo")
+            .args(["search", crate_name, "--limit", "1"])
+            .output()
+            .map_err(|e| WasmrunError::from(format!("Failed to run cargo search: {e}")))?;
+
+        if !output.status.success() {
+            return Err(WasmrunError::from(format!(
+                "cargo search failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let search_output = String::from_utf8_lossy(&output.stdout);
+        if let Some(line) = search_output.lines().next() {
+            // Parse output like: wasmrust = "0.3.0"    # Rust to WebAssembly compiler
+            if let Some(start) = line.find('"') {
+                if let Some(end) = line[start + 1..].find('"') {
+                    let version = &line[start + 1..start + 1 + end];
+                    return Ok(version.to_string());
+                }
+            }
+        }
+
+        Err(WasmrunError::from(format!(
+            "Could not parse version from cargo search output for {crate_name}"
+        )))
+    }
+
+    fn detect_plugin_version_from_directory(&self, plugin_name: &str) -> Option<String> {
+        // Try to get plugin directory
+        let plugin_dir = match PluginUtils::get_plugin_directory(plugin_name) {
+            Ok(dir) => dir,
+            Err(_) => return None,
+        };
+
+        // Try to read version from Cargo.toml
+        let cargo_toml_path = plugin_dir.join("Cargo.toml");
+        if cargo_toml_path.exists() {
+            if let Ok(content) = std::fs::read_to_string(&cargo_toml_path) {
+                // Simple TOML parsing for version
+                for line in content.lines() {
+                    if line.trim().starts_with("version") && line.contains('=') {
+                        if let Some(start) = line.find('"') {
+                            if let Some(end) = line[start + 1..].find('"') {
+                                let version = &line[start + 1..start + 1 + end];
+                                return Some(version.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Try to read from metadata file
+        let metadata_path = plugin_dir.join(".wasmrun_metadata");
+        if metadata_path.exists() {
+            if let Ok(content) = std::fs::read_to_string(&metadata_path) {
+                for line in content.lines() {
+                    if line.trim().starts_with("version") && line.contains('=') {
+                        if let Some(start) = line.find('"') {
+                            if let Some(end) = line[start + 1..].find('"') {
+                                let version = &line[start + 1..start + 1 + end];
+                                return Some(version.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn reinstall_external_plugin(&mut self, plugin_name: &str, new_version: &str) -> Result<()> {
+        // Remove current plugin from memory
+        self.external_plugins.remove(plugin_name);
+
+        // Remove plugin directory
+        PluginInstaller::remove_plugin_directory(plugin_name)?;
+
+        // Install the plugin again (this will get the latest version)
+        let _result = PluginInstaller::install_external_plugin(plugin_name)?;
+
+        // 🔧 FIX: Update the actual plugin metadata files with the new version
+        PluginInstaller::update_plugin_metadata(plugin_name, new_version)?;
+
+        // Update the config with the new version
+        if let Some(entry) = self.config.external_plugins.get_mut(plugin_name) {
+            entry.info.version = new_version.to_string();
+            if let PluginSource::CratesIo { version, .. } = &mut entry.source {
+                *version = new_version.to_string();
+            }
+        }
+
+        // Save config
+        self.config.save()?;
+
+        // Reload the plugin
+        self.reload_single_plugin(plugin_name)?;
+
+        Ok(())
+    }
+
+    pub fn uninstall_plugin(&mut self, plugin_name: &str) -> Result<()> {
+        if self.external_plugins.contains_key(plugin_name) {
+            self.external_plugins.remove(plugin_name);
+        }
+
+        if self.config.external_plugins.contains_key(plugin_name) {
+            self.config.external_plugins.remove(plugin_name);
+            self.config.save()?;
+        }
+
+        self.update_stats();
+        Ok(())
+    }
+
+    // Detect the actual version of an installed external plugin
+    fn detect_plugin_version(&self, plugin_name: &str) -> String {
+        if let Ok(output) = std::process::Command::new(plugin_name)
+            .arg("--version")
+            .output()
+        {
+            if output.status.success() {
+                let version_output = String::from_utf8_lossy(&output.stdout);
+                if let Some(version_line) = version_output.lines().next() {
+                    let parts: Vec<&str> = version_line.split_whitespace().collect();
+                    if parts.len() >= 2 {
+                        return parts[1].to_string();
+                    }
+                    if let Ok(re) = regex::Regex::new(r"(\d+\.\d+\.\d+)") {
+                        if let Some(cap) = re.captures(&version_output) {
+                            if let Some(version) = cap.get(1) {
+                                return version.as_str().to_string();
                             }
                         }
                     }
@@ -731,7 +804,7 @@ impl PluginManager {
         }
 
         if let Ok(output) = std::process::Command::new("cargo")
-            .args(["install", "--list"])
+            .args(["search", plugin_name, "--limit", "1"])
             .output()
         {
             if output.status.success() {
@@ -748,25 +821,25 @@ impl PluginManager {
             }
         }
 
-        "unknown".to_string()
-    }
-
-    pub fn is_plugin_installed(&self, plugin_name: &str) -> bool {
-        // Check if it's a builtin plugin
-        if self
-            .builtin_plugins
-            .iter()
-            .any(|p| p.info().name == plugin_name)
+        if let Ok(output) = std::process::Command::new("cargo")
+            .args(["install", "--list"])
+            .output()
         {
-            return true;
-        }
-
-        // Check if it's an external plugin that's actually loaded and functional
-        if self.external_plugins.contains_key(plugin_name) {
-            return true;
+            if output.status.success() {
+                let search_output = String::from_utf8_lossy(&output.stdout);
+                if let Some(line) = search_output.lines().next() {
+                    if let Ok(re) = regex::Regex::new(r#"=\s*"([^"]+)""#) {
+                        if let Some(cap) = re.captures(line) {
+                            if let Some(version) = cap.get(1) {
+                                return version.as_str().to_string();
+                            }
+                        }
+                    }
+                }
+            }
         }
 
-        false
+        "unknown".to_string()
     }
 
     pub fn register_installed_plugin(&mut self, plugin_name: &str) -> Result<()> {
@@ -865,49 +938,11 @@ impl PluginManager {
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn check_plugin_health(&self, plugin_name: &str) -> Result<PluginHealthStatus> {
-        if !self.is_plugin_installed(plugin_name) {
-            return Ok(PluginHealthStatus::NotFound);
-        }
-
-        // Use registry for dependency checking
-        let missing_deps = PluginRegistry::check_plugin_dependencies(plugin_name);
-
-        if !missing_deps.is_empty() {
-            return Ok(PluginHealthStatus::MissingDependencies(missing_deps));
-        }
-
-        if let Some(entry) = self.config.external_plugins.get(plugin_name) {
-            match ExternalPluginLoader::load(entry) {
-                Ok(_) => Ok(PluginHealthStatus::Healthy),
-                Err(e) => Ok(PluginHealthStatus::LoadError(e.to_string())),
-            }
-        } else {
-            Ok(PluginHealthStatus::Healthy)
-        }
-    }
-
     fn get_plugin_directory(&self, plugin_name: &str) -> Result<std::path::PathBuf> {
         let config_dir = crate::config::WasmrunConfig::config_dir()?;
         Ok(config_dir.join("plugins").join(plugin_name))
     }
 
-    #[allow(dead_code)]
-    fn is_tool_available_in_path(&self, tool: &str) -> bool {
-        let which_cmd = if cfg!(target_os = "windows") {
-            "where"
-        } else {
-            "which"
-        };
-
-        std::process::Command::new(which_cmd)
-            .arg(tool)
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
-    }
-
     /// Install plugin
     pub fn install_plugin(&mut self, plugin_name: &str) -> Result<()> {
         // Check if already installed
@@ -932,6 +967,21 @@ impl PluginManager {
     }
 }
 
+/// Load an installed plugin from its config entry
+#[cfg(feature = "external-plugins")]
+fn load_external_plugin(entry: &ExternalPluginEntry) -> Result<Box<dyn Plugin>> {
+    ExternalPluginLoader::load(entry)
+}
+
+/// Builds without `external-plugins` only run the built-in plugins
+#[cfg(not(feature = "external-plugins"))]
+fn load_external_plugin(entry: &ExternalPluginEntry) -> Result<Box<dyn Plugin>> {
+    Err(WasmrunError::feature_disabled(
+        format!("Plugin '{}'", entry.info.name),
+        "external-plugins",
+    ))
+}
+
 #[derive(Debug, Clone)]
 pub enum PluginHealthStatus {
     Healthy,
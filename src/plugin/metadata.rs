@@ -16,6 +16,9 @@ pub struct PluginMetadata {
     pub dependencies: MetadataDependencies,
     pub exports: Option<MetadataExports>,
     pub frameworks: Option<MetadataFrameworks>,
+    /// Console panels, from `[[package.metadata.wasm_plugin.panels]]`
+    #[serde(default)]
+    pub panels: Vec<MetadataPanel>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +54,17 @@ pub struct MetadataFrameworks {
     pub auto_detect: bool,
 }
 
+/// A console panel an external plugin ships. Its routes under
+/// `/api/plugin/<name>/` run `<plugin> panel-api <METHOD> <PATH> -p
+/// <project>` with the request body on stdin and answer with its stdout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataPanel {
+    pub id: String,
+    pub title: String,
+    /// HTML fragment, relative to the plugin's directory
+    pub html: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CargoToml {
     package: CargoPackage,
@@ -242,6 +256,7 @@ impl PluginMetadata {
             },
             exports: Some(Self::create_default_exports(&name)),
             frameworks: None,
+            panels: vec![],
         }
     }
 
@@ -419,6 +434,7 @@ fn parse_crates_io_metadata_response(crate_name: &str, response: &str) -> Result
         },
         exports: None,
         frameworks: None,
+        panels: vec![],
     })
 }
 
@@ -1,16 +1,22 @@
 //! Plugin system for Wasmrun
 
 use crate::compiler::builder::WasmBuilder;
+use panel::{PanelRequest, PanelResponse, PluginPanel};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+#[cfg(feature = "external-plugins")]
 pub mod bridge;
 pub mod builtin;
+#[cfg(feature = "external-plugins")]
 pub mod external;
+#[cfg(feature = "external-plugins")]
 pub mod installer;
 pub mod languages;
 pub mod manager;
+#[cfg_attr(not(feature = "external-plugins"), allow(dead_code))]
 pub mod metadata;
+pub mod panel;
 pub mod registry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +30,16 @@ pub trait Plugin: Send + Sync {
     fn info(&self) -> &PluginInfo;
     fn can_handle_project(&self, project_path: &str) -> bool;
     fn get_builder(&self) -> Box<dyn WasmBuilder>;
+
+    /// Panels to add to the console page while `project_path` is served
+    fn panels(&self, _project_path: &str) -> Vec<PluginPanel> {
+        Vec::new()
+    }
+
+    /// Answer a panel's request to `/api/plugin/<name>/...`; `None` is a 404
+    fn handle_panel_request(&self, _request: &PanelRequest) -> Option<PanelResponse> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,58 @@
+//! Browser panels contributed by plugins
+//!
+//! A plugin can add tabs to the console page with [`Plugin::panels`]: each
+//! panel is an HTML fragment, and its scripts get a `panel` object with the
+//! panel's root element and `panel.api(path, init)`, a `fetch` against the
+//! plugin's own routes under `/api/plugin/<name>/`. Those requests reach
+//! [`Plugin::handle_panel_request`].
+//!
+//! [`Plugin::panels`]: super::Plugin::panels
+//! [`Plugin::handle_panel_request`]: super::Plugin::handle_panel_request
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PluginPanel {
+    /// Unique within the plugin; used in the tab's id
+    pub id: String,
+    /// The tab's label
+    pub title: String,
+    /// Markup for the panel; `<script>` elements are run when it is shown
+    pub html: String,
+}
+
+/// A request from a panel to `/api/plugin/<name>/<path>`
+#[cfg_attr(not(feature = "external-plugins"), allow(dead_code))]
+#[derive(Debug, Clone, Copy)]
+pub struct PanelRequest<'a> {
+    pub method: &'a str,
+    /// The route below the plugin's prefix, without a leading `/`
+    pub path: &'a str,
+    /// The query string, without the `?`
+    pub query: &'a str,
+    pub body: &'a [u8],
+    /// The project being served, when there is one
+    pub project_path: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl PanelResponse {
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json".to_string(),
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    #[cfg_attr(not(feature = "external-plugins"), allow(dead_code))]
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message.into() }))
+    }
+}
@@ -7,6 +7,12 @@ pub struct PluginRegistry {
     entries: HashMap<String, ExternalPluginEntry>,
 }
 
+impl Default for PluginRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PluginRegistry {
     #[allow(dead_code)]
     pub fn new() -> Self {
@@ -88,6 +94,7 @@ impl PluginRegistry {
     }
 
     /// Validates if a plugin exists and is installable
+    #[cfg(feature = "external-plugins")]
     pub fn validate_plugin(plugin_name: &str) -> Result<bool> {
         if plugin_name.is_empty() {
             return Ok(false);
@@ -117,6 +124,7 @@ impl PluginRegistry {
     }
 
     /// Creates a plugin entry
+    #[cfg(feature = "external-plugins")]
     pub fn create_plugin_entry(plugin_name: &str) -> Result<ExternalPluginEntry> {
         use crate::plugin::external::ExternalPluginLoader;
         ExternalPluginLoader::create_generic_entry(plugin_name)
@@ -195,6 +203,7 @@ fn parse_crate_metadata(
         },
         exports: None,
         frameworks: None,
+        panels: vec![],
     })
 }
 
@@ -0,0 +1,590 @@
+//! Remote builds: handing a project's builds to a machine running
+//! `wasmrun build-agent`, configured by `[remote_build]` in wasmrun.toml.
+//!
+//! A build takes up to three requests, all `POST`s under
+//! `/v1/projects/<key>/`, where the key names the project on the agent:
+//!
+//! - `sync` sends the path and digest of every source file; the agent drops
+//!   files the project no longer has and answers with the paths it lacks
+//! - `files` uploads those paths as a gzipped tarball
+//! - `build` builds, from the synced files or a git commit, and answers
+//!   with a line of JSON, the build result, followed by a gzipped tarball
+//!   of the output directory
+//!
+//! Build directories such as `target` and `node_modules` are never synced,
+//! so the agent's stay warm between builds.
+
+use super::cache::sha256_digest;
+use crate::compiler::builder::{
+    BuildConfig, BuildPlan, BuildResult, OptimizationLevel, OutputNaming, TargetType, WasmBuilder,
+};
+use crate::config::project::{RemoteBuildConfig, RemoteSource};
+use crate::error::{CompilationError, CompilationResult, Result, WasmrunError};
+use crate::utils::GitState;
+pub use crate::utils::{file_digest, list_files, safe_relative};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::RwLock;
+use ureq::http::Response;
+use ureq::{Agent, AsSendBody, Body};
+
+/// Where the token for the agent comes from, on both ends
+pub const BUILD_TOKEN_ENV: &str = "WASMRUN_BUILD_TOKEN";
+/// Largest upload or artifact tarball, unpacked
+pub const MAX_ARCHIVE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+/// Largest JSON request or reply, such as the digests of every file
+pub const MAX_JSON_BYTES: u64 = 64 * 1024 * 1024;
+
+static ACTIVE: RwLock<Option<RemoteBuild>> = RwLock::new(None);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncRequest {
+    /// Digest of each file, by `/`-separated path under the project
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncResponse {
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildRequest {
+    /// The builder's language name, e.g. `Rust`
+    pub language: String,
+    pub optimization: OptimizationLevel,
+    pub target_type: TargetType,
+    #[serde(default)]
+    pub naming: OutputNaming,
+    /// Build this commit instead of the synced files
+    pub git: Option<GitSource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitSource {
+    pub url: String,
+    pub commit: String,
+    /// The project's directory in the repository, `""` at its root
+    pub subdir: String,
+}
+
+impl GitSource {
+    /// Refuse a commit that isn't a full object id, a url git would read as
+    /// an option and a subdir outside the repository, before any of them
+    /// reach a git command line
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let hex = self.commit.chars().all(|c| c.is_ascii_hexdigit());
+        if !hex || !matches!(self.commit.len(), 40 | 64) {
+            return Err(format!("Invalid commit {:?}", self.commit));
+        }
+        if self.url.is_empty() || self.url.starts_with('-') {
+            return Err(format!("Invalid repository url {:?}", self.url));
+        }
+        if !self.subdir.is_empty() && safe_relative(&self.subdir).is_none() {
+            return Err(format!("Invalid subdir {:?}", self.subdir));
+        }
+        Ok(())
+    }
+}
+
+/// The first line of a `build` reply; the output tarball follows it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildResponse {
+    /// Paths relative to the output directory
+    pub result: BuildResult,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// Pack the `paths` under `root` into a gzipped tarball written to `out`,
+/// which is handed back when it's complete
+pub fn pack<W: Write>(root: &Path, paths: &[String], out: W) -> io::Result<W> {
+    let gz = flate2::write::GzEncoder::new(out, flate2::Compression::fast());
+    let mut builder = tar::Builder::new(gz);
+    for path in paths {
+        let relative = safe_relative(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("Unsafe path {path}"))
+        })?;
+        builder.append_path_with_name(root.join(&relative), &relative)?;
+    }
+    builder.into_inner()?.finish()
+}
+
+/// Unpack a gzipped tarball of regular files into `dest`, returning how
+/// many it held. Files are written afresh, so their modification time is
+/// now and incremental builds see them as changed; only the executable bit
+/// of their mode is kept.
+pub fn unpack(archive: impl Read, dest: &Path) -> io::Result<usize> {
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    let mut files = 0;
+    let mut total = 0;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type() != tar::EntryType::Regular {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().to_string();
+        let relative = safe_relative(&path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Unsafe path {path}"))
+        })?;
+        total += entry.size();
+        if total > MAX_ARCHIVE_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Archive unpacks to more than {} MB",
+                    MAX_ARCHIVE_BYTES / (1024 * 1024)
+                ),
+            ));
+        }
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut fs::File::create(&target)?)?;
+        #[cfg(unix)]
+        if entry.header().mode()? & 0o111 != 0 {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&target, fs::Permissions::from_mode(0o755))?;
+        }
+        files += 1;
+    }
+    Ok(files)
+}
+
+/// The agent builds projects are handed to while `[remote_build]` is in
+/// effect
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteBuild {
+    pub url: String,
+    pub token: Option<String>,
+    pub source: RemoteSource,
+    pub exclude: Vec<PathBuf>,
+}
+
+impl RemoteBuild {
+    /// The settings for `config`, `None` when it names no agent
+    pub fn from_config(config: &RemoteBuildConfig) -> Result<Option<Self>> {
+        let Some(url) = &config.url else {
+            return Ok(None);
+        };
+        if !super::is_remote_url(url) {
+            return Err(WasmrunError::from(format!(
+                "[remote_build] url must start with http:// or https://, not {url:?}"
+            )));
+        }
+        let exclude = config
+            .exclude
+            .iter()
+            .map(|path| {
+                safe_relative(path).ok_or_else(|| {
+                    WasmrunError::from(format!(
+                        "[remote_build] exclude needs paths inside the project, not {path:?}"
+                    ))
+                })
+            })
+            .collect::<Result<_>>()?;
+        let token_env = config.token_env.as_deref().unwrap_or(BUILD_TOKEN_ENV);
+        Ok(Some(Self {
+            url: url.trim_end_matches('/').to_string(),
+            token: std::env::var(token_env).ok().filter(|t| !t.is_empty()),
+            source: config.source,
+            exclude,
+        }))
+    }
+
+    /// Hand builds to `remote` from now on; `None` builds locally again
+    pub fn set_active(remote: Option<Self>) {
+        *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = remote;
+    }
+
+    pub fn active() -> Option<Self> {
+        ACTIVE.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// The agent's name for the project at `path`: its directory name and a
+/// hash of where it is and whose it is, so checkouts don't share a
+/// workspace
+pub fn project_key(path: &Path) -> io::Result<String> {
+    let path = std::path::absolute(path)?;
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let hash = sha256_digest(format!("{user}\0{}", path.display()).as_bytes());
+    let name: String = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    Ok(format!("{name}-{}", &hash["sha256:".len()..][..12]))
+}
+
+/// Where a git remote build finds the project: the repository's `origin`,
+/// the `HEAD` commit and the project's directory in it
+fn git_source(project: &Path) -> std::result::Result<GitSource, String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(project)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let state = GitState::detect(project).ok_or_else(|| {
+        format!(
+            "source = \"git\" needs {} to be in a git repository with a commit",
+            project.display()
+        )
+    })?;
+    if state.dirty {
+        eprintln!(
+            "⚠️  Building {} on the agent; uncommitted changes aren't part of it",
+            state.describe()
+        );
+    }
+    let url = git(&["remote", "get-url", "origin"])
+        .ok_or_else(|| "source = \"git\" needs an `origin` remote to build from".to_string())?;
+    let subdir = git(&["rev-parse", "--show-prefix"]).unwrap_or_default();
+    Ok(GitSource {
+        url,
+        commit: state.commit,
+        subdir: subdir.trim_end_matches('/').to_string(),
+    })
+}
+
+/// A builder that runs its builds on the agent, for the same language as
+/// the local builder it stands in for
+pub struct RemoteBuilder {
+    inner: Box<dyn WasmBuilder>,
+    remote: RemoteBuild,
+    agent: Agent,
+}
+
+impl RemoteBuilder {
+    pub fn new(inner: Box<dyn WasmBuilder>, remote: RemoteBuild) -> Self {
+        let agent: Agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self {
+            inner,
+            remote,
+            agent,
+        }
+    }
+
+    fn failed(&self, reason: impl Into<String>) -> CompilationError {
+        CompilationError::build_failed(self.inner.language_name(), reason)
+    }
+
+    /// Send `body` to the agent, returning its successful reply with the
+    /// body unread
+    fn post(
+        &self,
+        path: &str,
+        content_type: &str,
+        body: impl AsSendBody,
+    ) -> CompilationResult<Response<Body>> {
+        let url = format!("{}{path}", self.remote.url);
+        let mut request = self.agent.post(&url).header("Content-Type", content_type);
+        if let Some(token) = &self.remote.token {
+            request = request.header("Authorization", format!("Bearer {token}"));
+        }
+        let mut response = request
+            .send(body)
+            .map_err(|e| self.failed(format!("Can't reach the build agent at {url}: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let data = self.read(&mut response)?;
+            let message = serde_json::from_slice::<ErrorResponse>(&data)
+                .map(|e| e.error)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&data).trim().to_string());
+            return Err(self.failed(format!(
+                "The build agent answered HTTP {}: {message}",
+                status.as_u16()
+            )));
+        }
+        Ok(response)
+    }
+
+    fn read(&self, response: &mut Response<Body>) -> CompilationResult<Vec<u8>> {
+        response
+            .body_mut()
+            .with_config()
+            .limit(MAX_JSON_BYTES)
+            .read_to_vec()
+            .map_err(|e| self.failed(format!("Reading the build agent's answer failed: {e}")))
+    }
+
+    fn post_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> CompilationResult<T> {
+        let body = serde_json::to_vec(body).map_err(|e| self.failed(e.to_string()))?;
+        let data = self.read(&mut self.post(path, "application/json", &body)?)?;
+        serde_json::from_slice(&data)
+            .map_err(|e| self.failed(format!("Unexpected answer from the build agent: {e}")))
+    }
+
+    /// Bring the agent's copy of the project up to date, returning how many
+    /// files were uploaded
+    fn sync(&self, key: &str, config: &BuildConfig) -> CompilationResult<usize> {
+        let io_failed = |e: io::Error| self.failed(format!("Reading the project failed: {e}"));
+        let project = Path::new(&config.project_path);
+        let mut exclude = self.remote.exclude.clone();
+        // Artifacts from an output directory inside the project aren't sources
+        let project_dir = std::path::absolute(project).map_err(io_failed)?;
+        let output_dir = std::path::absolute(&config.output_dir).map_err(io_failed)?;
+        if let Ok(inside) = output_dir.strip_prefix(&project_dir) {
+            if !inside.as_os_str().is_empty() {
+                exclude.push(inside.to_path_buf());
+            }
+        }
+
+        let files = list_files(project, &exclude)
+            .and_then(|files| {
+                files
+                    .into_iter()
+                    .map(|(name, path)| Ok((name, file_digest(&path)?)))
+                    .collect::<io::Result<BTreeMap<_, _>>>()
+            })
+            .map_err(io_failed)?;
+        let SyncResponse { missing } =
+            self.post_json(&format!("/v1/projects/{key}/sync"), &SyncRequest { files })?;
+        if !missing.is_empty() {
+            // Spooled to disk rather than memory; an upload can be gigabytes
+            let mut archive = tempfile::tempfile()
+                .and_then(|file| pack(project, &missing, file))
+                .map_err(io_failed)?;
+            archive.rewind().map_err(io_failed)?;
+            self.post(
+                &format!("/v1/projects/{key}/files"),
+                "application/gzip",
+                archive,
+            )?;
+        }
+        Ok(missing.len())
+    }
+}
+
+impl WasmBuilder for RemoteBuilder {
+    fn can_handle_project(&self, project_path: &str) -> bool {
+        self.inner.can_handle_project(project_path)
+    }
+
+    fn build(&self, config: &BuildConfig) -> CompilationResult<BuildResult> {
+        let project = Path::new(&config.project_path);
+        let key = project_key(project).map_err(|e| self.failed(e.to_string()))?;
+        let git = match self.remote.source {
+            RemoteSource::Upload => {
+                let uploaded = self.sync(&key, config)?;
+                if config.verbose {
+                    println!(
+                        "📡 Uploaded {uploaded} changed files to {}",
+                        self.remote.url
+                    );
+                }
+                None
+            }
+            RemoteSource::Git => Some(git_source(project).map_err(|e| self.failed(e))?),
+        };
+
+        let request = serde_json::to_vec(&BuildRequest {
+            language: self.inner.language_name().to_string(),
+            optimization: config.optimization_level.clone(),
+            target_type: config.target_type.clone(),
+            naming: config.naming.clone(),
+            git,
+        })
+        .map_err(|e| self.failed(e.to_string()))?;
+        let reply = self.post(
+            &format!("/v1/projects/{key}/build"),
+            "application/json",
+            &request,
+        )?;
+        // The result line, then the artifacts, unpacked as they arrive
+        let mut reply = BufReader::new(
+            reply
+                .into_body()
+                .into_with_config()
+                .limit(MAX_JSON_BYTES + MAX_ARCHIVE_BYTES)
+                .reader(),
+        );
+        let mut line = Vec::new();
+        (&mut reply)
+            .take(MAX_JSON_BYTES)
+            .read_until(b'\n', &mut line)
+            .map_err(|e| self.failed(format!("Reading the build agent's answer failed: {e}")))?;
+        let response: BuildResponse = serde_json::from_slice(&line)
+            .map_err(|e| self.failed(format!("Unexpected answer from the build agent: {e}")))?;
+        let output_dir = Path::new(&config.output_dir);
+        let files = unpack(reply, output_dir)
+            .map_err(|e| self.failed(format!("Unpacking the build agent's artifacts: {e}")))?;
+        if config.verbose {
+            println!(
+                "📦 Built on {} in {:.1}s, {files} files downloaded",
+                self.remote.url, response.seconds
+            );
+        }
+
+        let local = |path: &str| output_dir.join(path).to_string_lossy().to_string();
+        let result = response.result;
+        Ok(BuildResult {
+            wasm_path: local(&result.wasm_path),
+            js_path: result.js_path.as_deref().map(local),
+            additional_files: result.additional_files.iter().map(|p| local(p)).collect(),
+            ..result
+        })
+    }
+
+    fn clean(&self, project_path: &str) -> Result<()> {
+        self.inner.clean(project_path)
+    }
+
+    fn clone_box(&self) -> Box<dyn WasmBuilder> {
+        Box::new(Self::new(self.inner.clone_box(), self.remote.clone()))
+    }
+
+    fn language_name(&self) -> &str {
+        self.inner.language_name()
+    }
+
+    fn entry_file_candidates(&self) -> &[&str] {
+        self.inner.entry_file_candidates()
+    }
+
+    fn supported_extensions(&self) -> &[&str] {
+        self.inner.supported_extensions()
+    }
+
+    /// The agent has the toolchain; nothing is needed here
+    fn check_dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn validate_project(&self, project_path: &str) -> CompilationResult<()> {
+        self.inner.validate_project(project_path)
+    }
+
+    fn plan(&self, config: &BuildConfig) -> CompilationResult<BuildPlan> {
+        let source = match self.remote.source {
+            RemoteSource::Upload => "after uploading changed files",
+            RemoteSource::Git => "from the pushed HEAD commit",
+        };
+        Ok(self.inner.plan(config)?.note(format!(
+            "Runs on the build agent at {} {source}",
+            self.remote.url
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_git_source_validate() {
+        let source = GitSource {
+            url: "https://github.com/example/app.git".to_string(),
+            commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            subdir: "web".to_string(),
+        };
+        assert!(source.validate().is_ok());
+        for commit in ["--upload-pack=touch x", "HEAD", "0123abc", &"g".repeat(40)] {
+            let bad = GitSource {
+                commit: commit.to_string(),
+                ..source.clone()
+            };
+            assert!(bad.validate().is_err(), "{commit}");
+        }
+        let option = GitSource {
+            url: "--upload-pack=touch x".to_string(),
+            ..source.clone()
+        };
+        assert!(option.validate().is_err());
+        let outside = GitSource {
+            subdir: "../other".to_string(),
+            ..source
+        };
+        assert!(outside.validate().is_err());
+    }
+
+    #[test]
+    fn test_list_pack_unpack() {
+        let project = tempdir().unwrap();
+        let root = project.path();
+        for (path, content) in [
+            ("Cargo.toml", "[package]"),
+            ("src/lib.rs", "pub fn a() {}"),
+            ("target/debug/app", "binary"),
+            ("web/node_modules/x/index.js", "x"),
+            ("dist/app.wasm", "wasm"),
+        ] {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::write(root.join(path), content).unwrap();
+        }
+
+        let files = list_files(root, &[PathBuf::from("dist")]).unwrap();
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            ["Cargo.toml", "src/lib.rs"]
+        );
+        let digest = file_digest(&files["src/lib.rs"]).unwrap();
+        assert_eq!(digest, sha256_digest(b"pub fn a() {}"));
+
+        let archive = pack(root, &["src/lib.rs".to_string()], Vec::new()).unwrap();
+        let dest = tempdir().unwrap();
+        assert_eq!(unpack(&archive[..], dest.path()).unwrap(), 1);
+        assert_eq!(
+            fs::read_to_string(dest.path().join("src/lib.rs")).unwrap(),
+            "pub fn a() {}"
+        );
+        assert!(pack(root, &["../escape".to_string()], Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        assert_eq!(
+            RemoteBuild::from_config(&RemoteBuildConfig::default()).unwrap(),
+            None
+        );
+        let config = RemoteBuildConfig {
+            url: Some("http://buildbox:8420/".to_string()),
+            token_env: Some("WASMRUN_TEST_UNSET_TOKEN".to_string()),
+            exclude: vec!["assets/video".to_string()],
+            ..Default::default()
+        };
+        let remote = RemoteBuild::from_config(&config).unwrap().unwrap();
+        assert_eq!(remote.url, "http://buildbox:8420");
+        assert_eq!(remote.token, None);
+        assert_eq!(remote.exclude, [PathBuf::from("assets/video")]);
+
+        let outside = RemoteBuildConfig {
+            exclude: vec!["../x".to_string()],
+            ..config
+        };
+        assert!(RemoteBuild::from_config(&outside).is_err());
+    }
+
+    #[test]
+    fn test_project_key() {
+        let key = project_key(Path::new("/work/my app")).unwrap();
+        assert!(key.starts_with("my-app-"), "{key}");
+        assert_eq!(key.len(), "my-app-".len() + 12);
+        assert_ne!(key, project_key(Path::new("/other/my app")).unwrap());
+    }
+}
@@ -0,0 +1,189 @@
+//! Content-addressed blob cache for modules fetched from remote sources.
+//!
+//! Blobs live under `~/.wasmrun/cache/blobs/sha256/<hex>`, keyed by the
+//! digest of their contents, so the same module pulled under two tags (or
+//! from two registries) is stored once and a digest-pinned reference can be
+//! served without touching the network.
+
+use crate::error::{Result, WasmrunError};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "deploy")]
+use std::fs;
+#[cfg(feature = "deploy")]
+use std::path::PathBuf;
+
+#[cfg(feature = "deploy")]
+pub struct BlobCache {
+    root: PathBuf,
+}
+
+/// What a cached blob holds. Modules keep a `.wasm` extension so the cached
+/// file can be handed straight to the server and interpreter, which both key
+/// off the extension.
+#[cfg(feature = "deploy")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobKind {
+    Module,
+    Metadata,
+}
+
+#[cfg(feature = "deploy")]
+impl BlobCache {
+    /// Open the cache under the user's home directory, creating it if needed.
+    pub fn new() -> Result<Self> {
+        let root = dirs::home_dir()
+            .ok_or_else(|| WasmrunError::from("Could not determine home directory".to_string()))?
+            .join(".wasmrun")
+            .join("cache");
+        Self::with_root(root)
+    }
+
+    pub fn with_root(root: PathBuf) -> Result<Self> {
+        let blobs = root.join("blobs").join("sha256");
+        fs::create_dir_all(&blobs).map_err(|e| {
+            WasmrunError::from(format!(
+                "Failed to create cache directory {}: {e}",
+                blobs.display()
+            ))
+        })?;
+        Ok(Self { root })
+    }
+
+    /// Path a blob with the given `sha256:<hex>` digest is stored at.
+    pub fn blob_path(&self, digest: &str, kind: BlobKind) -> Result<PathBuf> {
+        let hex = parse_sha256_digest(digest)?;
+        let name = match kind {
+            BlobKind::Module => format!("{hex}.wasm"),
+            BlobKind::Metadata => hex,
+        };
+        Ok(self.root.join("blobs").join("sha256").join(name))
+    }
+
+    /// Return the cached blob path if present and still matching its digest.
+    /// A corrupted entry is removed and reported as a miss.
+    pub fn get(&self, digest: &str, kind: BlobKind) -> Result<Option<PathBuf>> {
+        let path = self.blob_path(digest, kind)?;
+        let Ok(data) = fs::read(&path) else {
+            return Ok(None);
+        };
+        if sha256_digest(&data) != normalize_digest(digest)? {
+            let _ = fs::remove_file(&path);
+            return Ok(None);
+        }
+        Ok(Some(path))
+    }
+
+    /// Store `data` after checking it hashes to `digest`.
+    pub fn put(&self, digest: &str, data: &[u8], kind: BlobKind) -> Result<PathBuf> {
+        let actual = sha256_digest(data);
+        if actual != normalize_digest(digest)? {
+            return Err(WasmrunError::from(format!(
+                "Digest mismatch: expected {digest}, got {actual}"
+            )));
+        }
+        let path = self.blob_path(digest, kind)?;
+        // Write to a sibling temp file first so a concurrent reader never
+        // observes a partially written blob.
+        let tmp = path.with_extension("partial");
+        fs::write(&tmp, data)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| {
+                WasmrunError::from(format!(
+                    "Failed to write cache entry {}: {e}",
+                    path.display()
+                ))
+            })?;
+        Ok(path)
+    }
+}
+
+/// `sha256:<hex>` digest of `data`, in OCI digest notation.
+pub fn sha256_digest(data: &[u8]) -> String {
+    let hash = Sha256::digest(data);
+    let hex: String = hash.iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256:{hex}")
+}
+
+/// Validate a `sha256:<hex>` digest (a bare 64-char hex string is accepted
+/// too) and return the lowercase hex part.
+pub fn parse_sha256_digest(digest: &str) -> Result<String> {
+    let hex = digest.strip_prefix("sha256:").unwrap_or(digest);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(WasmrunError::from(format!(
+            "Invalid sha256 digest '{digest}': expected 64 hex characters"
+        )));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+/// Canonical `sha256:<lowercase hex>` form of a digest.
+pub fn normalize_digest(digest: &str) -> Result<String> {
+    Ok(format!("sha256:{}", parse_sha256_digest(digest)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "deploy")]
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sha256_digest_format() {
+        let digest = sha256_digest(b"");
+        assert_eq!(
+            digest,
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_parse_digest_accepts_bare_hex() {
+        let hex = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(parse_sha256_digest(hex).unwrap(), hex.to_ascii_lowercase());
+    }
+
+    #[test]
+    fn test_parse_digest_rejects_garbage() {
+        assert!(parse_sha256_digest("sha256:abc").is_err());
+        assert!(parse_sha256_digest("../../etc/passwd").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "deploy")]
+    fn test_cache_roundtrip() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let data = b"\0asm\x01\0\0\0";
+        let digest = sha256_digest(data);
+
+        assert!(cache.get(&digest, BlobKind::Module).unwrap().is_none());
+        let path = cache.put(&digest, data, BlobKind::Module).unwrap();
+        assert_eq!(
+            cache.get(&digest, BlobKind::Module).unwrap(),
+            Some(path.clone())
+        );
+        assert_eq!(fs::read(path).unwrap(), data);
+    }
+
+    #[test]
+    #[cfg(feature = "deploy")]
+    fn test_cache_rejects_mismatched_digest() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let digest = sha256_digest(b"one");
+        assert!(cache.put(&digest, b"two", BlobKind::Module).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "deploy")]
+    fn test_corrupted_entry_is_a_miss() {
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let digest = sha256_digest(b"module");
+        let path = cache.put(&digest, b"module", BlobKind::Metadata).unwrap();
+        fs::write(&path, b"tampered").unwrap();
+
+        assert!(cache.get(&digest, BlobKind::Metadata).unwrap().is_none());
+        assert!(!path.exists());
+    }
+}
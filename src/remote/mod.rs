@@ -0,0 +1,50 @@
+//! Fetching and publishing modules outside the local filesystem, and
+//! building on other machines.
+//!
+//! Only the digest helpers are built without the `deploy` feature, for
+//! checking `--sha256` against local files.
+
+#[cfg(feature = "deploy")]
+pub mod build;
+pub mod cache;
+#[cfg(feature = "deploy")]
+pub mod oci;
+#[cfg(feature = "deploy")]
+pub mod url;
+
+#[cfg(feature = "deploy")]
+pub use cache::BlobCache;
+#[cfg(feature = "deploy")]
+pub use oci::{OciClient, OciReference};
+#[cfg(feature = "deploy")]
+pub use url::fetch_module;
+
+use crate::compiler::builder::WasmBuilder;
+
+/// `builder`, or one that hands its builds to the build agent while
+/// `[remote_build]` is in effect
+pub fn remote_or_local(builder: Box<dyn WasmBuilder>) -> Box<dyn WasmBuilder> {
+    #[cfg(feature = "deploy")]
+    if let Some(remote) = build::RemoteBuild::active() {
+        return Box::new(build::RemoteBuilder::new(builder, remote));
+    }
+    builder
+}
+
+/// Whether `input` is an `http://` or `https://` URL.
+pub fn is_remote_url(input: &str) -> bool {
+    input.starts_with("https://") || input.starts_with("http://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_remote_url() {
+        assert!(is_remote_url("https://example.com/app.wasm"));
+        assert!(is_remote_url("http://localhost:8000/app.wasm"));
+        assert!(!is_remote_url("./app.wasm"));
+        assert!(!is_remote_url("ghcr.io/me/app:v1"));
+    }
+}
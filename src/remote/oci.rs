@@ -0,0 +1,861 @@
+//! Minimal OCI distribution client for WebAssembly artifacts.
+//!
+//! Modules are pushed following the CNCF "Wasm OCI artifact" layout: a
+//! `application/vnd.wasm.config.v0+json` config blob and a single
+//! `application/wasm` layer holding the module bytes, tied together by a
+//! standard OCI image manifest. Only the parts of the distribution API needed
+//! for that layout are implemented (blob upload, manifest put/get, blob get),
+//! plus the anonymous/basic/bearer token auth dance registries expect.
+
+use super::cache::{normalize_digest, sha256_digest, BlobCache, BlobKind};
+use crate::error::{Result, WasmrunError};
+use crate::ui::progress;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use ureq::http::Response;
+use ureq::{Agent, Body, RequestBuilder};
+
+pub const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const WASM_CONFIG_MEDIA_TYPE: &str = "application/vnd.wasm.config.v0+json";
+pub const WASM_LAYER_MEDIA_TYPE: &str = "application/wasm";
+
+/// Registry credentials, used for basic auth and for token requests.
+const USERNAME_ENV: &str = "WASMRUN_REGISTRY_USERNAME";
+const PASSWORD_ENV: &str = "WASMRUN_REGISTRY_PASSWORD";
+
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+const DEFAULT_TAG: &str = "latest";
+const MAX_MANIFEST_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_BLOB_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// A parsed `registry/repository[:tag][@digest]` reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OciReference {
+    pub registry: String,
+    pub repository: String,
+    pub tag: String,
+    pub digest: Option<String>,
+}
+
+impl OciReference {
+    /// Parse a reference such as `ghcr.io/me/app:v1` or
+    /// `oci://localhost:5000/app@sha256:...`. The registry host is required
+    /// (there is no implicit Docker Hub default) so that a bare relative path
+    /// is never mistaken for a reference.
+    pub fn parse(input: &str) -> Result<Self> {
+        let raw = input.strip_prefix("oci://").unwrap_or(input);
+
+        let (name_and_tag, digest) = match raw.split_once('@') {
+            Some((name, digest)) => (name, Some(normalize_digest(digest)?)),
+            None => (raw, None),
+        };
+
+        let (registry, rest) = name_and_tag.split_once('/').ok_or_else(|| {
+            WasmrunError::from(format!(
+                "Invalid OCI reference '{input}': expected registry/repository[:tag]"
+            ))
+        })?;
+        if !is_registry_host(registry) {
+            return Err(WasmrunError::from(format!(
+                "Invalid OCI reference '{input}': '{registry}' is not a registry host"
+            )));
+        }
+
+        // A ':' after the last '/' separates the tag; earlier ones belong to
+        // the registry port and were already split off above.
+        let (repository, tag) = match rest.rsplit_once(':') {
+            Some((repo, tag)) if !tag.contains('/') => (repo, tag.to_string()),
+            _ => (rest, DEFAULT_TAG.to_string()),
+        };
+
+        let valid_repo = !repository.is_empty()
+            && repository.split('/').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c))
+            });
+        if !valid_repo {
+            return Err(WasmrunError::from(format!(
+                "Invalid OCI reference '{input}': repository '{repository}' must be lowercase alphanumerics separated by '/', '.', '_' or '-'"
+            )));
+        }
+        if tag.is_empty() || tag.len() > 128 {
+            return Err(WasmrunError::from(format!(
+                "Invalid OCI reference '{input}': bad tag '{tag}'"
+            )));
+        }
+
+        Ok(Self {
+            registry: registry.to_string(),
+            repository: repository.to_string(),
+            tag,
+            digest,
+        })
+    }
+
+    /// Whether `input` should be treated as a registry reference rather than
+    /// a local path. `oci://` is always a reference; otherwise the input must
+    /// parse and not name something on disk.
+    pub fn is_reference(input: &str) -> bool {
+        if input.starts_with("oci://") {
+            return true;
+        }
+        !std::path::Path::new(input).exists() && Self::parse(input).is_ok()
+    }
+
+    /// Tag or digest used to address the manifest.
+    fn manifest_ref(&self) -> &str {
+        self.digest.as_deref().unwrap_or(&self.tag)
+    }
+
+    /// Base URL of the registry's distribution API. Loopback registries are
+    /// spoken to over plain HTTP, which is what a local `registry:2` serves.
+    fn base_url(&self) -> String {
+        let host = self.registry.split(':').next().unwrap_or_default();
+        let scheme = if host == "localhost" || host == "127.0.0.1" {
+            "http"
+        } else {
+            "https"
+        };
+        format!("{scheme}://{}", self.registry)
+    }
+
+    /// Suggested local filename for a pulled module (`app.wasm` for `.../app:tag`).
+    pub fn module_filename(&self) -> String {
+        let name = self.repository.rsplit('/').next().unwrap_or("module");
+        format!("{name}.wasm")
+    }
+}
+
+impl fmt::Display for OciReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}:{}", self.registry, self.repository, self.tag)?;
+        if let Some(digest) = &self.digest {
+            write!(f, "@{digest}")?;
+        }
+        Ok(())
+    }
+}
+
+fn is_registry_host(component: &str) -> bool {
+    if component.starts_with('.') {
+        return false;
+    }
+    component == "localhost" || component.contains('.') || component.contains(':')
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub config: Descriptor,
+    pub layers: Vec<Descriptor>,
+}
+
+impl Manifest {
+    /// The layer carrying the module bytes.
+    pub fn wasm_layer(&self) -> Result<&Descriptor> {
+        self.layers
+            .iter()
+            .find(|l| l.media_type == WASM_LAYER_MEDIA_TYPE)
+            .ok_or_else(|| {
+                let found: Vec<&str> = self.layers.iter().map(|l| l.media_type.as_str()).collect();
+                WasmrunError::from(format!(
+                    "Manifest has no {WASM_LAYER_MEDIA_TYPE} layer (found: {})",
+                    if found.is_empty() {
+                        "none".to_string()
+                    } else {
+                        found.join(", ")
+                    }
+                ))
+            })
+    }
+}
+
+/// Config blob of a Wasm OCI artifact.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WasmConfig {
+    created: String,
+    architecture: String,
+    os: String,
+    layer_digests: Vec<String>,
+}
+
+/// Outcome of a successful push.
+#[derive(Debug, Clone)]
+pub struct PushedArtifact {
+    pub manifest_digest: String,
+    pub layer_digest: String,
+    pub size: u64,
+}
+
+/// Outcome of a successful pull; `path` points into the blob cache.
+#[derive(Debug, Clone)]
+pub struct PulledArtifact {
+    pub manifest_digest: String,
+    pub layer_digest: String,
+    pub size: u64,
+    pub path: PathBuf,
+    pub from_cache: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+}
+
+pub struct OciClient {
+    agent: Agent,
+    reference: OciReference,
+    authorization: Option<String>,
+}
+
+impl OciClient {
+    pub fn new(reference: OciReference) -> Self {
+        let agent: Agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self {
+            agent,
+            reference,
+            authorization: None,
+        }
+    }
+
+    /// Upload `wasm` as a Wasm OCI artifact and tag it with the reference's tag.
+    /// `os` goes into the artifact config (`wasip1` for WASI command modules).
+    pub fn push(&mut self, wasm: &[u8], title: &str, os: &str) -> Result<PushedArtifact> {
+        if self.reference.digest.is_some() {
+            return Err(WasmrunError::from(format!(
+                "Cannot push to a digest reference ({}); use a tag",
+                self.reference
+            )));
+        }
+
+        let layer_digest = sha256_digest(wasm);
+        let config = WasmConfig {
+            created: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            architecture: "wasm".to_string(),
+            os: os.to_string(),
+            layer_digests: vec![layer_digest.clone()],
+        };
+        let config_bytes = serde_json::to_vec(&config)
+            .map_err(|e| WasmrunError::from(format!("Failed to encode artifact config: {e}")))?;
+        let config_digest = sha256_digest(&config_bytes);
+
+        self.upload_blob(&config_bytes, &config_digest)?;
+        self.upload_blob(wasm, &layer_digest)?;
+
+        let mut annotations = BTreeMap::new();
+        annotations.insert(TITLE_ANNOTATION.to_string(), title.to_string());
+        let manifest = Manifest {
+            schema_version: 2,
+            media_type: Some(MANIFEST_MEDIA_TYPE.to_string()),
+            config: Descriptor {
+                media_type: WASM_CONFIG_MEDIA_TYPE.to_string(),
+                digest: config_digest,
+                size: config_bytes.len() as u64,
+                annotations: BTreeMap::new(),
+            },
+            layers: vec![Descriptor {
+                media_type: WASM_LAYER_MEDIA_TYPE.to_string(),
+                digest: layer_digest.clone(),
+                size: wasm.len() as u64,
+                annotations,
+            }],
+        };
+        let manifest_bytes = serde_json::to_vec(&manifest)
+            .map_err(|e| WasmrunError::from(format!("Failed to encode manifest: {e}")))?;
+
+        let url = format!(
+            "{}/v2/{}/manifests/{}",
+            self.reference.base_url(),
+            self.reference.repository,
+            self.reference.tag
+        );
+        let resp = self.send(
+            Method::Put,
+            &url,
+            &[("Content-Type", MANIFEST_MEDIA_TYPE)],
+            &manifest_bytes,
+            "pull,push",
+        )?;
+        check_status(resp, "push manifest")?;
+
+        Ok(PushedArtifact {
+            manifest_digest: sha256_digest(&manifest_bytes),
+            layer_digest,
+            size: wasm.len() as u64,
+        })
+    }
+
+    /// Resolve the reference and make sure its module is in `cache`.
+    ///
+    /// Digest-pinned references whose manifest and layer are already cached
+    /// are served without any network access; tags are always re-resolved
+    /// since they can move.
+    pub fn pull(&mut self, cache: &BlobCache) -> Result<PulledArtifact> {
+        let cached_manifest = match &self.reference.digest {
+            Some(digest) => cache.get(digest, BlobKind::Metadata)?,
+            None => None,
+        };
+
+        let (manifest_digest, manifest_bytes) = match cached_manifest {
+            Some(path) => {
+                let bytes = std::fs::read(&path)?;
+                (sha256_digest(&bytes), bytes)
+            }
+            None => self.fetch_manifest(cache)?,
+        };
+
+        let manifest: Manifest = serde_json::from_slice(&manifest_bytes).map_err(|e| {
+            WasmrunError::from(format!("Invalid manifest for {}: {e}", self.reference))
+        })?;
+        let layer = manifest.wasm_layer()?.clone();
+
+        if let Some(path) = cache.get(&layer.digest, BlobKind::Module)? {
+            return Ok(PulledArtifact {
+                manifest_digest,
+                layer_digest: layer.digest,
+                size: layer.size,
+                path,
+                from_cache: true,
+            });
+        }
+
+        let url = format!(
+            "{}/v2/{}/blobs/{}",
+            self.reference.base_url(),
+            self.reference.repository,
+            layer.digest
+        );
+        let resp = check_status(
+            self.send(Method::Get, &url, &[], &[], "pull")?,
+            "fetch module blob",
+        )?;
+        let data = progress::download(
+            resp.into_body().into_reader(),
+            Some(layer.size),
+            MAX_BLOB_BYTES,
+            format!("Downloading {}", self.reference),
+        )
+        .map_err(|e| WasmrunError::from(format!("Failed to read registry response: {e}")))?;
+        // BlobCache::put refuses data that doesn't hash to the digest, so a
+        // tampered or truncated download never lands in the cache.
+        let path = cache.put(&layer.digest, &data, BlobKind::Module)?;
+
+        Ok(PulledArtifact {
+            manifest_digest,
+            layer_digest: layer.digest,
+            size: data.len() as u64,
+            path,
+            from_cache: false,
+        })
+    }
+
+    fn fetch_manifest(&mut self, cache: &BlobCache) -> Result<(String, Vec<u8>)> {
+        let url = format!(
+            "{}/v2/{}/manifests/{}",
+            self.reference.base_url(),
+            self.reference.repository,
+            self.reference.manifest_ref()
+        );
+        let resp = self.send(
+            Method::Get,
+            &url,
+            &[("Accept", MANIFEST_MEDIA_TYPE)],
+            &[],
+            "pull",
+        )?;
+        let bytes = read_body(check_status(resp, "fetch manifest")?, MAX_MANIFEST_BYTES)?;
+        let digest = sha256_digest(&bytes);
+
+        if let Some(expected) = &self.reference.digest {
+            if &digest != expected {
+                return Err(WasmrunError::from(format!(
+                    "Manifest digest mismatch for {}: registry returned {digest}",
+                    self.reference
+                )));
+            }
+        }
+        cache.put(&digest, &bytes, BlobKind::Metadata)?;
+        Ok((digest, bytes))
+    }
+
+    /// Upload a blob unless the registry already has it (monolithic upload).
+    fn upload_blob(&mut self, data: &[u8], digest: &str) -> Result<()> {
+        let base = self.reference.base_url();
+        let repo = self.reference.repository.clone();
+
+        let exists = self.send(
+            Method::Head,
+            &format!("{base}/v2/{repo}/blobs/{digest}"),
+            &[],
+            &[],
+            "pull,push",
+        )?;
+        if exists.status().is_success() {
+            return Ok(());
+        }
+
+        let resp = self.send(
+            Method::Post,
+            &format!("{base}/v2/{repo}/blobs/uploads/"),
+            &[],
+            &[],
+            "pull,push",
+        )?;
+        let resp = check_status(resp, "start blob upload")?;
+        let location = resp
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| WasmrunError::from("Registry did not return an upload location"))?;
+
+        let mut upload_url = if location.starts_with("http://") || location.starts_with("https://")
+        {
+            location.to_string()
+        } else {
+            format!("{base}{location}")
+        };
+        upload_url.push(if upload_url.contains('?') { '&' } else { '?' });
+        upload_url.push_str(&format!("digest={}", encode_query(digest)));
+
+        let resp = self.send(
+            Method::Put,
+            &upload_url,
+            &[("Content-Type", "application/octet-stream")],
+            data,
+            "pull,push",
+        )?;
+        check_status(resp, "upload blob")?;
+        Ok(())
+    }
+
+    /// Send a request, authenticating and retrying once if the registry
+    /// answers 401 with a challenge.
+    fn send(
+        &mut self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+        actions: &str,
+    ) -> Result<Response<Body>> {
+        let resp = self.dispatch(method, url, headers, body)?;
+        if resp.status().as_u16() != 401 {
+            return Ok(resp);
+        }
+
+        let challenge = resp
+            .headers()
+            .get("www-authenticate")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        self.authenticate(&challenge, actions)?;
+        self.dispatch(method, url, headers, body)
+    }
+
+    fn dispatch(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<Response<Body>> {
+        let auth = self.authorization.as_deref();
+        let result = match method {
+            Method::Get => with_headers(self.agent.get(url), headers, auth).call(),
+            Method::Head => with_headers(self.agent.head(url), headers, auth).call(),
+            Method::Post => with_headers(self.agent.post(url), headers, auth).send(body),
+            Method::Put => with_headers(self.agent.put(url), headers, auth).send(body),
+        };
+        result.map_err(|e| WasmrunError::from(format!("Registry request to {url} failed: {e}")))
+    }
+
+    fn authenticate(&mut self, challenge: &str, actions: &str) -> Result<()> {
+        let credentials = registry_credentials();
+        let (scheme, params) = parse_challenge(challenge).ok_or_else(|| {
+            WasmrunError::from(format!(
+                "Registry {} requires authentication but sent no usable challenge",
+                self.reference.registry
+            ))
+        })?;
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let (user, pass) = credentials.ok_or_else(|| missing_credentials(&self.reference))?;
+            self.authorization = Some(basic_auth(&user, &pass));
+            return Ok(());
+        }
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            return Err(WasmrunError::from(format!(
+                "Unsupported registry auth scheme '{scheme}'"
+            )));
+        }
+
+        let realm = params
+            .get("realm")
+            .ok_or_else(|| WasmrunError::from("Bearer challenge is missing a realm"))?;
+        let scope = format!("repository:{}:{actions}", self.reference.repository);
+        let mut url = format!("{realm}?scope={}", encode_query(&scope));
+        if let Some(service) = params.get("service") {
+            url.push_str(&format!("&service={}", encode_query(service)));
+        }
+
+        let mut req = self.agent.get(&url);
+        if let Some((user, pass)) = &credentials {
+            req = req.header("Authorization", basic_auth(user, pass));
+        }
+        let resp = req
+            .call()
+            .map_err(|e| WasmrunError::from(format!("Token request to {realm} failed: {e}")))?;
+        if resp.status().as_u16() == 401 || resp.status().as_u16() == 403 {
+            return Err(missing_credentials(&self.reference));
+        }
+        let body = read_body(
+            check_status(resp, "fetch registry token")?,
+            MAX_MANIFEST_BYTES,
+        )?;
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            token: Option<String>,
+            access_token: Option<String>,
+        }
+        let token: TokenResponse = serde_json::from_slice(&body)
+            .map_err(|e| WasmrunError::from(format!("Invalid token response from {realm}: {e}")))?;
+        let token = token.token.or(token.access_token).ok_or_else(|| {
+            WasmrunError::from(format!("Token response from {realm} has no token"))
+        })?;
+        self.authorization = Some(format!("Bearer {token}"));
+        Ok(())
+    }
+}
+
+fn with_headers<B>(
+    mut req: RequestBuilder<B>,
+    headers: &[(&str, &str)],
+    auth: Option<&str>,
+) -> RequestBuilder<B> {
+    for (name, value) in headers {
+        req = req.header(*name, *value);
+    }
+    if let Some(auth) = auth {
+        req = req.header("Authorization", auth);
+    }
+    req
+}
+
+fn check_status(resp: Response<Body>, action: &str) -> Result<Response<Body>> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let mut resp = resp;
+    let detail = resp
+        .body_mut()
+        .with_config()
+        .limit(4096)
+        .read_to_string()
+        .unwrap_or_default();
+    Err(WasmrunError::from(format!(
+        "Registry refused to {action}: HTTP {}{}",
+        status.as_u16(),
+        if detail.trim().is_empty() {
+            String::new()
+        } else {
+            format!(" - {}", detail.trim())
+        }
+    )))
+}
+
+fn read_body(mut resp: Response<Body>, limit: u64) -> Result<Vec<u8>> {
+    resp.body_mut()
+        .with_config()
+        .limit(limit)
+        .read_to_vec()
+        .map_err(|e| WasmrunError::from(format!("Failed to read registry response: {e}")))
+}
+
+fn registry_credentials() -> Option<(String, String)> {
+    let user = std::env::var(USERNAME_ENV).ok()?;
+    let pass = std::env::var(PASSWORD_ENV).unwrap_or_default();
+    Some((user, pass))
+}
+
+fn missing_credentials(reference: &OciReference) -> WasmrunError {
+    WasmrunError::from(format!(
+        "Registry {} rejected the request. Set {USERNAME_ENV} and {PASSWORD_ENV} (e.g. a GitHub token for ghcr.io)",
+        reference.registry
+    ))
+}
+
+fn basic_auth(user: &str, pass: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+    format!("Basic {encoded}")
+}
+
+/// Parse a `WWW-Authenticate` header into its scheme and parameters.
+fn parse_challenge(header: &str) -> Option<(String, BTreeMap<String, String>)> {
+    let header = header.trim();
+    if header.is_empty() {
+        return None;
+    }
+    let (scheme, rest) = header.split_once(' ').unwrap_or((header, ""));
+    let mut params = BTreeMap::new();
+    let mut rest = rest.trim();
+
+    while !rest.is_empty() {
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+        let key = key
+            .trim()
+            .trim_start_matches(',')
+            .trim()
+            .to_ascii_lowercase();
+        let after = after.trim_start();
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            let end = quoted.find('"')?;
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            let end = after.find(',').unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        params.insert(key, value.to_string());
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+
+    Some((scheme.to_string(), params))
+}
+
+/// Percent-encode a query-string value.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_reference_with_tag() {
+        let r = OciReference::parse("ghcr.io/me/app:v1").unwrap();
+        assert_eq!(r.registry, "ghcr.io");
+        assert_eq!(r.repository, "me/app");
+        assert_eq!(r.tag, "v1");
+        assert_eq!(r.digest, None);
+        assert_eq!(r.base_url(), "https://ghcr.io");
+    }
+
+    #[test]
+    fn test_parse_reference_defaults_to_latest() {
+        let r = OciReference::parse("oci://registry.example.com/app").unwrap();
+        assert_eq!(r.tag, "latest");
+        assert_eq!(r.to_string(), "registry.example.com/app:latest");
+    }
+
+    #[test]
+    fn test_parse_reference_with_port_and_digest() {
+        let digest = sha256_digest(b"x");
+        let r = OciReference::parse(&format!("localhost:5000/team/app@{digest}")).unwrap();
+        assert_eq!(r.registry, "localhost:5000");
+        assert_eq!(r.repository, "team/app");
+        assert_eq!(r.tag, "latest");
+        assert_eq!(r.digest.as_deref(), Some(digest.as_str()));
+        assert_eq!(r.manifest_ref(), digest);
+        assert_eq!(r.base_url(), "http://localhost:5000");
+    }
+
+    #[test]
+    fn test_parse_reference_rejects_paths() {
+        assert!(OciReference::parse("app.wasm").is_err());
+        assert!(OciReference::parse("./build/app.wasm").is_err());
+        assert!(OciReference::parse("examples/app").is_err());
+        assert!(OciReference::parse("ghcr.io/Me/App:v1").is_err());
+        assert!(OciReference::parse("ghcr.io/me/app@sha256:short").is_err());
+    }
+
+    #[test]
+    fn test_module_filename() {
+        let r = OciReference::parse("ghcr.io/me/hello-world:1.0").unwrap();
+        assert_eq!(r.module_filename(), "hello-world.wasm");
+    }
+
+    #[test]
+    fn test_parse_bearer_challenge() {
+        let (scheme, params) = parse_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:me/app:pull""#,
+        )
+        .unwrap();
+        assert_eq!(scheme, "Bearer");
+        assert_eq!(params["realm"], "https://ghcr.io/token");
+        assert_eq!(params["service"], "ghcr.io");
+        assert_eq!(params["scope"], "repository:me/app:pull");
+    }
+
+    #[test]
+    fn test_parse_basic_challenge() {
+        let (scheme, params) = parse_challenge(r#"Basic realm="Registry Realm""#).unwrap();
+        assert_eq!(scheme, "Basic");
+        assert_eq!(params["realm"], "Registry Realm");
+        assert!(parse_challenge("").is_none());
+    }
+
+    #[test]
+    fn test_encode_query() {
+        assert_eq!(
+            encode_query("repository:me/app:pull,push"),
+            "repository%3Ame%2Fapp%3Apull%2Cpush"
+        );
+    }
+
+    #[test]
+    fn test_manifest_without_wasm_layer() {
+        let manifest = Manifest {
+            schema_version: 2,
+            media_type: None,
+            config: Descriptor {
+                media_type: WASM_CONFIG_MEDIA_TYPE.to_string(),
+                digest: sha256_digest(b"{}"),
+                size: 2,
+                annotations: BTreeMap::new(),
+            },
+            layers: vec![],
+        };
+        let err = manifest.wasm_layer().unwrap_err().to_string();
+        assert!(err.contains("application/wasm"));
+    }
+
+    /// In-memory stand-in for a distribution-spec registry, enough for the
+    /// push/pull flow.
+    fn spawn_registry() -> (String, Arc<Mutex<usize>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let blob_gets = Arc::new(Mutex::new(0));
+        let blob_gets_thread = blob_gets.clone();
+
+        std::thread::spawn(move || {
+            let mut blobs: HashMap<String, Vec<u8>> = HashMap::new();
+            let mut manifests: HashMap<String, Vec<u8>> = HashMap::new();
+
+            for mut request in server.incoming_requests() {
+                let url = request.url().to_string();
+                let method = request.method().to_string();
+                let mut body = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut body);
+                let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+                let response = if method == "POST" && path.ends_with("/blobs/uploads/") {
+                    tiny_http::Response::from_data(Vec::new())
+                        .with_status_code(202)
+                        .with_header(
+                            tiny_http::Header::from_bytes("Location", "/upload/1").unwrap(),
+                        )
+                } else if method == "PUT" && path.starts_with("/upload/") {
+                    let digest = query.trim_start_matches("digest=").replace("%3A", ":");
+                    blobs.insert(digest, body);
+                    tiny_http::Response::from_data(Vec::new()).with_status_code(201)
+                } else if let Some(digest) = path.split("/blobs/").nth(1) {
+                    match blobs.get(digest) {
+                        Some(data) if method == "GET" => {
+                            *blob_gets_thread.lock().unwrap() += 1;
+                            tiny_http::Response::from_data(data.clone())
+                        }
+                        Some(_) => tiny_http::Response::from_data(Vec::new()),
+                        None => tiny_http::Response::from_data(Vec::new()).with_status_code(404),
+                    }
+                } else if let Some(reference) = path.split("/manifests/").nth(1) {
+                    if method == "PUT" {
+                        manifests.insert(sha256_digest(&body), body.clone());
+                        manifests.insert(reference.to_string(), body);
+                        tiny_http::Response::from_data(Vec::new()).with_status_code(201)
+                    } else {
+                        match manifests.get(reference) {
+                            Some(data) => tiny_http::Response::from_data(data.clone()),
+                            None => {
+                                tiny_http::Response::from_data(Vec::new()).with_status_code(404)
+                            }
+                        }
+                    }
+                } else {
+                    tiny_http::Response::from_data(Vec::new()).with_status_code(404)
+                };
+                let _ = request.respond(response);
+            }
+        });
+
+        (format!("127.0.0.1:{port}"), blob_gets)
+    }
+
+    #[test]
+    fn test_push_then_pull_roundtrip() {
+        let (registry, blob_gets) = spawn_registry();
+        let wasm = b"\0asm\x01\0\0\0".to_vec();
+
+        let reference = OciReference::parse(&format!("{registry}/me/app:v1")).unwrap();
+        let pushed = OciClient::new(reference.clone())
+            .push(&wasm, "app.wasm", "wasip1")
+            .unwrap();
+        assert_eq!(pushed.layer_digest, sha256_digest(&wasm));
+
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let pulled = OciClient::new(reference).pull(&cache).unwrap();
+        assert_eq!(pulled.manifest_digest, pushed.manifest_digest);
+        assert!(!pulled.from_cache);
+        assert_eq!(std::fs::read(&pulled.path).unwrap(), wasm);
+        assert_eq!(*blob_gets.lock().unwrap(), 1);
+
+        // Pinning by digest resolves entirely from the cache.
+        let pinned =
+            OciReference::parse(&format!("{registry}/me/app@{}", pushed.manifest_digest)).unwrap();
+        let again = OciClient::new(pinned).pull(&cache).unwrap();
+        assert!(again.from_cache);
+        assert_eq!(again.path, pulled.path);
+        assert_eq!(*blob_gets.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_pull_missing_tag_fails() {
+        let (registry, _) = spawn_registry();
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let reference = OciReference::parse(&format!("{registry}/me/app:nope")).unwrap();
+        let err = OciClient::new(reference).pull(&cache).unwrap_err();
+        assert!(err.to_string().contains("HTTP 404"));
+    }
+}
@@ -0,0 +1,154 @@
+//! Fetch modules over plain HTTP(S) into the blob cache.
+
+use super::cache::{normalize_digest, sha256_digest, BlobCache, BlobKind};
+use crate::error::{Result, WasmrunError};
+use crate::ui::progress;
+use std::path::PathBuf;
+
+const MAX_MODULE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// A module fetched from a URL; `path` points into the blob cache.
+#[derive(Debug, Clone)]
+pub struct FetchedModule {
+    pub path: PathBuf,
+    pub digest: String,
+    pub size: u64,
+    pub from_cache: bool,
+}
+
+/// Download `url` into `cache`, verifying it against `expected_sha256` when
+/// one is given.
+///
+/// With a digest, a cached copy is reused without touching the network.
+/// Without one there is nothing to key the cache on before downloading, so
+/// the module is always fetched; the caller should surface the digest so it
+/// can be pinned next time.
+pub fn fetch_module(
+    url: &str,
+    expected_sha256: Option<&str>,
+    cache: &BlobCache,
+) -> Result<FetchedModule> {
+    let expected = expected_sha256.map(normalize_digest).transpose()?;
+
+    if let Some(digest) = &expected {
+        if let Some(path) = cache.get(digest, BlobKind::Module)? {
+            let size = std::fs::metadata(&path)?.len();
+            return Ok(FetchedModule {
+                path,
+                digest: digest.clone(),
+                size,
+                from_cache: true,
+            });
+        }
+    }
+
+    let body = ureq::get(url)
+        .call()
+        .map_err(|e| WasmrunError::from(format!("HTTP request failed for {url}: {e}")))?
+        .into_body();
+    let total = body.content_length();
+    let data = progress::download(
+        body.into_reader(),
+        total,
+        MAX_MODULE_BYTES,
+        format!("Downloading {url}"),
+    )
+    .map_err(|e| WasmrunError::from(format!("Failed to download {url}: {e}")))?;
+
+    let digest = sha256_digest(&data);
+    if let Some(expected) = &expected {
+        if &digest != expected {
+            return Err(WasmrunError::from(format!(
+                "Checksum mismatch for {url}: expected {expected}, got {digest}"
+            )));
+        }
+    }
+    if !data.starts_with(b"\0asm") {
+        return Err(WasmrunError::invalid_file_format(
+            url,
+            "Downloaded file is not a WebAssembly module",
+        ));
+    }
+
+    let path = cache.put(&digest, &data, BlobKind::Module)?;
+    Ok(FetchedModule {
+        path,
+        digest,
+        size: data.len() as u64,
+        from_cache: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    const MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    /// Serve `body` for every request, counting hits.
+    fn serve(body: &'static [u8]) -> (String, Arc<AtomicUsize>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_thread = hits.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                hits_thread.fetch_add(1, Ordering::SeqCst);
+                let _ = request.respond(tiny_http::Response::from_data(body.to_vec()));
+            }
+        });
+        (format!("http://127.0.0.1:{port}/app.wasm"), hits)
+    }
+
+    #[test]
+    fn test_fetch_with_digest_is_cached() {
+        let (url, hits) = serve(MODULE);
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let digest = sha256_digest(MODULE);
+
+        let first = fetch_module(&url, Some(&digest), &cache).unwrap();
+        assert!(!first.from_cache);
+        assert_eq!(std::fs::read(&first.path).unwrap(), MODULE);
+
+        let second = fetch_module(&url, Some(&digest), &cache).unwrap();
+        assert!(second.from_cache);
+        assert_eq!(second.path, first.path);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_fetch_without_digest_always_downloads() {
+        let (url, hits) = serve(MODULE);
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+
+        let fetched = fetch_module(&url, None, &cache).unwrap();
+        assert_eq!(fetched.digest, sha256_digest(MODULE));
+        fetch_module(&url, None, &cache).unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_fetch_checksum_mismatch_is_not_cached() {
+        let (url, _) = serve(MODULE);
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        let wrong = sha256_digest(b"something else");
+
+        let err = fetch_module(&url, Some(&wrong), &cache).unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert!(cache.get(&wrong, BlobKind::Module).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fetch_rejects_non_wasm() {
+        let (url, _) = serve(b"<html>not found</html>");
+        let dir = tempdir().unwrap();
+        let cache = BlobCache::with_root(dir.path().to_path_buf()).unwrap();
+        assert!(fetch_module(&url, None, &cache).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! The JSON-RPC 2.0 control API behind `wasmrun serve-api`, for editors and
+//! other long-running clients. Messages are framed as in LSP, each body
+//! preceded by a `Content-Length` header, over stdio or a socket.
+//!
+//! [`PROTOCOL_VERSION`] is reported by `initialize` and `status` and only
+//! changes when a method or result changes incompatibly; new methods and new
+//! result fields don't bump it.
+
+mod session;
+
+pub use session::Api;
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// A method that was understood but couldn't be carried out
+pub const REQUEST_FAILED: i64 = -32000;
+
+/// A JSON-RPC error object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self::new(REQUEST_FAILED, message)
+    }
+}
+
+/// Read one framed message body, `None` at the end of the input. Headers
+/// other than `Content-Length`, such as `Content-Type`, are ignored.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return match length {
+                None => Ok(None),
+                Some(_) => Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            // Blank lines between messages
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = Some(value.trim().parse::<usize>().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Bad Content-Length: {}", value.trim()),
+                    )
+                })?);
+            }
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or_default()];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write `body` as one framed message
+pub fn write_message(writer: &mut impl Write, body: &Value) -> io::Result<()> {
+    let text = body.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{text}", text.len())?;
+    writer.flush()
+}
+
+static NEXT_PEER: AtomicU64 = AtomicU64::new(1);
+
+/// One connected client: where its responses and notifications go
+#[derive(Clone)]
+pub struct Peer {
+    id: u64,
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl Peer {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            id: NEXT_PEER.fetch_add(1, Ordering::Relaxed),
+            out: Arc::new(Mutex::new(out)),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Send a message; false once the client has gone away
+    pub fn send(&self, body: &Value) -> bool {
+        let mut out = self.out.lock().unwrap();
+        write_message(&mut *out, body).is_ok()
+    }
+
+    pub fn respond(&self, id: Value, result: Result<Value, RpcError>) -> bool {
+        self.send(&response(id, result))
+    }
+
+    pub fn notify(&self, method: &str, params: Value) -> bool {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_message_framing() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({"jsonrpc": "2.0", "method": "status"})).unwrap();
+        write_message(&mut buffer, &json!({"é": 1})).unwrap();
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.starts_with("Content-Length: 35\r\n\r\n{"), "{text}");
+
+        let mut reader = Cursor::new(buffer);
+        let first: Value =
+            serde_json::from_str(&read_message(&mut reader).unwrap().unwrap()).unwrap();
+        assert_eq!(first["method"], "status");
+        // Content-Length counts bytes, not characters
+        let second = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(second, r#"{"é":1}"#);
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_message_errors() {
+        let mut truncated = Cursor::new(b"Content-Length: 10\r\n\r\n{}".to_vec());
+        assert!(read_message(&mut truncated).is_err());
+
+        let mut bad_length = Cursor::new(b"Content-Length: ten\r\n\r\n".to_vec());
+        assert!(read_message(&mut bad_length).is_err());
+
+        let mut headers_only = Cursor::new(b"Content-Type: x\r\n".to_vec());
+        assert_eq!(read_message(&mut headers_only).unwrap(), None);
+    }
+}
@@ -0,0 +1,544 @@
+//! The `serve-api` methods and the state they share across connections:
+//! the dev server the API started, running builds and the log backlog.
+//!
+//! Builds and the dev server run as `wasmrun` child processes, so their
+//! output can't interleave with the protocol on stdout; every line they
+//! print is kept in the backlog and sent to subscribers as a `log`
+//! notification.
+
+use super::{
+    read_message, Peer, RpcError, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+    PROTOCOL_VERSION,
+};
+use crate::compiler::artifacts::{BuildManifest, BUILD_MANIFEST_FILE};
+use crate::error::{Result, WasmrunError};
+use crate::server;
+use crate::utils::ModuleDetails;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Log lines kept for `logs/subscribe` to replay
+const LOG_BACKLOG: usize = 1000;
+
+const DEFAULT_PORT: u16 = 8420;
+
+/// Every method, as listed by `initialize`
+const METHODS: [&str; 9] = [
+    "initialize",
+    "status",
+    "build",
+    "run",
+    "stop",
+    "inspect",
+    "logs/subscribe",
+    "logs/unsubscribe",
+    "shutdown",
+];
+
+/// The dev server started by `run`
+struct App {
+    child: Child,
+    path: String,
+    port: u16,
+}
+
+pub struct Api {
+    exe: PathBuf,
+    app: Mutex<Option<App>>,
+    builds: AtomicUsize,
+    subscribers: Mutex<Vec<Peer>>,
+    backlog: Mutex<VecDeque<Value>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BuildParams {
+    path: String,
+    output: Option<String>,
+    optimization: Option<String>,
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RunParams {
+    path: String,
+    port: Option<u16>,
+    #[serde(default)]
+    watch: bool,
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InspectParams {
+    path: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct SubscribeParams {
+    /// How many past lines to return
+    #[serde(default)]
+    backlog: usize,
+}
+
+impl Api {
+    pub fn new() -> Result<Arc<Self>> {
+        let exe = std::env::current_exe()
+            .map_err(|e| WasmrunError::add_context("Locating the wasmrun binary", e))?;
+        Ok(Arc::new(Self::with_exe(exe)))
+    }
+
+    fn with_exe(exe: PathBuf) -> Self {
+        Self {
+            exe,
+            app: Mutex::new(None),
+            builds: AtomicUsize::new(0),
+            subscribers: Mutex::new(Vec::new()),
+            backlog: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Answer one client's messages until it disconnects or sends `shutdown`
+    pub fn serve(self: &Arc<Self>, mut reader: impl BufRead, peer: Peer) -> io::Result<()> {
+        let result = loop {
+            match read_message(&mut reader) {
+                Ok(Some(text)) => {
+                    if !self.dispatch(&text, &peer) {
+                        break Ok(());
+                    }
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.unsubscribe(&peer);
+        result
+    }
+
+    /// Handle one message; false after `shutdown`
+    fn dispatch(self: &Arc<Self>, text: &str, peer: &Peer) -> bool {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => {
+                peer.respond(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())));
+                return true;
+            }
+        };
+        let id = message.get("id").cloned();
+        let Some(method) = message
+            .get("method")
+            .and_then(Value::as_str)
+            .filter(|_| message["jsonrpc"] == "2.0")
+        else {
+            peer.respond(
+                id.unwrap_or(Value::Null),
+                Err(RpcError::new(
+                    INVALID_REQUEST,
+                    "Expected a JSON-RPC 2.0 request",
+                )),
+            );
+            return true;
+        };
+        let params = message.get("params").cloned().unwrap_or(json!({}));
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "version": env!("CARGO_PKG_VERSION"),
+                "methods": METHODS,
+            })),
+            "status" => Ok(self.status()),
+            "build" => match parse_params(params) {
+                // Answered from the build's thread when it finishes
+                Ok(params) => return self.start_build(params, id, peer),
+                Err(e) => Err(e),
+            },
+            "run" => parse_params(params).and_then(|params| self.run(params)),
+            "stop" => Ok(json!({ "stopped": self.stop() })),
+            "inspect" => parse_params(params).and_then(inspect),
+            "logs/subscribe" => {
+                parse_params::<SubscribeParams>(params).map(|params| self.subscribe(peer, params))
+            }
+            "logs/unsubscribe" => {
+                self.unsubscribe(peer);
+                Ok(Value::Null)
+            }
+            "shutdown" => {
+                self.stop_app();
+                if let Some(id) = id {
+                    peer.respond(id, Ok(Value::Null));
+                }
+                return false;
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method: {method}"),
+            )),
+        };
+
+        // Notifications get no response
+        if let Some(id) = id {
+            peer.respond(id, result);
+        }
+        true
+    }
+
+    fn status(&self) -> Value {
+        let app = self.app.lock().unwrap().as_mut().map(|app| {
+            let exit_code = app.child.try_wait().ok().flatten().map(|s| s.code());
+            json!({
+                "pid": app.child.id(),
+                "path": app.path,
+                "port": app.port,
+                "url": format!("http://localhost:{}", app.port),
+                "running": exit_code.is_none(),
+                "exitCode": exit_code.flatten(),
+            })
+        });
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "version": env!("CARGO_PKG_VERSION"),
+            "builds": self.builds.load(Ordering::Relaxed),
+            "serverRunning": server::is_server_running(),
+            "app": app,
+        })
+    }
+
+    /// Run `wasmrun compile` in the background, answering `id` when done
+    fn start_build(self: &Arc<Self>, params: BuildParams, id: Option<Value>, peer: &Peer) -> bool {
+        let output = params.output.clone().unwrap_or_else(|| ".".to_string());
+        let mut args = vec![
+            "compile".to_string(),
+            params.path.clone(),
+            "--output".to_string(),
+            output.clone(),
+        ];
+        if let Some(optimization) = params.optimization {
+            args.extend(["--optimization".to_string(), optimization]);
+        }
+        if let Some(language) = params.language {
+            args.extend(["--language".to_string(), language]);
+        }
+
+        self.builds.fetch_add(1, Ordering::Relaxed);
+        let api = Arc::clone(self);
+        let peer = peer.clone();
+        thread::spawn(move || {
+            let result = api
+                .run_to_end("build", &args)
+                .map_err(|e| RpcError::failed(format!("Failed to start the build: {e}")))
+                .map(|status| {
+                    let manifest =
+                        std::fs::read_to_string(Path::new(&output).join(BUILD_MANIFEST_FILE))
+                            .ok()
+                            .and_then(|text| serde_json::from_str::<BuildManifest>(&text).ok())
+                            .filter(|_| status.success());
+                    json!({
+                        "success": status.success(),
+                        "exitCode": status.code(),
+                        "output": output,
+                        "manifest": manifest,
+                    })
+                });
+            api.builds.fetch_sub(1, Ordering::Relaxed);
+            if let Some(id) = id {
+                peer.respond(id, result);
+            }
+        });
+        true
+    }
+
+    /// Start `wasmrun run` for a project, unless the API's server is up
+    fn run(self: &Arc<Self>, params: RunParams) -> std::result::Result<Value, RpcError> {
+        let mut app = self.app.lock().unwrap();
+        if let Some(running) = app.as_mut() {
+            if matches!(running.child.try_wait(), Ok(None)) {
+                return Err(RpcError::failed(format!(
+                    "{} is already being served on port {}; call stop first",
+                    running.path, running.port
+                )));
+            }
+        }
+
+        let port = params.port.unwrap_or(DEFAULT_PORT);
+        let mut args = vec![
+            "run".to_string(),
+            params.path.clone(),
+            "--port".to_string(),
+            port.to_string(),
+        ];
+        if params.watch {
+            args.push("--watch".to_string());
+        }
+        if let Some(language) = params.language {
+            args.extend(["--language".to_string(), language]);
+        }
+        let (child, _) = self
+            .spawn("run", &args)
+            .map_err(|e| RpcError::failed(format!("Failed to start the dev server: {e}")))?;
+
+        let result = json!({
+            "pid": child.id(),
+            "port": port,
+            "url": format!("http://localhost:{port}"),
+        });
+        *app = Some(App {
+            child,
+            path: params.path,
+            port,
+        });
+        Ok(result)
+    }
+
+    /// Stop the dev server, whether the API started it or not. True if
+    /// there was one to stop.
+    fn stop(self: &Arc<Self>) -> bool {
+        let recorded = server::is_server_running();
+        if recorded {
+            // `wasmrun stop` also clears the PID file and any Unix socket
+            let _ = self.run_to_end("stop", &["stop".to_string()]);
+        }
+        self.stop_app() || recorded
+    }
+
+    /// Kill the dev server the API started, if it is still running
+    pub fn stop_app(&self) -> bool {
+        let Some(mut app) = self.app.lock().unwrap().take() else {
+            return false;
+        };
+        let running = matches!(app.child.try_wait(), Ok(None));
+        let _ = app.child.kill();
+        let _ = app.child.wait();
+        running
+    }
+
+    fn subscribe(&self, peer: &Peer, params: SubscribeParams) -> Value {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if !subscribers.iter().any(|p| p.id() == peer.id()) {
+            subscribers.push(peer.clone());
+        }
+        let backlog = self.backlog.lock().unwrap();
+        let skip = backlog.len().saturating_sub(params.backlog);
+        json!({ "lines": backlog.iter().skip(skip).collect::<Vec<_>>() })
+    }
+
+    fn unsubscribe(&self, peer: &Peer) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|p| p.id() != peer.id());
+    }
+
+    /// Record a line of child output and send it to subscribers
+    fn publish(&self, source: &str, stream: &str, line: String) {
+        let params = json!({
+            "source": source,
+            "stream": stream,
+            "line": line,
+            "time": chrono::Utc::now().to_rfc3339(),
+        });
+        {
+            let mut backlog = self.backlog.lock().unwrap();
+            if backlog.len() == LOG_BACKLOG {
+                backlog.pop_front();
+            }
+            backlog.push_back(params.clone());
+        }
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|peer| peer.notify("log", params.clone()));
+    }
+
+    /// Run `wasmrun <args>` to completion, publishing all of its output
+    /// before returning
+    fn run_to_end(
+        self: &Arc<Self>,
+        source: &'static str,
+        args: &[String],
+    ) -> io::Result<ExitStatus> {
+        let (mut child, readers) = self.spawn(source, args)?;
+        let status = child.wait()?;
+        for reader in readers {
+            let _ = reader.join();
+        }
+        Ok(status)
+    }
+
+    /// Start `wasmrun <args>` with its output going to [`Self::publish`]
+    /// from a thread per stream
+    fn spawn(
+        self: &Arc<Self>,
+        source: &'static str,
+        args: &[String],
+    ) -> io::Result<(Child, Vec<JoinHandle<()>>)> {
+        let mut child = Command::new(&self.exe)
+            .arg("--no-color")
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let pipes: [(&'static str, Option<Box<dyn Read + Send>>); 2] = [
+            ("stdout", child.stdout.take().map(|p| Box::new(p) as _)),
+            ("stderr", child.stderr.take().map(|p| Box::new(p) as _)),
+        ];
+        let readers = pipes
+            .into_iter()
+            .filter_map(|(stream, pipe)| Some((stream, pipe?)))
+            .map(|(stream, pipe)| {
+                let api = Arc::clone(self);
+                thread::spawn(move || {
+                    for line in BufReader::new(pipe).lines().map_while(io::Result::ok) {
+                        api.publish(source, stream, line);
+                    }
+                })
+            })
+            .collect();
+        Ok((child, readers))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn inspect(params: InspectParams) -> std::result::Result<Value, RpcError> {
+    // Read, not mapped: a build this API started can rewrite the module
+    // while it's parsed
+    let bytes = std::fs::read(&params.path)
+        .map_err(|e| RpcError::failed(format!("Reading {}: {e}", params.path)))?;
+    let details = ModuleDetails::from_bytes(&bytes).map_err(RpcError::failed)?;
+    serde_json::to_value(details).map_err(|e| RpcError::failed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::write_message;
+    use std::io::{Cursor, Write};
+
+    /// A writer tests can read back
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Sink {
+        fn messages(&self) -> Vec<Value> {
+            let mut reader = Cursor::new(self.0.lock().unwrap().clone());
+            std::iter::from_fn(|| read_message(&mut reader).unwrap())
+                .map(|text| serde_json::from_str(&text).unwrap())
+                .collect()
+        }
+    }
+
+    fn session(requests: &[Value]) -> Vec<Value> {
+        let mut input = Vec::new();
+        for request in requests {
+            write_message(&mut input, request).unwrap();
+        }
+        let sink = Sink::default();
+        let api = Arc::new(Api::with_exe(PathBuf::from("/nonexistent/wasmrun")));
+        api.serve(Cursor::new(input), Peer::new(Box::new(sink.clone())))
+            .unwrap();
+        sink.messages()
+    }
+
+    #[test]
+    fn test_initialize_and_errors() {
+        let replies = session(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "frobnicate"}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "inspect", "params": {"file": "x"}}),
+            json!({"id": 4, "method": "status"}),
+            // A notification: no reply
+            json!({"jsonrpc": "2.0", "method": "logs/unsubscribe"}),
+            json!({"jsonrpc": "2.0", "id": 5, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "id": 6, "method": "status"}),
+        ]);
+
+        assert_eq!(replies.len(), 5, "{replies:?}");
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(replies[0]["result"]["methods"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("build")));
+        assert_eq!(replies[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(replies[2]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(replies[3]["error"]["code"], INVALID_REQUEST);
+        // Nothing is answered after shutdown
+        assert_eq!(
+            replies[4],
+            json!({"jsonrpc": "2.0", "id": 5, "result": null})
+        );
+    }
+
+    #[test]
+    fn test_inspect_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = dir.path().join("empty.wasm");
+        std::fs::write(&wasm, [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00]).unwrap();
+
+        let replies = session(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "inspect", "params": {"path": wasm}}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "inspect", "params": {"path": dir.path().join("missing.wasm")}}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "status"}),
+        ]);
+        assert_eq!(replies[0]["result"]["version"], 1);
+        assert_eq!(replies[0]["result"]["exports"], json!([]));
+        assert_eq!(replies[1]["error"]["code"], super::super::REQUEST_FAILED);
+        assert_eq!(replies[2]["result"]["app"], Value::Null);
+        assert_eq!(replies[2]["result"]["builds"], 0);
+    }
+
+    #[test]
+    fn test_log_backlog_and_subscribers() {
+        let api = Api::with_exe(PathBuf::from("/nonexistent/wasmrun"));
+        for n in 0..LOG_BACKLOG + 5 {
+            api.publish("build", "stdout", format!("line {n}"));
+        }
+
+        let sink = Sink::default();
+        let peer = Peer::new(Box::new(sink.clone()));
+        let replay = api.subscribe(&peer, SubscribeParams { backlog: 2 });
+        let lines: Vec<&str> = replay["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|line| line["line"].as_str().unwrap())
+            .collect();
+        assert_eq!(lines, ["line 1003", "line 1004"]);
+        assert_eq!(api.backlog.lock().unwrap().len(), LOG_BACKLOG);
+
+        api.publish("run", "stderr", "served".to_string());
+        api.unsubscribe(&peer);
+        api.publish("run", "stderr", "unheard".to_string());
+        let notes = sink.messages();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0]["method"], "log");
+        assert_eq!(notes[0]["params"]["source"], "run");
+        assert_eq!(notes[0]["params"]["line"], "served");
+    }
+}
@@ -0,0 +1,817 @@
+//! Embedded JS engine for `wasmrun exec --bindgen`.
+//!
+//! The generated JS glue runs in an embedded JavaScript engine (Boa). A
+//! prelude gives it `WebAssembly`, `TextEncoder`/`TextDecoder`, `console` and,
+//! for `--target nodejs` glue, a small `require`. `WebAssembly.Instance` runs
+//! the module on wasmrun's own interpreter. The instance's linear memory is
+//! mirrored into an `ArrayBuffer` and copied across at every call between JS
+//! and wasm, which is slow but keeps the glue's memory views valid.
+
+use super::{glue_kind, GlueKind};
+use crate::error::{Result, WasmrunError};
+use crate::runtime::core::executor::Executor;
+use crate::runtime::core::linker::{ClosureHostFunction, Linker};
+use crate::runtime::core::memory::LinearMemory;
+use crate::runtime::core::module::{ExportKind, FunctionType, ImportKind, Module, ValueType};
+use crate::runtime::core::panic::PanicReport;
+use crate::runtime::core::values::Value;
+use boa_engine::builtins::promise::PromiseState;
+use boa_engine::module::SimpleModuleLoader;
+use boa_engine::object::builtins::{JsArrayBuffer, JsPromise};
+use boa_engine::{
+    js_string, Context, JsArgs, JsBigInt, JsError, JsNativeError, JsObject, JsResult, JsString,
+    JsValue, NativeFunction, Source,
+};
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+const PRELUDE: &str = include_str!("prelude.js");
+
+/// Global the prelude keeps the instance's memory `ArrayBuffer` in
+const BUFFER_GLOBAL: &str = "__wasmrunBuffer";
+/// Global the prelude keeps the instance's import functions in
+const IMPORTS_GLOBAL: &str = "__wasmrunImports";
+/// Tail of `console.error` output kept for finding a panic message
+const MAX_KEPT_STDERR: usize = 64 * 1024;
+
+#[derive(Default)]
+struct BindgenState {
+    /// Compiled modules with their bytes, which are parsed again for each
+    /// instance
+    modules: Vec<(Vec<u8>, Module)>,
+    /// `None` before instantiation and while an export runs
+    instance: Option<Executor>,
+    /// What the glue wrote to `console.error`, where panic hooks print
+    stderr: String,
+}
+
+thread_local! {
+    static STATE: RefCell<BindgenState> = RefCell::default();
+    /// The engine while an export runs, so imports can call back into JS
+    static RUNNING_CONTEXT: Cell<*mut Context> = const { Cell::new(std::ptr::null_mut()) };
+}
+
+/// Load the glue for `wasm_path`, initialize the module, and call the glue's
+/// `call` export with `args` if given, printing what it returns
+pub fn run_bindgen(
+    wasm_path: &Path,
+    glue_path: &Path,
+    call: Option<&str>,
+    args: &[String],
+) -> Result<()> {
+    let source = fs::read_to_string(glue_path).map_err(|e| {
+        WasmrunError::from(format!(
+            "Failed to read JS glue '{}': {e}",
+            glue_path.display()
+        ))
+    })?;
+    let kind = glue_kind(&source).map_err(WasmrunError::from)?;
+    let glue_path = glue_path
+        .canonicalize()
+        .map_err(|e| WasmrunError::from(format!("{}: {e}", glue_path.display())))?;
+    let wasm_path = wasm_path
+        .canonicalize()
+        .map_err(|e| WasmrunError::from(format!("{}: {e}", wasm_path.display())))?;
+
+    STATE.with(|state| state.replace(BindgenState::default()));
+    let result = run_glue(&glue_path, &wasm_path, kind, call, args);
+    let state = STATE.with(|state| state.replace(BindgenState::default()));
+    result.map_err(|e| {
+        let error = e.to_string();
+        match PanicReport::detect(&error, &state.stderr, &[]) {
+            Some(panic) => WasmrunError::from(format!("{panic}\n(trapped with: {error})")),
+            None => e,
+        }
+    })
+}
+
+fn run_glue(
+    glue_path: &Path,
+    wasm_path: &Path,
+    kind: GlueKind,
+    call: Option<&str>,
+    args: &[String],
+) -> Result<()> {
+    let glue_dir = glue_path.parent().unwrap_or(Path::new("/"));
+    let loader = Rc::new(SimpleModuleLoader::new(glue_dir).map_err(js_setup_error)?);
+    let mut context = Context::builder()
+        .module_loader(loader.clone())
+        .build()
+        .map_err(js_setup_error)?;
+    let ctx = &mut context;
+    register_natives(ctx).map_err(js_setup_error)?;
+    ctx.eval(Source::from_bytes(PRELUDE))
+        .map_err(js_setup_error)?;
+
+    let exports = match kind {
+        GlueKind::EsModule { init_takes_object } => {
+            let source = Source::from_filepath(glue_path)
+                .map_err(|e| WasmrunError::from(format!("{}: {e}", glue_path.display())))?;
+            let module = boa_engine::Module::parse(source, None, ctx)
+                .map_err(|e| js_error("Failed to parse the JS glue", e, ctx))?;
+            loader.insert(glue_path.to_path_buf(), module.clone());
+            let loaded = module.load_link_evaluate(ctx);
+            settle(loaded, ctx).map_err(|e| js_error("Failed to load the JS glue", e, ctx))?;
+            let namespace = module.namespace(ctx);
+
+            let init = namespace
+                .get(js_string!("initSync"), ctx)
+                .map_err(|e| js_error("Failed to load the JS glue", e, ctx))?;
+            let wasm_module = call_global("__wasmrunCompileFile", &[path_value(wasm_path)], ctx)
+                .map_err(|e| js_error("Failed to compile the module", e, ctx))?;
+            let init_arg = if init_takes_object {
+                let options = JsObject::with_object_proto(ctx.intrinsics());
+                options
+                    .set(js_string!("module"), wasm_module, false, ctx)
+                    .map_err(|e| js_error("Failed to initialize the module", e, ctx))?;
+                options.into()
+            } else {
+                wasm_module
+            };
+            call_value(&init, &[init_arg], ctx)
+                .map_err(|e| js_error("Failed to initialize the module", e, ctx))?;
+            JsValue::from(namespace)
+        }
+        GlueKind::CommonJs => call_global("__wasmrunRequire", &[path_value(glue_path)], ctx)
+            .map_err(|e| js_error("Failed to load the JS glue", e, ctx))?,
+    };
+    ctx.run_jobs();
+
+    let Some(name) = call else {
+        return Ok(());
+    };
+    let function = exports
+        .as_object()
+        .map(|exports| exports.get(JsString::from(name), ctx))
+        .transpose()
+        .map_err(|e| js_error("Failed to read the glue's exports", e, ctx))?
+        .filter(JsValue::is_callable)
+        .ok_or_else(|| {
+            WasmrunError::from(format!("The JS glue doesn't export a function '{name}'"))
+        })?;
+    let js_args: Vec<JsValue> = args.iter().map(|arg| js_argument(arg)).collect();
+    let mut result = call_value(&function, &js_args, ctx).map_err(|e| js_error(name, e, ctx))?;
+    if let Some(promise) = result.as_promise().cloned() {
+        let promise = JsPromise::from_object(promise).map_err(|e| js_error(name, e, ctx))?;
+        result = settle(promise, ctx).map_err(|e| js_error(name, e, ctx))?;
+    }
+    ctx.run_jobs();
+    if !result.is_undefined() {
+        let text = call_global("__wasmrunFormat", &[result], ctx)
+            .and_then(|v| v.to_string(ctx))
+            .map_err(|e| js_error(name, e, ctx))?;
+        println!("{}", text.to_std_string_escaped());
+    }
+    Ok(())
+}
+
+/// Run jobs until `promise` settles
+fn settle(promise: JsPromise, ctx: &mut Context) -> JsResult<JsValue> {
+    ctx.run_jobs();
+    match promise.state() {
+        PromiseState::Fulfilled(value) => Ok(value),
+        PromiseState::Rejected(reason) => Err(JsError::from_opaque(reason)),
+        PromiseState::Pending => Err(JsNativeError::error()
+            .with_message("promise never settled")
+            .into()),
+    }
+}
+
+/// A CLI argument as a JS number when it is one, else a string
+fn js_argument(arg: &str) -> JsValue {
+    match arg.parse::<f64>() {
+        Ok(number) => JsValue::from(number),
+        Err(_) => JsValue::from(JsString::from(arg)),
+    }
+}
+
+fn path_value(path: &Path) -> JsValue {
+    JsValue::from(JsString::from(path.to_string_lossy().as_ref()))
+}
+
+fn call_value(function: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let function = function
+        .as_callable()
+        .ok_or_else(|| JsError::from(JsNativeError::typ().with_message("not a function")))?;
+    function.call(&JsValue::undefined(), args, ctx)
+}
+
+fn call_global(name: &str, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let function = ctx.global_object().get(JsString::from(name), ctx)?;
+    call_value(&function, args, ctx)
+}
+
+fn js_setup_error(e: JsError) -> WasmrunError {
+    WasmrunError::from(format!("Failed to start the JavaScript engine: {e}"))
+}
+
+/// `what` failed with the thrown `e`, shown as `Name: message`
+fn js_error(what: &str, e: JsError, ctx: &mut Context) -> WasmrunError {
+    let thrown = e.to_opaque(ctx);
+    let message = thrown
+        .as_object()
+        .and_then(|error| {
+            let name = error
+                .get(js_string!("name"), ctx)
+                .ok()?
+                .to_string(ctx)
+                .ok()?;
+            let message = error
+                .get(js_string!("message"), ctx)
+                .ok()?
+                .to_string(ctx)
+                .ok()?;
+            Some(format!(
+                "{}: {}",
+                name.to_std_string_escaped(),
+                message.to_std_string_escaped()
+            ))
+        })
+        .unwrap_or_else(|| thrown.display().to_string());
+    WasmrunError::from(format!("{what}: {message}"))
+}
+
+fn type_error(message: impl Into<String>) -> JsError {
+    JsNativeError::typ().with_message(message.into()).into()
+}
+
+fn arg_string(args: &[JsValue], index: usize, ctx: &mut Context) -> JsResult<String> {
+    Ok(args
+        .get_or_undefined(index)
+        .to_string(ctx)?
+        .to_std_string_escaped())
+}
+
+fn arg_u32(args: &[JsValue], index: usize, ctx: &mut Context) -> JsResult<u32> {
+    args.get_or_undefined(index).to_u32(ctx)
+}
+
+fn register_natives(ctx: &mut Context) -> JsResult<()> {
+    let natives: [(&str, usize, NativeFunction); 11] = [
+        ("__wasmrun_print", 2, NativeFunction::from_fn_ptr(print)),
+        (
+            "__wasmrun_read_file",
+            1,
+            NativeFunction::from_fn_ptr(read_file),
+        ),
+        (
+            "__wasmrun_read_text",
+            1,
+            NativeFunction::from_fn_ptr(read_text),
+        ),
+        (
+            "__wasmrun_utf8_decode",
+            5,
+            NativeFunction::from_fn_ptr(utf8_decode),
+        ),
+        ("__wasmrun_compile", 3, NativeFunction::from_fn_ptr(compile)),
+        (
+            "__wasmrun_module_info",
+            1,
+            NativeFunction::from_fn_ptr(module_info),
+        ),
+        (
+            "__wasmrun_instantiate",
+            1,
+            NativeFunction::from_fn_ptr(instantiate),
+        ),
+        (
+            "__wasmrun_call",
+            1,
+            NativeFunction::from_fn_ptr(call_export),
+        ),
+        (
+            "__wasmrun_memory_grow",
+            1,
+            NativeFunction::from_fn_ptr(memory_grow),
+        ),
+        ("__wasmrun_table", 4, NativeFunction::from_fn_ptr(table_op)),
+        (
+            "__wasmrun_global",
+            1,
+            NativeFunction::from_fn_ptr(global_value),
+        ),
+    ];
+    for (name, length, function) in natives {
+        ctx.register_global_callable(JsString::from(name), length, function)?;
+    }
+    Ok(())
+}
+
+fn print(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let text = arg_string(args, 1, ctx)?;
+    if arg_u32(args, 0, ctx)? == 2 {
+        eprintln!("{text}");
+        STATE.with(|state| {
+            let stderr = &mut state.borrow_mut().stderr;
+            stderr.push_str(&text);
+            stderr.push('\n');
+            if stderr.len() > MAX_KEPT_STDERR {
+                let mut cut = stderr.len() - MAX_KEPT_STDERR;
+                while !stderr.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                stderr.drain(..cut);
+            }
+        });
+    } else {
+        println!("{text}");
+    }
+    Ok(JsValue::undefined())
+}
+
+fn read_file(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let path = arg_string(args, 0, ctx)?;
+    let bytes = fs::read(&path).map_err(|e| type_error(format!("{path}: {e}")))?;
+    Ok(JsArrayBuffer::from_byte_block(bytes, ctx)?.into())
+}
+
+fn read_text(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let path = arg_string(args, 0, ctx)?;
+    let text = fs::read_to_string(&path).map_err(|e| type_error(format!("{path}: {e}")))?;
+    Ok(JsString::from(text.as_str()).into())
+}
+
+/// Bytes `offset..offset + length` of the `ArrayBuffer` in `args[index]`
+fn buffer_bytes(args: &[JsValue], index: usize, ctx: &mut Context) -> JsResult<Vec<u8>> {
+    let object = args
+        .get_or_undefined(index)
+        .as_object()
+        .cloned()
+        .ok_or_else(|| type_error("expected an ArrayBuffer"))?;
+    let buffer = JsArrayBuffer::from_object(object)?;
+    let offset = arg_u32(args, index + 1, ctx)? as usize;
+    let length = arg_u32(args, index + 2, ctx)? as usize;
+    let data = buffer
+        .data()
+        .ok_or_else(|| type_error("ArrayBuffer is detached"))?;
+    data.get(offset..offset + length)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| type_error("range outside the ArrayBuffer"))
+}
+
+fn utf8_decode(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let bytes = buffer_bytes(args, 0, ctx)?;
+    let fatal = args.get_or_undefined(3).to_boolean();
+    let ignore_bom = args.get_or_undefined(4).to_boolean();
+    let text = if fatal {
+        String::from_utf8(bytes).map_err(|_| type_error("The encoded data was not valid UTF-8"))?
+    } else {
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    let text = if ignore_bom {
+        text.as_str()
+    } else {
+        text.strip_prefix('\u{feff}').unwrap_or(&text)
+    };
+    Ok(JsString::from(text).into())
+}
+
+fn compile(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let bytes = buffer_bytes(args, 0, ctx)?;
+    let module = Module::parse(&bytes).map_err(|e| {
+        JsError::from(JsNativeError::syntax().with_message(format!("Invalid module: {e}")))
+    })?;
+    let id = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.modules.push((bytes, module));
+        state.modules.len() - 1
+    });
+    Ok(JsValue::from(id as u32))
+}
+
+fn kind_name(kind: &ExportKind) -> &'static str {
+    match kind {
+        ExportKind::Function => "function",
+        ExportKind::Table => "table",
+        ExportKind::Memory => "memory",
+        ExportKind::Global => "global",
+    }
+}
+
+fn with_module<T>(id: u32, f: impl FnOnce(&Module) -> T) -> JsResult<T> {
+    STATE.with(|state| {
+        state
+            .borrow()
+            .modules
+            .get(id as usize)
+            .map(|(_, module)| f(module))
+            .ok_or_else(|| type_error(format!("unknown module {id}")))
+    })
+}
+
+/// Imports and exports of a compiled module, as JSON
+fn module_info(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let id = arg_u32(args, 0, ctx)?;
+    let info = with_module(id, |module| {
+        let imports: Vec<_> = module
+            .imports
+            .iter()
+            .map(|import| {
+                let kind = match import.kind {
+                    ImportKind::Function(_) => "function",
+                    ImportKind::Table(_) => "table",
+                    ImportKind::Memory(_) => "memory",
+                    ImportKind::Global(_) => "global",
+                };
+                serde_json::json!({ "module": import.module, "name": import.name, "kind": kind })
+            })
+            .collect();
+        let mut exports: Vec<_> = module.exports.values().collect();
+        exports.sort_by(|a, b| a.name.cmp(&b.name));
+        let exports: Vec<_> = exports
+            .into_iter()
+            .map(|export| {
+                serde_json::json!({
+                    "name": export.name,
+                    "kind": kind_name(&export.kind),
+                    "index": export.index,
+                })
+            })
+            .collect();
+        serde_json::json!({ "imports": imports, "exports": exports }).to_string()
+    })?;
+    Ok(JsString::from(info.as_str()).into())
+}
+
+/// Create the instance, with each function import calling the JS function
+/// at the same position in the import list
+fn instantiate(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let id = arg_u32(args, 0, ctx)?;
+    let bytes = STATE.with(|state| {
+        state
+            .borrow()
+            .modules
+            .get(id as usize)
+            .map(|(bytes, _)| bytes.clone())
+            .ok_or_else(|| type_error(format!("unknown module {id}")))
+    })?;
+    let module = Module::parse(&bytes).map_err(type_error)?;
+
+    let mut linker = Linker::new();
+    let mut function_imports = 0;
+    for import in &module.imports {
+        let ImportKind::Function(type_idx) = import.kind else {
+            continue;
+        };
+        let func_type = module
+            .types
+            .get(type_idx as usize)
+            .cloned()
+            .ok_or_else(|| type_error(format!("bad type index {type_idx}")))?;
+        let slot = function_imports;
+        function_imports += 1;
+        let params = func_type.params.len();
+        let results = func_type.results.len();
+        linker.register(
+            &import.module,
+            &import.name,
+            Box::new(ClosureHostFunction::new(
+                move |args: Vec<Value>, memory: &mut LinearMemory| {
+                    call_import(slot, &func_type.results, args, memory)
+                },
+                params,
+                results,
+            )),
+        );
+    }
+
+    let mut executor = Executor::new_with_linker(module, linker)
+        .map_err(|e| JsError::from(JsNativeError::error().with_message(e)))?;
+    publish_memory(&executor.context().memory, ctx)?;
+    if let Some(start) = executor.module().start {
+        run_wasm(&mut executor, ctx, |executor| executor.execute(start))?;
+    }
+    STATE.with(|state| state.borrow_mut().instance = Some(executor));
+    Ok(JsValue::undefined())
+}
+
+/// Call JS import `slot` from inside the interpreter
+fn call_import(
+    slot: usize,
+    result_types: &[ValueType],
+    args: Vec<Value>,
+    memory: &mut LinearMemory,
+) -> std::result::Result<Vec<Value>, String> {
+    let context = RUNNING_CONTEXT.with(Cell::get);
+    if context.is_null() {
+        return Err("JS import called outside of an export call".to_string());
+    }
+    // SAFETY: `run_wasm` stores the engine's `&mut Context` here only for the
+    // duration of an interpreter call and doesn't touch it until that call
+    // returns, so this is the only live use of it. Imports run synchronously
+    // on the same thread.
+    let ctx = unsafe { &mut *context };
+    let describe = |e: JsError, ctx: &mut Context| {
+        let thrown = e.to_opaque(ctx);
+        let text = thrown
+            .to_string(ctx)
+            .map(|s| s.to_std_string_escaped())
+            .unwrap_or_else(|_| thrown.display().to_string());
+        format!("JS import threw: {text}")
+    };
+    let result = (|| {
+        publish_memory(memory, ctx)?;
+        let imports = ctx.global_object().get(js_string!(IMPORTS_GLOBAL), ctx)?;
+        let function = imports
+            .as_object()
+            .ok_or_else(|| type_error("imports missing"))?
+            .get(slot, ctx)?;
+        let js_args = args
+            .iter()
+            .map(|value| to_js(value, ctx))
+            .collect::<JsResult<Vec<_>>>()?;
+        let returned = call_value(&function, &js_args, ctx)?;
+        pull_memory(memory, ctx)?;
+        match result_types {
+            [] => Ok(Vec::new()),
+            [ty] => Ok(vec![from_js(&returned, ty, ctx)?]),
+            types => {
+                let values = returned
+                    .as_object()
+                    .ok_or_else(|| type_error("expected an array of results"))?;
+                types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| from_js(&values.get(i, ctx)?, ty, ctx))
+                    .collect()
+            }
+        }
+    })();
+    result.map_err(|e| describe(e, ctx))
+}
+
+/// Run `f` on the interpreter with `ctx` reachable from imports
+fn run_wasm<T>(
+    executor: &mut Executor,
+    ctx: &mut Context,
+    f: impl FnOnce(&mut Executor) -> std::result::Result<T, String>,
+) -> JsResult<T> {
+    pull_memory(&mut executor.context_mut().memory, ctx)?;
+    let previous = RUNNING_CONTEXT.with(|c| c.replace(ctx as *mut Context));
+    let result = f(executor);
+    RUNNING_CONTEXT.with(|c| c.set(previous));
+    publish_memory(&executor.context().memory, ctx)?;
+    result.map_err(|e| {
+        let message = match Executor::is_proc_exit(&e) {
+            Some(code) => format!("the module exited with code {code}"),
+            None => e,
+        };
+        JsError::from(JsNativeError::error().with_message(message))
+    })
+}
+
+/// Run `f` with the instance, which is taken out for the duration so a JS
+/// import can't re-enter it
+fn with_instance<T>(
+    ctx: &mut Context,
+    f: impl FnOnce(&mut Executor, &mut Context) -> JsResult<T>,
+) -> JsResult<T> {
+    let mut executor = STATE
+        .with(|state| state.borrow_mut().instance.take())
+        .ok_or_else(|| {
+            type_error(
+                "no wasm instance, or a JS import called back into wasm, which the interpreter can't re-enter",
+            )
+        })?;
+    let result = f(&mut executor, ctx);
+    STATE.with(|state| state.borrow_mut().instance = Some(executor));
+    result
+}
+
+fn call_export(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let name = arg_string(args, 0, ctx)?;
+    with_instance(ctx, |executor, ctx| {
+        let module = executor.module();
+        let func_idx = match module.exports.get(&name) {
+            Some(export) if matches!(export.kind, ExportKind::Function) => export.index,
+            _ => return Err(type_error(format!("no exported function '{name}'"))),
+        };
+        let func_type = function_type(module, func_idx)
+            .ok_or_else(|| type_error(format!("no type for function {func_idx}")))?;
+        let wasm_args = func_type
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| from_js(args.get_or_undefined(i + 1), ty, ctx))
+            .collect::<JsResult<Vec<_>>>()?;
+        let results = run_wasm(executor, ctx, |executor| {
+            executor.execute_with_args(func_idx, wasm_args)
+        })?;
+        match results.as_slice() {
+            [] => Ok(JsValue::undefined()),
+            [value] => to_js(value, ctx),
+            values => {
+                let values = values
+                    .iter()
+                    .map(|value| to_js(value, ctx))
+                    .collect::<JsResult<Vec<_>>>()?;
+                Ok(boa_engine::object::builtins::JsArray::from_iter(values, ctx).into())
+            }
+        }
+    })
+}
+
+fn function_type(module: &Module, func_idx: u32) -> Option<FunctionType> {
+    let imported = module
+        .imports
+        .iter()
+        .filter_map(|import| match import.kind {
+            ImportKind::Function(type_idx) => Some(type_idx),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let type_idx = match imported.get(func_idx as usize) {
+        Some(type_idx) => *type_idx,
+        None => {
+            module
+                .functions
+                .get(func_idx as usize - imported.len())?
+                .type_index
+        }
+    };
+    module.types.get(type_idx as usize).cloned()
+}
+
+fn memory_grow(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let pages = arg_u32(args, 0, ctx)?;
+    with_instance(ctx, |executor, ctx| {
+        pull_memory(&mut executor.context_mut().memory, ctx)?;
+        let previous = executor
+            .context_mut()
+            .memory
+            .grow(pages)
+            .map_err(|e| JsError::from(JsNativeError::range().with_message(e)))?;
+        publish_memory(&executor.context().memory, ctx)?;
+        Ok(JsValue::from(previous))
+    })
+}
+
+fn table_op(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let table_idx = arg_u32(args, 0, ctx)?;
+    let op = arg_string(args, 1, ctx)?;
+    with_instance(ctx, |executor, ctx| {
+        let table = executor.table_mut(table_idx).map_err(type_error)?;
+        let element_type = table.element_type;
+        match op.as_str() {
+            "size" => Ok(JsValue::from(table.size())),
+            "get" => {
+                let index = arg_u32(args, 2, ctx)?;
+                let value = table.get(index).map_err(range_error)?;
+                to_js(&value, ctx)
+            }
+            "set" => {
+                let index = arg_u32(args, 2, ctx)?;
+                let value = from_js(args.get_or_undefined(3), &element_type, ctx)?;
+                table.set(index, value).map_err(range_error)?;
+                Ok(JsValue::undefined())
+            }
+            "grow" => {
+                let delta = arg_u32(args, 2, ctx)?;
+                let init = from_js(args.get_or_undefined(3), &element_type, ctx)?;
+                let previous = table.grow(delta, init);
+                if previous < 0 {
+                    return Err(range_error("table can't grow past its maximum".to_string()));
+                }
+                Ok(JsValue::from(previous))
+            }
+            _ => Err(type_error(format!("unknown table operation {op}"))),
+        }
+    })
+}
+
+fn range_error(message: String) -> JsError {
+    JsNativeError::range().with_message(message).into()
+}
+
+fn global_value(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let index = arg_u32(args, 0, ctx)? as usize;
+    with_instance(ctx, |executor, ctx| {
+        let value = *executor
+            .context()
+            .globals
+            .get(index)
+            .ok_or_else(|| type_error(format!("no global {index}")))?;
+        to_js(&value, ctx)
+    })
+}
+
+fn to_js(value: &Value, ctx: &mut Context) -> JsResult<JsValue> {
+    Ok(match value {
+        Value::I32(v) => JsValue::from(*v),
+        Value::I64(v) => JsValue::from(JsBigInt::new(*v)),
+        Value::F32(v) => JsValue::from(*v),
+        Value::F64(v) => JsValue::from(*v),
+        Value::ExternRef(Some(handle)) => {
+            call_global("__wasmrunFromHandle", &[JsValue::from(*handle)], ctx)?
+        }
+        Value::FuncRef(Some(idx)) => JsValue::from(*idx),
+        Value::ExternRef(None) | Value::FuncRef(None) => JsValue::null(),
+    })
+}
+
+fn from_js(value: &JsValue, ty: &ValueType, ctx: &mut Context) -> JsResult<Value> {
+    Ok(match ty {
+        ValueType::I32 => Value::I32(value.to_i32(ctx)?),
+        ValueType::I64 => Value::I64(value.to_big_int64(ctx)?),
+        ValueType::F32 => Value::F32(value.to_number(ctx)? as f32),
+        ValueType::F64 => Value::F64(value.to_number(ctx)?),
+        ValueType::ExternRef => {
+            let handle =
+                call_global("__wasmrunToHandle", std::slice::from_ref(value), ctx)?.to_i32(ctx)?;
+            Value::ExternRef(u32::try_from(handle).ok())
+        }
+        ValueType::FuncRef if value.is_null() || value.is_undefined() => Value::FuncRef(None),
+        ValueType::FuncRef => Value::FuncRef(Some(value.to_u32(ctx)?)),
+        ValueType::V128 => return Err(type_error("v128 values can't cross into JS")),
+    })
+}
+
+fn current_buffer(ctx: &mut Context) -> JsResult<Option<JsArrayBuffer>> {
+    let value = ctx.global_object().get(js_string!(BUFFER_GLOBAL), ctx)?;
+    Ok(value
+        .as_object()
+        .and_then(|object| JsArrayBuffer::from_object(object.clone()).ok()))
+}
+
+/// Copy the interpreter's memory into the JS `ArrayBuffer`. After a grow, the
+/// old buffer is detached so the glue notices and makes new views.
+fn publish_memory(memory: &LinearMemory, ctx: &mut Context) -> JsResult<()> {
+    let bytes = memory
+        .read_bytes(0, memory.size_bytes())
+        .map_err(type_error)?;
+    if let Some(buffer) = current_buffer(ctx)? {
+        if let Some(mut data) = buffer.data_mut() {
+            if data.len() == bytes.len() {
+                data.copy_from_slice(&bytes);
+                return Ok(());
+            }
+        }
+        buffer.detach(&JsValue::undefined())?;
+    }
+    let buffer = JsArrayBuffer::from_byte_block(bytes, ctx)?;
+    ctx.global_object()
+        .set(js_string!(BUFFER_GLOBAL), buffer, false, ctx)?;
+    Ok(())
+}
+
+/// Copy the JS `ArrayBuffer` back into the interpreter's memory
+fn pull_memory(memory: &mut LinearMemory, ctx: &mut Context) -> JsResult<()> {
+    if let Some(buffer) = current_buffer(ctx)? {
+        if let Some(data) = buffer.data() {
+            let len = data.len().min(memory.size_bytes());
+            memory.write_bytes(0, &data[..len]).map_err(type_error)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::bindgen::default_glue_path;
+
+    #[test]
+    fn test_commonjs_glue_calls_into_wasm() {
+        // (func (export "add") (param i32 i32) (result i32)
+        //   local.get 0 local.get 1 i32.add call $log)
+        // with $log imported from "./app_bg.js" as "log" (i32) -> i32
+        let wasm = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+            0x01, 0x0c, 0x02, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x01, 0x7f, 0x01,
+            0x7f, // types
+            0x02, 0x13, 0x01, 0x0b, b'.', b'/', b'a', b'p', b'p', b'_', b'b', b'g', b'.', b'j',
+            b's', 0x03, b'l', b'o', b'g', 0x00, 0x01, // imports
+            0x03, 0x02, 0x01, 0x00, // functions
+            0x05, 0x03, 0x01, 0x00, 0x01, // memory
+            0x07, 0x10, 0x02, 0x03, b'a', b'd', b'd', 0x00, 0x01, 0x06, b'm', b'e', b'm', b'o',
+            b'r', b'y', 0x02, 0x00, // exports
+            0x0a, 0x0b, 0x01, 0x09, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x10, 0x00,
+            0x0b, // code
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app_bg.wasm"), wasm).unwrap();
+        fs::write(
+            dir.path().join("app.js"),
+            r#"
+const imports = { "./app_bg.js": { log: (n) => { globalThis.logged = n; return n * 10; } } };
+const bytes = require("fs").readFileSync(`${__dirname}/app_bg.wasm`);
+const wasm = new WebAssembly.Instance(new WebAssembly.Module(bytes), imports).exports;
+module.exports.add = (a, b) => {
+    const sum = wasm.add(a, b);
+    if (new Uint8Array(wasm.memory.buffer).length !== 65536) throw new Error("no memory");
+    return sum;
+};
+"#,
+        )
+        .unwrap();
+
+        let wasm_path = dir.path().join("app_bg.wasm");
+        let glue = default_glue_path(&wasm_path);
+        run_bindgen(&wasm_path, &glue, Some("add"), &["2".into(), "3".into()]).unwrap();
+        let missing = run_bindgen(&wasm_path, &glue, Some("sub"), &[]);
+        assert!(missing.unwrap_err().to_string().contains("'sub'"));
+    }
+}
@@ -0,0 +1,80 @@
+//! wasm-bindgen glue, for `wasmrun exec --bindgen` and `wasmrun run --runner`
+
+#[cfg(feature = "js-engine")]
+mod engine;
+
+#[cfg(feature = "js-engine")]
+pub use engine::run_bindgen;
+
+use std::path::{Path, PathBuf};
+
+/// How the glue was generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlueKind {
+    /// `--target web`: an ES module with `initSync`. Newer glue takes
+    /// `{ module }` rather than the module itself.
+    EsModule { init_takes_object: bool },
+    /// `--target nodejs`: CommonJS that loads the module itself
+    CommonJs,
+}
+
+pub fn glue_kind(source: &str) -> std::result::Result<GlueKind, String> {
+    if source.contains("_bg.wasm\"") && source.contains("import * as wasm from") {
+        return Err(
+            "The glue was built for --target bundler; rebuild with --target web or --target nodejs"
+                .to_string(),
+        );
+    }
+    let es_module = source
+        .lines()
+        .any(|line| line.starts_with("export ") || line.starts_with("import "));
+    if es_module {
+        if !source.contains("function initSync") {
+            return Err("The glue has no initSync(); rebuild with --target web".to_string());
+        }
+        Ok(GlueKind::EsModule {
+            init_takes_object: source.contains("using deprecated parameters for `initSync()`"),
+        })
+    } else if source.contains("module.exports") || source.contains("exports.") {
+        Ok(GlueKind::CommonJs)
+    } else {
+        Err(
+            "Unrecognized glue; expected wasm-bindgen output for --target web or nodejs"
+                .to_string(),
+        )
+    }
+}
+
+/// The glue wasm-bindgen writes beside `app_bg.wasm` is `app.js`
+pub fn default_glue_path(wasm_path: &Path) -> PathBuf {
+    let stem = wasm_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let name = stem.strip_suffix("_bg").unwrap_or(stem);
+    wasm_path.with_file_name(format!("{name}.js"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glue_kind_and_path() {
+        assert_eq!(
+            glue_kind("let wasm;\nexport function greet() {}\nfunction initSync(module) {}\n"),
+            Ok(GlueKind::EsModule {
+                init_takes_object: false
+            })
+        );
+        assert_eq!(
+            glue_kind("module.exports.greet = function() {};\n"),
+            Ok(GlueKind::CommonJs)
+        );
+        assert!(glue_kind("import * as wasm from \"./app_bg.wasm\";\n").is_err());
+        assert_eq!(
+            default_glue_path(Path::new("pkg/app_bg.wasm")),
+            Path::new("pkg/app.js")
+        );
+    }
+}
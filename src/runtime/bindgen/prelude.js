@@ -0,0 +1,276 @@
+// Globals wasm-bindgen glue expects from a browser or Node, for
+// `wasmrun exec --bindgen`. Natives named `__wasmrun_*` are defined in mod.rs.
+(() => {
+  const g = globalThis;
+
+  // console
+
+  const format = (value, nested) => {
+    if (typeof value === "string") return nested ? JSON.stringify(value) : value;
+    if (typeof value === "bigint") return `${value}n`;
+    if (typeof value === "function") return `[Function ${value.name || "anonymous"}]`;
+    if (value instanceof Error) return value.stack || `${value.name}: ${value.message}`;
+    if (Array.isArray(value)) return `[ ${value.map((v) => format(v, true)).join(", ")} ]`;
+    if (ArrayBuffer.isView(value)) return `${value.constructor.name}(${value.length}) [ ${Array.from(value).join(", ")} ]`;
+    if (value !== null && typeof value === "object") {
+      try {
+        return JSON.stringify(value);
+      } catch {
+        return String(value);
+      }
+    }
+    return String(value);
+  };
+  const print = (stream) => (...values) => __wasmrun_print(stream, values.map((v) => format(v, false)).join(" "));
+  g.console = {
+    log: print(1),
+    info: print(1),
+    debug: print(1),
+    trace: print(2),
+    warn: print(2),
+    error: print(2),
+  };
+  g.__wasmrunFormat = (value) => format(value, false);
+
+  // TextEncoder / TextDecoder (UTF-8 only)
+
+  const utf8Encode = (text) => {
+    const bytes = [];
+    for (const ch of text) {
+      let c = ch.codePointAt(0);
+      if (c >= 0xd800 && c <= 0xdfff) c = 0xfffd;
+      if (c < 0x80) bytes.push(c);
+      else if (c < 0x800) bytes.push(0xc0 | (c >> 6), 0x80 | (c & 63));
+      else if (c < 0x10000) bytes.push(0xe0 | (c >> 12), 0x80 | ((c >> 6) & 63), 0x80 | (c & 63));
+      else bytes.push(0xf0 | (c >> 18), 0x80 | ((c >> 12) & 63), 0x80 | ((c >> 6) & 63), 0x80 | (c & 63));
+    }
+    return bytes;
+  };
+
+  class TextEncoder {
+    get encoding() {
+      return "utf-8";
+    }
+    encode(text = "") {
+      return new Uint8Array(utf8Encode(String(text)));
+    }
+    encodeInto(text, view) {
+      let read = 0;
+      let written = 0;
+      for (const ch of String(text)) {
+        const bytes = utf8Encode(ch);
+        if (written + bytes.length > view.length) break;
+        view.set(bytes, written);
+        written += bytes.length;
+        read += ch.length;
+      }
+      return { read, written };
+    }
+  }
+
+  class TextDecoder {
+    constructor(label = "utf-8", options = {}) {
+      if (!["utf-8", "utf8", "unicode-1-1-utf-8"].includes(String(label).toLowerCase())) {
+        throw new RangeError(`TextDecoder: only utf-8 is supported, not ${label}`);
+      }
+      this.fatal = Boolean(options.fatal);
+      this.ignoreBOM = Boolean(options.ignoreBOM);
+    }
+    get encoding() {
+      return "utf-8";
+    }
+    decode(input) {
+      if (input === undefined) return "";
+      const view = ArrayBuffer.isView(input) ? input : new Uint8Array(input);
+      return __wasmrun_utf8_decode(view.buffer, view.byteOffset, view.byteLength, this.fatal, this.ignoreBOM);
+    }
+  }
+
+  g.TextEncoder = TextEncoder;
+  g.TextDecoder = TextDecoder;
+
+  // WebAssembly, backed by the interpreter
+
+  class CompileError extends Error {}
+  class LinkError extends Error {}
+  class RuntimeError extends Error {}
+
+  const bufferSource = (source) => {
+    if (source instanceof ArrayBuffer) return [source, 0, source.byteLength];
+    if (ArrayBuffer.isView(source)) return [source.buffer, source.byteOffset, source.byteLength];
+    throw new TypeError("WebAssembly.Module expects an ArrayBuffer or typed array");
+  };
+  const moduleInfo = (module) => JSON.parse(__wasmrun_module_info(module.__wasmrunId));
+
+  class Module {
+    constructor(source) {
+      this.__wasmrunId = __wasmrun_compile(...bufferSource(source));
+    }
+    static imports(module) {
+      return moduleInfo(module).imports.map(({ module, name, kind }) => ({ module, name, kind }));
+    }
+    static exports(module) {
+      return moduleInfo(module).exports.map(({ name, kind }) => ({ name, kind }));
+    }
+    static customSections() {
+      return [];
+    }
+  }
+
+  class Memory {
+    get buffer() {
+      return g.__wasmrunBuffer;
+    }
+    grow(pages) {
+      return __wasmrun_memory_grow(pages);
+    }
+  }
+
+  class Table {
+    constructor(index) {
+      this.__wasmrunIndex = index;
+    }
+    get length() {
+      return __wasmrun_table(this.__wasmrunIndex, "size");
+    }
+    get(index) {
+      return __wasmrun_table(this.__wasmrunIndex, "get", index);
+    }
+    set(index, value) {
+      __wasmrun_table(this.__wasmrunIndex, "set", index, value);
+    }
+    grow(delta, value) {
+      return __wasmrun_table(this.__wasmrunIndex, "grow", delta, value);
+    }
+  }
+
+  class Global {
+    constructor(index) {
+      this.__wasmrunIndex = index;
+    }
+    get value() {
+      return __wasmrun_global(this.__wasmrunIndex);
+    }
+    valueOf() {
+      return this.value;
+    }
+  }
+
+  class Instance {
+    constructor(module, imports = {}) {
+      const { imports: wanted, exports } = moduleInfo(module);
+      const functions = wanted.map(({ module: from, name, kind }) => {
+        const value = imports[from] === undefined ? undefined : imports[from][name];
+        if (kind !== "function") {
+          throw new LinkError(`Import ${from}.${name}: only function imports are supported`);
+        }
+        if (typeof value !== "function") {
+          throw new LinkError(`Import ${from}.${name} is not a function`);
+        }
+        return value;
+      });
+      g.__wasmrunImports = functions;
+      __wasmrun_instantiate(module.__wasmrunId);
+
+      this.exports = {};
+      for (const { name, kind, index } of exports) {
+        if (kind === "function") {
+          this.exports[name] = (...args) => __wasmrun_call(name, ...args);
+        } else if (kind === "memory") {
+          this.exports[name] = new Memory();
+        } else if (kind === "table") {
+          this.exports[name] = new Table(index);
+        } else if (kind === "global") {
+          this.exports[name] = new Global(index);
+        }
+      }
+      Object.freeze(this.exports);
+    }
+  }
+
+  // Externrefs cross into the interpreter as handles into this list
+  const externrefs = [];
+  g.__wasmrunToHandle = (value) => {
+    if (value === null) return -1;
+    externrefs.push(value);
+    return externrefs.length - 1;
+  };
+  g.__wasmrunFromHandle = (handle) => externrefs[handle];
+
+  g.WebAssembly = {
+    Module,
+    Instance,
+    Memory,
+    Table,
+    Global,
+    CompileError,
+    LinkError,
+    RuntimeError,
+    validate(source) {
+      try {
+        new Module(source);
+        return true;
+      } catch {
+        return false;
+      }
+    },
+    async compile(source) {
+      return new Module(source);
+    },
+    async instantiate(source, imports) {
+      if (source instanceof Module) return new Instance(source, imports);
+      const module = new Module(source);
+      return { module, instance: new Instance(module, imports) };
+    },
+    async instantiateStreaming() {
+      throw new TypeError("WebAssembly.instantiateStreaming is not available; use initSync");
+    },
+  };
+  g.__wasmrunCompileFile = (path) => new Module(__wasmrun_read_file(path));
+
+  // CommonJS, for `--target nodejs` glue
+
+  const dirname = (path) => {
+    const slash = path.lastIndexOf("/");
+    return slash > 0 ? path.slice(0, slash) : slash === 0 ? "/" : ".";
+  };
+  const join = (...parts) => parts.filter((p) => p !== "").join("/").replace(/\/+/g, "/");
+  const resolve = (from, specifier) => {
+    const out = [];
+    for (const part of (specifier.startsWith("/") ? specifier : join(from, specifier)).split("/")) {
+      if (part === "..") out.pop();
+      else if (part !== ".") out.push(part);
+    }
+    return out.join("/") || "/";
+  };
+  const builtins = {
+    fs: {
+      readFileSync(path, options) {
+        const encoding = typeof options === "string" ? options : options && options.encoding;
+        return encoding ? __wasmrun_read_text(String(path)) : new Uint8Array(__wasmrun_read_file(String(path)));
+      },
+    },
+    path: { join, dirname, resolve: (...parts) => parts.reduce((from, p) => resolve(from, p), "") },
+    util: { TextEncoder, TextDecoder },
+  };
+  const cache = new Map();
+  const load = (filename) => {
+    if (cache.has(filename)) return cache.get(filename).exports;
+    const module = { exports: {} };
+    cache.set(filename, module);
+    const dir = dirname(filename);
+    const require = (specifier) => {
+      const name = specifier.startsWith("node:") ? specifier.slice(5) : specifier;
+      if (builtins[name]) return builtins[name];
+      if (!name.startsWith(".") && !name.startsWith("/")) {
+        throw new Error(`Cannot find module '${specifier}' (only relative paths and fs, path, util are available)`);
+      }
+      const path = resolve(dir, name);
+      return load(path.endsWith(".js") ? path : `${path}.js`);
+    };
+    const body = new Function("exports", "require", "module", "__filename", "__dirname", __wasmrun_read_text(filename));
+    body(module.exports, require, module, filename, dir);
+    return module.exports;
+  };
+  g.__wasmrunRequire = load;
+})();
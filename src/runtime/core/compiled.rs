@@ -0,0 +1,269 @@
+/// Decoded function bodies for the interpreter
+/// A body is decoded once, on its first call, into a list of instructions
+/// whose `block`, `loop`, `if` and `else` already know where their `end` is.
+/// Later calls and branches run from that list instead of decoding LEB128
+/// immediates and scanning for the matching `end` again. An optimized copy,
+/// with common sequences fused by the optimizer, is what normally runs.
+use super::executor::{decode_instruction, Instruction};
+use super::module::Function;
+use super::optimizer;
+use std::io::Cursor;
+use std::sync::{Arc, OnceLock};
+
+/// A function body decoded into instructions, indexed by position in the
+/// list rather than by byte offset
+#[derive(Debug)]
+pub struct CompiledFunction {
+    pub instructions: Vec<Instruction>,
+    /// Byte offset in the body of each instruction, plus where decoding
+    /// stopped, for traces, coverage and backtraces
+    offsets: Vec<usize>,
+    /// For `block`, `loop` and `else`, the index of the matching `end`; for
+    /// `if`, of its `else`, or of its `end` when it has none. A block that is
+    /// never closed points past the last instruction.
+    targets: Vec<usize>,
+    /// Index of the `end` closing the function body itself
+    function_end: usize,
+    /// How many decoded instructions each one stands for, when fuel is
+    /// charged; empty when nothing was fused
+    weights: Vec<u32>,
+    /// Why decoding stopped before the end of the body. Running past the
+    /// last decoded instruction fails with it, as decoding on the fly did.
+    error: Option<String>,
+}
+
+impl CompiledFunction {
+    pub fn compile(code: &[u8]) -> Self {
+        let mut cursor = Cursor::new(code);
+        let mut instructions = Vec::new();
+        let mut offsets = Vec::new();
+        let mut error = None;
+        while (cursor.position() as usize) < code.len() {
+            let offset = cursor.position() as usize;
+            match decode_instruction(&mut cursor) {
+                Ok(instr) => {
+                    instructions.push(instr);
+                    offsets.push(offset);
+                }
+                Err(e) => {
+                    cursor.set_position(offset as u64);
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+        offsets.push(cursor.position() as usize);
+        Self::new(instructions, offsets, Vec::new(), error)
+    }
+
+    /// The same body with common instruction sequences fused by the
+    /// optimizer. Block instructions are never fused, so branches land
+    /// where they did.
+    pub fn optimize(&self) -> Self {
+        let fused = optimizer::fuse(&self.instructions, &self.offsets);
+        Self::new(
+            fused.instructions,
+            fused.offsets,
+            fused.weights,
+            self.error.clone(),
+        )
+    }
+
+    fn new(
+        instructions: Vec<Instruction>,
+        offsets: Vec<usize>,
+        weights: Vec<u32>,
+        error: Option<String>,
+    ) -> Self {
+        let len = instructions.len();
+        let mut targets = vec![len; len];
+        let mut function_end = None;
+        // Indices of the blocks, loops, ifs and elses still waiting for `end`
+        let mut open: Vec<usize> = Vec::new();
+        for (index, instr) in instructions.iter().enumerate() {
+            match instr {
+                Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => {
+                    open.push(index)
+                }
+                Instruction::Else => {
+                    if let Some(&opener) = open.last() {
+                        if matches!(instructions[opener], Instruction::If(_)) {
+                            targets[opener] = index;
+                            open.pop();
+                        }
+                    }
+                    open.push(index);
+                }
+                Instruction::End => match open.pop() {
+                    Some(opener) => targets[opener] = index,
+                    None => {
+                        function_end.get_or_insert(index);
+                    }
+                },
+                _ => {}
+            }
+        }
+        Self {
+            instructions,
+            offsets,
+            targets,
+            function_end: function_end.unwrap_or(len),
+            weights,
+            error,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Decoded instructions that instruction `pc` stands for
+    pub fn weight(&self, pc: usize) -> u64 {
+        self.weights.get(pc).map_or(1, |&weight| weight as u64)
+    }
+
+    /// Byte offset of instruction `pc` in the function body
+    pub fn offset(&self, pc: usize) -> usize {
+        self.offsets[pc.min(self.instructions.len())]
+    }
+
+    /// Index of the `end` closing the `block`, `loop`, `if` or `else` at `pc`
+    pub fn end_of(&self, pc: usize) -> usize {
+        let target = self.targets[pc];
+        match self.instructions.get(target) {
+            Some(Instruction::Else) if matches!(self.instructions[pc], Instruction::If(_)) => {
+                self.targets[target]
+            }
+            _ => target,
+        }
+    }
+
+    /// Index of the `else` of the `if` at `pc`, if it has one
+    pub fn else_of(&self, pc: usize) -> Option<usize> {
+        let target = self.targets[pc];
+        matches!(self.instructions.get(target), Some(Instruction::Else)).then_some(target)
+    }
+
+    /// Index of the `end` closing the function body
+    pub fn function_end(&self) -> usize {
+        self.function_end
+    }
+
+    /// The error that stopped decoding, reported when execution reaches it
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// Compiled bodies of a module's defined functions, each filled in the
+/// first time the function is called
+#[derive(Debug, Default, Clone)]
+pub struct CodeCache {
+    functions: OnceLock<Box<[CacheSlot]>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct CacheSlot {
+    plain: OnceLock<Arc<CompiledFunction>>,
+    optimized: OnceLock<Arc<CompiledFunction>>,
+}
+
+impl CodeCache {
+    /// The compiled body of defined function `index`, instruction for
+    /// instruction as decoded; `None` past the last function
+    pub fn get(&self, functions: &[Function], index: usize) -> Option<Arc<CompiledFunction>> {
+        let (function, slot) = self.slot(functions, index)?;
+        Some(Arc::clone(slot.plain.get_or_init(|| {
+            Arc::new(CompiledFunction::compile(&function.code))
+        })))
+    }
+
+    /// The optimized body of defined function `index`; `None` past the last
+    /// function
+    pub fn get_optimized(
+        &self,
+        functions: &[Function],
+        index: usize,
+    ) -> Option<Arc<CompiledFunction>> {
+        let (function, slot) = self.slot(functions, index)?;
+        Some(Arc::clone(slot.optimized.get_or_init(|| {
+            Arc::new(CompiledFunction::compile(&function.code).optimize())
+        })))
+    }
+
+    fn slot<'a>(
+        &'a self,
+        functions: &'a [Function],
+        index: usize,
+    ) -> Option<(&'a Function, &'a CacheSlot)> {
+        let function = functions.get(index)?;
+        let slots = self
+            .functions
+            .get_or_init(|| functions.iter().map(|_| CacheSlot::default()).collect());
+        Some((function, slots.get(index)?))
+    }
+
+    /// Number of function bodies compiled so far
+    pub fn compiled(&self) -> usize {
+        self.functions.get().map_or(0, |slots| {
+            slots
+                .iter()
+                .filter(|slot| slot.plain.get().is_some() || slot.optimized.get().is_some())
+                .count()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_resolves_block_targets() {
+        // block; loop; br 1; end; end; i32.const 0; if; nop; else; nop; end; end
+        let code = [
+            0x02, 0x40, 0x03, 0x40, 0x0c, 0x01, 0x0b, 0x0b, 0x41, 0x00, 0x04, 0x40, 0x01, 0x05,
+            0x01, 0x0b, 0x0b,
+        ];
+        let body = CompiledFunction::compile(&code);
+        assert_eq!(body.len(), 12);
+        assert!(body.error().is_none());
+        assert_eq!(body.end_of(0), 4);
+        assert_eq!(body.end_of(1), 3);
+        assert_eq!(body.end_of(6), 10);
+        assert_eq!(body.else_of(6), Some(8));
+        assert_eq!(body.end_of(8), 10);
+        assert_eq!(body.function_end(), 11);
+        assert_eq!(body.offset(6), 10);
+        assert_eq!(body.offset(12), code.len());
+    }
+
+    #[test]
+    fn test_compile_keeps_what_decodes_before_an_unknown_opcode() {
+        // block; nop; <unknown 0xff>
+        let body = CompiledFunction::compile(&[0x02, 0x40, 0x01, 0xff, 0x0b, 0x0b]);
+        assert_eq!(body.len(), 2);
+        assert_eq!(body.end_of(0), 2);
+        assert_eq!(body.offset(2), 3);
+        assert!(body.error().unwrap().contains("0xFF"));
+    }
+
+    #[test]
+    fn test_code_cache_compiles_each_function_once() {
+        let function = Function {
+            type_index: 0,
+            locals: vec![],
+            code: vec![0x01, 0x0b],
+        };
+        let functions = vec![function.clone(), function];
+        let cache = CodeCache::default();
+        let first = cache.get(&functions, 1).unwrap();
+        assert!(Arc::ptr_eq(&first, &cache.get(&functions, 1).unwrap()));
+        assert_eq!(cache.compiled(), 1);
+        assert!(cache.get(&functions, 2).is_none());
+    }
+}
@@ -0,0 +1,164 @@
+/// Coverage counters for the interpreter
+/// Counts how often each function is entered and, optionally, how often each
+/// `block` and `loop` is entered and each `if` takes its then-arm. The counts
+/// are saved as JSON by `wasmrun exec --coverage` and read back by
+/// `wasmrun coverage report`.
+use super::executor::{decode_instruction, Instruction};
+use super::module::Module;
+use super::values::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// Counts collected while a module runs
+#[derive(Debug, Clone, Default)]
+pub struct CoverageCounters {
+    pub calls: HashMap<u32, u64>,
+    pub blocks: HashMap<(u32, usize), u64>,
+    /// Whether block entries are counted as well as calls
+    pub track_blocks: bool,
+}
+
+impl CoverageCounters {
+    pub fn new(track_blocks: bool) -> Self {
+        Self {
+            track_blocks,
+            ..Self::default()
+        }
+    }
+
+    /// Count `instr` about to run at `pc` in function `func_idx`, given the
+    /// operand stack before it runs
+    pub fn record(&mut self, func_idx: u32, pc: usize, instr: &Instruction, stack: &[Value]) {
+        // Every call starts decoding its body at offset 0, and no branch
+        // returns there
+        if pc == 0 {
+            *self.calls.entry(func_idx).or_default() += 1;
+        }
+        let entered = match instr {
+            Instruction::Block(_) | Instruction::Loop(_) => true,
+            Instruction::If(_) => !matches!(stack.last(), Some(Value::I32(0))),
+            _ => false,
+        };
+        if self.track_blocks && entered {
+            *self.blocks.entry((func_idx, pc)).or_default() += 1;
+        }
+    }
+}
+
+/// Saved coverage of one run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageData {
+    /// Path of the module that ran
+    pub module: String,
+    /// Every function defined in the module, executed or not
+    pub functions: Vec<FunctionCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCoverage {
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub calls: u64,
+    /// Present when block coverage was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<BlockCoverage>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockCoverage {
+    /// Byte offset of the block's opening instruction in the function body
+    pub offset: usize,
+    pub kind: String,
+    pub count: u64,
+}
+
+impl CoverageData {
+    /// Combine `counters` with the module's functions, so the functions and
+    /// blocks that never ran are listed with a zero count
+    pub fn collect(
+        module_path: &str,
+        module: &Module,
+        import_func_count: usize,
+        counters: &CoverageCounters,
+        name_of: impl Fn(u32) -> Option<String>,
+    ) -> Self {
+        let functions = module
+            .functions
+            .iter()
+            .enumerate()
+            .map(|(defined, function)| {
+                let index = (import_func_count + defined) as u32;
+                let blocks = counters.track_blocks.then(|| {
+                    block_offsets(&function.code)
+                        .into_iter()
+                        .map(|(offset, kind)| BlockCoverage {
+                            offset,
+                            kind: kind.to_string(),
+                            count: counters.blocks.get(&(index, offset)).copied().unwrap_or(0),
+                        })
+                        .collect()
+                });
+                FunctionCoverage {
+                    index,
+                    name: name_of(index),
+                    calls: counters.calls.get(&index).copied().unwrap_or(0),
+                    blocks,
+                }
+            })
+            .collect();
+        Self {
+            module: module_path.to_string(),
+            functions,
+        }
+    }
+}
+
+/// Offsets and kinds of the `block`, `loop` and `if` instructions in a body
+pub fn block_offsets(code: &[u8]) -> Vec<(usize, &'static str)> {
+    let mut cursor = Cursor::new(code);
+    let mut blocks = Vec::new();
+    while (cursor.position() as usize) < code.len() {
+        let offset = cursor.position() as usize;
+        let kind = match decode_instruction(&mut cursor) {
+            Ok(Instruction::Block(_)) => "block",
+            Ok(Instruction::Loop(_)) => "loop",
+            Ok(Instruction::If(_)) => "if",
+            Ok(_) => continue,
+            // An opcode the interpreter can't decode; the rest is unknown
+            Err(_) => break,
+        };
+        blocks.push((offset, kind));
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_offsets_and_counts() {
+        // block; loop; end; end; i32.const 1; if; end; end
+        let code = [
+            0x02, 0x40, 0x03, 0x40, 0x0b, 0x0b, 0x41, 0x01, 0x04, 0x40, 0x0b, 0x0b,
+        ];
+        assert_eq!(
+            block_offsets(&code),
+            vec![(0, "block"), (2, "loop"), (8, "if")]
+        );
+
+        let mut counters = CoverageCounters::new(true);
+        counters.record(3, 0, &Instruction::Block(None), &[]);
+        counters.record(3, 2, &Instruction::Loop(None), &[]);
+        counters.record(3, 2, &Instruction::Loop(None), &[]);
+        counters.record(3, 4, &Instruction::End, &[]);
+        // An if only counts when its then-arm runs
+        counters.record(3, 8, &Instruction::If(None), &[Value::I32(0)]);
+        assert_eq!(counters.calls[&3], 1);
+        assert_eq!(counters.blocks[&(3, 2)], 2);
+        assert!(!counters.blocks.contains_key(&(3, 4)));
+        assert!(!counters.blocks.contains_key(&(3, 8)));
+    }
+}
@@ -1,9 +1,15 @@
 /// WASM instruction executor
 /// Handles execution context, stack, call frames, and instruction dispatch
+use super::compiled::CompiledFunction;
+use super::coverage::{CoverageCounters, CoverageData};
 use super::linker::Linker;
 use super::memory::LinearMemory;
-use super::module::{ImportKind, Module, ValueType};
+use super::module::{ExportKind, ImportKind, Module, ValueType};
+use super::trace::InstructionTrace;
+use super::trap::{self, BacktraceFrame};
 use super::values::Value;
+use super::watchpoint::{self, MemoryWatch};
+use std::collections::HashMap;
 use std::io::Cursor;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -20,10 +26,22 @@ pub const FUEL_EXHAUSTED_ERROR: &str = "__wasmrun_fuel_exhausted__";
 /// this to distinguish a deliberate halt from a program error.
 pub const EXECUTION_CANCELLED_ERROR: &str = "__wasmrun_execution_cancelled__";
 
+/// Sentinel prefix for errors raised when a call-depth or operand-stack
+/// limit is hit. The rest of the message is meant for the user.
+pub const STACK_EXHAUSTED_ERROR: &str = "__wasmrun_stack_exhausted__";
+
+/// Default cap on nested wasm calls
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 4096;
+
+/// Default cap on values held on the operand stack
+pub const DEFAULT_MAX_OPERAND_STACK: usize = 1024 * 1024;
+
 /// Result of instruction dispatch for control flow signaling
 #[derive(Debug, Clone, PartialEq)]
 enum ControlFlow {
     Continue,
+    /// Continue at this instruction index instead of the next one
+    Jump(usize),
     Return,
 }
 
@@ -257,6 +275,14 @@ pub enum Instruction {
     CallIndirect(u32),
     Drop,
     Select,
+
+    // Fused instructions, produced by the optimizer and never decoded
+    LocalCopy(u32, u32),           // local.get a; local.set b
+    I32AddConst(i32),              // i32.const c; i32.add
+    I64AddConst(i64),              // i64.const c; i64.add
+    LocalGetI32AddConst(u32, i32), // local.get a; i32.const c; i32.add
+    LocalGetI32Load(u32, u32),     // local.get a; i32.load offset
+    BrUnless(u32),                 // i32.eqz; br_if label
 }
 /// Helper function to read a single byte
 fn read_u8(cursor: &mut Cursor<&[u8]>) -> Result<u8, String> {
@@ -826,9 +852,10 @@ pub struct BlockFrame {
     pub stack_depth: usize,
     /// Whether this is a loop (affects branching)
     pub is_loop: bool,
-    /// Bytecode position of the block start
+    /// Instruction index of the first instruction inside the block (loop
+    /// branch target)
     pub start_pos: usize,
-    /// Bytecode position after 'end' instruction (branch target)
+    /// Instruction index of the block's `end`; branches continue after it
     pub end_pos: usize,
     /// For if blocks: whether we're in the then-branch (true) or else-branch (false)
     pub is_then_branch: bool,
@@ -847,6 +874,10 @@ pub struct Frame {
     /// Operand stack depth at function entry (after popping args).
     /// Used by `return` to restore the stack to the correct depth.
     pub base_stack_depth: usize,
+    /// Offset in the function body of the instruction being executed.
+    /// Updated when a watchpoint is checked and when a trap unwinds through
+    /// the frame.
+    pub pc: usize,
 }
 
 impl Frame {
@@ -857,6 +888,7 @@ impl Frame {
             return_addr: 0,
             num_returns,
             base_stack_depth: 0,
+            pc: 0,
         }
     }
 
@@ -1073,11 +1105,11 @@ impl TableInstance {
         }
     }
 
-    fn size(&self) -> u32 {
+    pub fn size(&self) -> u32 {
         self.elements.len() as u32
     }
 
-    fn get(&self, idx: u32) -> Result<Value, String> {
+    pub fn get(&self, idx: u32) -> Result<Value, String> {
         self.elements.get(idx as usize).copied().ok_or_else(|| {
             format!(
                 "table access out of bounds: index {idx} (size {})",
@@ -1086,7 +1118,7 @@ impl TableInstance {
         })
     }
 
-    fn set(&mut self, idx: u32, val: Value) -> Result<(), String> {
+    pub fn set(&mut self, idx: u32, val: Value) -> Result<(), String> {
         let len = self.elements.len();
         let slot = self
             .elements
@@ -1098,7 +1130,7 @@ impl TableInstance {
 
     /// Grow the table by `n` slots filled with `init`. Returns the previous
     /// size on success, or `-1` if growth would exceed the table's maximum.
-    fn grow(&mut self, n: u32, init: Value) -> i32 {
+    pub fn grow(&mut self, n: u32, init: Value) -> i32 {
         let old = self.elements.len() as u32;
         let new_size = match old.checked_add(n) {
             Some(s) => s,
@@ -1125,7 +1157,9 @@ struct ElemSegmentState {
 /// WASM instruction executor
 pub struct Executor {
     context: ExecutionContext,
-    module: Module,
+    /// The parsed module, shared with any other executor instantiated from
+    /// it. Everything the program can change lives in the executor.
+    module: Arc<Module>,
     linker: Option<Linker>,
     import_func_count: usize,
     /// Runtime table instances, indexed by the module's table index space
@@ -1143,20 +1177,58 @@ pub struct Executor {
     /// check. `None` = not cancellable. Shared (`Arc`) so an outside thread —
     /// e.g. the agent server on wall-clock timeout — can trip it while we run.
     cancel: Option<Arc<AtomicBool>>,
+    /// Memory watchpoints checked before every write. `None` = no checks.
+    watch: Option<Box<MemoryWatch>>,
+    /// Deepest call nesting allowed before trapping with
+    /// `STACK_EXHAUSTED_ERROR`. Each wasm call recurses on the host stack, so
+    /// this also keeps deep recursion from overflowing the host thread.
+    max_call_depth: usize,
+    /// Largest operand stack allowed before trapping with
+    /// `STACK_EXHAUSTED_ERROR`.
+    max_operand_stack: usize,
+    /// Function names for backtraces, usually from the `name` section
+    function_names: HashMap<u32, String>,
+    /// Instruction trace recorded before every instruction. `None` = off.
+    trace: Option<Box<InstructionTrace>>,
+    /// Function and block entry counts. `None` = off.
+    coverage: Option<Box<CoverageCounters>>,
+    /// Run bodies through the optimizer. Instruction traces always show
+    /// the instructions as decoded.
+    optimize: bool,
 }
 
 impl Executor {
     /// Create new executor for module (no host function support).
-    pub fn new(module: Module) -> Result<Self, String> {
-        Self::build(module, None)
+    ///
+    /// Takes a [`Module`] or an `Arc<Module>`: executors made from clones of
+    /// one `Arc` share the parsed module and its decoded bodies, while each
+    /// gets its own memory, globals and tables.
+    pub fn new(module: impl Into<Arc<Module>>) -> Result<Self, String> {
+        Self::build(module.into(), None, None)
     }
 
     /// Create executor with a linker that provides host functions for imports.
-    pub fn new_with_linker(module: Module, linker: Linker) -> Result<Self, String> {
-        Self::build(module, Some(linker))
-    }
-
-    fn build(module: Module, linker: Option<Linker>) -> Result<Self, String> {
+    pub fn new_with_linker(module: impl Into<Arc<Module>>, linker: Linker) -> Result<Self, String> {
+        Self::build(module.into(), Some(linker), None)
+    }
+
+    /// Create executor with a linker, capping this instance's linear memory
+    /// at `max_memory_pages` whatever the module declares. The cap applies
+    /// before memory is allocated, so an oversized initial memory fails
+    /// instead of being allocated.
+    pub fn instantiate(
+        module: Arc<Module>,
+        linker: Linker,
+        max_memory_pages: Option<u32>,
+    ) -> Result<Self, String> {
+        Self::build(module, Some(linker), max_memory_pages)
+    }
+
+    fn build(
+        module: Arc<Module>,
+        linker: Option<Linker>,
+        max_memory_pages: Option<u32>,
+    ) -> Result<Self, String> {
         let import_func_count = module
             .imports
             .iter()
@@ -1173,6 +1245,10 @@ impl Executor {
             });
             imported_mem.unwrap_or((1, None))
         };
+        let max = match (max, max_memory_pages) {
+            (Some(max), Some(cap)) => Some(max.min(cap)),
+            (max, cap) => max.or(cap),
+        };
 
         let mut context = ExecutionContext::new(initial, max)?;
 
@@ -1266,9 +1342,22 @@ impl Executor {
             elem_segments,
             fuel: None,
             cancel: None,
+            watch: None,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_operand_stack: DEFAULT_MAX_OPERAND_STACK,
+            function_names: HashMap::new(),
+            trace: None,
+            coverage: None,
+            optimize: true,
         })
     }
 
+    /// Run fused instructions from the optimizer (the default), or every
+    /// instruction as decoded
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+
     /// Set the instruction budget ("fuel") for subsequent executions.
     ///
     /// `Some(n)` aborts execution after `n` instructions with
@@ -1296,6 +1385,168 @@ impl Executor {
         err.contains(EXECUTION_CANCELLED_ERROR)
     }
 
+    /// Install memory watchpoints; `None` removes them.
+    pub fn set_memory_watch(&mut self, watch: Option<MemoryWatch>) {
+        self.watch = watch.map(Box::new);
+    }
+
+    /// Number of watched writes seen so far
+    pub fn watch_hits(&self) -> u64 {
+        self.watch.as_ref().map_or(0, |w| w.hits)
+    }
+
+    /// Check whether an error string means a watchpoint stopped execution.
+    pub fn is_watchpoint_stop(err: &str) -> bool {
+        err.contains(watchpoint::WATCHPOINT_STOP_ERROR)
+    }
+
+    /// Set the call-depth and operand-stack limits.
+    ///
+    /// The host thread running the executor needs enough stack for
+    /// `max_call_depth` nested calls; see
+    /// [`interpreter_stack_bytes`](crate::interpreter_stack_bytes).
+    pub fn set_stack_limits(&mut self, max_call_depth: usize, max_operand_stack: usize) {
+        self.max_call_depth = max_call_depth;
+        self.max_operand_stack = max_operand_stack;
+    }
+
+    /// Check whether an error string means a stack limit was hit.
+    pub fn is_stack_exhausted(err: &str) -> bool {
+        err.contains(STACK_EXHAUSTED_ERROR)
+    }
+
+    /// Trap for a stack limit, naming the calls that led there
+    fn stack_exhausted(&self, what: String) -> String {
+        // Collapse runs of the same function so deep recursion stays readable
+        let mut runs: Vec<(u32, usize)> = Vec::new();
+        for frame in self.context.call_stack.iter().rev() {
+            match runs.last_mut() {
+                Some((func_idx, count)) if *func_idx == frame.func_idx => *count += 1,
+                _ => runs.push((frame.func_idx, 1)),
+            }
+        }
+        const SHOWN: usize = 12;
+        let mut message = format!("{STACK_EXHAUSTED_ERROR} {what}\nCall chain, innermost first:");
+        for &(func_idx, count) in runs.iter().take(SHOWN) {
+            let label = trap::function_label(func_idx, self.function_name(func_idx));
+            if count > 1 {
+                message.push_str(&format!("\n    {label} ×{count}"));
+            } else {
+                message.push_str(&format!("\n    {label}"));
+            }
+        }
+        if runs.len() > SHOWN {
+            let hidden: usize = runs[SHOWN..].iter().map(|&(_, count)| count).sum();
+            message.push_str(&format!("\n    ... {hidden} more frame(s)"));
+        }
+        message
+    }
+
+    /// Trap if one more call would go past the call-depth limit
+    fn check_call_depth(&self) -> Result<(), String> {
+        if self.context.call_stack.len() >= self.max_call_depth {
+            return Err(self.stack_exhausted(format!(
+                "Call stack exhausted: more than {} nested calls",
+                self.max_call_depth
+            )));
+        }
+        Ok(())
+    }
+
+    /// Name functions in backtraces, e.g. from the module's `name` section.
+    /// Functions without one fall back to their export name.
+    pub fn set_function_names(&mut self, names: HashMap<u32, String>) {
+        self.function_names = names;
+    }
+
+    /// Index of the function called `name` in the `name` section or the
+    /// exports, or written as `func[N]` or `N`
+    pub fn find_function(&self, name: &str) -> Option<u32> {
+        let index = name
+            .strip_prefix("func[")
+            .and_then(|rest| rest.strip_suffix(']'))
+            .unwrap_or(name);
+        if let Ok(index) = index.parse::<u32>() {
+            return Some(index);
+        }
+        self.function_names
+            .iter()
+            .filter(|(_, n)| n.as_str() == name)
+            .map(|(&index, _)| index)
+            .min()
+            .or_else(|| {
+                self.module
+                    .exports
+                    .get(name)
+                    .filter(|e| matches!(e.kind, ExportKind::Function))
+                    .map(|e| e.index)
+            })
+    }
+
+    /// Record every executed instruction into `trace`; `None` stops tracing.
+    pub fn set_instruction_trace(&mut self, trace: Option<InstructionTrace>) {
+        self.trace = trace.map(Box::new);
+    }
+
+    /// Remove the instruction trace, returning what it recorded
+    pub fn take_instruction_trace(&mut self) -> Option<InstructionTrace> {
+        self.trace.take().map(|trace| *trace)
+    }
+
+    /// Count function (and, if `counters.track_blocks`, block) entries;
+    /// `None` stops counting.
+    pub fn set_coverage(&mut self, counters: Option<CoverageCounters>) {
+        self.coverage = counters.map(Box::new);
+    }
+
+    /// Coverage so far, over every function the module defines
+    pub fn coverage_data(&self, module_path: &str) -> Option<CoverageData> {
+        let counters = self.coverage.as_ref()?;
+        Some(CoverageData::collect(
+            module_path,
+            &self.module,
+            self.import_func_count,
+            counters,
+            |index| self.function_name(index).map(str::to_string),
+        ))
+    }
+
+    fn function_name(&self, func_idx: u32) -> Option<&str> {
+        self.function_names
+            .get(&func_idx)
+            .map(String::as_str)
+            .or_else(|| {
+                self.module
+                    .exports
+                    .values()
+                    .find(|e| matches!(e.kind, ExportKind::Function) && e.index == func_idx)
+                    .map(|e| e.name.as_str())
+            })
+    }
+
+    /// Call stack, innermost first. After a trap the frames it unwound
+    /// through are still here, each at the instruction that was running.
+    pub fn backtrace_frames(&self) -> Vec<BacktraceFrame> {
+        self.context
+            .call_stack
+            .iter()
+            .rev()
+            .map(|frame| BacktraceFrame {
+                func_index: frame.func_idx,
+                name: self.function_name(frame.func_idx).map(str::to_string),
+                offset: frame.pc,
+            })
+            .collect()
+    }
+
+    /// Call stack, innermost first, as `func[N] "name" +0xPC`
+    pub fn backtrace(&self) -> Vec<String> {
+        self.backtrace_frames()
+            .iter()
+            .map(|frame| frame.to_string())
+            .collect()
+    }
+
     /// Execute a function by index and return its results
     pub fn execute(&mut self, func_idx: u32) -> Result<Vec<Value>, String> {
         self.execute_with_args(func_idx, Vec::new())
@@ -1312,9 +1563,10 @@ impl Executor {
             return self.call_host_function_with_args(func_idx, args);
         }
 
+        self.check_call_depth()?;
         let defined_idx = func_idx as usize - self.import_func_count;
 
-        // Get function signature and code (clone to avoid borrow issues)
+        // Get function signature and locals
         let func = {
             let func = self.module.functions.get(defined_idx).ok_or_else(|| {
                 format!("Function index {func_idx} out of bounds (defined index {defined_idx})")
@@ -1356,11 +1608,11 @@ impl Executor {
                 }
             }
 
-            // Return tuple with locals, num_returns, and code
-            (locals, func_type.results.len(), func.code.clone())
+            (locals, func_type.results.len())
         };
 
-        let (locals, num_returns, code) = func;
+        let (locals, num_returns) = func;
+        let body = self.compiled_function(defined_idx)?;
 
         // Create call frame
         let mut frame = Frame::new(func_idx, locals, num_returns);
@@ -1371,11 +1623,10 @@ impl Executor {
         // WASM spec: every function body is implicitly wrapped in a block.
         // Push it so the function-terminating 0x0b End byte pops this frame
         // rather than accidentally popping the caller's block frames.
-        self.context.push_block(None, false, 0, 0, false);
+        self.context
+            .push_block(None, false, 0, body.function_end(), false);
 
-        // Execute bytecode
-        let mut cursor = Cursor::new(code.as_slice());
-        let result = self.execute_bytecode(&mut cursor);
+        let result = self.execute_body(&body);
         self.context.block_stack.truncate(block_depth_before);
         result?;
 
@@ -1392,110 +1643,127 @@ impl Executor {
         Ok(results)
     }
 
-    /// Execute bytecode starting from current position in cursor
-    fn execute_bytecode(&mut self, cursor: &mut Cursor<&[u8]>) -> Result<(), String> {
-        loop {
-            if cursor.position() >= cursor.get_ref().len() as u64 {
-                break;
+    /// Decoded body of defined function `defined_idx`, from the module's
+    /// code cache
+    fn compiled_function(&self, defined_idx: usize) -> Result<Arc<CompiledFunction>, String> {
+        let body = if self.optimize && self.trace.is_none() {
+            self.module.optimized_function(defined_idx)
+        } else {
+            self.module.compiled_function(defined_idx)
+        };
+        body.ok_or_else(|| format!("Function index out of bounds (defined index {defined_idx})"))
+    }
+
+    /// Run a decoded function body from its first instruction
+    fn execute_body(&mut self, body: &CompiledFunction) -> Result<(), String> {
+        let frame_index = self.context.call_stack.len().checked_sub(1);
+        let mut pc = 0;
+        let result = self.run_body(body, &mut pc);
+        if result.is_err() {
+            // Frames stay on the call stack as a trap unwinds; record where
+            // each one was for the backtrace
+            if let Some(frame) = frame_index.and_then(|i| self.context.call_stack.get_mut(i)) {
+                frame.pc = body.offset(pc);
             }
+        }
+        result
+    }
 
-            // Charge one unit of fuel per instruction. `fuel` is shared across
-            // nested calls (each runs its own execute_bytecode against the same
-            // executor), so this bounds total instructions across the whole call
-            // tree, not just the current function body.
+    fn run_body(&mut self, body: &CompiledFunction, pc: &mut usize) -> Result<(), String> {
+        loop {
+            let Some(instr) = body.instructions.get(*pc) else {
+                // Past the last instruction: either the body ended, or it
+                // holds an instruction the interpreter couldn't decode
+                return match body.error() {
+                    Some(e) => Err(e.to_string()),
+                    None => Ok(()),
+                };
+            };
+            let offset = body.offset(*pc);
+
+            // Charge one unit of fuel per decoded instruction, so fused ones
+            // cost what they replace. `fuel` is shared across nested calls
+            // (each runs its own execute_body against the same executor), so
+            // this bounds total instructions across the whole call tree, not
+            // just the current function body.
             if let Some(remaining) = self.fuel.as_mut() {
-                if *remaining == 0 {
-                    return Err(FUEL_EXHAUSTED_ERROR.to_string());
-                }
-                *remaining -= 1;
+                *remaining = remaining
+                    .checked_sub(body.weight(*pc))
+                    .ok_or_else(|| FUEL_EXHAUSTED_ERROR.to_string())?;
             }
 
             // Cooperative cancellation: an outside thread (e.g. the agent
             // server on wall-clock timeout) can trip this flag to halt a
             // runaway execution that fuel alone wouldn't stop. The Relaxed
-            // atomic load is negligible next to instruction decode/dispatch.
+            // atomic load is negligible next to instruction dispatch.
             if let Some(flag) = self.cancel.as_ref() {
                 if flag.load(Ordering::Relaxed) {
                     return Err(EXECUTION_CANCELLED_ERROR.to_string());
                 }
             }
 
-            let instr = decode_instruction(cursor)?;
-            if self.dispatch_instruction(instr, cursor)? == ControlFlow::Return {
-                break;
+            if self.context.operand_stack.len() > self.max_operand_stack {
+                return Err(self.stack_exhausted(format!(
+                    "Operand stack exhausted: more than {} values",
+                    self.max_operand_stack
+                )));
             }
-        }
-        Ok(())
-    }
-
-    /// Skip bytecode until we find the matching else or end instruction.
-    /// Returns true if we stopped at an `else`, false if we stopped at an `end`.
-    fn skip_to_else_or_end(&mut self, cursor: &mut Cursor<&[u8]>) -> Result<bool, String> {
-        let mut depth = 0;
 
-        loop {
-            if cursor.position() >= cursor.get_ref().len() as u64 {
-                return Err("Unexpected EOF while seeking else/end".to_string());
+            if self.trace.is_some() {
+                self.record_trace(instr, offset);
             }
-
-            let instr = decode_instruction(cursor)?;
-            match instr {
-                Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => {
-                    depth += 1;
-                }
-                Instruction::Else if depth == 0 => {
-                    // Found matching else — cursor is now after `else`
-                    return Ok(true);
+            if let Some(coverage) = self.coverage.as_mut() {
+                if let Some(frame) = self.context.call_stack.last() {
+                    coverage.record(frame.func_idx, offset, instr, &self.context.operand_stack);
                 }
-                Instruction::End => {
-                    if depth == 0 {
-                        // Found matching end (no else branch) — cursor is now after `end`
-                        return Ok(false);
-                    }
-                    depth -= 1;
-                }
-                _ => {}
+            }
+            if self.watch.is_some() {
+                self.check_watchpoints(instr, offset)?;
+            }
+            match self.dispatch_instruction(instr, body, *pc)? {
+                ControlFlow::Continue => *pc += 1,
+                ControlFlow::Jump(target) => *pc = target,
+                ControlFlow::Return => break,
             }
         }
+        Ok(())
     }
 
-    /// Skip bytecode until we find the matching end instruction
-    fn skip_to_end(&mut self, cursor: &mut Cursor<&[u8]>) -> Result<(), String> {
-        self.skip_n_ends(cursor, 1)
+    /// Add `instr` to the instruction trace if its function is traced
+    fn record_trace(&mut self, instr: &Instruction, pc: usize) {
+        let Some(func_idx) = self.context.call_stack.last().map(|f| f.func_idx) else {
+            return;
+        };
+        if !self.trace.as_ref().is_some_and(|t| t.wants(func_idx)) {
+            return;
+        }
+        let label = trap::function_label(func_idx, self.function_name(func_idx));
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(&label, pc, instr, &self.context.operand_stack);
+        }
     }
 
-    /// Skip past `n` unmatched end instructions in the bytecode
-    fn skip_n_ends(&mut self, cursor: &mut Cursor<&[u8]>, n: usize) -> Result<(), String> {
-        let mut depth: i32 = 0;
-        let mut ends_found: usize = 0;
-
-        loop {
-            if cursor.position() >= cursor.get_ref().len() as u64 {
-                return Err("Unexpected EOF while seeking end".to_string());
-            }
-
-            let instr = decode_instruction(cursor)?;
-            match instr {
-                Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => {
-                    depth += 1;
-                }
-                Instruction::End => {
-                    if depth == 0 {
-                        ends_found += 1;
-                        if ends_found >= n {
-                            return Ok(());
-                        }
-                    } else {
-                        depth -= 1;
-                    }
-                }
-                _ => {}
-            }
+    /// Report `instr` if it is about to write into watched memory
+    fn check_watchpoints(&mut self, instr: &Instruction, pc: usize) -> Result<(), String> {
+        if let Some(frame) = self.context.call_stack.last_mut() {
+            frame.pc = pc;
+        }
+        let Some(watch) = self.watch.as_ref() else {
+            return Ok(());
+        };
+        let Some(hit) = watch.check(instr, &self.context.operand_stack) else {
+            return Ok(());
+        };
+        let backtrace = self.backtrace();
+        match self.watch.as_mut() {
+            Some(watch) => watch.report(&hit, &backtrace),
+            None => Ok(()),
         }
     }
 
-    /// Execute a branch to the given label depth
-    fn do_branch(&mut self, label: u32, cursor: &mut Cursor<&[u8]>) -> Result<(), String> {
+    /// Execute a branch to the given label depth, returning the index of
+    /// the instruction to continue at
+    fn do_branch(&mut self, label: u32) -> Result<usize, String> {
         let label_idx = label as usize;
         if label_idx >= self.context.block_stack.len() {
             let depth = self.context.block_stack.len();
@@ -1519,6 +1787,7 @@ impl Executor {
             0
         };
         let is_loop = target_block.is_loop;
+        let (start_pos, end_pos) = (target_block.start_pos, target_block.end_pos);
 
         // Restore operand stack: keep only the top `arity` values, truncate to target stack_depth
         let target_depth = target_block.stack_depth;
@@ -1540,21 +1809,19 @@ impl Executor {
         }
 
         if is_loop {
-            cursor.set_position(target_block.start_pos as u64);
             // Pop only the blocks above the loop (not the loop itself)
             for _ in 0..label_idx {
                 self.context.pop_block()?;
             }
+            Ok(start_pos)
         } else {
-            // Pop all blocks up to and including the target
+            // Pop all blocks up to and including the target, and continue
+            // after its `end`
             for _ in 0..=label_idx {
                 self.context.pop_block()?;
             }
-            // Skip past the remaining nested end instructions in bytecode
-            self.skip_n_ends(cursor, label_idx + 1)?;
+            Ok(end_pos + 1)
         }
-
-        Ok(())
     }
 
     /// Call a function with arguments already on stack
@@ -1563,9 +1830,10 @@ impl Executor {
             return self.call_host_function(func_idx);
         }
 
+        self.check_call_depth()?;
         let defined_idx = func_idx as usize - self.import_func_count;
 
-        let (arg_count, num_results, code, local_types) = {
+        let (arg_count, num_results, local_types) = {
             let func = self.module.functions.get(defined_idx).ok_or_else(|| {
                 format!("Function index {func_idx} out of bounds (defined index {defined_idx})")
             })?;
@@ -1579,10 +1847,10 @@ impl Executor {
             (
                 func_type.params.len(),
                 func_type.results.len(),
-                func.code.clone(),
                 func.locals.clone(),
             )
         };
+        let body = self.compiled_function(defined_idx)?;
 
         // Pop arguments from operand stack
         let args = self.context.pop_n(arg_count)?;
@@ -1622,11 +1890,10 @@ impl Executor {
         // WASM spec: every function body is implicitly wrapped in a block.
         // Push it so the function-terminating 0x0b End byte pops this frame
         // rather than accidentally popping the caller's block frames.
-        self.context.push_block(None, false, 0, 0, false);
+        self.context
+            .push_block(None, false, 0, body.function_end(), false);
 
-        // Execute function bytecode
-        let mut cursor = Cursor::new(code.as_slice());
-        let result = self.execute_bytecode(&mut cursor);
+        let result = self.execute_body(&body);
 
         // Restore block stack to the depth it had before this call.
         self.context.block_stack.truncate(block_depth_before);
@@ -1666,7 +1933,7 @@ impl Executor {
     }
 
     /// Mutably borrow table `idx`, erroring if the module has no such table.
-    fn table_mut(&mut self, idx: u32) -> Result<&mut TableInstance, String> {
+    pub fn table_mut(&mut self, idx: u32) -> Result<&mut TableInstance, String> {
         self.tables
             .get_mut(idx as usize)
             .ok_or_else(|| format!("Table index {idx} out of bounds"))
@@ -1823,10 +2090,11 @@ impl Executor {
     /// Dispatch instruction to handler
     fn dispatch_instruction(
         &mut self,
-        instr: Instruction,
-        cursor: &mut Cursor<&[u8]>,
+        instr: &Instruction,
+        body: &CompiledFunction,
+        pc: usize,
     ) -> Result<ControlFlow, String> {
-        match instr {
+        match *instr {
             // Constants
             Instruction::I32Const(v) => self.context.push(Value::I32(v)),
             Instruction::I64Const(v) => self.context.push(Value::I64(v)),
@@ -2184,6 +2452,44 @@ impl Executor {
                 frame.set_local(idx as usize, value)?;
             }
 
+            // Fused instructions
+            Instruction::LocalCopy(from, to) => {
+                let frame = self.context.current_frame_mut()?;
+                let value = frame.get_local(from as usize)?;
+                frame.set_local(to as usize, value)?;
+            }
+            Instruction::I32AddConst(c) => match self.context.pop()? {
+                Value::I32(x) => self.context.push(Value::I32(x.wrapping_add(c))),
+                _ => return Err("Type mismatch for i32.add".to_string()),
+            },
+            Instruction::I64AddConst(c) => match self.context.pop()? {
+                Value::I64(x) => self.context.push(Value::I64(x.wrapping_add(c))),
+                _ => return Err("Type mismatch for i64.add".to_string()),
+            },
+            Instruction::LocalGetI32AddConst(idx, c) => {
+                match self.context.current_frame()?.get_local(idx as usize)? {
+                    Value::I32(x) => self.context.push(Value::I32(x.wrapping_add(c))),
+                    _ => return Err("Type mismatch for i32.add".to_string()),
+                }
+            }
+            Instruction::LocalGetI32Load(idx, offset) => {
+                let addr = match self.context.current_frame()?.get_local(idx as usize)? {
+                    Value::I32(a) => (a as u32).wrapping_add(offset) as usize,
+                    _ => return Err("Address must be i32".to_string()),
+                };
+                let val = self.context.memory.read_i32(addr)?;
+                self.context.push(Value::I32(val));
+            }
+            Instruction::BrUnless(label) => {
+                let cond_value = match self.context.pop()? {
+                    Value::I32(v) => v,
+                    _ => return Err("Type mismatch for i32.eqz".to_string()),
+                };
+                if cond_value == 0 {
+                    return Ok(ControlFlow::Jump(self.do_branch(label)?));
+                }
+            }
+
             // i64 arithmetic
             Instruction::I64Add => {
                 let b = self.context.pop()?;
@@ -3313,14 +3619,15 @@ impl Executor {
             // Bulk-memory: data.drop — discard a data segment (no-op for us)
             Instruction::DataDrop(_seg_idx) => {}
 
-            // Control flow - proper implementation
+            // Control flow - block targets were resolved when the body was
+            // decoded
             Instruction::Block(block_type) => {
-                let pos = cursor.position() as usize;
-                self.context.push_block(block_type, false, pos, 0, false);
+                self.context
+                    .push_block(block_type, false, pc + 1, body.end_of(pc), false);
             }
             Instruction::Loop(block_type) => {
-                let pos = cursor.position() as usize;
-                self.context.push_block(block_type, true, pos, 0, false);
+                self.context
+                    .push_block(block_type, true, pc + 1, body.end_of(pc), false);
             }
             Instruction::If(block_type) => {
                 // Pop condition from stack
@@ -3330,31 +3637,31 @@ impl Executor {
                     _ => return Err("if requires i32 condition".to_string()),
                 };
 
+                let end = body.end_of(pc);
                 if cond_value != 0 {
                     // Condition is true — push frame, execute then-branch.
                     // If there's no else, the End instruction will pop this frame.
-                    // If there IS an else, the Else handler will skip to end and pop this frame.
-                    self.context.push_block(block_type, false, 0, 0, true);
+                    // If there IS an else, the Else handler will jump past end and pop this frame.
+                    self.context.push_block(block_type, false, 0, end, true);
+                } else if let Some(else_pos) = body.else_of(pc) {
+                    // Condition is false — continue after `else` with a frame
+                    // for the else-body, which the End at the close of this if pops.
+                    self.context.push_block(block_type, false, 0, end, false);
+                    return Ok(ControlFlow::Jump(else_pos + 1));
                 } else {
-                    // Condition is false — scan forward to find else or end.
-                    let found_else = self.skip_to_else_or_end(cursor)?;
-                    if found_else {
-                        // Cursor is now after `else`; push frame for the else-body.
-                        // The End at the close of this if will pop this frame.
-                        self.context.push_block(block_type, false, 0, 0, false);
-                    }
-                    // If found_else is false, `end` was already consumed — don't push a frame.
+                    // No else-body: continue after `end` without pushing a frame.
+                    return Ok(ControlFlow::Jump(end + 1));
                 }
             }
             Instruction::Else => {
-                // We just finished the then-branch. Skip to the matching `end`
-                // (which would otherwise be processed by the End handler, but we
-                // consume it here so we must also pop the block frame ourselves).
-                self.skip_to_end(cursor)?;
+                // We just finished the then-branch. Jump past the matching `end`
+                // (which would otherwise be processed by the End handler, so we
+                // must also pop the block frame ourselves).
                 self.context.pop_block()?;
+                return Ok(ControlFlow::Jump(body.end_of(pc) + 1));
             }
             Instruction::Br(label) => {
-                self.do_branch(label, cursor)?;
+                return Ok(ControlFlow::Jump(self.do_branch(label)?));
             }
             Instruction::BrIf(label) => {
                 let cond = self.context.pop()?;
@@ -3364,10 +3671,10 @@ impl Executor {
                 };
 
                 if cond_value != 0 {
-                    self.do_branch(label, cursor)?;
+                    return Ok(ControlFlow::Jump(self.do_branch(label)?));
                 }
             }
-            Instruction::BrTable(targets, default) => {
+            Instruction::BrTable(ref targets, default) => {
                 let index = match self.context.pop()? {
                     Value::I32(v) => v as u32,
                     _ => return Err("br_table index must be i32".to_string()),
@@ -3379,7 +3686,7 @@ impl Executor {
                     default
                 };
 
-                self.do_branch(label, cursor)?;
+                return Ok(ControlFlow::Jump(self.do_branch(label)?));
             }
 
             // Type conversions
@@ -3692,8 +3999,18 @@ impl Executor {
         &self.module
     }
 
+    /// The parsed module, to instantiate more executors from
+    pub fn shared_module(&self) -> Arc<Module> {
+        Arc::clone(&self.module)
+    }
+
+    /// The module, for changes; decoded bodies are dropped, since its code
+    /// may change. A module shared with other executors is copied first, so
+    /// they keep running the original.
     pub fn module_mut(&mut self) -> &mut Module {
-        &mut self.module
+        let module = Arc::make_mut(&mut self.module);
+        module.code_cache = Default::default();
+        module
     }
 
     pub fn import_func_count(&self) -> usize {
@@ -3709,7 +4026,7 @@ impl Executor {
 
 #[cfg(test)]
 mod tests {
-    use super::super::module::{Function, FunctionType, TableType};
+    use super::super::module::{Function, FunctionType, GlobalValue, MemoryType, TableType};
     use super::*;
     use std::collections::HashMap;
     use std::sync::atomic::{AtomicBool, Ordering};
@@ -3737,6 +4054,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         }
     }
 
@@ -3775,6 +4093,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
         let mut executor = Executor::new(module).unwrap();
         executor.set_fuel(None);
@@ -3839,6 +4158,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
         let mut executor = Executor::new(module).unwrap();
         executor.set_cancel_token(Some(Arc::new(AtomicBool::new(false))));
@@ -4552,6 +4872,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4587,6 +4908,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4621,6 +4943,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4659,6 +4982,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4688,6 +5012,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4716,6 +5041,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let executor = Executor::new(module).unwrap();
@@ -4741,6 +5067,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4773,6 +5100,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -4810,6 +5138,7 @@ mod tests {
                 offset_expr: vec![0x41, 0x10, 0x0B], // i32.const 16, end
                 data: b"Hello, WASM!".to_vec(),
             }],
+            code_cache: Default::default(),
         };
 
         let executor = Executor::new(module).unwrap();
@@ -4845,6 +5174,7 @@ mod tests {
                     data: vec![0xBE, 0xEF],
                 },
             ],
+            code_cache: Default::default(),
         };
 
         let executor = Executor::new(module).unwrap();
@@ -4876,6 +5206,7 @@ mod tests {
                 offset_expr: vec![0x41, 0xFF, 0xFF, 0x03, 0x0B], // i32.const 65535
                 data: vec![0x00, 0x01], // 2 bytes at offset 65535 overflows 1 page
             }],
+            code_cache: Default::default(),
         };
 
         let result = Executor::new(module);
@@ -4904,6 +5235,7 @@ mod tests {
                 offset_expr: vec![], // passive segment (empty offset)
                 data: vec![0xFF; 100],
             }],
+            code_cache: Default::default(),
         };
 
         let executor = Executor::new(module).unwrap();
@@ -4921,8 +5253,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xA7, 0x0B]; // i32.wrap_i64, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::I32(42));
     }
@@ -4936,8 +5269,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xAC, 0x0B]; // i64.extend_i32_s, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::I64(-1));
     }
@@ -4951,8 +5285,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xAD, 0x0B]; // i64.extend_i32_u, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::I64(0xFFFF_FFFF_i64));
     }
@@ -4966,8 +5301,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xB2, 0x0B]; // f32.convert_i32_s, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::F32(-42.0));
     }
@@ -4981,8 +5317,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xBB, 0x0B]; // f64.promote_f32, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         match executor.context.pop().unwrap() {
             Value::F64(x) => assert!((x - 1.5).abs() < 0.001),
@@ -4999,8 +5336,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xB6, 0x0B]; // f32.demote_f64, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         match executor.context.pop().unwrap() {
             Value::F32(x) => assert!((x - 2.5).abs() < 0.001),
@@ -5017,8 +5355,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xBC, 0x0B]; // i32.reinterpret_f32, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(
             executor.context.pop().unwrap(),
@@ -5035,8 +5374,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xBE, 0x0B]; // f32.reinterpret_i32, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::F32(1.0));
     }
@@ -5050,8 +5390,7 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xAA, 0x0B]; // i32.trunc_f64_s, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        let result = executor.execute_bytecode(&mut cursor);
+        let result = executor.execute_body(&CompiledFunction::compile(&bytecode));
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("NaN"));
     }
@@ -5065,8 +5404,7 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0xA9, 0x0B]; // i32.trunc_f32_u, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        let result = executor.execute_bytecode(&mut cursor);
+        let result = executor.execute_body(&CompiledFunction::compile(&bytecode));
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("overflow"));
     }
@@ -5082,8 +5420,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0x1B, 0x0B]; // select, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::I32(10));
     }
@@ -5099,8 +5438,9 @@ mod tests {
         executor.context.push_frame(frame);
 
         let bytecode = vec![0x1B, 0x0B]; // select, end
-        let mut cursor = Cursor::new(bytecode.as_slice());
-        executor.execute_bytecode(&mut cursor).unwrap();
+        executor
+            .execute_body(&CompiledFunction::compile(&bytecode))
+            .unwrap();
         executor.context.pop_frame().unwrap();
         assert_eq!(executor.context.pop().unwrap(), Value::I32(20));
     }
@@ -5160,6 +5500,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -5209,6 +5550,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut executor = Executor::new(module).unwrap();
@@ -5312,6 +5654,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         }
     }
 
@@ -5556,6 +5899,7 @@ mod tests {
                 function_indices: vec![0, 1],
             }],
             data: vec![],
+            code_cache: Default::default(),
         };
         let mut executor = Executor::new(module).unwrap();
         let results = executor.execute(2).unwrap();
@@ -5634,6 +5978,7 @@ mod tests {
                 function_indices: vec![0],
             }],
             data: vec![],
+            code_cache: Default::default(),
         };
         let mut executor = Executor::new(module).unwrap();
         let results = executor.execute(0).unwrap();
@@ -5645,4 +5990,454 @@ mod tests {
         let err = executor.execute(0).unwrap_err();
         assert!(err.contains("already dropped"), "got: {err}");
     }
+
+    #[test]
+    fn test_memory_watch_reports_and_stops() {
+        use super::super::module::{ExportDesc, ExportKind, MemoryType};
+        use super::super::watchpoint::{MemoryWatch, WatchAction, Watchpoint};
+
+        // store 7 at 0x10, then 9 at 0x100
+        let code = vec![
+            0x41, 0x10, 0x41, 0x07, 0x36, 0x02, 0x00, // i32.store 0x10 <- 7
+            0x41, 0x80, 0x02, 0x41, 0x09, 0x36, 0x02, 0x00, // i32.store 0x100 <- 9
+            0x0b,
+        ];
+        let module = || Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![],
+            }],
+            imports: vec![],
+            functions: vec![Function {
+                type_index: 0,
+                locals: vec![],
+                code: code.clone(),
+            }],
+            tables: vec![],
+            memory: Some(MemoryType {
+                initial: 1,
+                max: None,
+            }),
+            globals: vec![],
+            exports: HashMap::from([(
+                "poke".to_string(),
+                ExportDesc {
+                    name: "poke".to_string(),
+                    kind: ExportKind::Function,
+                    index: 0,
+                },
+            )]),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        };
+        let watch = |action| {
+            Some(MemoryWatch::new(
+                vec![Watchpoint::parse("0x100..0x104").unwrap()],
+                action,
+            ))
+        };
+
+        let mut executor = Executor::new(module()).unwrap();
+        executor.set_memory_watch(watch(WatchAction::Log));
+        executor.execute(0).unwrap();
+        assert_eq!(executor.watch_hits(), 1);
+        assert_eq!(executor.context.memory.read_i32(0x100).unwrap(), 9);
+
+        let mut executor = Executor::new(module()).unwrap();
+        executor.set_memory_watch(watch(WatchAction::Stop));
+        let err = executor.execute(0).unwrap_err();
+        assert!(Executor::is_watchpoint_stop(&err), "got: {err}");
+        // Stopped before the write, after the unwatched one
+        assert_eq!(executor.context.memory.read_i32(0x100).unwrap(), 0);
+        assert_eq!(executor.context.memory.read_i32(0x10).unwrap(), 7);
+        assert_eq!(executor.backtrace(), vec!["func[0] \"poke\" +0xc"]);
+    }
+
+    #[test]
+    fn test_stack_limits_trap_with_call_chain() {
+        use super::super::module::{ExportDesc, ExportKind};
+
+        let function = |code: Vec<u8>| Function {
+            type_index: 0,
+            locals: vec![],
+            code,
+        };
+        let module = || Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![],
+            }],
+            imports: vec![],
+            functions: vec![
+                function(vec![0x10, 0x01, 0x0b]),             // main: call 1
+                function(vec![0x10, 0x01, 0x0b]),             // call 1 forever
+                function(vec![0x41, 0x01, 0x10, 0x02, 0x0b]), // push, call 2 forever
+            ],
+            tables: vec![],
+            memory: None,
+            globals: vec![],
+            exports: HashMap::from([(
+                "main".to_string(),
+                ExportDesc {
+                    name: "main".to_string(),
+                    kind: ExportKind::Function,
+                    index: 0,
+                },
+            )]),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        };
+
+        // Test threads have small stacks, so keep the limits tiny
+        let mut executor = Executor::new(module()).unwrap();
+        executor.set_stack_limits(16, 8);
+        let err = executor.execute(0).unwrap_err();
+        assert!(Executor::is_stack_exhausted(&err), "got: {err}");
+        assert!(err.contains("more than 16 nested calls"), "got: {err}");
+        assert!(
+            err.ends_with("func[1] ×15\n    func[0] \"main\""),
+            "got: {err}"
+        );
+
+        let mut executor = Executor::new(module()).unwrap();
+        executor.set_stack_limits(16, 8);
+        let err = executor.execute(2).unwrap_err();
+        assert!(err.contains("Operand stack exhausted"), "got: {err}");
+    }
+
+    #[test]
+    fn test_trap_backtrace_names_frames_and_offsets() {
+        use super::super::module::{ExportDesc, ExportKind};
+
+        let function = |code: Vec<u8>| Function {
+            type_index: 0,
+            locals: vec![],
+            code,
+        };
+        let mut executor = Executor::new(Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![],
+            }],
+            imports: vec![],
+            functions: vec![
+                function(vec![0x41, 0x00, 0x1a, 0x10, 0x01, 0x0b]), // drop 0, call 1
+                function(vec![0x41, 0x01, 0x41, 0x00, 0x6d, 0x1a, 0x0b]), // 1 / 0
+            ],
+            tables: vec![],
+            memory: None,
+            globals: vec![],
+            exports: HashMap::from([(
+                "main".to_string(),
+                ExportDesc {
+                    name: "main".to_string(),
+                    kind: ExportKind::Function,
+                    index: 0,
+                },
+            )]),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        })
+        .unwrap();
+        executor.set_function_names(HashMap::from([(1, "app::divide".to_string())]));
+
+        executor.execute(0).unwrap_err();
+        assert_eq!(
+            executor.backtrace(),
+            vec!["func[1] \"app::divide\" +0x4", "func[0] \"main\" +0x3"]
+        );
+        assert_eq!(
+            executor.backtrace_frames()[0].name.as_deref(),
+            Some("app::divide")
+        );
+    }
+
+    #[test]
+    fn test_instruction_trace_follows_filter() {
+        use super::super::trace::InstructionTrace;
+        use std::collections::HashSet;
+
+        let function = |code: Vec<u8>| Function {
+            type_index: 0,
+            locals: vec![],
+            code,
+        };
+        let mut executor = Executor::new(Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![],
+            }],
+            imports: vec![],
+            functions: vec![
+                function(vec![0x10, 0x01, 0x0b]),       // call 1
+                function(vec![0x41, 0x05, 0x1a, 0x0b]), // drop 5
+            ],
+            tables: vec![],
+            memory: None,
+            globals: vec![],
+            exports: HashMap::new(),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        })
+        .unwrap();
+        executor.set_function_names(HashMap::from([(1, "leaf".to_string())]));
+        assert_eq!(executor.find_function("leaf"), Some(1));
+        assert_eq!(executor.find_function("func[0]"), Some(0));
+        assert_eq!(executor.find_function("missing"), None);
+
+        executor.set_instruction_trace(Some(InstructionTrace::new(Some(HashSet::from([1])), 16)));
+        executor.execute(0).unwrap();
+        let trace = executor.take_instruction_trace().unwrap();
+        let entries: Vec<&str> = trace.entries().collect();
+        assert_eq!(
+            entries,
+            vec![
+                "func[1] \"leaf\" +0x0  I32Const(5)  stack[0]",
+                "func[1] \"leaf\" +0x2  Drop  stack[1] i32:5",
+                "func[1] \"leaf\" +0x3  End  stack[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coverage_counts_calls_and_taken_ifs() {
+        use super::super::coverage::{BlockCoverage, CoverageCounters};
+
+        let function = |code: Vec<u8>| Function {
+            type_index: 0,
+            locals: vec![],
+            code,
+        };
+        let mut executor = Executor::new(Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![],
+            }],
+            imports: vec![],
+            functions: vec![
+                // i32.const 0; if; call 1; end; call 1
+                function(vec![
+                    0x41, 0x00, 0x04, 0x40, 0x10, 0x01, 0x0b, 0x10, 0x01, 0x0b,
+                ]),
+                function(vec![0x0b]),
+                function(vec![0x0b]),
+            ],
+            tables: vec![],
+            memory: None,
+            globals: vec![],
+            exports: HashMap::new(),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        })
+        .unwrap();
+        assert!(executor.coverage_data("m.wasm").is_none());
+
+        executor.set_coverage(Some(CoverageCounters::new(true)));
+        executor.execute(0).unwrap();
+        let coverage = executor.coverage_data("m.wasm").unwrap();
+        let calls: Vec<u64> = coverage.functions.iter().map(|f| f.calls).collect();
+        assert_eq!(calls, vec![1, 1, 0]);
+        assert_eq!(
+            coverage.functions[0].blocks,
+            Some(vec![BlockCoverage {
+                offset: 2,
+                kind: "if".to_string(),
+                count: 0,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_repeated_calls_share_one_decoded_body() {
+        let function = |params: Vec<ValueType>, code: Vec<u8>| Function {
+            type_index: params.len() as u32,
+            locals: vec![],
+            code,
+        };
+        let mut executor = Executor::new(Module {
+            version: 1,
+            types: vec![
+                FunctionType {
+                    params: vec![],
+                    results: vec![ValueType::I32],
+                },
+                FunctionType {
+                    params: vec![ValueType::I32],
+                    results: vec![ValueType::I32],
+                },
+            ],
+            imports: vec![],
+            functions: vec![
+                // block (result i32); local.get 0; if (result i32); i32.const 1;
+                // br 1; else; i32.const 2; end; end
+                function(
+                    vec![ValueType::I32],
+                    vec![
+                        0x02, 0x7f, 0x20, 0x00, 0x04, 0x7f, 0x41, 0x01, 0x0c, 0x01, 0x05, 0x41,
+                        0x02, 0x0b, 0x0b, 0x0b,
+                    ],
+                ),
+                // i32.const 0; if; <undecodable>; end; i32.const 7
+                function(
+                    vec![],
+                    vec![0x41, 0x00, 0x04, 0x40, 0xff, 0x0b, 0x41, 0x07, 0x0b],
+                ),
+            ],
+            tables: vec![],
+            memory: None,
+            globals: vec![],
+            exports: HashMap::new(),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        })
+        .unwrap();
+
+        for (arg, expected) in [(1, 1), (0, 2), (5, 1)] {
+            let results = executor
+                .execute_with_args(0, vec![Value::I32(arg)])
+                .unwrap();
+            assert_eq!(results, vec![Value::I32(expected)]);
+        }
+        assert_eq!(executor.module().code_cache.compiled(), 1);
+
+        // A body is decoded up to an opcode the interpreter doesn't know, and
+        // the call fails when it runs into it rather than when it starts
+        let err = executor.execute(1).unwrap_err();
+        assert!(err.contains("0xFF"), "{err}");
+        assert_eq!(executor.module().code_cache.compiled(), 2);
+    }
+
+    #[test]
+    fn test_optimized_body_matches_decoded_results_and_fuel() {
+        let module = || Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![ValueType::I32],
+            }],
+            imports: vec![],
+            functions: vec![Function {
+                type_index: 0,
+                locals: vec![(2, ValueType::I32)],
+                // block; loop; local.get 0; i32.const 10; i32.ge_s; br_if 1;
+                // local.get 1; local.get 0; i32.const 2; i32.const 3; i32.mul;
+                // i32.add; i32.add; local.set 1; local.get 0; i32.const 1;
+                // i32.add; local.set 0; br 0; end; end; local.get 1; end
+                code: vec![
+                    0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x41, 0x0a, 0x4e, 0x0d, 0x01, 0x20, 0x01,
+                    0x20, 0x00, 0x41, 0x02, 0x41, 0x03, 0x6c, 0x6a, 0x6a, 0x21, 0x01, 0x20, 0x00,
+                    0x41, 0x01, 0x6a, 0x21, 0x00, 0x0c, 0x00, 0x0b, 0x0b, 0x20, 0x01, 0x0b,
+                ],
+            }],
+            tables: vec![],
+            memory: None,
+            globals: vec![],
+            exports: HashMap::new(),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        };
+
+        let mut fuel_used = Vec::new();
+        for optimize in [false, true] {
+            let mut executor = Executor::new(module()).unwrap();
+            executor.set_optimize(optimize);
+            executor.set_fuel(Some(10_000));
+            // 0 + 1 + ... + 9, plus 6 each time round
+            assert_eq!(executor.execute(0).unwrap(), vec![Value::I32(105)]);
+            fuel_used.push(10_000 - executor.fuel.unwrap());
+        }
+        assert_eq!(fuel_used[0], fuel_used[1]);
+
+        let optimized = module().optimized_function(0).unwrap();
+        assert!(optimized.len() < CompiledFunction::compile(&module().functions[0].code).len());
+    }
+
+    #[test]
+    fn test_executors_sharing_a_module_keep_their_own_state() {
+        let module = Arc::new(Module {
+            version: 1,
+            types: vec![FunctionType {
+                params: vec![],
+                results: vec![ValueType::I32],
+            }],
+            imports: vec![],
+            // Bump global 0, store it at address 0 and load it back:
+            // global.get 0; i32.const 1; i32.add; global.set 0; i32.const 0;
+            // global.get 0; i32.store; i32.const 0; i32.load; end
+            functions: vec![Function {
+                type_index: 0,
+                locals: vec![],
+                code: vec![
+                    0x23, 0x00, 0x41, 0x01, 0x6a, 0x24, 0x00, 0x41, 0x00, 0x23, 0x00, 0x36, 0x02,
+                    0x00, 0x41, 0x00, 0x28, 0x02, 0x00, 0x0b,
+                ],
+            }],
+            tables: vec![],
+            memory: Some(MemoryType {
+                initial: 2,
+                max: None,
+            }),
+            globals: vec![GlobalValue {
+                mutable: true,
+                value_type: ValueType::I32,
+                init_expr: vec![],
+            }],
+            exports: HashMap::new(),
+            start: None,
+            elements: vec![],
+            data: vec![],
+            code_cache: Default::default(),
+        });
+
+        let counts: Vec<Vec<Value>> = (1..=4)
+            .map(|calls| {
+                let module = Arc::clone(&module);
+                std::thread::spawn(move || {
+                    let mut executor = Executor::new(module).unwrap();
+                    (0..calls).map(|_| executor.execute(0).unwrap()).last()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|thread| thread.join().unwrap().unwrap())
+            .collect();
+        assert_eq!(
+            counts,
+            (1..=4).map(|n| vec![Value::I32(n)]).collect::<Vec<_>>()
+        );
+        assert_eq!(module.code_cache.compiled(), 1);
+
+        // A memory cap applies to one instance, not to the shared module
+        let err = Executor::instantiate(Arc::clone(&module), Linker::new(), Some(1))
+            .err()
+            .unwrap();
+        assert!(err.contains("exceeds max pages"), "{err}");
+        let mut executor =
+            Executor::instantiate(Arc::clone(&module), Linker::new(), Some(2)).unwrap();
+        assert_eq!(executor.execute(0).unwrap(), vec![Value::I32(1)]);
+
+        // Changing one executor's module leaves the others' alone
+        executor.module_mut().globals.clear();
+        assert!(!Arc::ptr_eq(&executor.shared_module(), &module));
+        assert_eq!(module.globals.len(), 1);
+    }
 }
@@ -0,0 +1,288 @@
+/// Host functions defined in a TOML spec file
+/// `wasmrun exec --host-functions host.toml` registers simple host functions
+/// without writing Rust, so a module can be tested in isolation:
+///
+/// ```toml
+/// [imports."env::answer"]
+/// action = "return"
+/// values = [42]
+///
+/// [imports."env::load_input"]
+/// action = "read_file"
+/// path = "fixtures/input.bin"
+/// log = true
+/// ```
+use super::linker::{ClosureHostFunction, Linker};
+use super::memory::LinearMemory;
+use super::module::{FunctionType, ImportKind, Module, ValueType};
+use super::stubs::{log_call, zero_value};
+use super::values::Value;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HostSpec {
+    /// Behaviors keyed by `module::name`
+    #[serde(default)]
+    pub imports: BTreeMap<String, HostFunctionSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HostFunctionSpec {
+    #[serde(flatten)]
+    pub action: HostAction,
+    /// Print each call with its arguments and results
+    #[serde(default)]
+    pub log: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HostAction {
+    /// Return these values, one per result; missing ones are zero
+    Return {
+        #[serde(default)]
+        values: Vec<Number>,
+    },
+    /// Return the arguments, for an import whose results match its first
+    /// parameters
+    Echo,
+    /// Copy a file into memory at `(ptr, len)`, returning the bytes copied
+    ReadFile { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl HostSpec {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read host function spec {}: {e}", path.display()))?;
+        toml::from_str(&content).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Register the functions `module` imports on `linker`, replacing any it
+    /// already provides. Relative paths are resolved against `base_dir`.
+    /// Returns the registered imports as `module::name`.
+    pub fn register(
+        &self,
+        linker: &mut Linker,
+        module: &Module,
+        base_dir: &Path,
+    ) -> Result<Vec<String>, String> {
+        let mut registered = Vec::new();
+        for import in &module.imports {
+            let ImportKind::Function(type_idx) = import.kind else {
+                continue;
+            };
+            let name = format!("{}::{}", import.module, import.name);
+            let Some(spec) = self.imports.get(&name) else {
+                continue;
+            };
+            let func_type = module
+                .types
+                .get(type_idx as usize)
+                .ok_or_else(|| format!("Type index {type_idx} of import {name} out of bounds"))?;
+            let behavior = Behavior::new(&name, &spec.action, func_type, base_dir)?;
+            let log = spec.log;
+            let label = name.clone();
+            linker.register(
+                &import.module,
+                &import.name,
+                Box::new(ClosureHostFunction::new(
+                    move |args: Vec<Value>, mem: &mut LinearMemory| {
+                        let results = behavior.call(&args, mem)?;
+                        if log {
+                            log_call(&label, &args, &results);
+                        }
+                        Ok(results)
+                    },
+                    func_type.params.len(),
+                    func_type.results.len(),
+                )),
+            );
+            registered.push(name);
+        }
+        Ok(registered)
+    }
+}
+
+/// A spec action checked against the import's signature
+enum Behavior {
+    Return(Vec<Value>),
+    Echo(usize),
+    ReadFile { data: Vec<u8>, returns_len: bool },
+}
+
+impl Behavior {
+    fn new(
+        name: &str,
+        action: &HostAction,
+        func_type: &FunctionType,
+        base_dir: &Path,
+    ) -> Result<Self, String> {
+        match action {
+            HostAction::Return { values } => {
+                if values.len() > func_type.results.len() {
+                    return Err(format!(
+                        "{name} returns {} value(s), but the spec gives {}",
+                        func_type.results.len(),
+                        values.len()
+                    ));
+                }
+                func_type
+                    .results
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| match values.get(i) {
+                        Some(number) => typed_value(name, *number, ty),
+                        None => Ok(zero_value(ty)),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Behavior::Return)
+            }
+            HostAction::Echo => {
+                let results = &func_type.results;
+                if !func_type.params.starts_with(results) {
+                    return Err(format!(
+                        "{name} can't echo: its results {results:?} don't match its first parameters {:?}",
+                        func_type.params
+                    ));
+                }
+                Ok(Behavior::Echo(results.len()))
+            }
+            HostAction::ReadFile { path } => {
+                let pointer_and_length = [ValueType::I32, ValueType::I32];
+                let returns_len = match func_type.results.as_slice() {
+                    [] => false,
+                    [ValueType::I32] => true,
+                    _ => return Err(format!("{name} must return nothing or an i32 to read_file")),
+                };
+                if func_type.params != pointer_and_length {
+                    return Err(format!(
+                        "{name} must take (ptr: i32, len: i32) to read_file"
+                    ));
+                }
+                let path = base_dir.join(path);
+                let data = fs::read(&path)
+                    .map_err(|e| format!("{name}: failed to read {}: {e}", path.display()))?;
+                Ok(Behavior::ReadFile { data, returns_len })
+            }
+        }
+    }
+
+    fn call(&self, args: &[Value], mem: &mut LinearMemory) -> Result<Vec<Value>, String> {
+        match self {
+            Behavior::Return(values) => Ok(values.clone()),
+            Behavior::Echo(count) => Ok(args[..*count].to_vec()),
+            Behavior::ReadFile { data, returns_len } => {
+                let (Some(Value::I32(ptr)), Some(Value::I32(len))) = (args.first(), args.get(1))
+                else {
+                    return Err("read_file expects (ptr: i32, len: i32)".to_string());
+                };
+                let copied = data.len().min(*len as u32 as usize);
+                mem.write_bytes(*ptr as u32 as usize, &data[..copied])?;
+                Ok(if *returns_len {
+                    vec![Value::I32(copied as i32)]
+                } else {
+                    Vec::new()
+                })
+            }
+        }
+    }
+}
+
+fn typed_value(name: &str, number: Number, ty: &ValueType) -> Result<Value, String> {
+    let out_of_range = || format!("{name}: value out of range for {ty:?}");
+    Ok(match (number, ty) {
+        (Number::Int(v), ValueType::I32) => {
+            // Accept both signed and unsigned 32-bit spellings
+            if let Ok(v) = i32::try_from(v) {
+                Value::I32(v)
+            } else {
+                Value::I32(u32::try_from(v).map_err(|_| out_of_range())? as i32)
+            }
+        }
+        (Number::Int(v), ValueType::I64) => Value::I64(v),
+        (Number::Int(v), ValueType::F32) => Value::F32(v as f32),
+        (Number::Int(v), ValueType::F64) => Value::F64(v as f64),
+        (Number::Float(v), ValueType::F32) => Value::F32(v as f32),
+        (Number::Float(v), ValueType::F64) => Value::F64(v),
+        _ => return Err(format!("{name}: can't return {number:?} as {ty:?}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::core::module::ImportDesc;
+
+    #[test]
+    fn test_host_spec_registers_behaviors() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("input.txt"), "hello").unwrap();
+        let spec: HostSpec = toml::from_str(
+            r#"
+            [imports."env::answer"]
+            action = "return"
+            values = [42, 1.5]
+
+            [imports."env::same"]
+            action = "echo"
+            log = true
+
+            [imports."env::load"]
+            action = "read_file"
+            path = "input.txt"
+            "#,
+        )
+        .unwrap();
+
+        let mut module = Module::new();
+        let signatures = [
+            (vec![], vec![ValueType::I32, ValueType::F64]),
+            (vec![ValueType::I64, ValueType::I32], vec![ValueType::I64]),
+            (vec![ValueType::I32, ValueType::I32], vec![ValueType::I32]),
+        ];
+        for (i, (params, results)) in signatures.into_iter().enumerate() {
+            module.types.push(FunctionType { params, results });
+            module.imports.push(ImportDesc {
+                module: "env".to_string(),
+                name: ["answer", "same", "load"][i].to_string(),
+                kind: ImportKind::Function(i as u32),
+            });
+        }
+
+        let mut linker = Linker::new();
+        let registered = spec.register(&mut linker, &module, dir.path()).unwrap();
+        assert_eq!(registered, vec!["env::answer", "env::same", "env::load"]);
+
+        let mut memory = LinearMemory::new(1, None).unwrap();
+        let call = |name: &str, args: Vec<Value>, memory: &mut LinearMemory| {
+            linker.get_import("env", name).unwrap().call(args, memory)
+        };
+        assert_eq!(
+            call("answer", vec![], &mut memory),
+            Ok(vec![Value::I32(42), Value::F64(1.5)])
+        );
+        assert_eq!(
+            call("same", vec![Value::I64(9), Value::I32(1)], &mut memory),
+            Ok(vec![Value::I64(9)])
+        );
+        assert_eq!(
+            call("load", vec![Value::I32(16), Value::I32(3)], &mut memory),
+            Ok(vec![Value::I32(3)])
+        );
+        assert_eq!(memory.read_bytes(16, 4).unwrap(), b"hel\0");
+
+        // Signatures are checked when registering
+        module.types[0].results = vec![ValueType::I32];
+        assert!(spec.register(&mut linker, &module, dir.path()).is_err());
+    }
+}
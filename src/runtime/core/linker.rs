@@ -50,6 +50,12 @@ pub struct Linker {
     host_functions: HashMap<String, Box<dyn HostFunction>>,
 }
 
+impl Default for Linker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Linker {
     pub fn new() -> Self {
         Linker {
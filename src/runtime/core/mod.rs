@@ -1,12 +1,41 @@
 #![allow(dead_code)]
 
+//! The native interpreter. The module parser is always built, since
+//! analysis, `verify` and `inspect` read modules without running them.
+
+#[cfg(feature = "interpreter")]
+pub mod compiled;
+#[cfg(feature = "interpreter")]
 pub mod control_flow;
+#[cfg(feature = "interpreter")]
+pub mod coverage;
+#[cfg(feature = "interpreter")]
 pub mod executor;
+#[cfg(feature = "interpreter")]
+pub mod host_spec;
+#[cfg(feature = "interpreter")]
 pub mod linker;
+#[cfg(feature = "interpreter")]
 pub mod memory;
 pub mod module;
+#[cfg(feature = "interpreter")]
+pub mod module_cache;
+#[cfg(feature = "interpreter")]
 pub mod native_executor;
+#[cfg(feature = "interpreter")]
+pub mod optimizer;
+#[cfg(feature = "interpreter")]
+pub mod panic;
+#[cfg(feature = "interpreter")]
+pub mod stubs;
+#[cfg(feature = "interpreter")]
+pub mod trace;
+#[cfg(feature = "interpreter")]
+pub mod trap;
+#[cfg(feature = "interpreter")]
 pub mod values;
+#[cfg(feature = "interpreter")]
+pub mod watchpoint;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "interpreter"))]
 mod tests;
@@ -1,5 +1,10 @@
+#[cfg(feature = "interpreter")]
+use super::compiled::{CodeCache, CompiledFunction};
+use rayon::prelude::*;
 use std::collections::HashMap;
 use std::io::{Cursor, Read};
+#[cfg(feature = "interpreter")]
+use std::sync::Arc;
 
 const WASM_MAGIC_BYTES: &[u8; 4] = b"\0asm";
 const WASM_VERSION: u32 = 1;
@@ -119,7 +124,11 @@ pub struct ElementSegment {
 }
 
 /// Parsed WASM module
-#[derive(Debug)]
+///
+/// Holds nothing a running program changes, so one parse can be shared as an
+/// `Arc<Module>` by any number of executors, each with its own memory,
+/// globals and tables.
+#[derive(Debug, Clone)]
 pub struct Module {
     pub version: u32,
     pub types: Vec<FunctionType>,
@@ -132,137 +141,32 @@ pub struct Module {
     pub start: Option<u32>,
     pub elements: Vec<ElementSegment>,
     pub data: Vec<DataSegment>,
+    /// Function bodies decoded for the interpreter, filled in as they are
+    /// first called
+    #[cfg(feature = "interpreter")]
+    pub code_cache: CodeCache,
+}
+
+impl Default for Module {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Module {
     /// Parse a WASM module from bytes
+    ///
+    /// Sections are parsed in parallel once their boundaries are known, and
+    /// so are the function bodies of the code section.
     pub fn parse(bytes: &[u8]) -> Result<Self, String> {
-        let mut cursor = Cursor::new(bytes);
-        let mut module = Module {
-            version: 0,
-            types: Vec::new(),
-            imports: Vec::new(),
-            functions: Vec::new(),
-            tables: Vec::new(),
-            memory: None,
-            globals: Vec::new(),
-            exports: HashMap::new(),
-            start: None,
-            elements: Vec::new(),
-            data: Vec::new(),
-        };
-
-        // Verify magic bytes and version
-        let mut magic = [0u8; 4];
-        cursor
-            .read_exact(&mut magic)
-            .map_err(|_| "File too small")?;
-        if &magic != WASM_MAGIC_BYTES {
-            return Err("Invalid WASM magic bytes".to_string());
-        }
-
-        // Version is 4 fixed bytes (little-endian u32), not LEB128!
-        let mut version_bytes = [0u8; 4];
-        cursor
-            .read_exact(&mut version_bytes)
-            .map_err(|_| "File too small - missing version")?;
-        module.version = u32::from_le_bytes(version_bytes);
-        if module.version != WASM_VERSION {
-            return Err(format!("Unsupported WASM version: {}", module.version));
-        }
-
-        // Parse sections
-        let bytes = bytes.to_vec();
-        let total_len = bytes.len();
-        let mut pos = cursor.position() as usize;
-
-        while pos < total_len {
-            // Read section ID
-            let mut section_cursor = Cursor::new(bytes[pos..].to_vec());
-            let section_id = read_leb128_u32(&mut section_cursor)?;
-            pos += section_cursor.position() as usize;
-
-            // Read section size
-            section_cursor = Cursor::new(bytes[pos..].to_vec());
-            let section_size = read_leb128_u32(&mut section_cursor)? as usize;
-            pos += section_cursor.position() as usize;
-
-            let section_end = pos + section_size;
-            if section_end > total_len {
-                return Err(format!(
-                    "Section {section_id} extends beyond end of module (pos={pos}, size={section_size}, total={total_len})"
-                ));
-            }
-
-            let section_data = &bytes[pos..section_end];
-
-            match section_id {
-                0 => {
-                    // Custom section - skip
-                }
-                1 => {
-                    // Type section
-                    module.types = parse_type_section(section_data)?;
-                }
-                2 => {
-                    // Import section
-                    module.imports = parse_import_section(section_data, &module.types)?;
-                }
-                3 => {
-                    // Function section
-                    module.functions = parse_function_section(section_data)?;
-                }
-                4 => {
-                    // Table section
-                    module.tables = parse_table_section(section_data)?;
-                }
-                5 => {
-                    // Memory section
-                    module.memory = parse_memory_section(section_data)?;
-                }
-                6 => {
-                    // Global section
-                    module.globals = parse_global_section(section_data)?;
-                }
-                7 => {
-                    // Export section
-                    module.exports = parse_export_section(section_data)?;
-                }
-                8 => {
-                    // Start section
-                    let mut c = Cursor::new(section_data.to_vec());
-                    module.start = Some(read_leb128_u32(&mut c)?);
-                }
-                9 => {
-                    // Element section
-                    module.elements = parse_element_section(section_data)?;
-                }
-                10 => {
-                    // Code section - merge with function section
-                    let code_bodies = parse_code_section(section_data)?;
-                    for (i, body) in code_bodies.into_iter().enumerate() {
-                        if i < module.functions.len() {
-                            module.functions[i].locals = body.locals;
-                            module.functions[i].code = body.code;
-                        }
-                    }
-                }
-                11 => {
-                    // Data section
-                    module.data = parse_data_section(section_data)?;
-                }
-                12 => {
-                    // DataCount section - skip for now
-                }
-                _ => {
-                    // Unknown section - skip
-                }
-            }
-
-            pos = section_end;
-        }
+        LazyModule::parse(bytes)?.into_module()
+    }
 
-        Ok(module)
+    /// Parse everything but the code section: functions keep their type
+    /// index but get no locals or code, so large modules are read without
+    /// decoding a single function body
+    pub fn parse_headers(bytes: &[u8]) -> Result<Self, String> {
+        LazyModule::parse(bytes).map(LazyModule::into_headers)
     }
 
     /// Create an empty module (useful for testing)
@@ -279,9 +183,25 @@ impl Module {
             start: None,
             elements: Vec::new(),
             data: Vec::new(),
+            #[cfg(feature = "interpreter")]
+            code_cache: CodeCache::default(),
         }
     }
 
+    /// Decoded body of defined function `index`, decoded on first use and
+    /// shared by every later call
+    #[cfg(feature = "interpreter")]
+    pub fn compiled_function(&self, index: usize) -> Option<Arc<CompiledFunction>> {
+        self.code_cache.get(&self.functions, index)
+    }
+
+    /// Like [`Module::compiled_function`], with common instruction sequences
+    /// fused by the optimizer
+    #[cfg(feature = "interpreter")]
+    pub fn optimized_function(&self, index: usize) -> Option<Arc<CompiledFunction>> {
+        self.code_cache.get_optimized(&self.functions, index)
+    }
+
     /// Get function by index, accounting for imported functions
     pub fn get_function(&self, idx: u32) -> Option<&Function> {
         let import_count = self
@@ -314,6 +234,187 @@ impl Module {
     }
 }
 
+/// A module whose sections are parsed but whose function bodies are
+/// only located. Bodies are decoded on demand, for size profiling or
+/// execution, straight from the module's bytes, which may be
+/// memory-mapped (see [`crate::utils::MappedFile`]).
+pub struct LazyModule<'a> {
+    /// Functions here have a type but no locals or code
+    headers: Module,
+    /// Encoded body of each defined function, locals included
+    bodies: Vec<&'a [u8]>,
+}
+
+impl<'a> LazyModule<'a> {
+    /// Parse every section but the function bodies, which are only split
+    /// apart. Sections are parsed in parallel once their boundaries are
+    /// known; results are merged in file order, so the first malformed
+    /// section is the one reported.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        let mut cursor = Cursor::new(bytes);
+        let mut module = Module::new();
+
+        // Verify magic bytes and version
+        let mut magic = [0u8; 4];
+        cursor
+            .read_exact(&mut magic)
+            .map_err(|_| "File too small")?;
+        if &magic != WASM_MAGIC_BYTES {
+            return Err("Invalid WASM magic bytes".to_string());
+        }
+
+        // Version is 4 fixed bytes (little-endian u32), not LEB128!
+        let mut version_bytes = [0u8; 4];
+        cursor
+            .read_exact(&mut version_bytes)
+            .map_err(|_| "File too small - missing version")?;
+        module.version = u32::from_le_bytes(version_bytes);
+        if module.version != WASM_VERSION {
+            return Err(format!("Unsupported WASM version: {}", module.version));
+        }
+
+        let sections = section_spans(&mut cursor)?;
+        let parsed: Vec<Result<ParsedSection<'a>, String>> = sections
+            .into_par_iter()
+            .map(|(id, data)| parse_section(id, data))
+            .collect();
+
+        let mut bodies = Vec::new();
+        for section in parsed {
+            match section? {
+                ParsedSection::Types(types) => module.types = types,
+                ParsedSection::Imports(imports) => module.imports = imports,
+                ParsedSection::Functions(functions) => module.functions = functions,
+                ParsedSection::Tables(tables) => module.tables = tables,
+                ParsedSection::Memory(memory) => module.memory = memory,
+                ParsedSection::Globals(globals) => module.globals = globals,
+                ParsedSection::Exports(exports) => module.exports = exports,
+                ParsedSection::Start(start) => module.start = Some(start),
+                ParsedSection::Elements(elements) => module.elements = elements,
+                ParsedSection::Code(spans) => bodies = spans,
+                ParsedSection::Data(data) => module.data = data,
+                ParsedSection::Skipped => {}
+            }
+        }
+
+        Ok(Self {
+            headers: module,
+            bodies,
+        })
+    }
+
+    /// The module without its function bodies
+    pub fn headers(&self) -> &Module {
+        &self.headers
+    }
+
+    pub fn into_headers(self) -> Module {
+        self.headers
+    }
+
+    /// Size of the instructions of defined function `index`, without the
+    /// local declarations in front of them, which are all that is decoded
+    pub fn code_len(&self, index: usize) -> Result<usize, String> {
+        let Some(body) = self.bodies.get(index) else {
+            return Ok(0);
+        };
+        let mut cursor = Cursor::new(*body);
+        read_locals(&mut cursor)?;
+        Ok(body.len() - cursor.position() as usize)
+    }
+
+    /// Decode defined function `index`; `None` past the last function
+    pub fn function(&self, index: usize) -> Option<Result<Function, String>> {
+        let function = self.headers.functions.get(index)?;
+        let Some(body) = self.bodies.get(index) else {
+            return Some(Ok(function.clone()));
+        };
+        Some(parse_function_body(body).map(|body| Function {
+            type_index: function.type_index,
+            locals: body.locals,
+            code: body.code,
+        }))
+    }
+
+    /// Decode every function body, in parallel, into a complete [`Module`]
+    pub fn into_module(self) -> Result<Module, String> {
+        let mut module = self.headers;
+        let bodies: Vec<Result<FunctionBody, String>> = self
+            .bodies
+            .into_par_iter()
+            .map(parse_function_body)
+            .collect();
+
+        // Merge with the function section
+        for (function, body) in module.functions.iter_mut().zip(bodies) {
+            let body = body?;
+            function.locals = body.locals;
+            function.code = body.code;
+        }
+        Ok(module)
+    }
+}
+
+/// What one section contributes to a [`LazyModule`]
+enum ParsedSection<'a> {
+    Types(Vec<FunctionType>),
+    Imports(Vec<ImportDesc>),
+    Functions(Vec<Function>),
+    Tables(Vec<TableType>),
+    Memory(Option<MemoryType>),
+    Globals(Vec<GlobalValue>),
+    Exports(HashMap<String, ExportDesc>),
+    Start(u32),
+    Elements(Vec<ElementSegment>),
+    Code(Vec<&'a [u8]>),
+    Data(Vec<DataSegment>),
+    /// Custom, DataCount and unknown sections
+    Skipped,
+}
+
+/// Id and contents of each section after the module header, in file order
+fn section_spans<'a>(cursor: &mut Cursor<&'a [u8]>) -> Result<Vec<(u32, &'a [u8])>, String> {
+    let bytes: &'a [u8] = cursor.get_ref();
+    let total_len = bytes.len();
+    let mut sections = Vec::new();
+
+    while (cursor.position() as usize) < total_len {
+        let section_id = read_leb128_u32(cursor)?;
+        let section_size = read_leb128_u32(cursor)? as usize;
+        let pos = cursor.position() as usize;
+
+        let section_end = pos + section_size;
+        if section_end > total_len {
+            return Err(format!(
+                "Section {section_id} extends beyond end of module (pos={pos}, size={section_size}, total={total_len})"
+            ));
+        }
+
+        sections.push((section_id, &bytes[pos..section_end]));
+        cursor.set_position(section_end as u64);
+    }
+
+    Ok(sections)
+}
+
+fn parse_section(id: u32, data: &[u8]) -> Result<ParsedSection<'_>, String> {
+    Ok(match id {
+        1 => ParsedSection::Types(parse_type_section(data)?),
+        2 => ParsedSection::Imports(parse_import_section(data)?),
+        3 => ParsedSection::Functions(parse_function_section(data)?),
+        4 => ParsedSection::Tables(parse_table_section(data)?),
+        5 => ParsedSection::Memory(parse_memory_section(data)?),
+        6 => ParsedSection::Globals(parse_global_section(data)?),
+        7 => ParsedSection::Exports(parse_export_section(data)?),
+        8 => ParsedSection::Start(read_leb128_u32(&mut Cursor::new(data))?),
+        9 => ParsedSection::Elements(parse_element_section(data)?),
+        10 => ParsedSection::Code(split_code_section(data)?),
+        11 => ParsedSection::Data(parse_data_section(data)?),
+        // Custom (0), DataCount (12) and unknown sections are skipped
+        _ => ParsedSection::Skipped,
+    })
+}
+
 /// Parse Type section (function signatures)
 fn parse_type_section(data: &[u8]) -> Result<Vec<FunctionType>, String> {
     let mut cursor = Cursor::new(data);
@@ -347,7 +448,7 @@ fn parse_type_section(data: &[u8]) -> Result<Vec<FunctionType>, String> {
 }
 
 /// Parse Import section
-fn parse_import_section(data: &[u8], _types: &[FunctionType]) -> Result<Vec<ImportDesc>, String> {
+fn parse_import_section(data: &[u8]) -> Result<Vec<ImportDesc>, String> {
     let mut cursor = Cursor::new(data);
     let count = read_leb128_u32(&mut cursor)? as usize;
 
@@ -432,8 +533,9 @@ struct FunctionBody {
     code: Vec<u8>,
 }
 
-/// Parse Code section (function bodies)
-fn parse_code_section(data: &[u8]) -> Result<Vec<FunctionBody>, String> {
+/// Split the Code section into function bodies, found in one pass over
+/// their size prefixes; nothing inside a body is decoded
+fn split_code_section(data: &[u8]) -> Result<Vec<&[u8]>, String> {
     let mut cursor = Cursor::new(data);
     let count = read_leb128_u32(&mut cursor)? as usize;
 
@@ -447,26 +549,35 @@ fn parse_code_section(data: &[u8]) -> Result<Vec<FunctionBody>, String> {
             return Err("Code section overflow".to_string());
         }
 
-        // Parse locals
-        let locals_count = read_leb128_u32(&mut cursor)? as usize;
-        let mut locals = Vec::with_capacity(locals_count);
+        bodies.push(&data[body_start..body_end]);
+        cursor.set_position(body_end as u64);
+    }
 
-        for _ in 0..locals_count {
-            let count = read_leb128_u32(&mut cursor)?;
-            let value_type = read_value_type(&mut cursor)?;
-            locals.push((count, value_type));
-        }
+    Ok(bodies)
+}
 
-        // The rest of the body is the actual instruction code
-        let code_start = cursor.position() as usize;
-        let code = data[code_start..body_end].to_vec();
+/// Parse one function body: its local declarations, then the code
+fn parse_function_body(body: &[u8]) -> Result<FunctionBody, String> {
+    let mut cursor = Cursor::new(body);
+    let locals = read_locals(&mut cursor)?;
 
-        bodies.push(FunctionBody { locals, code });
+    // The rest of the body is the actual instruction code
+    let code = body[cursor.position() as usize..].to_vec();
+    Ok(FunctionBody { locals, code })
+}
 
-        cursor.set_position(body_end as u64);
+/// The local declarations at the start of a function body
+fn read_locals(cursor: &mut Cursor<&[u8]>) -> Result<Vec<(u32, ValueType)>, String> {
+    let locals_count = read_leb128_u32(cursor)? as usize;
+    let mut locals = Vec::with_capacity(locals_count);
+
+    for _ in 0..locals_count {
+        let count = read_leb128_u32(cursor)?;
+        let value_type = read_value_type(cursor)?;
+        locals.push((count, value_type));
     }
 
-    Ok(bodies)
+    Ok(locals)
 }
 
 /// Parse Table section
@@ -777,6 +888,66 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// One `() -> ()` type and two functions: one with an i32 local, one
+    /// that drops a constant
+    fn two_function_module() -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x03, 0x03, 0x02, 0x00, 0x00]);
+        bytes.extend_from_slice(&[
+            0x0a, 0x0c, 0x02, // code section, two bodies
+            0x04, 0x01, 0x01, 0x7f, 0x0b, // (local i32) end
+            0x05, 0x00, 0x41, 0x2a, 0x1a, 0x0b, // i32.const 42 drop end
+        ]);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_code_bodies() {
+        let module = Module::parse(&two_function_module()).unwrap();
+        assert_eq!(module.functions.len(), 2);
+        assert_eq!(module.functions[0].locals, vec![(1, ValueType::I32)]);
+        assert_eq!(module.functions[0].code, vec![0x0b]);
+        assert!(module.functions[1].locals.is_empty());
+        assert_eq!(module.functions[1].code, vec![0x41, 0x2a, 0x1a, 0x0b]);
+    }
+
+    #[test]
+    fn test_parse_headers_skips_code() {
+        let module = Module::parse_headers(&two_function_module()).unwrap();
+        assert_eq!(module.types.len(), 1);
+        assert_eq!(module.functions.len(), 2);
+        assert!(module.functions.iter().all(|f| f.code.is_empty()));
+    }
+
+    #[test]
+    fn test_lazy_module_decodes_bodies_on_demand() {
+        let bytes = two_function_module();
+        let lazy = LazyModule::parse(&bytes).unwrap();
+        assert!(lazy.headers().functions.iter().all(|f| f.code.is_empty()));
+        assert_eq!(lazy.code_len(0).unwrap(), 1);
+        assert_eq!(lazy.code_len(1).unwrap(), 4);
+
+        let second = lazy.function(1).unwrap().unwrap();
+        assert_eq!(second.code, vec![0x41, 0x2a, 0x1a, 0x0b]);
+        assert!(lazy.function(2).is_none());
+
+        let module = lazy.into_module().unwrap();
+        assert_eq!(module.functions[0].locals, vec![(1, ValueType::I32)]);
+    }
+
+    #[test]
+    fn test_parse_reports_truncated_body() {
+        let mut bytes = two_function_module();
+        // Second body claims one byte more than the section holds
+        let len = bytes.len();
+        bytes[len - 6] = 0x06;
+        assert_eq!(
+            Module::parse(&bytes).unwrap_err(),
+            "Code section overflow".to_string()
+        );
+    }
+
     #[test]
     fn test_value_type_from_byte() {
         assert_eq!(ValueType::from_byte(0x7F), Some(ValueType::I32));
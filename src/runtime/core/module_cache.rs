@@ -0,0 +1,125 @@
+/// Parsed modules shared between executions
+/// Running the same bytes again, from another request or another thread,
+/// reuses the parsed module and every function body already decoded for it
+/// instead of parsing from scratch. Each execution still instantiates its
+/// own executor, so nothing a program writes is shared.
+use super::module::Module;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Modules kept by [`ModuleCache::shared`]
+const SHARED_CAPACITY: usize = 16;
+
+/// The most recently used parsed modules, keyed by a hash of their bytes
+#[derive(Debug)]
+pub struct ModuleCache {
+    capacity: usize,
+    /// Least recently used first
+    entries: Mutex<Vec<([u8; 32], Arc<Module>)>>,
+}
+
+impl ModuleCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The cache used by every execution in this process
+    pub fn shared() -> &'static ModuleCache {
+        static SHARED: OnceLock<ModuleCache> = OnceLock::new();
+        SHARED.get_or_init(|| ModuleCache::new(SHARED_CAPACITY))
+    }
+
+    /// The parsed module for `bytes`, parsing them if no cached module has
+    /// the same contents. A module that fails to parse isn't kept.
+    pub fn get_or_parse(&self, bytes: &[u8]) -> Result<Arc<Module>, String> {
+        let key: [u8; 32] = Sha256::digest(bytes).into();
+        if let Some(module) = self.touch(&key) {
+            return Ok(module);
+        }
+
+        // Parse without holding the lock, so other modules stay available
+        let module = Arc::new(Module::parse(bytes)?);
+        let mut entries = self.lock();
+        if let Some(index) = entries.iter().position(|(k, _)| *k == key) {
+            // Another thread parsed the same bytes first; share its copy
+            let entry = entries.remove(index);
+            let module = Arc::clone(&entry.1);
+            entries.push(entry);
+            return Ok(module);
+        }
+        if entries.len() >= self.capacity.max(1) {
+            entries.remove(0);
+        }
+        entries.push((key, Arc::clone(&module)));
+        Ok(module)
+    }
+
+    /// Number of modules held
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cached module for `key`, marked as the most recently used
+    fn touch(&self, key: &[u8; 32]) -> Option<Arc<Module>> {
+        let mut entries = self.lock();
+        let index = entries.iter().position(|(k, _)| k == key)?;
+        let entry = entries.remove(index);
+        let module = Arc::clone(&entry.1);
+        entries.push(entry);
+        Some(module)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<([u8; 32], Arc<Module>)>> {
+        // A panic while holding the lock can't leave the list half-updated
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(func (result i32) i32.const <n>)`, exported as `run`
+    fn constant_module(n: u8) -> Vec<u8> {
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        wasm.extend([0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f]);
+        wasm.extend([0x03, 0x02, 0x01, 0x00]);
+        wasm.extend([0x07, 0x07, 0x01, 0x03, b'r', b'u', b'n', 0x00, 0x00]);
+        wasm.extend([0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, n, 0x0b]);
+        wasm
+    }
+
+    #[test]
+    fn test_same_bytes_share_one_parse() {
+        let cache = ModuleCache::new(4);
+        let first = cache.get_or_parse(&constant_module(1)).unwrap();
+        let again = cache.get_or_parse(&constant_module(1)).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        let other = cache.get_or_parse(&constant_module(2)).unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_least_recently_used_module_is_dropped() {
+        let cache = ModuleCache::new(2);
+        let first = cache.get_or_parse(&constant_module(1)).unwrap();
+        cache.get_or_parse(&constant_module(2)).unwrap();
+        cache.get_or_parse(&constant_module(1)).unwrap();
+        cache.get_or_parse(&constant_module(3)).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(
+            &first,
+            &cache.get_or_parse(&constant_module(1)).unwrap()
+        ));
+        assert!(cache.get_or_parse(b"not wasm").is_err());
+        assert_eq!(cache.len(), 2);
+    }
+}
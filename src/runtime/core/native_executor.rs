@@ -1,13 +1,27 @@
 /// Native WASM executor for running WASM files directly
-use super::executor::{Executor, WASI_PROC_EXIT_PREFIX};
+use super::coverage::{CoverageCounters, CoverageData};
+use super::executor::{
+    Executor, DEFAULT_MAX_CALL_DEPTH, DEFAULT_MAX_OPERAND_STACK, STACK_EXHAUSTED_ERROR,
+    WASI_PROC_EXIT_PREFIX,
+};
+use super::host_spec::HostSpec;
 use super::module::Module;
+use super::module_cache::ModuleCache;
+use super::panic::PanicReport;
+use super::stubs::{stub_unresolved_imports, StubMode};
+use super::trace::{InstructionTrace, DEFAULT_TRACE_LIMIT};
+use super::trap::{format_backtrace, BacktraceFrame};
 use super::values::Value;
+use super::watchpoint::{MemoryWatch, WatchAction, Watchpoint};
 use crate::error::{CommandError, Result, WasmrunError};
 use crate::runtime::wasi::{create_wasi_linker, WasiEnv};
+use crate::utils::{read_demangled_function_names, MappedFile};
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Resource ceilings applied to a single WASM execution.
 ///
@@ -21,13 +35,80 @@ pub struct ExecLimits {
     pub max_fuel: Option<u64>,
 }
 
+/// Interpreter options set from `wasmrun exec` flags
+#[derive(Debug, Clone, Default)]
+pub struct ExecOptions {
+    /// Memory ranges whose writes are reported (`--watch-mem`)
+    pub watchpoints: Vec<Watchpoint>,
+    /// What a watched write does (`--watch-action`)
+    pub watch_action: WatchAction,
+    /// Nested call limit (`--max-call-depth`); `None` uses the default
+    pub max_call_depth: Option<usize>,
+    /// Operand stack limit (`--max-stack-values`); `None` uses the default
+    pub max_operand_stack: Option<usize>,
+    /// TOML spec of host functions to register (`--host-functions`)
+    pub host_functions: Option<PathBuf>,
+    /// Stub for unresolved function imports (`--stub-imports`)
+    pub stub_imports: Option<StubMode>,
+    /// File the instruction trace is written to (`--trace-instructions`)
+    pub trace_path: Option<PathBuf>,
+    /// Functions to trace, by name or index (`--trace-filter`); empty = all
+    pub trace_filter: Vec<String>,
+    /// Trace entries kept (`--trace-limit`); `None` uses the default
+    pub trace_limit: Option<usize>,
+    /// File coverage counts are written to (`--coverage`)
+    pub coverage_path: Option<PathBuf>,
+    /// Count block entries as well as calls (`--coverage-blocks`)
+    pub coverage_blocks: bool,
+    /// Run every instruction as decoded, without fusing (`--no-optimize`)
+    pub no_optimize: bool,
+}
+
+/// What a run produced, for callers that present it themselves
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExecReport {
+    /// `proc_exit` code, or 0 when the entry point returned; `None` on a trap
+    pub exit_code: Option<i32>,
+    /// Values returned by the called function
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<String>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Why execution failed
+    pub error: Option<String>,
+    /// Call stack at the trap, innermost first
+    pub backtrace: Vec<BacktraceFrame>,
+    /// Set when the trap ended a Rust panic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panic: Option<PanicReport>,
+    /// Time spent in the called function, after instantiation
+    #[serde(skip)]
+    pub elapsed: Duration,
+}
+
+/// Host stack the interpreter uses per nested wasm call, with headroom.
+/// Debug builds keep far bigger frames in the instruction dispatch.
+const HOST_STACK_PER_CALL: usize = if cfg!(debug_assertions) {
+    64 * 1024
+} else {
+    16 * 1024
+};
+
+/// Host stack for everything outside the call recursion
+const HOST_STACK_BASE: usize = 8 * 1024 * 1024;
+
+/// Host thread stack needed to run `max_call_depth` nested wasm calls
+pub const fn interpreter_stack_bytes(max_call_depth: usize) -> usize {
+    HOST_STACK_BASE + max_call_depth.saturating_mul(HOST_STACK_PER_CALL)
+}
+
 pub fn execute_wasm_file(wasm_path: &str) -> Result<i32> {
     if !Path::new(wasm_path).exists() {
         return Err(WasmrunError::from(format!(
             "WASM file not found: {wasm_path}"
         )));
     }
-    let wasm_bytes = fs::read(wasm_path)
+    let wasm_bytes = MappedFile::open(wasm_path)
         .map_err(|e| WasmrunError::from(format!("Failed to read WASM file '{wasm_path}': {e}")))?;
     execute_wasm_bytes(&wasm_bytes)
 }
@@ -37,19 +118,40 @@ pub fn execute_wasm_file_with_args(
     function: Option<String>,
     args: Vec<String>,
 ) -> Result<i32> {
+    execute_wasm_file_with_options(wasm_path, function, args, &ExecOptions::default())
+}
+
+pub fn execute_wasm_file_with_options(
+    wasm_path: &str,
+    function: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+) -> Result<i32> {
+    print_report(execute_wasm_file_report(
+        wasm_path, function, args, options,
+    )?)
+}
+
+/// Run a WASM file and return its captured output instead of printing it
+pub fn execute_wasm_file_report(
+    wasm_path: &str,
+    function: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+) -> Result<ExecReport> {
     if !Path::new(wasm_path).exists() {
         return Err(WasmrunError::from(format!(
             "WASM file not found: {wasm_path}"
         )));
     }
-    let wasm_bytes = fs::read(wasm_path)
+    let wasm_bytes = MappedFile::open(wasm_path)
         .map_err(|e| WasmrunError::from(format!("Failed to read WASM file '{wasm_path}': {e}")))?;
     // Prepend wasm_path as argv[0] per WASI/POSIX convention.
     // The bare args from the CLI don't include a program name, but WASI programs
     // (e.g. QuickJS) index into argv[1] for their first real argument.
     let mut wasi_args = vec![wasm_path.to_string()];
     wasi_args.extend(args.iter().cloned());
-    execute_wasm_bytes_with_args(&wasm_bytes, function, wasi_args)
+    execute_wasm_bytes_report(&wasm_bytes, function, wasi_args, options)
 }
 
 pub fn execute_wasm_bytes(wasm_bytes: &[u8]) -> Result<i32> {
@@ -61,80 +163,223 @@ pub fn execute_wasm_bytes_with_args(
     function: Option<String>,
     args: Vec<String>,
 ) -> Result<i32> {
+    execute_wasm_bytes_with_options(wasm_bytes, function, args, &ExecOptions::default())
+}
+
+pub fn execute_wasm_bytes_with_options(
+    wasm_bytes: &[u8],
+    function: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+) -> Result<i32> {
+    print_report(execute_wasm_bytes_report(
+        wasm_bytes, function, args, options,
+    )?)
+}
+
+/// Print a report's captured output, and turn a trap into an error carrying
+/// its backtrace
+fn print_report(report: ExecReport) -> Result<i32> {
+    if !report.stdout.is_empty() {
+        print!("{}", report.stdout);
+    }
+    if !report.stderr.is_empty() {
+        eprint!("{}", report.stderr);
+    }
+    let error = match (report.error, &report.panic) {
+        (None, _) => return Ok(report.exit_code.unwrap_or(0)),
+        (Some(error), Some(panic)) => format!("{panic}\n(trapped with: {error})"),
+        (Some(error), None) => error,
+    };
+    if report.backtrace.is_empty() {
+        Err(WasmrunError::from(error))
+    } else {
+        Err(WasmrunError::from(format!(
+            "{error}\n{}",
+            format_backtrace(&report.backtrace)
+        )))
+    }
+}
+
+/// Run WASM bytes and return their captured output instead of printing it.
+/// A trap is reported in the result; `Err` means the module couldn't be run.
+pub fn execute_wasm_bytes_report(
+    wasm_bytes: &[u8],
+    function: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+) -> Result<ExecReport> {
+    // Run on a thread whose stack fits the call-depth limit, so the limit
+    // traps before the host stack overflows
+    let max_call_depth = options.max_call_depth.unwrap_or(DEFAULT_MAX_CALL_DEPTH);
+    std::thread::scope(|scope| {
+        let runner = std::thread::Builder::new()
+            .name("wasmrun-exec".to_string())
+            .stack_size(interpreter_stack_bytes(max_call_depth))
+            .spawn_scoped(scope, move || run_wasm_bytes(wasm_bytes, function, args, options))
+            .map_err(|e| {
+                WasmrunError::from(format!(
+                    "Failed to start the interpreter thread for a call depth of {max_call_depth}: {e}"
+                ))
+            })?;
+        runner
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+fn run_wasm_bytes(
+    wasm_bytes: &[u8],
+    function: Option<String>,
+    args: Vec<String>,
+    options: &ExecOptions,
+) -> Result<ExecReport> {
     let module = Module::parse(wasm_bytes)
         .map_err(|e| WasmrunError::from(format!("Failed to parse WASM module: {e}")))?;
 
     let wasi_env = Arc::new(Mutex::new(WasiEnv::new().with_args(args.clone())));
-    let wasi_linker = create_wasi_linker(wasi_env.clone());
+    let mut wasi_linker = create_wasi_linker(wasi_env.clone());
+    if let Some(path) = &options.host_functions {
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let registered = HostSpec::load(path)
+            .and_then(|spec| spec.register(&mut wasi_linker, &module, base_dir))
+            .map_err(WasmrunError::from)?;
+        eprintln!(
+            "🔌 Registered {} host function(s) from {}",
+            registered.len(),
+            path.display()
+        );
+    }
+    if let Some(mode) = options.stub_imports {
+        let stubbed = stub_unresolved_imports(&mut wasi_linker, &module, mode);
+        if !stubbed.is_empty() {
+            eprintln!(
+                "🔌 Stubbed {} unresolved import(s): {}",
+                stubbed.len(),
+                stubbed.join(", ")
+            );
+        }
+    }
 
     let mut executor = Executor::new_with_linker(module, wasi_linker)
         .map_err(|e| WasmrunError::from(format!("Failed to initialize executor: {e}")))?;
-
-    let func_idx = if let Some(func_name) = function {
-        find_export_function(executor.module(), &func_name)
-            .map(|(_, idx)| idx)
-            .ok_or_else(|| {
-                WasmrunError::from(format!(
-                    "Exported function '{func_name}' not found in WASM module"
-                ))
-            })?
-    } else {
-        let start_func = executor.module().start;
-        if let Some(func_idx) = start_func {
-            func_idx
-        } else if let Some((_, func_idx)) = find_export_function(executor.module(), "main") {
-            func_idx
-        } else if let Some((_, func_idx)) = find_export_function(executor.module(), "_start") {
-            func_idx
+    executor.set_function_names(read_demangled_function_names(wasm_bytes));
+    if !options.watchpoints.is_empty() {
+        executor.set_memory_watch(Some(MemoryWatch::new(
+            options.watchpoints.clone(),
+            options.watch_action,
+        )));
+    }
+    executor.set_stack_limits(
+        options.max_call_depth.unwrap_or(DEFAULT_MAX_CALL_DEPTH),
+        options
+            .max_operand_stack
+            .unwrap_or(DEFAULT_MAX_OPERAND_STACK),
+    );
+    if options.trace_path.is_some() {
+        let functions = if options.trace_filter.is_empty() {
+            None
         } else {
-            return Err(WasmrunError::from(
-                "No entry point found (checked: start section, main, _start)".to_string(),
-            ));
-        }
-    };
+            let functions = options
+                .trace_filter
+                .iter()
+                .map(|name| {
+                    executor
+                        .find_function(name)
+                        .ok_or_else(|| WasmrunError::from(format!("No function '{name}' to trace")))
+                })
+                .collect::<Result<_>>()?;
+            Some(functions)
+        };
+        executor.set_instruction_trace(Some(InstructionTrace::new(
+            functions,
+            options.trace_limit.unwrap_or(DEFAULT_TRACE_LIMIT),
+        )));
+    }
+
+    if options.coverage_path.is_some() {
+        executor.set_coverage(Some(CoverageCounters::new(options.coverage_blocks)));
+    }
+    executor.set_optimize(!options.no_optimize);
 
+    let func_idx = entry_function(executor.module(), function)?;
     let wasm_args = convert_string_args_to_values(&args);
 
-    match execute_function(&mut executor, func_idx, wasm_args) {
-        Ok(()) => {
-            // Print captured stdout
-            if let Ok(env) = wasi_env.lock() {
-                let out = env.get_stdout();
-                if !out.is_empty() {
-                    print!("{}", String::from_utf8_lossy(&out));
-                }
-                let err_out = env.get_stderr();
-                if !err_out.is_empty() {
-                    eprint!("{}", String::from_utf8_lossy(&err_out));
-                }
-            }
-            Ok(0)
+    let started = Instant::now();
+    let result = executor.execute_with_args(func_idx, wasm_args);
+    let elapsed = started.elapsed();
+    if !options.watchpoints.is_empty() {
+        eprintln!(
+            "👁️  {} watched write(s) to {}",
+            executor.watch_hits(),
+            options
+                .watchpoints
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if let (Some(path), Some(trace)) = (&options.trace_path, executor.take_instruction_trace()) {
+        // Written even after a trap, when the last instructions matter most
+        match trace.write_to(path) {
+            Ok(()) => eprintln!(
+                "🧵 Traced {} instruction(s), wrote the last {} to {}",
+                trace.traced,
+                trace.entries().count(),
+                path.display()
+            ),
+            Err(e) => eprintln!(
+                "⚠️  Failed to write the instruction trace to {}: {e}",
+                path.display()
+            ),
         }
-        Err(e) => {
-            if let Some(code) = extract_proc_exit(&e) {
-                // Print captured output even on proc_exit
-                if let Ok(env) = wasi_env.lock() {
-                    let out = env.get_stdout();
-                    if !out.is_empty() {
-                        print!("{}", String::from_utf8_lossy(&out));
-                    }
-                    let err_out = env.get_stderr();
-                    if !err_out.is_empty() {
-                        eprint!("{}", String::from_utf8_lossy(&err_out));
-                    }
+    }
+
+    if let Some(path) = &options.coverage_path {
+        // argv[0] is the module path when run from a file
+        let module_path = args.first().map_or("", String::as_str);
+        if let Some(coverage) = executor.coverage_data(module_path) {
+            write_coverage(path, &coverage);
+        }
+    }
+
+    let mut report = ExecReport {
+        elapsed,
+        ..ExecReport::default()
+    };
+    if let Ok(env) = wasi_env.lock() {
+        report.stdout = String::from_utf8_lossy(&env.get_stdout()).into_owned();
+        report.stderr = String::from_utf8_lossy(&env.get_stderr()).into_owned();
+    }
+    match result {
+        Ok(values) => {
+            report.exit_code = Some(0);
+            report.results = values.iter().map(format_result).collect();
+        }
+        Err(e) => match Executor::is_proc_exit(&e) {
+            Some(code) => report.exit_code = Some(code),
+            None => {
+                // Watchpoint stops and stack limits describe themselves; a
+                // backtrace of a stack overflow would be thousands of frames
+                if !Executor::is_watchpoint_stop(&e) && !Executor::is_stack_exhausted(&e) {
+                    report.backtrace = executor.backtrace_frames();
                 }
-                Ok(code)
-            } else {
-                Err(e)
+                report.panic = PanicReport::detect(&e, &report.stderr, &report.backtrace);
+                report.error = Some(describe_error(func_idx, &e));
             }
-        }
+        },
     }
+    Ok(report)
 }
 
 /// Execute WASM bytes using an existing WasiEnv (for agent session reuse).
 ///
 /// Unlike `execute_wasm_bytes_with_args`, this does not print captured output —
-/// the caller reads stdout/stderr from the WasiEnv after execution.
+/// the caller reads stdout/stderr from the WasiEnv after execution. The
+/// parsed module comes from [`ModuleCache::shared`], so repeated and
+/// concurrent runs of the same bytes parse them once.
 pub fn execute_wasm_bytes_with_env(
     wasm_bytes: &[u8],
     wasi_env: Arc<Mutex<WasiEnv>>,
@@ -143,53 +388,23 @@ pub fn execute_wasm_bytes_with_env(
     limits: ExecLimits,
     cancel: Option<Arc<AtomicBool>>,
 ) -> Result<i32> {
-    let mut module = Module::parse(wasm_bytes)
+    // Sessions keep running the same runtimes, so the parse is shared
+    let module = ModuleCache::shared()
+        .get_or_parse(wasm_bytes)
         .map_err(|e| WasmrunError::from(format!("Failed to parse WASM module: {e}")))?;
 
-    if let Some(cap) = limits.max_memory_pages {
-        if let Some(ref mut mem) = module.memory {
-            match mem.max {
-                Some(m) if m > cap => mem.max = Some(cap),
-                None => mem.max = Some(cap),
-                _ => {}
-            }
-        }
-    }
-
     if let Ok(mut env) = wasi_env.lock() {
         env.set_args(args.clone());
     }
 
     let wasi_linker = create_wasi_linker(wasi_env);
 
-    let mut executor = Executor::new_with_linker(module, wasi_linker)
+    let mut executor = Executor::instantiate(module, wasi_linker, limits.max_memory_pages)
         .map_err(|e| WasmrunError::from(format!("Failed to initialize executor: {e}")))?;
     executor.set_fuel(limits.max_fuel);
     executor.set_cancel_token(cancel);
 
-    let func_idx = if let Some(func_name) = function {
-        find_export_function(executor.module(), &func_name)
-            .map(|(_, idx)| idx)
-            .ok_or_else(|| {
-                WasmrunError::from(format!(
-                    "Exported function '{func_name}' not found in WASM module"
-                ))
-            })?
-    } else {
-        let start_func = executor.module().start;
-        if let Some(func_idx) = start_func {
-            func_idx
-        } else if let Some((_, func_idx)) = find_export_function(executor.module(), "main") {
-            func_idx
-        } else if let Some((_, func_idx)) = find_export_function(executor.module(), "_start") {
-            func_idx
-        } else {
-            return Err(WasmrunError::from(
-                "No entry point found (checked: start section, main, _start)".to_string(),
-            ));
-        }
-    };
-
+    let func_idx = entry_function(executor.module(), function)?;
     let wasm_args = convert_string_args_to_values(&args);
 
     match execute_function(&mut executor, func_idx, wasm_args) {
@@ -251,15 +466,85 @@ fn find_export_function(module: &Module, name: &str) -> Option<(String, u32)> {
     None
 }
 
+/// A returned value as `wasmtime run --invoke` prints it
+fn format_result(value: &Value) -> String {
+    match value {
+        Value::I32(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::F32(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::FuncRef(Some(idx)) | Value::ExternRef(Some(idx)) => format!("ref({idx})"),
+        Value::FuncRef(None) | Value::ExternRef(None) => "null".to_string(),
+    }
+}
+
+/// The user-facing part of an error that starts with `sentinel`
+fn strip_sentinel(err: &str, sentinel: &str) -> String {
+    err.split_once(sentinel)
+        .map_or(err, |(_, rest)| rest.trim())
+        .to_string()
+}
+
+fn write_coverage(path: &Path, coverage: &CoverageData) {
+    let written = serde_json::to_string_pretty(coverage)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    match written {
+        Ok(()) => eprintln!(
+            "📊 {}/{} function(s) executed, coverage written to {}",
+            coverage.functions.iter().filter(|f| f.calls > 0).count(),
+            coverage.functions.len(),
+            path.display()
+        ),
+        Err(e) => eprintln!("⚠️  Failed to write coverage to {}: {e}", path.display()),
+    }
+}
+
+/// The function to run: `function` if given, else the start section, `main`
+/// or `_start`
+fn entry_function(module: &Module, function: Option<String>) -> Result<u32> {
+    if let Some(func_name) = function {
+        return find_export_function(module, &func_name)
+            .map(|(_, idx)| idx)
+            .ok_or_else(|| {
+                WasmrunError::from(format!(
+                    "Exported function '{func_name}' not found in WASM module"
+                ))
+            });
+    }
+    module
+        .start
+        .or_else(|| find_export_function(module, "main").map(|(_, idx)| idx))
+        .or_else(|| find_export_function(module, "_start").map(|(_, idx)| idx))
+        .ok_or_else(|| {
+            WasmrunError::from(
+                "No entry point found (checked: start section, main, _start)".to_string(),
+            )
+        })
+}
+
+/// User-facing message for an execution error other than `proc_exit`
+fn describe_error(func_idx: u32, e: &str) -> String {
+    if Executor::is_watchpoint_stop(e) {
+        strip_sentinel(e, super::watchpoint::WATCHPOINT_STOP_ERROR)
+    } else if Executor::is_stack_exhausted(e) {
+        strip_sentinel(e, STACK_EXHAUSTED_ERROR)
+    } else if e.starts_with("Unresolved import:") {
+        format!(
+            "Error executing WASM function (index {func_idx}): {e} (use --stub-imports to stub it)"
+        )
+    } else {
+        format!("Error executing WASM function (index {func_idx}): {e}")
+    }
+}
+
 fn execute_function(executor: &mut Executor, func_idx: u32, args: Vec<Value>) -> Result<()> {
     executor.execute_with_args(func_idx, args).map_err(|e| {
         // Propagate proc_exit as-is so the caller can detect it
         if e.starts_with(WASI_PROC_EXIT_PREFIX) {
             WasmrunError::from(e)
         } else {
-            WasmrunError::from(format!(
-                "Error executing WASM function (index {func_idx}): {e}"
-            ))
+            WasmrunError::from(describe_error(func_idx, &e))
         }
     })?;
     Ok(())
@@ -592,6 +877,7 @@ mod tests {
             start: None,
             elements: vec![],
             data: vec![],
+            code_cache: Default::default(),
         };
 
         let mut linker = Linker::new();
@@ -0,0 +1,202 @@
+/// Peephole optimizer for decoded function bodies
+/// Folds constant arithmetic, merges `local.get`/`local.set` pairs and fuses
+/// a few common sequences into single instructions, so the interpreter
+/// dispatches fewer of them. Only straight-line code is rewritten: block
+/// instructions are kept as they are, so no branch can land inside a fused
+/// sequence.
+use super::executor::Instruction;
+
+/// A body after fusing
+pub struct Fused {
+    pub instructions: Vec<Instruction>,
+    /// Byte offset of the first decoded instruction behind each one, plus
+    /// where decoding stopped
+    pub offsets: Vec<usize>,
+    /// How many decoded instructions each one stands for
+    pub weights: Vec<u32>,
+}
+
+/// Fuse `instructions`, whose byte offsets are `offsets` followed by where
+/// decoding stopped
+pub fn fuse(instructions: &[Instruction], offsets: &[usize]) -> Fused {
+    let mut fused = Fused {
+        instructions: Vec::with_capacity(instructions.len()),
+        offsets: Vec::with_capacity(offsets.len()),
+        weights: Vec::with_capacity(instructions.len()),
+    };
+    for (instr, &offset) in instructions.iter().zip(offsets) {
+        fused.instructions.push(instr.clone());
+        fused.offsets.push(offset);
+        fused.weights.push(1);
+
+        // A rewrite can enable another, e.g. a folded constant feeding an add
+        while let Some((replaced, instr)) = rewrite(&fused.instructions) {
+            let start = fused.instructions.len() - replaced;
+            let weight = fused.weights.drain(start..).sum();
+            let offset = fused.offsets[start];
+            fused.instructions.truncate(start);
+            fused.offsets.truncate(start);
+            fused.instructions.push(instr);
+            fused.offsets.push(offset);
+            fused.weights.push(weight);
+        }
+    }
+    fused
+        .offsets
+        .extend(offsets.get(instructions.len()).copied());
+    fused
+}
+
+/// The instruction that can replace the last few of `code`, and how many it
+/// replaces
+fn rewrite(code: &[Instruction]) -> Option<(usize, Instruction)> {
+    use Instruction::*;
+    let folded = match code {
+        [.., I32Const(a), I32Const(b), op] => fold_i32(*a, *b, op),
+        [.., I64Const(a), I64Const(b), op] => fold_i64(*a, *b, op),
+        _ => None,
+    };
+    if let Some(constant) = folded {
+        return Some((3, constant));
+    }
+    match code {
+        [.., I32Const(a), I32Eqz] => Some((2, I32Const((*a == 0) as i32))),
+        [.., I64Const(a), I64Eqz] => Some((2, I32Const((*a == 0) as i32))),
+        [.., I32Const(c), I32Add] => Some((2, I32AddConst(*c))),
+        [.., I32Const(c), I32Sub] => Some((2, I32AddConst(c.wrapping_neg()))),
+        [.., I64Const(c), I64Add] => Some((2, I64AddConst(*c))),
+        [.., I64Const(c), I64Sub] => Some((2, I64AddConst(c.wrapping_neg()))),
+        [.., LocalGet(a), I32AddConst(c)] => Some((2, LocalGetI32AddConst(*a, *c))),
+        [.., LocalGet(a), I32Load(offset)] => Some((2, LocalGetI32Load(*a, *offset))),
+        [.., LocalGet(a), LocalSet(b)] => Some((2, LocalCopy(*a, *b))),
+        [.., LocalSet(a), LocalGet(b)] if a == b => Some((2, LocalTee(*a))),
+        [.., I32Eqz, BrIf(label)] => Some((2, BrUnless(*label))),
+        _ => None,
+    }
+}
+
+/// `a op b` for an i32 operator that can't trap
+fn fold_i32(a: i32, b: i32, op: &Instruction) -> Option<Instruction> {
+    use Instruction::*;
+    let (ua, ub) = (a as u32, b as u32);
+    let value = match op {
+        I32Add => a.wrapping_add(b),
+        I32Sub => a.wrapping_sub(b),
+        I32Mul => a.wrapping_mul(b),
+        I32And => a & b,
+        I32Or => a | b,
+        I32Xor => a ^ b,
+        I32Shl => a.wrapping_shl(ub & 31),
+        I32ShrS => a.wrapping_shr(ub & 31),
+        I32ShrU => (ua >> (ub & 31)) as i32,
+        I32Rotl => ua.rotate_left(ub & 31) as i32,
+        I32Rotr => ua.rotate_right(ub & 31) as i32,
+        I32Eq => (a == b) as i32,
+        I32Ne => (a != b) as i32,
+        I32LtS => (a < b) as i32,
+        I32LtU => (ua < ub) as i32,
+        I32GtS => (a > b) as i32,
+        I32GtU => (ua > ub) as i32,
+        I32LeS => (a <= b) as i32,
+        I32LeU => (ua <= ub) as i32,
+        I32GeS => (a >= b) as i32,
+        I32GeU => (ua >= ub) as i32,
+        _ => return None,
+    };
+    Some(I32Const(value))
+}
+
+/// `a op b` for an i64 operator that can't trap
+fn fold_i64(a: i64, b: i64, op: &Instruction) -> Option<Instruction> {
+    use Instruction::*;
+    let (ua, ub) = (a as u64, b as u64);
+    let shift = (ub & 63) as u32;
+    Some(match op {
+        I64Add => I64Const(a.wrapping_add(b)),
+        I64Sub => I64Const(a.wrapping_sub(b)),
+        I64Mul => I64Const(a.wrapping_mul(b)),
+        I64And => I64Const(a & b),
+        I64Or => I64Const(a | b),
+        I64Xor => I64Const(a ^ b),
+        I64Shl => I64Const(a.wrapping_shl(shift)),
+        I64ShrS => I64Const(a.wrapping_shr(shift)),
+        I64ShrU => I64Const((ua >> shift) as i64),
+        I64Rotl => I64Const(ua.rotate_left(shift) as i64),
+        I64Rotr => I64Const(ua.rotate_right(shift) as i64),
+        I64Eq => I32Const((a == b) as i32),
+        I64Ne => I32Const((a != b) as i32),
+        I64LtS => I32Const((a < b) as i32),
+        I64LtU => I32Const((ua < ub) as i32),
+        I64GtS => I32Const((a > b) as i32),
+        I64GtU => I32Const((ua > ub) as i32),
+        I64LeS => I32Const((a <= b) as i32),
+        I64LeU => I32Const((ua <= ub) as i32),
+        I64GeS => I32Const((a >= b) as i32),
+        I64GeU => I32Const((ua >= ub) as i32),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Instruction::*;
+
+    fn fuse_all(code: Vec<Instruction>) -> Fused {
+        let offsets: Vec<usize> = (0..=code.len()).collect();
+        fuse(&code, &offsets)
+    }
+
+    #[test]
+    fn test_fuse_folds_constants_and_merges_locals() {
+        let fused = fuse_all(vec![
+            LocalGet(0),
+            I32Const(6),
+            I32Const(7),
+            I32Mul,
+            I32Const(2),
+            I32Sub,
+            I32Add,
+            LocalSet(1),
+            LocalGet(1),
+            I32Eqz,
+            BrIf(0),
+            LocalGet(2),
+            LocalSet(3),
+            End,
+        ]);
+        let shown: Vec<String> = fused
+            .instructions
+            .iter()
+            .map(|i| format!("{i:?}"))
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                "LocalGetI32AddConst(0, 40)",
+                "LocalTee(1)",
+                "BrUnless(0)",
+                "LocalCopy(2, 3)",
+                "End"
+            ]
+        );
+        assert_eq!(fused.weights, [7, 2, 2, 2, 1]);
+        assert_eq!(fused.offsets, [0, 7, 9, 11, 13, 14]);
+    }
+
+    #[test]
+    fn test_fuse_keeps_trapping_operations_and_blocks() {
+        let code = vec![
+            I32Const(1),
+            I32Const(0),
+            I32DivS,
+            I32Const(1),
+            Loop(None),
+            I32Add,
+            End,
+        ];
+        let fused = fuse_all(code.clone());
+        assert_eq!(fused.instructions.len(), code.len());
+        assert_eq!(fused.weights, [1; 7]);
+    }
+}
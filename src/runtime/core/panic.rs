@@ -0,0 +1,253 @@
+/// Rust panics, reported as panics instead of the `unreachable` trap they
+/// end in. The message and location come from the panic output on stderr
+/// (std's hook, or a hook such as `console_error_panic_hook` for bindgen
+/// modules); the panicking function comes from the backtrace's names.
+use super::trap::BacktraceFrame;
+use serde::Serialize;
+use std::fmt;
+
+/// The interpreter's error for an executed `unreachable`
+const UNREACHABLE_TRAP: &str = "Unreachable instruction executed";
+
+/// Functions on the way from a panic to its trap
+const PANIC_MACHINERY: &[&str] = &[
+    "__rust_start_panic",
+    "__rust_abort",
+    "rust_panic",
+    "core::panicking::",
+    "std::panicking::",
+    "std::rt::",
+    "std::process::abort",
+    "std::sys::",
+    "panic_abort::",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::result::unwrap_failed",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PanicReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
+    /// `None` when the module panicked without printing a message
+    pub message: Option<String>,
+    /// `file:line:column` of the panic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Innermost function of the program itself in the backtrace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+impl PanicReport {
+    /// The panic behind a trap `error`, from what the module wrote to stderr
+    /// and the backtrace. `None` when the trap wasn't a Rust panic.
+    pub fn detect(error: &str, stderr: &str, backtrace: &[BacktraceFrame]) -> Option<Self> {
+        if !error.contains(UNREACHABLE_TRAP) {
+            return None;
+        }
+        let function = panicking_function(backtrace);
+        match Self::from_output(stderr) {
+            Some(report) => Some(Self {
+                function: function.or(report.function),
+                ..report
+            }),
+            None if backtrace.iter().any(is_machinery) => Some(Self {
+                thread: None,
+                message: None,
+                location: None,
+                function,
+            }),
+            None => None,
+        }
+    }
+
+    /// The last panic in a program's output, in the formats Rust has used:
+    ///
+    /// ```text
+    /// thread 'main' panicked at src/main.rs:3:5:
+    /// called `Option::unwrap()` on a `None` value
+    /// thread 'main' panicked at 'boom', src/main.rs:3:5
+    /// ```
+    pub fn from_output(output: &str) -> Option<Self> {
+        let start = output.rfind("panicked at ")?;
+        let line_start = output[..start].rfind('\n').map_or(0, |i| i + 1);
+        let thread = output[line_start..start]
+            .trim()
+            .strip_prefix("thread '")
+            .and_then(|rest| rest.strip_suffix('\''))
+            .map(str::to_string);
+        let rest = &output[start + "panicked at ".len()..];
+        let (first, following) = rest.split_once('\n').unwrap_or((rest, ""));
+
+        // Before Rust 1.73: 'message', file:line:column
+        if let Some(quoted) = first.strip_prefix('\'') {
+            if let Some((message, location)) = quoted.rsplit_once("', ") {
+                return Some(Self {
+                    thread,
+                    message: Some(message.to_string()),
+                    location: Some(location.trim().to_string()),
+                    function: None,
+                });
+            }
+        }
+
+        let location = first.trim().trim_end_matches(':');
+        let (location, message_lines) = if is_location(location) {
+            (Some(location.to_string()), following)
+        } else {
+            (None, rest)
+        };
+        let message: Vec<&str> = message_lines
+            .lines()
+            .take_while(|line| {
+                !line.starts_with("note: ")
+                    && !line.starts_with("stack backtrace:")
+                    && !line.starts_with("Stack:")
+            })
+            .collect();
+        let message = message.join("\n").trim().to_string();
+        Some(Self {
+            thread,
+            message: (!message.is_empty()).then_some(message),
+            location,
+            function: None,
+        })
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rust panic")?;
+        if let Some(thread) = &self.thread {
+            write!(f, " in thread '{thread}'")?;
+        }
+        match &self.message {
+            Some(message) => write!(f, ": {message}")?,
+            None => write!(
+                f,
+                " (no message was printed; a wasm32-unknown-unknown module needs a panic hook such as console_error_panic_hook)"
+            )?,
+        }
+        if let Some(location) = &self.location {
+            write!(f, "\n    at {location}")?;
+        }
+        if let Some(function) = &self.function {
+            write!(f, "\n    in {function}")?;
+        }
+        Ok(())
+    }
+}
+
+fn is_machinery(frame: &BacktraceFrame) -> bool {
+    frame.name.as_deref().is_some_and(|name| {
+        let name = name.trim_start_matches('<');
+        PANIC_MACHINERY
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    })
+}
+
+/// The first named frame past the panic machinery that isn't the standard
+/// library's
+fn panicking_function(backtrace: &[BacktraceFrame]) -> Option<String> {
+    let last_machinery = backtrace.iter().rposition(is_machinery)?;
+    backtrace[last_machinery + 1..]
+        .iter()
+        .filter_map(|frame| frame.name.as_deref())
+        .find(|name| {
+            let name = name.trim_start_matches('<');
+            !["core::", "std::", "alloc::"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+        .map(str::to_string)
+}
+
+/// `file:line:column`
+fn is_location(text: &str) -> bool {
+    let mut parts = text.rsplitn(3, ':');
+    let column = parts.next().unwrap_or_default();
+    let line = parts.next().unwrap_or_default();
+    let file = parts.next().unwrap_or_default();
+    !file.is_empty()
+        && !file.contains(' ')
+        && [line, column]
+            .iter()
+            .all(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(name: &str) -> BacktraceFrame {
+        BacktraceFrame {
+            func_index: 0,
+            name: Some(name.to_string()),
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_from_output() {
+        let stderr = "starting\nthread 'main' panicked at src/main.rs:3:5:\ncalled `Option::unwrap()` on a `None` value\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+        let report = PanicReport::from_output(stderr).unwrap();
+        assert_eq!(report.thread.as_deref(), Some("main"));
+        assert_eq!(
+            report.message.as_deref(),
+            Some("called `Option::unwrap()` on a `None` value")
+        );
+        assert_eq!(report.location.as_deref(), Some("src/main.rs:3:5"));
+
+        let old = PanicReport::from_output("thread 'main' panicked at 'boom', src/lib.rs:10:9\n")
+            .unwrap();
+        assert_eq!(old.message.as_deref(), Some("boom"));
+        assert_eq!(old.location.as_deref(), Some("src/lib.rs:10:9"));
+
+        // console_error_panic_hook prints no thread
+        let hook = PanicReport::from_output(
+            "panicked at src/lib.rs:7:5:\nindex out of bounds\n\nStack:\n\nError\n",
+        )
+        .unwrap();
+        assert_eq!(hook.thread, None);
+        assert_eq!(hook.message.as_deref(), Some("index out of bounds"));
+
+        assert_eq!(PanicReport::from_output("all good\n"), None);
+    }
+
+    #[test]
+    fn test_detect_from_backtrace() {
+        let backtrace = [
+            frame("panic_abort::__rust_start_panic::abort"),
+            frame("rust_panic"),
+            frame("std::panicking::rust_panic_with_hook"),
+            frame("core::panicking::panic_fmt"),
+            frame("core::option::unwrap_failed"),
+            frame("app::parse"),
+            frame("app::main"),
+        ];
+        let report = PanicReport::detect(
+            "Error executing WASM function (index 3): Unreachable instruction executed",
+            "",
+            &backtrace,
+        )
+        .unwrap();
+        assert_eq!(report.function.as_deref(), Some("app::parse"));
+        assert_eq!(report.message, None);
+        assert!(report.to_string().contains("console_error_panic_hook"));
+
+        assert_eq!(
+            PanicReport::detect(
+                "Unreachable instruction executed",
+                "",
+                &[frame("app::main")]
+            ),
+            None
+        );
+        assert_eq!(
+            PanicReport::detect("Out of bounds memory access", "", &backtrace),
+            None
+        );
+    }
+}
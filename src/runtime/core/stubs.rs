@@ -0,0 +1,150 @@
+/// Host stubs for imports nothing provides
+/// `wasmrun exec --stub-imports` registers a stub for each function import the
+/// linker can't resolve, so a module with exotic imports runs until it really
+/// depends on one of them.
+use super::linker::{ClosureHostFunction, Linker};
+use super::memory::LinearMemory;
+use super::module::{ImportKind, Module, ValueType};
+use super::trace::format_value;
+use super::values::Value;
+
+/// What a stubbed import does when called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StubMode {
+    /// Trap, naming the import
+    Trap,
+    /// Return zero for every result
+    Zero,
+    /// Print the call and its arguments, then return zeroes
+    Log,
+}
+
+impl StubMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "trap" => Ok(Self::Trap),
+            "zero" => Ok(Self::Zero),
+            "log" => Ok(Self::Log),
+            _ => Err(format!(
+                "Unknown stub mode '{value}' (use trap, zero or log)"
+            )),
+        }
+    }
+}
+
+/// Register a `mode` stub for every function import of `module` that
+/// `linker` doesn't provide. Returns the stubbed imports as `module::name`.
+pub fn stub_unresolved_imports(
+    linker: &mut Linker,
+    module: &Module,
+    mode: StubMode,
+) -> Vec<String> {
+    let mut stubbed = Vec::new();
+    for import in &module.imports {
+        let ImportKind::Function(type_idx) = import.kind else {
+            continue;
+        };
+        if linker.has_import(&import.module, &import.name) {
+            continue;
+        }
+        let Some(func_type) = module.types.get(type_idx as usize) else {
+            continue;
+        };
+        let label = format!("{}::{}", import.module, import.name);
+        let results: Vec<Value> = func_type.results.iter().map(zero_value).collect();
+        let result_count = results.len();
+        let name = label.clone();
+        linker.register(
+            &import.module,
+            &import.name,
+            Box::new(ClosureHostFunction::new(
+                move |args: Vec<Value>, _mem: &mut LinearMemory| match mode {
+                    StubMode::Trap => Err(format!(
+                        "Called unresolved import {name} (stubbed by --stub-imports trap)"
+                    )),
+                    StubMode::Zero => Ok(results.clone()),
+                    StubMode::Log => {
+                        log_call(&name, &args, &results);
+                        Ok(results.clone())
+                    }
+                },
+                func_type.params.len(),
+                result_count,
+            )),
+        );
+        stubbed.push(label);
+    }
+    stubbed
+}
+
+/// Print a host call as `module::name(args) -> [results]` on stderr
+pub fn log_call(name: &str, args: &[Value], results: &[Value]) {
+    let args: Vec<String> = args.iter().map(format_value).collect();
+    let results: Vec<String> = results.iter().map(format_value).collect();
+    eprintln!("🔌 {name}({}) -> [{}]", args.join(", "), results.join(", "));
+}
+
+/// The zero of a value type
+pub fn zero_value(value_type: &ValueType) -> Value {
+    match value_type {
+        ValueType::I64 => Value::I64(0),
+        ValueType::F32 => Value::F32(0.0),
+        ValueType::F64 => Value::F64(0.0),
+        ValueType::FuncRef => Value::FuncRef(None),
+        ValueType::ExternRef => Value::ExternRef(None),
+        // The interpreter has no v128 values
+        ValueType::I32 | ValueType::V128 => Value::I32(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::core::module::{FunctionType, ImportDesc};
+
+    #[test]
+    fn test_stub_unresolved_imports() {
+        let mut module = Module::new();
+        module.types.push(FunctionType {
+            params: vec![ValueType::I32],
+            results: vec![ValueType::I64],
+        });
+        for name in ["provided", "exotic"] {
+            module.imports.push(ImportDesc {
+                module: "env".to_string(),
+                name: name.to_string(),
+                kind: ImportKind::Function(0),
+            });
+        }
+        let mut linker = Linker::new();
+        linker.register(
+            "env",
+            "provided",
+            Box::new(ClosureHostFunction::new(
+                |_args, _mem: &mut LinearMemory| Ok(vec![Value::I64(7)]),
+                1,
+                1,
+            )),
+        );
+
+        let stubbed = stub_unresolved_imports(&mut linker, &module, StubMode::Zero);
+        assert_eq!(stubbed, vec!["env::exotic"]);
+        let mut memory = LinearMemory::new(1, None).unwrap();
+        let stub = linker.get_import("env", "exotic").unwrap();
+        assert_eq!(stub.signature(), (1, 1));
+        assert_eq!(
+            stub.call(vec![Value::I32(3)], &mut memory),
+            Ok(vec![Value::I64(0)])
+        );
+
+        let mut linker = Linker::new();
+        stub_unresolved_imports(&mut linker, &module, StubMode::Trap);
+        let err = linker
+            .get_import("env", "provided")
+            .unwrap()
+            .call(vec![Value::I32(3)], &mut memory)
+            .unwrap_err();
+        assert!(err.contains("env::provided"));
+        assert!(StubMode::parse("panic").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+/// Instruction-level tracing for the interpreter
+/// Each executed instruction is recorded with its function, offset and the
+/// top of the operand stack. Only the most recent entries are kept, so a
+/// trace of a long run still ends with the instructions leading up to a trap.
+use super::executor::Instruction;
+use super::values::Value;
+use std::collections::{HashSet, VecDeque};
+use std::io::Write;
+use std::path::Path;
+
+/// Entries kept when no limit is given
+pub const DEFAULT_TRACE_LIMIT: usize = 100_000;
+
+/// Operand stack values shown per entry, from the top
+const STACK_VALUES_SHOWN: usize = 6;
+
+/// Ring buffer of executed instructions
+#[derive(Debug, Clone)]
+pub struct InstructionTrace {
+    /// Functions to trace; `None` traces all of them
+    functions: Option<HashSet<u32>>,
+    entries: VecDeque<String>,
+    limit: usize,
+    /// Instructions traced, including ones dropped from the buffer
+    pub traced: u64,
+}
+
+impl InstructionTrace {
+    pub fn new(functions: Option<HashSet<u32>>, limit: usize) -> Self {
+        Self {
+            functions,
+            entries: VecDeque::new(),
+            limit: limit.max(1),
+            traced: 0,
+        }
+    }
+
+    pub fn wants(&self, func_idx: u32) -> bool {
+        self.functions
+            .as_ref()
+            .is_none_or(|functions| functions.contains(&func_idx))
+    }
+
+    /// Record `instr` about to run at `pc` in the function labelled `label`
+    pub fn record(&mut self, label: &str, pc: usize, instr: &Instruction, stack: &[Value]) {
+        self.traced += 1;
+        if self.entries.len() == self.limit {
+            self.entries.pop_front();
+        }
+        let shown = &stack[stack.len().saturating_sub(STACK_VALUES_SHOWN)..];
+        let mut entry = format!("{label} +{pc:#x}  {instr:?}  stack[{}]", stack.len());
+        if shown.len() < stack.len() {
+            entry.push_str(" ..");
+        }
+        for value in shown {
+            entry.push(' ');
+            entry.push_str(&format_value(value));
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+
+    /// Write the kept entries to `path`, oldest first
+    pub fn write_to(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(
+            out,
+            "# wasmrun instruction trace: {} traced, last {} kept",
+            self.traced,
+            self.entries.len()
+        )?;
+        for entry in &self.entries {
+            writeln!(out, "{entry}")?;
+        }
+        out.flush()
+    }
+}
+
+/// A value with its type, as `i32:5`
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::I32(v) => format!("i32:{v}"),
+        Value::I64(v) => format!("i64:{v}"),
+        Value::F32(v) => format!("f32:{v}"),
+        Value::F64(v) => format!("f64:{v}"),
+        Value::FuncRef(Some(idx)) => format!("funcref:{idx}"),
+        Value::ExternRef(Some(handle)) => format!("externref:{handle}"),
+        Value::FuncRef(None) | Value::ExternRef(None) => "ref.null".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_keeps_latest_entries() {
+        let mut trace = InstructionTrace::new(Some(HashSet::from([2])), 2);
+        assert!(trace.wants(2));
+        assert!(!trace.wants(3));
+
+        let stack: Vec<Value> = (0..8).map(Value::I32).collect();
+        trace.record("func[2]", 0, &Instruction::Nop, &[]);
+        trace.record("func[2]", 1, &Instruction::I32Const(7), &stack[..1]);
+        trace.record("func[2]", 3, &Instruction::I32Add, &stack);
+
+        assert_eq!(trace.traced, 3);
+        let entries: Vec<&str> = trace.entries().collect();
+        assert_eq!(
+            entries,
+            vec![
+                "func[2] +0x1  I32Const(7)  stack[1] i32:0",
+                "func[2] +0x3  I32Add  stack[8] .. i32:2 i32:3 i32:4 i32:5 i32:6 i32:7",
+            ]
+        );
+    }
+}
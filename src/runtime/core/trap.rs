@@ -0,0 +1,73 @@
+/// Backtraces for interpreter traps
+/// Frames are named from the module's `name` section (demangled) when it has
+/// one, falling back to export names.
+use serde::Serialize;
+use std::fmt;
+
+/// Most frames printed under a trap message
+pub const MAX_PRINTED_FRAMES: usize = 32;
+
+/// One call frame, innermost first in a backtrace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BacktraceFrame {
+    pub func_index: u32,
+    pub name: Option<String>,
+    /// Byte offset in the function body of the instruction being executed
+    pub offset: usize,
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} +{:#x}",
+            function_label(self.func_index, self.name.as_deref()),
+            self.offset
+        )
+    }
+}
+
+/// `func[N]`, with its name when it has one
+pub fn function_label(func_index: u32, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("func[{func_index}] \"{name}\""),
+        None => format!("func[{func_index}]"),
+    }
+}
+
+/// A numbered backtrace for printing under an error
+pub fn format_backtrace(frames: &[BacktraceFrame]) -> String {
+    let mut out = String::from("Backtrace:");
+    for (depth, frame) in frames.iter().take(MAX_PRINTED_FRAMES).enumerate() {
+        out.push_str(&format!("\n    #{depth} {frame}"));
+    }
+    if frames.len() > MAX_PRINTED_FRAMES {
+        out.push_str(&format!(
+            "\n    ... {} more frame(s)",
+            frames.len() - MAX_PRINTED_FRAMES
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_backtrace() {
+        let frame = |func_index, name: Option<&str>, offset| BacktraceFrame {
+            func_index,
+            name: name.map(str::to_string),
+            offset,
+        };
+        let frames = vec![frame(4, Some("app::parse"), 0x1c), frame(1, None, 0x3)];
+        assert_eq!(
+            format_backtrace(&frames),
+            "Backtrace:\n    #0 func[4] \"app::parse\" +0x1c\n    #1 func[1] +0x3"
+        );
+
+        let deep = vec![frame(2, None, 0); MAX_PRINTED_FRAMES + 3];
+        assert!(format_backtrace(&deep).ends_with("... 3 more frame(s)"));
+    }
+}
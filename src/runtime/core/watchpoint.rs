@@ -0,0 +1,280 @@
+/// Memory watchpoints for the interpreter
+/// A watchpoint covers a byte range of linear memory. Every store, and every
+/// `memory.fill`, `memory.copy` and `memory.init`, that writes into the range
+/// is reported with a backtrace before it happens.
+use super::executor::Instruction;
+use super::values::Value;
+use std::fmt;
+use std::io::{BufRead, IsTerminal, Write};
+
+/// Sentinel error returned when a watchpoint stops execution
+pub const WATCHPOINT_STOP_ERROR: &str = "__wasmrun_watchpoint_stop__";
+
+/// What happens when a watched range is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatchAction {
+    /// Print the write and a backtrace, then keep going
+    #[default]
+    Log,
+    /// Print, then wait for Enter on the terminal (`q` stops)
+    Pause,
+    /// Print, then end execution
+    Stop,
+}
+
+impl WatchAction {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "log" => Ok(Self::Log),
+            "pause" => Ok(Self::Pause),
+            "stop" => Ok(Self::Stop),
+            _ => Err(format!(
+                "Unknown watch action '{value}' (use log, pause or stop)"
+            )),
+        }
+    }
+}
+
+/// A half-open byte range `start..end` of linear memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Watchpoint {
+    /// Parse `START..END`, `START..=END`, `START+LEN` or a single `ADDR`.
+    /// Numbers are decimal or `0x` hex.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (start, end) = if let Some((start, end)) = spec.split_once("..=") {
+            (parse_addr(start)?, parse_addr(end)?.saturating_add(1))
+        } else if let Some((start, end)) = spec.split_once("..") {
+            (parse_addr(start)?, parse_addr(end)?)
+        } else if let Some((start, len)) = spec.split_once('+') {
+            let start = parse_addr(start)?;
+            (start, start.saturating_add(parse_addr(len)?))
+        } else {
+            let addr = parse_addr(spec)?;
+            (addr, addr.saturating_add(1))
+        };
+        if end <= start {
+            return Err(format!("Empty watch range '{spec}'"));
+        }
+        Ok(Self { start, end })
+    }
+
+    fn overlaps(&self, start: u64, len: u64) -> bool {
+        len > 0 && start < self.end && start.saturating_add(len) > self.start
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}..{:#x}", self.start, self.end)
+    }
+}
+
+fn parse_addr(value: &str) -> Result<u64, String> {
+    let value = value.trim().replace('_', "");
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| format!("Invalid address '{value}'"))
+}
+
+/// A write about to land in watched memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchHit {
+    pub watchpoint: Watchpoint,
+    pub op: &'static str,
+    pub addr: u64,
+    pub len: u64,
+    /// Stored value, for plain stores
+    pub value: Option<Value>,
+}
+
+/// Watchpoints installed on an executor
+#[derive(Debug, Clone, Default)]
+pub struct MemoryWatch {
+    pub watchpoints: Vec<Watchpoint>,
+    pub action: WatchAction,
+    pub hits: u64,
+}
+
+impl MemoryWatch {
+    pub fn new(watchpoints: Vec<Watchpoint>, action: WatchAction) -> Self {
+        Self {
+            watchpoints,
+            action,
+            hits: 0,
+        }
+    }
+
+    /// The watchpoint `instr` is about to write into, given the operand stack
+    /// before it runs
+    pub fn check(&self, instr: &Instruction, stack: &[Value]) -> Option<WatchHit> {
+        let (op, addr, len, value) = write_target(instr, stack)?;
+        let watchpoint = self
+            .watchpoints
+            .iter()
+            .find(|w| w.overlaps(addr, len))
+            .copied()?;
+        Some(WatchHit {
+            watchpoint,
+            op,
+            addr,
+            len,
+            value,
+        })
+    }
+
+    /// Report a hit and decide whether execution goes on
+    pub fn report(&mut self, hit: &WatchHit, backtrace: &[String]) -> Result<(), String> {
+        self.hits += 1;
+        let value = match hit.value {
+            Some(Value::I32(v)) => format!(" of {:#x}", v as u32),
+            Some(Value::I64(v)) => format!(" of {:#x}", v as u64),
+            Some(Value::F32(v)) => format!(" of {v}"),
+            Some(Value::F64(v)) => format!(" of {v}"),
+            _ => String::new(),
+        };
+        let mut out = std::io::stderr().lock();
+        let _ = writeln!(
+            out,
+            "👁️  Watchpoint {} hit: {}{value} writes {} byte(s) at {:#x}",
+            hit.watchpoint, hit.op, hit.len, hit.addr
+        );
+        for (depth, frame) in backtrace.iter().enumerate() {
+            let _ = writeln!(out, "    #{depth} {frame}");
+        }
+
+        match self.action {
+            WatchAction::Log => Ok(()),
+            WatchAction::Stop => Err(stop_error(hit)),
+            WatchAction::Pause => {
+                let stdin = std::io::stdin();
+                if !stdin.is_terminal() {
+                    return Ok(());
+                }
+                let _ = write!(out, "    Paused. Enter to continue, q to stop: ");
+                let _ = out.flush();
+                drop(out);
+                let mut line = String::new();
+                let _ = stdin.lock().read_line(&mut line);
+                if line.trim().eq_ignore_ascii_case("q") {
+                    Err(stop_error(hit))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn stop_error(hit: &WatchHit) -> String {
+    format!(
+        "{WATCHPOINT_STOP_ERROR} Stopped at watchpoint {} ({} at {:#x})",
+        hit.watchpoint, hit.op, hit.addr
+    )
+}
+
+/// Name, address and length of the memory write `instr` will make
+fn write_target(
+    instr: &Instruction,
+    stack: &[Value],
+) -> Option<(&'static str, u64, u64, Option<Value>)> {
+    let from_top = |n: usize| stack.len().checked_sub(n).and_then(|i| stack.get(i));
+    let as_u32 = |v: Option<&Value>| match v {
+        Some(Value::I32(x)) => Some(*x as u32 as u64),
+        _ => None,
+    };
+    let store = |op, offset: u32, len| {
+        let addr = as_u32(from_top(2))? + offset as u64;
+        Some((op, addr, len, from_top(1).copied()))
+    };
+    match *instr {
+        Instruction::I32Store(o) => store("i32.store", o, 4),
+        Instruction::I64Store(o) => store("i64.store", o, 8),
+        Instruction::F32Store(o) => store("f32.store", o, 4),
+        Instruction::F64Store(o) => store("f64.store", o, 8),
+        Instruction::I32Store8(o) => store("i32.store8", o, 1),
+        Instruction::I32Store16(o) => store("i32.store16", o, 2),
+        Instruction::I64Store8(o) => store("i64.store8", o, 1),
+        Instruction::I64Store16(o) => store("i64.store16", o, 2),
+        Instruction::I64Store32(o) => store("i64.store32", o, 4),
+        Instruction::MemoryFill => Some((
+            "memory.fill",
+            as_u32(from_top(3))?,
+            as_u32(from_top(1))?,
+            None,
+        )),
+        Instruction::MemoryCopy => Some((
+            "memory.copy",
+            as_u32(from_top(3))?,
+            as_u32(from_top(1))?,
+            None,
+        )),
+        Instruction::MemoryInit(_) => Some((
+            "memory.init",
+            as_u32(from_top(3))?,
+            as_u32(from_top(1))?,
+            None,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_watch_ranges() {
+        assert_eq!(
+            Watchpoint::parse("0x1000..0x1010").unwrap(),
+            Watchpoint {
+                start: 0x1000,
+                end: 0x1010
+            }
+        );
+        assert_eq!(Watchpoint::parse("16..=31").unwrap().end, 32);
+        assert_eq!(Watchpoint::parse("0x20+8").unwrap().end, 0x28);
+        assert_eq!(Watchpoint::parse("4096").unwrap().end, 4097);
+        assert!(Watchpoint::parse("0x10..0x10").is_err());
+        assert!(Watchpoint::parse("zz").is_err());
+        assert_eq!(WatchAction::parse("pause").unwrap(), WatchAction::Pause);
+        assert!(WatchAction::parse("break").is_err());
+    }
+
+    #[test]
+    fn test_check_finds_overlapping_writes() {
+        let watch = MemoryWatch::new(
+            vec![Watchpoint::parse("0x100..0x104").unwrap()],
+            WatchAction::Log,
+        );
+        // i32.store offset=2 at 0xfe lands on 0x100..0x104
+        let stack = [Value::I32(0xfe), Value::I32(42)];
+        let hit = watch.check(&Instruction::I32Store(2), &stack).unwrap();
+        assert_eq!((hit.op, hit.addr, hit.len), ("i32.store", 0x100, 4));
+        assert_eq!(hit.value, Some(Value::I32(42)));
+
+        // A store ending right before the range misses
+        let stack = [Value::I32(0xfc), Value::I32(1)];
+        assert!(watch.check(&Instruction::I32Store(0), &stack).is_none());
+
+        // memory.fill dest=0xf0 len=0x20 covers it; len 0 doesn't
+        let stack = [Value::I32(0xf0), Value::I32(0), Value::I32(0x20)];
+        assert_eq!(
+            watch.check(&Instruction::MemoryFill, &stack).unwrap().op,
+            "memory.fill"
+        );
+        let stack = [Value::I32(0x100), Value::I32(0), Value::I32(0)];
+        assert!(watch.check(&Instruction::MemoryFill, &stack).is_none());
+        assert!(watch.check(&Instruction::I32Add, &stack).is_none());
+    }
+}
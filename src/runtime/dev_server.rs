@@ -12,6 +12,8 @@ use super::wasi_fs::WasiFilesystem;
 
 pub struct DevServerManager {
     servers: Arc<Mutex<HashMap<Pid, DevServerHandle>>>,
+    /// Servers whose thread ended with an error, until collected
+    crashed: Arc<Mutex<Vec<(Pid, String)>>>,
 }
 
 struct DevServerHandle {
@@ -30,6 +32,7 @@ impl DevServerManager {
     pub fn new() -> Self {
         Self {
             servers: Arc::new(Mutex::new(HashMap::new())),
+            crashed: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -54,9 +57,13 @@ impl DevServerManager {
             servers.insert(pid, handle);
         }
 
+        let servers = Arc::clone(&self.servers);
+        let crashed = Arc::clone(&self.crashed);
         thread::spawn(move || {
             if let Err(e) = serve_wasi_files(port, &project_root, wasi_fs, stop_signal_clone) {
                 eprintln!("Dev server error for PID {pid}: {e}");
+                servers.lock().unwrap().remove(&pid);
+                crashed.lock().unwrap().push((pid, e.to_string()));
             }
         });
 
@@ -72,6 +79,11 @@ impl DevServerManager {
         Ok(())
     }
 
+    /// Servers that died since the last call, with their error
+    pub fn take_crashed(&self) -> Vec<(Pid, String)> {
+        std::mem::take(&mut *self.crashed.lock().unwrap())
+    }
+
     pub fn get_status(&self, pid: Pid) -> Option<DevServerStatus> {
         let servers = self.servers.lock().unwrap();
         servers.get(&pid).map(|s| DevServerStatus::Running(s.port))
@@ -129,17 +141,23 @@ fn serve_wasi_files(
             url_path.trim_start_matches('/')
         );
 
-        let response = match wasi_fs.read_file(&vfs_path) {
-            Ok(content) => {
+        // Files are streamed from the host rather than read whole first
+        let response = match wasi_fs.open_file(&vfs_path) {
+            Ok(file) => {
                 let content_type = get_content_type(Path::new(&url_path));
-                Response::from_data(content).with_header(
-                    tiny_http::Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
+                Response::from_file(file)
+                    .with_header(
+                        tiny_http::Header::from_bytes(
+                            &b"Content-Type"[..],
+                            content_type.as_bytes(),
+                        )
                         .unwrap(),
-                )
-            }
-            Err(_) => {
-                Response::from_string("404 Not Found").with_status_code(tiny_http::StatusCode(404))
+                    )
+                    .boxed()
             }
+            Err(_) => Response::from_string("404 Not Found")
+                .with_status_code(tiny_http::StatusCode(404))
+                .boxed(),
         };
 
         let _ = request.respond(response);
@@ -148,7 +166,7 @@ fn serve_wasi_files(
     Ok(())
 }
 
-fn get_content_type(path: &Path) -> String {
+pub fn get_content_type(path: &Path) -> String {
     match path.extension().and_then(|ext| ext.to_str()) {
         Some("html") => "text/html; charset=utf-8",
         Some("css") => "text/css",
@@ -243,6 +261,25 @@ mod tests {
         manager.stop_server(4).unwrap();
     }
 
+    #[test]
+    fn test_failed_server_is_reported_as_crashed() {
+        let temp = tempdir().unwrap();
+        let wasi_fs = create_wasi_fs_with_mount("/projects/5", temp.path());
+        let _taken = std::net::TcpListener::bind("127.0.0.1:19878").unwrap();
+
+        let manager = DevServerManager::new();
+        manager
+            .start_server(5, 19878, "/projects/5".to_string(), wasi_fs)
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let crashed = manager.take_crashed();
+        assert_eq!(crashed.len(), 1);
+        assert_eq!(crashed[0].0, 5);
+        assert!(manager.get_status(5).is_none());
+        assert!(manager.take_crashed().is_empty());
+    }
+
     #[test]
     fn test_content_type_detection() {
         assert_eq!(
@@ -0,0 +1,124 @@
+//! Hostname resolution for guest `getaddrinfo`: overrides from
+//! `wasmrun.toml` first, then the host resolver behind a small TTL cache.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::config::project::NetworkConfig;
+
+/// Entries kept before the oldest are evicted
+const CACHE_CAPACITY: usize = 256;
+
+pub struct Resolver {
+    overrides: HashMap<String, IpAddr>,
+    ttl: Duration,
+    cache: HashMap<String, (Vec<IpAddr>, Instant)>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new(&NetworkConfig::default())
+    }
+}
+
+impl Resolver {
+    pub fn new(config: &NetworkConfig) -> Self {
+        Self {
+            overrides: config
+                .hosts
+                .iter()
+                .map(|(name, ip)| (normalize(name), *ip))
+                .collect(),
+            ttl: Duration::from_secs(config.dns_cache_ttl),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn resolve(&mut self, hostname: &str) -> io::Result<Vec<IpAddr>> {
+        let key = normalize(hostname);
+        if let Some(ip) = self.overrides.get(&key) {
+            return Ok(vec![*ip]);
+        }
+        if let Some((addrs, resolved_at)) = self.cache.get(&key) {
+            if resolved_at.elapsed() < self.ttl {
+                return Ok(addrs.clone());
+            }
+        }
+
+        let mut addrs: Vec<IpAddr> = Vec::new();
+        for addr in (hostname, 0).to_socket_addrs()? {
+            if !addrs.contains(&addr.ip()) {
+                addrs.push(addr.ip());
+            }
+        }
+        if !self.ttl.is_zero() && !addrs.is_empty() {
+            self.insert(key, addrs.clone());
+        }
+        Ok(addrs)
+    }
+
+    fn insert(&mut self, key: String, addrs: Vec<IpAddr>) {
+        let ttl = self.ttl;
+        self.cache.retain(|_, (_, at)| at.elapsed() < ttl);
+        if self.cache.len() >= CACHE_CAPACITY {
+            if let Some(oldest) = self
+                .cache
+                .iter()
+                .min_by_key(|(_, (_, at))| *at)
+                .map(|(k, _)| k.clone())
+            {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.cache.insert(key, (addrs, Instant::now()));
+    }
+}
+
+/// Hostnames are case-insensitive, and a trailing dot marks the root
+fn normalize(hostname: &str) -> String {
+    hostname.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_and_cache() {
+        let config: NetworkConfig = toml::from_str(
+            r#"
+            dns_cache_ttl = 60
+
+            [hosts]
+            "My-API.internal" = "127.0.0.1"
+            "db.internal" = "::1"
+            "#,
+        )
+        .unwrap();
+        let mut resolver = Resolver::new(&config);
+
+        assert_eq!(
+            resolver.resolve("my-api.internal.").unwrap(),
+            vec![IpAddr::from([127, 0, 0, 1])]
+        );
+        assert_eq!(
+            resolver.resolve("DB.internal").unwrap(),
+            vec!["::1".parse::<IpAddr>().unwrap()]
+        );
+
+        assert_eq!(
+            resolver.resolve("127.0.0.2").unwrap(),
+            vec![IpAddr::from([127, 0, 0, 2])]
+        );
+        assert!(resolver.cache.contains_key("127.0.0.2"));
+
+        let mut uncached = Resolver::new(&NetworkConfig {
+            dns_cache_ttl: 0,
+            ..NetworkConfig::default()
+        });
+        uncached.resolve("127.0.0.2").unwrap();
+        assert!(uncached.cache.is_empty());
+    }
+}
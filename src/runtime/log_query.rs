@@ -0,0 +1,140 @@
+//! Filters for `GET /api/logs`, so the logs panel can narrow the trail on
+//! the server rather than downloading all of it on every refresh. Levels
+//! and sources are matched by their display names (`WARN`, `KERNEL`,
+//! `PYTHON`), case-insensitively.
+
+use anyhow::{bail, Context, Result};
+
+use crate::logging::LogEntry;
+use crate::ui::progress::strip_ansi;
+use crate::utils::percent_decode;
+
+const LEVELS: [&str; 4] = ["DEBUG", "INFO", "WARN", "ERROR"];
+
+/// A parsed `/api/logs` query string
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogQuery {
+    /// Levels to keep; empty keeps all
+    pub levels: Vec<String>,
+    /// Sources to keep; empty keeps all
+    pub sources: Vec<String>,
+    /// Text the message has to contain, ignoring case and ANSI colors
+    pub text: String,
+    pub pid: Option<u32>,
+    /// Only entries logged after this timestamp, as it appears in entries
+    pub since: Option<String>,
+    /// Keep the newest `limit` matches
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    /// Parse `level=&source=&q=&pid=&since=&limit=`; `level` and `source`
+    /// take comma-separated lists
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut parsed = Self::default();
+        let list = |value: &str| -> Vec<String> {
+            value
+                .split(',')
+                .map(|item| item.trim().to_uppercase())
+                .filter(|item| !item.is_empty() && item != "ALL")
+                .collect()
+        };
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value);
+            match key {
+                "level" => parsed.levels = list(&value),
+                "source" => parsed.sources = list(&value),
+                "q" => parsed.text = value.trim().to_lowercase(),
+                "pid" if !value.is_empty() => {
+                    parsed.pid = Some(value.parse().context("pid must be a number")?)
+                }
+                "since" if !value.is_empty() => parsed.since = Some(value),
+                "limit" if !value.is_empty() => {
+                    parsed.limit = Some(value.parse().context("limit must be a number")?)
+                }
+                _ => {}
+            }
+        }
+        if let Some(level) = parsed.levels.iter().find(|l| !LEVELS.contains(&l.as_str())) {
+            bail!("Unknown level {level}; use {}", LEVELS.join(", "));
+        }
+        Ok(parsed)
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        (self.levels.is_empty() || self.levels.contains(&entry.level.to_string()))
+            && (self.sources.is_empty() || self.sources.contains(&entry.source.to_string()))
+            && self.pid.is_none_or(|pid| entry.pid == Some(pid))
+            && self
+                .since
+                .as_ref()
+                .is_none_or(|since| entry.timestamp.as_str() > since.as_str())
+            && (self.text.is_empty()
+                || strip_ansi(&entry.message)
+                    .to_lowercase()
+                    .contains(&self.text))
+    }
+
+    /// The entries that match, oldest first
+    pub fn apply(&self, entries: &[LogEntry]) -> Vec<LogEntry> {
+        let mut matched: Vec<LogEntry> = entries
+            .iter()
+            .filter(|entry| self.matches(entry))
+            .cloned()
+            .collect();
+        if let Some(limit) = self.limit {
+            matched.drain(..matched.len().saturating_sub(limit));
+        }
+        matched
+    }
+}
+
+/// Display names of the sources in `entries`, sorted, for the panel's
+/// source filter
+pub fn sources(entries: &[LogEntry]) -> Vec<String> {
+    let mut names: Vec<String> = entries.iter().map(|e| e.source.to_string()).collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::LogSource;
+
+    #[test]
+    fn test_parse_and_filter() {
+        let mut entries = vec![
+            LogEntry::info(LogSource::Kernel, "Kernel started"),
+            LogEntry::warn(LogSource::LanguageRuntime("python".into()), "Slow import").with_pid(7),
+            LogEntry::error(LogSource::Filesystem, "Mount failed: /data"),
+            LogEntry::error(LogSource::Kernel, "Process 9 \x1b[31mexited\x1b[0m"),
+        ];
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.timestamp = format!("2026-01-01 00:00:0{i}.000");
+        }
+
+        let query = LogQuery::parse("level=warn%2CERROR&source=python,fs").unwrap();
+        let messages: Vec<_> = query
+            .apply(&entries)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(messages, ["Slow import", "Mount failed: /data"]);
+
+        let query = LogQuery::parse("q=9+EXITED+&level=all").unwrap();
+        assert_eq!(query.apply(&entries).len(), 1);
+        assert_eq!(LogQuery::parse("pid=7").unwrap().apply(&entries).len(), 1);
+
+        let query = LogQuery::parse("since=2026-01-01+00%3A00%3A01.000&limit=1").unwrap();
+        let newest = query.apply(&entries);
+        assert_eq!(newest.len(), 1);
+        assert_eq!(newest[0].message, "Process 9 \x1b[31mexited\x1b[0m");
+
+        assert!(LogQuery::parse("level=loud").is_err());
+        assert!(LogQuery::parse("limit=some").is_err());
+        assert_eq!(sources(&entries), ["FS", "KERNEL", "PYTHON"]);
+    }
+}
@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 
+use crate::runtime::procfs;
 use crate::runtime::scheduler::ProcessScheduler;
 use crate::runtime::wasi_fs::WasiFilesystem;
 
@@ -106,6 +107,9 @@ impl WasmMicroKernel {
         wasi_fs
             .mount("/", &workspace_root)
             .expect("Failed to mount workspace root");
+        wasi_fs
+            .set_volume_root(workspace_root.join("volumes"))
+            .expect("Failed to create volume root");
 
         Self {
             processes: Arc::new(RwLock::new(HashMap::new())),
@@ -128,6 +132,15 @@ impl WasmMicroKernel {
         Arc::clone(&self.wasi_fs)
     }
 
+    /// The kernel as `pid` sees it, so filesystem calls go through that
+    /// process's own mounts and permissions
+    pub fn process_view(&self, pid: Pid) -> Self {
+        Self {
+            wasi_fs: Arc::new(self.wasi_fs.for_process(pid)),
+            ..self.clone()
+        }
+    }
+
     pub fn start_scheduler(&self) -> Result<()> {
         let mut running = self.scheduler_running.lock().unwrap();
         if *running {
@@ -281,21 +294,45 @@ fn validate_path(path: &str) -> Result<()> {
 impl SyscallInterface for WasmMicroKernel {
     fn read_file(&self, path: &str) -> Result<Vec<u8>> {
         validate_path(path)?;
+        if procfs::is_virtual(path) {
+            return procfs::read(self, path);
+        }
         self.wasi_fs.read_file(path)
     }
 
     fn write_file(&self, path: &str, data: &[u8]) -> Result<()> {
         validate_path(path)?;
+        if procfs::is_virtual(path) {
+            if let Some(output) = procfs::write(path, data)? {
+                print!("{}", String::from_utf8_lossy(output));
+            }
+            return Ok(());
+        }
         self.wasi_fs.write_file(path, data)
     }
 
     fn list_directory(&self, path: &str) -> Result<Vec<VfsEntry>> {
         validate_path(path)?;
+        if procfs::is_virtual(path) {
+            return procfs::list(self, path);
+        }
         let entries = self.wasi_fs.path_readdir(path)?;
         let now = chrono::Utc::now();
-        Ok(entries
-            .into_iter()
-            .map(|e| VfsEntry {
+        let synthetic_roots = if path.trim_end_matches('/').is_empty() {
+            ["dev", "proc"].as_slice()
+        } else {
+            [].as_slice()
+        };
+        Ok(synthetic_roots
+            .iter()
+            .map(|name| VfsEntry {
+                path: format!("/{name}"),
+                is_directory: true,
+                size: None,
+                created_at: now,
+                modified_at: now,
+            })
+            .chain(entries.into_iter().map(|e| VfsEntry {
                 path: format!("{}/{}", path.trim_end_matches('/'), e.name),
                 is_directory: e.is_dir,
                 size: if e.is_dir {
@@ -305,17 +342,23 @@ impl SyscallInterface for WasmMicroKernel {
                 },
                 created_at: now,
                 modified_at: now,
-            })
+            }))
             .collect())
     }
 
     fn create_directory(&self, path: &str) -> Result<()> {
         validate_path(path)?;
+        if procfs::is_virtual(path) {
+            anyhow::bail!("Read-only file system: {path}");
+        }
         self.wasi_fs.path_create_directory(path)
     }
 
     fn delete_file(&self, path: &str) -> Result<()> {
         validate_path(path)?;
+        if procfs::is_virtual(path) {
+            anyhow::bail!("Read-only file system: {path}");
+        }
         self.wasi_fs.path_unlink_file(path)
     }
 }
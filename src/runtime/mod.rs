@@ -1,15 +1,56 @@
+pub mod bindgen;
 pub mod core;
+#[cfg(feature = "os-mode")]
 pub mod dev_server;
+#[cfg(feature = "os-mode")]
+pub mod dns;
+#[cfg(feature = "os-mode")]
 pub mod languages;
+#[cfg(feature = "os-mode")]
+pub mod log_query;
+#[cfg(feature = "os-mode")]
 pub mod microkernel;
+#[cfg(feature = "os-mode")]
 pub mod multilang_kernel;
+#[cfg(feature = "os-mode")]
 pub mod network_namespace;
+#[cfg(feature = "os-mode")]
+pub mod os_auth;
+#[cfg(feature = "os-mode")]
+pub mod os_bridge;
+#[cfg(feature = "os-mode")]
+pub mod os_limits;
+#[cfg(feature = "os-mode")]
+pub mod os_metrics;
+#[cfg(feature = "os-mode")]
 pub mod os_server;
+#[cfg(feature = "os-mode")]
+pub mod process_table;
+#[cfg(feature = "os-mode")]
+pub mod procfs;
+#[cfg(feature = "os-mode")]
 pub mod project_files;
+#[cfg(feature = "os-mode")]
 pub mod registry;
+#[cfg(any(feature = "os-mode", feature = "interpreter"))]
 pub mod runtime_cache;
+#[cfg(feature = "os-mode")]
 pub mod scheduler;
+#[cfg(feature = "os-mode")]
+pub mod supervisor;
+#[cfg(feature = "os-mode")]
 pub mod syscalls;
+#[cfg(feature = "os-mode")]
 pub mod tunnel;
+#[cfg(feature = "os-mode")]
+pub mod udp_forward;
+#[cfg(feature = "os-mode")]
+pub mod vfs_search;
+#[cfg(feature = "os-mode")]
+pub mod vfs_watch;
+#[cfg(feature = "os-mode")]
+pub mod virtual_switch;
+#[cfg(feature = "interpreter")]
 pub mod wasi;
+#[cfg(feature = "os-mode")]
 pub mod wasi_fs;
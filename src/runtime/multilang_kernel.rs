@@ -1,14 +1,23 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::config::project::{AuthConfig, FsConfig, NetworkConfig};
 use crate::logging::LogTrailSystem;
 use crate::runtime::dev_server::DevServerManager;
+use crate::runtime::dns::Resolver;
 use crate::runtime::microkernel::{Pid, WasmInstance, WasmMicroKernel};
-use crate::runtime::network_namespace::NetworkNamespace;
+use crate::runtime::network_namespace::{
+    GuestPort, HostPort, HostPortTable, NetworkNamespace, PortMapping, SocketProtocol,
+};
 use crate::runtime::registry::{DevServerStatus, LanguageRuntimeRegistry};
-use crate::runtime::syscalls::{SyscallArgs, SyscallHandler, SyscallResult};
+use crate::runtime::syscalls::{SocketInfo, SyscallArgs, SyscallHandler, SyscallResult};
+use crate::runtime::udp_forward::{UdpForwarder, UdpSessionInfo, DEFAULT_UDP_IDLE_TIMEOUT};
+use crate::runtime::virtual_switch::VirtualListenerInfo;
+use crate::runtime::wasi_fs::MountSpec;
 
 /// Multi-language kernel that orchestrates different language runtimes
 pub struct MultiLanguageKernel {
@@ -18,7 +27,13 @@ pub struct MultiLanguageKernel {
     dev_server_manager: Arc<DevServerManager>,
     syscall_handler: Arc<Mutex<SyscallHandler>>,
     process_languages: Arc<Mutex<HashMap<Pid, String>>>,
+    process_env: Arc<Mutex<HashMap<Pid, BTreeMap<String, String>>>>,
+    process_args: Arc<Mutex<HashMap<Pid, Vec<String>>>>,
     network_namespaces: Arc<Mutex<HashMap<Pid, Arc<NetworkNamespace>>>>,
+    /// Host ports claimed across every process's namespace
+    host_ports: HostPortTable,
+    /// Relays for UDP mappings, by process and guest port
+    udp_forwarders: Arc<Mutex<HashMap<(Pid, GuestPort), UdpForwarder>>>,
     log_system: Arc<LogTrailSystem>,
 }
 
@@ -35,6 +50,24 @@ pub struct OsRunConfig {
     pub tunnel_server: Option<String>,
     pub tunnel_secret: Option<String>,
     pub allow_cors: bool,
+    /// Largest accepted request body in MB; 0 means unlimited
+    pub max_body_mb: u32,
+    /// `POST`/`DELETE` API requests allowed per client IP per minute; 0 means unlimited
+    pub rate_limit: u32,
+    /// Reject every `POST`/`DELETE` API request with 403
+    pub read_only: bool,
+    /// Volumes and host directories mounted for the main project process only
+    #[serde(default)]
+    pub mounts: Vec<MountSpec>,
+    /// `[network]` from `wasmrun.toml`
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// `[fs]` from `wasmrun.toml`
+    #[serde(default)]
+    pub fs: FsConfig,
+    /// `[auth]` from `wasmrun.toml`
+    #[serde(default)]
+    pub auth: AuthConfig,
 }
 
 impl Default for MultiLanguageKernel {
@@ -56,7 +89,11 @@ impl MultiLanguageKernel {
             dev_server_manager: Arc::new(DevServerManager::new()),
             syscall_handler: Arc::new(Mutex::new(syscall_handler)),
             process_languages: Arc::new(Mutex::new(HashMap::new())),
+            process_env: Arc::new(Mutex::new(HashMap::new())),
+            process_args: Arc::new(Mutex::new(HashMap::new())),
             network_namespaces: Arc::new(Mutex::new(HashMap::new())),
+            host_ports: HostPortTable::probing(),
+            udp_forwarders: Arc::new(Mutex::new(HashMap::new())),
             log_system: Arc::new(LogTrailSystem::new()),
         }
     }
@@ -135,6 +172,17 @@ impl MultiLanguageKernel {
         &mut self,
         config: OsRunConfig,
         language: &str,
+    ) -> Result<Pid> {
+        self.run_project_with_entry(config, language, None)
+    }
+
+    /// Run a project, optionally starting from `entry` instead of the entry
+    /// point the runtime detects
+    pub fn run_project_with_entry(
+        &mut self,
+        config: OsRunConfig,
+        language: &str,
+        entry: Option<&str>,
     ) -> Result<Pid> {
         // 1. Ensure runtime is loaded
         self.ensure_runtime_loaded(language)?;
@@ -146,7 +194,10 @@ impl MultiLanguageKernel {
             .ok_or_else(|| anyhow::anyhow!("Runtime not found: {language}"))?;
 
         // 3. Prepare project bundle
-        let bundle = runtime.prepare_project(&config.project_path)?;
+        let mut bundle = runtime.prepare_project(&config.project_path)?;
+        if let Some(entry) = entry {
+            bundle.entry_point = entry.to_string();
+        }
 
         // 4. Run the project
         let pid = runtime.run_project(bundle, &mut self.base_kernel)?;
@@ -159,7 +210,10 @@ impl MultiLanguageKernel {
 
         // 6. Create network namespace for process
         {
-            let network_ns = Arc::new(NetworkNamespace::new(pid));
+            let network_ns = Arc::new(NetworkNamespace::with_host_ports(
+                pid,
+                self.host_ports.clone(),
+            ));
             let mut namespaces = self.network_namespaces.lock().unwrap();
             namespaces.insert(pid, network_ns);
         }
@@ -274,6 +328,34 @@ impl MultiLanguageKernel {
         process_languages.get(&pid).cloned()
     }
 
+    /// Record the environment a process was launched with
+    pub fn set_process_env(&self, pid: Pid, env: BTreeMap<String, String>) {
+        self.process_env.lock().unwrap().insert(pid, env);
+    }
+
+    pub fn get_process_env(&self, pid: Pid) -> BTreeMap<String, String> {
+        self.process_env
+            .lock()
+            .unwrap()
+            .get(&pid)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record the arguments a process was launched with, after its entry file
+    pub fn set_process_args(&self, pid: Pid, args: Vec<String>) {
+        self.process_args.lock().unwrap().insert(pid, args);
+    }
+
+    pub fn get_process_args(&self, pid: Pid) -> Vec<String> {
+        self.process_args
+            .lock()
+            .unwrap()
+            .get(&pid)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     /// List all active processes with their languages
     pub fn list_processes_with_languages(&self) -> Vec<(Pid, String, String)> {
         let processes = self.base_kernel.list_processes();
@@ -296,6 +378,11 @@ impl MultiLanguageKernel {
         self.dev_server_manager.get_status(pid)
     }
 
+    /// PIDs whose dev server died since the last call, with the error
+    pub fn take_crashed_dev_servers(&self) -> Vec<(Pid, String)> {
+        self.dev_server_manager.take_crashed()
+    }
+
     /// Stop a process and clean up associated resources
     pub fn kill_process(&mut self, pid: Pid) -> Result<()> {
         // Stop dev server if running
@@ -306,12 +393,20 @@ impl MultiLanguageKernel {
             let mut process_languages = self.process_languages.lock().unwrap();
             process_languages.remove(&pid);
         }
+        self.process_env.lock().unwrap().remove(&pid);
+        self.process_args.lock().unwrap().remove(&pid);
+        self.base_kernel.wasi_filesystem().unmount_process(pid);
 
-        // Remove network namespace
+        // Remove network namespace and its forwarders
         {
             let mut namespaces = self.network_namespaces.lock().unwrap();
             namespaces.remove(&pid);
         }
+        self.udp_forwarders
+            .lock()
+            .unwrap()
+            .retain(|(owner, _), _| *owner != pid);
+        self.syscall_handler.lock().unwrap().release_process(pid);
 
         // Kill the process in the base kernel
         self.base_kernel.kill_process(pid)?;
@@ -389,6 +484,114 @@ impl MultiLanguageKernel {
         namespaces.get(&pid).cloned()
     }
 
+    /// Map `guest_port` of `pid` to a host port. UDP mappings also start a
+    /// relay from the host port, closing peer sessions idle for
+    /// `idle_timeout` (a minute by default).
+    pub fn forward_port(
+        &self,
+        pid: Pid,
+        guest_port: GuestPort,
+        protocol: SocketProtocol,
+        preferred: Option<HostPort>,
+        idle_timeout: Option<Duration>,
+    ) -> Result<PortMapping> {
+        let ns = self
+            .get_network_namespace(pid)
+            .ok_or_else(|| anyhow::anyhow!("Process with PID {pid} not found"))?;
+        let mapping = ns.allocate_port_preferring(guest_port, protocol, preferred)?;
+
+        // A guest bound on its own port needs no relay
+        if protocol == SocketProtocol::Udp && mapping.host_port != guest_port {
+            let started = UdpForwarder::start(
+                SocketAddr::from(([127, 0, 0, 1], mapping.host_port)),
+                SocketAddr::from(([127, 0, 0, 1], guest_port)),
+                idle_timeout.unwrap_or(DEFAULT_UDP_IDLE_TIMEOUT),
+            );
+            match started {
+                Ok(forwarder) => {
+                    self.udp_forwarders
+                        .lock()
+                        .unwrap()
+                        .insert((pid, guest_port), forwarder);
+                }
+                Err(e) => {
+                    let _ = ns.deallocate_port(guest_port);
+                    anyhow::bail!("Failed to listen on UDP port {}: {e}", mapping.host_port);
+                }
+            }
+        }
+        Ok(mapping)
+    }
+
+    /// Remove a mapping and stop its relay
+    pub fn remove_port_forward(&self, pid: Pid, guest_port: GuestPort) -> Result<()> {
+        let ns = self
+            .get_network_namespace(pid)
+            .ok_or_else(|| anyhow::anyhow!("Process with PID {pid} not found"))?;
+        ns.deallocate_port(guest_port)?;
+        self.udp_forwarders
+            .lock()
+            .unwrap()
+            .remove(&(pid, guest_port));
+        Ok(())
+    }
+
+    /// Peers talking to a forwarded UDP port
+    pub fn udp_sessions(&self, pid: Pid, guest_port: GuestPort) -> Option<Vec<UdpSessionInfo>> {
+        self.udp_forwarders
+            .lock()
+            .unwrap()
+            .get(&(pid, guest_port))
+            .map(UdpForwarder::sessions)
+    }
+
+    /// Resolve guest `getaddrinfo` calls with `network`'s host overrides
+    /// and cache lifetime
+    pub fn configure_network(&self, network: &NetworkConfig) {
+        self.syscall_handler
+            .lock()
+            .unwrap()
+            .set_resolver(Resolver::new(network));
+    }
+
+    /// Enforce `fs`'s quotas on writes through the shared filesystem
+    pub fn configure_fs(&self, fs: &FsConfig) -> Result<()> {
+        self.wasi_filesystem().set_quotas(fs)
+    }
+
+    /// Listeners on the guest-to-guest virtual network
+    pub fn virtual_listeners(&self) -> Vec<VirtualListenerInfo> {
+        self.syscall_handler
+            .lock()
+            .unwrap()
+            .virtual_switch()
+            .listeners()
+    }
+
+    /// Open sockets of `pid` with their traffic so far
+    pub fn list_sockets(&self, pid: Pid) -> Vec<SocketInfo> {
+        self.syscall_handler.lock().unwrap().list_sockets(pid)
+    }
+
+    /// Close a guest's socket from outside the guest
+    pub fn disconnect_socket(&self, pid: Pid, fd: i32) -> Result<()> {
+        self.syscall_handler
+            .lock()
+            .unwrap()
+            .disconnect_socket(pid, fd)
+    }
+
+    /// Port mappings of every process, by PID then guest port
+    pub fn list_port_mappings(&self) -> Vec<(Pid, PortMapping)> {
+        let namespaces = self.network_namespaces.lock().unwrap();
+        let mut ports: Vec<(Pid, PortMapping)> = namespaces
+            .iter()
+            .flat_map(|(pid, ns)| ns.list_port_mappings().into_iter().map(|m| (*pid, m)))
+            .collect();
+        ports.sort_by_key(|(pid, m)| (*pid, m.guest_port));
+        ports
+    }
+
     /// Get network statistics for all processes
     pub fn get_network_stats(
         &self,
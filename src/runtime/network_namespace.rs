@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::net::SocketAddr;
+use std::net::{SocketAddr, TcpListener, UdpSocket};
 use std::sync::{Arc, RwLock};
 
 use crate::runtime::microkernel::Pid;
@@ -10,7 +10,7 @@ pub type SocketFd = i32;
 pub type HostPort = u16;
 pub type GuestPort = u16;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SocketProtocol {
     Tcp,
     Udp,
@@ -23,6 +23,78 @@ pub struct PortMapping {
     pub host_port: HostPort,
     pub protocol: SocketProtocol,
     pub created_at: std::time::SystemTime,
+    pub status: PortStatus,
+}
+
+/// Whether a mapping got the host port that was asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PortStatus {
+    Active,
+    /// `requested` was taken, by another guest process (`held_by`) or by
+    /// something else on the host, so an alternate was allocated
+    Remapped {
+        requested: HostPort,
+        held_by: Option<Pid>,
+    },
+}
+
+/// Host ports claimed by every namespace sharing the table, so two guest
+/// processes never get the same host port
+#[derive(Debug, Clone, Default)]
+pub struct HostPortTable {
+    claims: Arc<RwLock<HashMap<(HostPort, SocketProtocol), Pid>>>,
+    /// Also try binding a port before handing it out
+    probe_host: bool,
+}
+
+impl HostPortTable {
+    /// A table that skips ports other programs on the host are using
+    pub fn probing() -> Self {
+        Self {
+            probe_host: true,
+            ..Self::default()
+        }
+    }
+
+    /// The guest process holding `port`, if any
+    #[cfg(test)]
+    fn holder(&self, port: HostPort, protocol: SocketProtocol) -> Option<Pid> {
+        self.claims.read().unwrap().get(&(port, protocol)).copied()
+    }
+
+    /// Claim `port` for `pid`. On failure, the guest process holding it, or
+    /// `None` when it's in use outside wasmrun.
+    fn try_claim(
+        &self,
+        port: HostPort,
+        protocol: SocketProtocol,
+        pid: Pid,
+    ) -> std::result::Result<(), Option<Pid>> {
+        let mut claims = self.claims.write().unwrap();
+        if let Some(holder) = claims.get(&(port, protocol)) {
+            return Err(Some(*holder));
+        }
+        if self.probe_host && !host_port_free(port, protocol) {
+            return Err(None);
+        }
+        claims.insert((port, protocol), pid);
+        Ok(())
+    }
+
+    fn release(&self, port: HostPort, protocol: SocketProtocol, pid: Pid) {
+        let mut claims = self.claims.write().unwrap();
+        if claims.get(&(port, protocol)) == Some(&pid) {
+            claims.remove(&(port, protocol));
+        }
+    }
+}
+
+fn host_port_free(port: HostPort, protocol: SocketProtocol) -> bool {
+    match protocol {
+        SocketProtocol::Tcp => TcpListener::bind(("127.0.0.1", port)).is_ok(),
+        SocketProtocol::Udp => UdpSocket::bind(("127.0.0.1", port)).is_ok(),
+    }
 }
 
 #[allow(dead_code)]
@@ -51,11 +123,18 @@ pub struct NetworkNamespace {
     connections: Arc<RwLock<HashMap<SocketFd, ConnectionInfo>>>,
     next_host_port: Arc<RwLock<u16>>,
     allocated_host_ports: Arc<RwLock<HashSet<u16>>>,
+    host_ports: HostPortTable,
 }
 
 #[allow(dead_code)]
 impl NetworkNamespace {
+    /// A namespace with its own host port table, for use outside a kernel
     pub fn new(pid: Pid) -> Self {
+        Self::with_host_ports(pid, HostPortTable::default())
+    }
+
+    /// A namespace whose host ports are checked against `host_ports`
+    pub fn with_host_ports(pid: Pid, host_ports: HostPortTable) -> Self {
         let base_port = Self::calculate_base_port(pid);
 
         Self {
@@ -65,6 +144,7 @@ impl NetworkNamespace {
             connections: Arc::new(RwLock::new(HashMap::new())),
             next_host_port: Arc::new(RwLock::new(base_port)),
             allocated_host_ports: Arc::new(RwLock::new(HashSet::new())),
+            host_ports,
         }
     }
 
@@ -75,6 +155,18 @@ impl NetworkNamespace {
     }
 
     pub fn allocate_port(&self, guest_port: GuestPort, protocol: SocketProtocol) -> Result<u16> {
+        self.allocate_port_preferring(guest_port, protocol, None)
+            .map(|mapping| mapping.host_port)
+    }
+
+    /// Map `guest_port`, on `preferred` when it's free and otherwise on the
+    /// next free port in this process's range
+    pub fn allocate_port_preferring(
+        &self,
+        guest_port: GuestPort,
+        protocol: SocketProtocol,
+        preferred: Option<HostPort>,
+    ) -> Result<PortMapping> {
         let mut mappings = self.port_mappings.write().unwrap();
 
         if mappings.contains_key(&guest_port) {
@@ -84,6 +176,30 @@ impl NetworkNamespace {
         let mut next_port = self.next_host_port.write().unwrap();
         let mut allocated = self.allocated_host_ports.write().unwrap();
 
+        let mut status = PortStatus::Active;
+        if let Some(requested) = preferred {
+            let claimed = if allocated.contains(&requested) {
+                Err(Some(self.pid))
+            } else {
+                self.host_ports.try_claim(requested, protocol, self.pid)
+            };
+            match claimed {
+                Ok(()) => {
+                    allocated.insert(requested);
+                    let mapping = PortMapping {
+                        guest_port,
+                        host_port: requested,
+                        protocol,
+                        created_at: std::time::SystemTime::now(),
+                        status,
+                    };
+                    mappings.insert(guest_port, mapping.clone());
+                    return Ok(mapping);
+                }
+                Err(held_by) => status = PortStatus::Remapped { requested, held_by },
+            }
+        }
+
         let start = *next_port;
         let host_port = loop {
             let candidate = *next_port;
@@ -93,7 +209,12 @@ impl NetworkNamespace {
                 candidate + 1
             };
 
-            if !allocated.contains(&candidate) {
+            if !allocated.contains(&candidate)
+                && self
+                    .host_ports
+                    .try_claim(candidate, protocol, self.pid)
+                    .is_ok()
+            {
                 break candidate;
             }
 
@@ -114,10 +235,11 @@ impl NetworkNamespace {
             host_port,
             protocol,
             created_at: std::time::SystemTime::now(),
+            status,
         };
 
-        mappings.insert(guest_port, mapping);
-        Ok(host_port)
+        mappings.insert(guest_port, mapping.clone());
+        Ok(mapping)
     }
 
     pub fn deallocate_port(&self, guest_port: GuestPort) -> Result<()> {
@@ -127,6 +249,8 @@ impl NetworkNamespace {
             Some(mapping) => {
                 let mut allocated = self.allocated_host_ports.write().unwrap();
                 allocated.remove(&mapping.host_port);
+                self.host_ports
+                    .release(mapping.host_port, mapping.protocol, self.pid);
                 Ok(())
             }
             None => anyhow::bail!("Port {guest_port} not allocated"),
@@ -225,6 +349,17 @@ impl NetworkNamespace {
     }
 }
 
+impl Drop for NetworkNamespace {
+    fn drop(&mut self) {
+        if let Ok(mappings) = self.port_mappings.read() {
+            for mapping in mappings.values() {
+                self.host_ports
+                    .release(mapping.host_port, mapping.protocol, self.pid);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkStats {
     pub pid: Pid,
@@ -337,6 +472,51 @@ mod tests {
         assert_eq!(p_reuse, base + 5); // the one we deallocated (offset 5 from base)
     }
 
+    #[test]
+    fn test_shared_host_ports_remap_conflicts() {
+        let table = HostPortTable::default();
+        let first = NetworkNamespace::with_host_ports(1, table.clone());
+        let second = NetworkNamespace::with_host_ports(2, table.clone());
+
+        let mapping = first
+            .allocate_port_preferring(8080, SocketProtocol::Tcp, Some(18080))
+            .unwrap();
+        assert_eq!(mapping.host_port, 18080);
+        assert_eq!(mapping.status, PortStatus::Active);
+
+        // The same request from another process gets an alternate
+        let mapping = second
+            .allocate_port_preferring(8080, SocketProtocol::Tcp, Some(18080))
+            .unwrap();
+        assert_eq!(mapping.host_port, 12000);
+        assert_eq!(
+            mapping.status,
+            PortStatus::Remapped {
+                requested: 18080,
+                held_by: Some(1)
+            }
+        );
+        // UDP is a separate port space
+        let mapping = second
+            .allocate_port_preferring(8081, SocketProtocol::Udp, Some(18080))
+            .unwrap();
+        assert_eq!(mapping.status, PortStatus::Active);
+
+        // Ranges that overlap skip ports another process holds
+        let overlapping = NetworkNamespace::with_host_ports(11108, table.clone());
+        assert_eq!(overlapping.base_port, first.base_port);
+        first.allocate_port(80, SocketProtocol::Tcp).unwrap();
+        assert_eq!(
+            overlapping.allocate_port(80, SocketProtocol::Tcp).unwrap(),
+            first.base_port + 1
+        );
+
+        // Dropping a namespace frees its ports
+        drop(first);
+        assert_eq!(table.holder(18080, SocketProtocol::Tcp), None);
+        assert_eq!(table.holder(12000, SocketProtocol::Tcp), Some(2));
+    }
+
     #[test]
     fn test_connection_tracking() {
         let ns = NetworkNamespace::new(1);
@@ -0,0 +1,438 @@
+//! Login, sessions and an audit trail for the OS mode server
+//!
+//! Off unless `[auth] enabled = true` is set in `wasmrun.toml`. Users sign in
+//! with a password: the admin's comes from an environment variable so it
+//! never sits in the project, and extra users can be listed with salted
+//! Argon2 hashes of theirs (`wasmrun os --hash-password`). A login yields a
+//! session token, sent back as an `HttpOnly` cookie for the browser or as a
+//! bearer token for scripts, plus a CSRF token that cookie-authenticated
+//! `POST`/`DELETE` requests must echo in `X-CSRF-Token`.
+//!
+//! Every state-changing request is recorded in the audit log, with or
+//! without auth, in memory for `/api/auth/audit` and as JSON lines in
+//! `audit.log` beside the process table.
+
+use anyhow::Result;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::project::AuthConfig;
+
+pub const SESSION_COOKIE: &str = "wasmrun_session";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+pub const ADMIN_USER: &str = "admin";
+
+/// Failed logins from one address before it's locked out
+const MAX_FAILED_LOGINS: u32 = 5;
+const LOCKOUT: Duration = Duration::from_secs(60);
+/// Live sessions kept before the oldest is signed out
+const MAX_SESSIONS: usize = 256;
+/// Audit entries kept in memory
+const AUDIT_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub user: String,
+    pub csrf_token: String,
+    expires: Instant,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LoginError {
+    InvalidCredentials,
+    /// Too many failures from this address; try again after this long
+    LockedOut(Duration),
+    /// The OS couldn't supply random bytes for the session tokens
+    NoRandomness(String),
+}
+
+impl std::fmt::Display for LoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoginError::InvalidCredentials => write!(f, "Invalid username or password"),
+            LoginError::LockedOut(wait) => write!(
+                f,
+                "Too many failed logins; try again in {} seconds",
+                wait.as_secs().max(1)
+            ),
+            LoginError::NoRandomness(e) => write!(f, "Can't create a session token: {e}"),
+        }
+    }
+}
+
+pub struct Authenticator {
+    /// User name to Argon2 password hash, in PHC string form
+    users: HashMap<String, String>,
+    session_ttl: Duration,
+    sessions: Mutex<HashMap<String, Session>>,
+    failures: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl Authenticator {
+    /// `None` when auth is disabled. Enabled without any password is an
+    /// error rather than a server anyone can use.
+    pub fn from_config(config: &AuthConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let mut users = HashMap::new();
+        if let Ok(password) = std::env::var(&config.password_env) {
+            if !password.is_empty() {
+                users.insert(ADMIN_USER.to_string(), hash_password(&password)?);
+            }
+        }
+        for (name, hash) in &config.users {
+            let is_argon2 = PasswordHash::new(hash)
+                .is_ok_and(|parsed| parsed.algorithm.as_str().starts_with("argon2"));
+            if !is_argon2 {
+                anyhow::bail!(
+                    "[auth.users] {name}: expected an Argon2 hash, as printed by `wasmrun os --hash-password`"
+                );
+            }
+            users.insert(name.clone(), hash.clone());
+        }
+        if users.is_empty() {
+            anyhow::bail!(
+                "Auth is enabled but no password is set: export {} or add [auth.users]",
+                config.password_env
+            );
+        }
+        Ok(Some(Self {
+            users,
+            session_ttl: Duration::from_secs(config.session_hours.max(1) * 3600),
+            sessions: Mutex::new(HashMap::new()),
+            failures: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Check a password and open a session, returning its token. `user`
+    /// defaults to the admin.
+    pub fn login(
+        &self,
+        user: Option<&str>,
+        password: &str,
+        ip: Option<IpAddr>,
+    ) -> std::result::Result<(String, Session), LoginError> {
+        {
+            let mut failures = self.failures.lock().unwrap();
+            failures.retain(|_, (since, _)| since.elapsed() < LOCKOUT);
+            if let Some((since, count)) = ip.and_then(|ip| failures.get(&ip)) {
+                if *count >= MAX_FAILED_LOGINS {
+                    return Err(LoginError::LockedOut(
+                        LOCKOUT.saturating_sub(since.elapsed()),
+                    ));
+                }
+            }
+        }
+
+        let user = user.unwrap_or(ADMIN_USER);
+        // Verify either way so unknown users take as long to reject. The
+        // hash is slow, so other logins mustn't wait on the failures lock.
+        let expected = match self.users.get(user) {
+            Some(hash) => hash.as_str(),
+            None => unknown_user_hash(),
+        };
+        let matches = verify_password(password, expected) && self.users.contains_key(user);
+
+        if let Some(ip) = ip {
+            let mut failures = self.failures.lock().unwrap();
+            if matches {
+                failures.remove(&ip);
+            } else {
+                failures.entry(ip).or_insert((Instant::now(), 0)).1 += 1;
+            }
+        }
+        if !matches {
+            return Err(LoginError::InvalidCredentials);
+        }
+
+        let token = random_token().map_err(LoginError::NoRandomness)?;
+        let session = Session {
+            user: user.to_string(),
+            csrf_token: random_token().map_err(LoginError::NoRandomness)?,
+            expires: Instant::now() + self.session_ttl,
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.expires > Instant::now());
+        if sessions.len() >= MAX_SESSIONS {
+            if let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, s)| s.expires)
+                .map(|(t, _)| t.clone())
+            {
+                sessions.remove(&oldest);
+            }
+        }
+        sessions.insert(token.clone(), session.clone());
+        Ok((token, session))
+    }
+
+    /// The live session for `token`, if any
+    pub fn session(&self, token: &str) -> Option<Session> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get(token) {
+            Some(session) if session.expires > Instant::now() => Some(session.clone()),
+            Some(_) => {
+                sessions.remove(token);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn logout(&self, token: &str) {
+        self.sessions.lock().unwrap().remove(token);
+    }
+
+    pub fn session_count(&self) -> usize {
+        let now = Instant::now();
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|s| s.expires > now)
+            .count()
+    }
+
+    /// `Set-Cookie` value carrying `token`
+    pub fn session_cookie(&self, token: &str) -> String {
+        format!(
+            "{SESSION_COOKIE}={token}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}",
+            self.session_ttl.as_secs()
+        )
+    }
+}
+
+/// `Set-Cookie` value that clears the session cookie
+pub fn expired_cookie() -> String {
+    format!("{SESSION_COOKIE}=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0")
+}
+
+/// The session token in a `Cookie` header, if present
+pub fn token_from_cookie(cookie_header: &str) -> Option<&str> {
+    cookie_header.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        (name == SESSION_COOKIE && !value.is_empty()).then_some(value)
+    })
+}
+
+/// One recorded action
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    /// Signed-in user; absent when auth is off
+    pub user: Option<String>,
+    pub ip: Option<IpAddr>,
+    /// `METHOD /path`, or `login`, `login_failed`, `logout`
+    pub action: String,
+}
+
+pub struct AuditLog {
+    entries: Mutex<VecDeque<AuditEntry>>,
+    file: Option<PathBuf>,
+}
+
+impl AuditLog {
+    /// Keep entries in memory and, when `file` is given, append them to it
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            file,
+        }
+    }
+
+    pub fn record(&self, user: Option<&str>, ip: Option<IpAddr>, action: impl Into<String>) {
+        let entry = AuditEntry {
+            time: Utc::now(),
+            user: user.map(str::to_string),
+            ip,
+            action: action.into(),
+        };
+        if let Some(path) = &self.file {
+            let appended = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| {
+                    let line = serde_json::to_string(&entry).unwrap_or_default();
+                    writeln!(file, "{line}")
+                });
+            if let Err(e) = appended {
+                eprintln!("⚠️ Failed to write audit log {}: {e}", path.display());
+            }
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == AUDIT_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// The newest `limit` entries, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<AuditEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .skip(entries.len().saturating_sub(limit))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Argon2id hash of `password` with a fresh random salt, as a PHC string
+/// such as `$argon2id$v=19$m=19456,t=2,p=1$...`
+pub fn hash_password(password: &str) -> Result<String> {
+    hash_with(password, &Argon2::default())
+}
+
+fn hash_with(password: &str, argon2: &Argon2) -> Result<String> {
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt)
+        .map_err(|e| anyhow::anyhow!("Can't generate a password salt: {e}"))?;
+    let salt = SaltString::encode_b64(&salt)
+        .map_err(|e| anyhow::anyhow!("Can't encode the password salt: {e}"))?;
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow::anyhow!("Can't hash the password: {e}"))
+}
+
+/// Whether `password` matches a PHC `hash`, using the parameters it records
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|parsed| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok()
+    })
+}
+
+/// A hash no password is checked against successfully in practice, used so
+/// that rejecting an unknown user costs a full verification
+fn unknown_user_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password("").unwrap_or_default())
+}
+
+/// 32 bytes from the OS random number generator, as hex. Fails rather than
+/// falling back to anything guessable.
+fn random_token() -> std::result::Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Cheap parameters so tests don't spend seconds hashing; verification
+    /// reads them back from the hash
+    fn quick_hash(password: &str) -> String {
+        let params = argon2::Params::new(8, 1, 1, None).unwrap();
+        let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+        hash_with(password, &argon2).unwrap()
+    }
+
+    fn config_with_user(name: &str, password: &str) -> AuthConfig {
+        AuthConfig {
+            enabled: true,
+            password_env: "WASMRUN_TEST_UNSET_PASSWORD".to_string(),
+            users: BTreeMap::from([(name.to_string(), quick_hash(password))]),
+            ..AuthConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_login_sessions_and_lockout() {
+        assert!(Authenticator::from_config(&AuthConfig::default())
+            .unwrap()
+            .is_none());
+        let mut no_password = AuthConfig {
+            enabled: true,
+            password_env: "WASMRUN_TEST_UNSET_PASSWORD".to_string(),
+            ..AuthConfig::default()
+        };
+        assert!(Authenticator::from_config(&no_password).is_err());
+        no_password.users.insert("ops".into(), "not-a-hash".into());
+        assert!(Authenticator::from_config(&no_password).is_err());
+        // Unsalted SHA-256 hex isn't accepted any more
+        no_password.users.insert(
+            "ops".into(),
+            "f52fbd32b2b3b86ff88ef6c490628285f482af15ddcb29541f94bcf526a3f6c7".into(),
+        );
+        assert!(Authenticator::from_config(&no_password).is_err());
+
+        let auth = Authenticator::from_config(&config_with_user("ops", "hunter2"))
+            .unwrap()
+            .unwrap();
+        let ip: IpAddr = [192, 0, 2, 1].into();
+        assert_eq!(
+            auth.login(Some("ops"), "wrong", Some(ip)).unwrap_err(),
+            LoginError::InvalidCredentials
+        );
+        assert!(auth.login(None, "hunter2", Some(ip)).is_err());
+
+        let (token, session) = auth.login(Some("ops"), "hunter2", Some(ip)).unwrap();
+        assert_eq!(session.user, "ops");
+        assert_eq!(token.len(), 64);
+        assert_ne!(session.csrf_token, token);
+        assert_eq!(auth.session(&token).unwrap().csrf_token, session.csrf_token);
+        assert_eq!(auth.session_count(), 1);
+
+        let cookie = format!("theme=dark; {}", auth.session_cookie(&token));
+        assert_eq!(token_from_cookie(&cookie), Some(token.as_str()));
+        assert_eq!(token_from_cookie(&expired_cookie()), None);
+
+        auth.logout(&token);
+        assert!(auth.session(&token).is_none());
+
+        for _ in 0..MAX_FAILED_LOGINS {
+            let _ = auth.login(Some("ops"), "wrong", Some(ip));
+        }
+        assert!(matches!(
+            auth.login(Some("ops"), "hunter2", Some(ip)),
+            Err(LoginError::LockedOut(_))
+        ));
+        // Other addresses aren't affected
+        assert!(auth
+            .login(Some("ops"), "hunter2", Some([192, 0, 2, 2].into()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_password_hashes_are_salted() {
+        let first = quick_hash("hunter2");
+        let second = quick_hash("hunter2");
+        assert_ne!(first, second);
+        assert!(first.starts_with("$argon2id$"));
+        assert!(verify_password("hunter2", &first));
+        assert!(verify_password("hunter2", &second));
+        assert!(!verify_password("hunter3", &first));
+        assert!(!verify_password("hunter2", "garbage"));
+    }
+
+    #[test]
+    fn test_audit_log_keeps_recent_entries_and_appends_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let audit = AuditLog::new(Some(path.clone()));
+        audit.record(Some("ops"), None, "login");
+        audit.record(Some("ops"), None, "POST /api/kernel/restart");
+
+        let recent = audit.recent(1);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].action, "POST /api/kernel/restart");
+        let lines = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.contains("\"user\":\"ops\""));
+    }
+}
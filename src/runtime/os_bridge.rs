@@ -0,0 +1,385 @@
+//! Getting data out of an OS mode session: the host clipboard and VFS
+//! downloads
+//!
+//! The clipboard is the one on the machine running `wasmrun os`, reached
+//! through whichever copy tool the platform has. Downloads of a directory are
+//! packed into a `.tar.gz` or `.zip` on the fly, walking the VFS so mounts
+//! and per-process views are honoured. Tarballs can also go the other way,
+//! unpacked into the VFS in one request.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::Component;
+use std::process::{Command, Stdio};
+
+use crate::runtime::wasi_fs::WasiFilesystem;
+
+/// Largest directory, in bytes of file content, packed for a download
+pub const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Copy tools to try, in order, as `(program, args)`
+fn clipboard_commands(wayland: bool) -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(target_os = "windows") {
+        vec![("clip", &[])]
+    } else {
+        let mut commands: Vec<(&str, &[&str])> = vec![
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ];
+        if wayland {
+            commands.insert(0, ("wl-copy", &[]));
+        }
+        commands
+    }
+}
+
+/// Put `text` on the host clipboard and return the tool that took it
+pub fn copy_to_clipboard(text: &str) -> Result<&'static str> {
+    let commands = clipboard_commands(std::env::var_os("WAYLAND_DISPLAY").is_some());
+    for &(program, args) in &commands {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .with_context(|| format!("Failed to write to {program}"))?;
+        }
+        if child.wait().is_ok_and(|status| status.success()) {
+            return Ok(program);
+        }
+    }
+    let tried: Vec<&str> = commands.iter().map(|(program, _)| *program).collect();
+    anyhow::bail!(
+        "No clipboard tool found on the host (tried {})",
+        tried.join(", ")
+    )
+}
+
+/// Files under the VFS directory `dir`, as `(vfs path, archive path)` pairs
+/// with archive paths rooted at the directory's own name
+fn collect_files(fs: &WasiFilesystem, dir: &str) -> Result<Vec<(String, String)>> {
+    let dir = dir.trim_end_matches('/');
+    let root_name = dir
+        .rsplit('/')
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("root");
+
+    let mut files = Vec::new();
+    let mut total = 0u64;
+    let mut pending = vec![(dir.to_string(), root_name.to_string())];
+
+    while let Some((vfs_path, archive_path)) = pending.pop() {
+        let mut entries = fs.path_readdir(if vfs_path.is_empty() { "/" } else { &vfs_path })?;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        for entry in entries {
+            let child_vfs = format!("{vfs_path}/{}", entry.name);
+            let child_archive = format!("{archive_path}/{}", entry.name);
+            if entry.is_dir {
+                pending.push((child_vfs, child_archive));
+                continue;
+            }
+            total += entry.size;
+            if total > MAX_ARCHIVE_BYTES {
+                anyhow::bail!(
+                    "{dir} holds more than {} MB; download its files individually",
+                    MAX_ARCHIVE_BYTES / (1024 * 1024)
+                );
+            }
+            files.push((child_vfs, child_archive));
+        }
+    }
+    Ok(files)
+}
+
+/// Pack the VFS directory at `dir` into a gzipped tarball
+pub fn archive_directory(fs: &WasiFilesystem, dir: &str) -> Result<Vec<u8>> {
+    let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(gz);
+
+    for (vfs_path, archive_path) in collect_files(fs, dir)? {
+        let data = fs.read_file(&vfs_path)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        );
+        builder.append_data(&mut header, &archive_path, data.as_slice())?;
+    }
+
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Pack the VFS directory at `dir` into a deflated zip. Entries carry the
+/// DOS epoch as their timestamp, so exports of the same tree are identical.
+pub fn zip_directory(fs: &WasiFilesystem, dir: &str) -> Result<Vec<u8>> {
+    let files = collect_files(fs, dir)?;
+    if files.len() > u16::MAX as usize {
+        anyhow::bail!("{dir} holds more than {} files", u16::MAX);
+    }
+
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (vfs_path, archive_path) in &files {
+        let data = fs.read_file(vfs_path)?;
+        let mut crc = flate2::Crc::new();
+        crc.update(&data);
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data)?;
+        let compressed = encoder.finish()?;
+        let name = archive_path.as_bytes();
+        let offset = out.len() as u32;
+
+        // Version 2.0, UTF-8 names, deflate, 1980-01-01 00:00
+        let common = |buf: &mut Vec<u8>| {
+            buf.extend_from_slice(&20u16.to_le_bytes());
+            buf.extend_from_slice(&0x0800u16.to_le_bytes());
+            buf.extend_from_slice(&8u16.to_le_bytes());
+            buf.extend_from_slice(&0u16.to_le_bytes());
+            buf.extend_from_slice(&0x0021u16.to_le_bytes());
+            buf.extend_from_slice(&crc.sum().to_le_bytes());
+            buf.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&0u16.to_le_bytes());
+        };
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        common(&mut out);
+        out.extend_from_slice(name);
+        out.extend_from_slice(&compressed);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        common(&mut central);
+        central.extend_from_slice(&[0; 8]); // comment, disk, internal attrs
+        central.extend_from_slice(&(0o100644u32 << 16).to_le_bytes());
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name);
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // this disk, central directory disk
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    Ok(out)
+}
+
+/// What [`extract_archive`] wrote
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExtractSummary {
+    pub files: usize,
+    pub directories: usize,
+    pub bytes: u64,
+    /// Links, devices, and entries with absolute or `..` paths
+    pub skipped: usize,
+}
+
+/// Unpack a tarball, gzipped or not, into the VFS directory `dest`. Writes
+/// go through the VFS, so read-only mounts, quotas and watches apply.
+pub fn extract_archive(fs: &WasiFilesystem, dest: &str, archive: &[u8]) -> Result<ExtractSummary> {
+    let dest = dest.trim_end_matches('/');
+    let reader: Box<dyn Read + '_> = if archive.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::read::GzDecoder::new(archive))
+    } else {
+        Box::new(archive)
+    };
+    let mut tar = tar::Archive::new(reader);
+    let mut summary = ExtractSummary::default();
+    // The VFS resolves a path through its parent, so directories are made
+    // one level at a time
+    let mut created = std::collections::HashSet::new();
+    let mut create_dirs = |parts: &[String]| -> Result<()> {
+        for depth in 1..=parts.len() {
+            let dir = format!("{dest}/{}", parts[..depth].join("/"));
+            if !created.contains(&dir) {
+                fs.path_create_directory(&dir)?;
+                created.insert(dir);
+            }
+        }
+        Ok(())
+    };
+
+    fs.path_create_directory(if dest.is_empty() { "/" } else { dest })?;
+    for entry in tar.entries().context("Not a tar archive")? {
+        let mut entry = entry.context("Corrupt tar archive")?;
+        let kind = entry.header().entry_type();
+        let path = entry.path().context("Corrupt tar archive")?.into_owned();
+        let mut parts = Vec::new();
+        let mut safe = true;
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+                Component::CurDir => {}
+                _ => safe = false,
+            }
+        }
+        if !safe || !matches!(kind, tar::EntryType::Regular | tar::EntryType::Directory) {
+            summary.skipped += 1;
+            continue;
+        }
+        if parts.is_empty() {
+            continue;
+        }
+        let target = format!("{dest}/{}", parts.join("/"));
+
+        if kind == tar::EntryType::Directory {
+            create_dirs(&parts)?;
+            summary.directories += 1;
+            continue;
+        }
+        create_dirs(&parts[..parts.len() - 1])?;
+        summary.bytes += entry.size();
+        if summary.bytes > MAX_ARCHIVE_BYTES {
+            anyhow::bail!(
+                "Archive unpacks to more than {} MB",
+                MAX_ARCHIVE_BYTES / (1024 * 1024)
+            );
+        }
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut data)
+            .with_context(|| format!("Corrupt tar archive at {}", path.display()))?;
+        fs.write_file(&target, &data)?;
+        summary.files += 1;
+    }
+    Ok(summary)
+}
+
+/// `Content-Disposition` value offering `name` as a download
+pub fn attachment_header(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| {
+            if c == '"' || c == '\\' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("attachment; filename=\"{safe}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_archive_directory_walks_vfs() {
+        let host = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(host.path().join("dist/assets")).unwrap();
+        std::fs::write(host.path().join("dist/index.html"), b"<h1>hi</h1>").unwrap();
+        std::fs::write(host.path().join("dist/assets/app.js"), b"run()").unwrap();
+        let fs = WasiFilesystem::new();
+        fs.mount("/app", host.path()).unwrap();
+
+        let archive = archive_directory(&fs, "/app/dist/").unwrap();
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive.as_slice()));
+        let mut files: Vec<(String, String)> = tar
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().to_string();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (path, content)
+            })
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                ("dist/assets/app.js".to_string(), "run()".to_string()),
+                ("dist/index.html".to_string(), "<h1>hi</h1>".to_string()),
+            ]
+        );
+        assert!(archive_directory(&fs, "/app/missing").is_err());
+    }
+
+    #[test]
+    fn test_extract_archive_and_zip_round_trip() {
+        let host = tempfile::tempdir().unwrap();
+        let fs = WasiFilesystem::new();
+        fs.mount("/app", host.path()).unwrap();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut add = |path: &str, data: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            // `append_data` refuses `..`, so write the name into the header
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_cksum();
+            builder.append(&header, data).unwrap();
+        };
+        add("./site/index.html", b"<h1>hi</h1>");
+        add("site/js/app.js", b"run()");
+        add("../escape.txt", b"nope");
+        let tarball = builder.into_inner().unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(&tarball).unwrap();
+
+        let summary = extract_archive(&fs, "/app/seed", &gz.finish().unwrap()).unwrap();
+        assert_eq!((summary.files, summary.bytes, summary.skipped), (2, 16, 1));
+        assert_eq!(fs.read_file("/app/seed/site/js/app.js").unwrap(), b"run()");
+        assert!(!host.path().join("escape.txt").exists());
+        assert!(extract_archive(&fs, "/app/seed", b"not a tarball").is_err());
+
+        // Unpacked without a gzip layer too
+        extract_archive(&fs, "/app/plain", &tarball).unwrap();
+        assert!(fs.path_exists("/app/plain/site/index.html"));
+
+        let zip = zip_directory(&fs, "/app/seed/site").unwrap();
+        assert_eq!(&zip[..4], b"PK\x03\x04");
+        assert_eq!(&zip[zip.len() - 22..zip.len() - 18], b"PK\x05\x06");
+        assert_eq!(
+            u16::from_le_bytes([zip[zip.len() - 12], zip[zip.len() - 11]]),
+            2
+        );
+        // The first entry inflates back to the file it came from
+        let name_len = u16::from_le_bytes([zip[26], zip[27]]) as usize;
+        let compressed_len = u32::from_le_bytes(zip[18..22].try_into().unwrap()) as usize;
+        assert_eq!(&zip[30..30 + name_len], b"site/index.html");
+        let start = 30 + name_len;
+        let mut inflated = Vec::new();
+        flate2::read::DeflateDecoder::new(&zip[start..start + compressed_len])
+            .read_to_end(&mut inflated)
+            .unwrap();
+        assert_eq!(inflated, b"<h1>hi</h1>");
+    }
+
+    #[test]
+    fn test_attachment_header_and_clipboard_order() {
+        assert_eq!(
+            attachment_header("a\"b.txt"),
+            "attachment; filename=\"a_b.txt\""
+        );
+        if cfg!(target_os = "linux") {
+            assert_eq!(clipboard_commands(true)[0].0, "wl-copy");
+            assert_eq!(clipboard_commands(false)[0].0, "xclip");
+        }
+    }
+}
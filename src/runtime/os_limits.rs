@@ -0,0 +1,141 @@
+//! Per-IP rate limiting and request body caps for the OS mode API
+//!
+//! Only state-changing requests (`POST`/`DELETE` under `/api/`) count against
+//! the rate limit; the UI polls the read-only endpoints every few seconds and
+//! those are cheap to serve.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Clients tracked before stale windows are swept
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+/// Fixed one-minute request window per client IP. `0` disables the limit.
+pub struct RateLimiter {
+    per_minute: u32,
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count one request from `ip`. On rejection, returns how long until the
+    /// client's window resets.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        self.check_at(ip, Instant::now())
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() >= MAX_TRACKED_CLIENTS {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        }
+
+        let (start, count) = windows.entry(ip).or_insert((now, 0));
+        if now.duration_since(*start) >= WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.per_minute {
+            return Err(WINDOW.saturating_sub(now.duration_since(*start)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum BodyError {
+    TooLarge(u64),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for BodyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyError::TooLarge(limit) => {
+                write!(f, "Request body exceeds the {limit} byte limit")
+            }
+            BodyError::Io(e) => write!(f, "Failed to read request body: {e}"),
+        }
+    }
+}
+
+/// Read at most `limit` bytes (`None` for no limit). Catches chunked bodies
+/// that carry no `Content-Length` to check up front.
+pub fn read_body(reader: &mut dyn Read, limit: Option<u64>) -> Result<Vec<u8>, BodyError> {
+    let mut body = Vec::new();
+    match limit {
+        Some(limit) => {
+            reader
+                .take(limit + 1)
+                .read_to_end(&mut body)
+                .map_err(BodyError::Io)?;
+            if body.len() as u64 > limit {
+                return Err(BodyError::TooLarge(limit));
+            }
+        }
+        None => {
+            reader.read_to_end(&mut body).map_err(BodyError::Io)?;
+        }
+    }
+    Ok(body)
+}
+
+/// Body limit in bytes from a megabyte setting where `0` means unlimited
+pub fn body_limit_bytes(max_body_mb: u32) -> Option<u64> {
+    (max_body_mb > 0).then(|| u64::from(max_body_mb) * 1024 * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_is_per_ip_and_resets() {
+        let limiter = RateLimiter::new(2);
+        let a: IpAddr = "192.168.1.10".parse().unwrap();
+        let b: IpAddr = "192.168.1.11".parse().unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(a, now).is_ok());
+        assert!(limiter.check_at(a, now).is_ok());
+        let retry = limiter
+            .check_at(a, now + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(retry, Duration::from_secs(40));
+        assert!(limiter.check_at(b, now).is_ok());
+        assert!(limiter.check_at(a, now + WINDOW).is_ok());
+
+        let unlimited = RateLimiter::new(0);
+        for _ in 0..1000 {
+            assert!(unlimited.check_at(a, now).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_read_body_enforces_limit() {
+        let body = vec![b'x'; 2048];
+        assert_eq!(read_body(&mut &body[..], Some(2048)).unwrap().len(), 2048);
+        assert!(matches!(
+            read_body(&mut &body[..], Some(1024)),
+            Err(BodyError::TooLarge(1024))
+        ));
+        assert_eq!(read_body(&mut &body[..], None).unwrap().len(), 2048);
+        assert_eq!(body_limit_bytes(0), None);
+        assert_eq!(body_limit_bytes(2), Some(2 * 1024 * 1024));
+    }
+}
@@ -0,0 +1,372 @@
+//! Counters behind the OS mode `/metrics` endpoint
+//!
+//! Request and port-forward counters are accumulated here as the server
+//! handles requests; process, memory and network figures are read from the
+//! kernel at scrape time.
+
+use crate::runtime::multilang_kernel::MultiLanguageKernel;
+use crate::server::write_build_metrics;
+use crate::utils::{resident_memory_bytes, PrometheusText};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Routes without path parameters; anything else unmatched is counted as `other`
+const STATIC_ROUTES: &[&str] = &[
+    "/",
+    "/os.js",
+    "/index.css",
+    "/logging.js",
+    "/logs",
+    "/ws",
+    "/metrics",
+    "/api/runtimes",
+    "/api/project/files",
+    "/api/kernel/stats",
+    "/api/git",
+    "/api/kernel/start",
+    "/api/kernel/restart",
+    "/api/fs/stats",
+    "/api/fs/search",
+    "/api/logs",
+    "/api/logs/recent",
+    "/api/logs/stream",
+    "/api/tunnel/start",
+    "/api/tunnel/status",
+    "/api/tunnel/stop",
+    "/api/processes",
+    "/api/supervisor",
+    "/api/supervisor/groups",
+    "/api/volumes",
+    "/api/clipboard",
+    "/api/auth/session",
+    "/api/auth/login",
+    "/api/auth/logout",
+    "/api/auth/audit",
+];
+
+pub struct OsMetrics {
+    started: Instant,
+    /// Keyed by `(method, route, handled ok)`
+    requests: Mutex<BTreeMap<(String, String, bool), u64>>,
+    /// Keyed by `(operation, succeeded)`
+    port_forwards: Mutex<BTreeMap<(&'static str, bool), u64>>,
+    /// Requests turned away by `--read-only` or a rate or body limit, keyed by reason
+    rejections: Mutex<BTreeMap<&'static str, u64>>,
+    project_start: Mutex<Option<Duration>>,
+}
+
+impl Default for OsMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OsMetrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            requests: Mutex::new(BTreeMap::new()),
+            port_forwards: Mutex::new(BTreeMap::new()),
+            rejections: Mutex::new(BTreeMap::new()),
+            project_start: Mutex::new(None),
+        }
+    }
+
+    pub fn record_request(&self, method: &str, path: &str, ok: bool) {
+        *self
+            .requests
+            .lock()
+            .unwrap()
+            .entry((method.to_string(), route_label(path), ok))
+            .or_default() += 1;
+    }
+
+    /// `operation` is `create` or `delete`
+    pub fn record_port_forward(&self, operation: &'static str, ok: bool) {
+        *self
+            .port_forwards
+            .lock()
+            .unwrap()
+            .entry((operation, ok))
+            .or_default() += 1;
+    }
+
+    /// `reason` is `read_only`, `rate` or `body`
+    pub fn record_rejection(&self, reason: &'static str) {
+        *self.rejections.lock().unwrap().entry(reason).or_default() += 1;
+    }
+
+    pub fn record_project_start(&self, duration: Duration) {
+        *self.project_start.lock().unwrap() = Some(duration);
+    }
+
+    /// Prometheus text for `GET /metrics`
+    pub fn render(&self, kernel: &MultiLanguageKernel, tunnel_connected: bool) -> String {
+        let mut text = PrometheusText::new();
+        text.family(
+            "wasmrun_uptime_seconds",
+            "Seconds since the OS mode server started.",
+            "gauge",
+        )
+        .sample(
+            "wasmrun_uptime_seconds",
+            &[],
+            self.started.elapsed().as_secs() as f64,
+        );
+        if let Some(rss) = resident_memory_bytes() {
+            text.family(
+                "wasmrun_resident_memory_bytes",
+                "Resident memory of the wasmrun process.",
+                "gauge",
+            )
+            .sample("wasmrun_resident_memory_bytes", &[], rss as f64);
+        }
+
+        text.family(
+            "wasmrun_http_requests_total",
+            "Requests handled, by method, route and result.",
+            "counter",
+        );
+        for ((method, route, ok), count) in self.requests.lock().unwrap().iter() {
+            text.sample(
+                "wasmrun_http_requests_total",
+                &[
+                    ("method", method),
+                    ("route", route),
+                    ("result", if *ok { "ok" } else { "error" }),
+                ],
+                *count as f64,
+            );
+        }
+        text.family(
+            "wasmrun_http_rejected_requests_total",
+            "Requests refused with 403 (read-only), 429 or 413, by reason.",
+            "counter",
+        );
+        for (reason, count) in self.rejections.lock().unwrap().iter() {
+            text.sample(
+                "wasmrun_http_rejected_requests_total",
+                &[("reason", reason)],
+                *count as f64,
+            );
+        }
+
+        self.write_kernel_metrics(&mut text, kernel);
+
+        text.family(
+            "wasmrun_os_port_forward_operations_total",
+            "Port forward create and delete requests, by result.",
+            "counter",
+        );
+        for ((operation, ok), count) in self.port_forwards.lock().unwrap().iter() {
+            text.sample(
+                "wasmrun_os_port_forward_operations_total",
+                &[
+                    ("operation", operation),
+                    ("result", if *ok { "ok" } else { "error" }),
+                ],
+                *count as f64,
+            );
+        }
+        text.family(
+            "wasmrun_os_tunnel_connected",
+            "1 while the public tunnel is connected.",
+            "gauge",
+        )
+        .sample(
+            "wasmrun_os_tunnel_connected",
+            &[],
+            f64::from(u8::from(tunnel_connected)),
+        );
+
+        if let Some(start) = *self.project_start.lock().unwrap() {
+            text.family(
+                "wasmrun_os_project_start_seconds",
+                "Time the last project start took in the kernel.",
+                "gauge",
+            )
+            .sample("wasmrun_os_project_start_seconds", &[], start.as_secs_f64());
+        }
+        write_build_metrics(&mut text);
+        text.finish()
+    }
+
+    fn write_kernel_metrics(&self, text: &mut PrometheusText, kernel: &MultiLanguageKernel) {
+        let stats = kernel.get_statistics();
+        let processes = kernel.base_kernel().list_processes();
+
+        let mut by_state: BTreeMap<(String, String), u64> = BTreeMap::new();
+        for process in &processes {
+            *by_state
+                .entry((
+                    format!("{:?}", process.state).to_lowercase(),
+                    process.language.clone(),
+                ))
+                .or_default() += 1;
+        }
+        text.family(
+            "wasmrun_os_processes",
+            "Kernel processes, by state and language.",
+            "gauge",
+        );
+        for ((state, language), count) in &by_state {
+            text.sample(
+                "wasmrun_os_processes",
+                &[("state", state), ("language", language)],
+                *count as f64,
+            );
+        }
+
+        text.family(
+            "wasmrun_os_memory_usage_bytes",
+            "Memory used by all kernel processes.",
+            "gauge",
+        )
+        .sample(
+            "wasmrun_os_memory_usage_bytes",
+            &[],
+            stats.total_memory_usage as f64,
+        );
+        text.family(
+            "wasmrun_os_process_memory_bytes",
+            "Memory used by each kernel process.",
+            "gauge",
+        );
+        for process in &processes {
+            text.sample(
+                "wasmrun_os_process_memory_bytes",
+                &[("pid", &process.pid.to_string()), ("name", &process.name)],
+                process.memory_usage as f64,
+            );
+        }
+        text.family(
+            "wasmrun_os_dev_servers",
+            "Language dev servers running inside the kernel.",
+            "gauge",
+        )
+        .sample(
+            "wasmrun_os_dev_servers",
+            &[],
+            stats.active_dev_servers as f64,
+        );
+
+        let mut network: Vec<_> = kernel.get_network_stats().into_values().collect();
+        network.sort_by_key(|n| n.pid);
+        text.family(
+            "wasmrun_os_port_mappings",
+            "Guest ports forwarded to the host, by process.",
+            "gauge",
+        );
+        for n in &network {
+            text.sample(
+                "wasmrun_os_port_mappings",
+                &[("pid", &n.pid.to_string())],
+                n.allocated_ports as f64,
+            );
+        }
+        text.family(
+            "wasmrun_os_connections",
+            "Tracked guest sockets, by process and state.",
+            "gauge",
+        );
+        for n in &network {
+            let pid = n.pid.to_string();
+            let other = n
+                .total_connections
+                .saturating_sub(n.active_connections + n.listening_sockets);
+            for (state, count) in [
+                ("connected", n.active_connections),
+                ("listening", n.listening_sockets),
+                ("other", other),
+            ] {
+                text.sample(
+                    "wasmrun_os_connections",
+                    &[("pid", &pid), ("state", state)],
+                    count as f64,
+                );
+            }
+        }
+    }
+}
+
+/// Collapse a request path to its route so file paths, PIDs and ports don't
+/// each become their own label value
+pub fn route_label(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or("");
+    if STATIC_ROUTES.contains(&path) {
+        return path.to_string();
+    }
+
+    let segments: Vec<&str> = path.split('/').collect();
+    match segments.as_slice() {
+        ["", "app", ..] => "/app/*".to_string(),
+        ["", "assets", ..] => "/assets/*".to_string(),
+        ["", "api", "runtime", _] => "/api/runtime/:language".to_string(),
+        ["", "api", "fs", op @ ("read" | "list" | "write" | "mkdir" | "delete" | "download" | "export"
+        | "extract"), ..] => {
+            format!("/api/fs/{op}/*")
+        }
+        ["", "api", "processes", _] => "/api/processes/:pid".to_string(),
+        ["", "api", "processes", _, "config"] => "/api/processes/:pid/config".to_string(),
+        ["", "api", "processes", _, "restart-policy"] => {
+            "/api/processes/:pid/restart-policy".to_string()
+        }
+        ["", "api", "processes", _, "exit"] => "/api/processes/:pid/exit".to_string(),
+        ["", "api", "supervisor", "groups", _] => "/api/supervisor/groups/:name".to_string(),
+        ["", "api", "processes", _, "ports"] => "/api/processes/:pid/ports".to_string(),
+        ["", "api", "processes", _, "forward"] => "/api/processes/:pid/forward".to_string(),
+        ["", "api", "processes", _, "forward", _] => {
+            "/api/processes/:pid/forward/:port".to_string()
+        }
+        _ => "other".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_label_bounds_cardinality() {
+        assert_eq!(route_label("/api/kernel/stats"), "/api/kernel/stats");
+        assert_eq!(route_label("/api/logs/recent?limit=5"), "/api/logs/recent");
+        assert_eq!(route_label("/api/fs/read/src/main.rs"), "/api/fs/read/*");
+        assert_eq!(
+            route_label("/api/processes/42/forward/8080"),
+            "/api/processes/:pid/forward/:port"
+        );
+        assert_eq!(
+            route_label("/api/processes/7/ports"),
+            "/api/processes/:pid/ports"
+        );
+        assert_eq!(route_label("/api/runtime/python"), "/api/runtime/:language");
+        assert_eq!(route_label("/app/index.html"), "/app/*");
+        assert_eq!(route_label("/wp-login.php"), "other");
+    }
+
+    #[test]
+    fn test_render_includes_requests_and_kernel_state() {
+        let metrics = OsMetrics::new();
+        metrics.record_request("GET", "/api/fs/read/a.txt", true);
+        metrics.record_request("GET", "/api/fs/read/b.txt", true);
+        metrics.record_request("POST", "/api/processes/1/forward", false);
+        metrics.record_port_forward("create", false);
+        metrics.record_rejection("rate");
+        metrics.record_project_start(Duration::from_millis(250));
+
+        let kernel = MultiLanguageKernel::new();
+        let text = metrics.render(&kernel, false);
+        assert!(text.contains(
+            "wasmrun_http_requests_total{method=\"GET\",route=\"/api/fs/read/*\",result=\"ok\"} 2\n"
+        ));
+        assert!(text.contains(
+            "wasmrun_os_port_forward_operations_total{operation=\"create\",result=\"error\"} 1\n"
+        ));
+        assert!(text.contains("wasmrun_http_rejected_requests_total{reason=\"rate\"} 1\n"));
+        assert!(text.contains("wasmrun_os_memory_usage_bytes 0\n"));
+        assert!(text.contains("wasmrun_os_tunnel_connected 0\n"));
+        assert!(text.contains("wasmrun_os_project_start_seconds 0.25\n"));
+        assert!(text.contains("# TYPE wasmrun_build_duration_seconds summary\n"));
+    }
+}
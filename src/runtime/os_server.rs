@@ -1,13 +1,32 @@
 use crate::error::{Result, WasmrunError};
 use crate::logging::{LogEntry, LogSource, LogTrailSystem};
+use crate::runtime::dev_server::get_content_type;
+use crate::runtime::log_query;
+use crate::runtime::microkernel::{Pid, ProcessState, SyscallInterface};
 use crate::runtime::multilang_kernel::{MultiLanguageKernel, OsRunConfig};
+use crate::runtime::os_auth::{self, AuditLog, Authenticator, LoginError};
+use crate::runtime::os_bridge;
+use crate::runtime::os_limits::{body_limit_bytes, read_body, BodyError, RateLimiter};
+use crate::runtime::os_metrics::OsMetrics;
+use crate::runtime::process_table::{LaunchConfig, ProcessSpec, ProcessTable, RestartPolicy};
+use crate::runtime::procfs;
 use crate::runtime::project_files::ProjectFilesCollector;
 use crate::runtime::runtime_cache::RuntimeCache;
+use crate::runtime::supervisor::{GroupSpec, Supervisor};
+use crate::runtime::tunnel::bore::TunnelStatus;
 use crate::runtime::tunnel::BoreClient;
-use std::collections::HashMap;
+use crate::runtime::vfs_search;
+use crate::runtime::virtual_switch;
+use crate::runtime::wasi_fs::{DirEntry, MountSpec};
+use crate::server::{bind_server, Request};
+use crate::template::engine::{self, Escape, Vars};
+use crate::utils::{GitState, PROMETHEUS_CONTENT_TYPE};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
-use std::sync::{Arc, RwLock};
-use tiny_http::{Header, Method, Request, Response, Server};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Response};
 
 const TEMPLATE_INDEX_HTML: &str = include_str!("../../templates/os/index.html");
 const TEMPLATE_OS_JS: &str = include_str!("../../templates/os/os.js");
@@ -18,7 +37,16 @@ const TEMPLATE_LOGS_HTML: &str = include_str!("../../templates/os/logs.html");
 const ASSET_LOGO_PNG: &[u8] = include_bytes!("../../templates/assets/logo.png");
 const ASSET_LOGO_TEXT_PNG: &[u8] = include_bytes!("../../templates/assets/logo-text.png");
 
+/// How often the supervisor checks on supervised processes
+const SUPERVISOR_TICK: Duration = Duration::from_millis(250);
+
 /// OS Mode server providing the browser-based development interface
+///
+/// Requests are handled concurrently. A handler that needs more than one of
+/// the locks below takes them in this order: `project_pid`, then
+/// `process_table`, then `kernel`, then `supervisor`. Any of them may be
+/// released early, but none is taken while a later one is held.
+/// `tunnel_client` is never held together with another.
 pub struct OsServer {
     kernel: Arc<RwLock<MultiLanguageKernel>>,
     config: OsRunConfig,
@@ -28,6 +56,14 @@ pub struct OsServer {
     tunnel_client: Arc<RwLock<Option<BoreClient>>>,
     runtime_cache: RuntimeCache,
     cors_origin: String,
+    metrics: OsMetrics,
+    rate_limiter: RateLimiter,
+    body_limit: Option<u64>,
+    process_table: Mutex<ProcessTable>,
+    supervisor: Mutex<Supervisor>,
+    /// `None` when `[auth]` is off
+    auth: Option<Authenticator>,
+    audit: AuditLog,
 }
 
 impl OsServer {
@@ -39,6 +75,34 @@ impl OsServer {
             format!("http://127.0.0.1:{}", config.port.unwrap_or(8420))
         };
         let runtime_cache = RuntimeCache::new()?;
+        let rate_limiter = RateLimiter::new(config.rate_limit);
+        let body_limit = body_limit_bytes(config.max_body_mb);
+        let process_table = match ProcessTable::path_for_project(&config.project_path)
+            .and_then(ProcessTable::load)
+        {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("⚠️ Process table unavailable, launched processes won't persist: {e}");
+                ProcessTable::in_memory()
+            }
+        };
+        let auth = Authenticator::from_config(&config.auth)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        let audit = AuditLog::new(
+            process_table
+                .path()
+                .and_then(Path::parent)
+                .map(|dir| dir.join("audit.log")),
+        );
+        // Named volumes live beside the table so they outlast the server
+        if let Some(dir) = process_table.path().and_then(Path::parent) {
+            if let Err(e) = kernel
+                .wasi_filesystem()
+                .set_volume_root(dir.join("volumes"))
+            {
+                eprintln!("⚠️ Volume directory unavailable: {e}");
+            }
+        }
         let mut server = Self {
             kernel: Arc::new(RwLock::new(kernel)),
             config,
@@ -48,6 +112,13 @@ impl OsServer {
             tunnel_client: Arc::new(RwLock::new(None)),
             runtime_cache,
             cors_origin,
+            metrics: OsMetrics::new(),
+            rate_limiter,
+            body_limit,
+            supervisor: Mutex::new(Supervisor::new(process_table.groups().to_vec())),
+            process_table: Mutex::new(process_table),
+            auth,
+            audit,
         };
 
         // Load and process templates
@@ -73,19 +144,18 @@ impl OsServer {
 
         let port_str = self.config.port.unwrap_or(8420).to_string();
 
-        let index_content = TEMPLATE_INDEX_HTML
-            .replace("$PROJECT_NAME$", &project_name)
-            .replace("$LANGUAGE$", language)
-            .replace("$PROJECT_PATH$", &self.config.project_path)
-            .replace("$PORT$", &port_str)
-            .replace(
-                "<!-- @style-placeholder -->",
+        let vars = Vars::new()
+            .text("PROJECT_NAME", project_name)
+            .text("LANGUAGE", language)
+            .text("PROJECT_PATH", &self.config.project_path)
+            .text("PORT", port_str)
+            .text(
+                "style-placeholder",
                 "<link rel=\"stylesheet\" href=\"/index.css\">",
             )
-            .replace(
-                "<!-- @script-placeholder -->",
-                "<script src=\"/os.js\"></script>",
-            );
+            .text("script-placeholder", "<script src=\"/os.js\"></script>");
+        let index_content = engine::render(TEMPLATE_INDEX_HTML, Escape::Html, &vars)?;
+        let logs_content = engine::render(TEMPLATE_LOGS_HTML, Escape::Html, &vars)?;
 
         self.template_cache
             .insert("index.html".to_string(), index_content);
@@ -95,10 +165,8 @@ impl OsServer {
             .insert("index.css".to_string(), TEMPLATE_INDEX_CSS.to_string());
         self.template_cache
             .insert("logging.js".to_string(), TEMPLATE_LOGGING_JS.to_string());
-        self.template_cache.insert(
-            "logs.html".to_string(),
-            TEMPLATE_LOGS_HTML.replace("$PORT$", &port_str),
-        );
+        self.template_cache
+            .insert("logs.html".to_string(), logs_content);
 
         self.log_system.log(LogEntry::info(
             LogSource::Kernel,
@@ -153,27 +221,47 @@ impl OsServer {
 
     /// Start the OS server
     pub fn start(self, port: u16) -> Result<()> {
-        let server = Server::http(format!("127.0.0.1:{port}"))
-            .map_err(|e| WasmrunError::from(format!("Failed to start HTTP server: {e}")))?;
-
-        self.log_system.log(LogEntry::info(
+        let server = Arc::new(self);
+        let handler = Arc::clone(&server);
+        bind_server("127.0.0.1", port, move |request| {
+            handler.serve_request(request)
+        })
+        .map_err(|e| WasmrunError::from(format!("Failed to start HTTP server: {e}")))?;
+
+        let scheme = crate::server::scheme();
+        server.log_system.log(LogEntry::info(
             LogSource::Kernel,
-            format!("OS Mode server listening on http://127.0.0.1:{port}"),
+            format!("OS Mode server listening on {scheme}://127.0.0.1:{port}"),
         ));
-        println!("🌐 OS Mode server listening on http://127.0.0.1:{port}");
+        println!("🌐 OS Mode server listening on {scheme}://127.0.0.1:{port}");
+        if server.config.read_only {
+            println!("🔒 Read-only mode: file writes, project control, port forwards and tunnels are disabled");
+        }
+        if server.auth.is_some() {
+            println!("🔑 Login required: sign in through the browser or POST /api/auth/login");
+        }
 
         // Start the project in the kernel
-        self.start_project()?;
-
-        // Handle HTTP requests
-        for request in server.incoming_requests() {
-            match self.handle_request(request) {
-                Ok(_) => {}
-                Err(e) => eprintln!("Request handling error: {e}"),
-            }
+        server.start_project()?;
+        server.restore_processes();
+
+        // This thread checks on supervised processes for as long as the
+        // front serves requests
+        loop {
+            server.supervise();
+            thread::sleep(SUPERVISOR_TICK);
         }
+    }
 
-        Ok(())
+    /// Handle one request and record it
+    fn serve_request(&self, request: Request) {
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        let result = self.handle_request(request);
+        self.metrics.record_request(&method, &url, result.is_ok());
+        if let Err(e) = result {
+            eprintln!("Request handling error: {e}");
+        }
     }
 
     /// Start the project in the kernel.
@@ -196,18 +284,202 @@ impl OsServer {
         }
     }
 
+    /// Relaunch persisted processes whose restart policy is `on-boot`
+    fn restore_processes(&self) {
+        let (boot, total) = {
+            let table = self.process_table.lock().unwrap();
+            (table.boot_specs(), table.specs().len())
+        };
+        for spec in &boot {
+            match self.launch_process(spec) {
+                Ok(pid) => {
+                    self.log_system.log(
+                        LogEntry::info(
+                            LogSource::Kernel,
+                            format!("Restored process '{}' with PID: {pid}", spec.name),
+                        )
+                        .with_pid(pid),
+                    );
+                    println!("♻️  Restored process '{}' (PID {pid})", spec.name);
+                }
+                Err(e) => {
+                    self.log_system.log(LogEntry::error(
+                        LogSource::Kernel,
+                        format!("Failed to restore process '{}': {e}", spec.name),
+                    ));
+                    eprintln!("⚠️ Failed to restore process '{}': {e}", spec.name);
+                }
+            }
+        }
+        if total > boot.len() {
+            println!(
+                "💤 {} saved process definition(s) left stopped (restart_policy: never)",
+                total - boot.len()
+            );
+        }
+    }
+
+    /// Run a definition, give it its mounts, and record its PID
+    fn launch_process(&self, spec: &ProcessSpec) -> Result<Pid> {
+        let pid = {
+            let mut kernel = self.kernel.write().unwrap();
+            // The server's own port is taken; dev servers pick 8000 + PID
+            let config = OsRunConfig {
+                project_path: spec.project_path.clone(),
+                language: Some(spec.language.clone()),
+                port: None,
+                mounts: Vec::new(),
+                ..self.config.clone()
+            };
+            let pid = kernel
+                .run_project_with_entry(config, &spec.language, spec.command.as_deref())
+                .map_err(|e| WasmrunError::from(e.to_string()))?;
+            kernel.set_process_env(pid, spec.env.clone());
+            kernel.set_process_args(pid, spec.args.clone());
+            if let Err(e) = Self::mount_for_process(&kernel, pid, &spec.mounts) {
+                let _ = kernel.kill_process(pid);
+                return Err(e);
+            }
+            pid
+        };
+        self.process_table
+            .lock()
+            .unwrap()
+            .set_pid(&spec.name, Some(pid))?;
+        self.supervisor
+            .lock()
+            .unwrap()
+            .started(&spec.name, pid, Instant::now());
+        Ok(pid)
+    }
+
+    fn mount_for_process(
+        kernel: &MultiLanguageKernel,
+        pid: Pid,
+        mounts: &[MountSpec],
+    ) -> Result<()> {
+        for mount in mounts {
+            kernel
+                .wasi_filesystem()
+                .mount_for_process(pid, mount)
+                .map_err(|e| WasmrunError::from(format!("Failed to mount {}: {e}", mount.guest)))?;
+        }
+        Ok(())
+    }
+
+    /// Notice supervised processes that went down and relaunch those whose
+    /// backoff has run out
+    fn supervise(&self) {
+        let now = Instant::now();
+        let mut exits: Vec<(String, Pid, String)> = Vec::new();
+        {
+            let kernel = self.kernel.read().unwrap();
+            let supervisor = self.supervisor.lock().unwrap();
+            for (pid, error) in kernel.take_crashed_dev_servers() {
+                if let Some(name) = supervisor.member_for_pid(pid) {
+                    exits.push((
+                        name.to_string(),
+                        pid,
+                        format!("Dev server crashed: {error}"),
+                    ));
+                }
+            }
+            for (name, pid) in supervisor.running_pids() {
+                let alive = kernel
+                    .base_kernel()
+                    .get_process(pid)
+                    .is_some_and(|p| p.state != ProcessState::Terminated);
+                if !alive && !exits.iter().any(|(_, p, _)| *p == pid) {
+                    exits.push((
+                        name.to_string(),
+                        pid,
+                        "Process exited unexpectedly".to_string(),
+                    ));
+                }
+            }
+        }
+        for (name, pid, error) in exits {
+            self.record_exit(&name, pid, 1, Some(error));
+        }
+
+        let due = self.supervisor.lock().unwrap().due(now);
+        for name in due {
+            let spec = self.process_table.lock().unwrap().get(&name).cloned();
+            let Some(spec) = spec else {
+                self.supervisor.lock().unwrap().launch_failed(
+                    &name,
+                    "No saved process definition".to_string(),
+                    now,
+                );
+                continue;
+            };
+            match self.launch_process(&spec) {
+                Ok(pid) => {
+                    let message = format!("Supervisor restarted '{name}' with PID: {pid}");
+                    println!("🔁 {message}");
+                    self.log_system
+                        .log(LogEntry::info(LogSource::Kernel, message).with_pid(pid));
+                }
+                Err(e) => {
+                    self.log_system.log(LogEntry::error(
+                        LogSource::Kernel,
+                        format!("Supervisor failed to restart '{name}': {e}"),
+                    ));
+                    self.supervisor
+                        .lock()
+                        .unwrap()
+                        .launch_failed(&name, e.to_string(), now);
+                }
+            }
+        }
+    }
+
+    /// Clean up after a member's exit and let its group decide what's next
+    fn record_exit(&self, name: &str, pid: Pid, code: i32, error: Option<String>) {
+        let _ = self.kernel.write().unwrap().kill_process(pid);
+        let _ = self.process_table.lock().unwrap().set_pid(name, None);
+
+        let message = match &error {
+            Some(error) => format!("Process '{name}' (PID {pid}) exited with code {code}: {error}"),
+            None => format!("Process '{name}' (PID {pid}) exited with code {code}"),
+        };
+        let entry = if code == 0 {
+            println!("⏹️  {message}");
+            LogEntry::info(LogSource::Kernel, message)
+        } else {
+            eprintln!("⚠️ {message}");
+            LogEntry::warn(LogSource::Kernel, message)
+        };
+        self.log_system.log(entry.with_pid(pid));
+        self.supervisor
+            .lock()
+            .unwrap()
+            .exited(name, code, error, Instant::now());
+    }
+
     /// Core project startup logic. Acquires the kernel write lock, mounts the
     /// project, and runs it. Returns the new PID on success.
     /// Does NOT touch project_pid — callers are responsible for that.
     fn run_project_in_kernel(&self) -> Result<u32> {
+        // Read before taking the kernel lock; the process list locks the
+        // table first
+        let launch = self.process_table.lock().unwrap().project_config().clone();
         let mut kernel = self.kernel.write().unwrap();
 
         if let Err(e) = kernel.mount_project(&self.config.project_path) {
             eprintln!("⚠️ Failed to mount project directory: {e}");
         }
 
+        let started = Instant::now();
         match kernel.auto_detect_and_run(self.config.clone()) {
             Ok(pid) => {
+                self.metrics.record_project_start(started.elapsed());
+                kernel.set_process_env(pid, launch.env);
+                kernel.set_process_args(pid, launch.args);
+                if let Err(e) = Self::mount_for_process(&kernel, pid, &self.config.mounts) {
+                    let _ = kernel.kill_process(pid);
+                    return Err(e);
+                }
                 self.log_system.log(
                     LogEntry::info(
                         LogSource::Kernel,
@@ -226,6 +498,69 @@ impl OsServer {
     fn handle_request(&self, request: Request) -> Result<()> {
         let method = request.method().clone();
         let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or("").to_string();
+        let ip = request.remote_addr().copied().map(|addr| addr.ip());
+        let mutating = url.starts_with("/api/") && matches!(method, Method::Post | Method::Delete);
+
+        let mut user = None;
+        if let Some(auth) = &self.auth {
+            if method != Method::Options && requires_auth(&path) {
+                let Some((session, via_cookie)) = Self::request_session(auth, &request) else {
+                    self.metrics.record_rejection("auth");
+                    return self.send_error_status(
+                        request,
+                        401,
+                        "Sign in first: POST /api/auth/login",
+                    );
+                };
+                // Browsers attach cookies to cross-site requests; bearer
+                // tokens have to be added by the caller
+                if via_cookie
+                    && mutating
+                    && header_value(&request, os_auth::CSRF_HEADER) != Some(&session.csrf_token)
+                {
+                    self.metrics.record_rejection("csrf");
+                    return self.send_error_status(
+                        request,
+                        403,
+                        &format!("Missing or invalid {} header", os_auth::CSRF_HEADER),
+                    );
+                }
+                user = Some(session.user);
+            }
+        }
+
+        if mutating {
+            if self.config.read_only {
+                self.metrics.record_rejection("read_only");
+                return self.send_error_status(
+                    request,
+                    403,
+                    "Server is running with --read-only; changes are disabled",
+                );
+            }
+            if let Some(addr) = request.remote_addr().copied() {
+                if let Err(retry_after) = self.rate_limiter.check(addr.ip()) {
+                    self.metrics.record_rejection("rate");
+                    return self.send_rate_limited(request, retry_after);
+                }
+            }
+        }
+        if let (Some(limit), Some(length)) = (self.body_limit, request.body_length()) {
+            if length as u64 > limit {
+                self.metrics.record_rejection("body");
+                return self.send_error_status(
+                    request,
+                    413,
+                    &BodyError::TooLarge(limit).to_string(),
+                );
+            }
+        }
+
+        if mutating && !path.starts_with("/api/auth/") {
+            self.audit
+                .record(user.as_deref(), ip, format!("{method} {path}"));
+        }
 
         match (method, url.as_str()) {
             (Method::Options, _) => {
@@ -241,7 +576,7 @@ impl OsServer {
                     .with_header(
                         Header::from_bytes(
                             &b"Access-Control-Allow-Headers"[..],
-                            &b"Content-Type"[..],
+                            &b"Content-Type, Authorization, X-CSRF-Token"[..],
                         )
                         .unwrap(),
                     )
@@ -351,11 +686,54 @@ impl OsServer {
                 self.handle_project_files_request(request)?;
             }
 
+            (Method::Get, "/api/auth/session") => {
+                self.handle_auth_session_request(request)?;
+            }
+
+            (Method::Post, "/api/auth/login") => {
+                self.handle_login_request(request)?;
+            }
+
+            (Method::Post, "/api/auth/logout") => {
+                self.handle_logout_request(request, user.as_deref())?;
+            }
+
+            (Method::Get, path)
+                if path == "/api/auth/audit" || path.starts_with("/api/auth/audit?") =>
+            {
+                let limit = path
+                    .split_once("limit=")
+                    .and_then(|(_, n)| n.split('&').next()?.parse().ok())
+                    .unwrap_or(100);
+                let entries = self.audit.recent(limit);
+                self.send_json(
+                    request,
+                    200,
+                    serde_json::json!({ "success": true, "entries": entries }),
+                )?;
+            }
+
             // API endpoint for kernel statistics
+            (Method::Get, "/metrics") => {
+                self.handle_metrics_request(request)?;
+            }
+
             (Method::Get, "/api/kernel/stats") => {
                 self.handle_kernel_stats_request(request)?;
             }
 
+            // API endpoint for the project's branch, commit and dirty state
+            (Method::Get, "/api/git") => {
+                match GitState::detect(Path::new(&self.config.project_path)) {
+                    Some(state) => self.send_json(request, 200, serde_json::json!(state))?,
+                    None => self.send_json(
+                        request,
+                        404,
+                        serde_json::json!({ "error": "Project is not in a git repository" }),
+                    )?,
+                }
+            }
+
             // API endpoint for filesystem statistics
             (Method::Get, "/api/fs/stats") => {
                 self.handle_fs_stats_request(request)?;
@@ -373,6 +751,32 @@ impl OsServer {
                 self.handle_fs_list_request(request, dir_path)?;
             }
 
+            (Method::Get, path) if path.starts_with("/api/fs/download/") => {
+                let file_path = &path["/api/fs/download/".len()..];
+                self.handle_fs_download_request(request, file_path)?;
+            }
+
+            (Method::Get, path)
+                if path == "/api/fs/search" || path.starts_with("/api/fs/search?") =>
+            {
+                let query = path.split_once('?').map_or("", |(_, query)| query);
+                self.handle_fs_search_request(request, query)?;
+            }
+
+            (Method::Get, path) if path.starts_with("/api/fs/export/") => {
+                let dir_path = &path["/api/fs/export/".len()..];
+                self.handle_fs_export_request(request, dir_path)?;
+            }
+
+            (Method::Post, path) if path.starts_with("/api/fs/extract/") => {
+                let dir_path = &path["/api/fs/extract/".len()..];
+                self.handle_fs_extract_request(request, dir_path)?;
+            }
+
+            (Method::Post, "/api/clipboard") => {
+                self.handle_clipboard_request(request)?;
+            }
+
             // API endpoint for writing files
             (Method::Post, path) if path.starts_with("/api/fs/write/") => {
                 let file_path = &path[14..]; // Remove "/api/fs/write/"
@@ -399,7 +803,91 @@ impl OsServer {
                 self.handle_restart_project(request)?;
             }
 
+            // Process definitions, persisted across restarts
+            (Method::Get, "/api/processes") => {
+                self.handle_list_processes_request(request)?;
+            }
+
+            (Method::Post, "/api/processes") => {
+                self.handle_launch_process_request(request)?;
+            }
+
+            (Method::Post, path)
+                if path.starts_with("/api/processes/") && path.ends_with("/exit") =>
+            {
+                match path.split('/').nth(3).and_then(|p| p.parse::<Pid>().ok()) {
+                    Some(pid) => self.handle_process_exit_request(request, pid)?,
+                    None => self.send_error(request, "Invalid PID")?,
+                }
+            }
+
+            (Method::Get, "/api/volumes") => {
+                let volumes = self.kernel.read().unwrap().wasi_filesystem().list_volumes();
+                self.send_json(
+                    request,
+                    200,
+                    serde_json::json!({ "success": true, "volumes": volumes }),
+                )?;
+            }
+
+            (Method::Post, "/api/volumes") => {
+                self.handle_create_volume_request(request)?;
+            }
+
+            (Method::Get, "/api/supervisor") => {
+                let groups = self.supervisor.lock().unwrap().report(Instant::now());
+                self.send_json(
+                    request,
+                    200,
+                    serde_json::json!({ "success": true, "groups": groups }),
+                )?;
+            }
+
+            (Method::Post, "/api/supervisor/groups") => {
+                self.handle_set_group_request(request)?;
+            }
+
+            (Method::Delete, path) if path.starts_with("/api/supervisor/groups/") => {
+                let name = path.trim_start_matches("/api/supervisor/groups/");
+                self.handle_remove_group_request(request, name)?;
+            }
+
+            (Method::Get, path)
+                if path.starts_with("/api/processes/") && path.ends_with("/config") =>
+            {
+                match path.split('/').nth(3).and_then(|p| p.parse::<Pid>().ok()) {
+                    Some(pid) => self.handle_get_process_config_request(request, pid)?,
+                    None => self.send_error(request, "Invalid PID")?,
+                }
+            }
+
+            (Method::Post, path)
+                if path.starts_with("/api/processes/") && path.ends_with("/config") =>
+            {
+                match path.split('/').nth(3).and_then(|p| p.parse::<Pid>().ok()) {
+                    Some(pid) => self.handle_set_process_config_request(request, pid)?,
+                    None => self.send_error(request, "Invalid PID")?,
+                }
+            }
+
+            (Method::Post, path)
+                if path.starts_with("/api/processes/") && path.ends_with("/restart-policy") =>
+            {
+                match path.split('/').nth(3).and_then(|p| p.parse::<Pid>().ok()) {
+                    Some(pid) => self.handle_restart_policy_request(request, pid)?,
+                    None => self.send_error(request, "Invalid PID")?,
+                }
+            }
+
             // API endpoints for port forwarding
+            (Method::Get, "/api/ports") => {
+                self.handle_ports_table_request(request)?;
+            }
+
+            (Method::Get, "/api/network") => {
+                self.handle_virtual_network_request(request)?;
+            }
+
             (Method::Get, path)
                 if path.starts_with("/api/processes/") && path.ends_with("/ports") =>
             {
@@ -447,15 +935,53 @@ impl OsServer {
                 }
             }
 
+            (Method::Get, path)
+                if path.starts_with("/api/processes/") && path.ends_with("/sockets") =>
+            {
+                match path.split('/').nth(3).and_then(|p| p.parse::<Pid>().ok()) {
+                    Some(pid) => self.handle_list_sockets_request(request, pid)?,
+                    None => self.send_error(request, "Invalid PID")?,
+                }
+            }
+
+            (Method::Delete, path)
+                if path.starts_with("/api/processes/") && path.contains("/sockets/") =>
+            {
+                let parts: Vec<&str> = path.split('/').collect();
+                match (
+                    parts.get(3).and_then(|p| p.parse::<Pid>().ok()),
+                    parts.get(5).and_then(|p| p.parse::<i32>().ok()),
+                ) {
+                    (Some(pid), Some(fd)) if parts.len() == 6 => {
+                        self.handle_disconnect_socket_request(request, pid, fd)?
+                    }
+                    _ => self.send_error(request, "Invalid PID or fd")?,
+                }
+            }
+
+            (Method::Delete, path)
+                if path.starts_with("/api/processes/") && path.split('/').count() == 4 =>
+            {
+                match path.split('/').nth(3).and_then(|p| p.parse::<Pid>().ok()) {
+                    Some(pid) => self.handle_kill_process_request(request, pid)?,
+                    None => self.send_error(request, "Invalid PID")?,
+                }
+            }
+
             // API endpoint for logs
-            (Method::Get, "/api/logs") => {
-                self.handle_logs_request(request)?;
+            (Method::Get, path) if path == "/api/logs" || path.starts_with("/api/logs?") => {
+                let query = path.split_once('?').map_or("", |(_, query)| query);
+                self.handle_logs_request(request, query)?;
             }
 
             (Method::Get, "/api/logs/recent") => {
                 self.handle_recent_logs_request(request)?;
             }
 
+            (Method::Get, "/api/logs/stream") => {
+                self.handle_logs_stream_request(request)?;
+            }
+
             // Tunnel API endpoints
             (Method::Post, "/api/tunnel/start") => {
                 self.handle_tunnel_start_request(request)?;
@@ -533,75 +1059,939 @@ impl OsServer {
     /// Kill-and-restart is atomic under a single project_pid write lock.
     fn handle_restart_project(&self, request: Request) -> Result<()> {
         let mut project_pid = self.project_pid.write().unwrap();
+        let response_json = match self.restart_project(&mut project_pid) {
+            Ok(pid) => serde_json::json!({ "success": true, "pid": pid }),
+            Err(e) => {
+                serde_json::json!({ "success": false, "error": e.to_string() })
+            }
+        };
+
+        let status = if response_json["success"].as_bool() == Some(true) {
+            200
+        } else {
+            500
+        };
+
+        let response = Response::from_string(response_json.to_string())
+            .with_status_code(tiny_http::StatusCode(status))
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+            )
+            .with_header(self.cors_header());
+
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The session a request carries, and whether it came in a cookie rather
+    /// than an `Authorization: Bearer` header
+    fn request_session(
+        auth: &Authenticator,
+        request: &Request,
+    ) -> Option<(os_auth::Session, bool)> {
+        if let Some(token) =
+            header_value(request, "Authorization").and_then(|value| value.strip_prefix("Bearer "))
+        {
+            return auth.session(token.trim()).map(|session| (session, false));
+        }
+        let token = header_value(request, "Cookie").and_then(os_auth::token_from_cookie)?;
+        auth.session(token).map(|session| (session, true))
+    }
+
+    /// Whether auth is on and, if so, who is signed in. Open to everyone so
+    /// the UI knows whether to show its login form.
+    fn handle_auth_session_request(&self, request: Request) -> Result<()> {
+        let session = self
+            .auth
+            .as_ref()
+            .and_then(|auth| Self::request_session(auth, &request));
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({
+                "success": true,
+                "enabled": self.auth.is_some(),
+                "authenticated": self.auth.is_none() || session.is_some(),
+                "user": session.as_ref().map(|(s, _)| s.user.clone()),
+                "csrf_token": session.map(|(s, _)| s.csrf_token),
+            }),
+        )
+    }
+
+    fn handle_login_request(&self, mut request: Request) -> Result<()> {
+        let Some(auth) = &self.auth else {
+            return self.send_error_status(request, 404, "Auth is not enabled for this server");
+        };
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let body: serde_json::Value = match serde_json::from_slice(&content) {
+            Ok(body) => body,
+            Err(e) => return self.send_error(request, &format!("Invalid JSON: {e}")),
+        };
+        let Some(password) = body["password"].as_str() else {
+            return self.send_error(request, "Missing \"password\"");
+        };
+        let username = body["username"].as_str().filter(|u| !u.is_empty());
+        let ip = request.remote_addr().copied().map(|addr| addr.ip());
+
+        match auth.login(username, password, ip) {
+            Ok((token, session)) => {
+                self.audit.record(Some(&session.user), ip, "login");
+                let body = serde_json::json!({
+                    "success": true,
+                    "user": session.user,
+                    "token": token,
+                    "csrf_token": session.csrf_token,
+                });
+                let response = Response::from_string(body.to_string())
+                    .with_header(
+                        Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+                    )
+                    .with_header(
+                        Header::from_bytes(
+                            &b"Set-Cookie"[..],
+                            auth.session_cookie(&token).as_bytes(),
+                        )
+                        .unwrap(),
+                    )
+                    .with_header(self.cors_header());
+                request
+                    .respond(response)
+                    .map_err(|e| WasmrunError::from(e.to_string()))
+            }
+            Err(e) => {
+                self.audit
+                    .record(username.or(Some(os_auth::ADMIN_USER)), ip, "login_failed");
+                let status = match e {
+                    LoginError::InvalidCredentials => 401,
+                    LoginError::LockedOut(_) => 429,
+                    LoginError::NoRandomness(_) => 500,
+                };
+                self.send_error_status(request, status, &e.to_string())
+            }
+        }
+    }
+
+    fn handle_logout_request(&self, request: Request, user: Option<&str>) -> Result<()> {
+        if let Some(auth) = &self.auth {
+            if let Some(token) = header_value(&request, "Authorization")
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .or_else(|| header_value(&request, "Cookie").and_then(os_auth::token_from_cookie))
+            {
+                auth.logout(token.trim());
+            }
+            let ip = request.remote_addr().copied().map(|addr| addr.ip());
+            self.audit.record(user, ip, "logout");
+        }
+        let response = Response::from_string(serde_json::json!({ "success": true }).to_string())
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+            )
+            .with_header(
+                Header::from_bytes(&b"Set-Cookie"[..], os_auth::expired_cookie().as_bytes())
+                    .unwrap(),
+            )
+            .with_header(self.cors_header());
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))
+    }
+
+    /// Kill the main project if it's running and start it again. Callers
+    /// hold the project_pid write lock.
+    fn restart_project(&self, project_pid: &mut Option<u32>) -> Result<u32> {
+        if let Some(pid) = project_pid.take() {
+            let _ = self.kernel.write().unwrap().kill_process(pid);
+        }
+        let pid = self.run_project_in_kernel()?;
+        *project_pid = Some(pid);
+        Ok(pid)
+    }
+
+    /// Handle kernel statistics API request
+    fn handle_kernel_stats_request(&self, request: Request) -> Result<()> {
+        // `project_pid` before `kernel`, as restarts take them
+        let project_pid = *self.project_pid.read().unwrap();
+        let kernel = self.kernel.read().unwrap();
+        let stats = kernel.get_statistics();
+
+        let stats_json = serde_json::json!({
+            "status": "running",
+            "active_processes": stats.active_processes,
+            "total_memory_usage": stats.total_memory_usage,
+            "active_runtimes": stats.active_runtimes,
+            "active_dev_servers": stats.active_dev_servers,
+            "project_pid": project_pid,
+            "read_only": self.config.read_only,
+            "auth_sessions": self.auth.as_ref().map(Authenticator::session_count),
+            // System information
+            "os": stats.os,
+            "arch": stats.arch,
+            "kernel_version": stats.kernel_version,
+            // WASI capabilities
+            "wasi_capabilities": stats.wasi_capabilities,
+            "filesystem_mounts": stats.filesystem_mounts,
+            "supported_languages": stats.supported_languages,
+        });
+
+        let response = Response::from_string(stats_json.to_string())
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+            )
+            .with_header(self.cors_header());
+
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Prometheus scrape of request, kernel and port-forward metrics
+    fn handle_metrics_request(&self, request: Request) -> Result<()> {
+        let tunnel_connected = self
+            .tunnel_client
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|client| client.get_status() == TunnelStatus::Connected);
+        let body = {
+            let kernel = self.kernel.read().unwrap();
+            self.metrics.render(&kernel, tunnel_connected)
+        };
+
+        let response = Response::from_string(body).with_header(
+            Header::from_bytes(&b"Content-Type"[..], PROMETHEUS_CONTENT_TYPE.as_bytes()).unwrap(),
+        );
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    fn send_json(&self, request: Request, status: u16, body: serde_json::Value) -> Result<()> {
+        let response = Response::from_string(body.to_string())
+            .with_status_code(tiny_http::StatusCode(status))
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+            )
+            .with_header(self.cors_header());
+
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Running processes plus every saved definition, running or not
+    fn handle_list_processes_request(&self, request: Request) -> Result<()> {
+        let table = self.process_table.lock().unwrap();
+        let processes: Vec<serde_json::Value> = {
+            let kernel = self.kernel.read().unwrap();
+            let mut processes = kernel.base_kernel().list_processes();
+            processes.sort_by_key(|p| p.pid);
+            processes
+                .into_iter()
+                .map(|process| {
+                    let spec = table.find_by_pid(process.pid);
+                    serde_json::json!({
+                        "pid": process.pid,
+                        "name": spec.map_or(process.name.clone(), |s| s.name.clone()),
+                        "language": process.language,
+                        "state": process.state,
+                        "memory_usage": process.memory_usage,
+                        "env": kernel.get_process_env(process.pid),
+                        "args": kernel.get_process_args(process.pid),
+                        "restart_policy": spec.map(|s| s.restart_policy),
+                    })
+                })
+                .collect()
+        };
+
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({
+                "success": true,
+                "processes": processes,
+                "definitions": table.specs(),
+            }),
+        )
+    }
+
+    /// Launch a process from a JSON definition and persist it. Re-posting
+    /// the name of a stopped definition starts it again.
+    fn handle_launch_process_request(&self, mut request: Request) -> Result<()> {
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let body: serde_json::Value = match serde_json::from_slice(&content) {
+            Ok(v) => v,
+            Err(e) => return self.send_error(request, &format!("Invalid JSON: {e}")),
+        };
+        let spec = match self.process_spec_from_json(&body) {
+            Ok(spec) => spec,
+            Err(e) => return self.send_error(request, &e),
+        };
+
+        let running = self
+            .process_table
+            .lock()
+            .unwrap()
+            .get(&spec.name)
+            .and_then(|s| s.pid)
+            .filter(|pid| {
+                self.kernel
+                    .read()
+                    .unwrap()
+                    .base_kernel()
+                    .get_process(*pid)
+                    .is_some()
+            });
+        if let Some(pid) = running {
+            return self.send_json(
+                request,
+                409,
+                serde_json::json!({
+                    "success": false,
+                    "error": format!("Process '{}' is already running with PID {pid}", spec.name)
+                }),
+            );
+        }
+
+        self.process_table.lock().unwrap().upsert(spec.clone())?;
+        match self.launch_process(&spec) {
+            Ok(pid) => {
+                self.log_system.log(
+                    LogEntry::info(
+                        LogSource::Kernel,
+                        format!("Launched process '{}' with PID: {pid}", spec.name),
+                    )
+                    .with_pid(pid),
+                );
+                self.send_json(
+                    request,
+                    200,
+                    serde_json::json!({
+                        "success": true,
+                        "pid": pid,
+                        "process": ProcessSpec { pid: Some(pid), ..spec },
+                    }),
+                )
+            }
+            Err(e) => self.send_json(
+                request,
+                500,
+                serde_json::json!({ "success": false, "error": e.to_string() }),
+            ),
+        }
+    }
+
+    /// Fill in a definition from the request, defaulting to this server's project
+    fn process_spec_from_json(
+        &self,
+        body: &serde_json::Value,
+    ) -> std::result::Result<ProcessSpec, String> {
+        let name = match body.get("name").and_then(|v| v.as_str()) {
+            Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => return Err("Missing process name".to_string()),
+        };
+        let project_path = body
+            .get("project_path")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| self.config.project_path.clone());
+        if !Path::new(&project_path).is_dir() {
+            return Err(format!("Project path is not a directory: {project_path}"));
+        }
+
+        let language = match body.get("language").and_then(|v| v.as_str()) {
+            Some(language) => language.to_string(),
+            None => {
+                let kernel = self.kernel.read().unwrap();
+                kernel
+                    .registry()
+                    .detect_project_language(&project_path)
+                    .map(str::to_string)
+                    .or_else(|| self.config.language.clone())
+                    .ok_or_else(|| format!("Could not detect a language for {project_path}"))?
+            }
+        };
+
+        let launch = Self::launch_config_from_json(body, LaunchConfig::default())?;
+        let mounts = match body.get("mounts") {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    serde_json::Value::String(spec) => {
+                        MountSpec::parse(spec).map_err(|e| e.to_string())
+                    }
+                    other => serde_json::from_value::<MountSpec>(other.clone())
+                        .map_err(|e| format!("Invalid mount: {e}"))
+                        .and_then(|m| m.validate().map(|_| m).map_err(|e| e.to_string())),
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+            Some(_) => {
+                return Err(
+                    "mounts must be a list of \"volume:/path[:ro]\" strings or mount objects"
+                        .to_string(),
+                )
+            }
+        };
+        let restart_policy = match body.get("restart_policy").and_then(|v| v.as_str()) {
+            None => RestartPolicy::default(),
+            Some(value) => RestartPolicy::parse(value).ok_or_else(|| {
+                format!("Unknown restart_policy '{value}' (use never or on-boot)")
+            })?,
+        };
+
+        Ok(ProcessSpec {
+            name,
+            language,
+            project_path,
+            command: body
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            env: launch.env,
+            args: launch.args,
+            mounts,
+            restart_policy,
+            pid: None,
+        })
+    }
+
+    /// Apply the `env` and `args` fields of a request to `current`; a field
+    /// left out keeps its current value
+    fn launch_config_from_json(
+        body: &serde_json::Value,
+        current: LaunchConfig,
+    ) -> std::result::Result<LaunchConfig, String> {
+        let env: BTreeMap<String, String> = match body.get("env") {
+            None => current.env,
+            Some(serde_json::Value::Null) => BTreeMap::new(),
+            Some(env) => serde_json::from_value(env.clone())
+                .map_err(|_| "env must be an object of string values".to_string())?,
+        };
+        let args: Vec<String> = match body.get("args") {
+            None => current.args,
+            Some(serde_json::Value::Null) => Vec::new(),
+            Some(args) => serde_json::from_value(args.clone())
+                .map_err(|_| "args must be a list of strings".to_string())?,
+        };
+        let config = LaunchConfig { env, args };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Env and arguments of the main project or a launched definition
+    fn launch_config_for(&self, pid: Pid) -> Option<(String, LaunchConfig)> {
+        let is_project = self.project_pid.read().unwrap().as_ref() == Some(&pid);
+        let table = self.process_table.lock().unwrap();
+        if is_project {
+            return Some(("project".to_string(), table.project_config().clone()));
+        }
+        table.find_by_pid(pid).map(|spec| {
+            (
+                spec.name.clone(),
+                LaunchConfig {
+                    env: spec.env.clone(),
+                    args: spec.args.clone(),
+                },
+            )
+        })
+    }
+
+    fn handle_get_process_config_request(&self, request: Request, pid: Pid) -> Result<()> {
+        match self.launch_config_for(pid) {
+            Some((name, config)) => self.send_json(
+                request,
+                200,
+                serde_json::json!({
+                    "success": true,
+                    "pid": pid,
+                    "name": name,
+                    "env": config.env,
+                    "args": config.args,
+                }),
+            ),
+            None => self.send_json(
+                request,
+                404,
+                serde_json::json!({
+                    "success": false,
+                    "error": format!("PID {pid} is neither the project nor a launched process")
+                }),
+            ),
+        }
+    }
+
+    /// Save new env and arguments for a process and, unless the body says
+    /// `"restart": false`, restart it so they take effect
+    fn handle_set_process_config_request(&self, mut request: Request, pid: Pid) -> Result<()> {
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let body: serde_json::Value = match serde_json::from_slice(&content) {
+            Ok(v) => v,
+            Err(e) => return self.send_error(request, &format!("Invalid JSON: {e}")),
+        };
+        let Some((name, current)) = self.launch_config_for(pid) else {
+            return self.send_json(
+                request,
+                404,
+                serde_json::json!({
+                    "success": false,
+                    "error": format!("PID {pid} is neither the project nor a launched process")
+                }),
+            );
+        };
+        let config = match Self::launch_config_from_json(&body, current) {
+            Ok(config) => config,
+            Err(e) => return self.send_error(request, &e),
+        };
+        let restart = body
+            .get("restart")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let is_project = self.project_pid.read().unwrap().as_ref() == Some(&pid);
+        let relaunched = if is_project {
+            self.process_table
+                .lock()
+                .unwrap()
+                .set_project_config(config.clone())?;
+            if restart {
+                let mut project_pid = self.project_pid.write().unwrap();
+                Some(self.restart_project(&mut project_pid))
+            } else {
+                None
+            }
+        } else {
+            let spec = self
+                .process_table
+                .lock()
+                .unwrap()
+                .set_launch_config(pid, config.clone())?;
+            match spec {
+                Some(spec) if restart => {
+                    self.supervisor.lock().unwrap().stopped(pid);
+                    let _ = self.kernel.write().unwrap().kill_process(pid);
+                    self.process_table
+                        .lock()
+                        .unwrap()
+                        .set_pid(&spec.name, None)?;
+                    Some(self.launch_process(&spec))
+                }
+                _ => None,
+            }
+        };
+
+        let (status, new_pid, error) = match relaunched {
+            None => (200, pid, None),
+            Some(Ok(new_pid)) => {
+                self.log_system.log(
+                    LogEntry::info(
+                        LogSource::Kernel,
+                        format!("Restarted '{name}' with new config as PID: {new_pid}"),
+                    )
+                    .with_pid(new_pid),
+                );
+                (200, new_pid, None)
+            }
+            Some(Err(e)) => (500, pid, Some(e.to_string())),
+        };
+        let mut response = serde_json::json!({
+            "success": error.is_none(),
+            "pid": new_pid,
+            "name": name,
+            "env": config.env,
+            "args": config.args,
+            "restarted": restart && error.is_none(),
+        });
+        if let Some(error) = error {
+            response["error"] = serde_json::Value::String(error);
+        }
+        self.send_json(request, status, response)
+    }
+
+    fn handle_restart_policy_request(&self, mut request: Request, pid: Pid) -> Result<()> {
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let policy = serde_json::from_slice::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|v| {
+                v.get("restart_policy")?
+                    .as_str()
+                    .and_then(RestartPolicy::parse)
+            });
+        let Some(policy) = policy else {
+            return self.send_error(
+                request,
+                "Body must be {\"restart_policy\": \"never\" | \"on-boot\"}",
+            );
+        };
+
+        if self
+            .process_table
+            .lock()
+            .unwrap()
+            .set_restart_policy(pid, policy)?
+        {
+            self.send_json(
+                request,
+                200,
+                serde_json::json!({ "success": true, "pid": pid, "restart_policy": policy }),
+            )
+        } else {
+            self.send_json(
+                request,
+                404,
+                serde_json::json!({
+                    "success": false,
+                    "error": format!("PID {pid} was not launched through /api/processes")
+                }),
+            )
+        }
+    }
+
+    /// Exit report from the runtime running a launched process
+    fn handle_process_exit_request(&self, mut request: Request, pid: Pid) -> Result<()> {
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let body: serde_json::Value = serde_json::from_slice(&content).unwrap_or_default();
+        let code = body.get("code").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+        let error = body
+            .get("error")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        let name = self
+            .process_table
+            .lock()
+            .unwrap()
+            .find_by_pid(pid)
+            .map(|s| s.name.clone());
+        let Some(name) = name else {
+            return self.send_json(
+                request,
+                404,
+                serde_json::json!({
+                    "success": false,
+                    "error": format!("PID {pid} was not launched through /api/processes")
+                }),
+            );
+        };
+
+        self.record_exit(&name, pid, code, error);
+        let supervised = self.supervisor.lock().unwrap().is_supervised(&name);
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({ "success": true, "name": name, "supervised": supervised }),
+        )
+    }
+
+    /// Create or replace a supervisor group and start any member that isn't running
+    fn handle_set_group_request(&self, mut request: Request) -> Result<()> {
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let group: GroupSpec = match serde_json::from_slice(&content) {
+            Ok(group) => group,
+            Err(e) => return self.send_error(request, &format!("Invalid group: {e}")),
+        };
+        if group.name.trim().is_empty() || group.name.contains('/') {
+            return self.send_error(request, "Group name must be non-empty and contain no '/'");
+        }
+
+        let (groups, to_launch) = {
+            let mut table = self.process_table.lock().unwrap();
+            let unknown: Vec<&str> = group
+                .processes
+                .iter()
+                .filter(|name| table.get(name).is_none())
+                .map(String::as_str)
+                .collect();
+            if !unknown.is_empty() {
+                let message = format!(
+                    "Unknown process definition(s): {}. Create them with POST /api/processes first",
+                    unknown.join(", ")
+                );
+                drop(table);
+                return self.send_error(request, &message);
+            }
+
+            // Still holding the table: the kernel, then the supervisor
+            let kernel = self.kernel.read().unwrap();
+            let mut supervisor = self.supervisor.lock().unwrap();
+            supervisor.set_group(group.clone());
+            table.set_groups(supervisor.groups().to_vec())?;
+
+            let to_launch: Vec<ProcessSpec> = group
+                .processes
+                .iter()
+                .filter_map(|name| table.get(name))
+                .filter(|spec| {
+                    spec.pid
+                        .and_then(|pid| kernel.base_kernel().get_process(pid))
+                        .is_none()
+                })
+                .cloned()
+                .collect();
+            (supervisor.report(Instant::now()), to_launch)
+        };
+
+        let mut launch_errors = Vec::new();
+        for spec in &to_launch {
+            if let Err(e) = self.launch_process(spec) {
+                self.supervisor.lock().unwrap().launch_failed(
+                    &spec.name,
+                    e.to_string(),
+                    Instant::now(),
+                );
+                launch_errors.push(format!("{}: {e}", spec.name));
+            }
+        }
+
+        let group = groups.into_iter().find(|g| g.spec.name == group.name);
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({
+                "success": true,
+                "group": group,
+                "launch_errors": launch_errors,
+            }),
+        )
+    }
+
+    /// Drop a group; its processes keep running, unsupervised
+    fn handle_remove_group_request(&self, request: Request, name: &str) -> Result<()> {
+        // The table before the supervisor, as in group updates
+        let mut table = self.process_table.lock().unwrap();
+        let mut supervisor = self.supervisor.lock().unwrap();
+        if supervisor.remove_group(name).is_none() {
+            drop((supervisor, table));
+            return self.send_json(
+                request,
+                404,
+                serde_json::json!({
+                    "success": false,
+                    "error": format!("No supervisor group named '{name}'")
+                }),
+            );
+        }
+        table.set_groups(supervisor.groups().to_vec())?;
+        drop((supervisor, table));
+        self.send_json(request, 200, serde_json::json!({ "success": true }))
+    }
+
+    /// Create an empty named volume ahead of the processes that mount it
+    fn handle_create_volume_request(&self, mut request: Request) -> Result<()> {
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let name = serde_json::from_slice::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|body| body.get("name")?.as_str().map(str::to_string));
+        let Some(name) = name else {
+            return self.send_error(request, "Missing 'name' field");
+        };
+        let created = self
+            .kernel
+            .read()
+            .unwrap()
+            .wasi_filesystem()
+            .create_volume(&name);
+        match created {
+            Ok(_) => self.send_json(
+                request,
+                200,
+                serde_json::json!({ "success": true, "name": name }),
+            ),
+            Err(e) => self.send_error(request, &e.to_string()),
+        }
+    }
 
-        if let Some(pid) = *project_pid {
-            let mut kernel = self.kernel.write().unwrap();
-            let _ = kernel.kill_process(pid);
+    /// Stop a process and forget its saved definition
+    fn handle_kill_process_request(&self, request: Request, pid: Pid) -> Result<()> {
+        if self.project_pid.read().unwrap().as_ref() == Some(&pid) {
+            return self.send_json(
+                request,
+                409,
+                serde_json::json!({
+                    "success": false,
+                    "error": "Use /api/kernel/restart to control the main project"
+                }),
+            );
+        }
+        if let Err(e) = self.kernel.write().unwrap().kill_process(pid) {
+            return self.send_json(
+                request,
+                404,
+                serde_json::json!({ "success": false, "error": e.to_string() }),
+            );
         }
-        *project_pid = None;
+        self.supervisor.lock().unwrap().stopped(pid);
+        let removed = self.process_table.lock().unwrap().remove_by_pid(pid)?;
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({
+                "success": true,
+                "pid": pid,
+                "definition_removed": removed.is_some(),
+            }),
+        )
+    }
 
-        let response_json = match self.run_project_in_kernel() {
-            Ok(pid) => {
-                *project_pid = Some(pid);
-                serde_json::json!({ "success": true, "pid": pid })
-            }
-            Err(e) => {
-                serde_json::json!({ "success": false, "error": e.to_string() })
+    /// Guests' addresses on the virtual network and what they listen on
+    fn handle_list_sockets_request(&self, request: Request, pid: Pid) -> Result<()> {
+        let sockets = {
+            let kernel = self.kernel.read().unwrap();
+            if kernel.base_kernel().get_process(pid).is_none() {
+                drop(kernel);
+                return self.send_json(
+                    request,
+                    404,
+                    serde_json::json!({ "success": false, "error": format!("Process {pid} not found") }),
+                );
             }
+            kernel.list_sockets(pid)
         };
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({ "success": true, "pid": pid, "sockets": sockets }),
+        )
+    }
 
-        let status = if response_json["success"].as_bool() == Some(true) {
-            200
-        } else {
-            500
-        };
-
-        let response = Response::from_string(response_json.to_string())
-            .with_status_code(tiny_http::StatusCode(status))
-            .with_header(
-                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
-            )
-            .with_header(self.cors_header());
-
-        request
-            .respond(response)
-            .map_err(|e| WasmrunError::from(e.to_string()))?;
-        Ok(())
+    fn handle_disconnect_socket_request(&self, request: Request, pid: Pid, fd: i32) -> Result<()> {
+        let result = self.kernel.read().unwrap().disconnect_socket(pid, fd);
+        match result {
+            Ok(()) => self.send_json(
+                request,
+                200,
+                serde_json::json!({ "success": true, "pid": pid, "fd": fd }),
+            ),
+            Err(e) => self.send_json(
+                request,
+                404,
+                serde_json::json!({ "success": false, "error": e.to_string() }),
+            ),
+        }
     }
 
-    /// Handle kernel statistics API request
-    fn handle_kernel_stats_request(&self, request: Request) -> Result<()> {
-        let kernel = self.kernel.read().unwrap();
-        let stats = kernel.get_statistics();
+    fn handle_virtual_network_request(&self, request: Request) -> Result<()> {
+        let (hosts, listeners) = {
+            let kernel = self.kernel.read().unwrap();
+            let mut processes = kernel.base_kernel().list_processes();
+            processes.sort_by_key(|p| p.pid);
+            let hosts: Vec<serde_json::Value> = processes
+                .into_iter()
+                .map(|process| {
+                    serde_json::json!({
+                        "pid": process.pid,
+                        "name": process.name,
+                        "ip": virtual_switch::virtual_ip(process.pid),
+                        "hostname": format!("{}{}", process.name, virtual_switch::LOCAL_DOMAIN),
+                    })
+                })
+                .collect();
+            (hosts, kernel.virtual_listeners())
+        };
 
-        let project_pid = *self.project_pid.read().unwrap();
-        let stats_json = serde_json::json!({
-            "status": "running",
-            "active_processes": stats.active_processes,
-            "total_memory_usage": stats.total_memory_usage,
-            "active_runtimes": stats.active_runtimes,
-            "active_dev_servers": stats.active_dev_servers,
-            "project_pid": project_pid,
-            // System information
-            "os": stats.os,
-            "arch": stats.arch,
-            "kernel_version": stats.kernel_version,
-            // WASI capabilities
-            "wasi_capabilities": stats.wasi_capabilities,
-            "filesystem_mounts": stats.filesystem_mounts,
-            "supported_languages": stats.supported_languages,
-        });
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({
+                "success": true,
+                "subnet": format!("{}/16", virtual_switch::VIRTUAL_SUBNET),
+                "hosts": hosts,
+                "listeners": listeners,
+            }),
+        )
+    }
 
-        let response = Response::from_string(stats_json.to_string())
-            .with_header(
-                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
-            )
-            .with_header(self.cors_header());
+    /// Every process's port mappings in one table, plus the guest ports
+    /// more than one process has mapped
+    fn handle_ports_table_request(&self, request: Request) -> Result<()> {
+        let (ports, conflicts) = {
+            let table = self.process_table.lock().unwrap();
+            let kernel = self.kernel.read().unwrap();
+            let processes: HashMap<Pid, (String, String)> = kernel
+                .list_processes_with_languages()
+                .into_iter()
+                .map(|(pid, name, language)| {
+                    let name = table.find_by_pid(pid).map_or(name, |s| s.name.clone());
+                    (pid, (name, language))
+                })
+                .collect();
+
+            let mut by_guest_port: BTreeMap<(u16, String), Vec<Pid>> = BTreeMap::new();
+            let ports: Vec<serde_json::Value> = kernel
+                .list_port_mappings()
+                .into_iter()
+                .map(|(pid, m)| {
+                    let protocol = format!("{:?}", m.protocol).to_lowercase();
+                    by_guest_port
+                        .entry((m.guest_port, protocol.clone()))
+                        .or_default()
+                        .push(pid);
+                    let (name, language) = processes.get(&pid).cloned().unwrap_or_default();
+                    let mut row = serde_json::json!({
+                        "pid": pid,
+                        "process": name,
+                        "language": language,
+                        "guest_port": m.guest_port,
+                        "host_port": m.host_port,
+                        "protocol": protocol,
+                        "sessions": kernel.udp_sessions(pid, m.guest_port),
+                    });
+                    if let (Some(row), Ok(serde_json::Value::Object(status))) =
+                        (row.as_object_mut(), serde_json::to_value(m.status))
+                    {
+                        row.extend(status);
+                    }
+                    row
+                })
+                .collect();
+            let conflicts: Vec<serde_json::Value> = by_guest_port
+                .into_iter()
+                .filter(|(_, pids)| pids.len() > 1)
+                .map(|((guest_port, protocol), pids)| {
+                    serde_json::json!({
+                        "guest_port": guest_port,
+                        "protocol": protocol,
+                        "pids": pids,
+                    })
+                })
+                .collect();
+            (ports, conflicts)
+        };
 
-        request
-            .respond(response)
-            .map_err(|e| WasmrunError::from(e.to_string()))?;
-        Ok(())
+        self.send_json(
+            request,
+            200,
+            serde_json::json!({
+                "success": true,
+                "ports": ports,
+                "conflicts": conflicts,
+            }),
+        )
     }
 
     fn handle_list_ports_request(&self, request: Request, pid: u32) -> Result<()> {
@@ -624,7 +2014,9 @@ impl OsServer {
                         "host_port": m.host_port,
                         "protocol": format!("{:?}", m.protocol),
                         "created_at": m.created_at.duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default().as_secs()
+                            .unwrap_or_default().as_secs(),
+                        "status": m.status,
+                        "sessions": kernel.udp_sessions(pid, m.guest_port),
                     })
                 }).collect::<Vec<_>>(),
                 "network_stats": {
@@ -652,13 +2044,12 @@ impl OsServer {
     }
 
     fn handle_create_port_forward_request(&self, mut request: Request, pid: u32) -> Result<()> {
-        let mut content = String::new();
-        let mut reader = request.as_reader();
-        if let Err(e) = std::io::Read::read_to_string(&mut reader, &mut content) {
-            return self.send_error(request, &format!("Failed to read request body: {e}"));
-        }
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
 
-        let body: serde_json::Value = match serde_json::from_str(&content) {
+        let body: serde_json::Value = match serde_json::from_slice(&content) {
             Ok(v) => v,
             Err(e) => return self.send_error(request, &format!("Invalid JSON: {e}")),
         };
@@ -674,16 +2065,41 @@ impl OsServer {
             _ => return self.send_error(request, "Invalid protocol (must be 'tcp' or 'udp')"),
         };
 
+        // Optional: the host port to try first
+        let preferred = match body.get("host_port") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(v) => match v.as_u64() {
+                Some(p) if (1..=u16::MAX as u64).contains(&p) => Some(p as u16),
+                _ => return self.send_error(request, "Invalid host_port"),
+            },
+        };
+
+        // UDP only: close peer sessions after this long without traffic
+        let idle_timeout = match body.get("idle_timeout_secs") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(_) if protocol != crate::runtime::network_namespace::SocketProtocol::Udp => {
+                return self.send_error(request, "idle_timeout_secs applies to udp forwards only")
+            }
+            Some(v) => match v.as_u64() {
+                Some(secs) if secs > 0 => Some(std::time::Duration::from_secs(secs)),
+                _ => return self.send_error(request, "Invalid idle_timeout_secs"),
+            },
+        };
+
         let kernel = self.kernel.read().unwrap();
-        if let Some(ns) = kernel.get_network_namespace(pid) {
-            match ns.allocate_port(guest_port, protocol) {
-                Ok(host_port) => {
+        if kernel.get_network_namespace(pid).is_some() {
+            let allocated = kernel.forward_port(pid, guest_port, protocol, preferred, idle_timeout);
+            self.metrics
+                .record_port_forward("create", allocated.is_ok());
+            match allocated {
+                Ok(mapping) => {
                     let response_json = serde_json::json!({
                         "success": true,
                         "pid": pid,
                         "guest_port": guest_port,
-                        "host_port": host_port,
-                        "protocol": format!("{:?}", protocol)
+                        "host_port": mapping.host_port,
+                        "protocol": format!("{:?}", protocol),
+                        "status": mapping.status,
                     });
 
                     let response = Response::from_string(response_json.to_string())
@@ -715,8 +2131,10 @@ impl OsServer {
     ) -> Result<()> {
         let kernel = self.kernel.read().unwrap();
 
-        if let Some(ns) = kernel.get_network_namespace(pid) {
-            match ns.deallocate_port(guest_port) {
+        if kernel.get_network_namespace(pid).is_some() {
+            let removed = kernel.remove_port_forward(pid, guest_port);
+            self.metrics.record_port_forward("delete", removed.is_ok());
+            match removed {
                 Ok(()) => {
                     let response_json = serde_json::json!({
                         "success": true,
@@ -753,14 +2171,23 @@ impl OsServer {
 
         let wasmhub_lang = crate::runtime::runtime_cache::wasmhub_language(language);
 
-        match self.runtime_cache.get_runtime(wasmhub_lang) {
-            Ok(wasm_bytes) => {
+        // Streamed from the cache file; runtimes run to tens of megabytes
+        let runtime = self
+            .runtime_cache
+            .runtime_file(wasmhub_lang)
+            .and_then(|path| Ok(std::fs::File::open(path)?));
+        match runtime {
+            Ok(file) => {
+                let response = Response::from_file(file);
                 self.log_system.log(LogEntry::info(
                     LogSource::Kernel,
-                    format!("Serving {language} runtime ({} bytes)", wasm_bytes.len()),
+                    format!(
+                        "Serving {language} runtime ({} bytes)",
+                        response.data_length().unwrap_or(0)
+                    ),
                 ));
 
-                let response = Response::from_data(wasm_bytes)
+                let response = response
                     .with_header(
                         Header::from_bytes(&b"Content-Type"[..], &b"application/wasm"[..]).unwrap(),
                     )
@@ -811,6 +2238,10 @@ impl OsServer {
             "cached": self.runtime_cache.is_cached(wasmhub_lang),
             "cached_version": self.runtime_cache.cached_version(wasmhub_lang),
         });
+        // The browser runtime starts the main project with these
+        let launch = self.process_table.lock().unwrap().project_config().clone();
+        response_json["env"] = serde_json::json!(launch.env);
+        response_json["args"] = serde_json::json!(launch.args);
 
         if let Ok(manifest) = self.runtime_cache.fetch_manifest() {
             response_json["wasmhub_version"] = serde_json::Value::String(manifest.version);
@@ -900,16 +2331,55 @@ impl OsServer {
     }
 
     fn send_error(&self, request: Request, error_msg: &str) -> Result<()> {
+        self.send_error_status(request, 400, error_msg)
+    }
+
+    fn send_error_status(&self, request: Request, status: u16, error_msg: &str) -> Result<()> {
         let response_json = serde_json::json!({
             "success": false,
             "error": error_msg
         });
 
         let response = Response::from_string(response_json.to_string())
-            .with_status_code(tiny_http::StatusCode(400))
+            .with_status_code(tiny_http::StatusCode(status))
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+            )
+            .with_header(self.cors_header());
+
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 413 for an oversized chunked body, 400 if it could not be read
+    fn send_body_error(&self, request: Request, error: BodyError) -> Result<()> {
+        let status = match error {
+            BodyError::TooLarge(_) => {
+                self.metrics.record_rejection("body");
+                413
+            }
+            BodyError::Io(_) => 400,
+        };
+        self.send_error_status(request, status, &error.to_string())
+    }
+
+    fn send_rate_limited(&self, request: Request, retry_after: Duration) -> Result<()> {
+        let retry_secs = retry_after.as_secs().max(1);
+        let response_json = serde_json::json!({
+            "success": false,
+            "error": format!("Rate limit exceeded; retry in {retry_secs}s")
+        });
+
+        let response = Response::from_string(response_json.to_string())
+            .with_status_code(tiny_http::StatusCode(429))
             .with_header(
                 Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
             )
+            .with_header(
+                Header::from_bytes(&b"Retry-After"[..], retry_secs.to_string().as_bytes()).unwrap(),
+            )
             .with_header(self.cors_header());
 
         request
@@ -941,10 +2411,18 @@ impl OsServer {
                 );
 
                 match self.fetch_from_dev_server(&target_url) {
-                    Ok((content, content_type)) => {
-                        let response = Response::from_string(content).with_header(
-                            Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
-                                .unwrap(),
+                    Ok(Upstream { head, body }) => {
+                        // Relayed as it arrives rather than read whole first
+                        let response = Response::new(
+                            tiny_http::StatusCode(head.status),
+                            vec![Header::from_bytes(
+                                &b"Content-Type"[..],
+                                head.content_type.as_bytes(),
+                            )
+                            .unwrap()],
+                            body,
+                            head.length,
+                            None,
                         );
                         request
                             .respond(response)
@@ -984,9 +2462,10 @@ impl OsServer {
         Ok(())
     }
 
-    /// Fetch content from the dev server
-    fn fetch_from_dev_server(&self, url: &str) -> Result<(String, String)> {
-        use std::io::Read;
+    /// Request `url` from the dev server, returning once its headers have
+    /// arrived; the body is left on the connection to be streamed
+    fn fetch_from_dev_server(&self, url: &str) -> Result<Upstream> {
+        use std::io::BufReader;
         use std::net::TcpStream;
 
         // Parse the URL to get host and path
@@ -1003,34 +2482,17 @@ impl OsServer {
         let mut stream = TcpStream::connect(host)
             .map_err(|e| WasmrunError::from(format!("Failed to connect to dev server: {e}")))?;
 
-        // Send HTTP request
-        let request = format!("GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n");
+        // HTTP/1.0, so the body isn't chunked and ends when the connection
+        // closes
+        let request = format!("GET {path} HTTP/1.0\r\nHost: {host}\r\n\r\n");
         std::io::Write::write_all(&mut stream, request.as_bytes())
             .map_err(|e| WasmrunError::from(format!("Failed to send request: {e}")))?;
 
-        // Read response
-        let mut response = String::new();
-        stream
-            .read_to_string(&mut response)
-            .map_err(|e| WasmrunError::from(format!("Failed to read response: {e}")))?;
-
-        // Parse HTTP response
-        if let Some(header_end) = response.find("\r\n\r\n") {
-            let headers = &response[..header_end];
-            let body = &response[header_end + 4..];
-
-            // Extract content type from headers
-            let content_type = headers
-                .lines()
-                .find(|line| line.to_lowercase().starts_with("content-type:"))
-                .and_then(|line| line.split(':').nth(1))
-                .map(|ct| ct.trim().to_string())
-                .unwrap_or_else(|| "text/html".to_string());
-
-            Ok((body.to_string(), content_type))
-        } else {
-            Err(WasmrunError::from("Invalid HTTP response"))
-        }
+        let mut body = BufReader::new(stream);
+        let head = read_response_head(&mut body)
+            .map_err(|e| WasmrunError::from(format!("Failed to read response: {e}")))?
+            .ok_or_else(|| WasmrunError::from("Invalid HTTP response"))?;
+        Ok(Upstream { head, body })
     }
 
     /// Serve static assets from embedded data
@@ -1106,7 +2568,13 @@ impl OsServer {
             format!("/{file_path}")
         };
 
-        match wasi_fs.read_file(&normalized_path) {
+        let content = if procfs::is_virtual(&normalized_path) {
+            kernel.base_kernel().read_file(&normalized_path)
+        } else {
+            wasi_fs.read_file(&normalized_path)
+        };
+
+        match content {
             Ok(content) => {
                 // Try to detect if it's text or binary
                 let is_text = content
@@ -1177,7 +2645,27 @@ impl OsServer {
             format!("/{dir_path}")
         };
 
-        match wasi_fs.path_readdir(&normalized_path) {
+        // The root and the synthetic /proc and /dev trees are listed by the kernel
+        let entries = if procfs::is_virtual(&normalized_path) || normalized_path == "/" {
+            kernel
+                .base_kernel()
+                .list_directory(&normalized_path)
+                .map(|entries| {
+                    entries
+                        .into_iter()
+                        .map(|e| DirEntry {
+                            name: e.path.rsplit('/').next().unwrap_or_default().to_string(),
+                            is_dir: e.is_directory,
+                            is_file: !e.is_directory,
+                            size: e.size.unwrap_or(0) as u64,
+                        })
+                        .collect::<Vec<_>>()
+                })
+        } else {
+            wasi_fs.path_readdir(&normalized_path)
+        };
+
+        match entries {
             Ok(entries) => {
                 let response_json = serde_json::json!({
                     "success": true,
@@ -1217,13 +2705,244 @@ impl OsServer {
         Ok(())
     }
 
+    /// A file's raw bytes, or a directory packed as `.tar.gz`, as an attachment
+    fn handle_fs_download_request(&self, request: Request, file_path: &str) -> Result<()> {
+        let normalized_path = format!("/{}", file_path.trim_start_matches('/'));
+        let name = normalized_path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|n| !n.is_empty())
+            .unwrap_or("root")
+            .to_string();
+
+        let download = {
+            let kernel = self.kernel.read().unwrap();
+            let wasi_fs = kernel.wasi_filesystem();
+            if procfs::is_virtual(&normalized_path) {
+                kernel
+                    .base_kernel()
+                    .read_file(&normalized_path)
+                    .map(|data| (data, name, "text/plain".to_string()))
+            } else if !wasi_fs.path_exists(&normalized_path) {
+                return self.send_error_status(
+                    request,
+                    404,
+                    &format!("No such file or directory: {normalized_path}"),
+                );
+            } else if wasi_fs
+                .path_filestat_get(&normalized_path)
+                .is_ok_and(|stat| stat.is_dir)
+            {
+                os_bridge::archive_directory(wasi_fs, &normalized_path).map(|data| {
+                    (
+                        data,
+                        format!("{name}.tar.gz"),
+                        "application/gzip".to_string(),
+                    )
+                })
+            } else {
+                wasi_fs.read_file(&normalized_path).map(|data| {
+                    let content_type = get_content_type(Path::new(&name));
+                    (data, name, content_type)
+                })
+            }
+        };
+
+        let (data, filename, content_type) = match download {
+            Ok(download) => download,
+            Err(e) => return self.send_error(request, &e.to_string()),
+        };
+        let response = Response::from_data(data)
+            .with_header(Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).unwrap())
+            .with_header(
+                Header::from_bytes(
+                    &b"Content-Disposition"[..],
+                    os_bridge::attachment_header(&filename).as_bytes(),
+                )
+                .unwrap(),
+            )
+            .with_header(self.cors_header());
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Bounded filename and content search under a VFS directory
+    fn handle_fs_search_request(&self, request: Request, query: &str) -> Result<()> {
+        let query = match vfs_search::SearchQuery::parse(query) {
+            Ok(query) => query,
+            Err(e) => return self.send_error(request, &e.to_string()),
+        };
+        let results = {
+            let kernel = self.kernel.read().unwrap();
+            let wasi_fs = kernel.wasi_filesystem();
+            if !wasi_fs.path_exists(&query.path) {
+                return self.send_error_status(
+                    request,
+                    404,
+                    &format!("No such directory: {}", query.path),
+                );
+            }
+            vfs_search::search(wasi_fs, &query)
+        };
+        match results {
+            Ok(results) => self.send_json(
+                request,
+                200,
+                serde_json::json!({
+                    "success": true,
+                    "query": query.pattern,
+                    "path": query.path,
+                    "files": results.files,
+                    "files_scanned": results.files_scanned,
+                    "truncated": results.truncated,
+                }),
+            ),
+            Err(e) => self.send_error(request, &e.to_string()),
+        }
+    }
+
+    /// A VFS directory packed as `.zip`, as an attachment
+    fn handle_fs_export_request(&self, request: Request, dir_path: &str) -> Result<()> {
+        let normalized_path = format!("/{}", dir_path.trim_start_matches('/'));
+        let name = normalized_path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|n| !n.is_empty())
+            .unwrap_or("root")
+            .to_string();
+
+        let archive = {
+            let kernel = self.kernel.read().unwrap();
+            let wasi_fs = kernel.wasi_filesystem();
+            match wasi_fs.path_filestat_get(&normalized_path) {
+                Ok(stat) if stat.is_dir => os_bridge::zip_directory(wasi_fs, &normalized_path),
+                Ok(_) => {
+                    return self.send_error(request, &format!("Not a directory: {normalized_path}"))
+                }
+                Err(_) => {
+                    return self.send_error_status(
+                        request,
+                        404,
+                        &format!("No such directory: {normalized_path}"),
+                    )
+                }
+            }
+        };
+
+        let data = match archive {
+            Ok(data) => data,
+            Err(e) => return self.send_error(request, &e.to_string()),
+        };
+        let response = Response::from_data(data)
+            .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/zip"[..]).unwrap())
+            .with_header(
+                Header::from_bytes(
+                    &b"Content-Disposition"[..],
+                    os_bridge::attachment_header(&format!("{name}.zip")).as_bytes(),
+                )
+                .unwrap(),
+            )
+            .with_header(self.cors_header());
+        request
+            .respond(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Unpack a `.tar` or `.tar.gz` request body into a VFS directory
+    fn handle_fs_extract_request(&self, mut request: Request, dir_path: &str) -> Result<()> {
+        let body = match read_body(request.as_reader(), self.body_limit) {
+            Ok(body) => body,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let normalized_path = format!("/{}", dir_path.trim_start_matches('/'));
+
+        let result = {
+            let kernel = self.kernel.read().unwrap();
+            os_bridge::extract_archive(kernel.wasi_filesystem(), &normalized_path, &body)
+        };
+        match result {
+            Ok(summary) => self.send_json(
+                request,
+                200,
+                serde_json::json!({
+                    "success": true,
+                    "path": normalized_path,
+                    "files": summary.files,
+                    "directories": summary.directories,
+                    "bytes": summary.bytes,
+                    "skipped": summary.skipped,
+                }),
+            ),
+            Err(e) => self.send_error(request, &e.to_string()),
+        }
+    }
+
+    /// Copy `text`, or the contents of the VFS file at `path`, to the host
+    /// clipboard. 503 when the host has no clipboard tool, so the UI can fall
+    /// back to the browser clipboard.
+    fn handle_clipboard_request(&self, mut request: Request) -> Result<()> {
+        // A cross-site form can POST here without a preflight, so only the
+        // UI's own JSON requests may write to the host clipboard
+        if let Err(e) = same_origin_json(
+            header_value(&request, "Content-Type"),
+            header_value(&request, "Origin"),
+            header_value(&request, "Host"),
+        ) {
+            return self.send_error_status(request, 403, &e);
+        }
+        let content = match read_body(request.as_reader(), self.body_limit) {
+            Ok(content) => content,
+            Err(e) => return self.send_body_error(request, e),
+        };
+        let body: serde_json::Value = match serde_json::from_slice(&content) {
+            Ok(body) => body,
+            Err(e) => return self.send_error(request, &format!("Invalid JSON: {e}")),
+        };
+
+        let text = if let Some(text) = body.get("text").and_then(|t| t.as_str()) {
+            text.to_string()
+        } else if let Some(path) = body.get("path").and_then(|p| p.as_str()) {
+            let path = format!("/{}", path.trim_start_matches('/'));
+            let data = {
+                let kernel = self.kernel.read().unwrap();
+                if procfs::is_virtual(&path) {
+                    kernel.base_kernel().read_file(&path)
+                } else {
+                    kernel.wasi_filesystem().read_file(&path)
+                }
+            };
+            match data.map(String::from_utf8) {
+                Ok(Ok(text)) => text,
+                Ok(Err(_)) => {
+                    return self.send_error(request, &format!("{path} is not a text file"))
+                }
+                Err(e) => return self.send_error_status(request, 404, &e.to_string()),
+            }
+        } else {
+            return self.send_error(request, "Provide 'text' or 'path'");
+        };
+
+        match os_bridge::copy_to_clipboard(&text) {
+            Ok(tool) => self.send_json(
+                request,
+                200,
+                serde_json::json!({ "success": true, "bytes": text.len(), "tool": tool }),
+            ),
+            Err(e) => self.send_error_status(request, 503, &e.to_string()),
+        }
+    }
+
     /// Handle file write request
     fn handle_fs_write_request(&self, mut request: Request, file_path: &str) -> Result<()> {
-        // Read the request body
-        let mut body = Vec::new();
-        let mut reader = request.as_reader();
-        std::io::Read::read_to_end(&mut reader, &mut body)
-            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        let body = match read_body(request.as_reader(), self.body_limit) {
+            Ok(body) => body,
+            Err(e) => return self.send_body_error(request, e),
+        };
 
         let kernel = self.kernel.read().unwrap();
         let wasi_fs = kernel.wasi_filesystem();
@@ -1377,11 +3096,20 @@ impl OsServer {
         Ok(())
     }
 
-    fn handle_logs_request(&self, request: Request) -> Result<()> {
-        let logs = self.log_system.get_all();
+    /// The log trail narrowed by `query`; `total` counts every entry and
+    /// `sources` names all the sources seen, for the panel's filters
+    fn handle_logs_request(&self, request: Request, query: &str) -> Result<()> {
+        let query = match log_query::LogQuery::parse(query) {
+            Ok(query) => query,
+            Err(e) => return self.send_error(request, &e.to_string()),
+        };
+        let all = self.log_system.get_all();
+        let logs = query.apply(&all);
         let response_json = serde_json::json!({
             "success": true,
             "count": logs.len(),
+            "total": all.len(),
+            "sources": log_query::sources(&all),
             "logs": logs
         });
 
@@ -1398,6 +3126,25 @@ impl OsServer {
         Ok(())
     }
 
+    /// The last 100 entries and then each new one, as server-sent `log`
+    /// events
+    fn handle_logs_stream_request(&self, request: Request) -> Result<()> {
+        let response = Response::empty(200)
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"text/event-stream"[..]).unwrap(),
+            )
+            .with_header(Header::from_bytes(&b"Cache-Control"[..], &b"no-cache"[..]).unwrap())
+            .with_header(self.cors_header());
+        let events = request
+            .respond_events(response)
+            .map_err(|e| WasmrunError::from(e.to_string()))?;
+        // A client that stops reading is dropped rather than buffered for
+        self.log_system.subscribe(100, move |entry| {
+            serde_json::to_string(entry).is_ok_and(|json| events.send("log", &json))
+        });
+        Ok(())
+    }
+
     fn handle_recent_logs_request(&self, request: Request) -> Result<()> {
         let count = 100;
         let logs = self.log_system.get_recent(count);
@@ -1562,3 +3309,162 @@ impl OsServer {
         Ok(())
     }
 }
+
+/// Paths behind the login when auth is on: everything but the UI shell and
+/// its assets, which the sign-in form needs, and the login and session
+/// endpoints themselves
+fn requires_auth(path: &str) -> bool {
+    let public = matches!(
+        path,
+        "/" | "/os.js" | "/index.css" | "/logging.js" | "/api/auth/login" | "/api/auth/session"
+    ) || path.starts_with("/assets/");
+    !public
+}
+
+/// Accept a request only if it is JSON sent by a page served from this
+/// server: browsers always send `Origin` on a cross-origin POST, and can't
+/// send `application/json` to another site without a CORS preflight
+fn same_origin_json(
+    content_type: Option<&str>,
+    origin: Option<&str>,
+    host: Option<&str>,
+) -> std::result::Result<(), String> {
+    let is_json = content_type.is_some_and(|value| {
+        value
+            .split(';')
+            .next()
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+    });
+    if !is_json {
+        return Err("Content-Type must be application/json".to_string());
+    }
+    let origin_host = origin.and_then(|origin| {
+        origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"))
+    });
+    match (origin_host, host) {
+        (Some(origin_host), Some(host)) if origin_host.eq_ignore_ascii_case(host) => Ok(()),
+        _ => Err("Cross-origin requests are not allowed".to_string()),
+    }
+}
+
+fn header_value<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+/// A dev server response whose body hasn't been read yet
+struct Upstream {
+    head: ResponseHead,
+    body: std::io::BufReader<std::net::TcpStream>,
+}
+
+/// What the OS proxy keeps from a dev server response's status line and
+/// headers
+#[derive(Debug, PartialEq, Eq)]
+struct ResponseHead {
+    status: u16,
+    content_type: String,
+    length: Option<usize>,
+}
+
+/// Read a response's status line and headers, leaving `reader` at the
+/// start of the body; `None` when they aren't valid HTTP
+fn read_response_head(reader: &mut impl std::io::BufRead) -> std::io::Result<Option<ResponseHead>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let Some(status) = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+    else {
+        return Ok(None);
+    };
+
+    let mut head = ResponseHead {
+        status,
+        content_type: "text/html".to_string(),
+        length: None,
+    };
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            // The connection closed before the headers ended
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            return Ok(Some(head));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-type") {
+                head.content_type = value.trim().to_string();
+            } else if name.eq_ignore_ascii_case("content-length") {
+                head.length = value.trim().parse().ok();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_response_head_leaves_the_body() {
+        let mut reader = std::io::Cursor::new(
+            &b"HTTP/1.0 404 Not Found\r\nContent-Type: application/wasm\r\ncontent-length: 4\r\n\r\n\0asm"[..],
+        );
+        let head = read_response_head(&mut reader).unwrap().unwrap();
+        assert_eq!(
+            head,
+            ResponseHead {
+                status: 404,
+                content_type: "application/wasm".to_string(),
+                length: Some(4),
+            }
+        );
+        let mut body = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut body).unwrap();
+        assert_eq!(body, b"\0asm");
+
+        let mut truncated = std::io::Cursor::new(&b"HTTP/1.0 200 OK\r\nServer: x\r\n"[..]);
+        assert_eq!(read_response_head(&mut truncated).unwrap(), None);
+        let mut garbage = std::io::Cursor::new(&b"hello\r\n\r\n"[..]);
+        assert_eq!(read_response_head(&mut garbage).unwrap(), None);
+    }
+
+    #[test]
+    fn test_clipboard_accepts_only_same_origin_json() {
+        let host = Some("127.0.0.1:8420");
+        let json = Some("application/json; charset=utf-8");
+        let origin = Some("http://127.0.0.1:8420");
+        assert!(same_origin_json(json, origin, host).is_ok());
+        assert!(same_origin_json(Some("text/plain"), origin, host).is_err());
+        assert!(same_origin_json(None, origin, host).is_err());
+        assert!(same_origin_json(json, Some("http://evil.example"), host).is_err());
+        assert!(same_origin_json(json, Some("null"), host).is_err());
+        assert!(same_origin_json(json, None, host).is_err());
+    }
+
+    #[test]
+    fn test_everything_but_the_sign_in_page_requires_auth() {
+        for public in ["/", "/os.js", "/assets/logo.png", "/api/auth/login"] {
+            assert!(!requires_auth(public), "{public}");
+        }
+        for private in [
+            "/app/",
+            "/app/index.html",
+            "/logs",
+            "/metrics",
+            "/ws",
+            "/api/fs/list/",
+        ] {
+            assert!(requires_auth(private), "{private}");
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Persisted process definitions for OS mode
+//!
+//! Processes launched through `POST /api/processes` are written to
+//! `~/.wasmrun/os/<project>/processes.json` so they survive a server restart.
+//! On boot, definitions with `restart_policy = "on-boot"` are launched again;
+//! the rest are listed but stay stopped until started from the API.
+//! Supervisor groups, and the env and arguments set for the main project
+//! from the UI, are saved in the same file.
+
+use crate::config::WasmrunConfig;
+use crate::error::{Result, WasmrunError};
+use crate::runtime::microkernel::Pid;
+use crate::runtime::supervisor::GroupSpec;
+use crate::runtime::wasi_fs::MountSpec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Keep the definition but leave it stopped after a restart
+    #[default]
+    Never,
+    /// Launch again when the OS server boots
+    OnBoot,
+}
+
+impl RestartPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "never" | "no" => Some(Self::Never),
+            "on-boot" | "always" => Some(Self::OnBoot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSpec {
+    pub name: String,
+    pub language: String,
+    pub project_path: String,
+    /// Entry file to run instead of the project's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Arguments passed after the entry file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub mounts: Vec<MountSpec>,
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    /// PID of the current run; cleared when the table is loaded, since PIDs
+    /// don't survive a restart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<Pid>,
+}
+
+/// Environment and arguments a process is started with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LaunchConfig {
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl LaunchConfig {
+    pub fn is_empty(&self) -> bool {
+        self.env.is_empty() && self.args.is_empty()
+    }
+
+    /// Reject variable names a process environment can't hold
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for key in self.env.keys() {
+            if key.is_empty() || key.contains('=') || key.contains('\0') {
+                return Err(format!("Invalid environment variable name '{key}'"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TableFile {
+    processes: Vec<ProcessSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<GroupSpec>,
+    #[serde(default, skip_serializing_if = "LaunchConfig::is_empty")]
+    project: LaunchConfig,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessTable {
+    /// `None` keeps the table in memory only
+    path: Option<PathBuf>,
+    specs: Vec<ProcessSpec>,
+    groups: Vec<GroupSpec>,
+    project: LaunchConfig,
+}
+
+impl ProcessTable {
+    /// Where the table for `project_path` lives: one directory per project,
+    /// named after it and disambiguated by a hash of its absolute path
+    pub fn path_for_project(project_path: &str) -> Result<PathBuf> {
+        let absolute = fs::canonicalize(project_path)
+            .unwrap_or_else(|_| PathBuf::from(project_path))
+            .to_string_lossy()
+            .to_string();
+        let name = Path::new(&absolute)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "project".to_string());
+        let digest = Sha256::digest(absolute.as_bytes());
+        let hash: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+
+        Ok(WasmrunConfig::config_dir()?
+            .join("os")
+            .join(format!("{name}-{hash}"))
+            .join("processes.json"))
+    }
+
+    /// Load the table at `path`; a missing file is an empty table
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let (specs, groups, project) = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| WasmrunError::add_context(format!("Reading {}", path.display()), e))?;
+            let file: TableFile = serde_json::from_str(&content).map_err(|e| {
+                WasmrunError::from(format!("Invalid process table {}: {e}", path.display()))
+            })?;
+            let specs = file
+                .processes
+                .into_iter()
+                .map(|spec| ProcessSpec { pid: None, ..spec })
+                .collect();
+            (specs, file.groups, file.project)
+        } else {
+            (Vec::new(), Vec::new(), LaunchConfig::default())
+        };
+        Ok(Self {
+            path: Some(path),
+            specs,
+            groups,
+            project,
+        })
+    }
+
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// File the table is saved to, if it is persisted
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn specs(&self) -> &[ProcessSpec] {
+        &self.specs
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProcessSpec> {
+        self.specs.iter().find(|s| s.name == name)
+    }
+
+    pub fn find_by_pid(&self, pid: Pid) -> Option<&ProcessSpec> {
+        self.specs.iter().find(|s| s.pid == Some(pid))
+    }
+
+    pub fn groups(&self) -> &[GroupSpec] {
+        &self.groups
+    }
+
+    pub fn set_groups(&mut self, groups: Vec<GroupSpec>) -> Result<()> {
+        self.groups = groups;
+        self.save()
+    }
+
+    /// Env and arguments for the main project process
+    pub fn project_config(&self) -> &LaunchConfig {
+        &self.project
+    }
+
+    pub fn set_project_config(&mut self, config: LaunchConfig) -> Result<()> {
+        self.project = config;
+        self.save()
+    }
+
+    /// Definitions to launch when the server boots
+    pub fn boot_specs(&self) -> Vec<ProcessSpec> {
+        self.specs
+            .iter()
+            .filter(|s| s.restart_policy == RestartPolicy::OnBoot)
+            .cloned()
+            .collect()
+    }
+
+    /// Add or replace the definition with the same name
+    pub fn upsert(&mut self, spec: ProcessSpec) -> Result<()> {
+        match self.specs.iter_mut().find(|s| s.name == spec.name) {
+            Some(existing) => *existing = spec,
+            None => self.specs.push(spec),
+        }
+        self.save()
+    }
+
+    pub fn set_pid(&mut self, name: &str, pid: Option<Pid>) -> Result<()> {
+        if let Some(spec) = self.specs.iter_mut().find(|s| s.name == name) {
+            spec.pid = pid;
+        }
+        self.save()
+    }
+
+    pub fn set_restart_policy(&mut self, pid: Pid, policy: RestartPolicy) -> Result<bool> {
+        let Some(spec) = self.specs.iter_mut().find(|s| s.pid == Some(pid)) else {
+            return Ok(false);
+        };
+        spec.restart_policy = policy;
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Replace the env and arguments of the definition running as `pid`,
+    /// returning the updated definition
+    pub fn set_launch_config(
+        &mut self,
+        pid: Pid,
+        config: LaunchConfig,
+    ) -> Result<Option<ProcessSpec>> {
+        let Some(spec) = self.specs.iter_mut().find(|s| s.pid == Some(pid)) else {
+            return Ok(None);
+        };
+        spec.env = config.env;
+        spec.args = config.args;
+        let spec = spec.clone();
+        self.save()?;
+        Ok(Some(spec))
+    }
+
+    pub fn remove_by_pid(&mut self, pid: Pid) -> Result<Option<ProcessSpec>> {
+        let Some(index) = self.specs.iter().position(|s| s.pid == Some(pid)) else {
+            return Ok(None);
+        };
+        let spec = self.specs.remove(index);
+        self.save()?;
+        Ok(Some(spec))
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = TableFile {
+            processes: self.specs.clone(),
+            groups: self.groups.clone(),
+            project: self.project.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| WasmrunError::from(format!("Failed to encode process table: {e}")))?;
+        fs::write(path, content)
+            .map_err(|e| WasmrunError::add_context(format!("Writing {}", path.display()), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, policy: RestartPolicy) -> ProcessSpec {
+        ProcessSpec {
+            name: name.to_string(),
+            language: "nodejs".to_string(),
+            project_path: "/srv/app".to_string(),
+            command: Some("worker.js".to_string()),
+            env: BTreeMap::from([("MODE".to_string(), "queue".to_string())]),
+            args: Vec::new(),
+            mounts: vec![MountSpec::parse("shared:/data:ro").unwrap()],
+            restart_policy: policy,
+            pid: None,
+        }
+    }
+
+    #[test]
+    fn test_table_round_trips_and_forgets_pids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("os").join("processes.json");
+
+        let mut table = ProcessTable::load(path.clone()).unwrap();
+        assert!(table.specs().is_empty());
+        table.upsert(spec("worker", RestartPolicy::OnBoot)).unwrap();
+        table.upsert(spec("cron", RestartPolicy::Never)).unwrap();
+        table.set_pid("worker", Some(4)).unwrap();
+        assert_eq!(table.find_by_pid(4).unwrap().name, "worker");
+
+        let reloaded = ProcessTable::load(path.clone()).unwrap();
+        assert_eq!(reloaded.specs().len(), 2);
+        assert_eq!(reloaded.get("worker").unwrap().pid, None);
+        assert_eq!(reloaded.get("worker").unwrap().env["MODE"], "queue");
+        assert_eq!(
+            reloaded.get("worker").unwrap().mounts[0].volume.as_deref(),
+            Some("shared")
+        );
+        let boot: Vec<_> = reloaded.boot_specs().into_iter().map(|s| s.name).collect();
+        assert_eq!(boot, vec!["worker"]);
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("\"restart_policy\": \"on-boot\""));
+    }
+
+    #[test]
+    fn test_policy_update_and_removal_by_pid() {
+        let mut table = ProcessTable::in_memory();
+        table.upsert(spec("worker", RestartPolicy::Never)).unwrap();
+        table.set_pid("worker", Some(7)).unwrap();
+
+        assert!(table.set_restart_policy(7, RestartPolicy::OnBoot).unwrap());
+        assert!(!table.set_restart_policy(8, RestartPolicy::OnBoot).unwrap());
+        assert_eq!(table.boot_specs().len(), 1);
+
+        assert_eq!(table.remove_by_pid(7).unwrap().unwrap().name, "worker");
+        assert!(table.specs().is_empty());
+        assert_eq!(RestartPolicy::parse("always"), Some(RestartPolicy::OnBoot));
+        assert_eq!(RestartPolicy::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn test_launch_config_updates_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("processes.json");
+        let mut table = ProcessTable::load(path.clone()).unwrap();
+        table.upsert(spec("worker", RestartPolicy::Never)).unwrap();
+        table.set_pid("worker", Some(3)).unwrap();
+
+        let config = LaunchConfig {
+            env: BTreeMap::from([("PORT".to_string(), "9000".to_string())]),
+            args: vec!["--verbose".to_string()],
+        };
+        let updated = table.set_launch_config(3, config.clone()).unwrap().unwrap();
+        assert_eq!(updated.env["PORT"], "9000");
+        assert!(!updated.env.contains_key("MODE"));
+        assert!(table
+            .set_launch_config(4, config.clone())
+            .unwrap()
+            .is_none());
+        table.set_project_config(config.clone()).unwrap();
+
+        let reloaded = ProcessTable::load(path).unwrap();
+        assert_eq!(reloaded.get("worker").unwrap().args, vec!["--verbose"]);
+        assert_eq!(reloaded.project_config(), &config);
+
+        let bad = LaunchConfig {
+            env: BTreeMap::from([("A=B".to_string(), String::new())]),
+            args: Vec::new(),
+        };
+        assert!(bad.validate().is_err());
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,269 @@
+//! Synthetic `/proc` and `/dev` trees for the kernel VFS
+//!
+//! Nothing here is backed by the host filesystem. `/proc` is generated from
+//! the kernel's process table on every read, and `/dev` holds the handful of
+//! character devices guest programs commonly expect. Both trees are
+//! read-only apart from writes to the sink devices.
+
+use anyhow::Result;
+use std::fmt::Write as _;
+
+use crate::runtime::microkernel::{Pid, Process, ProcessState, VfsEntry, WasmMicroKernel};
+
+/// Bytes returned by a whole-file read of an endless device such as `/dev/zero`
+const DEVICE_BLOCK: usize = 4096;
+
+/// Memory a wasm32 process can address, reported as `MemTotal`
+const WASM32_ADDRESS_SPACE_KB: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Null,
+    Zero,
+    Random,
+    Urandom,
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+const DEVICES: &[(&str, Device)] = &[
+    ("null", Device::Null),
+    ("zero", Device::Zero),
+    ("random", Device::Random),
+    ("urandom", Device::Urandom),
+    ("stdin", Device::Stdin),
+    ("stdout", Device::Stdout),
+    ("stderr", Device::Stderr),
+];
+
+impl Device {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let name = path.trim_end_matches('/').strip_prefix("/dev/")?;
+        DEVICES.iter().find(|(n, _)| *n == name).map(|(_, d)| *d)
+    }
+
+    /// Up to `count` bytes from the device
+    pub fn read(self, count: usize) -> Result<Vec<u8>> {
+        Ok(match self {
+            Device::Zero => vec![0; count],
+            Device::Random | Device::Urandom => random_bytes(count)?,
+            // No input is attached, and the output streams are write-only
+            Device::Null | Device::Stdin | Device::Stdout | Device::Stderr => Vec::new(),
+        })
+    }
+}
+
+/// Whether `path` lies in one of the synthetic trees
+pub fn is_virtual(path: &str) -> bool {
+    ["/proc", "/dev"]
+        .iter()
+        .any(|root| path == *root || path.starts_with(&format!("{root}/")))
+}
+
+/// Rewrite `/proc/self` to the calling process
+pub fn resolve_self(path: &str, pid: Pid) -> String {
+    match path.strip_prefix("/proc/self") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("/proc/{pid}{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+/// Contents of a `/proc` file or one block of a `/dev` device
+pub fn read(kernel: &WasmMicroKernel, path: &str) -> Result<Vec<u8>> {
+    if let Some(device) = Device::from_path(path) {
+        return device.read(DEVICE_BLOCK);
+    }
+
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    let content = match segments.as_slice() {
+        ["", "proc", "meminfo"] => meminfo(&live_processes(kernel)),
+        ["", "proc", "mounts"] => mounts(kernel),
+        ["", "proc", pid, "status"] => status(&find_process(kernel, pid, path)?),
+        ["", "proc", pid, "cmdline"] => format!("{}\0", find_process(kernel, pid, path)?.name),
+        _ if list(kernel, path).is_ok() => anyhow::bail!("Is a directory: {path}"),
+        _ => anyhow::bail!("No such file: {path}"),
+    };
+    Ok(content.into_bytes())
+}
+
+/// Entries of `/proc`, `/proc/<pid>` or `/dev`
+pub fn list(kernel: &WasmMicroKernel, path: &str) -> Result<Vec<VfsEntry>> {
+    let path = path.trim_end_matches('/');
+    let segments: Vec<&str> = path.split('/').collect();
+    let (dirs, files): (Vec<String>, Vec<String>) = match segments.as_slice() {
+        ["", "proc"] => {
+            let mut pids: Vec<Pid> = live_processes(kernel).iter().map(|p| p.pid).collect();
+            pids.sort_unstable();
+            (
+                pids.iter().map(|p| p.to_string()).collect(),
+                vec!["meminfo".to_string(), "mounts".to_string()],
+            )
+        }
+        ["", "proc", pid] => {
+            find_process(kernel, pid, path)?;
+            (
+                Vec::new(),
+                vec!["cmdline".to_string(), "status".to_string()],
+            )
+        }
+        ["", "dev"] => (
+            Vec::new(),
+            DEVICES.iter().map(|(name, _)| name.to_string()).collect(),
+        ),
+        _ => anyhow::bail!("Not a directory: {path}"),
+    };
+
+    let now = chrono::Utc::now();
+    let entry = |name: &str, is_directory: bool| VfsEntry {
+        path: format!("{path}/{name}"),
+        is_directory,
+        size: if is_directory { None } else { Some(0) },
+        created_at: now,
+        modified_at: now,
+    };
+    Ok(dirs
+        .iter()
+        .map(|d| entry(d, true))
+        .chain(files.iter().map(|f| entry(f, false)))
+        .collect())
+}
+
+/// Apply a write to a `/dev` sink. Returns the bytes to pass through to the
+/// host stream for `/dev/stdout` and `/dev/stderr`.
+pub fn write<'a>(path: &str, data: &'a [u8]) -> Result<Option<&'a [u8]>> {
+    match Device::from_path(path) {
+        Some(Device::Stdout | Device::Stderr) => Ok(Some(data)),
+        // Writes to the random devices would mix in entropy on Linux; here
+        // they are accepted and dropped like /dev/null
+        Some(Device::Null | Device::Zero | Device::Random | Device::Urandom) => Ok(None),
+        Some(Device::Stdin) => anyhow::bail!("/dev/stdin is not writable"),
+        None => anyhow::bail!("Read-only file system: {path}"),
+    }
+}
+
+fn live_processes(kernel: &WasmMicroKernel) -> Vec<Process> {
+    kernel
+        .list_processes()
+        .into_iter()
+        .filter(|p| p.state != ProcessState::Terminated)
+        .collect()
+}
+
+fn find_process(kernel: &WasmMicroKernel, pid: &str, path: &str) -> Result<Process> {
+    pid.parse::<Pid>()
+        .ok()
+        .and_then(|pid| kernel.get_process(pid))
+        .filter(|p| p.state != ProcessState::Terminated)
+        .ok_or_else(|| anyhow::anyhow!("No such process: {path}"))
+}
+
+fn status(process: &Process) -> String {
+    let state = match process.state {
+        ProcessState::Ready => "R (ready)",
+        ProcessState::Running => "R (running)",
+        ProcessState::Blocked => "S (blocked)",
+        ProcessState::Terminated => "Z (terminated)",
+    };
+    let mut out = String::new();
+    let _ = writeln!(out, "Name:\t{}", process.name);
+    let _ = writeln!(out, "State:\t{state}");
+    let _ = writeln!(out, "Pid:\t{}", process.pid);
+    let _ = writeln!(out, "PPid:\t{}", process.parent_pid.unwrap_or(0));
+    let _ = writeln!(out, "Language:\t{}", process.language);
+    let _ = writeln!(out, "VmRSS:\t{} kB", process.memory_usage.div_ceil(1024));
+    let _ = writeln!(out, "Started:\t{}", process.created_at.to_rfc3339());
+    out
+}
+
+fn meminfo(processes: &[Process]) -> String {
+    let used_kb: usize = processes
+        .iter()
+        .map(|p| p.memory_usage.div_ceil(1024))
+        .sum();
+    let free_kb = WASM32_ADDRESS_SPACE_KB.saturating_sub(used_kb);
+    let mut out = String::new();
+    let _ = writeln!(out, "MemTotal:       {WASM32_ADDRESS_SPACE_KB:>8} kB");
+    let _ = writeln!(out, "MemFree:        {free_kb:>8} kB");
+    let _ = writeln!(out, "MemAvailable:   {free_kb:>8} kB");
+    let _ = writeln!(out, "MemUsed:        {used_kb:>8} kB");
+    let _ = writeln!(out, "Processes:      {:>8}", processes.len());
+    out
+}
+
+/// Guest mount points only; host paths stay hidden from the guest
+fn mounts(kernel: &WasmMicroKernel) -> String {
+    let mut out = String::new();
+    for (guest, read_only) in kernel.wasi_filesystem().visible_mounts() {
+        let mode = if read_only { "ro" } else { "rw" };
+        let _ = writeln!(out, "wasifs {guest} wasifs {mode} 0 0");
+    }
+    let _ = writeln!(out, "proc /proc proc ro 0 0");
+    let _ = writeln!(out, "devfs /dev devfs rw 0 0");
+    out
+}
+
+/// Bytes from the OS RNG. A failure is an I/O error for the guest: a
+/// stand-in would be predictable yet read as random.
+fn random_bytes(count: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0; count];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| anyhow::anyhow!("Input/output error: no entropy from the OS: {e}"))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_reflects_process_table() {
+        let kernel = WasmMicroKernel::new();
+        let pid = kernel
+            .create_process("api".into(), "nodejs".into(), None)
+            .unwrap();
+        kernel.load_wasm_module(pid, &[0; 2048]).unwrap();
+
+        let status =
+            String::from_utf8(read(&kernel, &format!("/proc/{pid}/status")).unwrap()).unwrap();
+        assert!(status.contains("Name:\tapi\n"));
+        assert!(status.contains("State:\tR (running)\n"));
+        assert!(status.contains("VmRSS:\t2 kB\n"));
+
+        let meminfo = String::from_utf8(read(&kernel, "/proc/meminfo").unwrap()).unwrap();
+        assert!(meminfo.contains("MemUsed:               2 kB\n"));
+
+        let names: Vec<String> = list(&kernel, "/proc")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert!(names.contains(&format!("/proc/{pid}")));
+        assert!(names.contains(&"/proc/meminfo".to_string()));
+
+        kernel.kill_process(pid).unwrap();
+        assert!(read(&kernel, &format!("/proc/{pid}/status")).is_err());
+        assert!(read(&kernel, "/proc").is_err());
+    }
+
+    #[test]
+    fn test_devices() {
+        assert_eq!(Device::Zero.read(4).unwrap(), vec![0; 4]);
+        assert_eq!(Device::Random.read(100).unwrap().len(), 100);
+        assert_ne!(
+            Device::Urandom.read(32).unwrap(),
+            Device::Urandom.read(32).unwrap()
+        );
+        assert!(Device::Null.read(10).unwrap().is_empty());
+        assert_eq!(Device::from_path("/dev/null"), Some(Device::Null));
+        assert_eq!(Device::from_path("/dev/sda"), None);
+
+        assert_eq!(write("/dev/stdout", b"hi").unwrap(), Some(&b"hi"[..]));
+        assert_eq!(write("/dev/null", b"hi").unwrap(), None);
+        assert!(write("/proc/meminfo", b"x").is_err());
+
+        assert_eq!(resolve_self("/proc/self/status", 7), "/proc/7/status");
+        assert_eq!(resolve_self("/proc/selfish", 7), "/proc/selfish");
+        assert!(is_virtual("/dev") && is_virtual("/proc/1") && !is_virtual("/devices"));
+    }
+}
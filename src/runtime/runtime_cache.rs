@@ -1,8 +1,9 @@
 use crate::error::{Result, WasmrunError};
+use crate::ui::progress;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// The wasmhub release wasmrun is pinned to. Must match the tag in
 /// `WASMHUB_BASE_URL`; cached runtimes downloaded from a different release
@@ -132,8 +133,10 @@ impl RuntimeCache {
 
     fn download_runtime(&self, language: &str, filename: &str) -> Result<Vec<u8>> {
         let url = format!("{}/{filename}", wasmhub_base_url());
-        println!("📦 Downloading {language} runtime from wasmhub...");
-        http_get_bytes(&url)
+        http_get_bytes(
+            &url,
+            &format!("Downloading {language} runtime from wasmhub"),
+        )
     }
 
     fn validate_checksum(&self, data: &[u8], expected_sha256: &str, filename: &str) -> Result<()> {
@@ -146,7 +149,29 @@ impl RuntimeCache {
         Ok(())
     }
 
+    /// Path of `language`'s runtime in the cache, downloading it first when
+    /// it isn't there, so it can be streamed rather than read into memory
+    pub fn runtime_file(&self, language: &str) -> Result<PathBuf> {
+        if let Some(path) = self.cached_runtime(language)? {
+            return Ok(path);
+        }
+        self.get_runtime(language)?;
+        self.cached_runtime(language)?
+            .ok_or_else(|| WasmrunError::from(format!("The {language} runtime wasn't cached")))
+    }
+
     fn load_from_cache(&self, language: &str) -> Result<Option<Vec<u8>>> {
+        let Some(wasm_path) = self.cached_runtime(language)? else {
+            return Ok(None);
+        };
+        fs::read(&wasm_path)
+            .map(Some)
+            .map_err(|e| WasmrunError::from(format!("Failed to read cached runtime: {e}")))
+    }
+
+    /// The cached runtime file for `language`, if it's from the pinned
+    /// release and its checksum still matches
+    fn cached_runtime(&self, language: &str) -> Result<Option<PathBuf>> {
         let meta_path = self.cache_dir.join(format!("{language}.json"));
         if !meta_path.exists() {
             return Ok(None);
@@ -178,10 +203,8 @@ impl RuntimeCache {
             return Ok(None);
         }
 
-        let wasm_bytes = fs::read(&wasm_path)
+        let (actual_sha, size) = file_sha256_hex(&wasm_path)
             .map_err(|e| WasmrunError::from(format!("Failed to read cached runtime: {e}")))?;
-
-        let actual_sha = sha256_hex(&wasm_bytes);
         if actual_sha != meta.sha256 {
             println!("⚠️ Cache integrity check failed for {language}, re-downloading...");
             return Ok(None);
@@ -190,10 +213,10 @@ impl RuntimeCache {
         println!(
             "✅ Using cached {language} runtime v{} ({})",
             meta.version,
-            format_bytes(wasm_bytes.len())
+            format_bytes(size as usize)
         );
 
-        Ok(Some(wasm_bytes))
+        Ok(Some(wasm_path))
     }
 
     fn save_to_cache(
@@ -308,16 +331,15 @@ fn http_get_string(url: &str) -> Result<String> {
     Ok(buf)
 }
 
-fn http_get_bytes(url: &str) -> Result<Vec<u8>> {
+fn http_get_bytes(url: &str, message: &str) -> Result<Vec<u8>> {
     let body = ureq::get(url)
         .call()
         .map_err(|e| WasmrunError::from(format!("HTTP request failed for {url}: {e}")))?
         .into_body();
 
-    let mut buf = Vec::new();
-    std::io::Read::read_to_end(&mut body.into_reader(), &mut buf)
-        .map_err(|e| WasmrunError::from(format!("Failed to read response body: {e}")))?;
-    Ok(buf)
+    let total = body.content_length();
+    progress::download(body.into_reader(), total, u64::MAX, message)
+        .map_err(|e| WasmrunError::from(format!("Failed to read response body: {e}")))
 }
 
 fn sha256_hex(data: &[u8]) -> String {
@@ -326,6 +348,15 @@ fn sha256_hex(data: &[u8]) -> String {
     hash.iter().map(|b| format!("{b:02x}")).collect()
 }
 
+/// [`sha256_hex`] of a file and its size, reading it in chunks
+fn file_sha256_hex(path: &Path) -> std::io::Result<(String, u64)> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    let hash = hasher.finalize();
+    Ok((hash.iter().map(|b| format!("{b:02x}")).collect(), size))
+}
+
 fn format_bytes(bytes: usize) -> String {
     if bytes < 1024 {
         format!("{bytes} B")
@@ -360,6 +391,7 @@ pub fn language_for_project(project_path: &str) -> Result<String> {
 }
 
 /// Maps project language names to wasmhub runtime identifiers.
+#[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
 pub fn wasmhub_language(language: &str) -> &str {
     match language {
         "nodejs" | "javascript" | "js" => "nodejs",
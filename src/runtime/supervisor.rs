@@ -0,0 +1,380 @@
+//! Supervisor groups for OS mode processes
+//!
+//! A group names a set of saved process definitions (see
+//! [`process_table`](super::process_table)) and decides what happens when one
+//! of them exits: restart it after an exponential backoff, or leave it down.
+//! Exits come from the browser runtime (`POST /api/processes/<pid>/exit`),
+//! from a member's dev server dying, or from its kernel process disappearing.
+
+use crate::runtime::microkernel::Pid;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Longest wait between restarts, however many have failed in a row
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A member that stays up this long gets its restart count reset
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SupervisorRestart {
+    /// Restart after any exit, clean or not
+    Always,
+    /// Restart only after a non-zero exit
+    #[default]
+    OnFailure,
+    /// Record the exit and leave the process down
+    Never,
+}
+
+impl SupervisorRestart {
+    fn should_restart(self, exit_code: i32) -> bool {
+        match self {
+            Self::Always => true,
+            Self::OnFailure => exit_code != 0,
+            Self::Never => false,
+        }
+    }
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_backoff_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupSpec {
+    pub name: String,
+    #[serde(default)]
+    pub restart: SupervisorRestart,
+    /// Consecutive restarts before the supervisor gives up; 0 means unlimited
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    /// Delay before the first restart, doubled for each one after it
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+    /// Names of process definitions in this group
+    pub processes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemberStatus {
+    /// Not launched since the server started
+    Stopped,
+    Running,
+    /// Waiting out the backoff before the next restart
+    Backoff,
+    /// Exited and the policy says to leave it down
+    Exited,
+    /// Hit `max_restarts`
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+struct MemberState {
+    pid: Option<Pid>,
+    status: MemberStatus,
+    restarts: u32,
+    last_exit: Option<i32>,
+    last_error: Option<String>,
+    started_at: Option<Instant>,
+    restart_at: Option<Instant>,
+}
+
+impl Default for MemberState {
+    fn default() -> Self {
+        Self {
+            pid: None,
+            status: MemberStatus::Stopped,
+            restarts: 0,
+            last_exit: None,
+            last_error: None,
+            started_at: None,
+            restart_at: None,
+        }
+    }
+}
+
+/// What `GET /api/supervisor` reports for one member
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberReport {
+    pub name: String,
+    pub pid: Option<Pid>,
+    pub status: MemberStatus,
+    pub restarts: u32,
+    pub last_exit: Option<i32>,
+    pub last_error: Option<String>,
+    /// Milliseconds until the next restart while in backoff
+    pub restart_in_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupReport {
+    #[serde(flatten)]
+    pub spec: GroupSpec,
+    pub members: Vec<MemberReport>,
+}
+
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    groups: Vec<GroupSpec>,
+    members: HashMap<String, MemberState>,
+}
+
+impl Supervisor {
+    pub fn new(groups: Vec<GroupSpec>) -> Self {
+        Self {
+            groups,
+            members: HashMap::new(),
+        }
+    }
+
+    pub fn groups(&self) -> &[GroupSpec] {
+        &self.groups
+    }
+
+    /// Add or replace the group with the same name. A process belongs to at
+    /// most one group, so it is taken out of any other group first.
+    pub fn set_group(&mut self, spec: GroupSpec) {
+        for group in self.groups.iter_mut().filter(|g| g.name != spec.name) {
+            group.processes.retain(|p| !spec.processes.contains(p));
+        }
+        match self.groups.iter_mut().find(|g| g.name == spec.name) {
+            Some(existing) => *existing = spec,
+            None => self.groups.push(spec),
+        }
+    }
+
+    pub fn remove_group(&mut self, name: &str) -> Option<GroupSpec> {
+        let index = self.groups.iter().position(|g| g.name == name)?;
+        let group = self.groups.remove(index);
+        for process in &group.processes {
+            self.members.remove(process);
+        }
+        Some(group)
+    }
+
+    fn group_of(&self, process: &str) -> Option<&GroupSpec> {
+        self.groups
+            .iter()
+            .find(|g| g.processes.iter().any(|p| p == process))
+    }
+
+    pub fn is_supervised(&self, process: &str) -> bool {
+        self.group_of(process).is_some()
+    }
+
+    /// Record that `process` is running as `pid`
+    pub fn started(&mut self, process: &str, pid: Pid, now: Instant) {
+        let member = self.members.entry(process.to_string()).or_default();
+        member.pid = Some(pid);
+        member.status = MemberStatus::Running;
+        member.started_at = Some(now);
+        member.restart_at = None;
+    }
+
+    /// Record a failed launch attempt; counts as a failing exit
+    pub fn launch_failed(&mut self, process: &str, error: String, now: Instant) {
+        self.exited(process, 1, Some(error), now);
+    }
+
+    /// Forget the PID of a member that was stopped on purpose, so its exit
+    /// isn't treated as a crash
+    pub fn stopped(&mut self, pid: Pid) {
+        if let Some(member) = self.members.values_mut().find(|m| m.pid == Some(pid)) {
+            *member = MemberState::default();
+        }
+    }
+
+    /// Name of the supervised member currently running as `pid`
+    pub fn member_for_pid(&self, pid: Pid) -> Option<&str> {
+        self.running_pids()
+            .find(|(_, p)| *p == pid)
+            .map(|(name, _)| name)
+    }
+
+    /// Supervised members believed to be running, with their PIDs
+    pub fn running_pids(&self) -> impl Iterator<Item = (&str, Pid)> {
+        self.members
+            .iter()
+            .filter(|(name, m)| m.status == MemberStatus::Running && self.is_supervised(name))
+            .filter_map(|(name, m)| m.pid.map(|pid| (name.as_str(), pid)))
+    }
+
+    /// Apply the group policy to an exit of `process`
+    pub fn exited(&mut self, process: &str, code: i32, error: Option<String>, now: Instant) {
+        let Some(group) = self.group_of(process).cloned() else {
+            return;
+        };
+        let member = self.members.entry(process.to_string()).or_default();
+        if member
+            .started_at
+            .is_some_and(|started| now.duration_since(started) >= STABLE_AFTER)
+        {
+            member.restarts = 0;
+        }
+        member.pid = None;
+        member.started_at = None;
+        member.last_exit = Some(code);
+        member.last_error = error;
+
+        if !group.restart.should_restart(code) {
+            member.status = MemberStatus::Exited;
+            member.restart_at = None;
+        } else if group.max_restarts > 0 && member.restarts >= group.max_restarts {
+            member.status = MemberStatus::Failed;
+            member.restart_at = None;
+        } else {
+            let backoff = Duration::from_millis(group.backoff_ms)
+                .saturating_mul(2u32.saturating_pow(member.restarts.min(16)))
+                .min(MAX_BACKOFF);
+            member.status = MemberStatus::Backoff;
+            member.restart_at = Some(now + backoff);
+            member.restarts += 1;
+        }
+    }
+
+    /// Members whose backoff has run out; the caller relaunches them and
+    /// reports back through [`started`](Self::started) or
+    /// [`launch_failed`](Self::launch_failed)
+    pub fn due(&self, now: Instant) -> Vec<String> {
+        let mut due: Vec<String> = self
+            .members
+            .iter()
+            .filter(|(_, m)| m.status == MemberStatus::Backoff)
+            .filter(|(_, m)| m.restart_at.is_some_and(|at| at <= now))
+            .map(|(name, _)| name.clone())
+            .collect();
+        due.sort();
+        due
+    }
+
+    pub fn report(&self, now: Instant) -> Vec<GroupReport> {
+        self.groups
+            .iter()
+            .map(|group| GroupReport {
+                spec: group.clone(),
+                members: group
+                    .processes
+                    .iter()
+                    .map(|name| {
+                        let state = self.members.get(name).cloned().unwrap_or_default();
+                        MemberReport {
+                            name: name.clone(),
+                            pid: state.pid,
+                            status: state.status,
+                            restarts: state.restarts,
+                            last_exit: state.last_exit,
+                            last_error: state.last_error,
+                            restart_in_ms: state
+                                .restart_at
+                                .map(|at| at.saturating_duration_since(now).as_millis() as u64),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(restart: SupervisorRestart, max_restarts: u32) -> GroupSpec {
+        GroupSpec {
+            name: "web".to_string(),
+            restart,
+            max_restarts,
+            backoff_ms: 500,
+            processes: vec!["api".to_string(), "worker".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_on_failure_backs_off_then_gives_up() {
+        let mut supervisor = Supervisor::new(vec![group(SupervisorRestart::OnFailure, 2)]);
+        let now = Instant::now();
+
+        supervisor.started("api", 3, now);
+        assert_eq!(supervisor.member_for_pid(3), Some("api"));
+        supervisor.exited("api", 1, Some("dev server crashed".to_string()), now);
+        assert!(supervisor.due(now).is_empty());
+        assert_eq!(
+            supervisor.due(now + Duration::from_millis(500)),
+            vec!["api"]
+        );
+
+        supervisor.started("api", 4, now);
+        supervisor.exited("api", 1, None, now);
+        assert!(supervisor.due(now + Duration::from_millis(999)).is_empty());
+        assert_eq!(
+            supervisor.due(now + Duration::from_millis(1000)),
+            vec!["api"]
+        );
+
+        supervisor.started("api", 5, now);
+        supervisor.exited("api", 1, None, now);
+        let report = supervisor.report(now);
+        let api = &report[0].members[0];
+        assert_eq!(api.status, MemberStatus::Failed);
+        assert_eq!(api.restarts, 2);
+        assert_eq!(report[0].members[1].status, MemberStatus::Stopped);
+
+        // A clean exit is left alone under on-failure
+        supervisor.started("worker", 6, now);
+        supervisor.exited("worker", 0, None, now);
+        assert_eq!(
+            supervisor.report(now)[0].members[1].status,
+            MemberStatus::Exited
+        );
+    }
+
+    #[test]
+    fn test_stable_run_resets_restart_count() {
+        let mut supervisor = Supervisor::new(vec![group(SupervisorRestart::Always, 1)]);
+        let now = Instant::now();
+
+        supervisor.started("api", 3, now);
+        supervisor.exited("api", 0, None, now);
+        supervisor.started("api", 4, now);
+        supervisor.exited("api", 0, None, now + STABLE_AFTER);
+        assert_eq!(
+            supervisor.report(now)[0].members[0].status,
+            MemberStatus::Backoff
+        );
+
+        supervisor.stopped(4);
+        supervisor.started("api", 7, now);
+        supervisor.stopped(7);
+        assert_eq!(supervisor.running_pids().count(), 0);
+    }
+
+    #[test]
+    fn test_process_moves_between_groups() {
+        let mut supervisor = Supervisor::new(vec![group(SupervisorRestart::Never, 0)]);
+        supervisor.set_group(GroupSpec {
+            name: "jobs".to_string(),
+            restart: SupervisorRestart::Always,
+            max_restarts: 0,
+            backoff_ms: 100,
+            processes: vec!["worker".to_string()],
+        });
+
+        assert_eq!(supervisor.groups()[0].processes, vec!["api"]);
+        assert_eq!(supervisor.group_of("worker").unwrap().name, "jobs");
+        assert!(supervisor.remove_group("jobs").is_some());
+        assert!(!supervisor.is_supervised("worker"));
+        let parsed: GroupSpec =
+            serde_json::from_str(r#"{"name": "db", "restart": "always", "processes": []}"#)
+                .unwrap();
+        assert_eq!(parsed.restart, SupervisorRestart::Always);
+        assert_eq!((parsed.max_restarts, parsed.backoff_ms), (5, 1000));
+    }
+}
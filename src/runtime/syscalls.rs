@@ -1,10 +1,15 @@
+use crate::runtime::dns::Resolver;
 use crate::runtime::microkernel::{Pid, SyscallInterface, VfsEntry, WasmMicroKernel};
+use crate::runtime::procfs::{self, Device};
+use crate::runtime::vfs_watch::{WatchEvent, WATCH_RECURSIVE};
+use crate::runtime::virtual_switch::{self, VirtualStream, VirtualSwitch};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read as IoRead, Write as IoWrite};
-use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::net::{IpAddr, Shutdown, SocketAddr, SocketAddrV4, TcpListener, TcpStream, UdpSocket};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// System call numbers for OS mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -30,6 +35,12 @@ pub enum SyscallNumber {
     SockShutdown = 26,
     SockClose = 27,
     GetAddrInfo = 28,
+    Watch = 29,
+    Unwatch = 30,
+    WatchRead = 31,
+    SockSetOpt = 32,
+    SockGetOpt = 33,
+    Poll = 34,
 }
 
 impl TryFrom<u32> for SyscallNumber {
@@ -58,6 +69,12 @@ impl TryFrom<u32> for SyscallNumber {
             26 => Ok(SyscallNumber::SockShutdown),
             27 => Ok(SyscallNumber::SockClose),
             28 => Ok(SyscallNumber::GetAddrInfo),
+            29 => Ok(SyscallNumber::Watch),
+            30 => Ok(SyscallNumber::Unwatch),
+            31 => Ok(SyscallNumber::WatchRead),
+            32 => Ok(SyscallNumber::SockSetOpt),
+            33 => Ok(SyscallNumber::SockGetOpt),
+            34 => Ok(SyscallNumber::Poll),
             _ => Err(anyhow::anyhow!("Unknown syscall number: {value}")),
         }
     }
@@ -92,9 +109,24 @@ pub enum SyscallReturn {
     FileDescriptor(i32),
     ProcessId(Pid),
     VfsEntries(Vec<VfsEntry>),
+    WatchEvents(Vec<WatchEvent>),
+    PollEvents(Vec<PollEvent>),
     Unit,
 }
 
+/// `poll` interest bits: wait for an fd to be readable, writable, or both
+pub const POLL_READ: i64 = 0x1;
+pub const POLL_WRITE: i64 = 0x2;
+
+/// An fd `poll` found ready, or couldn't check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PollEvent {
+    pub fd: i32,
+    pub readable: bool,
+    pub writable: bool,
+    pub error: Option<String>,
+}
+
 /// Address family for sockets
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressFamily {
@@ -135,6 +167,116 @@ impl TryFrom<i64> for SocketType {
     }
 }
 
+/// Options for `sock_setsockopt` and `sock_getsockopt`, numbered as on Linux
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketOption {
+    NoDelay = 1,
+    KeepAlive = 9,
+    RecvTimeout = 20,
+    SendTimeout = 21,
+}
+
+impl TryFrom<i64> for SocketOption {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i64) -> Result<Self> {
+        match value {
+            1 => Ok(SocketOption::NoDelay),
+            9 => Ok(SocketOption::KeepAlive),
+            20 => Ok(SocketOption::RecvTimeout),
+            21 => Ok(SocketOption::SendTimeout),
+            _ => Err(anyhow::anyhow!("Unknown socket option: {value}")),
+        }
+    }
+}
+
+/// Options a guest set on a socket. They're kept on the descriptor because
+/// the host socket doesn't exist until bind or connect, and are applied
+/// again whenever it's replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SocketOptions {
+    pub keepalive: bool,
+    pub nodelay: bool,
+    /// Milliseconds; `None` blocks without limit
+    pub recv_timeout_ms: Option<u64>,
+    pub send_timeout_ms: Option<u64>,
+}
+
+impl SocketOption {
+    fn name(self) -> &'static str {
+        match self {
+            SocketOption::NoDelay => "TCP_NODELAY",
+            SocketOption::KeepAlive => "SO_KEEPALIVE",
+            SocketOption::RecvTimeout => "SO_RCVTIMEO",
+            SocketOption::SendTimeout => "SO_SNDTIMEO",
+        }
+    }
+}
+
+impl SocketOptions {
+    fn get(&self, option: SocketOption) -> i64 {
+        match option {
+            SocketOption::NoDelay => self.nodelay as i64,
+            SocketOption::KeepAlive => self.keepalive as i64,
+            SocketOption::RecvTimeout => self.recv_timeout_ms.unwrap_or(0) as i64,
+            SocketOption::SendTimeout => self.send_timeout_ms.unwrap_or(0) as i64,
+        }
+    }
+
+    /// Timeouts of 0 remove the limit, as with `SO_RCVTIMEO`
+    fn set(&mut self, option: SocketOption, value: i64) {
+        let timeout = (value > 0).then_some(value as u64);
+        match option {
+            SocketOption::NoDelay => self.nodelay = value != 0,
+            SocketOption::KeepAlive => self.keepalive = value != 0,
+            SocketOption::RecvTimeout => self.recv_timeout_ms = timeout,
+            SocketOption::SendTimeout => self.send_timeout_ms = timeout,
+        }
+    }
+
+    /// Set the options on the host socket behind `handle`. Virtual sockets
+    /// have none: their reads never block and their writes never fill up.
+    fn apply(&self, handle: &SocketHandle) -> std::io::Result<()> {
+        match handle {
+            SocketHandle::TcpStream(stream) => {
+                self.apply_to(socket2::SockRef::from(&*stream.lock().unwrap()), true)
+            }
+            SocketHandle::TcpListener(listener) => {
+                self.apply_to(socket2::SockRef::from(&*listener.lock().unwrap()), true)
+            }
+            SocketHandle::UdpSocket(socket) => {
+                self.apply_to(socket2::SockRef::from(&*socket.lock().unwrap()), false)
+            }
+            SocketHandle::Placeholder
+            | SocketHandle::VirtualListener(_)
+            | SocketHandle::VirtualStream(_) => Ok(()),
+        }
+    }
+
+    fn apply_to(&self, socket: socket2::SockRef<'_>, tcp: bool) -> std::io::Result<()> {
+        socket.set_read_timeout(self.recv_timeout_ms.map(Duration::from_millis))?;
+        socket.set_write_timeout(self.send_timeout_ms.map(Duration::from_millis))?;
+        if tcp {
+            socket.set_keepalive(self.keepalive)?;
+            socket.set_tcp_nodelay(self.nodelay)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+/// Whether a non-blocking peek found data, the end of the stream, or an
+/// error the next `sock_recv` will report
+fn peek_ready(peeked: std::io::Result<usize>) -> bool {
+    !matches!(peeked, Err(e) if e.kind() == std::io::ErrorKind::WouldBlock)
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SocketState {
@@ -151,6 +293,9 @@ pub enum SocketHandle {
     TcpListener(Arc<Mutex<TcpListener>>),
     TcpStream(Arc<Mutex<TcpStream>>),
     UdpSocket(Arc<Mutex<UdpSocket>>),
+    /// Bound to the process's address on the virtual network
+    VirtualListener(SocketAddrV4),
+    VirtualStream(Arc<VirtualStream>),
 }
 
 impl Clone for SocketHandle {
@@ -160,6 +305,8 @@ impl Clone for SocketHandle {
             SocketHandle::TcpListener(l) => SocketHandle::TcpListener(Arc::clone(l)),
             SocketHandle::TcpStream(s) => SocketHandle::TcpStream(Arc::clone(s)),
             SocketHandle::UdpSocket(u) => SocketHandle::UdpSocket(Arc::clone(u)),
+            SocketHandle::VirtualListener(addr) => SocketHandle::VirtualListener(*addr),
+            SocketHandle::VirtualStream(s) => SocketHandle::VirtualStream(Arc::clone(s)),
         }
     }
 }
@@ -187,9 +334,27 @@ pub enum FileDescriptor {
         state: SocketState,
         local_addr: Option<SocketAddr>,
         peer_addr: Option<SocketAddr>,
+        /// Totals of successful `sock_recv` and `sock_send` calls
+        bytes_in: u64,
+        bytes_out: u64,
+        options: SocketOptions,
     },
 }
 
+/// An open socket, for `/api/processes/<pid>/sockets`
+#[derive(Debug, Clone, Serialize)]
+pub struct SocketInfo {
+    pub fd: i32,
+    /// `tcp`, `udp`, or `virtual` for the guest-to-guest network
+    pub kind: &'static str,
+    pub state: &'static str,
+    pub local_addr: Option<SocketAddr>,
+    pub peer_addr: Option<SocketAddr>,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub options: SocketOptions,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct OpenFlags {
@@ -286,6 +451,9 @@ impl FileDescriptorTable {
                 state: SocketState::Created,
                 local_addr: None,
                 peer_addr: None,
+                bytes_in: 0,
+                bytes_out: 0,
+                options: SocketOptions::default(),
             },
         );
 
@@ -310,6 +478,11 @@ impl FileDescriptorTable {
 pub struct SyscallHandler {
     kernel: WasmMicroKernel,
     fd_tables: HashMap<Pid, FileDescriptorTable>,
+    switch: VirtualSwitch,
+    resolver: Resolver,
+    /// Connections `poll` took off a host listener to see it was readable,
+    /// handed out by the next `sock_accept` on it
+    accepted: HashMap<(Pid, i32), VecDeque<(TcpStream, SocketAddr)>>,
 }
 
 #[allow(dead_code)]
@@ -318,7 +491,97 @@ impl SyscallHandler {
         Self {
             kernel,
             fd_tables: HashMap::new(),
+            switch: VirtualSwitch::new(),
+            resolver: Resolver::default(),
+            accepted: HashMap::new(),
+        }
+    }
+
+    pub fn set_resolver(&mut self, resolver: Resolver) {
+        self.resolver = resolver;
+    }
+
+    pub fn virtual_switch(&self) -> &VirtualSwitch {
+        &self.switch
+    }
+
+    /// Drop a stopped process's descriptors, closing its sockets and
+    /// virtual listeners
+    pub fn release_process(&mut self, pid: Pid) {
+        self.fd_tables.remove(&pid);
+        self.accepted.retain(|(owner, _), _| *owner != pid);
+        self.switch.release_process(pid);
+    }
+
+    /// Every open socket of `pid`, in fd order
+    pub fn list_sockets(&self, pid: Pid) -> Vec<SocketInfo> {
+        let Some(table) = self.fd_tables.get(&pid) else {
+            return Vec::new();
+        };
+        let mut sockets: Vec<SocketInfo> = table
+            .descriptors
+            .iter()
+            .filter_map(|(fd, descriptor)| match descriptor {
+                FileDescriptor::Socket {
+                    handle,
+                    socket_type,
+                    state,
+                    local_addr,
+                    peer_addr,
+                    bytes_in,
+                    bytes_out,
+                    options,
+                    ..
+                } => Some(SocketInfo {
+                    fd: *fd,
+                    kind: match (handle, socket_type) {
+                        (SocketHandle::VirtualListener(_) | SocketHandle::VirtualStream(_), _) => {
+                            "virtual"
+                        }
+                        (_, SocketType::Stream) => "tcp",
+                        (_, SocketType::Dgram) => "udp",
+                    },
+                    state: match state {
+                        SocketState::Created => "created",
+                        SocketState::Bound => "bound",
+                        SocketState::Listening => "listening",
+                        SocketState::Connected => "connected",
+                    },
+                    local_addr: *local_addr,
+                    peer_addr: *peer_addr,
+                    bytes_in: *bytes_in,
+                    bytes_out: *bytes_out,
+                    options: *options,
+                }),
+                FileDescriptor::File { .. } => None,
+            })
+            .collect();
+        sockets.sort_by_key(|s| s.fd);
+        sockets
+    }
+
+    /// Shut down and close a socket of `pid` on the guest's behalf. Its
+    /// peer sees the connection end; the guest's next call on `fd` fails.
+    pub fn disconnect_socket(&mut self, pid: Pid, fd: i32) -> Result<()> {
+        let table = self
+            .fd_tables
+            .get_mut(&pid)
+            .ok_or_else(|| anyhow::anyhow!("Process {pid} has no open sockets"))?;
+        let handle = match table.get(fd) {
+            Some(FileDescriptor::Socket { handle, .. }) => handle.clone(),
+            _ => return Err(anyhow::anyhow!("fd {fd} of process {pid} is not a socket")),
+        };
+        match &handle {
+            SocketHandle::TcpStream(stream) => {
+                let _ = stream.lock().unwrap().shutdown(Shutdown::Both);
+            }
+            SocketHandle::VirtualStream(stream) => stream.shutdown(Shutdown::Both),
+            SocketHandle::VirtualListener(addr) => self.switch.unlisten(*addr),
+            _ => {}
         }
+        table.close(fd);
+        self.accepted.remove(&(pid, fd));
+        Ok(())
     }
 
     /// Handle a system call from a process
@@ -355,6 +618,12 @@ impl SyscallHandler {
             SyscallNumber::SockShutdown => self.handle_sock_shutdown(pid, args),
             SyscallNumber::SockClose => self.handle_sock_close(pid, args),
             SyscallNumber::GetAddrInfo => self.handle_getaddrinfo(pid, args),
+            SyscallNumber::Watch => self.handle_watch(pid, args),
+            SyscallNumber::Unwatch => self.handle_unwatch(pid, args),
+            SyscallNumber::WatchRead => self.handle_watch_read(pid, args),
+            SyscallNumber::SockSetOpt => self.handle_sock_setsockopt(pid, args),
+            SyscallNumber::SockGetOpt => self.handle_sock_getsockopt(pid, args),
+            SyscallNumber::Poll => self.handle_poll(pid, args),
         }
     }
 
@@ -364,7 +633,7 @@ impl SyscallHandler {
         }
 
         let path = match &args.args[0] {
-            SyscallArg::String(s) => s.clone(),
+            SyscallArg::String(s) => procfs::resolve_self(s, pid),
             _ => return SyscallResult::Error("open: invalid path argument".to_string()),
         };
 
@@ -427,7 +696,15 @@ impl SyscallHandler {
                     );
                 }
 
-                match self.kernel.read_file(path) {
+                // Devices are streams, so the offset doesn't apply
+                if let Some(device) = Device::from_path(path) {
+                    return match device.read(count) {
+                        Ok(data) => SyscallResult::Success(SyscallReturn::Buffer(data)),
+                        Err(e) => SyscallResult::Error(format!("read: {path}: {e}")),
+                    };
+                }
+
+                match self.kernel.process_view(pid).read_file(path) {
                     Ok(data) => {
                         let start = (*offset).min(data.len());
                         let end = (start + count).min(data.len());
@@ -487,7 +764,16 @@ impl SyscallHandler {
                     );
                 }
 
-                match self.kernel.write_file(path, &data) {
+                if matches!(
+                    Device::from_path(path),
+                    Some(Device::Stdout | Device::Stderr)
+                ) {
+                    let output = String::from_utf8_lossy(&data);
+                    println!("[PID {pid}] {output}");
+                    return SyscallResult::Success(SyscallReturn::Number(data.len() as i64));
+                }
+
+                match self.kernel.process_view(pid).write_file(path, &data) {
                     Ok(_) => SyscallResult::Success(SyscallReturn::Number(data.len() as i64)),
                     Err(e) => SyscallResult::Error(format!("write: {e}")),
                 }
@@ -517,6 +803,16 @@ impl SyscallHandler {
             }
         };
 
+        if let Some(FileDescriptor::Socket {
+            handle: SocketHandle::VirtualListener(addr),
+            state: SocketState::Listening,
+            ..
+        }) = fd_table.get(fd)
+        {
+            self.switch.unlisten(*addr);
+        }
+
+        self.accepted.remove(&(pid, fd));
         if fd_table.close(fd) {
             SyscallResult::Success(SyscallReturn::Number(0))
         } else {
@@ -524,7 +820,7 @@ impl SyscallHandler {
         }
     }
 
-    fn handle_mkdir(&mut self, _pid: Pid, args: SyscallArgs) -> SyscallResult {
+    fn handle_mkdir(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
         if args.args.is_empty() {
             return SyscallResult::Error("mkdir: insufficient arguments".to_string());
         }
@@ -534,13 +830,13 @@ impl SyscallHandler {
             _ => return SyscallResult::Error("mkdir: invalid path argument".to_string()),
         };
 
-        match self.kernel.create_directory(path) {
+        match self.kernel.process_view(pid).create_directory(path) {
             Ok(_) => SyscallResult::Success(SyscallReturn::Number(0)),
             Err(e) => SyscallResult::Error(format!("mkdir: {e}")),
         }
     }
 
-    fn handle_rmdir(&mut self, _pid: Pid, args: SyscallArgs) -> SyscallResult {
+    fn handle_rmdir(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
         if args.args.is_empty() {
             return SyscallResult::Error("rmdir: insufficient arguments".to_string());
         }
@@ -550,13 +846,18 @@ impl SyscallHandler {
             _ => return SyscallResult::Error("rmdir: invalid path argument".to_string()),
         };
 
-        match self.kernel.wasi_filesystem().path_remove_directory(path) {
+        match self
+            .kernel
+            .process_view(pid)
+            .wasi_filesystem()
+            .path_remove_directory(path)
+        {
             Ok(_) => SyscallResult::Success(SyscallReturn::Number(0)),
             Err(e) => SyscallResult::Error(format!("rmdir: {e}")),
         }
     }
 
-    fn handle_unlink(&mut self, _pid: Pid, args: SyscallArgs) -> SyscallResult {
+    fn handle_unlink(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
         if args.args.is_empty() {
             return SyscallResult::Error("unlink: insufficient arguments".to_string());
         }
@@ -566,28 +867,96 @@ impl SyscallHandler {
             _ => return SyscallResult::Error("unlink: invalid path argument".to_string()),
         };
 
-        match self.kernel.delete_file(path) {
+        match self.kernel.process_view(pid).delete_file(path) {
             Ok(_) => SyscallResult::Success(SyscallReturn::Number(0)),
             Err(e) => SyscallResult::Error(format!("unlink: {e}")),
         }
     }
 
-    fn handle_stat(&mut self, _pid: Pid, args: SyscallArgs) -> SyscallResult {
+    fn handle_stat(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
         if args.args.is_empty() {
             return SyscallResult::Error("stat: insufficient arguments".to_string());
         }
 
         let path = match &args.args[0] {
-            SyscallArg::String(s) => s,
+            SyscallArg::String(s) => procfs::resolve_self(s, pid),
             _ => return SyscallResult::Error("stat: invalid path argument".to_string()),
         };
 
-        match self.kernel.list_directory(path) {
+        match self.kernel.process_view(pid).list_directory(&path) {
             Ok(entries) => SyscallResult::Success(SyscallReturn::VfsEntries(entries)),
             Err(e) => SyscallResult::Error(format!("stat: {e}")),
         }
     }
 
+    /// Subscribe to changes under a path; returns a watch descriptor
+    fn handle_watch(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
+        if args.args.is_empty() {
+            return SyscallResult::Error("watch: insufficient arguments".to_string());
+        }
+
+        let path = match &args.args[0] {
+            SyscallArg::String(s) => s,
+            _ => return SyscallResult::Error("watch: invalid path argument".to_string()),
+        };
+
+        let flags = match args.args.get(1) {
+            Some(SyscallArg::Number(n)) => *n as u32,
+            None => 0,
+            _ => return SyscallResult::Error("watch: invalid flags argument".to_string()),
+        };
+
+        if procfs::is_virtual(path) {
+            return SyscallResult::Error(format!("watch: {path} cannot be watched"));
+        }
+
+        match self
+            .kernel
+            .process_view(pid)
+            .wasi_filesystem()
+            .add_watch(path, flags & WATCH_RECURSIVE != 0)
+        {
+            Ok(wd) => SyscallResult::Success(SyscallReturn::Number(wd as i64)),
+            Err(e) => SyscallResult::Error(format!("watch: {e}")),
+        }
+    }
+
+    fn handle_unwatch(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
+        let wd = match args.args.first() {
+            Some(SyscallArg::Number(n)) => *n as i32,
+            Some(_) => return SyscallResult::Error("unwatch: invalid wd argument".to_string()),
+            None => return SyscallResult::Error("unwatch: insufficient arguments".to_string()),
+        };
+
+        match self
+            .kernel
+            .process_view(pid)
+            .wasi_filesystem()
+            .remove_watch(wd)
+        {
+            Ok(_) => SyscallResult::Success(SyscallReturn::Number(0)),
+            Err(e) => SyscallResult::Error(format!("unwatch: {e}")),
+        }
+    }
+
+    /// Pending watch events, oldest first; never blocks
+    fn handle_watch_read(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
+        let max = match args.args.first() {
+            Some(SyscallArg::Number(n)) if *n > 0 => *n as usize,
+            Some(SyscallArg::Number(_)) | None => usize::MAX,
+            Some(_) => {
+                return SyscallResult::Error("watch_read: invalid count argument".to_string())
+            }
+        };
+
+        let events = self
+            .kernel
+            .process_view(pid)
+            .wasi_filesystem()
+            .take_watch_events(max);
+        SyscallResult::Success(SyscallReturn::WatchEvents(events))
+    }
+
     fn handle_getpid(&mut self, pid: Pid) -> SyscallResult {
         SyscallResult::Success(SyscallReturn::ProcessId(pid))
     }
@@ -726,12 +1095,40 @@ impl SyscallHandler {
                 handle,
                 state,
                 local_addr,
+                socket_type,
+                options,
                 ..
             } => {
                 if *state != SocketState::Created {
                     return SyscallResult::Error("sock_bind: socket already bound".to_string());
                 }
 
+                if let IpAddr::V4(vip) = ip {
+                    if virtual_switch::is_virtual(ip) {
+                        if virtual_switch::pid_for_ip(vip) != Some(pid) {
+                            return SyscallResult::Error(format!(
+                                "sock_bind: {vip} is not this process's virtual address"
+                            ));
+                        }
+                        if *socket_type != SocketType::Stream {
+                            return SyscallResult::Error(
+                                "sock_bind: the virtual network carries stream sockets only"
+                                    .to_string(),
+                            );
+                        }
+                        let port = if port == 0 {
+                            self.switch.ephemeral_port(vip)
+                        } else {
+                            port
+                        };
+                        let addr = SocketAddrV4::new(vip, port);
+                        *handle = SocketHandle::VirtualListener(addr);
+                        *state = SocketState::Bound;
+                        *local_addr = Some(SocketAddr::V4(addr));
+                        return SyscallResult::Success(SyscallReturn::Number(0));
+                    }
+                }
+
                 let result = match handle {
                     SocketHandle::Placeholder => {
                         let listener = match TcpListener::bind(bind_addr) {
@@ -757,10 +1154,15 @@ impl SyscallHandler {
                         *socket.lock().unwrap() = new_socket;
                         Ok(())
                     }
-                    SocketHandle::TcpStream(_) => Err(anyhow::anyhow!("Cannot bind TCP stream")),
+                    SocketHandle::TcpStream(_) | SocketHandle::VirtualStream(_) => {
+                        Err(anyhow::anyhow!("Cannot bind a connected stream"))
+                    }
+                    SocketHandle::VirtualListener(_) => {
+                        Err(anyhow::anyhow!("Socket already bound"))
+                    }
                 };
 
-                match result {
+                match result.and_then(|_| options.apply(handle).map_err(Into::into)) {
                     Ok(_) => {
                         *state = SocketState::Bound;
                         *local_addr = Some(bind_addr);
@@ -811,6 +1213,13 @@ impl SyscallHandler {
                         *state = SocketState::Listening;
                         SyscallResult::Success(SyscallReturn::Number(0))
                     }
+                    SocketHandle::VirtualListener(addr) => match self.switch.listen(pid, *addr) {
+                        Ok(()) => {
+                            *state = SocketState::Listening;
+                            SyscallResult::Success(SyscallReturn::Number(0))
+                        }
+                        Err(e) => SyscallResult::Error(format!("sock_listen: {e}")),
+                    },
                     _ => SyscallResult::Error("sock_listen: not a TCP socket".to_string()),
                 }
             }
@@ -840,19 +1249,51 @@ impl SyscallHandler {
             None => return SyscallResult::Error(format!("sock_accept: invalid fd: {fd}")),
         };
 
-        let (stream, peer_addr) = match descriptor {
-            FileDescriptor::Socket { handle, state, .. } => {
+        let (stream, peer_addr, local_addr, options) = match descriptor {
+            FileDescriptor::Socket {
+                handle,
+                state,
+                options,
+                ..
+            } => {
                 if *state != SocketState::Listening {
                     return SyscallResult::Error("sock_accept: socket not listening".to_string());
                 }
 
+                let polled = self
+                    .accepted
+                    .get_mut(&(pid, fd))
+                    .and_then(|pending| pending.pop_front());
                 match handle {
                     SocketHandle::TcpListener(listener) => {
-                        match listener.lock().unwrap().accept() {
-                            Ok((stream, addr)) => (stream, addr),
+                        match polled.map_or_else(|| listener.lock().unwrap().accept(), Ok) {
+                            Ok((stream, addr)) => {
+                                let local = stream.local_addr().ok();
+                                (
+                                    SocketHandle::TcpStream(Arc::new(Mutex::new(stream))),
+                                    addr,
+                                    local,
+                                    *options,
+                                )
+                            }
                             Err(e) => return SyscallResult::Error(format!("sock_accept: {e}")),
                         }
                     }
+                    // Never blocks: another guest may be the one to connect
+                    SocketHandle::VirtualListener(addr) => match self.switch.accept(*addr) {
+                        Some(stream) => {
+                            let peer = stream.peer_addr();
+                            (
+                                SocketHandle::VirtualStream(Arc::new(stream)),
+                                peer,
+                                Some(SocketAddr::V4(*addr)),
+                                *options,
+                            )
+                        }
+                        None => {
+                            return SyscallResult::Error("sock_accept: would block".to_string())
+                        }
+                    },
                     _ => {
                         return SyscallResult::Error(
                             "sock_accept: not a listening socket".to_string(),
@@ -865,19 +1306,23 @@ impl SyscallHandler {
             }
         };
 
-        let new_fd = fd_table.open_socket(
-            SocketHandle::TcpStream(Arc::new(Mutex::new(stream))),
-            AddressFamily::Inet,
-            SocketType::Stream,
-        );
+        // Accepted connections inherit the listener's options
+        if let Err(e) = options.apply(&stream) {
+            return SyscallResult::Error(format!("sock_accept: {e}"));
+        }
+        let new_fd = fd_table.open_socket(stream, AddressFamily::Inet, SocketType::Stream);
 
         if let Some(FileDescriptor::Socket {
             state,
+            local_addr: local,
             peer_addr: peer,
+            options: inherited,
             ..
         }) = fd_table.get_mut(new_fd)
         {
+            *inherited = options;
             *state = SocketState::Connected;
+            *local = local_addr;
             *peer = Some(peer_addr);
         }
 
@@ -925,25 +1370,53 @@ impl SyscallHandler {
             FileDescriptor::Socket {
                 handle,
                 state,
+                local_addr,
                 peer_addr,
                 socket_type,
+                options,
                 ..
             } => match socket_type {
+                SocketType::Stream if virtual_switch::is_virtual(ip) => {
+                    let IpAddr::V4(to) = ip else {
+                        unreachable!("virtual addresses are IPv4")
+                    };
+                    let vip = virtual_switch::virtual_ip(pid);
+                    let from = SocketAddrV4::new(vip, self.switch.ephemeral_port(vip));
+                    let stream = match self.switch.connect(from, SocketAddrV4::new(to, port)) {
+                        Ok(s) => s,
+                        Err(e) => return SyscallResult::Error(format!("sock_connect: {e}")),
+                    };
+
+                    *handle = SocketHandle::VirtualStream(Arc::new(stream));
+                    *state = SocketState::Connected;
+                    *local_addr = Some(SocketAddr::V4(from));
+                    *peer_addr = Some(connect_addr);
+                    SyscallResult::Success(SyscallReturn::Number(0))
+                }
+                SocketType::Dgram if virtual_switch::is_virtual(ip) => SyscallResult::Error(
+                    "sock_connect: the virtual network carries stream sockets only".to_string(),
+                ),
                 SocketType::Stream => {
                     let stream = match TcpStream::connect(connect_addr) {
                         Ok(s) => s,
                         Err(e) => return SyscallResult::Error(format!("sock_connect: {e}")),
                     };
 
+                    *local_addr = stream.local_addr().ok();
                     *handle = SocketHandle::TcpStream(Arc::new(Mutex::new(stream)));
+                    if let Err(e) = options.apply(handle) {
+                        return SyscallResult::Error(format!("sock_connect: {e}"));
+                    }
                     *state = SocketState::Connected;
                     *peer_addr = Some(connect_addr);
                     SyscallResult::Success(SyscallReturn::Number(0))
                 }
                 SocketType::Dgram => match handle {
                     SocketHandle::UdpSocket(socket) => {
-                        match socket.lock().unwrap().connect(connect_addr) {
+                        let socket = socket.lock().unwrap();
+                        match socket.connect(connect_addr) {
                             Ok(_) => {
+                                *local_addr = socket.local_addr().ok();
                                 *state = SocketState::Connected;
                                 *peer_addr = Some(connect_addr);
                                 SyscallResult::Success(SyscallReturn::Number(0))
@@ -975,18 +1448,23 @@ impl SyscallHandler {
             _ => return SyscallResult::Error("sock_recv: invalid length".to_string()),
         };
 
-        let fd_table = match self.fd_tables.get(&pid) {
+        let fd_table = match self.fd_tables.get_mut(&pid) {
             Some(table) => table,
             None => return SyscallResult::Error("sock_recv: no fd table".to_string()),
         };
 
-        let descriptor = match fd_table.get(fd) {
+        let descriptor = match fd_table.get_mut(fd) {
             Some(desc) => desc,
             None => return SyscallResult::Error(format!("sock_recv: invalid fd: {fd}")),
         };
 
         match descriptor {
-            FileDescriptor::Socket { handle, state, .. } => {
+            FileDescriptor::Socket {
+                handle,
+                state,
+                bytes_in,
+                ..
+            } => {
                 if *state != SocketState::Connected {
                     return SyscallResult::Error("sock_recv: socket not connected".to_string());
                 }
@@ -997,18 +1475,37 @@ impl SyscallHandler {
                     SocketHandle::TcpStream(stream) => {
                         match stream.lock().unwrap().read(&mut buffer) {
                             Ok(n) => n,
+                            Err(e) if is_timeout(&e) => {
+                                return SyscallResult::Error("sock_recv: timed out".to_string())
+                            }
                             Err(e) => return SyscallResult::Error(format!("sock_recv: {e}")),
                         }
                     }
                     SocketHandle::UdpSocket(socket) => {
                         match socket.lock().unwrap().recv(&mut buffer) {
                             Ok(n) => n,
+                            Err(e) if is_timeout(&e) => {
+                                return SyscallResult::Error("sock_recv: timed out".to_string())
+                            }
                             Err(e) => return SyscallResult::Error(format!("sock_recv: {e}")),
                         }
                     }
+                    SocketHandle::VirtualStream(stream) => {
+                        return match stream.read(max_len) {
+                            Ok(data) => {
+                                *bytes_in += data.len() as u64;
+                                SyscallResult::Success(SyscallReturn::Buffer(data))
+                            }
+                            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                                SyscallResult::Error("sock_recv: would block".to_string())
+                            }
+                            Err(e) => SyscallResult::Error(format!("sock_recv: {e}")),
+                        };
+                    }
                     _ => return SyscallResult::Error("sock_recv: invalid socket type".to_string()),
                 };
 
+                *bytes_in += bytes_read as u64;
                 buffer.truncate(bytes_read);
                 SyscallResult::Success(SyscallReturn::Buffer(buffer))
             }
@@ -1034,18 +1531,23 @@ impl SyscallHandler {
             _ => return SyscallResult::Error("sock_send: invalid data".to_string()),
         };
 
-        let fd_table = match self.fd_tables.get(&pid) {
+        let fd_table = match self.fd_tables.get_mut(&pid) {
             Some(table) => table,
             None => return SyscallResult::Error("sock_send: no fd table".to_string()),
         };
 
-        let descriptor = match fd_table.get(fd) {
+        let descriptor = match fd_table.get_mut(fd) {
             Some(desc) => desc,
             None => return SyscallResult::Error(format!("sock_send: invalid fd: {fd}")),
         };
 
         match descriptor {
-            FileDescriptor::Socket { handle, state, .. } => {
+            FileDescriptor::Socket {
+                handle,
+                state,
+                bytes_out,
+                ..
+            } => {
                 if *state != SocketState::Connected {
                     return SyscallResult::Error("sock_send: socket not connected".to_string());
                 }
@@ -1053,15 +1555,23 @@ impl SyscallHandler {
                 let bytes_sent = match handle {
                     SocketHandle::TcpStream(stream) => match stream.lock().unwrap().write(&data) {
                         Ok(n) => n,
+                        Err(e) if is_timeout(&e) => {
+                            return SyscallResult::Error("sock_send: timed out".to_string())
+                        }
                         Err(e) => return SyscallResult::Error(format!("sock_send: {e}")),
                     },
                     SocketHandle::UdpSocket(socket) => match socket.lock().unwrap().send(&data) {
                         Ok(n) => n,
                         Err(e) => return SyscallResult::Error(format!("sock_send: {e}")),
                     },
+                    SocketHandle::VirtualStream(stream) => match stream.write(&data) {
+                        Ok(n) => n,
+                        Err(e) => return SyscallResult::Error(format!("sock_send: {e}")),
+                    },
                     _ => return SyscallResult::Error("sock_send: invalid socket type".to_string()),
                 };
 
+                *bytes_out += bytes_sent as u64;
                 SyscallResult::Success(SyscallReturn::Number(bytes_sent as i64))
             }
             FileDescriptor::File { .. } => {
@@ -1106,6 +1616,10 @@ impl SyscallHandler {
                     Ok(_) => SyscallResult::Success(SyscallReturn::Number(0)),
                     Err(e) => SyscallResult::Error(format!("sock_shutdown: {e}")),
                 },
+                SocketHandle::VirtualStream(stream) => {
+                    stream.shutdown(how);
+                    SyscallResult::Success(SyscallReturn::Number(0))
+                }
                 _ => SyscallResult::Error(
                     "sock_shutdown: only TCP streams support shutdown".to_string(),
                 ),
@@ -1116,6 +1630,178 @@ impl SyscallHandler {
         }
     }
 
+    fn handle_sock_setsockopt(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
+        let (fd, option, value) = match args.args.as_slice() {
+            [SyscallArg::Number(fd), SyscallArg::Number(option), SyscallArg::Number(value), ..] => {
+                (*fd as i32, *option, *value)
+            }
+            _ => {
+                return SyscallResult::Error(
+                    "sock_setsockopt: expected fd, option and value".to_string(),
+                )
+            }
+        };
+        let option = match SocketOption::try_from(option) {
+            Ok(o) => o,
+            Err(e) => return SyscallResult::Error(format!("sock_setsockopt: {e}")),
+        };
+
+        let descriptor = match self.fd_tables.get_mut(&pid).and_then(|t| t.get_mut(fd)) {
+            Some(desc) => desc,
+            None => return SyscallResult::Error(format!("sock_setsockopt: invalid fd: {fd}")),
+        };
+
+        match descriptor {
+            FileDescriptor::Socket {
+                handle,
+                socket_type,
+                options,
+                ..
+            } => {
+                if *socket_type == SocketType::Dgram
+                    && matches!(option, SocketOption::NoDelay | SocketOption::KeepAlive)
+                {
+                    return SyscallResult::Error(format!(
+                        "sock_setsockopt: {} applies to stream sockets only",
+                        option.name()
+                    ));
+                }
+                let mut updated = *options;
+                updated.set(option, value);
+                match updated.apply(handle) {
+                    Ok(()) => {
+                        *options = updated;
+                        SyscallResult::Success(SyscallReturn::Number(0))
+                    }
+                    Err(e) => SyscallResult::Error(format!("sock_setsockopt: {e}")),
+                }
+            }
+            FileDescriptor::File { .. } => {
+                SyscallResult::Error("sock_setsockopt: not a socket".to_string())
+            }
+        }
+    }
+
+    fn handle_sock_getsockopt(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
+        let (fd, option) = match args.args.as_slice() {
+            [SyscallArg::Number(fd), SyscallArg::Number(option), ..] => (*fd as i32, *option),
+            _ => {
+                return SyscallResult::Error("sock_getsockopt: expected fd and option".to_string())
+            }
+        };
+        let option = match SocketOption::try_from(option) {
+            Ok(o) => o,
+            Err(e) => return SyscallResult::Error(format!("sock_getsockopt: {e}")),
+        };
+
+        match self.fd_tables.get(&pid).and_then(|t| t.get(fd)) {
+            Some(FileDescriptor::Socket { options, .. }) => {
+                SyscallResult::Success(SyscallReturn::Number(options.get(option)))
+            }
+            Some(FileDescriptor::File { .. }) => {
+                SyscallResult::Error("sock_getsockopt: not a socket".to_string())
+            }
+            None => SyscallResult::Error(format!("sock_getsockopt: invalid fd: {fd}")),
+        }
+    }
+
+    /// The fds among `(fd, interest)` pairs that are ready, without
+    /// blocking. Files and devices are always ready; sockets are checked
+    /// with a non-blocking peek, or accept for host listeners.
+    fn handle_poll(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
+        if args.args.is_empty() || args.args.len() % 2 != 0 {
+            return SyscallResult::Error("poll: expected fd and interest pairs".to_string());
+        }
+
+        let mut events = Vec::new();
+        for pair in args.args.chunks(2) {
+            let (fd, interest) = match pair {
+                [SyscallArg::Number(fd), SyscallArg::Number(interest)] => (*fd as i32, *interest),
+                _ => return SyscallResult::Error("poll: invalid fd or interest".to_string()),
+            };
+            let event = match self.fd_readiness(pid, fd) {
+                Ok((readable, writable)) => PollEvent {
+                    fd,
+                    readable: readable && interest & POLL_READ != 0,
+                    writable: writable && interest & POLL_WRITE != 0,
+                    error: None,
+                },
+                Err(e) => PollEvent {
+                    fd,
+                    readable: false,
+                    writable: false,
+                    error: Some(e),
+                },
+            };
+            if event.readable || event.writable || event.error.is_some() {
+                events.push(event);
+            }
+        }
+        SyscallResult::Success(SyscallReturn::PollEvents(events))
+    }
+
+    /// Whether `fd` of `pid` can be read and written without blocking
+    fn fd_readiness(&mut self, pid: Pid, fd: i32) -> std::result::Result<(bool, bool), String> {
+        let descriptor = self
+            .fd_tables
+            .get(&pid)
+            .and_then(|table| table.get(fd))
+            .ok_or_else(|| format!("invalid fd: {fd}"))?;
+
+        let handle = match descriptor {
+            FileDescriptor::File { path, flags, .. } => {
+                if Device::from_path(path).is_none() {
+                    self.kernel
+                        .process_view(pid)
+                        .read_file(path)
+                        .map_err(|e| e.to_string())?;
+                }
+                return Ok((flags.read, flags.write));
+            }
+            FileDescriptor::Socket { handle, .. } => handle.clone(),
+        };
+
+        match handle {
+            SocketHandle::Placeholder => Ok((false, false)),
+            SocketHandle::TcpListener(listener) => {
+                let pending = self.accepted.entry((pid, fd)).or_default();
+                if pending.is_empty() {
+                    let listener = listener.lock().unwrap();
+                    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+                    let accepted = listener.accept();
+                    listener.set_nonblocking(false).map_err(|e| e.to_string())?;
+                    match accepted {
+                        Ok((stream, addr)) => {
+                            // BSDs carry the listener's non-blocking mode over
+                            stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+                            pending.push_back((stream, addr));
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                        Err(e) => return Err(e.to_string()),
+                    }
+                }
+                Ok((!pending.is_empty(), false))
+            }
+            SocketHandle::TcpStream(stream) => {
+                let stream = stream.lock().unwrap();
+                stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+                let peeked = stream.peek(&mut [0u8; 1]);
+                stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+                Ok((peek_ready(peeked), true))
+            }
+            SocketHandle::UdpSocket(socket) => {
+                let socket = socket.lock().unwrap();
+                socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+                let peeked = socket.peek(&mut [0u8; 1]);
+                socket.set_nonblocking(false).map_err(|e| e.to_string())?;
+                Ok((peek_ready(peeked), true))
+            }
+            SocketHandle::VirtualListener(addr) => Ok((self.switch.has_pending(addr), false)),
+            // Writes to the virtual network are buffered and never block
+            SocketHandle::VirtualStream(stream) => Ok((stream.readable(), true)),
+        }
+    }
+
     fn handle_sock_close(&mut self, pid: Pid, args: SyscallArgs) -> SyscallResult {
         self.handle_close(pid, args)
     }
@@ -1172,10 +1858,32 @@ impl SyscallHandler {
             0
         };
 
-        let addr_str = format!("{hostname}:{port}");
-        let addrs: Result<Vec<SocketAddr>> = addr_str
-            .to_socket_addrs()
-            .map(|iter| iter.collect())
+        // `<process-name>.local` is another guest on the virtual network
+        if let Some(name) = hostname
+            .len()
+            .checked_sub(virtual_switch::LOCAL_DOMAIN.len())
+            .filter(|&at| {
+                hostname.is_char_boundary(at)
+                    && hostname[at..].eq_ignore_ascii_case(virtual_switch::LOCAL_DOMAIN)
+            })
+            .map(|at| &hostname[..at])
+        {
+            let mut processes = self.kernel.list_processes();
+            processes.sort_by_key(|p| p.pid);
+            if let Some(process) = processes.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
+                let addr = SocketAddrV4::new(virtual_switch::virtual_ip(process.pid), port);
+                return SyscallResult::Success(SyscallReturn::String(addr.to_string()));
+            }
+        }
+
+        let addrs: Result<Vec<SocketAddr>> = self
+            .resolver
+            .resolve(&hostname)
+            .map(|ips| {
+                ips.into_iter()
+                    .map(|ip| SocketAddr::new(ip, port))
+                    .collect()
+            })
             .map_err(|e| anyhow::anyhow!("DNS resolution failed for '{hostname}': {e}"));
 
         match addrs {
@@ -1411,6 +2119,19 @@ mod tests {
             SyscallNumber::try_from(28).unwrap(),
             SyscallNumber::GetAddrInfo
         );
+        assert_eq!(
+            SyscallNumber::try_from(31).unwrap(),
+            SyscallNumber::WatchRead
+        );
+        assert_eq!(
+            SyscallNumber::try_from(32).unwrap(),
+            SyscallNumber::SockSetOpt
+        );
+        assert_eq!(
+            SyscallNumber::try_from(33).unwrap(),
+            SyscallNumber::SockGetOpt
+        );
+        assert_eq!(SyscallNumber::try_from(34).unwrap(), SyscallNumber::Poll);
         assert!(SyscallNumber::try_from(999).is_err());
     }
 
@@ -1880,6 +2601,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_poll_reports_listener_readable_after_connect() {
+        let kernel = WasmMicroKernel::default();
+        let mut handler = SyscallHandler::new(kernel);
+        let pid: Pid = 1;
+        let number = |n: i64| SyscallArg::Number(n);
+        let poll = |handler: &mut SyscallHandler, fd: i32| {
+            let args = SyscallArgs {
+                args: vec![number(fd as i64), number(POLL_READ | POLL_WRITE)],
+            };
+            match handler.handle_syscall(pid, SyscallNumber::Poll as u32, args) {
+                SyscallResult::Success(SyscallReturn::PollEvents(events)) => events,
+                other => panic!("Expected poll events, got {other:?}"),
+            }
+        };
+
+        let fd = match handler.handle_sock_open(
+            pid,
+            SyscallArgs {
+                args: vec![
+                    number(AddressFamily::Inet as i64),
+                    number(SocketType::Stream as i64),
+                ],
+            },
+        ) {
+            SyscallResult::Success(SyscallReturn::FileDescriptor(fd)) => fd,
+            other => panic!("Expected fd, got {other:?}"),
+        };
+        handler.handle_sock_bind(
+            pid,
+            SyscallArgs {
+                args: vec![
+                    number(fd as i64),
+                    SyscallArg::String("127.0.0.1".to_string()),
+                    number(0),
+                ],
+            },
+        );
+        let result = handler.handle_sock_listen(
+            pid,
+            SyscallArgs {
+                args: vec![number(fd as i64), number(8)],
+            },
+        );
+        assert!(matches!(result, SyscallResult::Success(_)), "{result:?}");
+        let addr = match handler.fd_tables[&pid].get(fd) {
+            Some(FileDescriptor::Socket {
+                handle: SocketHandle::TcpListener(listener),
+                ..
+            }) => listener.lock().unwrap().local_addr().unwrap(),
+            other => panic!("Expected a listener, got {other:?}"),
+        };
+
+        assert!(poll(&mut handler, fd).is_empty());
+
+        let mut client = std::net::TcpStream::connect(addr).unwrap();
+        let mut events = poll(&mut handler, fd);
+        for _ in 0..50 {
+            if !events.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+            events = poll(&mut handler, fd);
+        }
+        assert_eq!(
+            events,
+            vec![PollEvent {
+                fd,
+                readable: true,
+                writable: false,
+                error: None,
+            }]
+        );
+        // Polling again keeps the connection for sock_accept
+        assert_eq!(poll(&mut handler, fd).len(), 1);
+
+        let conn = match handler.handle_sock_accept(
+            pid,
+            SyscallArgs {
+                args: vec![number(fd as i64)],
+            },
+        ) {
+            SyscallResult::Success(SyscallReturn::FileDescriptor(conn)) => conn,
+            other => panic!("Expected accepted fd, got {other:?}"),
+        };
+        assert!(poll(&mut handler, fd).is_empty());
+        assert!(!poll(&mut handler, conn)[0].readable);
+
+        client.write_all(b"ping").unwrap();
+        let mut events = poll(&mut handler, conn);
+        for _ in 0..50 {
+            if events[0].readable {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+            events = poll(&mut handler, conn);
+        }
+        assert!(events[0].readable && events[0].writable);
+
+        assert_eq!(
+            poll(&mut handler, 99)[0].error.as_deref(),
+            Some("invalid fd: 99")
+        );
+    }
+
     #[test]
     fn test_sock_open_dgram_creates_socket_immediately() {
         let kernel = WasmMicroKernel::default();
@@ -1907,6 +2733,267 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_getaddrinfo_uses_host_overrides() {
+        let mut handler = SyscallHandler::new(WasmMicroKernel::default());
+        let mut network = crate::config::project::NetworkConfig::default();
+        network
+            .hosts
+            .insert("my-api.internal".into(), IpAddr::from([127, 0, 0, 1]));
+        handler.set_resolver(Resolver::new(&network));
+
+        let result = handler.handle_getaddrinfo(
+            1,
+            SyscallArgs {
+                args: vec![
+                    SyscallArg::String("My-Api.Internal".into()),
+                    SyscallArg::Number(8080),
+                ],
+            },
+        );
+        assert!(
+            matches!(&result, SyscallResult::Success(SyscallReturn::String(addr)) if addr == "127.0.0.1:8080"),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn test_guests_connect_over_virtual_network() {
+        let kernel = WasmMicroKernel::default();
+        let server = kernel
+            .create_process("api".into(), "rust".into(), None)
+            .unwrap();
+        let client = kernel
+            .create_process("web".into(), "go".into(), None)
+            .unwrap();
+        let mut handler = SyscallHandler::new(kernel);
+        let args = |args: Vec<SyscallArg>| SyscallArgs { args };
+        let stream = || {
+            args(vec![
+                SyscallArg::Number(AddressFamily::Inet as i64),
+                SyscallArg::Number(SocketType::Stream as i64),
+            ])
+        };
+        let fd = |result: SyscallResult| match result {
+            SyscallResult::Success(SyscallReturn::FileDescriptor(fd)) => fd as i64,
+            other => panic!("Expected fd, got {other:?}"),
+        };
+
+        let server_ip = virtual_switch::virtual_ip(server).to_string();
+        let resolved = handler.handle_syscall(
+            client,
+            28,
+            args(vec![
+                SyscallArg::String("API.local".into()),
+                SyscallArg::Number(80),
+            ]),
+        );
+        assert!(
+            matches!(&resolved, SyscallResult::Success(SyscallReturn::String(addr)) if *addr == format!("{server_ip}:80")),
+            "{resolved:?}"
+        );
+
+        let listener = fd(handler.handle_syscall(server, 19, stream()));
+        // A process can only bind its own virtual address
+        let other_ip = virtual_switch::virtual_ip(client).to_string();
+        assert!(matches!(
+            handler.handle_syscall(
+                server,
+                20,
+                args(vec![
+                    SyscallArg::Number(listener),
+                    SyscallArg::String(other_ip),
+                    SyscallArg::Number(80)
+                ])
+            ),
+            SyscallResult::Error(_)
+        ));
+        for (num, extra) in [
+            (
+                20,
+                vec![
+                    SyscallArg::String(server_ip.clone()),
+                    SyscallArg::Number(80),
+                ],
+            ),
+            (21, vec![SyscallArg::Number(8)]),
+        ] {
+            let mut call = vec![SyscallArg::Number(listener)];
+            call.extend(extra);
+            let result = handler.handle_syscall(server, num, args(call));
+            assert!(matches!(result, SyscallResult::Success(_)), "{result:?}");
+        }
+        assert!(matches!(
+            handler.handle_syscall(server, 22, args(vec![SyscallArg::Number(listener)])),
+            SyscallResult::Error(e) if e.contains("would block")
+        ));
+
+        let conn = fd(handler.handle_syscall(client, 19, stream()));
+        let result = handler.handle_syscall(
+            client,
+            23,
+            args(vec![
+                SyscallArg::Number(conn),
+                SyscallArg::String(server_ip),
+                SyscallArg::Number(80),
+            ]),
+        );
+        assert!(matches!(result, SyscallResult::Success(_)), "{result:?}");
+        let readable = |handler: &mut SyscallHandler, pid: Pid, fd: i64| match handler
+            .handle_syscall(
+                pid,
+                34,
+                args(vec![SyscallArg::Number(fd), SyscallArg::Number(POLL_READ)]),
+            ) {
+            SyscallResult::Success(SyscallReturn::PollEvents(events)) => !events.is_empty(),
+            other => panic!("Expected poll events, got {other:?}"),
+        };
+        assert!(readable(&mut handler, server, listener));
+        let accepted =
+            fd(handler.handle_syscall(server, 22, args(vec![SyscallArg::Number(listener)])));
+        assert!(!readable(&mut handler, server, listener));
+        assert!(!readable(&mut handler, server, accepted));
+
+        handler.handle_syscall(
+            client,
+            25,
+            args(vec![
+                SyscallArg::Number(conn),
+                SyscallArg::String("ping".into()),
+            ]),
+        );
+        assert!(readable(&mut handler, server, accepted));
+        match handler.handle_syscall(
+            server,
+            24,
+            args(vec![SyscallArg::Number(accepted), SyscallArg::Number(64)]),
+        ) {
+            SyscallResult::Success(SyscallReturn::Buffer(data)) => assert_eq!(data, b"ping"),
+            other => panic!("recv failed: {other:?}"),
+        }
+
+        let sockets = handler.list_sockets(server);
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(
+            (sockets[0].kind, sockets[0].state),
+            ("virtual", "listening")
+        );
+        assert_eq!(sockets[1].state, "connected");
+        assert_eq!((sockets[1].bytes_in, sockets[1].bytes_out), (4, 0));
+        assert_eq!(
+            sockets[1].peer_addr,
+            handler.list_sockets(client)[0].local_addr
+        );
+        assert_eq!(handler.list_sockets(client)[0].bytes_out, 4);
+
+        // Disconnecting the listener frees its address on the switch
+        handler.disconnect_socket(server, listener as i32).unwrap();
+        assert!(handler.virtual_switch().listeners().is_empty());
+        assert!(handler.disconnect_socket(server, listener as i32).is_err());
+        assert_eq!(handler.list_sockets(server).len(), 1);
+
+        // Stopping the server closes its end
+        handler.release_process(server);
+        match handler.handle_syscall(
+            client,
+            24,
+            args(vec![SyscallArg::Number(conn), SyscallArg::Number(64)]),
+        ) {
+            SyscallResult::Success(SyscallReturn::Buffer(data)) => assert!(data.is_empty()),
+            other => panic!("Expected EOF, got {other:?}"),
+        }
+        assert!(handler.virtual_switch().listeners().is_empty());
+    }
+
+    #[test]
+    fn test_socket_options_carry_over_to_host_socket() {
+        let kernel = WasmMicroKernel::default();
+        let pid = kernel
+            .create_process("client".into(), "rust".into(), None)
+            .unwrap();
+        let mut handler = SyscallHandler::new(kernel);
+        let args = |args: Vec<i64>| SyscallArgs {
+            args: args.into_iter().map(SyscallArg::Number).collect(),
+        };
+        let open = |handler: &mut SyscallHandler, socket_type: SocketType| match handler
+            .handle_syscall(
+                pid,
+                19,
+                args(vec![AddressFamily::Inet as i64, socket_type as i64]),
+            ) {
+            SyscallResult::Success(SyscallReturn::FileDescriptor(fd)) => fd as i64,
+            other => panic!("Expected fd, got {other:?}"),
+        };
+
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        let fd = open(&mut handler, SocketType::Stream);
+
+        // Set before connect, while there is no host socket yet
+        for (option, value) in [
+            (SocketOption::NoDelay, 1),
+            (SocketOption::KeepAlive, 1),
+            (SocketOption::RecvTimeout, 50),
+        ] {
+            let result = handler.handle_syscall(pid, 32, args(vec![fd, option as i64, value]));
+            assert!(matches!(result, SyscallResult::Success(_)), "{result:?}");
+        }
+        assert!(matches!(
+            handler.handle_syscall(pid, 32, args(vec![fd, 99, 1])),
+            SyscallResult::Error(e) if e.contains("Unknown socket option")
+        ));
+
+        let result = handler.handle_syscall(
+            pid,
+            23,
+            SyscallArgs {
+                args: vec![
+                    SyscallArg::Number(fd),
+                    SyscallArg::String("127.0.0.1".into()),
+                    SyscallArg::Number(port as i64),
+                ],
+            },
+        );
+        assert!(matches!(result, SyscallResult::Success(_)), "{result:?}");
+        let _accepted = server.accept().unwrap();
+
+        match handler.fd_tables[&pid].get(fd as i32) {
+            Some(FileDescriptor::Socket {
+                handle: SocketHandle::TcpStream(stream),
+                ..
+            }) => {
+                let stream = stream.lock().unwrap();
+                let socket = socket2::SockRef::from(&*stream);
+                assert!(socket.tcp_nodelay().unwrap());
+                assert!(socket.keepalive().unwrap());
+                assert!(stream.read_timeout().unwrap().is_some());
+            }
+            other => panic!("Expected a connected stream, got {other:?}"),
+        }
+        assert!(matches!(
+            handler.handle_syscall(pid, 33, args(vec![fd, SocketOption::RecvTimeout as i64])),
+            SyscallResult::Success(SyscallReturn::Number(50))
+        ));
+        assert!(matches!(
+            handler.handle_syscall(pid, 24, args(vec![fd, 16])),
+            SyscallResult::Error(e) if e == "sock_recv: timed out"
+        ));
+
+        let udp = open(&mut handler, SocketType::Dgram);
+        assert!(matches!(
+            handler.handle_syscall(pid, 32, args(vec![udp, SocketOption::NoDelay as i64, 1])),
+            SyscallResult::Error(e) if e.contains("TCP_NODELAY")
+        ));
+        assert!(matches!(
+            handler.handle_syscall(
+                pid,
+                32,
+                args(vec![udp, SocketOption::SendTimeout as i64, 20])
+            ),
+            SyscallResult::Success(_)
+        ));
+    }
+
     #[test]
     fn test_kill_self_allowed() {
         let kernel = WasmMicroKernel::default();
@@ -1978,4 +3065,106 @@ mod tests {
             _ => panic!("Expected no-such-process error"),
         }
     }
+
+    #[test]
+    fn test_proc_self_and_devices_through_syscalls() {
+        let kernel = WasmMicroKernel::default();
+        let pid = kernel
+            .create_process("reader".into(), "rust".into(), None)
+            .unwrap();
+        let mut handler = SyscallHandler::new(kernel);
+
+        let open = |handler: &mut SyscallHandler, path: &str, flags: i64| {
+            let args = SyscallArgs {
+                args: vec![SyscallArg::String(path.into()), SyscallArg::Number(flags)],
+            };
+            match handler.handle_open(pid, args) {
+                SyscallResult::Success(SyscallReturn::FileDescriptor(fd)) => fd,
+                other => panic!("open failed: {other:?}"),
+            }
+        };
+        let read = |handler: &mut SyscallHandler, fd: i32, count: i64| {
+            let args = SyscallArgs {
+                args: vec![SyscallArg::Number(fd as i64), SyscallArg::Number(count)],
+            };
+            match handler.handle_read(pid, args) {
+                SyscallResult::Success(SyscallReturn::Buffer(data)) => data,
+                other => panic!("read failed: {other:?}"),
+            }
+        };
+
+        let fd = open(&mut handler, "/proc/self/status", 0x1);
+        let status = String::from_utf8(read(&mut handler, fd, 4096)).unwrap();
+        assert!(status.contains(&format!("Pid:\t{pid}\n")));
+
+        let fd = open(&mut handler, "/dev/zero", 0x1);
+        assert_eq!(read(&mut handler, fd, 16), vec![0; 16]);
+
+        let fd = open(&mut handler, "/dev/null", 0x2);
+        let args = SyscallArgs {
+            args: vec![
+                SyscallArg::Number(fd as i64),
+                SyscallArg::String("gone".into()),
+            ],
+        };
+        assert!(matches!(
+            handler.handle_write(pid, args),
+            SyscallResult::Success(SyscallReturn::Number(4))
+        ));
+
+        let args = SyscallArgs {
+            args: vec![SyscallArg::String("/".into())],
+        };
+        match handler.handle_stat(pid, args) {
+            SyscallResult::Success(SyscallReturn::VfsEntries(entries)) => {
+                assert!(entries.iter().any(|e| e.path == "/proc" && e.is_directory));
+                assert!(entries.iter().any(|e| e.path == "/dev" && e.is_directory));
+            }
+            other => panic!("stat failed: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_watch_syscalls() {
+        let kernel = WasmMicroKernel::default();
+        let pid = kernel
+            .create_process("watcher".into(), "nodejs".into(), None)
+            .unwrap();
+        let dir = format!("/watch-test-{pid}");
+        kernel.create_directory(&dir).unwrap();
+        let mut handler = SyscallHandler::new(kernel);
+
+        let args = |args: Vec<SyscallArg>| SyscallArgs { args };
+        let wd = match handler.handle_syscall(
+            pid,
+            29,
+            args(vec![SyscallArg::String(dir.clone()), SyscallArg::Number(0)]),
+        ) {
+            SyscallResult::Success(SyscallReturn::Number(wd)) => wd,
+            other => panic!("watch failed: {other:?}"),
+        };
+        assert!(matches!(
+            handler.handle_syscall(pid, 29, args(vec![SyscallArg::String("/proc".into())])),
+            SyscallResult::Error(_)
+        ));
+
+        handler.handle_syscall(pid, 5, args(vec![SyscallArg::String(format!("{dir}/src"))]));
+        match handler.handle_syscall(pid, 31, args(vec![])) {
+            SyscallResult::Success(SyscallReturn::WatchEvents(events)) => {
+                assert_eq!(events.len(), 1);
+                assert_eq!(events[0].wd as i64, wd);
+                assert_eq!(events[0].path, format!("{dir}/src"));
+            }
+            other => panic!("watch_read failed: {other:?}"),
+        }
+
+        assert!(matches!(
+            handler.handle_syscall(pid, 30, args(vec![SyscallArg::Number(wd)])),
+            SyscallResult::Success(_)
+        ));
+        assert!(matches!(
+            handler.handle_syscall(pid, 30, args(vec![SyscallArg::Number(wd)])),
+            SyscallResult::Error(_)
+        ));
+    }
 }
@@ -0,0 +1,285 @@
+//! UDP port forwarding. Each host-side peer gets its own session: a socket
+//! connected to the guest port, so replies find their way back to the peer
+//! that sent the request. Sessions with no traffic for the idle timeout are
+//! closed.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_UDP_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Sessions beyond this are dropped rather than spawning more threads
+const MAX_SESSIONS: usize = 1024;
+/// How often the relay threads check for shutdown and idle sessions
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const MAX_DATAGRAM: usize = 65_535;
+
+pub struct UdpForwarder {
+    /// Where peers reach the forwarder, for tests that bind port 0
+    #[cfg(test)]
+    local_addr: SocketAddr,
+    sessions: Arc<Mutex<HashMap<SocketAddr, Arc<Session>>>>,
+    stop: Arc<AtomicBool>,
+    relay: Option<JoinHandle<()>>,
+}
+
+struct Session {
+    upstream: UdpSocket,
+    last_active: Mutex<Instant>,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl Session {
+    fn touch(&self) {
+        *self.last_active.lock().unwrap() = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_active.lock().unwrap().elapsed()
+    }
+}
+
+/// A peer talking to a forwarded UDP port
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UdpSessionInfo {
+    pub peer: SocketAddr,
+    /// Bytes from the peer to the guest
+    pub bytes_in: u64,
+    /// Bytes from the guest to the peer
+    pub bytes_out: u64,
+    pub idle_secs: u64,
+}
+
+impl UdpForwarder {
+    /// Listen on `listen` and relay datagrams to `target`
+    pub fn start(
+        listen: SocketAddr,
+        target: SocketAddr,
+        idle_timeout: Duration,
+    ) -> io::Result<Self> {
+        let socket = UdpSocket::bind(listen)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let local_addr = socket.local_addr()?;
+        let socket = Arc::new(socket);
+        let sessions: Arc<Mutex<HashMap<SocketAddr, Arc<Session>>>> = Arc::default();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let relay = {
+            let sessions = Arc::clone(&sessions);
+            let stop = Arc::clone(&stop);
+            thread::Builder::new()
+                .name(format!("udp-forward-{}", local_addr.port()))
+                .spawn(move || relay_inbound(socket, target, idle_timeout, sessions, stop))?
+        };
+
+        Ok(Self {
+            #[cfg(test)]
+            local_addr,
+            sessions,
+            stop,
+            relay: Some(relay),
+        })
+    }
+
+    #[cfg(test)]
+    fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn sessions(&self) -> Vec<UdpSessionInfo> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut info: Vec<UdpSessionInfo> = sessions
+            .iter()
+            .map(|(peer, session)| UdpSessionInfo {
+                peer: *peer,
+                bytes_in: session.bytes_in.load(Ordering::Relaxed),
+                bytes_out: session.bytes_out.load(Ordering::Relaxed),
+                idle_secs: session.idle_for().as_secs(),
+            })
+            .collect();
+        info.sort_by_key(|s| s.peer);
+        info
+    }
+}
+
+impl Drop for UdpForwarder {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(relay) = self.relay.take() {
+            let _ = relay.join();
+        }
+        // Session threads hold the listening socket until they notice
+        let deadline = Instant::now() + POLL_INTERVAL * 5;
+        while !self.sessions.lock().unwrap().is_empty() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL / 4);
+        }
+    }
+}
+
+/// Peer to guest: find or open the peer's session and pass the datagram on
+fn relay_inbound(
+    socket: Arc<UdpSocket>,
+    target: SocketAddr,
+    idle_timeout: Duration,
+    sessions: Arc<Mutex<HashMap<SocketAddr, Arc<Session>>>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut buffer = vec![0u8; MAX_DATAGRAM];
+    while !stop.load(Ordering::Relaxed) {
+        let (len, peer) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            // Timeouts, and ICMP errors from earlier sends on some platforms
+            Err(_) => continue,
+        };
+
+        let existing = sessions.lock().unwrap().get(&peer).cloned();
+        let session = match existing {
+            Some(session) => session,
+            None => match open_session(&socket, peer, target, idle_timeout, &sessions, &stop) {
+                Some(session) => session,
+                None => continue,
+            },
+        };
+        if session.upstream.send(&buffer[..len]).is_ok() {
+            session.bytes_in.fetch_add(len as u64, Ordering::Relaxed);
+            session.touch();
+        }
+    }
+}
+
+fn open_session(
+    socket: &Arc<UdpSocket>,
+    peer: SocketAddr,
+    target: SocketAddr,
+    idle_timeout: Duration,
+    sessions: &Arc<Mutex<HashMap<SocketAddr, Arc<Session>>>>,
+    stop: &Arc<AtomicBool>,
+) -> Option<Arc<Session>> {
+    if sessions.lock().unwrap().len() >= MAX_SESSIONS {
+        return None;
+    }
+    let local: SocketAddr = if target.is_ipv4() {
+        ([127, 0, 0, 1], 0).into()
+    } else {
+        (std::net::Ipv6Addr::LOCALHOST, 0).into()
+    };
+    let upstream = UdpSocket::bind(local).ok()?;
+    upstream.connect(target).ok()?;
+    upstream.set_read_timeout(Some(POLL_INTERVAL)).ok()?;
+
+    let session = Arc::new(Session {
+        upstream,
+        last_active: Mutex::new(Instant::now()),
+        bytes_in: AtomicU64::new(0),
+        bytes_out: AtomicU64::new(0),
+    });
+    sessions.lock().unwrap().insert(peer, Arc::clone(&session));
+
+    let socket = Arc::clone(socket);
+    let registry = Arc::clone(sessions);
+    let stop = Arc::clone(stop);
+    let outbound = Arc::clone(&session);
+    let spawned = thread::Builder::new()
+        .name(format!("udp-session-{peer}"))
+        .spawn(move || relay_outbound(socket, peer, outbound, idle_timeout, registry, stop));
+    if spawned.is_err() {
+        sessions.lock().unwrap().remove(&peer);
+        return None;
+    }
+    Some(session)
+}
+
+/// Guest to peer, until the session goes idle or the forwarder stops
+fn relay_outbound(
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+    session: Arc<Session>,
+    idle_timeout: Duration,
+    sessions: Arc<Mutex<HashMap<SocketAddr, Arc<Session>>>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut buffer = vec![0u8; MAX_DATAGRAM];
+    while !stop.load(Ordering::Relaxed) {
+        match session.upstream.recv(&mut buffer) {
+            Ok(len) => {
+                if socket.send_to(&buffer[..len], peer).is_ok() {
+                    session.bytes_out.fetch_add(len as u64, Ordering::Relaxed);
+                    session.touch();
+                }
+            }
+            // Nothing is listening on the guest port yet
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {}
+            Err(e) if is_timeout(&e) => {}
+            Err(_) => break,
+        }
+        if session.idle_for() >= idle_timeout {
+            break;
+        }
+    }
+    let mut sessions = sessions.lock().unwrap();
+    if sessions
+        .get(&peer)
+        .is_some_and(|current| Arc::ptr_eq(current, &session))
+    {
+        sessions.remove(&peer);
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_udp_forwarder_relays_and_expires_sessions() {
+        // Stands in for a guest echo server
+        let guest = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let guest_addr = guest.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 64];
+            while let Ok((len, from)) = guest.recv_from(&mut buffer) {
+                let _ = guest.send_to(&buffer[..len], from);
+            }
+        });
+
+        let forwarder = UdpForwarder::start(
+            "127.0.0.1:0".parse().unwrap(),
+            guest_addr,
+            Duration::from_millis(500),
+        )
+        .unwrap();
+
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        client.send_to(b"ping", forwarder.local_addr()).unwrap();
+        let mut buffer = [0u8; 64];
+        let (len, from) = client.recv_from(&mut buffer).unwrap();
+        assert_eq!(&buffer[..len], b"ping");
+        assert_eq!(from, forwarder.local_addr());
+
+        let sessions = forwarder.sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].peer, client.local_addr().unwrap());
+        assert_eq!((sessions[0].bytes_in, sessions[0].bytes_out), (4, 4));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !forwarder.sessions().is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(forwarder.sessions().is_empty(), "idle session not closed");
+    }
+}
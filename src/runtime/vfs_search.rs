@@ -0,0 +1,255 @@
+//! Content and filename search across the VFS, for the file explorer's
+//! search box. Every search is bounded: it stops after a fixed number of
+//! files and matches, and skips large and binary files, so a query against
+//! `/` on a big project answers quickly with `truncated` set.
+
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::runtime::wasi_fs::WasiFilesystem;
+use crate::utils::percent_decode;
+
+/// Files read before the search gives up
+const MAX_FILES_SCANNED: usize = 5_000;
+/// Matching lines returned across all files
+const MAX_MATCHES: usize = 500;
+/// Larger files are matched by name only
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Context lines allowed either side of a match
+const MAX_CONTEXT: usize = 10;
+/// Lines are cut to this many characters in results
+const MAX_LINE_CHARS: usize = 240;
+
+/// A parsed `/api/fs/search` query string
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub pattern: String,
+    /// VFS directory to search under
+    pub path: String,
+    /// Treat `pattern` as a regular expression rather than literal text
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Lines of context before and after each match
+    pub context: usize,
+}
+
+impl SearchQuery {
+    /// Parse `q=&path=&regex=&case=&context=`; only `q` is required
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut search = Self {
+            pattern: String::new(),
+            path: "/".to_string(),
+            regex: false,
+            case_sensitive: false,
+            context: 2,
+        };
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value);
+            let flag = || matches!(value.as_str(), "1" | "true" | "yes");
+            match key {
+                "q" => search.pattern = value.clone(),
+                "path" => search.path = format!("/{}", value.trim_matches('/')),
+                "regex" => search.regex = flag(),
+                "case" => search.case_sensitive = flag(),
+                "context" => {
+                    search.context = value
+                        .parse::<usize>()
+                        .context("context must be a number")?
+                        .min(MAX_CONTEXT)
+                }
+                _ => {}
+            }
+        }
+        if search.pattern.is_empty() {
+            anyhow::bail!("Missing search query: pass ?q=<text>");
+        }
+        Ok(search)
+    }
+
+    fn matcher(&self) -> Result<Regex> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .size_limit(1 << 20)
+            .build()
+            .with_context(|| format!("Invalid pattern: {}", self.pattern))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub files: Vec<FileMatches>,
+    pub files_scanned: usize,
+    /// A limit was hit, so there may be more matches
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMatches {
+    pub path: String,
+    /// The file's name matches, whether or not its content does
+    pub name_match: bool,
+    pub lines: Vec<LineMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineMatch {
+    /// 1-based
+    pub line: usize,
+    pub text: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Search file names and contents under `query.path`
+pub fn search(fs: &WasiFilesystem, query: &SearchQuery) -> Result<SearchResults> {
+    let matcher = query.matcher()?;
+    let mut results = SearchResults {
+        files: Vec::new(),
+        files_scanned: 0,
+        truncated: false,
+    };
+    let mut total_lines = 0;
+    let mut pending = vec![query.path.trim_end_matches('/').to_string()];
+
+    while let Some(dir) = pending.pop() {
+        let mut entries = fs.path_readdir(if dir.is_empty() { "/" } else { &dir })?;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        // Files before subdirectories, each in name order
+        let mut subdirs = Vec::new();
+        for entry in entries {
+            let path = format!("{dir}/{}", entry.name);
+            if entry.is_dir {
+                subdirs.push(path);
+                continue;
+            }
+            if results.files_scanned >= MAX_FILES_SCANNED || total_lines >= MAX_MATCHES {
+                results.truncated = true;
+                return Ok(results);
+            }
+            results.files_scanned += 1;
+
+            let name_match = matcher.is_match(&entry.name);
+            let lines = if entry.size <= MAX_FILE_BYTES {
+                match fs.read_file(&path) {
+                    Ok(data) if !data[..data.len().min(8192)].contains(&0) => {
+                        let budget = MAX_MATCHES - total_lines;
+                        let (lines, more) = match_lines(
+                            &String::from_utf8_lossy(&data),
+                            &matcher,
+                            query.context,
+                            budget,
+                        );
+                        results.truncated |= more;
+                        lines
+                    }
+                    _ => Vec::new(),
+                }
+            } else {
+                Vec::new()
+            };
+            if name_match || !lines.is_empty() {
+                total_lines += lines.len();
+                results.files.push(FileMatches {
+                    path,
+                    name_match,
+                    lines,
+                });
+            }
+        }
+        pending.extend(subdirs.into_iter().rev());
+    }
+    Ok(results)
+}
+
+/// Up to `budget` matching lines with context, and whether more were left
+fn match_lines(
+    content: &str,
+    matcher: &Regex,
+    context: usize,
+    budget: usize,
+) -> (Vec<LineMatch>, bool) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut matches = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if !matcher.is_match(line) {
+            continue;
+        }
+        if matches.len() == budget {
+            return (matches, true);
+        }
+        let clip = |lines: &[&str]| lines.iter().map(|l| clip_line(l)).collect();
+        matches.push(LineMatch {
+            line: index + 1,
+            text: clip_line(line),
+            before: clip(&lines[index.saturating_sub(context)..index]),
+            after: clip(&lines[index + 1..(index + 1 + context).min(lines.len())]),
+        });
+    }
+    (matches, false)
+}
+
+fn clip_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let query = SearchQuery::parse("q=fn+main%28%29&path=src%2F&regex=1&context=50").unwrap();
+        assert_eq!(query.pattern, "fn main()");
+        assert_eq!(query.path, "/src");
+        assert!(query.regex && !query.case_sensitive);
+        assert_eq!(query.context, MAX_CONTEXT);
+        assert_eq!(SearchQuery::parse("q=caf%C3%A9").unwrap().pattern, "café");
+        assert!(SearchQuery::parse("path=/src").is_err());
+        assert!(SearchQuery::parse("q=%28&regex=1")
+            .unwrap()
+            .matcher()
+            .is_err());
+    }
+
+    #[test]
+    fn test_search_names_and_contents() {
+        let host = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(host.path().join("src/util")).unwrap();
+        std::fs::write(
+            host.path().join("src/main.rs"),
+            "use util;\n\nfn main() {\n    util::Greet();\n}\n",
+        )
+        .unwrap();
+        std::fs::write(host.path().join("src/util/greet.rs"), "pub fn greet() {}\n").unwrap();
+        std::fs::write(host.path().join("src/logo.png"), b"\x89PNG\0greet").unwrap();
+        let fs = WasiFilesystem::new();
+        fs.mount("/app", host.path()).unwrap();
+
+        let results = search(&fs, &SearchQuery::parse("q=greet&path=/app").unwrap()).unwrap();
+        assert!(!results.truncated);
+        assert_eq!(results.files_scanned, 3);
+        let paths: Vec<&str> = results.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["/app/src/main.rs", "/app/src/util/greet.rs"]);
+        let main = &results.files[0];
+        assert!(!main.name_match);
+        assert_eq!(main.lines[0].line, 4);
+        assert_eq!(main.lines[0].before, vec!["", "fn main() {"]);
+        assert_eq!(main.lines[0].after, vec!["}"]);
+        assert!(results.files[1].name_match);
+
+        let exact = SearchQuery::parse("q=Greet%5C%28&path=/app&regex=1&case=1").unwrap();
+        let results = search(&fs, &exact).unwrap();
+        assert_eq!(results.files.len(), 1);
+        assert_eq!(results.files[0].lines[0].text, "    util::Greet();");
+    }
+}
@@ -0,0 +1,308 @@
+//! inotify-style watches on the kernel VFS
+//!
+//! Watches are keyed by the host directory a guest path resolves to, so a
+//! process watching `/data` sees writes another process makes through its own
+//! mount of the same volume. Events come from two places: filesystem calls made
+//! through [`WasiFilesystem`](super::wasi_fs::WasiFilesystem), and a host
+//! watcher that catches edits made outside the kernel, such as a developer
+//! saving a mounted project file. The VFS marks a path before changing it, and
+//! host events for recently marked paths are dropped as echoes.
+
+use crate::runtime::microkernel::Pid;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Events held per process before the queue overflows
+const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// How long after a VFS change the host watcher's reports of it are ignored
+const ECHO_WINDOW: Duration = Duration::from_secs(1);
+
+/// Flag bit for the `watch` syscall: also report events in subdirectories
+pub const WATCH_RECURSIVE: u32 = 0x1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchEventKind {
+    Create,
+    Modify,
+    Delete,
+    /// Events were dropped because the process didn't read them in time
+    Overflow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchEvent {
+    /// Watch descriptor returned by the `watch` syscall; `-1` for an overflow
+    pub wd: i32,
+    /// Guest path, as seen through the watching process's mounts
+    pub path: String,
+    pub kind: WatchEventKind,
+}
+
+#[derive(Debug, Clone)]
+struct Watch {
+    pid: Pid,
+    wd: i32,
+    guest: String,
+    host: PathBuf,
+    recursive: bool,
+}
+
+impl Watch {
+    /// Like inotify, a watch on a directory covers its direct entries, and
+    /// a watch on a file covers the file itself
+    fn covers(&self, path: &Path) -> bool {
+        path == self.host
+            || path.parent() == Some(self.host.as_path())
+            || (self.recursive && path.starts_with(&self.host))
+    }
+
+    fn guest_path(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.host).unwrap_or(Path::new(""));
+        if relative.as_os_str().is_empty() {
+            return self.guest.clone();
+        }
+        format!(
+            "{}/{}",
+            self.guest.trim_end_matches('/'),
+            relative.to_string_lossy()
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WatchRegistry {
+    watches: Vec<Watch>,
+    queues: HashMap<Pid, VecDeque<WatchEvent>>,
+    overflowed: HashSet<Pid>,
+    /// Paths the VFS is changing or has just changed
+    recent: HashMap<PathBuf, Instant>,
+    next_wd: i32,
+}
+
+impl WatchRegistry {
+    pub fn add(&mut self, pid: Pid, guest: &str, host: PathBuf, recursive: bool) -> i32 {
+        self.next_wd += 1;
+        self.watches.push(Watch {
+            pid,
+            wd: self.next_wd,
+            guest: guest.to_string(),
+            host,
+            recursive,
+        });
+        self.next_wd
+    }
+
+    /// Drop watch `wd` of `pid`, returning its host path if no other watch
+    /// still needs it
+    pub fn remove(&mut self, pid: Pid, wd: i32) -> Option<Option<PathBuf>> {
+        let index = self
+            .watches
+            .iter()
+            .position(|w| w.pid == pid && w.wd == wd)?;
+        let watch = self.watches.remove(index);
+        if let Some(queue) = self.queues.get_mut(&pid) {
+            queue.retain(|e| e.wd != wd);
+        }
+        Some(self.unused(watch.host))
+    }
+
+    /// Drop every watch of `pid`, returning the host paths no longer needed
+    pub fn remove_process(&mut self, pid: Pid) -> Vec<PathBuf> {
+        let (removed, kept): (Vec<Watch>, Vec<Watch>) =
+            self.watches.drain(..).partition(|w| w.pid == pid);
+        self.watches = kept;
+        self.queues.remove(&pid);
+        self.overflowed.remove(&pid);
+        let mut unused: Vec<PathBuf> = removed
+            .into_iter()
+            .filter_map(|w| self.unused(w.host))
+            .collect();
+        unused.sort();
+        unused.dedup();
+        unused
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    fn unused(&self, host: PathBuf) -> Option<PathBuf> {
+        (!self.watches.iter().any(|w| w.host == host)).then_some(host)
+    }
+
+    /// Whether any watch on `host` covers its subdirectories
+    pub fn is_recursive(&self, host: &Path) -> bool {
+        self.watches.iter().any(|w| w.host == host && w.recursive)
+    }
+
+    /// Mark `path` as about to be changed through the VFS. Called before the
+    /// change, since the host watcher may report it before
+    /// [`notify`](Self::notify) runs.
+    pub fn expect(&mut self, path: &Path, now: Instant) {
+        if self.recent.len() >= MAX_QUEUED_EVENTS {
+            self.recent
+                .retain(|_, at| now.duration_since(*at) < ECHO_WINDOW);
+        }
+        self.recent.insert(path.to_path_buf(), now);
+    }
+
+    /// A change made through the VFS
+    pub fn notify(&mut self, path: &Path, kind: WatchEventKind) {
+        self.dispatch(path, kind);
+    }
+
+    /// A change reported by the host watcher
+    pub fn notify_host(&mut self, path: &Path, kind: WatchEventKind, now: Instant) {
+        let echo = self
+            .recent
+            .get(path)
+            .is_some_and(|at| now.duration_since(*at) < ECHO_WINDOW);
+        if !echo {
+            self.dispatch(path, kind);
+        }
+    }
+
+    fn dispatch(&mut self, path: &Path, kind: WatchEventKind) {
+        let events: Vec<(Pid, WatchEvent)> = self
+            .watches
+            .iter()
+            .filter(|w| w.covers(path))
+            .map(|w| {
+                (
+                    w.pid,
+                    WatchEvent {
+                        wd: w.wd,
+                        path: w.guest_path(path),
+                        kind,
+                    },
+                )
+            })
+            .collect();
+
+        for (pid, event) in events {
+            if self.overflowed.contains(&pid) {
+                continue;
+            }
+            let queue = self.queues.entry(pid).or_default();
+            // Identical back-to-back events are merged, as inotify does
+            if queue.back() == Some(&event) {
+                continue;
+            }
+            if queue.len() >= MAX_QUEUED_EVENTS {
+                queue.push_back(WatchEvent {
+                    wd: -1,
+                    path: String::new(),
+                    kind: WatchEventKind::Overflow,
+                });
+                self.overflowed.insert(pid);
+                continue;
+            }
+            queue.push_back(event);
+        }
+    }
+
+    /// Take up to `max` pending events for `pid`
+    pub fn take(&mut self, pid: Pid, max: usize) -> Vec<WatchEvent> {
+        let Some(queue) = self.queues.get_mut(&pid) else {
+            return Vec::new();
+        };
+        let events: Vec<WatchEvent> = queue.drain(..max.min(queue.len())).collect();
+        if queue.is_empty() {
+            self.overflowed.remove(&pid);
+        }
+        events
+    }
+}
+
+/// Map a host watcher event to the paths and kinds it changed
+pub fn host_events(event: &notify::Event) -> Vec<(PathBuf, WatchEventKind)> {
+    use notify::event::{ModifyKind, RenameMode};
+    use notify::EventKind;
+
+    let each = |kind| event.paths.iter().map(move |p| (p.clone(), kind)).collect();
+    match event.kind {
+        EventKind::Create(_) => each(WatchEventKind::Create),
+        EventKind::Remove(_) => each(WatchEventKind::Delete),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => each(WatchEventKind::Delete),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => each(WatchEventKind::Create),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match event.paths.as_slice() {
+            [from, to] => vec![
+                (from.clone(), WatchEventKind::Delete),
+                (to.clone(), WatchEventKind::Create),
+            ],
+            _ => Vec::new(),
+        },
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Modify(_) => each(WatchEventKind::Modify),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_scope_and_guest_paths() {
+        let mut registry = WatchRegistry::default();
+        let now = Instant::now();
+        let shallow = registry.add(1, "/app", PathBuf::from("/srv/app"), false);
+        let deep = registry.add(2, "/data", PathBuf::from("/srv/app"), true);
+
+        registry.expect(Path::new("/srv/app/index.js"), now);
+        registry.notify(Path::new("/srv/app/index.js"), WatchEventKind::Modify);
+        registry.notify(Path::new("/srv/app/index.js"), WatchEventKind::Modify);
+        registry.notify(Path::new("/srv/app/src/lib.js"), WatchEventKind::Create);
+        registry.notify(Path::new("/srv/other.js"), WatchEventKind::Create);
+
+        assert_eq!(
+            registry.take(1, 10),
+            vec![WatchEvent {
+                wd: shallow,
+                path: "/app/index.js".to_string(),
+                kind: WatchEventKind::Modify,
+            }]
+        );
+        let deep_events = registry.take(2, 10);
+        assert_eq!(deep_events.len(), 2);
+        assert_eq!(deep_events[1].wd, deep);
+        assert_eq!(deep_events[1].path, "/data/src/lib.js");
+        assert!(registry.take(2, 10).is_empty());
+
+        // The host watcher's report of a VFS write is an echo
+        registry.notify_host(Path::new("/srv/app/index.js"), WatchEventKind::Create, now);
+        registry.notify_host(Path::new("/srv/app/new.js"), WatchEventKind::Create, now);
+        assert_eq!(registry.take(1, 10)[0].path, "/app/new.js");
+        registry.notify_host(
+            Path::new("/srv/app/index.js"),
+            WatchEventKind::Modify,
+            now + ECHO_WINDOW,
+        );
+        assert_eq!(registry.take(1, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_overflow_and_removal() {
+        let mut registry = WatchRegistry::default();
+        let wd = registry.add(1, "/app", PathBuf::from("/srv/app"), false);
+        registry.add(2, "/app", PathBuf::from("/srv/app"), false);
+
+        for i in 0..MAX_QUEUED_EVENTS + 5 {
+            registry.notify(
+                Path::new(&format!("/srv/app/{i}.js")),
+                WatchEventKind::Create,
+            );
+        }
+        let events = registry.take(1, usize::MAX);
+        assert_eq!(events.len(), MAX_QUEUED_EVENTS + 1);
+        assert_eq!(events.last().unwrap().kind, WatchEventKind::Overflow);
+
+        assert_eq!(registry.remove(1, wd), Some(None));
+        assert_eq!(registry.remove(1, wd), None);
+        assert_eq!(registry.remove_process(2), vec![PathBuf::from("/srv/app")]);
+    }
+}
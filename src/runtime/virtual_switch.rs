@@ -0,0 +1,321 @@
+//! Guest-to-guest networking. Every kernel process gets a virtual IP in
+//! `10.88.0.0/16`; stream sockets bound to it are reachable from other
+//! guests through in-memory pipes, never touching a host socket.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Shutdown, SocketAddr, SocketAddrV4};
+use std::sync::{Arc, Mutex};
+
+use crate::runtime::microkernel::Pid;
+
+pub const VIRTUAL_SUBNET: Ipv4Addr = Ipv4Addr::new(10, 88, 0, 0);
+/// Suffix guests use to look up another process by name
+pub const LOCAL_DOMAIN: &str = ".local";
+
+const SUBNET_MASK: u32 = 0xffff_0000;
+/// Connections waiting in a listener's queue before new ones are refused
+const BACKLOG: usize = 128;
+const EPHEMERAL_START: u16 = 49152;
+
+/// The address of `pid` on the virtual network. PID 0 is `10.88.0.1`.
+pub fn virtual_ip(pid: Pid) -> Ipv4Addr {
+    let host = (pid % (!SUBNET_MASK - 1)) + 1;
+    Ipv4Addr::from(u32::from(VIRTUAL_SUBNET) | host)
+}
+
+pub fn pid_for_ip(ip: Ipv4Addr) -> Option<Pid> {
+    is_virtual(IpAddr::V4(ip)).then(|| (u32::from(ip) & !SUBNET_MASK) - 1)
+}
+
+pub fn is_virtual(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let host = u32::from(ip) & !SUBNET_MASK;
+            u32::from(ip) & SUBNET_MASK == u32::from(VIRTUAL_SUBNET)
+                && host != 0
+                && host != !SUBNET_MASK
+        }
+        IpAddr::V6(_) => false,
+    }
+}
+
+/// Routes stream connections between guest processes
+#[derive(Clone, Default)]
+pub struct VirtualSwitch {
+    state: Arc<Mutex<SwitchState>>,
+}
+
+#[derive(Default)]
+struct SwitchState {
+    listeners: HashMap<SocketAddrV4, Listener>,
+    next_ephemeral: HashMap<Ipv4Addr, u16>,
+}
+
+struct Listener {
+    owner: Pid,
+    pending: VecDeque<VirtualStream>,
+}
+
+/// A listener on the virtual network, for `/api` views
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VirtualListenerInfo {
+    pub pid: Pid,
+    pub address: SocketAddr,
+    pub pending: usize,
+}
+
+impl VirtualSwitch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept connections on `addr`, which must be `owner`'s virtual IP
+    pub fn listen(&self, owner: Pid, addr: SocketAddrV4) -> io::Result<()> {
+        if pid_for_ip(*addr.ip()) != Some(owner) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                format!("{} is not the virtual address of PID {owner}", addr.ip()),
+            ));
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.listeners.contains_key(&addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{addr} is already in use"),
+            ));
+        }
+        state.listeners.insert(
+            addr,
+            Listener {
+                owner,
+                pending: VecDeque::new(),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn unlisten(&self, addr: SocketAddrV4) {
+        self.state.lock().unwrap().listeners.remove(&addr);
+    }
+
+    /// A free port on `ip` for an unbound socket or a port 0 bind
+    pub fn ephemeral_port(&self, ip: Ipv4Addr) -> u16 {
+        let mut state = self.state.lock().unwrap();
+        let next = state.next_ephemeral.entry(ip).or_insert(EPHEMERAL_START);
+        let port = *next;
+        *next = if port == u16::MAX {
+            EPHEMERAL_START
+        } else {
+            port + 1
+        };
+        port
+    }
+
+    /// Connect from `from` to a listener, queueing the other end for it to
+    /// accept
+    pub fn connect(&self, from: SocketAddrV4, to: SocketAddrV4) -> io::Result<VirtualStream> {
+        let mut state = self.state.lock().unwrap();
+        let listener = state.listeners.get_mut(&to).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("Nothing is listening on {to}"),
+            )
+        })?;
+        if listener.pending.len() >= BACKLOG {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("{to} has too many pending connections"),
+            ));
+        }
+        let (client, server) = VirtualStream::pair(from, to);
+        listener.pending.push_back(server);
+        Ok(client)
+    }
+
+    /// The next connection waiting on `addr`, if any
+    pub fn accept(&self, addr: SocketAddrV4) -> Option<VirtualStream> {
+        let mut state = self.state.lock().unwrap();
+        state.listeners.get_mut(&addr)?.pending.pop_front()
+    }
+
+    /// Whether a connection is waiting on `addr`
+    pub fn has_pending(&self, addr: SocketAddrV4) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .listeners
+            .get(&addr)
+            .is_some_and(|listener| !listener.pending.is_empty())
+    }
+
+    /// Close every listener `pid` owns
+    pub fn release_process(&self, pid: Pid) {
+        let mut state = self.state.lock().unwrap();
+        state.listeners.retain(|_, listener| listener.owner != pid);
+        state.next_ephemeral.remove(&virtual_ip(pid));
+    }
+
+    pub fn listeners(&self) -> Vec<VirtualListenerInfo> {
+        let state = self.state.lock().unwrap();
+        let mut listeners: Vec<VirtualListenerInfo> = state
+            .listeners
+            .iter()
+            .map(|(addr, listener)| VirtualListenerInfo {
+                pid: listener.owner,
+                address: SocketAddr::V4(*addr),
+                pending: listener.pending.len(),
+            })
+            .collect();
+        listeners.sort_by_key(|l| l.address);
+        listeners
+    }
+}
+
+/// One direction of a connection
+#[derive(Default)]
+struct Pipe {
+    buffer: Mutex<(VecDeque<u8>, bool)>,
+}
+
+impl Pipe {
+    fn close(&self) {
+        self.buffer.lock().unwrap().1 = true;
+    }
+}
+
+/// One end of an in-memory stream connection. Reads never block: an empty
+/// pipe that is still open is `WouldBlock`, a closed one reads as EOF.
+pub struct VirtualStream {
+    local: SocketAddrV4,
+    peer: SocketAddrV4,
+    incoming: Arc<Pipe>,
+    outgoing: Arc<Pipe>,
+}
+
+impl VirtualStream {
+    fn pair(client: SocketAddrV4, server: SocketAddrV4) -> (Self, Self) {
+        let to_server = Arc::new(Pipe::default());
+        let to_client = Arc::new(Pipe::default());
+        (
+            Self {
+                local: client,
+                peer: server,
+                incoming: Arc::clone(&to_client),
+                outgoing: Arc::clone(&to_server),
+            },
+            Self {
+                local: server,
+                peer: client,
+                incoming: to_server,
+                outgoing: to_client,
+            },
+        )
+    }
+
+    pub fn peer_addr(&self) -> SocketAddr {
+        SocketAddr::V4(self.peer)
+    }
+
+    pub fn read(&self, max_len: usize) -> io::Result<Vec<u8>> {
+        let mut incoming = self.incoming.buffer.lock().unwrap();
+        let (buffer, closed) = &mut *incoming;
+        if buffer.is_empty() && !*closed {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let len = max_len.min(buffer.len());
+        Ok(buffer.drain(..len).collect())
+    }
+
+    /// Whether `read` would return without `WouldBlock`: data is waiting or
+    /// the peer has closed
+    pub fn readable(&self) -> bool {
+        let incoming = self.incoming.buffer.lock().unwrap();
+        !incoming.0.is_empty() || incoming.1
+    }
+
+    pub fn write(&self, data: &[u8]) -> io::Result<usize> {
+        let mut outgoing = self.outgoing.buffer.lock().unwrap();
+        if outgoing.1 {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        outgoing.0.extend(data);
+        Ok(data.len())
+    }
+
+    pub fn shutdown(&self, how: Shutdown) {
+        if matches!(how, Shutdown::Read | Shutdown::Both) {
+            self.incoming.close();
+        }
+        if matches!(how, Shutdown::Write | Shutdown::Both) {
+            self.outgoing.close();
+        }
+    }
+}
+
+impl Drop for VirtualStream {
+    fn drop(&mut self) {
+        self.shutdown(Shutdown::Both);
+    }
+}
+
+impl fmt::Debug for VirtualStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualStream")
+            .field("local", &self.local)
+            .field("peer", &self.peer)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_ips() {
+        assert_eq!(virtual_ip(0), Ipv4Addr::new(10, 88, 0, 1));
+        assert_eq!(virtual_ip(300), Ipv4Addr::new(10, 88, 1, 45));
+        assert_eq!(pid_for_ip(virtual_ip(300)), Some(300));
+        assert_eq!(pid_for_ip(Ipv4Addr::new(10, 88, 0, 0)), None);
+        assert_eq!(pid_for_ip(Ipv4Addr::new(127, 0, 0, 1)), None);
+    }
+
+    #[test]
+    fn test_switch_connects_streams() {
+        let switch = VirtualSwitch::new();
+        let server = SocketAddrV4::new(virtual_ip(1), 8080);
+        let client = SocketAddrV4::new(virtual_ip(2), switch.ephemeral_port(virtual_ip(2)));
+
+        assert_eq!(
+            switch.connect(client, server).unwrap_err().kind(),
+            io::ErrorKind::ConnectionRefused
+        );
+        // Only the owner can listen on its address
+        assert!(switch.listen(2, server).is_err());
+        switch.listen(1, server).unwrap();
+        assert!(switch.listen(1, server).is_err());
+
+        let client_end = switch.connect(client, server).unwrap();
+        let server_end = switch.accept(server).unwrap();
+        assert!(switch.accept(server).is_none());
+        assert_eq!(server_end.peer_addr(), SocketAddr::V4(client));
+
+        assert_eq!(
+            server_end.read(16).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+        client_end.write(b"hello").unwrap();
+        assert_eq!(server_end.read(3).unwrap(), b"hel");
+        assert_eq!(server_end.read(16).unwrap(), b"lo");
+
+        server_end.write(b"bye").unwrap();
+        drop(server_end);
+        assert_eq!(client_end.read(16).unwrap(), b"bye");
+        assert_eq!(client_end.read(16).unwrap(), b"");
+        assert!(client_end.write(b"more").is_err());
+
+        switch.release_process(1);
+        assert!(switch.listeners().is_empty());
+    }
+}
@@ -861,16 +861,33 @@ pub fn create_wasi_linker(env: Arc<Mutex<WasiEnv>>) -> Linker {
         )),
     );
 
-    // poll_oneoff (stub)
-    linker.register(
-        WASI_MODULE,
-        "poll_oneoff",
-        Box::new(ClosureHostFunction::new(
-            |_args, _mem| Ok(vec![Value::I32(syscalls::WASI_ENOSYS)]),
-            4,
-            1,
-        )),
-    );
+    // poll_oneoff
+    {
+        let env = env.clone();
+        linker.register(
+            WASI_MODULE,
+            "poll_oneoff",
+            Box::new(ClosureHostFunction::new(
+                move |args, mem| {
+                    let in_ptr = i32_arg(&args, 0)? as u32;
+                    let out_ptr = i32_arg(&args, 1)? as u32;
+                    let nsubscriptions = i32_arg(&args, 2)? as u32;
+                    let nevents_ptr = i32_arg(&args, 3)? as u32;
+                    let errno = syscalls::poll_oneoff(
+                        in_ptr,
+                        out_ptr,
+                        nsubscriptions,
+                        nevents_ptr,
+                        mem,
+                        &env,
+                    );
+                    Ok(vec![Value::I32(errno)])
+                },
+                4,
+                1,
+            )),
+        );
+    }
 
     // sched_yield (stub)
     linker.register(
@@ -2,7 +2,7 @@
 
 use crate::runtime::core::memory::LinearMemory;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::{FdKind, WasiEnv, WASI_STDERR_FD, WASI_STDIN_FD, WASI_STDOUT_FD};
 
@@ -37,6 +37,13 @@ const WASI_WHENCE_SET: u32 = 0;
 const WASI_WHENCE_CUR: u32 = 1;
 const WASI_WHENCE_END: u32 = 2;
 
+const WASI_EVENTTYPE_CLOCK: u8 = 0;
+const WASI_EVENTTYPE_FD_READ: u8 = 1;
+const WASI_EVENTTYPE_FD_WRITE: u8 = 2;
+const WASI_SUBSCRIPTION_CLOCK_ABSTIME: u16 = 1;
+const WASI_SUBSCRIPTION_SIZE: usize = 48;
+const WASI_EVENT_SIZE: usize = 32;
+
 fn read_guest_string(ptr: u32, len: u32, memory: &LinearMemory) -> Result<String, i32> {
     let bytes = memory
         .read_bytes(ptr as usize, len as usize)
@@ -930,20 +937,27 @@ pub fn environ_get(
 
 // ── clock & random ────────────────────────────────────────────────────
 
+/// Both clocks read wall time, so absolute `poll_oneoff` deadlines compare
+/// against what the guest got from `clock_time_get`
+fn clock_now(clock_id: u32) -> Result<u64, i32> {
+    match clock_id {
+        WASI_CLOCK_REALTIME | WASI_CLOCK_MONOTONIC => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .map_err(|_| WASI_EIO),
+        _ => Err(WASI_EINVAL),
+    }
+}
+
 pub fn clock_time_get(
     clock_id: u32,
     _precision: i64,
     time_ptr: u32,
     memory: &mut LinearMemory,
 ) -> i32 {
-    let nanos: i64 = match clock_id {
-        WASI_CLOCK_REALTIME | WASI_CLOCK_MONOTONIC => {
-            match SystemTime::now().duration_since(UNIX_EPOCH) {
-                Ok(d) => d.as_nanos() as i64,
-                Err(_) => return WASI_EIO,
-            }
-        }
-        _ => return WASI_EINVAL,
+    let nanos = match clock_now(clock_id) {
+        Ok(n) => n as i64,
+        Err(errno) => return errno,
     };
 
     if memory.write_i64(time_ptr as usize, nanos).is_err() {
@@ -952,6 +966,159 @@ pub fn clock_time_get(
     WASI_ESUCCESS
 }
 
+// ── poll ──────────────────────────────────────────────────────────────
+
+struct PollEvent {
+    userdata: i64,
+    error: i32,
+    kind: u8,
+    nbytes: u64,
+}
+
+/// Whether `fd` can be read or written without blocking, and for reads
+/// how many bytes are left
+fn fd_readiness(fd: u32, write: bool, env: &Arc<Mutex<WasiEnv>>) -> Result<u64, i32> {
+    let e = env.lock().map_err(|_| WASI_EIO)?;
+    let entry = e.get_fd(fd).ok_or(WASI_EBADF)?;
+    match (entry.kind, write) {
+        // stdin reads as EOF, which never blocks
+        (FdKind::Stdin, false) | (FdKind::Stdout | FdKind::Stderr, true) => Ok(0),
+        (FdKind::File, false) => {
+            let size = std::fs::metadata(&entry.host_path)
+                .map_err(|_| WASI_EIO)?
+                .len();
+            Ok(size.saturating_sub(entry.offset))
+        }
+        (FdKind::File, true) => Ok(0),
+        (FdKind::PreopenDir | FdKind::Directory, _) => Err(WASI_EISDIR),
+        _ => Err(WASI_EBADF),
+    }
+}
+
+/// poll_oneoff: wait until at least one subscription fires. Files and stdio
+/// never block, so fd subscriptions are reported at once; when there are
+/// none, this sleeps until the earliest clock deadline.
+pub fn poll_oneoff(
+    in_ptr: u32,
+    out_ptr: u32,
+    nsubscriptions: u32,
+    nevents_ptr: u32,
+    memory: &mut LinearMemory,
+    env: &Arc<Mutex<WasiEnv>>,
+) -> i32 {
+    if nsubscriptions == 0 {
+        return WASI_EINVAL;
+    }
+
+    // subscription layout (48 bytes):
+    //   0: userdata  8: tag (u8)
+    //  clock:       16: id (u32)  24: timeout  32: precision  40: flags (u16)
+    //  fd_readwrite: 16: fd (u32)
+    let mut events: Vec<PollEvent> = Vec::new();
+    let mut clocks: Vec<(i64, Result<u64, i32>)> = Vec::new();
+    for i in 0..nsubscriptions as usize {
+        let base = in_ptr as usize + i * WASI_SUBSCRIPTION_SIZE;
+        let (userdata, tag) = match (memory.read_i64(base), memory.read_u8(base + 8)) {
+            (Ok(u), Ok(t)) => (u, t),
+            _ => return WASI_EINVAL,
+        };
+        match tag {
+            WASI_EVENTTYPE_CLOCK => {
+                let (id, timeout, flags) = match (
+                    memory.read_i32(base + 16),
+                    memory.read_i64(base + 24),
+                    memory.read_u16(base + 40),
+                ) {
+                    (Ok(id), Ok(t), Ok(f)) => (id as u32, t as u64, f),
+                    _ => return WASI_EINVAL,
+                };
+                // Deadlines as wall-clock nanoseconds
+                let deadline = clock_now(id).map(|now| {
+                    if flags & WASI_SUBSCRIPTION_CLOCK_ABSTIME != 0 {
+                        timeout
+                    } else {
+                        now.saturating_add(timeout)
+                    }
+                });
+                clocks.push((userdata, deadline));
+            }
+            WASI_EVENTTYPE_FD_READ | WASI_EVENTTYPE_FD_WRITE => {
+                let fd = match memory.read_i32(base + 16) {
+                    Ok(fd) => fd as u32,
+                    Err(_) => return WASI_EINVAL,
+                };
+                let readiness = fd_readiness(fd, tag == WASI_EVENTTYPE_FD_WRITE, env);
+                events.push(PollEvent {
+                    userdata,
+                    error: readiness.err().unwrap_or(WASI_ESUCCESS),
+                    kind: tag,
+                    nbytes: readiness.unwrap_or(0),
+                });
+            }
+            _ => return WASI_EINVAL,
+        }
+    }
+
+    // A clock on an unknown id fails right away, like a ready fd
+    for (userdata, deadline) in &clocks {
+        if let Err(errno) = deadline {
+            events.push(PollEvent {
+                userdata: *userdata,
+                error: *errno,
+                kind: WASI_EVENTTYPE_CLOCK,
+                nbytes: 0,
+            });
+        }
+    }
+
+    if events.is_empty() {
+        let first = clocks.iter().filter_map(|(_, d)| d.ok()).min().unwrap_or(0);
+        let now = match clock_now(WASI_CLOCK_REALTIME) {
+            Ok(n) => n,
+            Err(errno) => return errno,
+        };
+        if first > now {
+            std::thread::sleep(Duration::from_nanos(first - now));
+        }
+        events.extend(
+            clocks
+                .iter()
+                .filter(|(_, d)| matches!(d, Ok(deadline) if *deadline <= first))
+                .map(|(userdata, _)| PollEvent {
+                    userdata: *userdata,
+                    error: WASI_ESUCCESS,
+                    kind: WASI_EVENTTYPE_CLOCK,
+                    nbytes: 0,
+                }),
+        );
+    }
+
+    // event layout (32 bytes):
+    //   0: userdata  8: error (u16)  10: type (u8)  16: nbytes  24: flags (u16)
+    for (i, event) in events.iter().enumerate() {
+        let base = out_ptr as usize + i * WASI_EVENT_SIZE;
+        for offset in 0..WASI_EVENT_SIZE {
+            if memory.write_u8(base + offset, 0).is_err() {
+                return WASI_EINVAL;
+            }
+        }
+        if memory.write_i64(base, event.userdata).is_err()
+            || memory.write_u16(base + 8, event.error as u16).is_err()
+            || memory.write_u8(base + 10, event.kind).is_err()
+            || memory.write_i64(base + 16, event.nbytes as i64).is_err()
+        {
+            return WASI_EINVAL;
+        }
+    }
+    if memory
+        .write_i32(nevents_ptr as usize, events.len() as i32)
+        .is_err()
+    {
+        return WASI_EINVAL;
+    }
+    WASI_ESUCCESS
+}
+
 /// fd_fdstat_set_flags: set file descriptor flags (WASI Preview 1).
 /// Most WASM runtimes ignore this silently; return success.
 pub fn fd_fdstat_set_flags(_fd: u32, _flags: u16) -> i32 {
@@ -1224,6 +1391,79 @@ mod tests {
         assert_eq!(clock_time_get(99, 0, 0, &mut mem), WASI_EINVAL);
     }
 
+    fn write_clock_subscription(mem: &mut LinearMemory, at: usize, userdata: i64, timeout: i64) {
+        mem.write_i64(at, userdata).unwrap();
+        mem.write_u8(at + 8, WASI_EVENTTYPE_CLOCK).unwrap();
+        mem.write_i32(at + 16, WASI_CLOCK_MONOTONIC as i32).unwrap();
+        mem.write_i64(at + 24, timeout).unwrap();
+    }
+
+    #[test]
+    fn test_poll_oneoff_sleeps_until_first_clock() {
+        let env = make_env();
+        let mut mem = LinearMemory::new(1, None).unwrap();
+        write_clock_subscription(&mut mem, 0, 7, 20_000_000);
+        write_clock_subscription(&mut mem, 48, 8, 5_000_000_000);
+
+        let started = std::time::Instant::now();
+        assert_eq!(poll_oneoff(0, 1024, 2, 2048, &mut mem, &env), WASI_ESUCCESS);
+        let waited = started.elapsed();
+        assert!(waited >= Duration::from_millis(20), "{waited:?}");
+        assert!(waited < Duration::from_secs(2), "{waited:?}");
+
+        assert_eq!(mem.read_i32(2048).unwrap(), 1);
+        assert_eq!(mem.read_i64(1024).unwrap(), 7);
+        assert_eq!(mem.read_u16(1032).unwrap(), 0);
+        assert_eq!(mem.read_u8(1034).unwrap(), WASI_EVENTTYPE_CLOCK);
+    }
+
+    #[test]
+    fn test_poll_oneoff_reports_ready_fds_without_waiting() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("data.txt"), b"hello").unwrap();
+        let env = Arc::new(Mutex::new(WasiEnv::new().with_preopen("/", dir.path())));
+        let mut mem = LinearMemory::new(1, None).unwrap();
+
+        mem.write_bytes(4000, b"data.txt").unwrap();
+        assert_eq!(
+            path_open(3, 4000, 8, 0, 0, 4100, &mut mem, &env),
+            WASI_ESUCCESS
+        );
+        let file_fd = mem.read_i32(4100).unwrap();
+
+        // An hour-long clock next to fds that are ready now
+        write_clock_subscription(&mut mem, 0, 1, 3_600_000_000_000);
+        for (i, (userdata, tag, fd)) in [
+            (2, WASI_EVENTTYPE_FD_READ, file_fd),
+            (3, WASI_EVENTTYPE_FD_WRITE, WASI_STDOUT_FD as i32),
+            (4, WASI_EVENTTYPE_FD_READ, 99),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let at = (i + 1) * 48;
+            mem.write_i64(at, userdata).unwrap();
+            mem.write_u8(at + 8, tag).unwrap();
+            mem.write_i32(at + 16, fd).unwrap();
+        }
+
+        assert_eq!(poll_oneoff(0, 1024, 4, 2048, &mut mem, &env), WASI_ESUCCESS);
+        assert_eq!(mem.read_i32(2048).unwrap(), 3);
+        let event = |i: usize| {
+            let at = 1024 + i * 32;
+            (
+                mem.read_i64(at).unwrap(),
+                mem.read_u16(at + 8).unwrap() as i32,
+                mem.read_i64(at + 16).unwrap(),
+            )
+        };
+        assert_eq!(event(0), (2, WASI_ESUCCESS, 5));
+        assert_eq!(event(1), (3, WASI_ESUCCESS, 0));
+        assert_eq!(event(2), (4, WASI_EBADF, 0));
+
+        assert_eq!(poll_oneoff(0, 1024, 0, 2048, &mut mem, &env), WASI_EINVAL);
+    }
+
     #[test]
     fn test_random_get_fills_buffer() {
         let mut mem = LinearMemory::new(1, None).unwrap();
@@ -5,21 +5,122 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use crate::config::project::FsConfig;
+use crate::runtime::microkernel::Pid;
+use crate::runtime::vfs_watch::{self, WatchEvent, WatchEventKind, WatchRegistry};
 
 /// WASI file descriptor type
 pub type WasiFd = u32;
 
 /// WASI filesystem manager for OS mode
+///
+/// Clones share all state. A clone made with [`for_process`] additionally
+/// resolves paths through that process's own mount table, which is where
+/// named volumes and per-process host mounts live.
+///
+/// [`for_process`]: WasiFilesystem::for_process
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct WasiFilesystem {
     mounts: Arc<RwLock<HashMap<String, PathBuf>>>,
+    process_mounts: Arc<RwLock<HashMap<Pid, HashMap<String, ProcessMount>>>>,
+    volume_root: Arc<RwLock<Option<PathBuf>>>,
     fd_table: Arc<RwLock<HashMap<WasiFd, OpenFile>>>,
     next_fd: Arc<RwLock<WasiFd>>,
     config: WasiConfig,
+    scope: Option<Pid>,
+    watches: Arc<Mutex<WatchRegistry>>,
+    /// Started with the first watch, to catch edits made outside the kernel
+    host_watcher: Arc<Mutex<Option<notify::RecommendedWatcher>>>,
+    /// Bytes each guest path may hold
+    quotas: Arc<RwLock<BTreeMap<String, u64>>>,
+    /// Bytes under each quota's host directory, measured on first use and
+    /// then kept current by writes through the VFS
+    quota_usage: Arc<Mutex<HashMap<PathBuf, u64>>>,
+    /// Net bytes each process has added through the VFS
+    process_usage: Arc<Mutex<HashMap<Pid, u64>>>,
+}
+
+/// A mount visible to a single process
+#[derive(Debug, Clone)]
+struct ProcessMount {
+    host: PathBuf,
+    read_only: bool,
+    volume: Option<String>,
+}
+
+/// A mount request: a named volume or a host directory at a guest path.
+/// The short form is `SOURCE:GUEST[:ro|rw]`, e.g. `shared:/data:ro`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MountSpec {
+    pub guest: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+}
+
+impl MountSpec {
+    /// Parse `SOURCE:GUEST[:ro|rw]`. A source made only of letters, digits,
+    /// `-`, `_` and `.` names a volume; anything else is a host path.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (rest, read_only) = match spec.rsplit_once(':') {
+            Some((rest, "ro")) => (rest, true),
+            Some((rest, "rw")) => (rest, false),
+            _ => (spec, false),
+        };
+        let Some((source, guest)) = rest.rsplit_once(':') else {
+            anyhow::bail!("Invalid mount '{spec}': expected SOURCE:GUEST[:ro|rw]");
+        };
+        let mount = if is_volume_name(source) {
+            Self {
+                guest: guest.to_string(),
+                host: None,
+                volume: Some(source.to_string()),
+                read_only,
+            }
+        } else {
+            Self {
+                guest: guest.to_string(),
+                host: Some(source.to_string()),
+                volume: None,
+                read_only,
+            }
+        };
+        mount.validate()?;
+        Ok(mount)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !self.guest.starts_with('/') || self.guest.split('/').any(|s| s == "..") {
+            anyhow::bail!("Mount guest path must be absolute: {}", self.guest);
+        }
+        match (&self.host, &self.volume) {
+            (Some(_), None) => Ok(()),
+            (None, Some(volume)) if is_volume_name(volume) => Ok(()),
+            (None, Some(volume)) => anyhow::bail!("Invalid volume name: {volume}"),
+            _ => anyhow::bail!(
+                "Mount at {} needs exactly one of host or volume",
+                self.guest
+            ),
+        }
+    }
+}
+
+fn is_volume_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
 }
 
 /// WASI filesystem configuration
@@ -89,12 +190,292 @@ impl WasiFilesystem {
     pub fn with_config(config: WasiConfig) -> Self {
         Self {
             mounts: Arc::new(RwLock::new(HashMap::new())),
+            process_mounts: Arc::new(RwLock::new(HashMap::new())),
+            volume_root: Arc::new(RwLock::new(None)),
             fd_table: Arc::new(RwLock::new(HashMap::new())),
             next_fd: Arc::new(RwLock::new(3)), // 0, 1, 2 reserved for stdin, stdout, stderr
             config,
+            scope: None,
+            watches: Arc::new(Mutex::new(WatchRegistry::default())),
+            host_watcher: Arc::new(Mutex::new(None)),
+            quotas: Arc::new(RwLock::new(BTreeMap::new())),
+            quota_usage: Arc::new(Mutex::new(HashMap::new())),
+            process_usage: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Cap the bytes stored under guest paths. Writes that would take a
+    /// path past its quota fail; existing files are left alone.
+    pub fn set_quotas(&self, config: &FsConfig) -> Result<()> {
+        let mut quotas = BTreeMap::new();
+        for (guest, mb) in &config.quotas {
+            if !guest.starts_with('/') || guest.split('/').any(|s| s == "..") {
+                anyhow::bail!("Quota path must be absolute: {guest}");
+            }
+            let guest = match guest.trim_end_matches('/') {
+                "" => "/",
+                trimmed => trimmed,
+            };
+            quotas.insert(guest.to_string(), mb.saturating_mul(1024 * 1024));
+        }
+        *self.quotas.write().unwrap() = quotas;
+        self.quota_usage.lock().unwrap().clear();
+        Ok(())
+    }
+
+    /// A view of this filesystem as `pid` sees it: its own mounts on top of
+    /// the shared ones
+    pub fn for_process(&self, pid: Pid) -> Self {
+        Self {
+            scope: Some(pid),
+            ..self.clone()
+        }
+    }
+
+    /// Directory under which named volumes are created
+    pub fn set_volume_root(&self, root: impl AsRef<Path>) -> Result<()> {
+        fs::create_dir_all(root.as_ref())?;
+        *self.volume_root.write().unwrap() = Some(root.as_ref().to_path_buf());
+        Ok(())
+    }
+
+    /// Create a named volume if it doesn't exist yet and return its host directory
+    pub fn create_volume(&self, name: &str) -> Result<PathBuf> {
+        if !is_volume_name(name) {
+            anyhow::bail!("Invalid volume name: {name}");
+        }
+        let root = self
+            .volume_root
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No volume root configured"))?;
+        let dir = root.join(name);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Every volume on disk, with the processes that have it mounted
+    pub fn list_volumes(&self) -> Vec<VolumeInfo> {
+        let Some(root) = self.volume_root.read().unwrap().clone() else {
+            return Vec::new();
+        };
+        let process_mounts = self.process_mounts.read().unwrap();
+        let mut volumes: Vec<VolumeInfo> = fs::read_dir(&root)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let mut mounted_by: Vec<VolumeMount> = process_mounts
+                    .iter()
+                    .flat_map(|(pid, mounts)| {
+                        mounts
+                            .iter()
+                            .filter(|(_, m)| m.volume.as_deref() == Some(name.as_str()))
+                            .map(|(guest, m)| VolumeMount {
+                                pid: *pid,
+                                guest_path: guest.clone(),
+                                read_only: m.read_only,
+                            })
+                    })
+                    .collect();
+                mounted_by.sort_by_key(|m| m.pid);
+                VolumeInfo {
+                    size: Self::calculate_dir_size(&entry.path()).unwrap_or(0),
+                    host_path: entry.path(),
+                    name,
+                    mounted_by,
+                }
+            })
+            .collect();
+        volumes.sort_by(|a, b| a.name.cmp(&b.name));
+        volumes
+    }
+
+    /// Mount a volume (created on first use) or host directory for `pid` only
+    pub fn mount_for_process(&self, pid: Pid, spec: &MountSpec) -> Result<()> {
+        spec.validate()?;
+        let host = match (&spec.volume, &spec.host) {
+            (Some(volume), _) => self.create_volume(volume)?,
+            (None, Some(host)) => {
+                let host = Path::new(host);
+                if !host.is_dir() {
+                    anyhow::bail!("Cannot mount non-directory path: {}", host.display());
+                }
+                host.canonicalize()?
+            }
+            (None, None) => unreachable!("validated above"),
+        };
+        self.process_mounts
+            .write()
+            .unwrap()
+            .entry(pid)
+            .or_default()
+            .insert(
+                spec.guest.trim_end_matches('/').to_string(),
+                ProcessMount {
+                    host,
+                    read_only: spec.read_only,
+                    volume: spec.volume.clone(),
+                },
+            );
+        Ok(())
+    }
+
+    /// Drop every mount and watch belonging to `pid`
+    pub fn unmount_process(&self, pid: Pid) {
+        self.process_mounts.write().unwrap().remove(&pid);
+        self.process_usage.lock().unwrap().remove(&pid);
+        let unused = self.watches.lock().unwrap().remove_process(pid);
+        self.unwatch_host(&unused);
+    }
+
+    /// Watch `virtual_path` on behalf of the scoped process and return the
+    /// watch descriptor. Events are collected with
+    /// [`take_watch_events`](Self::take_watch_events).
+    pub fn add_watch(&self, virtual_path: &str, recursive: bool) -> Result<i32> {
+        let Some(pid) = self.scope else {
+            anyhow::bail!("Watches belong to a process");
+        };
+        let host = self
+            .resolve_path(virtual_path, false)?
+            .canonicalize()
+            .with_context(|| format!("No such file or directory: {virtual_path}"))?;
+        let guest = match virtual_path.trim_end_matches('/') {
+            "" => "/",
+            trimmed => trimmed,
+        };
+        let wd = self
+            .watches
+            .lock()
+            .unwrap()
+            .add(pid, guest, host.clone(), recursive);
+        self.watch_host(&host, recursive);
+        Ok(wd)
+    }
+
+    pub fn remove_watch(&self, wd: i32) -> Result<()> {
+        let Some(pid) = self.scope else {
+            anyhow::bail!("Watches belong to a process");
+        };
+        let unused = self
+            .watches
+            .lock()
+            .unwrap()
+            .remove(pid, wd)
+            .ok_or_else(|| anyhow::anyhow!("Invalid watch descriptor: {wd}"))?;
+        self.unwatch_host(unused.as_slice());
+        Ok(())
+    }
+
+    /// Pending events for the scoped process, oldest first
+    pub fn take_watch_events(&self, max: usize) -> Vec<WatchEvent> {
+        match self.scope {
+            Some(pid) => self.watches.lock().unwrap().take(pid, max),
+            None => Vec::new(),
         }
     }
 
+    fn watch_host(&self, host: &Path, recursive: bool) {
+        use notify::Watcher;
+
+        let mut host_watcher = self.host_watcher.lock().unwrap();
+        if host_watcher.is_none() {
+            let watches = Arc::clone(&self.watches);
+            let watcher =
+                notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                    let Ok(event) = event else { return };
+                    let mut watches = watches.lock().unwrap();
+                    for (path, kind) in vfs_watch::host_events(&event) {
+                        watches.notify_host(&path, kind, Instant::now());
+                    }
+                });
+            match watcher {
+                Ok(watcher) => *host_watcher = Some(watcher),
+                Err(e) => {
+                    // VFS writes are still reported; only outside edits are missed
+                    crate::debug_println!("Host file watcher unavailable: {e}");
+                    return;
+                }
+            }
+        }
+        let mode = if recursive || self.watches.lock().unwrap().is_recursive(host) {
+            notify::RecursiveMode::Recursive
+        } else {
+            notify::RecursiveMode::NonRecursive
+        };
+        if let Some(watcher) = host_watcher.as_mut() {
+            if let Err(e) = watcher.watch(host, mode) {
+                crate::debug_println!("Failed to watch {}: {e}", host.display());
+            }
+        }
+    }
+
+    fn unwatch_host(&self, hosts: &[PathBuf]) {
+        use notify::Watcher;
+
+        if let Some(watcher) = self.host_watcher.lock().unwrap().as_mut() {
+            for host in hosts {
+                let _ = watcher.unwatch(host);
+            }
+        }
+    }
+
+    /// Run `change` on `host_path` and report it to any watches covering it
+    fn watched<T>(
+        &self,
+        host_path: &Path,
+        kind: WatchEventKind,
+        change: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        if self.watches.lock().unwrap().is_empty() {
+            return change();
+        }
+        // Watches are keyed by canonical paths; a path that doesn't exist yet
+        // has no canonical form of its own, so go through its parent
+        let key = host_path.canonicalize().unwrap_or_else(|_| {
+            match (
+                host_path.parent().and_then(|p| p.canonicalize().ok()),
+                host_path.file_name(),
+            ) {
+                (Some(parent), Some(name)) => parent.join(name),
+                _ => host_path.to_path_buf(),
+            }
+        });
+        self.watches.lock().unwrap().expect(&key, Instant::now());
+        let result = change()?;
+        self.watches.lock().unwrap().notify(&key, kind);
+        Ok(result)
+    }
+
+    /// Mounts visible in this view as `(guest path, read-only)`, shared ones first
+    pub fn visible_mounts(&self) -> Vec<(String, bool)> {
+        let mut shared: Vec<(String, bool)> = self
+            .mounts
+            .read()
+            .unwrap()
+            .keys()
+            .map(|guest| (guest.clone(), self.config.read_only))
+            .collect();
+        shared.sort();
+        let mut own: Vec<(String, bool)> = self
+            .scope
+            .and_then(|pid| {
+                self.process_mounts.read().unwrap().get(&pid).map(|mounts| {
+                    mounts
+                        .iter()
+                        .map(|(guest, m)| (guest.clone(), m.read_only))
+                        .collect()
+                })
+            })
+            .unwrap_or_default();
+        own.sort();
+        shared.extend(own);
+        shared
+    }
+
     /// Mount a host directory to a virtual path (WASI preopen)
     ///
     /// # Arguments
@@ -144,14 +525,17 @@ impl WasiFilesystem {
             anyhow::bail!("Filesystem is in read-only mode");
         }
 
-        let host_path = self.resolve_path(virtual_path)?;
+        let host_path =
+            self.resolve_path(virtual_path, flags.write || flags.create || flags.truncate)?;
 
         // Create the file if needed
         if flags.create && !host_path.exists() {
             if let Some(parent) = host_path.parent() {
                 fs::create_dir_all(parent)?;
             }
-            fs::File::create(&host_path)?;
+            self.watched(&host_path, WatchEventKind::Create, || {
+                Ok(fs::File::create(&host_path)?)
+            })?;
         }
 
         // Verify the file exists
@@ -161,7 +545,11 @@ impl WasiFilesystem {
 
         // Truncate if requested
         if flags.truncate && host_path.is_file() {
-            fs::File::create(&host_path)?;
+            let old_len = file_len(&host_path);
+            self.watched(&host_path, WatchEventKind::Modify, || {
+                Ok(fs::File::create(&host_path)?)
+            })?;
+            self.record_usage(virtual_path, -(old_len as i64));
         }
 
         // Allocate a file descriptor
@@ -226,29 +614,41 @@ impl WasiFilesystem {
             anyhow::bail!("File not open for writing");
         }
 
-        if open_file.flags.append {
-            // Append mode: write to end of file
-            let mut file = fs::OpenOptions::new().append(true).open(&open_file.path)?;
-            std::io::Write::write_all(&mut file, data)?;
-            open_file.offset += data.len();
+        let path = open_file.path.clone();
+        let virtual_path = open_file.virtual_path.clone();
+        let growth = if open_file.flags.append {
+            data.len() as u64
         } else {
-            // Write mode: write at current offset
-            let mut content = if open_file.path.exists() {
-                fs::read(&open_file.path)?
+            ((open_file.offset + data.len()) as u64).saturating_sub(file_len(&path))
+        };
+        self.check_quota(&virtual_path, growth)?;
+        self.watched(&path, WatchEventKind::Modify, || {
+            if open_file.flags.append {
+                // Append mode: write to end of file
+                let mut file = fs::OpenOptions::new().append(true).open(&open_file.path)?;
+                std::io::Write::write_all(&mut file, data)?;
+                open_file.offset += data.len();
             } else {
-                Vec::new()
-            };
-
-            // Expand content if needed
-            if open_file.offset + data.len() > content.len() {
-                content.resize(open_file.offset + data.len(), 0);
+                // Write mode: write at current offset
+                let mut content = if open_file.path.exists() {
+                    fs::read(&open_file.path)?
+                } else {
+                    Vec::new()
+                };
+
+                // Expand content if needed
+                if open_file.offset + data.len() > content.len() {
+                    content.resize(open_file.offset + data.len(), 0);
+                }
+
+                // Write data at offset
+                content[open_file.offset..open_file.offset + data.len()].copy_from_slice(data);
+                fs::write(&open_file.path, &content)?;
+                open_file.offset += data.len();
             }
-
-            // Write data at offset
-            content[open_file.offset..open_file.offset + data.len()].copy_from_slice(data);
-            fs::write(&open_file.path, &content)?;
-            open_file.offset += data.len();
-        }
+            Ok(())
+        })?;
+        self.record_usage(&virtual_path, growth as i64);
 
         Ok(data.len())
     }
@@ -293,10 +693,14 @@ impl WasiFilesystem {
             anyhow::bail!("Filesystem is in read-only mode");
         }
 
-        let host_path = self.resolve_path(virtual_path)?;
-        fs::create_dir_all(&host_path)
-            .with_context(|| format!("Failed to create directory: {virtual_path}"))?;
-        Ok(())
+        let host_path = self.resolve_path(virtual_path, true)?;
+        if host_path.exists() {
+            return Ok(());
+        }
+        self.watched(&host_path, WatchEventKind::Create, || {
+            fs::create_dir_all(&host_path)
+                .with_context(|| format!("Failed to create directory: {virtual_path}"))
+        })
     }
 
     /// WASI path_remove_directory - Remove a directory
@@ -305,10 +709,11 @@ impl WasiFilesystem {
             anyhow::bail!("Filesystem is in read-only mode");
         }
 
-        let host_path = self.resolve_path(virtual_path)?;
-        fs::remove_dir(&host_path)
-            .with_context(|| format!("Failed to remove directory: {virtual_path}"))?;
-        Ok(())
+        let host_path = self.resolve_path(virtual_path, true)?;
+        self.watched(&host_path, WatchEventKind::Delete, || {
+            fs::remove_dir(&host_path)
+                .with_context(|| format!("Failed to remove directory: {virtual_path}"))
+        })
     }
 
     /// WASI path_unlink_file - Delete a file
@@ -317,15 +722,19 @@ impl WasiFilesystem {
             anyhow::bail!("Filesystem is in read-only mode");
         }
 
-        let host_path = self.resolve_path(virtual_path)?;
-        fs::remove_file(&host_path)
-            .with_context(|| format!("Failed to unlink file: {virtual_path}"))?;
+        let host_path = self.resolve_path(virtual_path, true)?;
+        let old_len = file_len(&host_path);
+        self.watched(&host_path, WatchEventKind::Delete, || {
+            fs::remove_file(&host_path)
+                .with_context(|| format!("Failed to unlink file: {virtual_path}"))
+        })?;
+        self.record_usage(virtual_path, -(old_len as i64));
         Ok(())
     }
 
     /// WASI path_readdir - Read directory entries
     pub fn path_readdir(&self, virtual_path: &str) -> Result<Vec<DirEntry>> {
-        let host_path = self.resolve_path(virtual_path)?;
+        let host_path = self.resolve_path(virtual_path, false)?;
 
         let entries: Result<Vec<DirEntry>> = fs::read_dir(&host_path)?
             .map(|entry| {
@@ -345,7 +754,7 @@ impl WasiFilesystem {
 
     /// WASI path_filestat_get - Get file/directory metadata
     pub fn path_filestat_get(&self, virtual_path: &str) -> Result<FileStats> {
-        let host_path = self.resolve_path(virtual_path)?;
+        let host_path = self.resolve_path(virtual_path, false)?;
         let metadata = fs::metadata(&host_path)?;
 
         Ok(FileStats {
@@ -360,14 +769,14 @@ impl WasiFilesystem {
 
     /// Check if a path exists
     pub fn path_exists(&self, virtual_path: &str) -> bool {
-        self.resolve_path(virtual_path)
+        self.resolve_path(virtual_path, false)
             .map(|p| p.exists())
             .unwrap_or(false)
     }
 
     /// Read entire file contents (helper function)
     pub fn read_file(&self, virtual_path: &str) -> Result<Vec<u8>> {
-        let host_path = self.resolve_path(virtual_path)?;
+        let host_path = self.resolve_path(virtual_path, false)?;
         let data = fs::read(&host_path)?;
 
         if data.len() > self.config.max_file_size {
@@ -377,6 +786,23 @@ impl WasiFilesystem {
         Ok(data)
     }
 
+    /// Open a file to stream its contents, under the same size limit as
+    /// [`Self::read_file`]
+    pub fn open_file(&self, virtual_path: &str) -> Result<fs::File> {
+        let host_path = self.resolve_path(virtual_path, false)?;
+        let file = fs::File::open(&host_path)?;
+        let metadata = file.metadata()?;
+
+        if !metadata.is_file() {
+            anyhow::bail!("Not a file: {virtual_path}");
+        }
+        if metadata.len() > self.config.max_file_size as u64 {
+            anyhow::bail!("File size exceeds maximum allowed size");
+        }
+
+        Ok(file)
+    }
+
     /// Write entire file contents (helper function)
     pub fn write_file(&self, virtual_path: &str, data: &[u8]) -> Result<()> {
         if self.config.read_only {
@@ -387,16 +813,87 @@ impl WasiFilesystem {
             anyhow::bail!("File size exceeds maximum allowed size");
         }
 
-        let host_path = self.resolve_path(virtual_path)?;
+        let host_path = self.resolve_path(virtual_path, true)?;
 
         if let Some(parent) = host_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        fs::write(&host_path, data)?;
+        let kind = if host_path.exists() {
+            WatchEventKind::Modify
+        } else {
+            WatchEventKind::Create
+        };
+        let delta = data.len() as i64 - file_len(&host_path) as i64;
+        self.check_quota(virtual_path, delta.max(0) as u64)?;
+        self.watched(&host_path, kind, || Ok(fs::write(&host_path, data)?))?;
+        self.record_usage(virtual_path, delta);
+        Ok(())
+    }
+
+    /// Quotas covering `virtual_path`, with the host directory each measures
+    fn covering_quotas(&self, virtual_path: &str) -> Vec<(String, u64, PathBuf)> {
+        let quotas = self.quotas.read().unwrap().clone();
+        quotas
+            .into_iter()
+            .filter(|(guest, _)| {
+                guest == "/"
+                    || virtual_path == guest
+                    || virtual_path.starts_with(&format!("{guest}/"))
+            })
+            .filter_map(|(guest, limit)| {
+                let host = self.resolve_path(&guest, false).ok()?;
+                Some((guest, limit, host))
+            })
+            .collect()
+    }
+
+    /// Refuse to grow `virtual_path` by `growth` bytes past any quota on it
+    fn check_quota(&self, virtual_path: &str, growth: u64) -> Result<()> {
+        if growth == 0 {
+            return Ok(());
+        }
+        let quotas = self.covering_quotas(virtual_path);
+        let mut usage = self.quota_usage.lock().unwrap();
+        for (guest, limit, host) in quotas {
+            let used = *usage
+                .entry(host)
+                .or_insert_with_key(|host| Self::calculate_dir_size(host).unwrap_or(0));
+            if used.saturating_add(growth) > limit {
+                anyhow::bail!(
+                    "Disk quota exceeded for {guest}: {used} of {limit} bytes used, {growth} more requested"
+                );
+            }
+        }
         Ok(())
     }
 
+    /// Account a change of `delta` bytes at `virtual_path` after it's made
+    fn record_usage(&self, virtual_path: &str, delta: i64) {
+        if delta == 0 {
+            return;
+        }
+        let apply = |used: &mut u64| {
+            *used = used.saturating_add_signed(delta);
+        };
+        let quotas = self.covering_quotas(virtual_path);
+        let mut usage = self.quota_usage.lock().unwrap();
+        for (_, _, host) in quotas {
+            match usage.get_mut(&host) {
+                Some(used) => apply(used),
+                // Not measured yet: a scan now already includes the change
+                None => {
+                    let used = Self::calculate_dir_size(&host).unwrap_or(0);
+                    usage.insert(host, used);
+                }
+            }
+        }
+        drop(usage);
+        if let Some(pid) = self.scope {
+            apply(self.process_usage.lock().unwrap().entry(pid).or_default());
+        }
+    }
+
     /// Get WASI filesystem statistics
     pub fn get_stats(&self) -> WasiFilesystemStats {
         let mounts = self.mounts.read().unwrap();
@@ -409,41 +906,117 @@ impl WasiFilesystem {
 
         let fd_table = self.fd_table.read().unwrap();
         let open_fds = fd_table.len();
+        let mut mount_infos: Vec<MountInfo> = mounts
+            .iter()
+            .map(|(k, v)| MountInfo {
+                guest_path: k.clone(),
+                host_path: v.clone(),
+                size: Self::calculate_dir_size(v).unwrap_or(0),
+            })
+            .collect();
+        mount_infos.sort_by(|a, b| a.guest_path.cmp(&b.guest_path));
+        drop(mounts);
+
+        // Measured afresh, which also corrects for edits made outside the VFS
+        let quota_list = self.quotas.read().unwrap().clone();
+        let quotas = quota_list
+            .into_iter()
+            .map(|(guest, limit)| {
+                let used = self
+                    .resolve_path(&guest, false)
+                    .map(|host| {
+                        let used = Self::calculate_dir_size(&host).unwrap_or(0);
+                        self.quota_usage.lock().unwrap().insert(host, used);
+                        used
+                    })
+                    .unwrap_or(0);
+                QuotaInfo {
+                    guest_path: guest,
+                    limit,
+                    used,
+                }
+            })
+            .collect();
+
+        let process_mounts = self.process_mounts.read().unwrap();
+        let process_usage = self.process_usage.lock().unwrap();
+        let mut pids: Vec<Pid> = process_mounts
+            .keys()
+            .chain(process_usage.keys())
+            .copied()
+            .collect();
+        pids.sort_unstable();
+        pids.dedup();
+        let processes = pids
+            .into_iter()
+            .map(|pid| {
+                let mut mounts: Vec<MountInfo> = process_mounts
+                    .get(&pid)
+                    .into_iter()
+                    .flatten()
+                    .map(|(guest, m)| MountInfo {
+                        guest_path: guest.clone(),
+                        host_path: m.host.clone(),
+                        size: Self::calculate_dir_size(&m.host).unwrap_or(0),
+                    })
+                    .collect();
+                mounts.sort_by(|a, b| a.guest_path.cmp(&b.guest_path));
+                ProcessFsUsage {
+                    pid,
+                    written: process_usage.get(&pid).copied().unwrap_or(0),
+                    mounts,
+                }
+            })
+            .collect();
 
         WasiFilesystemStats {
             total_mounts,
             total_size,
             open_fds,
-            mounts: mounts
-                .iter()
-                .map(|(k, v)| MountInfo {
-                    guest_path: k.clone(),
-                    host_path: v.clone(),
-                    size: Self::calculate_dir_size(v).unwrap_or(0),
-                })
-                .collect(),
+            mounts: mount_infos,
+            quotas,
+            processes,
         }
     }
 
     /// Resolve a virtual WASI path to a real host path.
-    /// Uses longest-prefix matching so specific mounts shadow broader ones.
-    fn resolve_path(&self, virtual_path: &str) -> Result<PathBuf> {
-        let mounts = self.mounts.read().unwrap();
+    /// Uses longest-prefix matching so specific mounts shadow broader ones;
+    /// on a tie, the scoped process's own mount wins. `write` is refused on
+    /// read-only process mounts.
+    fn resolve_path(&self, virtual_path: &str, write: bool) -> Result<PathBuf> {
+        let mut candidates: Vec<(String, PathBuf, bool, bool)> = self
+            .mounts
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(guest, host)| (guest.clone(), host.clone(), false, false))
+            .collect();
+        if let Some(pid) = self.scope {
+            if let Some(own) = self.process_mounts.read().unwrap().get(&pid) {
+                candidates.extend(
+                    own.iter()
+                        .map(|(guest, m)| (guest.clone(), m.host.clone(), m.read_only, true)),
+                );
+            }
+        }
 
-        let best_match = mounts
+        let best_match = candidates
             .iter()
-            .filter(|(guest_path, _)| {
+            .filter(|(guest_path, ..)| {
                 virtual_path == guest_path.as_str()
                     || virtual_path.starts_with(&format!("{}/", guest_path.trim_end_matches('/')))
                     || guest_path.as_str() == "/"
             })
-            .max_by_key(|(guest_path, _)| guest_path.len());
+            .max_by_key(|(guest_path, _, _, own)| (guest_path.len(), *own));
 
-        let (guest_path, host_path) =
+        let (guest_path, host_path, read_only, _) =
             best_match.ok_or_else(|| anyhow::anyhow!("Path not mounted: {virtual_path}"))?;
+        if write && *read_only {
+            anyhow::bail!("Read-only mount {guest_path}: {virtual_path}");
+        }
 
         let relative = virtual_path
-            .strip_prefix(guest_path)
+            .strip_prefix(guest_path.as_str())
             .unwrap_or(virtual_path)
             .trim_start_matches('/');
 
@@ -492,6 +1065,10 @@ impl WasiFilesystem {
     }
 }
 
+fn file_len(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
 /// Seek position reference
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -528,6 +1105,42 @@ pub struct WasiFilesystemStats {
     pub total_size: u64,
     pub open_fds: usize,
     pub mounts: Vec<MountInfo>,
+    pub quotas: Vec<QuotaInfo>,
+    pub processes: Vec<ProcessFsUsage>,
+}
+
+/// A quota from `[fs.quotas]` and what its path holds now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaInfo {
+    pub guest_path: String,
+    pub limit: u64,
+    pub used: u64,
+}
+
+/// A process's share of the filesystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessFsUsage {
+    pub pid: Pid,
+    /// Net bytes the process added through the VFS since it started
+    pub written: u64,
+    /// Volumes and host directories mounted for this process only
+    pub mounts: Vec<MountInfo>,
+}
+
+/// A named volume and who has it mounted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeInfo {
+    pub name: String,
+    pub host_path: PathBuf,
+    pub size: u64,
+    pub mounted_by: Vec<VolumeMount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeMount {
+    pub pid: Pid,
+    pub guest_path: String,
+    pub read_only: bool,
 }
 
 /// Information about a single mount point
@@ -594,6 +1207,22 @@ mod tests {
         assert_eq!(content, b"Hello, WASI!");
     }
 
+    #[test]
+    fn test_open_file_streams_files_only() {
+        let fs = WasiFilesystem::new();
+        let temp = tempdir().unwrap();
+        fs.mount("/test", temp.path()).unwrap();
+        fs.write_file("/test/app.wasm", b"\0asm").unwrap();
+        fs.path_create_directory("/test/pkg").unwrap();
+
+        let mut content = Vec::new();
+        std::io::Read::read_to_end(&mut fs.open_file("/test/app.wasm").unwrap(), &mut content)
+            .unwrap();
+        assert_eq!(content, b"\0asm");
+        assert!(fs.open_file("/test/pkg").is_err());
+        assert!(fs.open_file("/test/missing.wasm").is_err());
+    }
+
     #[test]
     fn test_path_operations() {
         let fs = WasiFilesystem::new();
@@ -665,4 +1294,172 @@ mod tests {
         let result = fs.write_file("/test/file.txt", b"data");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_watch_sees_writes_through_shared_volume() {
+        let fs = WasiFilesystem::new();
+        let root = tempdir().unwrap();
+        fs.set_volume_root(root.path()).unwrap();
+        fs.mount_for_process(1, &MountSpec::parse("shared:/out").unwrap())
+            .unwrap();
+        fs.mount_for_process(2, &MountSpec::parse("shared:/data:ro").unwrap())
+            .unwrap();
+        let builder = fs.for_process(1);
+        let server = fs.for_process(2);
+
+        let wd = server.add_watch("/data/", false).unwrap();
+        builder.write_file("/out/app.js", b"v1").unwrap();
+        builder.write_file("/out/app.js", b"v2").unwrap();
+        builder.path_create_directory("/out/assets").unwrap();
+        builder
+            .write_file("/out/assets/logo.svg", b"<svg/>")
+            .unwrap();
+        builder.path_unlink_file("/out/app.js").unwrap();
+
+        let events: Vec<(String, WatchEventKind)> = server
+            .take_watch_events(usize::MAX)
+            .into_iter()
+            .map(|e| {
+                assert_eq!(e.wd, wd);
+                (e.path, e.kind)
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("/data/app.js".to_string(), WatchEventKind::Create),
+                ("/data/app.js".to_string(), WatchEventKind::Modify),
+                ("/data/assets".to_string(), WatchEventKind::Create),
+                ("/data/app.js".to_string(), WatchEventKind::Delete),
+            ]
+        );
+        assert!(builder.take_watch_events(10).is_empty());
+        assert!(fs.add_watch("/", false).is_err());
+
+        server.remove_watch(wd).unwrap();
+        assert!(server.remove_watch(wd).is_err());
+        server.add_watch("/data", true).unwrap();
+        fs.unmount_process(2);
+        builder.write_file("/out/later.js", b"").unwrap();
+        assert!(server.take_watch_events(10).is_empty());
+    }
+
+    #[test]
+    fn test_shared_volume_between_processes() {
+        let fs = WasiFilesystem::new();
+        let root = tempdir().unwrap();
+        fs.set_volume_root(root.path()).unwrap();
+
+        let builder = fs.for_process(1);
+        let server = fs.for_process(2);
+        fs.mount_for_process(1, &MountSpec::parse("shared:/data:rw").unwrap())
+            .unwrap();
+        fs.mount_for_process(2, &MountSpec::parse("shared:/data:ro").unwrap())
+            .unwrap();
+
+        builder
+            .write_file("/data/index.html", b"<h1>built</h1>")
+            .unwrap();
+        assert_eq!(
+            server.read_file("/data/index.html").unwrap(),
+            b"<h1>built</h1>"
+        );
+        let err = server.write_file("/data/index.html", b"x").unwrap_err();
+        assert!(err.to_string().contains("Read-only mount /data"));
+        assert!(server.path_unlink_file("/data/index.html").is_err());
+        assert!(server
+            .path_open(
+                "/data/index.html",
+                OpenFlags {
+                    write: true,
+                    ..Default::default()
+                },
+            )
+            .is_err());
+
+        // Other processes and the unscoped view don't see the volume
+        assert!(fs.for_process(3).read_file("/data/index.html").is_err());
+        assert!(fs.read_file("/data/index.html").is_err());
+
+        let volumes = fs.list_volumes();
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].name, "shared");
+        assert_eq!(volumes[0].mounted_by.len(), 2);
+        assert!(volumes[0].mounted_by[1].read_only);
+
+        fs.unmount_process(1);
+        assert!(builder.read_file("/data/index.html").is_err());
+        assert_eq!(fs.list_volumes()[0].mounted_by.len(), 1);
+    }
+
+    #[test]
+    fn test_quota_limits_writes_and_usage_is_reported() {
+        let fs = WasiFilesystem::new();
+        let temp = tempdir().unwrap();
+        fs.mount("/tmp", temp.path()).unwrap();
+        fs.set_quotas(&FsConfig {
+            quotas: BTreeMap::from([("/tmp/".to_string(), 1)]),
+        })
+        .unwrap();
+        assert!(fs
+            .set_quotas(&FsConfig {
+                quotas: BTreeMap::from([("tmp".to_string(), 1)]),
+            })
+            .is_err());
+
+        let guest = fs.for_process(7);
+        let half = vec![0u8; 512 * 1024];
+        guest.write_file("/tmp/a.bin", &half).unwrap();
+        let err = guest
+            .write_file("/tmp/b.bin", &[half.as_slice(), b"x"].concat())
+            .unwrap_err();
+        assert!(err.to_string().contains("Disk quota exceeded for /tmp"));
+        assert!(!temp.path().join("b.bin").exists());
+
+        // Appending through an fd counts too
+        let fd = guest
+            .path_open(
+                "/tmp/a.bin",
+                OpenFlags {
+                    write: true,
+                    append: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        guest.fd_write(fd, &half[..1024]).unwrap();
+        assert!(guest.fd_write(fd, &half).is_err());
+        guest.fd_close(fd).unwrap();
+
+        // Deleting frees the space again
+        guest.path_unlink_file("/tmp/a.bin").unwrap();
+        guest.write_file("/tmp/b.bin", &half).unwrap();
+
+        let stats = fs.get_stats();
+        assert_eq!(stats.quotas.len(), 1);
+        assert_eq!(stats.quotas[0].guest_path, "/tmp");
+        assert_eq!(stats.quotas[0].limit, 1024 * 1024);
+        assert_eq!(stats.quotas[0].used, half.len() as u64);
+        assert_eq!(stats.processes.len(), 1);
+        assert_eq!(stats.processes[0].pid, 7);
+        assert_eq!(stats.processes[0].written, half.len() as u64);
+
+        fs.unmount_process(7);
+        assert!(fs.get_stats().processes.is_empty());
+    }
+
+    #[test]
+    fn test_mount_spec_parsing() {
+        let spec = MountSpec::parse("cache:/var/cache").unwrap();
+        assert_eq!(spec.volume.as_deref(), Some("cache"));
+        assert!(!spec.read_only);
+
+        let spec = MountSpec::parse("./assets:/srv/assets:ro").unwrap();
+        assert_eq!(spec.host.as_deref(), Some("./assets"));
+        assert!(spec.read_only);
+
+        assert!(MountSpec::parse("shared").is_err());
+        assert!(MountSpec::parse("shared:data").is_err());
+        assert!(MountSpec::parse("shared:/a/../b").is_err());
+    }
 }
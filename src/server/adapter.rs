@@ -0,0 +1,330 @@
+//! Blocking route handlers behind the async front
+//!
+//! The handlers are synchronous and written against tiny_http's types. The
+//! front runs each one on the runtime's blocking pool with a [`Request`]
+//! that has the accessors of `tiny_http::Request`. The body is read from the
+//! connection as the handler asks for it, and the `tiny_http::Response` the
+//! handler answers with is streamed from the handler's thread, so a large
+//! file or a slow client holds that thread rather than the runtime.
+
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::{Body, Bytes, Frame, Incoming};
+use hyper::header::{HeaderName, HeaderValue, CONTENT_LENGTH, HOST};
+use hyper::{Response as HyperResponse, StatusCode};
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tiny_http::{Header, Method, Response};
+use tokio::sync::{mpsc, oneshot};
+
+/// Body chunks buffered between the connection and a handler, each way
+const BODY_CHUNKS: usize = 4;
+
+/// Size of the chunks a response body is read in
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Events buffered for a client before its [`EventSender`] gives up on it
+pub const EVENT_BACKLOG: usize = 1024;
+
+/// Response headers the front sets itself, or that only make sense on a
+/// single HTTP/1.1 connection and are invalid in HTTP/2
+const FRONT_HEADERS: [&str; 8] = [
+    "connection",
+    "content-length",
+    "keep-alive",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+pub type ReplyBody = BoxBody<Bytes, io::Error>;
+
+/// An HTTP request as a handler sees it
+pub struct Request {
+    method: Method,
+    url: String,
+    headers: Vec<Header>,
+    remote_addr: Option<SocketAddr>,
+    #[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
+    body_length: Option<usize>,
+    body: BodyReader,
+    reply: oneshot::Sender<Reply>,
+}
+
+/// What a handler answered with
+pub struct Reply {
+    status: u16,
+    headers: Vec<Header>,
+    length: Option<usize>,
+    body: mpsc::Receiver<io::Result<Bytes>>,
+}
+
+impl Request {
+    /// `request` for a handler, and where its reply will arrive. The body
+    /// stays on the connection until the handler reads it. `None` for a
+    /// method tiny_http can't represent.
+    pub fn new(
+        request: hyper::Request<Incoming>,
+        remote_addr: Option<SocketAddr>,
+    ) -> Option<(Self, oneshot::Receiver<Reply>)> {
+        let method = request.method().as_str().parse().ok()?;
+        let url = request
+            .uri()
+            .path_and_query()
+            .map_or("/", |path| path.as_str())
+            .to_string();
+        let mut headers: Vec<Header> = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Header::from_bytes(name.as_str(), value.as_bytes()).ok())
+            .collect();
+        // HTTP/2 carries the host in `:authority` instead of a Host header
+        if !request.headers().contains_key(HOST) {
+            if let Some(authority) = request.uri().authority() {
+                headers.extend(Header::from_bytes("Host", authority.as_str()).ok());
+            }
+        }
+        let body = request.into_body();
+        let body_length = body
+            .size_hint()
+            .exact()
+            .and_then(|length| usize::try_from(length).ok());
+
+        let (tx, chunks) = mpsc::channel(BODY_CHUNKS);
+        tokio::spawn(pump_body(body, tx));
+        let (reply, replied) = oneshot::channel();
+        let request = Self {
+            method,
+            url,
+            headers,
+            remote_addr,
+            body_length,
+            body: BodyReader {
+                chunks,
+                current: Bytes::new(),
+            },
+            reply,
+        };
+        Some((request, replied))
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path and query the client asked for
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    /// The client's address; `None` over a Unix socket
+    pub fn remote_addr(&self) -> Option<&SocketAddr> {
+        self.remote_addr.as_ref()
+    }
+
+    /// The body's length, when the client declared it
+    #[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
+    pub fn body_length(&self) -> Option<usize> {
+        self.body_length
+    }
+
+    /// The body, read from the connection as it arrives
+    pub fn as_reader(&mut self) -> &mut dyn Read {
+        &mut self.body
+    }
+
+    /// Send `response`, returning once its body has been handed to the
+    /// connection or the client has gone
+    pub fn respond<R: Read>(self, response: Response<R>) -> io::Result<()> {
+        let (tx, body) = mpsc::channel(BODY_CHUNKS);
+        let reply = Reply {
+            status: response.status_code().0,
+            headers: response.headers().to_vec(),
+            length: response.data_length(),
+            body,
+        };
+        self.reply.send(reply).map_err(|_| client_gone())?;
+
+        let mut reader = response.into_reader();
+        loop {
+            let mut chunk = vec![0; CHUNK_BYTES];
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    // Aborts the response instead of ending it short
+                    let message = e.to_string();
+                    let _ = tx.blocking_send(Err(e));
+                    return Err(io::Error::other(message));
+                }
+            };
+            chunk.truncate(read);
+            tx.blocking_send(Ok(Bytes::from(chunk)))
+                .map_err(|_| client_gone())?;
+        }
+    }
+
+    /// Send `response`'s status and headers, then keep the body open for
+    /// server-sent events after the handler returns
+    #[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
+    pub fn respond_events(self, response: Response<io::Empty>) -> io::Result<EventSender> {
+        let (tx, body) = mpsc::channel(EVENT_BACKLOG);
+        let reply = Reply {
+            status: response.status_code().0,
+            headers: response.headers().to_vec(),
+            length: None,
+            body,
+        };
+        self.reply.send(reply).map_err(|_| client_gone())?;
+        Ok(EventSender { tx })
+    }
+}
+
+impl Reply {
+    pub fn into_response(self) -> HyperResponse<ReplyBody> {
+        let mut response = HyperResponse::new(ChunkBody(self.body).boxed());
+        *response.status_mut() =
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let headers = response.headers_mut();
+        for header in &self.headers {
+            let name = header.field.as_str().as_str();
+            if FRONT_HEADERS
+                .iter()
+                .any(|hop| name.eq_ignore_ascii_case(hop))
+            {
+                continue;
+            }
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(header.value.as_str()),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        if let Some(length) = self.length {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+        }
+        response
+    }
+}
+
+/// A bodiless response with `status`, for requests no handler answered
+pub fn status_only(status: StatusCode) -> HyperResponse<ReplyBody> {
+    let mut response = HyperResponse::new(Empty::new().map_err(|never| match never {}).boxed());
+    *response.status_mut() = status;
+    response
+}
+
+/// Server-sent events for one client, from [`Request::respond_events`]
+#[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
+pub struct EventSender {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+}
+
+#[cfg_attr(not(feature = "os-mode"), allow(dead_code))]
+impl EventSender {
+    /// Send one `event` with `data`. `false` once the client has gone or has
+    /// fallen [`EVENT_BACKLOG`] events behind; dropping the sender then ends
+    /// the stream, so a stalled client can't hold events in memory.
+    pub fn send(&self, event: &str, data: &str) -> bool {
+        let mut message = format!("event: {event}\n");
+        for line in data.lines() {
+            message.push_str(&format!("data: {line}\n"));
+        }
+        message.push('\n');
+        self.tx.try_send(Ok(Bytes::from(message))).is_ok()
+    }
+}
+
+/// A request body, read on a handler's thread
+struct BodyReader {
+    chunks: mpsc::Receiver<io::Result<Bytes>>,
+    current: Bytes,
+}
+
+impl Read for BodyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => self.current = chunk?,
+                None => return Ok(0),
+            }
+        }
+        let read = buf.len().min(self.current.len());
+        buf[..read].copy_from_slice(&self.current.split_to(read));
+        Ok(read)
+    }
+}
+
+/// Move `body` to a handler as it reads, until it ends or the handler
+/// drops the request
+async fn pump_body(mut body: Incoming, tx: mpsc::Sender<io::Result<Bytes>>) {
+    while let Some(frame) = body.frame().await {
+        let chunk = match frame {
+            Ok(frame) => match frame.into_data() {
+                Ok(data) => Ok(data),
+                // Trailers
+                Err(_) => continue,
+            },
+            Err(e) => Err(io::Error::other(e)),
+        };
+        let failed = chunk.is_err();
+        if tx.send(chunk).await.is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// A reply body, sent as the handler produces it
+struct ChunkBody(mpsc::Receiver<io::Result<Bytes>>);
+
+impl Body for ChunkBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<io::Result<Frame<Bytes>>>> {
+        self.0
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(Frame::data)))
+    }
+}
+
+fn client_gone() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "the client has gone")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lagging_event_client_is_dropped() {
+        let (tx, mut rx) = mpsc::channel(EVENT_BACKLOG);
+        let events = EventSender { tx };
+        for _ in 0..EVENT_BACKLOG {
+            assert!(events.send("log", "line"));
+        }
+        assert!(!events.send("log", "one too many"));
+
+        let first = rx.try_recv().unwrap().unwrap();
+        assert_eq!(&first[..], b"event: log\ndata: line\n\n");
+        drop(events);
+        let mut left = 1;
+        while rx.try_recv().is_ok() {
+            left += 1;
+        }
+        assert_eq!(left, EVENT_BACKLOG);
+    }
+}
@@ -1,13 +1,23 @@
-use std::fs;
-use tiny_http::{Request, Response};
+use std::fs::{self, File};
+use std::path::Path;
+use tiny_http::Response;
 
+use super::metrics::HttpResponse;
 use super::utils::{check_assets_directory, content_type_header};
-use crate::commands::verify_wasm;
+use crate::commands::verify_wasm_bytes;
+use crate::compiler::artifacts::{BuildManifest, BUILD_MANIFEST_FILE};
 use crate::plugin::manager::PluginManager;
+use crate::plugin::panel::PanelRequest;
+use crate::utils::{GitState, ModuleDetails};
+use crate::watcher::WatchState;
 
-/// Serve WASM module information as JSON
-pub fn serve_module_info(request: Request, wasm_path: &str, project_path: Option<&str>) {
-    match verify_wasm(wasm_path) {
+/// WASM module information as JSON
+pub fn module_info_response(wasm_path: &str, project_path: Option<&str>) -> HttpResponse {
+    // Read rather than mapped: a rebuild may truncate the file meanwhile
+    let verified = fs::read(wasm_path)
+        .map_err(|e| format!("Error reading file: {e}"))
+        .and_then(|bytes| verify_wasm_bytes(&bytes));
+    match verified {
         Ok(verification_result) => {
             // Get plugin information for the project
             let plugin_info = if let Ok(plugin_manager) = PluginManager::new() {
@@ -90,16 +100,13 @@ pub fn serve_module_info(request: Request, wasm_path: &str, project_path: Option
 
             println!("📊 Serving module info for: {wasm_path}");
 
-            let response = Response::from_string(json_response.to_string())
+            Response::from_string(json_response.to_string())
                 .with_header(content_type_header("application/json"))
                 .with_header(
                     tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], b"*")
                         .unwrap(),
-                );
-
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending module info response: {e}");
-            }
+                )
+                .boxed()
         }
         Err(error) => {
             eprintln!("❗ Error analyzing WASM module {wasm_path}: {error}");
@@ -109,23 +116,112 @@ pub fn serve_module_info(request: Request, wasm_path: &str, project_path: Option
                 "valid_magic": false
             });
 
-            let response = Response::from_string(error_response.to_string())
+            Response::from_string(error_response.to_string())
                 .with_status_code(500)
                 .with_header(content_type_header("application/json"))
                 .with_header(
                     tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], b"*")
                         .unwrap(),
-                );
+                )
+                .boxed()
+        }
+    }
+}
 
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending error response: {e}");
-            }
+/// The structural breakdown used by the console's Module tab. The module
+/// is read rather than mapped: a rebuild can truncate it while it's parsed.
+pub fn module_details_response(wasm_path: &str) -> HttpResponse {
+    let details = fs::read(wasm_path)
+        .map_err(|e| format!("Failed to read {wasm_path}: {e}"))
+        .and_then(|bytes| ModuleDetails::from_bytes(&bytes));
+
+    let (body, status) = match details {
+        Ok(details) => match serde_json::to_string(&details) {
+            Ok(json) => (json, 200),
+            Err(e) => (
+                serde_json::json!({ "error": e.to_string() }).to_string(),
+                500,
+            ),
+        },
+        Err(error) => {
+            eprintln!("❗ Error analyzing WASM module {wasm_path}: {error}");
+            (serde_json::json!({ "error": error }).to_string(), 500)
+        }
+    };
+
+    json_response(body, status)
+}
+
+/// The `wasmrun-manifest.json` of the build being served
+pub fn build_manifest_response(wasm_path: &str) -> HttpResponse {
+    let Some(path) = BuildManifest::find_for(Path::new(wasm_path)) else {
+        let error = format!("No {BUILD_MANIFEST_FILE} for {wasm_path}; it wasn't built by wasmrun");
+        return json_response(serde_json::json!({ "error": error }).to_string(), 404);
+    };
+    match fs::read_to_string(&path) {
+        Ok(json) => json_response(json, 200),
+        Err(e) => {
+            let error = format!("Failed to read {}: {e}", path.display());
+            json_response(serde_json::json!({ "error": error }).to_string(), 500)
+        }
+    }
+}
+
+/// Body of `POST /api/build/trigger`: start a rebuild, or queue one behind
+/// the build in flight
+pub fn build_trigger_response() -> HttpResponse {
+    match super::trigger::trigger() {
+        Some((outcome, queue)) => {
+            let body = serde_json::json!({ "status": outcome, "queue": queue });
+            json_response(body.to_string(), 202)
+        }
+        None => {
+            let error = "This server has no project to rebuild";
+            json_response(serde_json::json!({ "error": error }).to_string(), 409)
         }
     }
 }
 
-/// Serve version information as JSON
-pub fn serve_version_info(request: Request) {
+/// Whether the watch session behind this server is paused
+pub fn watch_status_response(wasm_path: &str, watch_mode: bool) -> HttpResponse {
+    let body = match watch_session(wasm_path, watch_mode) {
+        Some(dir) => {
+            serde_json::json!({ "watching": true, "paused": WatchState::load(&dir).paused })
+        }
+        None => serde_json::json!({ "watching": false, "paused": false }),
+    };
+    json_response(body.to_string(), 200)
+}
+
+/// Pause or resume rebuilding in the watch session behind this server. The
+/// watch loop picks the change up and reports it in the terminal.
+pub fn watch_pause_response(wasm_path: &str, watch_mode: bool, paused: bool) -> HttpResponse {
+    let Some(dir) = watch_session(wasm_path, watch_mode) else {
+        let error = "This server isn't watching for changes";
+        return json_response(serde_json::json!({ "error": error }).to_string(), 409);
+    };
+    match (WatchState { paused }).save(&dir) {
+        Ok(()) => {
+            let body = serde_json::json!({ "watching": true, "paused": paused });
+            json_response(body.to_string(), 200)
+        }
+        Err(e) => {
+            let error = format!("Failed to save the watch state: {e}");
+            json_response(serde_json::json!({ "error": error }).to_string(), 500)
+        }
+    }
+}
+
+/// Where the state of the watch session that built `wasm_path` is kept
+fn watch_session(wasm_path: &str, watch_mode: bool) -> Option<std::path::PathBuf> {
+    if !watch_mode {
+        return None;
+    }
+    WatchState::dir_for_wasm(Path::new(wasm_path))
+}
+
+/// Version information as JSON
+pub fn version_info_response() -> HttpResponse {
     let version = env!("CARGO_PKG_VERSION");
     let name = env!("CARGO_PKG_NAME");
 
@@ -136,47 +232,125 @@ pub fn serve_version_info(request: Request) {
 
     println!("📊 Serving version info: {name} v{version}");
 
-    let response = Response::from_string(version_response.to_string())
+    json_response(version_response.to_string(), 200)
+}
+
+/// Branch, commit and dirty state of the repository the served project is
+/// in; 404 outside a repository
+pub fn git_info_response(wasm_path: &str, project_path: Option<&str>) -> HttpResponse {
+    let dir = project_path
+        .map(Path::new)
+        .or_else(|| Path::new(wasm_path).parent())
+        .unwrap_or(Path::new("."));
+    match GitState::detect(dir) {
+        Some(state) => json_response(serde_json::to_string(&state).unwrap_or_default(), 200),
+        None => {
+            let error = format!("{} is not in a git repository", dir.display());
+            json_response(serde_json::json!({ "error": error }).to_string(), 404)
+        }
+    }
+}
+
+/// Console panels from the plugin that builds the project
+pub fn plugin_panels_response(project_path: Option<&str>) -> HttpResponse {
+    let manager = match PluginManager::new() {
+        Ok(manager) => manager,
+        Err(e) => {
+            let error = format!("Failed to load plugins: {e}");
+            return json_response(serde_json::json!({ "error": error }).to_string(), 500);
+        }
+    };
+    let panels: Vec<_> = project_path
+        .and_then(|path| Some((path, manager.find_plugin_for_project(path)?)))
+        .map(|(path, plugin)| {
+            let name = &plugin.info().name;
+            plugin
+                .panels(path)
+                .into_iter()
+                .map(|panel| {
+                    serde_json::json!({
+                        "plugin": name,
+                        "id": panel.id,
+                        "title": panel.title,
+                        "html": panel.html,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    json_response(serde_json::json!({ "panels": panels }).to_string(), 200)
+}
+
+/// Hand a panel's `/api/plugin/<name>/...` request to the named plugin
+pub fn plugin_route_response(plugin_name: &str, request: &PanelRequest) -> HttpResponse {
+    let not_found =
+        |error: String| json_response(serde_json::json!({ "error": error }).to_string(), 404);
+    let manager = match PluginManager::new() {
+        Ok(manager) => manager,
+        Err(e) => {
+            let error = format!("Failed to load plugins: {e}");
+            return json_response(serde_json::json!({ "error": error }).to_string(), 500);
+        }
+    };
+    let Some(plugin) = manager.find_plugin_by_name(plugin_name) else {
+        return not_found(format!("No plugin named '{plugin_name}'"));
+    };
+    match plugin.handle_panel_request(request) {
+        Some(response) => Response::from_data(response.body)
+            .with_status_code(response.status)
+            .with_header(content_type_header(&response.content_type))
+            .with_header(
+                tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], b"*").unwrap(),
+            )
+            .boxed(),
+        None => not_found(format!(
+            "Plugin '{plugin_name}' has no route {} /{}",
+            request.method, request.path
+        )),
+    }
+}
+
+/// A JSON body that any origin may read
+pub fn json_response(body: String, status: u16) -> HttpResponse {
+    Response::from_string(body)
+        .with_status_code(status)
         .with_header(content_type_header("application/json"))
         .with_header(
             tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], b"*").unwrap(),
-        );
-
-    if let Err(e) = request.respond(response) {
-        eprintln!("❗ Error sending version info response: {e}");
-    }
+        )
+        .boxed()
 }
 
-/// Serve a file
-pub fn serve_file(request: Request, file_path: &str, content_type: &str) {
-    match fs::read(file_path) {
-        Ok(file_bytes) => {
+/// A file from disk, or a 500 if it can't be read. The body is streamed
+/// from the file as it's sent, so large modules are never held in memory.
+pub fn file_response(file_path: &str, content_type: &str) -> HttpResponse {
+    match File::open(file_path) {
+        Ok(file) => {
+            // Sent with its length, not chunked, so the browser can show
+            // download progress
+            let response = Response::from_file(file).with_chunked_threshold(usize::MAX);
             println!(
                 "🔄 Serving file: {} ({} bytes, content-type: {})",
                 file_path,
-                file_bytes.len(),
+                response.data_length().unwrap_or(0),
                 content_type
             );
-            let response =
-                Response::from_data(file_bytes).with_header(content_type_header(content_type));
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending file response: {e}");
-            }
+            response
+                .with_header(content_type_header(content_type))
+                .boxed()
         }
         Err(e) => {
             eprintln!("❗ Error reading file {file_path}: {e}");
-            let response = Response::from_string(format!("Error: {e}"))
+            Response::from_string(format!("Error: {e}"))
                 .with_status_code(500)
-                .with_header(content_type_header("text/plain"));
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending error response: {e}");
-            }
+                .with_header(content_type_header("text/plain"))
+                .boxed()
         }
     }
 }
 
-/// Serve a static asset file
-pub fn serve_asset(request: Request, url: &str) {
+/// A static asset file, or a 404 if it's missing
+pub fn asset_response(url: &str) -> HttpResponse {
     let asset_filename = url.strip_prefix("/assets/").unwrap_or("");
     let asset_path = format!("./assets/{asset_filename}");
 
@@ -196,30 +370,27 @@ pub fn serve_asset(request: Request, url: &str) {
         "application/octet-stream"
     };
 
-    match fs::read(&asset_path) {
-        Ok(asset_bytes) => {
+    match File::open(&asset_path) {
+        Ok(file) => {
+            let response = Response::from_file(file).with_chunked_threshold(usize::MAX);
             println!(
                 "🖼️ Successfully serving asset: {} ({} bytes)",
                 asset_path,
-                asset_bytes.len()
+                response.data_length().unwrap_or(0)
             );
-            let response =
-                Response::from_data(asset_bytes).with_header(content_type_header(content_type));
-            if let Err(e) = request.respond(response) {
-                eprintln!("‼️ Error sending asset response: {e}");
-            }
+            response
+                .with_header(content_type_header(content_type))
+                .boxed()
         }
         Err(e) => {
             eprintln!("‼️ Error reading asset file {asset_path}: {e} (does the file exist?)");
 
             check_assets_directory();
 
-            let response = Response::from_string(format!("Asset not found: {e}"))
+            Response::from_string(format!("Asset not found: {e}"))
                 .with_status_code(404)
-                .with_header(content_type_header("text/plain"));
-            if let Err(e) = request.respond(response) {
-                eprintln!("‼️ Error sending asset error response: {e}");
-            }
+                .with_header(content_type_header("text/plain"))
+                .boxed()
         }
     }
 }
@@ -0,0 +1,434 @@
+//! The async front of the dev and OS mode servers
+//!
+//! Connections are accepted by hyper on a shared tokio runtime, which speaks
+//! HTTP/2 with `--http2` and terminates TLS. Each request is handed to its
+//! server's route handler on the runtime's blocking pool through the
+//! [`Request`] adapter, so requests are handled concurrently and a
+//! long-lived response such as a server-sent event stream doesn't hold a
+//! handler once it has started.
+
+use super::adapter::{status_only, ReplyBody, Request};
+use hyper::body::Incoming;
+use hyper::{Request as HyperRequest, Response as HyperResponse, StatusCode};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::runtime::Runtime;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::{self, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+/// Threads running handlers at once, across every server in the process.
+/// Requests beyond that wait for a thread.
+const HANDLER_THREADS: usize = 64;
+
+/// The certificate and key for serving HTTP/2 over TLS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// `--http2`: serve through the HTTP/2 front, over TLS when `tls` is set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Http2Options {
+    pub tls: Option<TlsFiles>,
+}
+
+static HTTP2: Mutex<Option<Http2Options>> = Mutex::new(None);
+
+/// Speak HTTP/2 on servers bound from now on
+pub fn set_http2(options: Option<Http2Options>) {
+    *HTTP2.lock().unwrap() = options;
+}
+
+fn http2() -> Option<Http2Options> {
+    HTTP2.lock().unwrap().clone()
+}
+
+/// `https` when the front terminates TLS, for printed URLs
+pub fn scheme() -> &'static str {
+    match http2() {
+        Some(Http2Options { tls: Some(_) }) => "https",
+        _ => "http",
+    }
+}
+
+/// The runtime every front runs on
+fn runtime() -> Result<&'static Runtime, String> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_io()
+        .max_blocking_threads(HANDLER_THREADS)
+        .thread_name("wasmrun-front")
+        .build()
+        .map_err(|e| format!("Failed to start the server runtime: {e}"))?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// A route handler, called once per request on a blocking thread
+pub type Handler = Arc<dyn Fn(Request) + Send + Sync>;
+
+/// Serve `handler` on `host:port`, with HTTP/2 and TLS as `--http2` asks
+pub fn bind_server(
+    host: &str,
+    port: u16,
+    handler: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<(), String> {
+    let options = http2();
+    let tls = options
+        .as_ref()
+        .and_then(|options| options.tls.as_ref())
+        .map(tls_acceptor)
+        .transpose()?;
+    let front = Front {
+        handler: Arc::new(handler),
+        tls,
+        http2: options.is_some(),
+    };
+    front.bind_tcp(host, port)?;
+
+    if let Some(options) = options {
+        let protocol = if options.tls.is_some() {
+            "HTTP/2 over TLS"
+        } else {
+            "HTTP/2 (h2c) and HTTP/1.1"
+        };
+        println!("⚡ {protocol} on port {port}");
+    }
+    Ok(())
+}
+
+/// Serve `handler` on `host:port` in cleartext even when `--http2` has a
+/// certificate, so tools on this machine reach it without one
+pub fn bind_cleartext(
+    host: &str,
+    port: u16,
+    handler: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<(), String> {
+    let front = Front {
+        handler: Arc::new(handler),
+        tls: None,
+        http2: http2().is_some(),
+    };
+    front.bind_tcp(host, port)
+}
+
+/// Serve `handler` over HTTP/1.1 on the Unix socket at `path`
+#[cfg(unix)]
+pub fn bind_unix(
+    path: &Path,
+    handler: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<(), String> {
+    let listener = std::os::unix::net::UnixListener::bind(path)
+        .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+        .map_err(|e| format!("Failed to listen on {}: {e}", path.display()))?;
+    let front = Front {
+        handler: Arc::new(handler),
+        tls: None,
+        http2: false,
+    };
+    let runtime = runtime()?;
+    let _entered = runtime.enter();
+    let listener = tokio::net::UnixListener::from_std(listener)
+        .map_err(|e| format!("Failed to listen on {}: {e}", path.display()))?;
+    runtime.spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => front.connect(stream, None),
+                Err(e) => eprintln!("Accept error: {e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// One listener's handler and protocols
+#[derive(Clone)]
+struct Front {
+    handler: Handler,
+    tls: Option<TlsAcceptor>,
+    http2: bool,
+}
+
+impl Front {
+    fn bind_tcp(self, host: &str, port: u16) -> Result<(), String> {
+        let listener = TcpListener::bind((host, port))
+            .map_err(|e| format!("Failed to start server on {host}:{port}: {e}"))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure {host}:{port}: {e}"))?;
+        runtime()?.spawn(self.serve(listener));
+        Ok(())
+    }
+
+    async fn serve(self, listener: TcpListener) {
+        let listener = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("❌ Server front failed to start: {e}");
+                return;
+            }
+        };
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => self.connect(stream, Some(peer)),
+                Err(e) => eprintln!("Accept error: {e}"),
+            }
+        }
+    }
+
+    /// Serve one connection from `peer` on its own task
+    fn connect<S>(&self, stream: S, peer: Option<SocketAddr>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let front = self.clone();
+        tokio::spawn(async move {
+            let handler = front.handler;
+            let service = hyper::service::service_fn(move |request| {
+                handle(Arc::clone(&handler), request, peer)
+            });
+            let mut builder = auto::Builder::new(TokioExecutor::new());
+            if !front.http2 {
+                builder = builder.http1_only();
+            }
+            let served = match front.tls {
+                Some(tls) => match tls.accept(stream).await {
+                    Ok(stream) => {
+                        builder
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await
+                    }
+                    Err(_) => return,
+                },
+                None => {
+                    builder
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                }
+            };
+            if let Err(e) = served {
+                let peer = peer.map_or_else(|| "a Unix socket".to_string(), |p| p.to_string());
+                crate::debug_println!("Connection from {peer} ended: {e}");
+            }
+        });
+    }
+}
+
+/// Run `handler` for `request` on a blocking thread and stream back what
+/// it answers
+async fn handle(
+    handler: Handler,
+    request: HyperRequest<Incoming>,
+    peer: Option<SocketAddr>,
+) -> Result<HyperResponse<ReplyBody>, Infallible> {
+    let Some((request, reply)) = Request::new(request, peer) else {
+        return Ok(status_only(StatusCode::BAD_REQUEST));
+    };
+    tokio::task::spawn_blocking(move || handler(request));
+    // A handler that drops the request unanswered, or panics, gets a 500
+    // as it would from tiny_http
+    Ok(match reply.await {
+        Ok(reply) => reply.into_response(),
+        Err(_) => status_only(StatusCode::INTERNAL_SERVER_ERROR),
+    })
+}
+
+/// A TLS acceptor offering `h2` and `http/1.1` over ALPN
+fn tls_acceptor(files: &TlsFiles) -> Result<TlsAcceptor, String> {
+    let certs = CertificateDer::pem_file_iter(&files.cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| pem_error(&files.cert, e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates in {}", files.cert.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(&files.key).map_err(|e| pem_error(&files.key, e))?;
+
+    let mut config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+            .map_err(|e| format!("Invalid TLS certificate or key: {e}"))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn pem_error(path: &Path, error: impl std::fmt::Display) -> String {
+    format!("Failed to read {}: {error}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::{BodyExt, Empty, Full};
+    use hyper::body::Bytes;
+    use hyper::Version;
+    use hyper_util::client::legacy::connect::HttpConnector;
+    use hyper_util::client::legacy::Client;
+    use std::sync::{mpsc, Condvar};
+    use std::time::Duration;
+
+    /// A front on an ephemeral port serving `handler`, and a runtime to
+    /// drive clients with
+    fn start_front(
+        handler: impl Fn(Request) + Send + Sync + 'static,
+        http2: bool,
+    ) -> (SocketAddr, Runtime) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        let front = Front {
+            handler: Arc::new(handler),
+            tls: None,
+            http2,
+        };
+        runtime.spawn(front.serve(listener));
+        (addr, runtime)
+    }
+
+    #[test]
+    fn test_front_serves_http2_to_a_handler() {
+        let (seen_tx, seen) = mpsc::channel();
+        let handler = move |request: Request| {
+            seen_tx
+                .send((
+                    request.url().to_string(),
+                    request.remote_addr().copied(),
+                    request
+                        .headers()
+                        .iter()
+                        .any(|header| header.field.equiv("host")),
+                ))
+                .unwrap();
+            request
+                .respond(tiny_http::Response::from_string("hello"))
+                .unwrap();
+        };
+        let (addr, runtime) = start_front(handler, true);
+
+        let (version, body) = runtime.block_on(async {
+            let client: Client<HttpConnector, Empty<Bytes>> = Client::builder(TokioExecutor::new())
+                .http2_only(true)
+                .build_http();
+            let response = client
+                .get(format!("http://{addr}/app.wasm?v=1").parse().unwrap())
+                .await
+                .unwrap();
+            let version = response.version();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (version, body)
+        });
+
+        assert_eq!(version, Version::HTTP_2);
+        assert_eq!(&body[..], b"hello");
+        let (url, peer, has_host) = seen.recv().unwrap();
+        assert_eq!(url, "/app.wasm?v=1");
+        assert!(peer.is_some_and(|peer| peer.ip().is_loopback()));
+        assert!(has_host);
+    }
+
+    #[test]
+    fn test_handlers_run_concurrently_and_read_the_body() {
+        // Neither request is answered until both have arrived
+        let arrived = Arc::new((Mutex::new(0), Condvar::new()));
+        let handler = move |mut request: Request| {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let (count, both) = &*arrived;
+            let mut count = count.lock().unwrap();
+            *count += 1;
+            both.notify_all();
+            let (count, _) = both
+                .wait_timeout_while(count, Duration::from_secs(10), |count| *count < 2)
+                .unwrap();
+            assert_eq!(*count, 2, "the other request wasn't handled meanwhile");
+            drop(count);
+            request
+                .respond(tiny_http::Response::from_string(body.to_uppercase()))
+                .unwrap();
+        };
+        let (addr, runtime) = start_front(handler, false);
+
+        let (first, second) = runtime.block_on(async {
+            let client: Client<HttpConnector, Full<Bytes>> =
+                Client::builder(TokioExecutor::new()).build_http();
+            let post = |path: &str, body: &'static [u8]| {
+                let request = HyperRequest::post(format!("http://{addr}{path}"))
+                    .body(Full::new(Bytes::from_static(body)))
+                    .unwrap();
+                let client = client.clone();
+                async move {
+                    let response = client.request(request).await.unwrap();
+                    response.into_body().collect().await.unwrap().to_bytes()
+                }
+            };
+            let first = tokio::spawn(post("/first", b"one"));
+            let second = tokio::spawn(post("/second", b"two"));
+            (first.await.unwrap(), second.await.unwrap())
+        });
+        assert_eq!(&first[..], b"ONE");
+        assert_eq!(&second[..], b"TWO");
+    }
+
+    #[test]
+    fn test_handler_streams_events_after_returning() {
+        let handler = |request: Request| {
+            let response = tiny_http::Response::empty(200);
+            let events = request.respond_events(response).unwrap();
+            assert!(events.send("log", "first"));
+            assert!(events.send("log", "second\nline"));
+        };
+        let (addr, runtime) = start_front(handler, false);
+
+        let body = runtime.block_on(async {
+            let client: Client<HttpConnector, Empty<Bytes>> =
+                Client::builder(TokioExecutor::new()).build_http();
+            let response = client
+                .get(format!("http://{addr}/events").parse().unwrap())
+                .await
+                .unwrap();
+            response.into_body().collect().await.unwrap().to_bytes()
+        });
+        assert_eq!(
+            &body[..],
+            b"event: log\ndata: first\n\nevent: log\ndata: second\ndata: line\n\n"
+        );
+    }
+
+    #[test]
+    fn test_unanswered_request_is_a_server_error() {
+        let (addr, runtime) = start_front(drop, false);
+        let status = runtime.block_on(async {
+            let client: Client<HttpConnector, Full<Bytes>> =
+                Client::builder(TokioExecutor::new()).build_http();
+            let request = HyperRequest::post(format!("http://{addr}/"))
+                .body(Full::new(Bytes::from_static(b"ignored")))
+                .unwrap();
+            client.request(request).await.unwrap().status()
+        });
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_tls_acceptor_reports_missing_files() {
+        let files = TlsFiles {
+            cert: PathBuf::from("/nonexistent/cert.pem"),
+            key: PathBuf::from("/nonexistent/key.pem"),
+        };
+        let error = tls_acceptor(&files).err().unwrap();
+        assert!(error.contains("/nonexistent/cert.pem"), "{error}");
+    }
+}
@@ -1,24 +1,72 @@
 use std::fs;
 use std::path::Path;
-use tiny_http::{Request, Response};
+use std::sync::Mutex;
+use std::time::Instant;
+use tiny_http::{Method, Response};
 
-use super::api::{serve_asset, serve_file, serve_module_info, serve_version_info};
+use super::api::{
+    asset_response, build_manifest_response, build_trigger_response, file_response,
+    git_info_response, json_response, module_details_response, module_info_response,
+    plugin_panels_response, plugin_route_response, version_info_response, watch_pause_response,
+    watch_status_response,
+};
+use super::hot::HotJournal;
+use super::metrics::{self, HttpResponse, ServerMetrics};
+use super::perf::{Budget, PerfReport};
+use super::ports::{is_control_route, Surface};
 use super::utils::{content_type_header, determine_content_type};
+use super::Request;
+use crate::plugin::panel::PanelRequest;
 use crate::template::{TemplateManager, TemplateType};
+use crate::utils::PROMETHEUS_CONTENT_TYPE;
 
-/// Handle an incoming HTTP request
+/// Handle an incoming HTTP request. Requests are handled concurrently, so
+/// the state they share is locked only while it's used.
 #[allow(clippy::too_many_arguments)]
 pub fn handle_request(
-    request: Request,
+    mut request: Request,
+    surface: Surface,
     js_filename: Option<&str>,
     wasm_filename: &str,
     wasm_path: &str,
     project_path: Option<&str>,
     watch_mode: bool,
-    clients_to_reload: &mut Vec<String>,
+    clients_to_reload: &Mutex<Vec<String>>,
     template_manager: &TemplateManager,
     template_type: &TemplateType,
+    metrics: &Mutex<ServerMetrics>,
 ) {
+    let received = Instant::now();
+    let response = route(
+        &mut request,
+        surface,
+        js_filename,
+        wasm_filename,
+        wasm_path,
+        project_path,
+        watch_mode,
+        clients_to_reload,
+        template_manager,
+        template_type,
+        metrics,
+    );
+    metrics::respond(metrics, request, response, received);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn route(
+    request: &mut Request,
+    surface: Surface,
+    js_filename: Option<&str>,
+    wasm_filename: &str,
+    wasm_path: &str,
+    project_path: Option<&str>,
+    watch_mode: bool,
+    clients_to_reload: &Mutex<Vec<String>>,
+    template_manager: &TemplateManager,
+    template_type: &TemplateType,
+    metrics: &Mutex<ServerMetrics>,
+) -> HttpResponse {
     let url = request.url().to_string();
     let client_addr = match request.remote_addr() {
         Some(addr) => addr.to_string(),
@@ -27,13 +75,30 @@ pub fn handle_request(
 
     println!("📝 Received request for: {url}");
 
+    if surface.serves_control() && is_control_route(&url) {
+        if let Some(response) =
+            control_route(request, &url, wasm_path, project_path, watch_mode, metrics)
+        {
+            return response;
+        }
+    }
+    if !surface.serves_app() {
+        return not_found();
+    }
+
     if url == "/" {
         // Serve the main HTML page
-        let html = if watch_mode {
-            template_manager.generate_html_with_watch_mode(template_type, wasm_filename, true)
-        } else {
-            template_manager.generate_html(template_type, wasm_filename)
-        };
+        let accept_language = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Accept-Language"))
+            .map(|header| header.value.as_str());
+        let html = template_manager.generate_html_with_watch_mode(
+            template_type,
+            wasm_filename,
+            watch_mode,
+            crate::i18n::page_language(accept_language),
+        );
 
         let html = match html {
             Ok(html) => html,
@@ -45,53 +110,32 @@ pub fn handle_request(
             }
         };
 
-        let response = Response::from_string(html).with_header(content_type_header("text/html"));
-        if let Err(e) = request.respond(response) {
-            eprintln!("❗ Error sending HTML response: {e}");
-        }
-
+        let mut clients_to_reload = clients_to_reload.lock().unwrap();
         if watch_mode && !clients_to_reload.contains(&client_addr) {
             clients_to_reload.push(client_addr);
         }
-    } else if url == format!("/{wasm_filename}") {
-        serve_file(request, wasm_path, "application/wasm");
-    } else if let Some(js_file) = js_filename {
-        if url == format!("/{js_file}") {
-            let js_path = Path::new(wasm_path).parent().unwrap().join(js_file);
-            serve_file(request, js_path.to_str().unwrap(), "application/javascript");
-        }
-    } else if url == "/reload" {
-        if watch_mode {
-            // TODO: check if there was an actual file change
-            println!("🔄 Handling reload request in watch mode");
 
-            let response =
-                Response::from_string("no-reload").with_header(content_type_header("text/plain"));
-
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending reload response: {e}");
-            }
-        } else {
-            let response = Response::from_string("not-watching")
-                .with_header(content_type_header("text/plain"));
+        return Response::from_string(html)
+            .with_header(content_type_header("text/html"))
+            .boxed();
+    }
 
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending reload response: {e}");
-            }
-        }
-    } else if url == "/api/module-info" {
-        serve_module_info(request, wasm_path, project_path);
-    } else if url == "/api/version" {
-        serve_version_info(request);
+    // Hot-swapped files are requested with a cache-busting query
+    let url = url.split('?').next().unwrap_or_default();
+    if url == format!("/{wasm_filename}") {
+        file_response(wasm_path, "application/wasm")
+    } else if let Some(js_file) = js_filename.filter(|js| url == format!("/{js}")) {
+        let js_path = Path::new(wasm_path).parent().unwrap().join(js_file);
+        file_response(js_path.to_str().unwrap(), "application/javascript")
     } else if url.starts_with("/assets/") {
-        serve_asset(request, &url);
+        asset_response(url)
     } else {
         let base_dir = Path::new(wasm_path).parent().unwrap();
         let requested_file = base_dir.join(url.trim_start_matches('/'));
 
         if requested_file.exists() && requested_file.is_file() {
             let content_type = determine_content_type(&requested_file);
-            serve_file(request, requested_file.to_str().unwrap(), content_type);
+            file_response(requested_file.to_str().unwrap(), content_type)
         } else {
             if url.ends_with("_bg.wasm") {
                 if let Ok(entries) = fs::read_dir(base_dir) {
@@ -100,12 +144,10 @@ pub fn handle_request(
                         if let Some(name) = entry_path.file_name() {
                             if name.to_string_lossy().ends_with("_bg.wasm") && entry_path.is_file()
                             {
-                                serve_file(
-                                    request,
+                                return file_response(
                                     entry_path.to_str().unwrap(),
                                     "application/wasm",
                                 );
-                                return;
                             }
                         }
                     }
@@ -130,8 +172,7 @@ pub fn handle_request(
                                     "wasm" => "application/wasm",
                                     _ => "application/octet-stream",
                                 };
-                                serve_file(request, entry_path.to_str().unwrap(), content_type);
-                                return;
+                                return file_response(entry_path.to_str().unwrap(), content_type);
                             }
                         }
                     }
@@ -139,12 +180,171 @@ pub fn handle_request(
             }
 
             // 404 for all other requests
-            let response = Response::from_string("404 Not Found")
-                .with_status_code(404)
-                .with_header(content_type_header("text/plain"));
-            if let Err(e) = request.respond(response) {
-                eprintln!("❗ Error sending 404 response: {e}");
+            not_found()
+        }
+    }
+}
+
+/// The API, metrics and live-reload endpoints; `None` for a URL that
+/// isn't one of them
+fn control_route(
+    request: &mut Request,
+    url: &str,
+    wasm_path: &str,
+    project_path: Option<&str>,
+    watch_mode: bool,
+    metrics: &Mutex<ServerMetrics>,
+) -> Option<HttpResponse> {
+    let response = if let Some(query) = url.strip_prefix("/reload") {
+        reload_response(query, wasm_path, watch_mode)
+    } else if url == "/api/module-info" {
+        module_info_response(wasm_path, project_path)
+    } else if url == "/api/module/info" {
+        module_details_response(wasm_path)
+    } else if url == "/api/build/trigger" {
+        if *request.method() != Method::Post {
+            let error = "Use POST to trigger a rebuild";
+            return Some(json_response(
+                serde_json::json!({ "error": error }).to_string(),
+                405,
+            ));
+        }
+        build_trigger_response()
+    } else if url == "/api/watch" {
+        watch_status_response(wasm_path, watch_mode)
+    } else if url == "/api/watch/pause" || url == "/api/watch/resume" {
+        if *request.method() != Method::Post {
+            let error = "Use POST to pause or resume watching";
+            return Some(json_response(
+                serde_json::json!({ "error": error }).to_string(),
+                405,
+            ));
+        }
+        watch_pause_response(wasm_path, watch_mode, url == "/api/watch/pause")
+    } else if url == "/api/build/manifest" {
+        build_manifest_response(wasm_path)
+    } else if url == "/api/version" {
+        version_info_response()
+    } else if url == "/api/git" {
+        git_info_response(wasm_path, project_path)
+    } else if url == "/api/metrics" {
+        json_response(metrics.lock().unwrap().to_json().to_string(), 200)
+    } else if url == "/api/logs" {
+        json_response(metrics.lock().unwrap().logs_json().to_string(), 200)
+    } else if url == "/api/perf" && *request.method() == Method::Post {
+        record_perf(request, wasm_path, metrics)
+    } else if url == "/api/perf" || url.starts_with("/api/perf?") {
+        let query = url.split_once('?').map_or("", |(_, query)| query);
+        perf_response(query, &metrics.lock().unwrap())
+    } else if url == "/api/plugins/panels" {
+        plugin_panels_response(project_path)
+    } else if let Some(route) = url.strip_prefix("/api/plugin/") {
+        plugin_route(request, route, project_path)
+    } else if url == "/metrics" {
+        Response::from_string(metrics.lock().unwrap().to_prometheus())
+            .with_header(content_type_header(PROMETHEUS_CONTENT_TYPE))
+            .boxed()
+    } else {
+        return None;
+    };
+    Some(response)
+}
+
+/// The hot updates a watch-mode page hasn't applied. Pages send the last
+/// `seq` they saw as `?since=`; without it they only learn the current one.
+fn reload_response(query: &str, wasm_path: &str, watch_mode: bool) -> HttpResponse {
+    if !watch_mode {
+        return json_response(serde_json::json!({ "watching": false }).to_string(), 200);
+    }
+    let journal = HotJournal::for_wasm(Path::new(wasm_path));
+    let since = query
+        .strip_prefix("?since=")
+        .and_then(|seq| seq.parse().ok());
+    let updates = since.map(|seq| journal.since(seq)).unwrap_or_default();
+    let body = serde_json::json!({
+        "watching": true,
+        "seq": journal.latest(),
+        "updates": updates,
+    });
+    json_response(body.to_string(), 200)
+}
+
+/// A panel's request to `/api/plugin/<name>/<path>`; `route` is what follows
+/// the prefix
+fn plugin_route(request: &mut Request, route: &str, project_path: Option<&str>) -> HttpResponse {
+    let (plugin_name, rest) = route.split_once('/').unwrap_or((route, ""));
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut body = Vec::new();
+    if let Err(e) = request.as_reader().read_to_end(&mut body) {
+        let error = format!("Failed to read the request body: {e}");
+        return json_response(serde_json::json!({ "error": error }).to_string(), 400);
+    }
+    let method = request.method().as_str().to_string();
+    plugin_route_response(
+        plugin_name,
+        &PanelRequest {
+            method: &method,
+            path,
+            query,
+            body: &body,
+            project_path,
+        },
+    )
+}
+
+/// Store timing entries a page sent with `POST /api/perf`, and report the
+/// page's load once it has settled
+fn record_perf(
+    request: &mut Request,
+    wasm_path: &str,
+    metrics: &Mutex<ServerMetrics>,
+) -> HttpResponse {
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        let error = format!("Failed to read the request body: {e}");
+        return json_response(serde_json::json!({ "error": error }).to_string(), 400);
+    }
+    match serde_json::from_str::<PerfReport>(&body) {
+        Ok(report) => {
+            let mut metrics = metrics.lock().unwrap();
+            if let Some((audit, regressions)) =
+                metrics.perf_mut().record(report, Path::new(wasm_path))
+            {
+                metrics.log_load_audit(&audit, &regressions);
+            }
+            json_response(serde_json::json!({ "success": true }).to_string(), 200)
+        }
+        Err(e) => {
+            let error = format!("Invalid timing report: {e}");
+            json_response(serde_json::json!({ "error": error }).to_string(), 400)
+        }
+    }
+}
+
+/// The latest page load's timings. With `budget=NAME:MS,...` the response
+/// says which entries went over and is a 412 if any did, so CI can fail on
+/// it with `curl --fail`.
+fn perf_response(query: &str, metrics: &ServerMetrics) -> HttpResponse {
+    let mut budgets = Vec::new();
+    for pair in query.split('&') {
+        if let Some(list) = pair.strip_prefix("budget=") {
+            match Budget::parse_list(&crate::utils::percent_decode(list)) {
+                Ok(parsed) => budgets.extend(parsed),
+                Err(e) => {
+                    let error = e.to_string();
+                    return json_response(serde_json::json!({ "error": error }).to_string(), 400);
+                }
             }
         }
     }
+    let json = metrics.perf().to_json(&budgets);
+    let status = if json["ok"] == false { 412 } else { 200 };
+    json_response(json.to_string(), status)
+}
+
+fn not_found() -> HttpResponse {
+    Response::from_string("404 Not Found")
+        .with_status_code(404)
+        .with_header(content_type_header("text/plain"))
+        .boxed()
 }
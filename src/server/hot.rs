@@ -0,0 +1,269 @@
+//! Hot swapping in watch mode: stylesheet and asset edits are copied into
+//! the served build and announced to open pages, which replace the
+//! stylesheet or re-fetch the asset instead of reloading the page and
+//! instantiating the module again. Source edits still rebuild and reload.
+//!
+//! The watch loop appends updates to `wasmrun-hot.json` beside the numbered
+//! build directories, and pages poll `/reload?since=<seq>` for the ones
+//! they haven't applied yet.
+
+use crate::watcher::ChangeKind;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const HOT_UPDATES_FILE: &str = "wasmrun-hot.json";
+/// Updates kept in the journal; pages further behind reload instead
+const MAX_UPDATES: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HotAction {
+    /// Swap the stylesheet linked from `path`
+    Style,
+    /// Re-fetch elements that load `path`
+    Asset,
+    /// The module was rebuilt; reload the page
+    Reload,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotUpdate {
+    pub seq: u64,
+    pub action: HotAction,
+    /// The file's URL path under the served directory, without the leading
+    /// `/`; empty for reloads
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+}
+
+/// The updates recorded for one watch session's builds
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HotJournal {
+    pub updates: Vec<HotUpdate>,
+}
+
+impl HotJournal {
+    /// The journal in `dir`, or an empty one
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(dir.join(HOT_UPDATES_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// The journal for the build that produced `wasm_path`, looked for one
+    /// directory up, where the watch loop keeps it, and beside it
+    pub fn for_wasm(wasm_path: &Path) -> Self {
+        wasm_path
+            .parent()
+            .into_iter()
+            .flat_map(|dir| dir.ancestors().take(2))
+            .map(|dir| dir.join(HOT_UPDATES_FILE))
+            .find(|path| path.is_file())
+            .and_then(|path| path.parent().map(Self::load))
+            .unwrap_or_default()
+    }
+
+    /// Sequence number of the newest update, 0 when there are none
+    pub fn latest(&self) -> u64 {
+        self.updates.last().map_or(0, |update| update.seq)
+    }
+
+    /// Updates after `seq`, or a single reload when some of them have
+    /// already been dropped
+    pub fn since(&self, seq: u64) -> Vec<HotUpdate> {
+        let oldest = self.updates.first().map_or(0, |update| update.seq);
+        if seq < self.latest() && seq + 1 < oldest {
+            return vec![HotUpdate {
+                seq: self.latest(),
+                action: HotAction::Reload,
+                path: String::new(),
+            }];
+        }
+        self.updates
+            .iter()
+            .filter(|update| update.seq > seq)
+            .cloned()
+            .collect()
+    }
+
+    fn push(&mut self, action: HotAction, path: String) -> HotUpdate {
+        let update = HotUpdate {
+            seq: self.latest() + 1,
+            action,
+            path,
+        };
+        self.updates.push(update.clone());
+        if self.updates.len() > MAX_UPDATES {
+            self.updates.drain(..self.updates.len() - MAX_UPDATES);
+        }
+        update
+    }
+
+    fn save(&self, dir: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(dir.join(HOT_UPDATES_FILE), json + "\n")
+    }
+}
+
+/// Copy changed stylesheets and assets from `project` over their copies in
+/// `build_dir` and announce them. `None` when any of them isn't part of the
+/// build, e.g. a stylesheet compiled into the module, which needs a rebuild
+/// instead.
+pub fn swap(
+    project: &Path,
+    build_dir: &Path,
+    changes: &[(ChangeKind, String)],
+) -> io::Result<Option<Vec<HotUpdate>>> {
+    let mut copies = Vec::new();
+    for (kind, path) in changes {
+        let action = match kind {
+            ChangeKind::Style => HotAction::Style,
+            ChangeKind::Asset => HotAction::Asset,
+            ChangeKind::Source => return Ok(None),
+        };
+        let source = project.join(path);
+        let name = source.file_name().unwrap_or_default();
+        let served = served_copies(build_dir, name)?;
+        if served.is_empty() || !source.is_file() {
+            return Ok(None);
+        }
+        copies.extend(
+            served
+                .into_iter()
+                .map(|copy| (action, source.clone(), copy)),
+        );
+    }
+
+    let journal_dir = journal_dir(build_dir);
+    let mut journal = HotJournal::load(journal_dir);
+    let mut updates = Vec::new();
+    for (action, source, copy) in copies {
+        fs::copy(&source, &copy)?;
+        let path = copy
+            .strip_prefix(build_dir)
+            .unwrap_or(&copy)
+            .to_string_lossy()
+            .replace('\\', "/");
+        updates.push(journal.push(action, path));
+    }
+    journal.save(journal_dir)?;
+    Ok(Some(updates))
+}
+
+/// Announce a rebuild into `build_dir`, so open pages reload
+pub fn record_reload(build_dir: &Path) -> io::Result<()> {
+    let journal_dir = journal_dir(build_dir);
+    let mut journal = HotJournal::load(journal_dir);
+    journal.push(HotAction::Reload, String::new());
+    journal.save(journal_dir)
+}
+
+/// Watch builds are numbered directories under one root, which holds the
+/// journal so pages served from an older build still see newer updates
+fn journal_dir(build_dir: &Path) -> &Path {
+    build_dir.parent().unwrap_or(build_dir)
+}
+
+/// Files named `name` anywhere under `dir`
+fn served_copies(dir: &Path, name: &std::ffi::OsStr) -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if entry.file_name() == name {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_swap_copies_and_journals() {
+        let project = tempdir().unwrap();
+        fs::create_dir_all(project.path().join("www")).unwrap();
+        fs::write(project.path().join("www/style.css"), "body { color: red }").unwrap();
+        let builds = tempdir().unwrap();
+        let build_dir = builds.path().join("build-1");
+        fs::create_dir_all(build_dir.join("css")).unwrap();
+        fs::write(build_dir.join("css/style.css"), "body {}").unwrap();
+
+        let updates = swap(
+            project.path(),
+            &build_dir,
+            &[(ChangeKind::Style, "www/style.css".to_string())],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            updates,
+            [HotUpdate {
+                seq: 1,
+                action: HotAction::Style,
+                path: "css/style.css".to_string(),
+            }]
+        );
+        assert_eq!(
+            fs::read_to_string(build_dir.join("css/style.css")).unwrap(),
+            "body { color: red }"
+        );
+
+        record_reload(&build_dir).unwrap();
+        let journal = HotJournal::for_wasm(&build_dir.join("app.wasm"));
+        assert_eq!(journal.latest(), 2);
+        assert_eq!(journal.since(1)[0].action, HotAction::Reload);
+        assert!(journal.since(2).is_empty());
+    }
+
+    #[test]
+    fn test_swap_needs_a_served_copy() {
+        let project = tempdir().unwrap();
+        fs::write(project.path().join("logo.png"), "png").unwrap();
+        let build_dir = tempdir().unwrap();
+
+        let swapped = swap(
+            project.path(),
+            build_dir.path(),
+            &[(ChangeKind::Asset, "logo.png".to_string())],
+        )
+        .unwrap();
+        assert_eq!(swapped, None);
+    }
+
+    #[test]
+    fn test_since_reloads_when_behind_the_journal() {
+        let mut journal = HotJournal::default();
+        for _ in 0..MAX_UPDATES + 5 {
+            journal.push(HotAction::Asset, "logo.png".to_string());
+        }
+        assert_eq!(journal.updates.len(), MAX_UPDATES);
+        let behind = journal.since(1);
+        assert_eq!(behind.len(), 1);
+        assert_eq!(behind[0].action, HotAction::Reload);
+        assert_eq!(journal.since(journal.latest() - 1).len(), 1);
+    }
+
+    #[test]
+    fn test_classify() {
+        use crate::watcher::classify;
+        assert_eq!(classify(Path::new("src/lib.rs")), Some(ChangeKind::Source));
+        assert_eq!(classify(Path::new("Makefile")), Some(ChangeKind::Source));
+        assert_eq!(classify(Path::new("www/app.css")), Some(ChangeKind::Style));
+        assert_eq!(classify(Path::new("img/Logo.PNG")), Some(ChangeKind::Asset));
+        assert_eq!(classify(Path::new("target/app.css")), None);
+        assert_eq!(classify(Path::new("notes.md")), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Running dev servers, by name, so commands such as `wasmrun trigger` can
+//! reach one without being told its port
+//!
+//! Each server writes `~/.wasmrun/instances/<name>.json` when it starts
+//! listening. Servers are stopped by signals and can't remove their record,
+//! so records whose process has exited are skipped and deleted on read.
+
+use crate::config::WasmrunConfig;
+use crate::error::{Result, WasmrunError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The name of a server started without `--name`
+pub const DEFAULT_INSTANCE: &str = "default";
+
+static NAME: Mutex<Option<String>> = Mutex::new(None);
+
+/// Name the dev server this process starts
+pub fn set_instance_name(name: Option<String>) {
+    *NAME.lock().unwrap() = name;
+}
+
+fn instance_name() -> String {
+    NAME.lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_INSTANCE.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Instance {
+    pub name: String,
+    pub pid: u32,
+    /// TCP port the control endpoints answer on; `None` when they are only
+    /// on `socket`
+    pub control_port: Option<u16>,
+    /// Unix socket the server listens on, if it uses one
+    pub socket: Option<PathBuf>,
+    /// Whether `control_port` speaks TLS
+    pub tls: bool,
+}
+
+impl Instance {
+    /// The record for this process's server
+    pub(super) fn current(port: u16, socket: Option<&Path>, api_port: Option<u16>) -> Self {
+        let tls = api_port.is_none() && socket.is_none() && super::front::scheme() == "https";
+        Self {
+            name: instance_name(),
+            pid: std::process::id(),
+            control_port: api_port.or(socket.is_none().then_some(port)),
+            socket: socket.map(Path::to_path_buf),
+            tls,
+        }
+    }
+
+    fn path(dir: &Path, name: &str) -> PathBuf {
+        dir.join(format!("{name}.json"))
+    }
+}
+
+fn instances_dir() -> Result<PathBuf> {
+    Ok(WasmrunConfig::config_dir()?.join("instances"))
+}
+
+/// Record `instance`, replacing an earlier server of the same name
+pub(super) fn register(instance: &Instance) {
+    let written = instances_dir().and_then(|dir| {
+        fs::create_dir_all(&dir)?;
+        let json = serde_json::to_string_pretty(instance)
+            .map_err(|e| WasmrunError::from(format!("Failed to encode instance: {e}")))?;
+        fs::write(Instance::path(&dir, &instance.name), json)?;
+        Ok(())
+    });
+    if let Err(e) = written {
+        eprintln!("⚠️  Failed to record server '{}': {e}", instance.name);
+    }
+}
+
+/// Remove this process's record, when its server quits
+pub(super) fn unregister() {
+    let name = instance_name();
+    let Ok(path) = instances_dir().map(|dir| Instance::path(&dir, &name)) else {
+        return;
+    };
+    let ours = fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Instance>(&json).ok())
+        .is_some_and(|instance| instance.pid == std::process::id());
+    if ours {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Servers that are still running, by name
+pub fn running() -> Result<Vec<Instance>> {
+    running_in(&instances_dir()?, super::lifecycle::process_alive)
+}
+
+fn running_in(dir: &Path, alive: impl Fn(u32) -> bool) -> Result<Vec<Instance>> {
+    let mut instances = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(instances);
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let instance = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Instance>(&json).ok());
+        match instance {
+            Some(instance) if alive(instance.pid) => instances.push(instance),
+            _ => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(instances)
+}
+
+/// The running server called `name`, or without a name the only one
+pub fn find(name: Option<&str>) -> Result<Instance> {
+    pick(running()?, name)
+}
+
+fn pick(instances: Vec<Instance>, name: Option<&str>) -> Result<Instance> {
+    let names = || {
+        instances
+            .iter()
+            .map(|instance| instance.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if let Some(name) = name {
+        return instances
+            .iter()
+            .find(|instance| instance.name == name)
+            .cloned()
+            .ok_or_else(|| {
+                let running = if instances.is_empty() {
+                    "none are running".to_string()
+                } else {
+                    format!("running: {}", names())
+                };
+                WasmrunError::from(format!("No dev server named '{name}' ({running})"))
+            });
+    }
+    match instances.len() {
+        0 => Err(WasmrunError::from(
+            "No dev server is running; start one with `wasmrun run`",
+        )),
+        1 => Ok(instances.into_iter().next().unwrap()),
+        _ => Err(WasmrunError::from(format!(
+            "Several dev servers are running ({}); pick one with --name",
+            names()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn instance(name: &str, pid: u32) -> Instance {
+        Instance {
+            name: name.to_string(),
+            pid,
+            control_port: Some(8420),
+            socket: None,
+            tls: false,
+        }
+    }
+
+    #[test]
+    fn test_running_skips_and_removes_exited_servers() {
+        let dir = tempdir().unwrap();
+        for instance in [instance("web", 1), instance("api", 2)] {
+            let json = serde_json::to_string(&instance).unwrap();
+            fs::write(Instance::path(dir.path(), &instance.name), json).unwrap();
+        }
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let running = running_in(dir.path(), |pid| pid == 2).unwrap();
+        assert_eq!(running, vec![instance("api", 2)]);
+        assert!(!dir.path().join("web.json").exists());
+        assert!(!dir.path().join("broken.json").exists());
+        assert!(dir.path().join("api.json").exists());
+    }
+
+    #[test]
+    fn test_pick() {
+        let both = vec![instance("api", 1), instance("web", 2)];
+        assert_eq!(pick(both.clone(), Some("web")).unwrap().pid, 2);
+        assert!(pick(both.clone(), Some("docs"))
+            .unwrap_err()
+            .to_string()
+            .contains("running: api, web"));
+        assert!(pick(both, None).unwrap_err().to_string().contains("--name"));
+
+        assert_eq!(pick(vec![instance("api", 1)], None).unwrap().name, "api");
+        assert!(pick(Vec::new(), None).is_err());
+    }
+}
@@ -1,5 +1,45 @@
 use crate::config::PID_FILE;
 use crate::error::{Result, ServerError, WasmrunError};
+use std::path::PathBuf;
+
+/// The PID file: the server's PID, then `unix:<path>` for a server that
+/// listens on a Unix socket
+struct PidFile {
+    pid: Option<u32>,
+    socket: Option<PathBuf>,
+}
+
+impl PidFile {
+    fn read() -> Option<Self> {
+        std::fs::read_to_string(PID_FILE)
+            .ok()
+            .map(|text| Self::parse(&text))
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut lines = text.lines().map(str::trim);
+        Self {
+            pid: lines.next().and_then(|line| line.parse().ok()),
+            socket: lines
+                .find_map(|line| line.strip_prefix("unix:"))
+                .map(PathBuf::from),
+        }
+    }
+}
+
+/// Record this process as the server listening on the Unix socket `path`,
+/// so `wasmrun stop` can find it and remove the socket afterwards
+pub fn record_unix_server(path: &std::path::Path) {
+    let contents = format!("{}\nunix:{}\n", std::process::id(), path.display());
+    if let Err(e) = std::fs::write(PID_FILE, contents) {
+        eprintln!("⚠️  Failed to write {PID_FILE}: {e}");
+    }
+}
+
+/// The Unix socket the recorded server listens on, if it uses one
+pub fn recorded_socket() -> Option<PathBuf> {
+    PidFile::read()?.socket
+}
 
 /// Check if a wasmrun server is currently running
 pub fn is_server_running() -> bool {
@@ -7,8 +47,8 @@ pub fn is_server_running() -> bool {
         return false;
     }
 
-    if let Ok(pid_str) = std::fs::read_to_string(PID_FILE) {
-        if let Ok(pid) = pid_str.trim().parse::<u32>() {
+    if let Some(pid_file) = PidFile::read() {
+        if let Some(pid) = pid_file.pid {
             let ps_command = std::process::Command::new("ps")
                 .arg("-p")
                 .arg(pid.to_string())
@@ -24,6 +64,34 @@ pub fn is_server_running() -> bool {
     false
 }
 
+/// Whether a process is running; assumes it is if `ps` can't tell
+pub fn process_alive(pid: u32) -> bool {
+    match std::process::Command::new("ps")
+        .arg("-p")
+        .arg(pid.to_string())
+        .output()
+    {
+        Ok(output) => {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).lines().count() > 1
+        }
+        Err(_) => true,
+    }
+}
+
+/// Remove this process's PID file and Unix socket, when its server quits
+pub(super) fn release_server() {
+    let Some(pid_file) = PidFile::read() else {
+        return;
+    };
+    if pid_file.pid != Some(std::process::id()) {
+        return;
+    }
+    let _ = std::fs::remove_file(PID_FILE);
+    if let Some(socket) = pid_file.socket {
+        let _ = std::fs::remove_file(socket);
+    }
+}
+
 /// Stop an existing wasmrun server if one is running
 pub fn stop_existing_server() -> Result<()> {
     if !is_server_running() {
@@ -45,10 +113,11 @@ pub fn stop_existing_server() -> Result<()> {
         })
     })?;
 
-    let pid = pid_str.trim().parse::<u32>().map_err(|e| {
+    let pid_file = PidFile::parse(&pid_str);
+    let pid = pid_file.pid.ok_or_else(|| {
         WasmrunError::Server(ServerError::StopFailed {
             pid: 0,
-            reason: format!("Failed to parse PID '{}': {}", pid_str.trim(), e),
+            reason: format!("Failed to parse PID from '{}'", pid_str.trim()),
         })
     })?;
 
@@ -70,6 +139,10 @@ pub fn stop_existing_server() -> Result<()> {
                 reason: format!("Failed to remove PID file: {e}"),
             })
         })?;
+        // A killed server can't remove its own socket
+        if let Some(socket) = pid_file.socket {
+            let _ = std::fs::remove_file(socket);
+        }
         println!("💀 Existing Wasmrun server terminated successfully.");
         Ok(())
     } else {
@@ -85,6 +158,19 @@ pub fn stop_existing_server() -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pid_file_parse() {
+        let tcp = PidFile::parse("4242\n");
+        assert_eq!(tcp.pid, Some(4242));
+        assert_eq!(tcp.socket, None);
+
+        let unix = PidFile::parse("4242\nunix:/tmp/wasmrun.sock\n");
+        assert_eq!(unix.pid, Some(4242));
+        assert_eq!(unix.socket, Some(PathBuf::from("/tmp/wasmrun.sock")));
+
+        assert_eq!(PidFile::parse("garbage").pid, None);
+    }
+
     #[test]
     fn test_is_server_running_no_pid_file() {
         // This test ensures is_server_running doesn't crash when there's no PID file
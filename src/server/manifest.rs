@@ -0,0 +1,315 @@
+//! Serve several modules on one page from a `--manifest` file
+//!
+//! Each module's directory is exposed under `/modules/<name>/`, so
+//! wasm-bindgen glue can keep fetching its `_bg.wasm` relative to itself.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tiny_http::Response;
+
+use super::api::{asset_response, file_response, json_response};
+use super::metrics::{self, HttpResponse, ServerMetrics};
+use super::ports;
+use super::utils::{content_type_header, determine_content_type};
+use crate::config::manifest::{ManifestModule, ModuleManifest};
+use crate::template::engine::{self, Escape, Vars};
+use crate::utils::PROMETHEUS_CONTENT_TYPE;
+
+/// Serve every module in `manifest` on a single page until the server stops
+pub fn serve_manifest(
+    manifest: &ModuleManifest,
+    port: u16,
+    serve: bool,
+    access_log: bool,
+) -> Result<(), String> {
+    let order = manifest.load_order().map_err(|e| e.to_string())?;
+    let page = render_manifest_page(&order);
+    let listing = manifest_json(manifest, &order);
+
+    let metrics = Mutex::new(ServerMetrics::new(access_log));
+    let manifest = manifest.clone();
+    ports::listen(port, move |request, surface| {
+        let received = Instant::now();
+        let url = request.url().split('?').next().unwrap_or("").to_string();
+        println!("📝 Received request for: {url}");
+
+        let response = if !surface.routes(&url) {
+            text(format!("Not found: {url}"), "text/plain", 404)
+        } else if url == "/" {
+            text(page.clone(), "text/html", 200)
+        } else if url == "/api/manifest" {
+            text(listing.clone(), "application/json", 200)
+        } else if url == "/api/metrics" {
+            json_response(metrics.lock().unwrap().to_json().to_string(), 200)
+        } else if url == "/api/logs" {
+            json_response(metrics.lock().unwrap().logs_json().to_string(), 200)
+        } else if url == "/metrics" {
+            let body = metrics.lock().unwrap().to_prometheus();
+            text(body, PROMETHEUS_CONTENT_TYPE, 200)
+        } else if url.starts_with("/assets/") {
+            asset_response(&url)
+        } else if let Some(path) = module_file(&manifest, &url) {
+            let content_type = determine_content_type(&path);
+            file_response(&path.to_string_lossy(), content_type)
+        } else {
+            text(format!("Not found: {url}"), "text/plain", 404)
+        };
+        metrics::respond(&metrics, request, response, received);
+    })?;
+
+    if serve {
+        crate::server::utils::open_browser_when_ready(port);
+    }
+    ports::wait();
+
+    Ok(())
+}
+
+fn text(body: String, content_type: &str, status: u16) -> HttpResponse {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type_header(content_type))
+        .boxed()
+}
+
+/// Map `/modules/<name>/<file>` to a file next to that module's entry,
+/// refusing anything that would climb out of the module's directory.
+fn module_file(manifest: &ModuleManifest, url: &str) -> Option<PathBuf> {
+    let rest = url.strip_prefix("/modules/")?;
+    let (name, file) = rest.split_once('/')?;
+    let module = manifest.module(name)?;
+
+    let relative = Path::new(file);
+    if file.is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let dir = manifest.resolve(module).parent()?.to_path_buf();
+    let path = dir.join(relative);
+    path.is_file().then_some(path)
+}
+
+fn module_url(module: &ManifestModule) -> String {
+    let file = Path::new(module.entry())
+        .file_name()
+        .map(|f| f.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("/modules/{}/{file}", module.name)
+}
+
+fn manifest_json(manifest: &ModuleManifest, order: &[&ManifestModule]) -> String {
+    let modules: Vec<_> = order
+        .iter()
+        .map(|module| {
+            serde_json::json!({
+                "name": module.name,
+                "kind": if module.is_bindgen() { "bindgen" } else { "wasm" },
+                "url": module_url(module),
+                "path": manifest.resolve(module).display().to_string(),
+                "mount": module.mount,
+                "after": module.after,
+            })
+        })
+        .collect();
+    serde_json::json!({ "modules": modules }).to_string()
+}
+
+/// Build the host page: one section per mount point, a status list, and the
+/// loader that instantiates modules in order and wires up the message bus.
+fn render_manifest_page(order: &[&ManifestModule]) -> String {
+    let badges = order
+        .iter()
+        .map(|module| Vars::new().text("NAME", &module.name))
+        .collect();
+    let modules: Vec<_> = order
+        .iter()
+        .map(|module| {
+            serde_json::json!({
+                "name": module.name,
+                "kind": if module.is_bindgen() { "bindgen" } else { "wasm" },
+                "url": module_url(module),
+                "mount": module.mount,
+            })
+        })
+        .collect();
+    let vars = Vars::new()
+        .json("MODULES", modules.into())
+        .list("BADGES", badges);
+    engine::render(MANIFEST_PAGE, Escape::Html, &vars).expect("manifest page template is valid")
+}
+
+const MANIFEST_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>Wasmrun - Modules</title>
+<link rel="icon" href="/assets/logo.png" type="image/png">
+<style>
+  body { margin: 0; font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; background: #121212; color: #cdd6f4; }
+  #wasmrun-modules { display: flex; gap: 0.75rem; flex-wrap: wrap; padding: 0.5rem 1rem; background: #1e1e2e; border-bottom: 1px solid #313244; font-size: 0.85rem; }
+  #wasmrun-modules span { padding: 0.15rem 0.5rem; border-radius: 999px; background: #313244; }
+  #wasmrun-modules .ready { color: #50fa7b; }
+  #wasmrun-modules .failed { color: #ff5555; }
+  .wasmrun-mount { margin: 1rem; }
+</style>
+</head>
+<body>
+<div id="wasmrun-modules">{{#each BADGES}}<span data-module="$NAME$">$NAME$: pending</span>{{/each}}</div>
+<script type="module">
+const MODULES = $MODULES|json$;
+
+// In-page message bus shared by every module. Topics are strings; '*'
+// receives everything. JS callers get the payload as published, wasm
+// modules get it as a JSON string.
+const subscribers = new Map();
+const bus = {
+  publish(topic, payload, from) {
+    for (const key of [topic, '*']) {
+      for (const fn of subscribers.get(key) || []) {
+        try { fn(payload, topic, from); } catch (e) { console.error(`[Wasmrun] bus subscriber for '${key}' failed:`, e); }
+      }
+    }
+  },
+  subscribe(topic, fn) {
+    if (!subscribers.has(topic)) subscribers.set(topic, new Set());
+    subscribers.get(topic).add(fn);
+    return () => subscribers.get(topic).delete(fn);
+  },
+};
+
+const status = document.getElementById('wasmrun-modules');
+const badges = {};
+for (const badge of status.children) badges[badge.dataset.module] = badge;
+for (const m of MODULES) {
+  if (m.mount && !document.getElementById(m.mount)) {
+    const el = document.createElement('section');
+    el.id = m.mount;
+    el.className = 'wasmrun-mount';
+    el.dataset.module = m.name;
+    document.body.appendChild(el);
+  }
+}
+
+window.wasmrun = {
+  bus,
+  modules: {},
+  mountPoint(name) {
+    const m = MODULES.find(m => m.name === name);
+    return m && m.mount ? document.getElementById(m.mount) : null;
+  },
+};
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+async function loadWasm(m) {
+  let exports;
+  const read = (ptr, len) => decoder.decode(new Uint8Array(exports.memory.buffer, ptr, len));
+  const imports = {
+    wasmrun_bus: {
+      publish(topicPtr, topicLen, msgPtr, msgLen) {
+        bus.publish(read(topicPtr, topicLen), read(msgPtr, msgLen), m.name);
+      },
+    },
+  };
+  const { instance } = await WebAssembly.instantiateStreaming(fetch(m.url), imports);
+  exports = instance.exports;
+
+  // Modules exporting `alloc(len) -> ptr` and `on_message(topic_ptr, topic_len, msg_ptr, msg_len)` receive bus traffic
+  if (typeof exports.on_message === 'function' && typeof exports.alloc === 'function') {
+    bus.subscribe('*', (payload, topic, from) => {
+      if (from === m.name) return;
+      const write = text => {
+        const bytes = encoder.encode(text);
+        const ptr = exports.alloc(bytes.length);
+        new Uint8Array(exports.memory.buffer, ptr, bytes.length).set(bytes);
+        return [ptr, bytes.length];
+      };
+      const message = typeof payload === 'string' ? payload : JSON.stringify(payload);
+      exports.on_message(...write(topic), ...write(message));
+    });
+  }
+  if (typeof exports._start === 'function') exports._start();
+  return exports;
+}
+
+async function loadBindgen(m) {
+  const mod = await import(m.url);
+  if (typeof mod.default === 'function') await mod.default();
+  return mod;
+}
+
+for (const m of MODULES) {
+  const badge = badges[m.name];
+  badge.textContent = `${m.name}: loading`;
+  try {
+    const exports = m.kind === 'bindgen' ? await loadBindgen(m) : await loadWasm(m);
+    window.wasmrun.modules[m.name] = { exports, mount: window.wasmrun.mountPoint(m.name) };
+    badge.textContent = `${m.name}: ready`;
+    badge.className = 'ready';
+    bus.publish('wasmrun:loaded', { name: m.name }, 'wasmrun');
+  } catch (e) {
+    console.error(`[Wasmrun] Failed to load module '${m.name}':`, e);
+    badge.textContent = `${m.name}: failed`;
+    badge.title = String(e);
+    badge.className = 'failed';
+  }
+}
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn manifest_in(dir: &Path) -> ModuleManifest {
+        fs::create_dir_all(dir.join("ui/pkg")).unwrap();
+        fs::write(dir.join("ui/pkg/ui.js"), "export default async () => {}").unwrap();
+        fs::write(dir.join("ui/pkg/ui_bg.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        fs::write(dir.join("store.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        let path = dir.join("modules.toml");
+        fs::write(
+            &path,
+            "[[module]]\nname = \"ui\"\njs = \"ui/pkg/ui.js\"\nmount = \"app\"\nafter = [\"store\"]\n\n[[module]]\nname = \"store\"\nwasm = \"store.wasm\"\n",
+        )
+        .unwrap();
+        ModuleManifest::load(&path).unwrap()
+    }
+
+    #[test]
+    fn test_page_lists_modules_in_load_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = manifest_in(dir.path());
+        let page = render_manifest_page(&manifest.load_order().unwrap());
+
+        let store = page.find("/modules/store/store.wasm").unwrap();
+        let ui = page.find("/modules/ui/ui.js").unwrap();
+        assert!(store < ui);
+        assert!(page.contains(r#""mount":"app""#));
+        assert!(!page.contains("$MODULES"));
+        assert!(page.contains(r#"<span data-module="store">store: pending</span>"#));
+    }
+
+    #[test]
+    fn test_module_files_stay_inside_module_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = manifest_in(dir.path());
+
+        assert_eq!(
+            module_file(&manifest, "/modules/ui/ui_bg.wasm"),
+            Some(dir.path().join("ui/pkg/ui_bg.wasm"))
+        );
+        assert_eq!(module_file(&manifest, "/modules/ui/../../store.wasm"), None);
+        assert_eq!(module_file(&manifest, "/modules/ui//etc/passwd"), None);
+        assert_eq!(module_file(&manifest, "/modules/other/ui.js"), None);
+    }
+}
@@ -0,0 +1,534 @@
+//! Per-route request metrics and the opt-in access log for the dev server
+//!
+//! Every response goes out through [`respond`], which records
+//! status, size and timing. Time is split into the handler (reading files,
+//! rendering templates) and the write to the socket, so a slow page load can
+//! be pinned on server overhead or on the bytes being transferred.
+//!
+//! Build durations are recorded process-wide with [`record_build`], since the
+//! build finishes before the server that reports it is created.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tiny_http::ResponseBox;
+
+use super::perf::{LoadAudit, PerfLog};
+use super::Request;
+use crate::compiler::artifacts::BuildManifest;
+use crate::logging::{LogEntry, LogSource, LogTrailSystem};
+use crate::utils::{resident_memory_bytes, CommandExecutor, PrometheusText};
+
+/// A response whose body is read as it's written to the socket: files are
+/// streamed from disk, everything else comes from memory
+pub type HttpResponse = ResponseBox;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+pub const LATENCY_BUCKETS_MS: [f64; 10] = [
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0,
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct RouteStats {
+    pub requests: u64,
+    pub bytes: u64,
+    pub statuses: BTreeMap<u16, u64>,
+    pub handler_time: Duration,
+    pub write_time: Duration,
+    pub max_time: Duration,
+    /// Requests per latency bucket; the last slot counts everything slower
+    /// than the largest bound
+    pub buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl RouteStats {
+    fn record(&mut self, status: u16, bytes: u64, handler: Duration, write: Duration) {
+        let total = handler + write;
+        self.requests += 1;
+        self.bytes += bytes;
+        *self.statuses.entry(status).or_default() += 1;
+        self.handler_time += handler;
+        self.write_time += write;
+        self.max_time = self.max_time.max(total);
+
+        let ms = total.as_secs_f64() * 1000.0;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.handler_time + self.write_time
+    }
+
+    /// Cumulative counts per bucket bound, Prometheus style
+    pub fn cumulative_buckets(&self) -> Vec<(Option<f64>, u64)> {
+        let mut running = 0;
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                running += count;
+                (LATENCY_BUCKETS_MS.get(i).copied(), running)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuildStats {
+    pub count: u64,
+    pub total: Duration,
+    pub last: Duration,
+}
+
+/// Builds keyed by `(language, succeeded)`
+static BUILDS: Mutex<BTreeMap<(String, bool), BuildStats>> = Mutex::new(BTreeMap::new());
+
+/// Record how long a project build took so `/metrics` can report it
+pub fn record_build(language: &str, duration: Duration, success: bool) {
+    let mut builds = BUILDS.lock().unwrap();
+    let stats = builds
+        .entry((language.to_lowercase(), success))
+        .or_default();
+    stats.count += 1;
+    stats.total += duration;
+    stats.last = duration;
+}
+
+pub fn build_stats() -> Vec<(String, bool, BuildStats)> {
+    BUILDS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|((language, success), stats)| (language.clone(), *success, *stats))
+        .collect()
+}
+
+/// Append the build duration families shared by the dev server and OS mode
+pub fn write_build_metrics(text: &mut PrometheusText) {
+    let builds = build_stats();
+    text.family(
+        "wasmrun_build_duration_seconds",
+        "Time spent building the project, by language and result.",
+        "summary",
+    );
+    for (language, success, stats) in &builds {
+        let labels = [
+            ("language", language.as_str()),
+            ("result", if *success { "success" } else { "failure" }),
+        ];
+        text.sample(
+            "wasmrun_build_duration_seconds_sum",
+            &labels,
+            stats.total.as_secs_f64(),
+        )
+        .sample(
+            "wasmrun_build_duration_seconds_count",
+            &labels,
+            stats.count as f64,
+        );
+    }
+    text.family(
+        "wasmrun_build_last_duration_seconds",
+        "Duration of the most recent build, by language and result.",
+        "gauge",
+    );
+    for (language, success, stats) in &builds {
+        text.sample(
+            "wasmrun_build_last_duration_seconds",
+            &[
+                ("language", language.as_str()),
+                ("result", if *success { "success" } else { "failure" }),
+            ],
+            stats.last.as_secs_f64(),
+        );
+    }
+}
+
+pub struct ServerMetrics {
+    started: Instant,
+    /// Keyed by `(method, path)`; query strings are dropped
+    routes: BTreeMap<(String, String), RouteStats>,
+    access_log: bool,
+    log_trail: LogTrailSystem,
+    perf: PerfLog,
+}
+
+impl ServerMetrics {
+    pub fn new(access_log: bool) -> Self {
+        Self {
+            started: Instant::now(),
+            routes: BTreeMap::new(),
+            access_log,
+            log_trail: LogTrailSystem::new(),
+            perf: PerfLog::default(),
+        }
+    }
+
+    /// Add the served build's rebuild annotation, if its manifest has one,
+    /// to the log trail
+    pub fn log_rebuild_cause(&self, wasm_path: &str) {
+        let manifest = BuildManifest::find_for(Path::new(wasm_path))
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str::<BuildManifest>(&json).ok());
+        if let Some(manifest) = manifest.filter(|m| !m.rebuilt_for.is_empty()) {
+            self.log_trail.log(LogEntry::rebuild(manifest.rebuilt_for));
+        }
+    }
+
+    fn record(
+        &mut self,
+        method: &str,
+        path: &str,
+        status: u16,
+        bytes: u64,
+        handler_time: Duration,
+        write_time: Duration,
+    ) {
+        self.routes
+            .entry((method.to_string(), path.to_string()))
+            .or_default()
+            .record(status, bytes, handler_time, write_time);
+
+        if self.access_log {
+            let line = access_log_line(method, path, status, bytes, handler_time, write_time);
+            println!("🌐 {line}");
+            let entry = match status {
+                500.. => LogEntry::error(LogSource::DevServer, line),
+                400.. => LogEntry::warn(LogSource::DevServer, line),
+                _ => LogEntry::info(LogSource::DevServer, line),
+            };
+            self.log_trail.log(entry);
+        }
+    }
+
+    pub fn routes(&self) -> impl Iterator<Item = (&str, &str, &RouteStats)> {
+        self.routes
+            .iter()
+            .map(|((method, path), stats)| (method.as_str(), path.as_str(), stats))
+    }
+
+    /// Body of `GET /api/metrics`: routes sorted by total time spent
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut routes: Vec<_> = self.routes().collect();
+        routes.sort_by_key(|(_, _, stats)| std::cmp::Reverse(stats.total_time()));
+
+        let routes: Vec<_> = routes
+            .into_iter()
+            .map(|(method, path, stats)| {
+                let statuses: BTreeMap<String, u64> = stats
+                    .statuses
+                    .iter()
+                    .map(|(status, count)| (status.to_string(), *count))
+                    .collect();
+                let histogram: Vec<_> = stats
+                    .cumulative_buckets()
+                    .into_iter()
+                    .map(|(bound, count)| {
+                        serde_json::json!({
+                            "le": bound.map_or(serde_json::json!("+Inf"), |b| serde_json::json!(b)),
+                            "count": count,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "method": method,
+                    "path": path,
+                    "requests": stats.requests,
+                    "bytes": stats.bytes,
+                    "statuses": statuses,
+                    "avg_ms": millis(stats.total_time()) / stats.requests.max(1) as f64,
+                    "max_ms": millis(stats.max_time),
+                    "handler_ms": millis(stats.handler_time),
+                    "write_ms": millis(stats.write_time),
+                    "histogram": histogram,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "requests": self.routes.values().map(|s| s.requests).sum::<u64>(),
+            "bytes": self.routes.values().map(|s| s.bytes).sum::<u64>(),
+            "access_log": self.access_log,
+            "routes": routes,
+        })
+    }
+
+    /// Body of `GET /metrics`, in Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        let mut text = PrometheusText::new();
+        text.family(
+            "wasmrun_uptime_seconds",
+            "Seconds since the dev server started.",
+            "gauge",
+        )
+        .sample(
+            "wasmrun_uptime_seconds",
+            &[],
+            self.started.elapsed().as_secs() as f64,
+        );
+        if let Some(rss) = resident_memory_bytes() {
+            text.family(
+                "wasmrun_resident_memory_bytes",
+                "Resident memory of the wasmrun process.",
+                "gauge",
+            )
+            .sample("wasmrun_resident_memory_bytes", &[], rss as f64);
+        }
+
+        text.family(
+            "wasmrun_http_requests_total",
+            "Requests served, by method, path and status.",
+            "counter",
+        );
+        for (method, path, stats) in self.routes() {
+            for (status, count) in &stats.statuses {
+                text.sample(
+                    "wasmrun_http_requests_total",
+                    &[
+                        ("method", method),
+                        ("path", path),
+                        ("status", &status.to_string()),
+                    ],
+                    *count as f64,
+                );
+            }
+        }
+        text.family(
+            "wasmrun_http_response_bytes_total",
+            "Response body bytes sent, by method and path.",
+            "counter",
+        );
+        for (method, path, stats) in self.routes() {
+            text.sample(
+                "wasmrun_http_response_bytes_total",
+                &[("method", method), ("path", path)],
+                stats.bytes as f64,
+            );
+        }
+        text.family(
+            "wasmrun_http_request_duration_seconds",
+            "Time to handle and send a response, by method and path.",
+            "histogram",
+        );
+        for (method, path, stats) in self.routes() {
+            let buckets: Vec<_> = stats
+                .cumulative_buckets()
+                .into_iter()
+                .map(|(bound, count)| (bound.map(|ms| ms / 1000.0), count))
+                .collect();
+            text.histogram(
+                "wasmrun_http_request_duration_seconds",
+                &[("method", method), ("path", path)],
+                &buckets,
+                stats.total_time().as_secs_f64(),
+            );
+        }
+
+        write_build_metrics(&mut text);
+        text.finish()
+    }
+
+    /// Body of `GET /api/logs`: the rebuild annotation and access log
+    /// entries recorded so far
+    /// Print a page's [`LoadAudit`] and add it to the log trail, as a
+    /// warning when some timing regressed since the previous load
+    pub fn log_load_audit(&self, audit: &LoadAudit, regressions: &[String]) {
+        let line = audit.summary();
+        if regressions.is_empty() {
+            println!("⏱️  {line}");
+            self.log_trail
+                .log(LogEntry::info(LogSource::DevServer, line));
+        } else {
+            let line = format!("{line} (slower than last load: {})", regressions.join(", "));
+            println!("⚠️  {line}");
+            self.log_trail
+                .log(LogEntry::warn(LogSource::DevServer, line));
+        }
+    }
+
+    /// Timings reported by the pages this server served
+    pub fn perf(&self) -> &PerfLog {
+        &self.perf
+    }
+
+    pub fn perf_mut(&mut self) -> &mut PerfLog {
+        &mut self.perf
+    }
+
+    pub fn logs_json(&self) -> serde_json::Value {
+        let logs = self.log_trail.get_all();
+        serde_json::json!({
+            "success": true,
+            "count": logs.len(),
+            "logs": logs,
+        })
+    }
+}
+
+/// Send `response` and record it in `metrics`, which isn't locked while
+/// the body is written. `received` is when handling started.
+pub fn respond(
+    metrics: &Mutex<ServerMetrics>,
+    request: Request,
+    response: HttpResponse,
+    received: Instant,
+) {
+    let method = request.method().to_string();
+    let path = request.url().split('?').next().unwrap_or("").to_string();
+    let status = response.status_code().0;
+    let bytes = response.data_length().unwrap_or(0) as u64;
+
+    let handled = Instant::now();
+    if let Err(e) = request.respond(response) {
+        eprintln!("❗ Error sending response for {path}: {e}");
+    }
+    let handler_time = handled - received;
+    let write_time = handled.elapsed();
+
+    metrics
+        .lock()
+        .unwrap()
+        .record(&method, &path, status, bytes, handler_time, write_time);
+}
+
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+fn access_log_line(
+    method: &str,
+    path: &str,
+    status: u16,
+    bytes: u64,
+    handler_time: Duration,
+    write_time: Duration,
+) -> String {
+    format!(
+        "{method} {path} {status} {} {:.1}ms (handler {:.1}ms, write {:.1}ms)",
+        CommandExecutor::format_file_size(bytes),
+        millis(handler_time + write_time),
+        millis(handler_time),
+        millis(write_time),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_stats_buckets_and_totals() {
+        let mut metrics = ServerMetrics::new(true);
+        let ms = Duration::from_millis;
+        metrics.record("GET", "/app.wasm", 200, 4096, ms(2), ms(30));
+        metrics.record("GET", "/app.wasm", 200, 4096, ms(1), ms(3));
+        metrics.record("GET", "/missing", 404, 13, ms(0), ms(0));
+        metrics.record("GET", "/slow", 200, 10, ms(4000), ms(1));
+
+        let (_, _, wasm) = metrics
+            .routes()
+            .find(|(_, path, _)| *path == "/app.wasm")
+            .unwrap();
+        assert_eq!(wasm.requests, 2);
+        assert_eq!(wasm.bytes, 8192);
+        assert_eq!(wasm.handler_time, ms(3));
+        assert_eq!(wasm.write_time, ms(33));
+        assert_eq!(wasm.max_time, ms(32));
+        let cumulative = wasm.cumulative_buckets();
+        assert_eq!(cumulative[1], (Some(5.0), 1));
+        assert_eq!(cumulative[4], (Some(50.0), 2));
+        assert_eq!(cumulative.last(), Some(&(None, 2)));
+
+        let json = metrics.to_json();
+        assert_eq!(json["requests"], 4);
+        assert_eq!(json["routes"][0]["path"], "/slow");
+        assert_eq!(json["routes"][0]["histogram"][10]["le"], "+Inf");
+        assert_eq!(json["routes"][0]["histogram"][10]["count"], 1);
+        assert_eq!(json["routes"][1]["avg_ms"], 18.0);
+
+        let logs = metrics.logs_json();
+        assert_eq!(logs["count"], 4);
+        assert_eq!(logs["logs"][2]["level"], "Warn");
+        assert!(logs["logs"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("GET /app.wasm 200 4.00 KB 32.0ms"));
+    }
+
+    #[test]
+    fn test_prometheus_output() {
+        let mut metrics = ServerMetrics::new(false);
+        let ms = Duration::from_millis;
+        metrics.record("GET", "/app.wasm", 200, 4096, ms(2), ms(30));
+        metrics.record("GET", "/app.wasm", 304, 0, ms(1), ms(0));
+        record_build("Rust", ms(1500), true);
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains(
+            "wasmrun_http_requests_total{method=\"GET\",path=\"/app.wasm\",status=\"304\"} 1\n"
+        ));
+        assert!(text.contains(
+            "wasmrun_http_response_bytes_total{method=\"GET\",path=\"/app.wasm\"} 4096\n"
+        ));
+        assert!(text.contains(
+            "wasmrun_http_request_duration_seconds_bucket{method=\"GET\",path=\"/app.wasm\",le=\"0.001\"} 1\n"
+        ));
+        assert!(text.contains(
+            "wasmrun_http_request_duration_seconds_bucket{method=\"GET\",path=\"/app.wasm\",le=\"0.05\"} 2\n"
+        ));
+        assert!(text.contains(
+            "wasmrun_http_request_duration_seconds_count{method=\"GET\",path=\"/app.wasm\"} 2\n"
+        ));
+        assert!(text.contains(
+            "wasmrun_build_duration_seconds_count{language=\"rust\",result=\"success\"}"
+        ));
+        assert_eq!(
+            text.matches("# HELP ").count(),
+            text.matches("# TYPE ").count()
+        );
+    }
+
+    #[test]
+    fn test_rebuild_cause_is_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = dir.path().join("app.wasm");
+        let metrics = ServerMetrics::new(false);
+        metrics.log_rebuild_cause(&wasm.to_string_lossy());
+        assert_eq!(metrics.logs_json()["count"], 0);
+
+        std::fs::write(
+            dir.path()
+                .join(crate::compiler::artifacts::BUILD_MANIFEST_FILE),
+            r#"{"wasmrun_version": "0.0.0", "built_at": "", "project": "app",
+                "language": "Rust", "profile": "release", "target": null, "artifacts": [],
+                "rebuilt_for": [{"path": "src/lib.rs", "sha256": "abc"},
+                                {"path": "style.css", "sha256": null}]}"#,
+        )
+        .unwrap();
+        metrics.log_rebuild_cause(&wasm.to_string_lossy());
+        let logs = metrics.logs_json();
+        assert_eq!(logs["count"], 1);
+        assert_eq!(
+            logs["logs"][0]["message"],
+            "Rebuilt due to src/lib.rs, style.css"
+        );
+        assert_eq!(
+            logs["logs"][0]["changes"][1]["sha256"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_access_log_is_opt_in() {
+        let mut metrics = ServerMetrics::new(false);
+        metrics.record("GET", "/", 200, 10, Duration::ZERO, Duration::ZERO);
+        assert_eq!(metrics.logs_json()["count"], 0);
+        assert_eq!(metrics.to_json()["requests"], 1);
+    }
+}
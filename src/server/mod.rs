@@ -1,9 +1,28 @@
+mod adapter;
 mod api;
+mod front;
 mod handler;
+pub mod hot;
+pub mod instances;
 mod lifecycle;
+pub mod manifest;
+mod metrics;
+mod perf;
+mod ports;
 mod runner;
+mod shortcuts;
+pub mod trigger;
 pub mod utils;
 pub mod wasm;
 
-pub use lifecycle::{is_server_running, stop_existing_server};
+pub use adapter::Request;
+#[cfg(feature = "os-mode")]
+pub use front::{bind_server, scheme};
+pub use front::{set_http2, Http2Options, TlsFiles};
+pub use instances::set_instance_name;
+pub use lifecycle::{is_server_running, process_alive, recorded_socket, stop_existing_server};
+pub use metrics::record_build;
+#[cfg(feature = "os-mode")]
+pub use metrics::write_build_metrics;
+pub use ports::{parse_listen, server_url, set_api_port, set_unix_socket, unix_socket};
 pub use utils::ServerUtils;
@@ -0,0 +1,401 @@
+//! User Timing data from served pages, for `/api/perf`
+//!
+//! Every page gets a small observer (see `PERF_SCRIPT` in the template
+//! module) that reports the page's `performance.mark`/`measure` entries,
+//! its navigation timings and how long its `.wasm` files took to fetch. The
+//! dev server keeps the last few page loads, so CI can load the app in a
+//! headless browser and then ask `/api/perf?budget=...` whether startup
+//! stayed within its budgets.
+//!
+//! Once a page has settled it says so, and the server prints a
+//! [`LoadAudit`] of that load: the module's download size, how long it
+//! took to fetch, compile and instantiate, and when the page first
+//! rendered, compared with the load before it.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::utils::{gzip_size, CommandExecutor};
+
+/// Page loads kept; older ones are dropped first
+const MAX_PAGES: usize = 20;
+/// Entries kept per page load
+const MAX_ENTRIES: usize = 500;
+/// A timing is flagged as a regression when it grew by this fraction of the
+/// previous load's, and by at least `REGRESSION_MIN_MS`
+const REGRESSION_RATIO: f64 = 0.2;
+const REGRESSION_MIN_MS: f64 = 10.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerfEntry {
+    pub name: String,
+    /// `mark`, `measure`, `navigation` or `resource`
+    pub entry_type: String,
+    /// Milliseconds since the page started loading
+    pub start_time: f64,
+    /// Milliseconds; 0 for marks
+    #[serde(default)]
+    pub duration: f64,
+}
+
+impl PerfEntry {
+    /// What a budget for this entry limits: how long a measure, navigation
+    /// step or fetch took, or how soon after navigation a mark was reached
+    pub fn value(&self) -> f64 {
+        if self.entry_type == "mark" {
+            self.start_time
+        } else {
+            self.duration
+        }
+    }
+}
+
+/// What a page sends to `POST /api/perf`. Pages report in batches, all
+/// under the same `page` id.
+#[derive(Debug, Deserialize)]
+pub struct PerfReport {
+    pub page: String,
+    #[serde(default)]
+    pub url: String,
+    pub entries: Vec<PerfEntry>,
+    /// Set on the report sent once the page has settled after `load`
+    #[serde(default)]
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageLoad {
+    pub page: String,
+    pub url: String,
+    pub entries: Vec<PerfEntry>,
+    #[serde(skip)]
+    audited: bool,
+}
+
+impl PageLoad {
+    /// The last entry called `name`
+    pub fn entry(&self, name: &str) -> Option<&PerfEntry> {
+        self.entries.iter().rev().find(|entry| entry.name == name)
+    }
+}
+
+/// A limit on one entry, from `budget=NAME:MS`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Budget {
+    pub name: String,
+    pub limit_ms: f64,
+}
+
+impl Budget {
+    /// Parse a comma-separated `NAME:MS` list. Names may contain `:`; the
+    /// limit is after the last one.
+    pub fn parse_list(list: &str) -> Result<Vec<Self>> {
+        list.split(',')
+            .filter(|item| !item.trim().is_empty())
+            .map(|item| {
+                let (name, limit) = item
+                    .rsplit_once(':')
+                    .with_context(|| format!("Budget '{item}' should be NAME:MS"))?;
+                let limit_ms: f64 = limit
+                    .trim()
+                    .trim_end_matches("ms")
+                    .parse()
+                    .with_context(|| format!("Budget '{item}' has no limit in milliseconds"))?;
+                if name.trim().is_empty() {
+                    bail!("Budget '{item}' has no entry name");
+                }
+                Ok(Self {
+                    name: name.trim().to_string(),
+                    limit_ms,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetResult {
+    pub name: String,
+    pub limit_ms: f64,
+    /// `None` when the page didn't record the entry, which fails the budget
+    pub actual_ms: Option<f64>,
+    pub ok: bool,
+}
+
+/// How a page's first load went, printed once the page has settled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadAudit {
+    pub url: String,
+    /// The served module's file name
+    pub module: String,
+    pub bytes: u64,
+    pub gzip_bytes: u64,
+    /// Milliseconds to download the module
+    pub fetch_ms: Option<f64>,
+    /// Milliseconds in `WebAssembly.compile`
+    pub compile_ms: Option<f64>,
+    /// Milliseconds from starting to instantiate to a ready instance,
+    /// including compiling for the streaming and bytes forms
+    pub instantiate_ms: Option<f64>,
+    /// First contentful paint, in milliseconds after navigation
+    pub first_render_ms: Option<f64>,
+    /// The `load` event, in milliseconds after navigation
+    pub load_ms: Option<f64>,
+}
+
+impl LoadAudit {
+    /// Audit `load`, a page that served the module at `wasm_path`
+    pub fn new(load: &PageLoad, wasm_path: &Path) -> Self {
+        let module = wasm_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let wasm = fs::read(wasm_path).unwrap_or_default();
+        let value = |name: &str| load.entry(name).map(PerfEntry::value);
+        Self {
+            url: load.url.clone(),
+            bytes: wasm.len() as u64,
+            gzip_bytes: gzip_size(&wasm),
+            fetch_ms: value(&format!("fetch:{module}")),
+            compile_ms: value("wasm:compile"),
+            instantiate_ms: value("wasm:instantiate").or_else(|| value("wasmrun:instantiate")),
+            first_render_ms: value("paint:first-contentful-paint"),
+            load_ms: value("navigation:load"),
+            module,
+        }
+    }
+
+    fn timings(&self) -> [(&'static str, Option<f64>); 5] {
+        [
+            ("fetch", self.fetch_ms),
+            ("compile", self.compile_ms),
+            ("instantiate", self.instantiate_ms),
+            ("first render", self.first_render_ms),
+            ("load", self.load_ms),
+        ]
+    }
+
+    /// Timings that grew noticeably since `previous`, as `name +Nms`
+    pub fn regressions(&self, previous: &LoadAudit) -> Vec<String> {
+        self.timings()
+            .iter()
+            .zip(previous.timings())
+            .filter_map(|((name, now), (_, before))| {
+                let (now, before) = ((*now)?, before?);
+                let grew = now - before;
+                (grew >= REGRESSION_MIN_MS && grew > before * REGRESSION_RATIO)
+                    .then(|| format!("{name} +{grew:.0}ms"))
+            })
+            .collect()
+    }
+
+    /// One line for the terminal and log trail
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!(
+            "{} {} ({} gzip)",
+            self.module,
+            CommandExecutor::format_file_size(self.bytes),
+            CommandExecutor::format_file_size(self.gzip_bytes)
+        )];
+        for (name, ms) in self.timings() {
+            if let Some(ms) = ms {
+                parts.push(format!("{name} {ms:.0}ms"));
+            }
+        }
+        format!("First load of {}: {}", self.url, parts.join(" · "))
+    }
+}
+
+/// Page loads reported to this server, newest last
+#[derive(Debug, Default)]
+pub struct PerfLog {
+    pages: VecDeque<PageLoad>,
+    last_audit: Option<LoadAudit>,
+}
+
+impl PerfLog {
+    /// Add a page's entries. When the report says the page has settled,
+    /// returns the audit of its load and the timings that regressed since
+    /// the previous one.
+    pub fn record(
+        &mut self,
+        report: PerfReport,
+        wasm_path: &Path,
+    ) -> Option<(LoadAudit, Vec<String>)> {
+        let position = self.pages.iter().position(|load| load.page == report.page);
+        let load = match position {
+            Some(i) => &mut self.pages[i],
+            None => {
+                if self.pages.len() == MAX_PAGES {
+                    self.pages.pop_front();
+                }
+                self.pages.push_back(PageLoad {
+                    page: report.page,
+                    url: report.url,
+                    entries: Vec::new(),
+                    audited: false,
+                });
+                self.pages.back_mut().unwrap()
+            }
+        };
+        load.entries.extend(report.entries);
+        let excess = load.entries.len().saturating_sub(MAX_ENTRIES);
+        load.entries.drain(..excess);
+
+        if !report.complete || load.audited {
+            return None;
+        }
+        load.audited = true;
+        let audit = LoadAudit::new(load, wasm_path);
+        let regressions = self
+            .last_audit
+            .as_ref()
+            .map(|previous| audit.regressions(previous))
+            .unwrap_or_default();
+        self.last_audit = Some(audit.clone());
+        Some((audit, regressions))
+    }
+
+    /// The most recently started page load
+    pub fn latest(&self) -> Option<&PageLoad> {
+        self.pages.back()
+    }
+
+    pub fn check(&self, budgets: &[Budget]) -> Vec<BudgetResult> {
+        budgets
+            .iter()
+            .map(|budget| {
+                let actual_ms = self
+                    .latest()
+                    .and_then(|load| load.entry(&budget.name))
+                    .map(PerfEntry::value);
+                BudgetResult {
+                    name: budget.name.clone(),
+                    limit_ms: budget.limit_ms,
+                    actual_ms,
+                    ok: actual_ms.is_some_and(|ms| ms <= budget.limit_ms),
+                }
+            })
+            .collect()
+    }
+
+    /// The body of `GET /api/perf`: the latest page load, and with budgets,
+    /// how it did against them
+    pub fn to_json(&self, budgets: &[Budget]) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "page_loads": self.pages.len(),
+            "latest": self.latest(),
+            "audit": self.last_audit,
+        });
+        if !budgets.is_empty() {
+            let results = self.check(budgets);
+            json["ok"] = results.iter().all(|result| result.ok).into();
+            json["budgets"] = serde_json::to_value(results).unwrap_or_default();
+        }
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, entry_type: &str, start_time: f64, duration: f64) -> PerfEntry {
+        PerfEntry {
+            name: name.to_string(),
+            entry_type: entry_type.to_string(),
+            start_time,
+            duration,
+        }
+    }
+
+    fn report(page: &str, entries: Vec<PerfEntry>, complete: bool) -> PerfReport {
+        PerfReport {
+            page: page.to_string(),
+            url: "/".to_string(),
+            entries,
+            complete,
+        }
+    }
+
+    #[test]
+    fn test_budgets_against_latest_load() {
+        let wasm = Path::new("app.wasm");
+        let mut log = PerfLog::default();
+        log.record(
+            report("a", vec![entry("app:ready", "mark", 900.0, 0.0)], false),
+            wasm,
+        );
+        log.record(
+            report(
+                "b",
+                vec![entry("navigation:load", "navigation", 0.0, 120.0)],
+                false,
+            ),
+            wasm,
+        );
+        log.record(
+            report(
+                "b",
+                vec![
+                    entry("app:ready", "mark", 310.5, 0.0),
+                    entry("init", "measure", 100.0, 250.0),
+                ],
+                false,
+            ),
+            wasm,
+        );
+        assert_eq!(log.latest().unwrap().entries.len(), 3);
+
+        let budgets = Budget::parse_list("app:ready:400, init:200ms,missing:1").unwrap();
+        assert_eq!(budgets[0].name, "app:ready");
+        let results = log.check(&budgets);
+        assert!(results[0].ok);
+        assert_eq!(results[1].actual_ms, Some(250.0));
+        assert!(!results[1].ok);
+        assert_eq!(results[2].actual_ms, None);
+
+        let json = log.to_json(&budgets);
+        assert_eq!(json["page_loads"], 2);
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["latest"]["entries"][1]["name"], "app:ready");
+
+        assert!(Budget::parse_list("init").is_err());
+        assert!(Budget::parse_list("init:fast").is_err());
+    }
+
+    #[test]
+    fn test_load_audit_and_regressions() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = dir.path().join("app.wasm");
+        fs::write(&wasm, vec![0u8; 64 * 1024]).unwrap();
+        let load = |compile: f64| {
+            vec![
+                entry("fetch:app.wasm", "resource", 5.0, 12.0),
+                entry("wasm:compile", "measure", 20.0, compile),
+                entry("paint:first-contentful-paint", "mark", 80.0, 0.0),
+            ]
+        };
+
+        let mut log = PerfLog::default();
+        assert!(log.record(report("a", load(30.0), false), &wasm).is_none());
+        let (audit, regressions) = log.record(report("a", vec![], true), &wasm).unwrap();
+        assert!(regressions.is_empty());
+        assert_eq!(audit.bytes, 64 * 1024);
+        assert!(audit.gzip_bytes > 0 && audit.gzip_bytes < 1024);
+        assert_eq!(audit.instantiate_ms, None);
+        let summary = audit.summary();
+        assert!(summary.starts_with("First load of /: app.wasm 64.00 KB ("));
+        assert!(summary.ends_with(" gzip) · fetch 12ms · compile 30ms · first render 80ms"));
+        // Audited once per page
+        assert!(log.record(report("a", vec![], true), &wasm).is_none());
+
+        let (_, regressions) = log.record(report("b", load(55.0), true), &wasm).unwrap();
+        assert_eq!(regressions, ["compile +25ms"]);
+        let (_, regressions) = log.record(report("c", load(60.0), true), &wasm).unwrap();
+        assert!(regressions.is_empty());
+    }
+}
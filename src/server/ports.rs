@@ -0,0 +1,221 @@
+//! The dev server's listening ports
+//!
+//! With `--api-port`, the control endpoints (`/api/*`, `/metrics` and the
+//! live-reload `/reload`) move to a second port and the primary port serves
+//! only the app, so an app's own `/api/...` paths reach its files. Both
+//! ports go through the front and call one handler, told which port each
+//! request arrived on.
+//!
+//! With `--listen unix:<path>` the primary listener is a Unix socket instead
+//! of a TCP port, for running behind a local reverse proxy.
+
+use super::Request;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+static API_PORT: Mutex<Option<u16>> = Mutex::new(None);
+static UNIX_SOCKET: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Serve the control endpoints on `port` instead of the primary port
+pub fn set_api_port(port: Option<u16>) {
+    *API_PORT.lock().unwrap() = port;
+}
+
+/// The separate control port, if one was asked for
+pub fn api_port() -> Option<u16> {
+    *API_PORT.lock().unwrap()
+}
+
+/// Listen on the Unix socket `path` instead of the TCP port
+pub fn set_unix_socket(path: Option<PathBuf>) {
+    *UNIX_SOCKET.lock().unwrap() = path;
+}
+
+/// The Unix socket the dev server listens on, if one was asked for
+pub fn unix_socket() -> Option<PathBuf> {
+    UNIX_SOCKET.lock().unwrap().clone()
+}
+
+/// The socket path in a `--listen` value. Only `unix:<path>` is accepted;
+/// TCP ports are set with `--port`.
+pub fn parse_listen(value: &str) -> Result<PathBuf, String> {
+    match value.strip_prefix("unix:") {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+        _ => Err(format!(
+            "--listen expects unix:<path>, e.g. unix:/tmp/wasmrun.sock, not {value:?}; use --port for TCP"
+        )),
+    }
+}
+
+/// Where the dev server can be reached, for startup messages
+pub fn server_url(port: u16) -> String {
+    match unix_socket() {
+        Some(path) => format!("unix:{}", path.display()),
+        None => format!("{}://localhost:{port}", super::front::scheme()),
+    }
+}
+
+/// Which routes a request may reach, given the port it arrived on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    /// The only port: app content and control endpoints
+    All,
+    /// The primary port when `--api-port` is set: app content only
+    App,
+    /// The `--api-port` port: control endpoints only
+    Control,
+}
+
+impl Surface {
+    pub fn serves_control(self) -> bool {
+        self != Surface::App
+    }
+
+    pub fn serves_app(self) -> bool {
+        self != Surface::Control
+    }
+
+    /// Whether `url` is served on this port at all
+    pub fn routes(self, url: &str) -> bool {
+        match self {
+            Surface::All => true,
+            Surface::App => !is_control_route(url),
+            Surface::Control => is_control_route(url),
+        }
+    }
+}
+
+/// Whether `url` names a control endpoint rather than app content
+pub fn is_control_route(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url);
+    path.starts_with("/api/") || path == "/metrics" || path == "/reload"
+}
+
+/// Serve `handler` on the primary listener plus, with `--api-port`, the
+/// control listener. Requests are handled concurrently, each told which
+/// port it arrived on.
+pub fn listen(
+    port: u16,
+    handler: impl Fn(Request, Surface) + Send + Sync + 'static,
+) -> Result<(), String> {
+    let handler = Arc::new(handler);
+    let socket = unix_socket();
+    let api_port = api_port().filter(|&api| socket.is_some() || api != port);
+    let surface = if api_port.is_some() {
+        Surface::App
+    } else {
+        Surface::All
+    };
+    let primary = {
+        let handler = Arc::clone(&handler);
+        move |request| handler(request, surface)
+    };
+    match &socket {
+        Some(path) => bind_unix(path, primary)?,
+        None => super::front::bind_server("0.0.0.0", port, primary)?,
+    }
+
+    if let Some(api) = api_port {
+        super::front::bind_cleartext("0.0.0.0", api, move |request| {
+            handler(request, Surface::Control)
+        })?;
+        println!(
+            "🎛️  Control endpoints on port {api}; {} serves the app only",
+            server_url(port)
+        );
+    }
+
+    super::instances::register(&super::instances::Instance::current(
+        port,
+        socket.as_deref(),
+        api_port,
+    ));
+    Ok(())
+}
+
+/// Block while the listeners serve requests, which they do until the
+/// process exits
+pub fn wait() {
+    loop {
+        thread::park();
+    }
+}
+
+/// Serve `handler` on a Unix socket at `path`. A socket file nobody answers
+/// on, left by a server that didn't shut down cleanly, is replaced; any
+/// other file at `path` is left alone.
+#[cfg(unix)]
+fn bind_unix(path: &Path, handler: impl Fn(Request) + Send + Sync + 'static) -> Result<(), String> {
+    crate::utils::remove_stale_socket(path)?;
+    super::front::bind_unix(path, handler)?;
+    super::lifecycle::record_unix_server(path);
+    println!("🔌 Listening on unix:{}", path.display());
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn bind_unix(
+    path: &Path,
+    _handler: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<(), String> {
+    Err(format!(
+        "Unix sockets aren't supported on this platform ({})",
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_routes() {
+        assert!(is_control_route("/api/version"));
+        assert!(is_control_route("/api/logs?limit=5"));
+        assert!(is_control_route("/metrics"));
+        assert!(is_control_route("/reload"));
+        assert!(!is_control_route("/"));
+        assert!(!is_control_route("/app.wasm"));
+        assert!(!is_control_route("/apiary.js"));
+        assert!(!is_control_route("/assets/logo.png"));
+
+        assert!(Surface::All.serves_app() && Surface::All.serves_control());
+        assert!(!Surface::App.serves_control());
+        assert!(!Surface::Control.serves_app());
+        assert!(Surface::App.routes("/app.wasm") && !Surface::App.routes("/api/logs"));
+        assert!(Surface::Control.routes("/metrics") && !Surface::Control.routes("/"));
+    }
+
+    #[test]
+    fn test_parse_listen() {
+        assert_eq!(
+            parse_listen("unix:/tmp/wasmrun.sock"),
+            Ok(PathBuf::from("/tmp/wasmrun.sock"))
+        );
+        assert!(parse_listen("unix:").is_err());
+        assert!(parse_listen("127.0.0.1:8420").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_stale_sockets_are_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "keep me").unwrap();
+        assert!(crate::utils::remove_stale_socket(&file)
+            .unwrap_err()
+            .contains("isn't a socket"));
+        assert!(file.exists());
+
+        let socket = dir.path().join("app.sock");
+        let live = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        assert!(crate::utils::remove_stale_socket(&socket)
+            .unwrap_err()
+            .contains("already in use"));
+        drop(live);
+        crate::utils::remove_stale_socket(&socket).unwrap();
+        assert!(!socket.exists());
+        crate::utils::remove_stale_socket(&socket).unwrap();
+    }
+}
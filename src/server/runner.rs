@@ -148,6 +148,8 @@ pub fn run_project(
             None
         },
         serve,
+        worker: false,
+        access_log: false,
     };
 
     if is_wasm_bindgen {
@@ -311,6 +313,8 @@ fn search_for_js_files(
                                 project_path: None,
                                 output_dir: None,
                                 serve,
+                                worker: false,
+                                access_log: false,
                             })
                             .map_err(|e| {
                                 WasmrunError::Server(ServerError::startup_failed(
@@ -0,0 +1,111 @@
+//! Keys the dev server answers in the terminal while it serves, as in
+//! Vite's dev server: `r` rebuilds, `o` opens the browser, `w` pauses or
+//! resumes watching, `e` shows the last build error, `q` quits and `h`
+//! lists them. Read through [`crate::ui::keys`].
+
+use super::ports::server_url;
+use super::trigger::{self, Outcome};
+use crate::ui::keys;
+use crate::watcher::{PauseGate, WatchState};
+use std::path::{Path, PathBuf};
+
+const HELP: [(char, &str); 6] = [
+    ('r', "rebuild the project"),
+    ('o', "open the app in the browser"),
+    ('w', "pause or resume watching"),
+    ('e', "show the last build error"),
+    ('q', "stop the server and quit"),
+    ('h', "show these shortcuts"),
+];
+
+/// What the keys act on
+struct Session {
+    url: String,
+    wasm_path: PathBuf,
+    watch_mode: bool,
+}
+
+/// Answer keys for the server that just started serving `wasm_path`, while
+/// the returned listener is held
+pub(super) fn listen(port: u16, wasm_path: &str, watch_mode: bool) -> Option<keys::KeyListener> {
+    let session = Session {
+        url: server_url(port),
+        wasm_path: PathBuf::from(wasm_path),
+        watch_mode,
+    };
+    let listener = keys::listen(move |key| session.handle(key));
+    if listener.is_some() {
+        println!("⌨️  Press h for shortcuts, q to quit");
+    }
+    listener
+}
+
+impl Session {
+    fn handle(&self, key: char) {
+        match key {
+            'r' => rebuild(),
+            'o' => self.open(),
+            'w' | 'p' => self.toggle_watch(),
+            'e' => last_error(),
+            'q' => quit(),
+            'h' | '?' => help(),
+            _ => {}
+        }
+    }
+
+    fn open(&self) {
+        println!("🌐 Opening {}", self.url);
+        if let Err(e) = webbrowser::open(&self.url) {
+            println!("❗ Failed to open the browser: {e}");
+        }
+    }
+
+    fn toggle_watch(&self) {
+        let session = self
+            .watch_mode
+            .then(|| WatchState::dir_for_wasm(Path::new(&self.wasm_path)))
+            .flatten();
+        let Some(dir) = session else {
+            println!("👀 Not watching for changes; run with --watch to rebuild on save");
+            return;
+        };
+        match PauseGate::toggle(&dir) {
+            Ok(true) => println!("⏸️  Watching paused"),
+            Ok(false) => println!("▶️  Watching resumed"),
+            Err(e) => eprintln!("⚠️  Failed to pause or resume watching: {e}"),
+        }
+    }
+}
+
+fn rebuild() {
+    match trigger::trigger() {
+        Some((Outcome::Started, _)) => {}
+        Some((Outcome::Queued, _)) => {
+            println!("⏳ A build is running; another is queued after it")
+        }
+        None => println!("🔁 Nothing to rebuild: this server has no project"),
+    }
+}
+
+fn last_error() {
+    let status = trigger::status();
+    match status.last_error {
+        Some(error) => eprintln!("❌ Last build failed:\n{error}"),
+        None if status.completed == 0 => println!("✅ No rebuilds yet"),
+        None => println!("✅ The last build succeeded"),
+    }
+}
+
+fn help() {
+    println!("\n⌨️  Shortcuts");
+    for (key, action) in HELP {
+        println!("   {key}  {action}");
+    }
+}
+
+fn quit() -> ! {
+    println!("👋 Stopping the server");
+    super::instances::unregister();
+    super::lifecycle::release_server();
+    keys::quit(0)
+}
@@ -0,0 +1,129 @@
+//! Rebuilds on request, for editors and scripts that decide when to build
+//! instead of relying on file watching
+//!
+//! `POST /api/build/trigger` (what `wasmrun trigger` sends) rebuilds the
+//! served project on a background thread. Requests that arrive while a
+//! build is running are folded into one more build once it finishes, so a
+//! burst of saves costs at most two builds.
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Rebuild = Arc<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+static REBUILD: Mutex<Option<Rebuild>> = Mutex::new(None);
+static QUEUE: Mutex<BuildQueue> = Mutex::new(BuildQueue::new());
+
+/// What rebuilds the served project; unset when there's no project, e.g.
+/// when serving a `.wasm` file
+pub fn set_rebuild(rebuild: impl Fn() -> Result<(), String> + Send + Sync + 'static) {
+    *REBUILD.lock().unwrap() = Some(Arc::new(rebuild));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// A build started now
+    Started,
+    /// A build is running; another follows it
+    Queued,
+}
+
+/// Where builds stand, for the trigger's response
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BuildQueue {
+    pub building: bool,
+    /// A build is owed after the running one
+    pub pending: bool,
+    /// Triggered builds finished so far
+    pub completed: u64,
+    /// Why the last triggered build failed, if it did
+    pub last_error: Option<String>,
+}
+
+impl BuildQueue {
+    const fn new() -> Self {
+        Self {
+            building: false,
+            pending: false,
+            completed: 0,
+            last_error: None,
+        }
+    }
+
+    /// Ask for a build. `Started` means the caller runs it.
+    fn request(&mut self) -> Outcome {
+        if self.building {
+            self.pending = true;
+            Outcome::Queued
+        } else {
+            self.building = true;
+            Outcome::Started
+        }
+    }
+
+    /// Record a finished build; `true` when another is owed
+    fn finish(&mut self, result: Result<(), String>) -> bool {
+        self.completed += 1;
+        self.last_error = result.err();
+        if self.pending {
+            self.pending = false;
+            true
+        } else {
+            self.building = false;
+            false
+        }
+    }
+}
+
+/// Where triggered builds stand
+pub fn status() -> BuildQueue {
+    QUEUE.lock().unwrap().clone()
+}
+
+/// Start a rebuild, or queue one behind the build in flight. `None` when
+/// this server has nothing to rebuild.
+pub fn trigger() -> Option<(Outcome, BuildQueue)> {
+    let rebuild = REBUILD.lock().unwrap().clone()?;
+    let mut queue = QUEUE.lock().unwrap();
+    let outcome = queue.request();
+    if outcome == Outcome::Started {
+        thread::spawn(move || loop {
+            println!("🔁 Rebuild triggered");
+            let result = rebuild();
+            if let Err(e) = &result {
+                eprintln!("❌ Triggered rebuild failed: {e}");
+            }
+            if !QUEUE.lock().unwrap().finish(result) {
+                break;
+            }
+        });
+    }
+    Some((outcome, queue.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_during_a_build_are_folded() {
+        let mut queue = BuildQueue::new();
+        assert_eq!(queue.request(), Outcome::Started);
+        assert_eq!(queue.request(), Outcome::Queued);
+        assert_eq!(queue.request(), Outcome::Queued);
+
+        // One follow-up build for both queued requests
+        assert!(queue.finish(Ok(())));
+        assert!(queue.building);
+        assert!(!queue.finish(Err("boom".to_string())));
+        assert!(!queue.building);
+        assert_eq!(queue.completed, 2);
+        assert_eq!(queue.last_error.as_deref(), Some("boom"));
+
+        assert_eq!(queue.request(), Outcome::Started);
+        assert!(!queue.finish(Ok(())));
+        assert_eq!(queue.last_error, None);
+    }
+}
@@ -49,7 +49,11 @@ pub fn is_port_available(port: u16) -> bool {
 
 /// Wait for server to be ready and then open browser
 pub fn open_browser_when_ready(port: u16) {
-    let url = format!("http://localhost:{port}");
+    let url = super::ports::server_url(port);
+    if crate::ci::is_enabled() {
+        println!("🔗 Not opening a browser under --ci: {url}");
+        return;
+    }
 
     thread::spawn(move || {
         let start_time = Instant::now();
@@ -263,6 +267,9 @@ impl ServerUtils {
 
     /// Print a warning if the port is not available
     pub fn handle_port_conflict(port: u16) -> Result<u16> {
+        if super::ports::unix_socket().is_some() {
+            return Ok(port);
+        }
         match Self::check_port_availability(port) {
             PortStatus::Available => Ok(port),
             PortStatus::Unavailable { alternative } => {
@@ -1,9 +1,11 @@
-use std::fs;
-use std::path::Path;
-use tiny_http::Server;
-
 use super::handler;
+use super::metrics::ServerMetrics;
+use super::ports::{self, api_port};
+use super::shortcuts;
 use crate::template::{TemplateManager, TemplateType};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
 
 /// Simple server for non-watching mode
 pub fn serve_wasm_file(
@@ -12,42 +14,66 @@ pub fn serve_wasm_file(
     wasm_filename: &str,
     serve: bool,
 ) -> Result<(), String> {
-    serve_wasm_file_with_project(wasm_path, port, wasm_filename, None, serve)
+    serve_wasm_file_with_project(
+        wasm_path,
+        port,
+        wasm_filename,
+        None,
+        false,
+        serve,
+        false,
+        false,
+    )
 }
 
-/// Simple server for non-watching mode with optional project path
+/// Server with an optional project path; in watch mode pages poll for hot
+/// updates
+#[allow(clippy::too_many_arguments)]
 pub fn serve_wasm_file_with_project(
     wasm_path: &str,
     port: u16,
     wasm_filename: &str,
     project_path: Option<&str>,
+    watch_mode: bool,
     serve: bool,
+    worker: bool,
+    access_log: bool,
 ) -> Result<(), String> {
-    let server = Server::http(format!("0.0.0.0:{port}"))
-        .map_err(|e| format!("Failed to start server: {e}"))?;
+    let template_manager = TemplateManager::default()
+        .with_worker(worker)
+        .with_api_port(api_port());
+    let template_type = TemplateType::Console;
+    let metrics = ServerMetrics::new(access_log);
+    metrics.log_rebuild_cause(wasm_path);
+    let metrics = Mutex::new(metrics);
+
+    let clients_to_reload = Mutex::new(Vec::new());
+    ports::listen(port, {
+        let (wasm_path, wasm_filename) = (wasm_path.to_string(), wasm_filename.to_string());
+        let project_path = project_path.map(str::to_string);
+        move |request, surface| {
+            handler::handle_request(
+                request,
+                surface,
+                None,
+                &wasm_filename,
+                &wasm_path,
+                project_path.as_deref(),
+                watch_mode,
+                &clients_to_reload,
+                &template_manager,
+                &template_type,
+                &metrics,
+            );
+        }
+    })?;
 
     // Server is now ready
     if serve {
         crate::server::utils::open_browser_when_ready(port);
     }
-
-    let template_manager = TemplateManager::default();
-    let template_type = TemplateType::Console;
-
-    let mut clients_to_reload = Vec::new();
-    for request in server.incoming_requests() {
-        handler::handle_request(
-            request,
-            None,
-            wasm_filename,
-            wasm_path,
-            project_path,
-            false,
-            &mut clients_to_reload,
-            &template_manager,
-            &template_type,
-        );
-    }
+    let _keys = shortcuts::listen(port, wasm_path, watch_mode);
+    ports::wait();
 
     Ok(())
 }
@@ -72,14 +98,6 @@ pub fn serve_wasm_bindgen_files_with_project(
     project_path: Option<&str>,
     serve: bool,
 ) -> Result<(), String> {
-    let server = Server::http(format!("0.0.0.0:{port}"))
-        .map_err(|e| format!("Failed to start server: {e}"))?;
-
-    // Server is now ready
-    if serve {
-        crate::server::utils::open_browser_when_ready(port);
-    }
-
     let js_path_obj = Path::new(js_path);
     let js_filename = js_path_obj
         .file_name()
@@ -87,24 +105,39 @@ pub fn serve_wasm_bindgen_files_with_project(
         .to_string_lossy()
         .to_string();
 
-    let template_manager = TemplateManager::default();
+    let template_manager = TemplateManager::default().with_api_port(api_port());
     let template_type = TemplateType::App; // Use App template for wasm-bindgen projects
+    let metrics = ServerMetrics::new(false);
+    metrics.log_rebuild_cause(wasm_path);
+    let metrics = Mutex::new(metrics);
+
+    let clients_to_reload = Mutex::new(Vec::new());
+    ports::listen(port, {
+        let (wasm_path, wasm_filename) = (wasm_path.to_string(), wasm_filename.to_string());
+        let project_path = project_path.map(str::to_string);
+        move |request, surface| {
+            handler::handle_request(
+                request,
+                surface,
+                Some(&js_filename),
+                &wasm_filename,
+                &wasm_path,
+                project_path.as_deref(),
+                false,
+                &clients_to_reload,
+                &template_manager,
+                &template_type,
+                &metrics,
+            );
+        }
+    })?;
 
-    let mut clients_to_reload = Vec::new();
-
-    for request in server.incoming_requests() {
-        handler::handle_request(
-            request,
-            Some(&js_filename),
-            wasm_filename,
-            wasm_path,
-            project_path,
-            false,
-            &mut clients_to_reload,
-            &template_manager,
-            &template_type,
-        );
+    // Server is now ready
+    if serve {
+        crate::server::utils::open_browser_when_ready(port);
     }
+    let _keys = shortcuts::listen(port, wasm_path, false);
+    ports::wait();
 
     Ok(())
 }
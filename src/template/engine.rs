@@ -0,0 +1,498 @@
+//! A small templating engine for the pages wasmrun generates
+//!
+//! The syntax keeps the placeholders existing templates already use:
+//!
+//! - `$NAME$` inserts a variable, escaped for the template's context: HTML
+//!   text, or the inside of a JavaScript string literal. `$NAME|js$` picks
+//!   the script escaping in an HTML template, `$NAME|raw$` skips escaping and
+//!   `$NAME|json$` inserts a JSON literal.
+//! - `<!-- @name -->` is a slot for markup, inserted unescaped.
+//! - `{{#if NAME}}…{{else}}…{{/if}}` and `{{#each NAME}}…{{/each}}`. Inside a
+//!   loop the current item's fields shadow outer variables.
+//!
+//! Placeholders whose name isn't set are left as written, so a `$` or `{{`
+//! in ordinary script text passes through untouched.
+
+use crate::error::{Result, WasmrunError};
+use std::collections::BTreeMap;
+
+/// How variables are escaped when no filter is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    /// Text and attribute values in an HTML document
+    Html,
+    /// The inside of a quoted JavaScript string in an inline `<script>`
+    Script,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Bool(bool),
+    /// Inserted as a JSON literal, made safe for an inline `<script>`
+    Json(serde_json::Value),
+    List(Vec<Vars>),
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Text(text) => !text.is_empty(),
+            Value::Bool(flag) => *flag,
+            Value::Json(json) => !matches!(
+                json,
+                serde_json::Value::Null | serde_json::Value::Bool(false)
+            ),
+            Value::List(items) => !items.is_empty(),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Text(text) => serde_json::Value::String(text.clone()),
+            Value::Bool(flag) => serde_json::Value::Bool(*flag),
+            Value::Json(json) => json.clone(),
+            Value::List(items) => items
+                .iter()
+                .map(|vars| {
+                    let fields = vars.0.iter();
+                    serde_json::Value::Object(
+                        fields
+                            .map(|(name, value)| (name.clone(), value.to_json()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The variables a template is rendered with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vars(BTreeMap<String, Value>);
+
+impl Vars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.0.insert(name.to_string(), Value::Text(value.into()));
+        self
+    }
+
+    pub fn flag(mut self, name: &str, value: bool) -> Self {
+        self.0.insert(name.to_string(), Value::Bool(value));
+        self
+    }
+
+    pub fn json(mut self, name: &str, value: serde_json::Value) -> Self {
+        self.0.insert(name.to_string(), Value::Json(value));
+        self
+    }
+
+    pub fn list(mut self, name: &str, items: Vec<Vars>) -> Self {
+        self.0.insert(name.to_string(), Value::List(items));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Filter {
+    Escaped,
+    Script,
+    Raw,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var {
+        name: String,
+        filter: Filter,
+        /// The placeholder as written, kept for names that aren't set
+        source: String,
+    },
+    If {
+        name: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        name: String,
+        body: Vec<Node>,
+    },
+}
+
+/// A block opened by `{{#if}}` or `{{#each}}` and not yet closed
+enum Open {
+    If {
+        name: String,
+        line: usize,
+        /// Set once `{{else}}` is seen
+        then: Option<Vec<Node>>,
+    },
+    Each {
+        name: String,
+        line: usize,
+    },
+}
+
+/// A parsed template, ready to render any number of times
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+    escape: Escape,
+}
+
+impl Template {
+    pub fn parse(source: &str, escape: Escape) -> Result<Self> {
+        let mut nodes = Vec::new();
+        // Each open block with the nodes of the level it was opened in
+        let mut stack: Vec<(Open, Vec<Node>)> = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find(['$', '<', '{']) {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let line = source[..source.len() - rest.len()].matches('\n').count() + 1;
+
+            let Some((token, len)) = parse_token(rest) else {
+                let ch = rest.chars().next().unwrap_or_default();
+                text.push(ch);
+                rest = &rest[ch.len_utf8()..];
+                continue;
+            };
+            rest = &rest[len..];
+            if !text.is_empty() {
+                nodes.push(Node::Text(std::mem::take(&mut text)));
+            }
+
+            match token {
+                Token::Node(node) => nodes.push(node),
+                Token::If(name) => {
+                    let open = Open::If {
+                        name,
+                        line,
+                        then: None,
+                    };
+                    stack.push((open, std::mem::take(&mut nodes)));
+                }
+                Token::Each(name) => {
+                    stack.push((Open::Each { name, line }, std::mem::take(&mut nodes)));
+                }
+                Token::Else => match stack.last_mut() {
+                    Some((
+                        Open::If {
+                            then: then @ None, ..
+                        },
+                        _,
+                    )) => {
+                        *then = Some(std::mem::take(&mut nodes));
+                    }
+                    _ => return Err(syntax_error(line, "{{else}} outside an {{#if}}")),
+                },
+                Token::EndIf => match stack.pop() {
+                    Some((Open::If { name, then, .. }, parent)) => {
+                        let body = std::mem::replace(&mut nodes, parent);
+                        let (then, otherwise) = match then {
+                            Some(then) => (then, body),
+                            None => (body, Vec::new()),
+                        };
+                        nodes.push(Node::If {
+                            name,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    _ => return Err(syntax_error(line, "{{/if}} without a matching {{#if}}")),
+                },
+                Token::EndEach => match stack.pop() {
+                    Some((Open::Each { name, .. }, parent)) => {
+                        let body = std::mem::replace(&mut nodes, parent);
+                        nodes.push(Node::Each { name, body });
+                    }
+                    _ => return Err(syntax_error(line, "{{/each}} without a matching {{#each}}")),
+                },
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+
+        match stack.pop() {
+            Some((Open::If { name, line, .. }, _)) => Err(syntax_error(
+                line,
+                &format!("{{{{#if {name}}}}} is never closed"),
+            )),
+            Some((Open::Each { name, line }, _)) => Err(syntax_error(
+                line,
+                &format!("{{{{#each {name}}}}} is never closed"),
+            )),
+            None => Ok(Self { nodes, escape }),
+        }
+    }
+
+    pub fn render(&self, vars: &Vars) -> String {
+        let mut out = String::new();
+        self.render_nodes(&self.nodes, &mut vec![vars], &mut out);
+        out
+    }
+
+    fn render_nodes<'a>(&self, nodes: &'a [Node], scopes: &mut Vec<&'a Vars>, out: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Var {
+                    name,
+                    filter,
+                    source,
+                } => match lookup(scopes, name) {
+                    None => out.push_str(source),
+                    Some(value) => out.push_str(&self.format(value, *filter)),
+                },
+                Node::If {
+                    name,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = match lookup(scopes, name) {
+                        Some(value) if value.is_truthy() => then,
+                        _ => otherwise,
+                    };
+                    self.render_nodes(branch, scopes, out);
+                }
+                Node::Each { name, body } => {
+                    if let Some(Value::List(items)) = lookup(scopes, name) {
+                        for item in items {
+                            scopes.push(item);
+                            self.render_nodes(body, scopes, out);
+                            scopes.pop();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn format(&self, value: &Value, filter: Filter) -> String {
+        let text = match value {
+            Value::Text(text) => text.clone(),
+            Value::Bool(flag) => flag.to_string(),
+            Value::Json(_) | Value::List(_) => return script_safe_json(&value.to_json()),
+        };
+        match filter {
+            Filter::Raw => text,
+            Filter::Script => escape_script(&text),
+            Filter::Json => script_safe_json(&value.to_json()),
+            Filter::Escaped => match self.escape {
+                Escape::Html => escape_html(&text),
+                Escape::Script => escape_script(&text),
+            },
+        }
+    }
+}
+
+/// Parse `source` and render it in one go
+pub fn render(source: &str, escape: Escape, vars: &Vars) -> Result<String> {
+    Ok(Template::parse(source, escape)?.render(vars))
+}
+
+enum Token {
+    Node(Node),
+    If(String),
+    Each(String),
+    Else,
+    EndIf,
+    EndEach,
+}
+
+/// The tag at the start of `input` and its length, if it is one
+fn parse_token(input: &str) -> Option<(Token, usize)> {
+    if let Some(body) = input.strip_prefix('$') {
+        let end = body.find('$')?;
+        let (name, filter) = match body[..end].split_once('|') {
+            None => (&body[..end], Filter::Escaped),
+            Some((name, "raw")) => (name, Filter::Raw),
+            Some((name, "js")) => (name, Filter::Script),
+            Some((name, "json")) => (name, Filter::Json),
+            Some(_) => return None,
+        };
+        let valid = name.starts_with(|c: char| c.is_ascii_uppercase() || c == '_')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+        let source = &input[..end + 2];
+        return valid.then(|| {
+            let var = Node::Var {
+                name: name.to_string(),
+                filter,
+                source: source.to_string(),
+            };
+            (Token::Node(var), source.len())
+        });
+    }
+
+    if let Some(body) = input.strip_prefix("<!-- @") {
+        let end = body.find(" -->")?;
+        let name = &body[..end];
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        let source = &input[..end + "<!-- @ -->".len()];
+        return valid.then(|| {
+            let slot = Node::Var {
+                name: name.to_string(),
+                filter: Filter::Raw,
+                source: source.to_string(),
+            };
+            (Token::Node(slot), source.len())
+        });
+    }
+
+    let body = input.strip_prefix("{{")?;
+    let end = body.find("}}")?;
+    let tag = body[..end].trim();
+    let token = match tag {
+        "else" => Token::Else,
+        "/if" => Token::EndIf,
+        "/each" => Token::EndEach,
+        _ => {
+            let (keyword, name) = tag.split_once(' ')?;
+            let name = name.trim().to_string();
+            match keyword {
+                "#if" => Token::If(name),
+                "#each" => Token::Each(name),
+                _ => return None,
+            }
+        }
+    };
+    Some((token, end + 4))
+}
+
+fn lookup<'a>(scopes: &[&'a Vars], name: &str) -> Option<&'a Value> {
+    scopes.iter().rev().find_map(|vars| vars.0.get(name))
+}
+
+fn syntax_error(line: usize, message: &str) -> WasmrunError {
+    WasmrunError::from(format!("Template error on line {line}: {message}"))
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Escape for either kind of quoted JS string, without ever producing
+/// `</script>` or an HTML comment opener
+pub fn escape_script(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '"' => escaped.push_str("\\\""),
+            '`' => escaped.push_str("\\`"),
+            '$' => escaped.push_str("\\$"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// JSON that can sit inside an inline `<script>` as a literal
+fn script_safe_json(json: &serde_json::Value) -> String {
+    json.to_string()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variables_are_escaped_for_context() {
+        let vars = Vars::new().text("FILENAME", "a'b<\"c\">.wasm");
+        let html = render("<title>$FILENAME$</title>", Escape::Html, &vars).unwrap();
+        assert_eq!(html, "<title>a&#39;b&lt;&quot;c&quot;&gt;.wasm</title>");
+
+        let js = render("init('$FILENAME$');", Escape::Script, &vars).unwrap();
+        assert_eq!(js, r#"init('a\'b\u003c\"c\"\u003e.wasm');"#);
+
+        let inline = render("<script>f = '$FILENAME|js$'</script>", Escape::Html, &vars).unwrap();
+        assert_eq!(
+            inline,
+            r#"<script>f = 'a\'b\u003c\"c\"\u003e.wasm'</script>"#
+        );
+
+        let raw = render("$FILENAME|raw$ $FILENAME|json$", Escape::Html, &vars).unwrap();
+        assert_eq!(raw, r#"a'b<"c">.wasm "a'b\u003c\"c\"\u003e.wasm""#);
+
+        // Unknown names, lowercase `$x$` and JS template literals pass through
+        let untouched = "const s = `${a}`; $other$ $UNSET$ {{ x }} <!-- note -->";
+        assert_eq!(render(untouched, Escape::Script, &vars).unwrap(), untouched);
+    }
+
+    #[test]
+    fn test_conditionals_loops_and_slots() {
+        let template = Template::parse(
+            "<head><!-- @style --></head>{{#if WATCH}}watching{{else}}static{{/if}}\
+             <ul>{{#each MODULES}}<li>$NAME$@$PORT${{#if MOUNT}}#$MOUNT${{/if}}</li>{{/each}}</ul>",
+            Escape::Html,
+        )
+        .unwrap();
+        let vars = Vars::new()
+            .text("style", "<style>b{}</style>")
+            .flag("WATCH", true)
+            .text("PORT", "8420")
+            .list(
+                "MODULES",
+                vec![
+                    Vars::new().text("NAME", "ui").text("MOUNT", "app"),
+                    Vars::new().text("NAME", "<core>").text("MOUNT", ""),
+                ],
+            );
+        assert_eq!(
+            template.render(&vars),
+            "<head><style>b{}</style></head>watching\
+             <ul><li>ui@8420#app</li><li>&lt;core&gt;@8420</li></ul>"
+        );
+        let vars = vars.flag("WATCH", false).list("MODULES", Vec::new());
+        assert_eq!(
+            template.render(&vars),
+            "<head><style>b{}</style></head>static<ul></ul>"
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_blocks_are_errors() {
+        let error = Template::parse("a\n{{#if WATCH}}b", Escape::Html).unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+        assert!(Template::parse("{{/each}}", Escape::Html).is_err());
+        assert!(Template::parse("{{#each A}}{{else}}{{/each}}", Escape::Html).is_err());
+        assert!(Template::parse("{{#if A}}{{#each B}}{{/if}}{{/each}}", Escape::Html).is_err());
+    }
+}
@@ -0,0 +1,739 @@
+pub mod engine;
+
+use crate::error::{Result, WasmrunError};
+use engine::{Escape, Vars};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Injected into watch-mode pages so apps can keep their state across live
+/// reloads. Apps opt in by defining the hooks below; pages that don't are
+/// unaffected.
+///
+/// - `window.__wasmrun_serialize_state()` returns any JSON-serializable value;
+///   it is called right before the page unloads.
+/// - `window.__wasmrun_restore_state(state)` receives that value after the
+///   reload, whether it is assigned before or after the page finishes loading.
+/// - `window.__wasmrun_take_state()` returns the saved value once, for apps
+///   that prefer to read it while initializing (e.g. from Rust via `js_sys`).
+const HOT_STATE_SCRIPT: &str = r#"(function () {
+  var key = 'wasmrun:hot-state:$FILENAME$';
+  var pending;
+  try {
+    var saved = sessionStorage.getItem(key);
+    sessionStorage.removeItem(key);
+    if (saved !== null) pending = { state: JSON.parse(saved) };
+  } catch (e) {
+    console.warn('[Wasmrun] Discarding saved hot state:', e);
+  }
+
+  window.__wasmrun_take_state = function () {
+    var taken = pending;
+    pending = undefined;
+    return taken ? taken.state : undefined;
+  };
+
+  var restore;
+  Object.defineProperty(window, '__wasmrun_restore_state', {
+    configurable: true,
+    get: function () { return restore; },
+    set: function (fn) {
+      restore = fn;
+      if (typeof fn === 'function' && pending) {
+        try {
+          fn(window.__wasmrun_take_state());
+          console.log('[Wasmrun] Restored state from before reload');
+        } catch (e) {
+          console.warn('[Wasmrun] __wasmrun_restore_state failed:', e);
+        }
+      }
+    },
+  });
+
+  var stored = false;
+  function save() {
+    if (stored || typeof window.__wasmrun_serialize_state !== 'function') return;
+    stored = true;
+    try {
+      var state = window.__wasmrun_serialize_state();
+      if (state !== undefined) sessionStorage.setItem(key, JSON.stringify(state));
+    } catch (e) {
+      console.warn('[Wasmrun] __wasmrun_serialize_state failed, state not kept:', e);
+    }
+  }
+  window.addEventListener('beforeunload', save);
+  window.addEventListener('pagehide', save);
+})();"#;
+
+/// Injected into watch-mode pages to apply the updates described in
+/// [`crate::server::hot`]: stylesheet edits swap the matching `<link>` once
+/// the new one has loaded, asset edits re-fetch the elements that use them,
+/// and rebuilds reload the page. A style the page doesn't link reloads too.
+const HOT_SWAP_SCRIPT: &str = r#"(function () {
+  var apiPort = document.querySelector('meta[name="wasmrun-api-port"]');
+  var base = apiPort ? location.protocol + '//' + location.hostname + ':' + apiPort.content : '';
+  var since = null;
+
+  function matches(url, path) {
+    try {
+      return new URL(url, location.href).pathname === '/' + path;
+    } catch (e) {
+      return false;
+    }
+  }
+  function bust(url, seq) {
+    var busted = new URL(url, location.href);
+    busted.searchParams.set('wasmrun', seq);
+    return busted.href;
+  }
+
+  function swapStyle(update) {
+    var links = document.querySelectorAll('link[rel~="stylesheet"]');
+    var swapped = false;
+    links.forEach(function (link) {
+      if (!matches(link.href, update.path)) return;
+      var next = link.cloneNode();
+      next.href = bust(link.href, update.seq);
+      next.onload = next.onerror = function () { link.remove(); };
+      link.after(next);
+      swapped = true;
+    });
+    return swapped;
+  }
+  function swapAsset(update) {
+    ['src', 'href', 'poster'].forEach(function (attr) {
+      document.querySelectorAll('[' + attr + ']').forEach(function (el) {
+        if (el.matches('link[rel~="stylesheet"]') || !matches(el.getAttribute(attr), update.path)) return;
+        el.setAttribute(attr, bust(el.getAttribute(attr), update.seq));
+      });
+    });
+    return true;
+  }
+  function apply(update) {
+    if (update.action === 'style' && swapStyle(update)) {
+      console.log('[Wasmrun] Swapped stylesheet ' + update.path);
+      return true;
+    }
+    if (update.action === 'asset' && swapAsset(update)) {
+      console.log('[Wasmrun] Refreshed asset ' + update.path);
+      return true;
+    }
+    return false;
+  }
+
+  function poll() {
+    fetch(base + '/reload' + (since === null ? '' : '?since=' + since), { cache: 'no-store' })
+      .then(function (response) { return response.json(); })
+      .then(function (answer) {
+        if (!answer.watching) return;
+        for (var i = 0; i < answer.updates.length; i++) {
+          if (!apply(answer.updates[i])) {
+            location.reload();
+            return;
+          }
+        }
+        since = answer.seq;
+        setTimeout(poll, 1000);
+      })
+      .catch(function () { setTimeout(poll, 3000); });
+  }
+  poll();
+})();"#;
+
+/// Injected into every page to report its timings to `/api/perf`:
+/// `performance.mark`/`measure` entries, navigation milestones and `.wasm`
+/// fetch times, compile and instantiate times, and paints. Entries are
+/// batched and sent once they stop arriving; a report marked `complete`
+/// follows two seconds after `load` (or at `pagehide`, if that comes
+/// first), and the server audits the load then. The page's own entries are
+/// also kept in `window.__wasmrun_perf`, with a `wasmrun:perf` event for
+/// each, for the console's Timing tab.
+const PERF_SCRIPT: &str = r#"(function () {
+  if (typeof PerformanceObserver === 'undefined') return;
+  var apiPort = document.querySelector('meta[name="wasmrun-api-port"]');
+  var base = apiPort ? location.protocol + '//' + location.hostname + ':' + apiPort.content : '';
+  var page = Date.now().toString(36) + Math.random().toString(36).slice(2, 8);
+  var pending = [];
+  var timer = null;
+  var all = (window.__wasmrun_perf = []);
+
+  var settled = false;
+
+  function send(complete) {
+    clearTimeout(timer);
+    timer = null;
+    if (pending.length === 0 && !complete) return;
+    var body = JSON.stringify({
+      page: page, url: location.pathname, entries: pending, complete: complete === true,
+    });
+    pending = [];
+    if (!(navigator.sendBeacon && navigator.sendBeacon(base + '/api/perf', body))) {
+      fetch(base + '/api/perf', { method: 'POST', body: body, keepalive: true }).catch(function () {});
+    }
+  }
+  function add(name, type, start, duration) {
+    var entry = { name: name, entry_type: type, start_time: start, duration: duration };
+    pending.push(entry);
+    all.push(entry);
+    window.dispatchEvent(new CustomEvent('wasmrun:perf', { detail: entry }));
+    clearTimeout(timer);
+    timer = setTimeout(send, 500);
+  }
+
+  function observe(type, handle) {
+    try {
+      new PerformanceObserver(function (list) { list.getEntries().forEach(handle); })
+        .observe({ type: type, buffered: true });
+    } catch (e) {
+      // Entry type not supported by this browser
+    }
+  }
+  observe('mark', function (e) { add(e.name, 'mark', e.startTime, 0); });
+  observe('paint', function (e) { add('paint:' + e.name, 'mark', e.startTime, 0); });
+  observe('measure', function (e) { add(e.name, 'measure', e.startTime, e.duration); });
+  observe('resource', function (e) {
+    if (/\.wasm(\?|$)/.test(e.name)) {
+      add('fetch:' + new URL(e.name).pathname.split('/').pop(), 'resource', e.startTime, e.duration);
+    }
+  });
+  observe('navigation', function (e) {
+    if (e.responseStart > 0) add('navigation:first-byte', 'navigation', 0, e.responseStart);
+    if (e.domContentLoadedEventEnd > 0) {
+      add('navigation:dom-content-loaded', 'navigation', 0, e.domContentLoadedEventEnd);
+    }
+    if (e.loadEventEnd > 0) add('navigation:load', 'navigation', 0, e.loadEventEnd);
+  });
+
+  // Time compiling and instantiating, whichever API the page uses
+  ['compile', 'compileStreaming', 'instantiate', 'instantiateStreaming'].forEach(function (api) {
+    var original = WebAssembly[api];
+    if (typeof original !== 'function') return;
+    var name = api.indexOf('compile') === 0 ? 'wasm:compile' : 'wasm:instantiate';
+    WebAssembly[api] = function () {
+      var start = performance.now();
+      return original.apply(WebAssembly, arguments).then(function (result) {
+        add(name, 'measure', start, performance.now() - start);
+        return result;
+      });
+    };
+  });
+
+  // The page has settled a moment after `load`, when the server audits it
+  function settle() {
+    if (settled) return;
+    settled = true;
+    send(true);
+  }
+  window.addEventListener('load', function () { setTimeout(settle, 2000); });
+  window.addEventListener('pagehide', function () {
+    if (settled) send();
+    else settle();
+  });
+})();"#;
+
+/// Runs inside the Web Worker started by `WORKER_BRIDGE_SCRIPT`: instantiates
+/// the module off the main thread and answers `call`/`memory` requests.
+/// Function imports the worker can't provide are stubbed to return 0.
+const WORKER_SCRIPT: &str = r#"let instance;
+onmessage = async ({ data }) => {
+  const { id, op } = data;
+  try {
+    if (op === 'load') {
+      const module = await WebAssembly.compileStreaming(fetch(data.url));
+      const imports = {};
+      const stubbed = [];
+      for (const imp of WebAssembly.Module.imports(module)) {
+        if (imp.kind !== 'function') {
+          throw new Error(`Import ${imp.module}.${imp.name} (${imp.kind}) is not available in worker mode`);
+        }
+        stubbed.push(`${imp.module}.${imp.name}`);
+        (imports[imp.module] ||= {})[imp.name] = (...args) => {
+          postMessage({ log: `${imp.module}.${imp.name}(${args.join(', ')}) called; stubbed to return 0` });
+          return 0;
+        };
+      }
+      instance = await WebAssembly.instantiate(module, imports);
+      const exports = Object.keys(instance.exports);
+      postMessage({ id, result: {
+        exports,
+        functions: exports.filter(name => typeof instance.exports[name] === 'function'),
+        imports: stubbed,
+      } });
+    } else if (op === 'call') {
+      const fn = instance && instance.exports[data.name];
+      if (typeof fn !== 'function') throw new Error(`Function ${data.name} not found in exports`);
+      const result = fn(...data.args);
+      postMessage({ id, result: typeof result === 'bigint' ? result.toString() : result });
+    } else if (op === 'memory') {
+      const memory = instance && instance.exports.memory;
+      if (!(memory instanceof WebAssembly.Memory)) throw new Error('No memory export found');
+      postMessage({ id, result: data.grow ? memory.grow(data.grow) : memory.buffer.byteLength });
+    }
+  } catch (e) {
+    postMessage({ id, error: String((e && e.message) || e) });
+  }
+};"#;
+
+/// Injected when serving with `--worker`. Defines `window.__wasmrun_worker`,
+/// whose `load(url)` starts the worker and resolves to a comlink-style
+/// handle: `handle.exports.name(...args)` returns a promise of the result.
+const WORKER_BRIDGE_SCRIPT: &str = r#"(function () {
+  var source = $WORKER_SOURCE|json$;
+  window.__wasmrun_worker = {
+    load: function (url) {
+      var worker = new Worker(URL.createObjectURL(new Blob([source], { type: 'text/javascript' })));
+      var nextId = 0;
+      var pending = new Map();
+      worker.onmessage = function (event) {
+        var data = event.data;
+        if (data.log) {
+          console.log('[Wasmrun worker]', data.log);
+          return;
+        }
+        var call = pending.get(data.id);
+        if (!call) return;
+        pending.delete(data.id);
+        if (data.error !== undefined) call.reject(new Error(data.error));
+        else call.resolve(data.result);
+      };
+      worker.onerror = function (event) {
+        pending.forEach(function (call) { call.reject(new Error(event.message || 'Worker error')); });
+        pending.clear();
+      };
+      function rpc(message) {
+        return new Promise(function (resolve, reject) {
+          var id = ++nextId;
+          pending.set(id, { resolve: resolve, reject: reject });
+          message.id = id;
+          worker.postMessage(message);
+        });
+      }
+      var exports = new Proxy({}, {
+        get: function (_, name) {
+          // Not thenable, so the handle can be returned from async functions
+          if (typeof name !== 'string' || name === 'then') return undefined;
+          return function () {
+            return rpc({ op: 'call', name: name, args: Array.prototype.slice.call(arguments) });
+          };
+        },
+      });
+      return rpc({ op: 'load', url: new URL(url, location.href).href }).then(function (info) {
+        return {
+          info: info,
+          exports: exports,
+          memory: {
+            byteLength: function () { return rpc({ op: 'memory' }); },
+            grow: function (pages) { return rpc({ op: 'memory', grow: pages }); },
+          },
+          terminate: function () { worker.terminate(); },
+        };
+      });
+    },
+  };
+})();"#;
+
+/// Everything injected at a page's `<!-- @script-placeholder -->`, in the
+/// order it has to run
+const PAGE_SCRIPTS: &str = r#"{{#if API_PORT}}<meta name="wasmrun-api-port" content="$API_PORT$">{{/if}}{{#if WATCH}}<meta name="wasmrun-watch" content="true">{{/if}}
+<script>
+// Wasmrun performance timing
+<!-- @perf -->
+</script>{{#if WORKER}}<meta name="wasmrun-worker" content="true">
+<script>
+// Wasmrun Web Worker bridge
+<!-- @worker-bridge -->
+</script>{{/if}}{{#if WATCH}}
+<script>
+// Wasmrun hot state preservation
+<!-- @hot-state -->
+</script>
+<script>
+// Wasmrun hot swapping
+<!-- @hot-swap -->
+</script>{{/if}}{{#if WASI}}
+<script>
+// Wasmrun WASI implementation
+<!-- @wasi -->
+</script>{{/if}}
+<script>
+// Main script
+<!-- @main -->
+</script>"#;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum TemplateType {
+    Console,
+    App,
+}
+
+impl TemplateType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TemplateType::Console => "console",
+            TemplateType::App => "app",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Template {
+    pub html: engine::Template,
+    pub css: String,
+    pub js: engine::Template,
+    pub wasi_js: Option<String>,
+}
+
+pub struct TemplateManager {
+    templates: HashMap<TemplateType, Template>,
+    template_dir: PathBuf,
+    worker: bool,
+    api_port: Option<u16>,
+}
+
+impl TemplateManager {
+    pub fn new() -> Result<Self> {
+        // First, try to find templates relative to the current executable
+        // This works for: cargo run (debug), cargo install, and system installations
+        let template_dir = match Self::find_templates_dir() {
+            Some(dir) => dir,
+            None => PathBuf::from("templates"), // Fallback for development scenarios
+        };
+
+        let mut manager = Self {
+            templates: HashMap::new(),
+            template_dir,
+            worker: false,
+            api_port: None,
+        };
+        manager.load_templates()?;
+        Ok(manager)
+    }
+
+    /// Have generated pages instantiate the module in a Web Worker
+    pub fn with_worker(mut self, worker: bool) -> Self {
+        self.worker = worker;
+        self
+    }
+
+    /// Point generated pages at control endpoints served on another port
+    pub fn with_api_port(mut self, api_port: Option<u16>) -> Self {
+        self.api_port = api_port;
+        self
+    }
+
+    // Find templates directory by checking multiple locations
+    fn find_templates_dir() -> Option<PathBuf> {
+        // 1. Check relative to current directory first (for development/cargo run scenarios)
+        let cwd_templates = PathBuf::from("templates");
+        if cwd_templates.exists() && Self::is_valid_templates_dir(&cwd_templates) {
+            return cwd_templates.canonicalize().ok();
+        }
+
+        // 2. Check relative to executable (for installed versions)
+        if let Ok(exe_path) = std::env::current_exe() {
+            if let Some(exe_dir) = exe_path.parent() {
+                // Check both alongside executable and in parent directories
+                let mut check_path = exe_dir.to_path_buf();
+                for _ in 0..3 {
+                    let templates_path = check_path.join("templates");
+                    if templates_path.exists() && Self::is_valid_templates_dir(&templates_path) {
+                        return Some(templates_path);
+                    }
+                    if !check_path.pop() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // 3. Search up from current directory (fallback for nested execution)
+        if let Ok(mut current) = std::env::current_dir() {
+            for _ in 0..10 {
+                let templates_path = current.join("templates");
+                if templates_path.exists() && Self::is_valid_templates_dir(&templates_path) {
+                    return Some(templates_path);
+                }
+                if !current.pop() {
+                    break;
+                }
+            }
+        }
+
+        None
+    }
+
+    // Validate that a directory contains the required template subdirectories
+    fn is_valid_templates_dir(path: &Path) -> bool {
+        path.join("console").exists() && path.join("app").exists()
+    }
+
+    #[allow(dead_code)]
+    pub fn with_template_dir<P: AsRef<Path>>(template_dir: P) -> Result<Self> {
+        let mut manager = Self {
+            templates: HashMap::new(),
+            template_dir: template_dir.as_ref().to_path_buf(),
+            worker: false,
+            api_port: None,
+        };
+        manager.load_templates()?;
+        Ok(manager)
+    }
+
+    fn load_templates(&mut self) -> Result<()> {
+        // Load console template
+        let console_template = self.load_template(&TemplateType::Console)?;
+        self.templates
+            .insert(TemplateType::Console, console_template);
+
+        // Load app template
+        let app_template = self.load_template(&TemplateType::App)?;
+        self.templates.insert(TemplateType::App, app_template);
+
+        Ok(())
+    }
+
+    fn load_template(&self, template_type: &TemplateType) -> Result<Template> {
+        let template_path = self.template_dir.join(template_type.as_str());
+
+        if !template_path.exists() {
+            return Err(WasmrunError::from(format!(
+                "Template directory not found: {}",
+                template_path.display()
+            )));
+        }
+
+        let html = self.read_template_file(&template_path, "index.html")?;
+        let css = self.read_template_file(&template_path, "style.css")?;
+        let js = self.read_template_file(&template_path, "scripts.js")?;
+        let parse = |source: &str, escape, filename: &str| {
+            engine::Template::parse(source, escape).map_err(|e| {
+                WasmrunError::from(format!(
+                    "Invalid template {}: {e}",
+                    template_path.join(filename).display()
+                ))
+            })
+        };
+        let html = parse(&html, Escape::Html, "index.html")?;
+        let js = parse(&js, Escape::Script, "scripts.js")?;
+
+        // Load WASI JS
+        let wasi_js = match template_type {
+            // TODO: Replace wasmrun_wasi_impl.js with some library or CDN link in the future
+            TemplateType::Console => {
+                if template_path.join("wasmrun_wasi_impl.js").exists() {
+                    Some(self.read_template_file(&template_path, "wasmrun_wasi_impl.js")?)
+                } else {
+                    None
+                }
+            }
+            TemplateType::App => {
+                if template_path.join("wasmrun_wasi_impl.js").exists() {
+                    Some(self.read_template_file(&template_path, "wasmrun_wasi_impl.js")?)
+                } else {
+                    None
+                }
+            }
+        };
+
+        Ok(Template {
+            html,
+            css,
+            js,
+            wasi_js,
+        })
+    }
+
+    fn read_template_file(&self, template_path: &Path, filename: &str) -> Result<String> {
+        let file_path = template_path.join(filename);
+        fs::read_to_string(&file_path).map_err(|e| {
+            WasmrunError::from(format!(
+                "Failed to read template file {}: {}",
+                file_path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Render a page in the default language without live reload
+    #[cfg(test)]
+    fn generate_html(&self, template_type: &TemplateType, filename: &str) -> Result<String> {
+        let template = self.templates.get(template_type).ok_or_else(|| {
+            WasmrunError::from(format!("Template not found: {}", template_type.as_str()))
+        })?;
+
+        self.render_template(template, filename, false, crate::i18n::page_language(None))
+    }
+
+    /// Render a page in `language`, with the live-reload hooks if `watch_mode`
+    pub fn generate_html_with_watch_mode(
+        &self,
+        template_type: &TemplateType,
+        filename: &str,
+        watch_mode: bool,
+        language: &str,
+    ) -> Result<String> {
+        let template = self.templates.get(template_type).ok_or_else(|| {
+            WasmrunError::from(format!("Template not found: {}", template_type.as_str()))
+        })?;
+
+        self.render_template(template, filename, watch_mode, language)
+    }
+
+    fn render_template(
+        &self,
+        template: &Template,
+        filename: &str,
+        watch_mode: bool,
+        language: &str,
+    ) -> Result<String> {
+        let vars = Vars::new()
+            .text("FILENAME", filename)
+            .text("TITLE", self.generate_title(filename))
+            .text("LANG", language);
+
+        let mut scripts = Vars::new()
+            .text(
+                "API_PORT",
+                self.api_port
+                    .map(|port| port.to_string())
+                    .unwrap_or_default(),
+            )
+            .flag("WATCH", watch_mode)
+            .flag("WORKER", self.worker)
+            .text("perf", PERF_SCRIPT)
+            .text("main", template.js.render(&vars));
+        if self.worker {
+            let bridge = Vars::new().text("WORKER_SOURCE", WORKER_SCRIPT);
+            scripts = scripts.text(
+                "worker-bridge",
+                engine::render(WORKER_BRIDGE_SCRIPT, Escape::Script, &bridge)?,
+            );
+        }
+        if watch_mode {
+            scripts = scripts
+                .text(
+                    "hot-state",
+                    engine::render(HOT_STATE_SCRIPT, Escape::Script, &vars)?,
+                )
+                .text("hot-swap", HOT_SWAP_SCRIPT);
+        }
+        if let Some(wasi_js) = &template.wasi_js {
+            scripts = scripts.flag("WASI", true).text("wasi", wasi_js);
+        }
+
+        let vars = vars
+            .text(
+                "style-placeholder",
+                format!("<style>\n{}\n</style>", template.css),
+            )
+            .text(
+                "script-placeholder",
+                engine::render(PAGE_SCRIPTS, Escape::Html, &scripts)?,
+            );
+        Ok(template.html.render(&vars))
+    }
+
+    fn generate_title(&self, filename: &str) -> String {
+        let file_stem = Path::new(filename)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(filename);
+        format!("Wasmrun - {file_stem}")
+    }
+
+    #[allow(dead_code)]
+    pub fn list_available_templates(&self) -> Vec<&TemplateType> {
+        self.templates.keys().collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn has_template(&self, template_type: &TemplateType) -> bool {
+        self.templates.contains_key(template_type)
+    }
+}
+
+impl Default for TemplateManager {
+    fn default() -> Self {
+        Self::new().expect("Failed to load templates. Make sure the 'templates/' directory exists with console/ and app/ subdirectories.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager_with_app_template() -> (tempfile::TempDir, TemplateManager) {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["console", "app"] {
+            let sub = dir.path().join(name);
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(
+                sub.join("index.html"),
+                "<html lang=\"$LANG$\"><head><title>$TITLE$</title><!-- @style-placeholder --></head><body><!-- @script-placeholder --></body></html>",
+            )
+            .unwrap();
+            fs::write(sub.join("style.css"), "").unwrap();
+            fs::write(sub.join("scripts.js"), "init('$FILENAME$');").unwrap();
+        }
+        let manager = TemplateManager::with_template_dir(dir.path()).unwrap();
+        (dir, manager)
+    }
+
+    #[test]
+    fn test_hot_state_script_only_in_watch_mode() {
+        let (_dir, manager) = manager_with_app_template();
+
+        let watched = manager
+            .generate_html_with_watch_mode(&TemplateType::App, "app_bg.wasm", true, "en")
+            .unwrap();
+        assert!(watched.contains("__wasmrun_serialize_state"));
+        assert!(watched.contains("wasmrun:hot-state:app_bg.wasm"));
+        // The hooks must be installed before the app's own script runs
+        let hooks = watched.find("__wasmrun_take_state").unwrap();
+        assert!(hooks < watched.find("init('app_bg.wasm')").unwrap());
+        assert!(watched.contains("// Wasmrun hot swapping"));
+
+        let plain = manager
+            .generate_html(&TemplateType::App, "app_bg.wasm")
+            .unwrap();
+        assert!(!plain.contains("__wasmrun_serialize_state"));
+        assert!(!plain.contains("/reload"));
+        // Timings are reported with or without watching
+        let perf = plain.find("// Wasmrun performance timing").unwrap();
+        assert!(perf < plain.find("init('app_bg.wasm')").unwrap());
+    }
+
+    #[test]
+    fn test_worker_bridge_is_opt_in() {
+        let (_dir, manager) = manager_with_app_template();
+        let plain = manager
+            .generate_html(&TemplateType::Console, "app.wasm")
+            .unwrap();
+        assert!(!plain.contains("__wasmrun_worker"));
+
+        let manager = manager.with_worker(true);
+        let html = manager
+            .generate_html(&TemplateType::Console, "app.wasm")
+            .unwrap();
+        let bridge = html.find("// Wasmrun Web Worker bridge").unwrap();
+        assert!(bridge < html.find("init('app.wasm')").unwrap());
+        // The worker source is embedded as a string literal, so it must not
+        // contain anything that ends the surrounding script tag
+        let script_end = html[bridge..].find("</script>").unwrap();
+        assert!(html[bridge..bridge + script_end].contains("compileStreaming"));
+        assert!(!html.contains("$WORKER_SOURCE$"));
+    }
+
+    #[test]
+    fn test_filename_is_escaped_for_each_context() {
+        let (_dir, manager) = manager_with_app_template();
+        let html = manager
+            .generate_html_with_watch_mode(&TemplateType::App, "it's <b>.wasm", true, "es")
+            .unwrap();
+        assert!(html.contains("<title>Wasmrun - it&#39;s &lt;b&gt;</title>"));
+        assert!(html.contains(r"init('it\'s \u003cb\u003e.wasm');"));
+        assert!(html.contains(r"wasmrun:hot-state:it\'s \u003cb\u003e.wasm"));
+        assert!(!html.contains("<b>"));
+        assert!(html.starts_with(r#"<html lang="es">"#));
+    }
+}
@@ -0,0 +1,226 @@
+//! Single-key commands typed into the terminal while wasmrun keeps running,
+//! such as `p` to pause watching or `r` to rebuild while serving.
+//!
+//! On Unix the terminal leaves line mode while wasmrun listens, so a key
+//! acts as soon as it's pressed, and Ctrl+C arrives as a key that quits.
+//! Where `stty` isn't available, a key is a letter followed by Enter.
+//! Quitting through [`quit`], a Ctrl-C signal, dropping the [`KeyListener`]
+//! or a panic restores the terminal.
+
+use std::io::{self, IsTerminal, Read};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, Once};
+use std::thread;
+
+const CTRL_C: u8 = 0x03;
+const ESCAPE: u8 = 0x1b;
+
+/// The terminal's settings from before it left line mode, as `stty -g`
+/// printed them
+static SAVED_TERMINAL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Keys being listened for. Dropping it, on any return from the function
+/// holding it, puts the terminal back in line mode.
+#[must_use = "the terminal goes back to line mode when this is dropped"]
+pub struct KeyListener(());
+
+impl Drop for KeyListener {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Call `on_key` with each key pressed, lowercased, on a background
+/// thread, for as long as the returned listener is held. `None`, with
+/// nothing read, when stdin isn't a terminal or under `--ci`, so piped
+/// input stays with whatever runs under wasmrun.
+pub fn listen(on_key: impl Fn(char) + Send + 'static) -> Option<KeyListener> {
+    if crate::ci::is_enabled() || !io::stdin().is_terminal() {
+        return None;
+    }
+    let single_key = leave_line_mode();
+    if !single_key {
+        println!("⌨️  Follow each key with Enter in this terminal");
+    }
+    thread::spawn(move || {
+        let mut decoder = KeyDecoder::new(single_key);
+        for byte in io::stdin().lock().bytes() {
+            let Ok(byte) = byte else {
+                break;
+            };
+            if byte == CTRL_C {
+                quit(130);
+            }
+            if let Some(key) = decoder.feed(byte) {
+                on_key(key);
+            }
+        }
+    });
+    Some(KeyListener(()))
+}
+
+/// Stop running builds, put the terminal back in line mode and exit
+pub fn quit(code: i32) -> ! {
+    crate::utils::stop_running_tools();
+    restore_terminal();
+    std::process::exit(code)
+}
+
+/// Put the terminal back in line mode, if [`listen`] took it out
+pub fn restore_terminal() {
+    let saved = SAVED_TERMINAL
+        .lock()
+        .ok()
+        .and_then(|mut saved| saved.take());
+    if let Some(settings) = saved {
+        let _ = stty(&[&settings]);
+    }
+}
+
+/// Deliver keys without Enter, echo or signals; `false` when the terminal
+/// stays in line mode
+fn leave_line_mode() -> bool {
+    if !cfg!(unix) {
+        return false;
+    }
+    let Some(saved) = stty(&["-g"]) else {
+        return false;
+    };
+    if stty(&["-icanon", "-echo", "-isig", "min", "1", "time", "0"]).is_none() {
+        return false;
+    }
+    if let Ok(mut slot) = SAVED_TERMINAL.lock() {
+        *slot = Some(saved.trim().to_string());
+    }
+    restore_on_panic();
+    true
+}
+
+/// Restore the terminal before a panic's message is printed, so it's
+/// readable and the shell isn't left without echo
+fn restore_on_panic() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// Run `stty` on the terminal wasmrun reads from; its output on success
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Turns the bytes read from the terminal into keys, skipping the escape
+/// sequences arrow and function keys send
+struct KeyDecoder {
+    single_key: bool,
+    line: String,
+    escape: Escape,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// After ESC
+    Started,
+    /// Inside `ESC [` or `ESC O`, until the final byte
+    Sequence,
+}
+
+impl KeyDecoder {
+    fn new(single_key: bool) -> Self {
+        Self {
+            single_key,
+            line: String::new(),
+            escape: Escape::None,
+        }
+    }
+
+    fn feed(&mut self, byte: u8) -> Option<char> {
+        match self.escape {
+            Escape::Started => {
+                self.escape = if matches!(byte, b'[' | b'O') {
+                    Escape::Sequence
+                } else {
+                    Escape::None
+                };
+                return None;
+            }
+            Escape::Sequence => {
+                if (0x40..=0x7e).contains(&byte) {
+                    self.escape = Escape::None;
+                }
+                return None;
+            }
+            Escape::None if byte == ESCAPE => {
+                self.escape = Escape::Started;
+                return None;
+            }
+            Escape::None => {}
+        }
+
+        if self.single_key {
+            return command_key(&(byte as char).to_string());
+        }
+        if byte == b'\n' {
+            return command_key(&std::mem::take(&mut self.line));
+        }
+        self.line.push(byte as char);
+        None
+    }
+}
+
+/// The key a typed line stands for, lowercased; `None` unless it's one
+/// letter or `?`
+fn command_key(line: &str) -> Option<char> {
+    let mut chars = line.trim().chars();
+    let key = chars.next()?;
+    (chars.next().is_none() && (key.is_ascii_alphabetic() || key == '?'))
+        .then(|| key.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(single_key: bool, input: &[u8]) -> String {
+        let mut decoder = KeyDecoder::new(single_key);
+        input.iter().filter_map(|&b| decoder.feed(b)).collect()
+    }
+
+    #[test]
+    fn test_single_keys_skip_escape_sequences() {
+        assert_eq!(keys(true, b"rO?"), "ro?");
+        // Up arrow, Ctrl+Up, F1, then Alt+x
+        assert_eq!(keys(true, b"\x1b[Aq\x1b[1;5Ae\x1bOPo\x1bxw"), "qeow");
+        assert_eq!(keys(true, b" \r\n1"), "");
+    }
+
+    #[test]
+    fn test_dropping_the_listener_restores_the_terminal() {
+        // Not settings stty accepts, so a terminal running the tests is
+        // left alone
+        *SAVED_TERMINAL.lock().unwrap() = Some("--wasmrun-test".to_string());
+        drop(KeyListener(()));
+        assert!(SAVED_TERMINAL.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_line_mode_takes_one_letter_per_line() {
+        assert_eq!(keys(false, b"p\n  R \npause\n\n?\n"), "pr?");
+        assert_eq!(keys(false, b"q"), "");
+    }
+}
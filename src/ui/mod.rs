@@ -0,0 +1,57 @@
+pub mod keys;
+pub mod progress;
+
+/// `println!` that drops colour codes under `--no-color`
+macro_rules! say {
+    () => {
+        println!()
+    };
+    ($($arg:tt)*) => {
+        println!("{}", progress::paint(format!($($arg)*)))
+    };
+}
+
+/// Print a success message
+pub fn print_success(title: &str, message: &str) {
+    say!("\n\x1b[1;34m╭\x1b[0m");
+    say!("  ✅ \x1b[1;36m{title}\x1b[0m");
+    say!();
+    say!("  ✅ \x1b[1;32m{message}\x1b[0m");
+    say!("\x1b[1;34m╰\x1b[0m");
+}
+
+/// Print an info message, unless `--quiet`
+pub fn print_info(message: &str) {
+    if progress::is_quiet() {
+        return;
+    }
+    say!("\n\x1b[1;34m╭\x1b[0m");
+    say!("  ℹ️  \x1b[1;34m{message}\x1b[0m");
+    say!("\x1b[1;34m╰\x1b[0m");
+}
+
+/// Print a status message, unless `--quiet`
+pub fn print_status(message: &str) {
+    if progress::is_quiet() {
+        return;
+    }
+    say!("\n⏳ {message}");
+}
+
+/// Print init command information
+pub fn print_init_info(project_name: &str, template: &str, target_dir: &str) {
+    say!("\n\x1b[1;34m╭\x1b[0m");
+    say!("  🚀 \x1b[1;36mInitializing New Wasmrun Project\x1b[0m\n");
+    say!("  📦 \x1b[1;34mProject Name:\x1b[0m \x1b[1;33m{project_name}\x1b[0m");
+    say!("  🎯 \x1b[1;34mTemplate:\x1b[0m \x1b[1;33m{template}\x1b[0m");
+    say!("  📂 \x1b[1;34mDirectory:\x1b[0m \x1b[1;33m{target_dir}\x1b[0m");
+    say!("\x1b[1;34m╰\x1b[0m\n");
+}
+
+/// Print clean command information
+pub fn print_clean_info(project_path: &str) {
+    say!("\n\x1b[1;34m╭\x1b[0m");
+    say!("  🧹 \x1b[1;36mCleaning Project\x1b[0m\n");
+    say!("  📂 \x1b[1;34mProject Path:\x1b[0m \x1b[1;33m{project_path}\x1b[0m");
+    say!("\x1b[1;34m╰\x1b[0m\n");
+}
@@ -0,0 +1,319 @@
+//! Spinners and progress bars for the slow steps: compiling, installing
+//! plugins, downloading runtimes and modules, and pushing to registries.
+//!
+//! Bars are only animated on an interactive terminal. In CI, or when
+//! stderr is redirected, each task prints one line as it starts and one as
+//! it ends so logs stay readable; with `--quiet` nothing is printed.
+
+use indicatif::{HumanBytes, HumanDuration, ProgressBar, ProgressStyle};
+use std::io::{self, IsTerminal, Read};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Variables set by common CI services
+const CI_VARS: &[&str] = &[
+    "CI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TF_BUILD",
+    "JENKINS_URL",
+];
+const TICK: Duration = Duration::from_millis(100);
+#[cfg_attr(
+    not(any(feature = "os-mode", feature = "interpreter", feature = "deploy")),
+    allow(dead_code)
+)]
+const READ_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Display {
+    /// Animated spinners and bars on stderr
+    Animated,
+    /// A start and an end line per task
+    Plain,
+    /// No progress output at all
+    Quiet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Output {
+    display: Display,
+    color: bool,
+}
+
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+impl Output {
+    fn detect(
+        quiet: bool,
+        no_color: bool,
+        plain: bool,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let ci = plain
+            || CI_VARS
+                .iter()
+                .filter_map(|name| env(name))
+                .any(|value| !matches!(value.as_str(), "" | "0" | "false"));
+        let display = if quiet {
+            Display::Quiet
+        } else if ci || !io::stderr().is_terminal() {
+            Display::Plain
+        } else {
+            Display::Animated
+        };
+        let no_color = no_color || plain || env("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self {
+            display,
+            color: !no_color,
+        }
+    }
+}
+
+/// Choose how progress and colour are shown, from `--quiet`, `--no-color`,
+/// `NO_COLOR` and CI detection. `plain` (`--ci`) forces plain lines without
+/// colour. Call once at startup.
+pub fn init(quiet: bool, no_color: bool, plain: bool) {
+    let _ = OUTPUT.set(Output::detect(quiet, no_color, plain, |name| {
+        std::env::var(name).ok()
+    }));
+}
+
+fn output() -> Output {
+    *OUTPUT.get_or_init(|| Output::detect(false, false, false, |name| std::env::var(name).ok()))
+}
+
+pub fn display() -> Display {
+    output().display
+}
+
+pub fn is_quiet() -> bool {
+    display() == Display::Quiet
+}
+
+/// `text` without its ANSI colour codes when colour is turned off
+pub fn paint(text: String) -> String {
+    if output().color {
+        text
+    } else {
+        strip_ansi(&text)
+    }
+}
+
+/// `text` without its ANSI escape sequences
+pub fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end at their first letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// A running task. Dropping it without `finish` or `fail` just clears it.
+pub struct Task {
+    bar: Option<ProgressBar>,
+    message: String,
+    started: Instant,
+}
+
+/// A spinner for work with no measurable progress
+pub fn spinner(message: impl Into<String>) -> Task {
+    Task::start(message.into(), None, false)
+}
+
+/// A byte-count bar; without `total` it spins and counts bytes
+#[cfg_attr(
+    not(any(feature = "os-mode", feature = "interpreter", feature = "deploy")),
+    allow(dead_code)
+)]
+pub fn bytes(message: impl Into<String>, total: Option<u64>) -> Task {
+    Task::start(message.into(), total, true)
+}
+
+impl Task {
+    fn start(message: String, total: Option<u64>, counts_bytes: bool) -> Self {
+        let bar = match display() {
+            Display::Animated => Some(animated_bar(&message, total, counts_bytes)),
+            Display::Plain => {
+                eprintln!("⏳ {message}...");
+                None
+            }
+            Display::Quiet => None,
+        };
+        Self {
+            bar,
+            message,
+            started: Instant::now(),
+        }
+    }
+
+    #[cfg_attr(
+        not(any(feature = "os-mode", feature = "interpreter", feature = "deploy")),
+        allow(dead_code)
+    )]
+    pub fn inc(&self, delta: u64) {
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+        }
+    }
+
+    /// Replace the bar with a success line and the time taken
+    pub fn finish(self, message: impl AsRef<str>) {
+        let elapsed = HumanDuration(self.started.elapsed());
+        self.end(&format!("✅ {} ({elapsed})", message.as_ref()));
+    }
+
+    /// Replace the bar with a failure line; the caller reports the error
+    pub fn fail(self) {
+        let line = format!("❌ {} failed", self.message);
+        self.end(&line);
+    }
+
+    fn end(mut self, line: &str) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+        }
+        if display() != Display::Quiet {
+            eprintln!("{}", paint(line.to_string()));
+        }
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+fn animated_bar(message: &str, total: Option<u64>, counts_bytes: bool) -> ProgressBar {
+    let color = output().color;
+    let template = match (total, counts_bytes, color) {
+        (Some(_), _, true) => {
+            "{spinner:.cyan} {msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})"
+        }
+        (Some(_), _, false) => {
+            "{spinner} {msg} [{bar:30}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})"
+        }
+        (None, true, true) => "{spinner:.cyan} {msg} {bytes} ({bytes_per_sec})",
+        (None, true, false) => "{spinner} {msg} {bytes} ({bytes_per_sec})",
+        (None, false, true) => "{spinner:.cyan} {msg} ({elapsed})",
+        (None, false, false) => "{spinner} {msg} ({elapsed})",
+    };
+    let bar = match total {
+        Some(total) => ProgressBar::new(total),
+        None => ProgressBar::new_spinner(),
+    };
+    if let Ok(style) = ProgressStyle::with_template(template) {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar.set_message(message.to_string());
+    bar.enable_steady_tick(TICK);
+    bar
+}
+
+/// Read a download to the end behind a byte bar, failing once it passes
+/// `limit` bytes
+#[cfg_attr(
+    not(any(feature = "os-mode", feature = "interpreter", feature = "deploy")),
+    allow(dead_code)
+)]
+pub fn download(
+    mut reader: impl Read,
+    total: Option<u64>,
+    limit: u64,
+    message: impl Into<String>,
+) -> io::Result<Vec<u8>> {
+    let message = message.into();
+    let task = bytes(message.clone(), total);
+    let mut data = Vec::with_capacity(total.unwrap_or(0).min(limit) as usize);
+    let mut chunk = vec![0; READ_CHUNK];
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                task.fail();
+                return Err(e);
+            }
+        };
+        if data.len() as u64 + read as u64 > limit {
+            task.fail();
+            return Err(io::Error::other(format!(
+                "response is larger than {}",
+                HumanBytes(limit)
+            )));
+        }
+        data.extend_from_slice(&chunk[..read]);
+        task.inc(read as u64);
+    }
+    task.finish(format!("{message} ({})", HumanBytes(data.len() as u64)));
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_output() {
+        let none = |_: &str| None;
+        assert_eq!(
+            Output::detect(true, false, false, none),
+            Output {
+                display: Display::Quiet,
+                color: true
+            }
+        );
+
+        let ci = |name: &str| (name == "GITHUB_ACTIONS").then(|| "true".to_string());
+        assert_eq!(
+            Output::detect(false, false, false, ci).display,
+            Display::Plain
+        );
+
+        let no_color = |name: &str| (name == "NO_COLOR").then(|| "1".to_string());
+        assert!(!Output::detect(false, false, false, no_color).color);
+        assert!(!Output::detect(false, true, false, none).color);
+
+        // --ci
+        let plain = Output::detect(false, false, true, none);
+        assert_eq!(plain.display, Display::Plain);
+        assert!(!plain.color);
+        assert_eq!(
+            Output::detect(true, false, true, none).display,
+            Display::Quiet
+        );
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("\x1b[1;34m╭\x1b[0m  ✅ \x1b[1;32mdone\x1b[0m"),
+            "╭  ✅ done"
+        );
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn test_download_enforces_limit() {
+        let data = vec![7u8; 200_000];
+        let read = download(&data[..], Some(200_000), 1 << 20, "test").unwrap();
+        assert_eq!(read, data);
+        assert!(download(&data[..], None, 100_000, "test").is_err());
+    }
+}
@@ -0,0 +1,621 @@
+//! Drive a headless Chrome/Chromium over the DevTools protocol
+//!
+//! Only the handful of CDP methods wasmrun needs are wrapped here: open a
+//! page, wait for it to become ready, take a screenshot, and record a
+//! screencast. The browser is found on the system rather than downloaded.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(20);
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const READY_FLAG: &str = "__wasmrun_snapshot_ready";
+
+/// Executable names tried on PATH, in order
+const BROWSER_CANDIDATES: &[&str] = &[
+    "google-chrome",
+    "google-chrome-stable",
+    "chromium",
+    "chromium-browser",
+    "chrome",
+    "microsoft-edge",
+];
+
+/// What a page has to do before it counts as ready
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadyCondition {
+    /// `document.readyState` is `complete`
+    Load,
+    /// An element matching the CSS selector exists
+    Selector(String),
+    /// The page dispatched an event with this name on `window` or `document`
+    Event(String),
+}
+
+impl ReadyCondition {
+    /// Expression that evaluates to `true` once the condition holds
+    pub fn expression(&self) -> String {
+        match self {
+            ReadyCondition::Load => "document.readyState === 'complete'".to_string(),
+            ReadyCondition::Selector(selector) => format!(
+                "document.readyState !== 'loading' && document.querySelector({}) !== null",
+                js_string(selector)
+            ),
+            ReadyCondition::Event(_) => format!("window.{READY_FLAG} === true"),
+        }
+    }
+
+    /// Script installed before any page script runs, if the condition needs one
+    pub fn init_script(&self) -> Option<String> {
+        match self {
+            ReadyCondition::Event(name) => Some(format!(
+                "(() => {{ const mark = () => {{ window.{READY_FLAG} = true; }}; \
+                 window.addEventListener({name}, mark); \
+                 document.addEventListener({name}, mark); }})();",
+                name = js_string(name)
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ReadyCondition::Load => "page load".to_string(),
+            ReadyCondition::Selector(selector) => format!("selector `{selector}`"),
+            ReadyCondition::Event(name) => format!("event `{name}`"),
+        }
+    }
+}
+
+/// A PNG frame from a screencast and when it was painted, in seconds
+pub struct ScreencastFrame {
+    pub timestamp: f64,
+    pub png: Vec<u8>,
+}
+
+/// A `console.*` call or uncaught exception reported by the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleMessage {
+    /// `log`, `info`, `warning`, `error`, `debug`, ...; exceptions are `error`
+    pub level: String,
+    pub text: String,
+}
+
+impl ConsoleMessage {
+    fn from_event(event: &Value) -> Option<Self> {
+        let params = &event["params"];
+        match event["method"].as_str()? {
+            "Runtime.consoleAPICalled" => {
+                let text = params["args"]
+                    .as_array()
+                    .map(|args| args.iter().map(remote_object_text).collect::<Vec<_>>())
+                    .unwrap_or_default()
+                    .join(" ");
+                Some(Self {
+                    level: params["type"].as_str().unwrap_or("log").to_string(),
+                    text,
+                })
+            }
+            "Runtime.exceptionThrown" => {
+                let details = &params["exceptionDetails"];
+                let text = details["exception"]["description"]
+                    .as_str()
+                    .or_else(|| details["text"].as_str())
+                    .unwrap_or("Uncaught exception");
+                Some(Self {
+                    level: "error".to_string(),
+                    text: text.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self.level.as_str(), "error" | "assert")
+    }
+}
+
+/// How a console argument reads in DevTools: strings bare, other
+/// primitives as JSON, objects by their description
+fn remote_object_text(arg: &Value) -> String {
+    match &arg["value"] {
+        Value::String(text) => text.clone(),
+        Value::Null => arg["description"]
+            .as_str()
+            .or_else(|| arg["type"].as_str())
+            .unwrap_or("")
+            .to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// A headless browser with a single page attached
+pub struct HeadlessBrowser {
+    process: Child,
+    socket: WebSocket<TcpStream>,
+    session_id: Option<String>,
+    next_id: u64,
+    events: VecDeque<Value>,
+    _profile: tempfile::TempDir,
+}
+
+impl HeadlessBrowser {
+    /// Locate a Chrome-compatible browser: `WASMRUN_CHROME`, then `CHROME_PATH`,
+    /// then well-known executable names on PATH and install locations.
+    pub fn find_executable() -> Option<PathBuf> {
+        for var in ["WASMRUN_CHROME", "CHROME_PATH"] {
+            if let Some(path) = env::var_os(var).map(PathBuf::from) {
+                if path.is_file() {
+                    return Some(path);
+                }
+            }
+        }
+
+        let path_dirs: Vec<PathBuf> = env::var_os("PATH")
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        for name in BROWSER_CANDIDATES {
+            for dir in &path_dirs {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+                let exe = dir.join(format!("{name}.exe"));
+                if exe.is_file() {
+                    return Some(exe);
+                }
+            }
+        }
+
+        [
+            "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+            "/Applications/Chromium.app/Contents/MacOS/Chromium",
+            "C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe",
+            "C:\\Program Files (x86)\\Google\\Chrome\\Application\\chrome.exe",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+    }
+
+    /// Start the browser and open a blank page with the given viewport
+    pub fn launch(executable: &Path, width: u32, height: u32) -> Result<Self, String> {
+        let profile =
+            tempfile::tempdir().map_err(|e| format!("Failed to create browser profile: {e}"))?;
+
+        let mut process = Command::new(executable)
+            .arg("--headless=new")
+            .arg("--remote-debugging-port=0")
+            .arg(format!("--user-data-dir={}", profile.path().display()))
+            .arg(format!("--window-size={width},{height}"))
+            .args([
+                "--no-first-run",
+                "--no-default-browser-check",
+                "--disable-extensions",
+                "--disable-background-networking",
+                "--hide-scrollbars",
+                "--mute-audio",
+                "about:blank",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {e}", executable.display()))?;
+
+        let ws_url = match wait_for_devtools_url(&mut process) {
+            Ok(url) => url,
+            Err(e) => {
+                let _ = process.kill();
+                let _ = process.wait();
+                return Err(e);
+            }
+        };
+
+        let socket = match connect(&ws_url) {
+            Ok(socket) => socket,
+            Err(e) => {
+                let _ = process.kill();
+                let _ = process.wait();
+                return Err(e);
+            }
+        };
+
+        let mut browser = Self {
+            process,
+            socket,
+            session_id: None,
+            next_id: 0,
+            events: VecDeque::new(),
+            _profile: profile,
+        };
+        browser.open_page(width, height)?;
+        Ok(browser)
+    }
+
+    fn open_page(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let target = self.call("Target.createTarget", json!({ "url": "about:blank" }))?;
+        let target_id = target["targetId"]
+            .as_str()
+            .ok_or("Browser did not return a target id")?
+            .to_string();
+        let attached = self.call(
+            "Target.attachToTarget",
+            json!({ "targetId": target_id, "flatten": true }),
+        )?;
+        self.session_id = Some(
+            attached["sessionId"]
+                .as_str()
+                .ok_or("Browser did not return a session id")?
+                .to_string(),
+        );
+
+        self.call("Page.enable", json!({}))?;
+        self.call(
+            "Emulation.setDeviceMetricsOverride",
+            json!({
+                "width": width,
+                "height": height,
+                "deviceScaleFactor": 1,
+                "mobile": false,
+            }),
+        )?;
+        Ok(())
+    }
+
+    /// Navigate to `url` and block until `ready` holds or `timeout` elapses
+    pub fn load(
+        &mut self,
+        url: &str,
+        ready: &ReadyCondition,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        if let Some(source) = ready.init_script() {
+            self.call(
+                "Page.addScriptToEvaluateOnNewDocument",
+                json!({ "source": source }),
+            )?;
+        }
+
+        self.navigate(url)?;
+
+        let expression = ready.expression();
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.evaluate(&expression)?.as_bool() == Some(true) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Timed out after {}s waiting for {}",
+                    timeout.as_secs(),
+                    ready.describe()
+                ));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Start loading `url` without waiting for it
+    pub fn navigate(&mut self, url: &str) -> Result<(), String> {
+        let navigation = self.call("Page.navigate", json!({ "url": url }))?;
+        match navigation["errorText"].as_str() {
+            Some(error) => Err(format!("Failed to load {url}: {error}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Report `console.*` calls and uncaught exceptions from now on; call
+    /// before [`navigate`](Self::navigate) to see everything the page logs.
+    pub fn capture_console(&mut self) -> Result<(), String> {
+        self.call("Runtime.enable", json!({}))?;
+        Ok(())
+    }
+
+    /// Console messages logged so far, waiting up to `wait` for new ones
+    pub fn console_messages(&mut self, wait: Duration) -> Result<Vec<ConsoleMessage>, String> {
+        let deadline = Instant::now() + wait;
+        while let Some(message) = self.read_message(deadline)? {
+            if message.get("method").is_some() {
+                self.events.push_back(message);
+            }
+        }
+
+        // Other Runtime events are of no use to callers, so drop them here
+        // instead of letting the queue grow for the whole session
+        Ok(self
+            .events
+            .drain(..)
+            .filter_map(|event| ConsoleMessage::from_event(&event))
+            .collect())
+    }
+
+    /// Evaluate a JavaScript expression in the page and return its value
+    pub fn evaluate(&mut self, expression: &str) -> Result<Value, String> {
+        let result = self.call(
+            "Runtime.evaluate",
+            json!({ "expression": expression, "returnByValue": true, "awaitPromise": true }),
+        )?;
+        if let Some(exception) = result.get("exceptionDetails") {
+            let text = exception["exception"]["description"]
+                .as_str()
+                .or_else(|| exception["text"].as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("Script error: {text}"));
+        }
+        Ok(result["result"]["value"].clone())
+    }
+
+    /// Capture the viewport, or the whole scrollable page with `full_page`.
+    /// `format` is `png` or `jpeg`.
+    pub fn screenshot(&mut self, format: &str, full_page: bool) -> Result<Vec<u8>, String> {
+        let mut params = json!({ "format": format, "captureBeyondViewport": full_page });
+        if full_page {
+            let metrics = self.call("Page.getLayoutMetrics", json!({}))?;
+            let size = &metrics["cssContentSize"];
+            params["clip"] = json!({
+                "x": 0,
+                "y": 0,
+                "width": size["width"].as_f64().unwrap_or(0.0).ceil(),
+                "height": size["height"].as_f64().unwrap_or(0.0).ceil(),
+                "scale": 1,
+            });
+        }
+
+        let shot = self.call("Page.captureScreenshot", params)?;
+        decode_data(&shot)
+    }
+
+    /// Record the page for `duration`. Chrome only sends a frame when
+    /// something repaints, so a still page yields few frames.
+    pub fn record(&mut self, duration: Duration) -> Result<Vec<ScreencastFrame>, String> {
+        self.call(
+            "Page.startScreencast",
+            json!({ "format": "png", "everyNthFrame": 1 }),
+        )?;
+
+        let mut frames = Vec::new();
+        let deadline = Instant::now() + duration;
+        while let Some(event) = self.next_event("Page.screencastFrame", deadline)? {
+            let params = &event["params"];
+            self.call(
+                "Page.screencastFrameAck",
+                json!({ "sessionId": params["sessionId"] }),
+            )?;
+            frames.push(ScreencastFrame {
+                timestamp: params["metadata"]["timestamp"].as_f64().unwrap_or(0.0),
+                png: decode_data(params)?,
+            });
+        }
+
+        self.call("Page.stopScreencast", json!({}))?;
+        Ok(frames)
+    }
+
+    /// Send a command to the attached page and wait for its result
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let mut message = json!({ "id": id, "method": method, "params": params });
+        // Target.* methods address the browser itself, everything else the page
+        if !method.starts_with("Target.") {
+            if let Some(session_id) = &self.session_id {
+                message["sessionId"] = json!(session_id);
+            }
+        }
+
+        self.socket
+            .send(Message::text(message.to_string()))
+            .map_err(|e| format!("DevTools connection failed: {e}"))?;
+
+        let deadline = Instant::now() + CALL_TIMEOUT;
+        loop {
+            let Some(reply) = self.read_message(deadline)? else {
+                return Err(format!("Browser did not answer {method}"));
+            };
+            if reply["id"].as_u64() == Some(id) {
+                if let Some(error) = reply.get("error") {
+                    let text = error["message"].as_str().unwrap_or("unknown error");
+                    return Err(format!("{method} failed: {text}"));
+                }
+                return Ok(reply["result"].clone());
+            }
+            if reply.get("method").is_some() {
+                self.events.push_back(reply);
+            }
+        }
+    }
+
+    /// Next queued or incoming event named `method`, or `None` at `deadline`
+    fn next_event(&mut self, method: &str, deadline: Instant) -> Result<Option<Value>, String> {
+        if let Some(pos) = self.events.iter().position(|e| e["method"] == method) {
+            return Ok(self.events.remove(pos));
+        }
+        while let Some(message) = self.read_message(deadline)? {
+            if message["method"] == method {
+                return Ok(Some(message));
+            }
+            if message.get("method").is_some() {
+                self.events.push_back(message);
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_message(&mut self, deadline: Instant) -> Result<Option<Value>, String> {
+        while Instant::now() < deadline {
+            match self.socket.read() {
+                Ok(Message::Text(text)) => {
+                    return serde_json::from_str(text.as_str())
+                        .map(Some)
+                        .map_err(|e| format!("Malformed DevTools message: {e}"));
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) => {}
+                Err(e) => return Err(format!("DevTools connection failed: {e}")),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for HeadlessBrowser {
+    fn drop(&mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Read the browser's stderr until it announces its DevTools endpoint
+fn wait_for_devtools_url(process: &mut Child) -> Result<String, String> {
+    let stderr = process
+        .stderr
+        .take()
+        .ok_or("Browser stderr is not captured")?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut sent = false;
+        // Keep draining after the URL so the browser never blocks on a full pipe
+        for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+            if !sent {
+                if let Some(url) = parse_devtools_url(&line) {
+                    let _ = tx.send(Ok(url));
+                    sent = true;
+                }
+            }
+        }
+        if !sent {
+            let _ = tx.send(Err(
+                "Browser exited before opening a DevTools endpoint".to_string()
+            ));
+        }
+    });
+
+    rx.recv_timeout(LAUNCH_TIMEOUT).unwrap_or_else(|_| {
+        Err(format!(
+            "Browser did not start within {}s",
+            LAUNCH_TIMEOUT.as_secs()
+        ))
+    })
+}
+
+fn parse_devtools_url(line: &str) -> Option<String> {
+    let url = line.trim().strip_prefix("DevTools listening on ")?;
+    url.starts_with("ws://").then(|| url.to_string())
+}
+
+fn connect(ws_url: &str) -> Result<WebSocket<TcpStream>, String> {
+    let address = ws_url
+        .strip_prefix("ws://")
+        .and_then(|rest| rest.split('/').next())
+        .ok_or_else(|| format!("Unexpected DevTools URL: {ws_url}"))?;
+    let stream = TcpStream::connect(address)
+        .map_err(|e| format!("Failed to connect to DevTools at {address}: {e}"))?;
+    let (socket, _) = tungstenite::client(ws_url, stream)
+        .map_err(|e| format!("DevTools handshake failed: {e}"))?;
+    socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|e| format!("Failed to configure DevTools connection: {e}"))?;
+    Ok(socket)
+}
+
+fn decode_data(result: &Value) -> Result<Vec<u8>, String> {
+    let data = result["data"]
+        .as_str()
+        .ok_or("Browser returned no image data")?;
+    BASE64
+        .decode(data)
+        .map_err(|e| format!("Browser returned invalid image data: {e}"))
+}
+
+fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devtools_url() {
+        assert_eq!(
+            parse_devtools_url("DevTools listening on ws://127.0.0.1:40123/devtools/browser/abc"),
+            Some("ws://127.0.0.1:40123/devtools/browser/abc".to_string())
+        );
+        assert_eq!(parse_devtools_url("[1016/120000.000:WARNING] noise"), None);
+    }
+
+    #[test]
+    fn test_console_message_from_events() {
+        let logged = json!({
+            "method": "Runtime.consoleAPICalled",
+            "params": {
+                "type": "log",
+                "args": [
+                    { "type": "string", "value": "passed:" },
+                    { "type": "number", "value": 3 },
+                    { "type": "object", "description": "Array(2)" },
+                ],
+            },
+        });
+        assert_eq!(
+            ConsoleMessage::from_event(&logged),
+            Some(ConsoleMessage {
+                level: "log".to_string(),
+                text: "passed: 3 Array(2)".to_string(),
+            })
+        );
+
+        let thrown = json!({
+            "method": "Runtime.exceptionThrown",
+            "params": { "exceptionDetails": {
+                "text": "Uncaught",
+                "exception": { "description": "RuntimeError: unreachable" },
+            }},
+        });
+        let message = ConsoleMessage::from_event(&thrown).unwrap();
+        assert!(message.is_error());
+        assert_eq!(message.text, "RuntimeError: unreachable");
+
+        assert_eq!(
+            ConsoleMessage::from_event(&json!({ "method": "Page.loadEventFired" })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ready_conditions_escape_user_input() {
+        let selector = ReadyCondition::Selector("div[data-x=\"a'b\"]".to_string());
+        assert!(selector
+            .expression()
+            .contains(r#"document.querySelector("div[data-x=\"a'b\"]")"#));
+        assert!(selector.init_script().is_none());
+
+        let event = ReadyCondition::Event("app-ready\");alert(1);//".to_string());
+        let script = event.init_script().unwrap();
+        assert!(script.contains(r#"addEventListener("app-ready\");alert(1);//", mark)"#));
+        assert_eq!(
+            event.expression(),
+            "window.__wasmrun_snapshot_ready === true"
+        );
+    }
+}
@@ -0,0 +1,569 @@
+//! Static call graph built from a module's code section.
+//!
+//! Function bodies are walked instruction by instruction so that `call`,
+//! `return_call` and `ref.func` immediates are found without being confused
+//! by constants or memory offsets that happen to contain the same bytes.
+
+use crate::runtime::core::module::{ExportKind, ImportKind, Module, ValueType};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A function defined in the module (imports have no body and no node)
+#[derive(Debug, Clone)]
+pub struct FunctionNode {
+    pub index: u32,
+    pub name: Option<String>,
+    /// Encoded body size in bytes, locals included
+    pub body_size: usize,
+    /// Functions called directly or referenced with `ref.func`
+    pub callees: BTreeSet<u32>,
+    pub calls_indirect: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallGraph {
+    pub import_count: u32,
+    /// `module::name` of each imported function, by function index
+    pub imported: Vec<String>,
+    pub functions: Vec<FunctionNode>,
+    /// Exported functions by export name
+    pub exports: BTreeMap<String, u32>,
+    pub start: Option<u32>,
+    /// Functions placed in tables or referenced from global initializers;
+    /// any of them may be reached through `call_indirect`
+    pub table_functions: BTreeSet<u32>,
+}
+
+/// Function references found in a single body or constant expression
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FunctionRefs {
+    pub calls: BTreeSet<u32>,
+    pub ref_funcs: BTreeSet<u32>,
+    pub calls_indirect: bool,
+}
+
+impl CallGraph {
+    /// Build the graph for `module`, labelling functions from `names`
+    /// (typically the name section).
+    pub fn build(module: &Module, names: &HashMap<u32, String>) -> Result<Self, String> {
+        let imported: Vec<String> = module
+            .imports
+            .iter()
+            .filter(|i| matches!(i.kind, ImportKind::Function(_)))
+            .map(|i| format!("{}::{}", i.module, i.name))
+            .collect();
+        let import_count = imported.len() as u32;
+
+        // Bodies are walked independently, in parallel
+        let functions = module
+            .functions
+            .par_iter()
+            .enumerate()
+            .map(|(i, func)| {
+                let index = import_count + i as u32;
+                let refs =
+                    scan_function_refs(&func.code).map_err(|e| format!("Function {index}: {e}"))?;
+                let locals_size = leb128_len(func.locals.len() as u32)
+                    + func
+                        .locals
+                        .iter()
+                        .map(|(count, _)| leb128_len(*count) + 1)
+                        .sum::<usize>();
+
+                Ok(FunctionNode {
+                    index,
+                    name: names.get(&index).cloned(),
+                    body_size: locals_size + func.code.len(),
+                    callees: refs.calls.union(&refs.ref_funcs).copied().collect(),
+                    calls_indirect: refs.calls_indirect,
+                })
+            })
+            .collect::<Vec<Result<FunctionNode, String>>>()
+            .into_iter()
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut table_functions: BTreeSet<u32> = module
+            .elements
+            .iter()
+            .flat_map(|e| e.function_indices.iter().copied())
+            .collect();
+        for global in &module.globals {
+            if let Ok(refs) = scan_function_refs(&global.init_expr) {
+                table_functions.extend(refs.ref_funcs);
+            }
+        }
+
+        let exports = module
+            .exports
+            .values()
+            .filter(|e| matches!(e.kind, ExportKind::Function))
+            .map(|e| (e.name.clone(), e.index))
+            .collect();
+
+        Ok(CallGraph {
+            import_count,
+            imported,
+            functions,
+            exports,
+            start: module.start,
+            table_functions,
+        })
+    }
+
+    /// The node for a defined function, or `None` for imports
+    pub fn function(&self, index: u32) -> Option<&FunctionNode> {
+        index
+            .checked_sub(self.import_count)
+            .and_then(|i| self.functions.get(i as usize))
+    }
+
+    /// Best available label for a function index
+    pub fn display_name(&self, index: u32) -> String {
+        if let Some(import) = self.imported.get(index as usize) {
+            return import.clone();
+        }
+        self.function(index)
+            .and_then(|f| f.name.clone())
+            .unwrap_or_else(|| format!("func[{index}]"))
+    }
+
+    /// Roots that are live regardless of which exports are kept: the start
+    /// function and everything reachable through a table.
+    pub fn implicit_roots(&self) -> Vec<u32> {
+        self.start
+            .iter()
+            .chain(self.table_functions.iter())
+            .copied()
+            .collect()
+    }
+
+    /// Every function index reachable from `roots`, roots included
+    pub fn reachable_from(&self, roots: impl IntoIterator<Item = u32>) -> BTreeSet<u32> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<u32> = roots.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            if !seen.insert(index) {
+                continue;
+            }
+            if let Some(node) = self.function(index) {
+                stack.extend(node.callees.iter().filter(|c| !seen.contains(c)));
+            }
+        }
+        seen
+    }
+}
+
+impl CallGraph {
+    /// Export names per function index, for labelling
+    fn export_names(&self) -> BTreeMap<u32, Vec<&str>> {
+        let mut by_index: BTreeMap<u32, Vec<&str>> = BTreeMap::new();
+        for (name, index) in &self.exports {
+            by_index.entry(*index).or_default().push(name);
+        }
+        by_index
+    }
+
+    /// Imported functions that something actually calls
+    fn called_imports(&self) -> BTreeSet<u32> {
+        self.functions
+            .iter()
+            .flat_map(|f| f.callees.iter().copied())
+            .chain(self.exports.values().copied())
+            .filter(|i| *i < self.import_count)
+            .collect()
+    }
+
+    /// Graphviz rendering: exports are highlighted, imports are drawn as
+    /// ellipses, and each defined function is labelled with its body size.
+    pub fn to_dot(&self) -> String {
+        let exports = self.export_names();
+        let mut out = String::from("digraph wasm {\n");
+        out.push_str("  rankdir=LR;\n");
+        out.push_str("  node [shape=box, fontname=\"monospace\", fontsize=10];\n");
+
+        for index in self.called_imports() {
+            out.push_str(&format!(
+                "  f{index} [label=\"{}\", shape=ellipse, style=dashed];\n",
+                dot_escape(&self.display_name(index))
+            ));
+        }
+        for node in &self.functions {
+            let mut label = format!(
+                "{}\\n{} bytes",
+                dot_escape(&self.display_name(node.index)),
+                node.body_size
+            );
+            let mut style = String::new();
+            if let Some(names) = exports.get(&node.index) {
+                label.push_str(&format!("\\nexport: {}", dot_escape(&names.join(", "))));
+                style = ", style=filled, fillcolor=\"#cde8ff\"".to_string();
+            } else if self.start == Some(node.index) {
+                style = ", style=filled, fillcolor=\"#d4f4d4\"".to_string();
+            }
+            out.push_str(&format!("  f{} [label=\"{label}\"{style}];\n", node.index));
+        }
+        for node in &self.functions {
+            for callee in &node.callees {
+                out.push_str(&format!("  f{} -> f{callee};\n", node.index));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// JSON rendering with the same nodes and edges as [`CallGraph::to_dot`]
+    pub fn to_json(&self) -> serde_json::Value {
+        let exports = self.export_names();
+        let imports = self.called_imports().into_iter().map(|index| {
+            serde_json::json!({
+                "index": index,
+                "name": self.display_name(index),
+                "imported": true,
+            })
+        });
+        let functions = self.functions.iter().map(|node| {
+            serde_json::json!({
+                "index": node.index,
+                "name": self.display_name(node.index),
+                "size": node.body_size,
+                "exports": exports.get(&node.index).cloned().unwrap_or_default(),
+                "start": self.start == Some(node.index),
+                "calls_indirect": node.calls_indirect,
+                "imported": false,
+            })
+        });
+        let edges: Vec<_> = self
+            .functions
+            .iter()
+            .flat_map(|node| {
+                node.callees
+                    .iter()
+                    .map(move |callee| serde_json::json!({ "from": node.index, "to": callee }))
+            })
+            .collect();
+
+        serde_json::json!({
+            "functions": imports.chain(functions).collect::<Vec<_>>(),
+            "edges": edges,
+        })
+    }
+}
+
+/// Escape a name for use inside a quoted DOT string
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Walk one function body (or constant expression) and collect the
+/// functions it references.
+pub fn scan_function_refs(code: &[u8]) -> Result<FunctionRefs, String> {
+    let mut refs = FunctionRefs::default();
+    let mut r = Reader {
+        bytes: code,
+        pos: 0,
+    };
+
+    while r.pos < code.len() {
+        let opcode = r.u8()?;
+        match opcode {
+            // call, return_call
+            0x10 | 0x12 => {
+                refs.calls.insert(r.leb_u32()?);
+            }
+            // call_indirect, return_call_indirect: type index, table index
+            0x11 | 0x13 => {
+                r.leb_u32()?;
+                r.leb_u32()?;
+                refs.calls_indirect = true;
+            }
+            // call_ref, return_call_ref: type index
+            0x14 | 0x15 => {
+                r.leb_u32()?;
+                refs.calls_indirect = true;
+            }
+            // ref.func
+            0xD2 => {
+                refs.ref_funcs.insert(r.leb_u32()?);
+            }
+            _ => skip_immediates(opcode, &mut r)?,
+        }
+    }
+
+    Ok(refs)
+}
+
+fn skip_immediates(opcode: u8, r: &mut Reader) -> Result<(), String> {
+    match opcode {
+        0x00 | 0x01 | 0x05 | 0x0B | 0x0F | 0x19 | 0x1A | 0x1B | 0xD1 | 0xD3 | 0xD5 => {}
+        0x45..=0xC4 => {}
+        // block, loop, if, try
+        0x02..=0x04 | 0x06 => r.block_type()?,
+        // catch, throw, rethrow, br, br_if, delegate, br_on_null, br_on_non_null
+        0x07..=0x09 | 0x0C | 0x0D | 0x18 | 0xD4 | 0xD6 => r.skip_leb()?,
+        0x0E => {
+            let count = r.leb_u32()?;
+            for _ in 0..=count {
+                r.skip_leb()?;
+            }
+        }
+        // select t*
+        0x1C => {
+            let count = r.leb_u32()?;
+            r.skip(count as usize)?;
+        }
+        // local.*, global.*, table.get, table.set
+        0x20..=0x26 => r.skip_leb()?,
+        0x28..=0x3E => r.mem_arg()?,
+        // memory.size, memory.grow
+        0x3F | 0x40 => r.skip_leb()?,
+        0x41 | 0x42 => r.skip_leb()?,
+        0x43 => r.skip(4)?,
+        0x44 => r.skip(8)?,
+        // ref.null heap type
+        0xD0 => r.block_type()?,
+        0xFC => match r.leb_u32()? {
+            0..=7 => {}
+            // memory.init, memory.copy, table.init, table.copy
+            8 | 10 | 12 | 14 => {
+                r.skip_leb()?;
+                r.skip_leb()?;
+            }
+            9 | 11 | 13 | 15..=17 => r.skip_leb()?,
+            sub => return Err(format!("unsupported instruction 0xFC {sub}")),
+        },
+        0xFD => match r.leb_u32()? {
+            0..=11 | 92 | 93 => r.mem_arg()?,
+            // v128.const, i8x16.shuffle
+            12 | 13 => r.skip(16)?,
+            // extract_lane / replace_lane
+            21..=34 => r.skip(1)?,
+            // load*_lane / store*_lane
+            84..=91 => {
+                r.mem_arg()?;
+                r.skip(1)?;
+            }
+            _ => {}
+        },
+        0xFE => match r.leb_u32()? {
+            // atomic.fence
+            3 => r.skip(1)?,
+            _ => r.mem_arg()?,
+        },
+        other => return Err(format!("unsupported instruction 0x{other:02X}")),
+    }
+    Ok(())
+}
+
+/// Number of bytes `value` takes as unsigned LEB128
+pub fn leb128_len(mut value: u32) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or("unexpected end of function body")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), String> {
+        if self.pos + n > self.bytes.len() {
+            return Err("unexpected end of function body".to_string());
+        }
+        self.pos += n;
+        Ok(())
+    }
+
+    fn leb_u32(&mut self) -> Result<u32, String> {
+        let mut result = 0u32;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            result |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+            if shift >= 35 {
+                return Err("invalid LEB128 encoding".to_string());
+            }
+        }
+    }
+
+    /// Skip a LEB128 value of any width and signedness
+    fn skip_leb(&mut self) -> Result<(), String> {
+        for _ in 0..10 {
+            if self.u8()? & 0x80 == 0 {
+                return Ok(());
+            }
+        }
+        Err("invalid LEB128 encoding".to_string())
+    }
+
+    /// Empty/value block types are one byte; type indices are an s33
+    fn block_type(&mut self) -> Result<(), String> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or("unexpected end of function body")?;
+        if byte == 0x40 || ValueType::from_byte(byte).is_some() {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.skip_leb()
+        }
+    }
+
+    /// Alignment (with the multi-memory index flag) and offset
+    fn mem_arg(&mut self) -> Result<(), String> {
+        let align = self.leb_u32()?;
+        if align & 0x40 != 0 {
+            self.skip_leb()?;
+        }
+        self.skip_leb()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::core::module::{ElementSegment, ExportDesc, Function};
+
+    fn func(code: &[u8]) -> Function {
+        Function {
+            type_index: 0,
+            locals: vec![],
+            code: code.to_vec(),
+        }
+    }
+
+    fn export(module: &mut Module, name: &str, index: u32) {
+        module.exports.insert(
+            name.to_string(),
+            ExportDesc {
+                name: name.to_string(),
+                kind: ExportKind::Function,
+                index,
+            },
+        );
+    }
+
+    #[test]
+    fn test_scan_ignores_call_opcode_bytes_in_immediates() {
+        // i32.const 16 (0x10 as immediate), i64.const, call 2, drop, end
+        let code = [0x41, 0x10, 0x42, 0x90, 0x01, 0x10, 0x02, 0x1A, 0x0B];
+        let refs = scan_function_refs(&code).unwrap();
+        assert_eq!(refs.calls, BTreeSet::from([2]));
+        assert!(!refs.calls_indirect);
+    }
+
+    #[test]
+    fn test_scan_control_memory_and_ref_func() {
+        let code = [
+            0x02, 0x40, // block
+            0x20, 0x00, // local.get 0
+            0x28, 0x02, 0x80, 0x01, // i32.load align=2 offset=128
+            0x0E, 0x01, 0x00, 0x00, // br_table [0] 0
+            0x0B, // end
+            0xD2, 0x05, // ref.func 5
+            0x11, 0x00, 0x00, // call_indirect
+            0xFC, 0x0B, 0x00, // memory.fill
+            0x0B,
+        ];
+        let refs = scan_function_refs(&code).unwrap();
+        assert!(refs.calls.is_empty());
+        assert_eq!(refs.ref_funcs, BTreeSet::from([5]));
+        assert!(refs.calls_indirect);
+    }
+
+    #[test]
+    fn test_scan_rejects_unknown_opcode() {
+        assert!(scan_function_refs(&[0xFF, 0x0B]).is_err());
+    }
+
+    #[test]
+    fn test_reachability() {
+        let mut module = Module::new();
+        // 0: main -> 1 -> 2, 3: unused, 4: only via table
+        module.functions = vec![
+            func(&[0x10, 0x01, 0x0B]),
+            func(&[0x10, 0x02, 0x0B]),
+            func(&[0x0B]),
+            func(&[0x0B]),
+            func(&[0x0B]),
+        ];
+        module.elements.push(ElementSegment {
+            offset_expr: vec![0x41, 0x00, 0x0B],
+            function_indices: vec![4],
+        });
+        export(&mut module, "main", 0);
+        export(&mut module, "unused", 3);
+
+        let graph = CallGraph::build(&module, &HashMap::from([(1, "helper".to_string())])).unwrap();
+        assert_eq!(graph.display_name(1), "helper");
+        assert_eq!(graph.display_name(2), "func[2]");
+
+        let mut roots = graph.implicit_roots();
+        roots.push(graph.exports["main"]);
+        let live = graph.reachable_from(roots);
+        assert_eq!(live, BTreeSet::from([0, 1, 2, 4]));
+    }
+
+    #[test]
+    fn test_leb128_len() {
+        assert_eq!(leb128_len(0), 1);
+        assert_eq!(leb128_len(127), 1);
+        assert_eq!(leb128_len(128), 2);
+        assert_eq!(leb128_len(u32::MAX), 5);
+    }
+
+    fn sample_graph() -> CallGraph {
+        let mut module = Module::new();
+        module
+            .imports
+            .push(crate::runtime::core::module::ImportDesc {
+                module: "env".to_string(),
+                name: "log".to_string(),
+                kind: ImportKind::Function(0),
+            });
+        // 1: run -> 2, 2: calls import 0
+        module.functions = vec![func(&[0x10, 0x02, 0x0B]), func(&[0x10, 0x00, 0x0B])];
+        export(&mut module, "run", 1);
+        CallGraph::build(&module, &HashMap::from([(2, "fmt<\"x\">".to_string())])).unwrap()
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = sample_graph().to_dot();
+        assert!(dot.starts_with("digraph wasm {"));
+        assert!(dot.contains("f0 [label=\"env::log\", shape=ellipse"));
+        assert!(dot.contains("f1 [label=\"func[1]\\n4 bytes\\nexport: run\""));
+        assert!(dot.contains("f2 [label=\"fmt<\\\"x\\\">\\n4 bytes\"]"));
+        assert!(dot.contains("f1 -> f2;"));
+        assert!(dot.contains("f2 -> f0;"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = sample_graph().to_json();
+        let functions = json["functions"].as_array().unwrap();
+        assert_eq!(functions.len(), 3);
+        assert_eq!(functions[0]["imported"], true);
+        assert_eq!(functions[1]["exports"][0], "run");
+        assert_eq!(json["edges"].as_array().unwrap().len(), 2);
+    }
+}
@@ -1,11 +1,35 @@
+use super::container::{ContainerBuild, ContainerRuntime};
 use crate::error::{CompilationError, CompilationResult};
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long a toolchain command may run unless configured otherwise
+pub const DEFAULT_BUILD_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Lines of each stream kept for a timeout report
+const PARTIAL_OUTPUT_LINES: usize = 20;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static BUILD_TIMEOUT: RwLock<Option<Duration>> = RwLock::new(Some(DEFAULT_BUILD_TIMEOUT));
+static CONTAINER: RwLock<Option<ContainerBuild>> = RwLock::new(None);
+/// Tools running now. Each leads its own process group, so stopping one
+/// stops everything it started.
+static RUNNING: Mutex<Vec<Running>> = Mutex::new(Vec::new());
+static CANCEL_HANDLER: OnceLock<()> = OnceLock::new();
 
 /// Shared builder command utilities
 pub struct CommandExecutor;
 
 impl CommandExecutor {
-    /// Check if a tool is installed on the system
+    /// Check if a tool is installed on the system, or in the build
+    /// container's image while builds run in one
     pub fn is_tool_installed(tool_name: &str) -> bool {
+        if let Some(container) = Self::container() {
+            return container.provides(tool_name);
+        }
+
         let command = if cfg!(target_os = "windows") {
             format!("where {tool_name}")
         } else {
@@ -27,25 +51,114 @@ impl CommandExecutor {
         .unwrap_or(false)
     }
 
-    /// Execute a command and return the result
+    /// Limit how long each toolchain command may run; `None` waits forever
+    pub fn set_build_timeout(timeout: Option<Duration>) {
+        *BUILD_TIMEOUT.write().unwrap_or_else(|e| e.into_inner()) = timeout;
+    }
+
+    pub fn build_timeout() -> Option<Duration> {
+        *BUILD_TIMEOUT.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run toolchain commands in a container from now on; `None` runs them
+    /// on the host again
+    pub fn set_container(container: Option<ContainerBuild>) {
+        *CONTAINER.write().unwrap_or_else(|e| e.into_inner()) = container;
+    }
+
+    pub fn container() -> Option<ContainerBuild> {
+        CONTAINER.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Execute a command and return the result. The command is stopped,
+    /// with everything it started, if it outlives the build timeout or the
+    /// user presses Ctrl-C.
     pub fn execute_command(
         command: &str,
         args: &[&str],
         working_dir: &str,
         verbose: bool,
-    ) -> CompilationResult<std::process::Output> {
+    ) -> CompilationResult<Output> {
         if verbose {
             println!("🔧 Executing: {} {}", command, args.join(" "));
         }
 
-        std::process::Command::new(command)
-            .args(args)
-            .current_dir(working_dir)
-            .output()
-            .map_err(|e| CompilationError::ToolExecutionFailed {
-                tool: command.to_string(),
-                reason: e.to_string(),
-            })
+        Self::execute_with_timeout(command, args, working_dir, Self::build_timeout())
+    }
+
+    fn execute_with_timeout(
+        program: &str,
+        args: &[&str],
+        working_dir: &str,
+        timeout: Option<Duration>,
+    ) -> CompilationResult<Output> {
+        let container = Self::container().map(|c| (c, ContainerBuild::next_name()));
+        // In a container, what can fail to start is the runtime
+        let launcher = container
+            .as_ref()
+            .map_or(program, |(container, _)| container.runtime.program());
+        let failed = |e: std::io::Error| CompilationError::ToolExecutionFailed {
+            tool: launcher.to_string(),
+            reason: e.to_string(),
+        };
+
+        let mut command = match &container {
+            Some((container, name)) => {
+                let working_dir = std::path::absolute(working_dir).map_err(failed)?;
+                let mut command = Command::new(container.runtime.program());
+                command.args(container.run_args(name, program, args, &working_dir));
+                command
+            }
+            None => {
+                let mut command = Command::new(program);
+                command.args(args).current_dir(working_dir);
+                command
+            }
+        };
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        install_cancel_handler();
+        isolate(&mut command);
+
+        let mut child = command.spawn().map_err(failed)?;
+        let tool = Running {
+            pid: child.id(),
+            container: container.map(|(container, name)| (container.runtime, name)),
+        };
+        let stdout = Captured::start(child.stdout.take());
+        let stderr = Captured::start(child.stderr.take());
+        running().push(tool.clone());
+        let status = wait_until(&mut child, timeout.map(|t| Instant::now() + t));
+        running().retain(|running| running.pid != tool.pid);
+
+        match status {
+            Ok(Some(status)) => Ok(Output {
+                status,
+                stdout: stdout.finish(),
+                stderr: stderr.finish(),
+            }),
+            Ok(None) => {
+                tool.stop();
+                let _ = child.wait();
+                let mut output = tail(&stdout.partial());
+                let stderr = tail(&stderr.partial());
+                if !output.is_empty() && !stderr.is_empty() {
+                    output.push('\n');
+                }
+                output.push_str(&stderr);
+                Err(CompilationError::TimedOut {
+                    tool: program.to_string(),
+                    timeout_secs: timeout.unwrap_or_default().as_secs(),
+                    output,
+                })
+            }
+            Err(e) => {
+                tool.stop();
+                Err(failed(e))
+            }
+        }
     }
 
     /// Execute a command with live output
@@ -83,24 +196,41 @@ impl CommandExecutor {
     }
 
     /// Copy output file to the target directory
+    #[allow(dead_code)] // TODO: Point creating-plugins.md at copy_as, then remove this
     pub fn copy_to_output(
         source: &str,
         output_dir: &str,
         language: &str,
     ) -> CompilationResult<String> {
         use crate::utils::PathResolver;
-        use std::fs;
         use std::path::Path;
 
-        let source_path = Path::new(source);
         let filename =
             PathResolver::get_filename(source).map_err(|_| CompilationError::BuildFailed {
                 language: language.to_string(),
                 reason: format!("Invalid source file path: {source}"),
             })?;
         let output_path = PathResolver::join_paths(output_dir, &filename);
+        Self::copy_as(source, Path::new(&output_path), language)
+    }
+
+    /// Copy a build artifact to `destination`, creating its directory
+    pub fn copy_as(
+        source: &str,
+        destination: &std::path::Path,
+        language: &str,
+    ) -> CompilationResult<String> {
+        use std::fs;
 
-        fs::copy(source_path, &output_path).map_err(|e| CompilationError::BuildFailed {
+        let output_path = destination.to_string_lossy().to_string();
+        if let Some(dir) = destination.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|_| {
+                CompilationError::OutputDirectoryCreationFailed {
+                    path: dir.to_string_lossy().to_string(),
+                }
+            })?;
+        }
+        fs::copy(source, destination).map_err(|e| CompilationError::BuildFailed {
             language: language.to_string(),
             reason: format!("Failed to copy {source} to {output_path}: {e}"),
         })?;
@@ -122,6 +252,160 @@ impl CommandExecutor {
     }
 }
 
+/// A tool started by [`CommandExecutor::execute_command`]
+#[derive(Clone)]
+struct Running {
+    pid: u32,
+    /// The container it runs in, which outlives its runtime client
+    container: Option<(ContainerRuntime, String)>,
+}
+
+impl Running {
+    fn stop(&self) {
+        kill_tree(self.pid);
+        if let Some((runtime, name)) = &self.container {
+            ContainerBuild::remove(*runtime, name);
+        }
+    }
+}
+
+fn running() -> std::sync::MutexGuard<'static, Vec<Running>> {
+    RUNNING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Install the Ctrl-C handler that stops running tools and their children
+/// before exiting. Tools don't get the terminal's Ctrl-C themselves, being
+/// in their own process groups. If something else already owns Ctrl-C,
+/// e.g. `wasmrun agent`, tools run until they finish or time out.
+fn install_cancel_handler() {
+    CANCEL_HANDLER.get_or_init(|| {
+        let _ = ctrlc::set_handler(|| {
+            crate::ui::keys::restore_terminal();
+            stop_running_tools();
+            std::process::exit(130);
+        });
+    });
+}
+
+/// Stop the tools running now and everything they started, for a Ctrl-C
+/// or a quit key while a build runs
+pub fn stop_running_tools() {
+    let stopped = std::mem::take(&mut *running());
+    for tool in &stopped {
+        tool.stop();
+    }
+    if !stopped.is_empty() {
+        eprintln!("\n🛑 Build cancelled");
+    }
+}
+
+/// Start the tool in a process group of its own
+#[cfg(unix)]
+fn isolate(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+fn isolate(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+/// Kill `pid` and its descendants: its process group on Unix, its process
+/// tree on Windows
+fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    let mut kill = {
+        let mut kill = Command::new("kill");
+        kill.args(["-KILL", "--", &format!("-{pid}")]);
+        kill
+    };
+    #[cfg(windows)]
+    let mut kill = {
+        let mut kill = Command::new("taskkill");
+        kill.args(["/T", "/F", "/PID", &pid.to_string()]);
+        kill
+    };
+    let _ = kill.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
+
+fn wait_until(child: &mut Child, deadline: Option<Instant>) -> std::io::Result<Option<ExitStatus>> {
+    let Some(deadline) = deadline else {
+        return child.wait().map(Some);
+    };
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A child's stdout or stderr, read on a thread so what it printed is
+/// available even if it never exits
+struct Captured {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl Captured {
+    fn start(stream: Option<impl Read + Send + 'static>) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let reader = stream.map(|mut stream| {
+            let buffer = Arc::clone(&buffer);
+            thread::spawn(move || {
+                let mut chunk = [0u8; 8192];
+                while let Ok(n) = stream.read(&mut chunk) {
+                    if n == 0 {
+                        break;
+                    }
+                    buffer
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .extend_from_slice(&chunk[..n]);
+                }
+            })
+        });
+        Self { buffer, reader }
+    }
+
+    /// Everything, once the stream closes
+    fn finish(mut self) -> Vec<u8> {
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+        self.partial()
+    }
+
+    /// What has been read so far. Waits briefly for the stream to drain,
+    /// but not for processes that escaped the kill and still hold it open.
+    fn partial(&self) -> Vec<u8> {
+        let drained_by = Instant::now() + Duration::from_millis(500);
+        while let Some(reader) = &self.reader {
+            if reader.is_finished() || Instant::now() >= drained_by {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// The last few lines of `bytes`
+fn tail(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(PARTIAL_OUTPUT_LINES)..].join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,6 +460,54 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_stops_the_process_tree() {
+        let temp_dir = tempdir().unwrap();
+        let started = Instant::now();
+        // The shell starts a background sleep, reports its pid, then hangs
+        let result = CommandExecutor::execute_with_timeout(
+            "sh",
+            &["-c", "sleep 30 & echo $!; echo almost done >&2; wait"],
+            temp_dir.path().to_str().unwrap(),
+            Some(Duration::from_millis(500)),
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let Err(CompilationError::TimedOut { tool, output, .. }) = result else {
+            panic!("Expected TimedOut error, got {result:?}");
+        };
+        assert_eq!(tool, "sh");
+        assert!(output.ends_with("almost done"), "{output}");
+
+        // Killed, or a zombie waiting for init to reap it
+        let grandchild = output.lines().next().unwrap();
+        let gone = || {
+            let signalable = Command::new("kill")
+                .args(["-0", grandchild])
+                .stderr(Stdio::null())
+                .status()
+                .unwrap()
+                .success();
+            !signalable
+                || std::fs::read_to_string(format!("/proc/{grandchild}/stat"))
+                    .is_ok_and(|stat| stat.contains(") Z "))
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !gone() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert!(gone(), "sleep {grandchild} outlived the timeout");
+    }
+
+    #[test]
+    fn test_tail_keeps_last_lines() {
+        let text: String = (1..=30).map(|i| format!("line {i}\n")).collect();
+        let kept = tail(text.as_bytes());
+        assert!(kept.starts_with("line 11\n"));
+        assert!(kept.ends_with("line 30"));
+    }
+
     #[test]
     fn test_copy_to_output() {
         let temp_dir = tempdir().unwrap();
@@ -0,0 +1,412 @@
+//! Running toolchain commands inside a container image, for `--in-container`
+//! builds on machines without the language's toolchain installed
+
+use crate::compiler::ProjectLanguage;
+use crate::config::project::ContainerConfig;
+use crate::utils::CommandExecutor;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const RUST_IMAGE: &str = "rust:1.83";
+const GO_IMAGE: &str = "tinygo/tinygo:0.34.0";
+const C_IMAGE: &str = "emscripten/emsdk:3.1.74";
+const ASC_IMAGE: &str = "node:22-slim";
+
+static NEXT_CONTAINER: AtomicUsize = AtomicUsize::new(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "docker" => Some(Self::Docker),
+            "podman" => Some(Self::Podman),
+            _ => None,
+        }
+    }
+
+    pub fn program(self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+
+    /// The first of docker and podman on the PATH
+    pub fn detect() -> Option<Self> {
+        [Self::Docker, Self::Podman]
+            .into_iter()
+            .find(|runtime| CommandExecutor::is_tool_installed(runtime.program()))
+    }
+}
+
+/// The image a language builds in by default, and what it needs
+struct LanguageImage {
+    image: &'static str,
+    tools: &'static [&'static str],
+    /// Named volumes kept between builds, by path in the container
+    caches: &'static [&'static str],
+}
+
+fn language_image(language: ProjectLanguage) -> Option<LanguageImage> {
+    match language {
+        ProjectLanguage::Rust => Some(LanguageImage {
+            image: RUST_IMAGE,
+            tools: &["cargo", "rustc", "wasm-bindgen"],
+            caches: &["/usr/local/cargo", "/usr/local/rustup"],
+        }),
+        ProjectLanguage::Go => Some(LanguageImage {
+            image: GO_IMAGE,
+            tools: &["tinygo", "go"],
+            caches: &[],
+        }),
+        ProjectLanguage::C => Some(LanguageImage {
+            image: C_IMAGE,
+            tools: &["emcc", "make"],
+            caches: &["/emsdk/upstream/emscripten/cache"],
+        }),
+        ProjectLanguage::Asc => Some(LanguageImage {
+            image: ASC_IMAGE,
+            tools: &["node", "npm", "npx"],
+            caches: &["/root/.npm"],
+        }),
+        ProjectLanguage::Python | ProjectLanguage::Unknown => None,
+    }
+}
+
+/// Installs what the Rust image lacks: the wasm targets, and the
+/// `wasm-bindgen` CLI at the version in the project's Cargo.lock, since the
+/// two must match. Both land in the cache volumes, so only the first build
+/// pays for them.
+fn rust_setup(project_path: &Path) -> String {
+    let install = match wasm_bindgen_version(project_path) {
+        Some(version) => format!(
+            "{{ wasm-bindgen --version 2>/dev/null | grep -qF {} || \
+             cargo install -f wasm-bindgen-cli --locked --version {}; }}",
+            sh_quote(&format!(" {version}")),
+            sh_quote(&version)
+        ),
+        None => "{ command -v wasm-bindgen >/dev/null || \
+                 cargo install wasm-bindgen-cli --locked; }"
+            .to_string(),
+    };
+    format!("rustup target add wasm32-unknown-unknown wasm32-wasip1 >/dev/null && {install}")
+}
+
+/// The `wasm-bindgen` version Cargo.lock pins, if it has one and it looks
+/// like a version rather than anything a shell would read
+fn wasm_bindgen_version(project_path: &Path) -> Option<String> {
+    let lock = fs::read_to_string(project_path.join("Cargo.lock")).ok()?;
+    let mut lines = lock.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line == r#"name = "wasm-bindgen""# {
+            return lines
+                .next()?
+                .strip_prefix("version = ")
+                .map(|v| v.trim_matches('"').to_string())
+                .filter(|v| is_version(v));
+        }
+    }
+    None
+}
+
+/// How toolchain commands run while `--in-container` is on: in a throwaway
+/// container of `image`, with the directories the build reads and writes
+/// mounted at the same paths as on the host
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerBuild {
+    pub runtime: ContainerRuntime,
+    pub image: String,
+    /// Shell command run before each toolchain command
+    pub setup: Option<String>,
+    /// Commands the image provides, which count as installed
+    pub tools: Vec<String>,
+    pub mounts: Vec<PathBuf>,
+    /// Named volume and the container path it's mounted at
+    pub volumes: Vec<(String, String)>,
+}
+
+impl ContainerBuild {
+    /// The build for `language`, with `[container]` from wasmrun.toml over
+    /// the language's defaults
+    pub fn for_language(
+        language: ProjectLanguage,
+        runtime: ContainerRuntime,
+        config: &ContainerConfig,
+        project_path: &Path,
+        mounts: Vec<PathBuf>,
+    ) -> Result<Self, String> {
+        let defaults = language_image(language);
+        let image = match (&config.image, &defaults) {
+            (Some(image), _) => image.clone(),
+            (None, Some(defaults)) => defaults.image.to_string(),
+            (None, None) => {
+                return Err(format!(
+                    "There's no container image for {language:?} projects; \
+                     set `image` under [container] in wasmrun.toml"
+                ))
+            }
+        };
+        let setup = match &config.setup {
+            Some(setup) => Some(setup.clone()),
+            None if language == ProjectLanguage::Rust => Some(rust_setup(project_path)),
+            None => None,
+        };
+        let tools = match (&config.tools, &defaults) {
+            (Some(tools), _) => tools.clone(),
+            (None, Some(defaults)) => defaults.tools.iter().map(|t| t.to_string()).collect(),
+            (None, None) => Vec::new(),
+        };
+        let volumes = defaults
+            .map(|defaults| defaults.caches)
+            .unwrap_or_default()
+            .iter()
+            .map(|path| (volume_name(&image, path), path.to_string()))
+            .collect();
+
+        Ok(Self {
+            runtime,
+            image,
+            setup: setup.filter(|s| !s.trim().is_empty()),
+            tools,
+            mounts,
+            volumes,
+        })
+    }
+
+    pub fn provides(&self, tool: &str) -> bool {
+        self.tools.iter().any(|t| t == tool)
+    }
+
+    /// A name for the next container, so it can be removed if the build is
+    /// stopped: killing the runtime's client doesn't stop the container
+    pub fn next_name() -> String {
+        format!(
+            "wasmrun-build-{}-{}",
+            std::process::id(),
+            NEXT_CONTAINER.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// Arguments to the runtime that run `program args` in `working_dir` in
+    /// a container called `name`
+    pub fn run_args(
+        &self,
+        name: &str,
+        program: &str,
+        args: &[&str],
+        working_dir: &Path,
+    ) -> Vec<String> {
+        let mut run = vec!["run", "--rm", "--name", name]
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        for mount in &self.mounts {
+            let mount = mount.to_string_lossy();
+            run.extend(["-v".to_string(), format!("{mount}:{mount}")]);
+        }
+        for (volume, path) in &self.volumes {
+            run.extend(["-v".to_string(), format!("{volume}:{path}")]);
+        }
+        run.extend([
+            "-w".to_string(),
+            working_dir.to_string_lossy().to_string(),
+            self.image.clone(),
+            "sh".to_string(),
+            "-c".to_string(),
+            self.script(),
+            "sh".to_string(),
+            program.to_string(),
+        ]);
+        run.extend(args.iter().map(|a| a.to_string()));
+        run
+    }
+
+    /// The shell script around the command, which comes in as `"$@"`
+    fn script(&self) -> String {
+        let mut script = String::new();
+        if let Some(setup) = &self.setup {
+            script.push_str(&format!("{{ {setup}; }} >&2 || exit $?; "));
+        }
+        script.push_str("\"$@\"; status=$?; ");
+        if let Some(owner) = self.restore_owner() {
+            script.push_str(&owner);
+        }
+        script.push_str("exit $status");
+        script
+    }
+
+    /// Docker runs builds as root, leaving root-owned files in the mounts;
+    /// hand them back to whoever owns the project. Rootless podman maps
+    /// root to the user already.
+    #[cfg(unix)]
+    fn restore_owner(&self) -> Option<String> {
+        use std::os::unix::fs::MetadataExt;
+
+        if self.runtime != ContainerRuntime::Docker {
+            return None;
+        }
+        let project = fs::metadata(self.mounts.first()?).ok()?;
+        if project.uid() == 0 {
+            return None;
+        }
+        let dirs = self
+            .mounts
+            .iter()
+            .map(|m| sh_quote(&m.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some(format!(
+            "find {dirs} -xdev -user 0 -exec chown -h {}:{} {{}} + 2>/dev/null; ",
+            project.uid(),
+            project.gid()
+        ))
+    }
+
+    #[cfg(not(unix))]
+    fn restore_owner(&self) -> Option<String> {
+        None
+    }
+
+    /// Remove a build's container, e.g. after its client was killed
+    pub fn remove(runtime: ContainerRuntime, name: &str) {
+        let _ = std::process::Command::new(runtime.program())
+            .args(["rm", "-f", name])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+    }
+}
+
+/// A volume name for `path` in `image`, e.g. `wasmrun-rust-1.83-usr-local-cargo`
+fn volume_name(image: &str, path: &str) -> String {
+    let name = format!("wasmrun-{image}-{}", path.trim_start_matches('/'));
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// A semver version such as `0.2.95` or `1.0.0-rc.1+build.5`
+fn is_version(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+}
+
+fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn build(
+        language: ProjectLanguage,
+        config: &ContainerConfig,
+        project: &Path,
+    ) -> ContainerBuild {
+        ContainerBuild::for_language(
+            language,
+            ContainerRuntime::Podman,
+            config,
+            project,
+            vec![project.to_path_buf(), PathBuf::from("/tmp/out")],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_language_images() {
+        let dir = tempdir().unwrap();
+        let config = ContainerConfig::default();
+
+        let go = build(ProjectLanguage::Go, &config, dir.path());
+        assert_eq!(go.image, GO_IMAGE);
+        assert!(go.provides("tinygo"));
+        assert!(!go.provides("cargo"));
+        assert_eq!(go.setup, None);
+
+        fs::write(
+            dir.path().join("Cargo.lock"),
+            "[[package]]\nname = \"wasm-bindgen\"\nversion = \"0.2.95\"\n",
+        )
+        .unwrap();
+        let rust = build(ProjectLanguage::Rust, &config, dir.path());
+        assert_eq!(rust.image, RUST_IMAGE);
+        assert!(rust.setup.unwrap().contains("--version '0.2.95'"));
+
+        fs::write(
+            dir.path().join("Cargo.lock"),
+            "[[package]]\nname = \"wasm-bindgen\"\nversion = \"0.2; rm -rf ~\"\n",
+        )
+        .unwrap();
+        let setup = build(ProjectLanguage::Rust, &config, dir.path())
+            .setup
+            .unwrap();
+        assert!(!setup.contains("rm -rf"), "{setup}");
+        assert_eq!(
+            rust.volumes[0],
+            (
+                "wasmrun-rust-1.83-usr-local-cargo".to_string(),
+                "/usr/local/cargo".to_string()
+            )
+        );
+
+        let python = ContainerBuild::for_language(
+            ProjectLanguage::Python,
+            ContainerRuntime::Docker,
+            &config,
+            dir.path(),
+            Vec::new(),
+        );
+        assert!(python.unwrap_err().contains("[container]"));
+
+        let custom = ContainerConfig {
+            image: Some("ghcr.io/me/py-wasm:1".to_string()),
+            tools: Some(vec!["waspy".to_string()]),
+            ..Default::default()
+        };
+        let python = build(ProjectLanguage::Python, &custom, dir.path());
+        assert_eq!(python.image, "ghcr.io/me/py-wasm:1");
+        assert!(python.provides("waspy"));
+    }
+
+    #[test]
+    fn test_run_args() {
+        let dir = tempdir().unwrap();
+        let go = build(ProjectLanguage::Go, &ContainerConfig::default(), dir.path());
+        let project = dir.path().to_string_lossy().to_string();
+        let args = go.run_args("b1", "tinygo", &["build", "-o", "app.wasm"], dir.path());
+
+        assert_eq!(args[..4], ["run", "--rm", "--name", "b1"]);
+        assert!(args.contains(&format!("{project}:{project}")));
+        assert!(args.contains(&"/tmp/out:/tmp/out".to_string()));
+        let image = args.iter().position(|a| a == GO_IMAGE).unwrap();
+        assert_eq!(args[image - 2..image], ["-w".to_string(), project]);
+        assert_eq!(args[image + 1..image + 3], ["sh", "-c"]);
+        assert_eq!(args[image + 3], "\"$@\"; status=$?; exit $status");
+        assert_eq!(
+            args[image + 4..],
+            ["sh", "tinygo", "build", "-o", "app.wasm"]
+        );
+    }
+
+    #[test]
+    fn test_sh_quote() {
+        assert_eq!(sh_quote("/a b/it's"), r"'/a b/it'\''s'");
+    }
+}
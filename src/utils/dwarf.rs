@@ -0,0 +1,496 @@
+//! Source line lookup from a module's DWARF `.debug_line` section
+//!
+//! In WebAssembly DWARF, addresses are byte offsets from the start of the
+//! code section's contents. [`code_body_offsets`] gives where each function's
+//! instructions begin on that scale, so an offset in a function body can be
+//! turned into an address and looked up in the [`LineTable`].
+
+use std::collections::HashMap;
+
+/// One row of the line table
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineRow {
+    address: u64,
+    file: usize,
+    line: u64,
+    end_sequence: bool,
+}
+
+/// Address to `file:line` map built from `.debug_line`
+#[derive(Debug, Clone, Default)]
+pub struct LineTable {
+    files: Vec<String>,
+    /// Sorted by address, with a sequence's end before anything starting at
+    /// the same address
+    rows: Vec<LineRow>,
+}
+
+impl LineTable {
+    /// Read the line table of a module, or `None` if it has no line info
+    pub fn from_module(bytes: &[u8]) -> Option<Self> {
+        let sections = custom_sections(bytes);
+        let debug_line = sections.get(".debug_line")?;
+        let mut table = LineTable::default();
+        let strings = Strings {
+            line_str: sections.get(".debug_line_str").copied().unwrap_or(&[]),
+            str: sections.get(".debug_str").copied().unwrap_or(&[]),
+        };
+        let mut offset = 0;
+        while offset < debug_line.len() {
+            match parse_unit(debug_line, offset, &strings, &mut table) {
+                Some(next) if next > offset => offset = next,
+                _ => break,
+            }
+        }
+        if table.rows.is_empty() {
+            return None;
+        }
+        table
+            .rows
+            .sort_by_key(|row| (row.address, !row.end_sequence));
+        Some(table)
+    }
+
+    /// Source file and line of the code at `address`
+    pub fn lookup(&self, address: u64) -> Option<(&str, u64)> {
+        let index = self.rows.partition_point(|row| row.address <= address);
+        let row = self.rows.get(index.checked_sub(1)?)?;
+        if row.end_sequence || row.line == 0 {
+            return None;
+        }
+        Some((self.files.get(row.file)?.as_str(), row.line))
+    }
+}
+
+struct Strings<'a> {
+    line_str: &'a [u8],
+    str: &'a [u8],
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> Option<u8> {
+        let byte = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(slice)
+    }
+
+    fn uint(&mut self, len: usize) -> Option<u64> {
+        let bytes = self.bytes(len)?;
+        Some(
+            bytes
+                .iter()
+                .rev()
+                .fold(0u64, |acc, &b| (acc << 8) | b as u64),
+        )
+    }
+
+    fn uleb(&mut self) -> Option<u64> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> Option<i64> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1i64 << shift;
+                }
+                return Some(result);
+            }
+        }
+    }
+
+    fn cstr(&mut self) -> Option<String> {
+        let rest = self.data.get(self.pos..)?;
+        let len = rest.iter().position(|&b| b == 0)?;
+        self.pos += len + 1;
+        Some(String::from_utf8_lossy(&rest[..len]).into_owned())
+    }
+}
+
+fn cstr_at(data: &[u8], offset: u64) -> Option<String> {
+    Reader {
+        data,
+        pos: usize::try_from(offset).ok()?,
+    }
+    .cstr()
+}
+
+/// Read one attribute of a DWARF 5 directory or file entry. Strings are
+/// returned as text, numbers as their decimal form.
+fn read_form(r: &mut Reader, form: u64, offset_size: usize, strings: &Strings) -> Option<String> {
+    Some(match form {
+        0x08 => r.cstr()?,                                        // string
+        0x1f => cstr_at(strings.line_str, r.uint(offset_size)?)?, // line_strp
+        0x0e => cstr_at(strings.str, r.uint(offset_size)?)?,      // strp
+        0x0f => r.uleb()?.to_string(),                            // udata
+        0x0b => r.uint(1)?.to_string(),                           // data1
+        0x05 => r.uint(2)?.to_string(),                           // data2
+        0x06 => r.uint(4)?.to_string(),                           // data4
+        0x07 => r.uint(8)?.to_string(),                           // data8
+        0x1e => {
+            r.bytes(16)?; // data16, e.g. an MD5
+            String::new()
+        }
+        0x09 => {
+            let len = r.uleb()? as usize; // block
+            r.bytes(len)?;
+            String::new()
+        }
+        _ => return None,
+    })
+}
+
+/// DWARF 5 directory or file list: `(path, directory index)` per entry
+fn read_entries(
+    r: &mut Reader,
+    offset_size: usize,
+    strings: &Strings,
+) -> Option<Vec<(String, usize)>> {
+    let format_count = r.u8()?;
+    let mut format = Vec::new();
+    for _ in 0..format_count {
+        format.push((r.uleb()?, r.uleb()?));
+    }
+    let count = r.uleb()?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let mut path = String::new();
+        let mut dir = 0;
+        for &(content, form) in &format {
+            let value = read_form(r, form, offset_size, strings)?;
+            match content {
+                1 => path = value,                     // DW_LNCT_path
+                2 => dir = value.parse().unwrap_or(0), // DW_LNCT_directory_index
+                _ => {}
+            }
+        }
+        entries.push((path, dir));
+    }
+    Some(entries)
+}
+
+fn join_path(dir: &str, file: &str) -> String {
+    if dir.is_empty() || file.starts_with('/') {
+        file.to_string()
+    } else {
+        format!("{}/{file}", dir.trim_end_matches('/'))
+    }
+}
+
+/// Parse the line program unit at `offset`, adding its files and rows to
+/// `table`. Returns the offset of the next unit.
+fn parse_unit(
+    data: &[u8],
+    offset: usize,
+    strings: &Strings,
+    table: &mut LineTable,
+) -> Option<usize> {
+    let mut r = Reader { data, pos: offset };
+    let (unit_length, offset_size) = match r.uint(4)? {
+        0xffff_ffff => (r.uint(8)?, 8),
+        len => (len, 4),
+    };
+    let unit_end = r.pos.checked_add(usize::try_from(unit_length).ok()?)?;
+    if unit_end > data.len() {
+        return None;
+    }
+    let version = r.uint(2)?;
+    if !(2..=5).contains(&version) {
+        return Some(unit_end);
+    }
+    if version >= 5 {
+        r.u8()?; // address_size
+        r.u8()?; // segment_selector_size
+    }
+    let header_length = r.uint(offset_size)? as usize;
+    let program_start = r.pos.checked_add(header_length)?;
+    let min_inst_length = r.u8()? as u64;
+    if version >= 4 {
+        r.u8()?; // maximum_operations_per_instruction
+    }
+    r.u8()?; // default_is_stmt
+    let line_base = r.u8()? as i8 as i64;
+    let line_range = r.u8()?.max(1) as u64;
+    let opcode_base = r.u8()?;
+    let mut standard_lengths = Vec::new();
+    for _ in 1..opcode_base {
+        standard_lengths.push(r.u8()?);
+    }
+
+    // Indices into `table.files` for this unit's file numbers
+    let mut files: Vec<usize> = Vec::new();
+    let first_file = if version >= 5 {
+        let dirs = read_entries(&mut r, offset_size, strings)?;
+        for (path, dir) in read_entries(&mut r, offset_size, strings)? {
+            let dir = dirs.get(dir).map_or("", |(d, _)| d.as_str());
+            table.files.push(join_path(dir, &path));
+            files.push(table.files.len() - 1);
+        }
+        0
+    } else {
+        let mut dirs = vec![String::new()];
+        loop {
+            let dir = r.cstr()?;
+            if dir.is_empty() {
+                break;
+            }
+            dirs.push(dir);
+        }
+        loop {
+            let name = r.cstr()?;
+            if name.is_empty() {
+                break;
+            }
+            let dir = r.uleb()? as usize;
+            r.uleb()?; // modification time
+            r.uleb()?; // length
+            table
+                .files
+                .push(join_path(dirs.get(dir).map_or("", String::as_str), &name));
+            files.push(table.files.len() - 1);
+        }
+        1
+    };
+
+    r.pos = program_start;
+    let file_index = |file: u64, files: &[usize]| -> usize {
+        (file as usize)
+            .checked_sub(first_file)
+            .and_then(|i| files.get(i).copied())
+            .unwrap_or(usize::MAX)
+    };
+    let mut address = 0u64;
+    let mut file = 1u64;
+    let mut line = 1i64;
+    let mut emit = |address: u64, file: u64, line: i64, end_sequence: bool, files: &[usize]| {
+        table.rows.push(LineRow {
+            address,
+            file: file_index(file, files),
+            line: line.max(0) as u64,
+            end_sequence,
+        });
+    };
+
+    while r.pos < unit_end {
+        let opcode = r.u8()?;
+        if opcode >= opcode_base {
+            let adjusted = (opcode - opcode_base) as u64;
+            address += (adjusted / line_range) * min_inst_length;
+            line += line_base + (adjusted % line_range) as i64;
+            emit(address, file, line, false, &files);
+            continue;
+        }
+        match opcode {
+            0 => {
+                let len = r.uleb()? as usize;
+                let end = r.pos.checked_add(len)?;
+                match r.u8()? {
+                    1 => {
+                        emit(address, file, line, true, &files);
+                        address = 0;
+                        file = 1;
+                        line = 1;
+                    }
+                    2 => address = r.uint(len.saturating_sub(1).min(8))?,
+                    _ => {}
+                }
+                r.pos = end;
+            }
+            1 => emit(address, file, line, false, &files),
+            2 => address += r.uleb()? * min_inst_length,
+            3 => line += r.sleb()?,
+            4 => file = r.uleb()?,
+            8 => address += ((255 - opcode_base) as u64 / line_range) * min_inst_length,
+            9 => address += r.uint(2)?,
+            _ => {
+                // Operands of the other standard opcodes are all ULEB128
+                let operands = standard_lengths.get(opcode as usize - 1).copied()?;
+                for _ in 0..operands {
+                    r.uleb()?;
+                }
+            }
+        }
+    }
+    Some(unit_end)
+}
+
+/// Custom sections of a module by name
+fn custom_sections(bytes: &[u8]) -> HashMap<String, &[u8]> {
+    let mut sections = HashMap::new();
+    let mut r = Reader {
+        data: bytes,
+        pos: 8,
+    };
+    while r.pos < bytes.len() {
+        let Some(id) = r.u8() else { break };
+        let Some(size) = r.uleb() else { break };
+        let start = r.pos;
+        let Some(end) = start
+            .checked_add(size as usize)
+            .filter(|&e| e <= bytes.len())
+        else {
+            break;
+        };
+        if id == 0 {
+            let name_len = r.uleb().unwrap_or(0) as usize;
+            if let Some(name) = r.bytes(name_len) {
+                sections.insert(
+                    String::from_utf8_lossy(name).into_owned(),
+                    &bytes[r.pos..end],
+                );
+            }
+        }
+        r.pos = end;
+    }
+    sections
+}
+
+/// For each function the module defines, the DWARF address of its first
+/// instruction
+pub fn code_body_offsets(bytes: &[u8]) -> Vec<u64> {
+    let mut r = Reader {
+        data: bytes,
+        pos: 8,
+    };
+    while r.pos < bytes.len() {
+        let (Some(id), Some(size)) = (r.u8(), r.uleb()) else {
+            break;
+        };
+        let start = r.pos;
+        let end = start.saturating_add(size as usize);
+        if id != 10 {
+            r.pos = end;
+            continue;
+        }
+        let mut offsets = Vec::new();
+        let count = r.uleb().unwrap_or(0);
+        for _ in 0..count {
+            let (Some(body_size), body_start) = (r.uleb(), r.pos) else {
+                break;
+            };
+            let Some(local_groups) = r.uleb() else { break };
+            for _ in 0..local_groups {
+                if r.uleb().is_none() || r.u8().is_none() {
+                    break;
+                }
+            }
+            offsets.push((r.pos - start) as u64);
+            r.pos = body_start + body_size as usize;
+        }
+        return offsets;
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leb(mut n: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                out.push(byte);
+                return out;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    fn custom(name: &str, payload: &[u8]) -> Vec<u8> {
+        let mut body = leb(name.len() as u64);
+        body.extend_from_slice(name.as_bytes());
+        body.extend_from_slice(payload);
+        let mut section = vec![0];
+        section.extend(leb(body.len() as u64));
+        section.extend(body);
+        section
+    }
+
+    /// A DWARF 4 line program for `src/lib.rs`: line 3 at 0x5, line 7 at
+    /// 0x9, sequence ending at 0x10
+    fn debug_line_v4() -> Vec<u8> {
+        let mut header = vec![
+            1,    // min_inst_length
+            1,    // max_ops_per_inst
+            1,    // default_is_stmt
+            0xfb, // line_base -5
+            14,   // line_range
+            13,   // opcode_base
+        ];
+        header.extend([0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+        header.extend(b"src\0\0");
+        header.extend(b"lib.rs\0\x01\0\0\0");
+
+        let mut program = vec![0, 5, 2];
+        program.extend(5u32.to_le_bytes()); // set_address 0x5
+        program.extend([3, 2, 1]); // advance_line +2, copy
+        program.extend([2, 4, 3, 4, 1]); // advance_pc 4, advance_line +4, copy
+        program.extend([2, 7, 0, 1, 1]); // advance_pc 7, end_sequence
+
+        let mut unit = 4u16.to_le_bytes().to_vec();
+        unit.extend((header.len() as u32).to_le_bytes());
+        unit.extend(header);
+        unit.extend(program);
+        let mut data = (unit.len() as u32).to_le_bytes().to_vec();
+        data.extend(unit);
+        data
+    }
+
+    #[test]
+    fn test_line_table_lookup() {
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        module.extend(custom(".debug_line", &debug_line_v4()));
+        let table = LineTable::from_module(&module).unwrap();
+
+        assert_eq!(table.lookup(0x4), None);
+        assert_eq!(table.lookup(0x5), Some(("src/lib.rs", 3)));
+        assert_eq!(table.lookup(0x8), Some(("src/lib.rs", 3)));
+        assert_eq!(table.lookup(0x9), Some(("src/lib.rs", 7)));
+        assert_eq!(table.lookup(0x10), None);
+
+        assert!(LineTable::from_module(b"\0asm\x01\0\0\0").is_none());
+    }
+
+    #[test]
+    fn test_code_body_offsets() {
+        // Two bodies: no locals, then one local group
+        let code = [2, 2, 0, 0x0b, 4, 1, 1, 0x7f, 0x0b];
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        module.push(10);
+        module.extend(leb(code.len() as u64));
+        module.extend(code);
+        assert_eq!(code_body_offsets(&module), vec![3, 8]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::{Result, WasmrunError};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// The branch, commit and working-tree state of the repository a project
+/// lives in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GitState {
+    /// `None` on a detached HEAD
+    pub branch: Option<String>,
+    pub commit: String,
+    /// Uncommitted changes, including untracked files
+    pub dirty: bool,
+    /// The paths `git status` reported, at most [`MAX_LISTED_CHANGES`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+}
+
+const MAX_LISTED_CHANGES: usize = 20;
+
+impl GitState {
+    /// `None` when `path` isn't inside a git repository with at least one
+    /// commit, or git isn't installed
+    pub fn detect(path: &Path) -> Option<Self> {
+        let commit = git(path, &["rev-parse", "HEAD"])?;
+        let branch = git(path, &["symbolic-ref", "--short", "-q", "HEAD"]);
+        let changes: Vec<String> = git(path, &["status", "--porcelain"])
+            .map(|status| status.lines().map(|line| line.trim().to_string()).collect())
+            .unwrap_or_default();
+        Some(Self {
+            branch,
+            commit,
+            dirty: !changes.is_empty(),
+            changes: changes.into_iter().take(MAX_LISTED_CHANGES).collect(),
+        })
+    }
+
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(12)]
+    }
+
+    /// `branch@commit`, with `-dirty` for uncommitted changes
+    pub fn describe(&self) -> String {
+        let dirty = if self.dirty { "-dirty" } else { "" };
+        match &self.branch {
+            Some(branch) => format!("{branch}@{}{dirty}", self.short_commit()),
+            None => format!("{}{dirty}", self.short_commit()),
+        }
+    }
+}
+
+/// Fails when `path` is in a git repository with uncommitted changes, for
+/// `--require-clean`. A path outside any repository fails too, since there
+/// is no commit to vouch for what was built.
+pub fn require_clean(path: &Path, action: &str) -> Result<()> {
+    let Some(state) = GitState::detect(path) else {
+        return Err(WasmrunError::from(format!(
+            "--require-clean: {} is not in a git repository with a commit, refusing to {action}",
+            path.display()
+        )));
+    };
+    if !state.dirty {
+        return Ok(());
+    }
+
+    let mut message = format!(
+        "--require-clean: {} has uncommitted changes, refusing to {action}. Commit or stash them first:",
+        state.describe()
+    );
+    for change in &state.changes {
+        message.push_str(&format!("\n  {change}"));
+    }
+    Err(WasmrunError::from(message))
+}
+
+/// Trimmed output of a git command run in `path`, if it succeeded and
+/// printed anything
+fn git(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn run(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn test_detect_and_require_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(GitState::detect(dir.path()).is_none());
+        assert!(require_clean(dir.path(), "push").is_err());
+
+        let committed = run(dir.path(), &["init", "-q", "-b", "main"])
+            && fs::write(dir.path().join("lib.rs"), "fn main() {}").is_ok()
+            && run(dir.path(), &["add", "."])
+            && run(
+                dir.path(),
+                &[
+                    "-c",
+                    "user.name=t",
+                    "-c",
+                    "user.email=t@example.com",
+                    "commit",
+                    "-qm",
+                    "init",
+                ],
+            );
+        if !committed {
+            // No usable git in this environment
+            return;
+        }
+
+        let state = GitState::detect(dir.path()).unwrap();
+        assert_eq!(state.branch.as_deref(), Some("main"));
+        assert!(!state.dirty);
+        assert_eq!(state.describe(), format!("main@{}", state.short_commit()));
+        assert!(require_clean(dir.path(), "push").is_ok());
+
+        fs::write(dir.path().join("new.rs"), "").unwrap();
+        let state = GitState::detect(dir.path()).unwrap();
+        assert!(state.dirty);
+        assert_eq!(state.changes, ["?? new.rs"]);
+        let error = require_clean(dir.path(), "push").unwrap_err().to_string();
+        assert!(error.contains("-dirty"), "{error}");
+        assert!(error.contains("new.rs"), "{error}");
+    }
+}
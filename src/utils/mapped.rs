@@ -0,0 +1,91 @@
+//! Read-only views of files on disk. Large files are memory-mapped, so a
+//! module is paged in as its sections are read instead of being copied
+//! into memory up front, and parts nothing looks at are never read.
+
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Files smaller than this are read; mapping them costs more than it saves
+const MAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// The contents of a file, mapped or read into memory
+pub struct MappedFile(Contents);
+
+enum Contents {
+    Mapped(memmap2::Mmap),
+    Read(Vec<u8>),
+}
+
+impl MappedFile {
+    /// Map `path` when it is large, and read it otherwise or when it can't
+    /// be mapped
+    ///
+    /// Keep the mapping only as long as it's being read: a build that
+    /// rewrites the file in place changes what the mapping sees, and one
+    /// that truncates it kills the process with SIGBUS. Long-running
+    /// servers, whose modules are rebuilt under them, read files instead.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len >= MAP_THRESHOLD {
+            // SAFETY: the map is read-only and short-lived; see above
+            if let Ok(map) = unsafe { memmap2::Mmap::map(&file) } {
+                return Ok(Self(Contents::Mapped(map)));
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(len as usize);
+        io::Read::read_to_end(&mut file, &mut bytes)?;
+        Ok(Self(Contents::Read(bytes)))
+    }
+
+    /// Whether the file is mapped rather than read
+    #[cfg(test)]
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.0, Contents::Mapped(_))
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            Contents::Mapped(map) => map,
+            Contents::Read(bytes) => bytes,
+        }
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_small_files_are_read_and_large_ones_mapped() {
+        let dir = tempdir().unwrap();
+        let small = dir.path().join("small.wasm");
+        std::fs::write(&small, b"\0asm\x01\0\0\0").unwrap();
+        let file = MappedFile::open(&small).unwrap();
+        assert!(!file.is_mapped());
+        assert_eq!(&*file, b"\0asm\x01\0\0\0");
+
+        let large = dir.path().join("large.wasm");
+        let bytes: Vec<u8> = (0..MAP_THRESHOLD + 7).map(|i| i as u8).collect();
+        std::fs::write(&large, &bytes).unwrap();
+        let file = MappedFile::open(&large).unwrap();
+        assert!(file.is_mapped());
+        assert_eq!(&*file, &bytes[..]);
+
+        assert!(MappedFile::open(dir.path().join("missing.wasm")).is_err());
+    }
+}
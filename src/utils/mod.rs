@@ -1,11 +1,32 @@
+mod browser;
+pub mod call_graph;
 mod command;
+mod container;
+#[cfg(feature = "interpreter")]
+pub mod dwarf;
+mod git;
+mod jsonc;
+mod mapped;
 mod path;
 mod plugin_utils;
+mod prometheus;
+mod query;
+mod sources;
 mod system;
 mod wasm_analysis;
 
-pub use command::CommandExecutor;
-pub use path::PathResolver;
+pub use browser::{ConsoleMessage, HeadlessBrowser, ReadyCondition, ScreencastFrame};
+pub use command::{stop_running_tools, CommandExecutor, DEFAULT_BUILD_TIMEOUT};
+pub use container::{ContainerBuild, ContainerRuntime};
+pub use git::{require_clean, GitState};
+pub use jsonc::strip_jsonc;
+pub use mapped::MappedFile;
+pub use path::{safe_relative, PathResolver};
 pub use plugin_utils::PluginUtils;
-pub use system::SystemUtils;
+pub use prometheus::{resident_memory_bytes, PrometheusText, PROMETHEUS_CONTENT_TYPE};
+pub use query::percent_decode;
+pub use sources::{file_digest, list_files};
+#[cfg(unix)]
+pub use system::remove_stale_socket;
+pub use system::{dir_size, gzip_size, SystemUtils};
 pub use wasm_analysis::*;
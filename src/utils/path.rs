@@ -1,6 +1,20 @@
 use crate::error::{Result, WasmrunError};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+
+/// `path` as a relative path that stays inside the directory it's joined
+/// to, `None` for absolute paths and `..`
+pub fn safe_relative(path: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
 
 /// Utility for resolving and handling file paths
 pub struct PathResolver;
@@ -240,58 +254,6 @@ impl PathResolver {
         Ok(())
     }
 
-    /// Clean all wasmrun temporary directories and files
-    pub fn cleanup_all_temp_directories() -> Result<()> {
-        println!("🧹 Starting cleanup of all wasmrun temporary directories...");
-
-        Self::cleanup_temp_directory("wasmrun_temp")?;
-        let temp_base = std::env::temp_dir();
-
-        if let Ok(entries) = fs::read_dir(&temp_base) {
-            let mut additional_cleaned = 0;
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                if entry_path.is_dir() {
-                    if let Some(dir_name) = entry_path.file_name() {
-                        let dir_name_str = dir_name.to_string_lossy();
-                        // Clean any directory that starts with wasmrun_
-                        if dir_name_str.starts_with("wasmrun_") && dir_name_str != "wasmrun_temp" {
-                            if let Err(e) = fs::remove_dir_all(&entry_path) {
-                                println!(
-                                    "⚠️  Warning: Failed to remove {}: {e}",
-                                    entry_path.display()
-                                );
-                            } else {
-                                println!(
-                                    "🗑️  Removed old temp directory: {}",
-                                    entry_path.display()
-                                );
-                                additional_cleaned += 1;
-                            }
-                        }
-                    }
-                }
-            }
-
-            if additional_cleaned > 0 {
-                println!("✅ Cleaned {additional_cleaned} additional temporary directories");
-            }
-        }
-
-        // Clean the PID file if it exists
-        let pid_file = "/tmp/wasmrun_server.pid";
-        if Path::new(pid_file).exists() {
-            if let Err(e) = fs::remove_file(pid_file) {
-                println!("⚠️  Warning: Failed to remove PID file: {e}");
-            } else {
-                println!("🗑️  Removed stale PID file");
-            }
-        }
-
-        println!("✅ Cleanup completed successfully!");
-        Ok(())
-    }
-
     /// Remove file
     pub fn remove_file(path: &str) -> Result<()> {
         fs::remove_file(path)
@@ -488,6 +450,18 @@ mod tests {
         assert!(result.unwrap().ends_with("main.rs"));
     }
 
+    #[test]
+    fn test_safe_relative() {
+        assert_eq!(
+            safe_relative("src/lib.rs"),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(safe_relative("./a"), Some(PathBuf::from("a")));
+        assert_eq!(safe_relative("../a"), None);
+        assert_eq!(safe_relative("/etc/passwd"), None);
+        assert_eq!(safe_relative(""), None);
+    }
+
     #[test]
     fn test_is_safe_path() {
         assert!(PathResolver::is_safe_path("safe/path/file.wasm"));
@@ -3,7 +3,9 @@ use crate::error::{Result, WasmrunError};
 use crate::plugin::registry::PluginRegistry;
 use crate::plugin::Plugin;
 use crate::utils::SystemUtils;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "external-plugins")]
+use std::path::Path;
+use std::path::PathBuf;
 
 pub struct PluginUtils;
 
@@ -33,6 +35,7 @@ impl PluginUtils {
         }
     }
 
+    #[cfg(feature = "external-plugins")]
     pub fn is_plugin_available(plugin_name: &str) -> bool {
         if let Ok(plugin_dir) = Self::get_plugin_directory(plugin_name) {
             let cargo_toml = plugin_dir.join("Cargo.toml");
@@ -101,6 +104,7 @@ impl PluginUtils {
         None
     }
 
+    #[cfg(feature = "external-plugins")]
     pub fn create_metadata_file(plugin_name: &str, plugin_dir: &Path, version: &str) -> Result<()> {
         let metadata_content = format!(
             "plugin_name={}\nversion={}\ninstall_date={}\n",
@@ -0,0 +1,134 @@
+//! Prometheus text exposition, rendered by hand for the dev server and OS
+//! mode `/metrics` endpoints.
+
+use std::fmt::Write;
+
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Builds a scrape body one metric family at a time: call [`family`] for the
+/// `# HELP`/`# TYPE` header, then [`sample`] (or [`histogram`]) per label set.
+///
+/// [`family`]: PrometheusText::family
+/// [`sample`]: PrometheusText::sample
+/// [`histogram`]: PrometheusText::histogram
+#[derive(Debug, Default)]
+pub struct PrometheusText {
+    out: String,
+}
+
+impl PrometheusText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn family(&mut self, name: &str, help: &str, kind: &str) -> &mut Self {
+        let _ = writeln!(self.out, "# HELP {name} {help}");
+        let _ = writeln!(self.out, "# TYPE {name} {kind}");
+        self
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(name);
+        write_labels(&mut self.out, labels);
+        let _ = writeln!(self.out, " {}", format_value(value));
+        self
+    }
+
+    /// `_bucket`, `_sum` and `_count` samples for one histogram series.
+    /// `buckets` holds cumulative counts per upper bound; `None` is `+Inf`.
+    pub fn histogram(
+        &mut self,
+        name: &str,
+        labels: &[(&str, &str)],
+        buckets: &[(Option<f64>, u64)],
+        sum: f64,
+    ) -> &mut Self {
+        let mut count = 0;
+        for (bound, cumulative) in buckets {
+            let le = bound.map_or("+Inf".to_string(), format_value);
+            let mut with_le = labels.to_vec();
+            with_le.push(("le", &le));
+            self.sample(&format!("{name}_bucket"), &with_le, *cumulative as f64);
+            count = *cumulative;
+        }
+        self.sample(&format!("{name}_sum"), labels, sum);
+        self.sample(&format!("{name}_count"), labels, count as f64)
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn write_labels(out: &mut String, labels: &[(&str, &str)]) {
+    if labels.is_empty() {
+        return;
+    }
+    out.push('{');
+    for (i, (key, value)) in labels.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(key);
+        out.push_str("=\"");
+        for c in value.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '"' => out.push_str("\\\""),
+                '\n' => out.push_str("\\n"),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+    out.push('}');
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Resident set size of this process, where the platform exposes it cheaply
+pub fn resident_memory_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_families_samples_and_escaping() {
+        let mut text = PrometheusText::new();
+        text.family("wasmrun_requests_total", "Requests served.", "counter")
+            .sample("wasmrun_requests_total", &[], 3.0)
+            .sample(
+                "wasmrun_requests_total",
+                &[("path", "/a\"b\\c"), ("status", "200")],
+                1.0,
+            );
+        text.family("wasmrun_latency_seconds", "Latency.", "histogram")
+            .histogram(
+                "wasmrun_latency_seconds",
+                &[("path", "/")],
+                &[(Some(0.005), 1), (Some(2.5), 3), (None, 4)],
+                0.75,
+            );
+        let text = text.finish();
+
+        assert!(text.starts_with(
+            "# HELP wasmrun_requests_total Requests served.\n# TYPE wasmrun_requests_total counter\nwasmrun_requests_total 3\n"
+        ));
+        assert!(text.contains(r#"wasmrun_requests_total{path="/a\"b\\c",status="200"} 1"#));
+        assert!(text.contains(r#"wasmrun_latency_seconds_bucket{path="/",le="0.005"} 1"#));
+        assert!(text.contains(r#"wasmrun_latency_seconds_bucket{path="/",le="+Inf"} 4"#));
+        assert!(text.contains("wasmrun_latency_seconds_sum{path=\"/\"} 0.75\n"));
+        assert!(text.contains("wasmrun_latency_seconds_count{path=\"/\"} 4\n"));
+    }
+}
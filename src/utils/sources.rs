@@ -0,0 +1,70 @@
+//! A project's source files, as the remote build sync and the artifact
+//! store see them: everything outside build directories, with digests that
+//! are only recomputed when a file changes
+
+use crate::remote::cache::sha256_digest;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Directories that hold build state rather than sources, wherever they are
+pub const BUILD_DIRS: [&str; 3] = [".git", "target", "node_modules"];
+
+/// Digests of files already hashed, by path, with the modification time and
+/// size they were taken at
+static DIGESTS: Mutex<Option<HashMap<PathBuf, DigestStamp>>> = Mutex::new(None);
+
+type DigestStamp = (SystemTime, u64, String);
+
+/// Every file under `root` by `/`-separated relative path, leaving out
+/// [`BUILD_DIRS`] and the `exclude`d paths
+pub fn list_files(root: &Path, exclude: &[PathBuf]) -> io::Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(root.join(&dir))? {
+            let entry = entry?;
+            let relative = dir.join(entry.file_name());
+            if exclude
+                .iter()
+                .any(|excluded| relative.starts_with(excluded))
+            {
+                continue;
+            }
+            let kind = entry.file_type()?;
+            if kind.is_dir() {
+                if !BUILD_DIRS.iter().any(|name| entry.file_name() == *name) {
+                    pending.push(relative);
+                }
+            } else if entry.path().is_file() {
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.insert(key, entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// The file's `sha256:` digest, reusing the last one while its size and
+/// modification time are unchanged
+pub fn file_digest(path: &Path) -> io::Result<String> {
+    let metadata = fs::metadata(path)?;
+    let stamp = (metadata.modified()?, metadata.len());
+    let mut digests = DIGESTS.lock().unwrap_or_else(|e| e.into_inner());
+    let digests = digests.get_or_insert_with(HashMap::new);
+    if let Some((modified, len, digest)) = digests.get(path) {
+        if (*modified, *len) == stamp {
+            return Ok(digest.clone());
+        }
+    }
+    let digest = sha256_digest(&fs::read(path)?);
+    digests.insert(path.to_path_buf(), (stamp.0, stamp.1, digest.clone()));
+    Ok(digest)
+}
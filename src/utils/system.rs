@@ -33,6 +33,7 @@ impl SystemUtils {
     }
 
     /// Get the latest version of a crate from crates.io
+    #[cfg_attr(not(feature = "external-plugins"), allow(dead_code))]
     pub fn get_latest_crates_version(crate_name: &str) -> Option<String> {
         if let Ok(output) = Command::new("cargo")
             .args(["search", crate_name, "--limit", "1"])
@@ -92,3 +93,56 @@ impl SystemUtils {
         }
     }
 }
+
+/// Recursively sum the size of all regular files under `dir`.
+///
+/// Returns 0 if the directory does not exist or cannot be read; symlinks are
+/// not followed. Used for agent session quotas and build retention.
+pub fn dir_size(dir: &std::path::Path) -> u64 {
+    let mut total = 0u64;
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            total += dir_size(&entry.path());
+        } else if meta.is_file() {
+            total += meta.len();
+        }
+    }
+    total
+}
+
+/// Size of `bytes` gzipped at the default level, as a server would send them
+pub fn gzip_size(bytes: &[u8]) -> u64 {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let _ = encoder.write_all(bytes);
+    encoder.finish().map_or(0, |gz| gz.len() as u64)
+}
+
+/// Clear the way for a Unix socket server at `path`. A socket left by a
+/// server that didn't shut down cleanly is removed; one that still answers,
+/// or a path that isn't a socket at all, is an error rather than deleted.
+#[cfg(unix)]
+pub fn remove_stale_socket(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !meta.file_type().is_socket() {
+        return Err(format!("{} exists and isn't a socket", path.display()));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(format!("{} is already in use", path.display()));
+    }
+    std::fs::remove_file(path)
+        .map_err(|e| format!("Failed to remove stale socket {}: {e}", path.display()))
+}
@@ -1,6 +1,11 @@
-use crate::commands::{verify_wasm, VerificationResult};
+use crate::commands::module_display::{format_function_signature, format_value_type};
+use crate::commands::{verify_wasm_bytes, VerificationResult};
 use crate::error::{Result, WasmrunError};
+use crate::runtime::core::module::{ExportKind, ImportKind, LazyModule, TableType};
 use crate::utils::{CommandExecutor, PathResolver};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
@@ -63,8 +68,11 @@ impl WasmAnalysis {
 
         let file_size = CommandExecutor::format_file_size(file_size_bytes);
 
-        // Verify wasm
-        let verification = verify_wasm(path).ok();
+        // Read rather than mapped, since the dev server analyzes modules a
+        // rebuild may truncate
+        let verification = fs::read(path)
+            .ok()
+            .and_then(|bytes| verify_wasm_bytes(&bytes).ok());
 
         let is_valid = verification.as_ref().is_some_and(|v| v.valid_magic);
 
@@ -303,6 +311,410 @@ impl ProjectAnalysis {
     }
 }
 
+/// Structural summary of a module, served to the console UI by the dev
+/// server's `/api/module/info`
+#[derive(Debug, Serialize)]
+pub struct ModuleDetails {
+    pub file_size: usize,
+    pub version: u32,
+    pub imports: Vec<ImportDetail>,
+    pub exports: Vec<ExportDetail>,
+    pub memories: Vec<LimitsDetail>,
+    pub tables: Vec<LimitsDetail>,
+    pub globals: usize,
+    pub start: Option<u32>,
+    /// Whether the module's JS imports reach for WebGPU
+    pub webgpu: bool,
+    pub functions: FunctionSummary,
+    /// Every section in file order; custom sections carry their own name
+    pub sections: Vec<SectionDetail>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportDetail {
+    pub module: String,
+    pub name: String,
+    pub kind: &'static str,
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportDetail {
+    pub name: String,
+    pub kind: &'static str,
+    pub index: u32,
+    pub signature: Option<String>,
+    /// Demangled name-section name of the exported function, if different
+    pub function: Option<String>,
+}
+
+/// A memory (sizes in 64 KiB pages) or a table (sizes in elements)
+#[derive(Debug, Serialize)]
+pub struct LimitsDetail {
+    pub initial: u32,
+    pub max: Option<u32>,
+    pub element_type: Option<&'static str>,
+    /// `module::name` when the memory or table is imported
+    pub imported_from: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionSummary {
+    pub imported: usize,
+    pub defined: usize,
+    pub code_size: usize,
+    pub largest: Vec<FunctionSize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionSize {
+    pub index: u32,
+    pub name: String,
+    pub size: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SectionDetail {
+    pub id: u8,
+    pub name: String,
+    pub size: usize,
+    pub custom: bool,
+}
+
+const SECTION_NAMES: [&str; 13] = [
+    "Custom",
+    "Type",
+    "Import",
+    "Function",
+    "Table",
+    "Memory",
+    "Global",
+    "Export",
+    "Start",
+    "Element",
+    "Code",
+    "Data",
+    "DataCount",
+];
+
+impl ModuleDetails {
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, String> {
+        // Function bodies are only measured, never decoded
+        let (lazy, names) = rayon::join(
+            || LazyModule::parse(bytes),
+            || read_demangled_function_names(bytes),
+        );
+        let lazy = lazy?;
+        let module = lazy.headers();
+        let signature = |type_index: u32| {
+            module
+                .types
+                .get(type_index as usize)
+                .map(|t| format_function_signature(&t.params, &t.results))
+        };
+
+        let mut imports = Vec::with_capacity(module.imports.len());
+        let mut memories = Vec::new();
+        let mut tables = Vec::new();
+        let mut function_types = Vec::new();
+        for import in &module.imports {
+            let qualified = format!("{}::{}", import.module, import.name);
+            let (kind, sig) = match &import.kind {
+                ImportKind::Function(type_index) => {
+                    function_types.push(*type_index);
+                    ("function", signature(*type_index))
+                }
+                ImportKind::Table(table) => {
+                    tables.push(table_detail(table, Some(qualified)));
+                    ("table", None)
+                }
+                ImportKind::Memory(memory) => {
+                    memories.push(LimitsDetail {
+                        initial: memory.initial,
+                        max: memory.max,
+                        element_type: None,
+                        imported_from: Some(qualified),
+                    });
+                    ("memory", None)
+                }
+                ImportKind::Global(_) => ("global", None),
+            };
+            imports.push(ImportDetail {
+                module: import.module.clone(),
+                name: import.name.clone(),
+                kind,
+                signature: sig,
+            });
+        }
+        let imported_functions = function_types.len();
+        function_types.extend(module.functions.iter().map(|f| f.type_index));
+
+        if let Some(memory) = &module.memory {
+            memories.push(LimitsDetail {
+                initial: memory.initial,
+                max: memory.max,
+                element_type: None,
+                imported_from: None,
+            });
+        }
+        tables.extend(module.tables.iter().map(|t| table_detail(t, None)));
+
+        let mut exports: Vec<ExportDetail> = module
+            .exports
+            .values()
+            .map(|export| {
+                let is_function = matches!(export.kind, ExportKind::Function);
+                ExportDetail {
+                    name: export.name.clone(),
+                    kind: match export.kind {
+                        ExportKind::Function => "function",
+                        ExportKind::Table => "table",
+                        ExportKind::Memory => "memory",
+                        ExportKind::Global => "global",
+                    },
+                    index: export.index,
+                    signature: function_types
+                        .get(export.index as usize)
+                        .filter(|_| is_function)
+                        .and_then(|t| signature(*t)),
+                    function: names
+                        .get(&export.index)
+                        .filter(|n| is_function && **n != export.name)
+                        .cloned(),
+                }
+            })
+            .collect();
+        exports.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut largest = (0..module.functions.len())
+            .into_par_iter()
+            .map(|i| {
+                let index = (imported_functions + i) as u32;
+                Ok(FunctionSize {
+                    index,
+                    name: names
+                        .get(&index)
+                        .cloned()
+                        .unwrap_or_else(|| format!("func[{index}]")),
+                    size: lazy.code_len(i)?,
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, String>>()?;
+        largest.sort_by(|a, b| b.size.cmp(&a.size).then(a.index.cmp(&b.index)));
+        let code_size = largest.iter().map(|f| f.size).sum();
+        largest.truncate(10);
+
+        Ok(ModuleDetails {
+            file_size: bytes.len(),
+            version: module.version,
+            imports,
+            exports,
+            memories,
+            tables,
+            globals: module.globals.len(),
+            start: module.start,
+            webgpu: module
+                .imports
+                .iter()
+                .any(|i| is_webgpu_import(&i.module, &i.name)),
+            functions: FunctionSummary {
+                imported: imported_functions,
+                defined: module.functions.len(),
+                code_size,
+                largest,
+            },
+            sections: read_sections(bytes),
+        })
+    }
+}
+
+/// Recognise WebGPU bindings among a module's imports: wasm-bindgen shims
+/// (`__wbg_requestAdapter_…`, `__wbg_instanceof_GpuAdapter_…`), Emscripten's
+/// `wgpu*` functions, and `webgpu`/`wgpu` import namespaces.
+pub fn is_webgpu_import(module: &str, name: &str) -> bool {
+    let module = module.to_ascii_lowercase();
+    let name = name.to_ascii_lowercase();
+    module.contains("gpu")
+        || name.contains("gpu")
+        || name.contains("requestadapter")
+        || name.contains("getpreferredcanvasformat")
+}
+
+fn table_detail(table: &TableType, imported_from: Option<String>) -> LimitsDetail {
+    LimitsDetail {
+        initial: table.initial,
+        max: table.max,
+        element_type: Some(format_value_type(table.element_type)),
+        imported_from,
+    }
+}
+
+fn read_sections(bytes: &[u8]) -> Vec<SectionDetail> {
+    section_spans(bytes)
+        .into_iter()
+        .map(|span| SectionDetail {
+            id: span.id,
+            size: span.range.len(),
+            custom: span.id == 0,
+            name: span.name,
+        })
+        .collect()
+}
+
+/// Where a section's contents sit in the module
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionSpan {
+    pub id: u8,
+    /// The custom section's own name for id 0
+    pub name: String,
+    pub range: std::ops::Range<usize>,
+}
+
+/// Every section in file order; stops at the first malformed header
+pub fn section_spans(bytes: &[u8]) -> Vec<SectionSpan> {
+    let mut spans = Vec::new();
+    let mut pos = 8;
+
+    while pos < bytes.len() {
+        let id = bytes[pos];
+        pos += 1;
+        let Some(size) = read_leb(bytes, &mut pos) else {
+            break;
+        };
+        let end = pos + size as usize;
+        if end > bytes.len() {
+            break;
+        }
+
+        let name = if id == 0 {
+            let mut p = pos;
+            read_name(bytes, &mut p).unwrap_or_default()
+        } else {
+            SECTION_NAMES
+                .get(id as usize)
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| format!("Unknown({id})"))
+        };
+        spans.push(SectionSpan {
+            id,
+            name,
+            range: pos..end,
+        });
+        pos = end;
+    }
+
+    spans
+}
+
+/// Function names from the `name` custom section, keyed by function index.
+/// Returns an empty map when the module has no (or a malformed) name section.
+pub fn read_function_names(bytes: &[u8]) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    let mut pos = 8;
+
+    while pos < bytes.len() {
+        let Some(id) = bytes.get(pos).copied() else {
+            break;
+        };
+        pos += 1;
+        let Some(size) = read_leb(bytes, &mut pos) else {
+            break;
+        };
+        let end = pos + size as usize;
+        if end > bytes.len() {
+            break;
+        }
+
+        if id == 0 {
+            let mut p = pos;
+            if read_name(bytes, &mut p).as_deref() == Some("name") {
+                parse_function_name_map(&bytes[p..end], &mut names);
+            }
+        }
+        pos = end;
+    }
+
+    names
+}
+
+/// Human-readable form of a Rust (legacy or v0) or Itanium C++ symbol.
+/// Anything that isn't a mangled name is returned unchanged.
+pub fn demangle(name: &str) -> String {
+    if let Ok(symbol) = rustc_demangle::try_demangle(name) {
+        // `{:#}` drops the trailing `::h0123abcd` hash
+        return format!("{symbol:#}");
+    }
+    if name.starts_with("_Z") {
+        if let Ok(symbol) = cpp_demangle::Symbol::new(name) {
+            if let Ok(demangled) = symbol.demangle(&cpp_demangle::DemangleOptions::default()) {
+                return demangled;
+            }
+        }
+    }
+    name.to_string()
+}
+
+/// [`read_function_names`] with every name passed through [`demangle`],
+/// in parallel since large modules carry hundreds of thousands of them
+pub fn read_demangled_function_names(bytes: &[u8]) -> HashMap<u32, String> {
+    read_function_names(bytes)
+        .into_par_iter()
+        .map(|(index, name)| (index, demangle(&name)))
+        .collect()
+}
+
+fn parse_function_name_map(data: &[u8], names: &mut HashMap<u32, String>) {
+    let mut pos = 0;
+    while pos < data.len() {
+        let subsection = data[pos];
+        pos += 1;
+        let Some(size) = read_leb(data, &mut pos) else {
+            return;
+        };
+        let end = (pos + size as usize).min(data.len());
+
+        // Subsection 1 is the function name map
+        if subsection == 1 {
+            let mut p = pos;
+            let Some(count) = read_leb(data, &mut p) else {
+                return;
+            };
+            for _ in 0..count {
+                let (Some(index), Some(name)) = (read_leb(data, &mut p), read_name(data, &mut p))
+                else {
+                    return;
+                };
+                names.insert(index, name);
+            }
+        }
+        pos = end;
+    }
+}
+
+fn read_leb(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut result = 0u32;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        result |= ((byte & 0x7F) as u32).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+        shift += 7;
+        if shift >= 35 {
+            return None;
+        }
+    }
+}
+
+fn read_name(bytes: &[u8], pos: &mut usize) -> Option<String> {
+    let len = read_leb(bytes, pos)? as usize;
+    let raw = bytes.get(*pos..*pos + len)?;
+    *pos += len;
+    Some(String::from_utf8_lossy(raw).into_owned())
+}
+
 // Helper functions
 
 fn extract_entry_points(verification: &VerificationResult) -> Vec<String> {
@@ -623,4 +1035,94 @@ mod tests {
                 || summary.contains("🔧")
         );
     }
+
+    #[test]
+    fn test_read_function_names() {
+        let mut bytes = VALID_WASM_BYTES.to_vec();
+        // custom "name" section with a function name map {0: "main", 2: "helper"}
+        let mut payload = vec![4];
+        payload.extend_from_slice(b"name");
+        let map = [
+            0x02, 0x00, 0x04, b'm', b'a', b'i', b'n', 0x02, 0x06, b'h', b'e', b'l', b'p', b'e',
+            b'r',
+        ];
+        payload.push(0x01);
+        payload.push(map.len() as u8);
+        payload.extend_from_slice(&map);
+        bytes.push(0x00);
+        bytes.push(payload.len() as u8);
+        bytes.extend_from_slice(&payload);
+
+        let names = read_function_names(&bytes);
+        assert_eq!(names.get(&0).map(String::as_str), Some("main"));
+        assert_eq!(names.get(&2).map(String::as_str), Some("helper"));
+        assert!(read_function_names(&VALID_WASM_BYTES).is_empty());
+    }
+
+    #[test]
+    fn test_demangle() {
+        assert_eq!(
+            demangle("_ZN4core3fmt5write17h0123456789abcdefE"),
+            "core::fmt::write"
+        );
+        assert_eq!(demangle("_RNvCs1234_7mycrate4main"), "mycrate::main");
+        assert_eq!(demangle("_Z3addii"), "add(int, int)");
+        assert_eq!(demangle("_ZNK3foo3barEv"), "foo::bar() const");
+        assert_eq!(demangle("fd_write"), "fd_write");
+        assert_eq!(demangle("_Zgarbage"), "_Zgarbage");
+    }
+
+    #[test]
+    fn test_module_details() {
+        let mut bytes = VALID_WASM_BYTES.to_vec();
+        // type () -> i32; import env::log as function; one defined function
+        bytes.extend_from_slice(&[0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7F]);
+        bytes.extend_from_slice(&[
+            0x02, 0x0B, 0x01, 0x03, b'e', b'n', b'v', 0x03, b'l', b'o', b'g', 0x00, 0x00,
+        ]);
+        bytes.extend_from_slice(&[0x03, 0x02, 0x01, 0x00]);
+        // memory: 1 page, max 2
+        bytes.extend_from_slice(&[0x05, 0x04, 0x01, 0x01, 0x01, 0x02]);
+        // export "answer" -> func 1
+        bytes.extend_from_slice(&[
+            0x07, 0x0A, 0x01, 0x06, b'a', b'n', b's', b'w', b'e', b'r', 0x00, 0x01,
+        ]);
+        // code: i32.const 42
+        bytes.extend_from_slice(&[0x0A, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2A, 0x0B]);
+        // custom "producers" section
+        bytes.extend_from_slice(&[
+            0x00, 0x0B, 0x09, b'p', b'r', b'o', b'd', b'u', b'c', b'e', b'r', b's', 0x00,
+        ]);
+
+        let details = ModuleDetails::from_bytes(&bytes).unwrap();
+        assert_eq!(details.imports.len(), 1);
+        assert_eq!(details.imports[0].kind, "function");
+        assert_eq!(details.imports[0].signature.as_deref(), Some("() -> i32"));
+        assert_eq!(details.exports[0].name, "answer");
+        assert_eq!(details.exports[0].signature.as_deref(), Some("() -> i32"));
+        assert_eq!(details.memories[0].initial, 1);
+        assert_eq!(details.memories[0].max, Some(2));
+        assert_eq!(details.functions.imported, 1);
+        assert_eq!(details.functions.defined, 1);
+        assert_eq!(details.functions.largest[0].name, "func[1]");
+        assert!(!details.webgpu);
+
+        let custom: Vec<&SectionDetail> = details.sections.iter().filter(|s| s.custom).collect();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].name, "producers");
+        assert_eq!(details.sections[0].name, "Type");
+    }
+
+    #[test]
+    fn test_is_webgpu_import() {
+        assert!(is_webgpu_import("wbg", "__wbg_requestAdapter_a8f6ec2d"));
+        assert!(is_webgpu_import(
+            "wbg",
+            "__wbg_instanceof_GpuCanvasContext_1eb1"
+        ));
+        assert!(is_webgpu_import("env", "wgpuDeviceCreateBuffer"));
+        assert!(is_webgpu_import("webgpu", "create_device"));
+        assert!(!is_webgpu_import("wbg", "__wbg_getContext_efc4"));
+        assert!(!is_webgpu_import("wasi_snapshot_preview1", "fd_write"));
+    }
 }
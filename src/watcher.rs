@@ -1,14 +1,28 @@
+use crate::logging::FileChange;
+use crate::t;
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebouncedEvent, DebouncedEventKind};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
 use std::time::Duration;
 
+/// The watch session's state, written beside the numbered watch builds
+pub const WATCH_STATE_FILE: &str = "wasmrun-watch.json";
+/// How long a watch loop waits for changes before checking whether it was
+/// paused or resumed
+pub const PAUSE_POLL: Duration = Duration::from_millis(250);
+
 #[allow(dead_code)]
 pub struct ProjectWatcher {
     debounced_receiver: Option<Receiver<Result<Vec<DebouncedEvent>, notify::Error>>>,
     #[allow(dead_code)]
     watcher: Option<notify_debouncer_mini::Debouncer<RecommendedWatcher>>,
+    /// Canonical, since the events carry canonical paths
+    root: PathBuf,
 }
 
 impl ProjectWatcher {
@@ -40,6 +54,7 @@ impl ProjectWatcher {
         Ok(Self {
             debounced_receiver: Some(rx),
             watcher: Some(debouncer),
+            root: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
         })
     }
 
@@ -52,38 +67,266 @@ impl ProjectWatcher {
         }
     }
 
+    /// Like [`Self::wait_for_change`], but `None` once `timeout` passes
+    /// without changes
+    pub fn wait_for_change_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Option<Result<Vec<DebouncedEvent>, notify::Error>> {
+        self.debounced_receiver
+            .as_ref()
+            .and_then(|rx| rx.recv_timeout(timeout).ok())
+    }
+
     #[allow(dead_code)]
     pub fn should_recompile(&self, events: &[DebouncedEvent]) -> bool {
+        events
+            .iter()
+            .any(|event| change_kind(event) == Some(ChangeKind::Source))
+    }
+
+    /// The stylesheets and assets `events` touched, relative to the project
+    /// root, for swapping into the served page without a rebuild
+    pub fn hot_swaps(&self, events: &[DebouncedEvent]) -> Vec<(ChangeKind, String)> {
+        let mut swaps: Vec<(ChangeKind, String)> = Vec::new();
         for event in events {
-            if event.kind == DebouncedEventKind::Any {
-                let path = &event.path;
-
-                if path.components().any(|c| {
-                    let s = c.as_os_str().to_string_lossy();
-                    s == "target" || s.starts_with(".")
-                }) {
-                    continue;
-                }
+            let Some(kind @ (ChangeKind::Style | ChangeKind::Asset)) = change_kind(event) else {
+                continue;
+            };
+            let path = self.relative(&event.path);
+            if !swaps.iter().any(|(_, swapped)| *swapped == path) {
+                swaps.push((kind, path));
+            }
+        }
+        swaps
+    }
+
+    /// The files `events` touched, relative to the project root and hashed
+    /// as they are now, for annotating the rebuild they trigger
+    pub fn changed_files(&self, events: &[DebouncedEvent]) -> Vec<FileChange> {
+        let mut changes: Vec<FileChange> = Vec::new();
+        for event in events.iter().filter(|event| change_kind(event).is_some()) {
+            let path = self.relative(&event.path);
+            if changes.iter().any(|change| change.path == path) {
+                continue;
+            }
+            let sha256 = std::fs::read(&event.path).ok().map(|data| {
+                Sha256::digest(data)
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect()
+            });
+            changes.push(FileChange { path, sha256 });
+        }
+        changes
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+/// Whether a watch session rebuilds on changes. The watch loop, the `p`
+/// key and the dev server's `/api/watch/*` routes share it through
+/// [`WATCH_STATE_FILE`], as the server may not be the process watching.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchState {
+    /// Changes are held back until watching resumes
+    pub paused: bool,
+}
+
+impl WatchState {
+    /// The state in `dir`, or the default
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(dir.join(WATCH_STATE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(dir.join(WATCH_STATE_FILE), json + "\n")
+    }
+
+    /// The directory holding the state of the watch session that built
+    /// `wasm_path`: one up, where the watch loop keeps it, or beside it.
+    /// `None` when no watch session built it.
+    pub fn dir_for_wasm(wasm_path: &Path) -> Option<PathBuf> {
+        wasm_path
+            .parent()
+            .into_iter()
+            .flat_map(|dir| dir.ancestors().take(2))
+            .find(|dir| dir.join(WATCH_STATE_FILE).is_file())
+            .map(Path::to_path_buf)
+    }
+}
+
+/// The watch loop's side of pausing: changes that arrive while paused are
+/// held, and handed over together on resume, so a long refactor costs one
+/// rebuild once it's done instead of one per save
+pub struct PauseGate {
+    dir: PathBuf,
+    paused: bool,
+    held: Vec<DebouncedEvent>,
+}
+
+impl PauseGate {
+    /// Start unpaused, with the state kept in `dir`
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        WatchState::default().save(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            paused: false,
+            held: Vec::new(),
+        })
+    }
 
-                if let Some(ext) = path.extension() {
-                    let ext = ext.to_string_lossy().to_lowercase();
-
-                    if [
-                        "rs", "go", "c", "cpp", "h", "hpp", "ts", "js", "toml", "py", "mod",
-                    ]
-                    .contains(&ext.as_str())
-                    {
-                        return true;
-                    }
-                } else if path.file_name().is_some_and(|f| {
-                    let name = f.to_string_lossy().to_lowercase();
-                    ["cargo.toml", "makefile", "go.mod", "package.json"].contains(&name.as_str())
-                }) {
-                    return true;
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Flip between paused and watching
+    pub fn toggle(dir: &Path) -> io::Result<bool> {
+        let paused = !WatchState::load(dir).paused;
+        WatchState { paused }.save(dir)?;
+        Ok(paused)
+    }
+
+    /// The events to act on now: none while paused, and on resume the
+    /// ones held back followed by `events`
+    pub fn pass(&mut self, events: Vec<DebouncedEvent>) -> Vec<DebouncedEvent> {
+        let paused = WatchState::load(&self.dir).paused;
+        if paused != self.paused {
+            self.paused = paused;
+            if paused {
+                println!("{}", t!("watch.paused"));
+            } else {
+                println!("{}", t!("watch.resumed"));
+                if !self.held.is_empty() {
+                    println!("{}", t!("watch.held", count = self.held.len()));
                 }
             }
         }
+        if paused {
+            for event in events {
+                if change_kind(&event).is_some() && !self.held.contains(&event) {
+                    self.held.push(event);
+                }
+            }
+            return Vec::new();
+        }
+        let mut passed = std::mem::take(&mut self.held);
+        passed.extend(events);
+        passed
+    }
+}
+
+/// What a changed file asks of the running app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Rebuild the module and reload the page
+    Source,
+    /// Replace the stylesheet in place
+    Style,
+    /// Re-fetch the image, font or media file
+    Asset,
+}
+
+const SOURCE_EXTENSIONS: [&str; 11] = [
+    "rs", "go", "c", "cpp", "h", "hpp", "ts", "js", "toml", "py", "mod",
+];
+const ASSET_EXTENSIONS: [&str; 16] = [
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "ico", "woff", "woff2", "ttf", "otf",
+    "mp3", "wav", "ogg", "mp4",
+];
+
+fn change_kind(event: &DebouncedEvent) -> Option<ChangeKind> {
+    if event.kind != DebouncedEventKind::Any {
+        return None;
+    }
+    classify(&event.path)
+}
+
+/// How a change to `path` reaches the app, `None` for files that don't
+/// affect it
+pub fn classify(path: &Path) -> Option<ChangeKind> {
+    if path.components().any(|c| {
+        let s = c.as_os_str().to_string_lossy();
+        s == "target" || s.starts_with(".")
+    }) {
+        return None;
+    }
+
+    if let Some(ext) = path.extension() {
+        let ext = ext.to_string_lossy().to_lowercase();
+        if SOURCE_EXTENSIONS.contains(&ext.as_str()) {
+            Some(ChangeKind::Source)
+        } else if ext == "css" {
+            Some(ChangeKind::Style)
+        } else if ASSET_EXTENSIONS.contains(&ext.as_str()) {
+            Some(ChangeKind::Asset)
+        } else {
+            None
+        }
+    } else {
+        path.file_name()
+            .is_some_and(|f| {
+                let name = f.to_string_lossy().to_lowercase();
+                ["cargo.toml", "makefile", "go.mod", "package.json"].contains(&name.as_str())
+            })
+            .then_some(ChangeKind::Source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn change(path: &str) -> DebouncedEvent {
+        DebouncedEvent::new(PathBuf::from(path), DebouncedEventKind::Any)
+    }
+
+    #[test]
+    fn test_pause_holds_changes_until_resumed() {
+        let dir = tempdir().unwrap();
+        let mut gate = PauseGate::new(dir.path()).unwrap();
+        assert_eq!(gate.pass(vec![change("src/lib.rs")]).len(), 1);
+
+        assert!(PauseGate::toggle(dir.path()).unwrap());
+        assert!(gate.pass(vec![change("src/lib.rs")]).is_empty());
+        assert!(gate
+            .pass(vec![change("src/lib.rs"), change("notes.txt")])
+            .is_empty());
+        assert!(gate.pass(Vec::new()).is_empty());
+
+        // One pass for everything held, without duplicates or ignored files
+        assert!(!PauseGate::toggle(dir.path()).unwrap());
+        assert_eq!(
+            gate.pass(vec![change("style.css")]),
+            vec![change("src/lib.rs"), change("style.css")]
+        );
+        assert!(gate.pass(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_state_is_found_from_the_served_module() {
+        let dir = tempdir().unwrap();
+        let build = dir.path().join("build-0003");
+        fs::create_dir_all(build.join("pkg")).unwrap();
+        assert_eq!(WatchState::dir_for_wasm(&build.join("app.wasm")), None);
 
-        false
+        PauseGate::new(dir.path()).unwrap();
+        assert_eq!(
+            WatchState::dir_for_wasm(&build.join("app.wasm")).as_deref(),
+            Some(dir.path())
+        );
+        assert!(!WatchState::load(dir.path()).paused);
+        assert_eq!(WatchState::dir_for_wasm(&build.join("pkg/app.wasm")), None);
     }
 }
@@ -0,0 +1,60 @@
+//! Tests for the `wasmrun_core` library API, used the way an embedding tool
+//! would: detect a project, inspect a module and run it, without the CLI
+
+use std::fs;
+use wasmrun_core::{
+    detect_project_language, verify_wasm, verify_wasm_bytes, ModuleType, ProjectLanguage,
+    WasmAnalysis,
+};
+
+/// A module exporting an empty `_start`
+const START_MODULE: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type: () -> ()
+    0x03, 0x02, 0x01, 0x00, // function 0 has type 0
+    0x07, 0x0a, 0x01, 0x06, b'_', b's', b't', b'a', b'r', b't', 0x00, 0x00, // export _start
+    0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b, // body: end
+];
+
+#[test]
+fn test_detect_project_language() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    assert_eq!(detect_project_language(path), ProjectLanguage::Unknown);
+
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"app\"").unwrap();
+    assert_eq!(detect_project_language(path), ProjectLanguage::Rust);
+}
+
+#[test]
+fn test_inspect_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let wasm = dir.path().join("app.wasm");
+    fs::write(&wasm, START_MODULE).unwrap();
+    let wasm = wasm.to_str().unwrap();
+
+    let verification = verify_wasm(wasm).unwrap();
+    assert!(verification.valid_magic);
+    assert_eq!(verification.export_names, ["_start"]);
+    let from_bytes = verify_wasm_bytes(START_MODULE).unwrap();
+    assert_eq!(from_bytes.export_names, verification.export_names);
+
+    let analysis = WasmAnalysis::analyze(wasm).unwrap();
+    assert!(analysis.is_valid);
+    assert!(matches!(analysis.module_type, ModuleType::WasiModule));
+    assert_eq!(analysis.entry_points, ["_start"]);
+}
+
+#[cfg(feature = "interpreter")]
+#[test]
+fn test_execute_module() {
+    assert_eq!(wasmrun_core::execute_wasm_bytes(START_MODULE).unwrap(), 0);
+}
+
+#[cfg(feature = "interpreter")]
+#[test]
+fn test_call_function_with_executor() {
+    let module = wasmrun_core::Module::parse(START_MODULE).unwrap();
+    let mut executor = wasmrun_core::Executor::new(module).unwrap();
+    assert!(executor.execute(0).unwrap().is_empty());
+}
@@ -1,7 +1,7 @@
 //! Integration tests for the `wasmrun exec` command
 //! These tests verify end-to-end functionality of executing WASM files with various configurations
 
-#[cfg(test)]
+#[cfg(all(test, feature = "interpreter"))]
 mod exec_integration_tests {
     use std::path::PathBuf;
     use std::process::Command;
@@ -2,7 +2,9 @@ import { useState, useEffect } from 'preact/hooks'
 import { BaseLayout } from '@/layouts/BaseLayout'
 import { StatusBar } from '@/components/StatusBar'
 import { StatusMessage } from '@/types'
-import { loadWasmModule } from '@/utils/wasm'
+import { loadWasmModule, fetchModuleDetails } from '@/utils/wasm'
+import { detectWebGpu, WebGpuStatus } from '@/utils/webgpu'
+import { t } from '@/i18n'
 
 // These will be replaced by the Rust template processor
 declare const TITLE: string
@@ -10,35 +12,45 @@ declare const FILENAME: string
 
 export function App() {
   const [status, setStatus] = useState<StatusMessage>({
-    message: 'Loading WASM module...',
+    message: t('app.loading'),
     type: 'info',
   })
 
   const [wasmError, setWasmError] = useState<string | null>(null)
+  const [webgpu, setWebgpu] = useState<WebGpuStatus | null>(null)
 
   useEffect(() => {
     initializeWasm()
   }, [])
 
   async function initializeWasm() {
+    // Check WebGPU up front for modules that use it, so a missing API shows
+    // guidance instead of an opaque exception from inside the module
+    let gpu: WebGpuStatus | null = null
+    const details = await fetchModuleDetails()
+    if (details?.webgpu) {
+      gpu = await detectWebGpu()
+      setWebgpu(gpu)
+    }
+
     try {
       setStatus({
-        message: `Loading WASM module: ${FILENAME}`,
+        message: t('app.loadingFile', { file: FILENAME }),
         type: 'info',
       })
 
       await loadWasmModule(FILENAME)
 
       setStatus({
-        message: '✅ WASM Module loaded successfully!',
+        message: t('app.loaded'),
         type: 'success',
       })
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : 'Unknown error'
+      const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
       console.error('❌ Error loading WASM module:', error)
 
       setStatus({
-        message: '❌ Error loading WASM module',
+        message: gpu && !gpu.available ? t('app.loadFailedWebGpu') : t('app.loadFailed'),
         type: 'error',
       })
 
@@ -48,20 +60,20 @@ export function App() {
 
   return (
     <BaseLayout title={TITLE}>
-      <StatusBar status={status} />
+      <StatusBar status={status} webgpu={webgpu} />
 
       <div class="flex-1 relative overflow-hidden">
         {wasmError ? (
           <div class="p-8 m-8 border-2 border-light-error dark:border-dark-error rounded-lg">
             <h2 class="text-light-error dark:text-dark-error text-2xl font-semibold mb-4">
-              Error Loading WASM Module
+              {t('app.errorTitle')}
             </h2>
-            <p class="mb-4">There was an error loading the WASM module:</p>
+            <p class="mb-4">{t('app.errorIntro')}</p>
             <pre class="bg-light-bg dark:bg-dark-bg p-4 rounded overflow-auto border border-light-surface3 dark:border-dark-surface3 text-sm font-mono">
               {wasmError}
             </pre>
             <p class="mt-4 text-light-textDim dark:text-dark-textDim">
-              Check the browser console for more details.
+              {t('app.errorHint')}
             </p>
           </div>
         ) : (
@@ -0,0 +1,24 @@
+import { useMemo } from 'preact/hooks'
+import { ansiCss, hasAnsi, parseAnsi } from '@/utils/ansi'
+
+// Text that may carry ANSI colors, as emitted by compilers and guest
+// programs; plain text renders as is
+export function AnsiText({ text }: { text: string }) {
+  const segments = useMemo(() => (hasAnsi(text) ? parseAnsi(text) : null), [text])
+  if (!segments) return <>{text}</>
+
+  return (
+    <>
+      {segments.map((segment, i) => {
+        const style = ansiCss(segment.style)
+        return Object.keys(style).length === 0 ? (
+          segment.text
+        ) : (
+          <span key={i} style={style}>
+            {segment.text}
+          </span>
+        )
+      })}
+    </>
+  )
+}
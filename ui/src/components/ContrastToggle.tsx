@@ -0,0 +1,39 @@
+import clsx from 'clsx'
+import { useHighContrast } from '@/hooks/useHighContrast'
+import { t } from '@/i18n'
+
+interface ContrastToggleProps {
+  class?: string
+}
+
+export function ContrastToggle({ class: className }: ContrastToggleProps) {
+  const { highContrast, toggleHighContrast } = useHighContrast()
+
+  return (
+    <button
+      type="button"
+      onClick={toggleHighContrast}
+      aria-pressed={highContrast}
+      aria-label={t('a11y.highContrast')}
+      title={t('a11y.highContrast')}
+      class={clsx(
+        'p-2 rounded-lg transition-colors duration-200 hover:bg-black/10 dark:hover:bg-white/10',
+        className
+      )}
+    >
+      <svg
+        xmlns="http://www.w3.org/2000/svg"
+        width="20"
+        height="20"
+        viewBox="0 0 24 24"
+        fill="none"
+        stroke="currentColor"
+        stroke-width="2"
+        aria-hidden="true"
+      >
+        <circle cx="12" cy="12" r="9"></circle>
+        <path d="M12 3a9 9 0 0 1 0 18z" fill="currentColor"></path>
+      </svg>
+    </button>
+  )
+}
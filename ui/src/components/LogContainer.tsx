@@ -1,12 +1,16 @@
 import { useEffect, useRef, useState } from 'preact/hooks'
 import { LogEntry } from '@/types'
 import clsx from 'clsx'
+import { t } from '@/i18n'
+import { AnsiText } from './AnsiText'
 
 interface LogContainerProps {
   logs: LogEntry[]
   height?: string
   onCommand?: (command: string) => void
   interactive?: boolean
+  // Shows a clear button when set
+  onClear?: () => void
 }
 
 export function LogContainer({
@@ -14,6 +18,7 @@ export function LogContainer({
   height = 'h-full',
   onCommand,
   interactive = false,
+  onClear,
 }: LogContainerProps) {
   const containerRef = useRef<HTMLDivElement>(null)
   const inputRef = useRef<HTMLInputElement>(null)
@@ -78,10 +83,26 @@ export function LogContainer({
   }
 
   return (
-    <div class={clsx('flex flex-col', height)} onClick={handleContainerClick}>
+    <div class={clsx('relative flex flex-col', height)} onClick={handleContainerClick}>
+      {onClear && logs.length > 0 && (
+        <button
+          type="button"
+          onClick={e => {
+            e.stopPropagation()
+            onClear()
+          }}
+          class="absolute top-2 right-4 z-10 text-xs px-2 py-1 rounded border border-light-surface3 dark:border-dark-surface3 bg-light-surface2 dark:bg-dark-surface2 text-light-textDim dark:text-dark-textDim hover:text-light-text dark:hover:text-dark-text"
+        >
+          {t('console.clear')}
+        </button>
+      )}
       <div
         ref={containerRef}
         onScroll={handleScroll}
+        role="log"
+        aria-live="polite"
+        aria-label={t('a11y.consoleOutput')}
+        tabIndex={0}
         class={clsx(
           'bg-light-bg dark:bg-dark-bg border border-light-surface3 dark:border-dark-surface3 rounded-t p-4 overflow-y-auto text-left font-mono text-sm flex-1 cursor-text max-h-full',
           !interactive && 'rounded-b'
@@ -90,23 +111,32 @@ export function LogContainer({
       >
         {logs.length === 0 ? (
           <div class="text-light-textDim dark:text-dark-textDim italic">
-            {interactive ? '' : 'No logs yet...'}
+            {interactive ? '' : t('console.noLogs')}
           </div>
         ) : (
           logs.map((log, index) => (
-            <div
-              key={index}
-              class={clsx('mb-1 flex justify-between', {
-                'text-light-success dark:text-dark-success': log.type === 'success',
-                'text-light-error dark:text-dark-error': log.type === 'error',
-                'text-light-info dark:text-dark-info': log.type === 'info',
-                'text-light-warning dark:text-dark-warning': log.type === 'warning',
-              })}
-            >
-              <span class="flex-1">{log.message}</span>
-              <span class="text-light-textDim dark:text-dark-textDim text-xs ml-4">
-                {log.timestamp.toLocaleTimeString()}
-              </span>
+            <div key={index}>
+              {index > 0 && logs[index - 1].restored && !log.restored && (
+                <div class="my-2 border-t border-dashed border-light-surface3 dark:border-dark-surface3 text-center text-xs text-light-textDim dark:text-dark-textDim">
+                  {t('console.reloaded')}
+                </div>
+              )}
+              <div
+                class={clsx('mb-1 flex justify-between', {
+                  'opacity-60': log.restored,
+                  'text-light-success dark:text-dark-success': log.type === 'success',
+                  'text-light-error dark:text-dark-error': log.type === 'error',
+                  'text-light-info dark:text-dark-info': log.type === 'info',
+                  'text-light-warning dark:text-dark-warning': log.type === 'warning',
+                })}
+              >
+                <span class="flex-1">
+                  <AnsiText text={log.message} />
+                </span>
+                <span class="text-light-textDim dark:text-dark-textDim text-xs ml-4">
+                  {log.timestamp.toLocaleTimeString()}
+                </span>
+              </div>
             </div>
           ))
         )}
@@ -124,6 +154,7 @@ export function LogContainer({
               value={currentCommand}
               onInput={e => setCurrentCommand((e.target as HTMLInputElement).value)}
               onKeyDown={handleKeyDown}
+              aria-label={t('a11y.command')}
               class="flex-1 bg-transparent text-light-textMuted dark:text-dark-textMuted border-none outline-none font-mono text-sm"
               autoFocus
             />
@@ -0,0 +1,200 @@
+import { useState } from 'preact/hooks'
+import { MemorySample, formatBytes, growthRate } from '@/utils/memory'
+import { t } from '@/i18n'
+
+interface MemoryTimelineProps {
+  samples: MemorySample[]
+  threshold: number | null
+  onThresholdChange: (bytes: number | null) => void
+  onReset: () => void
+}
+
+const WIDTH = 600
+const HEIGHT = 200
+// Growth over this window is shown as the current rate
+const RATE_WINDOW_MS = 30_000
+
+// Points of a polyline plotting `value` against time, scaled to the chart
+function points(
+  samples: MemorySample[],
+  value: (sample: MemorySample) => number | undefined,
+  max: number
+): string {
+  const start = samples[0].time
+  const span = Math.max(samples[samples.length - 1].time - start, 1)
+  return samples
+    .flatMap(sample => {
+      const v = value(sample)
+      if (v === undefined) return []
+      const x = ((sample.time - start) / span) * WIDTH
+      const y = HEIGHT - (v / max) * HEIGHT
+      return [`${x.toFixed(1)},${y.toFixed(1)}`]
+    })
+    .join(' ')
+}
+
+export function MemoryTimeline({
+  samples,
+  threshold,
+  onThresholdChange,
+  onReset,
+}: MemoryTimelineProps) {
+  const [thresholdInput, setThresholdInput] = useState(
+    threshold ? String(threshold / (1024 * 1024)) : ''
+  )
+
+  const last = samples[samples.length - 1]
+  const peak = samples.reduce((max, sample) => Math.max(max, sample.bytes), 0)
+  const rate = growthRate(samples, RATE_WINDOW_MS)
+  const isOver = last !== undefined && threshold !== null && last.bytes > threshold
+  const max = Math.max(peak, threshold ?? 0) * 1.1 || 1
+  const hasHeap = samples.some(sample => sample.heapBytes !== undefined)
+  const hasBindgen = samples.some(sample => sample.bindgenBytes !== undefined)
+
+  const applyThreshold = () => {
+    const mb = parseFloat(thresholdInput)
+    onThresholdChange(mb > 0 ? Math.round(mb * 1024 * 1024) : null)
+  }
+
+  return (
+    <div class="p-6 space-y-4">
+      <div class="flex items-center justify-between">
+        <div>
+          <h2 class="text-2xl font-bold text-light-textPrimary dark:text-dark-textPrimary mb-2">
+            {t('console.heap.title')}
+          </h2>
+          <p class="text-light-textDim dark:text-dark-textDim">{t('console.heap.description')}</p>
+        </div>
+        <button
+          type="button"
+          onClick={onReset}
+          class="px-3 py-1 text-sm rounded bg-light-surface3 dark:bg-dark-surface3 text-light-textMuted dark:text-dark-textMuted hover:text-light-textPrimary dark:hover:text-dark-textPrimary"
+        >
+          {t('console.heap.reset')}
+        </button>
+      </div>
+
+      {!last ? (
+        <p class="text-light-textDim dark:text-dark-textDim">{t('console.heap.waiting')}</p>
+      ) : (
+        <>
+          {isOver && (
+            <div
+              role="alert"
+              class="px-4 py-2 rounded-lg border border-light-warning dark:border-dark-warning text-light-warning dark:text-dark-warning"
+            >
+              {t('console.heap.overThreshold', {
+                size: formatBytes(last.bytes),
+                threshold: formatBytes(threshold ?? 0),
+              })}
+            </div>
+          )}
+
+          <dl class="grid grid-cols-2 md:grid-cols-4 gap-4">
+            {[
+              [t('console.heap.current'), formatBytes(last.bytes)],
+              [t('console.heap.peak'), formatBytes(peak)],
+              [
+                t('console.heap.growth'),
+                rate === null ? '—' : `${rate > 0 ? '+' : ''}${formatBytes(Math.round(rate))}/s`,
+              ],
+              last.heapBytes !== undefined
+                ? [t('console.heap.heap'), formatBytes(last.heapBytes)]
+                : [t('console.heap.pages'), String(last.bytes / (64 * 1024))],
+            ].map(([label, value]) => (
+              <div
+                key={label}
+                class="p-3 bg-light-surface2 dark:bg-dark-surface2 rounded-lg border border-light-surface3 dark:border-dark-surface3"
+              >
+                <dt class="text-xs text-light-textDim dark:text-dark-textDim">{label}</dt>
+                <dd class="font-mono text-lg text-light-textPrimary dark:text-dark-textPrimary">
+                  {value}
+                </dd>
+              </div>
+            ))}
+          </dl>
+
+          <svg
+            viewBox={`0 0 ${WIDTH} ${HEIGHT}`}
+            preserveAspectRatio="none"
+            role="img"
+            aria-label={t('console.heap.chart')}
+            class="w-full h-48 bg-light-surface2 dark:bg-dark-surface2 rounded-lg border border-light-surface3 dark:border-dark-surface3"
+          >
+            {threshold !== null && (
+              <line
+                x1="0"
+                x2={WIDTH}
+                y1={HEIGHT - (threshold / max) * HEIGHT}
+                y2={HEIGHT - (threshold / max) * HEIGHT}
+                stroke="#f59e0b"
+                stroke-dasharray="6 4"
+                vector-effect="non-scaling-stroke"
+              />
+            )}
+            <polyline
+              points={points(samples, sample => sample.bytes, max)}
+              fill="none"
+              stroke="#60a5fa"
+              stroke-width="2"
+              vector-effect="non-scaling-stroke"
+            />
+            {hasHeap && (
+              <polyline
+                points={points(samples, sample => sample.heapBytes, max)}
+                fill="none"
+                stroke="#4ade80"
+                stroke-width="1.5"
+                vector-effect="non-scaling-stroke"
+              />
+            )}
+            {hasBindgen && (
+              <polyline
+                points={points(samples, sample => sample.bindgenBytes, max)}
+                fill="none"
+                stroke="#c084fc"
+                stroke-width="1.5"
+                vector-effect="non-scaling-stroke"
+              />
+            )}
+          </svg>
+
+          <div class="flex flex-wrap gap-4 text-xs text-light-textDim dark:text-dark-textDim">
+            <span style={{ color: '#60a5fa' }}>■ {t('console.heap.legend.memory')}</span>
+            {hasHeap && <span style={{ color: '#4ade80' }}>■ {t('console.heap.legend.heap')}</span>}
+            {hasBindgen && (
+              <span style={{ color: '#c084fc' }}>■ {t('console.heap.legend.bindgen')}</span>
+            )}
+            {threshold !== null && (
+              <span style={{ color: '#f59e0b' }}>┄ {t('console.heap.legend.threshold')}</span>
+            )}
+          </div>
+        </>
+      )}
+
+      <form
+        class="flex items-center gap-2 text-sm"
+        onSubmit={e => {
+          e.preventDefault()
+          applyThreshold()
+        }}
+      >
+        <label for="memory-threshold" class="text-light-textMuted dark:text-dark-textMuted">
+          {t('console.heap.thresholdLabel')}
+        </label>
+        <input
+          id="memory-threshold"
+          type="number"
+          min="0"
+          step="any"
+          value={thresholdInput}
+          onInput={e => setThresholdInput((e.target as HTMLInputElement).value)}
+          onBlur={applyThreshold}
+          placeholder={t('console.heap.thresholdPlaceholder')}
+          class="w-28 px-2 py-1 rounded bg-light-surface3 dark:bg-dark-surface3 text-light-textPrimary dark:text-dark-textPrimary font-mono"
+        />
+        <span class="text-light-textDim dark:text-dark-textDim">MB</span>
+      </form>
+    </div>
+  )
+}
@@ -3,7 +3,9 @@ import {
   ModuleDetailsCard,
   ExportsCard,
   ImportsCard,
+  MemoryTablesCard,
   PluginCard,
+  SizeBreakdownCard,
   WasiSupportCard,
 } from '@/components/modules'
 
@@ -40,6 +42,16 @@ export function ModuleInfo({ moduleInfo }: ModuleInfoProps) {
           <ImportsCard moduleInfo={moduleInfo} />
         </div>
 
+        {/* Memories, Tables and Globals */}
+        <div class="md:col-span-1 xl:col-span-1">
+          <MemoryTablesCard moduleInfo={moduleInfo} />
+        </div>
+
+        {/* Section and Function Sizes */}
+        <div class="md:col-span-2 xl:col-span-2">
+          <SizeBreakdownCard moduleInfo={moduleInfo} />
+        </div>
+
         {/* Plugin Info */}
         <div class="md:col-span-1 xl:col-span-1">
           <PluginCard moduleInfo={moduleInfo} />
@@ -0,0 +1,54 @@
+import { useEffect, useRef } from 'preact/hooks'
+import { PluginPanelInfo } from '@/hooks/usePluginPanels'
+import { apiUrl } from '@/utils/api'
+
+interface PluginPanelProps {
+  panel: PluginPanelInfo
+}
+
+/** The `panel` object a plugin's scripts are given */
+export interface PanelContext {
+  root: HTMLElement
+  /** `fetch` against the plugin's routes under `/api/plugin/<name>/` */
+  api: (path: string, init?: RequestInit) => Promise<Response>
+}
+
+declare global {
+  interface Window {
+    __wasmrun_panels?: Record<string, PanelContext>
+  }
+}
+
+/**
+ * A plugin's HTML fragment. Scripts inserted with innerHTML don't run, so
+ * each one is re-created, wrapped to receive this panel's `panel` object.
+ */
+export function PluginPanel({ panel }: PluginPanelProps) {
+  const root = useRef<HTMLDivElement>(null)
+
+  useEffect(() => {
+    const element = root.current
+    if (!element) return
+    const key = `${panel.plugin}/${panel.id}`
+    const base = `/api/plugin/${encodeURIComponent(panel.plugin)}/`
+    window.__wasmrun_panels = window.__wasmrun_panels ?? {}
+    window.__wasmrun_panels[key] = {
+      root: element,
+      api: (path, init) => fetch(apiUrl(base + path.replace(/^\//, '')), init),
+    }
+
+    element.innerHTML = panel.html
+    element.querySelectorAll('script').forEach(original => {
+      const script = document.createElement('script')
+      script.textContent = `(function (panel) {\n${original.textContent ?? ''}\n})(window.__wasmrun_panels[${JSON.stringify(key)}])`
+      original.replaceWith(script)
+    })
+
+    return () => {
+      element.innerHTML = ''
+      delete window.__wasmrun_panels?.[key]
+    }
+  }, [panel])
+
+  return <div ref={root} class="text-light-textPrimary dark:text-dark-textPrimary" />
+}
@@ -0,0 +1,49 @@
+import { useState, useEffect } from 'preact/hooks'
+import { t } from '@/i18n'
+import { apiUrl } from '@/utils/api'
+
+interface FileChange {
+  path: string
+  sha256: string | null
+}
+
+// Shows which edits a watch rebuild picked up, from the build manifest's
+// `rebuilt_for`, so behavior changes can be matched to the files behind them
+export function RebuildNotice() {
+  const [changes, setChanges] = useState<FileChange[]>([])
+
+  useEffect(() => {
+    fetch(apiUrl('/api/build/manifest'))
+      .then(response => (response.ok ? response.json() : null))
+      .then(manifest => setChanges(manifest?.rebuilt_for ?? []))
+      .catch(() => setChanges([]))
+  }, [])
+
+  if (changes.length === 0) return null
+
+  const details = changes
+    .map(change => `${change.path}  ${change.sha256?.slice(0, 12) ?? t('rebuild.deleted')}`)
+    .join('\n')
+
+  return (
+    <div
+      role="status"
+      class="fixed bottom-12 right-4 z-40 max-w-md flex items-start gap-3 px-4 py-3 rounded-lg shadow-lg bg-light-surface2 dark:bg-dark-surface2 border border-light-surface3 dark:border-dark-surface3 text-sm"
+    >
+      <span title={details}>
+        {t('rebuild.dueTo')}{' '}
+        <span class="font-mono text-light-accent2 dark:text-dark-accent">
+          {changes.map(change => change.path).join(', ')}
+        </span>
+      </span>
+      <button
+        type="button"
+        onClick={() => setChanges([])}
+        aria-label={t('rebuild.dismiss')}
+        class="text-light-textDim dark:text-dark-textDim hover:text-light-text dark:hover:text-dark-text"
+      >
+        ×
+      </button>
+    </div>
+  )
+}
@@ -1,26 +1,59 @@
+import { useState } from 'preact/hooks'
 import { StatusMessage } from '@/types'
+import { WebGpuStatus } from '@/utils/webgpu'
+import { WebGpuPanel } from '@/components/WebGpuPanel'
 import clsx from 'clsx'
+import { t } from '@/i18n'
 
 interface StatusBarProps {
   status: StatusMessage
+  // Set when the module imports WebGPU bindings
+  webgpu?: WebGpuStatus | null
 }
 
-export function StatusBar({ status }: StatusBarProps) {
+export function StatusBar({ status, webgpu }: StatusBarProps) {
+  // Until toggled, the panel is open only when WebGPU is missing, since the
+  // app will likely fail without it
+  const [showGpu, setShowGpu] = useState<boolean | null>(null)
+  const gpuPanelOpen = webgpu ? (showGpu ?? !webgpu.available) : false
+
   return (
-    <div class="bg-light-surface2 dark:bg-dark-surface2 px-4 py-2 flex items-center justify-between border-b border-light-surface3 dark:border-dark-surface3">
-      <div
-        class={clsx('flex items-center', {
-          'text-light-success dark:text-dark-success': status.type === 'success',
-          'text-light-error dark:text-dark-error': status.type === 'error',
-          'text-light-info dark:text-dark-info': status.type === 'info',
-          'text-light-warning dark:text-dark-warning': status.type === 'warning',
-        })}
-      >
-        {status.type === 'info' && (
-          <div class="inline-block w-5 h-5 border-2 border-light-textMuted/30 dark:border-white/30 border-t-light-textMuted dark:border-t-white rounded-full animate-spin mr-2"></div>
+    <>
+      <div class="bg-light-surface2 dark:bg-dark-surface2 px-4 py-2 flex items-center justify-between border-b border-light-surface3 dark:border-dark-surface3">
+        <div
+          class={clsx('flex items-center', {
+            'text-light-success dark:text-dark-success': status.type === 'success',
+            'text-light-error dark:text-dark-error': status.type === 'error',
+            'text-light-info dark:text-dark-info': status.type === 'info',
+            'text-light-warning dark:text-dark-warning': status.type === 'warning',
+          })}
+        >
+          {status.type === 'info' && (
+            <div class="inline-block w-5 h-5 border-2 border-light-textMuted/30 dark:border-white/30 border-t-light-textMuted dark:border-t-white rounded-full animate-spin mr-2"></div>
+          )}
+          <span>{status.message}</span>
+        </div>
+
+        {webgpu && (
+          <button
+            type="button"
+            onClick={() => setShowGpu(!gpuPanelOpen)}
+            title={t('webgpu.details')}
+            class={clsx(
+              'text-xs px-2 py-1 rounded-full border',
+              webgpu.available
+                ? 'border-light-success dark:border-dark-success text-light-success dark:text-dark-success'
+                : 'border-light-warning dark:border-dark-warning text-light-warning dark:text-dark-warning'
+            )}
+          >
+            {webgpu.available
+              ? t('webgpu.available', { vendor: webgpu.adapter.vendor })
+              : t('webgpu.unavailable')}
+          </button>
         )}
-        <span>{status.message}</span>
       </div>
-    </div>
+
+      {webgpu && gpuPanelOpen && <WebGpuPanel status={webgpu} />}
+    </>
   )
 }
@@ -1,6 +1,7 @@
 import { useState } from 'preact/hooks'
 import { TabItem } from '@/types'
 import clsx from 'clsx'
+import { moveFocus } from '@/utils/a11y'
 
 interface TabsProps {
   tabs: TabItem[]
@@ -12,13 +13,28 @@ export function Tabs({ tabs, defaultTab }: TabsProps) {
 
   const activeTabContent = tabs.find(tab => tab.id === activeTab)?.content
 
+  const handleKeyDown = (e: KeyboardEvent) => {
+    const target = moveFocus(e, 'horizontal')
+    if (target?.dataset.tab) setActiveTab(target.dataset.tab)
+  }
+
   return (
     <div class="max-w-4xl mx-auto my-6 rounded-lg overflow-hidden bg-dark-surface border border-dark-surface3">
-      <div class="flex bg-dark-surface2 border-b border-dark-surface3">
+      <div
+        role="tablist"
+        data-roving-group
+        class="flex bg-dark-surface2 border-b border-dark-surface3"
+      >
         {tabs.map(tab => (
           <button
             key={tab.id}
+            role="tab"
+            aria-selected={activeTab === tab.id}
+            tabIndex={activeTab === tab.id ? 0 : -1}
+            data-roving
+            data-tab={tab.id}
             onClick={() => !tab.disabled && setActiveTab(tab.id)}
+            onKeyDown={handleKeyDown}
             class={clsx('px-6 py-3 text-sm font-medium transition-colors duration-200', {
               'bg-dark-surface border-b-2 border-dark-accent text-dark-textMuted':
                 activeTab === tab.id,
@@ -33,7 +49,9 @@ export function Tabs({ tabs, defaultTab }: TabsProps) {
       </div>
 
       <div class="relative min-h-96">
-        <div class="p-4">{activeTabContent}</div>
+        <div role="tabpanel" class="p-4">
+          {activeTabContent}
+        </div>
       </div>
     </div>
   )
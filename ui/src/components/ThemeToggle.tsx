@@ -1,13 +1,16 @@
 import { useTheme } from '@/contexts/ThemeContext'
+import { t } from '@/i18n'
 
 export function ThemeToggle() {
   const { theme, toggleTheme } = useTheme()
+  const label = t(theme === 'dark' ? 'a11y.themeToLight' : 'a11y.themeToDark')
 
   return (
     <button
       onClick={toggleTheme}
       class="p-2 rounded-lg transition-colors duration-200 hover:bg-black/10 dark:hover:bg-white/10 focus:outline-none focus:ring-2 focus:ring-light-accent2 dark:focus:ring-dark-accent2"
-      title={`Switch to ${theme === 'dark' ? 'light' : 'dark'} mode`}
+      title={label}
+      aria-label={label}
     >
       {theme === 'dark' ? (
         // Sun icon for light mode
@@ -0,0 +1,89 @@
+import { PerfEntry } from '@/hooks/usePerfEntries'
+import { t } from '@/i18n'
+
+interface TimingPanelProps {
+  entries: PerfEntry[]
+}
+
+const TYPE_COLORS: Record<PerfEntry['entry_type'], string> = {
+  mark: '#facc15',
+  measure: '#60a5fa',
+  navigation: '#4ade80',
+  resource: '#c084fc',
+}
+
+const ms = (value: number) => `${value.toFixed(1)} ms`
+
+// performance.mark/measure entries and load milestones of this page, as a
+// waterfall on a shared time axis
+export function TimingPanel({ entries }: TimingPanelProps) {
+  const sorted = [...entries].sort((a, b) => a.start_time - b.start_time)
+  const end = Math.max(1, ...sorted.map(entry => entry.start_time + entry.duration))
+
+  return (
+    <div class="p-6 space-y-4">
+      <div>
+        <h2 class="text-2xl font-bold text-light-textPrimary dark:text-dark-textPrimary mb-2">
+          {t('console.timing.title')}
+        </h2>
+        <p class="text-light-textDim dark:text-dark-textDim">{t('console.timing.description')}</p>
+      </div>
+
+      {sorted.length === 0 ? (
+        <p class="text-light-textDim dark:text-dark-textDim">{t('console.timing.empty')}</p>
+      ) : (
+        <table class="w-full text-sm">
+          <thead>
+            <tr class="text-left text-light-textDim dark:text-dark-textDim">
+              <th class="py-1 pr-4 font-medium">{t('console.timing.name')}</th>
+              <th class="py-1 pr-4 font-medium text-right">{t('console.timing.start')}</th>
+              <th class="py-1 pr-4 font-medium text-right">{t('console.timing.duration')}</th>
+              <th class="py-1 font-medium w-1/2"></th>
+            </tr>
+          </thead>
+          <tbody>
+            {sorted.map((entry, i) => (
+              <tr
+                key={i}
+                class="border-t border-light-surface3 dark:border-dark-surface3 text-light-textPrimary dark:text-dark-textPrimary"
+              >
+                <td class="py-1 pr-4 font-mono break-all">
+                  <span style={{ color: TYPE_COLORS[entry.entry_type] }} title={entry.entry_type}>
+                    ●
+                  </span>{' '}
+                  {entry.name}
+                </td>
+                <td class="py-1 pr-4 font-mono text-right whitespace-nowrap">
+                  {ms(entry.start_time)}
+                </td>
+                <td class="py-1 pr-4 font-mono text-right whitespace-nowrap">
+                  {entry.entry_type === 'mark' ? '—' : ms(entry.duration)}
+                </td>
+                <td class="py-1">
+                  <div class="relative h-3 bg-light-surface2 dark:bg-dark-surface2 rounded">
+                    <div
+                      class="absolute h-3 rounded"
+                      style={{
+                        left: `${(entry.start_time / end) * 100}%`,
+                        width:
+                          entry.entry_type === 'mark'
+                            ? '2px'
+                            : `${Math.max((entry.duration / end) * 100, 0.5)}%`,
+                        backgroundColor: TYPE_COLORS[entry.entry_type],
+                      }}
+                    />
+                  </div>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+
+      <p class="text-xs text-light-textDim dark:text-dark-textDim">
+        {t('console.timing.apiHint')}{' '}
+        <code class="font-mono">/api/perf?budget=navigation:load:1000,wasmrun:ready:500</code>
+      </p>
+    </div>
+  )
+}
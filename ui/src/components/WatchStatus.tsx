@@ -0,0 +1,66 @@
+import { useState, useEffect } from 'preact/hooks'
+import { t } from '@/i18n'
+import { apiUrl } from '@/utils/api'
+
+interface WatchInfo {
+  watching: boolean
+  paused: boolean
+}
+
+const POLL_MS = 2000
+
+// Shows whether watch mode is rebuilding or paused, and pauses or resumes
+// it through `/api/watch/pause` and `/api/watch/resume`. Pausing from the
+// terminal shows up on the next poll.
+export function WatchStatus() {
+  const [watch, setWatch] = useState<WatchInfo | null>(null)
+
+  useEffect(() => {
+    let timer: ReturnType<typeof setTimeout>
+    const poll = () => {
+      fetch(apiUrl('/api/watch'), { cache: 'no-store' })
+        .then(response => (response.ok ? response.json() : null))
+        .then((info: WatchInfo | null) => {
+          setWatch(info)
+          if (info?.watching) timer = setTimeout(poll, POLL_MS)
+        })
+        .catch(() => setWatch(null))
+    }
+    poll()
+    return () => clearTimeout(timer)
+  }, [])
+
+  if (!watch?.watching) return null
+
+  const toggle = () => {
+    const action = watch.paused ? 'resume' : 'pause'
+    fetch(apiUrl(`/api/watch/${action}`), { method: 'POST' })
+      .then(response => (response.ok ? response.json() : null))
+      .then((info: WatchInfo | null) => info && setWatch(info))
+      .catch(() => {})
+  }
+
+  return (
+    <div
+      role="status"
+      class="fixed bottom-12 left-4 z-40 flex items-center gap-3 px-3 py-2 rounded-lg shadow-lg bg-light-surface2 dark:bg-dark-surface2 border border-light-surface3 dark:border-dark-surface3 text-sm"
+    >
+      <span
+        class={
+          watch.paused
+            ? 'text-light-accent2 dark:text-dark-accent'
+            : 'text-light-textDim dark:text-dark-textDim'
+        }
+      >
+        {watch.paused ? t('watch.paused') : t('watch.watching')}
+      </span>
+      <button
+        type="button"
+        onClick={toggle}
+        class="font-semibold text-light-text dark:text-dark-text hover:underline"
+      >
+        {watch.paused ? t('watch.resume') : t('watch.pause')}
+      </button>
+    </div>
+  )
+}
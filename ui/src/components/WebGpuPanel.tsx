@@ -0,0 +1,78 @@
+import { Fragment } from 'preact'
+import { WebGpuStatus, webGpuGuidance } from '@/utils/webgpu'
+
+interface WebGpuPanelProps {
+  status: WebGpuStatus
+}
+
+export function WebGpuPanel({ status }: WebGpuPanelProps) {
+  const guidance = webGpuGuidance(status)
+
+  return (
+    <div class="px-4 py-3 bg-light-surface dark:bg-dark-surface border-b border-light-surface3 dark:border-dark-surface3 text-sm">
+      {status.available ? (
+        <div class="grid grid-cols-1 md:grid-cols-2 gap-4">
+          <div>
+            <h4 class="font-semibold text-light-text dark:text-dark-text mb-2">GPU Adapter</h4>
+            <dl class="grid grid-cols-[max-content_1fr] gap-x-3 gap-y-1">
+              <dt class="text-light-textDim dark:text-dark-textDim">Vendor</dt>
+              <dd>{status.adapter.vendor}</dd>
+              {status.adapter.architecture && (
+                <>
+                  <dt class="text-light-textDim dark:text-dark-textDim">Architecture</dt>
+                  <dd>{status.adapter.architecture}</dd>
+                </>
+              )}
+              {(status.adapter.description || status.adapter.device) && (
+                <>
+                  <dt class="text-light-textDim dark:text-dark-textDim">Device</dt>
+                  <dd>{status.adapter.description || status.adapter.device}</dd>
+                </>
+              )}
+              <dt class="text-light-textDim dark:text-dark-textDim">Fallback</dt>
+              <dd>{status.adapter.isFallbackAdapter ? 'yes (software)' : 'no'}</dd>
+            </dl>
+          </div>
+          <div>
+            <h4 class="font-semibold text-light-text dark:text-dark-text mb-2">Limits</h4>
+            <dl class="grid grid-cols-[max-content_1fr] gap-x-3 gap-y-1 font-mono text-xs">
+              {Object.entries(status.adapter.limits).map(([key, value]) => (
+                <Fragment key={key}>
+                  <dt class="text-light-textDim dark:text-dark-textDim">{key}</dt>
+                  <dd>{value.toLocaleString()}</dd>
+                </Fragment>
+              ))}
+            </dl>
+          </div>
+          {status.adapter.features.length > 0 && (
+            <div class="md:col-span-2">
+              <h4 class="font-semibold text-light-text dark:text-dark-text mb-2">Features</h4>
+              <div class="flex flex-wrap gap-1">
+                {status.adapter.features.map(feature => (
+                  <code
+                    key={feature}
+                    class="px-2 py-0.5 rounded bg-light-surface3 dark:bg-dark-surface3 text-xs"
+                  >
+                    {feature}
+                  </code>
+                ))}
+              </div>
+            </div>
+          )}
+        </div>
+      ) : (
+        <h4 class="font-semibold text-light-warning dark:text-dark-warning mb-2">
+          This module uses WebGPU, which isn't available in this browser
+        </h4>
+      )}
+
+      {guidance.length > 0 && (
+        <ul class="mt-2 list-disc pl-5 space-y-1 text-light-textMuted dark:text-dark-textMuted">
+          {guidance.map((step, idx) => (
+            <li key={idx}>{step}</li>
+          ))}
+        </ul>
+      )}
+    </div>
+  )
+}
@@ -16,13 +16,35 @@ export function ExportsCard({ moduleInfo }: ExportsCardProps) {
         </div>
         {moduleInfo && (
           <span class="text-xs bg-light-surface3 dark:bg-dark-surface3 px-2 py-1 rounded-full text-light-textDim dark:text-dark-textDim">
-            {moduleInfo.exports?.length || 0}
+            {moduleInfo.details?.exports.length ?? moduleInfo.exports?.length ?? 0}
           </span>
         )}
       </div>
 
       <div class="overflow-y-auto">
-        {moduleInfo?.exports && moduleInfo.exports.length > 0 ? (
+        {moduleInfo?.details && moduleInfo.details.exports.length > 0 ? (
+          <div class="space-y-2">
+            {moduleInfo.details.exports.map((exp, idx) => (
+              <div key={idx} class="p-2 bg-light-surface3 dark:bg-dark-surface3 rounded-lg">
+                <div class="flex items-center">
+                  <span class="w-2 h-2 bg-green-400 rounded-full mr-3 flex-shrink-0"></span>
+                  <code class="font-mono text-sm text-light-textPrimary dark:text-dark-textPrimary">
+                    {exp.name}
+                  </code>
+                  <span class="ml-auto text-xs text-light-textDim dark:text-dark-textDim">
+                    {exp.kind}
+                  </span>
+                </div>
+                {(exp.signature || exp.function) && (
+                  <div class="ml-5 mt-1 font-mono text-xs text-light-textDim dark:text-dark-textDim break-all">
+                    {exp.function && <span>{exp.function} </span>}
+                    {exp.signature}
+                  </div>
+                )}
+              </div>
+            ))}
+          </div>
+        ) : moduleInfo?.exports && moduleInfo.exports.length > 0 ? (
           <div class="space-y-2">
             {moduleInfo.exports.map((exp, idx) => (
               <div
@@ -16,13 +16,34 @@ export function ImportsCard({ moduleInfo }: ImportsCardProps) {
         </div>
         {moduleInfo && (
           <span class="text-xs bg-light-surface3 dark:bg-dark-surface3 px-2 py-1 rounded-full text-light-textDim dark:text-dark-textDim">
-            {moduleInfo.imports?.length || 0}
+            {moduleInfo.details?.imports.length ?? moduleInfo.imports?.length ?? 0}
           </span>
         )}
       </div>
 
       <div class="overflow-y-auto">
-        {moduleInfo?.imports && moduleInfo.imports.length > 0 ? (
+        {moduleInfo?.details && moduleInfo.details.imports.length > 0 ? (
+          <div class="space-y-2">
+            {moduleInfo.details.imports.map((imp, idx) => (
+              <div key={idx} class="p-2 bg-light-surface3 dark:bg-dark-surface3 rounded-lg">
+                <div class="flex items-center">
+                  <span class="w-2 h-2 bg-purple-400 rounded-full mr-3 flex-shrink-0"></span>
+                  <code class="font-mono text-sm text-light-textPrimary dark:text-dark-textPrimary break-all">
+                    {imp.module}.{imp.name}
+                  </code>
+                  <span class="ml-auto pl-2 text-xs text-light-textDim dark:text-dark-textDim">
+                    {imp.kind}
+                  </span>
+                </div>
+                {imp.signature && (
+                  <div class="ml-5 mt-1 font-mono text-xs text-light-textDim dark:text-dark-textDim">
+                    {imp.signature}
+                  </div>
+                )}
+              </div>
+            ))}
+          </div>
+        ) : moduleInfo?.imports && moduleInfo.imports.length > 0 ? (
           <div class="space-y-2">
             {moduleInfo.imports.map((imp, idx) => (
               <div
@@ -0,0 +1,82 @@
+import { ModuleLimitsDetail, WasmModuleInfo } from '@/types'
+
+interface MemoryTablesCardProps {
+  moduleInfo: WasmModuleInfo | null
+}
+
+function formatLimits(limits: ModuleLimitsDetail, unit: string): string {
+  const max = limits.max === null ? 'unbounded' : `${limits.max}`
+  return `${limits.initial} → ${max} ${unit}`
+}
+
+export function MemoryTablesCard({ moduleInfo }: MemoryTablesCardProps) {
+  const details = moduleInfo?.details
+
+  const rows = details
+    ? [
+        ...details.memories.map(mem => ({
+          label: 'memory',
+          limits: formatLimits(mem, 'pages'),
+          importedFrom: mem.imported_from,
+        })),
+        ...details.tables.map(table => ({
+          label: `table (${table.element_type ?? 'unknown'})`,
+          limits: formatLimits(table, 'elements'),
+          importedFrom: table.imported_from,
+        })),
+      ]
+    : []
+
+  return (
+    <div class="bg-light-surface2 dark:bg-dark-surface2 rounded-xl p-6 border border-light-surface3 dark:border-dark-surface3 h-full">
+      <div class="flex items-center mb-4">
+        <div class="w-3 h-3 bg-orange-500 rounded-full mr-3"></div>
+        <h3 class="text-lg font-semibold text-light-textPrimary dark:text-dark-textPrimary">
+          Memories & Tables
+        </h3>
+      </div>
+
+      {details ? (
+        <div class="space-y-2">
+          {rows.map((row, idx) => (
+            <div key={idx} class="p-2 bg-light-surface3 dark:bg-dark-surface3 rounded-lg">
+              <div class="flex items-center justify-between">
+                <span class="text-sm font-medium text-light-textPrimary dark:text-dark-textPrimary">
+                  {row.label}
+                </span>
+                <code class="font-mono text-xs text-light-textDim dark:text-dark-textDim">
+                  {row.limits}
+                </code>
+              </div>
+              {row.importedFrom && (
+                <div class="mt-1 text-xs text-light-textDim dark:text-dark-textDim">
+                  imported from <code class="font-mono">{row.importedFrom}</code>
+                </div>
+              )}
+            </div>
+          ))}
+          {rows.length === 0 && (
+            <p class="text-light-textDim dark:text-dark-textDim text-sm">
+              No memories or tables declared
+            </p>
+          )}
+          <div class="flex justify-between pt-2 text-sm">
+            <span class="text-light-textDim dark:text-dark-textDim">Globals</span>
+            <span class="text-light-textPrimary dark:text-dark-textPrimary">{details.globals}</span>
+          </div>
+          <div class="flex justify-between text-sm">
+            <span class="text-light-textDim dark:text-dark-textDim">Start function</span>
+            <span class="text-light-textPrimary dark:text-dark-textPrimary">
+              {details.start === null ? 'none' : `#${details.start}`}
+            </span>
+          </div>
+        </div>
+      ) : (
+        <div class="text-center py-4">
+          <span class="text-2xl mb-2 block">⏳</span>
+          <p class="text-light-textDim dark:text-dark-textDim text-sm">Loading module layout...</p>
+        </div>
+      )}
+    </div>
+  )
+}
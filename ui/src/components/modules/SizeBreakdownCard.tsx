@@ -0,0 +1,114 @@
+import { WasmModuleInfo } from '@/types'
+import { formatBytes } from '@/utils/wasm'
+
+interface SizeBreakdownCardProps {
+  moduleInfo: WasmModuleInfo | null
+}
+
+export function SizeBreakdownCard({ moduleInfo }: SizeBreakdownCardProps) {
+  const details = moduleInfo?.details
+
+  if (!details) {
+    return (
+      <div class="bg-light-surface2 dark:bg-dark-surface2 rounded-xl p-6 border border-light-surface3 dark:border-dark-surface3 h-full">
+        <div class="text-center py-4">
+          <span class="text-2xl mb-2 block">⏳</span>
+          <p class="text-light-textDim dark:text-dark-textDim text-sm">Loading size breakdown...</p>
+        </div>
+      </div>
+    )
+  }
+
+  const total = details.file_size || 1
+  const sections = [...details.sections].sort((a, b) => b.size - a.size)
+  const customCount = details.sections.filter(section => section.custom).length
+
+  return (
+    <div class="bg-light-surface2 dark:bg-dark-surface2 rounded-xl p-6 border border-light-surface3 dark:border-dark-surface3 h-full">
+      <div class="flex items-center justify-between mb-4">
+        <div class="flex items-center">
+          <div class="w-3 h-3 bg-cyan-500 rounded-full mr-3"></div>
+          <h3 class="text-lg font-semibold text-light-textPrimary dark:text-dark-textPrimary">
+            Size Breakdown
+          </h3>
+        </div>
+        <span class="text-xs bg-light-surface3 dark:bg-dark-surface3 px-2 py-1 rounded-full text-light-textDim dark:text-dark-textDim">
+          {formatBytes(details.file_size)}
+        </span>
+      </div>
+
+      <div class="grid grid-cols-1 lg:grid-cols-2 gap-6">
+        <div>
+          <h4 class="text-sm font-medium text-light-textMuted dark:text-dark-textMuted mb-2">
+            Sections
+            {customCount > 0 && (
+              <span class="ml-2 text-xs text-light-textDim dark:text-dark-textDim">
+                ({customCount} custom)
+              </span>
+            )}
+          </h4>
+          <div class="space-y-2">
+            {sections.map((section, idx) => {
+              const percent = (section.size / total) * 100
+              return (
+                <div key={idx}>
+                  <div class="flex justify-between text-xs mb-1">
+                    <span class="text-light-textPrimary dark:text-dark-textPrimary">
+                      {section.custom ? (
+                        <>
+                          <code class="font-mono">{section.name}</code>
+                          <span class="ml-1 text-light-textDim dark:text-dark-textDim">
+                            (custom)
+                          </span>
+                        </>
+                      ) : (
+                        section.name
+                      )}
+                    </span>
+                    <span class="text-light-textDim dark:text-dark-textDim">
+                      {formatBytes(section.size)} · {percent.toFixed(1)}%
+                    </span>
+                  </div>
+                  <div class="h-1.5 bg-light-surface3 dark:bg-dark-surface3 rounded-full">
+                    <div
+                      class={`h-1.5 rounded-full ${section.custom ? 'bg-gray-400' : 'bg-cyan-500'}`}
+                      style={{ width: `${Math.max(percent, 0.5)}%` }}
+                    ></div>
+                  </div>
+                </div>
+              )
+            })}
+          </div>
+        </div>
+
+        <div>
+          <h4 class="text-sm font-medium text-light-textMuted dark:text-dark-textMuted mb-2">
+            Largest functions
+            <span class="ml-2 text-xs text-light-textDim dark:text-dark-textDim">
+              ({details.functions.defined} defined, {details.functions.imported} imported)
+            </span>
+          </h4>
+          {details.functions.largest.length > 0 ? (
+            <div class="space-y-1">
+              {details.functions.largest.map(func => (
+                <div
+                  key={func.index}
+                  class="flex justify-between items-center p-2 bg-light-surface3 dark:bg-dark-surface3 rounded-lg"
+                >
+                  <code class="font-mono text-xs text-light-textPrimary dark:text-dark-textPrimary truncate mr-2">
+                    {func.name}
+                  </code>
+                  <span class="text-xs text-light-textDim dark:text-dark-textDim flex-shrink-0">
+                    {formatBytes(func.size)}
+                  </span>
+                </div>
+              ))}
+            </div>
+          ) : (
+            <p class="text-light-textDim dark:text-dark-textDim text-sm">No function bodies</p>
+          )}
+        </div>
+      </div>
+    </div>
+  )
+}
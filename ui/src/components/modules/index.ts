@@ -3,3 +3,5 @@ export { ExportsCard } from './ExportsCard'
 export { ImportsCard } from './ImportsCard'
 export { PluginCard } from './PluginCard'
 export { WasiSupportCard } from './WasiSupportCard'
+export { MemoryTablesCard } from './MemoryTablesCard'
+export { SizeBreakdownCard } from './SizeBreakdownCard'
@@ -57,20 +57,26 @@ export default function ApplicationPanel({
         <div className="bg-black/30 backdrop-blur-lg border border-green-500/30 rounded-xl p-6">
           <div className="flex items-center justify-between mb-4">
             <h3 className="text-lg font-semibold text-green-400">🏃‍♂️ Runtime Environment</h3>
-            <div className="flex gap-3">
-              <button
-                onClick={handleStart}
-                className="px-4 py-2 bg-green-600/80 hover:bg-green-600 backdrop-blur-sm border border-green-400/30 rounded-lg font-medium transition-all"
-              >
-                ▶️ Start
-              </button>
-              <button
-                onClick={handleRestart}
-                className="px-4 py-2 bg-yellow-600/80 hover:bg-yellow-600 backdrop-blur-sm border border-yellow-400/30 rounded-lg font-medium transition-all"
-              >
-                🔄 Restart
-              </button>
-            </div>
+            {kernelStats?.read_only ? (
+              <span className="px-3 py-1 bg-gray-600/40 border border-gray-400/30 rounded-lg text-sm text-white/70">
+                🔒 Read-only
+              </span>
+            ) : (
+              <div className="flex gap-3">
+                <button
+                  onClick={handleStart}
+                  className="px-4 py-2 bg-green-600/80 hover:bg-green-600 backdrop-blur-sm border border-green-400/30 rounded-lg font-medium transition-all"
+                >
+                  ▶️ Start
+                </button>
+                <button
+                  onClick={handleRestart}
+                  className="px-4 py-2 bg-yellow-600/80 hover:bg-yellow-600 backdrop-blur-sm border border-yellow-400/30 rounded-lg font-medium transition-all"
+                >
+                  🔄 Restart
+                </button>
+              </div>
+            )}
           </div>
           <div className="bg-black/60 backdrop-blur-sm border border-green-500/20 p-4 rounded-lg font-mono text-sm">
             <div className="text-white/70">Runtime initializing {language}...</div>
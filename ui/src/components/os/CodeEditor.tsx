@@ -0,0 +1,70 @@
+import { useMemo, useRef } from 'preact/hooks'
+import { highlight, languageFor } from '../../utils/highlight'
+
+interface CodeEditorProps {
+  path: string
+  value: string
+  editing: boolean
+  onChange: (value: string) => void
+  onSave: () => void
+}
+
+const textClasses = 'font-mono text-sm leading-6 p-4 whitespace-pre'
+
+/**
+ * A transparent textarea over a highlighted copy of its text, so typing
+ * stays native while the view is coloured by the file's extension.
+ */
+export default function CodeEditor({ path, value, editing, onChange, onSave }: CodeEditorProps) {
+  const highlightRef = useRef<HTMLPreElement>(null)
+  const language = languageFor(path)
+  // A trailing newline needs a character after it to get its own line
+  const html = useMemo(() => `${highlight(value, language)}\n `, [value, language])
+
+  const syncScroll = (e: Event) => {
+    const textarea = e.currentTarget as HTMLTextAreaElement
+    if (highlightRef.current) {
+      highlightRef.current.scrollTop = textarea.scrollTop
+      highlightRef.current.scrollLeft = textarea.scrollLeft
+    }
+  }
+
+  const handleKeyDown = (e: KeyboardEvent) => {
+    const textarea = e.currentTarget as HTMLTextAreaElement
+    if ((e.ctrlKey || e.metaKey) && e.key === 's') {
+      e.preventDefault()
+      onSave()
+    } else if (e.key === 'Tab' && !e.shiftKey) {
+      e.preventDefault()
+      const { selectionStart, selectionEnd } = textarea
+      onChange(`${value.slice(0, selectionStart)}  ${value.slice(selectionEnd)}`)
+      requestAnimationFrame(() => {
+        textarea.selectionStart = textarea.selectionEnd = selectionStart + 2
+      })
+    }
+  }
+
+  return (
+    <div className="relative w-full h-full">
+      <div className="absolute top-2 right-3 z-10 text-xs text-white/40 pointer-events-none">
+        {language ?? 'plain text'}
+      </div>
+      <pre
+        ref={highlightRef}
+        aria-hidden={editing}
+        className={`absolute inset-0 m-0 overflow-auto text-white ${textClasses}`}
+        dangerouslySetInnerHTML={{ __html: html }}
+      />
+      {editing && (
+        <textarea
+          value={value}
+          spellcheck={false}
+          onInput={e => onChange(e.currentTarget.value)}
+          onScroll={syncScroll}
+          onKeyDown={handleKeyDown}
+          className={`absolute inset-0 w-full h-full resize-none bg-transparent text-transparent caret-white focus:outline-none overflow-auto ${textClasses}`}
+        />
+      )}
+    </div>
+  )
+}
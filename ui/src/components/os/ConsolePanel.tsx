@@ -2,6 +2,7 @@ import { useEffect, useRef } from 'preact/hooks'
 import { clsx } from 'clsx'
 import type { ConsoleLine, StatusType } from '../../types/osTypes'
 import type { WasmRunnerStatus } from '../../os/WasmRunner'
+import { AnsiText } from '../AnsiText'
 
 interface ConsolePanelProps {
   lines: ConsoleLine[]
@@ -69,6 +70,7 @@ export default function ConsolePanel({
           <h2 className="text-xl font-bold text-green-400">Console</h2>
           <div className="flex items-center gap-3 mt-1">
             <span
+              role="status"
               className={clsx('text-xs px-2 py-0.5 rounded-full border', {
                 'bg-gray-500/20 border-gray-500/40 text-gray-300': wasmStatus === 'idle',
                 'bg-yellow-500/20 border-yellow-500/40 text-yellow-300':
@@ -92,7 +94,7 @@ export default function ConsolePanel({
               onClick={onRun}
               className="px-3 py-1.5 text-sm bg-green-600/80 hover:bg-green-600 border border-green-400/30 rounded-lg transition-all"
             >
-              ▶ Run
+              <span aria-hidden="true">▶</span> Run
             </button>
           )}
           {canStop && (
@@ -100,7 +102,7 @@ export default function ConsolePanel({
               onClick={onStop}
               className="px-3 py-1.5 text-sm bg-red-600/80 hover:bg-red-600 border border-red-400/30 rounded-lg transition-all"
             >
-              ■ Stop
+              <span aria-hidden="true">■</span> Stop
             </button>
           )}
           <button
@@ -114,6 +116,10 @@ export default function ConsolePanel({
 
       <div
         ref={scrollRef}
+        role="log"
+        aria-live="polite"
+        aria-label="Console output"
+        tabIndex={0}
         className="flex-1 overflow-y-auto bg-black/60 font-mono text-sm p-4 space-y-px"
       >
         {lines.length === 0 && runtimeStatus !== 'running' && (
@@ -133,7 +139,7 @@ export default function ConsolePanel({
                 'text-blue-400': line.stream === 'system',
               })}
             >
-              {line.text}
+              <AnsiText text={line.text} />
             </span>
           </div>
         ))}
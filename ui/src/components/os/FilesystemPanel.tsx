@@ -1,5 +1,9 @@
 import { clsx } from 'clsx'
-import type { FilesystemStats, DirEntry } from '../../types/osTypes'
+import { useEffect, useRef, useState } from 'preact/hooks'
+import type { FilesystemStats, DirEntry, FsSearchResults } from '../../types/osTypes'
+import { copyToClipboard, downloadPath, exportZip, extractArchive } from '../../utils/osUtils'
+import { moveFocus } from '../../utils/a11y'
+import CodeEditor from './CodeEditor'
 
 interface FilesystemPanelProps {
   fsStats: FilesystemStats | null
@@ -9,6 +13,9 @@ interface FilesystemPanelProps {
   selectedFile: string | null
   fileContent: string
   isEditing: boolean
+  saveStatus: string | null
+  rebuildOnSave: boolean
+  readOnly: boolean
   onNavigateUp: () => void
   onRefresh: () => void
   onNavigate: (path: string) => void
@@ -17,6 +24,7 @@ interface FilesystemPanelProps {
   onSave: () => void
   onCancel: () => void
   onContentChange: (content: string) => void
+  onRebuildOnSaveChange: (enabled: boolean) => void
   formatBytes: (bytes: number) => string
 }
 
@@ -28,6 +36,9 @@ export default function FilesystemPanel({
   selectedFile,
   fileContent,
   isEditing,
+  saveStatus,
+  rebuildOnSave,
+  readOnly,
   onNavigateUp,
   onRefresh,
   onNavigate,
@@ -36,8 +47,89 @@ export default function FilesystemPanel({
   onSave,
   onCancel,
   onContentChange,
+  onRebuildOnSaveChange,
   formatBytes,
 }: FilesystemPanelProps) {
+  const [copyState, setCopyState] = useState<'idle' | 'copied' | 'failed'>('idle')
+  const [extractError, setExtractError] = useState<string | null>(null)
+  const [searchQuery, setSearchQuery] = useState('')
+  const [searchResults, setSearchResults] = useState<FsSearchResults | null>(null)
+  const [searchError, setSearchError] = useState<string | null>(null)
+  const listRef = useRef<HTMLDivElement>(null)
+  // Set when the listing changes from inside the list, so focus follows it
+  const refocusList = useRef(false)
+
+  useEffect(() => {
+    if (!refocusList.current) return
+    refocusList.current = false
+    listRef.current?.querySelector<HTMLElement>('[data-roving]')?.focus()
+  }, [dirEntries])
+
+  const openEntry = (entry: DirEntry) => {
+    const fullPath = `${currentPath}/${entry.name}`
+    if (entry.is_dir) {
+      refocusList.current = true
+      onNavigate(fullPath)
+    } else {
+      onFileSelect(fullPath)
+    }
+  }
+
+  const handleEntryKeyDown = (e: KeyboardEvent) => {
+    if (moveFocus(e, 'vertical')) return
+    if (e.key === 'Backspace' && currentPath !== `/${projectName}`) {
+      e.preventDefault()
+      refocusList.current = true
+      onNavigateUp()
+    }
+  }
+
+  const selectedIndex = dirEntries.findIndex(
+    entry => selectedFile === `${currentPath}/${entry.name}`
+  )
+
+  const runSearch = async (e: Event) => {
+    e.preventDefault()
+    if (!searchQuery.trim()) {
+      setSearchResults(null)
+      setSearchError(null)
+      return
+    }
+    const params = new URLSearchParams({ q: searchQuery, path: currentPath })
+    try {
+      const response = await fetch(`/api/fs/search?${params}`)
+      const data = await response.json()
+      if (data.success) {
+        setSearchResults(data)
+        setSearchError(null)
+      } else {
+        setSearchResults(null)
+        setSearchError(data.error ?? 'Search failed')
+      }
+    } catch {
+      setSearchError('Search failed')
+    }
+  }
+
+  const clearSearch = () => {
+    setSearchQuery('')
+    setSearchResults(null)
+    setSearchError(null)
+  }
+
+  const handleCopy = async (path: string) => {
+    setCopyState((await copyToClipboard({ path })) ? 'copied' : 'failed')
+    setTimeout(() => setCopyState('idle'), 2000)
+  }
+
+  const handleExtract = async (input: HTMLInputElement) => {
+    const archive = input.files?.[0]
+    input.value = ''
+    if (!archive) return
+    setExtractError(await extractArchive(currentPath, archive))
+    onRefresh()
+  }
+
   return (
     <div className="h-full flex flex-col">
       <div className="border-b border-green-500/20 bg-black/20 backdrop-blur-lg p-6">
@@ -62,22 +154,113 @@ export default function FilesystemPanel({
               >
                 🔄 Refresh
               </button>
+              <button
+                onClick={() => downloadPath(currentPath)}
+                title="Download this directory as .tar.gz"
+                className="px-3 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-sm"
+              >
+                ⬇️ Download
+              </button>
+              <button
+                onClick={() => exportZip(currentPath)}
+                title="Download this directory as .zip"
+                className="px-3 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-sm"
+              >
+                🗜️ Zip
+              </button>
+              {!readOnly && (
+                <label
+                  title="Unpack a .tar or .tar.gz into this directory"
+                  className="px-3 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-sm cursor-pointer"
+                >
+                  📦 Extract
+                  <input
+                    type="file"
+                    accept=".tar,.tar.gz,.tgz"
+                    className="hidden"
+                    onChange={e => handleExtract(e.target as HTMLInputElement)}
+                  />
+                </label>
+              )}
             </div>
             <div className="text-sm text-green-400 font-mono mb-2">📂 {currentPath}</div>
+            {extractError && <div className="text-red-400 text-xs mb-2">{extractError}</div>}
+            <form onSubmit={runSearch} className="flex gap-2">
+              <input
+                type="search"
+                value={searchQuery}
+                onInput={e => setSearchQuery((e.target as HTMLInputElement).value)}
+                placeholder={`Search in ${currentPath}`}
+                aria-label={`Search in ${currentPath}`}
+                className="flex-1 bg-black/40 border border-green-500/30 rounded px-2 py-1 text-sm font-mono text-white"
+              />
+              {searchResults && (
+                <button
+                  type="button"
+                  onClick={clearSearch}
+                  aria-label="Clear search"
+                  className="px-2 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-sm"
+                >
+                  ✕
+                </button>
+              )}
+            </form>
+            {searchError && <div className="text-red-400 text-xs mt-2">{searchError}</div>}
           </div>
 
-          <div className="space-y-1">
-            {dirEntries.map(entry => (
+          {searchResults && (
+            <div className="space-y-3">
+              <div className="text-xs text-white/50">
+                {searchResults.files.length} files matched, {searchResults.files_scanned} scanned
+                {searchResults.truncated && ' (stopped early, narrow the search to see more)'}
+              </div>
+              {searchResults.files.map(file => (
+                <div key={file.path}>
+                  <button
+                    onClick={() => onFileSelect(file.path)}
+                    className={clsx(
+                      'w-full text-left px-2 py-1 rounded hover:bg-green-500/20 text-sm font-mono truncate',
+                      file.name_match ? 'text-green-300' : 'text-white/80'
+                    )}
+                  >
+                    📄 {file.path.slice(currentPath.length + 1)}
+                  </button>
+                  {file.lines.map(line => (
+                    <button
+                      key={line.line}
+                      onClick={() => onFileSelect(file.path)}
+                      title={[...line.before, line.text, ...line.after].join('\n')}
+                      className="w-full text-left pl-6 pr-2 text-xs font-mono text-white/60 hover:text-white truncate"
+                    >
+                      <span className="text-green-400/70 mr-2">{line.line}</span>
+                      {line.text.trim()}
+                    </button>
+                  ))}
+                </div>
+              ))}
+            </div>
+          )}
+
+          <div
+            ref={listRef}
+            role="group"
+            aria-label={`Contents of ${currentPath}`}
+            data-roving-group
+            className={clsx('space-y-1', { hidden: searchResults })}
+          >
+            {dirEntries.map((entry, index) => (
               <button
                 key={entry.name}
-                onClick={() => {
-                  const fullPath = `${currentPath}/${entry.name}`
-                  if (entry.is_dir) {
-                    onNavigate(fullPath)
-                  } else {
-                    onFileSelect(fullPath)
-                  }
-                }}
+                onClick={() => openEntry(entry)}
+                onKeyDown={handleEntryKeyDown}
+                aria-label={
+                  entry.is_dir
+                    ? `Folder ${entry.name}`
+                    : `File ${entry.name}, ${formatBytes(entry.size)}`
+                }
+                aria-current={index === selectedIndex ? 'true' : undefined}
+                tabIndex={index === Math.max(selectedIndex, 0) ? 0 : -1}
+                data-roving
                 className={clsx(
                   'w-full flex items-center justify-between px-3 py-2 rounded hover:bg-green-500/20 transition-colors text-left',
                   {
@@ -86,7 +269,7 @@ export default function FilesystemPanel({
                 )}
               >
                 <div className="flex items-center gap-2">
-                  <span>{entry.is_dir ? '📁' : '📄'}</span>
+                  <span aria-hidden="true">{entry.is_dir ? '📁' : '📄'}</span>
                   <span className="text-sm font-mono">{entry.name}</span>
                 </div>
                 {entry.is_file && (
@@ -96,7 +279,7 @@ export default function FilesystemPanel({
             ))}
           </div>
 
-          {dirEntries.length === 0 && (
+          {dirEntries.length === 0 && !searchResults && (
             <div className="text-center text-white/50 py-8">
               <div className="text-4xl mb-2">📂</div>
               <div>Empty directory</div>
@@ -122,6 +305,39 @@ export default function FilesystemPanel({
                 <div className="text-xl font-bold">{fsStats?.open_fds || 0}</div>
               </div>
             </div>
+            {fsStats?.quotas && fsStats.quotas.length > 0 && (
+              <div className="mt-4 space-y-2">
+                {fsStats.quotas.map(quota => {
+                  const percent = Math.min(100, (quota.used / Math.max(quota.limit, 1)) * 100)
+                  return (
+                    <div key={quota.guest_path} className="text-xs">
+                      <div className="flex justify-between mb-1 text-white/70">
+                        <span className="font-mono">{quota.guest_path}</span>
+                        <span>
+                          {formatBytes(quota.used)} / {formatBytes(quota.limit)}
+                        </span>
+                      </div>
+                      <div
+                        role="progressbar"
+                        aria-label={`Quota used for ${quota.guest_path}`}
+                        aria-valuemin={0}
+                        aria-valuemax={100}
+                        aria-valuenow={Math.round(percent)}
+                        className="h-1.5 bg-white/10 rounded-full overflow-hidden"
+                      >
+                        <div
+                          className={clsx(
+                            'h-full rounded-full',
+                            percent >= 90 ? 'bg-red-400' : 'bg-green-400'
+                          )}
+                          style={{ width: `${percent}%` }}
+                        />
+                      </div>
+                    </div>
+                  )
+                })}
+              </div>
+            )}
           </div>
 
           {/* File content viewer/editor */}
@@ -131,6 +347,42 @@ export default function FilesystemPanel({
                 <div className="flex items-center justify-between mb-3">
                   <div className="text-sm text-green-400 font-mono">{selectedFile}</div>
                   <div className="flex gap-2">
+                    {!isEditing && (
+                      <>
+                        <button
+                          onClick={() => handleCopy(selectedFile)}
+                          title="Copy file contents to the host clipboard"
+                          className="px-3 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-sm"
+                        >
+                          {copyState === 'copied'
+                            ? '✅ Copied'
+                            : copyState === 'failed'
+                              ? '⚠️ Copy failed'
+                              : '📋 Copy'}
+                        </button>
+                        <button
+                          onClick={() => downloadPath(selectedFile)}
+                          className="px-3 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-sm"
+                        >
+                          ⬇️ Download
+                        </button>
+                      </>
+                    )}
+                    {!readOnly && (
+                      <label
+                        title="Restart the project after every save"
+                        className="flex items-center gap-1 px-2 text-xs text-white/70"
+                      >
+                        <input
+                          type="checkbox"
+                          checked={rebuildOnSave}
+                          onChange={e =>
+                            onRebuildOnSaveChange((e.target as HTMLInputElement).checked)
+                          }
+                        />
+                        Rebuild on save
+                      </label>
+                    )}
                     {isEditing ? (
                       <>
                         <button
@@ -146,6 +398,8 @@ export default function FilesystemPanel({
                           ❌ Cancel
                         </button>
                       </>
+                    ) : readOnly ? (
+                      <span className="px-3 py-1 text-sm text-white/60">🔒 Read-only</span>
                     ) : (
                       <button
                         onClick={onEdit}
@@ -156,18 +410,24 @@ export default function FilesystemPanel({
                     )}
                   </div>
                 </div>
+                {saveStatus && (
+                  <div
+                    className={clsx(
+                      'mb-2 text-xs',
+                      saveStatus.includes('failed') ? 'text-red-400' : 'text-green-400/80'
+                    )}
+                  >
+                    {saveStatus}
+                  </div>
+                )}
                 <div className="flex-1 bg-black/50 border border-green-500/30 rounded-lg overflow-hidden">
-                  {isEditing ? (
-                    <textarea
-                      value={fileContent}
-                      onChange={e => onContentChange(e.currentTarget.value)}
-                      className="w-full h-full bg-transparent text-white font-mono text-sm p-4 resize-none focus:outline-none"
-                    />
-                  ) : (
-                    <pre className="w-full h-full text-white font-mono text-sm p-4 overflow-auto">
-                      {fileContent}
-                    </pre>
-                  )}
+                  <CodeEditor
+                    path={selectedFile}
+                    value={fileContent}
+                    editing={isEditing}
+                    onChange={onContentChange}
+                    onSave={onSave}
+                  />
                 </div>
               </div>
             ) : (
@@ -1,4 +1,6 @@
 import StatusIndicator from './StatusIndicator'
+import { ContrastToggle } from '../ContrastToggle'
+import { useGit, describeGit } from '../../hooks/useGit'
 import type { StatusType, KernelStats } from '../../types/osTypes'
 
 interface HeaderProps {
@@ -14,6 +16,8 @@ export default function Header({
   runtimeStatus,
   kernelStats,
 }: HeaderProps) {
+  const git = useGit()
+
   return (
     <header className="bg-black/30 backdrop-blur-xl border-b border-green-500/20 p-4">
       <div className="flex items-center justify-between">
@@ -21,7 +25,15 @@ export default function Header({
           <img src="/assets/logo-text.png" alt="wasmrun OS" className="h-8 object-contain" />
           <div className="flex flex-col">
             <span className="text-white font-bold text-lg">OS</span>
-            <span className="text-green-400/80 text-xs">{projectName}</span>
+            <span className="text-green-400/80 text-xs">
+              {projectName}
+              {git && (
+                <span className="ml-2 font-mono text-white/60" title={git.changes?.join('\n')}>
+                  {describeGit(git)}
+                  {git.dirty && <span className="ml-1 text-yellow-400">● uncommitted</span>}
+                </span>
+              )}
+            </span>
           </div>
         </div>
         <div className="flex items-center gap-4">
@@ -41,6 +53,7 @@ export default function Header({
                     : 'Loading Runtime…'
             }
           />
+          <ContrastToggle class="text-white/80" />
         </div>
       </div>
     </header>
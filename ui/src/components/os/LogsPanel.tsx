@@ -1,5 +1,11 @@
 import { useState, useEffect, useCallback } from 'preact/hooks'
 import type { LogEntry } from '../../types/osTypes'
+import { copyToClipboard } from '../../utils/osUtils'
+import { stripAnsi } from '../../utils/ansi'
+import { AnsiText } from '../AnsiText'
+
+/** Entries kept in the panel, matching /api/logs/recent */
+const MAX_LOGS = 100
 
 interface LogsPanelProps {}
 
@@ -10,6 +16,7 @@ export default function LogsPanel(_props: LogsPanelProps) {
   const [sourceFilter, setSourceFilter] = useState<string>('all')
   const [isAutoRefresh, setIsAutoRefresh] = useState(true)
   const [lastUpdateTime, setLastUpdateTime] = useState<string>('')
+  const [copyState, setCopyState] = useState<'idle' | 'copied' | 'failed'>('idle')
 
   const fetchLogs = useCallback(async () => {
     try {
@@ -34,12 +41,33 @@ export default function LogsPanel(_props: LogsPanelProps) {
     setFilteredLogs(filtered)
   }, [logs, levelFilter, sourceFilter])
 
-  // Initial load and auto-refresh
+  // Initial load, then live entries from the event stream while
+  // auto-refresh is on; polls instead if the stream can't be opened
   useEffect(() => {
-    fetchLogs()
-    if (isAutoRefresh) {
-      const interval = setInterval(fetchLogs, 2000)
-      return () => clearInterval(interval)
+    if (!isAutoRefresh) {
+      fetchLogs()
+      return
+    }
+
+    let interval: ReturnType<typeof setInterval> | undefined
+    const source = new EventSource('/api/logs/stream')
+    // The stream starts with the recent backlog
+    source.onopen = () => setLogs([])
+    source.addEventListener('log', event => {
+      const entry = JSON.parse((event as MessageEvent).data) as LogEntry
+      setLogs(current => [...current, entry].slice(-MAX_LOGS))
+      setLastUpdateTime(new Date().toLocaleTimeString())
+    })
+    source.onerror = () => {
+      if (source.readyState === EventSource.CLOSED && !interval) {
+        fetchLogs()
+        interval = setInterval(fetchLogs, 2000)
+      }
+    }
+
+    return () => {
+      source.close()
+      if (interval) clearInterval(interval)
     }
   }, [fetchLogs, isAutoRefresh])
 
@@ -90,6 +118,22 @@ export default function LogsPanel(_props: LogsPanelProps) {
     return colors[source] || 'border-l-green-500'
   }
 
+  const logsAsText = () =>
+    filteredLogs
+      .map(
+        log =>
+          `[${log.timestamp}] [${log.level}] [${log.source}${log.pid ? `:${log.pid}` : ''}] ${stripAnsi(log.message)}`
+      )
+      .join('\n')
+
+  // Copies the text selected in the log view, or every filtered log if nothing is selected
+  const copyLogs = async () => {
+    const selection = window.getSelection()?.toString() ?? ''
+    const text = selection.trim() ? selection : logsAsText()
+    setCopyState((await copyToClipboard({ text })) ? 'copied' : 'failed')
+    setTimeout(() => setCopyState('idle'), 2000)
+  }
+
   const exportLogs = (format: 'json' | 'csv' | 'txt') => {
     let content = ''
     const timestamp = new Date().toISOString().replace(/[:.]/g, '-')
@@ -100,16 +144,11 @@ export default function LogsPanel(_props: LogsPanelProps) {
     } else if (format === 'csv') {
       content = 'timestamp,level,source,pid,message\n'
       filteredLogs.forEach(log => {
-        const message = (log.message || '').replace(/"/g, '""')
+        const message = stripAnsi(log.message || '').replace(/"/g, '""')
         content += `"${log.timestamp}","${log.level}","${log.source}","${log.pid || ''}","${message}"\n`
       })
     } else {
-      content = filteredLogs
-        .map(
-          log =>
-            `[${log.timestamp}] [${log.level}] [${log.source}${log.pid ? `:${log.pid}` : ''}] ${log.message}`
-        )
-        .join('\n')
+      content = logsAsText()
     }
 
     const blob = new Blob([content], { type: 'text/plain' })
@@ -180,6 +219,13 @@ export default function LogsPanel(_props: LogsPanelProps) {
         </label>
 
         <div className="flex gap-1">
+          <button
+            onClick={copyLogs}
+            title="Copy the selected logs, or all filtered logs, to the host clipboard"
+            className="px-2 py-1 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded text-xs text-white transition-colors"
+          >
+            {copyState === 'copied' ? '✅ Copied' : copyState === 'failed' ? '⚠️ Failed' : '📋 Copy'}
+          </button>
           <button
             onClick={() => exportLogs('json')}
             className="px-2 py-1 bg-blue-600/30 hover:bg-blue-600/50 border border-blue-500/30 rounded text-xs text-white transition-colors"
@@ -216,7 +262,12 @@ export default function LogsPanel(_props: LogsPanelProps) {
       </div>
 
       {/* Logs View */}
-      <div className="flex-1 overflow-y-auto p-4 space-y-1">
+      <div
+        role="log"
+        aria-label="Kernel logs"
+        tabIndex={0}
+        className="flex-1 overflow-y-auto p-4 space-y-1"
+      >
         {filteredLogs.length === 0 ? (
           <div className="flex items-center justify-center h-full text-white/50">
             <div className="text-center">
@@ -247,7 +298,7 @@ export default function LogsPanel(_props: LogsPanelProps) {
                 <div className="text-gray-500 text-xs min-w-fit flex-shrink-0">PID:{log.pid}</div>
               )}
               <div className="text-white/90 font-mono text-xs break-words flex-1">
-                {log.message}
+                <AnsiText text={log.message} />
               </div>
             </div>
           ))
@@ -0,0 +1,152 @@
+import { useState, useEffect, useCallback } from 'preact/hooks'
+import type { PortConflict, PortRow, PortStatus } from '../../types/osTypes'
+
+const statusStyles: Record<PortStatus, string> = {
+  active: 'bg-green-500/30 border-green-400/50 text-green-300',
+  remapped: 'bg-yellow-500/30 border-yellow-400/50 text-yellow-300',
+}
+
+interface PortsPanelProps {
+  readOnly: boolean
+}
+
+function remapReason(row: PortRow): string | null {
+  if (row.status !== 'remapped') return null
+  const holder =
+    row.held_by === null || row.held_by === undefined
+      ? 'another program on the host'
+      : `PID ${row.held_by}`
+  return `${row.requested} is held by ${holder}`
+}
+
+export default function PortsPanel({ readOnly }: PortsPanelProps) {
+  const [ports, setPorts] = useState<PortRow[]>([])
+  const [conflicts, setConflicts] = useState<PortConflict[]>([])
+  const [error, setError] = useState<string | null>(null)
+
+  const fetchPorts = useCallback(async () => {
+    try {
+      const response = await fetch('/api/ports')
+      const data = await response.json()
+      if (data.success) {
+        setPorts(data.ports)
+        setConflicts(data.conflicts)
+        setError(null)
+      }
+    } catch {
+      setError('Failed to load port mappings')
+    }
+  }, [])
+
+  const removePort = useCallback(
+    async (row: PortRow) => {
+      try {
+        const response = await fetch(`/api/processes/${row.pid}/forward/${row.guest_port}`, {
+          method: 'DELETE',
+        })
+        const data = await response.json()
+        if (!data.success) setError(data.error ?? 'Failed to remove port mapping')
+      } catch {
+        setError('Failed to remove port mapping')
+      }
+      fetchPorts()
+    },
+    [fetchPorts]
+  )
+
+  useEffect(() => {
+    fetchPorts()
+    const interval = setInterval(fetchPorts, 2000)
+    return () => clearInterval(interval)
+  }, [fetchPorts])
+
+  return (
+    <div className="h-full flex flex-col">
+      <div className="border-b border-green-500/20 bg-black/20 backdrop-blur-lg p-6">
+        <h2 className="text-2xl font-bold mb-2 text-green-400">Ports</h2>
+        <p className="text-white/80">Host ports mapped to guest ports, across every process</p>
+      </div>
+      <div className="flex-1 p-6 overflow-y-auto space-y-6">
+        {error && <div className="text-red-400 text-sm">{error}</div>}
+        {conflicts.length > 0 && (
+          <div className="bg-yellow-500/10 border border-yellow-400/30 rounded-xl p-4 text-sm text-yellow-200">
+            {conflicts.map(conflict => (
+              <div key={`${conflict.protocol}-${conflict.guest_port}`}>
+                Guest port <span className="font-mono">{conflict.guest_port}</span>/
+                {conflict.protocol} is mapped by PIDs{' '}
+                <span className="font-mono">{conflict.pids.join(', ')}</span>
+              </div>
+            ))}
+          </div>
+        )}
+        {ports.length === 0 && !error ? (
+          <div className="text-white/60 text-sm">
+            No port mappings. Create one with <code>POST /api/processes/&lt;pid&gt;/forward</code>.
+          </div>
+        ) : (
+          <div className="bg-black/20 backdrop-blur-lg border border-green-500/30 rounded-xl p-6">
+            <table className="w-full text-sm">
+              <thead>
+                <tr className="text-left text-white/50">
+                  <th className="pb-2 font-medium">Process</th>
+                  <th className="pb-2 font-medium">PID</th>
+                  <th className="pb-2 font-medium">Guest port</th>
+                  <th className="pb-2 font-medium">Host port</th>
+                  <th className="pb-2 font-medium">Protocol</th>
+                  <th className="pb-2 font-medium">Status</th>
+                  <th className="pb-2 font-medium">Sessions</th>
+                  <th className="pb-2 font-medium"></th>
+                </tr>
+              </thead>
+              <tbody>
+                {ports.map(row => (
+                  <tr
+                    key={`${row.pid}-${row.protocol}-${row.guest_port}`}
+                    className="border-t border-white/10"
+                  >
+                    <td className="py-2 font-mono">
+                      {row.process || '—'}
+                      {row.language && (
+                        <span className="ml-2 text-xs text-white/50">{row.language}</span>
+                      )}
+                    </td>
+                    <td className="py-2 font-mono">{row.pid}</td>
+                    <td className="py-2 font-mono">{row.guest_port}</td>
+                    <td className="py-2 font-mono">{row.host_port}</td>
+                    <td className="py-2 text-white/70">{row.protocol}</td>
+                    <td className="py-2" title={remapReason(row) ?? undefined}>
+                      <span
+                        className={`px-2 py-0.5 border rounded-full text-xs ${statusStyles[row.status]}`}
+                      >
+                        {row.status}
+                      </span>
+                      {row.status === 'remapped' && (
+                        <span className="ml-2 text-xs text-yellow-300/80">{remapReason(row)}</span>
+                      )}
+                    </td>
+                    <td
+                      className="py-2 font-mono text-xs text-white/70"
+                      title={row.sessions?.map(s => s.peer).join('\n')}
+                    >
+                      {row.sessions ? row.sessions.length : '—'}
+                    </td>
+                    <td className="py-2 text-right">
+                      {!readOnly && (
+                        <button
+                          onClick={() => removePort(row)}
+                          className="px-3 py-1 text-xs bg-red-500/20 border border-red-400/30 text-red-300 rounded-lg hover:bg-red-500/30 transition-colors"
+                        >
+                          Remove
+                        </button>
+                      )}
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,203 @@
+import { useState, useEffect } from 'preact/hooks'
+import type { ProcessConfig } from '../../types/osTypes'
+
+interface ProcessConfigFormProps {
+  pid: number
+  readOnly: boolean
+  onSaved: (pid: number, restarted: boolean) => void
+  onClose: () => void
+}
+
+interface EnvRow {
+  key: string
+  value: string
+}
+
+export default function ProcessConfigForm({
+  pid,
+  readOnly,
+  onSaved,
+  onClose,
+}: ProcessConfigFormProps) {
+  const [name, setName] = useState('')
+  const [envRows, setEnvRows] = useState<EnvRow[]>([])
+  const [args, setArgs] = useState<string[]>([])
+  const [error, setError] = useState<string | null>(null)
+  const [saving, setSaving] = useState(false)
+
+  useEffect(() => {
+    setError(null)
+    fetch(`/api/processes/${pid}/config`)
+      .then(response => response.json())
+      .then(data => {
+        if (!data.success) {
+          setError(data.error || 'Failed to load config')
+          return
+        }
+        const config = data as ProcessConfig
+        setName(config.name)
+        setEnvRows(Object.entries(config.env).map(([key, value]) => ({ key, value })))
+        setArgs(config.args)
+      })
+      .catch(() => setError('Failed to load config'))
+  }, [pid])
+
+  const updateEnv = (index: number, row: Partial<EnvRow>) => {
+    setEnvRows(rows => rows.map((r, i) => (i === index ? { ...r, ...row } : r)))
+  }
+
+  const updateArg = (index: number, value: string) => {
+    setArgs(list => list.map((a, i) => (i === index ? value : a)))
+  }
+
+  const save = async (restart: boolean) => {
+    const env: Record<string, string> = {}
+    for (const { key, value } of envRows) {
+      const trimmed = key.trim()
+      if (!trimmed) continue
+      if (trimmed.includes('=')) {
+        setError(`Invalid variable name '${trimmed}'`)
+        return
+      }
+      env[trimmed] = value
+    }
+
+    setSaving(true)
+    setError(null)
+    try {
+      const response = await fetch(`/api/processes/${pid}/config`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ env, args, restart }),
+      })
+      const data = await response.json()
+      if (!data.success) {
+        setError(data.error || 'Failed to save config')
+        return
+      }
+      onSaved(data.pid, data.restarted)
+    } catch {
+      setError('Failed to save config')
+    } finally {
+      setSaving(false)
+    }
+  }
+
+  const inputClass =
+    'bg-black/40 border border-white/20 rounded px-2 py-1 text-sm font-mono text-white disabled:opacity-60'
+  const buttonClass =
+    'px-3 py-1 text-xs border rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed'
+
+  return (
+    <div className="bg-black/20 backdrop-blur-lg border border-green-500/30 rounded-xl p-6">
+      <div className="flex items-center justify-between mb-4">
+        <h3 className="text-lg font-semibold text-green-400">
+          {name || `PID ${pid}`} <span className="text-sm text-white/50 font-mono">PID {pid}</span>
+        </h3>
+        <button
+          onClick={onClose}
+          className={`${buttonClass} bg-gray-500/20 border-gray-400/30 text-white/80 hover:bg-gray-500/30`}
+        >
+          Close
+        </button>
+      </div>
+
+      {error && <div className="text-red-400 text-sm mb-4">{error}</div>}
+
+      <div className="mb-6">
+        <div className="text-sm text-white/60 mb-2">Environment</div>
+        <div className="space-y-2">
+          {envRows.map((row, index) => (
+            <div key={index} className="flex gap-2">
+              <input
+                className={`${inputClass} w-1/3`}
+                placeholder="NAME"
+                value={row.key}
+                disabled={readOnly}
+                onInput={e => updateEnv(index, { key: (e.target as HTMLInputElement).value })}
+              />
+              <input
+                className={`${inputClass} flex-1`}
+                placeholder="value"
+                value={row.value}
+                disabled={readOnly}
+                onInput={e => updateEnv(index, { value: (e.target as HTMLInputElement).value })}
+              />
+              {!readOnly && (
+                <button
+                  onClick={() => setEnvRows(rows => rows.filter((_, i) => i !== index))}
+                  className={`${buttonClass} bg-red-500/20 border-red-400/30 text-red-300 hover:bg-red-500/30`}
+                >
+                  Remove
+                </button>
+              )}
+            </div>
+          ))}
+          {envRows.length === 0 && <div className="text-white/40 text-sm">No variables set</div>}
+        </div>
+        {!readOnly && (
+          <button
+            onClick={() => setEnvRows(rows => [...rows, { key: '', value: '' }])}
+            className={`${buttonClass} mt-2 bg-green-500/20 border-green-400/30 text-green-300 hover:bg-green-500/30`}
+          >
+            Add variable
+          </button>
+        )}
+      </div>
+
+      <div className="mb-6">
+        <div className="text-sm text-white/60 mb-2">Arguments (after the entry file)</div>
+        <div className="space-y-2">
+          {args.map((arg, index) => (
+            <div key={index} className="flex gap-2">
+              <input
+                className={`${inputClass} flex-1`}
+                value={arg}
+                disabled={readOnly}
+                onInput={e => updateArg(index, (e.target as HTMLInputElement).value)}
+              />
+              {!readOnly && (
+                <button
+                  onClick={() => setArgs(list => list.filter((_, i) => i !== index))}
+                  className={`${buttonClass} bg-red-500/20 border-red-400/30 text-red-300 hover:bg-red-500/30`}
+                >
+                  Remove
+                </button>
+              )}
+            </div>
+          ))}
+          {args.length === 0 && <div className="text-white/40 text-sm">No arguments</div>}
+        </div>
+        {!readOnly && (
+          <button
+            onClick={() => setArgs(list => [...list, ''])}
+            className={`${buttonClass} mt-2 bg-green-500/20 border-green-400/30 text-green-300 hover:bg-green-500/30`}
+          >
+            Add argument
+          </button>
+        )}
+      </div>
+
+      {readOnly ? (
+        <div className="text-white/50 text-sm">Read-only server: config can't be changed</div>
+      ) : (
+        <div className="flex gap-2">
+          <button
+            onClick={() => save(true)}
+            disabled={saving}
+            className={`${buttonClass} bg-green-500/20 border-green-400/30 text-green-300 hover:bg-green-500/30`}
+          >
+            {saving ? 'Saving…' : 'Save & restart'}
+          </button>
+          <button
+            onClick={() => save(false)}
+            disabled={saving}
+            className={`${buttonClass} bg-blue-500/20 border-blue-400/30 text-blue-300 hover:bg-blue-500/30`}
+          >
+            Save for next restart
+          </button>
+        </div>
+      )}
+    </div>
+  )
+}
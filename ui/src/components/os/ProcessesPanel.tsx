@@ -0,0 +1,189 @@
+import { useState, useEffect, useCallback } from 'preact/hooks'
+import type { KernelProcess, MemberStatus, SupervisorGroup } from '../../types/osTypes'
+import ProcessConfigForm from './ProcessConfigForm'
+
+const statusStyles: Record<MemberStatus, string> = {
+  running: 'bg-green-500/30 border-green-400/50 text-green-300',
+  backoff: 'bg-yellow-500/30 border-yellow-400/50 text-yellow-300',
+  exited: 'bg-gray-500/30 border-gray-400/50 text-gray-300',
+  failed: 'bg-red-500/30 border-red-400/50 text-red-300',
+  stopped: 'bg-gray-500/20 border-gray-500/40 text-white/60',
+}
+
+interface ProcessesPanelProps {
+  projectPid: number | null
+  readOnly: boolean
+  onProjectRestarted: () => void
+}
+
+export default function ProcessesPanel({
+  projectPid,
+  readOnly,
+  onProjectRestarted,
+}: ProcessesPanelProps) {
+  const [groups, setGroups] = useState<SupervisorGroup[]>([])
+  const [processes, setProcesses] = useState<KernelProcess[]>([])
+  const [editingPid, setEditingPid] = useState<number | null>(null)
+  const [error, setError] = useState<string | null>(null)
+
+  const fetchGroups = useCallback(async () => {
+    try {
+      const response = await fetch('/api/supervisor')
+      const data = await response.json()
+      if (data.success) {
+        setGroups(data.groups)
+        setError(null)
+      }
+    } catch {
+      setError('Failed to load supervisor state')
+    }
+    try {
+      const response = await fetch('/api/processes')
+      const data = await response.json()
+      if (data.success) setProcesses(data.processes)
+    } catch {
+      // the supervisor error above covers an unreachable server
+    }
+  }, [])
+
+  const handleSaved = useCallback(
+    (newPid: number, restarted: boolean) => {
+      // The browser runtime runs the main project, so it restarts too
+      if (restarted && editingPid === projectPid) onProjectRestarted()
+      setEditingPid(newPid)
+      fetchGroups()
+    },
+    [editingPid, projectPid, onProjectRestarted, fetchGroups]
+  )
+
+  useEffect(() => {
+    fetchGroups()
+    const interval = setInterval(fetchGroups, 2000)
+    return () => clearInterval(interval)
+  }, [fetchGroups])
+
+  return (
+    <div className="h-full flex flex-col">
+      <div className="border-b border-green-500/20 bg-black/20 backdrop-blur-lg p-6">
+        <h2 className="text-2xl font-bold mb-2 text-green-400">Processes</h2>
+        <p className="text-white/80">
+          Kernel processes, their env and arguments, and supervisor groups
+        </p>
+      </div>
+      <div className="flex-1 p-6 overflow-y-auto space-y-6">
+        {error && <div className="text-red-400 text-sm">{error}</div>}
+        {editingPid !== null && (
+          <ProcessConfigForm
+            key={editingPid}
+            pid={editingPid}
+            readOnly={readOnly}
+            onSaved={handleSaved}
+            onClose={() => setEditingPid(null)}
+          />
+        )}
+        {processes.length > 0 && (
+          <div className="bg-black/20 backdrop-blur-lg border border-green-500/30 rounded-xl p-6">
+            <h3 className="text-lg font-semibold text-green-400 mb-4">Running</h3>
+            <table className="w-full text-sm">
+              <thead>
+                <tr className="text-left text-white/50">
+                  <th className="pb-2 font-medium">Process</th>
+                  <th className="pb-2 font-medium">PID</th>
+                  <th className="pb-2 font-medium">State</th>
+                  <th className="pb-2 font-medium">Env / args</th>
+                  <th className="pb-2 font-medium"></th>
+                </tr>
+              </thead>
+              <tbody>
+                {processes.map(process => {
+                  const editable = process.pid === projectPid || process.restart_policy !== null
+                  return (
+                    <tr key={process.pid} className="border-t border-white/10">
+                      <td className="py-2 font-mono">
+                        {process.name}
+                        {process.pid === projectPid && (
+                          <span className="ml-2 text-xs text-white/50">(project)</span>
+                        )}
+                      </td>
+                      <td className="py-2 font-mono">{process.pid}</td>
+                      <td className="py-2 text-white/70">{process.state}</td>
+                      <td className="py-2 font-mono text-xs text-white/70">
+                        {Object.keys(process.env).length} vars
+                        {process.args.length > 0 && ` · ${process.args.join(' ')}`}
+                      </td>
+                      <td className="py-2 text-right">
+                        {editable && (
+                          <button
+                            onClick={() => setEditingPid(process.pid)}
+                            className="px-3 py-1 text-xs bg-green-500/20 border border-green-400/30 text-green-300 rounded-lg hover:bg-green-500/30 transition-colors"
+                          >
+                            Configure
+                          </button>
+                        )}
+                      </td>
+                    </tr>
+                  )
+                })}
+              </tbody>
+            </table>
+          </div>
+        )}
+        {groups.length === 0 && !error && (
+          <div className="text-white/60 text-sm">
+            No supervisor groups. Create one with <code>POST /api/supervisor/groups</code>.
+          </div>
+        )}
+        {groups.map(group => (
+          <div
+            key={group.name}
+            className="bg-black/20 backdrop-blur-lg border border-green-500/30 rounded-xl p-6"
+          >
+            <div className="flex items-center justify-between mb-4">
+              <h3 className="text-lg font-semibold text-green-400">{group.name}</h3>
+              <div className="text-xs text-white/60 font-mono">
+                restart: {group.restart} · max {group.max_restarts || '∞'} · backoff{' '}
+                {group.backoff_ms}ms
+              </div>
+            </div>
+            <table className="w-full text-sm">
+              <thead>
+                <tr className="text-left text-white/50">
+                  <th className="pb-2 font-medium">Process</th>
+                  <th className="pb-2 font-medium">PID</th>
+                  <th className="pb-2 font-medium">Status</th>
+                  <th className="pb-2 font-medium">Restarts</th>
+                  <th className="pb-2 font-medium">Last exit</th>
+                </tr>
+              </thead>
+              <tbody>
+                {group.members.map(member => (
+                  <tr key={member.name} className="border-t border-white/10">
+                    <td className="py-2 font-mono">{member.name}</td>
+                    <td className="py-2 font-mono">{member.pid ?? '—'}</td>
+                    <td className="py-2">
+                      <span
+                        className={`px-2 py-0.5 border rounded-full text-xs ${statusStyles[member.status]}`}
+                      >
+                        {member.status}
+                        {member.status === 'backoff' && member.restart_in_ms !== null
+                          ? ` (${Math.ceil(member.restart_in_ms / 1000)}s)`
+                          : ''}
+                      </span>
+                    </td>
+                    <td className="py-2 font-mono">{member.restarts}</td>
+                    <td className="py-2 text-white/70" title={member.last_error ?? undefined}>
+                      {member.last_exit ?? '—'}
+                      {member.last_error && (
+                        <span className="ml-2 text-xs text-red-300/80">{member.last_error}</span>
+                      )}
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        ))}
+      </div>
+    </div>
+  )
+}
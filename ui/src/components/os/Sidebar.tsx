@@ -1,5 +1,6 @@
 import { clsx } from 'clsx'
 import type { PanelType } from '../../types/osTypes'
+import { moveFocus } from '../../utils/a11y'
 
 interface SidebarProps {
   panels: PanelType[]
@@ -13,7 +14,11 @@ export default function Sidebar({ panels, activePanel, onPanelChange }: SidebarP
   const monitoringPanels = panels.slice(5)
 
   return (
-    <nav className="w-80 bg-black/20 backdrop-blur-lg border-r border-green-500/20 p-6">
+    <nav
+      aria-label="Panels"
+      data-roving-group
+      className="w-80 bg-black/20 backdrop-blur-lg border-r border-green-500/20 p-6"
+    >
       <div className="space-y-8">
         <PanelSection
           title="🎯 PROJECT"
@@ -54,6 +59,10 @@ function PanelSection({ title, panels, activePanel, onPanelChange }: PanelSectio
           <button
             key={panel.id}
             onClick={() => onPanelChange(panel.id)}
+            onKeyDown={e => moveFocus(e, 'vertical')}
+            aria-current={activePanel === panel.id ? 'page' : undefined}
+            tabIndex={activePanel === panel.id ? 0 : -1}
+            data-roving
             className={clsx(
               'w-full flex items-center gap-3 px-4 py-3 rounded-lg backdrop-blur-sm transition-all duration-200',
               {
@@ -63,7 +72,7 @@ function PanelSection({ title, panels, activePanel, onPanelChange }: PanelSectio
               }
             )}
           >
-            <span>{panel.icon}</span>
+            <span aria-hidden="true">{panel.icon}</span>
             <span className="font-medium">{panel.name}</span>
           </button>
         ))}
@@ -0,0 +1,149 @@
+import { useState, useEffect, useCallback } from 'preact/hooks'
+import type { KernelProcess, SocketRow, SocketState } from '../../types/osTypes'
+import { formatBytes } from '../../utils/osUtils'
+
+const stateStyles: Record<SocketState, string> = {
+  created: 'bg-white/10 border-white/20 text-white/70',
+  bound: 'bg-blue-500/30 border-blue-400/50 text-blue-300',
+  listening: 'bg-yellow-500/30 border-yellow-400/50 text-yellow-300',
+  connected: 'bg-green-500/30 border-green-400/50 text-green-300',
+}
+
+interface SocketsPanelProps {
+  readOnly: boolean
+}
+
+export default function SocketsPanel({ readOnly }: SocketsPanelProps) {
+  const [processes, setProcesses] = useState<KernelProcess[]>([])
+  const [pid, setPid] = useState<number | null>(null)
+  const [sockets, setSockets] = useState<SocketRow[]>([])
+  const [error, setError] = useState<string | null>(null)
+
+  const fetchSockets = useCallback(async () => {
+    try {
+      const response = await fetch('/api/processes')
+      const data = await response.json()
+      if (data.success) setProcesses(data.processes)
+    } catch {
+      setError('Failed to load processes')
+      return
+    }
+    if (pid === null) return
+    try {
+      const response = await fetch(`/api/processes/${pid}/sockets`)
+      const data = await response.json()
+      if (data.success) {
+        setSockets(data.sockets)
+        setError(null)
+      } else {
+        setSockets([])
+        setError(data.error ?? 'Failed to load sockets')
+      }
+    } catch {
+      setError('Failed to load sockets')
+    }
+  }, [pid])
+
+  const disconnect = useCallback(
+    async (socket: SocketRow) => {
+      try {
+        const response = await fetch(`/api/processes/${pid}/sockets/${socket.fd}`, {
+          method: 'DELETE',
+        })
+        const data = await response.json()
+        if (!data.success) setError(data.error ?? 'Failed to disconnect socket')
+      } catch {
+        setError('Failed to disconnect socket')
+      }
+      fetchSockets()
+    },
+    [pid, fetchSockets]
+  )
+
+  useEffect(() => {
+    if (pid === null && processes.length > 0) setPid(processes[0].pid)
+  }, [pid, processes])
+
+  useEffect(() => {
+    fetchSockets()
+    const interval = setInterval(fetchSockets, 2000)
+    return () => clearInterval(interval)
+  }, [fetchSockets])
+
+  return (
+    <div className="h-full flex flex-col">
+      <div className="border-b border-green-500/20 bg-black/20 backdrop-blur-lg p-6">
+        <h2 className="text-2xl font-bold mb-2 text-green-400">Sockets</h2>
+        <p className="text-white/80">Open connections of a guest process and their traffic</p>
+      </div>
+      <div className="flex-1 p-6 overflow-y-auto space-y-6">
+        <label className="flex items-center gap-3 text-sm text-white/70">
+          Process
+          <select
+            value={pid ?? ''}
+            onChange={e => setPid(Number((e.target as HTMLSelectElement).value))}
+            className="bg-black/40 border border-green-500/30 rounded-lg px-3 py-1 text-white font-mono"
+          >
+            {processes.map(process => (
+              <option key={process.pid} value={process.pid}>
+                {process.pid} · {process.name}
+              </option>
+            ))}
+          </select>
+        </label>
+        {error && <div className="text-red-400 text-sm">{error}</div>}
+        {sockets.length === 0 && !error ? (
+          <div className="text-white/60 text-sm">
+            {pid === null ? 'No processes running.' : 'This process has no open sockets.'}
+          </div>
+        ) : (
+          <div className="bg-black/20 backdrop-blur-lg border border-green-500/30 rounded-xl p-6">
+            <table className="w-full text-sm">
+              <thead>
+                <tr className="text-left text-white/50">
+                  <th className="pb-2 font-medium">FD</th>
+                  <th className="pb-2 font-medium">Kind</th>
+                  <th className="pb-2 font-medium">Local</th>
+                  <th className="pb-2 font-medium">Peer</th>
+                  <th className="pb-2 font-medium">State</th>
+                  <th className="pb-2 font-medium">In</th>
+                  <th className="pb-2 font-medium">Out</th>
+                  <th className="pb-2 font-medium"></th>
+                </tr>
+              </thead>
+              <tbody>
+                {sockets.map(socket => (
+                  <tr key={socket.fd} className="border-t border-white/10">
+                    <td className="py-2 font-mono">{socket.fd}</td>
+                    <td className="py-2 text-white/70">{socket.kind}</td>
+                    <td className="py-2 font-mono">{socket.local_addr ?? '—'}</td>
+                    <td className="py-2 font-mono">{socket.peer_addr ?? '—'}</td>
+                    <td className="py-2">
+                      <span
+                        className={`px-2 py-0.5 border rounded-full text-xs ${stateStyles[socket.state]}`}
+                      >
+                        {socket.state}
+                      </span>
+                    </td>
+                    <td className="py-2 font-mono">{formatBytes(socket.bytes_in)}</td>
+                    <td className="py-2 font-mono">{formatBytes(socket.bytes_out)}</td>
+                    <td className="py-2 text-right">
+                      {!readOnly && (
+                        <button
+                          onClick={() => disconnect(socket)}
+                          className="px-3 py-1 text-xs bg-red-500/20 border border-red-400/30 text-red-300 rounded-lg hover:bg-red-500/30 transition-colors"
+                        >
+                          Disconnect
+                        </button>
+                      )}
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+      </div>
+    </div>
+  )
+}
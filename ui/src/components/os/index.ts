@@ -6,6 +6,10 @@ export { default as ConsolePanel } from './ConsolePanel'
 export { default as KernelStatusPanel } from './KernelStatusPanel'
 export { default as FilesystemPanel } from './FilesystemPanel'
 export { default as LogsPanel } from './LogsPanel'
+export { default as ProcessesPanel } from './ProcessesPanel'
+export { default as ProcessConfigForm } from './ProcessConfigForm'
+export { default as PortsPanel } from './PortsPanel'
+export { default as SocketsPanel } from './SocketsPanel'
 export { panels } from './panels'
 export { formatUptime, formatBytes } from '../../utils/osUtils'
 export type {
@@ -16,4 +20,11 @@ export type {
   PanelType,
   StatusType,
   LogEntry,
+  SupervisorGroup,
+  KernelProcess,
+  ProcessConfig,
+  PortRow,
+  PortConflict,
+  UdpSession,
+  SocketRow,
 } from '../../types/osTypes'
@@ -5,7 +5,9 @@ export const panels: PanelType[] = [
   { id: 'kernel', name: 'Kernel Status', icon: '⚙️' },
   { id: 'console', name: 'Console', icon: '📟' },
   { id: 'filesystem', name: 'File System', icon: '📁' },
-  { id: 'processes', name: 'Processes (Coming Soon)', icon: '🔄' },
+  { id: 'processes', name: 'Processes', icon: '🔄' },
+  { id: 'ports', name: 'Ports', icon: '🔌' },
+  { id: 'sockets', name: 'Sockets', icon: '🧵' },
   { id: 'metrics', name: 'Metrics (Coming Soon)', icon: '📈' },
   { id: 'logs', name: 'Logs', icon: '📋' },
 ]
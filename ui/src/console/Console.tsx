@@ -1,11 +1,29 @@
-import { useState, useEffect, useCallback } from 'preact/hooks'
+import { useState, useEffect, useCallback, useMemo } from 'preact/hooks'
 import { ConsoleLayout } from '@/layouts/ConsoleLayout'
 import { LogContainer } from '@/components/LogContainer'
 import { FunctionPlayground } from '@/components/FunctionPlayground'
 import { ModuleInfo } from '@/components/ModuleInfo'
+import { MemoryTimeline } from '@/components/MemoryTimeline'
+import { TimingPanel } from '@/components/TimingPanel'
+import { PluginPanel } from '@/components/PluginPanel'
 import { LogEntry, ExportedFunction, WasmModuleInfo, TabItem } from '@/types'
-import { log, loadWasmModule, analyzeWasmModule, fetchModuleInspection } from '@/utils/wasm'
+import {
+  log,
+  loadWasmModule,
+  analyzeWasmModule,
+  fetchModuleInspection,
+  fetchModuleDetails,
+  getWorkerBridge,
+  WorkerModule,
+} from '@/utils/wasm'
 import { parseCommand } from '@/utils/commandParser'
+import { useConsoleHistory } from '@/hooks/useConsoleHistory'
+import { useMemoryTimeline } from '@/hooks/useMemoryTimeline'
+import { usePerfEntries } from '@/hooks/usePerfEntries'
+import { panelTabId, usePluginPanels } from '@/hooks/usePluginPanels'
+import { MemorySample, bindgenAllocator, formatBytes, sampleInstance, sampleWorker } from '@/utils/memory'
+import { isUnreachableTrap, takePanic, watchPanicOutput } from '@/utils/panic'
+import { t } from '@/i18n'
 
 // These will be replaced by the Rust template processor
 declare const FILENAME: string
@@ -109,86 +127,165 @@ const generateFunctionInfo = (name: string) => {
   }
 }
 
+// Log lines for a failed load or call: a Rust panic's message and location
+// in place of the trap it ended in, or `summary` with a hint when the trap
+// looks like a panic that printed nothing
+const failureLines = (error: unknown, summary: string): Array<[string, LogEntry['type']]> => {
+  const panic = takePanic(error)
+  if (panic) {
+    const message = panic.message ?? t('common.unknownError')
+    const title = panic.thread
+      ? t('console.panic.thread', { thread: panic.thread, message })
+      : t('console.panic', { message })
+    return [
+      [title, 'error'],
+      ...(panic.location
+        ? [[t('console.panic.at', { location: panic.location }), 'error'] as [string, 'error']]
+        : []),
+    ]
+  }
+  if (isUnreachableTrap(error)) {
+    return [
+      [summary, 'error'],
+      [t('console.panic.hint'), 'warning'],
+    ]
+  }
+  return [[summary, 'error']]
+}
+
 export function Console() {
-  const [logs, setLogs] = useState<LogEntry[]>([])
+  const { logs, append: appendLog, clear: clearLogs } = useConsoleHistory(FILENAME)
   const [moduleInfo, setModuleInfo] = useState<WasmModuleInfo | null>(null)
   const [exportedFunctions, setExportedFunctions] = useState<ExportedFunction[]>([])
   const [wasmInstance, setWasmInstance] = useState<WebAssembly.Instance | null>(null)
+  const [workerModule, setWorkerModule] = useState<WorkerModule | null>(null)
   const [activeTab, setActiveTab] = useState('console')
 
-  const addLog = useCallback((message: string, type: LogEntry['type'] = 'info') => {
-    const logEntry = log(message, type)
-    setLogs(prev => [...prev, logEntry])
-  }, [])
-
-  const initializeWasm = useCallback(async () => {
-    try {
-      addLog(`Loading WASM module: ${FILENAME}`)
+  const addLog = useCallback(
+    (message: string, type: LogEntry['type'] = 'info') => {
+      appendLog(log(message, type))
+    },
+    [appendLog]
+  )
 
-      const module = await loadWasmModule(FILENAME)
-      const analysis = analyzeWasmModule(module)
+  useEffect(() => watchPanicOutput(), [])
+
+  const sampleMemory = useMemo(() => {
+    if (workerModule) return () => sampleWorker(workerModule.memory)
+    if (wasmInstance) return () => sampleInstance(wasmInstance)
+    return null
+  }, [wasmInstance, workerModule])
+
+  const warnMemory = useCallback(
+    (sample: MemorySample, threshold: number) =>
+      addLog(
+        t('console.heap.overThreshold', {
+          size: formatBytes(sample.bytes),
+          threshold: formatBytes(threshold),
+        }),
+        'warning'
+      ),
+    [addLog]
+  )
 
-      // Instantiate the WASM module to create a runnable instance
-      // For wasm-bindgen modules, we need to provide the proper imports
-      let instance: WebAssembly.Instance
+  const memoryTimeline = useMemoryTimeline(sampleMemory, warnMemory)
+  const perfEntries = usePerfEntries()
+  const pluginPanels = usePluginPanels()
 
-      try {
-        // Try to instantiate without imports first
-        instance = new WebAssembly.Instance(module, {})
-      } catch {
-        // If that fails, try with basic imports for wasm-bindgen
-        const imports = {
-          wbg: {
-            __wbg_log_8b68cfc62b396cc3: (arg0: number, arg1: number) => {
-              // This will be called after the instance is created
-              // For now, just store the arguments - we'll extract the string later
-              if (instance && instance.exports.memory) {
-                try {
-                  const memory = instance.exports.memory as WebAssembly.Memory
-                  const ptr = arg0
-                  const len = arg1
-                  const bytes = new Uint8Array(memory.buffer, ptr, len)
-                  const message = new TextDecoder().decode(bytes)
-                  addLog(message, 'info')
-                } catch {
-                  addLog(`WASM log: ${arg0}, ${arg1}`, 'info')
-                }
-              } else {
-                // Store for later processing
-                setTimeout(() => {
-                  if (instance && instance.exports.memory) {
-                    try {
-                      const memory = instance.exports.memory as WebAssembly.Memory
-                      const ptr = arg0
-                      const len = arg1
-                      const bytes = new Uint8Array(memory.buffer, ptr, len)
-                      const message = new TextDecoder().decode(bytes)
-                      addLog(message, 'info')
-                    } catch {
-                      addLog(`WASM log: ${arg0}, ${arg1}`, 'info')
-                    }
+  const initializeWasm = useCallback(async () => {
+    try {
+      performance.mark('wasmrun:load-start')
+      addLog(t('console.loading', { file: FILENAME }))
+
+      let analysis: Partial<WasmModuleInfo>
+      const bridge = getWorkerBridge()
+
+      if (bridge) {
+        addLog('Instantiating WASM module in a Web Worker (--worker)...')
+        const remote = await bridge.load(FILENAME)
+        setWorkerModule(remote)
+        analysis = {
+          imports: remote.info.imports,
+          exports: remote.info.functions,
+          isWasi: remote.info.imports.some(imp => imp.startsWith('wasi')),
+        }
+        if (remote.info.imports.length > 0) {
+          addLog(
+            `Stubbed ${remote.info.imports.length} imports in the worker: ${remote.info.imports.join(', ')}`,
+            'warning'
+          )
+        }
+      } else {
+        const module = await loadWasmModule(FILENAME)
+        analysis = analyzeWasmModule(module)
+
+        // Instantiate the WASM module to create a runnable instance
+        // For wasm-bindgen modules, we need to provide the proper imports
+        let instance: WebAssembly.Instance
+
+        try {
+          // Try to instantiate without imports first
+          instance = new WebAssembly.Instance(module, {})
+        } catch {
+          // If that fails, try with basic imports for wasm-bindgen
+          const imports = {
+            wbg: {
+              __wbg_log_8b68cfc62b396cc3: (arg0: number, arg1: number) => {
+                // This will be called after the instance is created
+                // For now, just store the arguments - we'll extract the string later
+                if (instance && instance.exports.memory) {
+                  try {
+                    const memory = instance.exports.memory as WebAssembly.Memory
+                    const ptr = arg0
+                    const len = arg1
+                    const bytes = new Uint8Array(memory.buffer, ptr, len)
+                    const message = new TextDecoder().decode(bytes)
+                    addLog(message, 'info')
+                  } catch {
+                    addLog(`WASM log: ${arg0}, ${arg1}`, 'info')
                   }
-                }, 0)
-              }
-            },
-            __wbindgen_init_externref_table: () => {
-              // Initialize external reference table
+                } else {
+                  // Store for later processing
+                  setTimeout(() => {
+                    if (instance && instance.exports.memory) {
+                      try {
+                        const memory = instance.exports.memory as WebAssembly.Memory
+                        const ptr = arg0
+                        const len = arg1
+                        const bytes = new Uint8Array(memory.buffer, ptr, len)
+                        const message = new TextDecoder().decode(bytes)
+                        addLog(message, 'info')
+                      } catch {
+                        addLog(`WASM log: ${arg0}, ${arg1}`, 'info')
+                      }
+                    }
+                  }, 0)
+                }
+              },
+              __wbindgen_init_externref_table: () => {
+                // Initialize external reference table
+              },
             },
-          },
+          }
+
+          instance = new WebAssembly.Instance(module, imports)
         }
 
-        instance = new WebAssembly.Instance(module, imports)
-      }
+        // Debug: log all exports to understand the actual WASM interface
+        // const allExports = Object.keys(instance.exports)
+        // addLog(`Available exports: ${allExports.join(', ')}`, 'info')
 
-      // Debug: log all exports to understand the actual WASM interface
-      // const allExports = Object.keys(instance.exports)
-      // addLog(`Available exports: ${allExports.join(', ')}`, 'info')
+        setWasmInstance(instance)
+      }
 
-      setWasmInstance(instance)
+      performance.measure('wasmrun:instantiate', 'wasmrun:load-start')
 
       // Fetch real inspection data from backend
       addLog('Analyzing WASM module structure...')
-      const inspection = await fetchModuleInspection()
+      const [inspection, details] = await Promise.all([
+        fetchModuleInspection(),
+        fetchModuleDetails(),
+      ])
 
       // Create module info with real inspection data
       const moduleInfo: WasmModuleInfo = {
@@ -198,6 +295,7 @@ export function Console() {
         exports: analysis.exports || [],
         isWasi: analysis.isWasi || false,
         inspection: inspection || undefined,
+        details: details || undefined,
       }
 
       setModuleInfo(moduleInfo)
@@ -232,13 +330,15 @@ export function Console() {
 
       setExportedFunctions(functions)
 
+      performance.mark('wasmrun:ready')
       addLog('✅ WASM module loaded successfully!', 'success')
       addLog(`Found ${functions.length} exported functions`, 'info')
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : 'Unknown error'
+      const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
+      const lines = failureLines(error, `❌ Error loading WASM module: ${errorMessage}`)
       console.error('❌ Error loading WASM module:', error)
 
-      addLog(`❌ Error loading WASM module: ${errorMessage}`, 'error')
+      lines.forEach(([line, type]) => addLog(line, type))
     }
   }, [addLog])
 
@@ -248,6 +348,21 @@ export function Console() {
 
   const handleFunctionCall = useCallback(
     async (functionName: string, args: any[], skipLog = false) => {
+      if (workerModule) {
+        if (!skipLog) {
+          addLog(`> ${functionName}(${args.join(', ')})`, 'info')
+        }
+        try {
+          const result = await workerModule.exports[functionName](...args)
+          addLog(`✅ ${result}`, 'success')
+          return result
+        } catch (error) {
+          const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
+          failureLines(error, `❌ ${errorMessage}`).forEach(([line, type]) => addLog(line, type))
+          throw error
+        }
+      }
+
       if (!wasmInstance) {
         throw new Error('WASM module not loaded')
       }
@@ -278,7 +393,7 @@ export function Console() {
             addLog(`✅ main() executed successfully: ${result}`, 'success')
             return result
           } catch (error) {
-            const errorMessage = error instanceof Error ? error.message : 'Unknown error'
+            const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
             addLog(`❌ main() failed: ${errorMessage}`, 'error')
             throw error
           }
@@ -292,7 +407,7 @@ export function Console() {
             addLog(`✅ ${functionName}() result: ${result}`, 'success')
             return result
           } catch (error) {
-            const errorMessage = error instanceof Error ? error.message : 'Unknown error'
+            const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
             addLog(`❌ ${functionName}() failed: ${errorMessage}`, 'error')
             throw error
           }
@@ -301,8 +416,9 @@ export function Console() {
         // Special handling for wasm-bindgen string functions
         if (isStringFunction(functionName) && args.length > 0) {
           const memory = exports.memory as WebAssembly.Memory
-          const malloc = exports.__wbindgen_malloc
-          const free = exports.__wbindgen_free
+          const allocator = bindgenAllocator(exports)
+          const malloc = allocator?.malloc
+          const free = allocator?.free
 
           if (memory && malloc && free) {
             // Encode string to WASM memory
@@ -357,8 +473,9 @@ export function Console() {
         // Handle array functions specially - they expect array parameters
         if (isArrayFunction(functionName) && args.length > 0) {
           const memory = exports.memory as WebAssembly.Memory
-          const malloc = exports.__wbindgen_malloc
-          const free = exports.__wbindgen_free
+          const allocator = bindgenAllocator(exports)
+          const malloc = allocator?.malloc
+          const free = allocator?.free
 
           // Debug: log available wasm-bindgen functions
           addLog(`Debug: Available exports for memory management:`, 'info')
@@ -522,12 +639,12 @@ export function Console() {
         addLog(`✅ ${result}`, 'success')
         return result
       } catch (error) {
-        const errorMessage = error instanceof Error ? error.message : 'Unknown error'
-        addLog(`❌ ${errorMessage}`, 'error')
+        const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
+        failureLines(error, `❌ ${errorMessage}`).forEach(([line, type]) => addLog(line, type))
         throw error
       }
     },
-    [wasmInstance, addLog]
+    [wasmInstance, workerModule, addLog]
   )
 
   const handleConsoleCommand = useCallback(
@@ -539,14 +656,14 @@ export function Console() {
       try {
         switch (parsed.type) {
           case 'help':
-            addLog('Available commands:', 'info')
-            addLog('  help - Show this help message', 'info')
-            addLog('  clear - Clear the console', 'info')
-            addLog('  list - List available functions', 'info')
-            addLog('  memory.size() - Get memory size in pages', 'info')
-            addLog('  memory.grow(pages) - Grow memory by pages', 'info')
+            addLog(t('console.help.title'), 'info')
+            addLog(t('console.help.help'), 'info')
+            addLog(t('console.help.clear'), 'info')
+            addLog(t('console.help.list'), 'info')
+            addLog(t('console.help.memorySize'), 'info')
+            addLog(t('console.help.memoryGrow'), 'info')
             if (exportedFunctions.length > 0) {
-              addLog('Available functions (shown in Playground):', 'info')
+              addLog(t('console.help.functions'), 'info')
               exportedFunctions.forEach(func => {
                 addLog(`  ${func.signature}`, 'info')
               })
@@ -559,23 +676,23 @@ export function Console() {
                 name => isInternalFunction(name) || !exportedFunctions.some(f => f.name === name)
               )
               if (hiddenFunctions.length > 0) {
-                addLog('Internal functions (callable from console only):', 'info')
+                addLog(t('console.help.internal'), 'info')
                 hiddenFunctions.forEach(name => {
-                  addLog(`  ${name}() - Internal/system function`, 'info')
+                  addLog(t('console.help.internalEntry', { name }), 'info')
                 })
               }
             }
             break
 
           case 'clear':
-            setLogs([])
+            clearLogs()
             break
 
           case 'list':
             if (exportedFunctions.length === 0) {
-              addLog('No exported functions found', 'warning')
+              addLog(t('console.list.none'), 'warning')
             } else {
-              addLog(`Found ${exportedFunctions.length} exported functions:`, 'info')
+              addLog(t('console.list.found', { count: exportedFunctions.length }), 'info')
               exportedFunctions.forEach(func => {
                 addLog(`  ${func.signature}`, 'info')
               })
@@ -583,20 +700,47 @@ export function Console() {
             break
 
           case 'memory': {
+            if (workerModule) {
+              if (parsed.name === 'memory.size()') {
+                const bytes = await workerModule.memory.byteLength()
+                addLog(
+                  t('console.memory.size', {
+                    pages: bytes / (64 * 1024),
+                    mb: (bytes / 1024 / 1024).toFixed(2),
+                  }),
+                  'success'
+                )
+              } else if (parsed.name?.startsWith('memory.grow(')) {
+                const growMatch = parsed.name.match(/memory\.grow\((\d+)\)/)
+                if (growMatch) {
+                  const pages = parseInt(growMatch[1])
+                  const prevPages = await workerModule.memory.grow(pages)
+                  addLog(
+                    t('console.memory.grown', { from: prevPages, to: prevPages + pages }),
+                    'success'
+                  )
+                }
+              }
+              break
+            }
+
             if (!wasmInstance) {
-              throw new Error('WASM module not loaded')
+              throw new Error(t('console.notLoaded'))
             }
 
             const memory = (wasmInstance.exports as any).memory as WebAssembly.Memory
             if (!memory) {
-              addLog('❌ No memory export found', 'error')
+              addLog(t('console.memory.missing'), 'error')
               break
             }
 
             if (parsed.name === 'memory.size()') {
               const pages = memory.buffer.byteLength / (64 * 1024)
               addLog(
-                `Memory size: ${pages} pages (${(memory.buffer.byteLength / 1024 / 1024).toFixed(2)} MB)`,
+                t('console.memory.size', {
+                  pages,
+                  mb: (memory.buffer.byteLength / 1024 / 1024).toFixed(2),
+                }),
                 'success'
               )
             } else if (parsed.name?.startsWith('memory.grow(')) {
@@ -604,7 +748,10 @@ export function Console() {
               if (growMatch) {
                 const pages = parseInt(growMatch[1])
                 const prevPages = memory.grow(pages)
-                addLog(`Memory grown from ${prevPages} to ${prevPages + pages} pages`, 'success')
+                addLog(
+                  t('console.memory.grown', { from: prevPages, to: prevPages + pages }),
+                  'success'
+                )
               }
             }
             break
@@ -612,43 +759,71 @@ export function Console() {
 
           case 'function':
             if (!parsed.name) {
-              throw new Error('Function name is required')
+              throw new Error(t('console.functionRequired'))
             }
             await handleFunctionCall(parsed.name, parsed.args || [], true)
             break
 
           case 'unknown':
           default:
-            addLog(`❌ Unknown command: ${command}`, 'error')
-            addLog('Type "help" for available commands', 'info')
+            addLog(t('console.unknownCommand', { command }), 'error')
+            addLog(t('console.helpHint'), 'info')
             break
         }
       } catch (error) {
-        const errorMessage = error instanceof Error ? error.message : 'Unknown error'
+        const errorMessage = error instanceof Error ? error.message : t('common.unknownError')
         addLog(`❌ ${errorMessage}`, 'error')
       }
     },
-    [wasmInstance, exportedFunctions, addLog, handleFunctionCall]
+    [wasmInstance, workerModule, exportedFunctions, addLog, clearLogs, handleFunctionCall]
   )
 
   const tabs: TabItem[] = [
     {
       id: 'console',
-      label: 'Console',
-      content: <LogContainer logs={logs} onCommand={handleConsoleCommand} interactive={true} />,
+      label: t('console.tab.console'),
+      content: (
+        <LogContainer
+          logs={logs}
+          onCommand={handleConsoleCommand}
+          interactive={true}
+          onClear={clearLogs}
+        />
+      ),
     },
     {
       id: 'playground',
-      label: 'Playground',
+      label: t('console.tab.playground'),
       content: (
         <FunctionPlayground functions={exportedFunctions} onFunctionCall={handleFunctionCall} />
       ),
     },
     {
       id: 'info',
-      label: 'Module Info',
+      label: t('console.tab.module'),
       content: <ModuleInfo moduleInfo={moduleInfo} />,
+    },    {
+      id: 'memory',
+      label: t('console.tab.memory'),
+      content: (
+        <MemoryTimeline
+          samples={memoryTimeline.samples}
+          threshold={memoryTimeline.threshold}
+          onThresholdChange={memoryTimeline.setThreshold}
+          onReset={memoryTimeline.reset}
+        />
+      ),
+    },
+    {
+      id: 'timing',
+      label: t('console.tab.timing'),
+      content: <TimingPanel entries={perfEntries} />,
     },
+    ...pluginPanels.map(panel => ({
+      id: panelTabId(panel),
+      label: panel.title,
+      content: <PluginPanel panel={panel} />,
+    })),
   ]
 
   const renderActiveTabContent = () => {
@@ -661,7 +836,12 @@ export function Console() {
       case 'console':
         return (
           <div class={containerClass}>
-            <LogContainer logs={logs} onCommand={handleConsoleCommand} interactive={true} />
+            <LogContainer
+              logs={logs}
+              onCommand={handleConsoleCommand}
+              interactive={true}
+              onClear={clearLogs}
+            />
           </div>
         )
       case 'playground':
@@ -676,12 +856,43 @@ export function Console() {
             <ModuleInfo moduleInfo={moduleInfo} />
           </div>
         )
-      default:
+      case 'memory':
         return (
           <div class={containerClass}>
-            <LogContainer logs={logs} onCommand={handleConsoleCommand} interactive={true} />
+            <MemoryTimeline
+            samples={memoryTimeline.samples}
+            threshold={memoryTimeline.threshold}
+            onThresholdChange={memoryTimeline.setThreshold}
+            onReset={memoryTimeline.reset}
+          />
           </div>
         )
+      case 'timing':
+        return (
+          <div class={containerClass}>
+            <TimingPanel entries={perfEntries} />
+          </div>
+        )
+      default: {
+        const panel = pluginPanels.find(panel => panelTabId(panel) === activeTab)
+        if (panel) {
+          return (
+            <div class={containerClass}>
+              <PluginPanel panel={panel} />
+            </div>
+          )
+        }
+        return (
+          <div class={containerClass}>
+            <LogContainer
+              logs={logs}
+              onCommand={handleConsoleCommand}
+              interactive={true}
+              onClear={clearLogs}
+            />
+          </div>
+        )
+      }
     }
   }
 
@@ -0,0 +1,68 @@
+import { useCallback, useState } from 'preact/hooks'
+import { LogEntry } from '@/types'
+
+// Entries kept per tab; older ones are dropped first
+const MAX_ENTRIES = 500
+
+interface StoredEntry {
+  timestamp: string
+  message: string
+  type: LogEntry['type']
+}
+
+function load(key: string): LogEntry[] {
+  try {
+    const saved = JSON.parse(sessionStorage.getItem(key) ?? '[]') as StoredEntry[]
+    return saved.map(entry => ({
+      timestamp: new Date(entry.timestamp),
+      message: entry.message,
+      type: entry.type,
+      restored: true,
+    }))
+  } catch {
+    return []
+  }
+}
+
+function save(key: string, logs: LogEntry[]) {
+  const stored: StoredEntry[] = logs.map(({ timestamp, message, type }) => ({
+    timestamp: timestamp.toISOString(),
+    message,
+    type,
+  }))
+  try {
+    sessionStorage.setItem(key, JSON.stringify(stored))
+  } catch {
+    // Storage full or disabled: the console still works, it just won't
+    // survive the next reload
+  }
+}
+
+/**
+ * Console entries for the module `filename`, kept in the tab's
+ * sessionStorage so they survive live reloads. Entries from before the
+ * last reload come back marked `restored`; `clear` empties both the
+ * console and what is stored.
+ */
+export function useConsoleHistory(filename: string) {
+  const key = `wasmrun:console:${filename}`
+  const [logs, setLogs] = useState<LogEntry[]>(() => load(key))
+
+  const append = useCallback(
+    (entry: LogEntry) => {
+      setLogs(prev => {
+        const next = [...prev, entry].slice(-MAX_ENTRIES)
+        save(key, next)
+        return next
+      })
+    },
+    [key]
+  )
+
+  const clear = useCallback(() => {
+    sessionStorage.removeItem(key)
+    setLogs([])
+  }, [key])
+
+  return { logs, append, clear }
+}
@@ -0,0 +1,36 @@
+import { useState, useEffect } from 'preact/hooks'
+import { apiUrl } from '@/utils/api'
+
+export interface GitState {
+  branch: string | null
+  commit: string
+  dirty: boolean
+  changes?: string[]
+}
+
+/** The served project's git state; null outside a repository or while loading */
+export function useGit() {
+  const [git, setGit] = useState<GitState | null>(null)
+
+  useEffect(() => {
+    const fetchGit = async () => {
+      try {
+        const response = await fetch(apiUrl('/api/git'))
+        setGit(response.ok ? await response.json() : null)
+      } catch (err) {
+        console.error('Error fetching git state:', err)
+        setGit(null)
+      }
+    }
+
+    fetchGit()
+  }, [])
+
+  return git
+}
+
+/** `branch@commit`, shortened the way the CLI prints it */
+export function describeGit(git: GitState): string {
+  const commit = git.commit.slice(0, 12)
+  return git.branch ? `${git.branch}@${commit}` : commit
+}
@@ -0,0 +1,31 @@
+import { useEffect, useState } from 'preact/hooks'
+
+const STORAGE_KEY = 'wasmrun-contrast'
+
+function initialHighContrast(): boolean {
+  const saved = localStorage.getItem(STORAGE_KEY)
+  if (saved) return saved === 'high'
+  return window.matchMedia?.('(prefers-contrast: more)').matches ?? false
+}
+
+/**
+ * High-contrast mode, shared by the console and OS UIs. Sets the
+ * `high-contrast` class on <html>, which globals.css styles, and remembers
+ * the choice; until one is made it follows the system preference.
+ */
+export function useHighContrast() {
+  const [highContrast, setHighContrast] = useState(initialHighContrast)
+
+  useEffect(() => {
+    document.documentElement.classList.toggle('high-contrast', highContrast)
+  }, [highContrast])
+
+  const toggleHighContrast = () => {
+    setHighContrast(prev => {
+      localStorage.setItem(STORAGE_KEY, prev ? 'normal' : 'high')
+      return !prev
+    })
+  }
+
+  return { highContrast, toggleHighContrast }
+}
@@ -0,0 +1,68 @@
+import { useCallback, useEffect, useRef, useState } from 'preact/hooks'
+import { MemorySample } from '@/utils/memory'
+
+const INTERVAL_MS = 1000
+// Five minutes of samples
+const MAX_SAMPLES = 300
+const THRESHOLD_KEY = 'wasmrun:memoryThreshold'
+
+function loadThreshold(): number | null {
+  const saved = Number(localStorage.getItem(THRESHOLD_KEY))
+  return saved > 0 ? saved : null
+}
+
+/**
+ * Samples from `sample` once a second while `sample` is set, with a warning
+ * threshold in bytes that persists across pages. `onThreshold` is called
+ * once each time memory rises past the threshold.
+ */
+export function useMemoryTimeline(
+  sample: (() => MemorySample | null | Promise<MemorySample | null>) | null,
+  onThreshold: (sample: MemorySample, threshold: number) => void
+) {
+  const [samples, setSamples] = useState<MemorySample[]>([])
+  const [threshold, setThresholdState] = useState<number | null>(loadThreshold)
+  const over = useRef(false)
+
+  useEffect(() => {
+    if (!sample) return
+    let stopped = false
+    const tick = async () => {
+      const next = await Promise.resolve()
+        .then(sample)
+        // The module went away mid-sample, e.g. during a reload
+        .catch(() => null)
+      if (!next || stopped) return
+      setSamples(prev => [...prev, next].slice(-MAX_SAMPLES))
+    }
+    tick()
+    const timer = setInterval(tick, INTERVAL_MS)
+    return () => {
+      stopped = true
+      clearInterval(timer)
+    }
+  }, [sample])
+
+  useEffect(() => {
+    const last = samples[samples.length - 1]
+    if (!last || threshold === null) return
+    const isOver = last.bytes > threshold
+    if (isOver && !over.current) onThreshold(last, threshold)
+    over.current = isOver
+  }, [samples, threshold, onThreshold])
+
+  const setThreshold = useCallback((bytes: number | null) => {
+    if (bytes && bytes > 0) {
+      localStorage.setItem(THRESHOLD_KEY, String(bytes))
+      setThresholdState(bytes)
+    } else {
+      localStorage.removeItem(THRESHOLD_KEY)
+      setThresholdState(null)
+    }
+    over.current = false
+  }, [])
+
+  const reset = useCallback(() => setSamples([]), [])
+
+  return { samples, threshold, setThreshold, reset }
+}
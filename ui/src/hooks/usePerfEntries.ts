@@ -0,0 +1,33 @@
+import { useEffect, useState } from 'preact/hooks'
+
+export interface PerfEntry {
+  name: string
+  entry_type: 'mark' | 'measure' | 'navigation' | 'resource'
+  start_time: number
+  duration: number
+}
+
+declare global {
+  interface Window {
+    __wasmrun_perf?: PerfEntry[]
+  }
+}
+
+/**
+ * This page's timing entries, as collected by the script wasmrun injects
+ * into served pages (which also reports them to `/api/perf`). Empty when
+ * the page wasn't served by wasmrun.
+ */
+export function usePerfEntries(): PerfEntry[] {
+  const [entries, setEntries] = useState<PerfEntry[]>(() => [...(window.__wasmrun_perf ?? [])])
+
+  useEffect(() => {
+    const update = () => setEntries([...(window.__wasmrun_perf ?? [])])
+    // Entries may have arrived between the first render and now
+    update()
+    window.addEventListener('wasmrun:perf', update)
+    return () => window.removeEventListener('wasmrun:perf', update)
+  }, [])
+
+  return entries
+}
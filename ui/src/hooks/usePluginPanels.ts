@@ -0,0 +1,34 @@
+import { useState, useEffect } from 'preact/hooks'
+import { apiUrl } from '@/utils/api'
+
+export interface PluginPanelInfo {
+  plugin: string
+  id: string
+  title: string
+  html: string
+}
+
+/** Console panels contributed by the plugin that builds the project */
+export function usePluginPanels() {
+  const [panels, setPanels] = useState<PluginPanelInfo[]>([])
+
+  useEffect(() => {
+    const fetchPanels = async () => {
+      try {
+        const response = await fetch(apiUrl('/api/plugins/panels'))
+        if (response.ok) setPanels((await response.json()).panels ?? [])
+      } catch (err) {
+        console.error('Error fetching plugin panels:', err)
+      }
+    }
+
+    fetchPanels()
+  }, [])
+
+  return panels
+}
+
+/** The tab id for a plugin panel */
+export function panelTabId(panel: PluginPanelInfo): string {
+  return `plugin:${panel.plugin}:${panel.id}`
+}
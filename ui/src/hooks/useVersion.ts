@@ -1,4 +1,5 @@
 import { useState, useEffect } from 'preact/hooks'
+import { apiUrl } from '@/utils/api'
 
 interface VersionInfo {
   name: string
@@ -14,7 +15,7 @@ export function useVersion() {
     const fetchVersion = async () => {
       try {
         setLoading(true)
-        const response = await fetch('/api/version')
+        const response = await fetch(apiUrl('/api/version'))
         if (!response.ok) {
           throw new Error(`Failed to fetch version: ${response.statusText}`)
         }
@@ -0,0 +1,32 @@
+// UI translations. Each language is a JSON catalog in ./locales keyed like
+// English; anything a catalog leaves out falls back to English. To add a
+// language, add its catalog and list it in `catalogs`.
+//
+// The server sets <html lang> from `--lang`, `WASMRUN_LANG` or the request's
+// Accept-Language; pages opened without it use the browser's languages.
+
+import en from './locales/en.json'
+import es from './locales/es.json'
+
+export type MessageKey = keyof typeof en
+
+const catalogs: Record<string, Partial<Record<MessageKey, string>>> = { en, es }
+
+function detectLanguage(): string {
+  const requested = [document.documentElement.lang, ...navigator.languages]
+  for (const tag of requested) {
+    const primary = tag?.split('-')[0].toLowerCase()
+    if (primary && primary in catalogs) return primary
+  }
+  return 'en'
+}
+
+export const language = detectLanguage()
+
+/** The message for `key`, with `{name}` arguments filled in */
+export function t(key: MessageKey, args: Record<string, string | number> = {}): string {
+  const template = catalogs[language][key] ?? en[key]
+  return template.replace(/\{(\w+)\}/g, (match, name) =>
+    name in args ? String(args[name]) : match
+  )
+}
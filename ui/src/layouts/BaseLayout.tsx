@@ -1,5 +1,6 @@
 import { ComponentChildren } from 'preact'
 import { ThemeToggle } from '@/components/ThemeToggle'
+import { ContrastToggle } from '@/components/ContrastToggle'
 import { useVersion } from '@/hooks/useVersion'
 
 interface BaseLayoutProps {
@@ -26,7 +27,10 @@ export function BaseLayout({ title, children, showFooter = true }: BaseLayoutPro
           </div>
           <h1 class="ml-4 text-3xl font-semibold text-light-text dark:text-dark-text">{title}</h1>
         </div>
-        <ThemeToggle />
+        <div class="flex items-center gap-2">
+          <ContrastToggle />
+          <ThemeToggle />
+        </div>
       </header>
 
       <main class="flex-1 flex flex-col">{children}</main>
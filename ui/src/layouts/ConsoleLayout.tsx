@@ -1,8 +1,14 @@
 import { ComponentChildren } from 'preact'
 import { TabItem } from '@/types'
 import { ThemeToggle } from '@/components/ThemeToggle'
+import { ContrastToggle } from '@/components/ContrastToggle'
+import { RebuildNotice } from '@/components/RebuildNotice'
+import { WatchStatus } from '@/components/WatchStatus'
 import { useVersion } from '@/hooks/useVersion'
+import { useGit, describeGit } from '@/hooks/useGit'
 import clsx from 'clsx'
+import { t } from '@/i18n'
+import { moveFocus } from '@/utils/a11y'
 
 interface ConsoleLayoutProps {
   filename?: string
@@ -20,9 +26,21 @@ export function ConsoleLayout({
   onTabChange,
 }: ConsoleLayoutProps) {
   const { version, loading } = useVersion()
+  const git = useGit()
+
+  const handleTabKeyDown = (e: KeyboardEvent) => {
+    const target = moveFocus(e, 'horizontal')
+    if (target?.dataset.tab) onTabChange?.(target.dataset.tab)
+  }
 
   return (
     <div class="h-screen flex flex-col bg-light-bg dark:bg-dark-bg text-light-text dark:text-dark-text overflow-hidden">
+      <a
+        href="#main-content"
+        class="sr-only focus:not-sr-only focus:absolute focus:top-2 focus:left-2 focus:z-50 focus:px-4 focus:py-2 focus:rounded focus:bg-light-surface dark:focus:bg-dark-surface"
+      >
+        {t('a11y.skipToContent')}
+      </a>
       <header class="bg-light-surface dark:bg-dark-surface shadow-lg flex-shrink-0">
         <div class="flex items-center justify-between px-8 py-4">
           <div class="flex items-center">
@@ -39,7 +57,7 @@ export function ConsoleLayout({
           <div class="flex-1 flex justify-center items-center">
             {filename && (
               <p class="text-sm text-light-textDim dark:text-dark-textDim mt-1">
-                Running:{' '}
+                {t('console.running')}{' '}
                 <span class="font-mono text-green-500 dark:text-green-400">{filename}</span>
               </p>
             )}
@@ -101,15 +119,29 @@ export function ConsoleLayout({
                 </g>
               </svg>
             </a>
+            <ContrastToggle />
             <ThemeToggle />
           </div>
         </div>
         {tabs && tabs.length > 0 && (
-          <div class="flex bg-light-surface2 dark:bg-dark-surface2 border-t border-light-surface3 dark:border-dark-surface3">
+          <div
+            role="tablist"
+            aria-label={t('a11y.views')}
+            data-roving-group
+            class="flex bg-light-surface2 dark:bg-dark-surface2 border-t border-light-surface3 dark:border-dark-surface3"
+          >
             {tabs.map(tab => (
               <button
                 key={tab.id}
+                id={`tab-${tab.id}`}
+                role="tab"
+                aria-selected={activeTab === tab.id}
+                aria-controls="main-content"
+                tabIndex={activeTab === tab.id ? 0 : -1}
+                data-roving
+                data-tab={tab.id}
                 onClick={() => !tab.disabled && onTabChange?.(tab.id)}
+                onKeyDown={handleTabKeyDown}
                 class={clsx('px-6 py-3 text-sm font-medium transition-colors duration-200', {
                   'bg-light-surface dark:bg-dark-surface border-b-2 border-light-accent2 dark:border-dark-accent text-light-textMuted dark:text-dark-textMuted':
                     activeTab === tab.id,
@@ -127,14 +159,38 @@ export function ConsoleLayout({
         )}
       </header>
 
-      <main class="flex-1 flex flex-col min-h-0">{children}</main>
+      <main
+        id="main-content"
+        tabIndex={-1}
+        role={tabs && tabs.length > 0 ? 'tabpanel' : undefined}
+        aria-labelledby={activeTab ? `tab-${activeTab}` : undefined}
+        class="flex-1 flex flex-col min-h-0 focus:outline-none"
+      >
+        {children}
+      </main>
+
+      <RebuildNotice />
+      <WatchStatus />
 
       <footer class="bg-light-surface dark:bg-dark-surface py-2 flex-shrink-0">
         <div class="px-8">
-          <div class="flex justify-center items-center">
+          <div class="flex justify-center items-center gap-4">
             <p class="text-xs font-semibold text-light-textDim dark:text-dark-textDim">
               Wasmrun{!loading && version && ` v${version}`}
             </p>
+            {git && (
+              <p
+                class="text-xs font-mono text-light-textDim dark:text-dark-textDim"
+                title={git.changes?.join('\n')}
+              >
+                {describeGit(git)}
+                {git.dirty && (
+                  <span class="ml-2 text-yellow-600 dark:text-yellow-400">
+                    ● {t('console.gitDirty')}
+                  </span>
+                )}
+              </p>
+            )}
           </div>
         </div>
       </footer>
@@ -0,0 +1,99 @@
+import type { ComponentChildren } from 'preact'
+import { useEffect, useState } from 'preact/hooks'
+import { fetchSession, login, logout, type AuthSession } from './auth'
+
+interface AuthGateProps {
+  children: ComponentChildren
+}
+
+/** Shows a sign-in form instead of the OS UI until the server accepts a login */
+export default function AuthGate({ children }: AuthGateProps) {
+  const [session, setSession] = useState<AuthSession | null>(null)
+  const [username, setUsername] = useState('admin')
+  const [password, setPassword] = useState('')
+  const [error, setError] = useState<string | null>(null)
+
+  const refresh = async () => {
+    try {
+      setSession(await fetchSession())
+    } catch {
+      // Older servers have no auth endpoints
+      setSession({ enabled: false, authenticated: true, user: null, csrf_token: null })
+    }
+  }
+
+  useEffect(() => {
+    refresh()
+  }, [])
+
+  const handleSubmit = async (e: Event) => {
+    e.preventDefault()
+    const failure = await login(username, password)
+    setError(failure)
+    if (!failure) {
+      setPassword('')
+      await refresh()
+    }
+  }
+
+  const handleLogout = async () => {
+    await logout()
+    await refresh()
+  }
+
+  if (!session) return null
+
+  if (!session.authenticated) {
+    return (
+      <div className="min-h-screen flex items-center justify-center bg-gradient-to-br from-black via-gray-900 to-green-900 text-white">
+        <form
+          onSubmit={handleSubmit}
+          className="w-80 bg-black/40 backdrop-blur-lg border border-green-500/30 rounded-xl p-6 space-y-4"
+        >
+          <img src="/assets/logo-text.png" alt="wasmrun OS" className="h-8 mx-auto object-contain" />
+          <p className="text-sm text-white/70 text-center">Sign in to this OS mode server</p>
+          <input
+            value={username}
+            onInput={e => setUsername((e.target as HTMLInputElement).value)}
+            placeholder="Username"
+            autoComplete="username"
+            className="w-full bg-black/40 border border-green-500/30 rounded px-3 py-2 text-sm"
+          />
+          <input
+            type="password"
+            value={password}
+            onInput={e => setPassword((e.target as HTMLInputElement).value)}
+            placeholder="Password"
+            autoComplete="current-password"
+            autoFocus
+            className="w-full bg-black/40 border border-green-500/30 rounded px-3 py-2 text-sm"
+          />
+          {error && <div className="text-red-400 text-xs">{error}</div>}
+          <button
+            type="submit"
+            className="w-full py-2 bg-green-600 hover:bg-green-700 border border-green-400/50 rounded text-sm"
+          >
+            Sign in
+          </button>
+        </form>
+      </div>
+    )
+  }
+
+  return (
+    <>
+      {children}
+      {session.enabled && (
+        <div className="fixed bottom-4 left-4 text-xs text-white/60 flex items-center gap-2">
+          👤 {session.user}
+          <button
+            onClick={handleLogout}
+            className="px-2 py-0.5 bg-green-600/30 hover:bg-green-600/50 border border-green-500/30 rounded"
+          >
+            Sign out
+          </button>
+        </div>
+      )}
+    </>
+  )
+}
@@ -6,6 +6,9 @@ import KernelStatusPanel from '../components/os/KernelStatusPanel'
 import ConsolePanel from '../components/os/ConsolePanel'
 import FilesystemPanel from '../components/os/FilesystemPanel'
 import LogsPanel from '../components/os/LogsPanel'
+import ProcessesPanel from '../components/os/ProcessesPanel'
+import PortsPanel from '../components/os/PortsPanel'
+import SocketsPanel from '../components/os/SocketsPanel'
 import { panels } from '../components/os/panels'
 import { formatUptime, formatBytes } from '../utils/osUtils'
 import { WasmRunner } from './WasmRunner'
@@ -50,6 +53,10 @@ export default function OSMode() {
   const [selectedFile, setSelectedFile] = useState<string | null>(null)
   const [fileContent, setFileContent] = useState<string>('')
   const [isEditing, setIsEditing] = useState(false)
+  const [saveStatus, setSaveStatus] = useState<string | null>(null)
+  const [rebuildOnSave, setRebuildOnSave] = useState(
+    () => localStorage.getItem('wasmrun-rebuild-on-save') === 'true'
+  )
 
   const projectName = (window as any).PROJECT_NAME || 'Unknown Project'
   const [currentPath, setCurrentPath] = useState(`/${projectName}`)
@@ -167,21 +174,49 @@ export default function OSMode() {
     }
   }, [])
 
-  const saveFile = useCallback(async (path: string, content: string) => {
-    try {
-      const response = await fetch(`/api/fs/write${path}`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'text/plain' },
-        body: content,
-      })
-      const data = await response.json()
-      if (data.success) {
-        setIsEditing(false)
+  const saveFile = useCallback(
+    async (path: string, content: string) => {
+      try {
+        const response = await fetch(`/api/fs/write${path}`, {
+          method: 'POST',
+          headers: { 'Content-Type': 'text/plain' },
+          body: content,
+        })
+        const data = await response.json()
+        if (!data.success) {
+          setSaveStatus(`Save failed: ${data.error ?? response.statusText}`)
+          return
+        }
+      } catch {
+        setSaveStatus('Save failed')
+        return
+      }
+      if (!rebuildOnSave) {
+        setSaveStatus('Saved')
+        return
       }
-    } catch {
-      // ignore
-    }
-  }, [])
+
+      setSaveStatus('Saved, rebuilding…')
+      try {
+        const response = await fetch('/api/kernel/restart', { method: 'POST' })
+        const data = await response.json()
+        if (data.success) {
+          setSaveStatus(`Saved and restarted as PID ${data.pid}`)
+          startWasmRunner()
+        } else {
+          setSaveStatus(`Saved, but the rebuild failed: ${data.error}`)
+        }
+      } catch {
+        setSaveStatus('Saved, but the rebuild failed')
+      }
+    },
+    [rebuildOnSave, startWasmRunner]
+  )
+
+  const toggleRebuildOnSave = (enabled: boolean) => {
+    localStorage.setItem('wasmrun-rebuild-on-save', String(enabled))
+    setRebuildOnSave(enabled)
+  }
 
   // --- Effects ---
 
@@ -222,6 +257,8 @@ export default function OSMode() {
   }
 
   const handleFileSelect = (path: string) => {
+    setIsEditing(false)
+    setSaveStatus(null)
     readFile(path)
   }
 
@@ -233,6 +270,7 @@ export default function OSMode() {
 
   const handleCancel = () => {
     setIsEditing(false)
+    setSaveStatus(null)
     if (selectedFile) {
       readFile(selectedFile)
     }
@@ -283,6 +321,9 @@ export default function OSMode() {
             selectedFile={selectedFile}
             fileContent={fileContent}
             isEditing={isEditing}
+            saveStatus={saveStatus}
+            rebuildOnSave={rebuildOnSave}
+            readOnly={kernelStats?.read_only ?? false}
             onNavigateUp={handleNavigateUp}
             onRefresh={handleRefresh}
             onNavigate={handleNavigate}
@@ -291,10 +332,26 @@ export default function OSMode() {
             onSave={handleSave}
             onCancel={handleCancel}
             onContentChange={setFileContent}
+            onRebuildOnSaveChange={toggleRebuildOnSave}
             formatBytes={formatBytes}
           />
         )
 
+      case 'processes':
+        return (
+          <ProcessesPanel
+            projectPid={kernelStats?.project_pid ?? null}
+            readOnly={kernelStats?.read_only ?? false}
+            onProjectRestarted={startWasmRunner}
+          />
+        )
+
+      case 'ports':
+        return <PortsPanel readOnly={kernelStats?.read_only ?? false} />
+
+      case 'sockets':
+        return <SocketsPanel readOnly={kernelStats?.read_only ?? false} />
+
       case 'logs':
         return <LogsPanel />
 
@@ -315,6 +372,12 @@ export default function OSMode() {
 
   return (
     <div className="min-h-screen bg-gradient-to-br from-black via-gray-900 to-green-900 text-white">
+      <a
+        href="#os-main"
+        className="sr-only focus:not-sr-only focus:absolute focus:top-2 focus:left-2 focus:z-50 focus:px-4 focus:py-2 focus:rounded focus:bg-black"
+      >
+        Skip to panel
+      </a>
       <Header
         projectName={projectName}
         kernelStatus={kernelStatus}
@@ -324,7 +387,13 @@ export default function OSMode() {
 
       <div className="flex h-[calc(100vh-80px)]">
         <Sidebar panels={panels} activePanel={activePanel} onPanelChange={setActivePanel} />
-        <main className="flex-1 overflow-hidden bg-black/10 backdrop-blur-sm">{renderPanel()}</main>
+        <main
+          id="os-main"
+          tabIndex={-1}
+          className="flex-1 overflow-hidden bg-black/10 backdrop-blur-sm focus:outline-none"
+        >
+          {renderPanel()}
+        </main>
       </div>
 
       <div className="fixed bottom-4 right-4 text-xs text-white/50">
@@ -1,4 +1,5 @@
 import { WASIImplementation, WASI_ERRNO } from '../wasi/wasmrun_wasi_impl.js'
+import { isUnreachableTrap, parsePanic } from '../utils/panic'
 
 export type WasmRunnerStatus =
   | 'idle'
@@ -32,6 +33,8 @@ interface RuntimeInfoResponse {
   wasmhub_runtime: string
   cached: boolean
   cached_version?: string
+  env?: Record<string, string>
+  args?: string[]
 }
 
 const ENTRY_CANDIDATES: Record<string, string[]> = {
@@ -60,6 +63,8 @@ export class WasmRunner {
   private status: WasmRunnerStatus = 'idle'
   private callbacks: WasmRunnerCallbacks
   private wasiInstance: WASIImplementation | null = null
+  // Tail of the guest's stderr, where a Rust panic prints its message
+  private recentStderr = ''
 
   constructor(callbacks: WasmRunnerCallbacks = {}) {
     this.callbacks = callbacks
@@ -90,7 +95,7 @@ export class WasmRunner {
       this.setStatus('populating-fs')
       const entryFile = this.detectEntryFile(runtimeInfo.detected_language, projectFiles.files)
 
-      this.wasiInstance = this.createWasiInstance(runtimeLang, entryFile)
+      this.wasiInstance = this.createWasiInstance(runtimeLang, entryFile, runtimeInfo)
       this.populateFilesystem(projectFiles.files)
 
       this.setStatus('starting')
@@ -151,16 +156,23 @@ export class WasmRunner {
     return data
   }
 
-  private createWasiInstance(runtimeLang: string, entryFile: string): WASIImplementation {
+  private createWasiInstance(
+    runtimeLang: string,
+    entryFile: string,
+    launch: Pick<RuntimeInfoResponse, 'env' | 'args'>
+  ): WASIImplementation {
     const runtimeName = RUNTIME_NAMES[runtimeLang] || runtimeLang
     const args = entryFile ? [runtimeName, entryFile] : [runtimeName]
 
     return new WASIImplementation({
-      args,
-      env: {},
+      args: [...args, ...(launch.args ?? [])],
+      env: launch.env ?? {},
       preopens: { '/': '/' },
       stdout: (text: string) => this.callbacks.onStdout?.(text),
-      stderr: (text: string) => this.callbacks.onStderr?.(text),
+      stderr: (text: string) => {
+        this.recentStderr = (this.recentStderr + text).slice(-16 * 1024)
+        this.callbacks.onStderr?.(text)
+      },
     })
   }
 
@@ -243,8 +255,15 @@ export class WasmRunner {
         return
       }
 
+      const panic = isUnreachableTrap(err) ? parsePanic(this.recentStderr) : null
       this.setStatus('error')
-      this.callbacks.onError?.(err)
+      if (panic) {
+        const thread = panic.thread ? ` in thread '${panic.thread}'` : ''
+        const at = panic.location ? `\n    at ${panic.location}` : ''
+        this.callbacks.onError?.(new Error(`Rust panic${thread}: ${panic.message ?? err.message}${at}`))
+      } else {
+        this.callbacks.onError?.(err)
+      }
     } else {
       this.setStatus('error')
       this.callbacks.onError?.(new Error(String(err)))
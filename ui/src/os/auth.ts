@@ -0,0 +1,60 @@
+// Session handling for OS mode servers started with `[auth] enabled = true`.
+// The session itself is an HttpOnly cookie; state-changing requests also
+// have to carry the session's CSRF token, which `installCsrfHeader` adds to
+// every same-origin `fetch`.
+
+export interface AuthSession {
+  enabled: boolean
+  authenticated: boolean
+  user: string | null
+  csrf_token: string | null
+}
+
+let csrfToken: string | null = null
+
+export function installCsrfHeader(): void {
+  const originalFetch = window.fetch.bind(window)
+  window.fetch = (input: RequestInfo | URL, init: RequestInit = {}) => {
+    const method = (init.method ?? 'GET').toUpperCase()
+    const url = typeof input === 'string' ? input : input instanceof URL ? input.href : input.url
+    const sameOrigin = new URL(url, window.location.href).origin === window.location.origin
+    if (csrfToken && sameOrigin && method !== 'GET' && method !== 'HEAD') {
+      const headers = new Headers(init.headers)
+      headers.set('X-CSRF-Token', csrfToken)
+      init = { ...init, headers }
+    }
+    return originalFetch(input, init)
+  }
+}
+
+export async function fetchSession(): Promise<AuthSession> {
+  const response = await fetch('/api/auth/session')
+  const session: AuthSession = await response.json()
+  csrfToken = session.csrf_token
+  return session
+}
+
+/** Sign in; resolves to an error message on failure */
+export async function login(username: string, password: string): Promise<string | null> {
+  try {
+    const response = await fetch('/api/auth/login', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ username, password }),
+    })
+    const data = await response.json()
+    if (!data.success) return data.error ?? 'Sign-in failed'
+    csrfToken = data.csrf_token
+    return null
+  } catch {
+    return 'Sign-in failed'
+  }
+}
+
+export async function logout(): Promise<void> {
+  try {
+    await fetch('/api/auth/logout', { method: 'POST' })
+  } finally {
+    csrfToken = null
+  }
+}
@@ -1,5 +1,14 @@
 import { render } from 'preact'
 import OSMode from './OSMode'
+import AuthGate from './AuthGate'
+import { installCsrfHeader } from './auth'
 import '@/styles/globals.css'
 
-render(<OSMode />, document.getElementById('root')!)
+installCsrfHeader()
+
+render(
+  <AuthGate>
+    <OSMode />
+  </AuthGate>,
+  document.getElementById('root')!
+)
@@ -25,6 +25,60 @@ export interface WasmInspectionInfo {
   plugin?: PluginInfo
 }
 
+export interface ModuleImportDetail {
+  module: string
+  name: string
+  kind: 'function' | 'table' | 'memory' | 'global'
+  signature: string | null
+}
+
+export interface ModuleExportDetail {
+  name: string
+  kind: 'function' | 'table' | 'memory' | 'global'
+  index: number
+  signature: string | null
+  function: string | null
+}
+
+export interface ModuleLimitsDetail {
+  initial: number
+  max: number | null
+  element_type: string | null
+  imported_from: string | null
+}
+
+export interface ModuleFunctionSize {
+  index: number
+  name: string
+  size: number
+}
+
+export interface ModuleSectionDetail {
+  id: number
+  name: string
+  size: number
+  custom: boolean
+}
+
+export interface ModuleDetails {
+  file_size: number
+  version: number
+  imports: ModuleImportDetail[]
+  exports: ModuleExportDetail[]
+  memories: ModuleLimitsDetail[]
+  tables: ModuleLimitsDetail[]
+  globals: number
+  start: number | null
+  webgpu: boolean
+  functions: {
+    imported: number
+    defined: number
+    code_size: number
+    largest: ModuleFunctionSize[]
+  }
+  sections: ModuleSectionDetail[]
+}
+
 export interface PluginCapabilities {
   compile_wasm: boolean
   compile_webapp: boolean
@@ -58,12 +112,15 @@ export interface WasmModuleInfo {
   isWasi: boolean
   plugin?: PluginInfo
   inspection?: WasmInspectionInfo
+  details?: ModuleDetails
 }
 
 export interface LogEntry {
   timestamp: Date
   message: string
   type: 'info' | 'success' | 'error' | 'warning'
+  // Logged before the page last reloaded
+  restored?: boolean
 }
 
 export interface FunctionParameter {
@@ -5,6 +5,7 @@ export interface KernelStats {
   active_runtimes: string[]
   active_dev_servers: number
   project_pid: number | null
+  read_only: boolean
   // System information
   os: string
   arch: string
@@ -15,17 +16,48 @@ export interface KernelStats {
   supported_languages: string[]
 }
 
+export interface FsMountInfo {
+  guest_path: string
+  host_path: string
+  size: number
+}
+
+export interface FsQuota {
+  guest_path: string
+  limit: number
+  used: number
+}
+
 export interface FilesystemStats {
   total_mounts: number
   total_size: number
   open_fds: number
-  mounts: Array<{
-    guest_path: string
-    host_path: string
-    size: number
+  mounts: FsMountInfo[]
+  quotas: FsQuota[]
+  processes: Array<{
+    pid: number
+    written: number
+    mounts: FsMountInfo[]
   }>
 }
 
+export interface FsSearchFile {
+  path: string
+  name_match: boolean
+  lines: Array<{
+    line: number
+    text: string
+    before: string[]
+    after: string[]
+  }>
+}
+
+export interface FsSearchResults {
+  files: FsSearchFile[]
+  files_scanned: number
+  truncated: boolean
+}
+
 export interface DirEntry {
   name: string
   is_dir: boolean
@@ -55,3 +87,96 @@ export interface LogEntry {
   message: string
   pid?: number
 }
+
+export type SupervisorRestart = 'always' | 'on-failure' | 'never'
+
+export type MemberStatus = 'stopped' | 'running' | 'backoff' | 'exited' | 'failed'
+
+export interface SupervisorMember {
+  name: string
+  pid: number | null
+  status: MemberStatus
+  restarts: number
+  last_exit: number | null
+  last_error: string | null
+  restart_in_ms: number | null
+}
+
+export interface SupervisorGroup {
+  name: string
+  restart: SupervisorRestart
+  max_restarts: number
+  backoff_ms: number
+  processes: string[]
+  members: SupervisorMember[]
+}
+
+export interface KernelProcess {
+  pid: number
+  name: string
+  language: string
+  state: string
+  memory_usage: number
+  env: Record<string, string>
+  args: string[]
+  restart_policy: 'never' | 'on-boot' | null
+}
+
+export type PortStatus = 'active' | 'remapped'
+
+export interface PortRow {
+  pid: number
+  process: string
+  language: string
+  guest_port: number
+  host_port: number
+  protocol: 'tcp' | 'udp'
+  status: PortStatus
+  /** Set when remapped: the host port that was taken */
+  requested?: number
+  /** The process holding `requested`, or null for a program outside wasmrun */
+  held_by?: number | null
+  /** Peers of a UDP forward; null for TCP */
+  sessions: UdpSession[] | null
+}
+
+export interface UdpSession {
+  peer: string
+  bytes_in: number
+  bytes_out: number
+  idle_secs: number
+}
+
+export interface PortConflict {
+  guest_port: number
+  protocol: 'tcp' | 'udp'
+  pids: number[]
+}
+
+export type SocketState = 'created' | 'bound' | 'listening' | 'connected'
+
+export interface SocketRow {
+  fd: number
+  /** `virtual` for the guest-to-guest network */
+  kind: 'tcp' | 'udp' | 'virtual'
+  state: SocketState
+  local_addr: string | null
+  peer_addr: string | null
+  bytes_in: number
+  bytes_out: number
+  options: SocketOptions
+}
+
+export interface SocketOptions {
+  keepalive: boolean
+  nodelay: boolean
+  recv_timeout_ms: number | null
+  send_timeout_ms: number | null
+}
+
+export interface ProcessConfig {
+  pid: number
+  name: string
+  env: Record<string, string>
+  args: string[]
+}
@@ -0,0 +1,35 @@
+// Keyboard helpers shared by tab bars, the OS sidebar and the file explorer.
+// A group is any element marked `data-roving-group`; its items are the
+// `data-roving` elements inside it. Only one item per group is in the tab
+// order (tabIndex 0), and the arrow keys move focus between the rest.
+
+type Orientation = 'horizontal' | 'vertical'
+
+const keys: Record<Orientation, [string, string]> = {
+  horizontal: ['ArrowLeft', 'ArrowRight'],
+  vertical: ['ArrowUp', 'ArrowDown'],
+}
+
+/**
+ * Move focus for Arrow, Home and End keys within the target's group.
+ * Returns the newly focused item, or null if the key wasn't handled.
+ */
+export function moveFocus(e: KeyboardEvent, orientation: Orientation): HTMLElement | null {
+  const current = e.currentTarget as HTMLElement
+  const group = current.closest('[data-roving-group]')
+  if (!group) return null
+  const items = Array.from(group.querySelectorAll<HTMLElement>('[data-roving]:not([disabled])'))
+  const index = items.indexOf(current)
+  const [previous, next] = keys[orientation]
+
+  let target: HTMLElement | undefined
+  if (e.key === previous) target = items[(index - 1 + items.length) % items.length]
+  else if (e.key === next) target = items[(index + 1) % items.length]
+  else if (e.key === 'Home') target = items[0]
+  else if (e.key === 'End') target = items[items.length - 1]
+  if (!target) return null
+
+  e.preventDefault()
+  target.focus()
+  return target
+}
@@ -0,0 +1,155 @@
+// ANSI escape handling for guest and toolchain output shown in the browser.
+// SGR sequences (colors, bold, italic, underline) become styled segments;
+// every other escape, such as cursor movement or line erasing, is dropped.
+
+export interface AnsiStyle {
+  color?: string
+  background?: string
+  bold?: boolean
+  dim?: boolean
+  italic?: boolean
+  underline?: boolean
+}
+
+export interface AnsiSegment {
+  text: string
+  style: AnsiStyle
+}
+
+// The xterm palette, readable on the panels' dark backgrounds
+const PALETTE = [
+  '#4b5563',
+  '#f87171',
+  '#4ade80',
+  '#facc15',
+  '#60a5fa',
+  '#c084fc',
+  '#22d3ee',
+  '#e5e7eb',
+  '#9ca3af',
+  '#fca5a5',
+  '#86efac',
+  '#fde047',
+  '#93c5fd',
+  '#d8b4fe',
+  '#67e8f9',
+  '#ffffff',
+]
+
+// CSI sequences, OSC sequences (ended by BEL or ST) and two-byte escapes
+// eslint-disable-next-line no-control-regex
+const ESCAPE = /\x1b\[([0-9;?]*)([A-Za-z])|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]/g
+
+function color256(n: number): string | undefined {
+  if (n < 16) return PALETTE[n]
+  if (n < 232) {
+    const level = (v: number) => (v === 0 ? 0 : 55 + v * 40)
+    const i = n - 16
+    return `rgb(${level(Math.floor(i / 36))}, ${level(Math.floor(i / 6) % 6)}, ${level(i % 6)})`
+  }
+  if (n < 256) {
+    const gray = 8 + (n - 232) * 10
+    return `rgb(${gray}, ${gray}, ${gray})`
+  }
+  return undefined
+}
+
+// Apply one SGR parameter list to `style`, returning the new style
+function applySgr(style: AnsiStyle, params: number[]): AnsiStyle {
+  const next = { ...style }
+  for (let i = 0; i < params.length; i++) {
+    const code = params[i]
+    if (code === 0) {
+      for (const key of Object.keys(next)) delete next[key as keyof AnsiStyle]
+    } else if (code === 1) next.bold = true
+    else if (code === 2) next.dim = true
+    else if (code === 3) next.italic = true
+    else if (code === 4) next.underline = true
+    else if (code === 22) next.bold = next.dim = false
+    else if (code === 23) next.italic = false
+    else if (code === 24) next.underline = false
+    else if (code >= 30 && code <= 37) next.color = PALETTE[code - 30]
+    else if (code >= 90 && code <= 97) next.color = PALETTE[code - 90 + 8]
+    else if (code === 39) delete next.color
+    else if (code >= 40 && code <= 47) next.background = PALETTE[code - 40]
+    else if (code >= 100 && code <= 107) next.background = PALETTE[code - 100 + 8]
+    else if (code === 49) delete next.background
+    else if (code === 38 || code === 48) {
+      // 38;5;n picks from the 256-color palette, 38;2;r;g;b is true color
+      let value: string | undefined
+      if (params[i + 1] === 5) {
+        value = color256(params[i + 2])
+        i += 2
+      } else if (params[i + 1] === 2) {
+        const [r, g, b] = params.slice(i + 2, i + 5)
+        value = `rgb(${r ?? 0}, ${g ?? 0}, ${b ?? 0})`
+        i += 4
+      }
+      if (value) {
+        if (code === 38) next.color = value
+        else next.background = value
+      }
+    }
+  }
+  return next
+}
+
+// Keep what a terminal would show after carriage returns: progress bars
+// redraw a line by returning to its start
+function collapseCarriageReturns(text: string): string {
+  if (!text.includes('\r')) return text
+  return text
+    .split('\n')
+    .map(line => {
+      const parts = line.replace(/\r+$/, '').split('\r')
+      return parts[parts.length - 1]
+    })
+    .join('\n')
+}
+
+/** Split `text` into runs of identically styled text */
+export function parseAnsi(text: string): AnsiSegment[] {
+  const input = collapseCarriageReturns(text)
+  const segments: AnsiSegment[] = []
+  let style: AnsiStyle = {}
+  let last = 0
+  const push = (end: number) => {
+    if (end > last) segments.push({ text: input.slice(last, end), style })
+  }
+
+  for (const match of input.matchAll(ESCAPE)) {
+    const index = match.index ?? 0
+    push(index)
+    last = index + match[0].length
+    if (match[2] === 'm') {
+      const params = match[1] === '' ? [0] : match[1].split(';').map(p => Number(p) || 0)
+      style = applySgr(style, params)
+    }
+  }
+  push(input.length)
+  return segments
+}
+
+/** Whether `text` contains escapes worth parsing */
+export function hasAnsi(text: string): boolean {
+  return text.includes('\x1b') || text.includes('\r')
+}
+
+/** `text` without any escape sequences */
+export function stripAnsi(text: string): string {
+  return parseAnsi(text)
+    .map(segment => segment.text)
+    .join('')
+}
+
+/** Inline CSS for a segment's style */
+export function ansiCss(style: AnsiStyle): Record<string, string> {
+  const css: Record<string, string> = {}
+  if (style.color) css.color = style.color
+  if (style.background) css.backgroundColor = style.background
+  if (style.bold) css.fontWeight = 'bold'
+  if (style.dim) css.opacity = '0.7'
+  if (style.italic) css.fontStyle = 'italic'
+  if (style.underline) css.textDecoration = 'underline'
+  return css
+}
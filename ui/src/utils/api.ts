@@ -0,0 +1,9 @@
+// With `--api-port` the dev server moves its control endpoints to a second
+// port and names it in <meta name="wasmrun-api-port">; otherwise they are
+// same-origin
+const apiPort = document.querySelector<HTMLMetaElement>('meta[name="wasmrun-api-port"]')?.content
+
+/** URL of a control endpoint such as `/api/version` */
+export function apiUrl(path: string): string {
+  return apiPort ? `${location.protocol}//${location.hostname}:${apiPort}${path}` : path
+}
@@ -0,0 +1,248 @@
+// Lightweight syntax highlighting for the file editor. Each language is a
+// list of token rules tried in order at every position; anything no rule
+// matches is emitted as plain text.
+
+type TokenKind = 'comment' | 'string' | 'number' | 'keyword' | 'type' | 'tag' | 'attr'
+
+interface Grammar {
+  rules: Array<[TokenKind, RegExp]>
+}
+
+const tokenClasses: Record<TokenKind, string> = {
+  comment: 'text-white/40 italic',
+  string: 'text-amber-300',
+  number: 'text-purple-300',
+  keyword: 'text-sky-400',
+  type: 'text-green-300',
+  tag: 'text-sky-400',
+  attr: 'text-green-300',
+}
+
+const keywords = (words: string) => new RegExp(`\\b(?:${words.split(' ').join('|')})\\b`, 'y')
+
+const NUMBER: [TokenKind, RegExp] = [
+  'number',
+  /\b(?:0x[\da-fA-F_]+|\d[\d_]*(?:\.\d+)?(?:e[+-]?\d+)?)\b/y,
+]
+const DOUBLE_QUOTED: [TokenKind, RegExp] = ['string', /"(?:[^"\\\n]|\\.)*"?/y]
+const SINGLE_QUOTED: [TokenKind, RegExp] = ['string', /'(?:[^'\\\n]|\\.)*'?/y]
+const SLASH_COMMENTS: Array<[TokenKind, RegExp]> = [
+  ['comment', /\/\/.*/y],
+  ['comment', /\/\*[\s\S]*?(?:\*\/|$)/y],
+]
+const HASH_COMMENT: [TokenKind, RegExp] = ['comment', /#.*/y]
+const CAPITALISED: [TokenKind, RegExp] = ['type', /\b[A-Z][A-Za-z0-9_]*\b/y]
+
+const grammars: Record<string, Grammar> = {
+  rust: {
+    rules: [
+      ...SLASH_COMMENTS,
+      DOUBLE_QUOTED,
+      ['string', /'(?:[^'\\\n]|\\.)'/y],
+      [
+        'keyword',
+        keywords(
+          'as async await break const continue crate dyn else enum extern false fn for if impl in let loop match mod move mut pub ref return self Self static struct super trait true type unsafe use where while'
+        ),
+      ],
+      CAPITALISED,
+      NUMBER,
+    ],
+  },
+  javascript: {
+    rules: [
+      ...SLASH_COMMENTS,
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+      ['string', /`(?:[^`\\]|\\.)*`?/y],
+      [
+        'keyword',
+        keywords(
+          'async await break case catch class const continue default delete do else export extends false finally for from function if import in instanceof interface let new null of return static super switch this throw true try type typeof undefined var void while yield'
+        ),
+      ],
+      CAPITALISED,
+      NUMBER,
+    ],
+  },
+  python: {
+    rules: [
+      HASH_COMMENT,
+      ['string', /"""[\s\S]*?(?:"""|$)/y],
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+      [
+        'keyword',
+        keywords(
+          'and as assert async await break class continue def del elif else except False finally for from global if import in is lambda None nonlocal not or pass raise return True try while with yield'
+        ),
+      ],
+      CAPITALISED,
+      NUMBER,
+    ],
+  },
+  go: {
+    rules: [
+      ...SLASH_COMMENTS,
+      DOUBLE_QUOTED,
+      ['string', /`[^`]*`?/y],
+      [
+        'keyword',
+        keywords(
+          'break case chan const continue default defer else fallthrough false for func go goto if import interface map nil package range return select struct switch true type var'
+        ),
+      ],
+      CAPITALISED,
+      NUMBER,
+    ],
+  },
+  c: {
+    rules: [
+      ...SLASH_COMMENTS,
+      ['keyword', /#\s*\w+/y],
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+      [
+        'keyword',
+        keywords(
+          'auto bool break case char class const continue default delete do double else enum extern false float for if inline int long namespace new nullptr private public return short signed sizeof static struct switch template this true typedef union unsigned using virtual void volatile while'
+        ),
+      ],
+      NUMBER,
+    ],
+  },
+  json: {
+    rules: [
+      ['attr', /"(?:[^"\\\n]|\\.)*"(?=\s*:)/y],
+      DOUBLE_QUOTED,
+      ['keyword', keywords('true false null')],
+      NUMBER,
+    ],
+  },
+  toml: {
+    rules: [
+      HASH_COMMENT,
+      ['tag', /^\s*\[\[?[^\]\n]*\]\]?/my],
+      ['attr', /^\s*[\w.\-"]+(?=\s*=)/my],
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+      ['keyword', keywords('true false')],
+      NUMBER,
+    ],
+  },
+  markup: {
+    rules: [
+      ['comment', /<!--[\s\S]*?(?:-->|$)/y],
+      ['tag', /<\/?[\w:-]+|\/?>/y],
+      ['attr', /\b[\w:-]+(?==)/y],
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+    ],
+  },
+  css: {
+    rules: [
+      ['comment', /\/\*[\s\S]*?(?:\*\/|$)/y],
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+      ['attr', /[\w-]+(?=\s*:)/y],
+      ['tag', /[.#]?[\w-]+(?=[^{};]*\{)/y],
+      ['number', /-?\d*\.?\d+(?:px|em|rem|%|vh|vw|s|ms)?\b/y],
+    ],
+  },
+  shell: {
+    rules: [
+      HASH_COMMENT,
+      DOUBLE_QUOTED,
+      SINGLE_QUOTED,
+      ['attr', /\$\{?\w+\}?/y],
+      [
+        'keyword',
+        keywords(
+          'case do done elif else esac export fi for function if in local return then until while'
+        ),
+      ],
+    ],
+  },
+  markdown: {
+    rules: [
+      ['tag', /^#{1,6} .*/my],
+      ['string', /`[^`\n]*`/y],
+      ['keyword', /\*\*[^*\n]+\*\*/y],
+      ['attr', /\[[^\]\n]*\]\([^)\n]*\)/y],
+    ],
+  },
+}
+
+const extensions: Record<string, string> = {
+  rs: 'rust',
+  js: 'javascript',
+  mjs: 'javascript',
+  cjs: 'javascript',
+  jsx: 'javascript',
+  ts: 'javascript',
+  tsx: 'javascript',
+  py: 'python',
+  go: 'go',
+  c: 'c',
+  h: 'c',
+  cc: 'c',
+  cpp: 'c',
+  hpp: 'c',
+  zig: 'c',
+  json: 'json',
+  toml: 'toml',
+  html: 'markup',
+  htm: 'markup',
+  xml: 'markup',
+  svg: 'markup',
+  css: 'css',
+  sh: 'shell',
+  bash: 'shell',
+  md: 'markdown',
+}
+
+/** The grammar name for a file path, or `null` for plain text */
+export function languageFor(path: string): string | null {
+  const name = path.split('/').pop() ?? ''
+  if (name === 'Makefile' || name === 'Dockerfile') return 'shell'
+  const extension = name.includes('.') ? name.split('.').pop()!.toLowerCase() : ''
+  return extensions[extension] ?? null
+}
+
+function escapeHtml(text: string): string {
+  return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;')
+}
+
+/** HTML for `code` with tokens wrapped in coloured spans */
+export function highlight(code: string, language: string | null): string {
+  const grammar = language ? grammars[language] : undefined
+  if (!grammar) return escapeHtml(code)
+
+  let html = ''
+  let plain = ''
+  let position = 0
+  while (position < code.length) {
+    let matched = false
+    for (const [kind, pattern] of grammar.rules) {
+      pattern.lastIndex = position
+      const match = pattern.exec(code)
+      if (match && match[0].length > 0) {
+        html += escapeHtml(plain)
+        plain = ''
+        html += `<span class="${tokenClasses[kind]}">${escapeHtml(match[0])}</span>`
+        position += match[0].length
+        matched = true
+        break
+      }
+    }
+    if (!matched) {
+      // Step over a whole word so keywords aren't matched inside identifiers
+      const word = /\w+|./y
+      word.lastIndex = position
+      const text = word.exec(code)?.[0] ?? code[position]
+      plain += text
+      position += text.length
+    }
+  }
+  return html + escapeHtml(plain)
+}
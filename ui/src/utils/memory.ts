@@ -0,0 +1,95 @@
+// Memory usage of the running module, sampled over time for the console's
+// Memory tab. Linear memory only grows, so a module whose memory keeps
+// growing while it's idle is usually leaking.
+
+export const PAGE_SIZE = 64 * 1024
+
+export interface MemorySample {
+  /** Milliseconds since the page loaded */
+  time: number
+  /** Size of linear memory, from `memory.size` */
+  bytes: number
+  /** Linear memory above `__heap_base`, where the allocator hands out memory */
+  heapBytes?: number
+  /** Bytes the console allocated with `__wbindgen_malloc` and hasn't freed */
+  bindgenBytes?: number
+}
+
+// Allocations made through `__wbindgen_malloc`, by pointer
+const liveAllocations = new Map<number, number>()
+
+/**
+ * wasm-bindgen's allocator for `exports`, counting what is allocated and
+ * freed so the timeline can show what is still outstanding. `null` when the
+ * module isn't a wasm-bindgen module.
+ */
+export function bindgenAllocator(exports: Record<string, any>): {
+  malloc: (size: number, align: number) => number
+  free: (ptr: number, size?: number, align?: number) => void
+} | null {
+  const malloc = exports.__wbindgen_malloc
+  const free = exports.__wbindgen_free
+  if (typeof malloc !== 'function' || typeof free !== 'function') return null
+
+  return {
+    malloc: (size, align) => {
+      const ptr = malloc(size, align)
+      liveAllocations.set(ptr, size)
+      return ptr
+    },
+    free: (ptr, ...rest) => {
+      free(ptr, ...rest)
+      liveAllocations.delete(ptr)
+    },
+  }
+}
+
+function bindgenBytes(): number | undefined {
+  if (liveAllocations.size === 0) return undefined
+  let total = 0
+  for (const size of liveAllocations.values()) total += size
+  return total
+}
+
+/** Sample an instance on the page */
+export function sampleInstance(instance: WebAssembly.Instance): MemorySample | null {
+  const exports = instance.exports as Record<string, any>
+  const memory = exports.memory as WebAssembly.Memory | undefined
+  if (!memory) return null
+
+  const bytes = memory.buffer.byteLength
+  const heapBase = exports.__heap_base as WebAssembly.Global | undefined
+  const base = heapBase instanceof WebAssembly.Global ? Number(heapBase.value) : undefined
+  return {
+    time: performance.now(),
+    bytes,
+    heapBytes: base !== undefined && base <= bytes ? bytes - base : undefined,
+    bindgenBytes: bindgenBytes(),
+  }
+}
+
+/** Sample a module running in a worker, which only reports its size */
+export async function sampleWorker(memory: {
+  byteLength: () => Promise<number>
+}): Promise<MemorySample> {
+  return { time: performance.now(), bytes: await memory.byteLength() }
+}
+
+/**
+ * Bytes per second that memory grew by over the last `windowMs`, or `null`
+ * until the samples cover that long
+ */
+export function growthRate(samples: MemorySample[], windowMs: number): number | null {
+  const last = samples[samples.length - 1]
+  if (!last) return null
+  const first = samples.find(sample => last.time - sample.time <= windowMs)
+  if (!first || last.time - samples[0].time < windowMs) return null
+  const seconds = (last.time - first.time) / 1000
+  return seconds > 0 ? (last.bytes - first.bytes) / seconds : null
+}
+
+export function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`
+  return `${(bytes / (1024 * 1024)).toFixed(2)} MB`
+}
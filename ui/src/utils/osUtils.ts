@@ -19,3 +19,62 @@ export function formatBytes(bytes: number): string {
   const i = Math.floor(Math.log(bytes) / Math.log(k))
   return Math.round((bytes / Math.pow(k, i)) * 100) / 100 + ' ' + sizes[i]
 }
+
+/**
+ * Copy text to the clipboard of the machine running wasmrun, falling back to
+ * the browser clipboard when the host has no copy tool or the API refuses.
+ */
+export async function copyToClipboard(body: { text: string } | { path: string }): Promise<boolean> {
+  try {
+    const response = await fetch('/api/clipboard', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(body),
+    })
+    if (response.ok) return true
+  } catch {
+    // fall through to the browser clipboard
+  }
+
+  try {
+    let text: string
+    if ('text' in body) {
+      text = body.text
+    } else {
+      const response = await fetch(`/api/fs/read${body.path}`)
+      const data = await response.json()
+      if (!data.success || data.type !== 'text') return false
+      text = data.content
+    }
+    await navigator.clipboard.writeText(text)
+    return true
+  } catch {
+    return false
+  }
+}
+
+/** Download a VFS file, or a directory as .tar.gz */
+export function downloadPath(path: string): void {
+  const a = document.createElement('a')
+  a.href = `/api/fs/download${path}`
+  a.download = ''
+  a.click()
+}
+
+export function exportZip(path: string): void {
+  const a = document.createElement('a')
+  a.href = `/api/fs/export${path}`
+  a.download = ''
+  a.click()
+}
+
+/** Unpack a tarball into a VFS directory; resolves to an error message on failure */
+export async function extractArchive(path: string, archive: File): Promise<string | null> {
+  try {
+    const response = await fetch(`/api/fs/extract${path}`, { method: 'POST', body: archive })
+    const data = await response.json()
+    return data.success ? null : (data.error ?? 'Failed to extract archive')
+  } catch {
+    return 'Failed to extract archive'
+  }
+}
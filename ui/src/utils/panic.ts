@@ -0,0 +1,83 @@
+// Rust panics in the browser end in a bare `RuntimeError: unreachable`. The
+// message is printed just before, by std's hook on WASI stderr or by
+// console_error_panic_hook for bindgen modules, both through console.error.
+// Watching that output lets the console report the panic instead of the trap.
+
+export interface PanicReport {
+  thread?: string
+  message?: string
+  location?: string
+}
+
+const LOCATION = /^\S+:\d+:\d+$/
+
+/** The last panic in `output`, in the formats Rust has printed them */
+export function parsePanic(output: string): PanicReport | null {
+  const start = output.lastIndexOf('panicked at ')
+  if (start === -1) return null
+
+  const lineStart = output.lastIndexOf('\n', start) + 1
+  const prefix = output.slice(lineStart, start).trim()
+  const thread = /^thread '(.*)'$/.exec(prefix)?.[1]
+  const rest = output.slice(start + 'panicked at '.length)
+  const newline = rest.indexOf('\n')
+  const first = newline === -1 ? rest : rest.slice(0, newline)
+  const following = newline === -1 ? '' : rest.slice(newline + 1)
+
+  // Before Rust 1.73: 'message', file:line:column
+  const quoted = /^'(.*)', (\S+:\d+:\d+)\s*$/.exec(first)
+  if (quoted) {
+    return { thread, message: quoted[1], location: quoted[2] }
+  }
+
+  const location = first.trim().replace(/:$/, '')
+  const hasLocation = LOCATION.test(location)
+  const lines = (hasLocation ? following : rest).split('\n')
+  const end = lines.findIndex(
+    line => line.startsWith('note: ') || line.startsWith('stack backtrace:') || line.startsWith('Stack:')
+  )
+  const message = (end === -1 ? lines : lines.slice(0, end)).join('\n').trim()
+  return {
+    thread,
+    message: message || undefined,
+    location: hasLocation ? location : undefined,
+  }
+}
+
+// console.error output since the last call, where panic messages land
+let recentErrors = ''
+const MAX_KEPT = 16 * 1024
+
+/**
+ * Keep an eye on console.error for panic messages; returns a function that
+ * stops watching
+ */
+export function watchPanicOutput(): () => void {
+  const original = console.error
+  console.error = (...args: unknown[]) => {
+    recentErrors = (recentErrors + args.map(String).join(' ') + '\n').slice(-MAX_KEPT)
+    original.apply(console, args)
+  }
+  return () => {
+    console.error = original
+  }
+}
+
+/**
+ * Whether `error` is the trap an aborting wasm module ends in. Browsers word
+ * it differently, and errors passed back from a worker lose their type.
+ */
+export function isUnreachableTrap(error: unknown): boolean {
+  return error instanceof Error && /\bunreachable\b/i.test(error.message)
+}
+
+/**
+ * The panic behind `error`, if it was an `unreachable` trap with a panic
+ * message printed before it. Clears what has been seen so far either way,
+ * so a later trap isn't blamed on an old panic.
+ */
+export function takePanic(error: unknown): PanicReport | null {
+  const output = recentErrors
+  recentErrors = ''
+  return isUnreachableTrap(error) ? parsePanic(output) : null
+}
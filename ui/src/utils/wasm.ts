@@ -1,4 +1,5 @@
-import { LogEntry, WasmModuleInfo, WasmInspectionInfo } from '@/types'
+import { LogEntry, WasmModuleInfo, WasmInspectionInfo, ModuleDetails } from '@/types'
+import { apiUrl } from '@/utils/api'
 
 export function log(message: string, type: LogEntry['type'] = 'info'): LogEntry {
   const entry: LogEntry = {
@@ -121,7 +122,7 @@ export function analyzeWasmModule(module: WebAssembly.Module): Partial<WasmModul
 
 export async function fetchModuleInspection(): Promise<WasmInspectionInfo | null> {
   try {
-    const response = await fetch('/api/module-info')
+    const response = await fetch(apiUrl('/api/module-info'))
 
     if (!response.ok) {
       throw new Error(`HTTP error! status: ${response.status}`)
@@ -140,3 +141,53 @@ export async function fetchModuleInspection(): Promise<WasmInspectionInfo | null
     return null
   }
 }
+
+export async function fetchModuleDetails(): Promise<ModuleDetails | null> {
+  try {
+    const response = await fetch(apiUrl('/api/module/info'))
+
+    if (!response.ok) {
+      throw new Error(`HTTP error! status: ${response.status}`)
+    }
+
+    const data = await response.json()
+
+    if (data.error) {
+      console.error('Module details error:', data.error)
+      return null
+    }
+
+    return data as ModuleDetails
+  } catch (error) {
+    console.error('Error fetching module details:', error)
+    return null
+  }
+}
+
+export interface WorkerModuleInfo {
+  exports: string[]
+  functions: string[]
+  // Every function import; the worker stubs them all to return 0
+  imports: string[]
+}
+
+// A module instantiated inside a Web Worker by the `--worker` bridge. Every
+// export call is an RPC round-trip, so results are always promises.
+export interface WorkerModule {
+  info: WorkerModuleInfo
+  exports: Record<string, (...args: any[]) => Promise<any>>
+  memory: {
+    byteLength: () => Promise<number>
+    grow: (pages: number) => Promise<number>
+  }
+  terminate: () => void
+}
+
+interface WorkerBridge {
+  load: (url: string) => Promise<WorkerModule>
+}
+
+// Present only when the server was started with `--worker`
+export function getWorkerBridge(): WorkerBridge | null {
+  return ((window as any).__wasmrun_worker as WorkerBridge | undefined) ?? null
+}
@@ -0,0 +1,143 @@
+// WebGPU availability checks for modules whose imports use WebGPU. The goal
+// is to tell the user *why* it's missing instead of letting the app die on
+// `navigator.gpu is undefined`.
+
+export interface WebGpuAdapterInfo {
+  vendor: string
+  architecture: string
+  device: string
+  description: string
+  isFallbackAdapter: boolean
+  features: string[]
+  limits: Record<string, number>
+}
+
+export type WebGpuStatus =
+  | { available: true; adapter: WebGpuAdapterInfo }
+  | { available: false; reason: 'insecure-context' | 'no-api' | 'no-adapter' | 'error'; detail?: string }
+
+// A few limits that most often explain "works on my machine" differences
+const REPORTED_LIMITS = [
+  'maxTextureDimension2D',
+  'maxBufferSize',
+  'maxStorageBufferBindingSize',
+  'maxComputeWorkgroupSizeX',
+  'maxComputeInvocationsPerWorkgroup',
+  'maxBindGroups',
+]
+
+export async function detectWebGpu(): Promise<WebGpuStatus> {
+  if (!window.isSecureContext) {
+    return { available: false, reason: 'insecure-context' }
+  }
+
+  const gpu = (navigator as any).gpu
+  if (!gpu) {
+    return { available: false, reason: 'no-api' }
+  }
+
+  try {
+    const adapter = await gpu.requestAdapter()
+    if (!adapter) {
+      return { available: false, reason: 'no-adapter' }
+    }
+
+    // Older implementations only expose requestAdapterInfo()
+    const info =
+      adapter.info ??
+      (typeof adapter.requestAdapterInfo === 'function' ? await adapter.requestAdapterInfo() : {})
+
+    const limits: Record<string, number> = {}
+    for (const key of REPORTED_LIMITS) {
+      if (typeof adapter.limits?.[key] === 'number') {
+        limits[key] = adapter.limits[key]
+      }
+    }
+
+    return {
+      available: true,
+      adapter: {
+        vendor: info.vendor || 'unknown',
+        architecture: info.architecture || '',
+        device: info.device || '',
+        description: info.description || '',
+        isFallbackAdapter: Boolean(adapter.isFallbackAdapter ?? info.isFallbackAdapter),
+        features: Array.from(adapter.features ?? []).map(String).sort(),
+        limits,
+      },
+    }
+  } catch (error) {
+    return {
+      available: false,
+      reason: 'error',
+      detail: error instanceof Error ? error.message : String(error),
+    }
+  }
+}
+
+// Steps the user can take, tailored to the browser we're running in
+export function webGpuGuidance(status: WebGpuStatus): string[] {
+  if (status.available) {
+    return status.adapter.isFallbackAdapter
+      ? [
+          'The browser picked a software (fallback) adapter, so rendering will be slow.',
+          'Check that hardware acceleration is enabled in the browser settings and your GPU drivers are current.',
+        ]
+      : []
+  }
+
+  const ua = navigator.userAgent
+  const isFirefox = /Firefox\//.test(ua)
+  const isSafari = /Safari\//.test(ua) && !/Chrome\/|Chromium\//.test(ua)
+  const isChromium = /Chrome\/|Chromium\//.test(ua)
+  const isLinux = /Linux/.test(ua) && !/Android/.test(ua)
+
+  switch (status.reason) {
+    case 'insecure-context':
+      return [
+        'WebGPU only works in a secure context.',
+        `Open the page through http://localhost:${location.port || '8420'} instead of ${location.hostname}, or serve it over HTTPS.`,
+      ]
+    case 'no-adapter':
+      return [
+        'The browser supports WebGPU but found no usable GPU adapter.',
+        'Update your GPU drivers and make sure hardware acceleration is enabled.',
+        isChromium
+          ? 'Check chrome://gpu: WebGPU should be listed as "Hardware accelerated". Your GPU may be on the blocklist.'
+          : 'Your GPU or driver may be blocklisted by the browser.',
+      ]
+    case 'error':
+      return [
+        `Requesting a WebGPU adapter failed: ${status.detail ?? 'unknown error'}`,
+        'Restart the browser; a crashed GPU process stays disabled until then.',
+      ]
+    case 'no-api':
+    default:
+      if (isFirefox) {
+        return [
+          'Firefox ships WebGPU on Windows from version 141; other platforms are still rolling out.',
+          'On Firefox Nightly, set dom.webgpu.enabled to true in about:config.',
+          'Or use Chrome/Edge 113+ on Windows, macOS or ChromeOS.',
+        ]
+      }
+      if (isSafari) {
+        return [
+          'Safari enables WebGPU by default from Safari 26.',
+          'On older versions, turn on the WebGPU feature flag under Develop → Feature Flags.',
+        ]
+      }
+      if (isChromium && isLinux) {
+        return [
+          'Chrome on Linux does not enable WebGPU by default yet.',
+          'Enable chrome://flags/#enable-unsafe-webgpu and chrome://flags/#enable-vulkan, then restart the browser.',
+        ]
+      }
+      if (isChromium) {
+        return [
+          'WebGPU requires Chrome or Edge 113 or newer; update the browser.',
+          'If it is already current, check chrome://gpu for WebGPU status.',
+        ]
+      }
+      return ['This browser does not support WebGPU. Use Chrome or Edge 113+, Safari 26+, or Firefox 141+ on Windows.']
+  }
+}
@@ -1,21 +1,82 @@
+// Display names for the server's log sources, matching LogSource's Display
+const SOURCE_NAMES = {
+  Kernel: 'KERNEL',
+  WasmExecution: 'WASM',
+  DevServer: 'DEV_SERVER',
+  Filesystem: 'FS',
+  Syscall: 'SYSCALL',
+  Unknown: 'UNKNOWN',
+}
+
+const LEVELS = ['DEBUG', 'INFO', 'WARN', 'ERROR']
+
+// ANSI colors 0-15, readable on the page's dark background
+const ANSI_PALETTE = [
+  '#4b5563', '#f87171', '#4ade80', '#facc15', '#60a5fa', '#c084fc', '#22d3ee', '#e5e7eb',
+  '#9ca3af', '#fca5a5', '#86efac', '#fde047', '#93c5fd', '#d8b4fe', '#67e8f9', '#ffffff',
+]
+
+// CSI sequences, OSC sequences (ended by BEL or ST) and two-byte escapes
+// eslint-disable-next-line no-control-regex
+const ANSI_ESCAPE = /\x1b\[([0-9;?]*)([A-Za-z])|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]/g
+
+function escapeHtml(text) {
+  return String(text ?? '').replace(
+    /[&<>"']/g,
+    c => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' })[c]
+  )
+}
+
+// Color for `38;5;n`: the 16 base colors, a 6x6x6 cube, then grays
+function ansi256(n) {
+  if (n < 16) return ANSI_PALETTE[n]
+  if (n < 232) {
+    const level = v => (v === 0 ? 0 : 55 + v * 40)
+    const i = n - 16
+    return `rgb(${level(Math.floor(i / 36))}, ${level(Math.floor(i / 6) % 6)}, ${level(i % 6)})`
+  }
+  const gray = 8 + (n - 232) * 10
+  return `rgb(${gray}, ${gray}, ${gray})`
+}
+
 class LogsPanel {
   constructor(port = 8420) {
     this.port = port
     this.logs = []
+    this.total = 0
+    this.sources = []
     this.maxLogs = 1000
     this.autoRefresh = true
     this.refreshInterval = 2000
-    this.filterLevel = 'all'
+    this.filterLevels = new Set(LEVELS)
     this.filterSource = 'all'
+    this.search = ''
   }
 
-  async fetchLogs(recent = true) {
+  // Filters are applied by the server; see /api/logs in the OS mode docs
+  buildQuery() {
+    const params = new URLSearchParams()
+    if (this.filterLevels.size < LEVELS.length) {
+      params.set('level', [...this.filterLevels].join(','))
+    }
+    if (this.filterSource !== 'all') {
+      params.set('source', this.filterSource)
+    }
+    if (this.search.trim()) {
+      params.set('q', this.search.trim())
+    }
+    params.set('limit', String(this.maxLogs))
+    return params.toString()
+  }
+
+  async fetchLogs() {
     try {
-      const endpoint = recent ? '/api/logs/recent' : '/api/logs'
-      const response = await fetch(`http://localhost:${this.port}${endpoint}`)
+      const response = await fetch(`http://localhost:${this.port}/api/logs?${this.buildQuery()}`)
       const data = await response.json()
       if (data.success) {
         this.logs = data.logs || []
+        this.total = data.total ?? this.logs.length
+        this.sources = data.sources || []
         return this.logs
       }
     } catch (error) {
@@ -24,16 +85,93 @@ class LogsPanel {
     return []
   }
 
-  filterLogs(logs = null) {
-    const source = logs || this.logs
-    return source.filter(log => {
-      const levelMatch = this.filterLevel === 'all' || log.level === this.filterLevel
-      const sourceMatch =
-        this.filterSource === 'all' ||
-        log.source === this.filterSource ||
-        log.source.startsWith(this.filterSource)
-      return levelMatch && sourceMatch
-    })
+  toggleLevel(level) {
+    if (this.filterLevels.has(level)) {
+      this.filterLevels.delete(level)
+    } else {
+      this.filterLevels.add(level)
+    }
+  }
+
+  levelName(log) {
+    return String(log.level).toUpperCase()
+  }
+
+  sourceName(log) {
+    if (log.source && typeof log.source === 'object') {
+      return String(log.source.LanguageRuntime || 'UNKNOWN').toUpperCase()
+    }
+    return SOURCE_NAMES[log.source] || String(log.source).toUpperCase()
+  }
+
+  // `text` with carriage-return redraws collapsed and escapes removed
+  stripAnsi(text) {
+    return String(text ?? '')
+      .split('\n')
+      .map(line => line.replace(/\r+$/, '').split('\r').pop())
+      .join('\n')
+      .replace(ANSI_ESCAPE, '')
+  }
+
+  // Escaped HTML for `text`, with its ANSI colors and styles as spans
+  ansiToHtml(text) {
+    const input = String(text ?? '')
+      .split('\n')
+      .map(line => line.replace(/\r+$/, '').split('\r').pop())
+      .join('\n')
+    let style = {}
+    let html = ''
+    let last = 0
+    const emit = end => {
+      if (end <= last) return
+      const chunk = escapeHtml(input.slice(last, end))
+      const css = [
+        style.color && `color:${style.color}`,
+        style.background && `background-color:${style.background}`,
+        style.bold && 'font-weight:bold',
+        style.dim && 'opacity:0.7',
+        style.italic && 'font-style:italic',
+        style.underline && 'text-decoration:underline',
+      ].filter(Boolean)
+      html += css.length ? `<span style="${css.join(';')}">${chunk}</span>` : chunk
+    }
+    for (const match of input.matchAll(ANSI_ESCAPE)) {
+      emit(match.index)
+      last = match.index + match[0].length
+      if (match[2] !== 'm') continue
+      const params = match[1] === '' ? [0] : match[1].split(';').map(p => Number(p) || 0)
+      style = { ...style }
+      for (let i = 0; i < params.length; i++) {
+        const code = params[i]
+        if (code === 0) style = {}
+        else if (code === 1) style.bold = true
+        else if (code === 2) style.dim = true
+        else if (code === 3) style.italic = true
+        else if (code === 4) style.underline = true
+        else if (code === 22) style.bold = style.dim = false
+        else if (code === 23) style.italic = false
+        else if (code === 24) style.underline = false
+        else if (code >= 30 && code <= 37) style.color = ANSI_PALETTE[code - 30]
+        else if (code >= 90 && code <= 97) style.color = ANSI_PALETTE[code - 82]
+        else if (code === 39) delete style.color
+        else if (code >= 40 && code <= 47) style.background = ANSI_PALETTE[code - 40]
+        else if (code >= 100 && code <= 107) style.background = ANSI_PALETTE[code - 92]
+        else if (code === 49) delete style.background
+        else if (code === 38 || code === 48) {
+          const key = code === 38 ? 'color' : 'background'
+          if (params[i + 1] === 5) {
+            style[key] = ansi256(params[i + 2] ?? 0)
+            i += 2
+          } else if (params[i + 1] === 2) {
+            const [r = 0, g = 0, b = 0] = params.slice(i + 2, i + 5)
+            style[key] = `rgb(${r}, ${g}, ${b})`
+            i += 4
+          }
+        }
+      }
+    }
+    emit(input.length)
+    return html
   }
 
   formatTimestamp(timestamp) {
@@ -69,24 +207,34 @@ class LogsPanel {
       SYSCALL: '#06b6d4',
       UNKNOWN: '#9ca3af',
     }
-    return colors[source] || '#ffffff'
+    if (colors[source]) {
+      return colors[source]
+    }
+    // Language runtimes get a stable color of their own
+    let hash = 0
+    for (const char of source) {
+      hash = (hash * 31 + char.charCodeAt(0)) % 360
+    }
+    return `hsl(${hash}, 70%, 65%)`
   }
 
   startAutoRefresh() {
     if (this.autoRefresh) {
-      setInterval(() => this.fetchLogs(true), this.refreshInterval)
+      setInterval(() => this.fetchLogs(), this.refreshInterval)
     }
   }
 
   formatLogEntry(log) {
+    const level = this.levelName(log)
+    const source = this.sourceName(log)
     return {
       timestamp: this.formatTimestamp(log.timestamp),
-      level: log.level,
-      source: log.source,
+      level,
+      source,
       message: log.message,
       pid: log.pid,
-      levelColor: this.getLevelColor(log.level),
-      sourceColor: this.getSourceColor(log.source),
+      levelColor: this.getLevelColor(level),
+      sourceColor: this.getSourceColor(source),
     }
   }
 
@@ -94,6 +242,20 @@ class LogsPanel {
     this.logs = []
   }
 
+  // A Markdown table of `logs`, for pasting into issues and chats
+  toMarkdown(logs = this.logs) {
+    const cell = text => this.stripAnsi(text).replace(/\|/g, '\\|').replace(/\r?\n/g, '<br>')
+    const rows = logs.map(
+      log =>
+        `| ${cell(log.timestamp)} | ${this.levelName(log)} | ${cell(this.sourceName(log))} | ${log.pid ?? ''} | ${cell(log.message)} |`
+    )
+    return ['| Time | Level | Source | PID | Message |', '|---|---|---|---|---|', ...rows].join('\n')
+  }
+
+  async copyAsMarkdown(logs = this.logs) {
+    await navigator.clipboard.writeText(this.toMarkdown(logs))
+  }
+
   async exportLogs(format = 'json') {
     const timestamp = new Date().toISOString().replace(/[:.]/g, '-')
     const filename = `logs-${timestamp}.${format}`
@@ -104,14 +266,14 @@ class LogsPanel {
     } else if (format === 'csv') {
       content = 'timestamp,level,source,pid,message\n'
       this.logs.forEach(log => {
-        const message = (log.message || '').replace(/"/g, '""')
-        content += `"${log.timestamp}","${log.level}","${log.source}","${log.pid || ''}","${message}"\n`
+        const message = this.stripAnsi(log.message).replace(/"/g, '""')
+        content += `"${log.timestamp}","${this.levelName(log)}","${this.sourceName(log)}","${log.pid || ''}","${message}"\n`
       })
     } else {
       content = this.logs
         .map(
           log =>
-            `[${log.timestamp}] [${log.level}] [${log.source}${log.pid ? `:${log.pid}` : ''}] ${log.message}`
+            `[${log.timestamp}] [${this.levelName(log)}] [${this.sourceName(log)}${log.pid ? `:${log.pid}` : ''}] ${this.stripAnsi(log.message)}`
         )
         .join('\n')
     }
@@ -128,6 +290,6 @@ class LogsPanel {
 
 if (typeof window !== 'undefined') {
   window.LogsPanel = LogsPanel
+  window.LOG_LEVELS = LEVELS
+  window.escapeHtml = escapeHtml
 }
-
-export default LogsPanel